@@ -7,7 +7,7 @@ import eslintConfigPrettier from 'eslint-config-prettier';
 
 export default tseslint.config(
   {
-    ignores: ['dist', 'node_modules', 'src-tauri/target'],
+    ignores: ['dist', 'node_modules', 'src-tauri/target', 'src/types/bindings'],
   },
   {
     files: ['**/*.{ts,tsx,js,jsx}'],
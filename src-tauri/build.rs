@@ -1,3 +1,5 @@
 fn main() {
+    // 精简代理程序（未启用 gui）无需生成 Tauri 上下文
+    #[cfg(feature = "gui")]
     tauri_build::build()
 }
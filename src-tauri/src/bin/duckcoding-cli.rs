@@ -0,0 +1,15 @@
+// duckcoding-cli - 无界面的命令行程序
+//
+// 子命令与 GUI 主程序的命令行模式相同（`duckcoding profile list` 等），
+// 可在不构建界面的情况下使用：
+//
+//   cargo build --release --no-default-features --bin duckcoding-cli
+//
+// 配置沿用 ~/.duckcoding/，可通过 DUCKCODING_CONFIG_DIR 覆盖
+
+use std::process::ExitCode;
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    duckcoding::cli::run_from_env().await
+}
@@ -0,0 +1,162 @@
+// duckcoding-proxy - 无界面的精简透明代理程序
+//
+// 仅包含代理、会话与 Token 统计，不依赖 Tauri/托盘/更新器，适合服务器部署：
+//
+//   cargo build --release --no-default-features --bin duckcoding-proxy
+//
+// 配置沿用 ~/.duckcoding/（proxy.json、config.json），可通过 DUCKCODING_CONFIG_DIR 覆盖
+
+use duckcoding::core::{init_logger, install_panic_hook};
+use duckcoding::services::proxy_config_manager::ProxyConfigManager;
+use duckcoding::utils::config::read_global_config;
+use duckcoding::ProxyManager;
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+用法: duckcoding-proxy [选项]
+
+启动 proxy.json 中已启用的透明代理，按 Ctrl+C 退出。
+
+选项:
+  -t, --tool <TOOL>   仅启动指定工具的代理（可重复）：claude-code | codex | gemini-cli | amp-code
+                      或 tools.d 中声明了 proxy_protocol 的自定义工具
+  -h, --help          显示帮助
+  -V, --version       显示版本";
+
+/// 解析命令行参数，返回要启动的工具列表（空表示全部）
+fn parse_args() -> Result<Option<Vec<String>>, String> {
+    let mut tools = Vec::new();
+    let mut args = std::env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(None);
+            }
+            "-V" | "--version" => {
+                println!("duckcoding-proxy {}", env!("CARGO_PKG_VERSION"));
+                return Ok(None);
+            }
+            "-t" | "--tool" => {
+                tools.push(args.next().ok_or("--tool 需要参数")?);
+            }
+            other => return Err(format!("未知参数: {other}\n\n{USAGE}")),
+        }
+    }
+
+    Ok(Some(tools))
+}
+
+/// 启动已启用且配置完整的代理，返回成功启动的数量
+async fn start_proxies(manager: &ProxyManager, only: &[String]) -> anyhow::Result<usize> {
+    let store = ProxyConfigManager::new()?.load_proxy_store()?;
+    let tool_ids = store.tool_ids();
+    if let Some(tool) = only.iter().find(|t| !tool_ids.contains(t)) {
+        anyhow::bail!("不支持的工具: {tool}");
+    }
+    let mut started = 0;
+
+    for tool_id in &tool_ids {
+        let tool_id = tool_id.as_str();
+        if !only.is_empty() && !only.iter().any(|t| t == tool_id) {
+            continue;
+        }
+        let Some(config) = store.get_config(tool_id).cloned() else {
+            continue;
+        };
+        if !config.enabled {
+            tracing::debug!(tool_id = %tool_id, "代理未启用，跳过");
+            continue;
+        }
+        if config.local_api_key.is_none() {
+            tracing::warn!(tool_id = %tool_id, "未配置保护密钥，跳过启动");
+            continue;
+        }
+
+        let port = config.port;
+        match manager.start_proxy(tool_id, config).await {
+            Ok(_) => {
+                started += 1;
+                tracing::info!(tool_id = %tool_id, port = port, "代理启动成功");
+            }
+            Err(e) => tracing::error!(tool_id = %tool_id, error = ?e, "代理启动失败"),
+        }
+    }
+
+    Ok(started)
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let only = match parse_args() {
+        Ok(Some(tools)) => tools,
+        Ok(None) => return ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let log_config = read_global_config()
+        .ok()
+        .flatten()
+        .map(|cfg| cfg.log_config)
+        .unwrap_or_default();
+    if let Err(e) = init_logger(&log_config) {
+        eprintln!("WARNING: Failed to initialize logging system: {}", e);
+    }
+    install_panic_hook(&log_config);
+    tracing::info!("DuckCoding 精简代理启动");
+
+    // 数据迁移与 GUI 保持一致，保证两种程序可共用同一配置目录
+    if let Err(e) = duckcoding::create_migration_manager().run_all().await {
+        tracing::error!("迁移执行失败: {}", e);
+        return ExitCode::FAILURE;
+    }
+    duckcoding::services::recovery::run_startup_recovery();
+
+    let manager = ProxyManager::new();
+    match start_proxies(&manager, &only).await {
+        Ok(0) => {
+            tracing::error!("没有可启动的代理，请先在 proxy.json 中启用并配置代理");
+            duckcoding::services::recovery::mark_clean_shutdown();
+            return ExitCode::FAILURE;
+        }
+        Ok(count) => tracing::info!(count = count, "代理已就绪，按 Ctrl+C 退出"),
+        Err(e) => {
+            tracing::error!(error = ?e, "启动代理失败");
+            duckcoding::services::recovery::mark_clean_shutdown();
+            return ExitCode::FAILURE;
+        }
+    }
+
+    tokio::spawn(async {
+        duckcoding::services::pricing::remote_sync::start_sync_scheduler().await;
+    });
+    tokio::spawn(duckcoding::services::proxy::capture_store::run_purge_scheduler());
+
+    let metrics_config = read_global_config()
+        .ok()
+        .flatten()
+        .map(|cfg| cfg.metrics)
+        .unwrap_or_default();
+    if let Err(e) = duckcoding::services::proxy::metrics::apply_config(&metrics_config).await {
+        tracing::error!(error = ?e, "启动 Prometheus 指标监听失败");
+    }
+
+    if let Err(e) = tokio::signal::ctrl_c().await {
+        tracing::error!(error = ?e, "监听退出信号失败");
+    }
+
+    tracing::info!("正在关闭代理...");
+    if let Err(e) = manager.stop_all().await {
+        tracing::warn!(error = ?e, "停止代理失败");
+    }
+    duckcoding::services::session::shutdown_session_manager();
+    duckcoding::services::token_stats::shutdown_token_stats_manager();
+    duckcoding::services::recovery::mark_clean_shutdown();
+    tracing::info!("清理任务完成");
+
+    ExitCode::SUCCESS
+}
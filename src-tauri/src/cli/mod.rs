@@ -0,0 +1,297 @@
+//! 命令行子命令（无界面模式）
+//!
+//! 复用与 GUI 相同的服务层，便于在脚本或服务器中使用：
+//!
+//! ```text
+//! duckcoding profile list [--tool claude-code] [--json]
+//! duckcoding profile activate <TOOL> <NAME>
+//! duckcoding proxy start [-t claude-code]...   # 前台运行，Ctrl+C 退出
+//! duckcoding proxy stop [-t claude-code]...
+//! duckcoding proxy status [--json]
+//! duckcoding stats summary [--since 7d] [--tool codex] [--group-by model] [--json]
+//! duckcoding install <TOOL> [--method npm] [--force]
+//! duckcoding mcp                              # MCP 服务（标准输入输出）
+//! ```
+//!
+//! GUI 主程序在第一个参数为已知子命令时转入此处；
+//! 无界面构建（`--no-default-features`）可使用 `duckcoding-cli` 程序。
+
+mod proxy;
+mod stats;
+
+use crate::models::{InstallMethod, Tool};
+use crate::services::profile_manager::ProfileManager;
+use crate::services::proxy::config::apply_global_proxy;
+use crate::services::tool::InstallerService;
+use crate::utils::config::read_global_config;
+use anyhow::{anyhow, bail, Result};
+use clap::{Parser, Subcommand, ValueEnum};
+use std::ffi::OsString;
+use std::process::ExitCode;
+
+/// 支持的子命令（GUI 主程序据此判断是否进入命令行模式）
+const SUBCOMMANDS: [&str; 5] = ["profile", "proxy", "stats", "install", "mcp"];
+
+/// DuckCoding 命令行
+#[derive(Debug, Parser)]
+#[command(
+    name = "duckcoding",
+    version,
+    about = "DuckCoding 命令行（无需启动界面）"
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// 管理工具配置（Profile）
+    #[command(subcommand)]
+    Profile(ProfileCommand),
+    /// 管理透明代理
+    #[command(subcommand)]
+    Proxy(ProxyCommand),
+    /// 查询 Token 统计
+    #[command(subcommand)]
+    Stats(StatsCommand),
+    /// 安装 AI 编程工具
+    Install {
+        /// 工具 ID：claude-code | codex | gemini-cli
+        tool: String,
+        /// 安装方式
+        #[arg(long, value_enum, default_value_t = InstallMethodArg::Npm)]
+        method: InstallMethodArg,
+        /// 已安装时强制重新安装
+        #[arg(long)]
+        force: bool,
+    },
+    /// 以标准输入输出运行 MCP 服务（供 AI 编程工具启动，需在设置中启用）
+    Mcp,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ProfileCommand {
+    /// 列出 Profile（`*` 标记当前激活）
+    List {
+        /// 仅列出指定工具
+        #[arg(long)]
+        tool: Option<String>,
+        /// 以 JSON 输出
+        #[arg(long)]
+        json: bool,
+    },
+    /// 激活 Profile 并写入工具原生配置
+    Activate {
+        /// 工具 ID：claude-code | codex | gemini-cli
+        tool: String,
+        /// Profile 名称
+        name: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ProxyCommand {
+    /// 启动 proxy.json 中已启用的代理并在前台运行
+    Start {
+        /// 仅启动指定工具（可重复）
+        #[arg(short, long)]
+        tool: Vec<String>,
+    },
+    /// 停止由 `proxy start` 启动的代理
+    Stop {
+        /// 仅停止指定工具（可重复，省略时全部停止）
+        #[arg(short, long)]
+        tool: Vec<String>,
+    },
+    /// 查看各工具代理的配置与运行状态
+    Status {
+        /// 以 JSON 输出
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum StatsCommand {
+    /// 成本与用量汇总
+    Summary {
+        /// 起始时间：相对时长（30m / 24h / 7d / 2w）或日期（2026-10-01）
+        #[arg(long, default_value = "7d")]
+        since: String,
+        /// 工具类型过滤
+        #[arg(long)]
+        tool: Option<String>,
+        /// 标签过滤（如项目名）
+        #[arg(long)]
+        tag: Option<String>,
+        /// 分组方式
+        #[arg(long, value_enum, default_value_t = stats::GroupByArg::Model)]
+        group_by: stats::GroupByArg,
+        /// 以 JSON 输出
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// 安装方式参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum InstallMethodArg {
+    Npm,
+    Official,
+    Brew,
+}
+
+impl From<InstallMethodArg> for InstallMethod {
+    fn from(method: InstallMethodArg) -> Self {
+        match method {
+            InstallMethodArg::Npm => InstallMethod::Npm,
+            InstallMethodArg::Official => InstallMethod::Official,
+            InstallMethodArg::Brew => InstallMethod::Brew,
+        }
+    }
+}
+
+/// 参数是否为命令行调用（第一个参数为已知子命令）
+pub fn is_cli_invocation<I, T>(args: I) -> bool
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString>,
+{
+    args.into_iter()
+        .nth(1)
+        .map(Into::into)
+        .is_some_and(|arg| SUBCOMMANDS.iter().any(|cmd| arg == *cmd))
+}
+
+/// 解析命令行参数并执行（参数错误时由 clap 输出帮助并退出）
+pub async fn run_from_env() -> ExitCode {
+    run(Cli::parse()).await
+}
+
+/// 执行子命令
+pub async fn run(cli: Cli) -> ExitCode {
+    let result = match cli.command {
+        Command::Profile(command) => run_profile(command),
+        Command::Proxy(command) => proxy::run(command).await,
+        Command::Stats(command) => stats::run(command),
+        Command::Install {
+            tool,
+            method,
+            force,
+        } => run_install(&tool, method.into(), force).await,
+        Command::Mcp => run_mcp().await,
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("错误: {:#}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run_profile(command: ProfileCommand) -> Result<()> {
+    let manager = ProfileManager::new()?;
+    match command {
+        ProfileCommand::List { tool, json } => {
+            let descriptors: Vec<_> = manager
+                .list_all_descriptors()?
+                .into_iter()
+                .filter(|d| tool.as_deref().is_none_or(|t| d.tool_id == t))
+                .collect();
+            if json {
+                println!("{}", serde_json::to_string_pretty(&descriptors)?);
+                return Ok(());
+            }
+            if descriptors.is_empty() {
+                println!("暂无 Profile");
+                return Ok(());
+            }
+            for d in descriptors {
+                println!(
+                    "{} {:<12} {:<24} {:<40} {}",
+                    if d.is_active { "*" } else { " " },
+                    d.tool_id,
+                    d.name,
+                    d.base_url,
+                    d.api_key_preview
+                );
+            }
+        }
+        ProfileCommand::Activate { tool, name } => {
+            manager.activate_profile(&tool, &name)?;
+            println!("已激活 {} / {}", tool, name);
+        }
+    }
+    Ok(())
+}
+
+async fn run_install(tool_id: &str, method: InstallMethod, force: bool) -> Result<()> {
+    let tool = Tool::by_id(tool_id).ok_or_else(|| anyhow!("不支持的工具: {}", tool_id))?;
+    apply_global_proxy().ok();
+
+    let installer = InstallerService::new();
+    println!("正在安装 {}（{:?}）...", tool.name, method);
+    installer.install(&tool, &method, force).await?;
+    match installer.get_installed_version(&tool).await {
+        Some(version) => println!("{} 安装成功: {}", tool.name, version),
+        None => println!("{} 安装完成", tool.name),
+    }
+    Ok(())
+}
+
+async fn run_mcp() -> Result<()> {
+    let enabled = read_global_config()
+        .map_err(|e| anyhow!(e))?
+        .is_some_and(|c| c.mcp.enabled);
+    if !enabled {
+        bail!("MCP 服务未启用，请先在设置中开启");
+    }
+    crate::services::mcp::serve_stdio().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_cli_invocation() {
+        assert!(is_cli_invocation(["duckcoding", "proxy", "status"]));
+        assert!(is_cli_invocation(["duckcoding", "install", "codex"]));
+        assert!(is_cli_invocation(["duckcoding", "mcp"]));
+        // 无参数、未知参数（如 macOS 的 -psn_ 参数）仍启动界面
+        assert!(!is_cli_invocation(["duckcoding"]));
+        assert!(!is_cli_invocation(["duckcoding", "-psn_0_12345"]));
+    }
+
+    #[test]
+    fn test_parse_subcommands() {
+        let cli = Cli::try_parse_from(["duckcoding", "proxy", "start", "-t", "codex"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Proxy(ProxyCommand::Start { ref tool }) if tool == &["codex"]
+        ));
+
+        let cli =
+            Cli::try_parse_from(["duckcoding", "stats", "summary", "--since", "24h"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Stats(StatsCommand::Summary { ref since, json: false, .. }) if since == "24h"
+        ));
+
+        let cli =
+            Cli::try_parse_from(["duckcoding", "install", "codex", "--method", "brew"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Install {
+                method: InstallMethodArg::Brew,
+                force: false,
+                ..
+            }
+        ));
+
+        assert!(Cli::try_parse_from(["duckcoding", "profile", "activate", "codex"]).is_err());
+    }
+}
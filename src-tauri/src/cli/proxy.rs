@@ -0,0 +1,319 @@
+//! `duckcoding proxy` 子命令
+//!
+//! `proxy start` 在前台运行代理，并将运行状态写入配置目录下的 `cli_proxy.json`；
+//! `proxy stop` 通过修改该文件通知前台进程停止（每秒检查一次），无需依赖平台信号。
+
+use super::ProxyCommand;
+use crate::core::{init_logger, install_panic_hook};
+use crate::data::DataManager;
+use crate::models::proxy_config::{ProxyStore, ToolProxyConfig};
+use crate::services::proxy::ProxyManager;
+use crate::services::proxy_config_manager::ProxyConfigManager;
+use crate::utils::config::{config_dir, read_global_config};
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use std::net::{SocketAddr, TcpStream};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// 运行状态文件名
+const STATE_FILE: &str = "cli_proxy.json";
+
+/// 前台进程检查停止请求的间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// `proxy stop` 等待代理退出的最长时间
+const STOP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 端口探测超时
+const PROBE_TIMEOUT: Duration = Duration::from_millis(300);
+
+/// `proxy start` 的运行状态
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct CliProxyState {
+    pid: u32,
+    /// 启动时间（Unix 时间戳，秒）
+    started_at: i64,
+    /// 仍在运行的工具（`proxy stop` 从中移除）
+    tools: Vec<String>,
+}
+
+/// 单个工具的代理状态
+#[derive(Debug, Clone, Serialize)]
+struct ProxyStatusRow {
+    tool_id: String,
+    configured: bool,
+    enabled: bool,
+    /// 监听地址
+    address: Option<String>,
+    /// 监听地址可连接（GUI 或 CLI 启动的代理均可检测到）
+    running: bool,
+    /// 由 `proxy start` 启动
+    managed_by_cli: bool,
+}
+
+pub(super) async fn run(command: ProxyCommand) -> Result<()> {
+    match command {
+        ProxyCommand::Start { tool } => start(&tool).await,
+        ProxyCommand::Stop { tool } => stop(&tool).await,
+        ProxyCommand::Status { json } => status(json),
+    }
+}
+
+/// 校验工具 ID（内置工具与支持代理的自定义工具）
+fn validate_tools(tools: &[String], store: &ProxyStore) -> Result<()> {
+    let supported = store.tool_ids();
+    match tools.iter().find(|t| !supported.contains(t)) {
+        Some(tool) => bail!("不支持的工具: {}", tool),
+        None => Ok(()),
+    }
+}
+
+async fn start(only: &[String]) -> Result<()> {
+    let store = ProxyConfigManager::new()?.load_proxy_store()?;
+    validate_tools(only, &store)?;
+
+    if let Some(state) = read_state()? {
+        let mut configs = state
+            .tools
+            .iter()
+            .filter_map(|tool_id| store.get_config(tool_id));
+        if configs.any(is_listening) {
+            bail!("已有 proxy start 进程在运行（pid {}）", state.pid);
+        }
+        // 上次前台进程被强制结束时遗留的状态文件
+        remove_state()?;
+    }
+
+    let log_config = read_global_config()
+        .ok()
+        .flatten()
+        .map(|cfg| cfg.log_config)
+        .unwrap_or_default();
+    if let Err(e) = init_logger(&log_config) {
+        eprintln!("WARNING: Failed to initialize logging system: {}", e);
+    }
+    install_panic_hook(&log_config);
+    crate::create_migration_manager()
+        .run_all()
+        .await
+        .map_err(|e| anyhow!("迁移执行失败: {}", e))?;
+    crate::services::recovery::run_startup_recovery();
+
+    let manager = ProxyManager::new();
+    let mut started = Vec::new();
+    for tool_id in store.tool_ids() {
+        let tool_id = tool_id.as_str();
+        if !only.is_empty() && !only.iter().any(|t| t == tool_id) {
+            continue;
+        }
+        let Some(config) = store.get_config(tool_id).cloned() else {
+            continue;
+        };
+        if !config.enabled {
+            println!("{}: 代理未启用，跳过", tool_id);
+            continue;
+        }
+        if config.local_api_key.is_none() {
+            println!("{}: 未配置保护密钥，跳过", tool_id);
+            continue;
+        }
+        let address = config.listen_address();
+        match manager.start_proxy(tool_id, config).await {
+            Ok(()) => {
+                println!("{}: 已启动（{}）", tool_id, address);
+                started.push(tool_id.to_string());
+            }
+            Err(e) => eprintln!("{}: 启动失败: {:#}", tool_id, e),
+        }
+    }
+    if started.is_empty() {
+        crate::services::recovery::mark_clean_shutdown();
+        bail!("没有可启动的代理，请先在 proxy.json 中启用并配置代理");
+    }
+
+    write_state(&CliProxyState {
+        pid: std::process::id(),
+        started_at: chrono::Utc::now().timestamp(),
+        tools: started.clone(),
+    })?;
+    tokio::spawn(crate::services::pricing::remote_sync::start_sync_scheduler());
+    tokio::spawn(crate::services::proxy::capture_store::run_purge_scheduler());
+    println!("代理已就绪，按 Ctrl+C 或执行 `duckcoding proxy stop` 退出");
+
+    let mut running = started;
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => break,
+            _ = tokio::time::sleep(POLL_INTERVAL) => {}
+        }
+        let requested = read_state()
+            .ok()
+            .flatten()
+            .map(|s| s.tools)
+            .unwrap_or_default();
+        for tool_id in running.iter().filter(|t| !requested.contains(t)) {
+            match manager.stop_proxy(tool_id).await {
+                Ok(()) => println!("{}: 已停止", tool_id),
+                Err(e) => eprintln!("{}: 停止失败: {:#}", tool_id, e),
+            }
+        }
+        running.retain(|t| requested.contains(t));
+        if running.is_empty() {
+            break;
+        }
+    }
+
+    println!("正在关闭代理...");
+    if let Err(e) = manager.stop_all().await {
+        tracing::warn!(error = ?e, "停止代理失败");
+    }
+    crate::services::session::shutdown_session_manager();
+    crate::services::token_stats::shutdown_token_stats_manager();
+    remove_state()?;
+    crate::services::recovery::mark_clean_shutdown();
+    Ok(())
+}
+
+async fn stop(only: &[String]) -> Result<()> {
+    let store = ProxyConfigManager::new()?.load_proxy_store()?;
+    validate_tools(only, &store)?;
+    let Some(mut state) = read_state()? else {
+        bail!("没有由 proxy start 启动的代理");
+    };
+
+    let stopping: Vec<String> = if only.is_empty() {
+        state.tools.clone()
+    } else {
+        state
+            .tools
+            .iter()
+            .filter(|t| only.contains(t))
+            .cloned()
+            .collect()
+    };
+    if stopping.is_empty() {
+        bail!("指定的工具不是由 proxy start 启动的");
+    }
+    state.tools.retain(|t| !stopping.contains(t));
+    write_state(&state)?;
+
+    // 等待前台进程处理停止请求
+    let deadline = tokio::time::Instant::now() + STOP_TIMEOUT;
+    loop {
+        let still_running: Vec<&String> = stopping
+            .iter()
+            .filter(|t| store.get_config(t).is_some_and(is_listening))
+            .collect();
+        if still_running.is_empty() {
+            break;
+        }
+        if tokio::time::Instant::now() >= deadline {
+            bail!("等待代理停止超时（pid {}）: {:?}", state.pid, still_running);
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+    for tool_id in &stopping {
+        println!("{}: 已停止", tool_id);
+    }
+    Ok(())
+}
+
+fn status(json: bool) -> Result<()> {
+    let store = ProxyConfigManager::new()?.load_proxy_store()?;
+    let state = read_state()?.unwrap_or_default();
+    let rows: Vec<ProxyStatusRow> = store
+        .tool_ids()
+        .into_iter()
+        .map(|tool_id| {
+            let config = store.get_config(&tool_id);
+            ProxyStatusRow {
+                configured: config.is_some(),
+                enabled: config.is_some_and(|c| c.enabled),
+                address: config.map(|c| c.listen_address()),
+                running: config.is_some_and(is_listening),
+                managed_by_cli: state.tools.contains(&tool_id),
+                tool_id,
+            }
+        })
+        .collect();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&rows)?);
+        return Ok(());
+    }
+    for row in rows {
+        let state = match (row.running, row.managed_by_cli) {
+            (true, true) => "运行中（CLI）",
+            (true, false) => "运行中",
+            (false, _) if !row.configured => "未配置",
+            (false, _) if !row.enabled => "未启用",
+            (false, _) => "已停止",
+        };
+        println!(
+            "{:<12} {:<24} {}",
+            row.tool_id,
+            row.address.as_deref().unwrap_or("-"),
+            state
+        );
+    }
+    Ok(())
+}
+
+/// 代理监听地址是否可连接
+fn is_listening(config: &ToolProxyConfig) -> bool {
+    if let Some(path) = config.listen_socket.as_deref() {
+        #[cfg(unix)]
+        return std::os::unix::net::UnixStream::connect(path.trim()).is_ok();
+        #[cfg(not(unix))]
+        return std::path::Path::new(path.trim()).exists();
+    }
+    let addr = SocketAddr::from(([127, 0, 0, 1], config.port));
+    TcpStream::connect_timeout(&addr, PROBE_TIMEOUT).is_ok()
+}
+
+/// 等待 Ctrl+C（Unix 下同时响应 SIGTERM）
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        if let Ok(mut term) = signal(SignalKind::terminate()) {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = term.recv() => {}
+            }
+            return;
+        }
+    }
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+fn state_path() -> Result<PathBuf> {
+    Ok(config_dir().map_err(|e| anyhow!(e))?.join(STATE_FILE))
+}
+
+fn read_state() -> Result<Option<CliProxyState>> {
+    let path = state_path()?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let value = DataManager::new().json_uncached().read(&path)?;
+    Ok(serde_json::from_value(value).ok())
+}
+
+fn write_state(state: &CliProxyState) -> Result<()> {
+    DataManager::new()
+        .json_uncached()
+        .write(&state_path()?, &serde_json::to_value(state)?)?;
+    Ok(())
+}
+
+fn remove_state() -> Result<()> {
+    let path = state_path()?;
+    if path.exists() {
+        std::fs::remove_file(&path)?;
+    }
+    Ok(())
+}
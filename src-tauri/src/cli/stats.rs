@@ -0,0 +1,101 @@
+//! `duckcoding stats` 子命令
+
+use super::StatsCommand;
+use crate::services::token_stats::{
+    parse_since, CostGroupBy, CostSummaryQuery, TokenStatsAnalytics,
+};
+use crate::utils::config::config_dir;
+use anyhow::{anyhow, Result};
+use chrono::Local;
+use clap::ValueEnum;
+
+/// 分组方式参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum GroupByArg {
+    Model,
+    Config,
+    Session,
+    UpstreamKey,
+    ClientKey,
+}
+
+impl From<GroupByArg> for CostGroupBy {
+    fn from(group_by: GroupByArg) -> Self {
+        match group_by {
+            GroupByArg::Model => CostGroupBy::Model,
+            GroupByArg::Config => CostGroupBy::Config,
+            GroupByArg::Session => CostGroupBy::Session,
+            GroupByArg::UpstreamKey => CostGroupBy::UpstreamKey,
+            GroupByArg::ClientKey => CostGroupBy::ClientKey,
+        }
+    }
+}
+
+pub(super) fn run(command: StatsCommand) -> Result<()> {
+    match command {
+        StatsCommand::Summary {
+            since,
+            tool,
+            tag,
+            group_by,
+            json,
+        } => summary(&since, tool, tag, group_by.into(), json),
+    }
+}
+
+fn summary(
+    since: &str,
+    tool: Option<String>,
+    tag: Option<String>,
+    group_by: CostGroupBy,
+    json: bool,
+) -> Result<()> {
+    let start_time = parse_since(since, Local::now())?;
+    let db_path = config_dir().map_err(|e| anyhow!(e))?.join("token_stats.db");
+    let summaries = if db_path.exists() {
+        TokenStatsAnalytics::new(db_path).query_cost_summary(&CostSummaryQuery {
+            start_time: Some(start_time),
+            end_time: None,
+            tool_type: tool,
+            session_id: None,
+            tag,
+            group_by,
+        })?
+    } else {
+        Vec::new()
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&summaries)?);
+        return Ok(());
+    }
+    if summaries.is_empty() {
+        println!("{} 以来暂无统计数据", since);
+        return Ok(());
+    }
+
+    println!(
+        "{:<36} {:>10} {:>14} {:>14} {:>12}",
+        "分组", "请求数", "输入 Token", "输出 Token", "成本 (USD)"
+    );
+    for s in &summaries {
+        let name = if s.group_name.is_empty() {
+            "-"
+        } else {
+            s.group_name.as_str()
+        };
+        println!(
+            "{:<36} {:>10} {:>14} {:>14} {:>12.4}",
+            name, s.request_count, s.input_tokens, s.output_tokens, s.total_cost
+        );
+    }
+    println!(
+        "{:<36} {:>10} {:>14} {:>14} {:>12.4}",
+        "合计",
+        summaries.iter().map(|s| s.request_count).sum::<i64>(),
+        summaries.iter().map(|s| s.input_tokens).sum::<i64>(),
+        summaries.iter().map(|s| s.output_tokens).sum::<i64>(),
+        summaries.iter().map(|s| s.total_cost).sum::<f64>()
+    );
+    Ok(())
+}
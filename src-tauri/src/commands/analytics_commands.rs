@@ -1,11 +1,23 @@
 //! Token统计分析相关的Tauri命令
 
 use anyhow::Result;
+use duckcoding::models::config::UsageReportFormat;
+use duckcoding::services::pricing::PRICING_MANAGER;
+use duckcoding::services::token_stats::analytics::{tag_filter_param, TAG_FILTER_CLAUSE};
 use duckcoding::services::token_stats::{
-    CostGroupBy, CostSummaryQuery, TimeGranularity, TokenStatsAnalytics, TrendDataPoint, TrendQuery,
+    CacheEfficiencyQuery, CacheEfficiencyReport, CostGroupBy, CostSummaryQuery, EpochSummary,
+    GeneratedUsageReport, LatencyStats, LatencyStatsQuery, ModelBreakdown, ModelBreakdownQuery,
+    MonthlyCostQuery, MonthlyCostReport, ProductivityAnalytics, ProductivityQuery,
+    ProductivityReport, ReportImportSummary, ReportOutput, ReportService, SavedReport,
+    SavedReportManager, ScrubOptions, SqlConsole, SqlConsoleQuery, SqlConsoleResult,
+    SqlHistoryEntry, StatsEpoch, StatsEpochManager, StatsScrubber, TimeGranularity,
+    TokenStatsAnalytics, ToolComparison, ToolComparisonQuery, TrendDataPoint, TrendQuery,
+    UsageReportRange,
 };
+use duckcoding::utils::config::read_global_config;
 use duckcoding::utils::config_dir;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// 按模型分组的成本统计
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,6 +41,36 @@ pub struct ConfigCostStat {
     pub request_count: i64,
 }
 
+/// 按 Key 池 Key 别名分组的用量统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyCostStat {
+    /// Key 别名
+    pub key_alias: String,
+    /// 总成本（USD）
+    pub total_cost: f64,
+    /// 请求数
+    pub request_count: i64,
+    /// 输入 Token 总数
+    pub input_tokens: i64,
+    /// 输出 Token 总数
+    pub output_tokens: i64,
+}
+
+/// 按客户端 Key 标签分组的用量统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientCostStat {
+    /// 客户端 Key 标签
+    pub client_label: String,
+    /// 总成本（USD）
+    pub total_cost: f64,
+    /// 请求数
+    pub request_count: i64,
+    /// 输入 Token 总数
+    pub input_tokens: i64,
+    /// 输出 Token 总数
+    pub output_tokens: i64,
+}
+
 /// 成本汇总数据（前端期望的格式）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CostSummary {
@@ -46,6 +88,10 @@ pub struct CostSummary {
     pub cost_by_model: Vec<ModelCostStat>,
     /// 按配置分组的成本
     pub cost_by_config: Vec<ConfigCostStat>,
+    /// 按 Key 池 Key 别名分组的用量（未使用 Key 池时为空）
+    pub cost_by_key: Vec<KeyCostStat>,
+    /// 按客户端 Key 标签分组的用量（未使用客户端 Key 时为空）
+    pub cost_by_client: Vec<ClientCostStat>,
     /// 按天的成本趋势
     pub daily_costs: Vec<DailyCost>,
 }
@@ -87,6 +133,7 @@ pub async fn query_token_trends(query: TrendQuery) -> Result<Vec<TrendDataPoint>
 /// - `end_time`: 结束时间戳（毫秒）
 /// - `tool_type`: 工具类型过滤（可选）
 /// - `session_id`: 会话 ID 过滤（可选）
+/// - `tag`: 标签过滤（可选，如项目名）
 ///
 /// # 返回
 /// - `Ok(CostSummary)`: 成本汇总数据
@@ -97,6 +144,7 @@ pub async fn query_cost_summary(
     end_time: i64,
     tool_type: Option<String>,
     session_id: Option<String>,
+    tag: Option<String>,
 ) -> Result<CostSummary, String> {
     let db_path = config_dir()
         .map_err(|e| format!("Failed to get config dir: {}", e))?
@@ -110,6 +158,7 @@ pub async fn query_cost_summary(
         end_time: Some(end_time),
         tool_type: tool_type.clone(),
         session_id: session_id.clone(),
+        tag: tag.clone(),
         group_by: CostGroupBy::Model, // 默认分组，实际查询时会覆盖
     };
 
@@ -131,12 +180,31 @@ pub async fn query_cost_summary(
         .query_cost_summary(&config_query)
         .map_err(|e| format!("Failed to query cost by config: {}", e))?;
 
+    // 2.1 查询按 Key 池 Key 别名分组的用量
+    let key_query = CostSummaryQuery {
+        group_by: CostGroupBy::UpstreamKey,
+        ..base_query.clone()
+    };
+    let key_summaries = analytics
+        .query_cost_summary(&key_query)
+        .map_err(|e| format!("Failed to query cost by upstream key: {}", e))?;
+
+    // 2.2 查询按客户端 Key 标签分组的用量
+    let client_query = CostSummaryQuery {
+        group_by: CostGroupBy::ClientKey,
+        ..base_query.clone()
+    };
+    let client_summaries = analytics
+        .query_cost_summary(&client_query)
+        .map_err(|e| format!("Failed to query cost by client key: {}", e))?;
+
     // 3. 查询按天的成本趋势
     let trend_query = TrendQuery {
         start_time: Some(start_time),
         end_time: Some(end_time),
         tool_type: tool_type.clone(),
         session_id: session_id.clone(),
+        tag: tag.clone(),
         granularity: TimeGranularity::Day,
         ..Default::default()
     };
@@ -171,6 +239,11 @@ pub async fn query_cost_summary(
         params.push(Box::new(sid.clone()));
     }
 
+    if let Some(ref tag) = tag {
+        where_clauses.push(TAG_FILTER_CLAUSE);
+        params.push(Box::new(tag_filter_param(tag)));
+    }
+
     let where_clause = where_clauses.join(" AND ");
 
     let sql = format!(
@@ -229,6 +302,28 @@ pub async fn query_cost_summary(
                 request_count: s.request_count,
             })
             .collect(),
+        cost_by_key: key_summaries
+            .into_iter()
+            .filter(|s| !s.group_name.is_empty())
+            .map(|s| KeyCostStat {
+                key_alias: s.group_name,
+                total_cost: s.total_cost,
+                request_count: s.request_count,
+                input_tokens: s.input_tokens,
+                output_tokens: s.output_tokens,
+            })
+            .collect(),
+        cost_by_client: client_summaries
+            .into_iter()
+            .filter(|s| !s.group_name.is_empty())
+            .map(|s| ClientCostStat {
+                client_label: s.group_name,
+                total_cost: s.total_cost,
+                request_count: s.request_count,
+                input_tokens: s.input_tokens,
+                output_tokens: s.output_tokens,
+            })
+            .collect(),
         daily_costs: daily_trends
             .into_iter()
             .map(|d| DailyCost {
@@ -329,7 +424,7 @@ mod tests {
         for (i, model) in models.iter().enumerate() {
             for (j, config) in configs.iter().enumerate() {
                 for k in 0..3 {
-                    let log = TokenLog::new(
+                    let mut log = TokenLog::new(
                         "claude-code".to_string(),
                         base_time - (k * 1000),
                         "127.0.0.1".to_string(),
@@ -356,6 +451,11 @@ mod tests {
                         0.0033,
                         Some("test_template".to_string()),
                     );
+                    // 前两条经 Key 池转发，第三条未使用 Key 池
+                    log.upstream_key_alias =
+                        ["key-a", "key-b"].get(k as usize).map(|s| s.to_string());
+                    // 第一条使用客户端 Key，其余使用主保护密钥
+                    log.client_key_label = (k == 0).then(|| "laptop".to_string());
                     db.insert_log(&log).unwrap();
                 }
             }
@@ -393,5 +493,342 @@ mod tests {
             assert_eq!(summary.request_count, 6); // 每个配置6条记录（2个模型 × 3条）
             assert!(summary.total_cost > 0.0);
         }
+
+        // 按 Key 别名分组（未使用 Key 池的请求归为空别名）
+        let key_query = CostSummaryQuery {
+            tool_type: Some("claude-code".to_string()),
+            group_by: CostGroupBy::UpstreamKey,
+            ..Default::default()
+        };
+        let mut key_summaries = analytics.query_cost_summary(&key_query).unwrap();
+        key_summaries.sort_by(|a, b| a.group_name.cmp(&b.group_name));
+        let groups: Vec<_> = key_summaries
+            .iter()
+            .map(|s| (s.group_name.as_str(), s.request_count))
+            .collect();
+        assert_eq!(groups, [("", 4), ("key-a", 4), ("key-b", 4)]);
+
+        // 按客户端 Key 标签分组（使用主保护密钥的请求归为空标签）
+        let client_query = CostSummaryQuery {
+            tool_type: Some("claude-code".to_string()),
+            group_by: CostGroupBy::ClientKey,
+            ..Default::default()
+        };
+        let mut client_summaries = analytics.query_cost_summary(&client_query).unwrap();
+        client_summaries.sort_by(|a, b| a.group_name.cmp(&b.group_name));
+        let groups: Vec<_> = client_summaries
+            .iter()
+            .map(|s| (s.group_name.as_str(), s.request_count))
+            .collect();
+        assert_eq!(groups, [("", 8), ("laptop", 4)]);
     }
 }
+
+/// 查询生产力关联指标（git 变更行数 × 会话成本）
+///
+/// # 返回
+/// - `Ok(ProductivityReport)`: 包含每千行成本、每次提交成本
+/// - `Err`: 仓库无效或查询失败
+#[tauri::command]
+pub async fn query_productivity_metrics(
+    query: ProductivityQuery,
+) -> Result<ProductivityReport, String> {
+    let db_path = config_dir()
+        .map_err(|e| format!("Failed to get config dir: {}", e))?
+        .join("token_stats.db");
+
+    tokio::task::spawn_blocking(move || {
+        ProductivityAnalytics::new(db_path)
+            .query(&query)
+            .map_err(|e| format!("Failed to query productivity metrics: {}", e))
+    })
+    .await
+    .map_err(|e| format!("生产力统计任务失败: {}", e))?
+}
+
+/// 查询脱敏后的成本汇总（截图 / 演示模式）
+///
+/// 配置名称替换为短哈希，会话 ID 缩短，成本可选按数量级取整
+#[tauri::command]
+pub async fn query_anonymized_cost_summary(
+    start_time: i64,
+    end_time: i64,
+    tool_type: Option<String>,
+    session_id: Option<String>,
+    tag: Option<String>,
+    options: ScrubOptions,
+) -> Result<serde_json::Value, String> {
+    let summary = query_cost_summary(start_time, end_time, tool_type, session_id, tag).await?;
+    StatsScrubber::new(options)
+        .scrub_serializable(&summary)
+        .map_err(|e| format!("脱敏成本汇总失败: {}", e))
+}
+
+/// 脱敏任意统计数据（趋势、日志、对比等前端已获取的数据）
+#[tauri::command]
+pub async fn anonymize_stats_payload(
+    mut payload: serde_json::Value,
+    options: ScrubOptions,
+) -> Result<serde_json::Value, String> {
+    StatsScrubber::new(options).scrub(&mut payload);
+    Ok(payload)
+}
+
+/// 查询同一时间段内各工具的横向对比
+///
+/// # 返回
+/// - `Ok(ToolComparison)`: 每个工具的每次请求成本、平均延迟、失败率、缓存命中率，以及各项最优工具
+/// - `Err`: 查询失败
+#[tauri::command]
+pub async fn query_tool_comparison(query: ToolComparisonQuery) -> Result<ToolComparison, String> {
+    let db_path = config_dir()
+        .map_err(|e| format!("Failed to get config dir: {}", e))?
+        .join("token_stats.db");
+
+    TokenStatsAnalytics::new(db_path)
+        .query_tool_comparison(&query)
+        .map_err(|e| format!("Failed to query tool comparison: {}", e))
+}
+
+/// 查询按模型的用量排行（"Top models" 卡片）
+///
+/// # 返回
+/// - `Ok(ModelBreakdown)`: 每个模型的请求数、Token、成本、平均延迟、失败率，以及与上一等长周期的对比
+/// - `Err`: 范围无效或查询失败
+#[tauri::command]
+pub async fn get_model_breakdown(range: UsageReportRange) -> Result<ModelBreakdown, String> {
+    let (start_time, end_time) = range
+        .resolve(chrono::Utc::now().timestamp_millis())
+        .map_err(|e| e.to_string())?;
+    let db_path = config_dir()
+        .map_err(|e| format!("Failed to get config dir: {}", e))?
+        .join("token_stats.db");
+
+    TokenStatsAnalytics::new(db_path)
+        .query_model_breakdown(&ModelBreakdownQuery {
+            start_time,
+            end_time,
+        })
+        .map_err(|e| format!("Failed to query model breakdown: {}", e))
+}
+
+/// 查询响应时间分位数，用于对比不同模型 / 供应商配置的响应速度
+///
+/// # 返回
+/// - `Ok(Vec<LatencyStats>)`: 每个分组的请求数、平均值与 p50 / p90 / p99 响应时间
+/// - `Err`: 查询失败
+#[tauri::command]
+pub async fn query_latency_stats(query: LatencyStatsQuery) -> Result<Vec<LatencyStats>, String> {
+    let db_path = config_dir()
+        .map_err(|e| format!("Failed to get config dir: {}", e))?
+        .join("token_stats.db");
+
+    TokenStatsAnalytics::new(db_path)
+        .query_latency_stats(&query)
+        .map_err(|e| format!("Failed to query latency stats: {}", e))
+}
+
+/// 查询月度账单（用量成本 + 价格模板的月最低消费）
+#[tauri::command]
+pub async fn query_monthly_cost_report(
+    query: MonthlyCostQuery,
+) -> Result<Vec<MonthlyCostReport>, String> {
+    let db_path = config_dir()
+        .map_err(|e| format!("Failed to get config dir: {}", e))?
+        .join("token_stats.db");
+
+    let min_monthly_fees: HashMap<String, f64> = PRICING_MANAGER
+        .list_templates()
+        .map_err(|e| format!("Failed to list pricing templates: {}", e))?
+        .into_iter()
+        .filter(|t| t.min_monthly_fee > 0.0)
+        .map(|t| (t.id, t.min_monthly_fee))
+        .collect();
+
+    TokenStatsAnalytics::new(db_path)
+        .query_monthly_costs(&query, &min_monthly_fees)
+        .map_err(|e| format!("Failed to query monthly costs: {}", e))
+}
+
+/// 查询缓存效率（缓存读取占比与按价格模板估算的缓存节省）
+#[tauri::command]
+pub async fn query_cache_efficiency(
+    query: CacheEfficiencyQuery,
+) -> Result<CacheEfficiencyReport, String> {
+    let db_path = config_dir()
+        .map_err(|e| format!("Failed to get config dir: {}", e))?
+        .join("token_stats.db");
+
+    // 以 100 万输入 Token 计价得到模板中的输入单价（含模板倍率）
+    let input_price_lookup = |template_id: &str, tool_type: &str, model: &str| {
+        PRICING_MANAGER
+            .calculate_cost(
+                Some(template_id).filter(|id| !id.is_empty()),
+                Some(tool_type),
+                model,
+                1_000_000,
+                0,
+                0,
+                0,
+                0,
+                0,
+            )
+            .ok()
+            .map(|breakdown| breakdown.input_price / 1_000_000.0)
+    };
+
+    TokenStatsAnalytics::new(db_path)
+        .query_cache_efficiency(&query, input_price_lookup)
+        .map_err(|e| format!("Failed to query cache efficiency: {}", e))
+}
+
+/// 执行只读 SQL 查询（SQL 控制台）
+///
+/// 仅允许单条 SELECT/WITH 语句，受行数与超时限制，执行记录会写入查询历史
+#[tauri::command]
+pub async fn run_sql_console_query(query: SqlConsoleQuery) -> Result<SqlConsoleResult, String> {
+    tokio::task::spawn_blocking(move || {
+        SqlConsole::new()
+            .and_then(|console| console.execute(&query))
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("SQL 查询任务失败: {}", e))?
+}
+
+/// 获取 SQL 控制台查询历史（最新在前）
+#[tauri::command]
+pub async fn get_sql_console_history() -> Result<Vec<SqlHistoryEntry>, String> {
+    SqlConsole::new()
+        .and_then(|console| console.history())
+        .map_err(|e| format!("读取查询历史失败: {}", e))
+}
+
+/// 清空 SQL 控制台查询历史
+#[tauri::command]
+pub async fn clear_sql_console_history() -> Result<(), String> {
+    SqlConsole::new()
+        .and_then(|console| console.clear_history())
+        .map_err(|e| format!("清空查询历史失败: {}", e))
+}
+
+/// 列出已保存的报表
+#[tauri::command]
+pub async fn list_saved_reports() -> Result<Vec<SavedReport>, String> {
+    SavedReportManager::new()
+        .and_then(|mgr| mgr.list())
+        .map_err(|e| format!("读取报表失败: {}", e))
+}
+
+/// 保存报表（id 为空时新建）
+#[tauri::command]
+pub async fn save_report(report: SavedReport) -> Result<SavedReport, String> {
+    SavedReportManager::new()
+        .and_then(|mgr| mgr.save(report))
+        .map_err(|e| format!("保存报表失败: {}", e))
+}
+
+/// 删除报表
+#[tauri::command]
+pub async fn delete_saved_report(id: String) -> Result<(), String> {
+    SavedReportManager::new()
+        .and_then(|mgr| mgr.delete(&id))
+        .map_err(|e| format!("删除报表失败: {}", e))
+}
+
+/// 运行已保存的报表
+#[tauri::command]
+pub async fn run_saved_report(id: String) -> Result<ReportOutput, String> {
+    tokio::task::spawn_blocking(move || {
+        SavedReportManager::new()
+            .and_then(|mgr| mgr.run(&id))
+            .map_err(|e| format!("运行报表失败: {}", e))
+    })
+    .await
+    .map_err(|e| format!("报表任务失败: {}", e))?
+}
+
+/// 导出报表为 JSON 字符串（ids 为空时导出全部）
+#[tauri::command]
+pub async fn export_saved_reports(ids: Vec<String>) -> Result<String, String> {
+    SavedReportManager::new()
+        .and_then(|mgr| mgr.export(&ids))
+        .map_err(|e| format!("导出报表失败: {}", e))
+}
+
+/// 从 JSON 字符串导入报表
+#[tauri::command]
+pub async fn import_saved_reports(
+    content: String,
+    overwrite: bool,
+) -> Result<ReportImportSummary, String> {
+    SavedReportManager::new()
+        .and_then(|mgr| mgr.import(&content, overwrite))
+        .map_err(|e| format!("导入报表失败: {}", e))
+}
+
+/// 生成用量报告（总成本、常用模型与会话、缓存节省、热力图）并写入报告目录
+///
+/// `format` 为空时使用配置中的输出格式
+#[tauri::command]
+pub async fn generate_usage_report(
+    range: UsageReportRange,
+    format: Option<UsageReportFormat>,
+) -> Result<GeneratedUsageReport, String> {
+    tokio::task::spawn_blocking(move || {
+        let config = read_global_config()
+            .ok()
+            .flatten()
+            .map(|c| c.token_stats_config.usage_report)
+            .unwrap_or_default();
+        ReportService::new(&config)
+            .and_then(|service| service.generate(range, format.unwrap_or(config.format)))
+            .map_err(|e| format!("生成用量报告失败: {}", e))
+    })
+    .await
+    .map_err(|e| format!("报告任务失败: {}", e))?
+}
+
+/// 创建统计周期管理器（确保周期表已创建）
+fn epoch_manager() -> Result<StatsEpochManager, String> {
+    let db_path = config_dir()
+        .map_err(|e| format!("Failed to get config dir: {}", e))?
+        .join("token_stats.db");
+    let manager = StatsEpochManager::new(db_path);
+    manager
+        .init_tables()
+        .map_err(|e| format!("初始化统计周期失败: {}", e))?;
+    Ok(manager)
+}
+
+/// 获取当前统计周期（仪表板默认以其开始时间为下限）
+#[tauri::command]
+pub async fn get_current_stats_epoch() -> Result<StatsEpoch, String> {
+    epoch_manager()?
+        .current()
+        .map_err(|e| format!("获取统计周期失败: {}", e))
+}
+
+/// 列出所有统计周期
+#[tauri::command]
+pub async fn list_stats_epochs() -> Result<Vec<StatsEpoch>, String> {
+    epoch_manager()?
+        .list()
+        .map_err(|e| format!("读取统计周期失败: {}", e))
+}
+
+/// 查询统计周期的分组用量
+#[tauri::command]
+pub async fn get_stats_epoch_summary(epoch_id: i64) -> Result<Vec<EpochSummary>, String> {
+    epoch_manager()?
+        .summary(epoch_id)
+        .map_err(|e| format!("查询统计周期失败: {}", e))
+}
+
+/// 关闭当前统计周期并归档，开启新周期
+#[tauri::command]
+pub async fn start_new_stats_epoch(name: Option<String>) -> Result<StatsEpoch, String> {
+    epoch_manager()?
+        .start_new(name)
+        .map_err(|e| format!("开启新统计周期失败: {}", e))
+}
@@ -5,7 +5,7 @@
 
 use ::duckcoding::http_client::build_client;
 use ::duckcoding::models::{BalanceConfig, BalanceStore};
-use ::duckcoding::services::balance::BalanceManager;
+use ::duckcoding::services::balance::{BalanceManager, BalanceService, BalanceSummary};
 use ::duckcoding::services::proxy::config::apply_global_proxy;
 use std::collections::HashMap;
 
@@ -123,3 +123,16 @@ pub async fn migrate_balance_from_localstorage(
     tracing::info!("从 localStorage 迁移了 {} 个余额监控配置", count);
     Ok(count)
 }
+
+// ========== 余额聚合 ==========
+
+/// 获取所有供应商与 Profile 的余额及历史
+///
+/// `refresh` 为 true 时先立即查询一轮，否则返回最近一次定时查询的结果
+#[tauri::command]
+pub async fn get_all_balances(refresh: Option<bool>) -> Result<Vec<BalanceSummary>, String> {
+    BalanceService::global()
+        .get_all_balances(refresh.unwrap_or(false))
+        .await
+        .map_err(|e| e.to_string())
+}
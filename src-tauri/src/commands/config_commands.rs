@@ -2,12 +2,16 @@
 
 use serde_json::Value;
 
+use ::duckcoding::data::compat::{self, CompatFileReport};
+use ::duckcoding::models::proxy_config::ProxyStore;
 use ::duckcoding::services::config::{
     claude, codex, gemini, ClaudeSettingsPayload, CodexSettingsPayload, GeminiEnvPayload,
     GeminiSettingsPayload,
 };
+use ::duckcoding::services::profile_manager::{ProfileManager, ProfilesStore};
 use ::duckcoding::services::proxy::config::apply_global_proxy;
-use ::duckcoding::utils::config::{read_global_config, write_global_config};
+use ::duckcoding::services::proxy_config_manager::ProxyConfigManager;
+use ::duckcoding::utils::config::{global_config_path, read_global_config, write_global_config};
 use ::duckcoding::GlobalConfig;
 
 // ==================== 类型定义 ====================
@@ -61,6 +65,11 @@ pub async fn update_token_stats_config(
     // 读取当前配置
     let mut global_config = read_global_config()?.ok_or_else(|| "全局配置不存在".to_string())?;
 
+    // 先应用请求流水导出配置，无法打开输出文件时不落盘
+    ::duckcoding::services::token_stats::FlightRecorder::global()
+        .configure(&config.flight_recorder)
+        .map_err(|e| e.to_string())?;
+
     // 仅更新 token_stats_config 字段
     global_config.token_stats_config = config;
 
@@ -73,6 +82,20 @@ pub async fn get_global_config() -> Result<Option<GlobalConfig>, String> {
     read_global_config()
 }
 
+/// 配置兼容性报告：列出全局配置、Profile、透明代理配置中当前版本不理解的字段
+#[tauri::command]
+pub async fn get_config_compat_report() -> Result<Vec<CompatFileReport>, String> {
+    let config_path = global_config_path()?;
+    let profile_mgr = ProfileManager::new().map_err(|e| e.to_string())?;
+    let proxy_mgr = ProxyConfigManager::new().map_err(|e| e.to_string())?;
+
+    Ok(vec![
+        compat::inspect_file::<GlobalConfig>("config.json", &config_path),
+        compat::inspect_file::<ProfilesStore>("profiles.json", profile_mgr.profiles_path()),
+        compat::inspect_file::<ProxyStore>("proxy.json", proxy_mgr.store_path()),
+    ])
+}
+
 #[tauri::command]
 pub async fn generate_api_key_for_tool(tool: String) -> Result<GenerateApiKeyResult, String> {
     // 应用代理配置（如果已配置）
@@ -277,6 +300,45 @@ pub async fn update_single_instance_config(enabled: bool) -> Result<(), String>
     Ok(())
 }
 
+// ==================== 只读观察模式命令 ====================
+
+/// 获取只读观察模式状态
+#[tauri::command]
+pub async fn get_observer_mode() -> Result<bool, String> {
+    Ok(::duckcoding::data::guard::is_observer_mode())
+}
+
+/// 切换只读观察模式（立即生效）
+///
+/// 运行中的透明代理停止时需要还原工具配置，因此开启前要求先停止所有代理。
+#[tauri::command]
+pub async fn set_observer_mode(
+    enabled: bool,
+    proxy_state: tauri::State<'_, super::proxy_commands::ProxyManagerState>,
+) -> Result<(), String> {
+    if enabled {
+        let running: Vec<String> = proxy_state
+            .manager
+            .get_all_status()
+            .await
+            .into_iter()
+            .filter_map(|(tool_id, running)| running.then_some(tool_id))
+            .collect();
+        if !running.is_empty() {
+            return Err(format!("请先停止运行中的透明代理: {}", running.join(", ")));
+        }
+    }
+
+    let mut config = read_global_config()
+        .map_err(|e| format!("读取配置失败: {e}"))?
+        .ok_or("配置文件不存在")?;
+    config.observer_mode = enabled;
+    write_global_config(&config).map_err(|e| format!("保存配置失败: {e}"))?;
+    ::duckcoding::data::guard::set_observer_mode(enabled);
+
+    Ok(())
+}
+
 // ==================== 配置监听命令 ====================
 
 /// 阻止外部变更（恢复到快照）
@@ -292,20 +354,21 @@ pub async fn update_single_instance_config(enabled: bool) -> Result<(), String>
 pub fn block_external_change(tool_id: String) -> Result<(), String> {
     use ::duckcoding::data::snapshots;
     use ::duckcoding::data::DataManager;
-    use ::duckcoding::models::Tool;
+    use ::duckcoding::services::config::watcher::watch_target_dir;
 
     // 获取快照
     let snapshot = snapshots::get_snapshot(&tool_id)
         .map_err(|e| format!("读取快照失败: {}", e))?
         .ok_or_else(|| "没有可用的配置快照".to_string())?;
 
-    // 获取工具定义
-    let tool = Tool::by_id(&tool_id).ok_or_else(|| format!("未找到工具: {}", tool_id))?;
+    // 获取配置目录（工具或额外监听路径）
+    let config_dir =
+        watch_target_dir(&tool_id).ok_or_else(|| format!("未找到工具: {}", tool_id))?;
     let manager = DataManager::new();
 
     // 恢复所有配置文件
     for (filename, content) in &snapshot.files {
-        let config_path = tool.config_dir.join(filename);
+        let config_path = config_dir.join(filename);
 
         if filename.ends_with(".json") {
             // JSON 文件：直接写入
@@ -317,9 +380,13 @@ pub fn block_external_change(tool_id: String) -> Result<(), String> {
             // TOML 文件：将 JSON 转换回 TOML
             let toml_value: toml::Value = serde_json::from_value(content.clone())
                 .map_err(|e| format!("JSON 转 TOML 失败: {}", e))?;
-            let toml_str =
-                toml::to_string(&toml_value).map_err(|e| format!("TOML 序列化失败: {}", e))?;
-            std::fs::write(&config_path, toml_str)
+            let doc = toml::to_string(&toml_value)
+                .map_err(|e| format!("TOML 序列化失败: {}", e))?
+                .parse::<toml_edit::DocumentMut>()
+                .map_err(|e| format!("TOML 解析失败: {}", e))?;
+            manager
+                .toml()
+                .write(&config_path, &doc)
                 .map_err(|e| format!("写入 {} 失败: {}", filename, e))?;
         } else if filename.ends_with(".env") || filename == ".env" {
             // ENV 文件：将 JSON 转换回键值对
@@ -344,6 +411,12 @@ pub fn block_external_change(tool_id: String) -> Result<(), String> {
         store.save().map_err(|e| format!("保存日志失败: {}", e))?;
     }
 
+    ::duckcoding::services::audit::record(
+        ::duckcoding::services::audit::AuditAction::ExternalChangeBlocked,
+        &tool_id,
+        None,
+        serde_json::json!({ "restored_files": snapshot.files.keys().collect::<Vec<_>>() }),
+    );
     tracing::info!(tool_id = %tool_id, "已阻止外部变更并恢复所有配置文件");
 
     Ok(())
@@ -360,12 +433,8 @@ pub fn block_external_change(tool_id: String) -> Result<(), String> {
 /// 操作成功返回 Ok
 #[tauri::command]
 pub fn allow_external_change(tool_id: String) -> Result<(), String> {
-    use ::duckcoding::models::Tool;
-
-    let tool = Tool::by_id(&tool_id).ok_or_else(|| format!("未找到工具: {}", tool_id))?;
-
     // 重新保存快照（读取所有配置文件）
-    ::duckcoding::services::config::watcher::save_snapshot_for_tool(&tool)
+    ::duckcoding::services::config::watcher::save_snapshot_by_id(&tool_id)
         .map_err(|e| format!("保存快照失败: {}", e))?;
 
     // 更新日志记录
@@ -377,6 +446,12 @@ pub fn allow_external_change(tool_id: String) -> Result<(), String> {
         store.save().map_err(|e| format!("保存日志失败: {}", e))?;
     }
 
+    ::duckcoding::services::audit::record(
+        ::duckcoding::services::audit::AuditAction::ExternalChangeAllowed,
+        &tool_id,
+        None,
+        serde_json::json!({}),
+    );
     tracing::info!(tool_id = %tool_id, "已允许外部变更并更新所有配置文件快照");
 
     Ok(())
@@ -392,21 +467,130 @@ pub fn get_watch_config() -> Result<::duckcoding::models::config::ConfigWatchCon
 }
 
 /// 更新监听配置
+///
+/// 额外监听路径变化时为新增路径建立快照并重启配置守护。
 #[tauri::command]
 pub fn update_watch_config(
+    app: tauri::AppHandle,
     config: ::duckcoding::models::config::ConfigWatchConfig,
 ) -> Result<(), String> {
+    use ::duckcoding::services::config::watcher;
+
     let mut global_config = read_global_config()
         .map_err(|e| format!("读取配置失败: {e}"))?
         .ok_or("配置文件不存在")?;
+    let previous_paths = std::mem::take(&mut global_config.config_watch.watched_paths);
+    let paths_changed = previous_paths != config.watched_paths;
     global_config.config_watch = config;
     write_global_config(&global_config).map_err(|e| format!("保存配置失败: {e}"))?;
 
     tracing::info!("配置监听配置已更新");
 
+    if paths_changed {
+        for watched in &global_config.config_watch.watched_paths {
+            if previous_paths.iter().any(|p| p.path == watched.path) {
+                continue;
+            }
+            let id = watcher::watched_path_id(&watched.path);
+            if let Err(e) = watcher::save_snapshot_by_id(&id) {
+                tracing::warn!(path = %watched.path, error = ?e, "保存监听路径快照失败");
+            }
+        }
+        if let Err(e) = ::duckcoding::services::config::start_watcher(app) {
+            tracing::warn!(error = ?e, "重启配置守护失败");
+        }
+    }
+
     Ok(())
 }
 
+/// 获取各工具实际使用的配置目录
+#[tauri::command]
+pub fn get_tool_config_dirs() -> Result<Vec<::duckcoding::models::ToolConfigDir>, String> {
+    Ok(::duckcoding::Tool::all()
+        .iter()
+        .map(|tool| ::duckcoding::Tool::resolve_config_dir(&tool.id))
+        .collect())
+}
+
+/// 设置工具配置目录覆盖（`config_dir` 为空时清除覆盖）
+///
+/// 保存后刷新该工具的配置快照并重启配置守护，使监听切换到新目录。
+#[tauri::command]
+pub fn set_tool_config_dir(
+    app: tauri::AppHandle,
+    tool_id: String,
+    config_dir: Option<String>,
+) -> Result<::duckcoding::models::ToolConfigDir, String> {
+    if ::duckcoding::Tool::by_id(&tool_id).is_none() {
+        return Err(format!("未知工具: {tool_id}"));
+    }
+    let config_dir = config_dir
+        .map(|dir| dir.trim().to_string())
+        .filter(|dir| !dir.is_empty());
+
+    let mut global_config = read_global_config()
+        .map_err(|e| format!("读取配置失败: {e}"))?
+        .ok_or("配置文件不存在")?;
+    match &config_dir {
+        Some(dir) => {
+            global_config
+                .tool_config_dirs
+                .insert(tool_id.clone(), dir.clone());
+        }
+        None => {
+            global_config.tool_config_dirs.remove(&tool_id);
+        }
+    }
+
+    // 校验目录存在后再保存，避免之后的写入落到错误位置
+    let resolved = ::duckcoding::Tool::resolve_config_dir_with_overrides(
+        &tool_id,
+        &global_config.tool_config_dirs,
+    );
+    if config_dir.is_some() && !resolved.exists {
+        return Err(format!("配置目录不存在: {}", resolved.config_dir.display()));
+    }
+    write_global_config(&global_config).map_err(|e| format!("保存配置失败: {e}"))?;
+    tracing::info!(
+        tool_id = %tool_id,
+        config_dir = %resolved.config_dir.display(),
+        source = ?resolved.source,
+        "工具配置目录已更新"
+    );
+
+    // 快照需按新目录重新建立
+    if let Some(tool) = ::duckcoding::Tool::by_id(&tool_id) {
+        if let Err(e) = ::duckcoding::services::config::watcher::save_snapshot_for_tool(&tool) {
+            tracing::warn!(tool_id = %tool_id, error = ?e, "刷新配置快照失败");
+        }
+    }
+    if let Err(e) = ::duckcoding::services::config::start_watcher(app) {
+        tracing::warn!(error = ?e, "重启配置守护失败");
+    }
+
+    Ok(resolved)
+}
+
+/// 模拟外部配置变更事件（开发调试用）
+///
+/// 事件经过真实的过滤、变更日志与通知流程。
+/// 仅 Debug 构建或设置 `DUCKCODING_DEV_TOOLS=1` 时可用。
+#[tauri::command]
+pub fn simulate_external_config_change(
+    change: ::duckcoding::services::config::simulate::SimulatedChange,
+) -> Result<::duckcoding::services::config::simulate::SimulationResult, String> {
+    use ::duckcoding::services::config::simulate;
+
+    if !simulate::simulation_enabled() {
+        return Err(format!(
+            "模拟变更仅在开发模式可用（设置 {}=1 启用）",
+            simulate::DEV_TOOLS_ENV
+        ));
+    }
+    simulate::simulate_external_change(&change).map_err(|e| e.to_string())
+}
+
 // ==================== 配置守护管理命令 ====================
 
 /// 更新敏感字段配置
@@ -0,0 +1,54 @@
+//! 崩溃报告命令
+//!
+//! 报告只保存在本地；发送需先在设置中开启上传，再由用户对单个报告确认发送
+
+use duckcoding::core::crash::{self, CrashReport, CrashReportSummary};
+use duckcoding::models::config::CrashReportConfig;
+use duckcoding::utils::config::{read_global_config, write_global_config};
+
+/// 列出本地崩溃报告（从新到旧）
+#[tauri::command]
+pub async fn list_crash_reports() -> Result<Vec<CrashReportSummary>, String> {
+    crash::list_crash_reports().map_err(|e| e.to_string())
+}
+
+/// 获取崩溃报告详情（发送前供用户查看完整内容）
+#[tauri::command]
+pub async fn get_crash_report(id: String) -> Result<CrashReport, String> {
+    crash::get_crash_report(&id).map_err(|e| e.to_string())
+}
+
+/// 删除崩溃报告
+#[tauri::command]
+pub async fn delete_crash_report(id: String) -> Result<(), String> {
+    crash::delete_crash_report(&id).map_err(|e| e.to_string())
+}
+
+/// 发送崩溃报告（用户确认后调用）
+#[tauri::command]
+pub async fn send_crash_report(id: String) -> Result<CrashReport, String> {
+    crash::send_crash_report(&id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 更新崩溃报告上传配置
+#[tauri::command]
+pub async fn update_crash_report_config(config: CrashReportConfig) -> Result<(), String> {
+    if let Some(endpoint) = config.endpoint.as_deref().filter(|e| !e.trim().is_empty()) {
+        let parsed = url::Url::parse(endpoint.trim()).map_err(|e| format!("接收地址无效: {e}"))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err("接收地址必须是 http(s) 地址".to_string());
+        }
+    }
+    let mut global_config = read_global_config()
+        .map_err(|e| format!("读取配置失败: {e}"))?
+        .ok_or("配置文件不存在")?;
+    global_config.crash_report = config;
+    write_global_config(&global_config).map_err(|e| format!("保存配置失败: {e}"))?;
+    tracing::info!(
+        send_enabled = global_config.crash_report.send_enabled,
+        "崩溃报告配置已更新"
+    );
+    Ok(())
+}
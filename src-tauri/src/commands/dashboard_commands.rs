@@ -2,6 +2,12 @@
 //
 // 仪表板状态管理 Tauri 命令
 
+use crate::commands::profile_commands::ProfileManagerState;
+use crate::commands::proxy_commands::ProxyManagerState;
+use crate::commands::update_commands::UpdateServiceState;
+use ::duckcoding::models::dashboard::DashboardSummary;
+use ::duckcoding::services::dashboard_summary::{dashboard_summary, DashboardSources};
+use ::duckcoding::services::proxy::config::apply_global_proxy;
 use ::duckcoding::services::DashboardManager;
 use anyhow::Result;
 use tauri::State;
@@ -84,3 +90,24 @@ pub async fn set_selected_provider_id(
         .set_selected_provider_id(provider_id)
         .map_err(|e| format!("设置选中供应商失败: {}", e))
 }
+
+/// 获取仪表板汇总（今日用量、运行中的代理、激活的 Profile、供应商健康、最近的配置变更、可用更新）
+///
+/// 结果缓存 10 秒，`force` 为 true 时重新收集
+#[tauri::command]
+pub async fn get_dashboard_summary(
+    force: Option<bool>,
+    proxy_state: State<'_, ProxyManagerState>,
+    profile_state: State<'_, ProfileManagerState>,
+    update_state: State<'_, UpdateServiceState>,
+) -> Result<DashboardSummary, String> {
+    // 更新检查需要访问网络
+    apply_global_proxy().ok();
+
+    let sources = DashboardSources {
+        proxy_manager: &proxy_state.manager,
+        profile_manager: &profile_state.manager,
+        update_service: &update_state.service,
+    };
+    Ok(dashboard_summary(sources, force.unwrap_or(false)).await)
+}
@@ -1,6 +1,8 @@
 // 日志配置管理命令
-// 提供前端查询和更新日志配置的接口
+// 提供前端查询和更新日志配置、管理日志文件的接口
 
+use duckcoding::core::logger::{list_log_files, purge_log_files};
+use duckcoding::core::LogFileInfo;
 use duckcoding::models::config::LogConfig;
 use duckcoding::utils::config::{read_global_config, write_global_config};
 use tauri::command;
@@ -70,3 +72,29 @@ pub async fn update_log_config(new_config: LogConfig) -> Result<String, String>
         Ok("日志配置已保存，需要重启应用后生效".to_string())
     }
 }
+
+/// 当前配置的日志目录（None 表示默认目录 `~/.duckcoding/logs`）
+fn configured_log_path() -> Option<String> {
+    read_global_config()
+        .ok()
+        .flatten()
+        .and_then(|c| c.log_config.file_path)
+}
+
+/// 列出日志文件（当前日志在前，其余历史日志从新到旧）
+#[command]
+pub async fn get_log_files() -> Result<Vec<LogFileInfo>, String> {
+    list_log_files(configured_log_path().as_deref()).map_err(|e| e.to_string())
+}
+
+/// 清理历史日志文件
+///
+/// `older_than_days` 为空时删除全部历史日志；当前正在写入的日志文件不受影响。
+/// 返回删除的文件数。
+#[command]
+pub async fn purge_logs(older_than_days: Option<u32>) -> Result<usize, String> {
+    let removed = purge_log_files(configured_log_path().as_deref(), older_than_days)
+        .map_err(|e| format!("清理日志失败: {}", e))?;
+    tracing::info!(removed, older_than_days = ?older_than_days, "已清理历史日志");
+    Ok(removed)
+}
@@ -28,8 +28,19 @@ fn create_minimal_config() -> GlobalConfig {
         external_poll_interval_ms: 5000,
         single_instance_enabled: true,
         startup_enabled: false,
+        startup_mode: duckcoding::models::config::StartupMode::default(),
         config_watch: duckcoding::models::config::ConfigWatchConfig::default(),
         token_stats_config: duckcoding::models::config::TokenStatsConfig::default(),
+        tool_config_dirs: std::collections::HashMap::new(),
+        observer_mode: false,
+        maintenance: duckcoding::models::config::MaintenanceConfig::default(),
+        metrics: duckcoding::models::config::MetricsConfig::default(),
+        pricing_sync: duckcoding::models::config::PricingSyncConfig::default(),
+        admin_api: duckcoding::models::config::AdminApiConfig::default(),
+        mcp: duckcoding::models::config::McpConfig::default(),
+        balance_monitor: duckcoding::models::config::BalanceMonitorConfig::default(),
+        crash_report: duckcoding::models::config::CrashReportConfig::default(),
+        extra_paths: Vec::new(),
     }
 }
 
@@ -2,7 +2,10 @@
 ///
 /// 提供价格模板的 CRUD 操作和工具默认模板管理
 use duckcoding::models::pricing::PricingTemplate;
-use duckcoding::services::pricing::PRICING_MANAGER;
+use duckcoding::services::pricing::{
+    configured_catalog_url, CatalogSyncReport, CostScenario, CostSimulation,
+    PricingTemplateValidation, PRICING_MANAGER,
+};
 
 use super::error::AppResult;
 
@@ -100,3 +103,69 @@ pub async fn get_default_template(tool_id: String) -> AppResult<PricingTemplate>
     let template = PRICING_MANAGER.get_default_template(&tool_id)?;
     Ok(template)
 }
+
+/// 校验价格模板（别名冲突检测）
+///
+/// # 参数
+///
+/// - `template_id`: 模板 ID（为空时校验所有模板）
+///
+/// # 返回
+///
+/// 每个模板的校验结果，`ambiguous > 0` 表示存在无法计价的别名
+#[tauri::command]
+pub async fn validate_pricing_template(
+    template_id: Option<String>,
+) -> AppResult<Vec<PricingTemplateValidation>> {
+    let results = match template_id {
+        Some(id) => vec![PRICING_MANAGER.validate_saved_template(&id)?],
+        None => PRICING_MANAGER.validate_all_templates()?,
+    };
+    Ok(results)
+}
+
+/// 校验尚未保存的价格模板（缺少缓存价格、循环继承、来源模板缺失、别名冲突等）
+///
+/// # 参数
+///
+/// - `template`: 待校验的模板数据
+#[tauri::command]
+pub async fn validate_pricing_template_draft(
+    template: PricingTemplate,
+) -> AppResult<PricingTemplateValidation> {
+    Ok(PRICING_MANAGER.validate_template(&template))
+}
+
+/// 按假设的 Token 用量试算成本（不写入统计数据）
+///
+/// # 参数
+///
+/// - `template_id`: 模板 ID
+/// - `scenario`: 试算场景（模型、各类 Token 数、请求次数、假设的当月用量）
+#[tauri::command]
+pub async fn simulate_cost(
+    template_id: String,
+    scenario: CostScenario,
+) -> AppResult<CostSimulation> {
+    let simulation = PRICING_MANAGER.simulate_cost(&template_id, &scenario)?;
+    Ok(simulation)
+}
+
+/// 立即从远程价格目录同步模型定价
+///
+/// # 参数
+///
+/// - `url`: 价格目录地址（为空时使用配置的地址）
+///
+/// # 返回
+///
+/// 各内置模板新增 / 更新的模型
+#[tauri::command]
+pub async fn sync_pricing_catalog(url: Option<String>) -> AppResult<CatalogSyncReport> {
+    let url = url
+        .map(|u| u.trim().to_string())
+        .filter(|u| !u.is_empty())
+        .unwrap_or_else(configured_catalog_url);
+    let report = PRICING_MANAGER.sync_remote_catalog(&url).await?;
+    Ok(report)
+}
@@ -1,7 +1,10 @@
 //! Profile 管理 Tauri 命令（v2.1 - 简化版）
 
 use super::error::AppResult;
-use ::duckcoding::services::profile_manager::{AmpProfileSelection, ProfileDescriptor, ProfileRef};
+use ::duckcoding::services::profile_manager::{
+    AmpProfileSelection, ConfigDriftReport, CredentialValidation, NativeConfigSnippet,
+    ProfileDescriptor, ProfileHealth, ProfileHealthMatrix, ProfileRef,
+};
 use serde::Deserialize;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -112,10 +115,25 @@ pub async fn pm_save_profile(
     tool_id: String,
     name: String,
     input: ProfileInput,
+    proxy_state: tauri::State<'_, super::proxy_commands::ProxyManagerState>,
 ) -> AppResult<()> {
+    let pricing_template_id = match &input {
+        ProfileInput::Claude {
+            pricing_template_id,
+            ..
+        }
+        | ProfileInput::Codex {
+            pricing_template_id,
+            ..
+        }
+        | ProfileInput::Gemini {
+            pricing_template_id,
+            ..
+        } => pricing_template_id.clone(),
+    };
     let manager = state.manager.write().await; // 写锁
 
-    match tool_id.as_str() {
+    let result = match tool_id.as_str() {
         "claude-code" => {
             if let ProfileInput::Claude {
                 api_key,
@@ -180,8 +198,49 @@ pub async fn pm_save_profile(
                 })
             }
         }
-        _ => Err(super::error::AppError::ToolNotFound { tool: tool_id }),
+        _ => Err(super::error::AppError::ToolNotFound {
+            tool: tool_id.clone(),
+        }),
+    };
+    drop(manager);
+
+    if result.is_ok() {
+        sync_proxy_pricing_template(&tool_id, &name, pricing_template_id, &proxy_state).await;
+    }
+    result
+}
+
+/// Profile 正被透明代理使用时，同步其绑定的价格模板到代理配置
+async fn sync_proxy_pricing_template(
+    tool_id: &str,
+    profile_name: &str,
+    pricing_template_id: Option<String>,
+    proxy_state: &super::proxy_commands::ProxyManagerState,
+) {
+    let Ok(config_mgr) = ::duckcoding::services::proxy_config_manager::ProxyConfigManager::new()
+    else {
+        return;
+    };
+    let Ok(Some(mut config)) = config_mgr.get_config(tool_id) else {
+        return;
+    };
+    if config.real_profile_name.as_deref() != Some(profile_name)
+        || config.pricing_template_id == pricing_template_id
+    {
+        return;
+    }
+
+    config.pricing_template_id = pricing_template_id;
+    if let Err(e) = config_mgr.update_config(tool_id, config.clone()) {
+        tracing::warn!("同步代理价格模板失败: {} - {}", tool_id, e);
+        return;
+    }
+    if proxy_state.manager.is_running(tool_id).await {
+        if let Err(e) = proxy_state.manager.update_config(tool_id, config).await {
+            tracing::warn!("更新运行中代理的价格模板失败: {} - {}", tool_id, e);
+        }
     }
+    tracing::info!("已同步代理价格模板: {} -> {}", tool_id, profile_name);
 }
 
 /// 删除 Profile
@@ -227,6 +286,74 @@ pub async fn pm_capture_from_native(
     Ok(manager.capture_from_native(&tool_id, &name)?)
 }
 
+/// 导出 Profile 为工具原生格式的配置片段
+#[tauri::command]
+pub async fn pm_export_native_snippet(
+    state: tauri::State<'_, ProfileManagerState>,
+    tool_id: String,
+    name: String,
+    redact_keys: bool,
+) -> AppResult<NativeConfigSnippet> {
+    let manager = state.manager.read().await;
+    Ok(manager.export_native_snippet(&tool_id, &name, redact_keys)?)
+}
+
+/// 检测原生配置是否偏离当前激活的 Profile
+#[tauri::command]
+pub async fn pm_check_config_drift(
+    state: tauri::State<'_, ProfileManagerState>,
+    tool_id: String,
+) -> AppResult<ConfigDriftReport> {
+    let manager = state.manager.read().await;
+    Ok(manager.check_config_drift(&tool_id)?)
+}
+
+/// 重新应用当前激活的 Profile 修复配置漂移
+#[tauri::command]
+pub async fn pm_repair_drift(
+    state: tauri::State<'_, ProfileManagerState>,
+    tool_id: String,
+) -> AppResult<ConfigDriftReport> {
+    let manager = state.manager.write().await;
+    Ok(manager.repair_drift(&tool_id)?)
+}
+
+/// 检查单个 Profile 的上游可达性、延迟与 API Key
+#[tauri::command]
+pub async fn pm_check_profile_health(
+    state: tauri::State<'_, ProfileManagerState>,
+    tool_id: String,
+    profile_name: String,
+) -> AppResult<ProfileHealth> {
+    let manager = state.manager.read().await;
+    Ok(manager
+        .check_profile_health(&tool_id, &profile_name)
+        .await?)
+}
+
+/// 以真实请求验证 Profile 的 API Key，返回模型列表与剩余额度
+#[tauri::command]
+pub async fn validate_profile_credentials(
+    state: tauri::State<'_, ProfileManagerState>,
+    tool_id: String,
+    profile_name: String,
+) -> AppResult<CredentialValidation> {
+    let manager = state.manager.read().await;
+    Ok(manager
+        .validate_profile_credentials(&tool_id, &profile_name)
+        .await?)
+}
+
+/// 并发检查所有 Profile，返回健康矩阵
+#[tauri::command]
+pub async fn pm_check_all_profiles(
+    state: tauri::State<'_, ProfileManagerState>,
+    force: bool,
+) -> AppResult<ProfileHealthMatrix> {
+    let manager = state.manager.read().await;
+    Ok(manager.check_all_profiles(force).await?)
+}
+
 // ==================== AMP Profile Selection ====================
 
 /// AMP Profile 选择输入（前端传递）
@@ -0,0 +1,53 @@
+//! 项目注册命令
+//!
+//! 注册项目时识别技术栈，并按映射规则给出 Profile / 模型建议
+
+use duckcoding::services::project::{
+    detect_stack, ProjectRecord, ProjectRegistry, ProjectStack, StackRule,
+};
+
+fn registry() -> Result<ProjectRegistry, String> {
+    ProjectRegistry::new().map_err(|e| e.to_string())
+}
+
+/// 列出已注册项目
+#[tauri::command]
+pub async fn list_projects() -> Result<Vec<ProjectRecord>, String> {
+    registry()?.list().map_err(|e| e.to_string())
+}
+
+/// 注册项目（同一路径重复注册时刷新识别结果）
+#[tauri::command]
+pub async fn register_project(path: String, name: Option<String>) -> Result<ProjectRecord, String> {
+    registry()?.register(&path, name).map_err(|e| e.to_string())
+}
+
+/// 重新识别项目技术栈并刷新建议
+#[tauri::command]
+pub async fn redetect_project(id: String) -> Result<ProjectRecord, String> {
+    registry()?.redetect(&id).map_err(|e| e.to_string())
+}
+
+/// 删除项目
+#[tauri::command]
+pub async fn remove_project(id: String) -> Result<(), String> {
+    registry()?.remove(&id).map_err(|e| e.to_string())
+}
+
+/// 预览目录的技术栈识别结果（不注册）
+#[tauri::command]
+pub async fn detect_project_stack(path: String) -> Result<ProjectStack, String> {
+    detect_stack(std::path::Path::new(path.trim())).map_err(|e| e.to_string())
+}
+
+/// 获取技术栈映射规则
+#[tauri::command]
+pub async fn get_project_stack_rules() -> Result<Vec<StackRule>, String> {
+    registry()?.rules().map_err(|e| e.to_string())
+}
+
+/// 保存技术栈映射规则
+#[tauri::command]
+pub async fn save_project_stack_rules(rules: Vec<StackRule>) -> Result<(), String> {
+    registry()?.save_rules(&rules).map_err(|e| e.to_string())
+}
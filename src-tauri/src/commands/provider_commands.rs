@@ -3,6 +3,10 @@
 // 供应商管理 Tauri 命令
 
 use ::duckcoding::models::provider::Provider;
+use ::duckcoding::services::provider::onboarding;
+use ::duckcoding::services::provider::{
+    OnboardingReport, OnboardingRequest, ProviderHealth, ProviderHealthMonitor,
+};
 use ::duckcoding::services::ProviderManager;
 use anyhow::Result;
 use tauri::State;
@@ -278,3 +282,24 @@ pub async fn validate_provider_config(provider: Provider) -> Result<ValidationRe
         })
     }
 }
+
+/// 获取工具各上游的健康状况（最近探测结果、24 小时可用率与延迟）
+#[tauri::command]
+pub async fn get_provider_health(tool_id: String) -> Result<Vec<ProviderHealth>, String> {
+    tokio::task::spawn_blocking(move || ProviderHealthMonitor::global().health_report(&tool_id))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// 供应商批量接入：探测上游支持的接口风格，为对应工具创建并验证 Profile
+#[tauri::command]
+pub async fn onboard_provider(
+    state: State<'_, super::profile_commands::ProfileManagerState>,
+    request: OnboardingRequest,
+) -> Result<OnboardingReport, String> {
+    let manager = state.manager.write().await;
+    onboarding::onboard_provider(&manager, &request)
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -6,6 +6,7 @@ use tauri::State;
 
 use crate::commands::profile_commands::ProfileManagerState;
 use ::duckcoding::services::amp_native_config;
+use ::duckcoding::services::proxy::auth_bridge::{self, AuthBridgeStatus};
 use ::duckcoding::services::proxy::ProxyManager;
 use ::duckcoding::services::proxy_config_manager::ProxyConfigManager;
 use ::duckcoding::utils::config::read_global_config;
@@ -22,6 +23,12 @@ pub struct ProxyManagerState {
 pub struct TransparentProxyStatus {
     running: bool,
     port: u16,
+    /// 多上游故障转移状态（仅运行中返回）
+    failover: Option<::duckcoding::services::proxy::failover::FailoverStatus>,
+    /// 上游限流排队状态（仅运行中返回）
+    queue: Option<::duckcoding::services::proxy::upstream_queue::UpstreamQueueStatus>,
+    /// 并发上游请求状态（仅运行中返回）
+    concurrency: Option<::duckcoding::services::proxy::concurrency_limit::ConcurrencyStatus>,
 }
 
 #[derive(serde::Deserialize)]
@@ -62,9 +69,12 @@ pub async fn test_proxy_request(
     // 根据代理配置构建客户端
     let client = if proxy_config.enabled {
         // 构建代理 URL
-        let auth = if let (Some(username), Some(password)) =
-            (&proxy_config.username, &proxy_config.password)
-        {
+        let (scheme, with_credentials) = auth_bridge::proxy_url_scheme(&proxy_config.proxy_type);
+        let auth = if let (true, Some(username), Some(password)) = (
+            with_credentials,
+            &proxy_config.username,
+            &proxy_config.password,
+        ) {
             if !username.is_empty() && !password.is_empty() {
                 format!("{username}:{password}@")
             } else {
@@ -74,12 +84,6 @@ pub async fn test_proxy_request(
             String::new()
         };
 
-        let scheme = match proxy_config.proxy_type.as_str() {
-            "socks5" => "socks5",
-            "https" => "https",
-            _ => "http",
-        };
-
         let proxy_url = format!(
             "{}://{}{}:{}",
             scheme, auth, proxy_config.host, proxy_config.port
@@ -118,11 +122,19 @@ pub async fn test_proxy_request(
         Ok(resp) => {
             let status = resp.status().as_u16();
             let url_ret = resp.url().as_str().to_string();
+            // 407：识别企业代理要求的认证方式，NTLM/Negotiate 提示使用本地认证桥
+            let error =
+                (resp.status() == reqwest::StatusCode::PROXY_AUTHENTICATION_REQUIRED).then(|| {
+                    auth_bridge::auth_required_hint(
+                        &auth_bridge::schemes_from_headers(resp.headers()),
+                        auth_bridge::is_bridge_proxy_type(&proxy_config.proxy_type),
+                    )
+                });
             Ok(TestProxyResult {
                 success: resp.status().is_success(),
                 status,
                 url: Some(url_ret),
-                error: None,
+                error,
             })
         }
         Err(e) => Ok(TestProxyResult {
@@ -134,13 +146,23 @@ pub async fn test_proxy_request(
     }
 }
 
+/// 检测本地认证桥（px/cntlm）是否可用，用于 NTLM/Negotiate 企业代理
+#[tauri::command]
+pub async fn probe_proxy_auth_bridge(
+    host: String,
+    port: u16,
+    test_url: String,
+) -> Result<AuthBridgeStatus, String> {
+    Ok(auth_bridge::probe_auth_bridge(host.trim(), port, &test_url).await)
+}
+
 // ==================== 多工具代理命令（新架构） ====================
 /// 内部实现：尝试启动代理（支持回滚）
 pub(crate) async fn try_start_proxy_internal(
     tool_id: &str,
     manager_state: &ProxyManagerState,
     profile_state: &ProfileManagerState,
-) -> Result<(String, u16), String> {
+) -> Result<(String, String), String> {
     let profile_mgr = profile_state.manager.read().await;
     let proxy_config_mgr = ProxyConfigManager::new().map_err(|e| e.to_string())?;
 
@@ -259,7 +281,7 @@ pub(crate) async fn try_start_proxy_internal(
 
     // ========== 启动代理 ==========
 
-    let proxy_port = tool_config.port;
+    let listen_address = tool_config.listen_address();
 
     manager_state
         .manager
@@ -267,7 +289,7 @@ pub(crate) async fn try_start_proxy_internal(
         .await
         .map_err(|e| format!("启动代理失败: {}", e))?;
 
-    Ok((tool_id.to_string(), proxy_port))
+    Ok((tool_id.to_string(), listen_address))
 }
 
 /// 启动指定工具的透明代理（带事务回滚）
@@ -291,9 +313,9 @@ pub(crate) async fn start_tool_proxy_internal(
 
     // 执行启动操作
     match try_start_proxy_internal(tool_id, manager_state, profile_state).await {
-        Ok((tool_id, proxy_port)) => Ok(format!(
-            "✅ {} 透明代理已启动\n监听端口: {}\n已切换到代理配置",
-            tool_id, proxy_port
+        Ok((tool_id, listen_address)) => Ok(format!(
+            "✅ {} 透明代理已启动\n监听地址: {}\n已切换到代理配置",
+            tool_id, listen_address
         )),
         Err(e) => {
             // 启动失败，开始回滚
@@ -439,29 +461,45 @@ pub async fn get_all_proxy_status(
 
     let mut status_map = HashMap::new();
 
-    for tool_id in &["claude-code", "codex", "gemini-cli", "amp-code"] {
+    use ::duckcoding::models::proxy_config::ToolProxyConfig;
+
+    for tool_id in proxy_store.tool_ids() {
         let port = proxy_store
-            .get_config(tool_id)
+            .get_config(&tool_id)
             .map(|tc| tc.port)
-            .unwrap_or_else(|| match *tool_id {
-                "claude-code" => 8787,
-                "codex" => 8788,
-                "gemini-cli" => 8789,
-                "amp-code" => 8790,
-                _ => 8790,
-            });
+            .unwrap_or_else(|| ToolProxyConfig::default_port(&tool_id));
 
-        let running = manager_state.manager.is_running(tool_id).await;
+        let running = manager_state.manager.is_running(&tool_id).await;
+        let failover = manager_state.manager.failover_status(&tool_id).await;
+        let queue = manager_state.manager.queue_status(&tool_id).await;
+        let concurrency = manager_state.manager.concurrency_status(&tool_id).await;
 
         status_map.insert(
-            tool_id.to_string(),
-            TransparentProxyStatus { running, port },
+            tool_id,
+            TransparentProxyStatus {
+                running,
+                port,
+                failover,
+                queue,
+                concurrency,
+            },
         );
     }
 
     Ok(status_map)
 }
 
+/// 读取 Profile 的上游信息（API Key, Base URL, 价格模板 ID）
+fn resolve_profile_upstream(
+    profile_mgr: &::duckcoding::services::profile_manager::ProfileManager,
+    tool_id: &str,
+    profile_name: &str,
+) -> Result<(String, String, Option<String>), String> {
+    profile_mgr
+        .get_profile_upstream(tool_id, profile_name)
+        .map_err(|e| e.to_string())
+}
+
 /// 从 Profile 更新代理配置（不激活 Profile）
 pub(crate) async fn update_proxy_from_profile_internal(
     tool_id: &str,
@@ -473,39 +511,8 @@ pub(crate) async fn update_proxy_from_profile_internal(
     let proxy_config_mgr = ProxyConfigManager::new().map_err(|e| e.to_string())?;
 
     // 根据工具类型读取 Profile
-    let (api_key, base_url, pricing_template_id) = match tool_id {
-        "claude-code" => {
-            let profile = profile_mgr
-                .get_claude_profile(profile_name)
-                .map_err(|e| e.to_string())?;
-            (
-                profile.api_key,
-                profile.base_url,
-                profile.pricing_template_id,
-            )
-        }
-        "codex" => {
-            let profile = profile_mgr
-                .get_codex_profile(profile_name)
-                .map_err(|e| e.to_string())?;
-            (
-                profile.api_key,
-                profile.base_url,
-                profile.pricing_template_id,
-            )
-        }
-        "gemini-cli" => {
-            let profile = profile_mgr
-                .get_gemini_profile(profile_name)
-                .map_err(|e| e.to_string())?;
-            (
-                profile.api_key,
-                profile.base_url,
-                profile.pricing_template_id,
-            )
-        }
-        _ => return Err(format!("不支持的工具: {}", tool_id)),
-    };
+    let (api_key, base_url, pricing_template_id) =
+        resolve_profile_upstream(&profile_mgr, tool_id, profile_name)?;
 
     // 更新代理配置的 real_* 字段
     let mut proxy_config = proxy_config_mgr
@@ -571,6 +578,27 @@ pub async fn update_proxy_config(
         return Err(format!("{} 代理正在运行，请先停止代理再修改配置", tool_id));
     }
 
+    ::duckcoding::services::proxy::redaction::validate(&config.redaction)
+        .map_err(|e| e.to_string())?;
+    ::duckcoding::services::proxy::egress::validate(&config.egress_proxy)
+        .map_err(|e| e.to_string())?;
+    ::duckcoding::services::proxy::listener::validate(&config).map_err(|e| e.to_string())?;
+
+    // 开启 Profile 自动切换时，备用 Profile 必须存在
+    if config.profile_failover.enabled {
+        let backup = config
+            .profile_failover
+            .backup_profile
+            .as_deref()
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| "开启自动切换时必须指定备用 Profile".to_string())?;
+        if config.profile_failover.error_threshold == 0 {
+            return Err("自动切换的连续错误次数必须大于 0".to_string());
+        }
+        let profile_mgr = profile_state.manager.read().await;
+        resolve_profile_upstream(&profile_mgr, &tool_id, backup)?;
+    }
+
     // ========== 更新配置到全局配置文件 ==========
     let proxy_mgr = ProxyConfigManager::new().map_err(|e| e.to_string())?;
     proxy_mgr
@@ -647,3 +675,323 @@ pub async fn get_all_proxy_configs(
     let proxy_mgr = ProxyConfigManager::new().map_err(|e| e.to_string())?;
     proxy_mgr.get_all_configs().map_err(|e| e.to_string())
 }
+
+/// 获取请求体捕获状态（当前捕获数量与过期时间）
+#[tauri::command]
+pub async fn get_body_capture_status(
+) -> Result<::duckcoding::services::proxy::capture_store::CaptureStatus, String> {
+    ::duckcoding::services::proxy::capture_store::capture_status().map_err(|e| e.to_string())
+}
+
+/// 立即删除请求体捕获（tool_id 为空时删除全部工具），返回删除数量
+#[tauri::command]
+pub async fn clear_body_captures(tool_id: Option<String>) -> Result<usize, String> {
+    ::duckcoding::services::proxy::capture_store::clear_captures(tool_id.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// 获取各工具的响应缓存统计（条目数、命中 / 未命中次数）
+#[tauri::command]
+pub async fn get_proxy_cache_stats(
+) -> Result<Vec<::duckcoding::services::proxy::response_cache::ResponseCacheStats>, String> {
+    Ok(::duckcoding::services::proxy::response_cache::ResponseCache::global().stats())
+}
+
+/// 清空响应缓存（tool_id 为空时清空全部工具），返回删除的条目数
+#[tauri::command]
+pub async fn clear_proxy_cache(tool_id: Option<String>) -> Result<usize, String> {
+    let removed = ::duckcoding::services::proxy::response_cache::ResponseCache::global()
+        .clear(tool_id.as_deref());
+    tracing::info!(tool_id = ?tool_id, removed, "响应缓存已清空");
+    Ok(removed)
+}
+
+/// 列出未过期的请求捕获（最新在前，默认 100 条）
+#[tauri::command]
+pub async fn list_request_logs(
+    tool_id: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<::duckcoding::services::proxy::capture_store::CaptureSummary>, String> {
+    ::duckcoding::services::proxy::capture_store::list_captures(
+        tool_id.as_deref(),
+        limit.unwrap_or(100),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// 获取单条请求捕获（请求头、请求体与响应体）
+#[tauri::command]
+pub async fn get_request_log(
+    id: String,
+) -> Result<::duckcoding::services::proxy::capture_store::CapturedExchange, String> {
+    ::duckcoding::services::proxy::capture_store::get_capture(&id).map_err(|e| e.to_string())
+}
+
+/// 经本地透明代理重放一条请求捕获
+#[tauri::command]
+pub async fn replay_request(
+    id: String,
+) -> Result<::duckcoding::services::proxy::capture_store::ReplayResult, String> {
+    ::duckcoding::services::proxy::capture_store::replay_capture(&id)
+        .await
+        .map_err(|e| format!("{e:#}"))
+}
+
+/// 按计划把请求捕获重放到指定上游，返回延迟与错误分布
+#[tauri::command]
+pub async fn run_replay(
+    plan: ::duckcoding::services::proxy::replay::ReplayPlan,
+) -> Result<::duckcoding::services::proxy::replay::ReplayReport, String> {
+    ::duckcoding::services::proxy::replay::run_replay(plan)
+        .await
+        .map_err(|e| format!("{e:#}"))
+}
+
+/// 开始供应商试用：临时代理 + 项目级配置，到期自动清理
+#[tauri::command]
+pub async fn start_provider_trial(
+    request: ::duckcoding::services::proxy::trial::TrialRequest,
+) -> Result<::duckcoding::services::proxy::trial::TrialSession, String> {
+    ::duckcoding::services::proxy::trial::start_trial(request)
+        .await
+        .map_err(|e| format!("{e:#}"))
+}
+
+/// 列出进行中的供应商试用
+#[tauri::command]
+pub async fn list_provider_trials(
+) -> Result<Vec<::duckcoding::services::proxy::trial::TrialSession>, String> {
+    ::duckcoding::services::proxy::trial::list_trials().map_err(|e| e.to_string())
+}
+
+/// 提前结束供应商试用
+#[tauri::command]
+pub async fn stop_provider_trial(id: String) -> Result<(), String> {
+    ::duckcoding::services::proxy::trial::stop_trial(&id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// ==================== 模型路由规则 ====================
+
+/// 保存模型路由规则的输入（`id` 为空时新建）
+#[derive(serde::Deserialize)]
+pub struct RoutingRuleInput {
+    id: Option<String>,
+    model_pattern: String,
+    profile_name: String,
+    #[serde(default = "default_rule_enabled")]
+    enabled: bool,
+}
+
+fn default_rule_enabled() -> bool {
+    true
+}
+
+/// 持久化代理配置，代理运行中时同步热更新
+async fn persist_proxy_config(
+    tool_id: &str,
+    proxy_config_mgr: &ProxyConfigManager,
+    proxy_config: ::duckcoding::models::proxy_config::ToolProxyConfig,
+    manager_state: &ProxyManagerState,
+) -> Result<(), String> {
+    proxy_config_mgr
+        .update_config(tool_id, proxy_config.clone())
+        .map_err(|e| e.to_string())?;
+
+    if manager_state.manager.is_running(tool_id).await {
+        manager_state
+            .manager
+            .update_config(tool_id, proxy_config)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// 获取指定工具的模型路由规则
+#[tauri::command]
+pub async fn list_routing_rules(
+    tool_id: String,
+) -> Result<Vec<::duckcoding::models::proxy_config::ModelRoutingRule>, String> {
+    let proxy_config_mgr = ProxyConfigManager::new().map_err(|e| e.to_string())?;
+    Ok(proxy_config_mgr
+        .get_config(&tool_id)
+        .map_err(|e| e.to_string())?
+        .map(|config| config.routing_rules)
+        .unwrap_or_default())
+}
+
+/// 新建或更新模型路由规则（上游信息从目标 Profile 解析）
+#[tauri::command]
+pub async fn save_routing_rule(
+    tool_id: String,
+    rule: RoutingRuleInput,
+    manager_state: State<'_, ProxyManagerState>,
+    profile_state: State<'_, ProfileManagerState>,
+) -> Result<::duckcoding::models::proxy_config::ModelRoutingRule, String> {
+    use ::duckcoding::models::proxy_config::{ModelRoutingRule, ToolProxyConfig};
+
+    if tool_id == "amp-code" {
+        return Err("AMP 代理按请求自动分发到各工具，不支持模型路由规则".to_string());
+    }
+    let model_pattern = rule.model_pattern.trim().to_string();
+    if model_pattern.is_empty() {
+        return Err("模型匹配规则不能为空".to_string());
+    }
+
+    let (api_key, base_url, pricing_template_id) = {
+        let profile_mgr = profile_state.manager.read().await;
+        resolve_profile_upstream(&profile_mgr, &tool_id, &rule.profile_name)?
+    };
+
+    let proxy_config_mgr = ProxyConfigManager::new().map_err(|e| e.to_string())?;
+    let mut proxy_config = proxy_config_mgr
+        .get_config(&tool_id)
+        .map_err(|e| e.to_string())?
+        .unwrap_or_else(|| ToolProxyConfig::new(ToolProxyConfig::default_port(&tool_id)));
+
+    let saved = ModelRoutingRule {
+        id: rule
+            .id
+            .filter(|id| !id.is_empty())
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+        model_pattern,
+        profile_name: rule.profile_name,
+        base_url,
+        api_key,
+        pricing_template_id,
+        enabled: rule.enabled,
+    };
+    match proxy_config
+        .routing_rules
+        .iter_mut()
+        .find(|existing| existing.id == saved.id)
+    {
+        Some(existing) => *existing = saved.clone(),
+        None => proxy_config.routing_rules.push(saved.clone()),
+    }
+
+    persist_proxy_config(&tool_id, &proxy_config_mgr, proxy_config, &manager_state).await?;
+    tracing::info!(
+        tool_id = %tool_id,
+        pattern = %saved.model_pattern,
+        profile = %saved.profile_name,
+        "已保存模型路由规则"
+    );
+    Ok(saved)
+}
+
+/// 删除模型路由规则
+#[tauri::command]
+pub async fn delete_routing_rule(
+    tool_id: String,
+    rule_id: String,
+    manager_state: State<'_, ProxyManagerState>,
+) -> Result<(), String> {
+    let proxy_config_mgr = ProxyConfigManager::new().map_err(|e| e.to_string())?;
+    let mut proxy_config = proxy_config_mgr
+        .get_config(&tool_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("工具 {} 没有代理配置", tool_id))?;
+
+    let before = proxy_config.routing_rules.len();
+    proxy_config.routing_rules.retain(|rule| rule.id != rule_id);
+    if proxy_config.routing_rules.len() == before {
+        return Err(format!("路由规则不存在: {}", rule_id));
+    }
+
+    persist_proxy_config(&tool_id, &proxy_config_mgr, proxy_config, &manager_state).await
+}
+
+/// 获取指定工具代理的客户端 Key
+#[tauri::command]
+pub async fn list_proxy_client_keys(
+    tool_id: String,
+) -> Result<Vec<::duckcoding::models::proxy_config::ClientApiKey>, String> {
+    let proxy_config_mgr = ProxyConfigManager::new().map_err(|e| e.to_string())?;
+    Ok(proxy_config_mgr
+        .get_config(&tool_id)
+        .map_err(|e| e.to_string())?
+        .map(|config| config.client_keys)
+        .unwrap_or_default())
+}
+
+/// 为代理生成新的客户端 Key（请求按标签分别统计用量）
+#[tauri::command]
+pub async fn create_proxy_client_key(
+    tool_id: String,
+    label: String,
+    manager_state: State<'_, ProxyManagerState>,
+) -> Result<::duckcoding::models::proxy_config::ClientApiKey, String> {
+    use ::duckcoding::models::proxy_config::{ClientApiKey, ToolProxyConfig};
+
+    let label = label.trim().to_string();
+    if label.is_empty() {
+        return Err("客户端 Key 标签不能为空".to_string());
+    }
+
+    let proxy_config_mgr = ProxyConfigManager::new().map_err(|e| e.to_string())?;
+    let mut proxy_config = proxy_config_mgr
+        .get_config(&tool_id)
+        .map_err(|e| e.to_string())?
+        .unwrap_or_else(|| ToolProxyConfig::new(ToolProxyConfig::default_port(&tool_id)));
+    if proxy_config.client_keys.iter().any(|k| k.label == label) {
+        return Err(format!("客户端 Key 标签已存在: {}", label));
+    }
+
+    let key = ClientApiKey {
+        id: uuid::Uuid::new_v4().to_string(),
+        label,
+        api_key: format!("dc-client-{}", uuid::Uuid::new_v4().simple()),
+        created_at: chrono::Utc::now().timestamp_millis(),
+    };
+    proxy_config.client_keys.push(key.clone());
+
+    persist_proxy_config(&tool_id, &proxy_config_mgr, proxy_config, &manager_state).await?;
+    tracing::info!(tool_id = %tool_id, label = %key.label, "已创建客户端 Key");
+    Ok(key)
+}
+
+/// 吊销客户端 Key（代理运行中时立即生效）
+#[tauri::command]
+pub async fn revoke_proxy_client_key(
+    tool_id: String,
+    key_id: String,
+    manager_state: State<'_, ProxyManagerState>,
+) -> Result<(), String> {
+    let proxy_config_mgr = ProxyConfigManager::new().map_err(|e| e.to_string())?;
+    let mut proxy_config = proxy_config_mgr
+        .get_config(&tool_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("工具 {} 没有代理配置", tool_id))?;
+
+    let before = proxy_config.client_keys.len();
+    proxy_config.client_keys.retain(|k| k.id != key_id);
+    if proxy_config.client_keys.len() == before {
+        return Err(format!("客户端 Key 不存在: {}", key_id));
+    }
+
+    persist_proxy_config(&tool_id, &proxy_config_mgr, proxy_config, &manager_state).await?;
+    tracing::info!(tool_id = %tool_id, key_id = %key_id, "已吊销客户端 Key");
+    Ok(())
+}
+
+// ==================== 成本预检 ====================
+
+/// 等待确认的高成本请求
+#[tauri::command]
+pub async fn list_pending_cost_confirmations(
+) -> Result<Vec<::duckcoding::services::proxy::cost_guard::PendingCostConfirmation>, String> {
+    Ok(::duckcoding::services::proxy::cost_guard::CostGuard::global().list_pending())
+}
+
+/// 确认（继续转发）或拒绝等待中的高成本请求
+#[tauri::command]
+pub async fn confirm_cost_guard_request(id: String, approve: bool) -> Result<(), String> {
+    if !::duckcoding::services::proxy::cost_guard::CostGuard::global().resolve(&id, approve) {
+        return Err(format!("请求不存在或已超时: {}", id));
+    }
+    tracing::info!(id = %id, approve, "已处理成本预检确认");
+    Ok(())
+}
@@ -0,0 +1,58 @@
+//! 异常退出恢复命令
+//!
+//! 前端启动后查询恢复报告，向用户展示上次异常退出后修复了哪些内容；
+//! 并提供「配置指向未运行代理」的检查与手动还原
+
+use tauri::State;
+
+use crate::commands::proxy_commands::ProxyManagerState;
+use duckcoding::services::proxy_config_manager::ProxyConfigManager;
+use duckcoding::services::recovery::{self, RecoveryReport, StaleProxyConfig};
+
+/// 获取启动时的异常退出恢复报告（上次为正常退出时返回 None）
+#[tauri::command]
+pub async fn get_startup_recovery_report() -> Result<Option<RecoveryReport>, String> {
+    Ok(recovery::last_recovery_report())
+}
+
+/// 用户确认后清除恢复报告
+#[tauri::command]
+pub async fn dismiss_startup_recovery_report() -> Result<(), String> {
+    recovery::dismiss_recovery_report();
+    Ok(())
+}
+
+/// 检查工具配置是否指向未运行的代理
+#[tauri::command]
+pub async fn check_proxy_config_consistency(
+    manager_state: State<'_, ProxyManagerState>,
+) -> Result<Vec<StaleProxyConfig>, String> {
+    recovery::find_stale_proxy_configs(&manager_state.manager)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 还原指向未运行代理的工具配置
+///
+/// 代理正在运行时拒绝还原；返回还原说明（无备份可还原时返回 None）
+#[tauri::command]
+pub async fn restore_stale_proxy_config(
+    tool_id: String,
+    manager_state: State<'_, ProxyManagerState>,
+) -> Result<Option<String>, String> {
+    if manager_state.manager.is_running(&tool_id).await {
+        return Err(format!("{} 代理正在运行，无需还原", tool_id));
+    }
+    recovery::restore_tool_config(&tool_id, false).map_err(|e| e.to_string())
+}
+
+/// 设置是否自动还原指向未运行代理的工具配置
+#[tauri::command]
+pub async fn set_auto_restore_stale_config(enabled: bool) -> Result<(), String> {
+    let proxy_mgr = ProxyConfigManager::new().map_err(|e| e.to_string())?;
+    let mut store = proxy_mgr.load_proxy_store().map_err(|e| e.to_string())?;
+    store.auto_restore_stale_config = enabled;
+    proxy_mgr
+        .save_proxy_store(&store)
+        .map_err(|e| e.to_string())
+}
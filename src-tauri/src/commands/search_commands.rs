@@ -0,0 +1,18 @@
+//! 全局搜索命令
+//!
+//! 为命令面板提供统一搜索入口（Profile、会话、设置项、请求日志）
+
+use duckcoding::services::search::{self, SearchQuery, SearchResult};
+
+/// 全局搜索，按相关度降序返回
+#[tauri::command]
+pub async fn global_search(query: SearchQuery) -> Result<Vec<SearchResult>, String> {
+    search::global_search(&query).map_err(|e| e.to_string())
+}
+
+/// 使搜索索引失效（数据变更后前端可主动调用）
+#[tauri::command]
+pub async fn invalidate_search_index() -> Result<(), String> {
+    search::invalidate_search_index();
+    Ok(())
+}
@@ -1,7 +1,12 @@
 // 会话管理 Tauri 命令
 
-use crate::commands::error::AppResult;
+use crate::commands::error::{AppError, AppResult};
+use duckcoding::services::session::context_usage::{
+    self, ContextUsageTracker, SessionContextUsage,
+};
+use duckcoding::services::session::transcript::{self, TranscriptFormat};
 use duckcoding::services::session::{SessionListResponse, SESSION_MANAGER};
+use duckcoding::services::token_stats::TokenStatsManager;
 
 /// 获取会话列表
 #[tauri::command]
@@ -16,13 +21,19 @@ pub async fn get_session_list(
 /// 删除单个会话
 #[tauri::command]
 pub async fn delete_session(session_id: String) -> AppResult<()> {
-    Ok(SESSION_MANAGER.delete_session(&session_id)?)
+    SESSION_MANAGER.delete_session(&session_id)?;
+    transcript::delete_transcript(&session_id)?;
+    ContextUsageTracker::global().forget(&session_id);
+    Ok(())
 }
 
 /// 清空指定工具的所有会话
 #[tauri::command]
 pub async fn clear_all_sessions(tool_id: String) -> AppResult<()> {
-    Ok(SESSION_MANAGER.clear_sessions(&tool_id)?)
+    SESSION_MANAGER.clear_sessions(&tool_id)?;
+    transcript::delete_tool_transcripts(&tool_id)?;
+    ContextUsageTracker::global().forget_tool(&tool_id);
+    Ok(())
 }
 
 /// 更新会话配置
@@ -50,3 +61,47 @@ pub async fn update_session_config(
 pub async fn update_session_note(session_id: String, note: Option<String>) -> AppResult<()> {
     Ok(SESSION_MANAGER.update_session_note(&session_id, note.as_deref())?)
 }
+
+/// 设置会话标签（按项目归集花费），并回填该会话已有日志的标签
+///
+/// 返回规范化后的标签；传入空列表清除标签且不再自动打项目标签
+#[tauri::command]
+pub async fn tag_session(session_id: String, tags: Vec<String>) -> AppResult<Vec<String>> {
+    let session = SESSION_MANAGER
+        .get_session(&session_id)?
+        .ok_or_else(|| AppError::Custom(format!("会话不存在: {}", session_id)))?;
+    let tags = SESSION_MANAGER
+        .update_session_tags(&session_id, &tags)?
+        .unwrap_or_default();
+    TokenStatsManager::get().update_session_tags(&session.tool_id, &session.display_id, &tags)?;
+    Ok(tags)
+}
+
+/// 开启或关闭会话对话记录（关闭后保留已有记录）
+#[tauri::command]
+pub async fn set_session_recording(session_id: String, enabled: bool) -> AppResult<()> {
+    Ok(transcript::set_recording(&session_id, enabled)?)
+}
+
+/// 获取已开启对话记录的会话 ID
+#[tauri::command]
+pub async fn get_recording_sessions() -> AppResult<Vec<String>> {
+    Ok(transcript::recording_sessions())
+}
+
+/// 导出会话对话记录，返回 Markdown 或 JSON 文本
+#[tauri::command]
+pub async fn export_session_transcript(
+    session_id: String,
+    format: TranscriptFormat,
+) -> AppResult<String> {
+    Ok(transcript::export_transcript(&session_id, format)?)
+}
+
+/// 获取会话的上下文窗口占用（会话尚无成功请求时返回 None）
+#[tauri::command]
+pub async fn get_session_context_usage(
+    session_id: String,
+) -> AppResult<Option<SessionContextUsage>> {
+    Ok(context_usage::session_context_usage(&session_id)?)
+}
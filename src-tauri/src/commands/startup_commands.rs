@@ -4,6 +4,7 @@
 //!
 //! 提供前端调用的开机自启动配置管理接口
 
+use duckcoding::models::config::StartupMode;
 use duckcoding::utils::auto_startup::{
     disable_auto_startup, enable_auto_startup, is_auto_startup_enabled,
 };
@@ -44,6 +45,16 @@ pub async fn get_startup_config() -> Result<bool, String> {
 /// - 失败返回 Err(错误信息)
 #[tauri::command]
 pub async fn update_startup_config(enabled: bool) -> Result<(), String> {
+    set_autostart(enabled, None).await
+}
+
+/// 设置开机自启动及自启动时的启动方式
+///
+/// # 参数
+/// - `enabled`: 是否注册开机自启动项（启用时总是重新写入，使旧版本注册的启动项带上启动参数）
+/// - `startup_mode`: 自启动时显示窗口、隐藏到托盘或后台运行（省略时保持不变）
+#[tauri::command]
+pub async fn set_autostart(enabled: bool, startup_mode: Option<StartupMode>) -> Result<(), String> {
     // 根据参数调用系统API
     if enabled {
         enable_auto_startup().map_err(|e| e.to_string())?;
@@ -60,7 +71,11 @@ pub async fn update_startup_config(enabled: bool) -> Result<(), String> {
     };
 
     config.startup_enabled = enabled;
+    if let Some(mode) = startup_mode {
+        config.startup_mode = mode;
+    }
     write_global_config(&config).map_err(|e| e.to_string())?;
+    tracing::info!(enabled = enabled, startup_mode = ?config.startup_mode, "开机自启动配置已更新");
 
     Ok(())
 }
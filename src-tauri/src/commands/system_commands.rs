@@ -0,0 +1,192 @@
+//! 系统健康报告与维护命令
+
+use super::proxy_commands::ProxyManagerState;
+use duckcoding::models::config::{AdminApiConfig, MaintenanceConfig, McpConfig, MetricsConfig};
+use duckcoding::models::ApiHandshake;
+use duckcoding::services::admin_api::{self, AdminApiStatus};
+use duckcoding::services::audit::{self, AuditEntry, AuditVerification};
+use duckcoding::services::diagnostics::{self, DiagnosticsReport};
+use duckcoding::services::environment::{EnvironmentFix, EnvironmentReport, EnvironmentService};
+use duckcoding::services::maintenance::{self, MaintenanceReport, MaintenanceStatus};
+use duckcoding::services::mcp::{self, McpStatus};
+use duckcoding::services::proxy::metrics::{self, MetricsStatus};
+use duckcoding::services::system_health::{collect_system_health, SystemHealthReport};
+use duckcoding::utils::config::{read_global_config, write_global_config};
+use tauri::State;
+
+/// 获取系统健康报告（后台组件运行状态）
+#[tauri::command]
+pub async fn get_system_health() -> Result<SystemHealthReport, String> {
+    Ok(collect_system_health())
+}
+
+/// 执行自诊断（配置、端口、数据库、磁盘、上游、时钟与工具命令）
+#[tauri::command]
+pub async fn run_diagnostics(
+    proxy_state: State<'_, ProxyManagerState>,
+) -> Result<DiagnosticsReport, String> {
+    Ok(diagnostics::run_diagnostics(&proxy_state.manager).await)
+}
+
+/// 环境诊断（PATH、Node.js/npm、代理环境变量）
+#[tauri::command]
+pub async fn inspect_environment() -> Result<EnvironmentReport, String> {
+    Ok(EnvironmentService::new().inspect().await)
+}
+
+/// 应用环境诊断给出的修复，返回是否修改了配置
+#[tauri::command]
+pub async fn apply_environment_fix(fix: EnvironmentFix) -> Result<bool, String> {
+    EnvironmentService::new()
+        .apply_fix(&fix)
+        .map_err(|e| e.to_string())
+}
+
+/// 前后端接口版本握手（前端启动时比对）
+#[tauri::command]
+pub async fn get_api_handshake() -> Result<ApiHandshake, String> {
+    Ok(ApiHandshake::current())
+}
+
+/// 获取夜间维护调度状态
+#[tauri::command]
+pub async fn get_maintenance_status() -> Result<MaintenanceStatus, String> {
+    Ok(maintenance::maintenance_status())
+}
+
+/// 更新夜间维护配置
+#[tauri::command]
+pub async fn update_maintenance_config(config: MaintenanceConfig) -> Result<(), String> {
+    maintenance::validate_config(&config).map_err(|e| e.to_string())?;
+    let mut global_config = read_global_config()
+        .map_err(|e| format!("读取配置失败: {e}"))?
+        .ok_or("配置文件不存在")?;
+    global_config.maintenance = config;
+    write_global_config(&global_config).map_err(|e| format!("保存配置失败: {e}"))?;
+    tracing::info!("夜间维护配置已更新");
+    Ok(())
+}
+
+/// 立即执行一次维护
+///
+/// `force` 为 false 时存在活跃会话会拒绝执行。
+#[tauri::command]
+pub async fn run_maintenance_now(
+    force: bool,
+    proxy_state: State<'_, ProxyManagerState>,
+) -> Result<MaintenanceReport, String> {
+    let config = maintenance::maintenance_status().config;
+    if !force {
+        if let Some(reason) =
+            maintenance::active_session_reason(&proxy_state.manager, config.idle_minutes).await
+        {
+            return Err(reason);
+        }
+    }
+    maintenance::run_maintenance(&proxy_state.manager, &config, force)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取 Prometheus 指标监听状态
+#[tauri::command]
+pub async fn get_metrics_status() -> Result<MetricsStatus, String> {
+    let config = read_global_config()
+        .map_err(|e| format!("读取配置失败: {e}"))?
+        .map(|c| c.metrics)
+        .unwrap_or_default();
+    Ok(metrics::metrics_status(config).await)
+}
+
+/// 更新 Prometheus 指标配置（立即启动、重启或停止监听）
+#[tauri::command]
+pub async fn update_metrics_config(config: MetricsConfig) -> Result<MetricsStatus, String> {
+    let mut global_config = read_global_config()
+        .map_err(|e| format!("读取配置失败: {e}"))?
+        .ok_or("配置文件不存在")?;
+    metrics::apply_config(&config)
+        .await
+        .map_err(|e| e.to_string())?;
+    global_config.metrics = config.clone();
+    write_global_config(&global_config).map_err(|e| format!("保存配置失败: {e}"))?;
+    tracing::info!("Prometheus 指标配置已更新");
+    Ok(metrics::metrics_status(config).await)
+}
+
+/// 获取本机管理 API 状态
+#[tauri::command]
+pub async fn get_admin_api_status() -> Result<AdminApiStatus, String> {
+    let config = read_global_config()
+        .map_err(|e| format!("读取配置失败: {e}"))?
+        .map(|c| c.admin_api)
+        .unwrap_or_default();
+    Ok(admin_api::admin_api_status(config).await)
+}
+
+/// 更新本机管理 API 配置（立即启动、重启或停止监听）
+///
+/// 启用时未提供令牌则自动生成；传入空令牌可重新生成。
+#[tauri::command]
+pub async fn update_admin_api_config(
+    mut config: AdminApiConfig,
+    proxy_state: State<'_, ProxyManagerState>,
+) -> Result<AdminApiStatus, String> {
+    let mut global_config = read_global_config()
+        .map_err(|e| format!("读取配置失败: {e}"))?
+        .ok_or("配置文件不存在")?;
+    if config.enabled && config.token.as_deref().is_none_or(|t| t.trim().is_empty()) {
+        config.token = Some(admin_api::generate_token());
+    }
+    admin_api::apply_config(&config, proxy_state.manager.clone())
+        .await
+        .map_err(|e| e.to_string())?;
+    global_config.admin_api = config.clone();
+    write_global_config(&global_config).map_err(|e| format!("保存配置失败: {e}"))?;
+    tracing::info!("管理 API 配置已更新");
+    Ok(admin_api::admin_api_status(config).await)
+}
+
+/// 获取 MCP 服务状态
+#[tauri::command]
+pub async fn get_mcp_status() -> Result<McpStatus, String> {
+    let config = read_global_config()
+        .map_err(|e| format!("读取配置失败: {e}"))?
+        .map(|c| c.mcp)
+        .unwrap_or_default();
+    Ok(mcp::mcp_status(config).await)
+}
+
+/// 更新 MCP 服务配置（立即启动、重启或停止 HTTP 接入）
+///
+/// 启用 HTTP 接入时未提供令牌则自动生成。
+#[tauri::command]
+pub async fn update_mcp_config(mut config: McpConfig) -> Result<McpStatus, String> {
+    let mut global_config = read_global_config()
+        .map_err(|e| format!("读取配置失败: {e}"))?
+        .ok_or("配置文件不存在")?;
+    if config.http_enabled && config.token.as_deref().is_none_or(|t| t.trim().is_empty()) {
+        config.token = Some(admin_api::generate_token());
+    }
+    mcp::apply_config(&config)
+        .await
+        .map_err(|e| e.to_string())?;
+    global_config.mcp = config.clone();
+    write_global_config(&global_config).map_err(|e| format!("保存配置失败: {e}"))?;
+    tracing::info!("MCP 服务配置已更新");
+    Ok(mcp::mcp_status(config).await)
+}
+
+/// 查询凭证操作审计日志（最新在前，默认最多 200 条）
+#[tauri::command]
+pub async fn get_audit_log(
+    tool_id: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<AuditEntry>, String> {
+    audit::read_entries(tool_id.as_deref(), limit.unwrap_or(200)).map_err(|e| e.to_string())
+}
+
+/// 校验审计日志哈希链是否完整
+#[tauri::command]
+pub async fn verify_audit_log() -> Result<AuditVerification, String> {
+    audit::verify().map_err(|e| e.to_string())
+}
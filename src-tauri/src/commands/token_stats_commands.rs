@@ -1,5 +1,8 @@
 use duckcoding::models::token_stats::{SessionStats, TokenLogsPage, TokenStatsQuery};
-use duckcoding::services::token_stats::TokenStatsManager;
+use duckcoding::services::token_stats::{
+    BudgetStatus, DbMaintenanceReport, LogExportFormat, LogExportSummary, RedactionEventLog,
+    RedactionStats, RedactionStatsQuery, TokenStatsManager,
+};
 
 /// 查询会话实时统计
 #[tauri::command]
@@ -20,6 +23,18 @@ pub async fn query_token_logs(query_params: TokenStatsQuery) -> Result<TokenLogs
         .map_err(|e| e.to_string())
 }
 
+/// 按筛选条件导出Token日志（CSV / JSON Lines，忽略分页参数）
+#[tauri::command]
+pub async fn export_token_logs(
+    query: TokenStatsQuery,
+    format: LogExportFormat,
+    path: String,
+) -> Result<LogExportSummary, String> {
+    TokenStatsManager::get()
+        .export_logs(&query, format, std::path::Path::new(&path))
+        .map_err(|e| e.to_string())
+}
+
 /// 手动清理旧日志
 #[tauri::command]
 pub async fn cleanup_token_logs(
@@ -50,6 +65,38 @@ pub async fn force_token_stats_checkpoint() -> Result<(), String> {
         .map_err(|e| e.to_string())
 }
 
+/// 维护 Token 统计数据库
+///
+/// 按配置的大小上限删除最旧日志，然后执行 VACUUM、重建索引与 ANALYZE，
+/// 返回维护前后的数据库与 WAL 文件大小
+#[tauri::command]
+pub async fn maintain_token_stats_db() -> Result<DbMaintenanceReport, String> {
+    tokio::task::spawn_blocking(|| TokenStatsManager::get().maintain_db())
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// 获取消费预算状态（立即重新检查，可按工具过滤）
+#[tauri::command]
+pub async fn get_budget_status(tool_id: Option<String>) -> Result<Vec<BudgetStatus>, String> {
+    let statuses = TokenStatsManager::get()
+        .check_budgets()
+        .map_err(|e| e.to_string())?;
+    Ok(statuses
+        .into_iter()
+        .filter(|s| tool_id.as_ref().is_none_or(|id| &s.tool_id == id))
+        .collect())
+}
+
+/// 获取请求内容脱敏命中统计
+#[tauri::command]
+pub async fn get_redaction_stats(query: RedactionStatsQuery) -> Result<RedactionStats, String> {
+    RedactionEventLog::open_default()
+        .and_then(|log| log.stats(&query))
+        .map_err(|e| e.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,24 @@
+use crate::commands::error::AppResult;
+use ::duckcoding::services::tool::custom_tools::{custom_tools_report, reload_custom_tools};
+use ::duckcoding::services::tool::CustomToolsReport;
+
+/// 获取已加载的自定义工具（含加载失败的定义文件）
+#[tauri::command]
+pub async fn list_custom_tools() -> AppResult<CustomToolsReport> {
+    Ok(custom_tools_report())
+}
+
+/// 重新读取 `~/.duckcoding/tools.d` 下的自定义工具定义
+///
+/// 重新加载后新建的工具列表、版本检查与代理处理器立即生效；
+/// 配置监听需等下次重启监听（或重启应用）后纳入新工具
+#[tauri::command]
+pub async fn reload_custom_tool_definitions() -> AppResult<CustomToolsReport> {
+    let report = reload_custom_tools();
+    tracing::info!(
+        tools = report.tools.len(),
+        errors = report.errors.len(),
+        "已重新加载自定义工具"
+    );
+    Ok(report)
+}
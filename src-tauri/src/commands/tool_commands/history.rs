@@ -0,0 +1,89 @@
+use crate::commands::error::{AppError, AppResult};
+use crate::commands::tool_management::ToolRegistryState;
+use ::duckcoding::models::Tool;
+use ::duckcoding::services::proxy::config::apply_global_proxy;
+use ::duckcoding::services::tool::{InstallAttempt, InstallHistory, InstallOperation};
+use ::duckcoding::services::InstallerService;
+
+/// 列出安装/更新历史（按时间倒序，可按工具过滤）
+#[tauri::command]
+pub async fn list_install_history(
+    tool_id: Option<String>,
+    limit: Option<usize>,
+) -> AppResult<Vec<InstallAttempt>> {
+    let history = InstallHistory::new()?;
+    Ok(history.list(tool_id.as_deref(), limit.unwrap_or(50))?)
+}
+
+/// 获取单条安装/更新记录（包含完整执行过程）
+#[tauri::command]
+pub async fn get_install_attempt(id: String) -> AppResult<InstallAttempt> {
+    Ok(InstallHistory::new()?.get(&id)?)
+}
+
+/// 原样重新执行历史记录中的安装/更新操作
+///
+/// 重新执行同样会写入一条新记录，返回该记录
+#[tauri::command]
+pub async fn rerun_install_attempt(
+    id: String,
+    registry_state: tauri::State<'_, ToolRegistryState>,
+) -> AppResult<InstallAttempt> {
+    apply_global_proxy().ok();
+
+    let history = InstallHistory::new()?;
+    let attempt = history.get(&id)?;
+    tracing::info!(id = %id, operation = ?attempt.operation, "重新执行安装操作");
+
+    // 操作失败同样会写入历史，失败原因从新记录中查看
+    let outcome = match &attempt.operation {
+        InstallOperation::Install {
+            tool_id,
+            method,
+            force,
+        } => {
+            let tool = Tool::by_id(tool_id).ok_or_else(|| AppError::ToolNotFound {
+                tool: tool_id.clone(),
+            })?;
+            InstallerService::new()
+                .install(&tool, method, *force)
+                .await
+                .map(|_| ())
+        }
+        InstallOperation::InstallVersion { tool_id, version } => {
+            let tool = Tool::by_id(tool_id).ok_or_else(|| AppError::ToolNotFound {
+                tool: tool_id.clone(),
+            })?;
+            InstallerService::new()
+                .install_specific_version(&tool, version)
+                .await
+                .map(|_| ())
+        }
+        InstallOperation::UpdateInstance {
+            instance_id, force, ..
+        } => {
+            let registry = registry_state.registry.lock().await;
+            registry
+                .update_instance(instance_id, *force)
+                .await
+                .map(|_| ())
+        }
+    };
+    if let Err(e) = outcome {
+        tracing::warn!(id = %id, error = ?e, "重新执行安装操作失败");
+    }
+
+    history
+        .list(Some(attempt.operation.tool_id()), 1)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| AppError::Internal {
+            message: "未找到重新执行的记录".to_string(),
+        })
+}
+
+/// 清空安装/更新历史
+#[tauri::command]
+pub async fn clear_install_history() -> AppResult<usize> {
+    Ok(InstallHistory::new()?.clear()?)
+}
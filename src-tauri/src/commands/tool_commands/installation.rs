@@ -1,9 +1,69 @@
 use crate::commands::error::{AppError, AppResult};
 use crate::commands::tool_management::ToolRegistryState;
-use crate::commands::types::{InstallResult, ToolStatus};
+use crate::commands::types::{InstallPrecheck, InstallResult, ToolStatus};
 use ::duckcoding::models::{InstallMethod, Tool};
 use ::duckcoding::services::proxy::config::apply_global_proxy;
-use ::duckcoding::services::InstallerService;
+use ::duckcoding::services::tool::{
+    cancel_install, InstallProgress, ToolStatusCache, INSTALL_PROGRESS_EVENT,
+};
+use ::duckcoding::services::{InstallerService, VersionService};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter};
+
+/// 解析前端传入的安装方法
+fn parse_install_method(method: &str) -> AppResult<InstallMethod> {
+    match method {
+        "npm" => Ok(InstallMethod::Npm),
+        "brew" => Ok(InstallMethod::Brew),
+        "official" => Ok(InstallMethod::Official),
+        _ => Err(AppError::ValidationError {
+            field: "method".to_string(),
+            reason: format!("未知的安装方法: {}", method),
+        }),
+    }
+}
+
+/// 查询当前平台镜像产物状态（镜像站不可用时视为未知，不阻塞安装）
+async fn check_platform_mirror(
+    tool: &Tool,
+    method: &str,
+    install_method: &InstallMethod,
+) -> InstallPrecheck {
+    let artifact = match VersionService::new()
+        .platform_artifact_state(&tool.id, install_method)
+        .await
+    {
+        Ok(artifact) => artifact,
+        Err(e) => {
+            tracing::warn!(tool = %tool.id, error = ?e, "获取镜像产物状态失败");
+            None
+        }
+    };
+    let mirror_is_stale = artifact.as_ref().is_some_and(|a| a.is_stale);
+    let warning = mirror_is_stale.then(|| {
+        let version = artifact
+            .as_ref()
+            .and_then(|a| a.version.as_deref())
+            .unwrap_or("旧版本");
+        format!(
+            "镜像站 {} 的 {}/{} 安装包尚未同步到最新版本，本次将安装 {}",
+            tool.name,
+            std::env::consts::OS,
+            std::env::consts::ARCH,
+            version
+        )
+    });
+
+    InstallPrecheck {
+        tool_id: tool.id.clone(),
+        method: method.to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        artifact,
+        mirror_is_stale,
+        warning,
+    }
+}
 
 /// 检查所有工具的安装状态（新架构：优先从数据库读取）
 ///
@@ -42,11 +102,16 @@ pub async fn refresh_tool_status(
 }
 
 /// 安装指定工具
+///
+/// 安装过程通过 `install-progress` 事件实时推送（阶段、百分比、日志行），
+/// 前端可传入 install_id 以便调用 `cancel_tool_install` 取消（未传入时自动生成）
 #[tauri::command]
 pub async fn install_tool(
     tool: String,
     method: String,
     force: Option<bool>,
+    install_id: Option<String>,
+    app: AppHandle,
 ) -> AppResult<InstallResult> {
     // 应用代理配置（如果已配置）
     apply_global_proxy().ok();
@@ -60,24 +125,31 @@ pub async fn install_tool(
         Tool::by_id(&tool).ok_or_else(|| AppError::ToolNotFound { tool: tool.clone() })?;
 
     // 转换安装方法
-    let install_method = match method.as_str() {
-        "npm" => InstallMethod::Npm,
-        "brew" => InstallMethod::Brew,
-        "official" => InstallMethod::Official,
-        _ => {
-            return Err(AppError::ValidationError {
-                field: "method".to_string(),
-                reason: format!("未知的安装方法: {}", method),
-            })
-        }
-    };
+    let install_method = parse_install_method(&method)?;
 
-    // 使用 InstallerService 安装
+    // 预检当前平台的镜像产物同步状态（滞后时仍安装，但在结果中提示）
+    let precheck = check_platform_mirror(&tool_obj, &method, &install_method).await;
+
+    // 使用 InstallerService 安装，输出实时转发为前端事件
+    let install_id = install_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let progress = InstallProgress::new(
+        &install_id,
+        &tool_obj.id,
+        Arc::new(move |event| {
+            if let Err(e) = app.emit(INSTALL_PROGRESS_EVENT, &event) {
+                tracing::error!(error = ?e, "发送安装进度事件失败");
+            }
+        }),
+    );
     let installer = InstallerService::new();
 
-    match installer.install(&tool_obj, &install_method, force).await {
+    match installer
+        .install_with_progress(&tool_obj, &install_method, force, &progress)
+        .await
+    {
         Ok(_) => {
             // 安装成功（前端会调用 refresh_tool_status 更新数据库）
+            ToolStatusCache::global().invalidate(&tool_obj.id);
 
             // 构造成功消息
             let message = match method.as_str() {
@@ -86,6 +158,10 @@ pub async fn install_tool(
                 "official" => format!("✅ {} 安装成功！", tool_obj.name),
                 _ => format!("✅ {} 安装成功！", tool_obj.name),
             };
+            let message = match precheck.warning {
+                Some(warning) => format!("{}\n⚠️ {}", message, warning),
+                None => message,
+            };
 
             Ok(InstallResult {
                 success: true,
@@ -99,3 +175,23 @@ pub async fn install_tool(
         }
     }
 }
+
+/// 取消进行中的安装
+///
+/// 返回是否找到该安装任务（已结束的任务返回 false）
+#[tauri::command]
+pub async fn cancel_tool_install(install_id: String) -> AppResult<bool> {
+    Ok(cancel_install(&install_id))
+}
+
+/// 安装前预检：查询当前平台对应安装包在镜像站的同步状态
+#[tauri::command]
+pub async fn precheck_tool_install(tool: String, method: String) -> AppResult<InstallPrecheck> {
+    apply_global_proxy().ok();
+
+    let tool_obj =
+        Tool::by_id(&tool).ok_or_else(|| AppError::ToolNotFound { tool: tool.clone() })?;
+    let install_method = parse_install_method(&method)?;
+
+    Ok(check_platform_mirror(&tool_obj, &method, &install_method).await)
+}
@@ -1,4 +1,6 @@
+mod custom;
 mod detection;
+mod history;
 mod installation;
 mod management;
 mod scanner;
@@ -6,7 +8,9 @@ mod update;
 mod validation;
 
 // 重新导出所有命令函数
+pub use custom::*;
 pub use detection::*;
+pub use history::*;
 pub use installation::*;
 pub use management::*;
 pub use scanner::*;
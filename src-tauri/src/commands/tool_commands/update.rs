@@ -1,8 +1,11 @@
 use crate::commands::error::{AppError, AppResult};
 use crate::commands::tool_management::ToolRegistryState;
 use crate::commands::types::{ToolStatus, UpdateResult};
-use ::duckcoding::models::Tool;
+use ::duckcoding::models::{Tool, ToolStatusSnapshot};
 use ::duckcoding::services::proxy::config::apply_global_proxy;
+use ::duckcoding::services::tool::registry::ToolVersionHistory;
+use ::duckcoding::services::tool::version::VersionInfo;
+use ::duckcoding::services::tool::ToolStatusCache;
 use ::duckcoding::services::VersionService;
 
 /// 检查工具更新（不执行更新）
@@ -89,21 +92,56 @@ pub async fn check_all_updates() -> AppResult<Vec<UpdateResult>> {
     let version_service = VersionService::new();
     let version_infos = version_service.check_all_tools().await;
 
-    let results = version_infos
-        .into_iter()
-        .map(|info| UpdateResult {
-            success: true,
-            message: "检查完成".to_string(),
-            has_update: info.has_update,
-            current_version: info.installed_version,
-            latest_version: info.latest_version,
-            mirror_version: info.mirror_version,
-            mirror_is_stale: Some(info.mirror_is_stale),
-            tool_id: Some(info.tool_id),
-        })
-        .collect();
-
-    Ok(results)
+    Ok(version_infos.into_iter().map(to_update_result).collect())
+}
+
+/// 一次性刷新所有工具状态（安装状态 + 版本检查）
+///
+/// 工作流程：
+/// 1. 并行获取本地工具状态（数据库）与版本检查结果
+/// 2. 版本检查优先使用 ToolStatusCache 中未过期的结果，force 时忽略缓存
+///
+/// 返回：工具状态快照
+#[tauri::command]
+pub async fn refresh_all_tool_status(
+    force: Option<bool>,
+    registry_state: tauri::State<'_, ToolRegistryState>,
+) -> AppResult<ToolStatusSnapshot> {
+    apply_global_proxy().ok();
+
+    let tools = Tool::all();
+    let cache = ToolStatusCache::global();
+    if force.unwrap_or(false) {
+        cache.clear();
+    }
+    let from_cache = tools.iter().all(|tool| cache.get(&tool.id).is_some());
+
+    let version_service = VersionService::new();
+    let registry = registry_state.registry.lock().await;
+    let (statuses, version_infos) = tokio::join!(
+        registry.get_local_tool_status(),
+        version_service.check_all(&tools)
+    );
+
+    Ok(ToolStatusSnapshot {
+        tools: statuses?,
+        updates: version_infos.into_iter().map(to_update_result).collect(),
+        from_cache,
+        checked_at: chrono::Utc::now().timestamp(),
+    })
+}
+
+fn to_update_result(info: VersionInfo) -> UpdateResult {
+    UpdateResult {
+        success: true,
+        message: "检查完成".to_string(),
+        has_update: info.has_update,
+        current_version: info.installed_version,
+        latest_version: info.latest_version,
+        mirror_version: info.mirror_version,
+        mirror_is_stale: Some(info.mirror_is_stale),
+        tool_id: Some(info.tool_id),
+    }
 }
 
 /// 更新工具实例（使用配置的安装器路径）
@@ -126,3 +164,59 @@ pub async fn update_tool_instance(
         .update_instance(&instance_id, force.unwrap_or(false))
         .await?)
 }
+
+/// 通过 npm 安装工具的指定版本
+///
+/// pin 为 true 时同时固定该版本（固定后普通更新会被拒绝）
+///
+/// 返回：安装后检测到的版本
+#[tauri::command]
+pub async fn install_specific_version(
+    tool: String,
+    version: String,
+    pin: Option<bool>,
+    registry_state: tauri::State<'_, ToolRegistryState>,
+) -> AppResult<String> {
+    apply_global_proxy().ok();
+
+    let registry = registry_state.registry.lock().await;
+    Ok(registry
+        .install_specific_version(&tool, &version, pin.unwrap_or(false))
+        .await?)
+}
+
+/// 回滚工具到指定版本（通过 npm 安装该版本并固定）
+///
+/// 返回：回滚后检测到的版本
+#[tauri::command]
+pub async fn rollback_tool(
+    tool: String,
+    version: String,
+    registry_state: tauri::State<'_, ToolRegistryState>,
+) -> AppResult<String> {
+    apply_global_proxy().ok();
+
+    let registry = registry_state.registry.lock().await;
+    Ok(registry.rollback_tool(&tool, &version).await?)
+}
+
+/// 获取工具的已安装版本历史与固定版本
+#[tauri::command]
+pub async fn get_tool_version_history(
+    tool: String,
+    registry_state: tauri::State<'_, ToolRegistryState>,
+) -> AppResult<ToolVersionHistory> {
+    let registry = registry_state.registry.lock().await;
+    Ok(registry.get_version_history(&tool).await?)
+}
+
+/// 固定工具版本（version 为空时取消固定）
+#[tauri::command]
+pub async fn set_tool_version_pin(
+    tool: String,
+    version: Option<String>,
+    registry_state: tauri::State<'_, ToolRegistryState>,
+) -> AppResult<()> {
+    let registry = registry_state.registry.lock().await;
+    Ok(registry.set_version_pin(&tool, version).await?)
+}
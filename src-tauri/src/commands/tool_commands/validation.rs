@@ -1,56 +1,28 @@
 use crate::commands::error::AppResult;
 use crate::commands::tool_management::ToolRegistryState;
 use crate::commands::types::NodeEnvironment;
-use ::duckcoding::utils::platform::PlatformInfo;
-use std::process::Command;
-
-#[cfg(target_os = "windows")]
-use std::os::windows::process::CommandExt;
+use ::duckcoding::services::tool::user_prefix::{
+    ensure_user_path, user_prefix_path_status, UserPrefixPathStatus,
+};
+use ::duckcoding::utils::CommandExecutor;
 
 /// 检测 Node.js 和 npm 环境
 #[tauri::command]
 pub async fn check_node_environment() -> AppResult<NodeEnvironment> {
-    let enhanced_path = PlatformInfo::current().build_enhanced_path();
-    let run_command = |cmd: &str| -> Result<std::process::Output, std::io::Error> {
-        #[cfg(target_os = "windows")]
-        {
-            Command::new("cmd")
-                .env("PATH", &enhanced_path)
-                .arg("/C")
-                .arg(cmd)
-                .creation_flags(0x08000000) // CREATE_NO_WINDOW - 隐藏终端窗口
-                .output()
-        }
-        #[cfg(not(target_os = "windows"))]
-        {
-            Command::new("sh")
-                .env("PATH", &enhanced_path)
-                .arg("-c")
-                .arg(cmd)
-                .output()
-        }
-    };
+    let executor = CommandExecutor::new();
 
     // 检测node
-    let (node_available, node_version) = if let Ok(output) = run_command("node --version 2>&1") {
-        if output.status.success() {
-            let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            (true, Some(version))
-        } else {
-            (false, None)
-        }
+    let node = executor.execute_async("node --version 2>&1").await;
+    let (node_available, node_version) = if node.success {
+        (true, Some(node.stdout))
     } else {
         (false, None)
     };
 
     // 检测npm
-    let (npm_available, npm_version) = if let Ok(output) = run_command("npm --version 2>&1") {
-        if output.status.success() {
-            let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            (true, Some(version))
-        } else {
-            (false, None)
-        }
+    let npm = executor.execute_async("npm --version 2>&1").await;
+    let (npm_available, npm_version) = if npm.success {
+        (true, Some(npm.stdout))
     } else {
         (false, None)
     };
@@ -79,3 +51,16 @@ pub async fn validate_tool_path(
     let registry = registry_state.registry.lock().await;
     Ok(registry.validate_tool_path(&path).await?)
 }
+
+/// 检查用户级安装目录（非管理员回退安装位置）是否在 PATH 中
+#[tauri::command]
+pub async fn check_user_install_path() -> AppResult<UserPrefixPathStatus> {
+    Ok(user_prefix_path_status())
+}
+
+/// 将用户级安装目录加入用户 PATH（仅 Windows 自动修改，其他平台返回提示）
+#[tauri::command]
+pub async fn fix_user_install_path() -> AppResult<UserPrefixPathStatus> {
+    ensure_user_path()?;
+    Ok(user_prefix_path_status())
+}
@@ -1,5 +1,5 @@
 use duckcoding::models::{SSHConfig, ToolInstance};
-use duckcoding::services::tool::ToolRegistry;
+use duckcoding::services::tool::{ssh_tunnel, SshTunnelInfo, ToolRegistry};
 use duckcoding::utils::WSLExecutor;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -57,7 +57,36 @@ pub async fn add_wsl_tool_instance(
         .map_err(|e| format!("添加WSL实例失败: {}", e))
 }
 
-/// 添加SSH工具实例（本期仅存储配置）
+/// 在WSL发行版中安装工具（npm），并登记为WSL实例
+#[tauri::command]
+pub async fn install_wsl_tool_instance(
+    state: tauri::State<'_, ToolRegistryState>,
+    base_id: String,
+    distro_name: String,
+) -> Result<ToolInstance, String> {
+    let registry = state.registry.lock().await;
+    registry
+        .install_wsl_instance(&base_id, &distro_name)
+        .await
+        .map_err(|e| format!("在WSL中安装工具失败: {}", e))
+}
+
+/// 将 Profile 应用到WSL实例的配置文件，返回写入的配置目录
+#[tauri::command]
+pub async fn apply_profile_to_wsl_instance(
+    state: tauri::State<'_, ToolRegistryState>,
+    instance_id: String,
+    profile_name: String,
+) -> Result<String, String> {
+    let registry = state.registry.lock().await;
+    registry
+        .apply_profile_to_wsl_instance(&instance_id, &profile_name)
+        .await
+        .map(|dir| dir.display().to_string())
+        .map_err(|e| format!("应用 Profile 到WSL实例失败: {}", e))
+}
+
+/// 添加SSH工具实例（连接远程主机检测工具，不可达时仅保存配置）
 #[tauri::command]
 pub async fn add_ssh_tool_instance(
     state: tauri::State<'_, ToolRegistryState>,
@@ -71,6 +100,72 @@ pub async fn add_ssh_tool_instance(
         .map_err(|e| format!("添加SSH实例失败: {}", e))
 }
 
+/// 重新检测SSH实例（安装状态、路径、版本）
+#[tauri::command]
+pub async fn refresh_ssh_tool_instance(
+    state: tauri::State<'_, ToolRegistryState>,
+    instance_id: String,
+) -> Result<ToolInstance, String> {
+    let registry = state.registry.lock().await;
+    registry
+        .refresh_ssh_instance(&instance_id)
+        .await
+        .map_err(|e| format!("检测SSH实例失败: {}", e))
+}
+
+/// 在远程主机上安装工具（npm）
+#[tauri::command]
+pub async fn install_ssh_tool_instance(
+    state: tauri::State<'_, ToolRegistryState>,
+    instance_id: String,
+) -> Result<ToolInstance, String> {
+    let registry = state.registry.lock().await;
+    registry
+        .install_ssh_instance(&instance_id)
+        .await
+        .map_err(|e| format!("远程安装工具失败: {}", e))
+}
+
+/// 将 Profile 应用到SSH实例的远程配置文件，返回远程配置目录
+#[tauri::command]
+pub async fn apply_profile_to_ssh_instance(
+    state: tauri::State<'_, ToolRegistryState>,
+    instance_id: String,
+    profile_name: String,
+) -> Result<String, String> {
+    let registry = state.registry.lock().await;
+    registry
+        .apply_profile_to_ssh_instance(&instance_id, &profile_name)
+        .await
+        .map_err(|e| format!("应用 Profile 到SSH实例失败: {}", e))
+}
+
+/// 建立SSH反向隧道，让远程工具经本机透明代理转发
+#[tauri::command]
+pub async fn start_ssh_proxy_tunnel(
+    state: tauri::State<'_, ToolRegistryState>,
+    instance_id: String,
+    remote_port: Option<u16>,
+) -> Result<SshTunnelInfo, String> {
+    let registry = state.registry.lock().await;
+    registry
+        .start_ssh_proxy_tunnel(&instance_id, remote_port)
+        .await
+        .map_err(|e| format!("建立SSH隧道失败: {}", e))
+}
+
+/// 关闭SSH反向隧道（隧道不存在时返回 false）
+#[tauri::command]
+pub async fn stop_ssh_proxy_tunnel(instance_id: String) -> Result<bool, String> {
+    Ok(ssh_tunnel::stop_tunnel(&instance_id))
+}
+
+/// 列出运行中的SSH反向隧道
+#[tauri::command]
+pub async fn list_ssh_proxy_tunnels() -> Result<Vec<SshTunnelInfo>, String> {
+    Ok(ssh_tunnel::list_tunnels())
+}
+
 /// 删除工具实例（仅SSH类型）
 #[tauri::command]
 pub async fn delete_tool_instance(
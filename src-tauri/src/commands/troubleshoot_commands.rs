@@ -0,0 +1,27 @@
+//! 引导式故障排查命令
+
+use super::proxy_commands::ProxyManagerState;
+use duckcoding::services::troubleshoot::{
+    self, FlowContext, TroubleshootFlow, TroubleshootFlowInfo, TroubleshootReport,
+};
+
+/// 列出可用的诊断流程
+#[tauri::command]
+pub fn list_troubleshoot_flows() -> Vec<TroubleshootFlowInfo> {
+    troubleshoot::list_flows()
+}
+
+/// 对指定工具执行诊断流程
+#[tauri::command]
+pub async fn run_troubleshoot_flow(
+    flow: TroubleshootFlow,
+    tool_id: String,
+    proxy_state: tauri::State<'_, ProxyManagerState>,
+) -> Result<TroubleshootReport, String> {
+    let ctx = FlowContext {
+        proxy_manager: &proxy_state.manager,
+    };
+    troubleshoot::run_flow(flow, &tool_id, &ctx)
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -2,6 +2,7 @@
 
 // 重新导出 models 层的类型
 pub use duckcoding::models::{ToolStatus, UpdateResult};
+pub use duckcoding::services::tool::MirrorArtifactState;
 
 /// Node 环境信息
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -19,3 +20,17 @@ pub struct InstallResult {
     pub message: String,
     pub output: String,
 }
+
+/// 安装预检结果（当前平台的镜像产物同步状态）
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct InstallPrecheck {
+    pub tool_id: String,
+    pub method: String,
+    pub os: String,
+    pub arch: String,
+    /// 当前平台对应的镜像产物（镜像站未提供分平台状态时为 None）
+    pub artifact: Option<MirrorArtifactState>,
+    pub mirror_is_stale: bool,
+    /// 需要提示用户的警告
+    pub warning: Option<String>,
+}
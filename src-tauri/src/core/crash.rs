@@ -0,0 +1,409 @@
+//! 崩溃与后台任务错误报告
+//!
+//! - panic hook：记录 panic 信息与调用栈，再交给原有 hook 处理
+//! - 后台任务错误：`report_task_error` 记录后台任务中无法向上传递的错误
+//!
+//! 报告以 JSON 保存在 `~/.duckcoding/crashes/`，包含应用版本、系统信息与最近的日志片段，
+//! 写入前对密钥、邮箱与用户目录脱敏。报告不会自动上传，仅在开启上传后由用户逐个确认发送。
+
+use super::http::get_global_client;
+use super::log_rotation::ACTIVE_LOG_FILE;
+use super::logger::get_log_dir;
+use crate::models::config::{LogConfig, LogOutput};
+use crate::services::proxy::redaction::redact_builtin_text;
+use crate::utils::config::{config_dir, read_global_config};
+use anyhow::{anyhow, bail, Context, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::backtrace::Backtrace;
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// 报告目录名（位于配置目录下）
+const CRASH_DIR: &str = "crashes";
+/// 本地最多保留的报告数
+const MAX_REPORTS: usize = 50;
+/// 附带的日志行数
+const LOG_TAIL_LINES: usize = 100;
+/// 读取日志末尾的字节数上限
+const LOG_TAIL_BYTES: u64 = 64 * 1024;
+/// 发送报告的超时时间
+const SEND_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// 日志目录（安装 panic hook 时确定；仅控制台输出时为 None）
+static LOG_DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+/// 内置脱敏规则未覆盖的认证头与密钥赋值，替换为 `${1}[REDACTED]`
+static SECRET_PATTERNS: Lazy<Vec<Regex>> = Lazy::new(|| {
+    [
+        r"(?i)(bearer\s+)[A-Za-z0-9._~+/\-]{8,}=*",
+        r#"(?i)((?:api[_-]?key|token|secret|password|authorization)["']?\s*[:=]\s*["']?)[^\s"',;}]{4,}"#,
+        r"(dc-client-)[0-9a-f]{32}",
+    ]
+    .iter()
+    .map(|pattern| Regex::new(pattern).unwrap())
+    .collect()
+});
+
+/// 报告类型
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CrashKind {
+    Panic,
+    TaskError,
+}
+
+/// 崩溃报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReport {
+    pub id: String,
+    pub kind: CrashKind,
+    /// panic 所在线程名 / 出错的后台任务名
+    pub source: String,
+    pub message: String,
+    /// panic 位置（`文件:行:列`）
+    pub location: Option<String>,
+    /// 调用栈（后台任务错误为错误链）
+    pub backtrace: String,
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    /// 最近的日志
+    pub log_tail: Vec<String>,
+    /// 创建时间（Unix 时间戳，毫秒）
+    pub created_at: i64,
+    /// 发送时间（未发送时为 None）
+    #[serde(default)]
+    pub sent_at: Option<i64>,
+}
+
+/// 报告列表项（不含调用栈与日志）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReportSummary {
+    pub id: String,
+    pub kind: CrashKind,
+    pub source: String,
+    pub message: String,
+    pub app_version: String,
+    pub created_at: i64,
+    pub sent_at: Option<i64>,
+}
+
+impl From<&CrashReport> for CrashReportSummary {
+    fn from(report: &CrashReport) -> Self {
+        Self {
+            id: report.id.clone(),
+            kind: report.kind,
+            source: report.source.clone(),
+            message: report.message.clone(),
+            app_version: report.app_version.clone(),
+            created_at: report.created_at,
+            sent_at: report.sent_at,
+        }
+    }
+}
+
+impl CrashReport {
+    fn new(
+        kind: CrashKind,
+        source: String,
+        message: &str,
+        location: Option<String>,
+        backtrace: &str,
+        log_tail: Vec<String>,
+    ) -> Self {
+        let now = chrono::Local::now();
+        let suffix = uuid::Uuid::new_v4().simple().to_string();
+        Self {
+            id: format!("{}-{}", now.format("%Y%m%d-%H%M%S"), &suffix[..8]),
+            kind,
+            source,
+            message: scrub(message),
+            location,
+            backtrace: scrub(backtrace),
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            log_tail: log_tail.iter().map(|line| scrub(line)).collect(),
+            created_at: now.timestamp_millis(),
+            sent_at: None,
+        }
+    }
+}
+
+/// 安装 panic hook（重复调用无效）
+///
+/// 在 `init_logger` 之后调用，以便报告附带当前日志文件的末尾内容。
+pub fn install_panic_hook(log_config: &LogConfig) {
+    let log_dir = (log_config.output != LogOutput::Console)
+        .then(|| get_log_dir(log_config.file_path.as_deref()).ok())
+        .flatten();
+    if LOG_DIR.set(log_dir).is_err() {
+        return;
+    }
+
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let message = panic_message(info.payload());
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()));
+        let thread = std::thread::current()
+            .name()
+            .unwrap_or("unnamed")
+            .to_string();
+        let backtrace = Backtrace::force_capture().to_string();
+        let report = CrashReport::new(
+            CrashKind::Panic,
+            thread,
+            &message,
+            location,
+            &backtrace,
+            read_log_tail(),
+        );
+        // panic 过程中不再经过 tracing，直接输出到 stderr
+        match save_report(&report) {
+            Ok(path) => eprintln!("崩溃报告已保存: {}", path.display()),
+            Err(e) => eprintln!("保存崩溃报告失败: {}", e),
+        }
+        previous(info);
+    }));
+}
+
+/// 记录后台任务中无法向上传递的错误
+pub fn report_task_error(source: &str, error: &anyhow::Error) {
+    let report = CrashReport::new(
+        CrashKind::TaskError,
+        source.to_string(),
+        &format!("{:#}", error),
+        None,
+        &format!("{:?}", error),
+        read_log_tail(),
+    );
+    match save_report(&report) {
+        Ok(_) => tracing::error!(
+            source = %source,
+            report_id = %report.id,
+            error = ?error,
+            "后台任务出错，已生成崩溃报告"
+        ),
+        Err(e) => tracing::warn!(source = %source, error = %e, "保存崩溃报告失败"),
+    }
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "未知 panic".to_string())
+}
+
+/// 脱敏：内置密钥规则、认证头与密钥赋值、用户主目录
+fn scrub(text: &str) -> String {
+    let mut text = redact_builtin_text(text);
+    for regex in SECRET_PATTERNS.iter() {
+        text = regex.replace_all(&text, "${1}[REDACTED]").into_owned();
+    }
+    if let Some(home) = dirs::home_dir() {
+        let home = home.to_string_lossy();
+        if home.len() > 1 {
+            text = text.replace(home.as_ref(), "~");
+        }
+    }
+    text
+}
+
+/// 读取当前日志文件末尾的若干行
+fn read_log_tail() -> Vec<String> {
+    let Some(Some(dir)) = LOG_DIR.get() else {
+        return Vec::new();
+    };
+    tail_lines(&dir.join(ACTIVE_LOG_FILE), LOG_TAIL_LINES).unwrap_or_default()
+}
+
+fn tail_lines(path: &Path, max_lines: usize) -> std::io::Result<Vec<String>> {
+    let mut file = fs::File::open(path)?;
+    let len = file.metadata()?.len();
+    let start = len.saturating_sub(LOG_TAIL_BYTES);
+    file.seek(SeekFrom::Start(start))?;
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf)?;
+
+    let text = String::from_utf8_lossy(&buf);
+    let mut lines: Vec<&str> = text.lines().collect();
+    // 从文件中间开始读取时，第一行可能不完整
+    if start > 0 && !lines.is_empty() {
+        lines.remove(0);
+    }
+    let skip = lines.len().saturating_sub(max_lines);
+    Ok(lines[skip..].iter().map(|line| line.to_string()).collect())
+}
+
+fn crash_dir() -> Result<PathBuf> {
+    Ok(config_dir().map_err(|e| anyhow!(e))?.join(CRASH_DIR))
+}
+
+fn save_report(report: &CrashReport) -> Result<PathBuf> {
+    save_report_in(&crash_dir()?, report)
+}
+
+fn save_report_in(dir: &Path, report: &CrashReport) -> Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!("{}.json", report.id));
+    fs::write(&path, serde_json::to_vec_pretty(report)?)?;
+    prune_reports_in(dir, MAX_REPORTS)?;
+    Ok(path)
+}
+
+/// 按创建时间从新到旧读取全部报告（无法解析的文件跳过）
+fn load_reports_in(dir: &Path) -> Result<Vec<CrashReport>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut reports: Vec<CrashReport> = fs::read_dir(dir)?
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|entry| serde_json::from_slice(&fs::read(entry.path()).ok()?).ok())
+        .collect();
+    reports.sort_by(|a, b| b.created_at.cmp(&a.created_at).then(b.id.cmp(&a.id)));
+    Ok(reports)
+}
+
+fn prune_reports_in(dir: &Path, keep: usize) -> Result<()> {
+    for report in load_reports_in(dir)?.into_iter().skip(keep) {
+        let _ = fs::remove_file(dir.join(format!("{}.json", report.id)));
+    }
+    Ok(())
+}
+
+/// 报告文件路径（拒绝包含路径分隔符等字符的 ID）
+fn report_path_in(dir: &Path, id: &str) -> Result<PathBuf> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        bail!("无效的崩溃报告 ID: {}", id);
+    }
+    Ok(dir.join(format!("{id}.json")))
+}
+
+/// 列出崩溃报告（从新到旧）
+pub fn list_crash_reports() -> Result<Vec<CrashReportSummary>> {
+    Ok(load_reports_in(&crash_dir()?)?
+        .iter()
+        .map(CrashReportSummary::from)
+        .collect())
+}
+
+/// 读取单个崩溃报告
+pub fn get_crash_report(id: &str) -> Result<CrashReport> {
+    let path = report_path_in(&crash_dir()?, id)?;
+    let content = fs::read(&path).with_context(|| format!("崩溃报告不存在: {}", id))?;
+    Ok(serde_json::from_slice(&content)?)
+}
+
+/// 删除单个崩溃报告
+pub fn delete_crash_report(id: &str) -> Result<()> {
+    let path = report_path_in(&crash_dir()?, id)?;
+    fs::remove_file(&path).with_context(|| format!("删除崩溃报告失败: {}", id))
+}
+
+/// 发送崩溃报告（需在设置中开启上传并配置接收地址），成功后记录发送时间
+pub async fn send_crash_report(id: &str) -> Result<CrashReport> {
+    let config = read_global_config()
+        .ok()
+        .flatten()
+        .map(|c| c.crash_report)
+        .unwrap_or_default();
+    if !config.send_enabled {
+        bail!("未开启崩溃报告上传");
+    }
+    let endpoint = config
+        .endpoint
+        .filter(|e| !e.trim().is_empty())
+        .ok_or_else(|| anyhow!("未配置崩溃报告接收地址"))?;
+
+    let mut report = get_crash_report(id)?;
+    let response = get_global_client()?
+        .post(endpoint.trim())
+        .timeout(SEND_TIMEOUT)
+        .json(&report)
+        .send()
+        .await
+        .context("发送崩溃报告失败")?;
+    if !response.status().is_success() {
+        bail!("发送崩溃报告失败: 状态码 {}", response.status());
+    }
+
+    report.sent_at = Some(chrono::Utc::now().timestamp_millis());
+    fs::write(
+        report_path_in(&crash_dir()?, id)?,
+        serde_json::to_vec_pretty(&report)?,
+    )?;
+    tracing::info!(report_id = %id, "崩溃报告已发送");
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(message: &str) -> CrashReport {
+        CrashReport::new(
+            CrashKind::TaskError,
+            "test".to_string(),
+            message,
+            None,
+            "",
+            Vec::new(),
+        )
+    }
+
+    #[test]
+    fn test_scrub() {
+        let text = scrub(
+            "Authorization: Bearer abcdefgh12345678 api_key=\"secret-value\" dc-client-0123456789abcdef0123456789abcdef",
+        );
+        assert!(!text.contains("abcdefgh12345678"));
+        assert!(!text.contains("secret-value"));
+        assert!(!text.contains("0123456789abcdef"));
+        assert!(text.contains("[REDACTED]"));
+
+        let key = format!("sk-ant-{}", "a".repeat(30));
+        assert!(!scrub(&format!("upstream rejected {key}")).contains(&key));
+    }
+
+    #[test]
+    fn test_save_list_and_prune_reports() {
+        let dir = tempfile::tempdir().unwrap();
+        for i in 0..4 {
+            let mut report = report(&format!("error {i}"));
+            report.created_at += i;
+            save_report_in(dir.path(), &report).unwrap();
+        }
+        prune_reports_in(dir.path(), 2).unwrap();
+
+        let reports = load_reports_in(dir.path()).unwrap();
+        let messages: Vec<&str> = reports.iter().map(|r| r.message.as_str()).collect();
+        assert_eq!(messages, vec!["error 3", "error 2"]);
+    }
+
+    #[test]
+    fn test_report_path_rejects_traversal() {
+        let dir = Path::new("/tmp/crashes");
+        assert!(report_path_in(dir, "20261016-120000-abcd1234").is_ok());
+        assert!(report_path_in(dir, "../config").is_err());
+        assert!(report_path_in(dir, "").is_err());
+    }
+
+    #[test]
+    fn test_tail_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(ACTIVE_LOG_FILE);
+        let content: String = (0..10).map(|i| format!("line {i}\n")).collect();
+        fs::write(&path, content).unwrap();
+        assert_eq!(tail_lines(&path, 2).unwrap(), vec!["line 8", "line 9"]);
+    }
+}
@@ -1,5 +1,6 @@
 use crate::core::error::{AppError, AppResult};
 use crate::models::GlobalConfig;
+use crate::services::proxy::auth_bridge;
 use reqwest::Client;
 
 const USER_AGENT: &str = concat!("DuckCoding/", env!("CARGO_PKG_VERSION"));
@@ -63,10 +64,15 @@ fn build_proxy_url(config: &GlobalConfig) -> AppResult<String> {
             reason: "代理端口未设置".to_string(),
         })?;
 
+    // NTLM/Negotiate 经本地认证桥，凭据由桥接程序处理
+    let (scheme, with_credentials) = auth_bridge::proxy_url_scheme(proxy_type);
+
     // 构建认证部分
-    let auth = if let (Some(username), Some(password)) =
-        (&config.proxy_username, &config.proxy_password)
-    {
+    let auth = if let (true, Some(username), Some(password)) = (
+        with_credentials,
+        &config.proxy_username,
+        &config.proxy_password,
+    ) {
         if !username.is_empty() && !password.is_empty() {
             format!("{username}:{password}@")
         } else {
@@ -77,13 +83,6 @@ fn build_proxy_url(config: &GlobalConfig) -> AppResult<String> {
     };
 
     // 构建完整 URL
-    let scheme = match proxy_type.as_str() {
-        "socks5" => "socks5",
-        "https" => "https",
-        "http" => "http",
-        _ => "http",
-    };
-
     Ok(format!("{scheme}://{auth}{host}:{port}"))
 }
 
@@ -114,8 +113,19 @@ mod tests {
             external_poll_interval_ms: 5000,
             single_instance_enabled: true,
             startup_enabled: false,
+            startup_mode: crate::models::config::StartupMode::default(),
             config_watch: crate::models::config::ConfigWatchConfig::default(),
             token_stats_config: crate::models::config::TokenStatsConfig::default(),
+            tool_config_dirs: std::collections::HashMap::new(),
+            observer_mode: false,
+            maintenance: crate::models::config::MaintenanceConfig::default(),
+            metrics: crate::models::config::MetricsConfig::default(),
+            pricing_sync: crate::models::config::PricingSyncConfig::default(),
+            admin_api: crate::models::config::AdminApiConfig::default(),
+            mcp: crate::models::config::McpConfig::default(),
+            balance_monitor: crate::models::config::BalanceMonitorConfig::default(),
+            crash_report: crate::models::config::CrashReportConfig::default(),
+            extra_paths: Vec::new(),
         };
 
         let url = build_proxy_url(&config).unwrap();
@@ -145,8 +155,19 @@ mod tests {
             external_poll_interval_ms: 5000,
             single_instance_enabled: true,
             startup_enabled: false,
+            startup_mode: crate::models::config::StartupMode::default(),
             config_watch: crate::models::config::ConfigWatchConfig::default(),
             token_stats_config: crate::models::config::TokenStatsConfig::default(),
+            tool_config_dirs: std::collections::HashMap::new(),
+            observer_mode: false,
+            maintenance: crate::models::config::MaintenanceConfig::default(),
+            metrics: crate::models::config::MetricsConfig::default(),
+            pricing_sync: crate::models::config::PricingSyncConfig::default(),
+            admin_api: crate::models::config::AdminApiConfig::default(),
+            mcp: crate::models::config::McpConfig::default(),
+            balance_monitor: crate::models::config::BalanceMonitorConfig::default(),
+            crash_report: crate::models::config::CrashReportConfig::default(),
+            extra_paths: Vec::new(),
         };
 
         let url = build_proxy_url(&config).unwrap();
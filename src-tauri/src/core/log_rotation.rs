@@ -0,0 +1,355 @@
+//! 日志文件滚动与保留
+//!
+//! 当前日志写入 `duckcoding.log`，超过大小上限或跨天时重命名为
+//! `duckcoding.YYYYMMDD-HHMMSS.log`，并按配置 gzip 压缩为 `.log.gz`；
+//! 滚动后按保留文件数与保留天数清理历史日志。
+//!
+//! 旧版按天滚动产生的 `duckcoding.YYYY-MM-DD` 文件同样视为历史日志。
+
+use crate::models::config::LogRotationConfig;
+use anyhow::Result;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime};
+use flate2::{write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// 当前正在写入的日志文件名
+pub const ACTIVE_LOG_FILE: &str = "duckcoding.log";
+
+const LOG_PREFIX: &str = "duckcoding.";
+const ROTATED_STAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+/// 日志文件信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogFileInfo {
+    pub name: String,
+    pub path: String,
+    pub size_bytes: u64,
+    /// 最后修改时间（Unix 时间戳，毫秒）
+    pub modified_at: i64,
+    /// 是否为 gzip 压缩文件
+    pub compressed: bool,
+    /// 是否为当前正在写入的日志文件
+    pub active: bool,
+}
+
+/// 按大小与日期滚动的日志写入器（配合 `tracing_appender::non_blocking` 使用）
+pub struct RotatingFileWriter {
+    dir: PathBuf,
+    config: LogRotationConfig,
+    file: Option<File>,
+    size: u64,
+    opened_on: NaiveDate,
+}
+
+impl RotatingFileWriter {
+    /// 打开（或续写）日志目录下的 `duckcoding.log`
+    pub fn new(dir: impl Into<PathBuf>, config: LogRotationConfig) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        let file = open_append(&dir.join(ACTIVE_LOG_FILE))?;
+        let metadata = file.metadata()?;
+        let opened_on = metadata
+            .modified()
+            .map(|t| DateTime::<Local>::from(t).date_naive())
+            .unwrap_or_else(|_| Local::now().date_naive());
+        Ok(Self {
+            dir,
+            config,
+            file: Some(file),
+            size: metadata.len(),
+            opened_on,
+        })
+    }
+
+    fn max_bytes(&self) -> u64 {
+        u64::from(self.config.max_file_size_mb.max(1)) * 1024 * 1024
+    }
+
+    fn should_rotate(&self, incoming: usize) -> bool {
+        self.size > 0
+            && (self.size + incoming as u64 > self.max_bytes()
+                || Local::now().date_naive() != self.opened_on)
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        let active = self.dir.join(ACTIVE_LOG_FILE);
+        let rotated = unique_rotated_path(&self.dir, Local::now().naive_local());
+
+        // Windows 下无法重命名已打开的文件，先关闭当前句柄
+        if let Some(mut file) = self.file.take() {
+            let _ = file.flush();
+        }
+        let renamed = fs::rename(&active, &rotated);
+        self.file = Some(open_append(&active)?);
+        // 重命名失败时同样重置计数，避免每次写入都重试滚动
+        self.size = 0;
+        self.opened_on = Local::now().date_naive();
+        renamed?;
+
+        // 压缩与清理放到后台线程，避免阻塞日志写入
+        let dir = self.dir.clone();
+        let config = self.config.clone();
+        std::thread::spawn(move || {
+            if config.compress {
+                if let Err(e) = compress_file(&rotated) {
+                    eprintln!("压缩日志文件失败 {}: {}", rotated.display(), e);
+                }
+            }
+            if let Err(e) = enforce_retention(&dir, &config) {
+                eprintln!("清理历史日志失败: {}", e);
+            }
+        });
+        Ok(())
+    }
+}
+
+impl Write for RotatingFileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // 写日志本身不能再走 tracing，失败时输出到 stderr
+        if self.should_rotate(buf.len()) {
+            if let Err(e) = self.rotate() {
+                eprintln!("日志文件滚动失败: {}", e);
+            }
+        }
+        let file = match self.file.as_mut() {
+            Some(file) => file,
+            None => self
+                .file
+                .insert(open_append(&self.dir.join(ACTIVE_LOG_FILE))?),
+        };
+        let written = file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.file.as_mut() {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+/// 生成不与现有文件（含压缩后文件）冲突的滚动文件路径
+fn unique_rotated_path(dir: &Path, now: NaiveDateTime) -> PathBuf {
+    let stamp = now.format(ROTATED_STAMP_FORMAT).to_string();
+    let mut suffix = 0;
+    loop {
+        let name = if suffix == 0 {
+            format!("{LOG_PREFIX}{stamp}.log")
+        } else {
+            format!("{LOG_PREFIX}{stamp}-{suffix}.log")
+        };
+        let path = dir.join(&name);
+        if !path.exists() && !dir.join(format!("{name}.gz")).exists() {
+            return path;
+        }
+        suffix += 1;
+    }
+}
+
+/// 将日志文件压缩为 `.gz` 并删除原文件
+fn compress_file(path: &Path) -> io::Result<PathBuf> {
+    let mut target = path.as_os_str().to_owned();
+    target.push(".gz");
+    let target = PathBuf::from(target);
+
+    let mut input = File::open(path)?;
+    let mut encoder = GzEncoder::new(File::create(&target)?, Compression::default());
+    io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?;
+    drop(input);
+    fs::remove_file(path)?;
+    Ok(target)
+}
+
+/// 是否为滚动出的历史日志（含旧版按天滚动的文件）
+fn is_history_log(name: &str) -> bool {
+    let Some(stem) = name.strip_prefix(LOG_PREFIX) else {
+        return false;
+    };
+    let stem = stem.strip_suffix(".gz").unwrap_or(stem);
+    match stem.strip_suffix(".log") {
+        Some(stamp) => stamp
+            .get(..15)
+            .is_some_and(|s| NaiveDateTime::parse_from_str(s, ROTATED_STAMP_FORMAT).is_ok()),
+        None => NaiveDate::parse_from_str(stem, "%Y-%m-%d").is_ok(),
+    }
+}
+
+/// 历史日志文件（按修改时间从新到旧排序）
+fn history_files(dir: &Path) -> Result<Vec<(PathBuf, SystemTime)>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut files: Vec<(PathBuf, SystemTime)> = fs::read_dir(dir)?
+        .flatten()
+        .filter(|entry| entry.file_name().to_str().is_some_and(is_history_log))
+        .filter_map(|entry| {
+            let modified = entry.metadata().ok()?.modified().ok()?;
+            Some((entry.path(), modified))
+        })
+        .collect();
+    // 修改时间相同时按文件名（时间戳）排序
+    files.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| b.0.cmp(&a.0)));
+    Ok(files)
+}
+
+/// 按保留文件数与保留天数清理历史日志，返回删除的文件数
+pub fn enforce_retention(dir: &Path, config: &LogRotationConfig) -> Result<usize> {
+    let max_age = (config.max_age_days > 0)
+        .then(|| Duration::from_secs(u64::from(config.max_age_days) * 86_400));
+    let now = SystemTime::now();
+
+    let mut removed = 0;
+    for (index, (path, modified)) in history_files(dir)?.into_iter().enumerate() {
+        let expired = max_age.is_some_and(|age| {
+            now.duration_since(modified)
+                .is_ok_and(|elapsed| elapsed > age)
+        });
+        if (index >= config.max_files as usize || expired) && fs::remove_file(&path).is_ok() {
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// 删除历史日志（`older_than` 为 None 时删除全部），当前日志文件不受影响
+pub fn purge_history(dir: &Path, older_than: Option<Duration>) -> Result<usize> {
+    let now = SystemTime::now();
+    let mut removed = 0;
+    for (path, modified) in history_files(dir)? {
+        let matched = older_than.is_none_or(|age| {
+            now.duration_since(modified)
+                .is_ok_and(|elapsed| elapsed > age)
+        });
+        if matched && fs::remove_file(&path).is_ok() {
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// 列出日志目录下的当前日志与历史日志（当前日志在前，其余从新到旧）
+pub fn list_log_files(dir: &Path) -> Result<Vec<LogFileInfo>> {
+    let active = dir.join(ACTIVE_LOG_FILE);
+    let mut paths: Vec<PathBuf> = history_files(dir)?.into_iter().map(|(p, _)| p).collect();
+    if active.exists() {
+        paths.insert(0, active.clone());
+    }
+
+    let mut files = Vec::with_capacity(paths.len());
+    for path in paths {
+        let Ok(metadata) = fs::metadata(&path) else {
+            continue;
+        };
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        files.push(LogFileInfo {
+            compressed: name.ends_with(".gz"),
+            active: path == active,
+            modified_at: metadata
+                .modified()
+                .map(|t| DateTime::<Local>::from(t).timestamp_millis())
+                .unwrap_or_default(),
+            size_bytes: metadata.len(),
+            path: path.to_string_lossy().into_owned(),
+            name,
+        });
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn config(max_files: u32) -> LogRotationConfig {
+        LogRotationConfig {
+            max_file_size_mb: 1,
+            max_files,
+            max_age_days: 0,
+            compress: false,
+        }
+    }
+
+    #[test]
+    fn test_is_history_log() {
+        assert!(is_history_log("duckcoding.20261016-120000.log"));
+        assert!(is_history_log("duckcoding.20261016-120000-2.log.gz"));
+        assert!(is_history_log("duckcoding.2026-10-16"));
+        assert!(!is_history_log(ACTIVE_LOG_FILE));
+        assert!(!is_history_log("duckcoding.db"));
+        assert!(!is_history_log("other.20261016-120000.log"));
+    }
+
+    #[test]
+    fn test_rotate_by_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = RotatingFileWriter::new(dir.path(), config(10)).unwrap();
+        let chunk = vec![b'x'; 600 * 1024];
+        writer.write_all(&chunk).unwrap();
+        writer.write_all(&chunk).unwrap();
+        writer.flush().unwrap();
+
+        let files = list_log_files(dir.path()).unwrap();
+        assert_eq!(files.len(), 2);
+        assert!(files[0].active);
+        assert_eq!(files[0].size_bytes, chunk.len() as u64);
+        assert_eq!(files[1].size_bytes, chunk.len() as u64);
+    }
+
+    #[test]
+    fn test_enforce_retention_and_purge() {
+        let dir = tempfile::tempdir().unwrap();
+        for second in 0..5 {
+            let name = format!("duckcoding.20261016-12000{second}.log");
+            fs::write(dir.path().join(name), "log").unwrap();
+        }
+        fs::write(dir.path().join(ACTIVE_LOG_FILE), "log").unwrap();
+
+        assert_eq!(enforce_retention(dir.path(), &config(2)).unwrap(), 3);
+        let names: Vec<String> = list_log_files(dir.path())
+            .unwrap()
+            .into_iter()
+            .map(|f| f.name)
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ACTIVE_LOG_FILE,
+                "duckcoding.20261016-120004.log",
+                "duckcoding.20261016-120003.log"
+            ]
+        );
+
+        assert_eq!(purge_history(dir.path(), None).unwrap(), 2);
+        assert!(dir.path().join(ACTIVE_LOG_FILE).exists());
+    }
+
+    #[test]
+    fn test_compress_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("duckcoding.20261016-120000.log");
+        fs::write(&path, "hello log").unwrap();
+
+        let target = compress_file(&path).unwrap();
+        assert!(!path.exists());
+        let mut content = String::new();
+        flate2::read::GzDecoder::new(File::open(target).unwrap())
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "hello log");
+    }
+}
@@ -1,6 +1,7 @@
+use super::log_rotation::{self, LogFileInfo, RotatingFileWriter};
 use crate::models::config::{LogConfig, LogFormat, LogLevel, LogOutput};
 use std::sync::OnceLock;
-use tracing_appender::{non_blocking, rolling};
+use tracing_appender::non_blocking;
 use tracing_subscriber::{
     fmt::{self, format::FmtSpan},
     layer::SubscriberExt,
@@ -19,6 +20,7 @@ static LOG_LEVEL_HANDLE: OnceLock<Handle<EnvFilter, Registry>> = OnceLock::new()
 /// - 输出格式（JSON/纯文本）
 /// - 输出目标（控制台/文件/both）
 /// - 文件路径（用于文件输出）
+/// - 文件滚动（按大小/日期滚动、压缩与保留策略，见 `log_rotation`）
 ///
 /// # 热重载支持
 /// 日志级别可以通过 `update_log_level` 函数动态调整，无需重启应用。
@@ -57,21 +59,21 @@ pub fn init_logger(config: &LogConfig) -> anyhow::Result<()> {
                 .init();
         }
         (LogOutput::File, LogFormat::Text) => {
-            let file_layer = create_file_text_layer(config.file_path.as_deref())?;
+            let file_layer = create_file_text_layer(config)?;
             Registry::default()
                 .with(filter_layer)
                 .with(file_layer)
                 .init();
         }
         (LogOutput::File, LogFormat::Json) => {
-            let file_layer = create_file_json_layer(config.file_path.as_deref())?;
+            let file_layer = create_file_json_layer(config)?;
             Registry::default()
                 .with(filter_layer)
                 .with(file_layer)
                 .init();
         }
         (LogOutput::Both, LogFormat::Text) => {
-            let file_layer = create_file_text_layer(config.file_path.as_deref())?;
+            let file_layer = create_file_text_layer(config)?;
             Registry::default()
                 .with(filter_layer)
                 .with(create_console_text_layer())
@@ -79,7 +81,7 @@ pub fn init_logger(config: &LogConfig) -> anyhow::Result<()> {
                 .init();
         }
         (LogOutput::Both, LogFormat::Json) => {
-            let file_layer = create_file_json_layer(config.file_path.as_deref())?;
+            let file_layer = create_file_json_layer(config)?;
             Registry::default()
                 .with(filter_layer)
                 .with(create_console_json_layer())
@@ -146,18 +148,12 @@ where
 
 /// 创建文件文本格式输出层
 fn create_file_text_layer<S>(
-    file_path: Option<&str>,
+    config: &LogConfig,
 ) -> anyhow::Result<Box<dyn Layer<S> + Send + Sync + 'static>>
 where
     S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
 {
-    let log_dir = get_log_dir(file_path)?;
-    let file_appender = rolling::daily(log_dir, "duckcoding");
-    let (non_blocking, guard) = non_blocking(file_appender);
-
-    // 存储 guard 到全局静态变量（防止被 drop）
-    Box::leak(Box::new(guard));
-
+    let non_blocking = create_file_writer(config)?;
     Ok(fmt::layer()
         .with_writer(non_blocking)
         .with_target(cfg!(debug_assertions))
@@ -168,18 +164,12 @@ where
 
 /// 创建文件 JSON 格式输出层
 fn create_file_json_layer<S>(
-    file_path: Option<&str>,
+    config: &LogConfig,
 ) -> anyhow::Result<Box<dyn Layer<S> + Send + Sync + 'static>>
 where
     S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
 {
-    let log_dir = get_log_dir(file_path)?;
-    let file_appender = rolling::daily(log_dir, "duckcoding");
-    let (non_blocking, guard) = non_blocking(file_appender);
-
-    // 存储 guard 到全局静态变量（防止被 drop）
-    Box::leak(Box::new(guard));
-
+    let non_blocking = create_file_writer(config)?;
     Ok(fmt::layer()
         .json()
         .with_writer(non_blocking)
@@ -189,8 +179,22 @@ where
         .boxed())
 }
 
+/// 创建滚动日志文件的非阻塞写入器
+fn create_file_writer(config: &LogConfig) -> anyhow::Result<non_blocking::NonBlocking> {
+    let log_dir = get_log_dir(config.file_path.as_deref())?;
+    // 启动时先按保留策略清理一次历史日志
+    log_rotation::enforce_retention(&log_dir, &config.rotation)?;
+    let writer = RotatingFileWriter::new(log_dir, config.rotation.clone())?;
+    let (non_blocking, guard) = non_blocking(writer);
+
+    // 存储 guard 到全局静态变量（防止被 drop）
+    Box::leak(Box::new(guard));
+
+    Ok(non_blocking)
+}
+
 /// 获取日志目录
-fn get_log_dir(file_path: Option<&str>) -> anyhow::Result<std::path::PathBuf> {
+pub(crate) fn get_log_dir(file_path: Option<&str>) -> anyhow::Result<std::path::PathBuf> {
     match file_path {
         Some(path) => Ok(std::path::PathBuf::from(path)),
         None => {
@@ -206,6 +210,31 @@ fn get_log_dir(file_path: Option<&str>) -> anyhow::Result<std::path::PathBuf> {
     }
 }
 
+/// 删除超过保留天数的历史日志文件
+///
+/// 返回删除的文件数。
+pub fn prune_log_files(file_path: Option<&str>, keep_days: u32) -> anyhow::Result<usize> {
+    purge_log_files(file_path, Some(keep_days))
+}
+
+/// 删除历史日志文件（`older_than_days` 为 None 时删除全部），不影响当前日志文件
+///
+/// 返回删除的文件数。
+pub fn purge_log_files(
+    file_path: Option<&str>,
+    older_than_days: Option<u32>,
+) -> anyhow::Result<usize> {
+    let log_dir = get_log_dir(file_path)?;
+    let older_than =
+        older_than_days.map(|days| std::time::Duration::from_secs(u64::from(days) * 86_400));
+    log_rotation::purge_history(&log_dir, older_than)
+}
+
+/// 列出当前日志与历史日志文件
+pub fn list_log_files(file_path: Option<&str>) -> anyhow::Result<Vec<LogFileInfo>> {
+    log_rotation::list_log_files(&get_log_dir(file_path)?)
+}
+
 /// 动态更新日志级别（热重载）
 ///
 /// 此函数支持在应用运行时动态调整日志级别，无需重启。
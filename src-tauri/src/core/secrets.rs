@@ -0,0 +1,450 @@
+//! 密钥安全存储
+//!
+//! 配置文件（profiles.json / proxy.json / config.json）中只保存密钥引用
+//! `dc-secret:<backend>:<id>`，明文保存在：
+//! - 系统钥匙串：macOS Keychain / Windows 凭据管理器 / Linux Secret Service（libsecret）
+//! - 钥匙串不可用时回退到 AES-256-GCM 加密文件（`secrets.enc.json`，主密钥为同目录的 `secrets.key`）
+//!
+//! 读取配置时按引用解密；尚未迁移的明文值原样使用，下次保存时自动转为引用。
+//! 设置环境变量 `DUCKCODING_SECRET_BACKEND=file` 可强制使用加密文件（无桌面会话的服务器环境）。
+
+use aes_gcm::aead::{Aead, KeyInit, OsRng};
+use aes_gcm::{AeadCore, Aes256Gcm, Key, Nonce};
+use anyhow::{anyhow, bail, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use once_cell::sync::Lazy;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::utils::config_dir;
+
+/// 密钥引用前缀
+pub const SECRET_REF_PREFIX: &str = "dc-secret:";
+
+/// 钥匙串中的服务名
+const KEYRING_SERVICE: &str = "DuckCoding";
+
+/// 加密文件名与主密钥文件名
+const SECRETS_FILE: &str = "secrets.enc.json";
+const MASTER_KEY_FILE: &str = "secrets.key";
+
+/// AES-GCM nonce 长度
+const NONCE_LEN: usize = 12;
+
+static SECRET_STORE: Lazy<SecretStore> = Lazy::new(|| {
+    let dir = config_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let force_file = cfg!(test)
+        || std::env::var("DUCKCODING_SECRET_BACKEND").is_ok_and(|v| v.eq_ignore_ascii_case("file"));
+    let keychain_available = !force_file && probe_keychain();
+    if !keychain_available {
+        tracing::info!("系统钥匙串不可用，密钥使用加密文件保存: {}", dir.display());
+    }
+    SecretStore {
+        dir,
+        keychain_available,
+        file_lock: Mutex::new(()),
+    }
+});
+
+/// 写入并删除一个探测条目，确认钥匙串可用
+fn probe_keychain() -> bool {
+    let probe = || -> keyring::Result<()> {
+        let entry = keyring::Entry::new(KEYRING_SERVICE, "__probe__")?;
+        entry.set_password("probe")?;
+        entry.get_password()?;
+        entry.delete_credential()
+    };
+    match probe() {
+        Ok(()) => true,
+        Err(e) => {
+            tracing::debug!("系统钥匙串探测失败: {}", e);
+            false
+        }
+    }
+}
+
+/// 密钥存储后端
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecretBackend {
+    Keychain,
+    File,
+}
+
+impl SecretBackend {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Keychain => "keychain",
+            Self::File => "file",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "keychain" => Some(Self::Keychain),
+            "file" => Some(Self::File),
+            _ => None,
+        }
+    }
+}
+
+/// 是否为密钥引用
+pub fn is_secret_ref(value: &str) -> bool {
+    value.starts_with(SECRET_REF_PREFIX)
+}
+
+fn parse_ref(reference: &str) -> Result<(SecretBackend, &str)> {
+    let rest = reference
+        .strip_prefix(SECRET_REF_PREFIX)
+        .ok_or_else(|| anyhow!("不是密钥引用"))?;
+    let (backend, id) = rest
+        .split_once(':')
+        .ok_or_else(|| anyhow!("密钥引用格式无效: {}", reference))?;
+    let backend =
+        SecretBackend::parse(backend).ok_or_else(|| anyhow!("未知的密钥存储后端: {}", backend))?;
+    if id.is_empty() {
+        bail!("密钥引用缺少 ID: {}", reference);
+    }
+    Ok((backend, id))
+}
+
+/// 包含密钥字段的配置
+///
+/// 实现方按稳定的 ID（如 `profile/claude-code/<name>`）逐个访问密钥字段，
+/// 同一字段每次保存得到相同的 ID，更新时覆盖原有密钥。
+pub trait SecretFields {
+    fn visit_secrets(&mut self, visit: &mut dyn FnMut(&str, &mut String));
+}
+
+/// 密钥存储
+pub struct SecretStore {
+    dir: PathBuf,
+    keychain_available: bool,
+    file_lock: Mutex<()>,
+}
+
+impl SecretStore {
+    /// 全局密钥存储（首次访问时探测钥匙串）
+    pub fn global() -> &'static SecretStore {
+        &SECRET_STORE
+    }
+
+    /// 仅使用加密文件的存储（测试与无桌面环境）
+    pub fn file_only(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            keychain_available: false,
+            file_lock: Mutex::new(()),
+        }
+    }
+
+    /// 新密钥写入的后端
+    pub fn backend(&self) -> SecretBackend {
+        if self.keychain_available {
+            SecretBackend::Keychain
+        } else {
+            SecretBackend::File
+        }
+    }
+
+    /// 保存明文并返回引用
+    ///
+    /// 空值与已是引用的值原样返回；钥匙串写入失败时回退到加密文件
+    pub fn seal(&self, id: &str, value: &str) -> Result<String> {
+        if value.is_empty() || is_secret_ref(value) {
+            return Ok(value.to_string());
+        }
+        if self.keychain_available {
+            match self.keychain_set(id, value) {
+                Ok(()) => return Ok(Self::make_ref(SecretBackend::Keychain, id)),
+                Err(e) => tracing::warn!(id = %id, error = %e, "写入系统钥匙串失败，改用加密文件"),
+            }
+        }
+        self.file_set(id, value)?;
+        Ok(Self::make_ref(SecretBackend::File, id))
+    }
+
+    /// 解析引用得到明文（非引用值原样返回）
+    pub fn reveal(&self, value: &str) -> Result<String> {
+        if !is_secret_ref(value) {
+            return Ok(value.to_string());
+        }
+        let (backend, id) = parse_ref(value)?;
+        match backend {
+            SecretBackend::Keychain => keyring::Entry::new(KEYRING_SERVICE, id)
+                .and_then(|entry| entry.get_password())
+                .with_context(|| format!("从系统钥匙串读取密钥失败: {}", id)),
+            SecretBackend::File => self
+                .file_read()?
+                .get(id)
+                .map(|sealed| self.decrypt(sealed))
+                .transpose()?
+                .ok_or_else(|| anyhow!("加密文件中不存在密钥: {}", id)),
+        }
+    }
+
+    /// 删除引用指向的密钥（不存在时忽略）
+    pub fn delete(&self, reference: &str) -> Result<()> {
+        let (backend, id) = parse_ref(reference)?;
+        match backend {
+            SecretBackend::Keychain => {
+                match keyring::Entry::new(KEYRING_SERVICE, id)
+                    .and_then(|entry| entry.delete_credential())
+                {
+                    Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+                    Err(e) => Err(anyhow!("删除系统钥匙串中的密钥失败: {}", e)),
+                }
+            }
+            SecretBackend::File => {
+                let _guard = self.file_lock.lock().unwrap();
+                let mut entries = self.file_read()?;
+                if entries.remove(id).is_some() {
+                    self.file_write(&entries)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// 将配置中的明文密钥替换为引用
+    pub fn seal_fields<T: SecretFields>(&self, target: &mut T) -> Result<()> {
+        let mut first_error = None;
+        target.visit_secrets(&mut |id, value| match self.seal(id, value) {
+            Ok(reference) => *value = reference,
+            Err(e) => {
+                first_error.get_or_insert(e);
+            }
+        });
+        first_error.map_or(Ok(()), Err)
+    }
+
+    /// 将配置中的引用解析为明文
+    ///
+    /// 单个密钥解析失败时保留引用（避免保存时丢失），仅记录警告
+    pub fn reveal_fields<T: SecretFields>(&self, target: &mut T) {
+        target.visit_secrets(&mut |id, value| {
+            if !is_secret_ref(value) {
+                return;
+            }
+            match self.reveal(value) {
+                Ok(plain) => *value = plain,
+                Err(e) => tracing::warn!(id = %id, error = %e, "解析密钥引用失败"),
+            }
+        });
+    }
+
+    /// 删除旧配置中存在、新配置中已不再引用的密钥（删除 Profile、缩短 Key 池等）
+    pub fn prune(&self, previous: &Value, current: &Value) {
+        let keep = collect_refs(current);
+        for reference in collect_refs(previous).difference(&keep) {
+            if let Err(e) = self.delete(reference) {
+                tracing::warn!(reference = %reference, error = %e, "清理失效密钥失败");
+            }
+        }
+    }
+
+    fn make_ref(backend: SecretBackend, id: &str) -> String {
+        format!("{SECRET_REF_PREFIX}{}:{id}", backend.as_str())
+    }
+
+    fn keychain_set(&self, id: &str, value: &str) -> keyring::Result<()> {
+        let entry = keyring::Entry::new(KEYRING_SERVICE, id)?;
+        // 内容未变时跳过写入，减少钥匙串访问
+        if entry.get_password().is_ok_and(|existing| existing == value) {
+            return Ok(());
+        }
+        entry.set_password(value)
+    }
+
+    // ==================== 加密文件后端 ====================
+
+    fn file_path(&self) -> PathBuf {
+        self.dir.join(SECRETS_FILE)
+    }
+
+    fn file_set(&self, id: &str, value: &str) -> Result<()> {
+        let _guard = self.file_lock.lock().unwrap();
+        let mut entries = self.file_read()?;
+        if let Some(existing) = entries.get(id) {
+            if self.decrypt(existing).is_ok_and(|plain| plain == value) {
+                return Ok(());
+            }
+        }
+        entries.insert(id.to_string(), self.encrypt(value)?);
+        self.file_write(&entries)
+    }
+
+    fn file_read(&self) -> Result<BTreeMap<String, String>> {
+        let path = self.file_path();
+        if !path.exists() {
+            return Ok(BTreeMap::new());
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("读取密钥文件失败: {}", path.display()))?;
+        serde_json::from_str(&content).with_context(|| format!("密钥文件损坏: {}", path.display()))
+    }
+
+    fn file_write(&self, entries: &BTreeMap<String, String>) -> Result<()> {
+        let path = self.file_path();
+        write_private(&path, serde_json::to_string_pretty(entries)?.as_bytes())
+    }
+
+    /// 读取主密钥（不存在时生成）
+    fn master_key(&self) -> Result<Key<Aes256Gcm>> {
+        let path = self.dir.join(MASTER_KEY_FILE);
+        if path.exists() {
+            let encoded = std::fs::read_to_string(&path)
+                .with_context(|| format!("读取主密钥失败: {}", path.display()))?;
+            let bytes: [u8; 32] = BASE64
+                .decode(encoded.trim())
+                .context("主密钥格式无效")?
+                .try_into()
+                .map_err(|_| anyhow!("主密钥长度无效: {}", path.display()))?;
+            return Ok(Key::<Aes256Gcm>::from(bytes));
+        }
+        let key = Aes256Gcm::generate_key(OsRng);
+        write_private(&path, BASE64.encode(key).as_bytes())?;
+        Ok(key)
+    }
+
+    fn encrypt(&self, plain: &str) -> Result<String> {
+        let cipher = Aes256Gcm::new(&self.master_key()?);
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, plain.as_bytes())
+            .map_err(|_| anyhow!("加密密钥失败"))?;
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        Ok(BASE64.encode(sealed))
+    }
+
+    fn decrypt(&self, sealed: &str) -> Result<String> {
+        let bytes = BASE64.decode(sealed).context("密文格式无效")?;
+        if bytes.len() <= NONCE_LEN {
+            bail!("密文长度无效");
+        }
+        let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+        let nonce: [u8; NONCE_LEN] = nonce.try_into()?;
+        let cipher = Aes256Gcm::new(&self.master_key()?);
+        let plain = cipher
+            .decrypt(&Nonce::from(nonce), ciphertext)
+            .map_err(|_| anyhow!("解密密钥失败（主密钥不匹配或密文被篡改）"))?;
+        String::from_utf8(plain).context("密钥不是有效的 UTF-8")
+    }
+}
+
+/// 收集 JSON 中出现的全部密钥引用
+pub fn collect_refs(value: &Value) -> HashSet<String> {
+    fn walk(value: &Value, refs: &mut HashSet<String>) {
+        match value {
+            Value::String(s) if is_secret_ref(s) => {
+                refs.insert(s.clone());
+            }
+            Value::Array(items) => items.iter().for_each(|item| walk(item, refs)),
+            Value::Object(map) => map.values().for_each(|item| walk(item, refs)),
+            _ => {}
+        }
+    }
+    let mut refs = HashSet::new();
+    walk(value, &mut refs);
+    refs
+}
+
+/// 写入仅当前用户可读的文件
+fn write_private(path: &Path, content: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, content).with_context(|| format!("写入失败: {}", tmp.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&tmp, std::fs::Permissions::from_mode(0o600))?;
+    }
+    std::fs::rename(&tmp, path).with_context(|| format!("写入失败: {}", path.display()))?;
+    Ok(())
+}
+
+/// 访问可选密钥字段
+pub fn visit_optional(
+    id: &str,
+    value: &mut Option<String>,
+    visit: &mut dyn FnMut(&str, &mut String),
+) {
+    if let Some(value) = value {
+        visit(id, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    struct Sample {
+        key: String,
+        optional: Option<String>,
+    }
+
+    impl SecretFields for Sample {
+        fn visit_secrets(&mut self, visit: &mut dyn FnMut(&str, &mut String)) {
+            visit("sample/key", &mut self.key);
+            visit_optional("sample/optional", &mut self.optional, visit);
+        }
+    }
+
+    #[test]
+    fn test_seal_and_reveal_with_file_backend() {
+        let dir = tempdir().unwrap();
+        let store = SecretStore::file_only(dir.path());
+
+        let mut sample = Sample {
+            key: "sk-plain".to_string(),
+            optional: Some(String::new()),
+        };
+        store.seal_fields(&mut sample).unwrap();
+        assert_eq!(sample.key, "dc-secret:file:sample/key");
+        // 空值不保存
+        assert_eq!(sample.optional.as_deref(), Some(""));
+
+        let on_disk = std::fs::read_to_string(dir.path().join(SECRETS_FILE)).unwrap();
+        assert!(!on_disk.contains("sk-plain"));
+
+        // 再次保存保持引用不变
+        store.seal_fields(&mut sample).unwrap();
+        assert_eq!(sample.key, "dc-secret:file:sample/key");
+
+        store.reveal_fields(&mut sample);
+        assert_eq!(sample.key, "sk-plain");
+
+        // 未迁移的明文原样返回
+        assert_eq!(store.reveal("legacy-plain").unwrap(), "legacy-plain");
+    }
+
+    #[test]
+    fn test_prune_and_invalid_refs() {
+        let dir = tempdir().unwrap();
+        let store = SecretStore::file_only(dir.path());
+
+        let a = store.seal("profile/codex/a", "key-a").unwrap();
+        let b = store.seal("profile/codex/b", "key-b").unwrap();
+        let previous = serde_json::json!({"a": {"api_key": a}, "b": {"api_key": b}});
+        let current = serde_json::json!({"a": {"api_key": a}});
+        store.prune(&previous, &current);
+
+        assert_eq!(store.reveal(&a).unwrap(), "key-a");
+        assert!(store.reveal(&b).is_err());
+
+        // 解析失败时保留引用
+        let mut sample = Sample {
+            key: "dc-secret:file:missing".to_string(),
+            optional: None,
+        };
+        store.reveal_fields(&mut sample);
+        assert_eq!(sample.key, "dc-secret:file:missing");
+        assert!(store.reveal("dc-secret:vault:x").is_err());
+    }
+}
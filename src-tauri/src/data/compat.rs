@@ -0,0 +1,387 @@
+//! 跨版本配置兼容
+//!
+//! 应用降级后，新版本写入的配置可能包含旧版本不认识的字段，或旧版本无法解析的取值
+//! （例如新增的枚举值）。本模块提供：
+//!
+//! - `from_value_tolerant`: 容错反序列化，逐个丢弃无法解析的字段（回退为默认值）
+//! - `preserve_fields`: 写回时保留旧版本不认识/无法解析的字段，升级回新版本后不丢失
+//! - `inspect_file`: 生成兼容性报告，列出当前版本不理解的字段
+//!
+//! 字段路径只在 JSON 对象间展开，数组整体视为一个值。
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::Path;
+
+/// 容错解析最多丢弃的字段数（防止异常输入导致长时间循环）
+const MAX_DROPPED_FIELDS: usize = 64;
+
+/// 报告中字段取值预览的最大长度
+const MAX_PREVIEW_CHARS: usize = 120;
+
+/// 兼容问题类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompatIssueKind {
+    /// 当前版本不认识的字段（原样保留）
+    Unknown,
+    /// 当前版本无法解析的取值（已回退默认值，写回时保留原值）
+    Incompatible,
+}
+
+/// 单个兼容问题
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompatIssue {
+    /// 字段路径（如 `proxy_configs.codex.new_option`）
+    pub path: String,
+    pub kind: CompatIssueKind,
+    /// 原始取值预览
+    pub value_preview: String,
+}
+
+/// 单个配置文件的兼容性报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompatFileReport {
+    /// 配置名称（如 `config.json`）
+    pub name: String,
+    pub path: String,
+    pub exists: bool,
+    /// 文件中记录的版本号
+    pub file_version: Option<String>,
+    pub issues: Vec<CompatIssue>,
+    /// 容错解析仍失败时的错误（当前版本无法使用该文件）
+    pub error: Option<String>,
+}
+
+type FieldPath = Vec<String>;
+
+/// 容错反序列化：解析失败时定位出错字段并移除后重试，返回结果与被丢弃的字段路径
+///
+/// 被丢弃字段由结构体的 `#[serde(default)]` 补齐；必填字段出错时会继续上溯丢弃所在对象，
+/// 直到根对象仍无法解析才返回错误。
+pub fn from_value_tolerant<T: DeserializeOwned>(
+    value: Value,
+) -> Result<(T, Vec<FieldPath>), serde_json::Error> {
+    let mut value = value;
+    let mut dropped = Vec::new();
+    loop {
+        let (text, line_paths) = render_with_paths(&value);
+        let err = match serde_json::from_str::<T>(&text) {
+            Ok(parsed) => return Ok((parsed, dropped)),
+            Err(err) => err,
+        };
+        let path = match line_paths.get(err.line().saturating_sub(1)) {
+            Some(path) if !path.is_empty() && dropped.len() < MAX_DROPPED_FIELDS => path.clone(),
+            _ => return Err(err),
+        };
+        if remove_path(&mut value, &path).is_none() {
+            return Err(err);
+        }
+        tracing::warn!(field = %join_path(&path), error = %err, "配置字段无法解析，已回退默认值");
+        dropped.push(path);
+    }
+}
+
+/// 写回前保留原文件中当前版本不认识或无法解析的字段
+///
+/// - 不认识的字段：新内容中对应位置不存在时写回原值
+/// - 无法解析的字段：新内容仍为回退的默认值（用户未修改）时写回原值
+///
+/// 字段所在的父对象在新内容中已被删除时（例如删除了整个 Profile），不再保留。
+/// 返回保留的字段数。
+pub fn preserve_fields<T: DeserializeOwned + Serialize>(
+    existing: &Value,
+    new_value: &mut Value,
+) -> usize {
+    let Ok((issues, roundtrip)) = collect_issues::<T>(existing) else {
+        return 0;
+    };
+
+    let mut preserved = 0;
+    for (path, kind) in issues {
+        let Some((key, parent_path)) = path.split_last() else {
+            continue;
+        };
+        let Some(original) = get_path(existing, &path) else {
+            continue;
+        };
+        let Some(parent) = get_path_mut(new_value, parent_path).and_then(Value::as_object_mut)
+        else {
+            continue;
+        };
+        let keep = match (kind, parent.get(key)) {
+            (_, None) => true,
+            (CompatIssueKind::Incompatible, Some(current)) => {
+                get_path(&roundtrip, &path) == Some(current)
+            }
+            (CompatIssueKind::Unknown, Some(_)) => false,
+        };
+        if keep {
+            parent.insert(key.clone(), original.clone());
+            preserved += 1;
+        }
+    }
+    preserved
+}
+
+/// 检查配置文件，列出当前版本不理解的字段
+pub fn inspect_file<T: DeserializeOwned + Serialize>(name: &str, path: &Path) -> CompatFileReport {
+    let mut report = CompatFileReport {
+        name: name.to_string(),
+        path: path.to_string_lossy().to_string(),
+        exists: path.exists(),
+        file_version: None,
+        issues: Vec::new(),
+        error: None,
+    };
+    if !report.exists {
+        return report;
+    }
+
+    let value = match crate::data::DataManager::new().json_uncached().read(path) {
+        Ok(value) => value,
+        Err(e) => {
+            report.error = Some(e.to_string());
+            return report;
+        }
+    };
+    report.file_version = value
+        .get("version")
+        .and_then(Value::as_str)
+        .map(String::from);
+
+    match collect_issues::<T>(&value) {
+        Ok((issues, _)) => {
+            report.issues = issues
+                .into_iter()
+                .map(|(path, kind)| CompatIssue {
+                    value_preview: get_path(&value, &path).map(preview).unwrap_or_default(),
+                    path: join_path(&path),
+                    kind,
+                })
+                .collect();
+        }
+        Err(e) => report.error = Some(e.to_string()),
+    }
+    report
+}
+
+/// 对比原始内容与“容错解析 + 重新序列化”的结果，得到兼容问题及重新序列化的内容
+fn collect_issues<T: DeserializeOwned + Serialize>(
+    existing: &Value,
+) -> Result<(Vec<(FieldPath, CompatIssueKind)>, Value), serde_json::Error> {
+    let (parsed, dropped) = from_value_tolerant::<T>(existing.clone())?;
+    let roundtrip = serde_json::to_value(parsed)?;
+
+    let mut unknown = Vec::new();
+    diff_unknown(existing, &roundtrip, &mut Vec::new(), &mut unknown);
+
+    let mut issues: Vec<(FieldPath, CompatIssueKind)> = dropped
+        .into_iter()
+        .map(|path| (path, CompatIssueKind::Incompatible))
+        .collect();
+    for path in unknown {
+        if !issues.iter().any(|(p, _)| path.starts_with(p)) {
+            issues.push((path, CompatIssueKind::Unknown));
+        }
+    }
+    Ok((issues, roundtrip))
+}
+
+/// 找出原始内容中存在、重新序列化后消失的字段（值为 null 的字段视为等价于缺省）
+fn diff_unknown(
+    original: &Value,
+    roundtrip: &Value,
+    path: &mut FieldPath,
+    out: &mut Vec<FieldPath>,
+) {
+    let (Some(original), Some(roundtrip)) = (original.as_object(), roundtrip.as_object()) else {
+        return;
+    };
+    for (key, value) in original {
+        if value.is_null() {
+            continue;
+        }
+        path.push(key.clone());
+        match roundtrip.get(key) {
+            None => out.push(path.clone()),
+            Some(next) => diff_unknown(value, next, path, out),
+        }
+        path.pop();
+    }
+}
+
+/// 按“一行一个字段”渲染 JSON，并记录每行对应的字段路径（用于从解析错误的行号定位字段）
+fn render_with_paths(value: &Value) -> (String, Vec<FieldPath>) {
+    let mut lines = Vec::new();
+    match value.as_object() {
+        Some(map) => {
+            lines.push(("{".to_string(), Vec::new()));
+            render_members(map, &mut Vec::new(), &mut lines);
+            lines.push(("}".to_string(), Vec::new()));
+        }
+        None => lines.push((value.to_string(), Vec::new())),
+    }
+    let text = lines
+        .iter()
+        .map(|(line, _)| line.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+    (text, lines.into_iter().map(|(_, path)| path).collect())
+}
+
+fn render_members(
+    map: &Map<String, Value>,
+    path: &mut FieldPath,
+    lines: &mut Vec<(String, FieldPath)>,
+) {
+    let last = map.len().saturating_sub(1);
+    for (index, (key, value)) in map.iter().enumerate() {
+        let comma = if index < last { "," } else { "" };
+        let key_json = Value::String(key.clone()).to_string();
+        path.push(key.clone());
+        match value.as_object() {
+            Some(child) if !child.is_empty() => {
+                lines.push((format!("{key_json}: {{"), path.clone()));
+                render_members(child, path, lines);
+                lines.push((format!("}}{comma}"), path.clone()));
+            }
+            _ => lines.push((format!("{key_json}: {value}{comma}"), path.clone())),
+        }
+        path.pop();
+    }
+}
+
+fn get_path<'a>(value: &'a Value, path: &[String]) -> Option<&'a Value> {
+    path.iter().try_fold(value, |current, key| current.get(key))
+}
+
+fn get_path_mut<'a>(value: &'a mut Value, path: &[String]) -> Option<&'a mut Value> {
+    path.iter()
+        .try_fold(value, |current, key| current.get_mut(key))
+}
+
+fn remove_path(value: &mut Value, path: &[String]) -> Option<Value> {
+    let (key, parent_path) = path.split_last()?;
+    get_path_mut(value, parent_path)?
+        .as_object_mut()?
+        .remove(key)
+}
+
+fn join_path(path: &[String]) -> String {
+    path.join(".")
+}
+
+fn preview(value: &Value) -> String {
+    let text = value.to_string();
+    if text.chars().count() <= MAX_PREVIEW_CHARS {
+        return text;
+    }
+    let head: String = text.chars().take(MAX_PREVIEW_CHARS).collect();
+    format!("{head}…")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    enum Level {
+        #[default]
+        Info,
+        Debug,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Entry {
+        url: String,
+        #[serde(default)]
+        level: Level,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Store {
+        version: String,
+        #[serde(default)]
+        level: Level,
+        #[serde(default)]
+        entries: HashMap<String, Entry>,
+    }
+
+    fn newer_file() -> Value {
+        json!({
+            "version": "9.0.0",
+            "level": "trace",
+            "new_flag": true,
+            "entries": {
+                "a": { "url": "https://a", "level": "verbose", "retries": 3 },
+                "b": { "level": "debug" }
+            }
+        })
+    }
+
+    #[test]
+    fn test_tolerant_parse_drops_incompatible_fields() {
+        assert!(serde_json::from_value::<Store>(newer_file()).is_err());
+
+        let (store, dropped) = from_value_tolerant::<Store>(newer_file()).unwrap();
+        assert_eq!(store.level, Level::Info);
+        assert_eq!(store.entries["a"].level, Level::Info);
+        assert!(!store.entries.contains_key("b"));
+        let dropped: Vec<String> = dropped.iter().map(|p| join_path(p)).collect();
+        assert!(dropped.contains(&"level".to_string()));
+        assert!(dropped.contains(&"entries.a.level".to_string()));
+        assert!(dropped.contains(&"entries.b".to_string()));
+
+        // 根对象缺少必填字段时仍返回错误
+        assert!(from_value_tolerant::<Store>(json!({ "level": "debug" })).is_err());
+    }
+
+    #[test]
+    fn test_preserve_fields_round_trip() {
+        let existing = newer_file();
+        let (mut store, _) = from_value_tolerant::<Store>(existing.clone()).unwrap();
+        store.version = "1.0.0".to_string();
+        store.entries.get_mut("a").unwrap().url = "https://changed".to_string();
+        let mut new_value = serde_json::to_value(&store).unwrap();
+
+        let preserved = preserve_fields::<Store>(&existing, &mut new_value);
+        assert_eq!(preserved, 5);
+        assert_eq!(new_value["version"], "1.0.0");
+        assert_eq!(new_value["level"], "trace");
+        assert_eq!(new_value["new_flag"], true);
+        assert_eq!(new_value["entries"]["a"]["url"], "https://changed");
+        assert_eq!(new_value["entries"]["a"]["level"], "verbose");
+        assert_eq!(new_value["entries"]["a"]["retries"], 3);
+        assert_eq!(new_value["entries"]["b"]["level"], "debug");
+
+        // 用户显式修改过的无法解析字段不再覆盖
+        let mut edited = serde_json::to_value(&store).unwrap();
+        edited["level"] = json!("debug");
+        preserve_fields::<Store>(&existing, &mut edited);
+        assert_eq!(edited["level"], "debug");
+    }
+
+    #[test]
+    fn test_inspect_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.json");
+        std::fs::write(&path, newer_file().to_string()).unwrap();
+
+        let report = inspect_file::<Store>("store.json", &path);
+        assert!(report.exists && report.error.is_none());
+        assert_eq!(report.file_version.as_deref(), Some("9.0.0"));
+        let find = |p: &str| report.issues.iter().find(|i| i.path == p).map(|i| i.kind);
+        assert_eq!(find("new_flag"), Some(CompatIssueKind::Unknown));
+        assert_eq!(find("entries.a.retries"), Some(CompatIssueKind::Unknown));
+        assert_eq!(find("level"), Some(CompatIssueKind::Incompatible));
+        assert_eq!(find("entries.b"), Some(CompatIssueKind::Incompatible));
+        assert_eq!(find("entries.b.level"), None);
+
+        assert!(!inspect_file::<Store>("missing", &dir.path().join("none.json")).exists);
+    }
+}
@@ -32,6 +32,15 @@ pub enum DataError {
     #[error("数据库错误: {0}")]
     Database(#[from] rusqlite::Error),
 
+    /// Schema 迁移步骤执行失败
+    #[error("数据库迁移 v{version} ({name}) 失败: {source}")]
+    SchemaMigration {
+        version: u32,
+        name: &'static str,
+        #[source]
+        source: rusqlite::Error,
+    },
+
     /// 资源未找到
     #[error("未找到资源: {0}")]
     NotFound(String),
@@ -51,6 +60,10 @@ pub enum DataError {
     /// 无效的键路径
     #[error("无效的键路径: {0}")]
     InvalidKey(String),
+
+    /// 只读观察模式下拒绝写入工具配置
+    #[error("只读观察模式下禁止写入工具配置: {}", .0.display())]
+    ReadOnly(PathBuf),
 }
 
 /// 便于与现有代码集成的类型别名
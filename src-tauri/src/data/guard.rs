@@ -0,0 +1,82 @@
+//! 只读观察模式写入守卫
+//!
+//! 观察模式下 DuckCoding 仍统计用量、检测配置变更，但绝不写入任何工具配置文件。
+//! 所有格式管理器在写入/删除前统一经过 [`ensure_writable`] 检查，
+//! 业务代码无需各自判断。
+//!
+//! 受保护的路径：
+//! - 各工具的全局配置目录（已解析目录覆盖与环境变量）
+//! - 项目级工具配置（父目录为 `.claude`/`.codex`/`.gemini` 的文件）
+
+use super::error::{DataError, Result};
+use crate::models::Tool;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// 观察模式开关（启动时按全局配置设置）
+static OBSERVER_MODE: AtomicBool = AtomicBool::new(false);
+
+/// 项目级工具配置目录名
+const PROJECT_CONFIG_DIRS: [&str; 3] = [".claude", ".codex", ".gemini"];
+
+/// 设置观察模式
+pub fn set_observer_mode(enabled: bool) {
+    let previous = OBSERVER_MODE.swap(enabled, Ordering::SeqCst);
+    if previous != enabled {
+        tracing::info!(enabled, "只读观察模式已切换");
+    }
+}
+
+/// 是否处于观察模式
+pub fn is_observer_mode() -> bool {
+    OBSERVER_MODE.load(Ordering::SeqCst)
+}
+
+/// 路径是否属于工具配置（不区分是否处于观察模式）
+pub fn is_tool_config_path(path: &Path) -> bool {
+    let in_project_dir = path
+        .parent()
+        .and_then(|parent| parent.file_name())
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| PROJECT_CONFIG_DIRS.contains(&name));
+    in_project_dir
+        || Tool::all()
+            .iter()
+            .any(|tool| path.starts_with(&tool.config_dir))
+}
+
+/// 写入前检查：观察模式下拒绝写入工具配置
+pub fn ensure_writable(path: &Path) -> Result<()> {
+    check_writable(is_observer_mode(), path)
+}
+
+fn check_writable(observer_mode: bool, path: &Path) -> Result<()> {
+    if observer_mode && is_tool_config_path(path) {
+        tracing::warn!(path = %path.display(), "只读观察模式下拒绝写入工具配置");
+        return Err(DataError::ReadOnly(path.to_path_buf()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_observer_mode_blocks_tool_config_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let project_settings = dir.path().join(".claude").join("settings.local.json");
+        let project_env = dir.path().join(".gemini").join(".env");
+        let own_file = dir.path().join("stats.json");
+        let tool_file = Tool::codex().config_dir.join("config.toml");
+
+        for path in [&project_settings, &project_env, &tool_file] {
+            assert!(matches!(
+                check_writable(true, path),
+                Err(DataError::ReadOnly(_))
+            ));
+            assert!(check_writable(false, path).is_ok());
+        }
+        assert!(check_writable(true, &own_file).is_ok());
+    }
+}
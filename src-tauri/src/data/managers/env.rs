@@ -86,6 +86,8 @@ impl EnvManager {
     /// - `path`: 文件路径
     /// - `pairs`: 键值对映射
     pub fn write(&self, path: &Path, pairs: &HashMap<String, String>) -> Result<()> {
+        crate::data::guard::ensure_writable(path)?;
+
         // 创建父目录
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent).map_err(|e| DataError::io(parent.to_path_buf(), e))?;
@@ -135,6 +137,8 @@ impl EnvManager {
     /// - `key`: 键名
     /// - `value`: 值
     pub fn set(&self, path: &Path, key: &str, value: &str) -> Result<()> {
+        crate::data::guard::ensure_writable(path)?;
+
         let mut lines = if path.exists() {
             fs::read_to_string(path)
                 .map_err(|e| DataError::io(path.to_path_buf(), e))?
@@ -201,6 +205,8 @@ impl EnvManager {
     /// - `path`: 文件路径
     /// - `key`: 键名
     pub fn delete(&self, path: &Path, key: &str) -> Result<()> {
+        crate::data::guard::ensure_writable(path)?;
+
         let lines = fs::read_to_string(path)
             .map_err(|e| DataError::io(path.to_path_buf(), e))?
             .lines()
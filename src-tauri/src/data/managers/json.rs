@@ -119,6 +119,8 @@ impl JsonManager {
     /// - `path`: 文件路径
     /// - `value`: JSON 值
     pub fn write(&self, path: &Path, value: &Value) -> Result<()> {
+        crate::data::guard::ensure_writable(path)?;
+
         // 创建父目录
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent).map_err(|e| DataError::io(parent.to_path_buf(), e))?;
@@ -242,6 +244,7 @@ impl JsonManager {
             self.write(path, &value)
         } else {
             // 删除整个文件
+            crate::data::guard::ensure_writable(path)?;
             fs::remove_file(path).map_err(|e| DataError::io(path.to_path_buf(), e))?;
 
             // 使缓存失效
@@ -1,7 +1,8 @@
 //! SQLite 数据库管理器
 //!
 //! 提供 SQLite 数据库的统一管理接口，支持：
-//! - 连接池管理（单连接 + Arc<Mutex>）
+//! - 连接池管理（单连接 + Arc<Mutex>，按路径共享）
+//! - 预编译语句缓存（热路径 SQL 只解析一次）
 //! - 查询缓存（集成 SqlQueryCache）
 //! - 事务支持
 //! - 自动表依赖追踪
@@ -36,6 +37,7 @@
 //! ```
 
 use crate::data::cache::{extract_tables, QueryKey, SqlQueryCache};
+use crate::data::schema::{self, SchemaMigration, SchemaMigrationReport};
 use crate::data::{DataError, Result};
 use rusqlite::{params_from_iter, Connection, Row, Transaction};
 use serde::{Deserialize, Serialize};
@@ -43,6 +45,11 @@ use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+/// 每个连接缓存的预编译语句数量
+///
+/// rusqlite 默认仅 16 条，统计查询与插入语句较多，适当放大避免频繁淘汰
+const STATEMENT_CACHE_CAPACITY: usize = 64;
+
 /// SQLite 管理器
 ///
 /// 支持带缓存和无缓存两种模式。
@@ -113,7 +120,9 @@ impl SqliteManager {
             std::fs::create_dir_all(parent).map_err(|e| DataError::io(parent.to_path_buf(), e))?;
         }
 
-        Connection::open(path).map_err(DataError::Database)
+        let conn = Connection::open(path).map_err(DataError::Database)?;
+        conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
+        Ok(conn)
     }
 
     /// 执行查询（返回通用行格式）
@@ -153,7 +162,7 @@ impl SqliteManager {
             .lock()
             .map_err(|e| DataError::Concurrency(e.to_string()))?;
 
-        let mut stmt = conn.prepare(sql).map_err(DataError::Database)?;
+        let mut stmt = conn.prepare_cached(sql).map_err(DataError::Database)?;
 
         // 获取列名
         let column_names: Vec<String> = stmt.column_names().iter().map(|s| s.to_string()).collect();
@@ -251,18 +260,41 @@ impl SqliteManager {
             .map_err(|e| DataError::Concurrency(e.to_string()))?;
 
         let affected = conn
-            .execute(sql, params_from_iter(params.iter()))
+            .prepare_cached(sql)
+            .and_then(|mut stmt| stmt.execute(params_from_iter(params.iter())))
+            .map_err(DataError::Database)?;
+
+        self.invalidate_tables(sql);
+
+        Ok(affected)
+    }
+
+    /// 执行插入并返回新行的 rowid（自动失效缓存）
+    ///
+    /// 与 `execute` 在同一次加锁内读取 `last_insert_rowid`，并发写入时不会取到其他插入的 ID
+    pub fn insert(&self, sql: &str, params: &[&str]) -> Result<i64> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| DataError::Concurrency(e.to_string()))?;
+
+        conn.prepare_cached(sql)
+            .and_then(|mut stmt| stmt.execute(params_from_iter(params.iter())))
             .map_err(DataError::Database)?;
+        let id = conn.last_insert_rowid();
+
+        self.invalidate_tables(sql);
+
+        Ok(id)
+    }
 
-        // 失效相关表的缓存
+    /// 失效 SQL 涉及表的查询缓存
+    fn invalidate_tables(&self, sql: &str) {
         if let Some(cache) = &self.cache {
-            let tables = extract_tables(sql);
-            for table in &tables {
+            for table in &extract_tables(sql) {
                 cache.invalidate_table(table);
             }
         }
-
-        Ok(affected)
     }
 
     /// 执行批量更新
@@ -281,22 +313,17 @@ impl SqliteManager {
             .lock()
             .map_err(|e| DataError::Concurrency(e.to_string()))?;
 
-        let mut results = Vec::new();
+        let mut stmt = conn.prepare_cached(sql).map_err(DataError::Database)?;
+        let mut results = Vec::with_capacity(params_list.len());
         for params in params_list {
-            let param_refs: Vec<&str> = params.iter().map(|s| s.as_str()).collect();
-            let affected = conn
-                .execute(sql, params_from_iter(param_refs.iter()))
+            let affected = stmt
+                .execute(params_from_iter(params.iter()))
                 .map_err(DataError::Database)?;
             results.push(affected);
         }
+        drop(stmt);
 
-        // 失效相关表的缓存
-        if let Some(cache) = &self.cache {
-            let tables = extract_tables(sql);
-            for table in &tables {
-                cache.invalidate_table(table);
-            }
-        }
+        self.invalidate_tables(sql);
 
         Ok(results)
     }
@@ -380,6 +407,23 @@ impl SqliteManager {
         Ok(())
     }
 
+    /// 执行 Schema 迁移（见 [`crate::data::schema`]）
+    pub fn migrate(&self, migrations: &[SchemaMigration]) -> Result<SchemaMigrationReport> {
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|e| DataError::Concurrency(e.to_string()))?;
+
+        let report = schema::migrate(&mut conn, &self.db_path, migrations)?;
+
+        // 清空所有缓存（表结构可能已变化）
+        if let Some(cache) = &self.cache {
+            cache.clear();
+        }
+
+        Ok(report)
+    }
+
     /// 清空缓存
     pub fn clear_cache(&self) {
         if let Some(cache) = &self.cache {
@@ -593,6 +637,23 @@ mod tests {
         assert_eq!(rows.len(), 3);
     }
 
+    #[test]
+    fn test_insert_returns_rowid_and_invalidates_cache() {
+        let (_temp_dir, manager) = create_test_db();
+        let count_sql = "SELECT COUNT(*) FROM users";
+        assert_eq!(manager.query(count_sql, &[]).unwrap()[0].values[0], 0);
+
+        // 重复执行同一插入语句（命中预编译语句缓存）
+        for (i, name) in ["Alice", "Bob"].iter().enumerate() {
+            let id = manager
+                .insert("INSERT INTO users (name, age) VALUES (?, ?)", &[name, "20"])
+                .unwrap();
+            assert_eq!(id, i as i64 + 1);
+        }
+
+        assert_eq!(manager.query(count_sql, &[]).unwrap()[0].values[0], 2);
+    }
+
     #[test]
     fn test_table_exists() {
         let (_temp_dir, manager) = create_test_db();
@@ -77,6 +77,8 @@ impl TomlManager {
     /// - `path`: 文件路径
     /// - `doc`: TOML 文档
     pub fn write(&self, path: &Path, doc: &DocumentMut) -> Result<()> {
+        crate::data::guard::ensure_writable(path)?;
+
         // 创建父目录
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent).map_err(|e| DataError::io(parent.to_path_buf(), e))?;
@@ -8,6 +8,9 @@
 //! - `cache`: 缓存层实现（LRU + 文件校验和 + SQL 查询缓存）
 //! - `managers`: 各格式管理器（JSON/TOML/ENV/SQLite）
 //! - `manager`: 统一入口 `DataManager`
+//! - `compat`: 跨版本配置兼容（容错解析、保留未知字段）
+//! - `guard`: 只读观察模式写入守卫
+//! - `schema`: SQLite Schema 版本迁移（版本记录、有序步骤、迁移前备份）
 //!
 //! # 使用示例
 //!
@@ -27,9 +30,12 @@
 
 pub mod cache;
 pub mod changelogs;
+pub mod compat;
 pub mod error;
+pub mod guard;
 pub mod manager;
 pub mod managers;
+pub mod schema;
 pub mod snapshots;
 
 #[cfg(test)]
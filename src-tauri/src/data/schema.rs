@@ -0,0 +1,288 @@
+//! SQLite Schema 版本迁移
+//!
+//! 每个数据库通过 `schema_version` 表记录已执行的迁移步骤：
+//! - 迁移步骤按版本号升序执行，每步在独立事务中运行并写入版本记录
+//! - 已有数据的数据库在执行待处理迁移前先备份为 `<文件名>.bak-v<当前版本>`
+//! - 数据库版本高于程序已知的最新版本（降级运行）时跳过迁移并记录警告
+//!
+//! # 使用示例
+//!
+//! ```rust
+//! use crate::data::schema::{add_column_if_missing, SchemaMigration};
+//!
+//! const MIGRATIONS: &[SchemaMigration] = &[
+//!     SchemaMigration {
+//!         version: 1,
+//!         name: "create_users",
+//!         up: |tx| tx.execute_batch("CREATE TABLE IF NOT EXISTS users (id INTEGER PRIMARY KEY)"),
+//!     },
+//!     SchemaMigration {
+//!         version: 2,
+//!         name: "add_user_name",
+//!         up: |tx| add_column_if_missing(tx, "users", "name", "TEXT").map(|_| ()),
+//!     },
+//! ];
+//!
+//! manager.migrate(MIGRATIONS)?;
+//! ```
+
+use crate::data::{DataError, Result};
+use rusqlite::{Connection, Transaction};
+use std::path::{Path, PathBuf};
+
+/// 版本记录表
+const SCHEMA_VERSION_TABLE_SQL: &str = "CREATE TABLE IF NOT EXISTS schema_version (
+    version INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    applied_at INTEGER NOT NULL
+)";
+
+/// 单个迁移步骤
+pub struct SchemaMigration {
+    /// 版本号（从 1 开始严格递增）
+    pub version: u32,
+    /// 步骤名称（写入版本记录，便于排查）
+    pub name: &'static str,
+    /// 迁移逻辑（在事务中执行，失败时整步回滚）
+    pub up: fn(&Transaction) -> rusqlite::Result<()>,
+}
+
+/// 迁移执行结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemaMigrationReport {
+    /// 迁移前的版本（0 表示未纳入版本管理的旧库或新库）
+    pub from_version: u32,
+    /// 迁移后的版本
+    pub to_version: u32,
+    /// 本次执行的步骤名称
+    pub applied: Vec<&'static str>,
+    /// 迁移前的备份文件
+    pub backup_path: Option<PathBuf>,
+}
+
+/// 读取数据库当前的 Schema 版本
+pub fn current_version(conn: &Connection) -> Result<u32> {
+    conn.execute_batch(SCHEMA_VERSION_TABLE_SQL)?;
+    let version: u32 = conn.query_row(
+        "SELECT COALESCE(MAX(version), 0) FROM schema_version",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(version)
+}
+
+/// 执行待处理的迁移步骤
+///
+/// `db_path` 为数据库文件路径，用于生成备份文件（内存数据库不备份）
+pub fn migrate(
+    conn: &mut Connection,
+    db_path: &Path,
+    migrations: &[SchemaMigration],
+) -> Result<SchemaMigrationReport> {
+    debug_assert!(
+        migrations.windows(2).all(|w| w[0].version < w[1].version),
+        "迁移步骤必须按版本号严格递增"
+    );
+
+    let from_version = current_version(conn)?;
+    let mut report = SchemaMigrationReport {
+        from_version,
+        to_version: from_version,
+        ..Default::default()
+    };
+
+    let latest = migrations.last().map(|m| m.version).unwrap_or(0);
+    if from_version > latest {
+        tracing::warn!(
+            db = %db_path.display(),
+            db_version = from_version,
+            known_version = latest,
+            "数据库版本高于当前程序支持的版本，跳过迁移"
+        );
+        return Ok(report);
+    }
+
+    let pending: Vec<&SchemaMigration> = migrations
+        .iter()
+        .filter(|m| m.version > from_version)
+        .collect();
+    if pending.is_empty() {
+        return Ok(report);
+    }
+
+    if has_user_tables(conn)? {
+        report.backup_path = backup(conn, db_path, from_version)?;
+    }
+
+    for migration in pending {
+        let tx = conn.transaction()?;
+        (migration.up)(&tx).map_err(|source| DataError::SchemaMigration {
+            version: migration.version,
+            name: migration.name,
+            source,
+        })?;
+        tx.execute(
+            "INSERT INTO schema_version (version, name, applied_at) VALUES (?1, ?2, ?3)",
+            rusqlite::params![
+                migration.version,
+                migration.name,
+                chrono::Utc::now().timestamp_millis()
+            ],
+        )?;
+        tx.commit()?;
+
+        report.to_version = migration.version;
+        report.applied.push(migration.name);
+    }
+
+    tracing::info!(
+        db = %db_path.display(),
+        from = report.from_version,
+        to = report.to_version,
+        steps = ?report.applied,
+        "数据库 Schema 迁移完成"
+    );
+
+    Ok(report)
+}
+
+/// 列不存在时添加（兼容未纳入版本管理的旧库），返回是否新增
+pub fn add_column_if_missing(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> rusqlite::Result<bool> {
+    let exists: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info(?1) WHERE name = ?2",
+        [table, column],
+        |row| row.get(0),
+    )?;
+    if exists {
+        return Ok(false);
+    }
+    conn.execute_batch(&format!(
+        "ALTER TABLE {table} ADD COLUMN {column} {definition}"
+    ))?;
+    Ok(true)
+}
+
+/// 数据库中是否已有业务表（新建的空库无需备份）
+fn has_user_tables(conn: &Connection) -> Result<bool> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master
+         WHERE type = 'table' AND name NOT LIKE 'sqlite_%' AND name != 'schema_version'",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+/// 将数据库完整备份到 `<文件名>.bak-v<版本>`（VACUUM INTO 包含 WAL 中尚未回写的数据）
+fn backup(conn: &Connection, db_path: &Path, version: u32) -> Result<Option<PathBuf>> {
+    let Some(file_name) = db_path.file_name().filter(|_| db_path.exists()) else {
+        return Ok(None);
+    };
+    let backup_path =
+        db_path.with_file_name(format!("{}.bak-v{}", file_name.to_string_lossy(), version));
+    if backup_path.exists() {
+        std::fs::remove_file(&backup_path).map_err(|e| DataError::io(&backup_path, e))?;
+    }
+    conn.execute("VACUUM INTO ?1", [backup_path.to_string_lossy().as_ref()])?;
+    tracing::info!(backup = %backup_path.display(), "迁移前已备份数据库");
+    Ok(Some(backup_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIGRATIONS: &[SchemaMigration] = &[
+        SchemaMigration {
+            version: 1,
+            name: "create_items",
+            up: |tx| tx.execute_batch("CREATE TABLE IF NOT EXISTS items (id INTEGER PRIMARY KEY)"),
+        },
+        SchemaMigration {
+            version: 2,
+            name: "add_item_price",
+            up: |tx| {
+                add_column_if_missing(tx, "items", "price", "REAL NOT NULL DEFAULT 0").map(|_| ())
+            },
+        },
+    ];
+
+    #[test]
+    fn test_migrate_fresh_db_without_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fresh.db");
+        let mut conn = Connection::open(&path).unwrap();
+
+        let report = migrate(&mut conn, &path, MIGRATIONS).unwrap();
+        assert_eq!(report.from_version, 0);
+        assert_eq!(report.to_version, 2);
+        assert_eq!(report.applied, vec!["create_items", "add_item_price"]);
+        assert!(report.backup_path.is_none());
+
+        // 再次执行无待处理步骤
+        let report = migrate(&mut conn, &path, MIGRATIONS).unwrap();
+        assert!(report.applied.is_empty());
+        assert_eq!(current_version(&conn).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_migrate_legacy_db_backs_up_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("legacy.db");
+        let mut conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE items (id INTEGER PRIMARY KEY); INSERT INTO items (id) VALUES (7);",
+        )
+        .unwrap();
+
+        let report = migrate(&mut conn, &path, MIGRATIONS).unwrap();
+        assert_eq!(report.to_version, 2);
+        let backup_path = report.backup_path.unwrap();
+        assert_eq!(backup_path, dir.path().join("legacy.db.bak-v0"));
+
+        // 备份保留旧结构，原库已补齐新列
+        let backup = Connection::open(&backup_path).unwrap();
+        assert!(!column_exists(&backup, "price"));
+        assert!(column_exists(&conn, "price"));
+        let price: f64 = conn
+            .query_row("SELECT price FROM items WHERE id = 7", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(price, 0.0);
+    }
+
+    #[test]
+    fn test_migrate_skips_newer_db_and_rolls_back_failed_step() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("newer.db");
+        let mut conn = Connection::open(&path).unwrap();
+        migrate(&mut conn, &path, MIGRATIONS).unwrap();
+
+        // 旧版本程序只知道 v1：保持数据库不变
+        let report = migrate(&mut conn, &path, &MIGRATIONS[..1]).unwrap();
+        assert_eq!(report.to_version, 2);
+        assert!(report.applied.is_empty());
+
+        let broken = [SchemaMigration {
+            version: 3,
+            name: "broken",
+            up: |tx| tx.execute_batch("ALTER TABLE missing ADD COLUMN x TEXT"),
+        }];
+        let err = migrate(&mut conn, &path, &broken).unwrap_err();
+        assert!(matches!(err, DataError::SchemaMigration { version: 3, .. }));
+        assert_eq!(current_version(&conn).unwrap(), 2);
+    }
+
+    fn column_exists(conn: &Connection, column: &str) -> bool {
+        conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('items') WHERE name = ?1",
+            [column],
+            |row| row.get(0),
+        )
+        .unwrap()
+    }
+}
@@ -1,11 +1,13 @@
 // lib.rs - 暴露服务层给 CLI 和 GUI 使用
 
+pub mod cli; // 命令行子命令（无界面模式）
 pub mod core; // 🆕 核心基础设施层
 pub mod data; // 🆕 统一数据管理层
 pub mod http_client;
 pub mod models;
 pub mod services;
-pub mod ui; // 🆕 UI 管理层
+#[cfg(feature = "gui")]
+pub mod ui; // 🆕 UI 管理层（仅 GUI 构建）
 pub mod utils;
 
 pub use models::*;
@@ -49,6 +51,7 @@ pub use core::{
 };
 
 // 🆕 导出 UI 管理层
+#[cfg(feature = "gui")]
 pub use ui::{
     // 托盘管理
     create_tray_menu,
@@ -64,19 +67,56 @@ pub use ui::{
     SINGLE_INSTANCE_EVENT,
 };
 
+/// 端口被占用时自启动的重试次数（上一个实例退出或端口释放可能需要几秒）
+const AUTO_START_ATTEMPTS: u32 = 5;
+
+/// 自启动重试间隔
+const AUTO_START_RETRY_DELAY: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// 未能自启动的工具
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AutoStartIssue {
+    pub tool_id: String,
+    pub reason: String,
+}
+
+/// 代理自启动结果（`proxy-auto-start-completed` 事件载荷）
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct AutoStartReport {
+    /// 启动成功的工具
+    pub started: Vec<String>,
+    /// 启动失败的工具（重试后端口仍被占用等）
+    pub failed: Vec<AutoStartIssue>,
+    /// 配置了自启动但缺少前置条件而跳过的工具
+    pub skipped: Vec<AutoStartIssue>,
+}
+
+impl AutoStartReport {
+    pub fn is_empty(&self) -> bool {
+        self.started.is_empty() && self.failed.is_empty() && self.skipped.is_empty()
+    }
+}
+
 /// 应用启动时自动启动符合条件的透明代理
 ///
-/// 条件：`enabled: true` 且 `auto_start: true`
-pub async fn auto_start_proxies(manager: &ProxyManager) {
+/// 条件：`enabled: true` 且 `auto_start: true`，并已设置保护密钥与上游（与手动启动相同）；
+/// 端口被占用时按间隔重试，仍失败则记入结果。
+pub async fn auto_start_proxies(manager: &ProxyManager) -> AutoStartReport {
     use services::proxy_config_manager::ProxyConfigManager;
 
+    let mut report = AutoStartReport::default();
+    if data::guard::is_observer_mode() {
+        tracing::info!("只读观察模式下跳过透明代理自启动");
+        return report;
+    }
+
     tracing::info!("检查透明代理自启动配置");
 
     let proxy_mgr = match ProxyConfigManager::new() {
         Ok(mgr) => mgr,
         Err(e) => {
             tracing::error!(error = ?e, "创建 ProxyConfigManager 失败");
-            return;
+            return report;
         }
     };
 
@@ -84,14 +124,12 @@ pub async fn auto_start_proxies(manager: &ProxyManager) {
         Ok(store) => store,
         Err(e) => {
             tracing::error!(error = ?e, "读取代理配置失败");
-            return;
+            return report;
         }
     };
 
-    let mut started_count = 0;
-    let mut failed_count = 0;
-
-    for tool_id in &["claude-code", "codex", "gemini-cli", "amp-code"] {
+    for tool_id in proxy_store.tool_ids() {
+        let tool_id = tool_id.as_str();
         let tool_config = match proxy_store.get_config(tool_id) {
             Some(cfg) => cfg.clone(),
             None => continue,
@@ -101,32 +139,112 @@ pub async fn auto_start_proxies(manager: &ProxyManager) {
             continue;
         }
 
-        if tool_config.local_api_key.is_none() {
-            tracing::warn!(tool_id = %tool_id, "未配置保护密钥，跳过自启动");
+        if let Some(reason) = auto_start_skip_reason(tool_id, &tool_config) {
+            tracing::warn!(tool_id = %tool_id, reason = %reason, "跳过代理自启动");
+            report.skipped.push(AutoStartIssue {
+                tool_id: tool_id.to_string(),
+                reason: reason.to_string(),
+            });
             continue;
         }
 
         tracing::info!(tool_id = %tool_id, port = tool_config.port, "自动启动代理");
 
-        match manager.start_proxy(tool_id, tool_config).await {
-            Ok(_) => {
-                started_count += 1;
-                tracing::info!(tool_id = %tool_id, "代理启动成功");
-            }
-            Err(e) => {
-                failed_count += 1;
-                tracing::error!(tool_id = %tool_id, error = ?e, "代理启动失败");
+        let mut attempt = 1;
+        loop {
+            match manager.start_proxy(tool_id, tool_config.clone()).await {
+                Ok(_) => {
+                    tracing::info!(tool_id = %tool_id, "代理启动成功");
+                    report.started.push(tool_id.to_string());
+                    break;
+                }
+                Err(e) if is_addr_in_use(&e) && attempt < AUTO_START_ATTEMPTS => {
+                    tracing::warn!(
+                        tool_id = %tool_id,
+                        port = tool_config.port,
+                        attempt = attempt,
+                        "端口被占用，稍后重试自启动"
+                    );
+                    attempt += 1;
+                    tokio::time::sleep(AUTO_START_RETRY_DELAY).await;
+                }
+                Err(e) => {
+                    tracing::error!(tool_id = %tool_id, error = ?e, "代理启动失败");
+                    report.failed.push(AutoStartIssue {
+                        tool_id: tool_id.to_string(),
+                        reason: format!("{:#}", e),
+                    });
+                    break;
+                }
             }
         }
     }
 
-    if started_count > 0 || failed_count > 0 {
+    if report.is_empty() {
+        tracing::debug!("没有配置自启动的代理");
+    } else {
         tracing::info!(
-            started = started_count,
-            failed = failed_count,
+            started = report.started.len(),
+            failed = report.failed.len(),
+            skipped = report.skipped.len(),
             "自启动代理完成"
         );
-    } else {
-        tracing::debug!("没有配置自启动的代理");
+    }
+    report
+}
+
+/// 自启动的前置条件（与界面手动启动的校验一致），不满足时返回原因
+fn auto_start_skip_reason(
+    tool_id: &str,
+    config: &models::proxy_config::ToolProxyConfig,
+) -> Option<&'static str> {
+    if config.local_api_key.is_none() {
+        return Some("未配置保护密钥");
+    }
+    // amp-code 按 Profile 选择动态路由，不使用 real_api_key/real_base_url
+    if tool_id != "amp-code" && (config.real_api_key.is_none() || config.real_base_url.is_none()) {
+        return Some("真实 API Key 或 Base URL 未设置");
+    }
+    None
+}
+
+/// 启动失败是否由于端口已被占用
+fn is_addr_in_use(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        cause
+            .downcast_ref::<std::io::Error>()
+            .is_some_and(|e| e.kind() == std::io::ErrorKind::AddrInUse)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_addr_in_use() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let bind_error = std::net::TcpListener::bind(("127.0.0.1", port)).unwrap_err();
+        let error = anyhow::Error::new(bind_error)
+            .context(format!("绑定端口 {} 失败", port))
+            .context("启动 codex 代理失败");
+        assert!(is_addr_in_use(&error));
+        assert!(!is_addr_in_use(&anyhow::anyhow!("透明代理保护密钥未设置")));
+    }
+
+    #[test]
+    fn test_auto_start_skip_reason() {
+        let mut config = models::proxy_config::ToolProxyConfig::new(8787);
+        assert_eq!(
+            auto_start_skip_reason("codex", &config),
+            Some("未配置保护密钥")
+        );
+        config.local_api_key = Some("local".to_string());
+        assert!(auto_start_skip_reason("codex", &config).is_some());
+        assert_eq!(auto_start_skip_reason("amp-code", &config), None);
+        config.real_api_key = Some("sk-real".to_string());
+        config.real_base_url = Some("https://api.example.com".to_string());
+        assert_eq!(auto_start_skip_reason("codex", &config), None);
     }
 }
@@ -1,10 +1,13 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+use duckcoding::models::config::StartupMode;
 use duckcoding::services::proxy::config::apply_global_proxy;
+use duckcoding::utils::auto_startup::resolve_startup_mode;
 use duckcoding::utils::config::read_global_config;
 use serde::Serialize;
 use std::env;
+use std::process::ExitCode;
 use tauri::{AppHandle, Emitter, Manager};
 
 // 导入 commands 模块
@@ -128,8 +131,180 @@ fn schedule_update_check(app_handle: AppHandle) {
     });
 }
 
+/// 将消费预算阈值提醒转发为前端事件
+fn forward_budget_events(app_handle: AppHandle) {
+    use duckcoding::services::token_stats::BudgetTracker;
+
+    BudgetTracker::global().set_notifier(Box::new(move |event| {
+        if let Err(e) = app_handle.emit("budget-threshold-reached", &event) {
+            tracing::error!(error = ?e, "发送预算提醒事件失败");
+        }
+    }));
+}
+
+/// 将成本预检的待确认请求转发为前端事件
+fn forward_cost_guard_events(app_handle: AppHandle) {
+    use duckcoding::services::proxy::cost_guard::CostGuard;
+
+    CostGuard::global().set_notifier(Box::new(move |event| {
+        if let Err(e) = app_handle.emit("cost-guard-confirmation", &event) {
+            tracing::error!(error = ?e, "发送成本确认事件失败");
+        }
+    }));
+}
+
+/// 将会话上下文占用提醒转发为前端事件
+fn forward_context_usage_events(app_handle: AppHandle) {
+    use duckcoding::services::session::context_usage::ContextUsageTracker;
+
+    ContextUsageTracker::global().set_notifier(Box::new(move |event| {
+        if let Err(e) = app_handle.emit("session-context-warning", &event) {
+            tracing::error!(error = ?e, "发送上下文占用提醒事件失败");
+        }
+    }));
+}
+
+/// 将用量异常提醒转发为前端事件
+fn forward_usage_anomaly_events(app_handle: AppHandle) {
+    use duckcoding::services::token_stats::AnomalyDetector;
+
+    AnomalyDetector::global().set_notifier(Box::new(move |event| {
+        if let Err(e) = app_handle.emit("usage-anomaly", &event) {
+            tracing::error!(error = ?e, "发送用量异常提醒事件失败");
+        }
+    }));
+}
+
+/// 将自动生成的用量报告转发为前端事件
+fn forward_usage_report_events(app_handle: AppHandle) {
+    use duckcoding::services::token_stats::ReportService;
+
+    ReportService::set_notifier(Box::new(move |event| {
+        if let Err(e) = app_handle.emit("usage-report-generated", &event) {
+            tracing::error!(error = ?e, "发送用量报告事件失败");
+        }
+    }));
+}
+
+/// 将流式请求的实时 Token 用量转发为前端事件
+fn forward_token_usage_events(app_handle: AppHandle) {
+    use duckcoding::services::token_stats::TokenStatsManager;
+
+    TokenStatsManager::set_usage_notifier(Box::new(move |event| {
+        if let Err(e) = app_handle.emit("token-usage-delta", &event) {
+            tracing::error!(error = ?e, "发送实时用量事件失败");
+        }
+    }));
+}
+
+/// 将透明代理的 Profile 自动切换转发为前端事件
+fn forward_proxy_failover_events(app: &tauri::App) {
+    let app_handle = app.handle().clone();
+    app.state::<ProxyManagerState>()
+        .manager
+        .set_failover_notifier(Box::new(move |event| {
+            if let Err(e) = app_handle.emit("proxy-failover", &event) {
+                tracing::error!(error = ?e, "发送 Profile 自动切换事件失败");
+            }
+            // 菜单栏中的代理配置选中项随之更新
+            #[cfg(target_os = "macos")]
+            {
+                let app_handle = app_handle.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = setup::menu::refresh_app_menu_async(&app_handle).await {
+                        tracing::error!(error = ?e, "刷新菜单失败");
+                    }
+                });
+            }
+        }));
+}
+
+/// 启动供应商健康探测，并将可达性变化转发为前端事件
+fn start_provider_health_monitor(app_handle: AppHandle) {
+    use duckcoding::services::provider::ProviderHealthMonitor;
+
+    ProviderHealthMonitor::global().set_notifier(Box::new(move |event| {
+        if let Err(e) = app_handle.emit("provider-health-changed", &event) {
+            tracing::error!(error = ?e, "发送供应商健康事件失败");
+        }
+        // 菜单栏中标记不可达的 Profile
+        #[cfg(target_os = "macos")]
+        {
+            let app_handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = setup::menu::refresh_app_menu_async(&app_handle).await {
+                    tracing::error!(error = ?e, "刷新菜单失败");
+                }
+            });
+        }
+    }));
+
+    tauri::async_runtime::spawn(async {
+        ProviderHealthMonitor::global().run().await;
+    });
+}
+
+/// 启动余额聚合监控，并将低余额提醒转发为前端事件
+fn start_balance_monitor(app_handle: AppHandle) {
+    use duckcoding::services::balance::BalanceService;
+
+    BalanceService::global().set_notifier(Box::new(move |event| {
+        if let Err(e) = app_handle.emit("balance-low", &event) {
+            tracing::error!(error = ?e, "发送低余额提醒事件失败");
+        }
+    }));
+
+    tauri::async_runtime::spawn(async {
+        BalanceService::global().run().await;
+    });
+}
+
+/// 自动启动配置的代理，完成后更新托盘并通知前端
+fn auto_start_proxies(app: &tauri::App) {
+    let app_handle = app.handle().clone();
+    let manager = app.state::<ProxyManagerState>().manager.clone();
+    tauri::async_runtime::spawn(async move {
+        let report = duckcoding::auto_start_proxies(&manager).await;
+        if report.is_empty() {
+            return;
+        }
+
+        #[cfg(target_os = "macos")]
+        if let Err(e) = setup::menu::refresh_app_menu_async(&app_handle).await {
+            tracing::error!(error = ?e, "刷新菜单失败");
+        }
+        #[cfg(not(target_os = "macos"))]
+        setup::tray::update_tray_tooltip(&app_handle, &report.started);
+
+        if let Err(e) = app_handle.emit("proxy-auto-start-completed", &report) {
+            tracing::error!(error = ?e, "发送代理自启动事件失败");
+        }
+    });
+}
+
+/// 按启动方式创建主窗口：后台运行时不创建窗口，之后从托盘或再次启动应用时打开
+fn create_initial_window(app: &tauri::App, startup_mode: StartupMode) -> tauri::Result<()> {
+    tracing::info!(startup_mode = ?startup_mode, "启动方式");
+    match startup_mode {
+        StartupMode::Window => {
+            setup::tray::create_main_window(app.handle(), true)?;
+        }
+        StartupMode::Minimized => {
+            let window = setup::tray::create_main_window(app.handle(), false)?;
+            duckcoding::ui::hide_window_to_tray(&window);
+        }
+        StartupMode::Background => {
+            // macOS: 不显示 Dock 图标，仅保留菜单栏图标
+            #[cfg(target_os = "macos")]
+            app.handle()
+                .set_activation_policy(tauri::ActivationPolicy::Accessory)?;
+        }
+    }
+    Ok(())
+}
+
 /// 执行应用启动钩子（setup）
-fn setup_app_hooks(app: &mut tauri::App) -> tauri::Result<()> {
+fn setup_app_hooks(app: &mut tauri::App, startup_mode: StartupMode) -> tauri::Result<()> {
     // 1. 应用代理配置
     apply_global_proxy().ok();
 
@@ -143,8 +318,8 @@ fn setup_app_hooks(app: &mut tauri::App) -> tauri::Result<()> {
     #[cfg(not(target_os = "macos"))]
     setup::tray::setup_system_tray(app)?;
 
-    // 5. 处理窗口关闭事件（跨平台：关闭按钮最小化到托盘/状态栏而非退出）
-    setup::tray::setup_window_close_handler(app)?;
+    // 5. 按启动方式创建主窗口（关闭按钮最小化到托盘/状态栏而非退出）
+    create_initial_window(app, startup_mode)?;
 
     // 6. 创建应用菜单栏（仅 macOS）
     #[cfg(target_os = "macos")]
@@ -153,10 +328,50 @@ fn setup_app_hooks(app: &mut tauri::App) -> tauri::Result<()> {
     // 7. 启动后检查更新
     schedule_update_check(app.handle().clone());
 
+    // 8. 转发消费预算提醒
+    forward_budget_events(app.handle().clone());
+
+    // 9. 转发用量异常提醒
+    forward_usage_anomaly_events(app.handle().clone());
+
+    // 10. 启动供应商健康探测
+    start_provider_health_monitor(app.handle().clone());
+
+    // 11. 转发 Profile 自动切换事件
+    forward_proxy_failover_events(app);
+
+    // 12. 转发流式请求的实时用量
+    forward_token_usage_events(app.handle().clone());
+
+    // 13. 启动余额聚合监控
+    start_balance_monitor(app.handle().clone());
+
+    // 14. 转发自动生成的用量报告
+    forward_usage_report_events(app.handle().clone());
+
+    // 15. 转发成本预检的待确认请求
+    forward_cost_guard_events(app.handle().clone());
+
+    // 16. 转发会话上下文占用提醒
+    forward_context_usage_events(app.handle().clone());
+
+    // 17. 自动启动配置的代理（托盘创建之后，以便更新托盘状态）
+    auto_start_proxies(app);
+
     Ok(())
 }
 
-fn main() {
+fn main() -> ExitCode {
+    // 第一个参数为子命令（如 `duckcoding proxy status`）时以命令行模式运行，不启动界面
+    if duckcoding::cli::is_cli_invocation(env::args_os()) {
+        return tauri::async_runtime::block_on(duckcoding::cli::run_from_env());
+    }
+
+    run_gui();
+    ExitCode::SUCCESS
+}
+
+fn run_gui() {
     // 使用封装的初始化函数
     let init_ctx = tauri::async_runtime::block_on(async {
         setup::initialize_app().await.expect("应用初始化失败")
@@ -202,6 +417,16 @@ fn main() {
         });
     }
 
+    // 本次启动方式（由自启动项启动时按配置隐藏窗口或后台运行）
+    let startup_mode = resolve_startup_mode(
+        env::args_os(),
+        read_global_config()
+            .ok()
+            .flatten()
+            .map(|c| c.startup_mode)
+            .unwrap_or_default(),
+    );
+
     // 判断单实例模式
     let single_instance_enabled = determine_single_instance_mode();
 
@@ -219,8 +444,8 @@ fn main() {
         .manage(provider_manager_state)
         .manage(dashboard_manager_state)
         .manage(checkin_scheduler_state)
-        .setup(|app| {
-            setup_app_hooks(app)?;
+        .setup(move |app| {
+            setup_app_hooks(app, startup_mode)?;
             Ok(())
         })
         .plugin(tauri_plugin_shell::init())
@@ -259,12 +484,27 @@ fn main() {
         check_installations,
         refresh_tool_status,
         check_node_environment,
+        check_user_install_path,
+        fix_user_install_path,
         install_tool,
+        cancel_tool_install,
+        precheck_tool_install,
         check_update,
         check_update_for_instance,
         refresh_all_tool_versions,
         check_all_updates,
+        refresh_all_tool_status,
+        install_specific_version,
+        rollback_tool,
+        get_tool_version_history,
+        set_tool_version_pin,
+        list_custom_tools,
+        reload_custom_tool_definitions,
         update_tool_instance,
+        list_install_history,
+        get_install_attempt,
+        rerun_install_attempt,
+        clear_install_history,
         validate_tool_path,
         add_manual_tool_instance,
         scan_installer_for_tool_path,
@@ -275,6 +515,7 @@ fn main() {
         save_global_config,
         update_token_stats_config,
         get_global_config,
+        get_config_compat_report,
         generate_api_key_for_tool,
         // 使用统计
         get_usage_stats,
@@ -287,6 +528,7 @@ fn main() {
         update_balance_config,
         delete_balance_config,
         migrate_balance_from_localstorage,
+        get_all_balances,
         // 窗口管理
         handle_close_action,
         refresh_app_menu,
@@ -294,6 +536,7 @@ fn main() {
         get_current_proxy,
         apply_proxy_now,
         test_proxy_request,
+        probe_proxy_auth_bridge,
         // Claude Code 配置
         get_claude_settings,
         save_claude_settings,
@@ -314,6 +557,25 @@ fn main() {
         get_proxy_config,
         update_proxy_config,
         get_all_proxy_configs,
+        list_routing_rules,
+        save_routing_rule,
+        delete_routing_rule,
+        list_proxy_client_keys,
+        create_proxy_client_key,
+        revoke_proxy_client_key,
+        list_pending_cost_confirmations,
+        confirm_cost_guard_request,
+        get_body_capture_status,
+        clear_body_captures,
+        get_proxy_cache_stats,
+        clear_proxy_cache,
+        list_request_logs,
+        get_request_log,
+        replay_request,
+        run_replay,
+        start_provider_trial,
+        list_provider_trials,
+        stop_provider_trial,
         // AMP 用户认证命令
         get_amp_user_info,
         validate_and_save_amp_token,
@@ -324,20 +586,54 @@ fn main() {
         clear_all_sessions,
         update_session_config,
         update_session_note,
+        tag_session,
+        set_session_recording,
+        get_recording_sessions,
+        export_session_transcript,
+        get_session_context_usage,
         // Token统计命令
         get_session_stats,
         query_token_logs,
+        export_token_logs,
         cleanup_token_logs,
         get_token_stats_summary,
         force_token_stats_checkpoint,
+        maintain_token_stats_db,
+        get_budget_status,
+        get_redaction_stats,
         // Token统计分析命令（Phase 4）
         query_token_trends,
         query_cost_summary,
+        query_productivity_metrics,
+        query_tool_comparison,
+        get_model_breakdown,
+        query_latency_stats,
+        query_monthly_cost_report,
+        query_cache_efficiency,
+        query_anonymized_cost_summary,
+        anonymize_stats_payload,
+        run_sql_console_query,
+        get_sql_console_history,
+        clear_sql_console_history,
+        list_saved_reports,
+        save_report,
+        delete_saved_report,
+        run_saved_report,
+        export_saved_reports,
+        import_saved_reports,
+        generate_usage_report,
+        get_current_stats_epoch,
+        list_stats_epochs,
+        get_stats_epoch_summary,
+        start_new_stats_epoch,
         // 配置监听控制
         block_external_change,
         allow_external_change,
         get_watch_config,
         update_watch_config,
+        get_tool_config_dirs,
+        set_tool_config_dir,
+        simulate_external_config_change,
         // 配置守护管理
         update_sensitive_fields,
         update_blacklist,
@@ -362,13 +658,29 @@ fn main() {
         // 日志管理命令
         get_log_config,
         update_log_config,
+        get_log_files,
+        purge_logs,
+        // 崩溃报告命令
+        list_crash_reports,
+        get_crash_report,
+        delete_crash_report,
+        send_crash_report,
+        update_crash_report_config,
         is_release_build,
         // 工具管理命令（工具管理系统）
         get_tool_instances,
         refresh_tool_instances,
         list_wsl_distributions,
         add_wsl_tool_instance,
+        install_wsl_tool_instance,
+        apply_profile_to_wsl_instance,
         add_ssh_tool_instance,
+        refresh_ssh_tool_instance,
+        install_ssh_tool_instance,
+        apply_profile_to_ssh_instance,
+        start_ssh_proxy_tunnel,
+        stop_ssh_proxy_tunnel,
+        list_ssh_proxy_tunnels,
         delete_tool_instance,
         // 引导管理命令
         get_onboarding_status,
@@ -378,9 +690,30 @@ fn main() {
         // 单实例模式配置命令
         get_single_instance_config,
         update_single_instance_config,
+        get_observer_mode,
+        set_observer_mode,
         // 开机自启动管理命令
         get_startup_config,
         update_startup_config,
+        set_autostart,
+        // 异常退出恢复命令
+        get_startup_recovery_report,
+        dismiss_startup_recovery_report,
+        check_proxy_config_consistency,
+        restore_stale_proxy_config,
+        set_auto_restore_stale_config,
+        global_search,
+        invalidate_search_index,
+        list_projects,
+        register_project,
+        redetect_project,
+        remove_project,
+        detect_project_stack,
+        get_project_stack_rules,
+        save_project_stack_rules,
+        // 引导式故障排查
+        list_troubleshoot_flows,
+        run_troubleshoot_flow,
         // Profile 管理命令（v2.0）
         pm_list_all_profiles,
         pm_list_tool_profiles,
@@ -391,6 +724,12 @@ fn main() {
         pm_get_active_profile_name,
         pm_get_active_profile,
         pm_capture_from_native,
+        pm_export_native_snippet,
+        pm_check_config_drift,
+        pm_repair_drift,
+        pm_check_profile_health,
+        pm_check_all_profiles,
+        validate_profile_credentials,
         pm_get_amp_selection,
         pm_save_amp_selection,
         // 供应商管理命令（v1.5.0）
@@ -400,6 +739,8 @@ fn main() {
         delete_provider,
         validate_provider_config,
         fetch_provider_api_addresses,
+        get_provider_health,
+        onboard_provider,
         // 令牌资产管理命令（NEW API 集成）
         fetch_provider_tokens,
         fetch_provider_groups,
@@ -415,6 +756,7 @@ fn main() {
         set_tool_instance_selection,
         get_selected_provider_id,
         set_selected_provider_id,
+        get_dashboard_summary,
         // 价格配置管理命令（Phase 6）
         list_pricing_templates,
         get_pricing_template,
@@ -422,6 +764,27 @@ fn main() {
         delete_pricing_template,
         set_default_template,
         get_default_template,
+        validate_pricing_template,
+        validate_pricing_template_draft,
+        simulate_cost,
+        sync_pricing_catalog,
+        // 系统健康报告
+        get_system_health,
+        run_diagnostics,
+        inspect_environment,
+        apply_environment_fix,
+        get_api_handshake,
+        get_maintenance_status,
+        update_maintenance_config,
+        get_metrics_status,
+        update_metrics_config,
+        get_admin_api_status,
+        update_admin_api_config,
+        get_mcp_status,
+        update_mcp_config,
+        run_maintenance_now,
+        get_audit_log,
+        verify_audit_log,
         // AMP 用户认证命令
         get_amp_user_info,
         validate_and_save_amp_token,
@@ -443,6 +806,12 @@ fn main() {
                 // 关闭 Token 统计后台任务
                 duckcoding::services::token_stats::shutdown_token_stats_manager();
 
+                // 还原供应商试用改写的项目配置
+                duckcoding::services::proxy::trial::cleanup_all_trials();
+
+                // 清除运行标记（下次启动不再视为异常退出）
+                duckcoding::services::recovery::mark_clean_shutdown();
+
                 tracing::info!("清理任务完成");
             }
 
@@ -479,6 +848,9 @@ fn main() {
                         }
 
                         tracing::debug!("从 Dock/Cmd+Tab 恢复窗口");
+                    } else {
+                        // 后台运行时窗口尚未创建
+                        setup::focus_main_window(app_handle);
                     }
                 }
             }
@@ -0,0 +1,29 @@
+//! 前后端接口版本握手
+//!
+//! 命令签名或载荷结构发生不兼容变更时递增 [`API_VERSION`]，
+//! 前端启动时比对自身期望的版本，及早发现前后端不匹配。
+
+use serde::{Deserialize, Serialize};
+
+/// 当前命令接口版本
+pub const API_VERSION: u32 = 1;
+
+/// 握手信息
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct ApiHandshake {
+    /// 命令接口版本
+    pub api_version: u32,
+    /// 应用版本
+    pub app_version: String,
+}
+
+impl ApiHandshake {
+    /// 当前后端的握手信息
+    pub fn current() -> Self {
+        Self {
+            api_version: API_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
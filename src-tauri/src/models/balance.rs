@@ -7,6 +7,7 @@ use std::collections::HashMap;
 
 /// 余额监控配置项
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct BalanceConfig {
     /// 配置 ID
     pub id: String,
@@ -41,6 +42,7 @@ pub struct BalanceConfig {
 
 /// 余额监控存储结构
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct BalanceStore {
     /// 存储格式版本
     pub version: u32,
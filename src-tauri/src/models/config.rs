@@ -1,11 +1,13 @@
 // filepath: e:\DuckCoding\src-tauri\src\models\config.rs
 
 // 全局配置结构，移动到 models 以便在库和二进制之间共享
+use crate::core::secrets::{visit_optional, SecretFields};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// 日志级别
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 #[serde(rename_all = "lowercase")]
 pub enum LogLevel {
     Trace,
@@ -39,6 +41,7 @@ impl LogLevel {
 
 /// 日志输出格式
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 #[serde(rename_all = "lowercase")]
 pub enum LogFormat {
     Json,
@@ -48,6 +51,7 @@ pub enum LogFormat {
 
 /// 日志输出目标
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 #[serde(rename_all = "lowercase")]
 pub enum LogOutput {
     Console,
@@ -58,6 +62,7 @@ pub enum LogOutput {
 
 /// 日志系统配置
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct LogConfig {
     #[serde(default)]
     pub level: LogLevel,
@@ -67,10 +72,58 @@ pub struct LogConfig {
     pub output: LogOutput,
     #[serde(default)]
     pub file_path: Option<String>,
+    /// 日志文件滚动与保留策略
+    #[serde(default)]
+    pub rotation: LogRotationConfig,
+}
+
+/// 日志文件滚动与保留策略
+///
+/// 当前日志写入 `duckcoding.log`，超过大小上限或跨天时滚动为
+/// `duckcoding.YYYYMMDD-HHMMSS.log`（可选 gzip 压缩）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct LogRotationConfig {
+    /// 单个日志文件大小上限（MB）
+    #[serde(default = "default_log_max_file_size_mb")]
+    pub max_file_size_mb: u32,
+    /// 保留的历史日志文件数（不含当前文件）
+    #[serde(default = "default_log_max_files")]
+    pub max_files: u32,
+    /// 历史日志保留天数（0 表示不按天数清理）
+    #[serde(default = "default_log_max_age_days")]
+    pub max_age_days: u32,
+    /// 是否 gzip 压缩滚动出的历史日志
+    #[serde(default = "default_true")]
+    pub compress: bool,
+}
+
+impl Default for LogRotationConfig {
+    fn default() -> Self {
+        Self {
+            max_file_size_mb: default_log_max_file_size_mb(),
+            max_files: default_log_max_files(),
+            max_age_days: default_log_max_age_days(),
+            compress: true,
+        }
+    }
+}
+
+fn default_log_max_file_size_mb() -> u32 {
+    20
+}
+
+fn default_log_max_files() -> u32 {
+    10
+}
+
+fn default_log_max_age_days() -> u32 {
+    14
 }
 
 /// 新用户引导状态
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct OnboardingStatus {
     /// 已完成的引导版本（例如："v1", "v2"）
     pub completed_version: String,
@@ -83,6 +136,7 @@ pub struct OnboardingStatus {
 
 /// 配置监听模式
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 #[serde(rename_all = "lowercase")]
 pub enum WatchMode {
     /// 默认模式：仅检测敏感字段（API Key/URL）
@@ -94,6 +148,7 @@ pub enum WatchMode {
 
 /// 配置监听配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct ConfigWatchConfig {
     /// 是否启用配置守护
     #[serde(default = "default_config_guard_enabled")]
@@ -112,10 +167,31 @@ pub struct ConfigWatchConfig {
     /// 格式：{ "claude-code": ["env.ANTHROPIC_AUTH_TOKEN", ...], ... }
     #[serde(default = "default_sensitive_fields")]
     pub sensitive_fields: HashMap<String, Vec<String>>,
+    /// 额外监听的路径（如项目内的 `.claude/settings.json` 或 `.gemini/` 目录）
+    #[serde(default)]
+    pub watched_paths: Vec<WatchedPath>,
+}
+
+/// 额外监听的配置文件或目录
+///
+/// 目录按非递归方式监听其中的 JSON / TOML / ENV 文件，字段路径带文件前缀
+/// （如 `settings.json:env.ANTHROPIC_AUTH_TOKEN`）；单个文件的字段路径不带前缀。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct WatchedPath {
+    /// 文件或目录路径（支持 `~/` 开头）
+    pub path: String,
+    /// 黑名单字段
+    #[serde(default)]
+    pub blacklist: Vec<String>,
+    /// 敏感字段（默认模式下仅检测这些字段）
+    #[serde(default)]
+    pub sensitive_fields: Vec<String>,
 }
 
 /// Token统计配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct TokenStatsConfig {
     /// 数据保留天数（None表示不限制）
     #[serde(default)]
@@ -126,6 +202,18 @@ pub struct TokenStatsConfig {
     /// 是否启用自动清理
     #[serde(default = "default_auto_cleanup_enabled")]
     pub auto_cleanup_enabled: bool,
+    /// 数据库大小上限（MB，None 表示不限制）；超出时后台维护删除最旧的日志
+    #[serde(default = "default_max_db_size_mb")]
+    pub max_db_size_mb: Option<u32>,
+    /// 请求流水导出配置
+    #[serde(default)]
+    pub flight_recorder: FlightRecorderConfig,
+    /// 用量异常检测配置
+    #[serde(default)]
+    pub anomaly_detection: UsageAnomalyConfig,
+    /// 用量报告配置
+    #[serde(default)]
+    pub usage_report: UsageReportConfig,
 }
 
 impl Default for TokenStatsConfig {
@@ -134,16 +222,408 @@ impl Default for TokenStatsConfig {
             retention_days: Some(30),
             max_log_count: Some(10000),
             auto_cleanup_enabled: true,
+            max_db_size_mb: default_max_db_size_mb(),
+            flight_recorder: FlightRecorderConfig::default(),
+            anomaly_detection: UsageAnomalyConfig::default(),
+            usage_report: UsageReportConfig::default(),
         }
     }
 }
 
+/// 请求流水导出（Flight Recorder）配置
+///
+/// 启用后每条完成的代理请求以一行 NDJSON 实时追加到指定文件
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct FlightRecorderConfig {
+    /// 是否启用
+    #[serde(default)]
+    pub enabled: bool,
+    /// 输出文件路径（支持 `~/` 前缀）
+    #[serde(default)]
+    pub file_path: Option<String>,
+}
+
 fn default_auto_cleanup_enabled() -> bool {
     true
 }
 
+fn default_max_db_size_mb() -> Option<u32> {
+    Some(1024)
+}
+
+/// 用量异常检测配置
+///
+/// 每分钟比较各工具当前小时的消费与 Token 用量和滚动基线
+/// （最近若干小时中有用量的小时的平均值），超过倍数时发送 `usage-anomaly` 事件
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct UsageAnomalyConfig {
+    /// 是否启用（默认开启）
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 超过基线多少倍视为异常
+    #[serde(default = "default_anomaly_multiplier")]
+    pub multiplier: f64,
+    /// 基线统计的小时数（不含当前小时）
+    #[serde(default = "default_anomaly_baseline_hours")]
+    pub baseline_hours: u32,
+    /// 当前小时消费低于该值（USD）时不提醒，避免小额波动误报
+    #[serde(default = "default_anomaly_min_cost_usd")]
+    pub min_cost_usd: f64,
+    /// 当前小时 Token 低于该值时不提醒
+    #[serde(default = "default_anomaly_min_tokens")]
+    pub min_tokens: i64,
+    /// 是否同时发送系统桌面通知
+    #[serde(default)]
+    pub desktop_notification: bool,
+}
+
+impl Default for UsageAnomalyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            multiplier: default_anomaly_multiplier(),
+            baseline_hours: default_anomaly_baseline_hours(),
+            min_cost_usd: default_anomaly_min_cost_usd(),
+            min_tokens: default_anomaly_min_tokens(),
+            desktop_notification: false,
+        }
+    }
+}
+
+fn default_anomaly_multiplier() -> f64 {
+    3.0
+}
+
+fn default_anomaly_baseline_hours() -> u32 {
+    24
+}
+
+fn default_anomaly_min_cost_usd() -> f64 {
+    1.0
+}
+
+fn default_anomaly_min_tokens() -> i64 {
+    1_000_000
+}
+
+/// 用量报告自动生成周期
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub enum UsageReportPeriod {
+    /// 每周（覆盖最近 7 天）
+    Weekly,
+    /// 每月（覆盖最近 30 天）
+    Monthly,
+}
+
+/// 用量报告输出格式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub enum UsageReportFormat {
+    #[default]
+    Markdown,
+    Html,
+}
+
+/// 用量报告配置
+///
+/// 按周期生成用量摘要（总成本、常用模型与会话、缓存节省、星期 × 小时热力图）写入磁盘，
+/// 生成后发送 `usage-report-generated` 事件
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct UsageReportConfig {
+    /// 自动生成周期（None 表示不自动生成）
+    #[serde(default)]
+    pub schedule: Option<UsageReportPeriod>,
+    /// 输出格式
+    #[serde(default)]
+    pub format: UsageReportFormat,
+    /// 输出目录（支持 `~/` 前缀，为空时使用 `~/.duckcoding/reports`）
+    #[serde(default)]
+    pub output_dir: Option<String>,
+    /// 生成后是否发送系统桌面通知
+    #[serde(default)]
+    pub desktop_notification: bool,
+}
+
+/// 夜间维护窗口配置
+///
+/// 在窗口内排空并重启透明代理、回写数据库 WAL、清理过期日志与统计数据。
+/// 存在活跃会话时推迟到窗口内的下一次检查。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct MaintenanceConfig {
+    /// 是否启用（默认关闭）
+    #[serde(default)]
+    pub enabled: bool,
+    /// 窗口开始时间（本地时间，HH:MM）
+    #[serde(default = "default_maintenance_window_start")]
+    pub window_start: String,
+    /// 窗口时长（分钟）
+    #[serde(default = "default_maintenance_window_minutes")]
+    pub window_minutes: u32,
+    /// 是否重启运行中的透明代理
+    #[serde(default = "default_true")]
+    pub restart_proxies: bool,
+    /// 最近多少分钟内有请求视为会话活跃
+    #[serde(default = "default_maintenance_idle_minutes")]
+    pub idle_minutes: u32,
+    /// 排空代理连接的最长等待时间（秒）
+    #[serde(default = "default_maintenance_drain_timeout_secs")]
+    pub drain_timeout_secs: u64,
+    /// 滚动日志保留天数
+    #[serde(default = "default_log_retention_days")]
+    pub log_retention_days: u32,
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window_start: default_maintenance_window_start(),
+            window_minutes: default_maintenance_window_minutes(),
+            restart_proxies: true,
+            idle_minutes: default_maintenance_idle_minutes(),
+            drain_timeout_secs: default_maintenance_drain_timeout_secs(),
+            log_retention_days: default_log_retention_days(),
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_maintenance_window_start() -> String {
+    "03:00".to_string()
+}
+
+fn default_maintenance_window_minutes() -> u32 {
+    60
+}
+
+fn default_maintenance_idle_minutes() -> u32 {
+    10
+}
+
+fn default_maintenance_drain_timeout_secs() -> u64 {
+    30
+}
+
+fn default_log_retention_days() -> u32 {
+    14
+}
+
+/// Prometheus 指标导出配置
+///
+/// 启用后在独立端口提供 `/metrics`，供监控系统抓取透明代理的请求、Token、成本与耗时指标。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct MetricsConfig {
+    /// 是否启用（默认关闭）
+    #[serde(default)]
+    pub enabled: bool,
+    /// 监听端口
+    #[serde(default = "default_metrics_port")]
+    pub port: u16,
+    /// 是否监听所有网卡（默认仅本机）
+    #[serde(default)]
+    pub allow_public: bool,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: default_metrics_port(),
+            allow_public: false,
+        }
+    }
+}
+
+fn default_metrics_port() -> u16 {
+    9464
+}
+
+/// 崩溃报告配置
+///
+/// 崩溃报告始终只保存在本地 `~/.duckcoding/crashes/`；
+/// 仅在开启上传后，由用户对单个报告手动确认发送。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct CrashReportConfig {
+    /// 是否允许发送崩溃报告（默认关闭）
+    #[serde(default)]
+    pub send_enabled: bool,
+    /// 报告接收地址（POST JSON）
+    #[serde(default)]
+    pub endpoint: Option<String>,
+}
+
+/// 本机管理 API 配置
+///
+/// 启用后在本机端口提供 REST 接口（代理状态、Profile 切换、Token 统计查询），
+/// 供 Shell 提示符、编辑器插件等外部工具集成；请求须携带 `Authorization: Bearer <token>`。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct AdminApiConfig {
+    /// 是否启用（默认关闭）
+    #[serde(default)]
+    pub enabled: bool,
+    /// 监听端口（仅监听 127.0.0.1）
+    #[serde(default = "default_admin_api_port")]
+    pub port: u16,
+    /// 访问令牌（启用时为空则自动生成）
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+impl Default for AdminApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: default_admin_api_port(),
+            token: None,
+        }
+    }
+}
+
+fn default_admin_api_port() -> u16 {
+    9465
+}
+
+/// 开机自启动时的启动方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub enum StartupMode {
+    /// 正常显示窗口
+    #[default]
+    Window,
+    /// 窗口隐藏到托盘
+    Minimized,
+    /// 后台运行：不创建窗口（代理、配置监听、Token 统计照常运行），从托盘打开窗口
+    Background,
+}
+
+/// MCP 服务配置
+///
+/// 启用后 AI 编程工具可通过 MCP（Model Context Protocol）查询用量、预算并切换 Profile：
+/// 标准输入输出方式由工具启动 `duckcoding mcp` 子进程；HTTP 方式由界面在本机端口监听。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct McpConfig {
+    /// 是否启用（默认关闭，关闭时 `duckcoding mcp` 拒绝启动）
+    #[serde(default)]
+    pub enabled: bool,
+    /// 是否同时在本机端口提供 HTTP 接入（仅监听 127.0.0.1）
+    #[serde(default)]
+    pub http_enabled: bool,
+    /// HTTP 监听端口
+    #[serde(default = "default_mcp_port")]
+    pub port: u16,
+    /// HTTP 访问令牌（启用 HTTP 时为空则自动生成）
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+impl Default for McpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            http_enabled: false,
+            port: default_mcp_port(),
+            token: None,
+        }
+    }
+}
+
+fn default_mcp_port() -> u16 {
+    9466
+}
+
+/// 价格目录远程同步配置
+///
+/// 定期下载公开的模型价格目录（LiteLLM 格式），将新模型与价格变动合并到内置价格模板。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct PricingSyncConfig {
+    /// 是否定期同步（默认开启）
+    #[serde(default = "default_pricing_sync_enabled")]
+    pub enabled: bool,
+    /// 价格目录地址（为空时使用内置镜像地址）
+    #[serde(default)]
+    pub catalog_url: Option<String>,
+    /// 同步间隔（小时）
+    #[serde(default = "default_pricing_sync_interval_hours")]
+    pub interval_hours: u32,
+}
+
+impl Default for PricingSyncConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_pricing_sync_enabled(),
+            catalog_url: None,
+            interval_hours: default_pricing_sync_interval_hours(),
+        }
+    }
+}
+
+fn default_pricing_sync_enabled() -> bool {
+    true
+}
+
+fn default_pricing_sync_interval_hours() -> u32 {
+    1
+}
+
+/// 余额监控配置
+///
+/// 定期查询各供应商与 Profile 的剩余额度，历史写入 SQLite；
+/// 剩余额度低于阈值时发送 `balance-low` 事件（回升到阈值以上后重新提醒）。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct BalanceMonitorConfig {
+    /// 是否定期查询（默认开启）
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 查询间隔（分钟）
+    #[serde(default = "default_balance_interval_minutes")]
+    pub interval_minutes: u32,
+    /// 低余额提醒阈值（USD），0 表示不提醒
+    #[serde(default = "default_low_balance_threshold_usd")]
+    pub low_balance_threshold_usd: f64,
+    /// 是否同时发送系统桌面通知
+    #[serde(default)]
+    pub desktop_notification: bool,
+}
+
+impl Default for BalanceMonitorConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_minutes: default_balance_interval_minutes(),
+            low_balance_threshold_usd: default_low_balance_threshold_usd(),
+            desktop_notification: false,
+        }
+    }
+}
+
+fn default_balance_interval_minutes() -> u32 {
+    30
+}
+
+fn default_low_balance_threshold_usd() -> f64 {
+    5.0
+}
+
 /// 配置文件快照
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct ConfigSnapshot {
     /// 工具 ID
     pub tool_id: String,
@@ -163,6 +643,7 @@ impl Default for ConfigWatchConfig {
             scan_interval: default_scan_interval(),
             blacklist: default_watch_blacklist(),
             sensitive_fields: default_sensitive_fields(),
+            watched_paths: Vec::new(),
         }
     }
 }
@@ -174,11 +655,17 @@ impl LogConfig {
         self.format == other.format
             && self.output == other.output
             && self.file_path == other.file_path
+            && self.rotation == other.rotation
     }
 }
 
-/// 单个工具的透明代理配置
+/// 单个工具的透明代理配置（旧版，保存在全局配置中；新版见 `proxy_config::ToolProxyConfig`）
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "bindings",
+    derive(ts_rs::TS),
+    ts(export, rename = "LegacyToolProxyConfig")
+)]
 pub struct ToolProxyConfig {
     pub enabled: bool,
     pub port: u16,
@@ -221,6 +708,7 @@ impl Default for ToolProxyConfig {
 }
 
 #[derive(Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct GlobalConfig {
     /// 配置文件版本（用于迁移管理）
     /// 默认值为 "0.0.0"，迁移后更新为对应的应用版本号
@@ -235,7 +723,7 @@ pub struct GlobalConfig {
     #[serde(default)]
     pub proxy_enabled: bool,
     #[serde(default)]
-    pub proxy_type: Option<String>, // "http", "https", "socks5"
+    pub proxy_type: Option<String>, // "http", "https", "socks5", "ntlm"/"negotiate"（经本地认证桥）
     #[serde(default)]
     pub proxy_host: Option<String>,
     #[serde(default)]
@@ -276,12 +764,45 @@ pub struct GlobalConfig {
     /// 开机自启动开关（默认关闭）
     #[serde(default)]
     pub startup_enabled: bool,
+    /// 开机自启动时的启动方式
+    #[serde(default)]
+    pub startup_mode: StartupMode,
     /// 配置监听配置
     #[serde(default)]
     pub config_watch: ConfigWatchConfig,
     /// Token统计配置
     #[serde(default)]
     pub token_stats_config: TokenStatsConfig,
+    /// 工具配置目录覆盖（tool_id -> 目录），用于配置目录不在默认位置的情况
+    #[serde(default)]
+    pub tool_config_dirs: HashMap<String, String>,
+    /// 只读观察模式：统计与变更检测照常，但绝不写入工具配置文件
+    #[serde(default)]
+    pub observer_mode: bool,
+    /// 夜间维护窗口
+    #[serde(default)]
+    pub maintenance: MaintenanceConfig,
+    /// Prometheus 指标导出
+    #[serde(default)]
+    pub metrics: MetricsConfig,
+    /// 价格目录远程同步
+    #[serde(default)]
+    pub pricing_sync: PricingSyncConfig,
+    /// 本机管理 API
+    #[serde(default)]
+    pub admin_api: AdminApiConfig,
+    /// MCP 服务
+    #[serde(default)]
+    pub mcp: McpConfig,
+    /// 余额监控
+    #[serde(default)]
+    pub balance_monitor: BalanceMonitorConfig,
+    /// 崩溃报告
+    #[serde(default)]
+    pub crash_report: CrashReportConfig,
+    /// 额外加入命令搜索路径的目录（环境诊断修复时追加）
+    #[serde(default)]
+    pub extra_paths: Vec<String>,
 }
 
 fn default_proxy_configs() -> HashMap<String, ToolProxyConfig> {
@@ -410,6 +931,28 @@ impl GlobalConfig {
     }
 }
 
+impl SecretFields for GlobalConfig {
+    fn visit_secrets(&mut self, visit: &mut dyn FnMut(&str, &mut String)) {
+        visit_optional("config/system_token", &mut self.system_token, visit);
+        visit_optional("config/proxy_password", &mut self.proxy_password, visit);
+        visit_optional("config/admin_api/token", &mut self.admin_api.token, visit);
+        visit_optional("config/mcp/token", &mut self.mcp.token, visit);
+        for (tool_id, proxy_config) in self.proxy_configs.iter_mut() {
+            let scope = format!("config/proxy_configs/{tool_id}");
+            visit_optional(
+                &format!("{scope}/local_api_key"),
+                &mut proxy_config.local_api_key,
+                visit,
+            );
+            visit_optional(
+                &format!("{scope}/real_api_key"),
+                &mut proxy_config.real_api_key,
+                visit,
+            );
+        }
+    }
+}
+
 fn default_external_watch_enabled() -> bool {
     true
 }
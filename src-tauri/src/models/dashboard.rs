@@ -7,6 +7,7 @@ use std::collections::HashMap;
 
 /// 仪表板配置存储
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct DashboardStore {
     /// 数据版本
     pub version: u32,
@@ -30,6 +31,111 @@ impl Default for DashboardStore {
     }
 }
 
+/// 仪表板汇总（一次调用返回仪表板首屏所需的全部数据）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct DashboardSummary {
+    /// 生成时间（Unix 时间戳，毫秒）
+    pub generated_at: i64,
+    /// 今日（本地时间 0 点起）所有工具的请求数
+    pub today_requests: i64,
+    /// 今日所有工具的总成本（USD）
+    pub today_cost: f64,
+    /// 正在运行的透明代理
+    pub active_proxies: Vec<DashboardProxy>,
+    /// 各工具当前激活的 Profile
+    pub active_profiles: Vec<DashboardActiveProfile>,
+    /// 各工具 Profile 上游的最近探测结果
+    pub provider_health: Vec<DashboardProviderHealth>,
+    /// 最近一次检测到的外部配置变更
+    pub last_config_event: Option<DashboardConfigEvent>,
+    /// 可用更新
+    pub updates: DashboardUpdates,
+    /// 获取失败的部分（其余部分照常返回）
+    pub errors: Vec<DashboardSectionError>,
+}
+
+/// 正在运行的透明代理
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct DashboardProxy {
+    pub tool_id: String,
+    pub port: u16,
+    /// 当前正在转发的请求数
+    pub in_flight: Option<u32>,
+}
+
+/// 工具当前激活的 Profile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct DashboardActiveProfile {
+    pub tool_id: String,
+    /// 激活的 Profile 名称（未激活时为 None）
+    pub profile: Option<String>,
+    /// 切换时间（Unix 时间戳，毫秒）
+    pub switched_at: Option<i64>,
+}
+
+/// 上游健康状况（不含历史探测记录）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct DashboardProviderHealth {
+    pub tool_id: String,
+    pub base_url: String,
+    pub profile_names: Vec<String>,
+    /// 最近一次探测是否可达（尚未探测时为 None）
+    pub reachable: Option<bool>,
+    pub latency_ms: Option<u64>,
+    /// 最近 24 小时可用率（0-100）
+    pub uptime_24h: Option<f64>,
+    /// 最近一次探测时间（Unix 时间戳，毫秒）
+    pub checked_at: Option<i64>,
+}
+
+/// 外部配置变更（不含变更前后的值）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct DashboardConfigEvent {
+    pub tool_id: String,
+    /// 检测时间（Unix 时间戳，毫秒）
+    pub timestamp: i64,
+    pub changed_fields: Vec<String>,
+    pub is_sensitive: bool,
+    /// 用户操作（allow/block/superseded/expired，待处理时为 None）
+    pub action: Option<String>,
+}
+
+/// 可用更新
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct DashboardUpdates {
+    /// 应用新版本（无更新或检查失败时为 None）
+    pub app: Option<AvailableUpdate>,
+    /// 有新版本的工具
+    pub tools: Vec<AvailableUpdate>,
+}
+
+/// 单个可用更新
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct AvailableUpdate {
+    /// 应用为 `duckcoding`，工具为工具 ID
+    pub id: String,
+    pub current_version: Option<String>,
+    pub latest_version: Option<String>,
+    /// 是否为强制更新（仅应用更新）
+    pub required: bool,
+}
+
+/// 汇总中获取失败的部分
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct DashboardSectionError {
+    /// 部分名称（与 [`DashboardSummary`] 字段名一致）
+    pub section: String,
+    pub message: String,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
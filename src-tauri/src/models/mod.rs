@@ -1,3 +1,4 @@
+pub mod api;
 pub mod balance;
 pub mod config;
 pub mod dashboard;
@@ -9,6 +10,7 @@ pub mod token_stats;
 pub mod tool;
 pub mod update;
 
+pub use api::{ApiHandshake, API_VERSION};
 pub use balance::*;
 pub use config::*;
 pub use dashboard::*;
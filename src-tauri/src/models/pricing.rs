@@ -3,6 +3,7 @@ use std::collections::HashMap;
 
 /// 单个模型的价格定义
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct ModelPrice {
     /// 提供商（如：anthropic、openai）
     pub provider: String,
@@ -37,6 +38,65 @@ pub struct ModelPrice {
     /// 模型别名列表（支持多种 ID 格式）
     #[serde(default)]
     pub aliases: Vec<String>,
+
+    /// 别名匹配优先级（多个模型共用同一别名时数值大者优先，默认 0）
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub priority: i32,
+
+    /// 阶梯价格（按当月累计用量切换，未达到任何阈值时使用上面的基础价格）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tiers: Vec<PriceTier>,
+
+    /// 上下文窗口（最大输入 Token 数，未设置时按内置模型族默认值）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_window: Option<i64>,
+}
+
+/// 阶梯价格档位
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct PriceTier {
+    /// 当月该模型累计 Token 数（输入 + 输出 + 缓存）达到该值后适用本档价格
+    pub threshold_tokens: i64,
+
+    /// 输入价格（USD/百万 Token）
+    pub input_price_per_1m: f64,
+
+    /// 输出价格（USD/百万 Token）
+    pub output_price_per_1m: f64,
+
+    /// 缓存写入价格 - 5分钟TTL（未设置时沿用基础价格，下同）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_write_price_per_1m: Option<f64>,
+
+    /// 缓存写入价格 - 1小时TTL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_write_1h_price_per_1m: Option<f64>,
+
+    /// 缓存读取价格
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read_price_per_1m: Option<f64>,
+
+    /// 推理输出价格
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning_output_price_per_1m: Option<f64>,
+}
+
+impl PriceTier {
+    /// 按倍率缩放（用于继承模型）
+    pub fn scaled(&self, multiplier: f64) -> Self {
+        Self {
+            threshold_tokens: self.threshold_tokens,
+            input_price_per_1m: self.input_price_per_1m * multiplier,
+            output_price_per_1m: self.output_price_per_1m * multiplier,
+            cache_write_price_per_1m: self.cache_write_price_per_1m.map(|p| p * multiplier),
+            cache_write_1h_price_per_1m: self.cache_write_1h_price_per_1m.map(|p| p * multiplier),
+            cache_read_price_per_1m: self.cache_read_price_per_1m.map(|p| p * multiplier),
+            reasoning_output_price_per_1m: self
+                .reasoning_output_price_per_1m
+                .map(|p| p * multiplier),
+        }
+    }
 }
 
 impl ModelPrice {
@@ -62,12 +122,45 @@ impl ModelPrice {
             reasoning_output_price_per_1m,
             currency: default_currency(),
             aliases,
+            priority: 0,
+            tiers: Vec::new(),
+            context_window: None,
+        }
+    }
+
+    /// 当月累计用量对应的阶梯档位（阈值最高且已达到的档位）
+    pub fn tier_for(&self, monthly_tokens: i64) -> Option<&PriceTier> {
+        self.tiers
+            .iter()
+            .filter(|tier| monthly_tokens >= tier.threshold_tokens)
+            .max_by_key(|tier| tier.threshold_tokens)
+    }
+
+    /// 应用阶梯档位后的价格
+    pub fn with_tier(&self, tier: &PriceTier) -> ModelPrice {
+        ModelPrice {
+            input_price_per_1m: tier.input_price_per_1m,
+            output_price_per_1m: tier.output_price_per_1m,
+            cache_write_price_per_1m: tier
+                .cache_write_price_per_1m
+                .or(self.cache_write_price_per_1m),
+            cache_write_1h_price_per_1m: tier
+                .cache_write_1h_price_per_1m
+                .or(self.cache_write_1h_price_per_1m),
+            cache_read_price_per_1m: tier
+                .cache_read_price_per_1m
+                .or(self.cache_read_price_per_1m),
+            reasoning_output_price_per_1m: tier
+                .reasoning_output_price_per_1m
+                .or(self.reasoning_output_price_per_1m),
+            ..self.clone()
         }
     }
 }
 
 /// 单个模型的继承配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct InheritedModel {
     /// 模型名称（如："claude-sonnet-4.5"）
     pub model_name: String,
@@ -77,6 +170,10 @@ pub struct InheritedModel {
 
     /// 倍率（应用到继承的价格上）
     pub multiplier: f64,
+
+    /// 别名匹配优先级（多条继承配置匹配同一模型时数值大者优先，默认 0）
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub priority: i32,
 }
 
 impl InheritedModel {
@@ -86,12 +183,14 @@ impl InheritedModel {
             model_name,
             source_template_id,
             multiplier,
+            priority: 0,
         }
     }
 }
 
 /// 价格模板（统一结构，支持三种模式）
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct PricingTemplate {
     /// 模板ID（唯一标识）
     pub id: String,
@@ -126,6 +225,14 @@ pub struct PricingTemplate {
     /// 是否为内置预设模板
     #[serde(default)]
     pub is_default_preset: bool,
+
+    /// 按次附加费（USD，每个产生用量的请求额外计费）
+    #[serde(default)]
+    pub per_request_fee: f64,
+
+    /// 月最低消费（USD，当月用量成本不足时按此金额结算）
+    #[serde(default)]
+    pub min_monthly_fee: f64,
 }
 
 impl PricingTemplate {
@@ -153,6 +260,8 @@ impl PricingTemplate {
             custom_models,
             tags,
             is_default_preset,
+            per_request_fee: 0.0,
+            min_monthly_fee: 0.0,
         }
     }
 
@@ -180,6 +289,7 @@ impl PricingTemplate {
 
 /// 工具默认模板配置（存储在 default_templates.json）
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct DefaultTemplatesConfig {
     /// 配置版本号（用于自动迁移）
     #[serde(default = "default_config_version")]
@@ -244,6 +354,10 @@ fn default_currency() -> String {
     "USD".to_string()
 }
 
+fn is_zero(value: &i32) -> bool {
+    *value == 0
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
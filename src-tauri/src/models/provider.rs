@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 
 /// 签到配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct CheckinConfig {
     /// 是否启用自动签到
     pub enabled: bool,
@@ -69,6 +70,7 @@ impl Default for CheckinConfig {
 
 /// 供应商配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct Provider {
     /// 唯一标识
     pub id: String,
@@ -98,6 +100,7 @@ pub struct Provider {
 
 /// 供应商存储结构
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct ProviderStore {
     /// 数据版本
     pub version: u32,
@@ -1,11 +1,15 @@
 //! 透明代理配置数据模型
 
+use crate::core::secrets::{visit_optional, SecretFields};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
 
 /// 单个工具的透明代理配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct ToolProxyConfig {
     pub enabled: bool,
     pub port: u16,
@@ -38,6 +42,768 @@ pub struct ToolProxyConfig {
     /// Tavily API Key（用于本地搜索，可选，无则降级 DuckDuckGo）
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub tavily_api_key: Option<String>,
+    /// SSE 统计旁路是否只保留统计相关事件（默认开启）
+    #[serde(default = "default_sse_tap_filter")]
+    pub sse_tap_filter: bool,
+    /// 非标准 SSE 兼容选项（未按 Profile 单独配置时使用）
+    #[serde(default)]
+    pub sse_compat: SseCompatConfig,
+    /// 需要捕获并随日志记录的上游响应头（不区分大小写）
+    #[serde(default = "default_captured_response_headers")]
+    pub captured_response_headers: Vec<String>,
+    /// 按 Profile 名称单独配置的 SSE 兼容选项（优先于 `sse_compat`）
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub sse_compat_profiles: HashMap<String, SseCompatConfig>,
+    /// 请求/响应体捕获（默认关闭，开启后按 TTL 强制过期删除）
+    #[serde(default)]
+    pub body_capture: BodyCaptureConfig,
+    /// 在响应中附加本次调用的成本与 Token 数（非流式为响应头，流式为末尾 SSE 注释）
+    #[serde(default)]
+    pub cost_annotation: bool,
+    /// 备用上游：主上游返回 5xx/429 或请求失败时按优先级依次重试
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub upstreams: Vec<UpstreamTarget>,
+    /// 主上游的 API Key 池（配置后按负载均衡策略轮换，代替 `real_api_key`）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub key_pool: Vec<PooledApiKey>,
+    /// Key 池负载均衡策略
+    #[serde(default)]
+    pub key_balance_mode: KeyBalanceMode,
+    /// 本地限流（默认关闭）
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+    /// 上游限流排队（默认关闭）
+    #[serde(default)]
+    pub upstream_queue: UpstreamQueueConfig,
+    /// 并发上游请求上限（默认关闭）
+    #[serde(default)]
+    pub concurrency_limit: ConcurrencyLimitConfig,
+    /// 上游超时（连接 / 总时长 / 流式响应空闲）
+    #[serde(default)]
+    pub upstream_timeout: UpstreamTimeoutConfig,
+    /// 幂等接口响应缓存（默认关闭）
+    #[serde(default)]
+    pub response_cache: ResponseCacheConfig,
+    /// 消费预算（默认关闭）
+    #[serde(default)]
+    pub budget: BudgetConfig,
+    /// 单次请求输入成本预检（默认关闭）
+    #[serde(default)]
+    pub cost_guard: CostGuardConfig,
+    /// 模型路由规则：按顺序匹配请求模型名，命中时改用规则指定 Profile 的上游
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub routing_rules: Vec<ModelRoutingRule>,
+    /// 请求内容脱敏（默认关闭）
+    #[serde(default)]
+    pub redaction: RedactionConfig,
+    /// Profile 自动切换：当前 Profile 的上游持续出错时切换到备用 Profile（默认关闭）
+    #[serde(default)]
+    pub profile_failover: ProfileFailoverConfig,
+    /// 主上游的 API 协议（未设置时视为与工具协议相同，不做转换）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upstream_protocol: Option<ApiProtocol>,
+    /// 转发上游时使用的出口代理（默认跟随全局代理）
+    #[serde(default)]
+    pub egress_proxy: EgressProxyConfig,
+    /// 本地套接字监听（Unix 域套接字路径 / Windows 命名管道名），设置后代替 TCP 端口
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub listen_socket: Option<String>,
+    /// 按客户端分发的本地 API Key（与 `local_api_key` 同时有效）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub client_keys: Vec<ClientApiKey>,
+}
+
+/// 按客户端分发的本地 API Key
+///
+/// 多台机器或多位成员共用同一代理时各用一个 Key，请求按 Key 的标签分别统计用量
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct ClientApiKey {
+    pub id: String,
+    /// 标签（记录到 Token 日志的 `client_key_label`）
+    pub label: String,
+    pub api_key: String,
+    /// 创建时间（Unix 时间戳，毫秒）
+    pub created_at: i64,
+}
+
+/// 本地 API Key 鉴权结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClientAuth {
+    /// Key 不匹配
+    Denied,
+    /// 使用主保护密钥（或未配置任何本地 Key）
+    Primary,
+    /// 使用客户端 Key（携带标签）
+    Client(String),
+}
+
+/// 模型路由规则
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct ModelRoutingRule {
+    pub id: String,
+    /// 模型名 glob（`*` 匹配任意字符，`?` 匹配单个字符，不区分大小写），如 `claude-haiku-*`
+    pub model_pattern: String,
+    /// 目标 Profile 名称
+    pub profile_name: String,
+    /// 保存规则时从 Profile 解析的上游地址
+    #[serde(default)]
+    pub base_url: String,
+    /// 保存规则时从 Profile 解析的 API Key
+    #[serde(default)]
+    pub api_key: String,
+    /// Profile 的价格模板（命中规则的请求按此计费）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pricing_template_id: Option<String>,
+    #[serde(default = "default_routing_rule_enabled")]
+    pub enabled: bool,
+}
+
+fn default_routing_rule_enabled() -> bool {
+    true
+}
+
+impl ModelRoutingRule {
+    /// 规则对应的上游
+    pub fn target(&self) -> UpstreamTarget {
+        UpstreamTarget {
+            base_url: self.base_url.clone(),
+            api_key: self.api_key.clone(),
+            priority: 0,
+            protocol: None,
+        }
+    }
+}
+
+/// 请求内容命中脱敏规则时的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub enum RedactionAction {
+    /// 替换为 `[REDACTED:规则名]` 后继续转发
+    #[default]
+    Mask,
+    /// 拒绝请求
+    Block,
+}
+
+/// 自定义脱敏规则
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct RedactionRule {
+    /// 规则名称（记录到命中统计）
+    pub name: String,
+    /// 正则表达式
+    pub pattern: String,
+    #[serde(default = "default_redaction_rule_enabled")]
+    pub enabled: bool,
+}
+
+fn default_redaction_rule_enabled() -> bool {
+    true
+}
+
+/// 请求内容脱敏（转发前扫描请求体中的密钥、内部主机名、邮箱等）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct RedactionConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub action: RedactionAction,
+    /// 启用内置规则（常见 API Key 格式、私钥、邮箱）
+    #[serde(default = "default_redaction_builtin_rules")]
+    pub builtin_rules: bool,
+    /// 自定义规则（如内部主机名）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<RedactionRule>,
+}
+
+fn default_redaction_builtin_rules() -> bool {
+    true
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            action: RedactionAction::default(),
+            builtin_rules: default_redaction_builtin_rules(),
+            rules: Vec::new(),
+        }
+    }
+}
+
+/// Profile 自动切换配置
+///
+/// 与 `upstreams` 的单请求重试不同，这里在当前 Profile 的主上游连续出错达到阈值后，
+/// 将代理配置整体切换到备用 Profile（写回配置文件，切换后不会自动切回）。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct ProfileFailoverConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 备用 Profile 名称
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backup_profile: Option<String>,
+    /// 触发切换的连续错误次数（请求失败或返回 5xx/429）
+    #[serde(default = "default_failover_error_threshold")]
+    pub error_threshold: u32,
+}
+
+fn default_failover_error_threshold() -> u32 {
+    3
+}
+
+/// 出口代理模式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub enum EgressMode {
+    /// 跟随全局代理设置（环境变量）
+    #[default]
+    Global,
+    /// 直连，忽略全局代理
+    Direct,
+    /// 使用本工具单独配置的代理
+    Custom,
+}
+
+/// 出口代理协议
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub enum EgressProxyType {
+    #[default]
+    Http,
+    Https,
+    /// SOCKS5（域名由代理端解析）
+    Socks5,
+}
+
+/// 工具级出口代理：透明代理转发上游请求时使用
+///
+/// 例如 Claude 流量走 VPN 隧道，Gemini 保持直连
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct EgressProxyConfig {
+    #[serde(default)]
+    pub mode: EgressMode,
+    #[serde(default)]
+    pub proxy_type: EgressProxyType,
+    #[serde(default)]
+    pub host: String,
+    #[serde(default)]
+    pub port: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+}
+
+impl Default for ProfileFailoverConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            backup_profile: None,
+            error_threshold: default_failover_error_threshold(),
+        }
+    }
+}
+
+impl ProfileFailoverConfig {
+    /// 当前 Profile 是否可切换到备用 Profile（已在使用备用 Profile 时不再切换）
+    pub fn is_active(&self, current_profile: Option<&str>) -> bool {
+        self.enabled
+            && self.error_threshold > 0
+            && self
+                .backup_profile
+                .as_deref()
+                .is_some_and(|backup| !backup.is_empty() && Some(backup) != current_profile)
+    }
+}
+
+/// 超出预算时的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub enum BudgetMode {
+    /// 仅提醒，继续转发
+    #[default]
+    Warn,
+    /// 拒绝新请求直到下一个统计周期
+    Block,
+}
+
+/// 按工具的消费预算（USD，按本地时间的自然日 / 周 / 月统计）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct BudgetConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 每日上限（None 表示不限制）
+    #[serde(default)]
+    pub daily_limit_usd: Option<f64>,
+    /// 每周上限（周一起算）
+    #[serde(default)]
+    pub weekly_limit_usd: Option<f64>,
+    /// 每月上限
+    #[serde(default)]
+    pub monthly_limit_usd: Option<f64>,
+    #[serde(default)]
+    pub mode: BudgetMode,
+    /// 达到上限的该百分比时发送提醒
+    #[serde(default = "default_budget_warn_percent")]
+    pub warn_percent: u8,
+}
+
+fn default_budget_warn_percent() -> u8 {
+    80
+}
+
+impl Default for BudgetConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            daily_limit_usd: None,
+            weekly_limit_usd: None,
+            monthly_limit_usd: None,
+            mode: BudgetMode::default(),
+            warn_percent: default_budget_warn_percent(),
+        }
+    }
+}
+
+impl BudgetConfig {
+    /// 是否设置了任一上限
+    pub fn is_active(&self) -> bool {
+        self.enabled
+            && [
+                self.daily_limit_usd,
+                self.weekly_limit_usd,
+                self.monthly_limit_usd,
+            ]
+            .iter()
+            .any(|limit| limit.is_some_and(|v| v > 0.0))
+    }
+}
+
+/// 单次请求预估成本超出阈值时的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub enum CostGuardMode {
+    /// 仅标记（响应头 + 日志），继续转发
+    #[default]
+    Tag,
+    /// 暂停转发，等待用户在界面确认
+    Confirm,
+    /// 直接拒绝
+    Reject,
+}
+
+/// 单次请求输入成本预检：转发前按请求体估算输入 Token 与成本，防止误发超长上下文
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct CostGuardConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 单次请求预估输入成本上限（USD）
+    #[serde(default = "default_cost_guard_max_input_cost")]
+    pub max_input_cost_usd: f64,
+    #[serde(default)]
+    pub mode: CostGuardMode,
+    /// 确认模式下等待用户确认的时长（秒），超时视为拒绝
+    #[serde(default = "default_cost_guard_confirm_timeout_secs")]
+    pub confirm_timeout_secs: u64,
+}
+
+fn default_cost_guard_max_input_cost() -> f64 {
+    1.0
+}
+
+fn default_cost_guard_confirm_timeout_secs() -> u64 {
+    120
+}
+
+impl Default for CostGuardConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_input_cost_usd: default_cost_guard_max_input_cost(),
+            mode: CostGuardMode::default(),
+            confirm_timeout_secs: default_cost_guard_confirm_timeout_secs(),
+        }
+    }
+}
+
+impl CostGuardConfig {
+    /// 是否设置了有效阈值
+    pub fn is_active(&self) -> bool {
+        self.enabled && self.max_input_cost_usd > 0.0
+    }
+}
+
+/// 本地限流配置（滑动 1 分钟窗口，超出时直接返回 429，不转发到上游）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct RateLimitConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 每分钟请求数上限（0 表示不限制）
+    #[serde(default)]
+    pub requests_per_minute: u32,
+    /// 每分钟 Token 数上限（输入 + 输出，0 表示不限制）
+    #[serde(default)]
+    pub tokens_per_minute: u32,
+    /// 按会话分别计算额度（关闭时整个工具共享额度）
+    #[serde(default)]
+    pub per_session: bool,
+}
+
+impl RateLimitConfig {
+    /// 是否设置了任一上限
+    pub fn is_active(&self) -> bool {
+        self.enabled && (self.requests_per_minute > 0 || self.tokens_per_minute > 0)
+    }
+}
+
+/// 上游限流排队配置：上游返回 429 且带 Retry-After 时暂存请求，等待后重新转发
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct UpstreamQueueConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 同时排队的请求数上限（超出时直接返回 429）
+    #[serde(default = "default_queue_max_requests")]
+    pub max_requests: u32,
+    /// 单个请求累计排队时长上限（秒）
+    #[serde(default = "default_queue_max_wait_secs")]
+    pub max_wait_secs: u64,
+}
+
+fn default_queue_max_requests() -> u32 {
+    8
+}
+
+fn default_queue_max_wait_secs() -> u64 {
+    60
+}
+
+impl Default for UpstreamQueueConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_requests: default_queue_max_requests(),
+            max_wait_secs: default_queue_max_wait_secs(),
+        }
+    }
+}
+
+/// 并发请求数达到上限时新请求的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub enum ConcurrencyOverflowMode {
+    /// 排队等待空闲名额，超过等待上限时返回 503
+    #[default]
+    Queue,
+    /// 直接返回 503
+    Reject,
+}
+
+/// 并发上游请求上限：限制同时转发到上游的请求数（含流式响应的整个传输过程），
+/// 防止大量并行 Agent 耗尽本地连接或触发上游并发限制
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct ConcurrencyLimitConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 同时转发到上游的请求数上限
+    #[serde(default = "default_concurrency_max_requests")]
+    pub max_concurrent: u32,
+    #[serde(default)]
+    pub overflow: ConcurrencyOverflowMode,
+    /// 排队模式下单个请求等待名额的时长上限（秒）
+    #[serde(default = "default_concurrency_max_wait_secs")]
+    pub max_wait_secs: u64,
+}
+
+fn default_concurrency_max_requests() -> u32 {
+    8
+}
+
+fn default_concurrency_max_wait_secs() -> u64 {
+    60
+}
+
+impl Default for ConcurrencyLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_concurrent: default_concurrency_max_requests(),
+            overflow: ConcurrencyOverflowMode::default(),
+            max_wait_secs: default_concurrency_max_wait_secs(),
+        }
+    }
+}
+
+impl ConcurrencyLimitConfig {
+    /// 是否生效（上限为 0 视为未设置）
+    pub fn is_active(&self) -> bool {
+        self.enabled && self.max_concurrent > 0
+    }
+}
+
+/// 上游超时配置（各项为 0 表示不限制），超时后返回 504 并记录为 `timeout` 状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct UpstreamTimeoutConfig {
+    /// 建立连接超时（秒）
+    #[serde(default = "default_connect_timeout_secs")]
+    pub connect_timeout_secs: u64,
+    /// 请求总超时（秒）：非流式请求为完整响应时间，流式请求为收到响应头之前的时间
+    #[serde(default = "default_total_timeout_secs")]
+    pub total_timeout_secs: u64,
+    /// 流式响应相邻两次收到数据的最长间隔（秒）
+    #[serde(default = "default_idle_stream_timeout_secs")]
+    pub idle_stream_timeout_secs: u64,
+}
+
+fn default_connect_timeout_secs() -> u64 {
+    30
+}
+
+fn default_total_timeout_secs() -> u64 {
+    600
+}
+
+fn default_idle_stream_timeout_secs() -> u64 {
+    300
+}
+
+impl Default for UpstreamTimeoutConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout_secs: default_connect_timeout_secs(),
+            total_timeout_secs: default_total_timeout_secs(),
+            idle_stream_timeout_secs: default_idle_stream_timeout_secs(),
+        }
+    }
+}
+
+impl UpstreamTimeoutConfig {
+    pub fn connect_timeout(&self) -> Option<Duration> {
+        non_zero_secs(self.connect_timeout_secs)
+    }
+
+    pub fn total_timeout(&self) -> Option<Duration> {
+        non_zero_secs(self.total_timeout_secs)
+    }
+
+    pub fn idle_stream_timeout(&self) -> Option<Duration> {
+        non_zero_secs(self.idle_stream_timeout_secs)
+    }
+}
+
+/// 0 表示不限制
+fn non_zero_secs(value: u64) -> Option<Duration> {
+    (value > 0).then(|| Duration::from_secs(value))
+}
+
+/// 响应缓存配置：缓存模型列表等幂等接口的成功响应，命中时不转发到上游
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct ResponseCacheConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 缓存有效期（秒）
+    #[serde(default = "default_cache_ttl_secs")]
+    pub ttl_secs: u64,
+    /// 缓存条目数上限（超出时淘汰最早写入的条目）
+    #[serde(default = "default_cache_max_entries")]
+    pub max_entries: u32,
+    /// 同时缓存请求体完全相同的 Embedding 请求
+    #[serde(default)]
+    pub cache_embeddings: bool,
+}
+
+fn default_cache_ttl_secs() -> u64 {
+    300
+}
+
+fn default_cache_max_entries() -> u32 {
+    256
+}
+
+impl Default for ResponseCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ttl_secs: default_cache_ttl_secs(),
+            max_entries: default_cache_max_entries(),
+            cache_embeddings: false,
+        }
+    }
+}
+
+/// Key 池中的单个 API Key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct PooledApiKey {
+    /// 别名（记录到 Token 日志的 `upstream_key_alias`，用于按 Key 统计用量）
+    pub alias: String,
+    pub api_key: String,
+    /// 权重（加权模式按比例分配请求；0 表示停用该 Key）
+    #[serde(default = "default_key_weight")]
+    pub weight: u32,
+}
+
+fn default_key_weight() -> u32 {
+    1
+}
+
+/// Key 池负载均衡策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub enum KeyBalanceMode {
+    /// 轮询
+    #[default]
+    RoundRobin,
+    /// 按权重平滑轮询
+    Weighted,
+}
+
+/// 上游目标
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct UpstreamTarget {
+    pub base_url: String,
+    pub api_key: String,
+    /// 优先级（越小越优先，同优先级按配置顺序）
+    #[serde(default)]
+    pub priority: u32,
+    /// 上游 API 协议（未设置时视为与工具协议相同，不做转换）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<ApiProtocol>,
+}
+
+/// 上游 API 协议（与客户端协议不同时由透明代理转换请求与响应）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub enum ApiProtocol {
+    /// Anthropic Messages API（`/v1/messages`）
+    Anthropic,
+    /// OpenAI Chat Completions API（`/v1/chat/completions`）
+    OpenaiChat,
+    /// OpenAI Responses API（`/v1/responses`）
+    OpenaiResponses,
+}
+
+fn default_sse_tap_filter() -> bool {
+    true
+}
+
+/// 默认捕获的上游响应头：官方 API 与常见中转站返回的请求 ID、CDN 节点
+pub fn default_captured_response_headers() -> Vec<String> {
+    [
+        "request-id",
+        "x-request-id",
+        "x-oneapi-request-id",
+        "cf-ray",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect()
+}
+
+/// 非标准 SSE 兼容选项（改写转发给客户端的流，默认全部关闭）
+///
+/// 会话诊断中记录的 SSE 差异可作为开启依据
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct SseCompatConfig {
+    /// 去除流首 UTF-8 BOM
+    #[serde(default)]
+    pub strip_bom: bool,
+    /// 丢弃 `:` 开头的注释 / keep-alive 行
+    #[serde(default)]
+    pub drop_comments: bool,
+    /// 为 `data:` 补齐空格
+    #[serde(default)]
+    pub fix_data_prefix: bool,
+    /// 将 `\r\n`、`\r` 统一为 `\n`
+    #[serde(default)]
+    pub normalize_line_endings: bool,
+    /// 根据 data 中的 `"type"` 补齐缺失的 `event:` 字段
+    #[serde(default)]
+    pub fill_event_names: bool,
+}
+
+impl SseCompatConfig {
+    /// 是否开启了任一改写
+    pub fn is_enabled(&self) -> bool {
+        self.strip_bom
+            || self.drop_comments
+            || self.fix_data_prefix
+            || self.normalize_line_endings
+            || self.fill_event_names
+    }
+}
+
+/// 请求/响应体捕获配置
+///
+/// 捕获内容包含提示词与密钥等敏感数据，因此过期删除不可关闭，只能调整时长
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct BodyCaptureConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 捕获保留时长（分钟），超出范围时按上下限处理
+    #[serde(default = "default_capture_ttl_minutes")]
+    pub ttl_minutes: u32,
+    /// 请求体/响应体各自的大小上限（KB），超出部分截断
+    #[serde(default = "default_capture_max_body_kb")]
+    pub max_body_kb: u32,
+}
+
+/// 捕获保留时长上限（7 天）
+pub const MAX_CAPTURE_TTL_MINUTES: u32 = 7 * 24 * 60;
+
+fn default_capture_ttl_minutes() -> u32 {
+    60
+}
+
+/// 捕获大小上限的上限（16 MB）
+pub const MAX_CAPTURE_BODY_KB: u32 = 16 * 1024;
+
+fn default_capture_max_body_kb() -> u32 {
+    1024
+}
+
+impl Default for BodyCaptureConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ttl_minutes: default_capture_ttl_minutes(),
+            max_body_kb: default_capture_max_body_kb(),
+        }
+    }
+}
+
+impl BodyCaptureConfig {
+    /// 生效的保留时长（分钟，限制在 1 分钟 ~ 7 天）
+    pub fn effective_ttl_minutes(&self) -> u32 {
+        self.ttl_minutes.clamp(1, MAX_CAPTURE_TTL_MINUTES)
+    }
+
+    /// 生效的单个请求体/响应体大小上限（字节，限制在 1 KB ~ 16 MB）
+    pub fn effective_max_body_bytes(&self) -> usize {
+        self.max_body_kb.clamp(1, MAX_CAPTURE_BODY_KB) as usize * 1024
+    }
 }
 
 impl ToolProxyConfig {
@@ -58,6 +824,133 @@ impl ToolProxyConfig {
             original_amp_settings: None,
             original_amp_secrets: None,
             tavily_api_key: None,
+            sse_tap_filter: default_sse_tap_filter(),
+            sse_compat: SseCompatConfig::default(),
+            captured_response_headers: default_captured_response_headers(),
+            sse_compat_profiles: HashMap::new(),
+            body_capture: BodyCaptureConfig::default(),
+            cost_annotation: false,
+            upstreams: Vec::new(),
+            key_pool: Vec::new(),
+            key_balance_mode: KeyBalanceMode::default(),
+            rate_limit: RateLimitConfig::default(),
+            upstream_queue: UpstreamQueueConfig::default(),
+            concurrency_limit: ConcurrencyLimitConfig::default(),
+            upstream_timeout: UpstreamTimeoutConfig::default(),
+            response_cache: ResponseCacheConfig::default(),
+            budget: BudgetConfig::default(),
+            cost_guard: CostGuardConfig::default(),
+            routing_rules: Vec::new(),
+            redaction: RedactionConfig::default(),
+            profile_failover: ProfileFailoverConfig::default(),
+            upstream_protocol: None,
+            egress_proxy: EgressProxyConfig::default(),
+            listen_socket: None,
+            client_keys: Vec::new(),
+        }
+    }
+
+    /// 按尝试顺序排列的上游：当前 Profile 的主上游始终优先，其后为按优先级排序的备用上游
+    pub fn upstream_candidates(&self) -> Vec<UpstreamTarget> {
+        let primary = self
+            .real_base_url
+            .as_ref()
+            .zip(self.real_api_key.as_ref())
+            .map(|(base_url, api_key)| UpstreamTarget {
+                base_url: base_url.clone(),
+                api_key: api_key.clone(),
+                priority: 0,
+                protocol: self.upstream_protocol,
+            });
+        let mut backups: Vec<UpstreamTarget> = self
+            .upstreams
+            .iter()
+            .filter(|u| !u.base_url.trim().is_empty())
+            .cloned()
+            .collect();
+        backups.sort_by_key(|u| u.priority);
+        primary.into_iter().chain(backups).collect()
+    }
+
+    /// 应用命中的模型路由规则：主上游、Profile 名称与价格模板切换为规则指定的 Profile
+    ///
+    /// 主上游协议属于原 Profile，规则指定的上游按工具协议直连
+    pub fn apply_routing_rule(&mut self, rule: &ModelRoutingRule) {
+        self.real_base_url = Some(rule.base_url.clone());
+        self.upstream_protocol = None;
+        self.real_api_key = Some(rule.api_key.clone());
+        self.real_profile_name = Some(rule.profile_name.clone());
+        self.pricing_template_id = rule.pricing_template_id.clone();
+    }
+
+    /// 校验客户端提供的本地 API Key
+    ///
+    /// 主保护密钥与客户端 Key 均可通过；未配置任何本地 Key 时不校验
+    pub fn authorize_client(&self, provided_key: &str) -> ClientAuth {
+        if let Some(key) = self.client_keys.iter().find(|k| k.api_key == provided_key) {
+            return ClientAuth::Client(key.label.clone());
+        }
+        match self.local_api_key.as_deref() {
+            Some(local_key) if local_key == provided_key => ClientAuth::Primary,
+            None if self.client_keys.is_empty() => ClientAuth::Primary,
+            _ => ClientAuth::Denied,
+        }
+    }
+
+    /// 访问密钥字段（ID 以 `scope` 为前缀，如 `proxy/claude-code`）
+    pub fn visit_secrets_in(&mut self, scope: &str, visit: &mut dyn FnMut(&str, &mut String)) {
+        visit_optional(
+            &format!("{scope}/local_api_key"),
+            &mut self.local_api_key,
+            visit,
+        );
+        visit_optional(
+            &format!("{scope}/real_api_key"),
+            &mut self.real_api_key,
+            visit,
+        );
+        visit_optional(
+            &format!("{scope}/tavily_api_key"),
+            &mut self.tavily_api_key,
+            visit,
+        );
+        visit_optional(
+            &format!("{scope}/egress_proxy/password"),
+            &mut self.egress_proxy.password,
+            visit,
+        );
+        for (i, upstream) in self.upstreams.iter_mut().enumerate() {
+            visit(&format!("{scope}/upstreams/{i}"), &mut upstream.api_key);
+        }
+        for (i, key) in self.key_pool.iter_mut().enumerate() {
+            visit(&format!("{scope}/key_pool/{i}"), &mut key.api_key);
+        }
+        for key in self.client_keys.iter_mut() {
+            visit(&format!("{scope}/client_keys/{}", key.id), &mut key.api_key);
+        }
+        for rule in self.routing_rules.iter_mut() {
+            visit(
+                &format!("{scope}/routing_rules/{}", rule.id),
+                &mut rule.api_key,
+            );
+        }
+    }
+
+    /// 当前 Profile 生效的 SSE 兼容选项
+    pub fn effective_sse_compat(&self) -> SseCompatConfig {
+        self.real_profile_name
+            .as_ref()
+            .and_then(|name| self.sse_compat_profiles.get(name))
+            .copied()
+            .unwrap_or(self.sse_compat)
+    }
+
+    /// 监听地址描述（本地套接字路径或 `host:port`）
+    pub fn listen_address(&self) -> String {
+        match self.listen_socket.as_deref() {
+            Some(path) => path.trim().to_string(),
+            None if self.allow_public => format!("0.0.0.0:{}", self.port),
+            None => format!("127.0.0.1:{}", self.port),
         }
     }
 
@@ -68,13 +961,14 @@ impl ToolProxyConfig {
             "codex" => 8788,
             "gemini-cli" => 8789,
             "amp-code" => 8790,
-            _ => 8787,
+            _ => custom_tool_proxy_port(tool_id).unwrap_or(8787),
         }
     }
 }
 
 /// proxy.json 顶层结构
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct ProxyStore {
     pub version: String,
     #[serde(rename = "claude-code")]
@@ -84,9 +978,19 @@ pub struct ProxyStore {
     pub gemini_cli: ToolProxyConfig,
     #[serde(rename = "amp-code", default = "default_amp_config")]
     pub amp_code: ToolProxyConfig,
+    /// 检测到配置指向未运行的代理时自动还原原始配置（默认开启）
+    #[serde(default = "default_auto_restore_stale_config")]
+    pub auto_restore_stale_config: bool,
+    /// 自定义工具（tools.d）的代理配置，按工具 ID 存储
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub custom_tools: HashMap<String, ToolProxyConfig>,
     pub metadata: ProxyMetadata,
 }
 
+fn default_auto_restore_stale_config() -> bool {
+    true
+}
+
 fn default_amp_config() -> ToolProxyConfig {
     ToolProxyConfig::new(8790)
 }
@@ -99,12 +1003,36 @@ impl ProxyStore {
             codex: ToolProxyConfig::new(8788),
             gemini_cli: ToolProxyConfig::new(8789),
             amp_code: ToolProxyConfig::new(8790),
+            auto_restore_stale_config: true,
+            custom_tools: HashMap::new(),
             metadata: ProxyMetadata {
                 last_updated: Utc::now(),
             },
         }
     }
 
+    /// 所有可代理工具的 ID（内置工具在前，随后是已有配置或声明了代理协议的自定义工具，按 ID 排序）
+    pub fn tool_ids(&self) -> Vec<String> {
+        let mut custom: Vec<String> = self
+            .custom_tools
+            .keys()
+            .cloned()
+            .chain(
+                crate::services::tool::custom_tools::custom_tools()
+                    .into_iter()
+                    .filter(|def| def.proxy_protocol.is_some())
+                    .map(|def| def.id),
+            )
+            .collect();
+        custom.sort();
+        custom.dedup();
+        ["claude-code", "codex", "gemini-cli", "amp-code"]
+            .into_iter()
+            .map(String::from)
+            .chain(custom)
+            .collect()
+    }
+
     /// 获取指定工具的配置
     pub fn get_config(&self, tool_id: &str) -> Option<&ToolProxyConfig> {
         match tool_id {
@@ -112,7 +1040,7 @@ impl ProxyStore {
             "codex" => Some(&self.codex),
             "gemini-cli" => Some(&self.gemini_cli),
             "amp-code" => Some(&self.amp_code),
-            _ => None,
+            _ => self.custom_tools.get(tool_id),
         }
     }
 
@@ -123,8 +1051,41 @@ impl ProxyStore {
             "codex" => Some(&mut self.codex),
             "gemini-cli" => Some(&mut self.gemini_cli),
             "amp-code" => Some(&mut self.amp_code),
-            _ => None,
+            _ => {
+                // 支持代理的自定义工具首次访问时分配端口并创建配置（保存后端口固定）
+                if !self.custom_tools.contains_key(tool_id) {
+                    let port = self.unused_port(custom_tool_proxy_port(tool_id)?);
+                    self.custom_tools
+                        .insert(tool_id.to_string(), ToolProxyConfig::new(port));
+                }
+                self.custom_tools.get_mut(tool_id)
+            }
+        }
+    }
+
+    /// 从 `preferred` 起第一个未被其他工具占用的端口（在自定义端口区间内循环查找）
+    fn unused_port(&self, preferred: u16) -> u16 {
+        let used: Vec<u16> = [
+            &self.claude_code,
+            &self.codex,
+            &self.gemini_cli,
+            &self.amp_code,
+        ]
+        .into_iter()
+        .chain(self.custom_tools.values())
+        .map(|config| config.port)
+        .collect();
+        if !used.contains(&preferred) {
+            return preferred;
         }
+        (0..CUSTOM_PROXY_PORT_RANGE)
+            .map(|offset| {
+                CUSTOM_PROXY_PORT_BASE
+                    + (preferred.wrapping_sub(CUSTOM_PROXY_PORT_BASE) + offset)
+                        % CUSTOM_PROXY_PORT_RANGE
+            })
+            .find(|port| !used.contains(port))
+            .unwrap_or(preferred)
     }
 
     /// 更新指定工具的配置
@@ -134,7 +1095,9 @@ impl ProxyStore {
             "codex" => self.codex = config,
             "gemini-cli" => self.gemini_cli = config,
             "amp-code" => self.amp_code = config,
-            _ => {}
+            _ => {
+                self.custom_tools.insert(tool_id.to_string(), config);
+            }
         }
         self.metadata.last_updated = Utc::now();
     }
@@ -146,7 +1109,91 @@ impl Default for ProxyStore {
     }
 }
 
+impl SecretFields for ProxyStore {
+    fn visit_secrets(&mut self, visit: &mut dyn FnMut(&str, &mut String)) {
+        self.claude_code
+            .visit_secrets_in("proxy/claude-code", visit);
+        self.codex.visit_secrets_in("proxy/codex", visit);
+        self.gemini_cli.visit_secrets_in("proxy/gemini-cli", visit);
+        self.amp_code.visit_secrets_in("proxy/amp-code", visit);
+        for (tool_id, config) in self.custom_tools.iter_mut() {
+            config.visit_secrets_in(&format!("proxy/{tool_id}"), visit);
+        }
+    }
+}
+
+/// 自定义工具默认代理端口区间（8791 起）
+const CUSTOM_PROXY_PORT_BASE: u16 = 8791;
+const CUSTOM_PROXY_PORT_RANGE: u16 = 200;
+
+/// 支持透明代理的自定义工具的默认端口（未声明 proxy_port 时由工具 ID 推导，增删其他定义不影响）
+fn custom_tool_proxy_port(tool_id: &str) -> Option<u16> {
+    let def = crate::services::tool::custom_tools::find_custom_tool(tool_id)
+        .filter(|def| def.proxy_protocol.is_some())?;
+    Some(
+        def.proxy_port
+            .unwrap_or_else(|| derived_proxy_port(tool_id)),
+    )
+}
+
+/// 由工具 ID 推导的稳定端口（FNV-1a 哈希映射到自定义端口区间）
+fn derived_proxy_port(tool_id: &str) -> u16 {
+    let hash = tool_id.bytes().fold(0x811c_9dc5_u32, |hash, byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    });
+    CUSTOM_PROXY_PORT_BASE + (hash % u32::from(CUSTOM_PROXY_PORT_RANGE)) as u16
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct ProxyMetadata {
     pub last_updated: DateTime<Utc>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_authorize_client() {
+        let mut config = ToolProxyConfig::new(8787);
+        assert_eq!(config.authorize_client("anything"), ClientAuth::Primary);
+
+        config.client_keys.push(ClientApiKey {
+            id: "k1".to_string(),
+            label: "laptop".to_string(),
+            api_key: "dc-laptop".to_string(),
+            created_at: 0,
+        });
+        // 只配置客户端 Key 时同样需要校验
+        assert_eq!(config.authorize_client("anything"), ClientAuth::Denied);
+        assert_eq!(
+            config.authorize_client("dc-laptop"),
+            ClientAuth::Client("laptop".to_string())
+        );
+
+        config.local_api_key = Some("sk-local".to_string());
+        assert_eq!(config.authorize_client("sk-local"), ClientAuth::Primary);
+        assert_eq!(config.authorize_client(""), ClientAuth::Denied);
+    }
+
+    #[test]
+    fn test_custom_proxy_port_is_stable_and_unused() {
+        let port = derived_proxy_port("opencode");
+        assert_eq!(port, derived_proxy_port("opencode"));
+        assert!(
+            (CUSTOM_PROXY_PORT_BASE..CUSTOM_PROXY_PORT_BASE + CUSTOM_PROXY_PORT_RANGE)
+                .contains(&port)
+        );
+
+        let mut store = ProxyStore::new();
+        assert_eq!(store.unused_port(port), port);
+        store.update_config("aider", ToolProxyConfig::new(port));
+        let next = store.unused_port(port);
+        assert_ne!(next, port);
+        assert!(
+            (CUSTOM_PROXY_PORT_BASE..CUSTOM_PROXY_PORT_BASE + CUSTOM_PROXY_PORT_RANGE)
+                .contains(&next)
+        );
+    }
+}
@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 
 /// 远程令牌（从 NEW API 拉取，不本地持久化）
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct RemoteToken {
     /// 令牌 ID
     pub id: i64,
@@ -50,6 +51,7 @@ pub struct RemoteToken {
 
 /// 远程令牌分组（API 返回的分组信息）
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct RemoteTokenGroupInfo {
     /// 分组描述
     pub desc: String,
@@ -59,6 +61,7 @@ pub struct RemoteTokenGroupInfo {
 
 /// 远程令牌分组（前端展示用）
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct RemoteTokenGroup {
     /// 分组 ID（即分组名称）
     pub id: String,
@@ -70,6 +73,7 @@ pub struct RemoteTokenGroup {
 
 /// 创建远程令牌请求
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct CreateRemoteTokenRequest {
     /// 令牌名称
     pub name: String,
@@ -91,6 +95,7 @@ pub struct CreateRemoteTokenRequest {
 
 /// 更新远程令牌请求（支持完整字段更新）
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct UpdateRemoteTokenRequest {
     /// 令牌名称
     pub name: String,
@@ -120,6 +125,7 @@ pub struct NewApiResponse<T> {
 
 /// NEW API 令牌列表响应的 data 部分
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct TokenListData {
     pub page: i32,
     pub page_size: i32,
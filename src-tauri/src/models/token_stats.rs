@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 
 /// Token日志记录
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct TokenLog {
     /// 主键ID（自增）
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -70,36 +71,58 @@ pub struct TokenLog {
     /// 输入部分价格（USD）
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(with = "crate::utils::precision::option_price_precision")]
+    #[cfg_attr(feature = "bindings", ts(as = "Option<f64>"))]
     pub input_price: Option<f64>,
 
     /// 输出部分价格（USD）
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(with = "crate::utils::precision::option_price_precision")]
+    #[cfg_attr(feature = "bindings", ts(as = "Option<f64>"))]
     pub output_price: Option<f64>,
 
     /// 缓存写入部分价格（USD）
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(with = "crate::utils::precision::option_price_precision")]
+    #[cfg_attr(feature = "bindings", ts(as = "Option<f64>"))]
     pub cache_write_price: Option<f64>,
 
     /// 缓存读取部分价格（USD）
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(with = "crate::utils::precision::option_price_precision")]
+    #[cfg_attr(feature = "bindings", ts(as = "Option<f64>"))]
     pub cache_read_price: Option<f64>,
 
     /// 推理Token部分价格（USD）
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(with = "crate::utils::precision::option_price_precision")]
+    #[cfg_attr(feature = "bindings", ts(as = "Option<f64>"))]
     pub reasoning_price: Option<f64>,
 
     /// 总成本（USD）
     #[serde(default)]
     #[serde(with = "crate::utils::precision::price_precision")]
+    #[cfg_attr(feature = "bindings", ts(as = "f64"))]
     pub total_cost: f64,
 
     /// 使用的价格模板ID
     #[serde(skip_serializing_if = "Option::is_none")]
     pub pricing_template_id: Option<String>,
+
+    /// 捕获的上游响应头（JSON 对象，如请求 ID、路由区域）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upstream_headers: Option<String>,
+
+    /// Key 池中本次使用的 Key 别名（用于按 Key 统计用量）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upstream_key_alias: Option<String>,
+
+    /// 鉴权所用的客户端 Key 标签（多个客户端共用代理时按客户端统计用量）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_key_label: Option<String>,
+
+    /// 会话标签（写入时从会话继承，用于按项目归集花费）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 }
 
 impl TokenLog {
@@ -159,6 +182,10 @@ impl TokenLog {
             reasoning_price,
             total_cost,
             pricing_template_id,
+            upstream_headers: None,
+            upstream_key_alias: None,
+            client_key_label: None,
+            tags: Vec::new(),
         }
     }
 
@@ -178,8 +205,153 @@ impl TokenLog {
     }
 }
 
+#[cfg(test)]
+impl TokenLog {
+    /// 测试用构建器：默认为 claude-code / claude-sonnet-4-5 的成功请求，Token 与成本均为 0
+    pub fn test_builder() -> TokenLogBuilder {
+        TokenLogBuilder(TokenLog::new(
+            "claude-code".to_string(),
+            0,
+            "127.0.0.1".to_string(),
+            "session".to_string(),
+            "default".to_string(),
+            "claude-sonnet-4-5".to_string(),
+            None,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            "success".to_string(),
+            "json".to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            0.0,
+            None,
+        ))
+    }
+}
+
+/// 测试用 TokenLog 构建器（见 [`TokenLog::test_builder`]）
+#[cfg(test)]
+pub struct TokenLogBuilder(TokenLog);
+
+#[cfg(test)]
+impl TokenLogBuilder {
+    pub fn tool(mut self, tool_type: &str) -> Self {
+        self.0.tool_type = tool_type.to_string();
+        self
+    }
+
+    pub fn timestamp(mut self, timestamp: i64) -> Self {
+        self.0.timestamp = timestamp;
+        self
+    }
+
+    pub fn session(mut self, session_id: &str) -> Self {
+        self.0.session_id = session_id.to_string();
+        self
+    }
+
+    pub fn config(mut self, config_name: &str) -> Self {
+        self.0.config_name = config_name.to_string();
+        self
+    }
+
+    pub fn model(mut self, model: &str) -> Self {
+        self.0.model = model.to_string();
+        self
+    }
+
+    pub fn message_id(mut self, message_id: &str) -> Self {
+        self.0.message_id = Some(message_id.to_string());
+        self
+    }
+
+    /// 输入与输出 Token
+    pub fn tokens(mut self, input: i64, output: i64) -> Self {
+        self.0.input_tokens = input;
+        self.0.output_tokens = output;
+        self
+    }
+
+    /// 缓存创建与缓存读取 Token
+    pub fn cache_tokens(mut self, creation: i64, read: i64) -> Self {
+        self.0.cache_creation_tokens = creation;
+        self.0.cache_read_tokens = read;
+        self
+    }
+
+    pub fn status(mut self, status: &str) -> Self {
+        self.0.request_status = status.to_string();
+        self
+    }
+
+    pub fn response_type(mut self, response_type: &str) -> Self {
+        self.0.response_type = response_type.to_string();
+        self
+    }
+
+    pub fn error(mut self, error_type: &str) -> Self {
+        self.0.error_type = Some(error_type.to_string());
+        self
+    }
+
+    pub fn error_detail(mut self, detail: &str) -> Self {
+        self.0.error_detail = Some(detail.to_string());
+        self
+    }
+
+    pub fn response_time(mut self, response_time_ms: impl Into<Option<i64>>) -> Self {
+        self.0.response_time_ms = response_time_ms.into();
+        self
+    }
+
+    pub fn input_price(mut self, price: f64) -> Self {
+        self.0.input_price = Some(price);
+        self
+    }
+
+    pub fn output_price(mut self, price: f64) -> Self {
+        self.0.output_price = Some(price);
+        self
+    }
+
+    pub fn cache_write_price(mut self, price: f64) -> Self {
+        self.0.cache_write_price = Some(price);
+        self
+    }
+
+    pub fn cache_read_price(mut self, price: f64) -> Self {
+        self.0.cache_read_price = Some(price);
+        self
+    }
+
+    pub fn cost(mut self, total_cost: f64) -> Self {
+        self.0.total_cost = total_cost;
+        self
+    }
+
+    pub fn template(mut self, template_id: &str) -> Self {
+        self.0.pricing_template_id = Some(template_id.to_string());
+        self
+    }
+
+    pub fn build(self) -> TokenLog {
+        self.0
+    }
+}
+
 /// 会话统计数据
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct SessionStats {
     /// 总输入Token数量
     pub total_input: i64,
@@ -227,6 +399,7 @@ impl SessionStats {
 
 /// Token日志查询参数
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct TokenStatsQuery {
     /// 工具类型筛选
     pub tool_type: Option<String>,
@@ -266,6 +439,7 @@ impl Default for TokenStatsQuery {
 
 /// 分页查询结果
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct TokenLogsPage {
     /// 日志列表
     pub logs: Vec<TokenLog>,
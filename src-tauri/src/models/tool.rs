@@ -1,8 +1,11 @@
+use crate::utils::expand_home_with;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 /// 工具状态
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct ToolStatus {
     pub id: String,
     pub name: String,
@@ -12,6 +15,7 @@ pub struct ToolStatus {
 
 /// 工具定义
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct Tool {
     pub id: String,
     pub name: String,
@@ -27,6 +31,7 @@ pub struct Tool {
 
 /// 环境变量配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct EnvVars {
     pub api_key: String,
     pub base_url: String,
@@ -34,6 +39,7 @@ pub struct EnvVars {
 
 /// 安装方法
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub enum InstallMethod {
     Official, // 官方脚本
     Npm,      // npm install
@@ -41,10 +47,167 @@ pub enum InstallMethod {
     Other,    // 其他（不支持APP内快捷更新）
 }
 
+/// 配置目录来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigDirSource {
+    /// 全局配置中的目录覆盖
+    Override,
+    /// 工具自身的环境变量（如 CLAUDE_CONFIG_DIR）
+    Env,
+    /// 默认目录（~/.claude 等）
+    Default,
+}
+
+/// 工具配置目录解析结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct ToolConfigDir {
+    pub tool_id: String,
+    pub config_dir: PathBuf,
+    pub source: ConfigDirSource,
+    /// 工具支持的配置目录环境变量
+    pub env_var: Option<String>,
+    /// 全局配置中设置的覆盖值
+    pub override_dir: Option<String>,
+    pub exists: bool,
+}
+
+/// 工具默认配置目录及对应的环境变量
+///
+/// Gemini CLI 的环境变量指定的是主目录，配置目录为其下的 `.gemini`。
+/// 自定义工具使用定义文件中的 `config_dir` / `config_dir_env`。
+fn config_dir_spec(tool_id: &str) -> Option<(String, String, Option<&'static str>)> {
+    let builtin = |dir: &str, env: &str, subdir: Option<&'static str>| {
+        Some((dir.to_string(), env.to_string(), subdir))
+    };
+    match tool_id {
+        "claude-code" => builtin(".claude", "CLAUDE_CONFIG_DIR", None),
+        "codex" => builtin(".codex", "CODEX_HOME", None),
+        "gemini-cli" => builtin(".gemini", "GEMINI_CLI_HOME", Some(".gemini")),
+        _ => crate::services::tool::custom_tools::find_custom_tool(tool_id)
+            .map(|def| (def.config_dir, def.config_dir_env.unwrap_or_default(), None)),
+    }
+}
+
+/// 目录本身是符号链接时解析为链接目标，保证监听与写入落在同一位置
+fn resolve_symlink(path: PathBuf) -> PathBuf {
+    if !path.is_symlink() {
+        return path;
+    }
+    match std::fs::read_link(&path) {
+        Ok(target) if target.is_absolute() => target,
+        Ok(target) => path
+            .parent()
+            .map(|parent| parent.join(&target))
+            .unwrap_or(target),
+        Err(_) => path,
+    }
+}
+
+/// 按优先级解析配置目录：目录覆盖 > 环境变量 > 默认目录
+fn resolve_config_dir_with(
+    tool_id: &str,
+    home_dir: &Path,
+    overrides: &HashMap<String, String>,
+    env: impl Fn(&str) -> Option<String>,
+) -> ToolConfigDir {
+    let (default_name, env_var, env_subdir) =
+        config_dir_spec(tool_id).unwrap_or((".duckcoding".to_string(), String::new(), None));
+    let env_var = env_var.as_str();
+    let override_dir = overrides
+        .get(tool_id)
+        .map(|dir| dir.trim().to_string())
+        .filter(|dir| !dir.is_empty());
+
+    let (config_dir, source) = if let Some(dir) = &override_dir {
+        (expand_home_with(dir, home_dir), ConfigDirSource::Override)
+    } else if let Some(dir) = (!env_var.is_empty())
+        .then(|| env(env_var))
+        .flatten()
+        .filter(|dir| !dir.trim().is_empty())
+    {
+        let base = expand_home_with(dir.trim(), home_dir);
+        let dir = match env_subdir {
+            Some(subdir) => base.join(subdir),
+            None => base,
+        };
+        (dir, ConfigDirSource::Env)
+    } else {
+        (
+            home_dir.join(expand_home_with(&default_name, home_dir)),
+            ConfigDirSource::Default,
+        )
+    };
+
+    let config_dir = resolve_symlink(config_dir);
+    ToolConfigDir {
+        tool_id: tool_id.to_string(),
+        exists: config_dir.is_dir(),
+        config_dir,
+        source,
+        env_var: (!env_var.is_empty()).then(|| env_var.to_string()),
+        override_dir,
+    }
+}
+
 impl Tool {
-    /// 获取所有工具
+    /// 解析工具的配置目录
+    ///
+    /// 用户可能通过 CLAUDE_CONFIG_DIR 等环境变量或符号链接迁移了配置目录，
+    /// 工具定义、配置监听与 Profile 激活都经由此处确定实际路径。
+    pub fn resolve_config_dir(tool_id: &str) -> ToolConfigDir {
+        let overrides = crate::utils::config::read_global_config()
+            .ok()
+            .flatten()
+            .map(|config| config.tool_config_dirs)
+            .unwrap_or_default();
+        Self::resolve_config_dir_with_overrides(tool_id, &overrides)
+    }
+
+    /// 按给定的目录覆盖解析配置目录（用于保存前校验）
+    pub fn resolve_config_dir_with_overrides(
+        tool_id: &str,
+        overrides: &HashMap<String, String>,
+    ) -> ToolConfigDir {
+        let home_dir = dirs::home_dir().expect("无法获取用户主目录");
+        resolve_config_dir_with(tool_id, &home_dir, overrides, |key| std::env::var(key).ok())
+    }
+
+    /// 生成在 POSIX shell 中输出配置目录的脚本（用于 WSL/远程环境）
+    ///
+    /// 与本机解析规则一致：工具环境变量优先，否则使用 `$HOME` 下的默认目录；
+    /// 本机的目录覆盖是 Windows 路径，不适用于 Linux 侧
+    pub fn posix_config_dir_script(tool_id: &str) -> String {
+        let (default_name, env_var, env_subdir) =
+            config_dir_spec(tool_id).unwrap_or((".duckcoding".to_string(), String::new(), None));
+        let default_dir = match default_name.strip_prefix('~') {
+            Some(rest) => format!("$HOME{rest}"),
+            None if default_name.starts_with('/') => default_name,
+            None => format!("$HOME/{default_name}"),
+        };
+        if env_var.is_empty() {
+            return format!("echo \"{default_dir}\"");
+        }
+        let env_dir = match env_subdir {
+            Some(subdir) => format!("${env_var}/{subdir}"),
+            None => format!("${env_var}"),
+        };
+        format!(
+            "if [ -n \"${env_var}\" ]; then echo \"{env_dir}\"; else echo \"{default_dir}\"; fi"
+        )
+    }
+
+    /// 获取所有工具（内置工具 + `tools.d` 中的自定义工具）
     pub fn all() -> Vec<Tool> {
-        vec![Tool::claude_code(), Tool::codex(), Tool::gemini_cli()]
+        let mut tools = vec![Tool::claude_code(), Tool::codex(), Tool::gemini_cli()];
+        tools.extend(
+            crate::services::tool::custom_tools::custom_tools()
+                .iter()
+                .map(|def| def.to_tool()),
+        );
+        tools
     }
 
     /// 根据 ID 获取工具
@@ -58,21 +221,21 @@ impl Tool {
             "claude-code" => vec!["settings.json".to_string()],
             "codex" => vec!["config.toml".to_string(), "auth.json".to_string()],
             "gemini-cli" => vec!["settings.json".to_string(), ".env".to_string()],
-            _ => vec![self.config_file.clone()],
+            _ => crate::services::tool::custom_tools::find_custom_tool(&self.id)
+                .map(|def| def.config_files)
+                .unwrap_or_else(|| vec![self.config_file.clone()]),
         }
     }
 
     /// Claude Code 定义
     pub fn claude_code() -> Tool {
-        let home_dir = dirs::home_dir().expect("无法获取用户主目录");
-
         Tool {
             id: "claude-code".to_string(),
             name: "Claude Code".to_string(),
             group_name: "Claude Code 专用分组".to_string(),
             npm_package: "@anthropic-ai/claude-code".to_string(),
             check_command: "claude --version".to_string(),
-            config_dir: home_dir.join(".claude"),
+            config_dir: Self::resolve_config_dir("claude-code").config_dir,
             config_file: "settings.json".to_string(),
             env_vars: EnvVars {
                 api_key: "ANTHROPIC_AUTH_TOKEN".to_string(),
@@ -84,15 +247,13 @@ impl Tool {
 
     /// CodeX 定义
     pub fn codex() -> Tool {
-        let home_dir = dirs::home_dir().expect("无法获取用户主目录");
-
         Tool {
             id: "codex".to_string(),
             name: "CodeX".to_string(),
             group_name: "CodeX 专用分组".to_string(),
             npm_package: "@openai/codex".to_string(),
             check_command: "codex --version".to_string(),
-            config_dir: home_dir.join(".codex"),
+            config_dir: Self::resolve_config_dir("codex").config_dir,
             config_file: "config.toml".to_string(),
             env_vars: EnvVars {
                 api_key: "OPENAI_API_KEY".to_string(),
@@ -104,15 +265,13 @@ impl Tool {
 
     /// Gemini CLI 定义
     pub fn gemini_cli() -> Tool {
-        let home_dir = dirs::home_dir().expect("无法获取用户主目录");
-
         Tool {
             id: "gemini-cli".to_string(),
             name: "Gemini CLI".to_string(),
             group_name: "Gemini CLI 专用分组".to_string(),
             npm_package: "@google/gemini-cli".to_string(),
             check_command: "gemini --version".to_string(),
-            config_dir: home_dir.join(".gemini"),
+            config_dir: Self::resolve_config_dir("gemini-cli").config_dir,
             config_file: "settings.json".to_string(),
             env_vars: EnvVars {
                 api_key: "GEMINI_API_KEY".to_string(),
@@ -141,6 +300,9 @@ impl Tool {
             "gemini-cli" => {
                 methods.push(InstallMethod::Npm);
             }
+            _ if !self.npm_package.is_empty() => {
+                methods.push(InstallMethod::Npm);
+            }
             _ => {}
         }
 
@@ -199,6 +361,7 @@ pub const DUCKCODING_BASE_URL: &str = "https://jp.duckcoding.com";
 
 /// 工具环境类型
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub enum ToolType {
     /// 本地环境
     Local,
@@ -231,6 +394,7 @@ impl ToolType {
 
 /// SSH 连接配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct SSHConfig {
     /// 显示名称（如"开发服务器"、"生产环境"）
     pub display_name: String,
@@ -246,6 +410,7 @@ pub struct SSHConfig {
 
 /// 工具实例（具体环境中的安装）
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct ToolInstance {
     /// 实例唯一标识（如"claude-code-local", "codex-wsl-Ubuntu", "gemini-ssh-dev"）
     pub instance_id: String,
@@ -374,6 +539,7 @@ impl ToolInstance {
 
 /// 工具更新结果
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct UpdateResult {
     pub success: bool,
     pub message: String,
@@ -384,3 +550,74 @@ pub struct UpdateResult {
     pub mirror_is_stale: Option<bool>,  // 镜像是否滞后
     pub tool_id: Option<String>,        // 工具ID，用于批量检查时识别工具
 }
+
+/// 工具状态快照（安装状态 + 版本检查结果，一次返回）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct ToolStatusSnapshot {
+    pub tools: Vec<ToolStatus>,
+    pub updates: Vec<UpdateResult>,
+    /// 本次结果是否全部来自缓存
+    pub from_cache: bool,
+    /// 生成时间（Unix 秒）
+    pub checked_at: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_config_dir_precedence() {
+        let home = tempfile::tempdir().unwrap();
+        let home_dir = home.path();
+        let mut overrides = HashMap::new();
+        let env = |key: &str| match key {
+            "CLAUDE_CONFIG_DIR" => Some("~/dotfiles/claude".to_string()),
+            "GEMINI_CLI_HOME" => Some("/opt/gemini-home".to_string()),
+            _ => None,
+        };
+
+        let claude = resolve_config_dir_with("claude-code", home_dir, &overrides, env);
+        assert_eq!(claude.source, ConfigDirSource::Env);
+        assert_eq!(claude.config_dir, home_dir.join("dotfiles/claude"));
+
+        let gemini = resolve_config_dir_with("gemini-cli", home_dir, &overrides, env);
+        assert_eq!(gemini.config_dir, PathBuf::from("/opt/gemini-home/.gemini"));
+
+        let codex = resolve_config_dir_with("codex", home_dir, &overrides, env);
+        assert_eq!(codex.source, ConfigDirSource::Default);
+        assert_eq!(codex.config_dir, home_dir.join(".codex"));
+        assert!(!codex.exists);
+
+        overrides.insert("claude-code".to_string(), "/srv/claude".to_string());
+        let claude = resolve_config_dir_with("claude-code", home_dir, &overrides, env);
+        assert_eq!(claude.source, ConfigDirSource::Override);
+        assert_eq!(claude.config_dir, PathBuf::from("/srv/claude"));
+    }
+
+    #[test]
+    fn test_posix_config_dir_script() {
+        assert_eq!(
+            Tool::posix_config_dir_script("claude-code"),
+            r#"if [ -n "$CLAUDE_CONFIG_DIR" ]; then echo "$CLAUDE_CONFIG_DIR"; else echo "$HOME/.claude"; fi"#
+        );
+        assert_eq!(
+            Tool::posix_config_dir_script("gemini-cli"),
+            r#"if [ -n "$GEMINI_CLI_HOME" ]; then echo "$GEMINI_CLI_HOME/.gemini"; else echo "$HOME/.gemini"; fi"#
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_config_dir_follows_symlink() {
+        let home = tempfile::tempdir().unwrap();
+        let target = home.path().join("dotfiles/codex");
+        std::fs::create_dir_all(&target).unwrap();
+        std::os::unix::fs::symlink(&target, home.path().join(".codex")).unwrap();
+
+        let codex = resolve_config_dir_with("codex", home.path(), &HashMap::new(), |_| None);
+        assert_eq!(codex.config_dir, target);
+        assert!(codex.exists);
+    }
+}
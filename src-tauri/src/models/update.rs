@@ -3,6 +3,7 @@ use std::path::PathBuf;
 
 /// 更新信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct UpdateInfo {
     pub current_version: String,
     pub latest_version: String,
@@ -16,6 +17,7 @@ pub struct UpdateInfo {
 
 /// 更新状态
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub enum UpdateStatus {
     #[default]
     Idle, // 空闲状态
@@ -32,6 +34,7 @@ pub enum UpdateStatus {
 
 /// 下载进度
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct DownloadProgress {
     pub downloaded_bytes: u64,
     pub total_bytes: u64,
@@ -52,6 +55,7 @@ pub struct DownloadTask {
 
 /// 平台信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct PlatformInfo {
     pub os: String,
     pub arch: String,
@@ -62,6 +66,7 @@ pub struct PlatformInfo {
 
 /// 包格式信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct PackageFormatInfo {
     pub platform: String,
     pub preferred_formats: Vec<String>,
@@ -70,6 +75,7 @@ pub struct PackageFormatInfo {
 
 /// 更新配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct UpdateConfig {
     pub auto_check: bool,
     pub check_interval_hours: u32,
@@ -98,6 +104,7 @@ pub struct UpdateApiResponse {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct UpdateUrls {
     // Windows 平台
     pub windows: Option<String>,     // 通用 Windows 安装包
@@ -0,0 +1,318 @@
+// 本机管理 API
+//
+// 启用后监听 `127.0.0.1:<port>`，供 Shell 提示符、编辑器插件等外部工具在不经过 Tauri IPC 的情况下集成：
+//
+//   GET  /api/v1/status                               版本与各工具代理状态
+//   GET  /api/v1/profiles?tool=<tool_id>              Profile 列表
+//   POST /api/v1/profiles/<tool_id>/<name>/activate   激活 Profile
+//   GET  /api/v1/stats/summary?since=7d&tool=&tag=&group_by=model   成本汇总
+//
+// 所有请求须携带 `Authorization: Bearer <token>`；响应均为 JSON，错误为 `{"error": "..."}`
+
+use anyhow::{bail, Result};
+use bytes::Bytes;
+use http_body_util::Full;
+use hyper::body::Incoming;
+use hyper::{header, Method, Request, Response, StatusCode};
+use once_cell::sync::Lazy;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use crate::models::config::AdminApiConfig;
+use crate::services::local_server::{HttpResponse, LocalServer};
+use crate::services::profile_manager::ProfileManager;
+use crate::services::proxy::ProxyManager;
+use crate::services::proxy_config_manager::ProxyConfigManager;
+use crate::services::token_stats::{
+    parse_since, CostGroupBy, CostSummaryQuery, TokenStatsAnalytics,
+};
+use crate::utils::config::config_dir;
+
+static SERVER: Lazy<LocalServer<AdminApiConfig>> = Lazy::new(|| LocalServer::new("管理 API"));
+
+/// 管理 API 监听状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct AdminApiStatus {
+    pub config: AdminApiConfig,
+    pub running: bool,
+    /// 接口地址（未运行时为 None）
+    pub endpoint: Option<String>,
+}
+
+/// 单个工具的代理状态
+#[derive(Debug, Clone, Serialize)]
+struct ProxyStatusEntry {
+    tool_id: String,
+    enabled: bool,
+    running: bool,
+    /// 监听地址
+    address: String,
+    /// 当前激活的 Profile
+    active_profile: Option<String>,
+}
+
+struct AdminState {
+    token: String,
+    proxy_manager: Arc<ProxyManager>,
+}
+
+/// 生成访问令牌（32 字节随机数的十六进制）
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// 按配置启动、重启或停止管理 API（配置未变化时不做任何操作）
+pub async fn apply_config(config: &AdminApiConfig, proxy_manager: Arc<ProxyManager>) -> Result<()> {
+    SERVER
+        .apply(config, |config| {
+            if !config.enabled {
+                return Ok(None);
+            }
+            let Some(token) = config.token.clone().filter(|t| !t.trim().is_empty()) else {
+                bail!("管理 API 未设置访问令牌");
+            };
+            let addr = SocketAddr::from(([127, 0, 0, 1], config.port));
+            let state = Arc::new(AdminState {
+                token,
+                proxy_manager,
+            });
+            Ok(Some((addr, move |req| handle(req, state.clone()))))
+        })
+        .await
+}
+
+/// 当前管理 API 状态
+pub async fn admin_api_status(config: AdminApiConfig) -> AdminApiStatus {
+    let running = SERVER.running().await;
+    AdminApiStatus {
+        endpoint: running
+            .as_ref()
+            .map(|(_, addr)| format!("http://{}/api/v1", addr)),
+        running: running.is_some(),
+        config: running.map(|(config, _)| config).unwrap_or(config),
+    }
+}
+
+async fn handle(req: Request<Incoming>, state: Arc<AdminState>) -> HttpResponse {
+    if !authorized(&req, &state.token) {
+        return error(StatusCode::UNAUTHORIZED, "缺少或错误的访问令牌");
+    }
+
+    let query: HashMap<String, String> = req
+        .uri()
+        .query()
+        .map(|q| {
+            url::form_urlencoded::parse(q.as_bytes())
+                .into_owned()
+                .collect()
+        })
+        .unwrap_or_default();
+    let segments: Vec<String> = req
+        .uri()
+        .path()
+        .trim_matches('/')
+        .split('/')
+        .map(|s| {
+            urlencoding::decode(s)
+                .map(|d| d.into_owned())
+                .unwrap_or_else(|_| s.to_string())
+        })
+        .collect();
+    let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+
+    let result = match (req.method(), segments.as_slice()) {
+        (&Method::GET, ["api", "v1", "status"]) => status(&state).await,
+        (&Method::GET, ["api", "v1", "profiles"]) => {
+            list_profiles(query.get("tool").map(String::as_str))
+        }
+        // 参数错误（Profile 不存在、时间范围无效等）返回 400
+        (&Method::POST, ["api", "v1", "profiles", tool_id, name, "activate"]) => {
+            return respond(activate_profile(tool_id, name), StatusCode::BAD_REQUEST);
+        }
+        (&Method::GET, ["api", "v1", "stats", "summary"]) => {
+            return respond(stats_summary(query).await, StatusCode::BAD_REQUEST);
+        }
+        _ => return error(StatusCode::NOT_FOUND, "接口不存在"),
+    };
+    respond(result, StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+fn respond(result: Result<serde_json::Value>, error_status: StatusCode) -> HttpResponse {
+    match result {
+        Ok(body) => json(StatusCode::OK, body),
+        Err(e) => error(error_status, &format!("{:#}", e)),
+    }
+}
+
+/// 校验 `Authorization: Bearer <token>`（定长比较，避免按耗时猜测令牌）
+pub(crate) fn authorized(req: &Request<Incoming>, token: &str) -> bool {
+    let Some(provided) = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+    else {
+        return false;
+    };
+    let (a, b) = (provided.trim().as_bytes(), token.as_bytes());
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+async fn status(state: &AdminState) -> Result<serde_json::Value> {
+    let store = ProxyConfigManager::new()?.load_proxy_store()?;
+    let profiles = ProfileManager::new()?;
+    let mut proxies = Vec::new();
+    for tool_id in store.tool_ids() {
+        let Some(config) = store.get_config(&tool_id) else {
+            continue;
+        };
+        proxies.push(ProxyStatusEntry {
+            enabled: config.enabled,
+            running: state.proxy_manager.is_running(&tool_id).await,
+            address: config.listen_address(),
+            active_profile: profiles.get_active_profile_name(&tool_id).ok().flatten(),
+            tool_id,
+        });
+    }
+    Ok(serde_json::json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "proxies": proxies,
+    }))
+}
+
+fn list_profiles(tool_id: Option<&str>) -> Result<serde_json::Value> {
+    let descriptors: Vec<_> = ProfileManager::new()?
+        .list_all_descriptors()?
+        .into_iter()
+        .filter(|d| tool_id.is_none_or(|t| d.tool_id == t))
+        .collect();
+    Ok(serde_json::to_value(descriptors)?)
+}
+
+fn activate_profile(tool_id: &str, name: &str) -> Result<serde_json::Value> {
+    ProfileManager::new()?.activate_profile(tool_id, name)?;
+    tracing::info!(tool_id = %tool_id, profile = %name, "通过管理 API 激活 Profile");
+    Ok(serde_json::json!({ "tool_id": tool_id, "active_profile": name }))
+}
+
+async fn stats_summary(query: HashMap<String, String>) -> Result<serde_json::Value> {
+    let since = query.get("since").map(String::as_str).unwrap_or("7d");
+    let start_time = parse_since(since, chrono::Local::now())?;
+    let group_by = match query.get("group_by").map(String::as_str) {
+        None | Some("model") => CostGroupBy::Model,
+        Some("config") => CostGroupBy::Config,
+        Some("session") => CostGroupBy::Session,
+        Some("upstream_key") => CostGroupBy::UpstreamKey,
+        Some("client_key") => CostGroupBy::ClientKey,
+        Some(other) => bail!("未知的分组方式: {}", other),
+    };
+    let cost_query = CostSummaryQuery {
+        start_time: Some(start_time),
+        end_time: None,
+        tool_type: query.get("tool").cloned(),
+        session_id: None,
+        tag: query.get("tag").cloned(),
+        group_by,
+    };
+
+    let db_path = config_dir()
+        .map_err(|e| anyhow::anyhow!(e))?
+        .join("token_stats.db");
+    let summaries = tokio::task::spawn_blocking(move || {
+        if !db_path.exists() {
+            return Ok(Vec::new());
+        }
+        TokenStatsAnalytics::new(db_path).query_cost_summary(&cost_query)
+    })
+    .await??;
+    Ok(serde_json::json!({
+        "start_time": start_time,
+        "total_cost": summaries.iter().map(|s| s.total_cost).sum::<f64>(),
+        "request_count": summaries.iter().map(|s| s.request_count).sum::<i64>(),
+        "groups": summaries,
+    }))
+}
+
+fn json(status: StatusCode, body: serde_json::Value) -> HttpResponse {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/json; charset=utf-8")
+        .body(Full::new(Bytes::from(body.to_string())))
+        .unwrap()
+}
+
+fn error(status: StatusCode, message: &str) -> HttpResponse {
+    json(status, serde_json::json!({ "error": message }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::local_server;
+    use tokio::net::TcpListener;
+    use tokio_util::sync::CancellationToken;
+
+    async fn start_server(token: &str) -> (String, CancellationToken) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let cancel = CancellationToken::new();
+        let state = Arc::new(AdminState {
+            token: token.to_string(),
+            proxy_manager: Arc::new(ProxyManager::new()),
+        });
+        tokio::spawn(local_server::serve(
+            listener,
+            cancel.clone(),
+            "管理 API",
+            move |req| handle(req, state.clone()),
+        ));
+        (base, cancel)
+    }
+
+    #[tokio::test]
+    async fn test_requires_bearer_token() {
+        let (base, cancel) = start_server("secret-token").await;
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+        let url = format!("{base}/api/v1/stats/summary?since=3y");
+
+        let resp = client.get(&url).send().await.unwrap();
+        assert_eq!(resp.status(), 401);
+        let resp = client.get(&url).bearer_auth("wrong").send().await.unwrap();
+        assert_eq!(resp.status(), 401);
+
+        // 令牌正确：进入路由（无效的时间范围返回 400）
+        let resp = client
+            .get(&url)
+            .bearer_auth("secret-token")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 400);
+        let body: serde_json::Value = resp.json().await.unwrap();
+        assert!(body["error"].as_str().unwrap().contains("3y"));
+
+        let resp = client
+            .delete(format!("{base}/api/v1/status"))
+            .bearer_auth("secret-token")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 404);
+
+        cancel.cancel();
+    }
+
+    #[test]
+    fn test_generate_token() {
+        let token = generate_token();
+        assert_eq!(token.len(), 64);
+        assert!(token.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(token, generate_token());
+    }
+}
@@ -0,0 +1,388 @@
+//! 凭证操作审计日志
+//!
+//! 记录所有影响凭证的操作（Profile 激活 / 保存 / 删除、透明代理配置变更、
+//! 外部配置变更的允许 / 阻止决定），供安全审查使用：
+//! - 默认存储于 `~/.duckcoding/audit/audit.jsonl`，只追加，每行一条记录
+//!   （ProfileManager 等调用方可指定目录，测试时写入临时目录）
+//! - 每条记录包含上一条记录的哈希，并以 SHA-256 计算自身哈希（哈希链），
+//!   任何修改、删除或插入都会在 [`verify`] 时被发现
+//! - 只记录变化了哪些字段，不记录 API Key 等敏感内容
+//!
+//! 写入失败只输出警告，不影响被审计的操作本身。
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::utils::config::config_dir;
+
+/// 审计日志文件名
+const AUDIT_FILE: &str = "audit.jsonl";
+
+/// 从文件末尾查找最后一条记录时每次读取的字节数
+const TAIL_CHUNK: u64 = 4096;
+
+/// 第一条记录的 prev_hash
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// 审计操作类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    ProfileActivated,
+    ProfileSaved,
+    ProfileDeleted,
+    ProxyConfigUpdated,
+    ExternalChangeAllowed,
+    ExternalChangeBlocked,
+}
+
+/// 单条审计记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct AuditEntry {
+    /// 序号（从 1 开始连续递增）
+    pub seq: u64,
+    pub timestamp: DateTime<Utc>,
+    pub action: AuditAction,
+    pub tool_id: String,
+    /// 操作对象（如 Profile 名称）
+    pub target: Option<String>,
+    /// 操作详情（变化的字段等，不含敏感内容）
+    pub details: Value,
+    /// 上一条记录的哈希
+    pub prev_hash: String,
+    /// 本条记录的哈希
+    pub hash: String,
+}
+
+/// 参与哈希计算的字段（不含 hash 本身）
+#[derive(Serialize)]
+struct HashedFields<'a> {
+    seq: u64,
+    timestamp: &'a DateTime<Utc>,
+    action: AuditAction,
+    tool_id: &'a str,
+    target: &'a Option<String>,
+    details: &'a Value,
+    prev_hash: &'a str,
+}
+
+impl AuditEntry {
+    fn compute_hash(&self) -> String {
+        let fields = HashedFields {
+            seq: self.seq,
+            timestamp: &self.timestamp,
+            action: self.action,
+            tool_id: &self.tool_id,
+            target: &self.target,
+            details: &self.details,
+            prev_hash: &self.prev_hash,
+        };
+        let payload = serde_json::to_vec(&fields).unwrap_or_default();
+        let digest = Sha256::digest(&payload);
+        digest.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+/// 审计日志校验结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct AuditVerification {
+    /// 哈希链完整
+    pub valid: bool,
+    /// 已校验的记录数
+    pub entries: usize,
+    /// 首个校验失败的行号（从 1 开始）
+    pub broken_at_line: Option<usize>,
+    /// 失败原因
+    pub error: Option<String>,
+    /// 最后一条记录的哈希（可另行保存，用于发现整段截断）
+    pub head_hash: Option<String>,
+}
+
+/// 默认审计日志目录（`~/.duckcoding/audit`）
+pub fn audit_dir() -> Result<PathBuf> {
+    Ok(config_dir()
+        .map_err(|e| anyhow!("无法获取配置目录: {}", e))?
+        .join("audit"))
+}
+
+/// 审计日志文件路径
+pub fn audit_path() -> Result<PathBuf> {
+    Ok(audit_dir()?.join(AUDIT_FILE))
+}
+
+/// 记录一条审计日志到默认目录（失败时只输出警告）
+pub fn record(action: AuditAction, tool_id: &str, target: Option<&str>, details: Value) {
+    match audit_dir() {
+        Ok(dir) => record_in(&dir, action, tool_id, target, details),
+        Err(e) => {
+            tracing::warn!(action = ?action, tool_id = %tool_id, error = ?e, "写入审计日志失败")
+        }
+    }
+}
+
+/// 记录一条审计日志到指定目录（失败时只输出警告）
+pub fn record_in(
+    dir: &Path,
+    action: AuditAction,
+    tool_id: &str,
+    target: Option<&str>,
+    details: Value,
+) {
+    if let Err(e) = append(&dir.join(AUDIT_FILE), action, tool_id, target, details) {
+        tracing::warn!(action = ?action, tool_id = %tool_id, error = ?e, "写入审计日志失败");
+    }
+}
+
+fn append(
+    path: &Path,
+    action: AuditAction,
+    tool_id: &str,
+    target: Option<&str>,
+    details: Value,
+) -> Result<AuditEntry> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).context("创建审计日志目录失败")?;
+    }
+    // 跨进程排他锁（GUI 与 CLI 可能同时写入）
+    let lock_file = File::create(path.with_extension("lock")).context("创建锁文件失败")?;
+    lock_file.lock_exclusive().context("获取文件锁失败")?;
+
+    let last = last_entry(path)?;
+    let mut entry = AuditEntry {
+        seq: last.as_ref().map_or(1, |e| e.seq + 1),
+        timestamp: Utc::now(),
+        action,
+        tool_id: tool_id.to_string(),
+        target: target.map(str::to_string),
+        details,
+        prev_hash: last.map_or_else(|| GENESIS_HASH.to_string(), |e| e.hash),
+        hash: String::new(),
+    };
+    entry.hash = entry.compute_hash();
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .context("打开审计日志失败")?;
+    writeln!(file, "{}", serde_json::to_string(&entry)?).context("写入审计日志失败")?;
+    file.sync_data().ok();
+    Ok(entry)
+}
+
+/// 读取最后一条记录（从文件末尾按块向前查找，不读取整个文件）
+fn last_entry(path: &Path) -> Result<Option<AuditEntry>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let mut file = File::open(path).context("读取审计日志失败")?;
+    let mut pos = file.metadata()?.len();
+    let mut tail: Vec<u8> = Vec::new();
+    let line = loop {
+        let end = tail
+            .iter()
+            .rposition(|b| !b.is_ascii_whitespace())
+            .map_or(0, |i| i + 1);
+        match tail[..end].iter().rposition(|&b| b == b'\n') {
+            Some(start) => break &tail[start + 1..end],
+            None if pos == 0 => break &tail[..end],
+            None => {}
+        }
+        let read = pos.min(TAIL_CHUNK);
+        pos -= read;
+        let mut chunk = vec![0; read as usize];
+        file.seek(SeekFrom::Start(pos))?;
+        file.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&tail);
+        tail = chunk;
+    };
+    if line.is_empty() {
+        return Ok(None);
+    }
+    serde_json::from_slice(line)
+        .map(Some)
+        .context("审计日志最后一条记录无法解析")
+}
+
+/// 比较两个 JSON 对象，返回值发生变化的顶层字段（按字段名排序）
+pub fn changed_fields(before: &Value, after: &Value) -> Vec<String> {
+    let empty = serde_json::Map::new();
+    let before = before.as_object().unwrap_or(&empty);
+    let after = after.as_object().unwrap_or(&empty);
+    let mut fields: Vec<String> = before
+        .keys()
+        .chain(after.keys())
+        .filter(|key| before.get(*key) != after.get(*key))
+        .cloned()
+        .collect();
+    fields.sort();
+    fields.dedup();
+    fields
+}
+
+/// 读取审计日志（最新在前，可按工具过滤）
+pub fn read_entries(tool_id: Option<&str>, limit: usize) -> Result<Vec<AuditEntry>> {
+    let path = audit_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let file = File::open(&path).context("读取审计日志失败")?;
+    let mut entries: Vec<AuditEntry> = BufReader::new(file)
+        .lines()
+        .map_while(|line| line.ok())
+        .filter_map(|line| serde_json::from_str::<AuditEntry>(&line).ok())
+        .filter(|e| tool_id.is_none_or(|t| e.tool_id == t))
+        .collect();
+    entries.reverse();
+    entries.truncate(limit);
+    Ok(entries)
+}
+
+/// 校验审计日志的哈希链
+pub fn verify() -> Result<AuditVerification> {
+    verify_file(&audit_path()?)
+}
+
+fn verify_file(path: &Path) -> Result<AuditVerification> {
+    let mut result = AuditVerification {
+        valid: true,
+        entries: 0,
+        broken_at_line: None,
+        error: None,
+        head_hash: None,
+    };
+    if !path.exists() {
+        return Ok(result);
+    }
+
+    let file = File::open(path).context("读取审计日志失败")?;
+    let mut prev_hash = GENESIS_HASH.to_string();
+    let mut expected_seq = 1;
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let error = match serde_json::from_str::<AuditEntry>(&line) {
+            Err(e) => Some(format!("记录无法解析: {}", e)),
+            Ok(entry) if entry.seq != expected_seq => Some(format!(
+                "序号不连续：期望 {}，实际 {}",
+                expected_seq, entry.seq
+            )),
+            Ok(entry) if entry.prev_hash != prev_hash => {
+                Some("prev_hash 与上一条记录不一致".to_string())
+            }
+            Ok(entry) if entry.compute_hash() != entry.hash => {
+                Some("记录内容与哈希不一致".to_string())
+            }
+            Ok(entry) => {
+                prev_hash = entry.hash;
+                expected_seq += 1;
+                result.entries += 1;
+                None
+            }
+        };
+        if let Some(error) = error {
+            result.valid = false;
+            result.broken_at_line = Some(index + 1);
+            result.error = Some(error);
+            break;
+        }
+    }
+    result.head_hash = (result.entries > 0).then_some(prev_hash);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_hash_chain_detects_tampering() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit").join(AUDIT_FILE);
+
+        let first = append(
+            &path,
+            AuditAction::ProfileSaved,
+            "codex",
+            Some("work"),
+            json!({ "created": true }),
+        )
+        .unwrap();
+        let second = append(
+            &path,
+            AuditAction::ProfileActivated,
+            "codex",
+            Some("work"),
+            json!({}),
+        )
+        .unwrap();
+        assert_eq!(first.prev_hash, GENESIS_HASH);
+        assert_eq!(second.seq, 2);
+        assert_eq!(second.prev_hash, first.hash);
+
+        let verification = verify_file(&path).unwrap();
+        assert!(verification.valid);
+        assert_eq!(verification.entries, 2);
+        assert_eq!(verification.head_hash, Some(second.hash.clone()));
+
+        // 修改第一条记录的内容
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, content.replacen("\"work\"", "\"home\"", 1)).unwrap();
+        let verification = verify_file(&path).unwrap();
+        assert!(!verification.valid);
+        assert_eq!(verification.broken_at_line, Some(1));
+
+        // 删除中间记录
+        std::fs::write(&path, content.lines().nth(1).unwrap()).unwrap();
+        let verification = verify_file(&path).unwrap();
+        assert!(!verification.valid);
+        assert!(verification.error.unwrap().contains("序号"));
+    }
+
+    #[test]
+    fn test_last_entry_spans_read_chunks() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(AUDIT_FILE);
+        assert!(last_entry(&path).unwrap().is_none());
+
+        let large = "x".repeat(TAIL_CHUNK as usize * 2);
+        for index in 0..3 {
+            append(
+                &path,
+                AuditAction::ProxyConfigUpdated,
+                "codex",
+                None,
+                json!({ "index": index, "padding": large }),
+            )
+            .unwrap();
+        }
+        let last = last_entry(&path).unwrap().unwrap();
+        assert_eq!(last.seq, 3);
+        assert_eq!(last.details["index"], 2);
+        assert!(verify_file(&path).unwrap().valid);
+    }
+
+    #[test]
+    fn test_changed_fields() {
+        let before = json!({ "port": 8787, "real_api_key": "sk-a", "upstreams": [] });
+        let after = json!({ "port": 8787, "real_api_key": "sk-b", "key_pool": [] });
+        assert_eq!(
+            changed_fields(&before, &after),
+            vec!["key_pool", "real_api_key", "upstreams"]
+        );
+        assert!(changed_fields(&after, &after).is_empty());
+    }
+}
@@ -1,7 +1,13 @@
 // Balance Service Module
 //
 // 余额监控配置管理服务
+// - monitor: 余额聚合监控（定时查询各供应商与 Profile 的额度，历史写入 SQLite）
 
 mod manager;
+pub mod monitor;
 
 pub use manager::BalanceManager;
+pub use monitor::{
+    BalanceHistoryLog, BalanceService, BalanceSnapshot, BalanceSourceKind, BalanceSummary,
+    LowBalanceEvent,
+};
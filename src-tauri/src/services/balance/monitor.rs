@@ -0,0 +1,687 @@
+//! 余额聚合监控
+//!
+//! 定时查询所有已配置来源的剩余额度：
+//! - 供应商（NEW API）：使用用户 ID 与系统访问令牌请求 `/api/user/self`
+//! - Profile（Claude Code / Codex）：请求 OpenAI 兼容账单接口，同一 Base URL + API Key 只查询一次
+//!
+//! 每次查询结果写入 `balance_history` 表（与 token_logs 同库），保留最近 30 天；
+//! 剩余额度低于阈值时通过回调通知，GUI 转发为 `balance-low` 事件。
+//!
+//! 金额单位为 USD，时间戳单位为毫秒。
+
+use crate::data::DataManager;
+use crate::models::config::BalanceMonitorConfig;
+use crate::models::provider::Provider;
+use crate::services::profile_manager::credentials::fetch_quota;
+use crate::services::profile_manager::{ProfileManager, ProfilesStore};
+use crate::services::provider::health::normalize_base_url;
+use crate::services::ProviderManager;
+use crate::utils::config::read_global_config;
+use crate::utils::config_dir;
+use anyhow::{anyhow, Context, Result};
+use futures_util::stream::{self, StreamExt};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
+
+/// 单次查询超时时间
+const FETCH_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// 查询的最大并发数
+const MAX_CONCURRENT_FETCHES: usize = 4;
+
+/// 历史记录保留时长（毫秒）
+const HISTORY_RETENTION_MS: i64 = 30 * 24 * 60 * 60 * 1000;
+
+/// 查询时返回的历史记录数
+const HISTORY_LIMIT: usize = 200;
+
+/// NEW API 额度单位（500000 = 1 USD）
+const NEW_API_QUOTA_PER_USD: f64 = 500_000.0;
+
+/// 查询额度的工具（Gemini 官方接口没有账单查询）
+const BALANCE_TOOLS: [&str; 2] = ["claude-code", "codex"];
+
+/// 内置代理 Profile 前缀（指向本地代理，不参与查询）
+const PROXY_PROFILE_PREFIX: &str = "dc_proxy_";
+
+static BALANCE_SERVICE: Lazy<BalanceService> = Lazy::new(BalanceService::default);
+
+/// 余额来源类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BalanceSourceKind {
+    Provider,
+    Profile,
+}
+
+/// 单次查询结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BalanceSnapshot {
+    pub timestamp: i64,
+    pub remaining: Option<f64>,
+    pub used: Option<f64>,
+    pub total: Option<f64>,
+    pub error: Option<String>,
+}
+
+/// 单个来源的余额与历史
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceSummary {
+    pub source_id: String,
+    pub kind: BalanceSourceKind,
+    /// 供应商名称或 Profile 名称
+    pub name: String,
+    pub tool_id: Option<String>,
+    pub base_url: String,
+    /// 共用该上游与 API Key 的 Profile
+    pub profile_names: Vec<String>,
+    /// 最近一次查询结果（尚未查询时为空）
+    pub latest: Option<BalanceSnapshot>,
+    /// 历史记录（新的在前）
+    pub history: Vec<BalanceSnapshot>,
+}
+
+/// 低余额事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LowBalanceEvent {
+    pub source_id: String,
+    pub kind: BalanceSourceKind,
+    pub name: String,
+    pub remaining: f64,
+    pub threshold: f64,
+    /// 是否同时发送系统桌面通知
+    pub desktop_notification: bool,
+    pub checked_at: i64,
+}
+
+/// 低余额回调
+pub type LowBalanceNotifier = Box<dyn Fn(LowBalanceEvent) + Send + Sync>;
+
+/// 查询凭证
+#[derive(Debug, Clone)]
+enum BalanceCredential {
+    NewApi {
+        user_id: String,
+        access_token: String,
+    },
+    Billing {
+        api_key: String,
+    },
+}
+
+/// 待查询的来源
+#[derive(Debug, Clone)]
+struct BalanceSource {
+    id: String,
+    kind: BalanceSourceKind,
+    name: String,
+    tool_id: Option<String>,
+    base_url: String,
+    profile_names: Vec<String>,
+    credential: BalanceCredential,
+}
+
+/// 收集所有可查询的来源（跳过未配置凭证的供应商与 Profile）
+fn collect_sources(providers: &[Provider], store: &ProfilesStore) -> Vec<BalanceSource> {
+    let mut sources: Vec<BalanceSource> = providers
+        .iter()
+        .filter(|p| !p.user_id.is_empty() && !p.access_token.is_empty())
+        .map(|p| BalanceSource {
+            id: format!("provider:{}", p.id),
+            kind: BalanceSourceKind::Provider,
+            name: p.name.clone(),
+            tool_id: None,
+            base_url: normalize_base_url(&p.website_url),
+            profile_names: Vec::new(),
+            credential: BalanceCredential::NewApi {
+                user_id: p.user_id.clone(),
+                access_token: p.access_token.clone(),
+            },
+        })
+        .collect();
+
+    for tool_id in BALANCE_TOOLS {
+        let mut profiles = store.get_tool_profiles(tool_id).unwrap_or_default();
+        profiles.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut tool_sources: Vec<(String, BalanceSource)> = Vec::new();
+        for (profile_name, api_key, base_url) in profiles {
+            let base_url = normalize_base_url(&base_url);
+            if profile_name.starts_with(PROXY_PROFILE_PREFIX)
+                || base_url.is_empty()
+                || api_key.is_empty()
+            {
+                continue;
+            }
+            match tool_sources
+                .iter_mut()
+                .find(|(key, s)| *key == api_key && s.base_url == base_url)
+            {
+                Some((_, source)) => source.profile_names.push(profile_name),
+                None => tool_sources.push((
+                    api_key.clone(),
+                    BalanceSource {
+                        id: format!("profile:{tool_id}/{profile_name}"),
+                        kind: BalanceSourceKind::Profile,
+                        name: profile_name.clone(),
+                        tool_id: Some(tool_id.to_string()),
+                        base_url,
+                        profile_names: vec![profile_name],
+                        credential: BalanceCredential::Billing { api_key },
+                    },
+                )),
+            }
+        }
+        sources.extend(tool_sources.into_iter().map(|(_, source)| source));
+    }
+    sources
+}
+
+/// 解析 NEW API `/api/user/self` 响应
+fn parse_new_api_user(body: &Value) -> Result<BalanceSnapshot> {
+    if body.get("success").and_then(Value::as_bool) != Some(true) {
+        let message = body
+            .get("message")
+            .and_then(Value::as_str)
+            .unwrap_or("未知错误");
+        return Err(anyhow!("API 返回错误: {}", message));
+    }
+    let data = body
+        .get("data")
+        .ok_or_else(|| anyhow!("未获取到用户信息"))?;
+    let quota = |field: &str| {
+        data.get(field)
+            .and_then(Value::as_f64)
+            .map(|v| v / NEW_API_QUOTA_PER_USD)
+    };
+    let remaining = quota("quota");
+    let used = quota("used_quota");
+    Ok(BalanceSnapshot {
+        timestamp: chrono::Utc::now().timestamp_millis(),
+        remaining,
+        used,
+        total: remaining.zip(used).map(|(r, u)| r + u),
+        error: None,
+    })
+}
+
+async fn fetch_source(client: &reqwest::Client, source: &BalanceSource) -> BalanceSnapshot {
+    let failed = |error: String| BalanceSnapshot {
+        timestamp: chrono::Utc::now().timestamp_millis(),
+        remaining: None,
+        used: None,
+        total: None,
+        error: Some(error),
+    };
+
+    match &source.credential {
+        BalanceCredential::NewApi {
+            user_id,
+            access_token,
+        } => {
+            let response = client
+                .get(format!("{}/api/user/self", source.base_url))
+                .timeout(FETCH_TIMEOUT)
+                .header("Authorization", format!("Bearer {access_token}"))
+                .header("New-Api-User", user_id)
+                .send()
+                .await;
+            let response = match response {
+                Ok(response) if response.status().is_success() => response,
+                Ok(response) => return failed(format!("上游返回 HTTP {}", response.status())),
+                Err(e) => return failed(e.to_string()),
+            };
+            match response.json::<Value>().await {
+                Ok(body) => parse_new_api_user(&body).unwrap_or_else(|e| failed(e.to_string())),
+                Err(e) => failed(format!("解析响应失败: {e}")),
+            }
+        }
+        BalanceCredential::Billing { api_key } => {
+            match fetch_quota(client, &source.base_url, api_key).await {
+                Some(quota) => BalanceSnapshot {
+                    timestamp: chrono::Utc::now().timestamp_millis(),
+                    remaining: quota.remaining,
+                    used: quota.used,
+                    total: quota.total,
+                    error: None,
+                },
+                None => failed("上游不支持账单查询接口".to_string()),
+            }
+        }
+    }
+}
+
+/// 余额历史记录
+pub struct BalanceHistoryLog {
+    db_path: PathBuf,
+}
+
+impl BalanceHistoryLog {
+    pub fn new(db_path: PathBuf) -> Self {
+        Self { db_path }
+    }
+
+    /// 使用默认统计数据库（与 token_logs 同库）
+    pub fn open_default() -> Result<Self> {
+        let dir = config_dir().map_err(|e| anyhow!(e))?;
+        Ok(Self::new(dir.join("token_stats.db")))
+    }
+
+    /// 创建余额记录表
+    pub fn init_table(&self) -> Result<()> {
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+        manager
+            .execute_raw(
+                "CREATE TABLE IF NOT EXISTS balance_history (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    timestamp INTEGER NOT NULL,
+                    source_id TEXT NOT NULL,
+                    remaining REAL,
+                    used REAL,
+                    total REAL,
+                    error TEXT
+                );
+                CREATE INDEX IF NOT EXISTS idx_balance_history_source
+                    ON balance_history(source_id, timestamp)",
+            )
+            .context("Failed to create balance_history table")?;
+        Ok(())
+    }
+
+    /// 记录一轮查询结果，并清理超出保留期的历史
+    pub fn record(&self, snapshots: &[(String, BalanceSnapshot)]) -> Result<()> {
+        if snapshots.is_empty() {
+            return Ok(());
+        }
+        self.init_table()?;
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+
+        let amount = |v: Option<f64>| v.map(|v| v.to_string()).unwrap_or_default();
+        let params: Vec<Vec<String>> = snapshots
+            .iter()
+            .map(|(source_id, snapshot)| {
+                vec![
+                    snapshot.timestamp.to_string(),
+                    source_id.clone(),
+                    amount(snapshot.remaining),
+                    amount(snapshot.used),
+                    amount(snapshot.total),
+                    snapshot.error.clone().unwrap_or_default(),
+                ]
+            })
+            .collect();
+        manager
+            .execute_batch(
+                "INSERT INTO balance_history
+                    (timestamp, source_id, remaining, used, total, error)
+                VALUES (?1, ?2, NULLIF(?3, ''), NULLIF(?4, ''), NULLIF(?5, ''), NULLIF(?6, ''))",
+                &params,
+            )
+            .context("Failed to insert balance snapshots")?;
+
+        let cutoff = snapshots
+            .iter()
+            .map(|(_, s)| s.timestamp)
+            .max()
+            .unwrap_or(0)
+            - HISTORY_RETENTION_MS;
+        manager
+            .execute(
+                "DELETE FROM balance_history WHERE timestamp < ?1",
+                &[&cutoff.to_string()],
+            )
+            .context("Failed to prune balance history")?;
+        Ok(())
+    }
+
+    /// 查询来源的历史记录（新的在前）
+    fn history(&self, source_id: &str) -> Result<Vec<BalanceSnapshot>> {
+        self.init_table()?;
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+
+        Ok(manager.transaction(|tx| {
+            let mut stmt = tx.prepare(
+                "SELECT timestamp, remaining, used, total, error
+                FROM balance_history
+                WHERE source_id = ?1
+                ORDER BY timestamp DESC, id DESC LIMIT ?2",
+            )?;
+            let history = stmt
+                .query_map(rusqlite::params![source_id, HISTORY_LIMIT], |row| {
+                    Ok(BalanceSnapshot {
+                        timestamp: row.get(0)?,
+                        remaining: row.get(1)?,
+                        used: row.get(2)?,
+                        total: row.get(3)?,
+                        error: row.get(4)?,
+                    })
+                })?
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(crate::data::DataError::Database)?;
+            Ok(history)
+        })?)
+    }
+}
+
+/// 读取余额监控配置
+fn monitor_config() -> BalanceMonitorConfig {
+    read_global_config()
+        .ok()
+        .flatten()
+        .map(|cfg| cfg.balance_monitor)
+        .unwrap_or_default()
+}
+
+fn load_sources() -> Result<Vec<BalanceSource>> {
+    let providers = ProviderManager::new()?.list_providers()?;
+    let store = ProfileManager::new()?.load_profiles_store()?;
+    Ok(collect_sources(&providers, &store))
+}
+
+/// 余额聚合服务（定时查询并提醒低余额）
+#[derive(Default)]
+pub struct BalanceService {
+    /// 当前低于阈值的来源（回升后移除，以便再次提醒）
+    low: Mutex<HashSet<String>>,
+    notifier: RwLock<Option<LowBalanceNotifier>>,
+}
+
+impl BalanceService {
+    /// 全局余额服务
+    pub fn global() -> &'static BalanceService {
+        &BALANCE_SERVICE
+    }
+
+    /// 设置低余额回调
+    pub fn set_notifier(&self, notifier: LowBalanceNotifier) {
+        *self.notifier.write().unwrap() = Some(notifier);
+    }
+
+    /// 持续定时查询（不会返回）
+    ///
+    /// 每轮重新读取配置：关闭监控或修改间隔、阈值后下一轮生效
+    pub async fn run(&self) {
+        // 首次延迟，避免影响启动速度
+        tokio::time::sleep(Duration::from_secs(30)).await;
+        loop {
+            let config = monitor_config();
+            if config.enabled {
+                if let Err(e) = self.refresh_all(&config).await {
+                    tracing::warn!(error = %e, "余额查询失败");
+                }
+            }
+            let minutes = u64::from(config.interval_minutes.max(1));
+            tokio::time::sleep(Duration::from_secs(minutes * 60)).await;
+        }
+    }
+
+    /// 立即查询所有来源
+    pub async fn refresh_all(&self, config: &BalanceMonitorConfig) -> Result<()> {
+        let sources = tokio::task::spawn_blocking(load_sources)
+            .await
+            .map_err(|e| anyhow!(e))??;
+        if sources.is_empty() {
+            return Ok(());
+        }
+
+        let client = crate::http_client::build_client().map_err(|e| anyhow!(e))?;
+        let results: Vec<(BalanceSource, BalanceSnapshot)> = stream::iter(sources)
+            .map(|source| {
+                let client = &client;
+                async move {
+                    let snapshot = fetch_source(client, &source).await;
+                    (source, snapshot)
+                }
+            })
+            .buffer_unordered(MAX_CONCURRENT_FETCHES)
+            .collect()
+            .await;
+
+        let failed = results.iter().filter(|(_, s)| s.error.is_some()).count();
+        tracing::debug!(total = results.len(), failed, "余额查询完成");
+
+        for (source, snapshot) in &results {
+            self.observe(source, snapshot, config);
+        }
+
+        let rows: Vec<(String, BalanceSnapshot)> = results
+            .into_iter()
+            .map(|(source, snapshot)| (source.id, snapshot))
+            .collect();
+        tokio::task::spawn_blocking(move || {
+            BalanceHistoryLog::open_default().and_then(|log| log.record(&rows))
+        })
+        .await
+        .map_err(|e| anyhow!(e))?
+    }
+
+    /// 剩余额度低于阈值时触发回调（持续低于阈值只提醒一次）
+    fn observe(
+        &self,
+        source: &BalanceSource,
+        snapshot: &BalanceSnapshot,
+        config: &BalanceMonitorConfig,
+    ) {
+        let Some(remaining) = snapshot.remaining else {
+            return;
+        };
+        let threshold = config.low_balance_threshold_usd;
+        if threshold <= 0.0 || remaining >= threshold {
+            self.low.lock().unwrap().remove(&source.id);
+            return;
+        }
+        if !self.low.lock().unwrap().insert(source.id.clone()) {
+            return;
+        }
+
+        tracing::warn!(
+            source = %source.id,
+            remaining,
+            threshold,
+            "余额低于提醒阈值"
+        );
+        if let Some(notifier) = self.notifier.read().unwrap().as_ref() {
+            notifier(LowBalanceEvent {
+                source_id: source.id.clone(),
+                kind: source.kind,
+                name: source.name.clone(),
+                remaining,
+                threshold,
+                desktop_notification: config.desktop_notification,
+                checked_at: snapshot.timestamp,
+            });
+        }
+    }
+
+    /// 查询所有来源的余额与历史（`refresh` 为 true 时先立即查询一轮）
+    pub async fn get_all_balances(&self, refresh: bool) -> Result<Vec<BalanceSummary>> {
+        if refresh {
+            self.refresh_all(&monitor_config()).await?;
+        }
+        tokio::task::spawn_blocking(|| -> Result<Vec<BalanceSummary>> {
+            let log = BalanceHistoryLog::open_default()?;
+            load_sources()?
+                .into_iter()
+                .map(|source| -> Result<BalanceSummary> {
+                    let history = log.history(&source.id)?;
+                    Ok(BalanceSummary {
+                        source_id: source.id,
+                        kind: source.kind,
+                        name: source.name,
+                        tool_id: source.tool_id,
+                        base_url: source.base_url,
+                        profile_names: source.profile_names,
+                        latest: history.first().cloned(),
+                        history,
+                    })
+                })
+                .collect()
+        })
+        .await
+        .map_err(|e| anyhow!(e))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::profile_manager::CodexProfile;
+    use serde_json::json;
+    use std::sync::Arc;
+    use tempfile::tempdir;
+
+    fn snapshot(timestamp: i64, remaining: Option<f64>) -> BalanceSnapshot {
+        BalanceSnapshot {
+            timestamp,
+            remaining,
+            used: Some(1.0),
+            total: remaining.map(|r| r + 1.0),
+            error: remaining.is_none().then(|| "timeout".to_string()),
+        }
+    }
+
+    fn profile_source(id: &str) -> BalanceSource {
+        BalanceSource {
+            id: id.to_string(),
+            kind: BalanceSourceKind::Profile,
+            name: "main".to_string(),
+            tool_id: Some("codex".to_string()),
+            base_url: "https://api.example.com".to_string(),
+            profile_names: vec!["main".to_string()],
+            credential: BalanceCredential::Billing {
+                api_key: "sk-test".to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_collect_sources() {
+        let mut provider = crate::models::ProviderStore::default().providers.remove(0);
+        let unconfigured = provider.clone();
+        provider.id = "relay".to_string();
+        provider.user_id = "42".to_string();
+        provider.access_token = "token".to_string();
+
+        let mut store = ProfilesStore::new();
+        for (name, key, url) in [
+            ("b", "sk-1", "https://api.example.com/"),
+            ("a", "sk-1", "https://api.example.com"),
+            ("c", "sk-2", "https://api.example.com"),
+            ("d", "", "https://other.example.com"),
+            ("dc_proxy_codex", "sk-local", "http://127.0.0.1:8788"),
+        ] {
+            store.codex.insert(
+                name.to_string(),
+                CodexProfile {
+                    api_key: key.to_string(),
+                    base_url: url.to_string(),
+                    wire_api: "responses".to_string(),
+                    source: Default::default(),
+                    created_at: chrono::Utc::now(),
+                    updated_at: chrono::Utc::now(),
+                    raw_config_toml: None,
+                    raw_auth_json: None,
+                    pricing_template_id: None,
+                },
+            );
+        }
+
+        let sources = collect_sources(&[unconfigured, provider], &store);
+        let ids: Vec<&str> = sources.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec!["provider:relay", "profile:codex/a", "profile:codex/c"]
+        );
+        assert_eq!(sources[1].profile_names, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_parse_new_api_user() {
+        let body = json!({
+            "success": true,
+            "message": "",
+            "data": { "quota": 2_500_000, "used_quota": 500_000 }
+        });
+        let snapshot = parse_new_api_user(&body).unwrap();
+        assert_eq!(snapshot.remaining, Some(5.0));
+        assert_eq!(snapshot.used, Some(1.0));
+        assert_eq!(snapshot.total, Some(6.0));
+
+        let denied = json!({ "success": false, "message": "无权进行此操作" });
+        assert!(parse_new_api_user(&denied)
+            .unwrap_err()
+            .to_string()
+            .contains("无权进行此操作"));
+    }
+
+    #[test]
+    fn test_observe_notifies_once_below_threshold() {
+        let service = BalanceService::default();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        service.set_notifier(Box::new(move |event| sink.lock().unwrap().push(event)));
+
+        let config = BalanceMonitorConfig::default();
+        let source = profile_source("profile:codex/main");
+        service.observe(&source, &snapshot(1, Some(10.0)), &config);
+        service.observe(&source, &snapshot(2, Some(4.0)), &config);
+        service.observe(&source, &snapshot(3, Some(3.0)), &config);
+        // 查询失败不改变状态
+        service.observe(&source, &snapshot(4, None), &config);
+        service.observe(&source, &snapshot(5, Some(8.0)), &config);
+        service.observe(&source, &snapshot(6, Some(1.0)), &config);
+
+        let disabled = BalanceMonitorConfig {
+            low_balance_threshold_usd: 0.0,
+            ..config
+        };
+        service.observe(
+            &profile_source("profile:codex/other"),
+            &snapshot(7, Some(0.5)),
+            &disabled,
+        );
+
+        let events = events.lock().unwrap();
+        let summary: Vec<(i64, f64)> = events.iter().map(|e| (e.checked_at, e.remaining)).collect();
+        assert_eq!(summary, vec![(2, 4.0), (6, 1.0)]);
+        assert_eq!(events[0].threshold, 5.0);
+    }
+
+    #[test]
+    fn test_record_and_history() {
+        let dir = tempdir().unwrap();
+        let log = BalanceHistoryLog::new(dir.path().join("balance.db"));
+        let now = HISTORY_RETENTION_MS * 2;
+
+        let rows: Vec<(String, BalanceSnapshot)> = vec![
+            (
+                "provider:a".to_string(),
+                snapshot(now - HISTORY_RETENTION_MS - 1, Some(9.0)),
+            ),
+            ("provider:a".to_string(), snapshot(now - 2_000, Some(8.0))),
+            ("provider:a".to_string(), snapshot(now - 1_000, None)),
+            ("provider:b".to_string(), snapshot(now, Some(1.5))),
+        ];
+        log.record(&rows).unwrap();
+
+        let history = log.history("provider:a").unwrap();
+        // 超出保留期的记录已被清理
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].timestamp, now - 1_000);
+        assert_eq!(history[0].error.as_deref(), Some("timeout"));
+        assert_eq!(history[0].remaining, None);
+        assert_eq!(history[1].remaining, Some(8.0));
+        assert_eq!(log.history("provider:b").unwrap()[0].total, Some(2.5));
+        assert!(log.history("provider:c").unwrap().is_empty());
+    }
+}
@@ -10,6 +10,7 @@
 //! - `codex`: Codex 配置管理
 //! - `gemini`: Gemini CLI 配置管理
 //! - `watcher`: 外部变更检测与文件监听
+//! - `simulate`: 模拟外部变更事件（开发调试用）
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
@@ -19,6 +20,7 @@ use serde_json::Value;
 pub mod claude;
 pub mod codex;
 pub mod gemini;
+pub mod simulate;
 pub mod types;
 pub mod utils;
 pub mod watcher;
@@ -27,7 +29,12 @@ pub mod watcher;
 pub use types::*;
 
 // 重导出 watcher 函数
-pub use watcher::{initialize_snapshots, start_watcher, ExternalConfigChange};
+#[cfg(feature = "gui")]
+pub use watcher::start_watcher;
+pub use watcher::{
+    initialize_snapshots, start_supervised_watcher, start_watcher_with, watcher_health,
+    ChangeNotifier, ExternalConfigChange, RecoveryNotifier, WatcherHealth, WatcherRecovery,
+};
 
 /// 统一的工具配置管理接口
 ///
@@ -0,0 +1,191 @@
+//! 模拟外部配置变更（开发调试用）
+//!
+//! 无需手动编辑配置文件即可合成 `ExternalConfigChange` 事件，
+//! 事件经过与真实检测相同的过滤、变更日志持久化与通知流程，
+//! 便于确定性地验证前端交互与自动化规则。
+//!
+//! 仅在 Debug 构建或设置环境变量 `DUCKCODING_DEV_TOOLS=1` 时可用。
+
+use super::watcher::{self, ChangeType, ExternalConfigChange, FieldChange};
+use crate::models::config::ConfigWatchConfig;
+use crate::models::Tool;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+/// 启用开发工具的环境变量
+pub const DEV_TOOLS_ENV: &str = "DUCKCODING_DEV_TOOLS";
+
+/// 模拟的字段变更（根据新旧值是否存在推断变更类型）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulatedField {
+    pub path: String,
+    #[serde(default)]
+    pub old_value: Option<JsonValue>,
+    #[serde(default)]
+    pub new_value: Option<JsonValue>,
+}
+
+/// 模拟变更请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulatedChange {
+    pub tool_id: String,
+    pub fields: Vec<SimulatedField>,
+    /// 强制指定敏感标记（None 时按敏感字段配置判定）
+    #[serde(default)]
+    pub is_sensitive: Option<bool>,
+    /// 跳过黑名单与监听模式过滤
+    #[serde(default)]
+    pub skip_filters: bool,
+}
+
+/// 模拟结果
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulationResult {
+    /// 实际发布的事件（被过滤掉时为 None）
+    pub change: Option<ExternalConfigChange>,
+    /// 是否已通过运行中的 watcher 通知前端
+    pub delivered: bool,
+}
+
+/// 是否允许使用模拟命令
+pub fn simulation_enabled() -> bool {
+    cfg!(debug_assertions) || std::env::var(DEV_TOOLS_ENV).is_ok_and(|v| v == "1")
+}
+
+/// 按请求构造变更事件（应用与真实检测相同的过滤规则）
+fn build_change(
+    request: &SimulatedChange,
+    watch_config: &ConfigWatchConfig,
+) -> Result<Option<ExternalConfigChange>> {
+    let tool =
+        Tool::by_id(&request.tool_id).ok_or_else(|| anyhow!("未知工具: {}", request.tool_id))?;
+    if request.fields.is_empty() {
+        return Err(anyhow!("至少需要一个字段变更"));
+    }
+
+    let mut fields = Vec::with_capacity(request.fields.len());
+    for field in &request.fields {
+        let change_type = match (&field.old_value, &field.new_value) {
+            (Some(_), Some(_)) => ChangeType::Modified,
+            (None, Some(_)) => ChangeType::Added,
+            (Some(_), None) => ChangeType::Deleted,
+            (None, None) => return Err(anyhow!("字段 {} 缺少新旧值", field.path)),
+        };
+        fields.push(FieldChange {
+            path: field.path.clone(),
+            old_value: field.old_value.clone(),
+            new_value: field.new_value.clone(),
+            change_type,
+        });
+    }
+
+    let filter_config = if request.skip_filters {
+        ConfigWatchConfig {
+            mode: crate::models::config::WatchMode::Full,
+            blacklist: Default::default(),
+            ..watch_config.clone()
+        }
+    } else {
+        watch_config.clone()
+    };
+
+    let mut change = watcher::filter_changes(&tool, fields, &filter_config);
+    if let (Some(change), Some(sensitive)) = (change.as_mut(), request.is_sensitive) {
+        change.is_sensitive = sensitive;
+    }
+    Ok(change)
+}
+
+/// 合成外部变更事件并走完整发布流程
+///
+/// watcher 未运行时仍写入变更日志，但不会通知前端。
+pub fn simulate_external_change(request: &SimulatedChange) -> Result<SimulationResult> {
+    let global_config = crate::utils::config::read_global_config()
+        .map_err(|e| anyhow!(e))?
+        .ok_or_else(|| anyhow!("全局配置文件不存在"))?;
+
+    let Some(change) = build_change(request, &global_config.config_watch)? else {
+        tracing::info!("模拟变更被过滤规则忽略: {}", request.tool_id);
+        return Ok(SimulationResult {
+            change: None,
+            delivered: false,
+        });
+    };
+
+    tracing::info!("发布模拟配置变更: {}", change.tool_id);
+    let delivered = watcher::publish_to_active_watcher(change.clone());
+    if !delivered {
+        tracing::warn!("配置守护未运行，模拟变更仅写入变更日志");
+        let noop: watcher::ChangeNotifier = Box::new(|_| {});
+        watcher::publish_change(change.clone(), &noop);
+    }
+
+    Ok(SimulationResult {
+        change: Some(change),
+        delivered,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::config::WatchMode;
+    use serde_json::json;
+
+    fn request(fields: Vec<(&str, Option<JsonValue>, Option<JsonValue>)>) -> SimulatedChange {
+        SimulatedChange {
+            tool_id: "claude-code".to_string(),
+            fields: fields
+                .into_iter()
+                .map(|(path, old_value, new_value)| SimulatedField {
+                    path: path.to_string(),
+                    old_value,
+                    new_value,
+                })
+                .collect(),
+            is_sensitive: None,
+            skip_filters: false,
+        }
+    }
+
+    #[test]
+    fn test_build_change_applies_watch_filters() {
+        let config = ConfigWatchConfig {
+            mode: WatchMode::Default,
+            ..Default::default()
+        };
+        let req = request(vec![
+            ("theme", Some(json!("dark")), Some(json!("light"))),
+            ("env.ANTHROPIC_AUTH_TOKEN", None, Some(json!("sk-new"))),
+        ]);
+
+        // 默认模式仅保留敏感字段
+        let change = build_change(&req, &config).unwrap().unwrap();
+        assert_eq!(change.changed_fields.len(), 1);
+        assert!(matches!(
+            change.changed_fields[0].change_type,
+            ChangeType::Added
+        ));
+        assert!(change.is_sensitive);
+
+        // 跳过过滤并强制非敏感
+        let req = SimulatedChange {
+            skip_filters: true,
+            is_sensitive: Some(false),
+            ..req
+        };
+        let change = build_change(&req, &config).unwrap().unwrap();
+        assert_eq!(change.changed_fields.len(), 2);
+        assert!(!change.is_sensitive);
+
+        // 非敏感字段在默认模式下被过滤
+        let filtered = request(vec![("theme", Some(json!("dark")), None)]);
+        assert!(build_change(&filtered, &config).unwrap().is_none());
+
+        let mut unknown = request(vec![("theme", None, Some(json!(1)))]);
+        unknown.tool_id = "unknown".to_string();
+        assert!(build_change(&unknown, &config).is_err());
+        assert!(build_change(&request(vec![("theme", None, None)]), &config).is_err());
+    }
+}
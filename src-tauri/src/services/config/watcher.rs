@@ -5,25 +5,28 @@
 // 2. 监听配置文件变更（notify）
 // 3. 检测变更并发送事件到前端
 // 4. Block/Allow 操作在 commands 层实现
+// 5. 支持额外监听项目内的配置文件或目录（`ConfigWatchConfig.watched_paths`），
+//    快照与事件以 `path:<路径>` 作为 ID
 
 use crate::data::changelogs::ConfigChangeRecord;
-use crate::models::config::{ConfigWatchConfig, WatchMode};
+use crate::models::config::{ConfigWatchConfig, WatchMode, WatchedPath};
 use crate::models::Tool;
+use crate::utils::expand_home;
 use anyhow::{anyhow, Result};
 use serde::Serialize;
 use serde_json::Value as JsonValue;
-use std::collections::HashMap;
-use std::path::Path;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
 use std::sync::{mpsc, Arc};
 use std::thread;
 use std::time::{Duration, Instant};
-use tauri::{AppHandle, Emitter};
 
 // ========== 导出类型 ==========
 
 /// 变更类型
 #[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 #[serde(rename_all = "lowercase")]
 pub enum ChangeType {
     Modified,
@@ -33,6 +36,7 @@ pub enum ChangeType {
 
 /// 单个字段的变更
 #[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct FieldChange {
     /// 字段路径
     pub path: String,
@@ -46,6 +50,7 @@ pub struct FieldChange {
 
 /// 外部配置变更事件
 #[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
 pub struct ExternalConfigChange {
     /// 工具 ID
     pub tool_id: String,
@@ -57,19 +62,173 @@ pub struct ExternalConfigChange {
     pub is_sensitive: bool,
 }
 
+// ========== 监听目标 ==========
+
+/// 额外监听路径的 ID 前缀
+const WATCHED_PATH_PREFIX: &str = "path:";
+
+/// 监听目标（工具配置目录或额外监听路径）
+#[derive(Debug, Clone)]
+struct WatchTarget {
+    /// 快照与事件使用的 ID（工具 ID 或 `path:<路径>`）
+    id: String,
+    /// 配置文件所在目录
+    dir: PathBuf,
+    /// 主配置文件名（其字段路径不加文件前缀；监听整个目录时为空）
+    main_file: String,
+    /// 监听的文件名列表
+    files: Vec<String>,
+}
+
+impl WatchTarget {
+    fn from_tool(tool: &Tool) -> Self {
+        Self {
+            id: tool.id.clone(),
+            dir: tool.config_dir.clone(),
+            main_file: tool.config_file.clone(),
+            files: tool.config_files(),
+        }
+    }
+
+    /// 额外监听路径（目录时列出其中支持的配置文件）
+    fn from_watched_path(path: &str) -> Self {
+        let id = watched_path_id(path);
+        let resolved = expand_home(path);
+        if resolved.is_dir() {
+            let mut files: Vec<String> = std::fs::read_dir(&resolved)
+                .map(|entries| {
+                    entries
+                        .flatten()
+                        .filter(|e| e.path().is_file())
+                        .filter_map(|e| e.file_name().into_string().ok())
+                        .filter(|name| is_supported_file(name))
+                        .collect()
+                })
+                .unwrap_or_default();
+            files.sort();
+            return Self {
+                id,
+                dir: resolved,
+                main_file: String::new(),
+                files,
+            };
+        }
+        let file_name = resolved
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        Self {
+            id,
+            dir: resolved.parent().map(Path::to_path_buf).unwrap_or_default(),
+            main_file: file_name.clone(),
+            files: vec![file_name],
+        }
+    }
+
+    /// 文件变更是否属于该目标
+    fn contains(&self, path: &Path) -> bool {
+        if path.parent() != Some(self.dir.as_path()) {
+            return false;
+        }
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            return false;
+        };
+        if self.main_file.is_empty() {
+            is_supported_file(name)
+        } else {
+            self.files.iter().any(|f| f == name)
+        }
+    }
+
+    /// 事件中展示的路径
+    fn display_path(&self) -> PathBuf {
+        if self.main_file.is_empty() {
+            self.dir.clone()
+        } else {
+            self.dir.join(&self.main_file)
+        }
+    }
+
+    /// 该目标适用的黑名单与敏感字段
+    fn rules<'a>(
+        &self,
+        watch_config: &'a ConfigWatchConfig,
+    ) -> (&'a [String], Option<&'a [String]>) {
+        if let Some(watched) = watch_config
+            .watched_paths
+            .iter()
+            .find(|w| watched_path_id(&w.path) == self.id)
+        {
+            return (&watched.blacklist, Some(&watched.sensitive_fields));
+        }
+        (
+            watch_config
+                .blacklist
+                .get(&self.id)
+                .map(Vec::as_slice)
+                .unwrap_or_default(),
+            watch_config
+                .sensitive_fields
+                .get(&self.id)
+                .map(Vec::as_slice),
+        )
+    }
+}
+
+/// 额外监听路径在快照与事件中使用的 ID
+pub fn watched_path_id(path: &str) -> String {
+    format!("{}{}", WATCHED_PATH_PREFIX, path)
+}
+
+/// 根据 ID 解析监听目标所在目录（工具配置目录或额外监听路径）
+pub fn watch_target_dir(id: &str) -> Option<PathBuf> {
+    resolve_target(id).map(|t| t.dir)
+}
+
+fn resolve_target(id: &str) -> Option<WatchTarget> {
+    match id.strip_prefix(WATCHED_PATH_PREFIX) {
+        Some(path) => Some(WatchTarget::from_watched_path(path)),
+        None => Tool::by_id(id).map(|tool| WatchTarget::from_tool(&tool)),
+    }
+}
+
+/// 当前需要监听的全部目标（内置工具 + 自定义工具 + 额外监听路径）
+fn watch_targets(watched_paths: &[WatchedPath]) -> Vec<WatchTarget> {
+    Tool::all()
+        .iter()
+        .map(WatchTarget::from_tool)
+        .chain(
+            watched_paths
+                .iter()
+                .map(|w| WatchTarget::from_watched_path(&w.path)),
+        )
+        .collect()
+}
+
+/// 读取配置中的额外监听路径（读取失败时视为空）
+fn configured_watched_paths() -> Vec<WatchedPath> {
+    crate::utils::config::read_global_config()
+        .ok()
+        .flatten()
+        .map(|c| c.config_watch.watched_paths)
+        .unwrap_or_default()
+}
+
+fn is_supported_file(name: &str) -> bool {
+    name.ends_with(".json") || name.ends_with(".toml") || name.ends_with(".env")
+}
+
 // ========== 快照管理 ==========
 
-/// 启动时初始化所有工具的配置快照
+/// 启动时初始化所有工具及额外监听路径的配置快照
 pub fn initialize_snapshots() -> Result<()> {
     tracing::info!("初始化配置快照...");
 
-    let tools = vec![Tool::claude_code(), Tool::codex(), Tool::gemini_cli()];
-
-    for tool in tools {
-        if let Err(e) = save_snapshot_for_tool(&tool) {
-            tracing::warn!("保存 {} 配置快照失败: {}", tool.id, e);
+    for target in watch_targets(&configured_watched_paths()) {
+        if let Err(e) = save_snapshot_for_target(&target) {
+            tracing::warn!("保存 {} 配置快照失败: {}", target.id, e);
         } else {
-            tracing::debug!("已保存 {} 配置快照", tool.id);
+            tracing::debug!("已保存 {} 配置快照", target.id);
         }
     }
 
@@ -78,15 +237,38 @@ pub fn initialize_snapshots() -> Result<()> {
 
 /// 为单个工具保存配置快照
 pub fn save_snapshot_for_tool(tool: &Tool) -> Result<()> {
+    save_snapshot_for_target(&WatchTarget::from_tool(tool))
+}
+
+/// 按 ID 保存配置快照（工具 ID 或 `path:<路径>`）
+pub fn save_snapshot_by_id(id: &str) -> Result<()> {
+    let target = resolve_target(id).ok_or_else(|| anyhow!("未找到监听目标: {}", id))?;
+    save_snapshot_for_target(&target)
+}
+
+fn save_snapshot_for_target(target: &WatchTarget) -> Result<()> {
+    let files = read_target_files(target)?;
+
+    if files.is_empty() {
+        tracing::warn!("{} 没有可用的配置文件", target.id);
+        return Ok(());
+    }
+
+    // 保存到独立快照文件
+    crate::data::snapshots::save_snapshot_files(&target.id, files)?;
+
+    Ok(())
+}
+
+/// 读取目标的所有配置文件（TOML / ENV 转换为 JSON 对象）
+fn read_target_files(target: &WatchTarget) -> Result<HashMap<String, JsonValue>> {
     use crate::data::DataManager;
-    use std::collections::HashMap;
 
     let manager = DataManager::new();
     let mut files = HashMap::new();
 
-    // 读取所有配置文件
-    for filename in tool.config_files() {
-        let config_path = tool.config_dir.join(&filename);
+    for filename in &target.files {
+        let config_path = target.dir.join(filename);
         if !config_path.exists() {
             tracing::debug!("配置文件不存在，跳过: {}", config_path.display());
             continue;
@@ -111,15 +293,7 @@ pub fn save_snapshot_for_tool(tool: &Tool) -> Result<()> {
         files.insert(filename.clone(), content);
     }
 
-    if files.is_empty() {
-        tracing::warn!("工具 {} 没有可用的配置文件", tool.id);
-        return Ok(());
-    }
-
-    // 保存到独立快照文件
-    crate::data::snapshots::save_snapshot_files(&tool.id, files)?;
-
-    Ok(())
+    Ok(files)
 }
 
 /// 将 TOML DocumentMut 转换为 JSON
@@ -250,59 +424,44 @@ fn contains_sensitive(fields: &[FieldChange], sensitive: &[String]) -> bool {
 
 // ========== 变更检测 ==========
 
-/// 检测单个工具的配置变更
-fn detect_tool_change(
-    tool: &Tool,
+/// 检测单个监听目标的配置变更
+fn detect_change(
+    target: &WatchTarget,
     watch_config: &ConfigWatchConfig,
 ) -> Result<Option<ExternalConfigChange>> {
-    use crate::data::DataManager;
-
     // 获取快照
-    let snapshot = match crate::data::snapshots::get_snapshot(&tool.id)? {
+    let snapshot = match crate::data::snapshots::get_snapshot(&target.id)? {
         Some(s) => s,
         None => {
             // 首次检测：自动保存当前状态作为快照
-            tracing::debug!("首次检测配置文件，自动保存快照: {}", tool.id);
-            save_snapshot_for_tool(tool)?;
+            tracing::debug!("首次检测配置文件，自动保存快照: {}", target.id);
+            save_snapshot_for_target(target)?;
             return Ok(None);
         }
     };
 
     // 读取所有当前配置文件
-    let manager = DataManager::new();
-    let mut current_files = std::collections::HashMap::new();
-
-    for filename in tool.config_files() {
-        let config_path = tool.config_dir.join(&filename);
-        if !config_path.exists() {
-            continue;
-        }
-
-        let content = if filename.ends_with(".json") {
-            manager.json_uncached().read(&config_path)?
-        } else if filename.ends_with(".toml") {
-            let doc = manager.toml().read_document(&config_path)?;
-            toml_to_json(&doc)?
-        } else if filename.ends_with(".env") || filename == ".env" {
-            let env_map = manager.env().read(&config_path)?;
-            serde_json::to_value(env_map)?
-        } else {
-            continue;
-        };
+    let current_files = read_target_files(target)?;
+    let all_changes = diff_files(target, &snapshot.files, &current_files);
 
-        current_files.insert(filename.clone(), content);
-    }
+    Ok(filter_target_changes(target, all_changes, watch_config))
+}
 
-    // 比较所有文件的变更
+/// 比较快照与当前文件的所有字段变更
+fn diff_files(
+    target: &WatchTarget,
+    snapshot_files: &HashMap<String, JsonValue>,
+    current_files: &HashMap<String, JsonValue>,
+) -> Vec<FieldChange> {
     let mut all_changes = Vec::new();
 
-    for (filename, new_content) in &current_files {
-        let old_content = snapshot.files.get(filename);
+    for (filename, new_content) in current_files {
+        let old_content = snapshot_files.get(filename);
         if let Some(old) = old_content {
             let file_changes = compute_diff(old, new_content, "");
             // 为每个变更字段添加文件前缀（如果不是主配置文件）
             for mut change in file_changes {
-                if filename != &tool.config_file {
+                if filename != &target.main_file {
                     change.path = format!("{}:{}", filename, change.path);
                 }
                 all_changes.push(change);
@@ -314,27 +473,42 @@ fn detect_tool_change(
     }
 
     // 检测删除的文件
-    for filename in snapshot.files.keys() {
+    for filename in snapshot_files.keys() {
         if !current_files.contains_key(filename) {
             tracing::debug!("检测到删除的配置文件: {}", filename);
         }
     }
 
+    all_changes
+}
+
+/// 按黑名单与监听模式过滤字段变更，生成外部变更事件（过滤后为空时返回 None）
+pub(super) fn filter_changes(
+    tool: &Tool,
+    all_changes: Vec<FieldChange>,
+    watch_config: &ConfigWatchConfig,
+) -> Option<ExternalConfigChange> {
+    filter_target_changes(&WatchTarget::from_tool(tool), all_changes, watch_config)
+}
+
+fn filter_target_changes(
+    target: &WatchTarget,
+    all_changes: Vec<FieldChange>,
+    watch_config: &ConfigWatchConfig,
+) -> Option<ExternalConfigChange> {
     if all_changes.is_empty() {
-        return Ok(None);
+        return None;
     }
+    let (blacklist, sensitive) = target.rules(watch_config);
 
     // 应用黑名单过滤
-    let mut changed_fields = all_changes;
-    if let Some(blacklist) = watch_config.blacklist.get(&tool.id) {
-        changed_fields = filter_blacklist(changed_fields, blacklist);
-    }
+    let mut changed_fields = filter_blacklist(all_changes, blacklist);
 
     // 根据监听模式过滤
     match watch_config.mode {
         WatchMode::Default => {
             // 默认模式：仅保留敏感字段变更
-            if let Some(sensitive) = watch_config.sensitive_fields.get(&tool.id) {
+            if let Some(sensitive) = sensitive {
                 changed_fields.retain(|field| contains_sensitive_field(&field.path, sensitive));
             } else {
                 // 没有敏感字段定义，清空变更列表
@@ -347,23 +521,18 @@ fn detect_tool_change(
     }
 
     if changed_fields.is_empty() {
-        return Ok(None);
+        return None;
     }
 
     // 检查是否包含敏感字段
-    let is_sensitive = if let Some(sensitive) = watch_config.sensitive_fields.get(&tool.id) {
-        contains_sensitive(&changed_fields, sensitive)
-    } else {
-        false
-    };
+    let is_sensitive = sensitive.is_some_and(|s| contains_sensitive(&changed_fields, s));
 
-    let config_path = tool.config_dir.join(&tool.config_file);
-    Ok(Some(ExternalConfigChange {
-        tool_id: tool.id.clone(),
-        path: config_path.to_string_lossy().to_string(),
+    Some(ExternalConfigChange {
+        tool_id: target.id.clone(),
+        path: target.display_path().to_string_lossy().to_string(),
         changed_fields,
         is_sensitive,
-    }))
+    })
 }
 
 /// 检查字段路径是否匹配敏感字段模式（支持文件前缀）
@@ -401,10 +570,101 @@ static WATCHER_HANDLE: once_cell::sync::Lazy<Mutex<Option<WatcherHandle>>> =
 static INTERNAL_CHANGE_SUPPRESS_UNTIL: once_cell::sync::Lazy<Mutex<HashMap<String, Instant>>> =
     once_cell::sync::Lazy::new(|| Mutex::new(HashMap::new()));
 
-/// Watcher 句柄
+/// 配置守护句柄（监听与处理线程由监督线程持有）
 struct WatcherHandle {
-    _watcher: RecommendedWatcher,
     stop_signal: Arc<AtomicBool>,
+    notifier: Arc<Mutex<ChangeNotifier>>,
+}
+
+/// 重启退避：初始 1 秒，每次翻倍，上限 60 秒
+const RESTART_BACKOFF_INITIAL: Duration = Duration::from_secs(1);
+const RESTART_BACKOFF_MAX: Duration = Duration::from_secs(60);
+
+/// 稳定运行超过该时长后重置退避
+const RESTART_BACKOFF_RESET: Duration = Duration::from_secs(300);
+
+/// 心跳超过该时长未更新视为处理线程卡住（处理线程每 500ms 更新一次）
+const HEARTBEAT_STALE_MS: i64 = 30_000;
+
+/// 当前监督线程代数（重新启动守护时递增，旧监督线程不再更新健康状态）
+static WATCHER_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// 处理线程最近一次心跳（Unix 时间戳，毫秒）
+static WATCHER_HEARTBEAT: AtomicI64 = AtomicI64::new(0);
+
+/// 配置守护健康状态
+static WATCHER_HEALTH: once_cell::sync::Lazy<Mutex<WatcherHealth>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(WatcherHealth::default()));
+
+/// 配置守护健康状态（供系统健康报告使用）
+#[derive(Debug, Clone, Default, Serialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct WatcherHealth {
+    /// 配置守护是否启用
+    pub enabled: bool,
+    /// 处理线程是否在运行
+    pub running: bool,
+    /// 心跳超时（线程存活但可能卡住）
+    pub stalled: bool,
+    /// 自动重启次数
+    pub restarts: u32,
+    /// 最近一次心跳（Unix 时间戳，毫秒）
+    pub last_heartbeat: Option<i64>,
+    /// 最近一次故障原因
+    pub last_failure: Option<String>,
+    /// 最近一次故障时间（Unix 时间戳，毫秒）
+    pub last_failure_at: Option<i64>,
+    /// 当前中断开始时间（正常运行时为 None）
+    pub down_since: Option<i64>,
+    /// 累计中断时长（毫秒）
+    pub total_downtime_ms: u64,
+}
+
+/// 配置检测中断后恢复的通知
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct WatcherRecovery {
+    /// 中断开始时间（Unix 时间戳，毫秒）
+    pub down_since: i64,
+    /// 恢复时间（Unix 时间戳，毫秒）
+    pub recovered_at: i64,
+    /// 本次中断时长（毫秒），期间发生的外部修改未被检测
+    pub downtime_ms: u64,
+    /// 中断原因
+    pub reason: String,
+    /// 累计重启次数
+    pub restarts: u32,
+}
+
+/// 检测恢复通知回调
+pub type RecoveryNotifier = Box<dyn Fn(WatcherRecovery) + Send + 'static>;
+
+/// 读取配置守护健康状态
+pub fn watcher_health() -> WatcherHealth {
+    let mut health = WATCHER_HEALTH.lock().unwrap().clone();
+    let heartbeat = WATCHER_HEARTBEAT.load(Ordering::Relaxed);
+    if heartbeat > 0 {
+        health.last_heartbeat = Some(heartbeat);
+    }
+    health.stalled =
+        health.running && chrono::Utc::now().timestamp_millis() - heartbeat > HEARTBEAT_STALE_MS;
+    health
+}
+
+/// 仅当前代数的监督线程可以更新健康状态
+fn update_health(generation: u64, update: impl FnOnce(&mut WatcherHealth)) {
+    if WATCHER_GENERATION.load(Ordering::SeqCst) == generation {
+        update(&mut WATCHER_HEALTH.lock().unwrap());
+    }
+}
+
+/// 提取线程 panic 信息
+fn panic_reason(payload: Box<dyn std::any::Any + Send>) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "未知 panic".to_string())
 }
 
 /// 标记某个工具发生内部配置写入，在短时间内跳过外部变更检测。
@@ -433,13 +693,55 @@ fn is_external_detection_suppressed(tool_id: &str) -> bool {
         .is_some_and(|expire_at| *expire_at > now)
 }
 
-/// 启动配置文件监听
-pub fn start_watcher(app_handle: AppHandle) -> Result<()> {
+/// 外部变更通知回调
+pub type ChangeNotifier = Box<dyn Fn(ExternalConfigChange) + Send + 'static>;
+
+/// 启动配置文件监听（检测到的变更通过 Tauri 事件发送到前端）
+#[cfg(feature = "gui")]
+pub fn start_watcher(app_handle: tauri::AppHandle) -> Result<()> {
+    use tauri::Emitter;
+
+    let recovery_handle = app_handle.clone();
+    start_supervised_watcher(
+        Box::new(move |change| {
+            if let Err(e) = app_handle.emit("external-config-changed", change) {
+                tracing::error!("发送配置变更事件失败: {}", e);
+            }
+        }),
+        Some(Box::new(move |recovery| {
+            if let Err(e) = recovery_handle.emit("config-watcher-recovered", recovery) {
+                tracing::error!("发送配置守护恢复事件失败: {}", e);
+            }
+        })),
+    )
+}
+
+/// 启动配置文件监听，检测到的变更交给 `notifier` 处理
+pub fn start_watcher_with(notifier: ChangeNotifier) -> Result<()> {
+    start_supervised_watcher(notifier, None)
+}
+
+/// 启动受监督的配置文件监听
+///
+/// 处理线程意外退出（panic 或通道断开）时按退避策略自动重启，
+/// 恢复后通过 `on_recovered` 通知检测中断的时长。
+pub fn start_supervised_watcher(
+    notifier: ChangeNotifier,
+    on_recovered: Option<RecoveryNotifier>,
+) -> Result<()> {
     // 读取配置判断是否启用
     let global_config = crate::utils::config::read_global_config()
         .map_err(|e| anyhow!(e))?
         .ok_or_else(|| anyhow!("全局配置文件不存在"))?;
 
+    // 停止旧的 watcher
+    stop_watcher()?;
+    let generation = WATCHER_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    *WATCHER_HEALTH.lock().unwrap() = WatcherHealth {
+        enabled: global_config.config_watch.enabled,
+        ..Default::default()
+    };
+
     if !global_config.config_watch.enabled {
         tracing::info!("配置守护已禁用，跳过启动 watcher");
         return Ok(());
@@ -448,14 +750,51 @@ pub fn start_watcher(app_handle: AppHandle) -> Result<()> {
     let scan_interval = global_config.config_watch.scan_interval;
     tracing::info!("启动配置守护，扫描间隔: {}秒", scan_interval);
 
-    // 停止旧的 watcher
-    stop_watcher()?;
+    let running = Arc::new(AtomicBool::new(true));
+    let notifier = Arc::new(Mutex::new(notifier));
+
+    // 首次启动同步进行，失败直接返回错误
+    let worker = spawn_worker(scan_interval, Arc::clone(&notifier), Arc::clone(&running))?;
+    update_health(generation, |h| h.running = true);
+
+    let supervisor_running = Arc::clone(&running);
+    let supervisor_notifier = Arc::clone(&notifier);
+    thread::spawn(move || {
+        supervise(
+            generation,
+            worker,
+            scan_interval,
+            supervisor_notifier,
+            supervisor_running,
+            on_recovered,
+        )
+    });
 
+    // 保存句柄
+    *WATCHER_HANDLE.lock().unwrap() = Some(WatcherHandle {
+        stop_signal: running,
+        notifier,
+    });
+
+    Ok(())
+}
+
+/// 监听器与处理线程
+struct Worker {
+    watcher: RecommendedWatcher,
+    thread: thread::JoinHandle<()>,
+}
+
+/// 创建 notify watcher 并启动处理线程
+fn spawn_worker(
+    scan_interval: u64,
+    notifier: Arc<Mutex<ChangeNotifier>>,
+    running: Arc<AtomicBool>,
+) -> Result<Worker> {
     let (tx, rx) = mpsc::channel();
-    let running = Arc::new(AtomicBool::new(true));
 
     // 创建 notify watcher
-    let tools = vec![Tool::claude_code(), Tool::codex(), Tool::gemini_cli()];
+    let targets = watch_targets(&configured_watched_paths());
 
     let mut watcher = RecommendedWatcher::new(
         move |res: Result<Event, notify::Error>| {
@@ -473,21 +812,32 @@ pub fn start_watcher(app_handle: AppHandle) -> Result<()> {
         notify::Config::default().with_poll_interval(Duration::from_secs(scan_interval)),
     )?;
 
-    // 监听所有工具的配置目录
-    for tool in &tools {
-        if tool.config_dir.exists() {
-            watcher.watch(&tool.config_dir, RecursiveMode::NonRecursive)?;
-            tracing::debug!("开始监听配置目录: {}", tool.config_dir.display());
+    // 监听所有工具的配置目录及额外监听路径所在目录
+    let dirs: BTreeSet<&PathBuf> = targets.iter().map(|t| &t.dir).collect();
+    for dir in dirs {
+        if dir.exists() {
+            watcher.watch(dir, RecursiveMode::NonRecursive)?;
+            tracing::debug!("开始监听配置目录: {}", dir.display());
         }
     }
 
     // 后台线程处理变更
-    let running_clone = running.clone();
-    thread::spawn(move || {
-        let mut last_check = std::collections::HashMap::new();
+    let thread = thread::Builder::new()
+        .name("config-watcher".to_string())
+        .spawn(move || {
+            let mut last_check = std::collections::HashMap::new();
+
+            while running.load(Ordering::Relaxed) {
+                WATCHER_HEARTBEAT.store(chrono::Utc::now().timestamp_millis(), Ordering::Relaxed);
+                let path = match rx.recv_timeout(Duration::from_millis(500)) {
+                    Ok(path) => path,
+                    Err(mpsc::RecvTimeoutError::Timeout) => continue,
+                    Err(mpsc::RecvTimeoutError::Disconnected) => {
+                        tracing::error!("配置监听通道已断开，处理线程退出");
+                        return;
+                    }
+                };
 
-        while running_clone.load(Ordering::Relaxed) {
-            if let Ok(path) = rx.recv_timeout(Duration::from_millis(500)) {
                 // 防抖：同一路径 500ms 内只处理一次
                 let now = std::time::Instant::now();
                 if let Some(last) = last_check.get(&path) {
@@ -497,22 +847,116 @@ pub fn start_watcher(app_handle: AppHandle) -> Result<()> {
                 }
                 last_check.insert(path.clone(), now);
 
-                // 检测变更
-                if let Err(e) = handle_file_change(&path, &app_handle) {
+                // 检测变更（处理线程 panic 时锁可能中毒，恢复后继续使用）
+                let notifier = notifier.lock().unwrap_or_else(|e| e.into_inner());
+                if let Err(e) = handle_file_change(&path, &notifier) {
                     tracing::error!("处理配置变更失败: {}", e);
                 }
             }
+        })?;
+
+    Ok(Worker { watcher, thread })
+}
+
+/// 监督处理线程：意外退出时按退避策略重启
+fn supervise(
+    generation: u64,
+    mut worker: Worker,
+    scan_interval: u64,
+    notifier: Arc<Mutex<ChangeNotifier>>,
+    running: Arc<AtomicBool>,
+    on_recovered: Option<RecoveryNotifier>,
+) {
+    let mut backoff = RESTART_BACKOFF_INITIAL;
+
+    loop {
+        let started = Instant::now();
+        let Worker { watcher, thread } = worker;
+        let result = thread.join();
+        drop(watcher);
+
+        if !running.load(Ordering::Relaxed) {
+            break;
         }
-    });
 
-    // 保存句柄
-    let handle = WatcherHandle {
-        _watcher: watcher,
-        stop_signal: running,
-    };
-    *WATCHER_HANDLE.lock().unwrap() = Some(handle);
+        // 处理线程意外退出：记录中断并准备重启
+        let reason = match result {
+            Ok(()) => "处理线程意外退出".to_string(),
+            Err(payload) => format!("处理线程 panic: {}", panic_reason(payload)),
+        };
+        let down_since = chrono::Utc::now().timestamp_millis();
+        if started.elapsed() > RESTART_BACKOFF_RESET {
+            backoff = RESTART_BACKOFF_INITIAL;
+        }
+        tracing::error!(
+            reason = %reason,
+            retry_in_secs = backoff.as_secs(),
+            "配置守护中断，外部变更检测已停止，将自动重启"
+        );
+        update_health(generation, |h| {
+            h.running = false;
+            h.last_failure = Some(reason.clone());
+            h.last_failure_at = Some(down_since);
+            h.down_since = Some(down_since);
+        });
 
-    Ok(())
+        worker = loop {
+            if !sleep_while_running(backoff, &running) {
+                update_health(generation, |h| h.running = false);
+                return;
+            }
+            backoff = (backoff * 2).min(RESTART_BACKOFF_MAX);
+
+            match spawn_worker(scan_interval, Arc::clone(&notifier), Arc::clone(&running)) {
+                Ok(worker) => break worker,
+                Err(e) => {
+                    tracing::warn!(error = %e, "重启配置守护失败");
+                    update_health(generation, |h| {
+                        h.last_failure = Some(format!("重启失败: {}", e));
+                        h.last_failure_at = Some(chrono::Utc::now().timestamp_millis());
+                    });
+                }
+            }
+        };
+
+        // 中断期间的外部修改要等文件再次变更时才会被检测，通知用户自行确认
+        let recovered_at = chrono::Utc::now().timestamp_millis();
+        let downtime_ms = (recovered_at - down_since).max(0) as u64;
+        let mut restarts = 0;
+        update_health(generation, |h| {
+            h.running = true;
+            h.restarts += 1;
+            h.down_since = None;
+            h.total_downtime_ms += downtime_ms;
+            restarts = h.restarts;
+        });
+        tracing::info!(downtime_ms, restarts, "配置守护已自动恢复");
+        if let Some(notify) = &on_recovered {
+            notify(WatcherRecovery {
+                down_since,
+                recovered_at,
+                downtime_ms,
+                reason,
+                restarts,
+            });
+        }
+    }
+
+    update_health(generation, |h| h.running = false);
+}
+
+/// 分段睡眠，期间守护被停止时返回 false
+fn sleep_while_running(duration: Duration, running: &AtomicBool) -> bool {
+    let deadline = Instant::now() + duration;
+    while Instant::now() < deadline {
+        if !running.load(Ordering::Relaxed) {
+            return false;
+        }
+        thread::sleep(
+            Duration::from_millis(100).min(deadline.saturating_duration_since(Instant::now())),
+        );
+    }
+    running.load(Ordering::Relaxed)
 }
 
 /// 停止配置文件监听
@@ -525,8 +969,19 @@ pub fn stop_watcher() -> Result<()> {
     Ok(())
 }
 
+/// 使用运行中 watcher 的通知器发布变更（watcher 未运行时返回 false）
+pub(super) fn publish_to_active_watcher(change: ExternalConfigChange) -> bool {
+    let notifier = match WATCHER_HANDLE.lock().unwrap().as_ref() {
+        Some(h) => Arc::clone(&h.notifier),
+        None => return false,
+    };
+    let notifier = notifier.lock().unwrap_or_else(|e| e.into_inner());
+    publish_change(change, &notifier);
+    true
+}
+
 /// 处理单个文件变更
-fn handle_file_change(path: &Path, app_handle: &AppHandle) -> Result<()> {
+fn handle_file_change(path: &Path, notifier: &ChangeNotifier) -> Result<()> {
     // 读取全局配置
     let global_config = crate::utils::config::read_global_config()
         .map_err(|e| anyhow!(e))?
@@ -534,79 +989,78 @@ fn handle_file_change(path: &Path, app_handle: &AppHandle) -> Result<()> {
 
     let watch_config = &global_config.config_watch;
 
-    // 找到对应的工具
-    let tools = vec![Tool::claude_code(), Tool::codex(), Tool::gemini_cli()];
+    // 找到对应的监听目标（工具或额外监听路径）
+    let Some(target) = watch_targets(&watch_config.watched_paths)
+        .into_iter()
+        .find(|target| target.contains(path))
+    else {
+        return Ok(());
+    };
 
-    for tool in tools {
-        // 检查是否是该工具的任一配置文件
-        let is_tool_config = tool.config_files().iter().any(|filename| {
-            let config_path = tool.config_dir.join(filename);
-            config_path == path
-        });
+    if is_external_detection_suppressed(&target.id) {
+        tracing::debug!(tool_id = %target.id, "检测到内部写入，跳过外部变更通知");
+        if let Err(error) = save_snapshot_for_target(&target) {
+            tracing::warn!(
+                error = ?error,
+                tool_id = %target.id,
+                "内部写入后刷新配置快照失败"
+            );
+        }
+        return Ok(());
+    }
 
-        if is_tool_config {
-            if is_external_detection_suppressed(&tool.id) {
-                tracing::debug!(tool_id = %tool.id, "检测到内部写入，跳过外部变更通知");
-                if let Err(error) = save_snapshot_for_tool(&tool) {
-                    tracing::warn!(
-                        error = ?error,
-                        tool_id = %tool.id,
-                        "内部写入后刷新配置快照失败"
-                    );
-                }
-                break;
-            }
+    // 检测变更
+    if let Some(change) = detect_change(&target, watch_config)? {
+        publish_change(change, notifier);
+    }
 
-            // 检测变更
-            if let Some(change) = detect_tool_change(&tool, watch_config)? {
-                tracing::info!(
-                    "检测到配置变更: {} ({} 个字段)",
-                    change.tool_id,
-                    change.changed_fields.len()
-                );
-
-                // 记录到变更日志
-                use crate::data::changelogs::ConfigChangeRecord;
-                use std::collections::HashMap;
-
-                let mut before_values = HashMap::new();
-                let mut after_values = HashMap::new();
-                let changed_field_paths: Vec<String> = change
-                    .changed_fields
-                    .iter()
-                    .map(|f| {
-                        if let Some(old) = &f.old_value {
-                            before_values.insert(f.path.clone(), old.clone());
-                        }
-                        if let Some(new) = &f.new_value {
-                            after_values.insert(f.path.clone(), new.clone());
-                        }
-                        f.path.clone()
-                    })
-                    .collect();
-
-                let record = ConfigChangeRecord {
-                    tool_id: change.tool_id.clone(),
-                    timestamp: chrono::Utc::now(),
-                    changed_fields: changed_field_paths,
-                    is_sensitive: change.is_sensitive,
-                    before_values,
-                    after_values,
-                    action: None, // 用户尚未操作
-                };
+    Ok(())
+}
 
-                if let Err(e) = save_change_record(record) {
-                    tracing::error!("保存变更日志失败: {}", e);
-                }
+/// 发布外部变更：写入变更日志并通知订阅方
+pub(super) fn publish_change(change: ExternalConfigChange, notifier: &ChangeNotifier) {
+    tracing::info!(
+        "检测到配置变更: {} ({} 个字段)",
+        change.tool_id,
+        change.changed_fields.len()
+    );
 
-                // 发送事件到前端
-                app_handle.emit("external-config-changed", change)?;
+    // 记录到变更日志
+    use crate::data::changelogs::ConfigChangeRecord;
+    use std::collections::HashMap;
+
+    let mut before_values = HashMap::new();
+    let mut after_values = HashMap::new();
+    let changed_field_paths: Vec<String> = change
+        .changed_fields
+        .iter()
+        .map(|f| {
+            if let Some(old) = &f.old_value {
+                before_values.insert(f.path.clone(), old.clone());
             }
-            break;
-        }
+            if let Some(new) = &f.new_value {
+                after_values.insert(f.path.clone(), new.clone());
+            }
+            f.path.clone()
+        })
+        .collect();
+
+    let record = ConfigChangeRecord {
+        tool_id: change.tool_id.clone(),
+        timestamp: chrono::Utc::now(),
+        changed_fields: changed_field_paths,
+        is_sensitive: change.is_sensitive,
+        before_values,
+        after_values,
+        action: None, // 用户尚未操作
+    };
+
+    if let Err(e) = save_change_record(record) {
+        tracing::error!("保存变更日志失败: {}", e);
     }
 
-    Ok(())
+    // 通知订阅方（GUI 下发送事件到前端）
+    notifier(change);
 }
 
 /// 保存变更记录到日志
@@ -618,3 +1072,77 @@ fn save_change_record(record: ConfigChangeRecord) -> Result<()> {
     store.save()?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_panic_reason_and_stoppable_sleep() {
+        let payload = std::panic::catch_unwind(|| panic!("boom {}", 1)).unwrap_err();
+        assert_eq!(panic_reason(payload), "boom 1");
+        let payload = std::panic::catch_unwind(|| panic!("static")).unwrap_err();
+        assert_eq!(panic_reason(payload), "static");
+
+        let running = AtomicBool::new(false);
+        let started = Instant::now();
+        assert!(!sleep_while_running(Duration::from_secs(5), &running));
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_watched_path_targets_and_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join(".gemini");
+        std::fs::create_dir_all(&project).unwrap();
+        std::fs::write(project.join("settings.json"), r#"{"model":"a"}"#).unwrap();
+        std::fs::write(project.join(".env"), "GEMINI_API_KEY=old\n").unwrap();
+        std::fs::write(project.join("notes.md"), "ignored").unwrap();
+
+        // 目录：列出支持的配置文件，字段路径带文件前缀
+        let dir_path = project.to_string_lossy().to_string();
+        let target = WatchTarget::from_watched_path(&dir_path);
+        assert_eq!(target.id, format!("path:{}", dir_path));
+        assert_eq!(target.files, vec![".env", "settings.json"]);
+        assert!(target.contains(&project.join("settings.json")));
+        assert!(!target.contains(&project.join("notes.md")));
+        assert_eq!(
+            resolve_target(&target.id).unwrap().dir,
+            project.to_path_buf()
+        );
+
+        let snapshot = read_target_files(&target).unwrap();
+        std::fs::write(project.join(".env"), "GEMINI_API_KEY=new\n").unwrap();
+        std::fs::write(project.join("settings.json"), r#"{"model":"b"}"#).unwrap();
+        let changes = diff_files(&target, &snapshot, &read_target_files(&target).unwrap());
+        assert_eq!(changes.len(), 2);
+
+        let mut config = ConfigWatchConfig {
+            watched_paths: vec![WatchedPath {
+                path: dir_path.clone(),
+                blacklist: vec![],
+                sensitive_fields: vec![".env:GEMINI_API_KEY".to_string()],
+            }],
+            ..Default::default()
+        };
+        // 默认模式仅保留该路径自己的敏感字段
+        let change = filter_target_changes(&target, changes.clone(), &config).unwrap();
+        assert_eq!(change.tool_id, target.id);
+        assert_eq!(change.changed_fields.len(), 1);
+        assert_eq!(change.changed_fields[0].path, ".env:GEMINI_API_KEY");
+        assert!(change.is_sensitive);
+
+        config.mode = WatchMode::Full;
+        config.watched_paths[0].blacklist = vec!["settings.json:model".to_string()];
+        let change = filter_target_changes(&target, changes, &config).unwrap();
+        assert_eq!(change.changed_fields.len(), 1);
+
+        // 单个文件：字段路径不带前缀
+        let file_path = project.join("settings.json").to_string_lossy().to_string();
+        let target = WatchTarget::from_watched_path(&file_path);
+        assert_eq!(target.dir, project);
+        assert_eq!(target.files, vec!["settings.json"]);
+        assert!(!target.contains(&project.join(".env")));
+        assert_eq!(target.display_path(), project.join("settings.json"));
+    }
+}
@@ -0,0 +1,344 @@
+//! 仪表板汇总
+//!
+//! 仪表板首屏需要的数据分散在多个模块中，这里在后端并发收集后一次返回：
+//! - 今日用量、运行中的代理、激活的 Profile、供应商健康、最近的外部配置变更、可用更新
+//! - 各部分互不影响，单个部分失败时记入 `errors`，其余部分照常返回
+//! - 汇总结果缓存 [`SUMMARY_TTL`]；应用更新检查需要访问网络，结果单独缓存 [`APP_UPDATE_TTL`]，
+//!   工具版本检查复用 `ToolStatusCache`
+
+use crate::data::changelogs::{ChangeLogStore, ConfigChangeRecord};
+use crate::models::dashboard::{
+    AvailableUpdate, DashboardActiveProfile, DashboardConfigEvent, DashboardProviderHealth,
+    DashboardProxy, DashboardSectionError, DashboardSummary, DashboardUpdates,
+};
+use crate::models::update::UpdateStatus;
+use crate::models::Tool;
+use crate::services::profile_manager::ProfileManager;
+use crate::services::provider::health::{ProviderHealth, ProviderHealthMonitor};
+use crate::services::proxy::ProxyManager;
+use crate::services::proxy_config_manager::ProxyConfigManager;
+use crate::services::token_stats::budget::{period_start, BudgetPeriod};
+use crate::services::token_stats::TokenStatsManager;
+use crate::services::update::UpdateService;
+use crate::services::VersionService;
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+/// 汇总结果的缓存时长
+pub const SUMMARY_TTL: Duration = Duration::from_secs(10);
+
+/// 应用更新检查结果的缓存时长
+pub const APP_UPDATE_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// 汇总 Profile 的工具
+const SUMMARY_TOOLS: [&str; 4] = ["claude-code", "codex", "gemini-cli", "amp-code"];
+
+/// 汇总供应商健康的工具（与健康探测一致）
+const HEALTH_TOOLS: [&str; 3] = ["claude-code", "codex", "gemini-cli"];
+
+/// 应用更新的 ID
+const APP_UPDATE_ID: &str = "duckcoding";
+
+static SUMMARY_CACHE: Lazy<TtlCache<DashboardSummary>> = Lazy::new(|| TtlCache::new(SUMMARY_TTL));
+
+static APP_UPDATE_CACHE: Lazy<TtlCache<Option<AvailableUpdate>>> =
+    Lazy::new(|| TtlCache::new(APP_UPDATE_TTL));
+
+/// 单值缓存（过期后需重新计算）
+struct TtlCache<T> {
+    ttl: Duration,
+    value: Mutex<Option<(Instant, T)>>,
+}
+
+impl<T: Clone> TtlCache<T> {
+    fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            value: Mutex::new(None),
+        }
+    }
+
+    /// 未过期的缓存值
+    fn get(&self) -> Option<T> {
+        self.value
+            .lock()
+            .unwrap()
+            .as_ref()
+            .filter(|(stored_at, _)| stored_at.elapsed() < self.ttl)
+            .map(|(_, value)| value.clone())
+    }
+
+    /// 最近一次缓存值（忽略是否过期）
+    fn last(&self) -> Option<T> {
+        self.value
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|(_, value)| value.clone())
+    }
+
+    fn store(&self, value: T) {
+        *self.value.lock().unwrap() = Some((Instant::now(), value));
+    }
+}
+
+/// 汇总所需的应用级服务（由命令层从 Tauri State 传入）
+pub struct DashboardSources<'a> {
+    pub proxy_manager: &'a ProxyManager,
+    pub profile_manager: &'a RwLock<ProfileManager>,
+    pub update_service: &'a UpdateService,
+}
+
+/// 获取仪表板汇总（`force` 时忽略汇总与应用更新缓存）
+pub async fn dashboard_summary(sources: DashboardSources<'_>, force: bool) -> DashboardSummary {
+    if !force {
+        if let Some(summary) = SUMMARY_CACHE.get() {
+            return summary;
+        }
+    }
+
+    let (usage, proxies, profiles, health, config_event, app_update, tool_updates) = tokio::join!(
+        blocking(today_usage),
+        active_proxies(sources.proxy_manager),
+        active_profiles(sources.profile_manager),
+        blocking(provider_health),
+        blocking(last_config_event),
+        app_update(sources.update_service, force),
+        tool_updates(),
+    );
+
+    let mut errors = Vec::new();
+    let (today_requests, today_cost) =
+        section(&mut errors, "today_cost", usage).unwrap_or_default();
+    let summary = DashboardSummary {
+        generated_at: chrono::Utc::now().timestamp_millis(),
+        today_requests,
+        today_cost,
+        active_proxies: section(&mut errors, "active_proxies", proxies).unwrap_or_default(),
+        active_profiles: section(&mut errors, "active_profiles", profiles).unwrap_or_default(),
+        provider_health: section(&mut errors, "provider_health", health).unwrap_or_default(),
+        last_config_event: section(&mut errors, "last_config_event", config_event).flatten(),
+        updates: DashboardUpdates {
+            app: section(&mut errors, "updates", app_update).flatten(),
+            tools: tool_updates,
+        },
+        errors,
+    };
+
+    SUMMARY_CACHE.store(summary.clone());
+    summary
+}
+
+/// 记录失败的部分，返回成功的结果
+fn section<T>(errors: &mut Vec<DashboardSectionError>, name: &str, result: Result<T>) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(e) => {
+            tracing::warn!(section = name, error = ?e, "仪表板汇总部分获取失败");
+            errors.push(DashboardSectionError {
+                section: name.to_string(),
+                message: format!("{e:#}"),
+            });
+            None
+        }
+    }
+}
+
+/// 在阻塞线程池中执行（数据库与文件读取）
+async fn blocking<T: Send + 'static>(f: fn() -> Result<T>) -> Result<T> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| anyhow!(e))?
+}
+
+/// 今日（本地时间 0 点起）的请求数与成本
+fn today_usage() -> Result<(i64, f64)> {
+    let since = period_start(BudgetPeriod::Daily, chrono::Local::now());
+    TokenStatsManager::get().usage_since(since)
+}
+
+async fn active_proxies(proxy_manager: &ProxyManager) -> Result<Vec<DashboardProxy>> {
+    let store = ProxyConfigManager::new()?.load_proxy_store()?;
+    let mut proxies = Vec::new();
+    for tool_id in store.tool_ids() {
+        if !proxy_manager.is_running(&tool_id).await {
+            continue;
+        }
+        let Some(config) = store.get_config(&tool_id) else {
+            continue;
+        };
+        proxies.push(DashboardProxy {
+            port: config.port,
+            in_flight: proxy_manager
+                .concurrency_status(&tool_id)
+                .await
+                .map(|status| status.in_flight),
+            tool_id,
+        });
+    }
+    Ok(proxies)
+}
+
+async fn active_profiles(
+    profile_manager: &RwLock<ProfileManager>,
+) -> Result<Vec<DashboardActiveProfile>> {
+    let manager = profile_manager.read().await;
+    SUMMARY_TOOLS
+        .iter()
+        .map(|tool_id| {
+            let active = manager.get_active_state(tool_id)?;
+            Ok(DashboardActiveProfile {
+                tool_id: tool_id.to_string(),
+                switched_at: active.as_ref().map(|a| a.switched_at.timestamp_millis()),
+                profile: active.map(|a| a.profile),
+            })
+        })
+        .collect()
+}
+
+fn provider_health() -> Result<Vec<DashboardProviderHealth>> {
+    let monitor = ProviderHealthMonitor::global();
+    let mut entries = Vec::new();
+    for tool_id in HEALTH_TOOLS {
+        entries.extend(
+            monitor
+                .health_report(tool_id)?
+                .into_iter()
+                .map(health_entry),
+        );
+    }
+    Ok(entries)
+}
+
+fn health_entry(health: ProviderHealth) -> DashboardProviderHealth {
+    DashboardProviderHealth {
+        tool_id: health.tool_id,
+        base_url: health.base_url,
+        profile_names: health.profile_names,
+        reachable: health.latest.as_ref().map(|s| s.reachable),
+        latency_ms: health.latest.as_ref().and_then(|s| s.latency_ms),
+        uptime_24h: health.uptime_24h,
+        checked_at: health.latest.as_ref().map(|s| s.timestamp),
+    }
+}
+
+fn last_config_event() -> Result<Option<DashboardConfigEvent>> {
+    Ok(ChangeLogStore::load()?.records.first().map(config_event))
+}
+
+fn config_event(record: &ConfigChangeRecord) -> DashboardConfigEvent {
+    DashboardConfigEvent {
+        tool_id: record.tool_id.clone(),
+        timestamp: record.timestamp.timestamp_millis(),
+        changed_fields: record.changed_fields.clone(),
+        is_sensitive: record.is_sensitive,
+        action: record.action.clone(),
+    }
+}
+
+/// 应用更新（下载 / 安装过程中不重新检查，沿用最近一次结果）
+async fn app_update(service: &UpdateService, force: bool) -> Result<Option<AvailableUpdate>> {
+    if !force {
+        if let Some(update) = APP_UPDATE_CACHE.get() {
+            return Ok(update);
+        }
+    }
+    if !matches!(
+        service.get_status().await,
+        UpdateStatus::Idle | UpdateStatus::Available | UpdateStatus::Failed(_)
+    ) {
+        return Ok(APP_UPDATE_CACHE.last().flatten());
+    }
+
+    let info = service.check_for_updates().await?;
+    let update = info.has_update.then(|| AvailableUpdate {
+        id: APP_UPDATE_ID.to_string(),
+        current_version: Some(info.current_version),
+        latest_version: Some(info.latest_version),
+        required: info.required,
+    });
+    APP_UPDATE_CACHE.store(update.clone());
+    Ok(update)
+}
+
+/// 有新版本的工具（版本检查结果由 `ToolStatusCache` 缓存）
+async fn tool_updates() -> Vec<AvailableUpdate> {
+    VersionService::new()
+        .check_all(&Tool::all())
+        .await
+        .into_iter()
+        .filter(|info| info.has_update)
+        .map(|info| AvailableUpdate {
+            id: info.tool_id,
+            current_version: info.installed_version,
+            latest_version: info.latest_version,
+            required: false,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::provider::health::ProviderHealthSample;
+
+    #[test]
+    fn test_ttl_cache_expires_but_keeps_last_value() {
+        let cache = TtlCache::new(Duration::from_millis(20));
+        assert_eq!(cache.get(), None::<u32>);
+        cache.store(1);
+        assert_eq!(cache.get(), Some(1));
+        std::thread::sleep(Duration::from_millis(30));
+        assert_eq!(cache.get(), None);
+        assert_eq!(cache.last(), Some(1));
+    }
+
+    #[test]
+    fn test_section_collects_errors() {
+        let mut errors = Vec::new();
+        assert_eq!(section(&mut errors, "today_cost", Ok(3)), Some(3));
+        assert_eq!(
+            section::<u32>(&mut errors, "updates", Err(anyhow!("offline"))),
+            None
+        );
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].section, "updates");
+        assert_eq!(errors[0].message, "offline");
+    }
+
+    #[test]
+    fn test_health_entry_uses_latest_sample() {
+        let entry = health_entry(ProviderHealth {
+            tool_id: "codex".to_string(),
+            base_url: "https://api.example.com".to_string(),
+            profile_names: vec!["work".to_string()],
+            latest: Some(ProviderHealthSample {
+                timestamp: 1_000,
+                reachable: false,
+                latency_ms: None,
+                status_code: None,
+                error: Some("connection refused".to_string()),
+            }),
+            uptime_24h: Some(50.0),
+            avg_latency_ms_24h: None,
+            recent: Vec::new(),
+        });
+        assert_eq!(entry.reachable, Some(false));
+        assert_eq!(entry.checked_at, Some(1_000));
+        assert_eq!(entry.uptime_24h, Some(50.0));
+
+        let unprobed = health_entry(ProviderHealth {
+            tool_id: "codex".to_string(),
+            base_url: "https://other.example.com".to_string(),
+            profile_names: Vec::new(),
+            latest: None,
+            uptime_24h: None,
+            avg_latency_ms_24h: None,
+            recent: Vec::new(),
+        });
+        assert_eq!(unprobed.reachable, None);
+    }
+}
@@ -0,0 +1,606 @@
+//! 自诊断
+//!
+//! 「诊断问题」按钮执行的全面健康检查。与引导式故障排查不同，所有检查互不依赖、全部执行：
+//! - 配置文件：配置目录下的 JSON 能否解析，核心配置能否按结构读取
+//! - 端口：已启用代理的端口空闲或由本进程的代理占用
+//! - 数据库：`PRAGMA integrity_check`
+//! - 磁盘空间：配置目录所在磁盘的剩余空间
+//! - 上游可达性与时钟偏差（以上游响应的 `Date` 头为参照）
+//! - 工具命令是否可用
+
+use crate::data::DataManager;
+use crate::models::tool::DUCKCODING_BASE_URL;
+use crate::models::Tool;
+use crate::services::profile_manager::ProfileManager;
+use crate::services::provider::health::probe_providers_once;
+use crate::services::proxy::ProxyManager;
+use crate::services::proxy_config_manager::ProxyConfigManager;
+use crate::utils::config::{config_dir, read_global_config};
+use crate::utils::version::parse_version;
+use crate::utils::CommandExecutor;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// 参与完整性检查的数据库
+const DATABASES: [&str; 3] = ["token_stats.db", "sessions.db", "install_history.db"];
+
+/// 剩余空间低于该值时警告 / 报错（字节）
+const DISK_WARNING_BYTES: u64 = 1024 * 1024 * 1024;
+const DISK_ERROR_BYTES: u64 = 200 * 1024 * 1024;
+
+/// 时钟偏差超过该值时警告 / 报错（秒）
+const CLOCK_SKEW_WARNING_SECS: i64 = 60;
+const CLOCK_SKEW_ERROR_SECS: i64 = 300;
+
+/// 获取参照时间的请求超时
+const CLOCK_PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 检查级别（按严重程度递增）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticSeverity {
+    Ok,
+    /// 提示信息（如工具未安装），不算问题
+    Info,
+    Warning,
+    Error,
+}
+
+/// 检查类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticCategory {
+    Config,
+    Port,
+    Database,
+    Disk,
+    Provider,
+    Clock,
+    Tool,
+}
+
+/// 单项检查结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagnosticCheck {
+    /// 检查 ID（如 `db:token_stats.db`）
+    pub id: String,
+    pub category: DiagnosticCategory,
+    pub title: String,
+    pub severity: DiagnosticSeverity,
+    pub detail: String,
+    pub fix: Option<String>,
+}
+
+impl DiagnosticCheck {
+    fn new(
+        category: DiagnosticCategory,
+        id: impl Into<String>,
+        title: impl Into<String>,
+        severity: DiagnosticSeverity,
+        detail: impl Into<String>,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            category,
+            title: title.into(),
+            severity,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn with_fix(mut self, fix: impl Into<String>) -> Self {
+        self.fix = Some(fix.into());
+        self
+    }
+}
+
+/// 诊断报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagnosticsReport {
+    pub checks: Vec<DiagnosticCheck>,
+    /// 最严重的检查级别
+    pub overall: DiagnosticSeverity,
+    pub error_count: usize,
+    pub warning_count: usize,
+    pub duration_ms: u64,
+    /// 完成时间（Unix 时间戳，毫秒）
+    pub generated_at: i64,
+}
+
+impl DiagnosticsReport {
+    fn from_checks(checks: Vec<DiagnosticCheck>, started: Instant) -> Self {
+        let count = |severity| checks.iter().filter(|c| c.severity == severity).count();
+        Self {
+            overall: checks
+                .iter()
+                .map(|c| c.severity)
+                .max()
+                .unwrap_or(DiagnosticSeverity::Ok),
+            error_count: count(DiagnosticSeverity::Error),
+            warning_count: count(DiagnosticSeverity::Warning),
+            duration_ms: started.elapsed().as_millis() as u64,
+            generated_at: chrono::Utc::now().timestamp_millis(),
+            checks,
+        }
+    }
+}
+
+/// 执行全部检查
+pub async fn run_diagnostics(proxy_manager: &ProxyManager) -> DiagnosticsReport {
+    let started = Instant::now();
+
+    // 文件与数据库检查是阻塞 IO，放到阻塞线程中与网络检查并行执行
+    let local = tokio::task::spawn_blocking(|| {
+        let mut checks = Vec::new();
+        match config_dir() {
+            Ok(dir) => {
+                checks.extend(check_config_files(&dir));
+                checks.extend(
+                    DATABASES
+                        .iter()
+                        .filter(|name| dir.join(name).exists())
+                        .map(|name| check_database(&dir, name)),
+                );
+                checks.push(check_disk_space(&dir));
+            }
+            Err(e) => checks.push(
+                DiagnosticCheck::new(
+                    DiagnosticCategory::Config,
+                    "config:dir",
+                    "配置目录",
+                    DiagnosticSeverity::Error,
+                    format!("无法访问配置目录: {e}"),
+                )
+                .with_fix("检查用户主目录权限，或通过 DUCKCODING_CONFIG_DIR 指定可写目录"),
+            ),
+        }
+        checks
+    });
+    let (local, ports, network, tools) = tokio::join!(
+        local,
+        check_ports(proxy_manager),
+        check_providers_and_clock(),
+        check_tools()
+    );
+
+    let mut checks = local.unwrap_or_else(|e| {
+        vec![DiagnosticCheck::new(
+            DiagnosticCategory::Config,
+            "config:task",
+            "本地检查",
+            DiagnosticSeverity::Error,
+            format!("本地检查任务异常退出: {e}"),
+        )]
+    });
+    checks.extend(ports);
+    checks.extend(network);
+    checks.extend(tools);
+
+    let report = DiagnosticsReport::from_checks(checks, started);
+    tracing::info!(
+        overall = ?report.overall,
+        errors = report.error_count,
+        warnings = report.warning_count,
+        duration_ms = report.duration_ms,
+        "自诊断完成"
+    );
+    report
+}
+
+// ==================== 配置文件 ====================
+
+/// 检查配置目录下的 JSON 文件；核心配置额外按结构读取
+fn check_config_files(dir: &Path) -> Vec<DiagnosticCheck> {
+    let mut names: Vec<String> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|e| e.path().is_file())
+                .filter_map(|e| e.file_name().to_str().map(str::to_string))
+                .filter(|name| name.ends_with(".json"))
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+
+    names
+        .into_iter()
+        .map(|name| {
+            let outcome = check_json_syntax(&dir.join(&name)).and_then(|()| match name.as_str() {
+                "config.json" => read_global_config().map(|_| ()),
+                "proxy.json" => ProxyConfigManager::new()
+                    .and_then(|m| m.load_proxy_store())
+                    .map(|_| ())
+                    .map_err(|e| e.to_string()),
+                "profiles.json" => ProfileManager::new()
+                    .and_then(|m| m.load_profiles_store())
+                    .map(|_| ())
+                    .map_err(|e| e.to_string()),
+                _ => Ok(()),
+            });
+            let check = |severity, detail: String| {
+                DiagnosticCheck::new(
+                    DiagnosticCategory::Config,
+                    format!("config:{name}"),
+                    name.clone(),
+                    severity,
+                    detail,
+                )
+            };
+            match outcome {
+                Ok(()) => check(DiagnosticSeverity::Ok, "可以正常解析".to_string()),
+                Err(e) => check(DiagnosticSeverity::Error, e).with_fix(format!(
+                    "修复或从备份恢复 ~/.duckcoding/{name}；无法修复时可删除后在应用中重新配置"
+                )),
+            }
+        })
+        .collect()
+}
+
+fn check_json_syntax(path: &Path) -> Result<(), String> {
+    let content = std::fs::read(path).map_err(|e| format!("读取失败: {e}"))?;
+    serde_json::from_slice::<serde_json::Value>(&content)
+        .map(|_| ())
+        .map_err(|e| format!("JSON 格式错误: {e}"))
+}
+
+// ==================== 端口 ====================
+
+/// 已启用代理的端口：由本进程的代理占用，或空闲可用
+async fn check_ports(proxy_manager: &ProxyManager) -> Vec<DiagnosticCheck> {
+    let store = match ProxyConfigManager::new().and_then(|m| m.load_proxy_store()) {
+        Ok(store) => store,
+        // 读取失败已在配置文件检查中报告
+        Err(_) => return Vec::new(),
+    };
+
+    let mut checks = Vec::new();
+    for (tool_id, config) in [
+        ("claude-code", &store.claude_code),
+        ("codex", &store.codex),
+        ("gemini-cli", &store.gemini_cli),
+        ("amp-code", &store.amp_code),
+    ] {
+        // 监听本地套接字 / 命名管道时不占用 TCP 端口
+        if !config.enabled || config.listen_socket.is_some() {
+            continue;
+        }
+        let check = |severity, detail: String| {
+            DiagnosticCheck::new(
+                DiagnosticCategory::Port,
+                format!("port:{tool_id}"),
+                format!("{tool_id} 代理端口 {}", config.port),
+                severity,
+                detail,
+            )
+        };
+        checks.push(if proxy_manager.is_running(tool_id).await {
+            check(
+                DiagnosticSeverity::Ok,
+                "由 DuckCoding 透明代理占用".to_string(),
+            )
+        } else if port_available(config.port, config.allow_public) {
+            check(DiagnosticSeverity::Ok, "端口空闲".to_string())
+        } else {
+            check(
+                DiagnosticSeverity::Error,
+                format!("端口 {} 被其他进程占用，代理无法启动", config.port),
+            )
+            .with_fix("关闭占用该端口的程序（包括命令行启动的代理），或在透明代理页面更换端口")
+        });
+    }
+    checks
+}
+
+fn port_available(port: u16, allow_public: bool) -> bool {
+    let host = if allow_public { "0.0.0.0" } else { "127.0.0.1" };
+    std::net::TcpListener::bind((host, port)).is_ok()
+}
+
+// ==================== 数据库与磁盘 ====================
+
+fn check_database(dir: &Path, name: &str) -> DiagnosticCheck {
+    let result = DataManager::global()
+        .sqlite(&dir.join(name))
+        .and_then(|db| {
+            db.transaction(|tx| {
+                let mut stmt = tx.prepare("PRAGMA integrity_check")?;
+                let rows = stmt
+                    .query_map([], |row| row.get::<_, String>(0))?
+                    .collect::<std::result::Result<Vec<_>, _>>()
+                    .map_err(crate::data::DataError::Database)?;
+                Ok(rows)
+            })
+        });
+
+    let check = |severity, detail: String| {
+        DiagnosticCheck::new(
+            DiagnosticCategory::Database,
+            format!("db:{name}"),
+            name,
+            severity,
+            detail,
+        )
+    };
+    match result {
+        Ok(rows) if rows.len() == 1 && rows[0] == "ok" => {
+            check(DiagnosticSeverity::Ok, "完整性检查通过".to_string())
+        }
+        Ok(rows) => check(
+            DiagnosticSeverity::Error,
+            format!(
+                "完整性检查发现 {} 个问题：{}",
+                rows.len(),
+                rows.iter().take(3).cloned().collect::<Vec<_>>().join("；")
+            ),
+        )
+        .with_fix("退出应用后备份并删除该数据库文件，重启后会重新创建（历史数据将丢失）"),
+        Err(e) => check(DiagnosticSeverity::Error, format!("无法打开数据库: {e}"))
+            .with_fix("确认没有其他程序锁定该文件，必要时重启应用"),
+    }
+}
+
+fn check_disk_space(dir: &Path) -> DiagnosticCheck {
+    let check = |severity, detail: String| {
+        DiagnosticCheck::new(
+            DiagnosticCategory::Disk,
+            "disk:config_dir",
+            "磁盘剩余空间",
+            severity,
+            detail,
+        )
+    };
+    match fs2::available_space(dir) {
+        Ok(bytes) => {
+            let detail = format!("配置目录所在磁盘剩余 {}", format_bytes(bytes));
+            match disk_severity(bytes) {
+                DiagnosticSeverity::Ok => check(DiagnosticSeverity::Ok, detail),
+                severity => check(severity, detail)
+                    .with_fix("清理磁盘空间，或在设置中清理历史日志与统计数据"),
+            }
+        }
+        Err(e) => check(
+            DiagnosticSeverity::Warning,
+            format!("无法获取磁盘空间: {e}"),
+        ),
+    }
+}
+
+fn disk_severity(available: u64) -> DiagnosticSeverity {
+    if available < DISK_ERROR_BYTES {
+        DiagnosticSeverity::Error
+    } else if available < DISK_WARNING_BYTES {
+        DiagnosticSeverity::Warning
+    } else {
+        DiagnosticSeverity::Ok
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const GB: f64 = 1024.0 * 1024.0 * 1024.0;
+    const MB: f64 = 1024.0 * 1024.0;
+    if bytes as f64 >= GB {
+        format!("{:.1} GB", bytes as f64 / GB)
+    } else {
+        format!("{:.0} MB", bytes as f64 / MB)
+    }
+}
+
+// ==================== 上游与时钟 ====================
+
+async fn check_providers_and_clock() -> Vec<DiagnosticCheck> {
+    let mut checks = Vec::new();
+    let probes = match probe_providers_once().await {
+        Ok(probes) => probes,
+        Err(e) => {
+            checks.push(DiagnosticCheck::new(
+                DiagnosticCategory::Provider,
+                "provider:probe",
+                "上游可达性",
+                DiagnosticSeverity::Warning,
+                format!("无法执行上游探测: {e}"),
+            ));
+            Vec::new()
+        }
+    };
+
+    for probe in &probes {
+        let title = format!("{} · {}", probe.tool_id, probe.base_url);
+        let id = format!("provider:{}:{}", probe.tool_id, probe.base_url);
+        let sample = &probe.sample;
+        checks.push(if sample.reachable {
+            DiagnosticCheck::new(
+                DiagnosticCategory::Provider,
+                id,
+                title,
+                DiagnosticSeverity::Ok,
+                format!(
+                    "可达（HTTP {}，{} ms）",
+                    sample.status_code.unwrap_or_default(),
+                    sample.latency_ms.unwrap_or_default()
+                ),
+            )
+        } else {
+            DiagnosticCheck::new(
+                DiagnosticCategory::Provider,
+                id,
+                title,
+                DiagnosticSeverity::Warning,
+                format!(
+                    "不可达：{}（Profile：{}）",
+                    sample.error.as_deref().unwrap_or("未知错误"),
+                    probe.profile_names.join("、")
+                ),
+            )
+            .with_fix("检查网络与代理设置，或确认该 Base URL 是否仍然有效")
+        });
+    }
+
+    // 优先使用可达的上游作为参照时间来源
+    let reference = probes
+        .iter()
+        .find(|p| p.sample.reachable)
+        .map(|p| p.base_url.clone())
+        .unwrap_or_else(|| DUCKCODING_BASE_URL.to_string());
+    checks.push(check_clock_skew(&reference).await);
+    checks
+}
+
+async fn check_clock_skew(reference_url: &str) -> DiagnosticCheck {
+    let check = |severity, detail: String| {
+        DiagnosticCheck::new(
+            DiagnosticCategory::Clock,
+            "clock:skew",
+            "系统时钟",
+            severity,
+            detail,
+        )
+    };
+    let server_time = match fetch_server_time(reference_url).await {
+        Ok(time) => time,
+        Err(e) => {
+            return check(
+                DiagnosticSeverity::Warning,
+                format!("无法获取参照时间（{reference_url}）: {e}"),
+            )
+        }
+    };
+
+    let skew = chrono::Utc::now().timestamp() - server_time.timestamp();
+    let detail = format!("本机时间与 {reference_url} 相差 {skew} 秒");
+    match clock_skew_severity(skew) {
+        DiagnosticSeverity::Ok => check(DiagnosticSeverity::Ok, detail),
+        severity => check(severity, detail)
+            .with_fix("开启系统的自动时间同步；时钟偏差过大会导致 TLS 握手与上游签名校验失败"),
+    }
+}
+
+async fn fetch_server_time(url: &str) -> anyhow::Result<chrono::DateTime<chrono::FixedOffset>> {
+    let client = crate::http_client::build_client().map_err(|e| anyhow::anyhow!(e))?;
+    let response = client.head(url).timeout(CLOCK_PROBE_TIMEOUT).send().await?;
+    let date = response
+        .headers()
+        .get(reqwest::header::DATE)
+        .and_then(|v| v.to_str().ok())
+        .ok_or_else(|| anyhow::anyhow!("响应缺少 Date 头"))?;
+    Ok(chrono::DateTime::parse_from_rfc2822(date)?)
+}
+
+fn clock_skew_severity(skew_secs: i64) -> DiagnosticSeverity {
+    match skew_secs.abs() {
+        s if s > CLOCK_SKEW_ERROR_SECS => DiagnosticSeverity::Error,
+        s if s > CLOCK_SKEW_WARNING_SECS => DiagnosticSeverity::Warning,
+        _ => DiagnosticSeverity::Ok,
+    }
+}
+
+// ==================== 工具命令 ====================
+
+async fn check_tools() -> Vec<DiagnosticCheck> {
+    let executor = CommandExecutor::new();
+    let mut checks = Vec::new();
+    for tool in Tool::all() {
+        let command = tool
+            .check_command
+            .split_whitespace()
+            .next()
+            .unwrap_or(&tool.check_command)
+            .to_string();
+        let check = |severity, detail: String| {
+            DiagnosticCheck::new(
+                DiagnosticCategory::Tool,
+                format!("tool:{}", tool.id),
+                tool.name.clone(),
+                severity,
+                detail,
+            )
+        };
+
+        if !executor.command_exists_async(&command).await {
+            checks.push(
+                check(
+                    DiagnosticSeverity::Info,
+                    format!("未找到 {command} 命令（未安装或不在 PATH 中）"),
+                )
+                .with_fix(
+                    "如需使用该工具，请在工具管理页面安装；已安装时可运行「版本检测失败」故障排查",
+                ),
+            );
+            continue;
+        }
+        let result = executor.execute_async(&tool.check_command).await;
+        checks.push(match parse_version(&result.stdout) {
+            Some(version) if result.success => check(
+                DiagnosticSeverity::Ok,
+                format!("{command} 可用（版本 {version}）"),
+            ),
+            _ => check(
+                DiagnosticSeverity::Warning,
+                format!("找到 {command} 命令，但版本命令执行失败"),
+            )
+            .with_fix("运行「安装成功，但版本检测失败」故障排查流程查看详细原因"),
+        });
+    }
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_severity_thresholds() {
+        assert_eq!(disk_severity(50 * 1024 * 1024), DiagnosticSeverity::Error);
+        assert_eq!(
+            disk_severity(500 * 1024 * 1024),
+            DiagnosticSeverity::Warning
+        );
+        assert_eq!(
+            disk_severity(10 * DISK_WARNING_BYTES),
+            DiagnosticSeverity::Ok
+        );
+
+        assert_eq!(clock_skew_severity(5), DiagnosticSeverity::Ok);
+        assert_eq!(clock_skew_severity(-120), DiagnosticSeverity::Warning);
+        assert_eq!(clock_skew_severity(3600), DiagnosticSeverity::Error);
+    }
+
+    #[test]
+    fn test_check_json_syntax() {
+        let dir = tempfile::tempdir().unwrap();
+        let good = dir.path().join("good.json");
+        let bad = dir.path().join("bad.json");
+        std::fs::write(&good, r#"{"a": 1}"#).unwrap();
+        std::fs::write(&bad, r#"{"a": "#).unwrap();
+
+        assert!(check_json_syntax(&good).is_ok());
+        assert!(check_json_syntax(&bad)
+            .unwrap_err()
+            .contains("JSON 格式错误"));
+    }
+
+    #[test]
+    fn test_report_overall() {
+        let checks = vec![
+            DiagnosticCheck::new(
+                DiagnosticCategory::Tool,
+                "tool:a",
+                "a",
+                DiagnosticSeverity::Info,
+                "",
+            ),
+            DiagnosticCheck::new(
+                DiagnosticCategory::Disk,
+                "disk",
+                "disk",
+                DiagnosticSeverity::Warning,
+                "",
+            ),
+        ];
+        let report = DiagnosticsReport::from_checks(checks, Instant::now());
+        assert_eq!(report.overall, DiagnosticSeverity::Warning);
+        assert_eq!((report.error_count, report.warning_count), (0, 1));
+    }
+}
@@ -0,0 +1,183 @@
+//! 本机 HTTP 服务
+//!
+//! 管理 API、MCP HTTP 接入与 Prometheus 指标监听共用的服务骨架：
+//! - 按配置启动、重启或停止，配置未变化时不做任何操作
+//! - 每个连接由 hyper http1 处理，取消令牌触发后停止接受新连接
+//! - 记录实际监听地址，供状态查询展示
+
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use http_body_util::Full;
+use hyper::body::Incoming;
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Request, Response};
+use hyper_util::rt::TokioIo;
+use std::future::Future;
+use std::net::SocketAddr;
+use tokio::net::TcpListener;
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+
+/// 本机服务的响应类型
+pub type HttpResponse = Response<Full<Bytes>>;
+
+struct RunningServer<C> {
+    config: C,
+    /// 实际监听地址
+    addr: SocketAddr,
+    cancel: CancellationToken,
+}
+
+/// 由配置驱动的单个本机 HTTP 服务
+pub struct LocalServer<C> {
+    /// 服务名称（用于日志与错误信息）
+    name: &'static str,
+    running: Mutex<Option<RunningServer<C>>>,
+}
+
+impl<C: Clone + PartialEq> LocalServer<C> {
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            running: Mutex::new(None),
+        }
+    }
+
+    /// 按配置启动、重启或停止服务（配置未变化时不做任何操作）
+    ///
+    /// `start` 返回监听地址与请求处理函数；返回 None 表示该配置下不运行服务
+    pub async fn apply<H, Fut>(
+        &self,
+        config: &C,
+        start: impl FnOnce(&C) -> Result<Option<(SocketAddr, H)>>,
+    ) -> Result<()>
+    where
+        H: Fn(Request<Incoming>) -> Fut + Clone + Send + Sync + 'static,
+        Fut: Future<Output = HttpResponse> + Send + 'static,
+    {
+        let mut running = self.running.lock().await;
+        if running.as_ref().is_some_and(|r| r.config == *config) {
+            return Ok(());
+        }
+        if let Some(stopped) = running.take() {
+            stopped.cancel.cancel();
+            tracing::info!(addr = %stopped.addr, "{}已停止", self.name);
+        }
+        let Some((addr, handler)) = start(config)? else {
+            return Ok(());
+        };
+
+        let listener = TcpListener::bind(addr)
+            .await
+            .with_context(|| format!("绑定端口 {} 失败（{}）", addr.port(), self.name))?;
+        let addr = listener.local_addr().unwrap_or(addr);
+        tracing::info!(addr = %addr, "{}已启动", self.name);
+
+        let cancel = CancellationToken::new();
+        tokio::spawn(serve(listener, cancel.clone(), self.name, handler));
+        *running = Some(RunningServer {
+            config: config.clone(),
+            addr,
+            cancel,
+        });
+        Ok(())
+    }
+
+    /// 运行中的配置与实际监听地址（未运行时为 None）
+    pub async fn running(&self) -> Option<(C, SocketAddr)> {
+        self.running
+            .lock()
+            .await
+            .as_ref()
+            .map(|r| (r.config.clone(), r.addr))
+    }
+}
+
+/// 接受连接直到取消，每个请求交给 `handler` 处理
+pub async fn serve<H, Fut>(
+    listener: TcpListener,
+    cancel: CancellationToken,
+    name: &'static str,
+    handler: H,
+) where
+    H: Fn(Request<Incoming>) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = HttpResponse> + Send + 'static,
+{
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => break,
+            result = listener.accept() => {
+                let Ok((stream, _)) = result else {
+                    continue;
+                };
+                let handler = handler.clone();
+                tokio::spawn(async move {
+                    let service = service_fn(move |req| {
+                        let response = handler(req);
+                        async move { Ok::<_, hyper::Error>(response.await) }
+                    });
+                    if let Err(e) = http1::Builder::new()
+                        .serve_connection(TokioIo::new(stream), service)
+                        .await
+                    {
+                        tracing::debug!(error = ?e, "{}连接处理失败", name);
+                    }
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::StatusCode;
+
+    type Handler = fn(Request<Incoming>) -> std::future::Ready<HttpResponse>;
+
+    fn echo_path(req: Request<Incoming>) -> std::future::Ready<HttpResponse> {
+        std::future::ready(
+            Response::builder()
+                .status(StatusCode::OK)
+                .body(Full::new(Bytes::from(req.uri().path().to_string())))
+                .unwrap(),
+        )
+    }
+
+    /// 配置为端口（None 表示停用）
+    fn start(port: &Option<u16>) -> Result<Option<(SocketAddr, Handler)>> {
+        Ok(port.map(|port| {
+            (
+                SocketAddr::from(([127, 0, 0, 1], port)),
+                echo_path as Handler,
+            )
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_apply_restarts_only_on_config_change() {
+        let server = LocalServer::new("测试服务");
+        server.apply(&Some(0), start).await.unwrap();
+        let (_, addr) = server.running().await.unwrap();
+        assert_ne!(addr.port(), 0);
+
+        // 配置未变化：保持原监听
+        server.apply(&Some(0), start).await.unwrap();
+        assert_eq!(server.running().await.unwrap().1, addr);
+
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+        let body = client
+            .get(format!("http://{addr}/ping"))
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert_eq!(body, "/ping");
+
+        server.apply(&None, start).await.unwrap();
+        assert!(server.running().await.is_none());
+    }
+}
@@ -0,0 +1,380 @@
+//! 夜间维护窗口
+//!
+//! 长时间运行的代理会累积连接状态，数据库 WAL 也会持续增长。
+//! 启用后在每天的维护窗口内依次执行：
+//! 1. 排空并重启运行中的透明代理
+//! 2. 回写并截断 Token 统计与会话数据库的 WAL
+//! 3. 清理超过保留天数的滚动日志
+//! 4. 按保留策略清理 Token 日志、过期会话与请求体捕获
+//!
+//! 窗口内每分钟检查一次；仍有活跃会话（代理连接未关闭或最近有请求）时推迟到下一次检查，
+//! 整个窗口都未空闲则当天跳过。
+
+use crate::models::config::MaintenanceConfig;
+use crate::services::proxy::capture_store;
+use crate::services::proxy::proxy_manager::ProxyManager;
+use crate::services::session::manager::SESSION_MANAGER;
+use crate::services::token_stats::TokenStatsManager;
+use crate::utils::config::read_global_config;
+use anyhow::{anyhow, Result};
+use chrono::{Duration as ChronoDuration, Local, NaiveDate, NaiveDateTime, NaiveTime};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// 调度检查间隔
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 单个维护步骤的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceStep {
+    pub name: String,
+    pub success: bool,
+    pub detail: String,
+}
+
+/// 一次维护的执行报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceReport {
+    /// 开始/结束时间（Unix 时间戳，毫秒）
+    pub started_at: i64,
+    pub finished_at: i64,
+    /// 是否手动触发（跳过活跃会话检查）
+    pub forced: bool,
+    pub success: bool,
+    pub steps: Vec<MaintenanceStep>,
+}
+
+/// 维护调度状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceStatus {
+    pub config: MaintenanceConfig,
+    pub running: bool,
+    /// 下一个维护窗口的开始时间（Unix 时间戳，毫秒；未启用时为 None）
+    pub next_window_start: Option<i64>,
+    pub last_report: Option<MaintenanceReport>,
+    /// 最近一次推迟/跳过的原因
+    pub last_skip_reason: Option<String>,
+}
+
+#[derive(Default)]
+struct SchedulerState {
+    running: bool,
+    /// 已完成维护的窗口（按窗口开始日期）
+    last_window: Option<NaiveDate>,
+    last_report: Option<MaintenanceReport>,
+    last_skip_reason: Option<String>,
+}
+
+static STATE: Lazy<Mutex<SchedulerState>> = Lazy::new(|| Mutex::new(SchedulerState::default()));
+
+fn parse_window_start(value: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M").ok()
+}
+
+/// 当前时间所在维护窗口的开始日期（窗口可跨越午夜）
+fn current_window(now: NaiveDateTime, start: NaiveTime, minutes: u32) -> Option<NaiveDate> {
+    let length = ChronoDuration::minutes(minutes.max(1) as i64);
+    [now.date(), now.date() - ChronoDuration::days(1)]
+        .into_iter()
+        .find(|date| {
+            let begin = date.and_time(start);
+            now >= begin && now < begin + length
+        })
+}
+
+/// 下一个维护窗口的开始时间（当前正处于窗口内时返回本窗口）
+fn next_window_start(now: NaiveDateTime, start: NaiveTime, minutes: u32) -> NaiveDateTime {
+    if let Some(date) = current_window(now, start, minutes) {
+        return date.and_time(start);
+    }
+    let today = now.date().and_time(start);
+    if today > now {
+        today
+    } else {
+        today + ChronoDuration::days(1)
+    }
+}
+
+fn to_timestamp_millis(time: NaiveDateTime) -> Option<i64> {
+    time.and_local_timezone(Local)
+        .earliest()
+        .map(|t| t.timestamp_millis())
+}
+
+fn load_config() -> MaintenanceConfig {
+    read_global_config()
+        .ok()
+        .flatten()
+        .map(|config| config.maintenance)
+        .unwrap_or_default()
+}
+
+/// 校验维护配置
+pub fn validate_config(config: &MaintenanceConfig) -> Result<()> {
+    if parse_window_start(&config.window_start).is_none() {
+        return Err(anyhow!(
+            "维护窗口开始时间格式无效（应为 HH:MM）: {}",
+            config.window_start
+        ));
+    }
+    if config.window_minutes == 0 || config.window_minutes > 24 * 60 {
+        return Err(anyhow!("维护窗口时长需在 1-1440 分钟之间"));
+    }
+    Ok(())
+}
+
+/// 获取维护调度状态
+pub fn maintenance_status() -> MaintenanceStatus {
+    let config = load_config();
+    let next_window_start = parse_window_start(&config.window_start)
+        .filter(|_| config.enabled)
+        .and_then(|start| {
+            to_timestamp_millis(next_window_start(
+                Local::now().naive_local(),
+                start,
+                config.window_minutes,
+            ))
+        });
+    let state = STATE.lock().unwrap();
+    MaintenanceStatus {
+        running: state.running,
+        next_window_start,
+        last_report: state.last_report.clone(),
+        last_skip_reason: state.last_skip_reason.clone(),
+        config,
+    }
+}
+
+/// 检查是否存在活跃会话，返回推迟原因
+pub async fn active_session_reason(
+    proxy_manager: &ProxyManager,
+    idle_minutes: u32,
+) -> Option<String> {
+    let connections = proxy_manager.active_connections().await;
+    if connections > 0 {
+        return Some(format!("透明代理仍有 {connections} 个打开的连接"));
+    }
+
+    let last_seen = SESSION_MANAGER
+        .list_recent_sessions(1)
+        .ok()?
+        .into_iter()
+        .next()?;
+    let idle_secs = chrono::Utc::now().timestamp() - last_seen.last_seen_at;
+    (idle_secs < idle_minutes as i64 * 60).then(|| {
+        format!(
+            "会话 {} 在 {} 分钟内仍有请求",
+            last_seen.display_id, idle_minutes
+        )
+    })
+}
+
+async fn run_step<F, Fut>(steps: &mut Vec<MaintenanceStep>, name: &str, step: F)
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<String>>,
+{
+    let (success, detail) = match step().await {
+        Ok(detail) => (true, detail),
+        Err(e) => (false, e.to_string()),
+    };
+    if success {
+        tracing::info!(step = name, detail = %detail, "维护步骤完成");
+    } else {
+        tracing::warn!(step = name, error = %detail, "维护步骤失败");
+    }
+    steps.push(MaintenanceStep {
+        name: name.to_string(),
+        success,
+        detail,
+    });
+}
+
+/// 执行一次维护（步骤失败不影响后续步骤）
+pub async fn run_maintenance(
+    proxy_manager: &ProxyManager,
+    config: &MaintenanceConfig,
+    forced: bool,
+) -> Result<MaintenanceReport> {
+    {
+        let mut state = STATE.lock().unwrap();
+        if state.running {
+            return Err(anyhow!("维护任务正在执行"));
+        }
+        state.running = true;
+    }
+
+    tracing::info!(forced, "开始执行维护任务");
+    let started_at = chrono::Utc::now().timestamp_millis();
+    let mut steps = Vec::new();
+
+    if config.restart_proxies {
+        run_step(&mut steps, "restart_proxies", || async {
+            let drain_timeout = Duration::from_secs(config.drain_timeout_secs);
+            let mut running: Vec<String> = proxy_manager
+                .get_all_status()
+                .await
+                .into_iter()
+                .filter_map(|(tool_id, running)| running.then_some(tool_id))
+                .collect();
+            running.sort();
+
+            let mut failed = Vec::new();
+            let mut forced_close = 0;
+            for tool_id in &running {
+                match proxy_manager.restart_proxy(tool_id, drain_timeout).await {
+                    Ok(true) => {}
+                    Ok(false) => forced_close += 1,
+                    Err(e) => failed.push(format!("{tool_id}: {e}")),
+                }
+            }
+            if !failed.is_empty() {
+                return Err(anyhow!("重启代理失败: {}", failed.join("; ")));
+            }
+            Ok(format!(
+                "已重启 {} 个代理（{} 个排空超时）",
+                running.len(),
+                forced_close
+            ))
+        })
+        .await;
+    }
+
+    run_step(&mut steps, "checkpoint", || async {
+        TokenStatsManager::get().force_checkpoint()?;
+        SESSION_MANAGER.force_checkpoint()?;
+        Ok("Token 统计与会话数据库 WAL 已回写".to_string())
+    })
+    .await;
+
+    run_step(&mut steps, "rotate_logs", || async {
+        let log_path = read_global_config()
+            .ok()
+            .flatten()
+            .and_then(|c| c.log_config.file_path);
+        let removed =
+            crate::core::logger::prune_log_files(log_path.as_deref(), config.log_retention_days)?;
+        Ok(format!("删除 {removed} 个过期日志文件"))
+    })
+    .await;
+
+    run_step(&mut steps, "retention", || async {
+        let stats_config = read_global_config()
+            .ok()
+            .flatten()
+            .map(|c| c.token_stats_config)
+            .unwrap_or_default();
+        let token_logs = if stats_config.auto_cleanup_enabled {
+            TokenStatsManager::get()
+                .cleanup_by_config(stats_config.retention_days, stats_config.max_log_count)?
+        } else {
+            0
+        };
+        let sessions = SESSION_MANAGER.cleanup_expired_sessions()?;
+        let captures = capture_store::purge_expired()?;
+        Ok(format!(
+            "清理 Token 日志 {token_logs} 条、会话 {sessions} 个、请求体捕获 {captures} 个"
+        ))
+    })
+    .await;
+
+    let report = MaintenanceReport {
+        started_at,
+        finished_at: chrono::Utc::now().timestamp_millis(),
+        forced,
+        success: steps.iter().all(|s| s.success),
+        steps,
+    };
+    tracing::info!(success = report.success, "维护任务执行完成");
+
+    let mut state = STATE.lock().unwrap();
+    state.running = false;
+    state.last_report = Some(report.clone());
+    Ok(report)
+}
+
+/// 启动维护调度器（不会返回）
+pub async fn run_scheduler(proxy_manager: Arc<ProxyManager>) {
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+    loop {
+        interval.tick().await;
+
+        let config = load_config();
+        if !config.enabled {
+            continue;
+        }
+        let Some(start) = parse_window_start(&config.window_start) else {
+            tracing::warn!(window_start = %config.window_start, "维护窗口开始时间无效");
+            continue;
+        };
+        let Some(window) = current_window(Local::now().naive_local(), start, config.window_minutes)
+        else {
+            continue;
+        };
+        if STATE.lock().unwrap().last_window == Some(window) {
+            continue;
+        }
+
+        if let Some(reason) = active_session_reason(&proxy_manager, config.idle_minutes).await {
+            tracing::debug!(reason = %reason, "存在活跃会话，推迟维护");
+            STATE.lock().unwrap().last_skip_reason = Some(reason);
+            continue;
+        }
+
+        match run_maintenance(&proxy_manager, &config, false).await {
+            Ok(_) => {
+                let mut state = STATE.lock().unwrap();
+                state.last_window = Some(window);
+                state.last_skip_reason = None;
+            }
+            Err(e) => tracing::warn!(error = %e, "维护任务未执行"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(date: &str, time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(&format!("{date} {time}"), "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn test_current_window_crosses_midnight() {
+        let start = parse_window_start("23:30").unwrap();
+        let day = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+
+        assert_eq!(
+            current_window(at("2026-03-01", "23:45"), start, 60),
+            Some(day)
+        );
+        assert_eq!(
+            current_window(at("2026-03-02", "00:15"), start, 60),
+            Some(day)
+        );
+        assert_eq!(current_window(at("2026-03-02", "00:30"), start, 60), None);
+        assert_eq!(current_window(at("2026-03-01", "23:00"), start, 60), None);
+        assert!(parse_window_start("25:00").is_none());
+    }
+
+    #[test]
+    fn test_next_window_start() {
+        let start = parse_window_start("03:00").unwrap();
+        assert_eq!(
+            next_window_start(at("2026-03-01", "01:00"), start, 60),
+            at("2026-03-01", "03:00")
+        );
+        assert_eq!(
+            next_window_start(at("2026-03-01", "03:20"), start, 60),
+            at("2026-03-01", "03:00")
+        );
+        assert_eq!(
+            next_window_start(at("2026-03-01", "05:00"), start, 60),
+            at("2026-03-02", "03:00")
+        );
+    }
+}
@@ -0,0 +1,191 @@
+//! MCP 的 HTTP 接入
+//!
+//! 监听 `POST http://127.0.0.1:<port>/mcp`，请求体为单条 JSON-RPC 消息，响应为 JSON
+//! （通知返回 202）。不提供服务端推送流（GET 返回 405）。
+//!
+//! 请求须携带 `Authorization: Bearer <token>`；带有非本机 `Origin` 的请求一律拒绝，
+//! 防止网页通过 DNS 重绑定访问本机端口。
+
+use anyhow::{bail, Result};
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full, Limited};
+use hyper::body::Incoming;
+use hyper::{header, Method, Request, Response, StatusCode};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use crate::models::config::McpConfig;
+use crate::services::admin_api::authorized;
+use crate::services::local_server::{HttpResponse, LocalServer};
+
+/// 请求体大小上限
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+static SERVER: Lazy<LocalServer<McpConfig>> = Lazy::new(|| LocalServer::new("MCP HTTP 接入"));
+
+/// MCP 服务状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct McpStatus {
+    pub config: McpConfig,
+    /// HTTP 接入正在监听
+    pub http_running: bool,
+    /// HTTP 接入地址（未运行时为 None）
+    pub endpoint: Option<String>,
+    /// 标准输入输出方式的启动命令（供 AI 编程工具配置）
+    pub stdio_command: Option<String>,
+}
+
+/// 按配置启动、重启或停止 HTTP 接入（配置未变化时不做任何操作）
+pub async fn apply_config(config: &McpConfig) -> Result<()> {
+    SERVER
+        .apply(config, |config| {
+            if !config.enabled || !config.http_enabled {
+                return Ok(None);
+            }
+            let Some(token) = config.token.clone().filter(|t| !t.trim().is_empty()) else {
+                bail!("MCP HTTP 接入未设置访问令牌");
+            };
+            let addr = SocketAddr::from(([127, 0, 0, 1], config.port));
+            let token = Arc::new(token);
+            Ok(Some((addr, move |req| handle(req, token.clone()))))
+        })
+        .await
+}
+
+/// 当前 MCP 服务状态
+pub async fn mcp_status(config: McpConfig) -> McpStatus {
+    let running = SERVER.running().await;
+    McpStatus {
+        endpoint: running
+            .as_ref()
+            .map(|(_, addr)| format!("http://{}/mcp", addr)),
+        http_running: running.is_some(),
+        config: running.map(|(config, _)| config).unwrap_or(config),
+        stdio_command: std::env::current_exe()
+            .ok()
+            .map(|exe| format!("\"{}\" mcp", exe.display())),
+    }
+}
+
+async fn handle(req: Request<Incoming>, token: Arc<String>) -> HttpResponse {
+    if req.uri().path() != "/mcp" {
+        return plain(StatusCode::NOT_FOUND, "接口不存在");
+    }
+    if !local_origin(&req) {
+        return plain(StatusCode::FORBIDDEN, "不允许的 Origin");
+    }
+    if !authorized(&req, &token) {
+        return plain(StatusCode::UNAUTHORIZED, "缺少或错误的访问令牌");
+    }
+    if req.method() != Method::POST {
+        return plain(StatusCode::METHOD_NOT_ALLOWED, "仅支持 POST");
+    }
+
+    let body = match Limited::new(req.into_body(), MAX_BODY_BYTES)
+        .collect()
+        .await
+    {
+        Ok(body) => body.to_bytes(),
+        Err(e) => return plain(StatusCode::BAD_REQUEST, &format!("读取请求体失败: {}", e)),
+    };
+    let raw = String::from_utf8_lossy(&body).into_owned();
+    match tokio::task::spawn_blocking(move || super::handle_raw(&raw)).await {
+        Ok(Some(response)) => Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Full::new(Bytes::from(response.to_string())))
+            .unwrap(),
+        Ok(None) => Response::builder()
+            .status(StatusCode::ACCEPTED)
+            .body(Full::new(Bytes::new()))
+            .unwrap(),
+        Err(e) => plain(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+    }
+}
+
+/// 未携带 Origin（命令行客户端）或 Origin 为本机地址
+fn local_origin(req: &Request<Incoming>) -> bool {
+    let Some(origin) = req.headers().get(header::ORIGIN) else {
+        return true;
+    };
+    let Some(host) = origin
+        .to_str()
+        .ok()
+        .and_then(|o| url::Url::parse(o).ok())
+        .and_then(|u| u.host_str().map(str::to_string))
+    else {
+        return false;
+    };
+    matches!(host.as_str(), "localhost" | "127.0.0.1" | "[::1]")
+}
+
+fn plain(status: StatusCode, message: &str) -> HttpResponse {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+        .body(Full::new(Bytes::from(message.to_string())))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::local_server;
+    use tokio::net::TcpListener;
+    use tokio_util::sync::CancellationToken;
+
+    #[tokio::test]
+    async fn test_http_transport() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+        let cancel = CancellationToken::new();
+        let token = Arc::new("mcp-token".to_string());
+        tokio::spawn(local_server::serve(
+            listener,
+            cancel.clone(),
+            "MCP HTTP 接入",
+            move |req| handle(req, token.clone()),
+        ));
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+        let ping = r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#;
+
+        let resp = client.post(&url).body(ping).send().await.unwrap();
+        assert_eq!(resp.status(), 401);
+        let resp = client
+            .post(&url)
+            .bearer_auth("mcp-token")
+            .header("Origin", "https://evil.example")
+            .body(ping)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 403);
+
+        let resp = client
+            .post(&url)
+            .bearer_auth("mcp-token")
+            .header("Origin", "http://localhost:1420")
+            .body(ping)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 200);
+        let body: serde_json::Value = resp.json().await.unwrap();
+        assert_eq!(body["id"], 1);
+        assert!(body["result"].is_object());
+
+        let resp = client
+            .post(&url)
+            .bearer_auth("mcp-token")
+            .body(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 202);
+
+        cancel.cancel();
+    }
+}
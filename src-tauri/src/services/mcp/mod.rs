@@ -0,0 +1,243 @@
+//! MCP 服务（Model Context Protocol）
+//!
+//! 让 Claude Code 等 AI 编程工具在对话中直接查询和控制 DuckCoding：
+//! - `get_current_usage`：指定时间以来的请求数、Token 与成本
+//! - `get_budget_remaining`：各工具当前周期的预算余额
+//! - `list_profiles` / `switch_profile`：查看与切换 Profile
+//!
+//! 协议为 JSON-RPC 2.0，支持两种传输方式：
+//! - 标准输入输出：由工具启动 `duckcoding mcp` 子进程，每行一条消息，
+//!   例如 `claude mcp add duckcoding -- duckcoding mcp`
+//! - HTTP：界面运行时监听 `POST http://127.0.0.1:<port>/mcp`（见 [`http`]）
+//!
+//! 两种方式均需在设置中启用 MCP 服务（`GlobalConfig.mcp.enabled`）。
+
+mod http;
+mod tools;
+
+pub use http::{apply_config, mcp_status, McpStatus};
+
+use anyhow::Result;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+/// 支持的协议版本（最新在前）
+const PROTOCOL_VERSIONS: [&str; 3] = ["2025-06-18", "2025-03-26", "2024-11-05"];
+
+// JSON-RPC 错误码
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// JSON-RPC 错误
+#[derive(Debug)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// 处理一条原始消息，返回需要回复的消息（通知无需回复）
+///
+/// 工具调用会读取统计数据库与配置文件，异步上下文中应在阻塞线程中调用。
+pub fn handle_raw(raw: &str) -> Option<Value> {
+    match serde_json::from_str::<Value>(raw) {
+        Ok(message) => handle_message(&message),
+        Err(e) => Some(error_response(
+            Value::Null,
+            RpcError::new(PARSE_ERROR, format!("无效的 JSON: {}", e)),
+        )),
+    }
+}
+
+/// 处理一条 JSON-RPC 消息
+pub fn handle_message(message: &Value) -> Option<Value> {
+    let id = message.get("id").cloned();
+    let Some(method) = message.get("method").and_then(Value::as_str) else {
+        // 客户端对服务端请求的响应（本服务不发起请求）无需处理
+        if message.get("result").is_some() || message.get("error").is_some() {
+            return None;
+        }
+        return Some(error_response(
+            id.unwrap_or(Value::Null),
+            RpcError::new(INVALID_REQUEST, "缺少 method"),
+        ));
+    };
+    let Some(id) = id else {
+        tracing::debug!(method = %method, "收到 MCP 通知");
+        return None;
+    };
+
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+    let result = match method {
+        "initialize" => Ok(initialize(&params)),
+        "ping" => Ok(json!({})),
+        "tools/list" => Ok(json!({ "tools": tools::definitions() })),
+        "tools/call" => call_tool(&params),
+        _ => Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("不支持的方法: {}", method),
+        )),
+    };
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(e) => error_response(id, e),
+    })
+}
+
+/// 通过标准输入输出提供服务，直到输入关闭
+///
+/// 标准输出只用于协议消息，诊断信息须写入标准错误。
+pub async fn serve_stdio() -> Result<()> {
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = tokio::task::spawn_blocking(move || handle_raw(&line)).await?;
+        if let Some(response) = response {
+            stdout
+                .write_all(format!("{}\n", response).as_bytes())
+                .await?;
+            stdout.flush().await?;
+        }
+    }
+    Ok(())
+}
+
+fn initialize(params: &Value) -> Value {
+    // 客户端请求的版本受支持时沿用，否则返回最新版本由客户端决定是否断开
+    let requested = params.get("protocolVersion").and_then(Value::as_str);
+    let version = requested
+        .filter(|v| PROTOCOL_VERSIONS.contains(v))
+        .unwrap_or(PROTOCOL_VERSIONS[0]);
+    json!({
+        "protocolVersion": version,
+        "capabilities": { "tools": { "listChanged": false } },
+        "serverInfo": {
+            "name": "duckcoding",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "instructions": "查询 DuckCoding 记录的 AI 编程工具用量、成本与预算，并切换各工具使用的 Profile。",
+    })
+}
+
+/// 工具执行失败以 `isError` 结果返回（便于模型读取原因），未知工具返回协议错误
+fn call_tool(params: &Value) -> Result<Value, RpcError> {
+    let Some(name) = params.get("name").and_then(Value::as_str) else {
+        return Err(RpcError::new(INVALID_PARAMS, "缺少工具名称"));
+    };
+    let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+    let Some(result) = tools::call(name, arguments) else {
+        return Err(RpcError::new(
+            INVALID_PARAMS,
+            format!("未知的工具: {}", name),
+        ));
+    };
+
+    Ok(match result {
+        Ok(value) => json!({
+            "content": [{
+                "type": "text",
+                "text": serde_json::to_string_pretty(&value).unwrap_or_default(),
+            }],
+            "structuredContent": value,
+            "isError": false,
+        }),
+        Err(e) => {
+            tracing::warn!(tool = %name, error = ?e, "MCP 工具调用失败");
+            json!({
+                "content": [{ "type": "text", "text": format!("{:#}", e) }],
+                "isError": true,
+            })
+        }
+    })
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": error.code, "message": error.message },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_initialize_and_list_tools() {
+        let response = handle_raw(
+            r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-03-26","capabilities":{}}}"#,
+        )
+        .unwrap();
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["result"]["protocolVersion"], "2025-03-26");
+        assert_eq!(response["result"]["serverInfo"]["name"], "duckcoding");
+
+        // 不支持的版本回退到最新版本
+        let response = handle_raw(
+            r#"{"jsonrpc":"2.0","id":2,"method":"initialize","params":{"protocolVersion":"1999-01-01"}}"#,
+        )
+        .unwrap();
+        assert_eq!(response["result"]["protocolVersion"], PROTOCOL_VERSIONS[0]);
+
+        // 通知无需回复
+        assert!(handle_raw(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#).is_none());
+
+        let response = handle_raw(r#"{"jsonrpc":"2.0","id":"t","method":"tools/list"}"#).unwrap();
+        let names: Vec<&str> = response["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "get_current_usage",
+                "get_budget_remaining",
+                "list_profiles",
+                "switch_profile"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_protocol_errors() {
+        let response = handle_raw("{not json").unwrap();
+        assert_eq!(response["error"]["code"], PARSE_ERROR);
+        assert_eq!(response["id"], Value::Null);
+
+        let response = handle_raw(r#"{"jsonrpc":"2.0","id":3,"method":"resources/list"}"#).unwrap();
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+
+        let response = handle_raw(
+            r#"{"jsonrpc":"2.0","id":4,"method":"tools/call","params":{"name":"rm_rf"}}"#,
+        )
+        .unwrap();
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+
+        // 参数错误以工具结果返回，供模型修正后重试
+        let response = handle_raw(
+            r#"{"jsonrpc":"2.0","id":5,"method":"tools/call","params":{"name":"get_current_usage","arguments":{"since":"3y"}}}"#,
+        )
+        .unwrap();
+        assert_eq!(response["result"]["isError"], true);
+        assert!(response["result"]["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("3y"));
+    }
+}
@@ -0,0 +1,194 @@
+//! MCP 工具定义与实现
+
+use anyhow::{anyhow, Result};
+use chrono::Local;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::services::profile_manager::ProfileManager;
+use crate::services::proxy_config_manager::ProxyConfigManager;
+use crate::services::token_stats::budget::period_start;
+use crate::services::token_stats::{
+    parse_since, BudgetPeriod, CostGroupBy, CostSummaryQuery, TokenStatsAnalytics,
+    TokenStatsManager,
+};
+use crate::utils::config::config_dir;
+
+#[derive(Debug, Deserialize)]
+struct UsageArgs {
+    since: Option<String>,
+    tool: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ToolFilterArgs {
+    tool: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SwitchProfileArgs {
+    tool: String,
+    name: String,
+}
+
+/// `tools/list` 返回的工具列表
+pub(super) fn definitions() -> Value {
+    // 可选工具随 proxy.json 与 tools.d 变化，每次列出时重新读取
+    let tool_ids = ProxyConfigManager::new()
+        .and_then(|mgr| mgr.load_proxy_store())
+        .unwrap_or_default()
+        .tool_ids();
+    let tool_filter = json!({
+        "type": "string",
+        "enum": tool_ids,
+        "description": "仅查询指定工具（省略时查询全部）",
+    });
+    json!([
+        {
+            "name": "get_current_usage",
+            "description": "查询经 DuckCoding 代理的请求数、Token 用量与成本（USD），按模型分组",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "since": {
+                        "type": "string",
+                        "description": "起始时间：相对时长（30m / 24h / 7d / 2w）或日期（2026-10-01），省略时为今天零点",
+                    },
+                    "tool": tool_filter,
+                },
+            },
+        },
+        {
+            "name": "get_budget_remaining",
+            "description": "查询各工具当前自然日 / 周 / 月的预算上限、已用金额与剩余金额（USD）",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "tool": tool_filter },
+            },
+        },
+        {
+            "name": "list_profiles",
+            "description": "列出各工具的 Profile（API Key 仅显示预览），is_active 标记当前使用的 Profile",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "tool": tool_filter },
+            },
+        },
+        {
+            "name": "switch_profile",
+            "description": "激活指定 Profile 并写入工具原生配置（之后发起的请求使用新的 API Key 与地址）",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "tool": { "type": "string", "enum": tool_ids },
+                    "name": { "type": "string", "description": "Profile 名称（见 list_profiles）" },
+                },
+                "required": ["tool", "name"],
+            },
+        },
+    ])
+}
+
+/// 调用工具（未知工具返回 None）
+pub(super) fn call(name: &str, arguments: Value) -> Option<Result<Value>> {
+    let result = match name {
+        "get_current_usage" => parse_args(arguments).and_then(current_usage),
+        "get_budget_remaining" => parse_args(arguments).and_then(budget_remaining),
+        "list_profiles" => parse_args(arguments).and_then(list_profiles),
+        "switch_profile" => parse_args(arguments).and_then(switch_profile),
+        _ => return None,
+    };
+    Some(result)
+}
+
+fn parse_args<T: serde::de::DeserializeOwned>(arguments: Value) -> Result<T> {
+    let arguments = if arguments.is_null() {
+        json!({})
+    } else {
+        arguments
+    };
+    serde_json::from_value(arguments).map_err(|e| anyhow!("参数无效: {}", e))
+}
+
+fn current_usage(args: UsageArgs) -> Result<Value> {
+    let now = Local::now();
+    let start_time = match args.since.as_deref() {
+        Some(since) => parse_since(since, now)?,
+        None => period_start(BudgetPeriod::Daily, now),
+    };
+    let db_path = config_dir().map_err(|e| anyhow!(e))?.join("token_stats.db");
+    let models = if db_path.exists() {
+        TokenStatsAnalytics::new(db_path).query_cost_summary(&CostSummaryQuery {
+            start_time: Some(start_time),
+            end_time: None,
+            tool_type: args.tool.clone(),
+            session_id: None,
+            tag: None,
+            group_by: CostGroupBy::Model,
+        })?
+    } else {
+        Vec::new()
+    };
+
+    Ok(json!({
+        "since": args.since.unwrap_or_else(|| "today".to_string()),
+        "start_time": start_time,
+        "tool": args.tool,
+        "request_count": models.iter().map(|m| m.request_count).sum::<i64>(),
+        "input_tokens": models.iter().map(|m| m.input_tokens).sum::<i64>(),
+        "output_tokens": models.iter().map(|m| m.output_tokens).sum::<i64>(),
+        "total_cost_usd": models.iter().fold(0.0, |sum, m| sum + m.total_cost),
+        "models": models,
+    }))
+}
+
+fn budget_remaining(args: ToolFilterArgs) -> Result<Value> {
+    let budgets: Vec<Value> = TokenStatsManager::get()
+        .check_budgets()?
+        .into_iter()
+        .filter(|b| b.enabled && !b.periods.is_empty())
+        .filter(|b| args.tool.as_deref().is_none_or(|t| b.tool_id == t))
+        .map(|b| {
+            let periods: Vec<Value> = b
+                .periods
+                .iter()
+                .map(|p| {
+                    json!({
+                        "period": p.period,
+                        "limit_usd": p.limit_usd,
+                        "spent_usd": p.spent_usd,
+                        "remaining_usd": (p.limit_usd - p.spent_usd).max(0.0),
+                        "percent": p.percent,
+                    })
+                })
+                .collect();
+            json!({
+                "tool_id": b.tool_id,
+                "mode": b.mode,
+                "warning": b.warning,
+                "exceeded": b.exceeded,
+                "periods": periods,
+            })
+        })
+        .collect();
+
+    if budgets.is_empty() {
+        return Ok(json!({ "budgets": budgets, "message": "未设置预算上限" }));
+    }
+    Ok(json!({ "budgets": budgets }))
+}
+
+fn list_profiles(args: ToolFilterArgs) -> Result<Value> {
+    let descriptors: Vec<_> = ProfileManager::new()?
+        .list_all_descriptors()?
+        .into_iter()
+        .filter(|d| args.tool.as_deref().is_none_or(|t| d.tool_id == t))
+        .collect();
+    Ok(json!({ "profiles": descriptors }))
+}
+
+fn switch_profile(args: SwitchProfileArgs) -> Result<Value> {
+    ProfileManager::new()?.activate_profile(&args.tool, &args.name)?;
+    tracing::info!(tool_id = %args.tool, profile = %args.name, "通过 MCP 激活 Profile");
+    Ok(json!({ "tool_id": args.tool, "active_profile": args.name }))
+}
@@ -191,8 +191,19 @@ impl MigrationManager {
                 external_poll_interval_ms: 5000,
                 single_instance_enabled: true,
                 startup_enabled: false,
+                startup_mode: crate::models::config::StartupMode::default(),
                 config_watch: crate::models::config::ConfigWatchConfig::default(),
                 token_stats_config: crate::models::config::TokenStatsConfig::default(),
+                tool_config_dirs: std::collections::HashMap::new(),
+                observer_mode: false,
+                maintenance: crate::models::config::MaintenanceConfig::default(),
+                metrics: crate::models::config::MetricsConfig::default(),
+                pricing_sync: crate::models::config::PricingSyncConfig::default(),
+                admin_api: crate::models::config::AdminApiConfig::default(),
+                mcp: crate::models::config::McpConfig::default(),
+                balance_monitor: crate::models::config::BalanceMonitorConfig::default(),
+                crash_report: crate::models::config::CrashReportConfig::default(),
+                extra_paths: Vec::new(),
             });
 
         config.version = Some(new_version.to_string());
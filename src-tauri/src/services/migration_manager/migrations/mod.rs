@@ -8,6 +8,7 @@ mod pricing_default_templates;
 mod profile_v2;
 mod proxy_config;
 mod proxy_config_split;
+mod secrets_storage;
 mod session_config;
 mod sqlite_to_json;
 
@@ -17,5 +18,6 @@ pub use pricing_default_templates::PricingDefaultTemplatesMigration;
 pub use profile_v2::ProfileV2Migration;
 pub use proxy_config::ProxyConfigMigration;
 pub use proxy_config_split::ProxyConfigSplitMigration;
+pub use secrets_storage::SecretsStorageMigration;
 pub use session_config::SessionConfigMigration;
 pub use sqlite_to_json::SqliteToJsonMigration;
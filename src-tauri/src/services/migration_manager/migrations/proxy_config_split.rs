@@ -244,12 +244,14 @@ fn parse_old_config(value: &Value) -> Result<ToolProxyConfig> {
         .as_object()
         .ok_or_else(|| anyhow::anyhow!("配置不是对象"))?;
 
+    let port = obj.get("port").and_then(|v| v.as_u64()).unwrap_or(8787) as u16;
+
     Ok(ToolProxyConfig {
         enabled: obj
             .get("enabled")
             .and_then(|v| v.as_bool())
             .unwrap_or(false),
-        port: obj.get("port").and_then(|v| v.as_u64()).unwrap_or(8787) as u16,
+        port,
         local_api_key: obj
             .get("local_api_key")
             .and_then(|v| v.as_str())
@@ -292,5 +294,7 @@ fn parse_old_config(value: &Value) -> Result<ToolProxyConfig> {
             .get("tavily_api_key")
             .and_then(|v| v.as_str())
             .map(|s| s.to_string()),
+        // 旧配置中不存在的新字段使用默认值
+        ..ToolProxyConfig::new(port)
     })
 }
@@ -0,0 +1,92 @@
+// 密钥存储迁移
+//
+// 将 profiles.json / proxy.json / config.json 中的明文 API Key 转存到密钥存储
+// （系统钥匙串或加密文件），配置文件中只保留引用
+
+use crate::core::secrets::{is_secret_ref, SecretFields};
+use crate::services::migration_manager::migration_trait::{Migration, MigrationResult};
+use crate::services::profile_manager::ProfileManager;
+use crate::services::proxy_config_manager::ProxyConfigManager;
+use crate::utils::config::{read_global_config, write_global_config};
+use anyhow::Result;
+use async_trait::async_trait;
+
+/// 密钥存储迁移（目标版本 1.5.8）
+pub struct SecretsStorageMigration;
+
+impl Default for SecretsStorageMigration {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SecretsStorageMigration {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// 统计仍为明文的密钥数量
+    fn count_plaintext<T: SecretFields>(target: &mut T) -> usize {
+        let mut count = 0;
+        target.visit_secrets(&mut |_, value| {
+            if !value.is_empty() && !is_secret_ref(value) {
+                count += 1;
+            }
+        });
+        count
+    }
+}
+
+#[async_trait]
+impl Migration for SecretsStorageMigration {
+    fn id(&self) -> &str {
+        "secrets_storage"
+    }
+
+    fn name(&self) -> &str {
+        "API Key 转存到密钥存储"
+    }
+
+    fn target_version(&self) -> &str {
+        "1.5.8"
+    }
+
+    async fn execute(&self) -> Result<MigrationResult> {
+        tracing::info!("开始将明文 API Key 转存到密钥存储");
+        let mut migrated = 0;
+
+        // 读取时已解析引用，保存时统一转为引用；只统计文件中原本为明文的字段
+        let profile_mgr = ProfileManager::new()?;
+        if profile_mgr.profiles_path().exists() {
+            let raw = std::fs::read_to_string(profile_mgr.profiles_path())?;
+            if let Ok(mut on_disk) = serde_json::from_str(&raw) {
+                migrated += Self::count_plaintext::<crate::ProfilesStore>(&mut on_disk);
+            }
+            let store = profile_mgr.load_profiles_store()?;
+            profile_mgr.save_profiles_store(&store)?;
+        }
+
+        let proxy_mgr = ProxyConfigManager::new()?;
+        if proxy_mgr.store_path().exists() {
+            let raw = std::fs::read_to_string(proxy_mgr.store_path())?;
+            if let Ok(mut on_disk) = serde_json::from_str(&raw) {
+                migrated +=
+                    Self::count_plaintext::<crate::models::proxy_config::ProxyStore>(&mut on_disk);
+            }
+            let store = proxy_mgr.load_proxy_store()?;
+            proxy_mgr.save_proxy_store(&store)?;
+        }
+
+        if let Some(config) = read_global_config().map_err(|e| anyhow::anyhow!(e))? {
+            write_global_config(&config).map_err(|e| anyhow::anyhow!(e))?;
+        }
+
+        Ok(MigrationResult {
+            migration_id: self.id().to_string(),
+            success: true,
+            message: format!("已转存 {} 个明文 API Key", migrated),
+            records_migrated: migrated,
+            duration_secs: 0.0,
+        })
+    }
+}
@@ -11,7 +11,8 @@ pub use migration_trait::{Migration, MigrationResult};
 pub use migrations::{
     BalanceLocalstorageToJsonMigration, GlobalConfigToProvidersMigration,
     PricingDefaultTemplatesMigration, ProfileV2Migration, ProxyConfigMigration,
-    ProxyConfigSplitMigration, SessionConfigMigration, SqliteToJsonMigration,
+    ProxyConfigSplitMigration, SecretsStorageMigration, SessionConfigMigration,
+    SqliteToJsonMigration,
 };
 
 use std::sync::Arc;
@@ -27,6 +28,7 @@ use std::sync::Arc;
 /// - BalanceLocalstorageToJsonMigration (1.4.1) - 余额监控 LocalStorage → JSON 迁移
 /// - GlobalConfigToProvidersMigration (1.5.0) - GlobalConfig 用户信息迁移到 Providers
 /// - PricingDefaultTemplatesMigration (1.5.5) - Codex 默认模板迁移到 builtin_openai
+/// - SecretsStorageMigration (1.5.8) - 明文 API Key 转存到系统钥匙串 / 加密文件
 pub fn create_migration_manager() -> MigrationManager {
     let mut manager = MigrationManager::new();
 
@@ -39,6 +41,7 @@ pub fn create_migration_manager() -> MigrationManager {
     manager.register(Arc::new(BalanceLocalstorageToJsonMigration::new()));
     manager.register(Arc::new(GlobalConfigToProvidersMigration::new()));
     manager.register(Arc::new(PricingDefaultTemplatesMigration::new()));
+    manager.register(Arc::new(SecretsStorageMigration::new()));
 
     tracing::debug!(
         "迁移管理器初始化完成，已注册 {} 个迁移",
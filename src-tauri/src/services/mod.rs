@@ -9,26 +9,41 @@
 // - migration_manager: 统一迁移管理（新）
 // - balance: 余额监控配置管理
 // - provider_manager: 供应商配置管理
+// - provider: 供应商上游健康探测
 // - new_api: NEW API 客户端服务
 // - token_stats: Token统计和请求记录
 // - checkin: 签到服务
 
+pub mod admin_api; // 本机管理 API
 pub mod amp_native_config; // AMP Code 原生配置管理
+pub mod audit; // 凭证操作审计日志（哈希链）
 pub mod balance;
 pub mod checkin; // 签到服务
 pub mod checkin_scheduler; // 签到调度器
 pub mod config;
 pub mod dashboard_manager; // 仪表板状态管理
+pub mod dashboard_summary; // 仪表板汇总（并发收集 + 缓存）
+pub mod diagnostics; // 自诊断
+pub mod environment; // 环境诊断（PATH / Node.js / 代理变量）
+pub mod local_server; // 本机 HTTP 服务（管理 API / MCP / 指标共用）
+pub mod maintenance; // 夜间维护窗口
+pub mod mcp; // MCP 服务
 pub mod migration_manager;
 pub mod new_api; // NEW API 客户端
 pub mod pricing; // 价格配置管理
 pub mod profile_manager; // Profile管理（v2.1）
+pub mod project; // 项目注册与技术栈识别
+pub mod provider; // 供应商上游健康探测
 pub mod provider_manager; // 供应商配置管理
 pub mod proxy;
 pub mod proxy_config_manager; // 透明代理配置管理（v2.1）
+pub mod recovery; // 异常退出恢复
+pub mod search; // 全局搜索
 pub mod session;
+pub mod system_health; // 系统健康报告
 pub mod token_stats; // Token统计服务
 pub mod tool;
+pub mod troubleshoot; // 引导式故障排查
 pub mod update;
 
 // 重新导出服务
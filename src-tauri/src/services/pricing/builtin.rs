@@ -27,10 +27,108 @@ pub fn builtin_openai_official_template() -> PricingTemplate {
         ),
     );
 
+    // GPT-5 / o 系列 / GPT-4.1 系列：缓存读取按官方 cached input 价格
+    // 推理 tokens 按输出价格计费（reasoning 价格留空，回退到输出价格）
+    for (model, input, output, cache_read, aliases) in [
+        (
+            "gpt-5.1-codex",
+            1.25,
+            10.0,
+            0.125,
+            &["gpt-5.1-codex", "gpt-5-1-codex", "gpt-5.1-codex-max"][..],
+        ),
+        (
+            "gpt-5.1-codex-mini",
+            0.25,
+            2.0,
+            0.025,
+            &["gpt-5.1-codex-mini", "gpt-5-1-codex-mini"][..],
+        ),
+        (
+            "gpt-5.1",
+            1.25,
+            10.0,
+            0.125,
+            &["gpt-5.1", "gpt-5-1", "gpt-5.1-2025-11-13"][..],
+        ),
+        ("gpt-5-codex", 1.25, 10.0, 0.125, &["gpt-5-codex"][..]),
+        (
+            "gpt-5",
+            1.25,
+            10.0,
+            0.125,
+            &["gpt-5", "gpt-5-2025-08-07", "gpt-5-chat-latest"][..],
+        ),
+        (
+            "gpt-5-mini",
+            0.25,
+            2.0,
+            0.025,
+            &["gpt-5-mini", "gpt-5-mini-2025-08-07"][..],
+        ),
+        (
+            "gpt-5-nano",
+            0.05,
+            0.4,
+            0.005,
+            &["gpt-5-nano", "gpt-5-nano-2025-08-07"][..],
+        ),
+        ("o3", 2.0, 8.0, 0.5, &["o3", "o3-2025-04-16"][..]),
+        (
+            "o4-mini",
+            1.1,
+            4.4,
+            0.275,
+            &["o4-mini", "o4-mini-2025-04-16"][..],
+        ),
+        (
+            "o3-mini",
+            1.1,
+            4.4,
+            0.55,
+            &["o3-mini", "o3-mini-2025-01-31"][..],
+        ),
+        (
+            "gpt-4.1",
+            2.0,
+            8.0,
+            0.5,
+            &["gpt-4.1", "gpt-4-1", "gpt-4.1-2025-04-14"][..],
+        ),
+        (
+            "gpt-4.1-mini",
+            0.4,
+            1.6,
+            0.1,
+            &["gpt-4.1-mini", "gpt-4-1-mini", "gpt-4.1-mini-2025-04-14"][..],
+        ),
+        (
+            "gpt-4.1-nano",
+            0.1,
+            0.4,
+            0.025,
+            &["gpt-4.1-nano", "gpt-4-1-nano", "gpt-4.1-nano-2025-04-14"][..],
+        ),
+    ] {
+        custom_models.insert(
+            model.to_string(),
+            ModelPrice::new(
+                "openai".to_string(),
+                input,
+                output,
+                None,
+                None,
+                Some(cache_read),
+                None,
+                aliases.iter().map(|a| a.to_string()).collect(),
+            ),
+        );
+    }
+
     PricingTemplate::new(
         "builtin_openai".to_string(),
         "内置OpenAI价格".to_string(),
-        "OpenAI 官方定价，包含 GPT/Codex 模型".to_string(),
+        "OpenAI 官方定价，包含 GPT-5/Codex、o 系列与 GPT-4.1 模型".to_string(),
         "1.0".to_string(),
         vec![], // 内置模板不使用继承
         custom_models,
@@ -270,6 +368,42 @@ pub fn builtin_gemini_official_template() -> PricingTemplate {
         ),
     );
 
+    // Gemini 2.5 Flash-Lite: $0.10 input / $0.40 output
+    custom_models.insert(
+        "gemini-2.5-flash-lite".to_string(),
+        ModelPrice::new(
+            "google".to_string(),
+            0.1,
+            0.4,
+            None,        // No cache write
+            None,        // No 1h cache
+            Some(0.025), // Cache read
+            None,
+            vec![
+                "gemini-2.5-flash-lite".to_string(),
+                "gemini-2-5-flash-lite".to_string(),
+            ],
+        ),
+    );
+
+    // Gemini 3 Pro Preview: $2 input / $12 output (≤200k tokens)
+    custom_models.insert(
+        "gemini-3-pro-preview".to_string(),
+        ModelPrice::new(
+            "google".to_string(),
+            2.0,
+            12.0,
+            None,       // No cache write
+            None,       // No 1h cache
+            Some(0.2),  // Cache read
+            Some(12.0), // Thinking tokens
+            vec![
+                "gemini-3-pro-preview".to_string(),
+                "gemini-3-pro".to_string(),
+            ],
+        ),
+    );
+
     PricingTemplate::new(
         "builtin_gemini".to_string(),
         "内置Gemini价格".to_string(),
@@ -286,6 +420,34 @@ pub fn builtin_gemini_official_template() -> PricingTemplate {
     )
 }
 
+/// 按模型族推断的默认上下文窗口（价格模板未设置 `context_window` 时使用）
+///
+/// 带 `[1m]` 后缀或 `-1m` 的 Claude 模型为 1M 上下文 Beta
+pub fn default_context_window(model: &str) -> Option<i64> {
+    let model = model.to_ascii_lowercase();
+    let model = model.rsplit('/').next().unwrap_or(&model);
+    let window = if model.starts_with("claude") || model.starts_with("opus") {
+        if model.contains("[1m]") || model.ends_with("-1m") {
+            1_000_000
+        } else {
+            200_000
+        }
+    } else if model.starts_with("gpt-5") {
+        400_000
+    } else if model.starts_with("gpt-4.1") || model.starts_with("gpt-4-1") {
+        1_047_576
+    } else if model.starts_with("gpt-4o") {
+        128_000
+    } else if model.starts_with("o3") || model.starts_with("o4") || model.starts_with("o1") {
+        200_000
+    } else if model.starts_with("gemini") {
+        1_048_576
+    } else {
+        return None;
+    };
+    Some(window)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -409,8 +571,8 @@ mod tests {
         assert!(template.is_default_preset);
         assert!(template.is_full_custom());
 
-        // 验证包含 1 个模型
-        assert_eq!(template.custom_models.len(), 1);
+        // 验证包含 14 个模型
+        assert_eq!(template.custom_models.len(), 14);
 
         // 验证 GPT-5.2 Codex 价格
         let gpt_5_2 = template.custom_models.get("gpt-5.2-codex").unwrap();
@@ -427,4 +589,54 @@ mod tests {
         assert!(gpt_5_2.aliases.contains(&"gpt-5.2".to_string()));
         assert!(gpt_5_2.aliases.contains(&"gpt-5-2-codex".to_string()));
     }
+
+    #[test]
+    fn test_builtin_openai_gemini_families() {
+        let openai = builtin_openai_official_template();
+        for model in [
+            "gpt-5",
+            "gpt-5-codex",
+            "o3",
+            "o4-mini",
+            "gpt-4.1",
+            "gpt-4.1-nano",
+        ] {
+            let price = openai.custom_models.get(model).unwrap();
+            assert_eq!(price.provider, "openai");
+            assert_eq!(price.cache_write_price_per_1m, None);
+            assert!(price.aliases.contains(&model.to_string()));
+        }
+        let gpt_5 = openai.custom_models.get("gpt-5").unwrap();
+        assert_eq!(gpt_5.input_price_per_1m, 1.25);
+        assert!(gpt_5.aliases.contains(&"gpt-5-2025-08-07".to_string()));
+
+        // 别名在整个模板内不能重复，否则匹配结果不确定
+        let mut seen = std::collections::HashSet::new();
+        for template in [openai, builtin_gemini_official_template()] {
+            for price in template.custom_models.values() {
+                for alias in &price.aliases {
+                    assert!(seen.insert(alias.clone()), "duplicate alias {alias}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_default_context_window() {
+        assert_eq!(
+            default_context_window("claude-sonnet-4-5-20250929"),
+            Some(200_000)
+        );
+        assert_eq!(
+            default_context_window("claude-sonnet-4-5[1m]"),
+            Some(1_000_000)
+        );
+        assert_eq!(
+            default_context_window("anthropic/claude-opus-4.1"),
+            Some(200_000)
+        );
+        assert_eq!(default_context_window("gpt-5-codex"), Some(400_000));
+        assert_eq!(default_context_window("gemini-2.5-pro"), Some(1_048_576));
+        assert_eq!(default_context_window("deepseek-chat"), None);
+    }
 }
@@ -0,0 +1,327 @@
+//! 别名冲突检测
+//!
+//! 多个模型（或多条继承配置）声明同一别名时，按以下规则确定匹配结果：
+//! 1. 模板内：模型名直接匹配 > 自定义模型别名 > 继承配置
+//! 2. 同一层级有多个候选时，`priority` 数值大者优先
+//! 3. 优先级相同且价格不同时视为歧义，计价返回错误
+//!
+//! 价格完全相同的重复别名不算冲突。
+
+use super::manager::PricingManager;
+use crate::models::pricing::{ModelPrice, PricingTemplate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// 候选来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AliasMatchSource {
+    /// 模板内自定义模型
+    Custom,
+    /// 继承配置
+    Inherited,
+}
+
+/// 别名匹配候选
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AliasCandidate {
+    pub model: String,
+    pub source: AliasMatchSource,
+    pub source_template_id: Option<String>,
+    pub priority: i32,
+    pub price: ModelPrice,
+}
+
+/// 候选判定结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AliasDecision {
+    NotFound,
+    /// 唯一候选（或多个候选价格完全相同）
+    Unique(usize),
+    /// 多个候选，按优先级选出
+    Priority(usize),
+    /// 最高优先级下仍有多个价格不同的候选
+    Ambiguous,
+}
+
+/// 单个别名的冲突
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AliasConflict {
+    pub template_id: String,
+    pub alias: String,
+    pub source: AliasMatchSource,
+    pub candidates: Vec<AliasCandidate>,
+    /// 按优先级选出的模型（歧义时为 None）
+    pub resolved_model: Option<String>,
+    pub ambiguous: bool,
+}
+
+fn same_price(a: &ModelPrice, b: &ModelPrice) -> bool {
+    a.input_price_per_1m == b.input_price_per_1m
+        && a.output_price_per_1m == b.output_price_per_1m
+        && a.cache_write_price_per_1m == b.cache_write_price_per_1m
+        && a.cache_write_1h_price_per_1m == b.cache_write_1h_price_per_1m
+        && a.cache_read_price_per_1m == b.cache_read_price_per_1m
+        && a.reasoning_output_price_per_1m == b.reasoning_output_price_per_1m
+}
+
+/// 在候选中确定匹配结果（候选需已按模型名排序，保证结果确定）
+pub(crate) fn decide(candidates: &[AliasCandidate]) -> AliasDecision {
+    let Some(top) = candidates.iter().map(|c| c.priority).max() else {
+        return AliasDecision::NotFound;
+    };
+    let first = candidates
+        .iter()
+        .position(|c| c.priority == top)
+        .unwrap_or_default();
+    let winner = &candidates[first].price;
+
+    if candidates.iter().all(|c| same_price(&c.price, winner)) {
+        return AliasDecision::Unique(first);
+    }
+    if candidates
+        .iter()
+        .filter(|c| c.priority == top)
+        .all(|c| same_price(&c.price, winner))
+    {
+        return AliasDecision::Priority(first);
+    }
+    AliasDecision::Ambiguous
+}
+
+impl PricingManager {
+    /// 收集别名匹配候选：模板内自定义模型优先，没有时再查继承配置
+    pub(crate) fn alias_candidates(
+        &self,
+        template: &PricingTemplate,
+        alias: &str,
+    ) -> Vec<AliasCandidate> {
+        self.alias_candidates_at(template, alias, 0)
+    }
+
+    /// 收集别名匹配候选（`depth` 为当前继承层级，用于终止循环继承）
+    pub(crate) fn alias_candidates_at(
+        &self,
+        template: &PricingTemplate,
+        alias: &str,
+        depth: usize,
+    ) -> Vec<AliasCandidate> {
+        let mut candidates: Vec<AliasCandidate> = template
+            .custom_models
+            .iter()
+            .filter(|(_, price)| price.aliases.iter().any(|a| a == alias))
+            .map(|(name, price)| AliasCandidate {
+                model: name.clone(),
+                source: AliasMatchSource::Custom,
+                source_template_id: None,
+                priority: price.priority,
+                price: price.clone(),
+            })
+            .collect();
+
+        if candidates.is_empty() {
+            for inherited in &template.inherited_models {
+                let Ok(source_template) = self.get_template(&inherited.source_template_id) else {
+                    continue;
+                };
+                let Ok(base_price) =
+                    self.resolve_model_price_at(&source_template, &inherited.model_name, depth + 1)
+                else {
+                    continue;
+                };
+                if inherited.model_name != alias && !base_price.aliases.iter().any(|a| a == alias) {
+                    continue;
+                }
+                let m = inherited.multiplier;
+                let tiers = base_price.tiers.iter().map(|t| t.scaled(m)).collect();
+                candidates.push(AliasCandidate {
+                    model: inherited.model_name.clone(),
+                    source: AliasMatchSource::Inherited,
+                    source_template_id: Some(inherited.source_template_id.clone()),
+                    priority: inherited.priority,
+                    price: ModelPrice {
+                        input_price_per_1m: base_price.input_price_per_1m * m,
+                        output_price_per_1m: base_price.output_price_per_1m * m,
+                        cache_write_price_per_1m: base_price
+                            .cache_write_price_per_1m
+                            .map(|p| p * m),
+                        cache_write_1h_price_per_1m: base_price
+                            .cache_write_1h_price_per_1m
+                            .map(|p| p * m),
+                        cache_read_price_per_1m: base_price.cache_read_price_per_1m.map(|p| p * m),
+                        reasoning_output_price_per_1m: base_price
+                            .reasoning_output_price_per_1m
+                            .map(|p| p * m),
+                        tiers,
+                        ..base_price
+                    },
+                });
+            }
+        }
+
+        candidates.sort_by(|a, b| {
+            (a.model.as_str(), a.source_template_id.as_deref())
+                .cmp(&(b.model.as_str(), b.source_template_id.as_deref()))
+        });
+        candidates
+    }
+
+    /// 检测单个模板中的别名冲突
+    pub fn detect_alias_conflicts(&self, template: &PricingTemplate) -> Vec<AliasConflict> {
+        let mut names = BTreeSet::new();
+        for price in template.custom_models.values() {
+            names.extend(price.aliases.iter().cloned());
+        }
+        for inherited in &template.inherited_models {
+            names.insert(inherited.model_name.clone());
+            if let Ok(source) = self.get_template(&inherited.source_template_id) {
+                if let Ok(price) = self.resolve_model_price(&source, &inherited.model_name) {
+                    names.extend(price.aliases);
+                }
+            }
+        }
+
+        let mut conflicts = Vec::new();
+        for alias in names {
+            // 模型名直接匹配优先，不会产生冲突
+            if template.custom_models.contains_key(&alias) {
+                continue;
+            }
+            let candidates = self.alias_candidates(template, &alias);
+            let (resolved_model, ambiguous) = match decide(&candidates) {
+                AliasDecision::NotFound | AliasDecision::Unique(_) => continue,
+                AliasDecision::Priority(i) => (Some(candidates[i].model.clone()), false),
+                AliasDecision::Ambiguous => (None, true),
+            };
+            conflicts.push(AliasConflict {
+                template_id: template.id.clone(),
+                alias,
+                source: candidates[0].source,
+                candidates,
+                resolved_model,
+                ambiguous,
+            });
+        }
+        conflicts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::DataManager;
+    use crate::models::pricing::InheritedModel;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    fn price(input: f64, aliases: &[&str]) -> ModelPrice {
+        ModelPrice::new(
+            "test".to_string(),
+            input,
+            input * 5.0,
+            None,
+            None,
+            None,
+            None,
+            aliases.iter().map(|a| a.to_string()).collect(),
+        )
+    }
+
+    fn template(id: &str, models: Vec<(&str, ModelPrice)>) -> PricingTemplate {
+        PricingTemplate::new(
+            id.to_string(),
+            id.to_string(),
+            String::new(),
+            "1.0".to_string(),
+            vec![],
+            models
+                .into_iter()
+                .map(|(name, price)| (name.to_string(), price))
+                .collect::<HashMap<_, _>>(),
+            vec![],
+            false,
+        )
+    }
+
+    #[test]
+    fn test_custom_alias_precedence() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = PricingManager::new_with_manager(
+            dir.path().to_path_buf(),
+            Arc::new(DataManager::new()),
+        );
+
+        let mut high = price(3.0, &["shared", "dup"]);
+        high.priority = 10;
+        let relay = template(
+            "relay",
+            vec![
+                ("model-a", price(1.0, &["ambiguous", "dup"])),
+                ("model-b", price(2.0, &["ambiguous", "same"])),
+                ("model-c", high),
+                ("model-d", price(1.0, &["shared"])),
+                ("model-e", price(2.0, &["same"])),
+            ],
+        );
+
+        // 优先级决定：model-c 胜出
+        let shared = manager.resolve_model_price(&relay, "shared").unwrap();
+        assert_eq!(shared.input_price_per_1m, 3.0);
+        // 价格相同的重复别名不算冲突
+        assert_eq!(
+            manager
+                .resolve_model_price(&relay, "same")
+                .unwrap()
+                .input_price_per_1m,
+            2.0
+        );
+        // 优先级相同且价格不同：报错
+        assert!(manager.resolve_model_price(&relay, "ambiguous").is_err());
+
+        let conflicts = manager.detect_alias_conflicts(&relay);
+        let summary: Vec<_> = conflicts
+            .iter()
+            .map(|c| (c.alias.as_str(), c.resolved_model.as_deref(), c.ambiguous))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("ambiguous", None, true),
+                ("dup", Some("model-c"), false),
+                ("shared", Some("model-c"), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_inherited_conflicts_use_priority() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = PricingManager::new_with_manager(
+            dir.path().to_path_buf(),
+            Arc::new(DataManager::new()),
+        );
+        manager
+            .save_template(&template("src_a", vec![("m", price(1.0, &["m-latest"]))]))
+            .unwrap();
+        manager
+            .save_template(&template("src_b", vec![("m", price(2.0, &["m-latest"]))]))
+            .unwrap();
+
+        let mut mixed = template("mixed", vec![]);
+        mixed.inherited_models = vec![
+            InheritedModel::new("m".to_string(), "src_a".to_string(), 1.0),
+            InheritedModel::new("m".to_string(), "src_b".to_string(), 1.0),
+        ];
+        manager.save_template(&mixed).unwrap();
+        assert!(manager.resolve_model_price(&mixed, "m-latest").is_err());
+        assert_eq!(manager.validate_template(&mixed).ambiguous, 2);
+
+        mixed.inherited_models[1].priority = 1;
+        manager.save_template(&mixed).unwrap();
+        let price = manager.resolve_model_price(&mixed, "m-latest").unwrap();
+        assert_eq!(price.input_price_per_1m, 2.0);
+        let validation = manager.validate_template(&mixed);
+        assert_eq!(validation.ambiguous, 0);
+        assert_eq!(validation.alias_conflicts.len(), 2);
+    }
+}
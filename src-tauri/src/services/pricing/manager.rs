@@ -4,13 +4,17 @@ use crate::services::pricing::builtin::{
     builtin_claude_official_template, builtin_gemini_official_template,
     builtin_openai_official_template,
 };
+use crate::services::pricing::conflicts::{decide, AliasDecision};
 use crate::services::pricing::remote_sync::RemoteSyncState;
+use crate::services::token_stats::budget::{period_start, BudgetPeriod};
+use crate::services::token_stats::TokenStatsManager;
 use crate::utils::precision::price_precision;
 use anyhow::{anyhow, Context, Result};
+use chrono::Local;
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 
 #[cfg(test)]
 use crate::models::pricing::InheritedModel;
@@ -38,6 +42,10 @@ pub struct CostBreakdown {
     #[serde(with = "price_precision")]
     pub reasoning_price: f64,
 
+    /// 按次附加费（USD）
+    #[serde(default, with = "price_precision")]
+    pub request_fee: f64,
+
     /// 总成本（USD）
     #[serde(with = "price_precision")]
     pub total_cost: f64,
@@ -53,6 +61,12 @@ lazy_static! {
     };
 }
 
+/// 继承解析的最大层级（防止循环继承导致无限递归）
+const MAX_INHERITANCE_DEPTH: usize = 16;
+
+/// 当月用量来源：按模型名（含别名）与起始时间（毫秒）返回累计 Token 数
+pub type MonthlyVolumeSource = Arc<dyn Fn(&[String], i64) -> Result<i64> + Send + Sync>;
+
 /// 价格管理服务
 pub struct PricingManager {
     /// DataManager 实例（Arc 包装以支持克隆）
@@ -67,6 +81,9 @@ pub struct PricingManager {
 
     /// 默认模板配置文件路径
     default_templates_path: PathBuf,
+
+    /// 阶梯计价使用的当月用量来源（未设置时始终使用基础价格）
+    volume_source: RwLock<Option<MonthlyVolumeSource>>,
 }
 
 impl PricingManager {
@@ -77,6 +94,9 @@ impl PricingManager {
 
         let manager = Self::new(base_dir)?;
         manager.initialize()?;
+        manager.set_volume_source(Arc::new(|models, since| {
+            TokenStatsManager::get().model_tokens_since(models, since)
+        }));
 
         Ok(manager)
     }
@@ -92,6 +112,14 @@ impl PricingManager {
             pricing_dir,
             templates_dir,
             default_templates_path,
+            volume_source: RwLock::new(None),
+        }
+    }
+
+    /// 设置阶梯计价使用的当月用量来源
+    pub fn set_volume_source(&self, source: MonthlyVolumeSource) {
+        if let Ok(mut guard) = self.volume_source.write() {
+            *guard = Some(source);
         }
     }
 
@@ -109,7 +137,7 @@ impl PricingManager {
         std::fs::create_dir_all(&self.templates_dir)
             .context("Failed to create templates directory")?;
 
-        // 仅在模板文件不存在时才写入内置默认值，避免覆盖远程同步的数据
+        // 模板文件不存在时写入内置默认值；已存在时只补充缺失的模型，避免覆盖远程同步的数据
         for (id, template) in [
             ("builtin_claude", builtin_claude_official_template()),
             ("builtin_openai", builtin_openai_official_template()),
@@ -118,6 +146,23 @@ impl PricingManager {
             let path = self.templates_dir.join(format!("{}.json", id));
             if !path.exists() {
                 self.save_template(&template)?;
+                continue;
+            }
+            if let Ok(mut existing) = self.get_template(id) {
+                let missing: Vec<_> = template
+                    .custom_models
+                    .into_iter()
+                    .filter(|(name, _)| !existing.custom_models.contains_key(name))
+                    .collect();
+                if !missing.is_empty() {
+                    tracing::info!(
+                        template = id,
+                        count = missing.len(),
+                        "补充内置模板缺失的模型"
+                    );
+                    existing.custom_models.extend(missing);
+                    self.save_template(&existing)?;
+                }
             }
         }
 
@@ -328,101 +373,137 @@ impl PricingManager {
         // 2. 解析模型价格（别名 → 继承 → 倍率）
         let model_price = self.resolve_model_price(&template, model)?;
 
+        // 阶梯价格：按本月（本次请求之前）的累计用量选择价格档
+        let model_price = self.apply_volume_tier(model, model_price);
+
         // 3. 计算各部分价格
-        let input_price = input_tokens as f64 * model_price.input_price_per_1m / 1_000_000.0;
-        let output_price = output_tokens as f64 * model_price.output_price_per_1m / 1_000_000.0;
-
-        // 缓存写入分别计价：5m 和 1h 使用不同价格
-        let cache_5m_tokens = cache_creation_tokens - cache_creation_1h_tokens;
-        let cache_write_5m_price = cache_5m_tokens as f64
-            * model_price.cache_write_price_per_1m.unwrap_or(0.0)
-            / 1_000_000.0;
-        let cache_write_1h_price = cache_creation_1h_tokens as f64
-            * model_price
-                .cache_write_1h_price_per_1m
-                .or(model_price.cache_write_price_per_1m) // 无 1h 价格时回退到 5m
-                .unwrap_or(0.0)
-            / 1_000_000.0;
-        let cache_write_price = cache_write_5m_price + cache_write_1h_price;
-
-        let cache_read_price = cache_read_tokens as f64
-            * model_price.cache_read_price_per_1m.unwrap_or(0.0)
-            / 1_000_000.0;
-
-        // 计算推理 Token 价格（如果有专用价格则使用，否则使用普通输出价格）
-        let reasoning_price =
-            if let Some(reasoning_price_per_1m) = model_price.reasoning_output_price_per_1m {
-                reasoning_tokens as f64 * reasoning_price_per_1m / 1_000_000.0
-            } else {
-                // 回退：使用普通输出价格
-                reasoning_tokens as f64 * model_price.output_price_per_1m / 1_000_000.0
-            };
-
-        // 4. 计算总成本
-        let total_cost =
-            input_price + output_price + cache_write_price + cache_read_price + reasoning_price;
-
-        Ok(CostBreakdown {
-            input_price,
-            output_price,
-            cache_write_price,
-            cache_read_price,
-            reasoning_price,
-            total_cost,
-            template_id: template.id.clone(),
-        })
+        Ok(cost_breakdown(
+            &template,
+            &model_price,
+            input_tokens,
+            output_tokens,
+            cache_creation_tokens,
+            cache_creation_1h_tokens,
+            cache_read_tokens,
+            reasoning_tokens,
+        ))
+    }
+
+    /// 模型的上下文窗口（优先取价格模板中的设置，其次按内置模型族默认值）
+    pub fn context_window(
+        &self,
+        template_id: Option<&str>,
+        tool_id: Option<&str>,
+        model: &str,
+    ) -> Option<i64> {
+        let template = match template_id {
+            Some(id) => self.get_template(id).ok(),
+            None => self
+                .get_default_template(tool_id.unwrap_or("claude-code"))
+                .ok(),
+        };
+        template
+            .and_then(|template| self.resolve_model_price(&template, model).ok())
+            .and_then(|price| price.context_window)
+            .filter(|window| *window > 0)
+            .or_else(|| super::builtin::default_context_window(model))
+    }
+
+    /// 按当月累计用量应用阶梯价格（无阶梯或用量未知时返回原价格）
+    fn apply_volume_tier(&self, model: &str, price: ModelPrice) -> ModelPrice {
+        if price.tiers.is_empty() {
+            return price;
+        }
+        let Some(source) = self.volume_source.read().ok().and_then(|g| g.clone()) else {
+            return price;
+        };
+
+        let mut models = vec![model.to_string()];
+        models.extend(price.aliases.iter().filter(|a| *a != model).cloned());
+        let since = period_start(BudgetPeriod::Monthly, Local::now());
+        let volume = match source(&models, since) {
+            Ok(volume) => volume,
+            Err(e) => {
+                tracing::warn!(model = model, error = %e, "读取当月用量失败，按基础价格计价");
+                return price;
+            }
+        };
+
+        match price.tier_for(volume) {
+            Some(tier) => {
+                tracing::debug!(
+                    model = model,
+                    monthly_tokens = volume,
+                    threshold = tier.threshold_tokens,
+                    "应用阶梯价格"
+                );
+                price.with_tier(tier)
+            }
+            None => price,
+        }
     }
 
     /// 解析模型价格（支持别名、继承、倍率）
-    fn resolve_model_price(&self, template: &PricingTemplate, model: &str) -> Result<ModelPrice> {
+    ///
+    /// 匹配顺序：模型名直接匹配 → 模板内别名 → 继承配置；同层级多个候选时按
+    /// `priority` 决定，仍无法确定时返回错误（见 [`super::conflicts`]）。
+    pub(crate) fn resolve_model_price(
+        &self,
+        template: &PricingTemplate,
+        model: &str,
+    ) -> Result<ModelPrice> {
+        self.resolve_model_price_at(template, model, 0)
+    }
+
+    /// 解析模型价格（`depth` 为当前继承层级，超过上限视为循环继承）
+    pub(crate) fn resolve_model_price_at(
+        &self,
+        template: &PricingTemplate,
+        model: &str,
+        depth: usize,
+    ) -> Result<ModelPrice> {
+        if depth > MAX_INHERITANCE_DEPTH {
+            return Err(anyhow!(
+                "Inheritance of model {} in template {} is too deep (circular inheritance?)",
+                model,
+                template.id
+            ));
+        }
+
         // 1. 优先查找自定义模型（直接匹配）
         if let Some(price) = template.custom_models.get(model) {
             return Ok(price.clone());
         }
 
-        // 2. 别名匹配自定义模型
-        for price in template.custom_models.values() {
-            if price.aliases.contains(&model.to_string()) {
-                return Ok(price.clone());
+        // 2. 别名匹配自定义模型，其次查找继承配置（支持别名匹配，应用倍率）
+        let candidates = self.alias_candidates_at(template, model, depth);
+        match decide(&candidates) {
+            AliasDecision::Unique(i) => return Ok(candidates[i].price.clone()),
+            AliasDecision::Priority(i) => {
+                tracing::debug!(
+                    template_id = %template.id,
+                    model = model,
+                    resolved = %candidates[i].model,
+                    "别名匹配到多个模型，按优先级选择"
+                );
+                return Ok(candidates[i].price.clone());
             }
-        }
-
-        // 3. 查找继承配置（支持别名匹配）
-        for inherited in &template.inherited_models {
-            // 加载源模板并获取基础价格（包括别名信息）
-            if let Ok(source_template) = self.get_template(&inherited.source_template_id) {
-                if let Ok(base_price) =
-                    self.resolve_model_price(&source_template, &inherited.model_name)
-                {
-                    // 检查请求的模型名是否匹配模型名或别名
-                    if inherited.model_name == model
-                        || base_price.aliases.contains(&model.to_string())
-                    {
-                        // 应用倍率
-                        return Ok(ModelPrice {
-                            provider: base_price.provider,
-                            input_price_per_1m: base_price.input_price_per_1m
-                                * inherited.multiplier,
-                            output_price_per_1m: base_price.output_price_per_1m
-                                * inherited.multiplier,
-                            cache_write_price_per_1m: base_price
-                                .cache_write_price_per_1m
-                                .map(|p| p * inherited.multiplier),
-                            cache_write_1h_price_per_1m: base_price
-                                .cache_write_1h_price_per_1m
-                                .map(|p| p * inherited.multiplier),
-                            cache_read_price_per_1m: base_price
-                                .cache_read_price_per_1m
-                                .map(|p| p * inherited.multiplier),
-                            reasoning_output_price_per_1m: base_price
-                                .reasoning_output_price_per_1m
-                                .map(|p| p * inherited.multiplier),
-                            currency: base_price.currency,
-                            aliases: base_price.aliases,
-                        });
-                    }
-                }
+            AliasDecision::Ambiguous => {
+                let names: Vec<&str> = candidates.iter().map(|c| c.model.as_str()).collect();
+                tracing::warn!(
+                    template_id = %template.id,
+                    model = model,
+                    candidates = ?names,
+                    "别名匹配到多个价格不同且优先级相同的模型，无法计价"
+                );
+                return Err(anyhow!(
+                    "Model {} is ambiguous in template {}: matches {}",
+                    model,
+                    template.id,
+                    names.join(", ")
+                ));
             }
+            AliasDecision::NotFound => {}
         }
 
         Err(anyhow!(
@@ -433,6 +514,75 @@ impl PricingManager {
     }
 }
 
+/// 按模型价格计算成本分解
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn cost_breakdown(
+    template: &PricingTemplate,
+    model_price: &ModelPrice,
+    input_tokens: i64,
+    output_tokens: i64,
+    cache_creation_tokens: i64,
+    cache_creation_1h_tokens: i64,
+    cache_read_tokens: i64,
+    reasoning_tokens: i64,
+) -> CostBreakdown {
+    let input_price = input_tokens as f64 * model_price.input_price_per_1m / 1_000_000.0;
+    let output_price = output_tokens as f64 * model_price.output_price_per_1m / 1_000_000.0;
+
+    // 缓存写入分别计价：5m 和 1h 使用不同价格
+    let cache_5m_tokens = cache_creation_tokens - cache_creation_1h_tokens;
+    let cache_write_5m_price =
+        cache_5m_tokens as f64 * model_price.cache_write_price_per_1m.unwrap_or(0.0) / 1_000_000.0;
+    let cache_write_1h_price = cache_creation_1h_tokens as f64
+        * model_price
+            .cache_write_1h_price_per_1m
+            .or(model_price.cache_write_price_per_1m) // 无 1h 价格时回退到 5m
+            .unwrap_or(0.0)
+        / 1_000_000.0;
+    let cache_write_price = cache_write_5m_price + cache_write_1h_price;
+
+    let cache_read_price =
+        cache_read_tokens as f64 * model_price.cache_read_price_per_1m.unwrap_or(0.0) / 1_000_000.0;
+
+    // 计算推理 Token 价格（如果有专用价格则使用，否则使用普通输出价格）
+    let reasoning_price =
+        if let Some(reasoning_price_per_1m) = model_price.reasoning_output_price_per_1m {
+            reasoning_tokens as f64 * reasoning_price_per_1m / 1_000_000.0
+        } else {
+            // 回退：使用普通输出价格
+            reasoning_tokens as f64 * model_price.output_price_per_1m / 1_000_000.0
+        };
+
+    // 按次附加费：仅对产生用量的请求收取
+    let has_usage =
+        input_tokens + output_tokens + cache_creation_tokens + cache_read_tokens + reasoning_tokens
+            > 0;
+    let request_fee = if has_usage {
+        template.per_request_fee
+    } else {
+        0.0
+    };
+
+    // 总成本
+    let total_cost = input_price
+        + output_price
+        + cache_write_price
+        + cache_read_price
+        + reasoning_price
+        + request_fee;
+
+    CostBreakdown {
+        input_price,
+        output_price,
+        cache_write_price,
+        cache_read_price,
+        reasoning_price,
+        request_fee,
+        total_cost,
+        template_id: template.id.clone(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -461,6 +611,47 @@ mod tests {
         assert!(template.is_default_preset);
     }
 
+    #[test]
+    fn test_initialize_backfills_builtin_models() {
+        let (manager, _dir) = create_test_manager();
+
+        // 模拟旧版本写入的 OpenAI 模板：仅包含一个模型且价格已被远程同步修改
+        let mut template = manager.get_template("builtin_openai").unwrap();
+        template
+            .custom_models
+            .retain(|name, _| name == "gpt-5.2-codex");
+        template
+            .custom_models
+            .get_mut("gpt-5.2-codex")
+            .unwrap()
+            .input_price_per_1m = 2.5;
+        manager.save_template(&template).unwrap();
+
+        manager.initialize().unwrap();
+        let template = manager.get_template("builtin_openai").unwrap();
+        assert_eq!(
+            template.custom_models["gpt-5.2-codex"].input_price_per_1m,
+            2.5
+        );
+
+        // codex 默认模板能解析 GPT-5 / o 系列的带日期别名
+        let default = manager.get_default_template("codex").unwrap();
+        assert_eq!(default.id, "builtin_openai");
+        let price = manager
+            .resolve_model_price(&default, "o4-mini-2025-04-16")
+            .unwrap();
+        assert_eq!(price.input_price_per_1m, 1.1);
+        let price = manager
+            .resolve_model_price(&default, "gpt-5-codex")
+            .unwrap();
+        assert_eq!(price.output_price_per_1m, 10.0);
+
+        let gemini = manager.get_default_template("gemini-cli").unwrap();
+        assert!(manager
+            .resolve_model_price(&gemini, "gemini-2.5-flash-lite")
+            .is_ok());
+    }
+
     #[test]
     fn test_resolve_model_price_with_alias() {
         let (manager, _dir) = create_test_manager();
@@ -526,6 +717,155 @@ mod tests {
         assert_eq!(breakdown.template_id, "builtin_claude");
     }
 
+    #[test]
+    fn test_calculate_cost_with_volume_tiers() {
+        use crate::models::pricing::PriceTier;
+        use std::sync::atomic::{AtomicI64, Ordering};
+
+        let (manager, _dir) = create_test_manager();
+
+        let mut price = ModelPrice::new(
+            "relay".to_string(),
+            4.0,
+            20.0,
+            None,
+            None,
+            Some(0.4),
+            None,
+            vec!["relay-model-latest".to_string()],
+        );
+        price.tiers = vec![
+            PriceTier {
+                threshold_tokens: 10_000_000,
+                input_price_per_1m: 2.0,
+                output_price_per_1m: 10.0,
+                cache_write_price_per_1m: None,
+                cache_write_1h_price_per_1m: None,
+                cache_read_price_per_1m: None,
+                reasoning_output_price_per_1m: None,
+            },
+            PriceTier {
+                threshold_tokens: 1_000_000,
+                input_price_per_1m: 3.0,
+                output_price_per_1m: 15.0,
+                cache_write_price_per_1m: None,
+                cache_write_1h_price_per_1m: None,
+                cache_read_price_per_1m: Some(0.3),
+                reasoning_output_price_per_1m: None,
+            },
+        ];
+        let mut models = std::collections::HashMap::new();
+        models.insert("relay-model".to_string(), price);
+        let template = PricingTemplate::new(
+            "test_tiers".to_string(),
+            "Tiers".to_string(),
+            "Test".to_string(),
+            "1.0".to_string(),
+            vec![],
+            models,
+            vec![],
+            false,
+        );
+        manager.save_template(&template).unwrap();
+
+        let volume = Arc::new(AtomicI64::new(0));
+        let source_volume = volume.clone();
+        manager.set_volume_source(Arc::new(move |models, _since| {
+            // 用量按模型名及其别名统计
+            assert_eq!(models, ["relay-model", "relay-model-latest"]);
+            Ok(source_volume.load(Ordering::SeqCst))
+        }));
+
+        let cost = |manager: &PricingManager| {
+            manager
+                .calculate_cost(
+                    Some("test_tiers"),
+                    None,
+                    "relay-model",
+                    1_000_000,
+                    1_000_000,
+                    0,
+                    0,
+                    1_000_000,
+                    0,
+                )
+                .unwrap()
+        };
+
+        // 未达到任何阈值：基础价格
+        assert!((cost(&manager).total_cost - 24.4).abs() < 1e-9);
+
+        // 达到第一档：缓存读取使用档位价格
+        volume.store(5_000_000, Ordering::SeqCst);
+        assert!((cost(&manager).total_cost - 18.3).abs() < 1e-9);
+
+        // 达到最高档：未设置的缓存读取价格沿用基础价格
+        volume.store(10_000_000, Ordering::SeqCst);
+        assert!((cost(&manager).total_cost - 12.4).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_calculate_cost_with_request_fee() {
+        let (manager, _dir) = create_test_manager();
+
+        let mut template = PricingTemplate::new(
+            "test_request_fee".to_string(),
+            "Relay".to_string(),
+            "Test".to_string(),
+            "1.0".to_string(),
+            vec![InheritedModel::new(
+                "claude-sonnet-4.5".to_string(),
+                "builtin_claude".to_string(),
+                1.0,
+            )],
+            Default::default(),
+            vec![],
+            false,
+        );
+        template.per_request_fee = 0.002;
+        template.min_monthly_fee = 10.0;
+        manager.save_template(&template).unwrap();
+
+        let breakdown = manager
+            .calculate_cost(
+                Some("test_request_fee"),
+                None,
+                "claude-sonnet-4.5",
+                1000,
+                0,
+                0,
+                0,
+                0,
+                0,
+            )
+            .unwrap();
+        assert_eq!(breakdown.request_fee, 0.002);
+        assert_eq!(breakdown.total_cost, 0.003 + 0.002);
+
+        // 无用量的请求不收附加费
+        let empty = manager
+            .calculate_cost(
+                Some("test_request_fee"),
+                None,
+                "claude-sonnet-4.5",
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+            )
+            .unwrap();
+        assert_eq!(empty.total_cost, 0.0);
+
+        // 旧模板文件缺少新字段时按 0 处理
+        let mut value = serde_json::to_value(&template).unwrap();
+        value.as_object_mut().unwrap().remove("per_request_fee");
+        value.as_object_mut().unwrap().remove("min_monthly_fee");
+        let legacy: PricingTemplate = serde_json::from_value(value).unwrap();
+        assert_eq!((legacy.per_request_fee, legacy.min_monthly_fee), (0.0, 0.0));
+    }
+
     #[test]
     fn test_multi_source_inheritance() {
         let (manager, _dir) = create_test_manager();
@@ -1,7 +1,14 @@
 pub mod builtin;
+pub mod conflicts;
 pub mod manager;
 pub mod remote_sync;
+pub mod validation;
 
 pub use builtin::*;
+pub use conflicts::{AliasCandidate, AliasConflict, AliasMatchSource};
 pub use manager::*;
 pub use remote_sync::*;
+pub use validation::{
+    CostScenario, CostSimulation, IssueSeverity, PricingTemplateValidation, TemplateIssue,
+    TemplateIssueKind,
+};
@@ -1,20 +1,77 @@
+//! 价格目录远程同步
+//!
+//! 下载公开的模型价格目录（LiteLLM `model_prices_and_context_window.json` 格式），
+//! 按提供商转换后合并到内置价格模板：
+//! - 目录中的新模型追加到模板
+//! - 已有模型（按名称或别名匹配）只更新价格，保留本地的别名与优先级
+//! - 目录中没有的本地模型保持不变
+
 use crate::http_client::build_client;
+use crate::models::config::PricingSyncConfig;
 use crate::models::pricing::{ModelPrice, PricingTemplate};
-use crate::services::pricing::PRICING_MANAGER;
+use crate::services::pricing::{PricingManager, PRICING_MANAGER};
+use crate::utils::config::read_global_config;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-const REMOTE_URL: &str = "https://raw.githubusercontent.com/Wei-Shaw/claude-relay-service/price-mirror/model_prices_and_context_window.json";
+/// 默认价格目录地址（LiteLLM 价格数据镜像）
+pub const DEFAULT_CATALOG_URL: &str = "https://raw.githubusercontent.com/Wei-Shaw/claude-relay-service/price-mirror/model_prices_and_context_window.json";
+
+/// 目录提供商与内置模板的对应关系
+const PROVIDER_TEMPLATES: [(&str, &str); 3] = [
+    ("anthropic", "builtin_claude"),
+    ("openai", "builtin_openai"),
+    ("gemini", "builtin_gemini"),
+];
+
+/// 价格比较容差（USD/百万 Token）
+const PRICE_EPSILON: f64 = 1e-9;
 
 /// 远程同步状态（持久化到 remote_sync_state.json）
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct RemoteSyncState {
+    /// 上次同步的目录地址（地址变化后不复用 ETag；旧状态文件没有该字段，视为默认地址）
+    #[serde(default)]
+    pub url: Option<String>,
     pub etag: Option<String>,
     pub last_modified: Option<String>,
     pub last_success_at: Option<i64>,
 }
 
+/// 单个价格模板的同步结果
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct TemplateSyncSummary {
+    pub template_id: String,
+    /// 新增的模型
+    pub added: Vec<String>,
+    /// 价格有变动的模型
+    pub updated: Vec<String>,
+    /// 价格未变化的模型数
+    pub unchanged: usize,
+}
+
+impl TemplateSyncSummary {
+    /// 模板是否有变化
+    pub fn changed(&self) -> bool {
+        !self.added.is_empty() || !self.updated.is_empty()
+    }
+}
+
+/// 价格目录同步结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct CatalogSyncReport {
+    /// 价格目录地址
+    pub url: String,
+    /// 目录未变化（HTTP 304），未修改任何模板
+    pub not_modified: bool,
+    pub templates: Vec<TemplateSyncSummary>,
+    /// 同步时间（Unix 时间戳，毫秒）
+    pub synced_at: i64,
+}
+
 /// 远程模型定价数据（宽松解析，所有字段可选）
 #[derive(Debug, Deserialize)]
 struct RemoteModelData {
@@ -24,55 +81,115 @@ struct RemoteModelData {
     cache_creation_input_token_cost: Option<f64>,
     cache_read_input_token_cost: Option<f64>,
     reasoning_cost_per_token: Option<f64>,
+    max_input_tokens: Option<f64>,
     mode: Option<String>,
 }
 
-/// 从远程同步最新模型定价数据并更新本地内置模板
-///
-/// 返回 Ok(true) 表示有更新，Ok(false) 表示无需更新（304）
-pub async fn sync_remote_prices() -> Result<bool> {
-    let client = build_client().map_err(|e| anyhow::anyhow!(e))?;
+impl PricingManager {
+    /// 从远程价格目录同步模型定价
+    ///
+    /// 同一地址使用 ETag 条件请求，目录未变化时不做任何修改
+    pub async fn sync_remote_catalog(&self, url: &str) -> Result<CatalogSyncReport> {
+        let client = build_client().map_err(|e| anyhow::anyhow!(e))?;
 
-    let state = PRICING_MANAGER.load_sync_state().unwrap_or_default();
+        let state = self.load_sync_state().unwrap_or_default();
+        let same_url = state.url.as_deref().unwrap_or(DEFAULT_CATALOG_URL) == url;
 
-    let mut request = client.get(REMOTE_URL);
-    if let Some(etag) = &state.etag {
-        request = request.header("If-None-Match", etag);
-    }
+        let mut request = client.get(url);
+        if let Some(etag) = state.etag.as_ref().filter(|_| same_url) {
+            request = request.header("If-None-Match", etag);
+        }
 
-    let response = request.send().await.context("远程价格同步请求失败")?;
+        let response = request.send().await.context("远程价格同步请求失败")?;
+        let now = chrono::Utc::now().timestamp_millis();
+
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            tracing::info!("远程价格数据未变化 (304)，跳过同步");
+            return Ok(CatalogSyncReport {
+                url: url.to_string(),
+                not_modified: true,
+                templates: vec![],
+                synced_at: now,
+            });
+        }
 
-    if response.status() == reqwest::StatusCode::NOT_MODIFIED {
-        tracing::info!("远程价格数据未变化 (304)，跳过同步");
-        return Ok(false);
-    }
+        if !response.status().is_success() {
+            anyhow::bail!("远程价格同步失败，HTTP 状态码: {}", response.status());
+        }
+
+        let header = |name: &str| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(|s| s.to_string())
+        };
+        let new_etag = header("etag");
+        let new_last_modified = header("last-modified");
+
+        let body = response.text().await.context("读取远程价格数据失败")?;
+        let templates = self.apply_catalog(&body)?;
 
-    if !response.status().is_success() {
-        anyhow::bail!("远程价格同步失败，HTTP 状态码: {}", response.status());
+        let new_state = RemoteSyncState {
+            url: Some(url.to_string()),
+            etag: new_etag,
+            last_modified: new_last_modified,
+            last_success_at: Some(now),
+        };
+        if let Err(e) = self.save_sync_state(&new_state) {
+            tracing::warn!("保存远程同步状态失败: {}", e);
+        }
+
+        Ok(CatalogSyncReport {
+            url: url.to_string(),
+            not_modified: false,
+            templates,
+            synced_at: now,
+        })
     }
 
-    let new_etag = response
-        .headers()
-        .get("etag")
-        .and_then(|v| v.to_str().ok())
-        .map(|s| s.to_string());
+    /// 将价格目录（LiteLLM JSON）合并到内置价格模板
+    pub fn apply_catalog(&self, catalog_json: &str) -> Result<Vec<TemplateSyncSummary>> {
+        let all_models: HashMap<String, RemoteModelData> =
+            serde_json::from_str(catalog_json).context("解析远程价格 JSON 失败")?;
+        let grouped = group_by_provider(&all_models);
+        if grouped.is_empty() {
+            anyhow::bail!("价格目录中没有可识别的 Claude / OpenAI / Gemini 模型");
+        }
 
-    let new_last_modified = response
-        .headers()
-        .get("last-modified")
-        .and_then(|v| v.to_str().ok())
-        .map(|s| s.to_string());
+        let mut summaries = Vec::new();
+        for (provider, template_id) in PROVIDER_TEMPLATES {
+            let Some(models) = grouped.get(provider) else {
+                continue;
+            };
+            let existing = self.get_template(template_id).ok();
+            let is_new = existing.is_none();
+            let (template, summary) = merge_catalog(provider, models, existing);
+            if is_new || summary.changed() {
+                self.save_template(&template)
+                    .with_context(|| format!("保存价格模板 {} 失败", template_id))?;
+            }
+            tracing::info!(
+                template = template_id,
+                added = summary.added.len(),
+                updated = summary.updated.len(),
+                unchanged = summary.unchanged,
+                "同步价格目录"
+            );
+            summaries.push(summary);
+        }
 
-    let body = response.text().await.context("读取远程价格数据失败")?;
-    let all_models: HashMap<String, RemoteModelData> =
-        serde_json::from_str(&body).context("解析远程价格 JSON 失败")?;
+        Ok(summaries)
+    }
+}
 
-    // 按 provider 分组过滤
-    let mut anthropic_models: HashMap<String, &RemoteModelData> = HashMap::new();
-    let mut openai_models: HashMap<String, &RemoteModelData> = HashMap::new();
-    let mut gemini_models: HashMap<String, &RemoteModelData> = HashMap::new();
+/// 按提供商筛选目录中的对话模型
+fn group_by_provider(
+    all_models: &HashMap<String, RemoteModelData>,
+) -> HashMap<&'static str, HashMap<String, &RemoteModelData>> {
+    let mut grouped: HashMap<&'static str, HashMap<String, &RemoteModelData>> = HashMap::new();
 
-    for (key, data) in &all_models {
+    for (key, data) in all_models {
         // 过滤掉包含 `/` 的第三方平台条目
         if key.contains('/') {
             continue;
@@ -92,77 +209,144 @@ pub async fn sync_remote_prices() -> Result<bool> {
             continue;
         }
 
-        match data.litellm_provider.as_deref() {
-            Some("anthropic") => {
-                anthropic_models.insert(key.clone(), data);
-            }
-            Some("openai") => {
-                openai_models.insert(key.clone(), data);
-            }
+        let provider = match data.litellm_provider.as_deref() {
+            Some("anthropic") => "anthropic",
+            Some("openai") => "openai",
             // Gemini 模型的 litellm_provider 以 "vertex_ai" 开头，且 key 以 "gemini-" 开头
-            Some(p) if p.starts_with("vertex_ai") && key.starts_with("gemini-") => {
-                gemini_models.insert(key.clone(), data);
-            }
-            _ => {}
-        }
+            Some(p) if p.starts_with("vertex_ai") && key.starts_with("gemini-") => "gemini",
+            _ => continue,
+        };
+        grouped
+            .entry(provider)
+            .or_default()
+            .insert(key.clone(), data);
     }
 
-    let mut updated_count = 0;
-
-    // 生成并保存 Anthropic 模板
-    if !anthropic_models.is_empty() {
-        let existing = PRICING_MANAGER.get_template("builtin_claude").ok();
-        let template =
-            build_template_from_remote("anthropic", &anthropic_models, existing.as_ref());
-        PRICING_MANAGER
-            .save_template(&template)
-            .context("保存远程 Anthropic 价格模板失败")?;
-        updated_count += anthropic_models.len();
-        tracing::info!("同步 Anthropic 模型定价：{} 个模型", anthropic_models.len());
+    grouped
+}
+
+/// 将目录中的模型合并到模板（模板不存在时新建）
+fn merge_catalog(
+    provider: &str,
+    models: &HashMap<String, &RemoteModelData>,
+    existing: Option<PricingTemplate>,
+) -> (PricingTemplate, TemplateSyncSummary) {
+    let now = chrono::Utc::now().timestamp_millis();
+    let mut template = existing.unwrap_or_else(|| builtin_template_shell(provider, now));
+    let mut summary = TemplateSyncSummary {
+        template_id: template.id.clone(),
+        ..Default::default()
+    };
+
+    // 固定顺序处理，保证同一模型的多个目录条目结果稳定
+    let mut keys: Vec<&String> = models.keys().collect();
+    keys.sort();
+
+    for key in keys {
+        let remote = model_price_from_remote(provider, key, models[key]);
+        match find_local_model(&template, key) {
+            Some(name) => {
+                let local = template
+                    .custom_models
+                    .get_mut(&name)
+                    .expect("matched model exists");
+                let window_changed = remote
+                    .context_window
+                    .is_some_and(|window| local.context_window != Some(window));
+                if same_prices(local, &remote) && !window_changed {
+                    summary.unchanged += 1;
+                } else {
+                    local.input_price_per_1m = remote.input_price_per_1m;
+                    local.output_price_per_1m = remote.output_price_per_1m;
+                    local.cache_write_price_per_1m = remote.cache_write_price_per_1m;
+                    local.cache_write_1h_price_per_1m = remote.cache_write_1h_price_per_1m;
+                    local.cache_read_price_per_1m = remote.cache_read_price_per_1m;
+                    local.reasoning_output_price_per_1m = remote.reasoning_output_price_per_1m;
+                    local.context_window = remote.context_window.or(local.context_window);
+                    if !summary.updated.contains(&name) {
+                        summary.updated.push(name);
+                    }
+                }
+            }
+            None => {
+                template.custom_models.insert(key.clone(), remote);
+                summary.added.push(key.clone());
+            }
+        }
     }
 
-    // 生成并保存 OpenAI 模板
-    if !openai_models.is_empty() {
-        let existing = PRICING_MANAGER.get_template("builtin_openai").ok();
-        let template = build_template_from_remote("openai", &openai_models, existing.as_ref());
-        PRICING_MANAGER
-            .save_template(&template)
-            .context("保存远程 OpenAI 价格模板失败")?;
-        updated_count += openai_models.len();
-        tracing::info!("同步 OpenAI 模型定价：{} 个模型", openai_models.len());
+    if summary.changed() {
+        template.updated_at = now;
     }
+    (template, summary)
+}
 
-    // 生成并保存 Gemini 模板
-    if !gemini_models.is_empty() {
-        let existing = PRICING_MANAGER.get_template("builtin_gemini").ok();
-        let template = build_template_from_remote("gemini", &gemini_models, existing.as_ref());
-        PRICING_MANAGER
-            .save_template(&template)
-            .context("保存远程 Gemini 价格模板失败")?;
-        updated_count += gemini_models.len();
-        tracing::info!("同步 Gemini 模型定价：{} 个模型", gemini_models.len());
+/// 查找目录条目对应的本地模型（先按名称，再按别名）
+fn find_local_model(template: &PricingTemplate, key: &str) -> Option<String> {
+    if template.custom_models.contains_key(key) {
+        return Some(key.to_string());
     }
+    let mut matches: Vec<&String> = template
+        .custom_models
+        .iter()
+        .filter(|(_, price)| price.aliases.iter().any(|a| a == key))
+        .map(|(name, _)| name)
+        .collect();
+    matches.sort();
+    matches.first().map(|name| name.to_string())
+}
 
-    // 更新同步状态
-    let new_state = RemoteSyncState {
-        etag: new_etag,
-        last_modified: new_last_modified,
-        last_success_at: Some(chrono::Utc::now().timestamp_millis()),
+/// 两个定价的各项价格是否一致
+fn same_prices(a: &ModelPrice, b: &ModelPrice) -> bool {
+    let eq = |x: f64, y: f64| (x - y).abs() < PRICE_EPSILON;
+    let eq_opt = |x: Option<f64>, y: Option<f64>| match (x, y) {
+        (Some(x), Some(y)) => eq(x, y),
+        (None, None) => true,
+        _ => false,
     };
-    if let Err(e) = PRICING_MANAGER.save_sync_state(&new_state) {
-        tracing::warn!("保存远程同步状态失败: {}", e);
-    }
+    eq(a.input_price_per_1m, b.input_price_per_1m)
+        && eq(a.output_price_per_1m, b.output_price_per_1m)
+        && eq_opt(a.cache_write_price_per_1m, b.cache_write_price_per_1m)
+        && eq_opt(a.cache_write_1h_price_per_1m, b.cache_write_1h_price_per_1m)
+        && eq_opt(a.cache_read_price_per_1m, b.cache_read_price_per_1m)
+        && eq_opt(
+            a.reasoning_output_price_per_1m,
+            b.reasoning_output_price_per_1m,
+        )
+}
 
-    tracing::info!("远程价格同步完成，共更新 {} 个模型", updated_count);
-    Ok(true)
+/// 将目录条目转换为模型定价（USD/Token → USD/百万 Token）
+fn model_price_from_remote(provider: &str, key: &str, data: &RemoteModelData) -> ModelPrice {
+    let input_per_1m = data.input_cost_per_token.unwrap_or(0.0) * 1_000_000.0;
+    let output_per_1m = data.output_cost_per_token.unwrap_or(0.0) * 1_000_000.0;
+    let cache_write = data
+        .cache_creation_input_token_cost
+        .map(|v| v * 1_000_000.0);
+    // Anthropic 1h 缓存写入价格 = input * 2.0（远程数据仅提供 5m 价格）
+    let cache_write_1h = if provider == "anthropic" {
+        data.input_cost_per_token.map(|v| v * 2.0 * 1_000_000.0)
+    } else {
+        None
+    };
+    let cache_read = data.cache_read_input_token_cost.map(|v| v * 1_000_000.0);
+    let reasoning = data.reasoning_cost_per_token.map(|v| v * 1_000_000.0);
+
+    let mut price = ModelPrice::new(
+        provider.to_string(),
+        input_per_1m,
+        output_per_1m,
+        cache_write,
+        cache_write_1h,
+        cache_read,
+        reasoning,
+        generate_aliases(key),
+    );
+    price.context_window = data.max_input_tokens.filter(|v| *v > 0.0).map(|v| v as i64);
+    price
 }
 
-/// 从远程数据构建内置价格模板
-fn build_template_from_remote(
-    provider: &str,
-    models: &HashMap<String, &RemoteModelData>,
-    existing_template: Option<&PricingTemplate>,
-) -> PricingTemplate {
+/// 内置价格模板的元数据（尚无模型）
+fn builtin_template_shell(provider: &str, now: i64) -> PricingTemplate {
     let (template_id, name, description, tags) = match provider {
         "anthropic" => (
             "builtin_claude",
@@ -193,53 +377,19 @@ fn build_template_from_remote(
         _ => ("builtin_unknown", "未知提供商", "未知提供商定价", vec![]),
     };
 
-    let mut custom_models = HashMap::new();
-
-    for (key, data) in models {
-        let input_per_1m = data.input_cost_per_token.unwrap_or(0.0) * 1_000_000.0;
-        let output_per_1m = data.output_cost_per_token.unwrap_or(0.0) * 1_000_000.0;
-        let cache_write = data
-            .cache_creation_input_token_cost
-            .map(|v| v * 1_000_000.0);
-        // Anthropic 1h 缓存写入价格 = input * 2.0（远程数据仅提供 5m 价格）
-        let cache_write_1h = if provider == "anthropic" {
-            data.input_cost_per_token.map(|v| v * 2.0 * 1_000_000.0)
-        } else {
-            None
-        };
-        let cache_read = data.cache_read_input_token_cost.map(|v| v * 1_000_000.0);
-        let reasoning = data.reasoning_cost_per_token.map(|v| v * 1_000_000.0);
-
-        let aliases = generate_aliases(key);
-
-        let model_price = ModelPrice::new(
-            provider.to_string(),
-            input_per_1m,
-            output_per_1m,
-            cache_write,
-            cache_write_1h,
-            cache_read,
-            reasoning,
-            aliases,
-        );
-
-        custom_models.insert(key.clone(), model_price);
-    }
-
-    let now = chrono::Utc::now().timestamp_millis();
-    let created_at = existing_template.map(|t| t.created_at).unwrap_or(now);
-
     PricingTemplate {
         id: template_id.to_string(),
         name: name.to_string(),
         description: description.to_string(),
         version: "1.0".to_string(),
-        created_at,
+        created_at: now,
         updated_at: now,
         inherited_models: vec![],
-        custom_models,
+        custom_models: HashMap::new(),
         tags,
         is_default_preset: true,
+        per_request_fee: 0.0,
+        min_monthly_fee: 0.0,
     }
 }
 
@@ -340,34 +490,65 @@ fn replace_digit_dots_with_dashes(s: &str) -> String {
     result
 }
 
+/// 读取价格目录同步配置
+fn sync_config() -> PricingSyncConfig {
+    read_global_config()
+        .ok()
+        .flatten()
+        .map(|cfg| cfg.pricing_sync)
+        .unwrap_or_default()
+}
+
+/// 配置的价格目录地址（未配置时使用默认地址）
+pub fn configured_catalog_url() -> String {
+    sync_config()
+        .catalog_url
+        .map(|url| url.trim().to_string())
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| DEFAULT_CATALOG_URL.to_string())
+}
+
+/// 按配置的目录地址同步全局价格模板
+pub async fn sync_remote_prices() -> Result<CatalogSyncReport> {
+    PRICING_MANAGER
+        .sync_remote_catalog(&configured_catalog_url())
+        .await
+}
+
 /// 启动定期同步调度器
+///
+/// 每轮重新读取配置：关闭同步或修改间隔、地址后下一轮生效
 pub async fn start_sync_scheduler() {
     tokio::spawn(async {
         // 首次延迟 5 秒，避免影响启动速度
         tokio::time::sleep(std::time::Duration::from_secs(5)).await;
 
-        // 首次同步
-        match sync_remote_prices().await {
-            Ok(true) => tracing::info!("首次远程价格同步成功"),
-            Ok(false) => tracing::info!("首次远程价格同步：数据未变化"),
-            Err(e) => tracing::warn!("首次远程价格同步失败: {}", e),
-        }
-
-        // 计算距离下一个整点的延迟
-        let now = chrono::Utc::now();
-        let secs_past_hour = (now.timestamp() % 3600) as u64;
-        let secs_to_next_hour = 3600 - secs_past_hour;
-        tokio::time::sleep(std::time::Duration::from_secs(secs_to_next_hour)).await;
-
-        // 每小时循环同步
-        let mut interval = tokio::time::interval(std::time::Duration::from_secs(3600));
         loop {
-            interval.tick().await;
-            match sync_remote_prices().await {
-                Ok(true) => tracing::info!("定时远程价格同步成功"),
-                Ok(false) => tracing::info!("定时远程价格同步：数据未变化"),
-                Err(e) => tracing::warn!("定时远程价格同步失败: {}", e),
+            let config = sync_config();
+            if config.enabled {
+                match sync_remote_prices().await {
+                    Ok(report) if report.not_modified => {
+                        tracing::info!("定时远程价格同步：数据未变化")
+                    }
+                    Ok(report) => tracing::info!(
+                        added = report
+                            .templates
+                            .iter()
+                            .map(|t| t.added.len())
+                            .sum::<usize>(),
+                        updated = report
+                            .templates
+                            .iter()
+                            .map(|t| t.updated.len())
+                            .sum::<usize>(),
+                        "定时远程价格同步成功"
+                    ),
+                    Err(e) => tracing::warn!("定时远程价格同步失败: {}", e),
+                }
             }
+
+            let hours = u64::from(config.interval_hours.max(1));
+            tokio::time::sleep(std::time::Duration::from_secs(hours * 3600)).await;
         }
     });
 }
@@ -375,6 +556,56 @@ pub async fn start_sync_scheduler() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::data::DataManager;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_apply_catalog_merges_into_builtin_templates() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = PricingManager::new_with_manager(
+            dir.path().to_path_buf(),
+            Arc::new(DataManager::new()),
+        );
+        manager.initialize().unwrap();
+
+        let catalog = r#"{
+            "claude-sonnet-4-5-20250929": {"litellm_provider": "anthropic", "mode": "chat",
+                "input_cost_per_token": 3.3e-6, "output_cost_per_token": 1.5e-5},
+            "claude-opus-9": {"litellm_provider": "anthropic", "mode": "chat",
+                "input_cost_per_token": 5e-6, "output_cost_per_token": 2.5e-5,
+                "cache_read_input_token_cost": 5e-7, "max_input_tokens": 200000},
+            "bedrock/claude-opus-9": {"litellm_provider": "bedrock", "mode": "chat",
+                "input_cost_per_token": 5e-6, "output_cost_per_token": 2.5e-5},
+            "text-embedding-9": {"litellm_provider": "openai", "mode": "embedding",
+                "input_cost_per_token": 1e-7, "output_cost_per_token": 1e-7}
+        }"#;
+
+        let summaries = manager.apply_catalog(catalog).unwrap();
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].template_id, "builtin_claude");
+        assert_eq!(summaries[0].added, vec!["claude-opus-9"]);
+        // 带日期的目录条目通过别名匹配到本地模型，只更新价格
+        assert_eq!(summaries[0].updated, vec!["claude-sonnet-4.5"]);
+
+        let template = manager.get_template("builtin_claude").unwrap();
+        let sonnet = &template.custom_models["claude-sonnet-4.5"];
+        assert!((sonnet.input_price_per_1m - 3.3).abs() < 1e-9);
+        assert!(sonnet.aliases.contains(&"claude-sonnet-4-5".to_string()));
+        let opus = &template.custom_models["claude-opus-9"];
+        assert!((opus.output_price_per_1m - 25.0).abs() < 1e-9);
+        assert!((opus.cache_read_price_per_1m.unwrap() - 0.5).abs() < 1e-9);
+        assert_eq!(opus.context_window, Some(200_000));
+        // 目录中没有的本地模型保留
+        assert!(template.custom_models.contains_key("claude-sonnet-4"));
+        assert!(!template.custom_models.contains_key("bedrock/claude-opus-9"));
+
+        // 再次同步同一目录不产生变化
+        let summaries = manager.apply_catalog(catalog).unwrap();
+        assert!(!summaries[0].changed());
+        assert_eq!(summaries[0].unchanged, 2);
+
+        assert!(manager.apply_catalog("{}").is_err());
+    }
 
     #[test]
     fn test_generate_aliases_with_date_suffix() {
@@ -0,0 +1,540 @@
+//! 价格模板校验与试算
+//!
+//! 校验（模板无需已保存，便于在保存或设为默认前检查）：
+//! - 负数价格、阶梯阈值无效
+//! - 缺少缓存价格（缓存 Token 会按 0 计价）
+//! - 继承的来源模板不存在、来源模板中找不到该模型
+//! - 循环继承
+//! - 别名冲突（见 [`super::conflicts`]）
+//!
+//! 试算按假设的 Token 用量计算成本，不读取也不写入统计数据。
+
+use super::conflicts::AliasConflict;
+use super::manager::{cost_breakdown, CostBreakdown, PricingManager};
+use crate::models::pricing::{ModelPrice, PricingTemplate};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
+
+/// 问题严重程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueSeverity {
+    /// 计价会失败或结果错误
+    Error,
+    /// 可能导致少计成本
+    Warning,
+}
+
+/// 问题类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TemplateIssueKind {
+    /// 价格为负数或阶梯阈值无效
+    InvalidPrice,
+    /// 缺少缓存价格
+    MissingCachePrice,
+    /// 继承的来源模板不存在
+    UnknownSourceTemplate,
+    /// 来源模板中找不到继承的模型
+    UnknownInheritedModel,
+    /// 循环继承
+    CircularInheritance,
+}
+
+/// 模板问题
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateIssue {
+    pub kind: TemplateIssueKind,
+    pub severity: IssueSeverity,
+    /// 相关模型（模板级问题为 None）
+    pub model: Option<String>,
+    pub message: String,
+}
+
+impl TemplateIssue {
+    fn error(kind: TemplateIssueKind, model: Option<&str>, message: String) -> Self {
+        Self {
+            kind,
+            severity: IssueSeverity::Error,
+            model: model.map(|m| m.to_string()),
+            message,
+        }
+    }
+
+    fn warning(kind: TemplateIssueKind, model: Option<&str>, message: String) -> Self {
+        Self {
+            kind,
+            severity: IssueSeverity::Warning,
+            model: model.map(|m| m.to_string()),
+            message,
+        }
+    }
+}
+
+/// 模板校验结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PricingTemplateValidation {
+    pub template_id: String,
+    pub issues: Vec<TemplateIssue>,
+    pub alias_conflicts: Vec<AliasConflict>,
+    /// 无法确定匹配结果的别名数（这些模型计价会失败）
+    pub ambiguous: usize,
+    /// 没有错误级问题且没有歧义别名
+    pub valid: bool,
+}
+
+/// 试算场景（假设的单次请求用量）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostScenario {
+    pub model: String,
+    #[serde(default)]
+    pub input_tokens: i64,
+    #[serde(default)]
+    pub output_tokens: i64,
+    /// 缓存创建 Token 总量（5m + 1h）
+    #[serde(default)]
+    pub cache_creation_tokens: i64,
+    #[serde(default)]
+    pub cache_creation_1h_tokens: i64,
+    #[serde(default)]
+    pub cache_read_tokens: i64,
+    #[serde(default)]
+    pub reasoning_tokens: i64,
+    /// 请求次数（默认 1）
+    #[serde(default = "default_requests")]
+    pub requests: u32,
+    /// 假设的当月已用 Token 数（用于选择阶梯价格，默认 0）
+    #[serde(default)]
+    pub monthly_tokens: i64,
+}
+
+fn default_requests() -> u32 {
+    1
+}
+
+/// 试算结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostSimulation {
+    pub template_id: String,
+    pub model: String,
+    /// 实际使用的模型价格（已应用阶梯价格）
+    pub price: ModelPrice,
+    /// 命中的阶梯阈值（未命中时为 None）
+    pub tier_threshold: Option<i64>,
+    /// 单次请求的成本分解
+    pub per_request: CostBreakdown,
+    pub requests: u32,
+    /// 全部请求的总成本（USD）
+    pub total_cost: f64,
+}
+
+impl PricingManager {
+    /// 校验价格模板（模板可以尚未保存）
+    pub fn validate_template(&self, template: &PricingTemplate) -> PricingTemplateValidation {
+        let mut issues = Vec::new();
+
+        let mut names: Vec<&String> = template.custom_models.keys().collect();
+        names.sort();
+        for name in names {
+            check_model_price(name, &template.custom_models[name], &mut issues);
+        }
+
+        let cycle = self.inheritance_cycle(template);
+        if let Some(cycle) = &cycle {
+            issues.push(TemplateIssue::error(
+                TemplateIssueKind::CircularInheritance,
+                None,
+                format!("循环继承: {}", cycle.join(" → ")),
+            ));
+        }
+
+        for inherited in &template.inherited_models {
+            let model = Some(inherited.model_name.as_str());
+            if inherited.multiplier < 0.0 {
+                issues.push(TemplateIssue::error(
+                    TemplateIssueKind::InvalidPrice,
+                    model,
+                    format!("继承倍率不能为负数: {}", inherited.multiplier),
+                ));
+            }
+            let source = if inherited.source_template_id == template.id {
+                Ok(template.clone())
+            } else {
+                self.get_template(&inherited.source_template_id)
+            };
+            let Ok(source) = source else {
+                issues.push(TemplateIssue::error(
+                    TemplateIssueKind::UnknownSourceTemplate,
+                    model,
+                    format!("来源模板不存在: {}", inherited.source_template_id),
+                ));
+                continue;
+            };
+            // 循环继承时解析没有意义（只会触发层级上限）
+            if cycle.is_none()
+                && self
+                    .resolve_model_price(&source, &inherited.model_name)
+                    .is_err()
+            {
+                issues.push(TemplateIssue::error(
+                    TemplateIssueKind::UnknownInheritedModel,
+                    model,
+                    format!(
+                        "来源模板 {} 中找不到模型 {}",
+                        inherited.source_template_id, inherited.model_name
+                    ),
+                ));
+            }
+        }
+
+        let alias_conflicts = if cycle.is_none() {
+            self.detect_alias_conflicts(template)
+        } else {
+            Vec::new()
+        };
+        let ambiguous = alias_conflicts.iter().filter(|c| c.ambiguous).count();
+        let valid = ambiguous == 0 && !issues.iter().any(|i| i.severity == IssueSeverity::Error);
+
+        PricingTemplateValidation {
+            template_id: template.id.clone(),
+            issues,
+            alias_conflicts,
+            ambiguous,
+            valid,
+        }
+    }
+
+    /// 校验已保存的价格模板
+    pub fn validate_saved_template(&self, template_id: &str) -> Result<PricingTemplateValidation> {
+        let template = self.get_template(template_id)?;
+        Ok(self.validate_template(&template))
+    }
+
+    /// 校验所有价格模板
+    pub fn validate_all_templates(&self) -> Result<Vec<PricingTemplateValidation>> {
+        Ok(self
+            .list_templates()?
+            .iter()
+            .map(|template| self.validate_template(template))
+            .collect())
+    }
+
+    /// 按假设的用量试算成本（阶梯价格按场景中的当月用量选择）
+    pub fn simulate_cost(
+        &self,
+        template_id: &str,
+        scenario: &CostScenario,
+    ) -> Result<CostSimulation> {
+        let template = self.get_template(template_id)?;
+        let base_price = self.resolve_model_price(&template, &scenario.model)?;
+        let tier = base_price.tier_for(scenario.monthly_tokens).cloned();
+        let price = match &tier {
+            Some(tier) => base_price.with_tier(tier),
+            None => base_price,
+        };
+
+        let per_request = cost_breakdown(
+            &template,
+            &price,
+            scenario.input_tokens,
+            scenario.output_tokens,
+            scenario.cache_creation_tokens,
+            scenario.cache_creation_1h_tokens,
+            scenario.cache_read_tokens,
+            scenario.reasoning_tokens,
+        );
+        let requests = scenario.requests.max(1);
+
+        Ok(CostSimulation {
+            template_id: template.id,
+            model: scenario.model.clone(),
+            price,
+            tier_threshold: tier.map(|t| t.threshold_tokens),
+            total_cost: per_request.total_cost * f64::from(requests),
+            per_request,
+            requests,
+        })
+    }
+
+    /// 查找从模板出发的继承环（未保存的模板替代同 ID 的已保存版本）
+    fn inheritance_cycle(&self, template: &PricingTemplate) -> Option<Vec<String>> {
+        let mut path = vec![template.id.clone()];
+        let mut done = HashSet::new();
+        self.find_cycle(template, &mut path, &mut done)
+    }
+
+    fn find_cycle(
+        &self,
+        current: &PricingTemplate,
+        path: &mut Vec<String>,
+        done: &mut HashSet<String>,
+    ) -> Option<Vec<String>> {
+        let sources: BTreeSet<&String> = current
+            .inherited_models
+            .iter()
+            .map(|m| &m.source_template_id)
+            .collect();
+
+        for source in sources {
+            if let Some(start) = path.iter().position(|id| id == source) {
+                let mut cycle = path[start..].to_vec();
+                cycle.push(source.clone());
+                return Some(cycle);
+            }
+            if done.contains(source) {
+                continue;
+            }
+            let Ok(next) = self.get_template(source) else {
+                continue;
+            };
+            path.push(source.clone());
+            if let Some(cycle) = self.find_cycle(&next, path, done) {
+                return Some(cycle);
+            }
+            path.pop();
+            done.insert(source.clone());
+        }
+        None
+    }
+}
+
+/// 检查单个自定义模型的价格
+fn check_model_price(name: &str, price: &ModelPrice, issues: &mut Vec<TemplateIssue>) {
+    let model = Some(name);
+    let prices = [
+        Some(price.input_price_per_1m),
+        Some(price.output_price_per_1m),
+        price.cache_write_price_per_1m,
+        price.cache_write_1h_price_per_1m,
+        price.cache_read_price_per_1m,
+        price.reasoning_output_price_per_1m,
+    ];
+    if prices.iter().flatten().any(|p| *p < 0.0 || !p.is_finite()) {
+        issues.push(TemplateIssue::error(
+            TemplateIssueKind::InvalidPrice,
+            model,
+            format!("模型 {} 存在负数或无效的价格", name),
+        ));
+    }
+
+    let mut thresholds = HashSet::new();
+    for tier in &price.tiers {
+        let tier_prices = [
+            Some(tier.input_price_per_1m),
+            Some(tier.output_price_per_1m),
+            tier.cache_write_price_per_1m,
+            tier.cache_write_1h_price_per_1m,
+            tier.cache_read_price_per_1m,
+            tier.reasoning_output_price_per_1m,
+        ];
+        if tier.threshold_tokens <= 0
+            || !thresholds.insert(tier.threshold_tokens)
+            || tier_prices
+                .iter()
+                .flatten()
+                .any(|p| *p < 0.0 || !p.is_finite())
+        {
+            issues.push(TemplateIssue::error(
+                TemplateIssueKind::InvalidPrice,
+                model,
+                format!(
+                    "模型 {} 的阶梯价格无效（阈值 {} 必须为正且不重复，价格不能为负数）",
+                    name, tier.threshold_tokens
+                ),
+            ));
+        }
+    }
+
+    if price.cache_read_price_per_1m.is_none() {
+        issues.push(TemplateIssue::warning(
+            TemplateIssueKind::MissingCachePrice,
+            model,
+            format!(
+                "模型 {} 未设置缓存读取价格，缓存命中的 Token 将按 0 计价",
+                name
+            ),
+        ));
+    }
+    // Anthropic 的缓存写入单独计费，其他提供商的缓存写入通常按输入计价
+    if price.provider == "anthropic" && price.cache_write_price_per_1m.is_none() {
+        issues.push(TemplateIssue::warning(
+            TemplateIssueKind::MissingCachePrice,
+            model,
+            format!(
+                "模型 {} 未设置缓存写入价格，缓存写入的 Token 将按 0 计价",
+                name
+            ),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::DataManager;
+    use crate::models::pricing::InheritedModel;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    fn template(id: &str, models: Vec<(&str, ModelPrice)>) -> PricingTemplate {
+        PricingTemplate::new(
+            id.to_string(),
+            id.to_string(),
+            String::new(),
+            "1.0".to_string(),
+            vec![],
+            models
+                .into_iter()
+                .map(|(name, price)| (name.to_string(), price))
+                .collect::<HashMap<_, _>>(),
+            vec![],
+            false,
+        )
+    }
+
+    fn manager() -> (PricingManager, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = PricingManager::new_with_manager(
+            dir.path().to_path_buf(),
+            Arc::new(DataManager::new()),
+        );
+        (manager, dir)
+    }
+
+    fn kinds(validation: &PricingTemplateValidation) -> Vec<TemplateIssueKind> {
+        validation.issues.iter().map(|i| i.kind).collect()
+    }
+
+    #[test]
+    fn test_validate_unsaved_template() {
+        let (manager, _dir) = manager();
+        manager
+            .save_template(&template(
+                "base",
+                vec![(
+                    "m",
+                    ModelPrice::new(
+                        "anthropic".to_string(),
+                        3.0,
+                        15.0,
+                        Some(3.75),
+                        None,
+                        Some(0.3),
+                        None,
+                        vec!["m".to_string()],
+                    ),
+                )],
+            ))
+            .unwrap();
+
+        let mut draft = template(
+            "draft",
+            vec![(
+                "custom",
+                ModelPrice::new(
+                    "anthropic".to_string(),
+                    -1.0,
+                    2.0,
+                    None,
+                    None,
+                    None,
+                    None,
+                    vec![],
+                ),
+            )],
+        );
+        draft.inherited_models = vec![
+            InheritedModel::new("m".to_string(), "base".to_string(), 1.2),
+            InheritedModel::new("missing".to_string(), "base".to_string(), 1.0),
+            InheritedModel::new("m".to_string(), "nowhere".to_string(), 1.0),
+        ];
+
+        let validation = manager.validate_template(&draft);
+        assert!(!validation.valid);
+        assert_eq!(
+            kinds(&validation),
+            vec![
+                TemplateIssueKind::InvalidPrice,
+                TemplateIssueKind::MissingCachePrice,
+                TemplateIssueKind::MissingCachePrice,
+                TemplateIssueKind::UnknownInheritedModel,
+                TemplateIssueKind::UnknownSourceTemplate,
+            ]
+        );
+        // 草稿未保存
+        assert!(manager.get_template("draft").is_err());
+    }
+
+    #[test]
+    fn test_validate_detects_circular_inheritance() {
+        let (manager, _dir) = manager();
+        let mut a = template("a", vec![]);
+        a.inherited_models = vec![InheritedModel::new("m".to_string(), "b".to_string(), 1.0)];
+        let mut b = template("b", vec![]);
+        b.inherited_models = vec![InheritedModel::new("m".to_string(), "a".to_string(), 1.0)];
+        manager.save_template(&a).unwrap();
+        manager.save_template(&b).unwrap();
+
+        let validation = manager.validate_template(&a);
+        assert!(!validation.valid);
+        assert_eq!(
+            kinds(&validation),
+            vec![TemplateIssueKind::CircularInheritance]
+        );
+        assert!(validation.issues[0].message.contains("a → b → a"));
+
+        // 计价不会无限递归
+        assert!(manager.resolve_model_price(&a, "m").is_err());
+    }
+
+    #[test]
+    fn test_simulate_cost() {
+        let (manager, _dir) = manager();
+        let mut relay = template(
+            "relay",
+            vec![(
+                "m",
+                ModelPrice::new(
+                    "openai".to_string(),
+                    2.0,
+                    8.0,
+                    None,
+                    None,
+                    Some(0.5),
+                    None,
+                    vec!["m".to_string()],
+                ),
+            )],
+        );
+        relay.per_request_fee = 0.01;
+        manager.save_template(&relay).unwrap();
+
+        let scenario = CostScenario {
+            model: "m".to_string(),
+            input_tokens: 1_000_000,
+            output_tokens: 500_000,
+            cache_creation_tokens: 0,
+            cache_creation_1h_tokens: 0,
+            cache_read_tokens: 2_000_000,
+            reasoning_tokens: 0,
+            requests: 10,
+            monthly_tokens: 0,
+        };
+        let simulation = manager.simulate_cost("relay", &scenario).unwrap();
+        // 2 + 4 + 1 + 0.01
+        assert!((simulation.per_request.total_cost - 7.01).abs() < 1e-9);
+        assert!((simulation.total_cost - 70.1).abs() < 1e-9);
+        assert_eq!(simulation.tier_threshold, None);
+
+        assert!(manager
+            .simulate_cost(
+                "relay",
+                &CostScenario {
+                    model: "x".to_string(),
+                    ..scenario
+                }
+            )
+            .is_err());
+    }
+}
@@ -0,0 +1,227 @@
+//! Profile 凭证验证
+//!
+//! 以真实请求验证 Profile 的 API Key：
+//! - 模型列表接口：判断 Key 是否被接受，并返回可用模型
+//! - OpenAI 兼容的账单接口（One API / New API 等中转站支持）：查询额度与余额
+//!
+//! 与健康检查不同，结果不缓存，每次调用都会请求上游。
+
+use super::health::{auth_from_status, probe_request, AuthStatus};
+use super::manager::ProfileManager;
+use crate::core::http::get_global_client;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::{Duration, Instant};
+
+/// 单次请求超时时间
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// 额度 / 余额（单位：美元）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CredentialQuota {
+    /// 总额度（无限额度时为 None）
+    pub total: Option<f64>,
+    /// 本期已用
+    pub used: Option<f64>,
+    /// 剩余额度
+    pub remaining: Option<f64>,
+    /// 额度来源接口
+    pub source: String,
+}
+
+/// 凭证验证结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CredentialValidation {
+    pub tool_id: String,
+    pub profile_name: String,
+    pub base_url: String,
+    /// API Key 是否被上游接受
+    pub valid: bool,
+    pub auth: AuthStatus,
+    pub status_code: Option<u16>,
+    pub latency_ms: Option<u64>,
+    /// 上游返回的模型列表
+    pub models: Vec<String>,
+    /// 上游不提供账单接口时为 None
+    pub quota: Option<CredentialQuota>,
+    pub error: Option<String>,
+    /// 验证时间（Unix 时间戳，毫秒）
+    pub checked_at: i64,
+}
+
+impl ProfileManager {
+    /// 验证 Profile 的 API Key，并查询模型列表与剩余额度
+    pub async fn validate_profile_credentials(
+        &self,
+        tool_id: &str,
+        profile_name: &str,
+    ) -> Result<CredentialValidation> {
+        let (_, api_key, base_url) = self
+            .load_profiles_store()?
+            .get_tool_profiles(tool_id)
+            .ok_or_else(|| anyhow!("不支持的工具: {}", tool_id))?
+            .into_iter()
+            .find(|(name, _, _)| name == profile_name)
+            .ok_or_else(|| anyhow!("Profile 不存在: {}/{}", tool_id, profile_name))?;
+
+        let mut result = CredentialValidation {
+            tool_id: tool_id.to_string(),
+            profile_name: profile_name.to_string(),
+            base_url: base_url.clone(),
+            valid: false,
+            auth: AuthStatus::Unchecked,
+            status_code: None,
+            latency_ms: None,
+            models: Vec::new(),
+            quota: None,
+            error: None,
+            checked_at: chrono::Utc::now().timestamp_millis(),
+        };
+        if base_url.trim().is_empty() || api_key.trim().is_empty() {
+            result.error = Some("未配置 Base URL 或 API Key".to_string());
+            return Ok(result);
+        }
+
+        let client = get_global_client()?;
+        let (url, headers) = probe_request(tool_id, &base_url, &api_key);
+        let mut request = client.get(&url).timeout(REQUEST_TIMEOUT);
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+
+        let started = Instant::now();
+        let response = match request.send().await {
+            Ok(response) => response,
+            Err(e) => {
+                result.error = Some(e.to_string());
+                return Ok(result);
+            }
+        };
+        let status = response.status().as_u16();
+        result.latency_ms = Some(started.elapsed().as_millis() as u64);
+        result.status_code = Some(status);
+        result.auth = auth_from_status(status);
+        result.valid = result.auth == AuthStatus::Valid;
+        if result.valid {
+            match response.json::<Value>().await {
+                Ok(body) => result.models = parse_model_ids(&body),
+                Err(e) => result.error = Some(format!("模型列表不是 JSON: {}", e)),
+            }
+        } else {
+            result.error = Some(format!("模型列表接口返回状态码 {}", status));
+        }
+
+        // Gemini 官方接口没有账单查询，Key 被拒绝时也无需再查
+        if tool_id != "gemini-cli" && result.auth != AuthStatus::Invalid {
+            result.quota = fetch_quota(&client, &base_url, &api_key).await;
+        }
+
+        tracing::info!(
+            tool_id = %tool_id,
+            profile = %profile_name,
+            valid = result.valid,
+            models = result.models.len(),
+            has_quota = result.quota.is_some(),
+            "Profile 凭证验证完成"
+        );
+        Ok(result)
+    }
+}
+
+/// 解析模型列表（OpenAI / Anthropic 的 `data[].id` 与 Gemini 的 `models[].name`）
+fn parse_model_ids(body: &Value) -> Vec<String> {
+    let items = body
+        .get("data")
+        .or_else(|| body.get("models"))
+        .and_then(Value::as_array);
+    items
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| item.get("id").or_else(|| item.get("name"))?.as_str())
+                .map(|id| id.trim_start_matches("models/").to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 查询 OpenAI 兼容账单接口（不支持时返回 None）
+pub async fn fetch_quota(
+    client: &reqwest::Client,
+    base_url: &str,
+    api_key: &str,
+) -> Option<CredentialQuota> {
+    let base = base_url.trim().trim_end_matches('/');
+    let root = base.strip_suffix("/v1").unwrap_or(base);
+    let get_json = |url: String| async move {
+        let response = client
+            .get(&url)
+            .timeout(REQUEST_TIMEOUT)
+            .bearer_auth(api_key)
+            .send()
+            .await
+            .ok()?;
+        if !response.status().is_success() {
+            return None;
+        }
+        response.json::<Value>().await.ok()
+    };
+
+    let subscription = get_json(format!("{root}/v1/dashboard/billing/subscription")).await?;
+    let today = chrono::Utc::now().date_naive();
+    let usage = get_json(format!(
+        "{root}/v1/dashboard/billing/usage?start_date={}&end_date={}",
+        today - chrono::Duration::days(99),
+        today + chrono::Duration::days(1)
+    ))
+    .await;
+    parse_billing(&subscription, usage.as_ref())
+}
+
+/// 解析账单接口：`hard_limit_usd` 为总额度，`total_usage` 以美分计
+fn parse_billing(subscription: &Value, usage: Option<&Value>) -> Option<CredentialQuota> {
+    let total = subscription.get("hard_limit_usd")?.as_f64()?;
+    // 中转站以超大额度表示无限额度
+    let total = (total < 100_000_000.0).then_some(total);
+    let used = usage
+        .and_then(|u| u.get("total_usage")?.as_f64())
+        .map(|cents| cents / 100.0);
+    Some(CredentialQuota {
+        total,
+        used,
+        remaining: total.zip(used).map(|(t, u)| (t - u).max(0.0)),
+        source: "dashboard/billing".to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_model_ids() {
+        let openai = json!({ "data": [{ "id": "gpt-5" }, { "id": "o3" }] });
+        assert_eq!(parse_model_ids(&openai), vec!["gpt-5", "o3"]);
+        let gemini = json!({ "models": [{ "name": "models/gemini-2.5-pro" }] });
+        assert_eq!(parse_model_ids(&gemini), vec!["gemini-2.5-pro"]);
+        assert!(parse_model_ids(&json!({ "error": "x" })).is_empty());
+    }
+
+    #[test]
+    fn test_parse_billing() {
+        let quota = parse_billing(
+            &json!({ "hard_limit_usd": 50.0 }),
+            Some(&json!({ "total_usage": 1250.0 })),
+        )
+        .unwrap();
+        assert_eq!(quota.total, Some(50.0));
+        assert_eq!(quota.used, Some(12.5));
+        assert_eq!(quota.remaining, Some(37.5));
+
+        let unlimited = parse_billing(&json!({ "hard_limit_usd": 100000000.0 }), None).unwrap();
+        assert_eq!((unlimited.total, unlimited.remaining), (None, None));
+        assert!(parse_billing(&json!({ "object": "list" }), None).is_none());
+    }
+}
@@ -0,0 +1,279 @@
+//! 配置漂移检测
+//!
+//! 比较工具原生配置文件与当前激活 Profile 应写入的值（Base URL、API Key、模型相关字段），
+//! 报告逐字段的差异。部分 CLI 工具在登录时会改写自身配置，可通过重新应用 Profile 修复。
+
+use super::manager::ProfileManager;
+use super::native_config::{claude_env, codex_base_url, gemini_env};
+use super::types::mask_api_key;
+use crate::data::DataManager;
+use crate::models::tool::Tool;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+
+/// 单个字段的漂移
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DriftField {
+    /// 原生配置文件名
+    pub file: String,
+    /// 字段路径（如 `env.ANTHROPIC_BASE_URL`）
+    pub field: String,
+    /// Profile 应写入的值（敏感字段已脱敏）
+    pub expected: String,
+    /// 当前实际值（字段或文件不存在时为 None，敏感字段已脱敏）
+    pub actual: Option<String>,
+    /// 是否为 API Key 等敏感字段
+    pub sensitive: bool,
+}
+
+/// 配置漂移检测结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigDriftReport {
+    pub tool_id: String,
+    /// 当前激活的 Profile（未激活时为 None，不做检测）
+    pub profile_name: Option<String>,
+    pub drifted: bool,
+    /// 检测的字段数
+    pub checked_fields: usize,
+    /// 发生漂移的字段
+    pub fields: Vec<DriftField>,
+    /// 检测时间（Unix 时间戳，毫秒）
+    pub checked_at: i64,
+}
+
+/// Profile 应写入原生配置的字段
+#[derive(Debug, Clone)]
+struct ExpectedField {
+    file: &'static str,
+    path: Vec<String>,
+    value: String,
+    sensitive: bool,
+}
+
+impl ExpectedField {
+    fn new(file: &'static str, path: &[&str], value: String, sensitive: bool) -> Self {
+        Self {
+            file,
+            path: path.iter().map(|s| s.to_string()).collect(),
+            value,
+            sensitive,
+        }
+    }
+}
+
+impl ProfileManager {
+    /// 检测工具原生配置是否偏离当前激活的 Profile
+    pub fn check_config_drift(&self, tool_id: &str) -> Result<ConfigDriftReport> {
+        let tool = Tool::by_id(tool_id).ok_or_else(|| anyhow!("未找到工具: {}", tool_id))?;
+        let profile_name = self.get_active_profile_name(tool_id)?;
+        let fields = match &profile_name {
+            Some(name) => self.expected_fields(tool_id, name)?,
+            None => Vec::new(),
+        };
+        let drift = detect_drift(&tool.config_dir, &fields)?;
+
+        Ok(ConfigDriftReport {
+            tool_id: tool_id.to_string(),
+            profile_name,
+            drifted: !drift.is_empty(),
+            checked_fields: fields.len(),
+            fields: drift,
+            checked_at: chrono::Utc::now().timestamp_millis(),
+        })
+    }
+
+    /// 重新应用当前激活的 Profile 修复漂移，返回修复后的检测结果
+    pub fn repair_drift(&self, tool_id: &str) -> Result<ConfigDriftReport> {
+        let profile_name = self
+            .get_active_profile_name(tool_id)?
+            .ok_or_else(|| anyhow!("{} 没有激活的 Profile", tool_id))?;
+
+        // 修复属于内部写入，不触发外部变更通知
+        crate::services::config::watcher::suppress_external_detection_for_tool(
+            tool_id,
+            Duration::from_secs(3),
+        );
+        self.activate_profile(tool_id, &profile_name)?;
+        tracing::info!(tool_id = %tool_id, profile = %profile_name, "已修复配置漂移");
+
+        self.check_config_drift(tool_id)
+    }
+
+    fn expected_fields(&self, tool_id: &str, profile_name: &str) -> Result<Vec<ExpectedField>> {
+        let fields = match tool_id {
+            "claude-code" => {
+                let profile = self.get_claude_profile(profile_name)?;
+                claude_env(&profile, &profile.api_key)
+                    .into_iter()
+                    .map(|(key, value)| {
+                        ExpectedField::new(
+                            "settings.json",
+                            &["env", key],
+                            value,
+                            key == "ANTHROPIC_AUTH_TOKEN",
+                        )
+                    })
+                    .collect()
+            }
+            "codex" => {
+                let profile = self.get_codex_profile(profile_name)?;
+                let provider = ["model_providers", profile_name];
+                vec![
+                    ExpectedField::new(
+                        "config.toml",
+                        &["model_provider"],
+                        profile_name.to_string(),
+                        false,
+                    ),
+                    ExpectedField::new(
+                        "config.toml",
+                        &[provider[0], provider[1], "base_url"],
+                        codex_base_url(&profile.base_url),
+                        false,
+                    ),
+                    ExpectedField::new(
+                        "config.toml",
+                        &[provider[0], provider[1], "wire_api"],
+                        profile.wire_api.clone(),
+                        false,
+                    ),
+                    ExpectedField::new("auth.json", &["OPENAI_API_KEY"], profile.api_key, true),
+                ]
+            }
+            "gemini-cli" => {
+                let profile = self.get_gemini_profile(profile_name)?;
+                gemini_env(&profile, &profile.api_key)
+                    .into_iter()
+                    .map(|(key, value)| {
+                        ExpectedField::new(".env", &[key], value, key == "GEMINI_API_KEY")
+                    })
+                    .collect()
+            }
+            _ => return Err(anyhow!("不支持的工具: {}", tool_id)),
+        };
+        Ok(fields)
+    }
+}
+
+/// 比较配置目录中的实际值与期望值，返回发生漂移的字段
+fn detect_drift(config_dir: &Path, fields: &[ExpectedField]) -> Result<Vec<DriftField>> {
+    let mut files: HashMap<&str, Option<Value>> = HashMap::new();
+    let mut drift = Vec::new();
+
+    for field in fields {
+        if !files.contains_key(field.file) {
+            files.insert(field.file, read_native_file(&config_dir.join(field.file))?);
+        }
+        let actual = files[field.file]
+            .as_ref()
+            .and_then(|content| field.path.iter().try_fold(content, |v, key| v.get(key)))
+            .map(|v| match v {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            });
+        if actual.as_deref() == Some(field.value.as_str()) {
+            continue;
+        }
+
+        let display = |value: &str| {
+            if field.sensitive {
+                mask_api_key(value)
+            } else {
+                value.to_string()
+            }
+        };
+        drift.push(DriftField {
+            file: field.file.to_string(),
+            field: field.path.join("."),
+            expected: display(&field.value),
+            actual: actual.as_deref().map(display),
+            sensitive: field.sensitive,
+        });
+    }
+
+    Ok(drift)
+}
+
+/// 读取原生配置文件为 JSON（TOML / ENV 转换为对象，文件不存在时返回 None）
+fn read_native_file(path: &Path) -> Result<Option<Value>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let manager = DataManager::new();
+    let name = path.to_string_lossy();
+    let value = if name.ends_with(".toml") {
+        let doc = manager.toml().read_document(path)?;
+        let toml_value: toml::Value =
+            toml::from_str(&doc.to_string()).map_err(|e| anyhow!("TOML 解析失败: {}", e))?;
+        serde_json::to_value(toml_value)?
+    } else if name.ends_with(".env") {
+        serde_json::to_value(manager.env().read(path)?)?
+    } else {
+        manager.json_uncached().read(path)?
+    };
+    Ok(Some(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_drift() {
+        let dir = tempfile::tempdir().unwrap();
+        let fields = vec![
+            ExpectedField::new(
+                "settings.json",
+                &["env", "ANTHROPIC_AUTH_TOKEN"],
+                "sk-ant-expected-key".to_string(),
+                true,
+            ),
+            ExpectedField::new(
+                "settings.json",
+                &["env", "ANTHROPIC_BASE_URL"],
+                "https://api.example.com".to_string(),
+                false,
+            ),
+            ExpectedField::new(
+                "config.toml",
+                &["model_providers", "work.v2", "wire_api"],
+                "responses".to_string(),
+                false,
+            ),
+            ExpectedField::new(".env", &["GEMINI_MODEL"], "gemini-pro".to_string(), false),
+        ];
+
+        std::fs::write(
+            dir.path().join("settings.json"),
+            r#"{"env":{"ANTHROPIC_AUTH_TOKEN":"sk-ant-rewritten-by-login","ANTHROPIC_BASE_URL":"https://api.example.com"}}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("config.toml"),
+            "[model_providers.\"work.v2\"]\nwire_api = \"responses\"\n",
+        )
+        .unwrap();
+
+        let drift = detect_drift(dir.path(), &fields).unwrap();
+        assert_eq!(drift.len(), 2);
+        // API Key 漂移只展示脱敏值
+        assert_eq!(drift[0].field, "env.ANTHROPIC_AUTH_TOKEN");
+        assert_eq!(drift[0].expected, "sk-a...-key");
+        assert_eq!(drift[0].actual.as_deref(), Some("sk-a...ogin"));
+        // 文件不存在
+        assert_eq!(drift[1].file, ".env");
+        assert_eq!(drift[1].actual, None);
+
+        std::fs::write(
+            dir.path().join("settings.json"),
+            r#"{"env":{"ANTHROPIC_AUTH_TOKEN":"sk-ant-expected-key","ANTHROPIC_BASE_URL":"https://api.example.com"}}"#,
+        )
+        .unwrap();
+        std::fs::write(dir.path().join(".env"), "GEMINI_MODEL=gemini-pro\n").unwrap();
+        assert!(detect_drift(dir.path(), &fields).unwrap().is_empty());
+    }
+}
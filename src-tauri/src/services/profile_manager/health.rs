@@ -0,0 +1,337 @@
+//! Profile 健康检查
+//!
+//! 对 Profile 的上游发起模型列表请求，检查可达性、延迟与 API Key 是否被接受。
+//! 批量检查并发执行（限制并发数），结果缓存一段时间，重复打开页面时不再重复请求。
+
+use super::manager::ProfileManager;
+use anyhow::Result;
+use futures_util::stream::{self, StreamExt};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+/// 单次检查超时时间
+const CHECK_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// 批量检查的最大并发数
+const MAX_CONCURRENT_CHECKS: usize = 8;
+
+/// 缓存结果的有效期（秒）
+const CACHE_TTL_SECS: i64 = 5 * 60;
+
+/// 内置代理 Profile 前缀（指向本地代理，不参与检查）
+const PROXY_PROFILE_PREFIX: &str = "dc_proxy_";
+
+/// 检查结果缓存（`tool_id/profile_name` -> 结果）
+static HEALTH_CACHE: Lazy<RwLock<HashMap<String, ProfileHealth>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// 认证状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthStatus {
+    /// 上游接受了 API Key
+    Valid,
+    /// 上游拒绝了 API Key（401/403）
+    Invalid,
+    /// 上游可达但无法判断（如不支持模型列表接口）
+    Unknown,
+    /// 上游不可达，未能检查
+    Unchecked,
+}
+
+/// 单个 Profile 的检查结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileHealth {
+    pub tool_id: String,
+    pub profile_name: String,
+    pub base_url: String,
+    pub reachable: bool,
+    pub latency_ms: Option<u64>,
+    pub auth: AuthStatus,
+    pub status_code: Option<u16>,
+    pub error: Option<String>,
+    /// 检查时间（Unix 时间戳，毫秒）
+    pub checked_at: i64,
+}
+
+impl ProfileHealth {
+    fn is_healthy(&self) -> bool {
+        self.reachable && self.auth == AuthStatus::Valid
+    }
+}
+
+/// 批量检查结果矩阵（Profile × 可达性/延迟/认证）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileHealthMatrix {
+    pub results: Vec<ProfileHealth>,
+    pub total: usize,
+    pub healthy: usize,
+    pub auth_failed: usize,
+    pub unreachable: usize,
+    /// 直接使用缓存的结果数
+    pub cached: usize,
+    pub checked_at: i64,
+}
+
+impl ProfileHealthMatrix {
+    fn from_results(mut results: Vec<ProfileHealth>, cached: usize) -> Self {
+        results.sort_by(|a, b| {
+            (a.tool_id.as_str(), a.profile_name.as_str())
+                .cmp(&(b.tool_id.as_str(), b.profile_name.as_str()))
+        });
+        Self {
+            total: results.len(),
+            healthy: results.iter().filter(|r| r.is_healthy()).count(),
+            auth_failed: results
+                .iter()
+                .filter(|r| r.auth == AuthStatus::Invalid)
+                .count(),
+            unreachable: results.iter().filter(|r| !r.reachable).count(),
+            cached,
+            checked_at: chrono::Utc::now().timestamp_millis(),
+            results,
+        }
+    }
+}
+
+/// 上游模型列表接口及认证请求头（用于验证 API Key）
+pub fn probe_request(
+    tool_id: &str,
+    base_url: &str,
+    api_key: &str,
+) -> (String, Vec<(&'static str, String)>) {
+    let base = base_url.trim().trim_end_matches('/');
+    match tool_id {
+        "codex" => {
+            let url = if base.ends_with("/v1") {
+                format!("{base}/models")
+            } else {
+                format!("{base}/v1/models")
+            };
+            (url, vec![("authorization", format!("Bearer {api_key}"))])
+        }
+        "gemini-cli" => (
+            format!("{base}/v1beta/models"),
+            vec![("x-goog-api-key", api_key.to_string())],
+        ),
+        _ => (
+            format!("{base}/v1/models"),
+            vec![
+                ("x-api-key", api_key.to_string()),
+                ("authorization", format!("Bearer {api_key}")),
+                ("anthropic-version", "2023-06-01".to_string()),
+            ],
+        ),
+    }
+}
+
+/// 根据 HTTP 状态码判断认证状态
+pub(super) fn auth_from_status(status: u16) -> AuthStatus {
+    match status {
+        200..=299 => AuthStatus::Valid,
+        401 | 403 => AuthStatus::Invalid,
+        _ => AuthStatus::Unknown,
+    }
+}
+
+fn cache_key(tool_id: &str, profile_name: &str) -> String {
+    format!("{tool_id}/{profile_name}")
+}
+
+/// 待检查的 Profile
+struct ProfileTarget {
+    tool_id: String,
+    profile_name: String,
+    api_key: String,
+    base_url: String,
+}
+
+async fn run_check(client: &reqwest::Client, target: ProfileTarget) -> ProfileHealth {
+    let mut health = ProfileHealth {
+        tool_id: target.tool_id,
+        profile_name: target.profile_name,
+        base_url: target.base_url,
+        reachable: false,
+        latency_ms: None,
+        auth: AuthStatus::Unchecked,
+        status_code: None,
+        error: None,
+        checked_at: chrono::Utc::now().timestamp_millis(),
+    };
+    if health.base_url.trim().is_empty() || target.api_key.trim().is_empty() {
+        health.error = Some("未配置 Base URL 或 API Key".to_string());
+        return health;
+    }
+
+    let (url, headers) = probe_request(&health.tool_id, &health.base_url, &target.api_key);
+    let mut request = client.get(&url).timeout(CHECK_TIMEOUT);
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+
+    let started = Instant::now();
+    match request.send().await {
+        Ok(resp) => {
+            let status = resp.status().as_u16();
+            health.reachable = true;
+            health.latency_ms = Some(started.elapsed().as_millis() as u64);
+            health.status_code = Some(status);
+            health.auth = auth_from_status(status);
+        }
+        Err(e) => health.error = Some(e.to_string()),
+    }
+    health
+}
+
+fn collect_targets(manager: &ProfileManager) -> Result<Vec<ProfileTarget>> {
+    let store = manager.load_profiles_store()?;
+    let mut targets = Vec::new();
+    for tool_id in ["claude-code", "codex", "gemini-cli"] {
+        for (profile_name, api_key, base_url) in
+            store.get_tool_profiles(tool_id).unwrap_or_default()
+        {
+            if profile_name.starts_with(PROXY_PROFILE_PREFIX) {
+                continue;
+            }
+            targets.push(ProfileTarget {
+                tool_id: tool_id.to_string(),
+                profile_name,
+                api_key,
+                base_url,
+            });
+        }
+    }
+    Ok(targets)
+}
+
+impl ProfileManager {
+    /// 检查单个 Profile（结果写入缓存）
+    pub async fn check_profile_health(
+        &self,
+        tool_id: &str,
+        profile_name: &str,
+    ) -> Result<ProfileHealth> {
+        let target = collect_targets(self)?
+            .into_iter()
+            .find(|t| t.tool_id == tool_id && t.profile_name == profile_name)
+            .ok_or_else(|| anyhow::anyhow!("Profile 不存在: {}/{}", tool_id, profile_name))?;
+        let client = crate::http_client::build_client().map_err(|e| anyhow::anyhow!(e))?;
+        let health = run_check(&client, target).await;
+        HEALTH_CACHE
+            .write()
+            .await
+            .insert(cache_key(tool_id, profile_name), health.clone());
+        Ok(health)
+    }
+
+    /// 并发检查所有 Profile
+    ///
+    /// `force` 为 false 时，有效期内的缓存结果直接复用，只检查其余 Profile。
+    pub async fn check_all_profiles(&self, force: bool) -> Result<ProfileHealthMatrix> {
+        let targets = collect_targets(self)?;
+        let now = chrono::Utc::now().timestamp_millis();
+
+        let mut results = Vec::new();
+        let mut pending = Vec::new();
+        {
+            let cache = HEALTH_CACHE.read().await;
+            for target in targets {
+                let cached = cache
+                    .get(&cache_key(&target.tool_id, &target.profile_name))
+                    .filter(|h| !force && now - h.checked_at < CACHE_TTL_SECS * 1000)
+                    .filter(|h| h.base_url == target.base_url);
+                match cached {
+                    Some(health) => results.push(health.clone()),
+                    None => pending.push(target),
+                }
+            }
+        }
+        let cached = results.len();
+
+        if !pending.is_empty() {
+            let client = crate::http_client::build_client().map_err(|e| anyhow::anyhow!(e))?;
+            let checked: Vec<ProfileHealth> = stream::iter(pending)
+                .map(|target| run_check(&client, target))
+                .buffer_unordered(MAX_CONCURRENT_CHECKS)
+                .collect()
+                .await;
+
+            let mut cache = HEALTH_CACHE.write().await;
+            for health in &checked {
+                cache.insert(
+                    cache_key(&health.tool_id, &health.profile_name),
+                    health.clone(),
+                );
+            }
+            results.extend(checked);
+        }
+
+        let matrix = ProfileHealthMatrix::from_results(results, cached);
+        tracing::info!(
+            total = matrix.total,
+            healthy = matrix.healthy,
+            auth_failed = matrix.auth_failed,
+            unreachable = matrix.unreachable,
+            cached = matrix.cached,
+            "Profile 批量健康检查完成"
+        );
+        Ok(matrix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_probe_request() {
+        let (url, headers) = probe_request("codex", "https://api.example.com/v1/", "sk");
+        assert_eq!(url, "https://api.example.com/v1/models");
+        assert_eq!(headers, vec![("authorization", "Bearer sk".to_string())]);
+
+        let (url, _) = probe_request("claude-code", "https://api.example.com", "sk");
+        assert_eq!(url, "https://api.example.com/v1/models");
+
+        let (url, headers) = probe_request("gemini-cli", "https://g.example.com", "gk");
+        assert_eq!(url, "https://g.example.com/v1beta/models");
+        assert_eq!(headers[0].0, "x-goog-api-key");
+    }
+
+    #[test]
+    fn test_matrix_summary() {
+        let health = |name: &str, reachable: bool, auth: AuthStatus| ProfileHealth {
+            tool_id: "codex".to_string(),
+            profile_name: name.to_string(),
+            base_url: "https://api.example.com".to_string(),
+            reachable,
+            latency_ms: reachable.then_some(120),
+            auth,
+            status_code: None,
+            error: None,
+            checked_at: 0,
+        };
+        let matrix = ProfileHealthMatrix::from_results(
+            vec![
+                health("c", true, auth_from_status(200)),
+                health("a", true, auth_from_status(401)),
+                health("b", false, AuthStatus::Unchecked),
+                health("d", true, auth_from_status(404)),
+            ],
+            1,
+        );
+        assert_eq!(
+            (
+                matrix.total,
+                matrix.healthy,
+                matrix.auth_failed,
+                matrix.unreachable
+            ),
+            (4, 1, 1, 1)
+        );
+        assert_eq!(matrix.results[0].profile_name, "a");
+        assert_eq!(matrix.results[3].auth, AuthStatus::Unknown);
+    }
+}
@@ -1,12 +1,16 @@
 //! ProfileManager 核心实现（v2.1 - 简化版）
 
 use super::types::*;
+use crate::core::secrets::SecretStore;
+use crate::data::compat;
 use crate::data::DataManager;
+use crate::services::audit::{self, AuditAction};
 use anyhow::{anyhow, Context, Result};
 use chrono::Utc;
 use fs2::FileExt;
+use serde_json::json;
 use std::fs::File;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// 系统保留的 Profile 名称前缀
 const RESERVED_PREFIX: &str = "dc_proxy_";
@@ -22,10 +26,39 @@ fn validate_profile_name(name: &str) -> Result<()> {
     Ok(())
 }
 
+/// 记录 Profile 保存审计（只记录哪些字段变化，不记录 API Key）
+fn audit_profile_saved(
+    audit_dir: &Path,
+    tool_id: &str,
+    name: &str,
+    previous: Option<(String, String)>,
+    api_key: &str,
+    base_url: &str,
+) {
+    let details = match previous {
+        None => json!({ "created": true, "base_url": base_url }),
+        Some((old_key, old_url)) => json!({
+            "created": false,
+            "api_key_changed": old_key != api_key,
+            "base_url_changed": old_url != base_url,
+            "base_url": base_url,
+        }),
+    };
+    audit::record_in(
+        audit_dir,
+        AuditAction::ProfileSaved,
+        tool_id,
+        Some(name),
+        details,
+    );
+}
+
 pub struct ProfileManager {
     data_manager: DataManager,
     profiles_path: PathBuf,
     active_path: PathBuf,
+    /// 审计日志目录
+    audit_dir: PathBuf,
 }
 
 impl ProfileManager {
@@ -38,6 +71,7 @@ impl ProfileManager {
             data_manager: DataManager::new(),
             profiles_path: duckcoding_dir.join("profiles.json"),
             active_path: duckcoding_dir.join("active.json"),
+            audit_dir: audit::audit_dir()?,
         })
     }
 
@@ -46,7 +80,15 @@ impl ProfileManager {
             return Ok(ProfilesStore::new());
         }
         let value = self.data_manager.json().read(&self.profiles_path)?;
-        serde_json::from_value(value).context("反序列化 ProfilesStore 失败")
+        let (mut store, _): (ProfilesStore, _) =
+            compat::from_value_tolerant(value).context("反序列化 ProfilesStore 失败")?;
+        SecretStore::global().reveal_fields(&mut store);
+        Ok(store)
+    }
+
+    /// profiles.json 路径
+    pub fn profiles_path(&self) -> &Path {
+        &self.profiles_path
     }
 
     pub fn save_profiles_store(&self, store: &ProfilesStore) -> Result<()> {
@@ -57,11 +99,21 @@ impl ProfileManager {
         // 获取排他锁（阻塞等待其他写操作完成）
         lock_file.lock_exclusive().context("获取文件锁失败")?;
 
-        // 执行写入（受锁保护）
-        let value = serde_json::to_value(store)?;
+        // 执行写入（受锁保护）：API Key 存入密钥存储，文件中只保留引用；保留其他版本写入的未知字段
+        let secrets = SecretStore::global();
+        let mut sealed = store.clone();
+        secrets.seal_fields(&mut sealed)?;
+        let mut value = serde_json::to_value(&sealed)?;
+        let existing = self.data_manager.json().read(&self.profiles_path).ok();
+        if let Some(existing) = &existing {
+            compat::preserve_fields::<ProfilesStore>(existing, &mut value);
+        }
         self.data_manager
             .json()
             .write(&self.profiles_path, &value)?;
+        if let Some(existing) = &existing {
+            secrets.prune(existing, &value);
+        }
 
         // 锁在 lock_file drop 时自动释放
         Ok(())
@@ -109,6 +161,10 @@ impl ProfileManager {
         validate_profile_name(name)?;
 
         let mut store = self.load_profiles_store()?;
+        let previous = store
+            .claude_code
+            .get(name)
+            .map(|p| (p.api_key.clone(), p.base_url.clone()));
 
         let profile = if let Some(existing) = store.claude_code.get_mut(name) {
             // 更新模式：只更新非空字段
@@ -139,9 +195,17 @@ impl ProfileManager {
             }
         };
 
-        store.claude_code.insert(name.to_string(), profile);
+        store.claude_code.insert(name.to_string(), profile.clone());
         store.metadata.last_updated = Utc::now();
         self.save_profiles_store(&store)?;
+        audit_profile_saved(
+            &self.audit_dir,
+            "claude-code",
+            name,
+            previous,
+            &profile.api_key,
+            &profile.base_url,
+        );
 
         // 如果当前 profile 已激活，自动重新应用配置
         let active_store = self.load_active_store()?;
@@ -208,6 +272,10 @@ impl ProfileManager {
         validate_profile_name(name)?;
 
         let mut store = self.load_profiles_store()?;
+        let previous = store
+            .codex
+            .get(name)
+            .map(|p| (p.api_key.clone(), p.base_url.clone()));
 
         let profile = if let Some(existing) = store.codex.get_mut(name) {
             // 更新模式：只更新非空字段
@@ -242,9 +310,17 @@ impl ProfileManager {
             }
         };
 
-        store.codex.insert(name.to_string(), profile);
+        store.codex.insert(name.to_string(), profile.clone());
         store.metadata.last_updated = Utc::now();
         self.save_profiles_store(&store)?;
+        audit_profile_saved(
+            &self.audit_dir,
+            "codex",
+            name,
+            previous,
+            &profile.api_key,
+            &profile.base_url,
+        );
 
         // 如果当前 profile 已激活，自动重新应用配置
         let active_store = self.load_active_store()?;
@@ -311,6 +387,10 @@ impl ProfileManager {
         validate_profile_name(name)?;
 
         let mut store = self.load_profiles_store()?;
+        let previous = store
+            .gemini_cli
+            .get(name)
+            .map(|p| (p.api_key.clone(), p.base_url.clone()));
 
         let profile = if let Some(existing) = store.gemini_cli.get_mut(name) {
             // 更新模式：只更新非空字段
@@ -347,9 +427,17 @@ impl ProfileManager {
             }
         };
 
-        store.gemini_cli.insert(name.to_string(), profile);
+        store.gemini_cli.insert(name.to_string(), profile.clone());
         store.metadata.last_updated = Utc::now();
         self.save_profiles_store(&store)?;
+        audit_profile_saved(
+            &self.audit_dir,
+            "gemini-cli",
+            name,
+            previous,
+            &profile.api_key,
+            &profile.base_url,
+        );
 
         // 如果当前 profile 已激活，自动重新应用配置
         let active_store = self.load_active_store()?;
@@ -372,6 +460,41 @@ impl ProfileManager {
             .ok_or_else(|| anyhow!("Gemini Profile 不存在: {}", name))
     }
 
+    /// 读取 Profile 的上游信息：(API Key, Base URL, 价格模板 ID)
+    pub fn get_profile_upstream(
+        &self,
+        tool_id: &str,
+        name: &str,
+    ) -> Result<(String, String, Option<String>)> {
+        match tool_id {
+            "claude-code" => {
+                let profile = self.get_claude_profile(name)?;
+                Ok((
+                    profile.api_key,
+                    profile.base_url,
+                    profile.pricing_template_id,
+                ))
+            }
+            "codex" => {
+                let profile = self.get_codex_profile(name)?;
+                Ok((
+                    profile.api_key,
+                    profile.base_url,
+                    profile.pricing_template_id,
+                ))
+            }
+            "gemini-cli" => {
+                let profile = self.get_gemini_profile(name)?;
+                Ok((
+                    profile.api_key,
+                    profile.base_url,
+                    profile.pricing_template_id,
+                ))
+            }
+            _ => Err(anyhow!("不支持的工具: {}", tool_id)),
+        }
+    }
+
     pub fn delete_gemini_profile(&self, name: &str) -> Result<()> {
         let mut store = self.load_profiles_store()?;
         store.gemini_cli.remove(name);
@@ -480,6 +603,13 @@ impl ProfileManager {
 
         // 应用到原生配置文件
         self.apply_to_native(tool_id, profile_name)?;
+        audit::record_in(
+            &self.audit_dir,
+            AuditAction::ProfileActivated,
+            tool_id,
+            Some(profile_name),
+            json!({}),
+        );
 
         // 读取应用后的配置并保存快照（为每个工具读取所有配置文件）
         let tool = crate::models::Tool::by_id(tool_id)
@@ -800,7 +930,15 @@ impl ProfileManager {
             "codex" => self.delete_codex_profile(name),
             "gemini-cli" => self.delete_gemini_profile(name),
             _ => Err(anyhow!("不支持的工具 ID: {}", tool_id)),
-        }
+        }?;
+        audit::record_in(
+            &self.audit_dir,
+            AuditAction::ProfileDeleted,
+            tool_id,
+            Some(name),
+            json!({}),
+        );
+        Ok(())
     }
 
     // ==================== 快照管理 ====================
@@ -938,6 +1076,7 @@ mod tests {
             data_manager: DataManager::new(),
             profiles_path: temp_dir.path().join("profiles.json"),
             active_path: temp_dir.path().join("active.json"),
+            audit_dir: temp_dir.path().join("audit"),
         }
     }
 
@@ -1101,4 +1240,52 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_export_native_snippet() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = test_manager(&temp_dir);
+        manager.save_claude_profile(
+            "work",
+            "sk-ant-secret".to_string(),
+            "https://a.example".to_string(),
+        )?;
+        manager.save_codex_profile(
+            "team.proxy",
+            "sk-codex-secret".to_string(),
+            "https://c.example/".to_string(),
+            Some("responses".to_string()),
+        )?;
+        manager.save_gemini_profile(
+            "g",
+            "gm-secret".to_string(),
+            "https://g.example".to_string(),
+            Some("gemini-2.5-pro".to_string()),
+        )?;
+
+        let claude = manager.export_native_snippet("claude-code", "work", false)?;
+        let value: serde_json::Value = serde_json::from_str(&claude.content)?;
+        assert_eq!(value["env"]["ANTHROPIC_AUTH_TOKEN"], "sk-ant-secret");
+        assert_eq!(value["env"]["ANTHROPIC_BASE_URL"], "https://a.example");
+
+        let codex = manager.export_native_snippet("codex", "team.proxy", true)?;
+        assert!(codex.redacted && !codex.content.contains("sk-codex-secret"));
+        let doc: toml::Value = toml::from_str(&codex.content)?;
+        assert_eq!(doc["model_provider"].as_str(), Some("team.proxy"));
+        assert_eq!(
+            doc["model_providers"]["team.proxy"]["base_url"].as_str(),
+            Some("https://c.example/v1")
+        );
+
+        let gemini = manager.export_native_snippet("gemini-cli", "g", true)?;
+        assert_eq!(
+            gemini.content,
+            "GEMINI_API_KEY=<YOUR_API_KEY>\nGOOGLE_GEMINI_BASE_URL=https://g.example\nGEMINI_MODEL=gemini-2.5-pro\n"
+        );
+
+        // 审计记录写入测试目录
+        let audit = std::fs::read_to_string(temp_dir.path().join("audit/audit.jsonl"))?;
+        assert_eq!(audit.lines().count(), 3);
+        Ok(())
+    }
 }
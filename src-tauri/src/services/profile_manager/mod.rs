@@ -4,13 +4,19 @@
 //! - profiles.json: 使用具体类型（ClaudeProfile/CodexProfile/GeminiProfile）
 //! - active.json: 激活状态管理
 
+pub mod credentials;
+mod drift;
+pub mod health;
 mod manager;
 mod native_config;
 pub mod types;
 
+pub use credentials::{CredentialQuota, CredentialValidation};
+pub use drift::{ConfigDriftReport, DriftField};
+pub use health::{AuthStatus, ProfileHealth, ProfileHealthMatrix};
 pub use manager::ProfileManager;
 pub use types::{
     ActiveMetadata, ActiveProfile, ActiveStore, AmpProfileSelection, ClaudeProfile, CodexProfile,
-    GeminiProfile, ProfileDescriptor, ProfileRef, ProfileSource, ProfilesMetadata, ProfilesStore,
-    TokenImportStatus,
+    GeminiProfile, NativeConfigSnippet, ProfileDescriptor, ProfileRef, ProfileSource,
+    ProfilesMetadata, ProfilesStore, TokenImportStatus,
 };
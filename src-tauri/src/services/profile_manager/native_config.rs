@@ -5,32 +5,64 @@ use crate::data::DataManager;
 use crate::models::tool::Tool;
 use anyhow::{anyhow, Result};
 use serde_json::{Map, Value};
+use std::path::Path;
 use toml_edit;
 
+/// 导出时替换 API Key 的占位符
+const API_KEY_PLACEHOLDER: &str = "<YOUR_API_KEY>";
+
 impl super::manager::ProfileManager {
     /// 将 Profile 应用到原生配置文件
     pub fn apply_profile_to_native(&self, tool_id: &str, profile_name: &str) -> Result<()> {
         let tool = Tool::by_id(tool_id).ok_or_else(|| anyhow!("未找到工具: {}", tool_id))?;
+        self.apply_profile_to_tool(&tool, profile_name)?;
+
+        tracing::info!("已应用 Profile: {} / {}", tool_id, profile_name);
+        Ok(())
+    }
 
+    /// 将 Profile 写入指定配置目录（如 WSL 发行版内的 `\\wsl$\...` 路径）
+    ///
+    /// 不修改激活状态，本机原生配置保持不变
+    pub fn apply_profile_to_config_dir(
+        &self,
+        tool_id: &str,
+        profile_name: &str,
+        config_dir: &Path,
+    ) -> Result<()> {
+        let mut tool = Tool::by_id(tool_id).ok_or_else(|| anyhow!("未找到工具: {}", tool_id))?;
+        tool.config_dir = config_dir.to_path_buf();
+        std::fs::create_dir_all(config_dir)
+            .map_err(|e| anyhow!("创建配置目录 {} 失败: {}", config_dir.display(), e))?;
+        self.apply_profile_to_tool(&tool, profile_name)?;
+
+        tracing::info!(
+            "已应用 Profile: {} / {} -> {}",
+            tool_id,
+            profile_name,
+            config_dir.display()
+        );
+        Ok(())
+    }
+
+    fn apply_profile_to_tool(&self, tool: &Tool, profile_name: &str) -> Result<()> {
+        let tool_id = tool.id.as_str();
         match tool_id {
             "claude-code" => {
                 let profile = self.get_claude_profile(profile_name)?;
-                apply_claude_native(&tool, &profile)?;
+                apply_claude_native(tool, &profile)
             }
             "codex" => {
                 let profile = self.get_codex_profile(profile_name)?;
                 // 使用 profile_name 作为 provider 名称
-                apply_codex_native(&tool, &profile, profile_name)?;
+                apply_codex_native(tool, &profile, profile_name)
             }
             "gemini-cli" => {
                 let profile = self.get_gemini_profile(profile_name)?;
-                apply_gemini_native(&tool, &profile)?;
+                apply_gemini_native(tool, &profile)
             }
-            _ => return Err(anyhow!("不支持的工具: {}", tool_id)),
+            _ => Err(anyhow!("不支持的工具: {}", tool_id)),
         }
-
-        tracing::info!("已应用 Profile: {} / {}", tool_id, profile_name);
-        Ok(())
     }
 
     /// 从原生配置捕获 Profile
@@ -56,6 +88,85 @@ impl super::manager::ProfileManager {
         tracing::info!("已捕获 Profile: {} / {}", tool_id, profile_name);
         Ok(())
     }
+
+    /// 将 Profile 导出为工具原生格式的配置片段，供未安装 DuckCoding 的成员直接使用
+    ///
+    /// `redact_keys` 为 true 时 API Key 替换为占位符
+    pub fn export_native_snippet(
+        &self,
+        tool_id: &str,
+        profile_name: &str,
+        redact_keys: bool,
+    ) -> Result<NativeConfigSnippet> {
+        let key = |api_key: &str| {
+            if redact_keys {
+                API_KEY_PLACEHOLDER.to_string()
+            } else {
+                api_key.to_string()
+            }
+        };
+
+        let (file_name, format, content) = match tool_id {
+            "claude-code" => {
+                let profile = self.get_claude_profile(profile_name)?;
+                (
+                    "settings.json",
+                    "json",
+                    render_claude_snippet(&profile, &key(&profile.api_key))?,
+                )
+            }
+            "codex" => {
+                let profile = self.get_codex_profile(profile_name)?;
+                (
+                    "config.toml",
+                    "toml",
+                    render_codex_snippet(&profile, profile_name, &key(&profile.api_key)),
+                )
+            }
+            "gemini-cli" => {
+                let profile = self.get_gemini_profile(profile_name)?;
+                (
+                    ".env",
+                    "dotenv",
+                    render_gemini_snippet(&profile, &key(&profile.api_key)),
+                )
+            }
+            _ => return Err(anyhow!("不支持的工具: {}", tool_id)),
+        };
+
+        Ok(NativeConfigSnippet {
+            tool_id: tool_id.to_string(),
+            profile_name: profile_name.to_string(),
+            file_name: file_name.to_string(),
+            format: format.to_string(),
+            content,
+            redacted: redact_keys,
+        })
+    }
+
+    /// 读取原生配置中当前生效的 Base URL（未配置时返回空字符串）
+    pub fn read_native_base_url(&self, tool_id: &str) -> Result<String> {
+        let tool = Tool::by_id(tool_id).ok_or_else(|| anyhow!("未找到工具: {}", tool_id))?;
+
+        match tool_id {
+            "claude-code" => capture_claude_config(&tool).map(|(_, base_url)| base_url),
+            "codex" => capture_codex_config(&tool).map(|(_, base_url, _)| base_url),
+            "gemini-cli" => capture_gemini_config(&tool).map(|(_, base_url, _)| base_url),
+            _ => Err(anyhow!("不支持的工具: {}", tool_id)),
+        }
+    }
+
+    /// 读取原生配置中当前生效的 API Key 与 Base URL（未配置时返回空字符串）
+    pub fn read_native_credentials(&self, tool_id: &str) -> Result<(String, String)> {
+        let tool = Tool::by_id(tool_id).ok_or_else(|| anyhow!("未找到工具: {}", tool_id))?;
+
+        match tool_id {
+            "claude-code" => capture_claude_config(&tool),
+            "codex" => capture_codex_config(&tool).map(|(key, base_url, _)| (key, base_url)),
+            "gemini-cli" => capture_gemini_config(&tool).map(|(key, base_url, _)| (key, base_url)),
+            _ => Err(anyhow!("不支持的工具: {}", tool_id)),
+        }
+    }
 }
 
 // ==================== Claude Code ====================
@@ -81,19 +192,32 @@ fn apply_claude_native(tool: &Tool, profile: &ClaudeProfile) -> Result<()> {
         .get_mut("env")
         .and_then(|v| v.as_object_mut())
         .ok_or_else(|| anyhow!("Claude 配置缺少 env 字段或格式错误"))?;
-    env.insert(
-        "ANTHROPIC_AUTH_TOKEN".to_string(),
-        Value::String(profile.api_key.clone()),
-    );
-    env.insert(
-        "ANTHROPIC_BASE_URL".to_string(),
-        Value::String(profile.base_url.clone()),
-    );
+    for (key, value) in claude_env(profile, &profile.api_key) {
+        env.insert(key.to_string(), Value::String(value));
+    }
 
     manager.json_uncached().write(&settings_path, &settings)?;
     Ok(())
 }
 
+/// Profile 写入 settings.json `env` 的字段
+pub(super) fn claude_env(profile: &ClaudeProfile, api_key: &str) -> [(&'static str, String); 2] {
+    [
+        ("ANTHROPIC_AUTH_TOKEN", api_key.to_string()),
+        ("ANTHROPIC_BASE_URL", profile.base_url.clone()),
+    ]
+}
+
+fn render_claude_snippet(profile: &ClaudeProfile, api_key: &str) -> Result<String> {
+    let env: Map<String, Value> = claude_env(profile, api_key)
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), Value::String(value)))
+        .collect();
+    Ok(serde_json::to_string_pretty(
+        &serde_json::json!({ "env": env }),
+    )?)
+}
+
 fn capture_claude_config(tool: &Tool) -> Result<(String, String)> {
     let manager = DataManager::new();
     let settings_path = tool.config_dir.join("settings.json");
@@ -147,13 +271,7 @@ fn apply_codex_native(tool: &Tool, profile: &CodexProfile, provider_name: &str)
     // 设置 model_provider 为 profile_name
     root_table.insert("model_provider", toml_edit::value(provider_name));
 
-    // 处理 base_url
-    let normalized = profile.base_url.trim_end_matches('/');
-    let base_url_with_v1 = if normalized.ends_with("/v1") {
-        normalized.to_string()
-    } else {
-        format!("{}/v1", normalized)
-    };
+    let base_url_with_v1 = codex_base_url(&profile.base_url);
 
     // 创建或更新 model_providers 表
     if !root_table.contains_key("model_providers") {
@@ -169,14 +287,10 @@ fn apply_codex_native(tool: &Tool, profile: &CodexProfile, provider_name: &str)
 
     // 检查或创建 provider
     if !providers_table.contains_key(provider_name) {
-        let mut table = toml_edit::Table::new();
-        table.set_implicit(false);
-        // 初始化所有必要字段
-        table.insert("name", toml_edit::value(provider_name));
-        table.insert("base_url", toml_edit::value(&base_url_with_v1));
-        table.insert("wire_api", toml_edit::value(&profile.wire_api));
-        table.insert("requires_openai_auth", toml_edit::value(true));
-        providers_table.insert(provider_name, toml_edit::Item::Table(table));
+        providers_table.insert(
+            provider_name,
+            toml_edit::Item::Table(codex_provider_table(profile, provider_name)),
+        );
         tracing::info!("创建新的 Codex provider: {}", provider_name);
     } else {
         // provider 已存在，检查是否需要更新
@@ -195,10 +309,9 @@ fn apply_codex_native(tool: &Tool, profile: &CodexProfile, provider_name: &str)
             .unwrap_or("");
 
         if current_base_url != base_url_with_v1 || current_wire_api != profile.wire_api {
-            provider_table.insert("name", toml_edit::value(provider_name));
-            provider_table.insert("base_url", toml_edit::value(&base_url_with_v1));
-            provider_table.insert("wire_api", toml_edit::value(&profile.wire_api));
-            provider_table.insert("requires_openai_auth", toml_edit::value(true));
+            for (key, item) in codex_provider_table(profile, provider_name).iter() {
+                provider_table.insert(key, item.clone());
+            }
             tracing::info!("更新 Codex provider 配置: {}", provider_name);
         }
     }
@@ -223,6 +336,47 @@ fn apply_codex_native(tool: &Tool, profile: &CodexProfile, provider_name: &str)
     Ok(())
 }
 
+/// 规范化 Codex base_url（补齐 `/v1` 后缀）
+pub(super) fn codex_base_url(base_url: &str) -> String {
+    let normalized = base_url.trim_end_matches('/');
+    if normalized.ends_with("/v1") {
+        normalized.to_string()
+    } else {
+        format!("{}/v1", normalized)
+    }
+}
+
+/// Profile 对应的 `[model_providers.<name>]` 表
+fn codex_provider_table(profile: &CodexProfile, provider_name: &str) -> toml_edit::Table {
+    let mut table = toml_edit::Table::new();
+    table.set_implicit(false);
+    table.insert("name", toml_edit::value(provider_name));
+    table.insert(
+        "base_url",
+        toml_edit::value(codex_base_url(&profile.base_url)),
+    );
+    table.insert("wire_api", toml_edit::value(&profile.wire_api));
+    table.insert("requires_openai_auth", toml_edit::value(true));
+    table
+}
+
+/// config.toml 片段；API Key 位于 auth.json，以注释形式给出
+fn render_codex_snippet(profile: &CodexProfile, provider_name: &str, api_key: &str) -> String {
+    let mut doc = toml_edit::DocumentMut::new();
+    doc.insert("model_provider", toml_edit::value(provider_name));
+
+    let mut providers = toml_edit::Table::new();
+    providers.set_implicit(true);
+    providers.insert(
+        provider_name,
+        toml_edit::Item::Table(codex_provider_table(profile, provider_name)),
+    );
+    doc.insert("model_providers", toml_edit::Item::Table(providers));
+
+    let auth = serde_json::json!({ "OPENAI_API_KEY": api_key });
+    format!("# ~/.codex/auth.json: {auth}\n{doc}")
+}
+
 fn capture_codex_config(tool: &Tool) -> Result<(String, String, String)> {
     let manager = DataManager::new();
     let config_path = tool.config_dir.join("config.toml");
@@ -267,19 +421,30 @@ fn apply_gemini_native(tool: &Tool, profile: &GeminiProfile) -> Result<()> {
     let manager = DataManager::new();
     let env_path = tool.config_dir.join(".env");
 
-    manager
-        .env()
-        .set(&env_path, "GEMINI_API_KEY", &profile.api_key)?;
-    manager
-        .env()
-        .set(&env_path, "GOOGLE_GEMINI_BASE_URL", &profile.base_url)?;
+    for (key, value) in gemini_env(profile, &profile.api_key) {
+        manager.env().set(&env_path, key, &value)?;
+    }
 
-    // 只在 model 有值时才写入
+    Ok(())
+}
+
+/// Profile 写入 .env 的变量（只在 model 有值时才写入 GEMINI_MODEL）
+pub(super) fn gemini_env(profile: &GeminiProfile, api_key: &str) -> Vec<(&'static str, String)> {
+    let mut env = vec![
+        ("GEMINI_API_KEY", api_key.to_string()),
+        ("GOOGLE_GEMINI_BASE_URL", profile.base_url.clone()),
+    ];
     if let Some(ref model) = profile.model {
-        manager.env().set(&env_path, "GEMINI_MODEL", model)?;
+        env.push(("GEMINI_MODEL", model.clone()));
     }
+    env
+}
 
-    Ok(())
+fn render_gemini_snippet(profile: &GeminiProfile, api_key: &str) -> String {
+    gemini_env(profile, api_key)
+        .into_iter()
+        .map(|(key, value)| format!("{key}={value}\n"))
+        .collect()
 }
 
 fn capture_gemini_config(tool: &Tool) -> Result<(String, String, String)> {
@@ -2,6 +2,7 @@
 //!
 //! 设计原则：工具分组即类型，使用具体结构体替代 enum
 
+use crate::core::secrets::SecretFields;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -189,6 +190,20 @@ impl Default for ProfilesStore {
     }
 }
 
+impl SecretFields for ProfilesStore {
+    fn visit_secrets(&mut self, visit: &mut dyn FnMut(&str, &mut String)) {
+        for (name, profile) in self.claude_code.iter_mut() {
+            visit(&format!("profile/claude-code/{name}"), &mut profile.api_key);
+        }
+        for (name, profile) in self.codex.iter_mut() {
+            visit(&format!("profile/codex/{name}"), &mut profile.api_key);
+        }
+        for (name, profile) in self.gemini_cli.iter_mut() {
+            visit(&format!("profile/gemini-cli/{name}"), &mut profile.api_key);
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProfilesMetadata {
     pub last_updated: DateTime<Utc>,
@@ -407,9 +422,25 @@ impl ProfileDescriptor {
     }
 }
 
+// ==================== 原生格式导出 ====================
+
+/// 工具原生格式的配置片段（分享给未安装 DuckCoding 的成员）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NativeConfigSnippet {
+    pub tool_id: String,
+    pub profile_name: String,
+    /// 片段对应的原生配置文件名（settings.json / config.toml / .env）
+    pub file_name: String,
+    /// 片段格式：`json` / `toml` / `dotenv`
+    pub format: String,
+    pub content: String,
+    /// API Key 是否已替换为占位符
+    pub redacted: bool,
+}
+
 // ==================== 辅助函数 ====================
 
-fn mask_api_key(key: &str) -> String {
+pub(super) fn mask_api_key(key: &str) -> String {
     if key.len() <= 8 {
         return "****".to_string();
     }
@@ -0,0 +1,280 @@
+//! 项目技术栈识别
+//!
+//! 只读取仓库根目录的清单文件（package.json、Cargo.toml、pyproject.toml 等），
+//! 不遍历源码，识别语言、框架以及是否为 monorepo。
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+
+/// 识别出的项目技术栈
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProjectStack {
+    /// 语言（如 "typescript"、"rust"、"python"）
+    pub languages: Vec<String>,
+    /// 框架（如 "react"、"tauri"、"django"）
+    pub frameworks: Vec<String>,
+    /// 是否为 monorepo（workspace 配置或多个子包）
+    pub is_monorepo: bool,
+    /// 命中的清单文件
+    pub markers: Vec<String>,
+}
+
+impl ProjectStack {
+    fn add(list: &mut Vec<String>, value: &str) {
+        if !list.iter().any(|v| v == value) {
+            list.push(value.to_string());
+        }
+    }
+
+    fn language(&mut self, value: &str) {
+        Self::add(&mut self.languages, value);
+    }
+
+    fn framework(&mut self, value: &str) {
+        Self::add(&mut self.frameworks, value);
+    }
+}
+
+/// JS 依赖名 → 框架
+const JS_FRAMEWORKS: [(&str, &str); 10] = [
+    ("next", "nextjs"),
+    ("react", "react"),
+    ("nuxt", "nuxt"),
+    ("vue", "vue"),
+    ("svelte", "svelte"),
+    ("@angular/core", "angular"),
+    ("express", "express"),
+    ("electron", "electron"),
+    ("@tauri-apps/api", "tauri"),
+    ("@nestjs/core", "nestjs"),
+];
+
+/// Rust 依赖名 → 框架
+const RUST_FRAMEWORKS: [(&str, &str); 5] = [
+    ("tauri", "tauri"),
+    ("axum", "axum"),
+    ("actix-web", "actix-web"),
+    ("bevy", "bevy"),
+    ("leptos", "leptos"),
+];
+
+/// Python 依赖名 → 框架
+const PYTHON_FRAMEWORKS: [(&str, &str); 5] = [
+    ("django", "django"),
+    ("fastapi", "fastapi"),
+    ("flask", "flask"),
+    ("torch", "pytorch"),
+    ("langchain", "langchain"),
+];
+
+/// JS monorepo 标记文件
+const JS_WORKSPACE_MARKERS: [&str; 4] =
+    ["pnpm-workspace.yaml", "lerna.json", "turbo.json", "nx.json"];
+
+/// 常见子包目录（包含多个带清单的子目录时视为 monorepo）
+const PACKAGE_DIRS: [&str; 3] = ["packages", "apps", "crates"];
+
+/// 子目录清单文件
+const MANIFESTS: [&str; 4] = ["package.json", "Cargo.toml", "pyproject.toml", "go.mod"];
+
+/// 识别项目技术栈
+pub fn detect_stack(root: &Path) -> Result<ProjectStack> {
+    if !root.is_dir() {
+        bail!("项目目录不存在: {}", root.display());
+    }
+
+    let mut stack = ProjectStack::default();
+
+    if let Some(content) = read_marker(root, "package.json", &mut stack) {
+        detect_package_json(root, &content, &mut stack);
+    }
+    if let Some(content) = read_marker(root, "Cargo.toml", &mut stack) {
+        detect_cargo_toml(&content, &mut stack);
+    }
+    if let Some(content) = read_marker(root, "pyproject.toml", &mut stack) {
+        stack.language("python");
+        detect_python_deps(&content, &mut stack);
+    }
+    if let Some(content) = read_marker(root, "requirements.txt", &mut stack) {
+        stack.language("python");
+        detect_python_deps(&content, &mut stack);
+    }
+    if read_marker(root, "go.mod", &mut stack).is_some() {
+        stack.language("go");
+    }
+    // Tauri 等项目的 Rust 部分位于子目录
+    if let Ok(content) = std::fs::read_to_string(root.join("src-tauri").join("Cargo.toml")) {
+        ProjectStack::add(&mut stack.markers, "src-tauri/Cargo.toml");
+        detect_cargo_toml(&content, &mut stack);
+    }
+
+    for marker in JS_WORKSPACE_MARKERS {
+        if root.join(marker).exists() {
+            ProjectStack::add(&mut stack.markers, marker);
+            stack.is_monorepo = true;
+        }
+    }
+    if count_sub_packages(root) >= 2 {
+        stack.is_monorepo = true;
+    }
+
+    Ok(stack)
+}
+
+/// 读取根目录清单文件并记录命中
+fn read_marker(root: &Path, name: &str, stack: &mut ProjectStack) -> Option<String> {
+    let content = std::fs::read_to_string(root.join(name)).ok()?;
+    ProjectStack::add(&mut stack.markers, name);
+    Some(content)
+}
+
+fn detect_package_json(root: &Path, content: &str, stack: &mut ProjectStack) {
+    let Ok(json) = serde_json::from_str::<Value>(content) else {
+        stack.language("javascript");
+        return;
+    };
+
+    let has_dep = |name: &str| {
+        ["dependencies", "devDependencies", "peerDependencies"]
+            .iter()
+            .any(|section| json.get(section).and_then(|deps| deps.get(name)).is_some())
+    };
+
+    if has_dep("typescript") || root.join("tsconfig.json").exists() {
+        stack.language("typescript");
+    } else {
+        stack.language("javascript");
+    }
+    for (dep, framework) in JS_FRAMEWORKS {
+        if has_dep(dep) {
+            stack.framework(framework);
+        }
+    }
+    if json.get("workspaces").is_some() {
+        stack.is_monorepo = true;
+    }
+}
+
+fn detect_cargo_toml(content: &str, stack: &mut ProjectStack) {
+    stack.language("rust");
+    let Ok(doc) = content.parse::<toml::Table>() else {
+        return;
+    };
+
+    if doc.contains_key("workspace") {
+        stack.is_monorepo = true;
+    }
+    let has_dep = |name: &str| {
+        ["dependencies", "dev-dependencies", "build-dependencies"]
+            .iter()
+            .any(|section| {
+                doc.get(*section)
+                    .and_then(|deps| deps.as_table())
+                    .is_some_and(|deps| deps.contains_key(name))
+            })
+    };
+    for (dep, framework) in RUST_FRAMEWORKS {
+        if has_dep(dep) {
+            stack.framework(framework);
+        }
+    }
+}
+
+/// 在依赖清单文本中查找 Python 框架（兼容 pyproject 与 requirements 的各种写法）
+fn detect_python_deps(content: &str, stack: &mut ProjectStack) {
+    let lower = content.to_lowercase();
+    for (dep, framework) in PYTHON_FRAMEWORKS {
+        let found = lower.lines().any(|line| {
+            let line = line.trim().trim_start_matches(['"', '\'']);
+            line.strip_prefix(dep).is_some_and(|rest| {
+                rest.is_empty()
+                    || rest.starts_with(|c: char| {
+                        matches!(
+                            c,
+                            '=' | '>' | '<' | '~' | '!' | '[' | ' ' | '"' | '\'' | ';'
+                        )
+                    })
+            })
+        });
+        if found {
+            stack.framework(framework);
+        }
+    }
+}
+
+/// 统计常见子包目录下带清单文件的子目录数
+fn count_sub_packages(root: &Path) -> usize {
+    PACKAGE_DIRS
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(root.join(dir)).ok())
+        .flat_map(|entries| entries.filter_map(|e| e.ok()))
+        .filter(|entry| {
+            let path = entry.path();
+            path.is_dir() && MANIFESTS.iter().any(|m| path.join(m).exists())
+        })
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_detect_typescript_monorepo() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("package.json"),
+            r#"{"workspaces":["packages/*"],"dependencies":{"react":"^19"},"devDependencies":{"typescript":"^5"}}"#,
+        )
+        .unwrap();
+
+        let stack = detect_stack(dir.path()).unwrap();
+        assert_eq!(stack.languages, vec!["typescript"]);
+        assert_eq!(stack.frameworks, vec!["react"]);
+        assert!(stack.is_monorepo);
+        assert_eq!(stack.markers, vec!["package.json"]);
+    }
+
+    #[test]
+    fn test_detect_tauri_and_python() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("package.json"),
+            r#"{"dependencies":{"@tauri-apps/api":"^2","vue":"^3"}}"#,
+        )
+        .unwrap();
+        fs::create_dir(dir.path().join("src-tauri")).unwrap();
+        fs::write(
+            dir.path().join("src-tauri").join("Cargo.toml"),
+            "[package]\nname = \"app\"\n\n[dependencies]\ntauri = { version = \"2\" }\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("requirements.txt"),
+            "fastapi>=0.110\nflask-cors==4.0\n",
+        )
+        .unwrap();
+
+        let stack = detect_stack(dir.path()).unwrap();
+        assert_eq!(stack.languages, vec!["javascript", "python", "rust"]);
+        assert_eq!(stack.frameworks, vec!["vue", "tauri", "fastapi"]);
+        assert!(!stack.is_monorepo);
+    }
+
+    #[test]
+    fn test_detect_sub_packages_as_monorepo() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["core", "cli"] {
+            let pkg = dir.path().join("crates").join(name);
+            fs::create_dir_all(&pkg).unwrap();
+            fs::write(pkg.join("Cargo.toml"), "[package]\nname = \"x\"\n").unwrap();
+        }
+
+        let stack = detect_stack(dir.path()).unwrap();
+        assert!(stack.is_monorepo);
+        assert!(stack.languages.is_empty());
+    }
+}
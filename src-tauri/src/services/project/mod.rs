@@ -0,0 +1,13 @@
+//! 项目管理
+//!
+//! - detect: 技术栈识别（清单文件）
+//! - registry: 项目注册表与默认配置建议
+
+pub mod detect;
+pub mod registry;
+
+pub use detect::{detect_stack, ProjectStack};
+pub use registry::{
+    default_rules, suggest, ProfileSuggestion, ProjectRecord, ProjectRegistry, RuleCondition,
+    StackRule,
+};
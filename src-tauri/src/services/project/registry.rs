@@ -0,0 +1,367 @@
+//! 项目注册表
+//!
+//! 注册项目时识别技术栈，并按可配置的映射规则生成 Profile / 模型建议，
+//! 建议随项目记录一起保存，供后续激活流程使用。
+//!
+//! - 项目记录：`~/.duckcoding/projects.json`
+//! - 映射规则：`~/.duckcoding/project_rules.json`（不存在时使用内置默认规则）
+
+use super::detect::{detect_stack, ProjectStack};
+use crate::data::DataManager;
+use crate::utils::config::config_dir;
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// 项目存储格式版本
+const PROJECTS_STORE_VERSION: u32 = 1;
+
+/// 规则匹配条件（所有已设置的条件都满足时命中）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleCondition {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub framework: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monorepo: Option<bool>,
+}
+
+impl RuleCondition {
+    fn matches(&self, stack: &ProjectStack) -> bool {
+        self.language
+            .as_ref()
+            .is_none_or(|lang| stack.languages.contains(lang))
+            && self
+                .framework
+                .as_ref()
+                .is_none_or(|fw| stack.frameworks.contains(fw))
+            && self.monorepo.is_none_or(|m| m == stack.is_monorepo)
+    }
+}
+
+/// 技术栈 → 默认配置映射规则
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StackRule {
+    #[serde(default)]
+    pub when: RuleCondition,
+    pub tool_id: String,
+    /// 建议激活的 Profile
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// 建议使用的模型
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// 建议原因（展示给用户）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// 单个工具的配置建议
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileSuggestion {
+    pub tool_id: String,
+    pub profile: Option<String>,
+    pub model: Option<String>,
+    /// 命中规则的原因
+    pub reasons: Vec<String>,
+}
+
+/// 项目记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectRecord {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    pub stack: ProjectStack,
+    pub suggestions: Vec<ProfileSuggestion>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ProjectsStore {
+    version: u32,
+    #[serde(default)]
+    projects: Vec<ProjectRecord>,
+}
+
+impl Default for ProjectsStore {
+    fn default() -> Self {
+        Self {
+            version: PROJECTS_STORE_VERSION,
+            projects: Vec::new(),
+        }
+    }
+}
+
+/// 内置默认规则：大型仓库建议长上下文模型
+pub fn default_rules() -> Vec<StackRule> {
+    vec![StackRule {
+        when: RuleCondition {
+            monorepo: Some(true),
+            ..Default::default()
+        },
+        tool_id: "claude-code".to_string(),
+        profile: None,
+        model: Some("sonnet[1m]".to_string()),
+        reason: Some("monorepo 代码量大，建议使用长上下文模型".to_string()),
+    }]
+}
+
+/// 按规则生成建议
+///
+/// 规则按顺序匹配，同一工具的 Profile / 模型以先命中的规则为准
+pub fn suggest(stack: &ProjectStack, rules: &[StackRule]) -> Vec<ProfileSuggestion> {
+    let mut suggestions: Vec<ProfileSuggestion> = Vec::new();
+
+    for rule in rules.iter().filter(|r| r.when.matches(stack)) {
+        let index = match suggestions.iter().position(|s| s.tool_id == rule.tool_id) {
+            Some(index) => index,
+            None => {
+                suggestions.push(ProfileSuggestion {
+                    tool_id: rule.tool_id.clone(),
+                    ..Default::default()
+                });
+                suggestions.len() - 1
+            }
+        };
+        let suggestion = &mut suggestions[index];
+        if suggestion.profile.is_none() {
+            suggestion.profile = rule.profile.clone();
+        }
+        if suggestion.model.is_none() {
+            suggestion.model = rule.model.clone();
+        }
+        if let Some(ref reason) = rule.reason {
+            suggestion.reasons.push(reason.clone());
+        }
+    }
+
+    suggestions
+}
+
+/// 项目注册表
+pub struct ProjectRegistry {
+    store_path: PathBuf,
+    rules_path: PathBuf,
+}
+
+impl ProjectRegistry {
+    /// 使用默认路径创建注册表
+    pub fn new() -> Result<Self> {
+        let dir = config_dir().map_err(|e| anyhow!("获取配置目录失败: {}", e))?;
+        Ok(Self::with_paths(
+            dir.join("projects.json"),
+            dir.join("project_rules.json"),
+        ))
+    }
+
+    /// 使用指定路径创建注册表
+    pub fn with_paths(store_path: PathBuf, rules_path: PathBuf) -> Self {
+        Self {
+            store_path,
+            rules_path,
+        }
+    }
+
+    /// 列出所有项目
+    pub fn list(&self) -> Result<Vec<ProjectRecord>> {
+        Ok(self.load_store()?.projects)
+    }
+
+    /// 获取单个项目
+    pub fn get(&self, id: &str) -> Result<ProjectRecord> {
+        self.load_store()?
+            .projects
+            .into_iter()
+            .find(|p| p.id == id)
+            .ok_or_else(|| anyhow!("项目不存在: {}", id))
+    }
+
+    /// 注册项目（识别技术栈并生成建议）；同一路径重复注册时更新原记录
+    pub fn register(&self, path: &str, name: Option<String>) -> Result<ProjectRecord> {
+        let root = Path::new(path.trim());
+        let stack = detect_stack(root)?;
+        let suggestions = suggest(&stack, &self.rules()?);
+        let path = root.to_string_lossy().to_string();
+        let name = name
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty())
+            .or_else(|| root.file_name().map(|n| n.to_string_lossy().to_string()))
+            .unwrap_or_else(|| path.clone());
+
+        let mut store = self.load_store()?;
+        let now = chrono::Utc::now().timestamp();
+        let record = match store.projects.iter_mut().find(|p| p.path == path) {
+            Some(existing) => {
+                existing.name = name;
+                existing.stack = stack;
+                existing.suggestions = suggestions;
+                existing.updated_at = now;
+                existing.clone()
+            }
+            None => {
+                let record = ProjectRecord {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    name,
+                    path,
+                    stack,
+                    suggestions,
+                    created_at: now,
+                    updated_at: now,
+                };
+                store.projects.push(record.clone());
+                record
+            }
+        };
+
+        self.save_store(&store)?;
+        tracing::info!(
+            project = %record.name,
+            languages = ?record.stack.languages,
+            frameworks = ?record.stack.frameworks,
+            "已注册项目"
+        );
+        Ok(record)
+    }
+
+    /// 重新识别项目技术栈并刷新建议
+    pub fn redetect(&self, id: &str) -> Result<ProjectRecord> {
+        let project = self.get(id)?;
+        self.register(&project.path, Some(project.name))
+    }
+
+    /// 删除项目
+    pub fn remove(&self, id: &str) -> Result<()> {
+        let mut store = self.load_store()?;
+        let before = store.projects.len();
+        store.projects.retain(|p| p.id != id);
+        if store.projects.len() == before {
+            bail!("项目不存在: {}", id);
+        }
+        self.save_store(&store)
+    }
+
+    /// 获取映射规则（未自定义时返回内置默认规则）
+    pub fn rules(&self) -> Result<Vec<StackRule>> {
+        if !self.rules_path.exists() {
+            return Ok(default_rules());
+        }
+        let value = DataManager::new().json_uncached().read(&self.rules_path)?;
+        serde_json::from_value(value).context("解析项目映射规则失败")
+    }
+
+    /// 保存映射规则（不会自动刷新已注册项目的建议）
+    pub fn save_rules(&self, rules: &[StackRule]) -> Result<()> {
+        if rules.iter().any(|r| r.tool_id.trim().is_empty()) {
+            bail!("规则必须指定工具");
+        }
+        let value = serde_json::to_value(rules)?;
+        DataManager::new()
+            .json_uncached()
+            .write(&self.rules_path, &value)?;
+        Ok(())
+    }
+
+    fn load_store(&self) -> Result<ProjectsStore> {
+        if !self.store_path.exists() {
+            return Ok(ProjectsStore::default());
+        }
+        let value = DataManager::new().json_uncached().read(&self.store_path)?;
+        serde_json::from_value(value).context("解析项目存储失败")
+    }
+
+    fn save_store(&self, store: &ProjectsStore) -> Result<()> {
+        let value = serde_json::to_value(store)?;
+        DataManager::new()
+            .json_uncached()
+            .write(&self.store_path, &value)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn registry(dir: &TempDir) -> ProjectRegistry {
+        ProjectRegistry::with_paths(
+            dir.path().join("projects.json"),
+            dir.path().join("project_rules.json"),
+        )
+    }
+
+    fn rule(when: RuleCondition, model: &str, reason: &str) -> StackRule {
+        StackRule {
+            when,
+            tool_id: "claude-code".to_string(),
+            profile: None,
+            model: Some(model.to_string()),
+            reason: Some(reason.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_suggest_first_match_wins() {
+        let stack = ProjectStack {
+            languages: vec!["rust".to_string()],
+            is_monorepo: true,
+            ..Default::default()
+        };
+        let rules = vec![
+            rule(
+                RuleCondition {
+                    monorepo: Some(true),
+                    ..Default::default()
+                },
+                "big",
+                "monorepo",
+            ),
+            rule(
+                RuleCondition {
+                    language: Some("rust".to_string()),
+                    ..Default::default()
+                },
+                "small",
+                "rust",
+            ),
+            rule(
+                RuleCondition {
+                    language: Some("python".to_string()),
+                    ..Default::default()
+                },
+                "py",
+                "python",
+            ),
+        ];
+
+        let suggestions = suggest(&stack, &rules);
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].model.as_deref(), Some("big"));
+        assert_eq!(suggestions[0].reasons, vec!["monorepo", "rust"]);
+    }
+
+    #[test]
+    fn test_register_updates_existing_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("web");
+        std::fs::create_dir(&project).unwrap();
+        std::fs::write(project.join("package.json"), r#"{"workspaces":["a"]}"#).unwrap();
+        let registry = registry(&dir);
+
+        let first = registry.register(&project.to_string_lossy(), None).unwrap();
+        assert_eq!(first.name, "web");
+        assert_eq!(first.suggestions, suggest(&first.stack, &default_rules()));
+
+        registry.save_rules(&[]).unwrap();
+        let second = registry.redetect(&first.id).unwrap();
+        assert_eq!(second.id, first.id);
+        assert!(second.suggestions.is_empty());
+        assert_eq!(registry.list().unwrap().len(), 1);
+
+        registry.remove(&first.id).unwrap();
+        assert!(registry.list().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,627 @@
+//! 供应商健康探测
+//!
+//! 定时对各工具 Profile 配置的上游（按 Base URL 去重）发起模型列表请求：
+//! - 收到 HTTP 响应且状态码小于 500 视为可达，同时记录延迟
+//! - 每次探测结果写入 `provider_health` 表（与 token_logs 同库），保留最近 7 天
+//! - 可达性发生变化时通过回调通知，GUI 转发为 `provider-health-changed` 事件
+//!
+//! 时间戳单位为毫秒。
+
+use crate::data::DataManager;
+use crate::services::profile_manager::health::probe_request;
+use crate::services::profile_manager::{ProfileManager, ProfilesStore};
+use crate::utils::config_dir;
+use anyhow::{anyhow, Context, Result};
+use futures_util::stream::{self, StreamExt};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+/// 探测间隔
+const PROBE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// 单次探测超时时间
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 探测的最大并发数
+const MAX_CONCURRENT_PROBES: usize = 8;
+
+/// 历史记录保留时长（毫秒）
+const HISTORY_RETENTION_MS: i64 = 7 * 24 * 60 * 60 * 1000;
+
+/// 可用率与平均延迟的统计窗口（毫秒）
+const SUMMARY_WINDOW_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// 查询时返回的最近探测记录数
+const RECENT_SAMPLE_LIMIT: usize = 20;
+
+/// 参与探测的工具
+const PROBE_TOOLS: [&str; 3] = ["claude-code", "codex", "gemini-cli"];
+
+/// 内置代理 Profile 前缀（指向本地代理，不参与探测）
+const PROXY_PROFILE_PREFIX: &str = "dc_proxy_";
+
+static PROVIDER_HEALTH_MONITOR: Lazy<ProviderHealthMonitor> =
+    Lazy::new(ProviderHealthMonitor::default);
+
+/// 单次探测结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProviderHealthSample {
+    pub timestamp: i64,
+    pub reachable: bool,
+    pub latency_ms: Option<u64>,
+    pub status_code: Option<u16>,
+    pub error: Option<String>,
+}
+
+/// 单个上游的健康状况
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderHealth {
+    pub tool_id: String,
+    pub base_url: String,
+    /// 使用该上游的 Profile
+    pub profile_names: Vec<String>,
+    /// 最近一次探测结果（尚未探测时为空）
+    pub latest: Option<ProviderHealthSample>,
+    /// 最近 24 小时可用率（0-100）
+    pub uptime_24h: Option<f64>,
+    /// 最近 24 小时可达探测的平均延迟
+    pub avg_latency_ms_24h: Option<f64>,
+    /// 最近的探测记录（新的在前）
+    pub recent: Vec<ProviderHealthSample>,
+}
+
+/// 上游可达性变化事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderHealthChangedEvent {
+    pub tool_id: String,
+    pub base_url: String,
+    pub profile_names: Vec<String>,
+    pub reachable: bool,
+    /// 变化前的可达性（首次探测为空）
+    pub previous: Option<bool>,
+    pub latency_ms: Option<u64>,
+    pub error: Option<String>,
+    pub checked_at: i64,
+}
+
+/// 可达性变化回调
+pub type ProviderHealthNotifier = Box<dyn Fn(ProviderHealthChangedEvent) + Send + Sync>;
+
+/// 待探测的上游
+#[derive(Debug, Clone)]
+struct ProbeTarget {
+    tool_id: String,
+    base_url: String,
+    api_key: String,
+    profile_names: Vec<String>,
+}
+
+/// 规范化 Base URL（去除首尾空白与末尾的 `/`），用于比较与去重
+pub(crate) fn normalize_base_url(base_url: &str) -> String {
+    base_url.trim().trim_end_matches('/').to_string()
+}
+
+/// 收集工具的上游（同一 Base URL 只探测一次，使用第一个配置了 API Key 的 Profile）
+fn collect_targets(store: &ProfilesStore, tool_id: &str) -> Vec<ProbeTarget> {
+    let mut profiles = store.get_tool_profiles(tool_id).unwrap_or_default();
+    profiles.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut targets: Vec<ProbeTarget> = Vec::new();
+    for (profile_name, api_key, base_url) in profiles {
+        let base_url = normalize_base_url(&base_url);
+        if profile_name.starts_with(PROXY_PROFILE_PREFIX) || base_url.is_empty() {
+            continue;
+        }
+        match targets.iter_mut().find(|t| t.base_url == base_url) {
+            Some(target) => {
+                if target.api_key.is_empty() {
+                    target.api_key = api_key;
+                }
+                target.profile_names.push(profile_name);
+            }
+            None => targets.push(ProbeTarget {
+                tool_id: tool_id.to_string(),
+                base_url,
+                api_key,
+                profile_names: vec![profile_name],
+            }),
+        }
+    }
+    targets
+}
+
+/// 5xx 视为上游故障，其余状态码（含 401/404）说明服务在线
+fn is_reachable_status(status: u16) -> bool {
+    status < 500
+}
+
+async fn probe(client: &reqwest::Client, target: &ProbeTarget) -> ProviderHealthSample {
+    let (url, headers) = probe_request(&target.tool_id, &target.base_url, &target.api_key);
+    let mut request = client.get(&url).timeout(PROBE_TIMEOUT);
+    if !target.api_key.is_empty() {
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+    }
+
+    let started = Instant::now();
+    let timestamp = chrono::Utc::now().timestamp_millis();
+    match request.send().await {
+        Ok(resp) => {
+            let status = resp.status().as_u16();
+            let reachable = is_reachable_status(status);
+            ProviderHealthSample {
+                timestamp,
+                reachable,
+                latency_ms: Some(started.elapsed().as_millis() as u64),
+                status_code: Some(status),
+                error: (!reachable).then(|| format!("上游返回 HTTP {status}")),
+            }
+        }
+        Err(e) => ProviderHealthSample {
+            timestamp,
+            reachable: false,
+            latency_ms: None,
+            status_code: None,
+            error: Some(e.to_string()),
+        },
+    }
+}
+
+/// 并发探测所有工具的上游
+async fn probe_targets() -> Result<Vec<(ProbeTarget, ProviderHealthSample)>> {
+    let store = ProfileManager::new()?.load_profiles_store()?;
+    let targets: Vec<ProbeTarget> = PROBE_TOOLS
+        .iter()
+        .flat_map(|tool_id| collect_targets(&store, tool_id))
+        .collect();
+    if targets.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let client = crate::http_client::build_client().map_err(|e| anyhow!(e))?;
+    Ok(stream::iter(targets)
+        .map(|target| {
+            let client = &client;
+            async move {
+                let sample = probe(client, &target).await;
+                (target, sample)
+            }
+        })
+        .buffer_unordered(MAX_CONCURRENT_PROBES)
+        .collect()
+        .await)
+}
+
+/// 单个上游的即时探测结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderProbe {
+    pub tool_id: String,
+    pub base_url: String,
+    pub profile_names: Vec<String>,
+    pub sample: ProviderHealthSample,
+}
+
+/// 立即探测所有工具的上游（不写入历史、不触发回调，供自诊断使用）
+pub async fn probe_providers_once() -> Result<Vec<ProviderProbe>> {
+    Ok(probe_targets()
+        .await?
+        .into_iter()
+        .map(|(target, sample)| ProviderProbe {
+            tool_id: target.tool_id,
+            base_url: target.base_url,
+            profile_names: target.profile_names,
+            sample,
+        })
+        .collect())
+}
+
+/// 探测历史记录
+pub struct ProviderHealthLog {
+    db_path: PathBuf,
+}
+
+impl ProviderHealthLog {
+    pub fn new(db_path: PathBuf) -> Self {
+        Self { db_path }
+    }
+
+    /// 使用默认统计数据库（与 token_logs 同库）
+    pub fn open_default() -> Result<Self> {
+        let dir = config_dir().map_err(|e| anyhow!(e))?;
+        Ok(Self::new(dir.join("token_stats.db")))
+    }
+
+    /// 创建探测记录表
+    pub fn init_table(&self) -> Result<()> {
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+        manager
+            .execute_raw(
+                "CREATE TABLE IF NOT EXISTS provider_health (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    timestamp INTEGER NOT NULL,
+                    tool_id TEXT NOT NULL,
+                    base_url TEXT NOT NULL,
+                    reachable INTEGER NOT NULL,
+                    latency_ms INTEGER,
+                    status_code INTEGER,
+                    error TEXT
+                );
+                CREATE INDEX IF NOT EXISTS idx_provider_health_target
+                    ON provider_health(tool_id, base_url, timestamp)",
+            )
+            .context("Failed to create provider_health table")?;
+        Ok(())
+    }
+
+    /// 记录一轮探测结果，并清理超出保留期的历史
+    pub fn record(&self, samples: &[(String, String, ProviderHealthSample)]) -> Result<()> {
+        if samples.is_empty() {
+            return Ok(());
+        }
+        self.init_table()?;
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+
+        let params: Vec<Vec<String>> = samples
+            .iter()
+            .map(|(tool_id, base_url, sample)| {
+                vec![
+                    sample.timestamp.to_string(),
+                    tool_id.clone(),
+                    base_url.clone(),
+                    (sample.reachable as i32).to_string(),
+                    sample.latency_ms.map(|v| v.to_string()).unwrap_or_default(),
+                    sample
+                        .status_code
+                        .map(|v| v.to_string())
+                        .unwrap_or_default(),
+                    sample.error.clone().unwrap_or_default(),
+                ]
+            })
+            .collect();
+        manager
+            .execute_batch(
+                "INSERT INTO provider_health
+                    (timestamp, tool_id, base_url, reachable, latency_ms, status_code, error)
+                VALUES (?1, ?2, ?3, ?4, NULLIF(?5, ''), NULLIF(?6, ''), NULLIF(?7, ''))",
+                &params,
+            )
+            .context("Failed to insert provider health samples")?;
+
+        let cutoff = samples
+            .iter()
+            .map(|(_, _, s)| s.timestamp)
+            .max()
+            .unwrap_or(0)
+            - HISTORY_RETENTION_MS;
+        manager
+            .execute(
+                "DELETE FROM provider_health WHERE timestamp < ?1",
+                &[&cutoff.to_string()],
+            )
+            .context("Failed to prune provider health history")?;
+        Ok(())
+    }
+
+    /// 汇总单个上游的探测历史
+    fn summarize(&self, target: &ProbeTarget, now: i64) -> Result<ProviderHealth> {
+        self.init_table()?;
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+        let since = now - SUMMARY_WINDOW_MS;
+
+        Ok(manager.transaction(|tx| {
+            let (total, up, avg_latency): (i64, i64, Option<f64>) = tx.query_row(
+                "SELECT COUNT(*), COALESCE(SUM(reachable), 0),
+                    AVG(CASE WHEN reachable = 1 THEN latency_ms END)
+                FROM provider_health
+                WHERE tool_id = ?1 AND base_url = ?2 AND timestamp >= ?3",
+                rusqlite::params![target.tool_id, target.base_url, since],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )?;
+
+            let mut stmt = tx.prepare(
+                "SELECT timestamp, reachable, latency_ms, status_code, error
+                FROM provider_health
+                WHERE tool_id = ?1 AND base_url = ?2
+                ORDER BY timestamp DESC, id DESC LIMIT ?3",
+            )?;
+            let recent = stmt
+                .query_map(
+                    rusqlite::params![target.tool_id, target.base_url, RECENT_SAMPLE_LIMIT],
+                    |row| {
+                        Ok(ProviderHealthSample {
+                            timestamp: row.get(0)?,
+                            reachable: row.get::<_, i64>(1)? != 0,
+                            latency_ms: row.get(2)?,
+                            status_code: row.get(3)?,
+                            error: row.get(4)?,
+                        })
+                    },
+                )?
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(crate::data::DataError::Database)?;
+
+            Ok(ProviderHealth {
+                tool_id: target.tool_id.clone(),
+                base_url: target.base_url.clone(),
+                profile_names: target.profile_names.clone(),
+                latest: recent.first().cloned(),
+                uptime_24h: (total > 0).then(|| up as f64 * 100.0 / total as f64),
+                avg_latency_ms_24h: avg_latency,
+                recent,
+            })
+        })?)
+    }
+}
+
+/// 最近一次探测的可达性
+struct LastState {
+    reachable: bool,
+    profile_names: Vec<String>,
+}
+
+/// 供应商健康监控（定时探测并通知可达性变化）
+#[derive(Default)]
+pub struct ProviderHealthMonitor {
+    /// `tool_id/base_url` -> 最近一次状态
+    states: RwLock<HashMap<String, LastState>>,
+    notifier: RwLock<Option<ProviderHealthNotifier>>,
+}
+
+impl ProviderHealthMonitor {
+    /// 全局健康监控
+    pub fn global() -> &'static ProviderHealthMonitor {
+        &PROVIDER_HEALTH_MONITOR
+    }
+
+    /// 设置可达性变化回调
+    pub fn set_notifier(&self, notifier: ProviderHealthNotifier) {
+        *self.notifier.write().unwrap() = Some(notifier);
+    }
+
+    /// 持续定时探测（不会返回）
+    pub async fn run(&self) {
+        tracing::info!(
+            interval_secs = PROBE_INTERVAL.as_secs(),
+            "供应商健康探测已启动"
+        );
+        let mut interval = tokio::time::interval(PROBE_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.probe_all().await {
+                tracing::warn!(error = %e, "供应商健康探测失败");
+            }
+        }
+    }
+
+    /// 立即探测所有工具的上游
+    pub async fn probe_all(&self) -> Result<()> {
+        let results = probe_targets().await?;
+        if results.is_empty() {
+            return Ok(());
+        }
+
+        let reachable = results.iter().filter(|(_, s)| s.reachable).count();
+        tracing::debug!(total = results.len(), reachable, "供应商健康探测完成");
+
+        for (target, sample) in &results {
+            self.observe(target, sample);
+        }
+
+        let rows: Vec<(String, String, ProviderHealthSample)> = results
+            .into_iter()
+            .map(|(target, sample)| (target.tool_id, target.base_url, sample))
+            .collect();
+        tokio::task::spawn_blocking(move || {
+            ProviderHealthLog::open_default().and_then(|log| log.record(&rows))
+        })
+        .await
+        .map_err(|e| anyhow!(e))?
+    }
+
+    /// 更新状态；可达性变化（或首次探测即不可达）时触发回调
+    fn observe(&self, target: &ProbeTarget, sample: &ProviderHealthSample) {
+        let key = format!("{}/{}", target.tool_id, target.base_url);
+        let previous = self.states.write().unwrap().insert(
+            key,
+            LastState {
+                reachable: sample.reachable,
+                profile_names: target.profile_names.clone(),
+            },
+        );
+        let previous = previous.map(|state| state.reachable);
+        if previous.unwrap_or(true) == sample.reachable {
+            return;
+        }
+
+        if sample.reachable {
+            tracing::info!(tool_id = %target.tool_id, base_url = %target.base_url, "供应商已恢复可达");
+        } else {
+            tracing::warn!(
+                tool_id = %target.tool_id,
+                base_url = %target.base_url,
+                error = ?sample.error,
+                "供应商不可达"
+            );
+        }
+        if let Some(notifier) = self.notifier.read().unwrap().as_ref() {
+            notifier(ProviderHealthChangedEvent {
+                tool_id: target.tool_id.clone(),
+                base_url: target.base_url.clone(),
+                profile_names: target.profile_names.clone(),
+                reachable: sample.reachable,
+                previous,
+                latency_ms: sample.latency_ms,
+                error: sample.error.clone(),
+                checked_at: sample.timestamp,
+            });
+        }
+    }
+
+    /// Profile 的上游在最近一次探测中是否不可达
+    pub fn is_profile_unreachable(&self, tool_id: &str, profile_name: &str) -> bool {
+        let prefix = format!("{tool_id}/");
+        self.states
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(key, _)| key.starts_with(&prefix))
+            .any(|(_, state)| {
+                !state.reachable && state.profile_names.iter().any(|name| name == profile_name)
+            })
+    }
+
+    /// 查询工具各上游的健康状况（按当前 Profile 配置）
+    pub fn health_report(&self, tool_id: &str) -> Result<Vec<ProviderHealth>> {
+        let store = ProfileManager::new()?.load_profiles_store()?;
+        let log = ProviderHealthLog::open_default()?;
+        let now = chrono::Utc::now().timestamp_millis();
+        collect_targets(&store, tool_id)
+            .iter()
+            .map(|target| log.summarize(target, now))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::profile_manager::CodexProfile;
+    use std::sync::{Arc, Mutex};
+    use tempfile::tempdir;
+
+    fn sample(timestamp: i64, reachable: bool, latency_ms: Option<u64>) -> ProviderHealthSample {
+        ProviderHealthSample {
+            timestamp,
+            reachable,
+            latency_ms,
+            status_code: reachable.then_some(200),
+            error: (!reachable).then(|| "connection refused".to_string()),
+        }
+    }
+
+    fn target(base_url: &str, profiles: &[&str]) -> ProbeTarget {
+        ProbeTarget {
+            tool_id: "codex".to_string(),
+            base_url: base_url.to_string(),
+            api_key: "sk-test".to_string(),
+            profile_names: profiles.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_collect_targets_dedupes_base_url() {
+        let mut store = ProfilesStore::new();
+        for (name, key, url) in [
+            ("b", "sk-b", "https://api.example.com/"),
+            ("a", "", "https://api.example.com"),
+            ("c", "sk-c", "https://other.example.com"),
+            ("dc_proxy_codex", "sk-local", "http://127.0.0.1:8788"),
+        ] {
+            store.codex.insert(
+                name.to_string(),
+                CodexProfile {
+                    api_key: key.to_string(),
+                    base_url: url.to_string(),
+                    wire_api: "responses".to_string(),
+                    source: Default::default(),
+                    created_at: chrono::Utc::now(),
+                    updated_at: chrono::Utc::now(),
+                    raw_config_toml: None,
+                    raw_auth_json: None,
+                    pricing_template_id: None,
+                },
+            );
+        }
+
+        let targets = collect_targets(&store, "codex");
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets[0].base_url, "https://api.example.com");
+        assert_eq!(targets[0].profile_names, vec!["a", "b"]);
+        assert_eq!(targets[0].api_key, "sk-b");
+        assert_eq!(targets[1].profile_names, vec!["c"]);
+    }
+
+    #[test]
+    fn test_observe_notifies_on_change() {
+        let monitor = ProviderHealthMonitor::default();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        monitor.set_notifier(Box::new(move |event| sink.lock().unwrap().push(event)));
+
+        let upstream = target("https://api.example.com", &["main"]);
+        monitor.observe(&upstream, &sample(1, true, Some(80)));
+        monitor.observe(&upstream, &sample(2, false, None));
+        assert!(monitor.is_profile_unreachable("codex", "main"));
+        assert!(!monitor.is_profile_unreachable("claude-code", "main"));
+        monitor.observe(&upstream, &sample(3, false, None));
+        monitor.observe(&upstream, &sample(4, true, Some(90)));
+
+        // 首次探测即不可达也需要通知
+        monitor.observe(
+            &target("https://dead.example.com", &["dead"]),
+            &sample(5, false, None),
+        );
+
+        let events = events.lock().unwrap();
+        let summary: Vec<(bool, Option<bool>)> =
+            events.iter().map(|e| (e.reachable, e.previous)).collect();
+        assert_eq!(
+            summary,
+            vec![(false, Some(true)), (true, Some(false)), (false, None)]
+        );
+        assert!(!monitor.is_profile_unreachable("codex", "main"));
+        assert!(monitor.is_profile_unreachable("codex", "dead"));
+    }
+
+    #[test]
+    fn test_record_and_summarize() {
+        let dir = tempdir().unwrap();
+        let log = ProviderHealthLog::new(dir.path().join("health.db"));
+        let upstream = target("https://api.example.com", &["main"]);
+        let now = HISTORY_RETENTION_MS * 2;
+
+        let rows: Vec<(String, String, ProviderHealthSample)> = [
+            sample(now - HISTORY_RETENTION_MS - 2_000, true, Some(10)),
+            sample(now - SUMMARY_WINDOW_MS - 1, false, None),
+            sample(now - 3_000, true, Some(100)),
+            sample(now - 2_000, false, None),
+            sample(now - 1_000, true, Some(200)),
+        ]
+        .into_iter()
+        .map(|s| ("codex".to_string(), upstream.base_url.clone(), s))
+        .collect();
+        log.record(&rows).unwrap();
+
+        let health = log.summarize(&upstream, now).unwrap();
+        // 超出保留期的记录已被清理
+        assert_eq!(health.recent.len(), 4);
+        assert_eq!(
+            health.latest.as_ref().map(|s| s.timestamp),
+            Some(now - 1_000)
+        );
+        assert_eq!(
+            health.recent[1].error.as_deref(),
+            Some("connection refused")
+        );
+        assert_eq!(health.recent[1].latency_ms, None);
+        let uptime = health.uptime_24h.unwrap();
+        assert!((uptime - 200.0 / 3.0).abs() < 1e-9);
+        assert_eq!(health.avg_latency_ms_24h, Some(150.0));
+
+        let empty = log
+            .summarize(&target("https://new.example.com", &["new"]), now)
+            .unwrap();
+        assert!(empty.latest.is_none() && empty.uptime_24h.is_none());
+    }
+}
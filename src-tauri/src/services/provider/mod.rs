@@ -0,0 +1,13 @@
+// 供应商上游服务
+//
+// - health: 上游可达性与延迟探测（定时执行，历史写入 SQLite）
+// - onboarding: 供应商批量接入（探测接口风格并自动创建 Profile）
+
+pub mod health;
+pub mod onboarding;
+
+pub use health::{
+    ProviderHealth, ProviderHealthChangedEvent, ProviderHealthLog, ProviderHealthMonitor,
+    ProviderHealthSample,
+};
+pub use onboarding::{ApiProbe, ApiStyle, OnboardedProfile, OnboardingReport, OnboardingRequest};
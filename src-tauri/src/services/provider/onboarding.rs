@@ -0,0 +1,415 @@
+//! 供应商批量接入
+//!
+//! 给定 Base URL 与 API Key：
+//! 1. 并发探测上游支持的接口风格（Anthropic / OpenAI / Gemini 的模型列表接口）
+//! 2. 为每种支持的风格创建对应工具的 Profile（Claude Code / Codex / Gemini CLI）
+//! 3. 以最小请求验证 Profile 可用（`max_tokens` 为 1 的生成请求，Gemini 使用不计费的 countTokens）
+//!
+//! 任一步骤失败只体现在报告中，不影响其余工具。
+
+use crate::services::profile_manager::health::probe_request;
+use crate::services::profile_manager::ProfileManager;
+use crate::services::provider::health::normalize_base_url;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+
+/// 单次探测 / 验证超时时间
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// 报告中保留的模型数
+const MAX_REPORTED_MODELS: usize = 20;
+
+/// 上游接口风格
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiStyle {
+    Anthropic,
+    OpenAi,
+    Gemini,
+}
+
+impl ApiStyle {
+    const ALL: [ApiStyle; 3] = [ApiStyle::Anthropic, ApiStyle::OpenAi, ApiStyle::Gemini];
+
+    /// 使用该风格接口的工具
+    pub fn tool_id(self) -> &'static str {
+        match self {
+            ApiStyle::Anthropic => "claude-code",
+            ApiStyle::OpenAi => "codex",
+            ApiStyle::Gemini => "gemini-cli",
+        }
+    }
+
+    /// 优先选用的模型名前缀
+    fn model_prefix(self) -> &'static str {
+        match self {
+            ApiStyle::Anthropic => "claude",
+            ApiStyle::OpenAi => "gpt",
+            ApiStyle::Gemini => "gemini",
+        }
+    }
+}
+
+/// 接入请求
+#[derive(Debug, Clone, Deserialize)]
+pub struct OnboardingRequest {
+    pub base_url: String,
+    pub api_key: String,
+    /// Profile 名称（为空时由 Base URL 的主机名生成）
+    #[serde(default)]
+    pub profile_name: Option<String>,
+    /// 同名 Profile 已存在时是否覆盖
+    #[serde(default)]
+    pub overwrite: bool,
+}
+
+/// 单种接口风格的探测结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiProbe {
+    pub style: ApiStyle,
+    pub tool_id: String,
+    pub url: String,
+    pub supported: bool,
+    pub status_code: Option<u16>,
+    pub latency_ms: Option<u64>,
+    /// 模型列表中的模型（最多 20 个）
+    pub models: Vec<String>,
+    pub error: Option<String>,
+}
+
+/// 单个工具的 Profile 创建与验证结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OnboardedProfile {
+    pub tool_id: String,
+    pub profile_name: String,
+    /// 是否写入了 Profile（同名已存在且未要求覆盖时为 false）
+    pub created: bool,
+    /// 最小请求是否成功
+    pub validated: bool,
+    /// 验证使用的模型
+    pub model: Option<String>,
+    pub status_code: Option<u16>,
+    pub error: Option<String>,
+}
+
+/// 接入报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OnboardingReport {
+    /// 规范化后的 Base URL（去除末尾的 `/` 与 `/v1`）
+    pub base_url: String,
+    pub profile_name: String,
+    pub probes: Vec<ApiProbe>,
+    pub profiles: Vec<OnboardedProfile>,
+}
+
+/// 探测上游并为支持的工具创建、验证 Profile
+pub async fn onboard_provider(
+    manager: &ProfileManager,
+    request: &OnboardingRequest,
+) -> Result<OnboardingReport> {
+    let base_url = strip_version_path(&normalize_base_url(&request.base_url));
+    let api_key = request.api_key.trim();
+    if base_url.is_empty() || api_key.is_empty() {
+        return Err(anyhow!("Base URL 与 API Key 不能为空"));
+    }
+    let profile_name = match request.profile_name.as_deref().map(str::trim) {
+        Some(name) if !name.is_empty() => name.to_string(),
+        _ => profile_name_from_url(&base_url)?,
+    };
+
+    let client = crate::http_client::build_client().map_err(|e| anyhow!(e))?;
+    let probes = futures_util::future::join_all(
+        ApiStyle::ALL
+            .iter()
+            .map(|style| probe(&client, *style, &base_url, api_key)),
+    )
+    .await;
+
+    let mut profiles = Vec::new();
+    for probe in probes.iter().filter(|p| p.supported) {
+        let model = pick_model(probe.style, &probe.models);
+        let mut result = OnboardedProfile {
+            tool_id: probe.tool_id.clone(),
+            profile_name: profile_name.clone(),
+            created: false,
+            validated: false,
+            model: model.clone(),
+            status_code: None,
+            error: None,
+        };
+
+        let exists = manager
+            .list_profiles(&probe.tool_id)?
+            .contains(&profile_name);
+        if exists && !request.overwrite {
+            result.error = Some("同名 Profile 已存在，未覆盖".to_string());
+            profiles.push(result);
+            continue;
+        }
+
+        let mut wire_api = "responses";
+        match model.as_deref() {
+            Some(model) => match validate(&client, probe.style, &base_url, api_key, model).await {
+                Ok((status, chat_fallback)) => {
+                    result.status_code = Some(status);
+                    result.validated = (200..300).contains(&status);
+                    if chat_fallback {
+                        wire_api = "chat";
+                    }
+                    if !result.validated {
+                        result.error = Some(format!("验证请求返回状态码 {}", status));
+                    }
+                }
+                Err(e) => result.error = Some(e.to_string()),
+            },
+            None => result.error = Some("未发现可用于验证的模型".to_string()),
+        }
+
+        let saved = match probe.style {
+            ApiStyle::Anthropic => {
+                manager.save_claude_profile(&profile_name, api_key.to_string(), base_url.clone())
+            }
+            ApiStyle::OpenAi => manager.save_codex_profile(
+                &profile_name,
+                api_key.to_string(),
+                base_url.clone(),
+                Some(wire_api.to_string()),
+            ),
+            ApiStyle::Gemini => manager.save_gemini_profile(
+                &profile_name,
+                api_key.to_string(),
+                base_url.clone(),
+                None,
+            ),
+        };
+        match saved {
+            Ok(()) => result.created = true,
+            Err(e) => result.error = Some(format!("保存 Profile 失败: {}", e)),
+        }
+        profiles.push(result);
+    }
+
+    tracing::info!(
+        base_url = %base_url,
+        profile = %profile_name,
+        supported = probes.iter().filter(|p| p.supported).count(),
+        created = profiles.iter().filter(|p| p.created).count(),
+        "供应商接入完成"
+    );
+    Ok(OnboardingReport {
+        base_url,
+        profile_name,
+        probes,
+        profiles,
+    })
+}
+
+/// 去除末尾的版本路径 `/v1`、`/v1beta`（各工具自行拼接版本前缀）
+fn strip_version_path(base_url: &str) -> String {
+    base_url
+        .strip_suffix("/v1beta")
+        .or_else(|| base_url.strip_suffix("/v1"))
+        .unwrap_or(base_url)
+        .to_string()
+}
+
+/// 由主机名生成 Profile 名称（如 `api.example.com` → `api-example-com`）
+fn profile_name_from_url(base_url: &str) -> Result<String> {
+    let url = url::Url::parse(base_url).map_err(|e| anyhow!("Base URL 无效: {}", e))?;
+    let host = url
+        .host_str()
+        .ok_or_else(|| anyhow!("Base URL 缺少主机名"))?;
+    Ok(host.replace('.', "-"))
+}
+
+async fn probe(
+    client: &reqwest::Client,
+    style: ApiStyle,
+    base_url: &str,
+    api_key: &str,
+) -> ApiProbe {
+    let (url, headers) = probe_request(style.tool_id(), base_url, api_key);
+    let mut result = ApiProbe {
+        style,
+        tool_id: style.tool_id().to_string(),
+        url: url.clone(),
+        supported: false,
+        status_code: None,
+        latency_ms: None,
+        models: Vec::new(),
+        error: None,
+    };
+
+    let mut request = client.get(&url).timeout(REQUEST_TIMEOUT);
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+    let started = Instant::now();
+    let response = match request.send().await {
+        Ok(response) => response,
+        Err(e) => {
+            result.error = Some(e.to_string());
+            return result;
+        }
+    };
+    result.latency_ms = Some(started.elapsed().as_millis() as u64);
+    result.status_code = Some(response.status().as_u16());
+    if !response.status().is_success() {
+        result.error = Some(format!("模型列表接口返回状态码 {}", response.status()));
+        return result;
+    }
+
+    match response.json::<Value>().await {
+        Ok(body) => match parse_models(style, &body) {
+            Some(models) => {
+                result.supported = true;
+                result.models = models;
+            }
+            None => result.error = Some("模型列表格式不匹配".to_string()),
+        },
+        Err(e) => result.error = Some(format!("模型列表不是 JSON: {}", e)),
+    }
+    result
+}
+
+/// 按接口风格解析模型列表（格式不匹配时返回 None）
+fn parse_models(style: ApiStyle, body: &Value) -> Option<Vec<String>> {
+    let (items, id_field) = match style {
+        ApiStyle::Anthropic | ApiStyle::OpenAi => (body.get("data")?.as_array()?, "id"),
+        ApiStyle::Gemini => (body.get("models")?.as_array()?, "name"),
+    };
+    Some(
+        items
+            .iter()
+            .filter_map(|item| item.get(id_field)?.as_str())
+            .map(|id| id.trim_start_matches("models/").to_string())
+            .take(MAX_REPORTED_MODELS)
+            .collect(),
+    )
+}
+
+/// 选择验证使用的模型（优先该风格的常见模型）
+fn pick_model(style: ApiStyle, models: &[String]) -> Option<String> {
+    models
+        .iter()
+        .find(|m| m.starts_with(style.model_prefix()))
+        .or_else(|| models.first())
+        .cloned()
+}
+
+/// 发送最小验证请求，返回状态码以及 OpenAI 风格是否回退到 Chat Completions
+async fn validate(
+    client: &reqwest::Client,
+    style: ApiStyle,
+    base_url: &str,
+    api_key: &str,
+    model: &str,
+) -> Result<(u16, bool)> {
+    let send = |url: String, headers: Vec<(&'static str, String)>, body: Value| {
+        let mut request = client.post(url).timeout(REQUEST_TIMEOUT).json(&body);
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        request.send()
+    };
+    let bearer = vec![("authorization", format!("Bearer {api_key}"))];
+
+    let status = match style {
+        ApiStyle::Anthropic => send(
+            format!("{base_url}/v1/messages"),
+            vec![
+                ("x-api-key", api_key.to_string()),
+                ("authorization", format!("Bearer {api_key}")),
+                ("anthropic-version", "2023-06-01".to_string()),
+            ],
+            json!({
+                "model": model,
+                "max_tokens": 1,
+                "messages": [{ "role": "user", "content": "ping" }],
+            }),
+        )
+        .await?
+        .status(),
+        ApiStyle::OpenAi => {
+            let status = send(
+                format!("{base_url}/v1/responses"),
+                bearer.clone(),
+                json!({ "model": model, "input": "ping", "max_output_tokens": 16 }),
+            )
+            .await?
+            .status();
+            // 不支持 Responses API 的上游改用 Chat Completions
+            if status.as_u16() == 404 || status.as_u16() == 405 {
+                let status = send(
+                    format!("{base_url}/v1/chat/completions"),
+                    bearer,
+                    json!({
+                        "model": model,
+                        "max_tokens": 1,
+                        "messages": [{ "role": "user", "content": "ping" }],
+                    }),
+                )
+                .await?
+                .status();
+                return Ok((status.as_u16(), true));
+            }
+            status
+        }
+        ApiStyle::Gemini => send(
+            format!("{base_url}/v1beta/models/{model}:countTokens"),
+            vec![("x-goog-api-key", api_key.to_string())],
+            json!({ "contents": [{ "parts": [{ "text": "ping" }] }] }),
+        )
+        .await?
+        .status(),
+    };
+    Ok((status.as_u16(), false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_helpers() {
+        assert_eq!(
+            strip_version_path(&normalize_base_url(" https://api.example.com/v1/ ")),
+            "https://api.example.com"
+        );
+        assert_eq!(
+            strip_version_path("https://g.example.com/v1beta"),
+            "https://g.example.com"
+        );
+        assert_eq!(
+            strip_version_path("https://api.example.com/openai"),
+            "https://api.example.com/openai"
+        );
+        assert_eq!(
+            profile_name_from_url("https://api.example.com").unwrap(),
+            "api-example-com"
+        );
+        assert!(profile_name_from_url("not a url").is_err());
+
+        let openai = json!({ "object": "list", "data": [{ "id": "o3" }, { "id": "gpt-5" }] });
+        let models = parse_models(ApiStyle::OpenAi, &openai).unwrap();
+        assert_eq!(models, vec!["o3", "gpt-5"]);
+        assert_eq!(
+            pick_model(ApiStyle::OpenAi, &models).as_deref(),
+            Some("gpt-5")
+        );
+        assert_eq!(
+            pick_model(ApiStyle::Anthropic, &models).as_deref(),
+            Some("o3")
+        );
+        assert!(parse_models(ApiStyle::Gemini, &openai).is_none());
+
+        let gemini = json!({ "models": [{ "name": "models/gemini-2.5-pro" }] });
+        assert_eq!(
+            parse_models(ApiStyle::Gemini, &gemini).unwrap(),
+            vec!["gemini-2.5-pro"]
+        );
+        assert_eq!(pick_model(ApiStyle::Gemini, &[]), None);
+    }
+}
@@ -0,0 +1,241 @@
+//! 企业认证代理（NTLM / Negotiate）支持
+//!
+//! reqwest 只支持 Basic 代理认证，而很多企业出口代理要求 NTLM 或 Kerberos（Negotiate）。
+//! 这两种认证需要多轮握手并依赖系统登录凭据，不在进程内实现，而是通过本地认证桥完成：
+//! 用户在本机运行 px（Windows 下使用当前登录身份，支持 NTLM/Kerberos）或 cntlm（NTLM），
+//! 由桥接程序对企业代理认证，DuckCoding 的全局 HTTP 客户端和透明代理的上游连接
+//! 都把本地桥当作普通 HTTP 代理使用。
+//!
+//! 代理类型选择 `ntlm` / `negotiate` 时，主机和端口填写本地桥的监听地址，
+//! 用户名和密码不会写入代理 URL（由桥接程序使用系统凭据或其自身配置）。
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// 连接本地认证桥的超时时间
+const BRIDGE_CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 通过认证桥发起测试请求的超时时间
+const BRIDGE_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 代理认证方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProxyAuthScheme {
+    Basic,
+    Digest,
+    Ntlm,
+    Negotiate,
+}
+
+impl ProxyAuthScheme {
+    /// 从 `Proxy-Authenticate` 质询中识别认证方式
+    fn from_challenge(challenge: &str) -> Option<Self> {
+        let name = challenge.split_whitespace().next()?;
+        match name.to_ascii_lowercase().as_str() {
+            "basic" => Some(Self::Basic),
+            "digest" => Some(Self::Digest),
+            "ntlm" => Some(Self::Ntlm),
+            "negotiate" | "kerberos" => Some(Self::Negotiate),
+            _ => None,
+        }
+    }
+
+    /// 是否需要通过本地认证桥
+    pub fn requires_bridge(self) -> bool {
+        matches!(self, Self::Ntlm | Self::Negotiate | Self::Digest)
+    }
+}
+
+/// 代理类型是否经本地认证桥（`ntlm` / `negotiate`）
+pub fn is_bridge_proxy_type(proxy_type: &str) -> bool {
+    matches!(proxy_type, "ntlm" | "negotiate")
+}
+
+/// 代理类型对应的 URL scheme，以及是否在 URL 中附带 Basic 凭据
+pub fn proxy_url_scheme(proxy_type: &str) -> (&'static str, bool) {
+    match proxy_type {
+        "socks5" => ("socks5", true),
+        "https" => ("https", true),
+        t if is_bridge_proxy_type(t) => ("http", false),
+        _ => ("http", true),
+    }
+}
+
+/// 解析 `Proxy-Authenticate` 响应头中的认证方式（去重，保持出现顺序）
+pub fn parse_proxy_authenticate<'a>(
+    values: impl IntoIterator<Item = &'a str>,
+) -> Vec<ProxyAuthScheme> {
+    let mut schemes = Vec::new();
+    for scheme in values
+        .into_iter()
+        .filter_map(ProxyAuthScheme::from_challenge)
+    {
+        if !schemes.contains(&scheme) {
+            schemes.push(scheme);
+        }
+    }
+    schemes
+}
+
+/// 从响应头中读取代理要求的认证方式
+pub fn schemes_from_headers(headers: &reqwest::header::HeaderMap) -> Vec<ProxyAuthScheme> {
+    parse_proxy_authenticate(
+        headers
+            .get_all(reqwest::header::PROXY_AUTHENTICATE)
+            .iter()
+            .filter_map(|v| v.to_str().ok()),
+    )
+}
+
+/// 代理返回 407 时的处理建议
+pub fn auth_required_hint(schemes: &[ProxyAuthScheme], via_bridge: bool) -> String {
+    if via_bridge {
+        return "本地认证桥未能通过企业代理认证，请检查 px/cntlm 的上游代理地址与凭据配置"
+            .to_string();
+    }
+    if schemes.iter().any(|s| s.requires_bridge()) {
+        let names: Vec<String> = schemes
+            .iter()
+            .map(|s| format!("{s:?}").to_uppercase())
+            .collect();
+        return format!(
+            "代理要求 {} 认证，无法直接使用用户名密码。{}",
+            names.join("/"),
+            bridge_setup_guide()
+        );
+    }
+    "代理认证失败，请检查用户名和密码".to_string()
+}
+
+/// 本地认证桥配置说明
+pub fn bridge_setup_guide() -> &'static str {
+    "请在本机运行认证桥：Windows 推荐 px（`px --proxy=企业代理:端口 --port=3128`，自动使用当前登录身份），\
+     macOS/Linux 可使用 cntlm（`cntlm -H` 生成密码哈希后配置上游代理）；\
+     然后将代理类型设为 NTLM/Negotiate，主机填 127.0.0.1，端口填认证桥监听端口"
+}
+
+/// 本地认证桥检测结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthBridgeStatus {
+    /// 认证桥端口是否在监听
+    pub listening: bool,
+    /// 经认证桥的测试请求是否通过企业代理认证
+    pub authenticated: bool,
+    /// 测试请求的 HTTP 状态码
+    pub status: Option<u16>,
+    /// 企业代理返回的认证方式（仅在认证失败时有值）
+    pub upstream_schemes: Vec<ProxyAuthScheme>,
+    pub hint: Option<String>,
+}
+
+/// 检测本地认证桥：端口是否可连接，以及经认证桥访问测试地址是否通过认证
+pub async fn probe_auth_bridge(host: &str, port: u16, test_url: &str) -> AuthBridgeStatus {
+    let listening = matches!(
+        tokio::time::timeout(
+            BRIDGE_CONNECT_TIMEOUT,
+            tokio::net::TcpStream::connect((host, port)),
+        )
+        .await,
+        Ok(Ok(_))
+    );
+    if !listening {
+        return AuthBridgeStatus {
+            listening,
+            authenticated: false,
+            status: None,
+            upstream_schemes: Vec::new(),
+            hint: Some(format!(
+                "无法连接本地认证桥 {host}:{port}。{}",
+                bridge_setup_guide()
+            )),
+        };
+    }
+
+    let client = reqwest::Proxy::all(format!("http://{host}:{port}")).and_then(|proxy| {
+        reqwest::Client::builder()
+            .proxy(proxy)
+            .timeout(BRIDGE_REQUEST_TIMEOUT)
+            .build()
+    });
+    let response = match client {
+        Ok(client) => client.get(test_url).send().await,
+        Err(e) => Err(e),
+    };
+
+    match response {
+        Ok(resp) if resp.status() == reqwest::StatusCode::PROXY_AUTHENTICATION_REQUIRED => {
+            let upstream_schemes = schemes_from_headers(resp.headers());
+            AuthBridgeStatus {
+                listening,
+                authenticated: false,
+                status: Some(resp.status().as_u16()),
+                hint: Some(auth_required_hint(&upstream_schemes, true)),
+                upstream_schemes,
+            }
+        }
+        Ok(resp) => AuthBridgeStatus {
+            listening,
+            authenticated: true,
+            status: Some(resp.status().as_u16()),
+            upstream_schemes: Vec::new(),
+            hint: None,
+        },
+        Err(e) => AuthBridgeStatus {
+            listening,
+            authenticated: false,
+            status: None,
+            upstream_schemes: Vec::new(),
+            hint: Some(format!("经认证桥访问 {test_url} 失败: {e}")),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proxy_authenticate() {
+        let schemes = parse_proxy_authenticate([
+            "Negotiate",
+            "NTLM",
+            "Basic realm=\"corp\"",
+            "ntlm TlRMTVNTUAACAAAA",
+            "Bearer",
+        ]);
+        assert_eq!(
+            schemes,
+            vec![
+                ProxyAuthScheme::Negotiate,
+                ProxyAuthScheme::Ntlm,
+                ProxyAuthScheme::Basic
+            ]
+        );
+        assert!(auth_required_hint(&schemes, false).contains("NEGOTIATE/NTLM/BASIC"));
+        assert_eq!(
+            auth_required_hint(&[ProxyAuthScheme::Basic], false),
+            "代理认证失败，请检查用户名和密码"
+        );
+    }
+
+    #[test]
+    fn test_proxy_url_scheme() {
+        assert_eq!(proxy_url_scheme("socks5"), ("socks5", true));
+        assert_eq!(proxy_url_scheme("http"), ("http", true));
+        assert_eq!(proxy_url_scheme("ntlm"), ("http", false));
+        assert_eq!(proxy_url_scheme("negotiate"), ("http", false));
+    }
+
+    #[tokio::test]
+    async fn test_probe_bridge_not_listening() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let status = probe_auth_bridge("127.0.0.1", port, "http://example.com").await;
+        assert!(!status.listening && !status.authenticated);
+        assert!(status.hint.unwrap().contains("px"));
+    }
+}
@@ -0,0 +1,589 @@
+//! 请求/响应体捕获存储
+//!
+//! 按工具开启后，代理把每次请求的请求头、请求体与响应体写入 `request_logs.db` 的
+//! `request_logs` 表，用于排查中转站问题，并可通过 [`replay_capture`] 经本地代理重放。
+//! 捕获内容包含提示词与密钥等敏感数据，因此：
+//!
+//! - 鉴权相关请求头（Authorization、x-api-key 等）写入前即脱敏
+//! - 请求体与响应体按配置的大小上限截断
+//! - 每条捕获写入时即确定过期时间，过期删除不可关闭
+//! - 后台任务定期清理过期捕获，应用启动时也会立即清理一次
+//! - [`capture_status`] 汇总当前捕获数量与过期时间，供前端醒目展示
+
+use crate::data::DataManager;
+use crate::models::proxy_config::BodyCaptureConfig;
+use crate::services::proxy_config_manager::ProxyConfigManager;
+use crate::utils::config::config_dir;
+use anyhow::{anyhow, bail, Context, Result};
+use hyper::HeaderMap;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// 捕获数据库文件名
+const CAPTURE_DB: &str = "request_logs.db";
+
+/// 过期清理间隔
+const PURGE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 写入前脱敏的请求头（小写）
+const REDACTED_HEADERS: [&str; 6] = [
+    "authorization",
+    "proxy-authorization",
+    "x-api-key",
+    "x-goog-api-key",
+    "cookie",
+    "set-cookie",
+];
+
+/// 脱敏占位符
+const REDACTED: &str = "[REDACTED]";
+
+/// 重放时不透传的请求头（由 HTTP 客户端重新生成）
+const HOP_HEADERS: [&str; 5] = [
+    "host",
+    "content-length",
+    "connection",
+    "transfer-encoding",
+    "accept-encoding",
+];
+
+/// 重放结果中保留的响应体上限
+const REPLAY_RESPONSE_LIMIT: usize = 1024 * 1024;
+
+const CREATE_TABLE_SQL: &str = "
+CREATE TABLE IF NOT EXISTS request_logs (
+    id TEXT PRIMARY KEY,
+    tool_id TEXT NOT NULL,
+    captured_at INTEGER NOT NULL,
+    expires_at INTEGER NOT NULL,
+    method TEXT NOT NULL,
+    path TEXT NOT NULL,
+    query TEXT,
+    request_headers TEXT NOT NULL DEFAULT '{}',
+    request_body TEXT NOT NULL,
+    request_truncated INTEGER NOT NULL DEFAULT 0,
+    response_status INTEGER NOT NULL,
+    response_body TEXT NOT NULL,
+    response_truncated INTEGER NOT NULL DEFAULT 0,
+    is_sse INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE INDEX IF NOT EXISTS idx_request_logs_tool ON request_logs(tool_id, captured_at);
+CREATE INDEX IF NOT EXISTS idx_request_logs_expires ON request_logs(expires_at);
+";
+
+/// 单次请求的捕获内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapturedExchange {
+    pub id: String,
+    pub tool_id: String,
+    /// 捕获时间（Unix 时间戳，毫秒）
+    pub captured_at: i64,
+    /// 过期时间（Unix 时间戳，毫秒）
+    pub expires_at: i64,
+    pub method: String,
+    pub path: String,
+    pub query: Option<String>,
+    /// 客户端请求头（鉴权相关已脱敏）
+    pub request_headers: BTreeMap<String, String>,
+    pub request_body: String,
+    /// 请求体是否因超出大小上限被截断（截断的请求不可重放）
+    pub request_truncated: bool,
+    /// 上游响应状态码（0 表示上游请求失败）
+    pub response_status: u16,
+    /// 响应体（SSE 响应为统计旁路保留的事件）
+    pub response_body: String,
+    pub response_truncated: bool,
+    pub is_sse: bool,
+}
+
+/// 捕获列表项（不含请求/响应体）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptureSummary {
+    pub id: String,
+    pub tool_id: String,
+    pub captured_at: i64,
+    pub expires_at: i64,
+    pub method: String,
+    pub path: String,
+    pub response_status: u16,
+    pub is_sse: bool,
+    /// 请求体与响应体的总字节数
+    pub size: u64,
+}
+
+/// 重放结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayResult {
+    pub capture_id: String,
+    pub status: u16,
+    pub duration_ms: u64,
+    /// 响应体（超过 1 MiB 时截断）
+    pub response_body: String,
+    pub response_truncated: bool,
+}
+
+/// 单个工具的捕获状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCaptureStatus {
+    pub tool_id: String,
+    pub enabled: bool,
+    /// 生效的保留时长（分钟）
+    pub ttl_minutes: u32,
+    /// 当前未过期的捕获数量
+    pub count: usize,
+    pub total_bytes: u64,
+    /// 最早过期时间（Unix 时间戳，毫秒）
+    pub next_expires_at: Option<i64>,
+    /// 最晚过期时间（Unix 时间戳，毫秒），此后该工具不再残留捕获
+    pub last_expires_at: Option<i64>,
+}
+
+/// 捕获存储总体状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptureStatus {
+    pub total_count: usize,
+    pub total_bytes: u64,
+    pub tools: Vec<ToolCaptureStatus>,
+}
+
+/// 待写入的一次请求
+pub struct CaptureRecord<'a> {
+    pub tool_id: &'a str,
+    pub method: &'a str,
+    pub path: &'a str,
+    pub query: Option<&'a str>,
+    pub headers: &'a HeaderMap,
+    pub request_body: &'a [u8],
+    pub response_status: u16,
+    pub response_body: &'a [u8],
+    pub is_sse: bool,
+}
+
+/// 捕获数据库路径
+fn capture_db_path() -> Result<PathBuf> {
+    Ok(config_dir().map_err(|e| anyhow!(e))?.join(CAPTURE_DB))
+}
+
+/// 获取捕获数据库（确保表已创建）
+fn open_db(db_path: &Path) -> Result<std::sync::Arc<crate::data::managers::sqlite::SqliteManager>> {
+    let manager = DataManager::global()
+        .sqlite(db_path)
+        .context("打开捕获数据库失败")?;
+    if !manager.table_exists("request_logs")? {
+        manager.execute_raw(CREATE_TABLE_SQL)?;
+    }
+    Ok(manager)
+}
+
+/// 脱敏请求头
+fn redact_headers(headers: &HeaderMap) -> BTreeMap<String, String> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let name = name.as_str().to_ascii_lowercase();
+            let value = if REDACTED_HEADERS.contains(&name.as_str()) {
+                REDACTED.to_string()
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).into_owned()
+            };
+            (name, value)
+        })
+        .collect()
+}
+
+/// 按字节上限截断（不拆分 UTF-8 字符），返回 (内容, 是否截断)
+fn truncate_body(body: &[u8], limit: usize) -> (String, bool) {
+    if body.len() <= limit {
+        return (String::from_utf8_lossy(body).into_owned(), false);
+    }
+    let text = String::from_utf8_lossy(&body[..limit]);
+    // 截断位置落在多字节字符中间时会产生替换字符，去掉即可
+    (text.trim_end_matches('\u{FFFD}').to_string(), true)
+}
+
+/// 写入一条捕获（未开启捕获时不做任何操作）
+pub fn record_capture(config: &BodyCaptureConfig, record: &CaptureRecord<'_>) -> Result<()> {
+    if !config.enabled {
+        return Ok(());
+    }
+    insert_capture(
+        &capture_db_path()?,
+        config,
+        record,
+        chrono::Utc::now().timestamp_millis(),
+    )
+}
+
+fn insert_capture(
+    db_path: &Path,
+    config: &BodyCaptureConfig,
+    record: &CaptureRecord<'_>,
+    captured_at: i64,
+) -> Result<()> {
+    let expires_at = captured_at + i64::from(config.effective_ttl_minutes()) * 60_000;
+    let limit = config.effective_max_body_bytes();
+    let (request_body, request_truncated) = truncate_body(record.request_body, limit);
+    let (response_body, response_truncated) = truncate_body(record.response_body, limit);
+    let headers = serde_json::to_string(&redact_headers(record.headers))?;
+
+    let params = [
+        uuid::Uuid::new_v4().to_string(),
+        record.tool_id.to_string(),
+        captured_at.to_string(),
+        expires_at.to_string(),
+        record.method.to_string(),
+        record.path.to_string(),
+        record.query.unwrap_or_default().to_string(),
+        headers,
+        request_body,
+        i32::from(request_truncated).to_string(),
+        record.response_status.to_string(),
+        response_body,
+        i32::from(response_truncated).to_string(),
+        i32::from(record.is_sse).to_string(),
+    ];
+    let param_refs: Vec<&str> = params.iter().map(|s| s.as_str()).collect();
+    open_db(db_path)?.execute(
+        "INSERT INTO request_logs (
+            id, tool_id, captured_at, expires_at, method, path, query, request_headers,
+            request_body, request_truncated, response_status, response_body,
+            response_truncated, is_sse
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, NULLIF(?7, ''), ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+        &param_refs,
+    )?;
+    Ok(())
+}
+
+/// 删除已过期的捕获，返回删除数量
+fn purge_db(db_path: &Path, tool_id: Option<&str>, now_ms: i64) -> Result<usize> {
+    let manager = open_db(db_path)?;
+    let now = now_ms.to_string();
+    Ok(match tool_id {
+        Some(tool_id) => manager.execute(
+            "DELETE FROM request_logs WHERE expires_at <= ?1 AND tool_id = ?2",
+            &[&now, tool_id],
+        )?,
+        None => manager.execute("DELETE FROM request_logs WHERE expires_at <= ?1", &[&now])?,
+    })
+}
+
+/// 删除所有已过期的捕获，返回删除数量
+pub fn purge_expired() -> Result<usize> {
+    purge_db(
+        &capture_db_path()?,
+        None,
+        chrono::Utc::now().timestamp_millis(),
+    )
+}
+
+/// 立即删除捕获（`tool_id` 为 None 时删除全部工具），返回删除数量
+pub fn clear_captures(tool_id: Option<&str>) -> Result<usize> {
+    // 以 i64::MAX 作为当前时间，视所有捕获为已过期
+    purge_db(&capture_db_path()?, tool_id, i64::MAX)
+}
+
+/// 汇总各工具未过期的捕获：tool_id -> (数量, 字节数, 最早过期, 最晚过期)
+fn summarize_db(db_path: &Path, now_ms: i64) -> Result<BTreeMap<String, (usize, u64, i64, i64)>> {
+    let rows = open_db(db_path)?.query(
+        "SELECT tool_id, COUNT(*),
+            COALESCE(SUM(LENGTH(CAST(request_body AS BLOB)) + LENGTH(CAST(response_body AS BLOB))), 0),
+            MIN(expires_at), MAX(expires_at)
+         FROM request_logs WHERE expires_at > ?1 GROUP BY tool_id",
+        &[&now_ms.to_string()],
+    )?;
+    Ok(rows
+        .iter()
+        .filter_map(|row| {
+            let v = &row.values;
+            Some((
+                v.first()?.as_str()?.to_string(),
+                (
+                    v.get(1)?.as_u64()? as usize,
+                    v.get(2)?.as_u64()?,
+                    v.get(3)?.as_i64()?,
+                    v.get(4)?.as_i64()?,
+                ),
+            ))
+        })
+        .collect())
+}
+
+/// 获取捕获存储状态
+pub fn capture_status() -> Result<CaptureStatus> {
+    let store = ProxyConfigManager::new()?.load_proxy_store()?;
+    let summary = summarize_db(&capture_db_path()?, chrono::Utc::now().timestamp_millis())?;
+
+    let tools: Vec<ToolCaptureStatus> = store
+        .tool_ids()
+        .into_iter()
+        .map(|tool_id| {
+            let config = store
+                .get_config(&tool_id)
+                .map(|c| c.body_capture)
+                .unwrap_or_default();
+            let stats = summary.get(&tool_id);
+            ToolCaptureStatus {
+                enabled: config.enabled,
+                ttl_minutes: config.effective_ttl_minutes(),
+                count: stats.map(|s| s.0).unwrap_or(0),
+                total_bytes: stats.map(|s| s.1).unwrap_or(0),
+                next_expires_at: stats.map(|s| s.2),
+                last_expires_at: stats.map(|s| s.3),
+                tool_id,
+            }
+        })
+        .collect();
+
+    Ok(CaptureStatus {
+        total_count: tools.iter().map(|t| t.count).sum(),
+        total_bytes: tools.iter().map(|t| t.total_bytes).sum(),
+        tools,
+    })
+}
+
+fn list_db(
+    db_path: &Path,
+    tool_id: Option<&str>,
+    limit: usize,
+    now_ms: i64,
+) -> Result<Vec<CaptureSummary>> {
+    let now = now_ms.to_string();
+    let limit = limit.to_string();
+    let rows = open_db(db_path)?.query(
+        "SELECT id, tool_id, captured_at, expires_at, method, path, response_status, is_sse,
+            LENGTH(CAST(request_body AS BLOB)) + LENGTH(CAST(response_body AS BLOB))
+         FROM request_logs
+         WHERE expires_at > ?1 AND (?2 = '' OR tool_id = ?2)
+         ORDER BY captured_at DESC LIMIT ?3",
+        &[&now, tool_id.unwrap_or_default(), &limit],
+    )?;
+    Ok(rows
+        .iter()
+        .filter_map(|row| {
+            let v = &row.values;
+            Some(CaptureSummary {
+                id: v.first()?.as_str()?.to_string(),
+                tool_id: v.get(1)?.as_str()?.to_string(),
+                captured_at: v.get(2)?.as_i64()?,
+                expires_at: v.get(3)?.as_i64()?,
+                method: v.get(4)?.as_str()?.to_string(),
+                path: v.get(5)?.as_str()?.to_string(),
+                response_status: v.get(6)?.as_u64()? as u16,
+                is_sse: v.get(7)?.as_i64()? != 0,
+                size: v.get(8)?.as_u64()?,
+            })
+        })
+        .collect())
+}
+
+/// 列出未过期的捕获（最新在前，不含请求/响应体）
+pub fn list_captures(tool_id: Option<&str>, limit: usize) -> Result<Vec<CaptureSummary>> {
+    list_db(
+        &capture_db_path()?,
+        tool_id,
+        limit,
+        chrono::Utc::now().timestamp_millis(),
+    )
+}
+
+fn get_db(db_path: &Path, id: &str, now_ms: i64) -> Result<CapturedExchange> {
+    let rows = open_db(db_path)?.query(
+        "SELECT id, tool_id, captured_at, expires_at, method, path, query, request_headers,
+            request_body, request_truncated, response_status, response_body,
+            response_truncated, is_sse
+         FROM request_logs WHERE id = ?1 AND expires_at > ?2",
+        &[id, &now_ms.to_string()],
+    )?;
+    let row = rows
+        .first()
+        .ok_or_else(|| anyhow!("捕获不存在或已过期: {}", id))?;
+    let v = &row.values;
+    let text = |i: usize| v.get(i).and_then(|v| v.as_str()).unwrap_or_default();
+    let int = |i: usize| v.get(i).and_then(|v| v.as_i64()).unwrap_or_default();
+
+    Ok(CapturedExchange {
+        id: text(0).to_string(),
+        tool_id: text(1).to_string(),
+        captured_at: int(2),
+        expires_at: int(3),
+        method: text(4).to_string(),
+        path: text(5).to_string(),
+        query: v.get(6).and_then(|v| v.as_str()).map(|s| s.to_string()),
+        request_headers: serde_json::from_str(text(7)).unwrap_or_default(),
+        request_body: text(8).to_string(),
+        request_truncated: int(9) != 0,
+        response_status: int(10) as u16,
+        response_body: text(11).to_string(),
+        response_truncated: int(12) != 0,
+        is_sse: int(13) != 0,
+    })
+}
+
+/// 获取单条捕获详情
+pub fn get_capture(id: &str) -> Result<CapturedExchange> {
+    get_db(
+        &capture_db_path()?,
+        id,
+        chrono::Utc::now().timestamp_millis(),
+    )
+}
+
+/// 经本地透明代理重放一条捕获
+///
+/// 重放请求与普通请求一样经过代理（统计、会话与捕获照常），
+/// 脱敏的鉴权头以代理的本地 API Key 替换，因此需要对应工具的代理正在运行。
+pub async fn replay_capture(id: &str) -> Result<ReplayResult> {
+    let capture = get_capture(id)?;
+    if capture.request_truncated {
+        bail!("请求体超出捕获大小上限已被截断，无法重放");
+    }
+    let proxy_config = ProxyConfigManager::new()?
+        .get_config(&capture.tool_id)?
+        .ok_or_else(|| anyhow!("未找到 {} 的代理配置", capture.tool_id))?;
+
+    let mut url = format!("http://127.0.0.1:{}{}", proxy_config.port, capture.path);
+    if let Some(query) = &capture.query {
+        url.push('?');
+        url.push_str(query);
+    }
+    let method = reqwest::Method::from_bytes(capture.method.as_bytes())
+        .with_context(|| format!("无效的请求方法: {}", capture.method))?;
+
+    let mut builder = reqwest::Client::new().request(method, &url);
+    for (name, value) in &capture.request_headers {
+        if value == REDACTED || HOP_HEADERS.contains(&name.as_str()) {
+            continue;
+        }
+        builder = builder.header(name, value);
+    }
+    if let Some(local_key) = &proxy_config.local_api_key {
+        builder = builder.header("authorization", format!("Bearer {}", local_key));
+    }
+    if !capture.request_body.is_empty() {
+        builder = builder.body(capture.request_body.clone());
+    }
+
+    tracing::info!(capture_id = %id, tool_id = %capture.tool_id, "重放捕获的请求");
+    let start = std::time::Instant::now();
+    let response = builder
+        .send()
+        .await
+        .with_context(|| format!("连接本地代理失败（{} 代理是否已启动？）", capture.tool_id))?;
+    let status = response.status().as_u16();
+    let body = response.bytes().await.context("读取重放响应失败")?;
+    let (response_body, response_truncated) = truncate_body(&body, REPLAY_RESPONSE_LIMIT);
+
+    Ok(ReplayResult {
+        capture_id: capture.id,
+        status,
+        duration_ms: start.elapsed().as_millis() as u64,
+        response_body,
+        response_truncated,
+    })
+}
+
+/// 启动过期清理任务（立即清理一次，之后定期执行，不会返回）
+pub async fn run_purge_scheduler() {
+    let mut interval = tokio::time::interval(PURGE_INTERVAL);
+    loop {
+        interval.tick().await;
+        match purge_expired() {
+            Ok(0) => {}
+            Ok(removed) => tracing::info!(removed = removed, "已删除过期的请求体捕获"),
+            Err(e) => tracing::warn!(error = ?e, "清理过期捕获失败"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::header::HeaderValue;
+
+    fn record<'a>(tool_id: &'a str, headers: &'a HeaderMap, body: &'a [u8]) -> CaptureRecord<'a> {
+        CaptureRecord {
+            tool_id,
+            method: "POST",
+            path: "/v1/messages",
+            query: Some("beta=true"),
+            headers,
+            request_body: body,
+            response_status: 200,
+            response_body: b"{\"ok\":true}",
+            is_sse: false,
+        }
+    }
+
+    #[test]
+    fn test_redact_and_truncate() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "authorization",
+            HeaderValue::from_static("Bearer sk-secret"),
+        );
+        headers.insert("x-api-key", HeaderValue::from_static("sk-secret"));
+        headers.insert("anthropic-version", HeaderValue::from_static("2023-06-01"));
+        let redacted = redact_headers(&headers);
+        assert_eq!(redacted["authorization"], REDACTED);
+        assert_eq!(redacted["x-api-key"], REDACTED);
+        assert_eq!(redacted["anthropic-version"], "2023-06-01");
+
+        assert_eq!(truncate_body(b"hello", 10), ("hello".to_string(), false));
+        // "你好" 为 6 字节，截断在第二个字符中间
+        assert_eq!(
+            truncate_body("你好".as_bytes(), 4),
+            ("你".to_string(), true)
+        );
+    }
+
+    #[test]
+    fn test_capture_lifecycle() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = dir.path().join(CAPTURE_DB);
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "authorization",
+            HeaderValue::from_static("Bearer sk-secret"),
+        );
+        let config = BodyCaptureConfig {
+            enabled: true,
+            ttl_minutes: 1,
+            max_body_kb: 1,
+        };
+
+        insert_capture(&db, &config, &record("codex", &headers, b"{}"), 1_000).unwrap();
+        let large = vec![b'a'; 2048];
+        insert_capture(
+            &db,
+            &config,
+            &record("claude-code", &headers, &large),
+            2_000,
+        )
+        .unwrap();
+
+        let list = list_db(&db, None, 10, 0).unwrap();
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].tool_id, "claude-code");
+        assert_eq!(list_db(&db, Some("codex"), 10, 0).unwrap().len(), 1);
+
+        let large = get_db(&db, &list[0].id, 0).unwrap();
+        assert!(large.request_truncated);
+        assert_eq!(large.request_body.len(), 1024);
+        assert_eq!(large.query.as_deref(), Some("beta=true"));
+        assert_eq!(large.request_headers["authorization"], REDACTED);
+
+        let summary = summarize_db(&db, 0).unwrap();
+        assert_eq!(summary["codex"].0, 1);
+        assert_eq!(summary["codex"].2, 61_000);
+
+        // codex 捕获 61_000 过期，claude-code 捕获 62_000 过期
+        assert_eq!(purge_db(&db, None, 61_000).unwrap(), 1);
+        assert!(get_db(&db, &list[0].id, 61_500).is_ok());
+        assert!(get_db(&db, &list[0].id, 62_000).is_err());
+        assert_eq!(purge_db(&db, Some("claude-code"), i64::MAX).unwrap(), 1);
+        assert!(list_db(&db, None, 10, 0).unwrap().is_empty());
+    }
+}
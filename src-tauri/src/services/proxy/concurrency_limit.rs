@@ -0,0 +1,227 @@
+// 并发上游请求上限
+//
+// 每个代理实例持有一个信号量，转发到上游前占用名额，响应（含流式响应）传输结束后释放：
+// - 排队模式下按到达顺序等待空闲名额，超过 `max_wait_secs` 返回 503
+// - 拒绝模式下名额已满直接返回 503
+// - 修改上限后新请求使用新的信号量，修改前已在转发的请求不计入新上限
+// - 未启用时仍统计当前并发数，随代理状态展示
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::models::proxy_config::{ConcurrencyLimitConfig, ConcurrencyOverflowMode};
+
+/// 代理实例的并发状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct ConcurrencyStatus {
+    /// 当前正在转发的请求数
+    pub in_flight: u32,
+    /// 当前等待名额的请求数
+    pub waiting: u32,
+    /// 并发上限（未启用时为 None）
+    pub limit: Option<u32>,
+    /// 启动以来因并发已满被拒绝的请求总数
+    pub total_rejected: u64,
+}
+
+/// 未获得名额的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConcurrencyRejection {
+    /// 拒绝模式下名额已满
+    Full,
+    /// 排队等待超时
+    Timeout,
+}
+
+/// 并发名额（转发结束时随 Drop 释放）
+#[derive(Debug)]
+pub struct ConcurrencyPermit {
+    _permit: Option<OwnedSemaphorePermit>,
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl Drop for ConcurrencyPermit {
+    fn drop(&mut self) {
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// 等待计数守卫（离开等待时自动减一，包括请求被取消）
+struct WaitingGuard<'a>(&'a AtomicUsize);
+
+impl Drop for WaitingGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// 并发限制器（重启实例后重置）
+#[derive(Debug, Default)]
+pub struct ConcurrencyLimiter {
+    in_flight: Arc<AtomicUsize>,
+    waiting: AtomicUsize,
+    total_rejected: AtomicU64,
+    /// 当前上限及其信号量（上限变化时替换）
+    semaphore: Mutex<Option<(u32, Arc<Semaphore>)>>,
+    /// 最近一次使用的配置（用于状态展示）
+    limit: Mutex<Option<u32>>,
+}
+
+impl ConcurrencyLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 占用一个并发名额（未启用时不限制，仅计数）
+    pub async fn acquire(
+        &self,
+        config: &ConcurrencyLimitConfig,
+    ) -> Result<ConcurrencyPermit, ConcurrencyRejection> {
+        if !config.is_active() {
+            *self.limit.lock().unwrap() = None;
+            return Ok(self.permit(None));
+        }
+        *self.limit.lock().unwrap() = Some(config.max_concurrent);
+
+        let semaphore = self.semaphore_for(config.max_concurrent);
+        if let Ok(permit) = Arc::clone(&semaphore).try_acquire_owned() {
+            return Ok(self.permit(Some(permit)));
+        }
+
+        if config.overflow == ConcurrencyOverflowMode::Reject {
+            self.total_rejected.fetch_add(1, Ordering::Relaxed);
+            return Err(ConcurrencyRejection::Full);
+        }
+
+        self.waiting.fetch_add(1, Ordering::SeqCst);
+        let _waiting = WaitingGuard(&self.waiting);
+        match tokio::time::timeout(
+            Duration::from_secs(config.max_wait_secs),
+            semaphore.acquire_owned(),
+        )
+        .await
+        {
+            Ok(Ok(permit)) => Ok(self.permit(Some(permit))),
+            // 信号量不会被关闭，仅超时会走到这里
+            _ => {
+                self.total_rejected.fetch_add(1, Ordering::Relaxed);
+                Err(ConcurrencyRejection::Timeout)
+            }
+        }
+    }
+
+    /// 当前正在转发的请求数
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+
+    pub fn status(&self) -> ConcurrencyStatus {
+        ConcurrencyStatus {
+            in_flight: self.in_flight() as u32,
+            waiting: self.waiting.load(Ordering::SeqCst) as u32,
+            limit: *self.limit.lock().unwrap(),
+            total_rejected: self.total_rejected.load(Ordering::Relaxed),
+        }
+    }
+
+    fn semaphore_for(&self, limit: u32) -> Arc<Semaphore> {
+        let mut current = self.semaphore.lock().unwrap();
+        match current.as_ref() {
+            Some((current_limit, semaphore)) if *current_limit == limit => Arc::clone(semaphore),
+            _ => {
+                let semaphore = Arc::new(Semaphore::new(limit as usize));
+                *current = Some((limit, Arc::clone(&semaphore)));
+                semaphore
+            }
+        }
+    }
+
+    fn permit(&self, permit: Option<OwnedSemaphorePermit>) -> ConcurrencyPermit {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        ConcurrencyPermit {
+            _permit: permit,
+            in_flight: Arc::clone(&self.in_flight),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(max_concurrent: u32, overflow: ConcurrencyOverflowMode) -> ConcurrencyLimitConfig {
+        ConcurrencyLimitConfig {
+            enabled: true,
+            max_concurrent,
+            overflow,
+            max_wait_secs: 1,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reject_when_full() {
+        let limiter = ConcurrencyLimiter::new();
+        let cfg = config(2, ConcurrencyOverflowMode::Reject);
+        let first = limiter.acquire(&cfg).await.unwrap();
+        let _second = limiter.acquire(&cfg).await.unwrap();
+        assert_eq!(
+            limiter.acquire(&cfg).await.unwrap_err(),
+            ConcurrencyRejection::Full
+        );
+
+        drop(first);
+        let _third = limiter.acquire(&cfg).await.unwrap();
+        let status = limiter.status();
+        assert_eq!(status.in_flight, 2);
+        assert_eq!(status.limit, Some(2));
+        assert_eq!(status.total_rejected, 1);
+    }
+
+    #[tokio::test]
+    async fn test_queue_waits_for_release_or_times_out() {
+        let limiter = Arc::new(ConcurrencyLimiter::new());
+        let cfg = config(1, ConcurrencyOverflowMode::Queue);
+        let held = limiter.acquire(&cfg).await.unwrap();
+
+        let waiter = {
+            let limiter = Arc::clone(&limiter);
+            tokio::spawn(async move { limiter.acquire(&cfg).await.map(|_| ()) })
+        };
+        tokio::task::yield_now().await;
+        assert_eq!(limiter.status().waiting, 1);
+        drop(held);
+        assert!(waiter.await.unwrap().is_ok());
+        assert_eq!(limiter.status().waiting, 0);
+
+        let _held = limiter.acquire(&cfg).await.unwrap();
+        let no_wait = ConcurrencyLimitConfig {
+            max_wait_secs: 0,
+            ..cfg
+        };
+        assert_eq!(
+            limiter.acquire(&no_wait).await.unwrap_err(),
+            ConcurrencyRejection::Timeout
+        );
+        assert_eq!(limiter.status().total_rejected, 1);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_only_counts() {
+        let limiter = ConcurrencyLimiter::new();
+        let cfg = ConcurrencyLimitConfig::default();
+        let permits: Vec<_> =
+            futures_util::future::join_all((0..20).map(|_| limiter.acquire(&cfg)))
+                .await
+                .into_iter()
+                .map(Result::unwrap)
+                .collect();
+        assert_eq!(limiter.in_flight(), 20);
+        assert_eq!(limiter.status().limit, None);
+        drop(permits);
+        assert_eq!(limiter.in_flight(), 0);
+    }
+}
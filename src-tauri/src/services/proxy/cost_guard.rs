@@ -0,0 +1,390 @@
+// 单次请求输入成本预检
+//
+// 转发前按请求体估算输入 Token（近似分词：ASCII 约 4 字符计 1 Token，其他字符各计 1 Token，
+// 图片 / 文档按固定 Token 计），再由 PricingManager 按输入单价估算成本（不考虑缓存命中，偏保守）。
+// 超出阈值时按配置处理：
+// - tag：继续转发，响应附加 `x-duckcoding-cost-guard` 头
+// - confirm：通过通知回调发送 `cost-guard-confirmation` 事件，等待用户确认（超时视为拒绝）
+// - reject：直接拒绝
+//
+// 未知模型或无法计价的请求一律放行
+
+use crate::models::proxy_config::{ApiProtocol, CostGuardConfig, CostGuardMode};
+use crate::services::pricing::PRICING_MANAGER;
+use crate::services::proxy::protocol;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+/// 标记超出阈值请求的响应头
+pub const COST_GUARD_HEADER: &str = "x-duckcoding-cost-guard";
+
+/// 平均每个 Token 对应的 ASCII 字符数
+const ASCII_CHARS_PER_TOKEN: usize = 4;
+
+/// 每条消息的结构开销（角色、分隔符等）
+const MESSAGE_OVERHEAD_TOKENS: usize = 4;
+
+/// 单张图片 / 单个文档的估算 Token 数
+const MEDIA_BLOCK_TOKENS: usize = 1600;
+
+static COST_GUARD: Lazy<CostGuard> = Lazy::new(CostGuard::default);
+
+/// 请求的输入成本估算
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct CostEstimate {
+    pub tool_id: String,
+    pub model: String,
+    /// 估算的输入 Token 数
+    pub input_tokens: i64,
+    /// 估算的输入成本（USD）
+    pub input_cost_usd: f64,
+    /// 配置的单次请求上限（USD）
+    pub threshold_usd: f64,
+}
+
+impl CostEstimate {
+    /// 是否超出阈值
+    pub fn exceeds_threshold(&self) -> bool {
+        self.input_cost_usd > self.threshold_usd
+    }
+
+    /// `x-duckcoding-cost-guard` 响应头的值
+    pub fn header_value(&self) -> String {
+        format!(
+            "tokens={}; cost_usd={:.6}; threshold_usd={:.6}",
+            self.input_tokens, self.input_cost_usd, self.threshold_usd
+        )
+    }
+}
+
+/// 等待用户确认的请求（`cost-guard-confirmation` 事件载荷）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct PendingCostConfirmation {
+    pub id: String,
+    pub estimate: CostEstimate,
+    /// 创建时间（Unix 时间戳，毫秒）
+    pub created_at: i64,
+    /// 超时时间（Unix 时间戳，毫秒），超时后请求被拒绝
+    pub expires_at: i64,
+}
+
+/// 预检结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum CostGuardDecision {
+    /// 未超出阈值（或无法估算）
+    Allow,
+    /// 超出阈值但继续转发（标记模式或用户已确认）
+    Tagged(CostEstimate),
+    /// 拒绝转发（拒绝模式、用户拒绝或确认超时）
+    Rejected(CostEstimate),
+}
+
+/// 确认请求通知回调
+pub type CostGuardNotifier = Box<dyn Fn(PendingCostConfirmation) + Send + Sync + 'static>;
+
+struct PendingEntry {
+    info: PendingCostConfirmation,
+    sender: oneshot::Sender<bool>,
+}
+
+/// 成本预检器（进程级单例，持有等待确认的请求）
+#[derive(Default)]
+pub struct CostGuard {
+    pending: Mutex<HashMap<String, PendingEntry>>,
+    notifier: RwLock<Option<CostGuardNotifier>>,
+}
+
+impl CostGuard {
+    /// 全局预检器
+    pub fn global() -> &'static CostGuard {
+        &COST_GUARD
+    }
+
+    /// 设置确认请求通知回调（由应用启动时注册，转发为前端事件）
+    pub fn set_notifier(&self, notifier: CostGuardNotifier) {
+        *self.notifier.write().unwrap() = Some(notifier);
+    }
+
+    /// 检查请求的预估输入成本，确认模式下会等待用户确认
+    pub async fn check(
+        &self,
+        tool_id: &str,
+        template_id: Option<&str>,
+        path: &str,
+        body: &[u8],
+        config: &CostGuardConfig,
+    ) -> CostGuardDecision {
+        if !config.is_active() {
+            return CostGuardDecision::Allow;
+        }
+        let Some(estimate) = estimate_request(tool_id, template_id, path, body, config) else {
+            return CostGuardDecision::Allow;
+        };
+        if !estimate.exceeds_threshold() {
+            return CostGuardDecision::Allow;
+        }
+
+        match config.mode {
+            CostGuardMode::Tag => CostGuardDecision::Tagged(estimate),
+            CostGuardMode::Reject => CostGuardDecision::Rejected(estimate),
+            CostGuardMode::Confirm => {
+                let timeout = Duration::from_secs(config.confirm_timeout_secs);
+                if self.wait_for_confirmation(estimate.clone(), timeout).await {
+                    CostGuardDecision::Tagged(estimate)
+                } else {
+                    CostGuardDecision::Rejected(estimate)
+                }
+            }
+        }
+    }
+
+    /// 登记待确认请求并等待用户决定（超时或通道关闭视为拒绝）
+    async fn wait_for_confirmation(&self, estimate: CostEstimate, timeout: Duration) -> bool {
+        let now = chrono::Utc::now().timestamp_millis();
+        let info = PendingCostConfirmation {
+            id: uuid::Uuid::new_v4().to_string(),
+            estimate,
+            created_at: now,
+            expires_at: now + timeout.as_millis() as i64,
+        };
+        let id = info.id.clone();
+        let (sender, receiver) = oneshot::channel();
+        self.pending.lock().unwrap().insert(
+            id.clone(),
+            PendingEntry {
+                info: info.clone(),
+                sender,
+            },
+        );
+
+        if let Some(notifier) = self.notifier.read().unwrap().as_ref() {
+            notifier(info);
+        } else {
+            tracing::warn!(id = %id, "未注册成本确认通知，请求将在超时后被拒绝");
+        }
+
+        let approved = matches!(tokio::time::timeout(timeout, receiver).await, Ok(Ok(true)));
+        self.pending.lock().unwrap().remove(&id);
+        approved
+    }
+
+    /// 确认或拒绝待确认请求（请求不存在或已超时时返回 false）
+    pub fn resolve(&self, id: &str, approve: bool) -> bool {
+        let entry = self.pending.lock().unwrap().remove(id);
+        match entry {
+            Some(entry) => entry.sender.send(approve).is_ok(),
+            None => false,
+        }
+    }
+
+    /// 等待确认的请求（按创建时间排序）
+    pub fn list_pending(&self) -> Vec<PendingCostConfirmation> {
+        let mut list: Vec<PendingCostConfirmation> = self
+            .pending
+            .lock()
+            .unwrap()
+            .values()
+            .map(|entry| entry.info.clone())
+            .collect();
+        list.sort_by_key(|info| info.created_at);
+        list
+    }
+}
+
+/// 估算请求的输入 Token 与成本（非 JSON、无模型名或模型无法计价时返回 None）
+pub fn estimate_request(
+    tool_id: &str,
+    template_id: Option<&str>,
+    path: &str,
+    body: &[u8],
+    config: &CostGuardConfig,
+) -> Option<CostEstimate> {
+    let json: Value = serde_json::from_slice(body).ok()?;
+    let model = json.get("model")?.as_str()?.to_string();
+    let input_tokens = estimate_input_tokens(path, &json) as i64;
+
+    let breakdown = PRICING_MANAGER
+        .calculate_cost(
+            template_id,
+            Some(tool_id),
+            &model,
+            input_tokens,
+            0,
+            0,
+            0,
+            0,
+            0,
+        )
+        .map_err(|e| tracing::debug!(model = %model, error = ?e, "成本预检无法计价，放行"))
+        .ok()?;
+
+    Some(CostEstimate {
+        tool_id: tool_id.to_string(),
+        model,
+        input_tokens,
+        input_cost_usd: breakdown.input_price,
+        threshold_usd: config.max_input_cost_usd,
+    })
+}
+
+/// 估算请求体的输入 Token 数（OpenAI 格式先转换为 Anthropic Messages 格式再统计）
+pub fn estimate_input_tokens(path: &str, body: &Value) -> usize {
+    let normalized = match protocol::client_protocol(path) {
+        Some(ApiProtocol::Anthropic) | None => None,
+        Some(protocol) => Some(protocol::request_as_anthropic(protocol, body)),
+    };
+    let body = normalized.as_ref().unwrap_or(body);
+
+    let mut units = 0;
+    for key in ["system", "messages", "tools", "input", "instructions"] {
+        if let Some(value) = body.get(key) {
+            units += content_units(value);
+        }
+    }
+    let messages = body
+        .get("messages")
+        .and_then(Value::as_array)
+        .map_or(0, Vec::len);
+    units.div_ceil(ASCII_CHARS_PER_TOKEN) + messages * MESSAGE_OVERHEAD_TOKENS
+}
+
+/// 内容的估算单位（ASCII 字符计 1，其他字符计 [`ASCII_CHARS_PER_TOKEN`]）
+fn content_units(value: &Value) -> usize {
+    match value {
+        Value::String(text) => text_units(text),
+        Value::Array(items) => items.iter().map(content_units).sum(),
+        Value::Object(map) => {
+            // 图片 / 文档的 base64 数据不按字符计
+            if matches!(
+                map.get("type").and_then(Value::as_str),
+                Some("image" | "document" | "image_url" | "input_image" | "input_file")
+            ) {
+                return MEDIA_BLOCK_TOKENS * ASCII_CHARS_PER_TOKEN;
+            }
+            map.iter()
+                .map(|(key, value)| text_units(key) + content_units(value))
+                .sum()
+        }
+        Value::Number(n) => n.to_string().len(),
+        Value::Bool(_) | Value::Null => 1,
+    }
+}
+
+fn text_units(text: &str) -> usize {
+    text.chars()
+        .map(|c| {
+            if c.is_ascii() {
+                1
+            } else {
+                ASCII_CHARS_PER_TOKEN
+            }
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_estimate_scales_with_text_length() {
+        let short = json!({"model": "m", "messages": [{"role": "user", "content": "hi"}]});
+        let long = json!({
+            "model": "m",
+            "messages": [{"role": "user", "content": "a".repeat(400_000)}]
+        });
+        let short_tokens = estimate_input_tokens("/v1/messages", &short);
+        let long_tokens = estimate_input_tokens("/v1/messages", &long);
+        assert!(short_tokens < 20);
+        assert!((100_000..100_100).contains(&long_tokens));
+    }
+
+    #[test]
+    fn test_estimate_counts_non_ascii_as_whole_tokens() {
+        let body = json!({"messages": [{"role": "user", "content": "你好世界"}]});
+        let ascii = json!({"messages": [{"role": "user", "content": "abcd"}]});
+        assert!(
+            estimate_input_tokens("/v1/messages", &body)
+                >= estimate_input_tokens("/v1/messages", &ascii) + 3
+        );
+    }
+
+    #[test]
+    fn test_estimate_skips_image_data() {
+        let body = json!({
+            "messages": [{
+                "role": "user",
+                "content": [{
+                    "type": "image",
+                    "source": {"type": "base64", "media_type": "image/png", "data": "A".repeat(1_000_000)}
+                }]
+            }]
+        });
+        let tokens = estimate_input_tokens("/v1/messages", &body);
+        assert!(tokens >= MEDIA_BLOCK_TOKENS);
+        assert!(tokens < MEDIA_BLOCK_TOKENS + 100);
+    }
+
+    #[test]
+    fn test_estimate_normalizes_openai_chat() {
+        let body = json!({
+            "model": "gpt-4o",
+            "messages": [
+                {"role": "system", "content": "s".repeat(4000)},
+                {"role": "user", "content": "u".repeat(4000)}
+            ]
+        });
+        let tokens = estimate_input_tokens("/v1/chat/completions", &body);
+        assert!((2000..2100).contains(&tokens));
+    }
+
+    #[tokio::test]
+    async fn test_confirmation_resolves_pending_request() {
+        let guard = CostGuard::default();
+        let estimate = CostEstimate {
+            tool_id: "claude-code".to_string(),
+            model: "claude-sonnet-4".to_string(),
+            input_tokens: 500_000,
+            input_cost_usd: 1.5,
+            threshold_usd: 1.0,
+        };
+        let wait = guard.wait_for_confirmation(estimate, Duration::from_secs(5));
+        let approve = async {
+            while guard.list_pending().is_empty() {
+                tokio::task::yield_now().await;
+            }
+            let id = guard.list_pending()[0].id.clone();
+            assert!(guard.resolve(&id, true));
+            assert!(!guard.resolve(&id, true));
+        };
+        let (approved, _) = tokio::join!(wait, approve);
+        assert!(approved);
+        assert!(guard.list_pending().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_confirmation_times_out_as_rejected() {
+        let guard = CostGuard::default();
+        let estimate = CostEstimate {
+            tool_id: "codex".to_string(),
+            model: "gpt-5".to_string(),
+            input_tokens: 1,
+            input_cost_usd: 2.0,
+            threshold_usd: 1.0,
+        };
+        assert!(
+            !guard
+                .wait_for_confirmation(estimate, Duration::from_millis(10))
+                .await
+        );
+        assert!(guard.list_pending().is_empty());
+    }
+}
@@ -0,0 +1,355 @@
+// 多上游故障转移
+//
+// 每个代理实例维护一份上游健康状态：
+// - 上游返回 5xx/429 或请求失败（超时、连接错误）时记为失败并进入冷却
+// - 冷却中的上游排到尝试顺序末尾，全部冷却时仍按原顺序尝试
+// - 最近一次成功响应的上游即为当前活跃上游，供前端展示
+// - 当前 Profile 的主上游连续出错次数单独统计，达到阈值后触发 Profile 自动切换
+//   （由 ProxyManager 切换到备用 Profile）
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::models::proxy_config::{ProfileFailoverConfig, ToolProxyConfig, UpstreamTarget};
+
+/// 失败后的冷却时长（毫秒）
+const COOLDOWN_MS: i64 = 30_000;
+
+/// 是否应切换到下一个上游重试
+pub fn is_retryable_status(status: u16) -> bool {
+    status == 429 || (500..=599).contains(&status)
+}
+
+/// 上游标识（同一地址可配置多个 Key）
+type UpstreamKey = (String, String);
+
+fn key_of(upstream: &UpstreamTarget) -> UpstreamKey {
+    (upstream.base_url.clone(), upstream.api_key.clone())
+}
+
+#[derive(Debug, Default, Clone)]
+struct UpstreamHealth {
+    consecutive_failures: u32,
+    cooldown_until: Option<i64>,
+    last_error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct FailoverInner {
+    health: HashMap<UpstreamKey, UpstreamHealth>,
+    active: Option<UpstreamKey>,
+    last_failover_at: Option<i64>,
+    /// 当前 Profile 主上游的连续错误次数
+    profile_errors: u32,
+    profile_last_error: Option<String>,
+    /// 本轮连续错误是否已触发过 Profile 切换（成功或重置后清除）
+    profile_triggered: bool,
+}
+
+/// Profile 切换触发回调（参数为触发时的连续错误次数与最后一次错误）
+pub type ProfileFailoverTrigger = Box<dyn Fn(u32, Option<String>) + Send + Sync>;
+
+/// 单个上游的故障转移状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct UpstreamStatus {
+    pub base_url: String,
+    pub priority: u32,
+    /// 是否为当前 Profile 的主上游
+    pub primary: bool,
+    /// 是否为最近一次成功响应的上游
+    pub active: bool,
+    /// 是否处于冷却中
+    pub cooling_down: bool,
+    pub consecutive_failures: u32,
+    /// 冷却结束时间（Unix 时间戳，毫秒）
+    pub cooldown_until: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+/// 代理实例的故障转移状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct FailoverStatus {
+    /// 当前活跃上游地址（尚无成功请求时为 None）
+    pub active_base_url: Option<String>,
+    /// 最近一次切换活跃上游的时间（Unix 时间戳，毫秒）
+    pub last_failover_at: Option<i64>,
+    pub upstreams: Vec<UpstreamStatus>,
+    /// 当前 Profile 主上游的连续错误次数（Profile 自动切换计数）
+    pub profile_consecutive_errors: u32,
+}
+
+/// `proxy-failover` 事件载荷：Profile 自动切换完成
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct ProxyFailoverEvent {
+    pub tool_id: String,
+    /// 切换前的 Profile
+    pub from_profile: Option<String>,
+    /// 切换后的备用 Profile
+    pub to_profile: String,
+    /// 触发切换时的连续错误次数
+    pub consecutive_errors: u32,
+    pub last_error: Option<String>,
+    /// 切换时间（Unix 时间戳，毫秒）
+    pub switched_at: i64,
+}
+
+/// 上游健康状态跟踪
+#[derive(Default)]
+pub struct FailoverState {
+    inner: Mutex<FailoverInner>,
+    profile_trigger: Mutex<Option<ProfileFailoverTrigger>>,
+}
+
+impl std::fmt::Debug for FailoverState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FailoverState")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl FailoverState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 按健康状态排列尝试顺序：可用上游保持原顺序在前，冷却中的按冷却结束时间排在后
+    pub fn order(&self, candidates: Vec<UpstreamTarget>) -> Vec<UpstreamTarget> {
+        self.order_at(candidates, chrono::Utc::now().timestamp_millis())
+    }
+
+    fn order_at(&self, candidates: Vec<UpstreamTarget>, now: i64) -> Vec<UpstreamTarget> {
+        let inner = self.inner.lock().unwrap();
+        let cooldown_of = |u: &UpstreamTarget| {
+            inner
+                .health
+                .get(&key_of(u))
+                .and_then(|h| h.cooldown_until)
+                .filter(|until| *until > now)
+        };
+
+        let (mut cooling, mut ordered): (Vec<_>, Vec<_>) = candidates
+            .into_iter()
+            .partition(|u| cooldown_of(u).is_some());
+        cooling.sort_by_key(|u| cooldown_of(u));
+        ordered.extend(cooling);
+        ordered
+    }
+
+    /// 记录上游失败
+    pub fn record_failure(&self, upstream: &UpstreamTarget, error: String) {
+        self.record_failure_at(upstream, error, chrono::Utc::now().timestamp_millis());
+    }
+
+    fn record_failure_at(&self, upstream: &UpstreamTarget, error: String, now: i64) {
+        let mut inner = self.inner.lock().unwrap();
+        let health = inner.health.entry(key_of(upstream)).or_default();
+        health.consecutive_failures += 1;
+        health.cooldown_until = Some(now + COOLDOWN_MS);
+        health.last_error = Some(error);
+    }
+
+    /// 记录上游成功响应，返回活跃上游是否发生切换
+    pub fn record_success(&self, upstream: &UpstreamTarget) -> bool {
+        self.record_success_at(upstream, chrono::Utc::now().timestamp_millis())
+    }
+
+    fn record_success_at(&self, upstream: &UpstreamTarget, now: i64) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        let key = key_of(upstream);
+        if let Some(health) = inner.health.get_mut(&key) {
+            health.consecutive_failures = 0;
+            health.cooldown_until = None;
+        }
+
+        let switched = inner.active.as_ref().is_some_and(|active| *active != key);
+        if switched {
+            inner.last_failover_at = Some(now);
+        }
+        inner.active = Some(key);
+        switched
+    }
+
+    /// 设置 Profile 切换触发回调
+    pub fn set_profile_trigger(&self, trigger: ProfileFailoverTrigger) {
+        *self.profile_trigger.lock().unwrap() = Some(trigger);
+    }
+
+    /// 记录当前 Profile 主上游出错；连续错误达到阈值时触发一次 Profile 切换
+    pub fn record_profile_error(
+        &self,
+        error: String,
+        policy: &ProfileFailoverConfig,
+        current_profile: Option<&str>,
+    ) {
+        let fired = {
+            let mut inner = self.inner.lock().unwrap();
+            inner.profile_errors += 1;
+            inner.profile_last_error = Some(error);
+            let fire = policy.is_active(current_profile)
+                && !inner.profile_triggered
+                && inner.profile_errors >= policy.error_threshold;
+            if fire {
+                inner.profile_triggered = true;
+            }
+            fire.then(|| (inner.profile_errors, inner.profile_last_error.clone()))
+        };
+
+        if let Some((errors, last_error)) = fired {
+            if let Some(trigger) = self.profile_trigger.lock().unwrap().as_ref() {
+                trigger(errors, last_error);
+            }
+        }
+    }
+
+    /// 当前 Profile 主上游成功响应或 Profile 已切换，清零连续错误计数
+    pub fn reset_profile_errors(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.profile_errors = 0;
+        inner.profile_last_error = None;
+        inner.profile_triggered = false;
+    }
+
+    /// 生成当前配置下的状态快照
+    pub fn snapshot(&self, config: &ToolProxyConfig) -> FailoverStatus {
+        let now = chrono::Utc::now().timestamp_millis();
+        let inner = self.inner.lock().unwrap();
+        let has_primary = config.real_base_url.is_some() && config.real_api_key.is_some();
+
+        let upstreams = config
+            .upstream_candidates()
+            .into_iter()
+            .enumerate()
+            .map(|(index, upstream)| {
+                let key = key_of(&upstream);
+                let health = inner.health.get(&key).cloned().unwrap_or_default();
+                let cooldown_until = health.cooldown_until.filter(|until| *until > now);
+                UpstreamStatus {
+                    primary: has_primary && index == 0,
+                    active: inner.active.as_ref() == Some(&key),
+                    cooling_down: cooldown_until.is_some(),
+                    consecutive_failures: health.consecutive_failures,
+                    cooldown_until,
+                    last_error: health.last_error,
+                    base_url: upstream.base_url,
+                    priority: upstream.priority,
+                }
+            })
+            .collect();
+
+        FailoverStatus {
+            active_base_url: inner.active.as_ref().map(|(base_url, _)| base_url.clone()),
+            last_failover_at: inner.last_failover_at,
+            upstreams,
+            profile_consecutive_errors: inner.profile_errors,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn upstream(base_url: &str, priority: u32) -> UpstreamTarget {
+        UpstreamTarget {
+            base_url: base_url.to_string(),
+            api_key: "sk-test".to_string(),
+            priority,
+            protocol: None,
+        }
+    }
+
+    #[test]
+    fn test_failed_upstream_moves_to_end_until_cooldown_expires() {
+        let state = FailoverState::new();
+        let candidates = vec![upstream("https://a", 0), upstream("https://b", 1)];
+
+        assert!(!state.record_success_at(&candidates[0], 0));
+        state.record_failure_at(&candidates[0], "HTTP 502".to_string(), 1_000);
+
+        let ordered = state.order_at(candidates.clone(), 2_000);
+        assert_eq!(ordered[0].base_url, "https://b");
+        assert_eq!(ordered[1].base_url, "https://a");
+
+        // 备用上游成功后成为活跃上游
+        assert!(state.record_success_at(&ordered[0], 2_000));
+
+        // 冷却结束后主上游恢复优先
+        let ordered = state.order_at(candidates, 1_000 + COOLDOWN_MS + 1);
+        assert_eq!(ordered[0].base_url, "https://a");
+    }
+
+    #[test]
+    fn test_candidates_and_retryable_status() {
+        let mut config = ToolProxyConfig::new(8787);
+        config.real_base_url = Some("https://primary".to_string());
+        config.real_api_key = Some("sk-primary".to_string());
+        config.upstreams = vec![
+            upstream("https://low", 5),
+            upstream("", 0),
+            upstream("https://high", 1),
+        ];
+
+        let urls: Vec<_> = config
+            .upstream_candidates()
+            .into_iter()
+            .map(|u| u.base_url)
+            .collect();
+        assert_eq!(urls, ["https://primary", "https://high", "https://low"]);
+
+        let status = FailoverState::new().snapshot(&config);
+        assert!(status.upstreams[0].primary);
+        assert!(!status.upstreams[1].primary);
+        assert!(status.active_base_url.is_none());
+
+        assert!(is_retryable_status(429));
+        assert!(is_retryable_status(503));
+        assert!(!is_retryable_status(400));
+        assert!(!is_retryable_status(200));
+    }
+
+    #[test]
+    fn test_profile_errors_trigger_once_at_threshold() {
+        use std::sync::Arc;
+
+        let state = FailoverState::new();
+        let fired = Arc::new(Mutex::new(Vec::new()));
+        let sink = fired.clone();
+        state.set_profile_trigger(Box::new(move |errors, last_error| {
+            sink.lock().unwrap().push((errors, last_error));
+        }));
+
+        let policy = ProfileFailoverConfig {
+            enabled: true,
+            backup_profile: Some("backup".to_string()),
+            error_threshold: 2,
+        };
+        state.record_profile_error("HTTP 502".to_string(), &policy, Some("main"));
+        // 成功响应清零计数
+        state.reset_profile_errors();
+        state.record_profile_error("HTTP 502".to_string(), &policy, Some("main"));
+        state.record_profile_error("timeout".to_string(), &policy, Some("main"));
+        state.record_profile_error("timeout".to_string(), &policy, Some("main"));
+        assert_eq!(
+            *fired.lock().unwrap(),
+            vec![(2, Some("timeout".to_string()))]
+        );
+
+        // 已在使用备用 Profile 时不再触发
+        state.reset_profile_errors();
+        for _ in 0..3 {
+            state.record_profile_error("HTTP 500".to_string(), &policy, Some("backup"));
+        }
+        assert_eq!(fired.lock().unwrap().len(), 1);
+        assert_eq!(
+            state
+                .snapshot(&ToolProxyConfig::new(8787))
+                .profile_consecutive_errors,
+            3
+        );
+    }
+}
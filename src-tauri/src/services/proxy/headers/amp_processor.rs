@@ -13,6 +13,8 @@ use super::{
     RequestProcessor,
 };
 use crate::services::profile_manager::ProfileManager;
+use crate::services::proxy::log_recorder::ParsedResponse;
+use crate::services::session::tags::CWD_HEADER;
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use bytes::Bytes;
@@ -423,7 +425,8 @@ impl AmpHeadersProcessor {
             let skip = name == hyper::header::AUTHORIZATION
                 || name == hyper::header::ACCEPT_ENCODING
                 || name == hyper::header::CONTENT_LENGTH
-                || name == hyper::header::HOST;
+                || name == hyper::header::HOST
+                || name.as_str() == CWD_HEADER;
             if skip {
                 continue;
             }
@@ -1405,13 +1408,13 @@ impl RequestProcessor for AmpHeadersProcessor {
         proxy_pricing_template_id: Option<&str>,
         request_body: &[u8],
         response_status: u16,
-        response_body: &[u8],
-        is_sse: bool,
+        parsed: ParsedResponse,
         response_time_ms: Option<i64>,
+        upstream_headers: Option<&str>,
+        upstream_key_alias: Option<&str>,
+        client_key_label: Option<&str>,
     ) -> Result<()> {
-        use crate::services::proxy::log_recorder::{
-            LogRecorder, RequestLogContext, ResponseParser,
-        };
+        use crate::services::proxy::log_recorder::{LogRecorder, RequestLogContext};
 
         // 仅记录 LLM 请求的日志，跳过 AmpInternal（/api/*）等非 LLM 请求
         let is_llm_request = if !request_body.is_empty() {
@@ -1454,12 +1457,14 @@ impl RequestProcessor for AmpHeadersProcessor {
             proxy_pricing_template_id,
             request_body,
             response_time_ms,
-        );
+        )
+        .with_upstream_headers(upstream_headers)
+        .with_upstream_key_alias(upstream_key_alias)
+        .with_client_key_label(client_key_label);
 
         // 覆盖写入日志的 tool_type 为 "amp-code"
         context.override_tool_type = Some("amp-code".to_string());
 
-        let parsed = ResponseParser::parse(response_body, response_status, is_sse);
         LogRecorder::record(&context, response_status, parsed).await?;
 
         Ok(())
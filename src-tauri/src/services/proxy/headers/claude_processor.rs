@@ -1,6 +1,8 @@
 // Claude Code 请求处理器
 
 use super::{ProcessedRequest, RequestProcessor};
+use crate::services::proxy::log_recorder::ParsedResponse;
+use crate::services::session::tags::{project_tag, CWD_HEADER};
 use crate::services::session::{SessionEvent, SESSION_MANAGER};
 use anyhow::Result;
 use async_trait::async_trait;
@@ -29,6 +31,9 @@ impl ClaudeHeadersProcessor {
         original_headers: &HyperHeaderMap,
         body: &[u8],
     ) -> Result<ProcessedRequest> {
+        // 客户端工作目录 → 自动项目标签
+        let project_tag = project_tag(original_headers);
+
         // 0. 查询会话配置并决定使用哪个 URL 和 API Key
         let (final_base_url, final_api_key) = if !body.is_empty() {
             // 尝试解析请求体 JSON 提取 user_id
@@ -55,6 +60,7 @@ impl ClaudeHeadersProcessor {
                                 session_id: user_id.to_string(),
                                 tool_id: caller_tool_id.to_string(),
                                 timestamp,
+                                project_tag: project_tag.clone(),
                             }) {
                                 tracing::warn!("Session 事件发送失败: {}", e);
                             }
@@ -65,6 +71,7 @@ impl ClaudeHeadersProcessor {
                                 session_id: user_id.to_string(),
                                 tool_id: caller_tool_id.to_string(),
                                 timestamp,
+                                project_tag: project_tag.clone(),
                             }) {
                                 tracing::warn!("Session 事件发送失败: {}", e);
                             }
@@ -76,6 +83,7 @@ impl ClaudeHeadersProcessor {
                             session_id: user_id.to_string(),
                             tool_id: caller_tool_id.to_string(),
                             timestamp,
+                            project_tag: project_tag.clone(),
                         }) {
                             tracing::warn!("Session 事件发送失败: {}", e);
                         }
@@ -107,6 +115,7 @@ impl ClaudeHeadersProcessor {
             if name_str.eq_ignore_ascii_case("host")
                 || name_str.eq_ignore_ascii_case("authorization")
                 || name_str.eq_ignore_ascii_case("x-api-key")
+                || name_str.eq_ignore_ascii_case(CWD_HEADER)
             {
                 continue;
             }
@@ -188,13 +197,13 @@ impl RequestProcessor for ClaudeHeadersProcessor {
         proxy_pricing_template_id: Option<&str>,
         request_body: &[u8],
         response_status: u16,
-        response_body: &[u8],
-        is_sse: bool,
+        parsed: ParsedResponse,
         response_time_ms: Option<i64>,
+        upstream_headers: Option<&str>,
+        upstream_key_alias: Option<&str>,
+        client_key_label: Option<&str>,
     ) -> Result<()> {
-        use crate::services::proxy::log_recorder::{
-            LogRecorder, RequestLogContext, ResponseParser,
-        };
+        use crate::services::proxy::log_recorder::{LogRecorder, RequestLogContext};
 
         // 1. 创建请求上下文（一次性提取所有信息）
         let context = RequestLogContext::from_request(
@@ -204,12 +213,12 @@ impl RequestProcessor for ClaudeHeadersProcessor {
             proxy_pricing_template_id,
             request_body,
             response_time_ms,
-        );
-
-        // 2. 解析响应
-        let parsed = ResponseParser::parse(response_body, response_status, is_sse);
+        )
+        .with_upstream_headers(upstream_headers)
+        .with_upstream_key_alias(upstream_key_alias)
+        .with_client_key_label(client_key_label);
 
-        // 3. 记录日志（自动处理成功/失败/解析错误）
+        // 2. 记录日志（自动处理成功/失败/解析错误）
         LogRecorder::record(&context, response_status, parsed).await?;
 
         Ok(())
@@ -1,6 +1,9 @@
 // Gemini CLI 请求处理器
 
 use super::{ProcessedRequest, RequestProcessor};
+use crate::models::proxy_config::ModelRoutingRule;
+use crate::services::proxy::model_router;
+use crate::services::session::tags::CWD_HEADER;
 use anyhow::Result;
 use async_trait::async_trait;
 use bytes::Bytes;
@@ -27,6 +30,16 @@ impl RequestProcessor for GeminiHeadersProcessor {
         "gemini-cli"
     }
 
+    /// Gemini 的模型名位于请求路径（`/models/{model}:generateContent`）
+    fn route_upstream<'a>(
+        &self,
+        rules: &'a [ModelRoutingRule],
+        path: &str,
+        _request_body: &[u8],
+    ) -> Option<&'a ModelRoutingRule> {
+        model_router::match_rule(rules, model_router::model_from_path(path)?)
+    }
+
     async fn process_outgoing_request(
         &self,
         base_url: &str,
@@ -50,6 +63,7 @@ impl RequestProcessor for GeminiHeadersProcessor {
                 || name_str.eq_ignore_ascii_case("x-goog-api-key")
                 || name_str.eq_ignore_ascii_case("authorization")
                 || name_str.eq_ignore_ascii_case("x-api-key")
+                || name_str.eq_ignore_ascii_case(CWD_HEADER)
             {
                 continue;
             }
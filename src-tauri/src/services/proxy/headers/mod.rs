@@ -6,6 +6,10 @@ use bytes::Bytes;
 use hyper::HeaderMap as HyperHeaderMap;
 use reqwest::header::HeaderMap as ReqwestHeaderMap;
 
+use super::log_recorder::ParsedResponse;
+use super::model_router;
+use crate::models::proxy_config::ModelRoutingRule;
+
 mod amp_processor;
 mod claude_processor;
 mod codex_processor;
@@ -109,6 +113,28 @@ pub trait RequestProcessor: Send + Sync + std::fmt::Debug {
         None
     }
 
+    /// 按模型路由规则选择上游
+    ///
+    /// # 参数
+    /// - `rules`: 代理配置中的模型路由规则（按顺序匹配）
+    /// - `path`: 原始请求路径
+    /// - `request_body`: 请求体字节数组
+    ///
+    /// # 默认实现
+    /// 使用 `extract_model` 从请求体提取模型名后匹配规则
+    fn route_upstream<'a>(
+        &self,
+        rules: &'a [ModelRoutingRule],
+        _path: &str,
+        request_body: &[u8],
+    ) -> Option<&'a ModelRoutingRule> {
+        if rules.is_empty() {
+            return None;
+        }
+        let model = self.extract_model(request_body)?;
+        model_router::match_rule(rules, &model)
+    }
+
     /// 记录请求日志（包括 Token 统计）
     ///
     /// 不同的 AI 工具有不同的数据格式和会话 ID 提取方式，
@@ -120,9 +146,11 @@ pub trait RequestProcessor: Send + Sync + std::fmt::Debug {
     /// - `proxy_pricing_template_id`: 代理配置的价格模板 ID
     /// - `request_body`: 请求体字节数组
     /// - `response_status`: HTTP 响应状态码
-    /// - `response_body`: 响应体字节数组
-    /// - `is_sse`: 是否为 SSE 流式响应
+    /// - `parsed`: 解析后的响应（SSE 为转发过程中累加的 Token 状态）
     /// - `response_time_ms`: 响应时间（毫秒）
+    /// - `upstream_headers`: 捕获的上游响应头（JSON 对象）
+    /// - `upstream_key_alias`: Key 池中本次使用的 Key 别名
+    /// - `client_key_label`: 鉴权所用的客户端 Key 标签
     ///
     /// # 默认实现
     /// 默认不记录日志（空操作）
@@ -134,9 +162,11 @@ pub trait RequestProcessor: Send + Sync + std::fmt::Debug {
         _proxy_pricing_template_id: Option<&str>,
         _request_body: &[u8],
         _response_status: u16,
-        _response_body: &[u8],
-        _is_sse: bool,
+        _parsed: ParsedResponse,
         _response_time_ms: Option<i64>,
+        _upstream_headers: Option<&str>,
+        _upstream_key_alias: Option<&str>,
+        _client_key_label: Option<&str>,
     ) -> Result<()> {
         Ok(())
     }
@@ -156,7 +186,17 @@ pub fn create_request_processor(tool_id: &str) -> Result<Box<dyn RequestProcesso
         "claude-code" => Ok(Box::new(ClaudeHeadersProcessor)),
         "codex" => Ok(Box::new(CodexHeadersProcessor)),
         "gemini-cli" => Ok(Box::new(GeminiHeadersProcessor)),
-        _ => Err(anyhow::anyhow!("不支持的工具: {}", tool_id)),
+        _ => {
+            // 自定义工具复用声明协议对应的内置处理器
+            let protocol = crate::services::tool::custom_tools::find_custom_tool(tool_id)
+                .and_then(|def| def.proxy_protocol);
+            match protocol.as_deref() {
+                Some("anthropic") => Ok(Box::new(ClaudeHeadersProcessor)),
+                Some("openai") => Ok(Box::new(CodexHeadersProcessor)),
+                Some("gemini") => Ok(Box::new(GeminiHeadersProcessor)),
+                _ => Err(anyhow::anyhow!("不支持的工具: {}", tool_id)),
+            }
+        }
     }
 }
 
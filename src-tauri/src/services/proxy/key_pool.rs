@@ -0,0 +1,115 @@
+// 上游 API Key 池负载均衡
+//
+// 同一上游地址配置多个 Key 时，每个请求按策略选出一个 Key：
+// - 轮询：依次使用每个启用的 Key
+// - 加权：平滑加权轮询（与 nginx 相同），请求按权重比例均匀分散
+//
+// 权重为 0 的 Key 视为停用；Key 池为空或全部停用时回退到 `real_api_key`
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::models::proxy_config::{KeyBalanceMode, PooledApiKey};
+
+#[derive(Debug, Default)]
+struct BalancerInner {
+    /// 轮询游标
+    cursor: usize,
+    /// 平滑加权轮询的当前权重（按别名记录，Key 池变更后自动适应）
+    current_weights: HashMap<String, i64>,
+}
+
+/// Key 池选择器（每个代理实例一个）
+#[derive(Debug, Default)]
+pub struct KeyBalancer {
+    inner: Mutex<BalancerInner>,
+}
+
+impl KeyBalancer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 为本次请求选出一个 Key（无可用 Key 时返回 None）
+    pub fn pick(&self, pool: &[PooledApiKey], mode: KeyBalanceMode) -> Option<PooledApiKey> {
+        let enabled: Vec<&PooledApiKey> = pool
+            .iter()
+            .filter(|k| k.weight > 0 && !k.api_key.is_empty())
+            .collect();
+        if enabled.is_empty() {
+            return None;
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        let picked = match mode {
+            KeyBalanceMode::RoundRobin => {
+                let index = inner.cursor % enabled.len();
+                inner.cursor = inner.cursor.wrapping_add(1);
+                enabled[index]
+            }
+            KeyBalanceMode::Weighted => {
+                let total: i64 = enabled.iter().map(|k| i64::from(k.weight)).sum();
+                inner
+                    .current_weights
+                    .retain(|alias, _| enabled.iter().any(|k| &k.alias == alias));
+
+                let mut best: Option<(&PooledApiKey, i64)> = None;
+                for key in &enabled {
+                    let weight = inner.current_weights.entry(key.alias.clone()).or_insert(0);
+                    *weight += i64::from(key.weight);
+                    if best.is_none_or(|(_, w)| *weight > w) {
+                        best = Some((key, *weight));
+                    }
+                }
+
+                let (key, _) = best?;
+                if let Some(weight) = inner.current_weights.get_mut(&key.alias) {
+                    *weight -= total;
+                }
+                key
+            }
+        };
+        Some(picked.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(alias: &str, weight: u32) -> PooledApiKey {
+        PooledApiKey {
+            alias: alias.to_string(),
+            api_key: format!("sk-{alias}"),
+            weight,
+        }
+    }
+
+    fn picks(balancer: &KeyBalancer, pool: &[PooledApiKey], mode: KeyBalanceMode) -> String {
+        (0..7)
+            .map(|_| balancer.pick(pool, mode).unwrap().alias)
+            .collect()
+    }
+
+    #[test]
+    fn test_round_robin_skips_disabled_keys() {
+        let pool = [key("a", 1), key("b", 0), key("c", 5)];
+        assert_eq!(
+            picks(&KeyBalancer::new(), &pool, KeyBalanceMode::RoundRobin),
+            "acacaca"
+        );
+        assert!(KeyBalancer::new()
+            .pick(&[key("b", 0)], KeyBalanceMode::RoundRobin)
+            .is_none());
+    }
+
+    #[test]
+    fn test_weighted_is_smooth_and_proportional() {
+        let pool = [key("a", 5), key("b", 1), key("c", 1)];
+        // 与 nginx 平滑加权轮询的经典序列一致
+        assert_eq!(
+            picks(&KeyBalancer::new(), &pool, KeyBalanceMode::Weighted),
+            "aabacaa"
+        );
+    }
+}
@@ -0,0 +1,228 @@
+// 透明代理监听器
+//
+// 默认监听 TCP 端口；配置 `listen_socket` 后改为监听本地套接字：
+// - Unix：Unix 域套接字（文件权限 0600，仅当前用户可连接，停止后删除套接字文件）
+// - Windows：命名管道（`\\.\pipe\名称`）
+//
+// 本地套接字模式下不占用端口，也忽略 `allow_public`，代理只能被本机进程访问
+
+use anyhow::{bail, Context, Result};
+use std::net::SocketAddr;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpListener;
+
+use crate::models::proxy_config::ToolProxyConfig;
+
+/// 监听器接受的连接（TCP / Unix 域套接字 / 命名管道）
+pub trait ProxyIo: AsyncRead + AsyncWrite + Send + Unpin {}
+
+impl<T: AsyncRead + AsyncWrite + Send + Unpin> ProxyIo for T {}
+
+pub type ProxyStream = Box<dyn ProxyIo>;
+
+/// Unix 域套接字路径长度上限（sockaddr_un.sun_path，取 macOS 的 104 字节）
+#[cfg(unix)]
+const MAX_UNIX_SOCKET_PATH: usize = 103;
+
+#[cfg(windows)]
+const PIPE_PREFIX: &str = r"\\.\pipe\";
+
+/// 代理监听器
+pub enum ProxyListener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix {
+        listener: tokio::net::UnixListener,
+        path: std::path::PathBuf,
+    },
+    #[cfg(windows)]
+    Pipe {
+        name: String,
+        /// 等待下一个客户端连接的管道实例
+        next: tokio::net::windows::named_pipe::NamedPipeServer,
+    },
+}
+
+/// 校验本地套接字配置
+pub fn validate(config: &ToolProxyConfig) -> Result<()> {
+    let Some(path) = config.listen_socket.as_deref() else {
+        return Ok(());
+    };
+    let path = path.trim();
+    if path.is_empty() {
+        bail!("本地套接字路径不能为空");
+    }
+    #[cfg(unix)]
+    if path.len() > MAX_UNIX_SOCKET_PATH {
+        bail!(
+            "Unix 套接字路径过长（最多 {} 字节）: {}",
+            MAX_UNIX_SOCKET_PATH,
+            path
+        );
+    }
+    #[cfg(windows)]
+    if !path.starts_with(PIPE_PREFIX) || path.len() == PIPE_PREFIX.len() {
+        bail!("命名管道名称必须以 {} 开头: {}", PIPE_PREFIX, path);
+    }
+    Ok(())
+}
+
+impl ProxyListener {
+    /// 按配置绑定监听地址
+    pub async fn bind(config: &ToolProxyConfig) -> Result<Self> {
+        validate(config)?;
+        if let Some(path) = config.listen_socket.as_deref() {
+            return Self::bind_local(path.trim());
+        }
+
+        let addr = if config.allow_public {
+            SocketAddr::from(([0, 0, 0, 0], config.port))
+        } else {
+            SocketAddr::from(([127, 0, 0, 1], config.port))
+        };
+        let listener = TcpListener::bind(addr)
+            .await
+            .context(format!("绑定端口 {} 失败", config.port))?;
+        Ok(Self::Tcp(listener))
+    }
+
+    #[cfg(unix)]
+    fn bind_local(path: &str) -> Result<Self> {
+        use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+
+        let path = std::path::PathBuf::from(path);
+        // 清理上次异常退出残留的套接字文件；仍有进程监听或不是套接字时拒绝覆盖
+        if let Ok(meta) = std::fs::symlink_metadata(&path) {
+            if !meta.file_type().is_socket() {
+                bail!("{} 已存在且不是套接字文件", path.display());
+            }
+            if std::os::unix::net::UnixStream::connect(&path).is_ok() {
+                bail!("套接字 {} 已被其他进程监听", path.display());
+            }
+            std::fs::remove_file(&path)
+                .with_context(|| format!("删除残留套接字失败: {}", path.display()))?;
+        }
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("创建套接字目录失败: {}", parent.display()))?;
+        }
+
+        let listener = tokio::net::UnixListener::bind(&path)
+            .with_context(|| format!("绑定套接字 {} 失败", path.display()))?;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))
+            .with_context(|| format!("设置套接字权限失败: {}", path.display()))?;
+        Ok(Self::Unix { listener, path })
+    }
+
+    #[cfg(windows)]
+    fn bind_local(name: &str) -> Result<Self> {
+        use tokio::net::windows::named_pipe::ServerOptions;
+
+        // first_pipe_instance：同名管道已被其他进程占用时失败
+        let next = ServerOptions::new()
+            .first_pipe_instance(true)
+            .reject_remote_clients(true)
+            .create(name)
+            .with_context(|| format!("创建命名管道 {} 失败", name))?;
+        Ok(Self::Pipe {
+            name: name.to_string(),
+            next,
+        })
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    fn bind_local(_path: &str) -> Result<Self> {
+        bail!("当前平台不支持本地套接字监听")
+    }
+
+    /// 接受下一个连接
+    pub async fn accept(&mut self) -> std::io::Result<ProxyStream> {
+        match self {
+            Self::Tcp(listener) => {
+                let (stream, _addr) = listener.accept().await?;
+                Ok(Box::new(stream))
+            }
+            #[cfg(unix)]
+            Self::Unix { listener, .. } => {
+                let (stream, _addr) = listener.accept().await?;
+                Ok(Box::new(stream))
+            }
+            #[cfg(windows)]
+            Self::Pipe { name, next } => {
+                use tokio::net::windows::named_pipe::ServerOptions;
+
+                next.connect().await?;
+                // 先创建下一个实例再交出已连接的实例，保证管道名始终可连接
+                let fresh = ServerOptions::new()
+                    .reject_remote_clients(true)
+                    .create(name.as_str())?;
+                Ok(Box::new(std::mem::replace(next, fresh)))
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for ProxyListener {
+    fn drop(&mut self) {
+        if let Self::Unix { path, .. } = self {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::services::proxy::{create_request_processor, ProxyInstance};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[test]
+    fn test_validate_socket_path() {
+        let mut config = ToolProxyConfig::new(8787);
+        assert!(validate(&config).is_ok());
+        config.listen_socket = Some("  ".to_string());
+        assert!(validate(&config).is_err());
+        config.listen_socket = Some(format!("/tmp/{}.sock", "x".repeat(120)));
+        assert!(validate(&config).is_err());
+        config.listen_socket = Some("/tmp/duckcoding.sock".to_string());
+        assert!(validate(&config).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_proxy_serves_over_unix_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("claude.sock");
+        // 残留的套接字文件会被清理
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+
+        let mut config = ToolProxyConfig::new(0);
+        config.local_api_key = Some("sk-local".to_string());
+        config.real_api_key = Some("sk-real".to_string());
+        config.real_base_url = Some("http://127.0.0.1:9".to_string());
+        config.listen_socket = Some(path.to_string_lossy().to_string());
+        let instance = ProxyInstance::new(
+            "claude-code".to_string(),
+            config,
+            create_request_processor("claude-code").unwrap(),
+        );
+        instance.start().await.unwrap();
+
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        // 未携带保护密钥：由代理本身返回 401，证明请求经套接字到达代理
+        let mut client = tokio::net::UnixStream::connect(&path).await.unwrap();
+        client
+            .write_all(b"GET /v1/models HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 401"), "{response}");
+
+        instance.stop().await.unwrap();
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,139 @@
+// 成本注解层
+//
+// 职责：在响应返回给客户端前计算本次请求的 Token 与成本，
+// 供代理写入响应头（非流式）或流末尾的 SSE 注释（流式），
+// 方便 CLI 外层脚本直接读取单次调用成本
+
+use super::recorder::LogRecorder;
+use super::{ApiFlavor, FlavorResolution, ParsedResponse, RequestLogContext};
+use crate::models::token_stats::TokenLog;
+use crate::services::token_stats::logger::create_logger;
+
+/// 成本响应头
+pub const COST_HEADER: &str = "x-duckcoding-cost";
+/// Token 总数响应头
+pub const TOKENS_HEADER: &str = "x-duckcoding-tokens";
+
+/// 单次请求的成本注解
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CostAnnotation {
+    /// 总成本（USD）
+    pub total_cost: f64,
+    /// Token 总数（输入 + 输出 + 缓存 + 推理）
+    pub total_tokens: i64,
+}
+
+impl CostAnnotation {
+    /// 按与统计日志相同的提取器和价格模板计算注解（无法提取 usage 时返回 None）
+    pub fn estimate(context: &RequestLogContext, parsed: ParsedResponse) -> Option<Self> {
+        let log = estimate_log(context, parsed)?;
+        Some(Self {
+            total_cost: log.total_cost,
+            total_tokens: log.input_tokens
+                + log.output_tokens
+                + log.cache_creation_tokens
+                + log.cache_read_tokens
+                + log.reasoning_tokens,
+        })
+    }
+
+    /// 响应头（非流式响应）
+    pub fn headers(&self) -> [(&'static str, String); 2] {
+        [
+            (COST_HEADER, format!("{:.6}", self.total_cost)),
+            (TOKENS_HEADER, self.total_tokens.to_string()),
+        ]
+    }
+
+    /// 流末尾追加的 SSE 注释（客户端按规范忽略 `:` 开头的行）
+    pub fn sse_comment(&self) -> String {
+        format!(
+            ": {COST_HEADER}={:.6} {TOKENS_HEADER}={}\n\n",
+            self.total_cost, self.total_tokens
+        )
+    }
+}
+
+/// 按与统计日志相同的提取器和价格模板生成已计价的日志（不写入数据库）
+pub(super) fn estimate_log(
+    context: &RequestLogContext,
+    parsed: ParsedResponse,
+) -> Option<TokenLog> {
+    let resolution = FlavorResolution::resolve(&context.tool_id, ApiFlavor::detect(&parsed));
+    if resolution.mismatch.is_some() {
+        return None;
+    }
+    let logger = create_logger(&resolution.extractor_tool).ok()?;
+
+    let mut log = match parsed {
+        ParsedResponse::Sse { stream, .. } => logger.log_sse_response(
+            &context.request_body,
+            &stream,
+            context.session_id.clone(),
+            context.config_name.clone(),
+            context.client_ip.clone(),
+            context.response_time_ms,
+        ),
+        ParsedResponse::Json { data } => logger.log_json_response(
+            &context.request_body,
+            &data,
+            context.session_id.clone(),
+            context.config_name.clone(),
+            context.client_ip.clone(),
+            context.response_time_ms,
+        ),
+        _ => return None,
+    }
+    .ok()?;
+    LogRecorder::finalize_log(context, &mut log);
+    Some(log)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_annotation_format() {
+        let annotation = CostAnnotation {
+            total_cost: 0.0123456789,
+            total_tokens: 1500,
+        };
+        assert_eq!(
+            annotation.headers(),
+            [
+                (COST_HEADER, "0.012346".to_string()),
+                (TOKENS_HEADER, "1500".to_string())
+            ]
+        );
+        assert_eq!(
+            annotation.sse_comment(),
+            ": x-duckcoding-cost=0.012346 x-duckcoding-tokens=1500\n\n"
+        );
+    }
+
+    #[test]
+    fn test_estimate_without_usage() {
+        let context = RequestLogContext {
+            tool_id: "claude-code".to_string(),
+            session_id: "session".to_string(),
+            full_session_id: "session".to_string(),
+            config_name: "default".to_string(),
+            client_ip: "127.0.0.1".to_string(),
+            pricing_template_id: None,
+            model: Some("claude-sonnet-4-5".to_string()),
+            is_stream: false,
+            request_body: Vec::new(),
+            response_time_ms: None,
+            override_tool_type: None,
+            upstream_headers: None,
+            upstream_key_alias: None,
+            client_key_label: None,
+            tags: Vec::new(),
+        };
+        assert_eq!(
+            CostAnnotation::estimate(&context, ParsedResponse::Empty),
+            None
+        );
+    }
+}
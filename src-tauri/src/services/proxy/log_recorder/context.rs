@@ -9,7 +9,8 @@ use crate::services::session::models::ProxySession;
 #[derive(Debug, Clone)]
 pub struct RequestLogContext {
     pub tool_id: String,
-    pub session_id: String, // 从 request_body 提取
+    pub session_id: String,      // 从 request_body 提取
+    pub full_session_id: String, // 完整 session_id（会话表主键）
     pub config_name: String,
     pub client_ip: String,
     pub pricing_template_id: Option<String>, // 会话级 > 代理级
@@ -18,6 +19,10 @@ pub struct RequestLogContext {
     pub request_body: Vec<u8>,               // 保留原始请求体
     pub response_time_ms: Option<i64>,       // 响应时间（毫秒）
     pub override_tool_type: Option<String>,  // 覆盖写入日志的 tool_type（供 AMP 等路由器使用）
+    pub upstream_headers: Option<String>,    // 捕获的上游响应头（JSON 对象）
+    pub upstream_key_alias: Option<String>,  // Key 池中本次使用的 Key 别名
+    pub client_key_label: Option<String>,    // 鉴权所用的客户端 Key 标签
+    pub tags: Vec<String>,                   // 会话标签（按项目归集花费）
 }
 
 impl RequestLogContext {
@@ -34,20 +39,8 @@ impl RequestLogContext {
         let (full_session_id, session_id, model, is_stream) = if !request_body.is_empty() {
             match serde_json::from_slice::<serde_json::Value>(request_body) {
                 Ok(json) => {
-                    // 根据工具类型提取 session_id
-                    let full_session_id = if tool_id == "codex" {
-                        // Codex: 从 prompt_cache_key 提取
-                        json["prompt_cache_key"]
-                            .as_str()
-                            .map(|s| s.to_string())
-                            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string())
-                    } else {
-                        // Claude 和其他: 从 metadata.user_id 提取
-                        json["metadata"]["user_id"]
-                            .as_str()
-                            .map(|s| s.to_string())
-                            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string())
-                    };
+                    let full_session_id = Self::extract_session_id(tool_id, &json)
+                        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
 
                     // 提取 display_id（用于存储日志）
                     let session_id = ProxySession::extract_display_id(&full_session_id);
@@ -69,10 +62,14 @@ impl RequestLogContext {
         // 查询会话级别的配置（优先级：会话 > 代理），使用完整 session_id 查询
         let (config_name, pricing_template_id) =
             Self::resolve_session_config(&full_session_id, config_name, proxy_pricing_template_id);
+        let tags = SESSION_MANAGER
+            .get_session_tags(&full_session_id)
+            .unwrap_or_default();
 
         Self {
             tool_id: tool_id.to_string(),
             session_id,
+            full_session_id,
             config_name,
             client_ip: client_ip.to_string(),
             pricing_template_id,
@@ -81,9 +78,43 @@ impl RequestLogContext {
             request_body: request_body.to_vec(),
             response_time_ms,
             override_tool_type: None,
+            upstream_headers: None,
+            upstream_key_alias: None,
+            client_key_label: None,
+            tags,
         }
     }
 
+    /// 根据工具类型从请求体提取完整 session_id
+    ///
+    /// Codex 使用 `prompt_cache_key`，Claude 和其他工具使用 `metadata.user_id`
+    pub fn extract_session_id(tool_id: &str, json: &serde_json::Value) -> Option<String> {
+        let value = if tool_id == "codex" {
+            &json["prompt_cache_key"]
+        } else {
+            &json["metadata"]["user_id"]
+        };
+        value.as_str().map(|s| s.to_string())
+    }
+
+    /// 附加捕获的上游响应头
+    pub fn with_upstream_headers(mut self, upstream_headers: Option<&str>) -> Self {
+        self.upstream_headers = upstream_headers.map(|s| s.to_string());
+        self
+    }
+
+    /// 附加 Key 池中本次使用的 Key 别名
+    pub fn with_upstream_key_alias(mut self, upstream_key_alias: Option<&str>) -> Self {
+        self.upstream_key_alias = upstream_key_alias.map(|s| s.to_string());
+        self
+    }
+
+    /// 附加鉴权所用的客户端 Key 标签
+    pub fn with_client_key_label(mut self, client_key_label: Option<&str>) -> Self {
+        self.client_key_label = client_key_label.map(|s| s.to_string());
+        self
+    }
+
     /// 解析会话级配置（同时提取 config_name 和 pricing_template_id）
     fn resolve_session_config(
         session_id: &str,
@@ -0,0 +1,271 @@
+// 上游 API 风格检测层
+//
+// 职责：根据响应形态识别上游实际使用的 API 风格，
+// 在用户把工具代理指向风格不符的中转站时切换提取器或给出明确告警
+
+use super::ParsedResponse;
+use crate::services::session::manager::SESSION_MANAGER;
+use once_cell::sync::Lazy;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// SSE 检测时最多检查的 data 块数量
+const MAX_SNIFF_LINES: usize = 8;
+
+/// 已记录风格的会话缓存上限（超出后整体清空）
+const MAX_CACHED_SESSIONS: usize = 4096;
+
+/// 已写入数据库的会话风格（完整 session_id → 风格），避免每次请求都写库
+static RECORDED_FLAVORS: Lazy<Mutex<HashMap<String, ApiFlavor>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 上游 API 风格
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiFlavor {
+    /// Anthropic Messages API
+    Anthropic,
+    /// OpenAI Responses API
+    OpenAiResponses,
+    /// OpenAI Chat Completions API
+    OpenAiChat,
+    /// Gemini generateContent API
+    Gemini,
+}
+
+impl ApiFlavor {
+    /// 风格标识（写入会话记录）
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiFlavor::Anthropic => "anthropic",
+            ApiFlavor::OpenAiResponses => "openai-responses",
+            ApiFlavor::OpenAiChat => "openai-chat",
+            ApiFlavor::Gemini => "gemini",
+        }
+    }
+
+    /// 工具预期的上游风格（amp-code 等按请求路由的工具返回 None）
+    pub fn expected_for_tool(tool_id: &str) -> Option<Self> {
+        match tool_id {
+            "claude-code" => Some(ApiFlavor::Anthropic),
+            "codex" => Some(ApiFlavor::OpenAiResponses),
+            "gemini-cli" => Some(ApiFlavor::Gemini),
+            _ => None,
+        }
+    }
+
+    /// 能够提取该风格 Token 的日志记录器（工具 ID）
+    pub fn extractor_tool(&self) -> Option<&'static str> {
+        match self {
+            ApiFlavor::Anthropic => Some("claude-code"),
+            ApiFlavor::OpenAiResponses => Some("codex"),
+            ApiFlavor::OpenAiChat | ApiFlavor::Gemini => None,
+        }
+    }
+
+    /// 从解析后的响应检测风格（无法判断时返回 None）
+    pub fn detect(parsed: &ParsedResponse) -> Option<Self> {
+        match parsed {
+            ParsedResponse::Sse { stream, .. } => stream
+                .head_lines()
+                .iter()
+                .take(MAX_SNIFF_LINES)
+                .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+                .find_map(|json| Self::detect_value(&json)),
+            ParsedResponse::Json { data } => Self::detect_value(data),
+            _ => None,
+        }
+    }
+
+    /// 从单个 JSON 对象（完整响应或 SSE 事件）检测风格
+    fn detect_value(json: &Value) -> Option<Self> {
+        if let Some(event_type) = json.get("type").and_then(|v| v.as_str()) {
+            if event_type.starts_with("response.") {
+                return Some(ApiFlavor::OpenAiResponses);
+            }
+            if event_type == "message"
+                || event_type == "ping"
+                || event_type.starts_with("message_")
+                || event_type.starts_with("content_block_")
+            {
+                return Some(ApiFlavor::Anthropic);
+            }
+        }
+
+        match json.get("object").and_then(|v| v.as_str()) {
+            Some("response") => return Some(ApiFlavor::OpenAiResponses),
+            Some("chat.completion") | Some("chat.completion.chunk") => {
+                return Some(ApiFlavor::OpenAiChat)
+            }
+            _ => {}
+        }
+
+        if json.get("candidates").is_some() || json.get("usageMetadata").is_some() {
+            return Some(ApiFlavor::Gemini);
+        }
+        if json.get("choices").is_some() {
+            return Some(ApiFlavor::OpenAiChat);
+        }
+
+        None
+    }
+}
+
+/// 风格检测结果：决定使用哪个提取器
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlavorResolution {
+    /// 实际使用的日志记录器（工具 ID）
+    pub extractor_tool: String,
+    /// 风格不符且无法切换时的说明（附加到 parse_error 详情）
+    pub mismatch: Option<String>,
+}
+
+impl FlavorResolution {
+    /// 根据工具预期与检测结果决定提取器
+    pub fn resolve(tool_id: &str, detected: Option<ApiFlavor>) -> Self {
+        let keep = Self {
+            extractor_tool: tool_id.to_string(),
+            mismatch: None,
+        };
+
+        let (Some(expected), Some(actual)) = (ApiFlavor::expected_for_tool(tool_id), detected)
+        else {
+            return keep;
+        };
+        if expected == actual {
+            return keep;
+        }
+
+        match actual.extractor_tool() {
+            Some(extractor) => Self {
+                extractor_tool: extractor.to_string(),
+                mismatch: None,
+            },
+            None => Self {
+                mismatch: Some(format!(
+                    "检测到上游为 {} 风格，与 {} 预期的 {} 风格不符",
+                    actual.as_str(),
+                    tool_id,
+                    expected.as_str()
+                )),
+                ..keep
+            },
+        }
+    }
+}
+
+/// 将检测到的风格记录到会话（每个会话仅在首次检测或风格变化时写库）
+///
+/// 返回 true 表示本次写入了新检测结果（调用方可据此决定是否告警）；
+/// 会话尚未落库或写入失败时返回 false，避免同一会话的每个请求重复告警
+pub fn record_session_flavor(full_session_id: &str, flavor: ApiFlavor) -> bool {
+    let Ok(mut cache) = RECORDED_FLAVORS.lock() else {
+        return false;
+    };
+    if cache.get(full_session_id) == Some(&flavor) {
+        return false;
+    }
+
+    match SESSION_MANAGER.update_session_flavor(full_session_id, flavor.as_str()) {
+        Ok(true) => {
+            if cache.len() >= MAX_CACHED_SESSIONS {
+                cache.clear();
+            }
+            cache.insert(full_session_id.to_string(), flavor);
+            true
+        }
+        // 会话尚未落库（批量写入延迟），下次响应再记录
+        Ok(false) => false,
+        Err(e) => {
+            tracing::debug!(error = ?e, "记录会话 API 风格失败");
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::token_stats::StreamingTokenAccumulator;
+    use serde_json::json;
+
+    fn sse(lines: &[&str]) -> ParsedResponse {
+        ParsedResponse::Sse {
+            stream: StreamingTokenAccumulator::from_data_lines(lines.iter().copied()),
+            quirks: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_detect_sse_flavors() {
+        assert_eq!(
+            ApiFlavor::detect(&sse(&[r#"{"type":"message_start","message":{}}"#])),
+            Some(ApiFlavor::Anthropic)
+        );
+        assert_eq!(
+            ApiFlavor::detect(&sse(&[r#"{"type":"response.created","response":{}}"#])),
+            Some(ApiFlavor::OpenAiResponses)
+        );
+        assert_eq!(
+            ApiFlavor::detect(&sse(&[
+                "not json",
+                r#"{"id":"c1","object":"chat.completion.chunk","choices":[]}"#
+            ])),
+            Some(ApiFlavor::OpenAiChat)
+        );
+        assert_eq!(
+            ApiFlavor::detect(&sse(&[r#"{"candidates":[],"usageMetadata":{}}"#])),
+            Some(ApiFlavor::Gemini)
+        );
+        assert_eq!(
+            ApiFlavor::detect(&sse(&[r#"{"type":"error","error":{}}"#])),
+            None
+        );
+    }
+
+    #[test]
+    fn test_detect_json_flavors() {
+        let anthropic = json!({"type": "message", "usage": {"input_tokens": 1}});
+        let responses = json!({"object": "response", "usage": {}});
+        let chat = json!({"object": "chat.completion", "choices": []});
+
+        assert_eq!(
+            ApiFlavor::detect(&ParsedResponse::Json { data: anthropic }),
+            Some(ApiFlavor::Anthropic)
+        );
+        assert_eq!(
+            ApiFlavor::detect(&ParsedResponse::Json { data: responses }),
+            Some(ApiFlavor::OpenAiResponses)
+        );
+        assert_eq!(
+            ApiFlavor::detect(&ParsedResponse::Json { data: chat }),
+            Some(ApiFlavor::OpenAiChat)
+        );
+        assert_eq!(ApiFlavor::detect(&ParsedResponse::Empty), None);
+    }
+
+    #[test]
+    fn test_resolve_switches_or_warns() {
+        // 风格一致或未检测到：保持原提取器
+        let same = FlavorResolution::resolve("claude-code", Some(ApiFlavor::Anthropic));
+        assert_eq!(same.extractor_tool, "claude-code");
+        assert!(same.mismatch.is_none());
+        let unknown = FlavorResolution::resolve("claude-code", None);
+        assert_eq!(unknown.extractor_tool, "claude-code");
+
+        // 有对应提取器：自动切换
+        let switched = FlavorResolution::resolve("claude-code", Some(ApiFlavor::OpenAiResponses));
+        assert_eq!(switched.extractor_tool, "codex");
+        assert!(switched.mismatch.is_none());
+
+        // 无对应提取器：保留原提取器并给出说明
+        let mismatch = FlavorResolution::resolve("claude-code", Some(ApiFlavor::OpenAiChat));
+        assert_eq!(mismatch.extractor_tool, "claude-code");
+        assert!(mismatch.mismatch.unwrap().contains("openai-chat"));
+
+        // amp-code 无固定预期
+        let amp = FlavorResolution::resolve("amp-code", Some(ApiFlavor::OpenAiChat));
+        assert_eq!(amp.extractor_tool, "amp-code");
+        assert!(amp.mismatch.is_none());
+    }
+}
@@ -0,0 +1,202 @@
+// 实时用量上报层
+//
+// 职责：SSE 流转发过程中按节流间隔估算当前请求的 Token 与成本，
+// 通过 TokenStatsManager 发送 `token-usage-delta` 事件；
+// 流结束后按实际 usage 发送 `done` 事件（写入数据库仍由 LogRecorder 负责）
+
+use std::time::Instant;
+
+use super::annotation::estimate_log;
+use super::{ParsedResponse, RequestLogContext};
+use crate::models::token_stats::TokenLog;
+use crate::services::token_stats::live_usage::{TokenUsageDelta, USAGE_DELTA_INTERVAL};
+use crate::services::token_stats::TokenStatsManager;
+
+/// 单个流式请求的实时用量上报器
+pub struct LiveUsageReporter {
+    context: RequestLogContext,
+    request_id: String,
+    last_emit: Option<Instant>,
+    /// 最近一次发送的事件（用于去重，以及流中断时补发 done）
+    last: Option<TokenUsageDelta>,
+    finished: bool,
+}
+
+impl LiveUsageReporter {
+    /// 创建上报器（没有事件接收方时返回 None，代理不做额外计算）
+    pub fn start(context: impl FnOnce() -> RequestLogContext) -> Option<Self> {
+        TokenStatsManager::has_usage_listener().then(|| Self {
+            context: context(),
+            request_id: uuid::Uuid::new_v4().to_string(),
+            last_emit: None,
+            last: None,
+            finished: false,
+        })
+    }
+
+    /// 距上次发送已超过节流间隔
+    pub fn is_due(&self) -> bool {
+        !self.finished
+            && self
+                .last_emit
+                .is_none_or(|at| at.elapsed() >= USAGE_DELTA_INTERVAL)
+    }
+
+    /// 流进行中：输出 Token 至少取按已生成内容估算的值
+    pub fn observe(&mut self, parsed: ParsedResponse) {
+        if self.finished {
+            return;
+        }
+        let parsed = match parsed {
+            ParsedResponse::Sse { stream, quirks } => ParsedResponse::Sse {
+                stream: stream.with_output_estimate(),
+                quirks,
+            },
+            other => other,
+        };
+        // 流开头尚未出现 usage 时不发送
+        let Some(log) = estimate_log(&self.context, parsed) else {
+            return;
+        };
+        let event = self.delta(&log, false);
+        if self
+            .last
+            .as_ref()
+            .is_some_and(|last| same_usage(last, &event))
+        {
+            return;
+        }
+        self.send(event);
+    }
+
+    /// 流结束：按实际 usage 发送最终事件（无法提取时沿用最近一次的数值）
+    pub fn finish(&mut self, parsed: ParsedResponse) {
+        if self.finished {
+            return;
+        }
+        self.finished = true;
+        let event = match estimate_log(&self.context, parsed) {
+            Some(log) => self.delta(&log, true),
+            None => match self.last.take() {
+                Some(last) => TokenUsageDelta {
+                    done: true,
+                    timestamp: chrono::Utc::now().timestamp_millis(),
+                    ..last
+                },
+                None => return,
+            },
+        };
+        self.send(event);
+    }
+
+    fn delta(&self, log: &TokenLog, done: bool) -> TokenUsageDelta {
+        TokenUsageDelta {
+            tool_id: log.tool_type.clone(),
+            session_id: self.context.session_id.clone(),
+            request_id: self.request_id.clone(),
+            model: log.model.clone(),
+            input_tokens: log.input_tokens,
+            output_tokens: log.output_tokens,
+            cache_creation_tokens: log.cache_creation_tokens,
+            cache_read_tokens: log.cache_read_tokens,
+            total_cost: log.total_cost,
+            done,
+            timestamp: chrono::Utc::now().timestamp_millis(),
+        }
+    }
+
+    fn send(&mut self, event: TokenUsageDelta) {
+        self.last_emit = Some(Instant::now());
+        self.last = Some(event.clone());
+        TokenStatsManager::emit_usage_delta(event);
+    }
+}
+
+fn same_usage(a: &TokenUsageDelta, b: &TokenUsageDelta) -> bool {
+    a.input_tokens == b.input_tokens
+        && a.output_tokens == b.output_tokens
+        && a.cache_creation_tokens == b.cache_creation_tokens
+        && a.cache_read_tokens == b.cache_read_tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::token_stats::StreamingTokenAccumulator;
+    use std::sync::{Arc, Mutex};
+
+    fn context() -> RequestLogContext {
+        RequestLogContext {
+            tool_id: "claude-code".to_string(),
+            session_id: "live-session".to_string(),
+            full_session_id: "live-session".to_string(),
+            config_name: "default".to_string(),
+            client_ip: "127.0.0.1".to_string(),
+            pricing_template_id: None,
+            model: Some("claude-sonnet-4-5".to_string()),
+            is_stream: true,
+            request_body: br#"{"model":"claude-sonnet-4-5","stream":true}"#.to_vec(),
+            response_time_ms: None,
+            override_tool_type: None,
+            upstream_headers: None,
+            upstream_key_alias: None,
+            client_key_label: None,
+            tags: Vec::new(),
+        }
+    }
+
+    fn sse(stream: &StreamingTokenAccumulator) -> ParsedResponse {
+        ParsedResponse::Sse {
+            stream: stream.clone(),
+            quirks: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_reporter_emits_estimates_then_done() {
+        let sink = Arc::new(Mutex::new(Vec::new()));
+        let events = Arc::clone(&sink);
+        TokenStatsManager::set_usage_notifier(Box::new(move |event| {
+            events.lock().unwrap().push(event)
+        }));
+
+        let mut reporter = LiveUsageReporter::start(context).unwrap();
+        let request_id = reporter.request_id.clone();
+        let mut stream = StreamingTokenAccumulator::new();
+
+        // 尚无 message_start：无法提取 usage，不发送
+        assert!(reporter.is_due());
+        reporter.observe(sse(&stream));
+
+        stream.push_data(
+            r#"{"type":"message_start","message":{"id":"msg_live","usage":{"input_tokens":100,"output_tokens":1}}}"#,
+        );
+        stream.push_data(
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"0123456789abcdef"}}"#,
+        );
+        reporter.observe(sse(&stream));
+        assert!(!reporter.is_due());
+
+        stream.push_data(r#"{"type":"message_delta","usage":{"output_tokens":3}}"#);
+        reporter.finish(sse(&stream));
+        assert!(!reporter.is_due());
+        reporter.finish(sse(&stream));
+
+        let events: Vec<_> = sink
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|e| e.request_id == request_id)
+            .cloned()
+            .collect();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].session_id, "live-session");
+        assert_eq!(events[0].model, "claude-sonnet-4-5");
+        assert_eq!(events[0].input_tokens, 100);
+        assert_eq!(events[0].output_tokens, 4);
+        assert!(!events[0].done);
+        // 最终事件以实际 usage 为准
+        assert_eq!(events[1].output_tokens, 3);
+        assert!(events[1].done);
+    }
+}
@@ -3,14 +3,24 @@
 // 职责：
 // - 提取请求上下文
 // - 解析响应数据（SSE/JSON）
+// - 检测上游实际 API 风格
+// - 诊断非标准 SSE 写法
 // - 提取 Token 统计
 // - 计算成本
+// - 流式请求的实时用量事件
 // - 记录到数据库
 
+mod annotation;
 mod context;
+mod flavor;
+mod live;
 mod parser;
+mod quirks;
 mod recorder;
 
+pub use annotation::CostAnnotation;
 pub use context::RequestLogContext;
+pub use flavor::{ApiFlavor, FlavorResolution};
+pub use live::LiveUsageReporter;
 pub use parser::{ParsedResponse, ResponseParser};
 pub use recorder::LogRecorder;
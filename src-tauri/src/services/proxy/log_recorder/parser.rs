@@ -2,13 +2,18 @@
 //
 // 职责：安全解析响应数据，区分 SSE 流式和 JSON 非流式，永不 panic
 
+use crate::services::proxy::utils::sse_quirks::{data_payloads, detect_quirks, SseQuirk};
+use crate::services::token_stats::StreamingTokenAccumulator;
 use serde_json::Value;
 
 /// 解析后的响应数据
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum ParsedResponse {
-    /// SSE 流式响应（已提取的 data 块）
-    Sse { data_lines: Vec<String> },
+    /// SSE 流式响应（已累加的 Token 状态，以及观察到的非标准写法）
+    Sse {
+        stream: StreamingTokenAccumulator,
+        quirks: Vec<SseQuirk>,
+    },
     /// JSON 响应（已解析的 JSON）
     Json { data: Value },
     /// 空响应（上游失败或连接中断）
@@ -49,25 +54,37 @@ impl ResponseParser {
     ///
     /// data: {"type":"message_delta","delta":{...},"usage":{...}}
     /// ```
+    ///
+    /// 兼容非标准中转：流首 BOM、`\r` 换行、`data:` 后无空格、注释 / keep-alive 行
     fn parse_sse(response_body: &[u8]) -> ParsedResponse {
         let body_str = String::from_utf8_lossy(response_body);
-        let data_lines: Vec<String> = body_str
-            .lines()
-            .filter(|line| line.starts_with("data: "))
-            .map(|line| line.trim_start_matches("data: ").to_string())
-            .filter(|line| !line.is_empty() && line != "[DONE]") // 过滤空行和结束标记
-            .collect();
+        // 空行和结束标记由累加器过滤
+        let stream = StreamingTokenAccumulator::from_data_lines(data_payloads(&body_str));
+        if stream.data_line_count() == 0 {
+            return Self::empty_sse(response_body.to_vec());
+        }
 
-        if data_lines.is_empty() {
-            // SSE 流为空或仅包含无效数据
-            return ParsedResponse::ParseError {
-                raw_bytes: response_body.to_vec(),
-                error: "SSE 流不包含有效的 data 块".to_string(),
-                response_type: "sse",
-            };
+        ParsedResponse::Sse {
+            stream,
+            quirks: detect_quirks(response_body),
         }
+    }
 
-        ParsedResponse::Sse { data_lines }
+    /// 由转发过程中累加的 SSE 状态构建解析结果（不持有响应体）
+    pub fn from_stream(stream: StreamingTokenAccumulator, quirks: Vec<SseQuirk>) -> ParsedResponse {
+        if stream.data_line_count() == 0 {
+            return Self::empty_sse(Vec::new());
+        }
+        ParsedResponse::Sse { stream, quirks }
+    }
+
+    /// SSE 流为空或仅包含无效数据
+    fn empty_sse(raw_bytes: Vec<u8>) -> ParsedResponse {
+        ParsedResponse::ParseError {
+            raw_bytes,
+            error: "SSE 流不包含有效的 data 块".to_string(),
+            response_type: "sse",
+        }
     }
 
     /// 解析 JSON 响应
@@ -82,3 +99,30 @@ impl ResponseParser {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sse_from_quirky_relay() {
+        let body = "\u{feff}: keep-alive\r\rdata:{\"type\":\"message_start\"}\r\r\
+data: {\"type\":\"message_delta\"}\r\rdata: [DONE]\r\r";
+
+        match ResponseParser::parse(body.as_bytes(), 200, true) {
+            ParsedResponse::Sse { stream, quirks } => {
+                assert_eq!(
+                    stream.head_lines(),
+                    vec![
+                        r#"{"type":"message_start"}"#.to_string(),
+                        r#"{"type":"message_delta"}"#.to_string(),
+                    ]
+                );
+                assert!(quirks.contains(&SseQuirk::Bom));
+                assert!(quirks.contains(&SseQuirk::DataWithoutSpace));
+                assert!(quirks.contains(&SseQuirk::BareCarriageReturn));
+            }
+            other => panic!("unexpected: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,80 @@
+// 非标准 SSE 诊断层
+//
+// 职责：把解析时观察到的非标准 SSE 写法累积记录到会话，
+// 让用户能看到具体是哪些差异，并据此开启对应的兼容选项
+
+use crate::services::proxy::utils::sse_quirks::SseQuirk;
+use crate::services::session::manager::SESSION_MANAGER;
+use once_cell::sync::Lazy;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Mutex;
+
+/// 已记录诊断的会话缓存上限（超出后整体清空）
+const MAX_CACHED_SESSIONS: usize = 4096;
+
+/// 已写入数据库的会话 SSE 差异（完整 session_id → 差异集合）
+static RECORDED_QUIRKS: Lazy<Mutex<HashMap<String, BTreeSet<SseQuirk>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 将差异序列化为会话字段（逗号分隔的诊断标识）
+pub fn format_quirks<'a>(quirks: impl IntoIterator<Item = &'a SseQuirk>) -> String {
+    quirks
+        .into_iter()
+        .map(|q| q.as_str())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// 累积记录会话观察到的 SSE 差异（仅在出现新差异时写库）
+///
+/// 返回本次新增的差异（调用方可据此决定是否告警）
+pub fn record_session_quirks(full_session_id: &str, quirks: &[SseQuirk]) -> Vec<SseQuirk> {
+    if quirks.is_empty() {
+        return Vec::new();
+    }
+    let Ok(mut cache) = RECORDED_QUIRKS.lock() else {
+        return Vec::new();
+    };
+
+    let known = cache.get(full_session_id).cloned().unwrap_or_default();
+    let new_quirks: Vec<SseQuirk> = quirks
+        .iter()
+        .filter(|q| !known.contains(q))
+        .copied()
+        .collect();
+    if new_quirks.is_empty() {
+        return new_quirks;
+    }
+
+    let mut merged = known;
+    merged.extend(new_quirks.iter().copied());
+
+    match SESSION_MANAGER.update_session_quirks(full_session_id, &format_quirks(&merged)) {
+        Ok(true) => {
+            if cache.len() >= MAX_CACHED_SESSIONS {
+                cache.clear();
+            }
+            cache.insert(full_session_id.to_string(), merged);
+        }
+        // 会话尚未落库（批量写入延迟），下次响应再记录
+        Ok(false) => {}
+        Err(e) => {
+            tracing::debug!(error = ?e, "记录会话 SSE 诊断失败");
+        }
+    }
+    new_quirks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_quirks() {
+        assert_eq!(format_quirks(&[]), "");
+        assert_eq!(
+            format_quirks(&[SseQuirk::Bom, SseQuirk::MissingEventName]),
+            "bom,missing-event-name"
+        );
+    }
+}
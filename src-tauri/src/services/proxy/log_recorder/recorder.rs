@@ -2,9 +2,17 @@
 //
 // 职责：统一的日志记录接口，处理成功/失败/解析错误等所有场景
 
-use super::{ParsedResponse, RequestLogContext};
-use crate::services::token_stats::logger::create_logger;
+use super::flavor::record_session_flavor;
+use super::quirks::{format_quirks, record_session_quirks};
+use super::{ApiFlavor, FlavorResolution, ParsedResponse, RequestLogContext};
+use crate::services::proxy::metrics::ProxyMetrics;
+use crate::services::proxy::rate_limit::RateLimiter;
+use crate::services::proxy::utils::sse_quirks::SseQuirk;
+use crate::services::session::context_usage::ContextUsageTracker;
+use crate::services::token_stats::logger::{create_logger, LogStatus, ResponseType};
 use crate::services::token_stats::manager::TokenStatsManager;
+use crate::services::token_stats::template_binding;
+use crate::services::token_stats::StreamingTokenAccumulator;
 use anyhow::Result;
 use hyper::StatusCode;
 
@@ -25,15 +33,17 @@ impl LogRecorder {
             // HTTP 4xx/5xx 错误
             Self::record_http_error(context, response_status, &status_code).await
         } else {
-            // HTTP 2xx/3xx 或无状态码，根据解析结果处理
+            // HTTP 2xx/3xx 或无状态码，先检测上游实际风格，再根据解析结果处理
+            let resolution = Self::resolve_flavor(context, &parsed);
             match parsed {
-                ParsedResponse::Sse { data_lines } => {
+                ParsedResponse::Sse { stream, quirks } => {
                     // SSE 成功响应
-                    Self::record_sse_success(context, data_lines).await
+                    Self::record_quirks(context, &quirks);
+                    Self::record_sse_success(context, &resolution, &stream).await
                 }
                 ParsedResponse::Json { data } => {
                     // JSON 成功响应
-                    Self::record_json_success(context, data).await
+                    Self::record_json_success(context, &resolution, data).await
                 }
                 ParsedResponse::Empty => {
                     // 空响应（上游失败）
@@ -51,16 +61,66 @@ impl LogRecorder {
         }
     }
 
+    /// 检测上游 API 风格，记录到会话并决定使用的提取器
+    fn resolve_flavor(context: &RequestLogContext, parsed: &ParsedResponse) -> FlavorResolution {
+        let detected = ApiFlavor::detect(parsed);
+        let resolution = FlavorResolution::resolve(&context.tool_id, detected);
+
+        let Some(flavor) = detected else {
+            return resolution;
+        };
+        // 每个会话仅在首次检测（或风格变化）时告警
+        if !record_session_flavor(&context.full_session_id, flavor) {
+            return resolution;
+        }
+
+        if let Some(ref mismatch) = resolution.mismatch {
+            tracing::warn!(
+                tool_id = %context.tool_id,
+                session_id = %context.session_id,
+                api_flavor = flavor.as_str(),
+                "{}，Token 统计将无法提取，请检查代理上游配置",
+                mismatch
+            );
+        } else if resolution.extractor_tool != context.tool_id {
+            tracing::warn!(
+                tool_id = %context.tool_id,
+                session_id = %context.session_id,
+                api_flavor = flavor.as_str(),
+                extractor = %resolution.extractor_tool,
+                "上游 API 风格与工具预期不符，已自动切换 Token 提取器"
+            );
+        }
+
+        resolution
+    }
+
+    /// 记录会话观察到的非标准 SSE 写法（每种差异每个会话仅告警一次）
+    fn record_quirks(context: &RequestLogContext, quirks: &[SseQuirk]) {
+        let new_quirks = record_session_quirks(&context.full_session_id, quirks);
+        if new_quirks.is_empty() {
+            return;
+        }
+
+        tracing::info!(
+            tool_id = %context.tool_id,
+            session_id = %context.session_id,
+            quirks = %format_quirks(&new_quirks),
+            "上游 SSE 存在非标准写法，已兼容解析；如客户端异常可在代理配置中开启 SSE 兼容选项"
+        );
+    }
+
     /// 记录 SSE 成功响应
     async fn record_sse_success(
         context: &RequestLogContext,
-        data_lines: Vec<String>,
+        resolution: &FlavorResolution,
+        stream: &StreamingTokenAccumulator,
     ) -> Result<()> {
-        let logger = create_logger(&context.tool_id)?;
+        let logger = create_logger(&resolution.extractor_tool)?;
 
         match logger.log_sse_response(
             &context.request_body,
-            data_lines,
+            stream,
             context.session_id.clone(),
             context.config_name.clone(),
             context.client_ip.clone(),
@@ -84,7 +144,8 @@ impl LogRecorder {
                 );
 
                 // Token 提取失败，记录为 parse_error
-                let error_detail = format!("SSE Token 提取失败: {}", e);
+                let error_detail =
+                    Self::with_mismatch(format!("SSE Token 提取失败: {}", e), resolution);
                 let failed_log = logger.log_failed_request(
                     &context.request_body,
                     context.session_id.clone(),
@@ -103,9 +164,10 @@ impl LogRecorder {
     /// 记录 JSON 成功响应
     async fn record_json_success(
         context: &RequestLogContext,
+        resolution: &FlavorResolution,
         data: serde_json::Value,
     ) -> Result<()> {
-        let logger = create_logger(&context.tool_id)?;
+        let logger = create_logger(&resolution.extractor_tool)?;
 
         match logger.log_json_response(
             &context.request_body,
@@ -133,7 +195,8 @@ impl LogRecorder {
                 );
 
                 // Token 提取失败，记录为 parse_error
-                let error_detail = format!("JSON Token 提取失败: {}", e);
+                let error_detail =
+                    Self::with_mismatch(format!("JSON Token 提取失败: {}", e), resolution);
                 let failed_log = logger.log_failed_request(
                     &context.request_body,
                     context.session_id.clone(),
@@ -237,11 +300,109 @@ impl LogRecorder {
         Ok(())
     }
 
-    /// 写入日志，如果 context 指定了 override_tool_type 则覆盖 tool_type
-    fn write_log(context: &RequestLogContext, mut log: crate::models::token_stats::TokenLog) {
-        if let Some(ref tid) = context.override_tool_type {
-            log.tool_type = tid.clone();
+    /// 记录被本地限流拦截的请求（未转发到上游，无 Token 用量）
+    pub fn record_rate_limited(context: &RequestLogContext, reason: &str) {
+        let log = Self::unbilled_log(context, LogStatus::RateLimited, "rate_limited", reason);
+        Self::write_log(context, log);
+    }
+
+    /// 记录上游超时的请求（流式响应超时前已收到的数据不计入用量）
+    pub fn record_timeout(context: &RequestLogContext, detail: &str) {
+        tracing::warn!(
+            tool_id = %context.tool_id,
+            session_id = %context.session_id,
+            detail = detail,
+            is_stream = context.is_stream,
+            "上游请求超时"
+        );
+
+        let log = Self::unbilled_log(context, LogStatus::Timeout, "timeout", detail);
+        Self::write_log(context, log);
+    }
+
+    /// 无 Token 用量的日志（限流、超时）
+    fn unbilled_log(
+        context: &RequestLogContext,
+        status: LogStatus,
+        error_type: &str,
+        detail: &str,
+    ) -> crate::models::token_stats::TokenLog {
+        crate::models::token_stats::TokenLog::new(
+            context.tool_id.clone(),
+            chrono::Utc::now().timestamp_millis(),
+            context.client_ip.clone(),
+            context.session_id.clone(),
+            context.config_name.clone(),
+            context
+                .model
+                .clone()
+                .unwrap_or_else(|| "unknown".to_string()),
+            None,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            status.as_str().to_string(),
+            ResponseType::Unknown.as_str().to_string(),
+            Some(error_type.to_string()),
+            Some(detail.to_string()),
+            context.response_time_ms,
+            None,
+            None,
+            None,
+            None,
+            None,
+            0.0,
+            None,
+        )
+    }
+
+    /// 在错误详情后附加风格不符说明
+    fn with_mismatch(detail: String, resolution: &FlavorResolution) -> String {
+        match resolution.mismatch {
+            Some(ref mismatch) => format!("{}（{}）", detail, mismatch),
+            None => detail,
         }
+    }
+
+    /// 写入日志，tool_type 固定为 context 的工具（或 override_tool_type）
+    ///
+    /// 切换提取器后日志仍归属原工具，避免统计被记到错误的工具下；
+    /// 请求或 Profile 绑定了价格模板时按该模板重新计价
+    fn write_log(context: &RequestLogContext, mut log: crate::models::token_stats::TokenLog) {
+        Self::finalize_log(context, &mut log);
+        // 回填限流器的 Token 用量（按写入日志的工具归属）
+        RateLimiter::global().record_tokens(
+            &log.tool_type,
+            &context.full_session_id,
+            log.total_tokens().max(0) as u64,
+        );
+        ProxyMetrics::global().observe(&log);
+        ContextUsageTracker::global().observe(&context.full_session_id, &log);
         TokenStatsManager::get().write_log(log);
     }
+
+    /// 补齐日志归属与计价（写入数据库与成本注解共用）
+    pub(super) fn finalize_log(
+        context: &RequestLogContext,
+        log: &mut crate::models::token_stats::TokenLog,
+    ) {
+        log.tool_type = context
+            .override_tool_type
+            .clone()
+            .unwrap_or_else(|| context.tool_id.clone());
+        log.upstream_headers = context.upstream_headers.clone();
+        log.upstream_key_alias = context.upstream_key_alias.clone();
+        log.client_key_label = context.client_key_label.clone();
+        log.tags = context.tags.clone();
+        if let Some(template_id) = template_binding::resolve_template_id(
+            &context.tool_id,
+            &context.config_name,
+            context.pricing_template_id.as_deref(),
+        ) {
+            template_binding::apply_template(log, &template_id);
+        }
+    }
 }
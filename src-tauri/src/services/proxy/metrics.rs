@@ -0,0 +1,366 @@
+// 透明代理 Prometheus 指标
+//
+// - 每条写入的 Token 日志都会累计到进程级指标注册表（请求数、Token 数、成本、上游耗时）
+// - 在 GlobalConfig.metrics 启用后，独立监听端口以 Prometheus 文本格式暴露 `/metrics`
+// - 指标与代理实例无关，代理重启或切换配置不会清零（进程重启后从零开始）
+
+use anyhow::Result;
+use bytes::Bytes;
+use http_body_util::Full;
+use hyper::body::Incoming;
+use hyper::{Method, Request, Response, StatusCode};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+
+use super::response_cache::ResponseCache;
+use crate::models::config::MetricsConfig;
+use crate::models::token_stats::TokenLog;
+use crate::services::local_server::{HttpResponse, LocalServer};
+
+/// 上游耗时直方图的桶上界（秒）
+const LATENCY_BUCKETS: [f64; 11] = [
+    0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0,
+];
+
+/// Token 类型标签与取值
+const TOKEN_TYPES: [&str; 5] = [
+    "input",
+    "output",
+    "cache_creation",
+    "cache_read",
+    "reasoning",
+];
+
+static METRICS: Lazy<ProxyMetrics> = Lazy::new(ProxyMetrics::default);
+
+static SERVER: Lazy<LocalServer<MetricsConfig>> =
+    Lazy::new(|| LocalServer::new("Prometheus 指标监听"));
+
+/// (tool, model, status)
+type SeriesKey = (String, String, String);
+
+#[derive(Debug, Default, Clone)]
+struct Series {
+    requests: u64,
+    tokens: [u64; 5],
+    cost_usd: f64,
+}
+
+#[derive(Debug, Clone)]
+struct Histogram {
+    /// 各桶累计计数（不含 +Inf）
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self {
+            buckets: [0; LATENCY_BUCKETS.len()],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+}
+
+impl Histogram {
+    fn observe(&mut self, seconds: f64) {
+        for (bucket, bound) in self.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if seconds <= bound {
+                *bucket += 1;
+            }
+        }
+        self.sum += seconds;
+        self.count += 1;
+    }
+}
+
+#[derive(Debug, Default)]
+struct MetricsInner {
+    series: BTreeMap<SeriesKey, Series>,
+    /// 按工具 + 模型的上游耗时
+    latency: BTreeMap<(String, String), Histogram>,
+}
+
+/// 进程级代理指标注册表
+#[derive(Debug, Default)]
+pub struct ProxyMetrics {
+    inner: Mutex<MetricsInner>,
+}
+
+/// 转义标签值（反斜杠、双引号、换行）
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+impl ProxyMetrics {
+    /// 全局指标注册表
+    pub fn global() -> &'static ProxyMetrics {
+        &METRICS
+    }
+
+    /// 累计一条已完成请求的日志
+    pub fn observe(&self, log: &TokenLog) {
+        let key = (
+            log.tool_type.clone(),
+            log.model.clone(),
+            log.request_status.clone(),
+        );
+        let tokens = [
+            log.input_tokens,
+            log.output_tokens,
+            log.cache_creation_tokens,
+            log.cache_read_tokens,
+            log.reasoning_tokens,
+        ];
+
+        let mut inner = self.inner.lock().unwrap();
+        let series = inner.series.entry(key).or_default();
+        series.requests += 1;
+        for (total, value) in series.tokens.iter_mut().zip(tokens) {
+            *total += value.max(0) as u64;
+        }
+        series.cost_usd += log.total_cost;
+
+        if let Some(ms) = log.response_time_ms.filter(|ms| *ms >= 0) {
+            inner
+                .latency
+                .entry((log.tool_type.clone(), log.model.clone()))
+                .or_default()
+                .observe(ms as f64 / 1000.0);
+        }
+    }
+
+    /// 以 Prometheus 文本格式（0.0.4）输出所有指标
+    pub fn render(&self) -> String {
+        let inner = self.inner.lock().unwrap();
+        let mut out = String::new();
+
+        out.push_str("# HELP duckcoding_proxy_requests_total 透明代理完成的请求数\n");
+        out.push_str("# TYPE duckcoding_proxy_requests_total counter\n");
+        for ((tool, model, status), series) in &inner.series {
+            let _ = writeln!(
+                out,
+                "duckcoding_proxy_requests_total{{tool=\"{}\",model=\"{}\",status=\"{}\"}} {}",
+                escape_label(tool),
+                escape_label(model),
+                escape_label(status),
+                series.requests
+            );
+        }
+
+        // Token 与成本按 tool + model 汇总（不区分状态）
+        let mut by_model: BTreeMap<(&str, &str), Series> = BTreeMap::new();
+        for ((tool, model, _), series) in &inner.series {
+            let total = by_model.entry((tool, model)).or_default();
+            for (sum, value) in total.tokens.iter_mut().zip(series.tokens) {
+                *sum += value;
+            }
+            total.cost_usd += series.cost_usd;
+        }
+
+        out.push_str("# HELP duckcoding_proxy_tokens_total 透明代理累计 Token 数\n");
+        out.push_str("# TYPE duckcoding_proxy_tokens_total counter\n");
+        for ((tool, model), series) in &by_model {
+            for (token_type, value) in TOKEN_TYPES.iter().zip(series.tokens) {
+                let _ = writeln!(
+                    out,
+                    "duckcoding_proxy_tokens_total{{tool=\"{}\",model=\"{}\",type=\"{}\"}} {}",
+                    escape_label(tool),
+                    escape_label(model),
+                    token_type,
+                    value
+                );
+            }
+        }
+
+        out.push_str("# HELP duckcoding_proxy_cost_usd_total 透明代理累计成本（USD）\n");
+        out.push_str("# TYPE duckcoding_proxy_cost_usd_total counter\n");
+        for ((tool, model), series) in &by_model {
+            let _ = writeln!(
+                out,
+                "duckcoding_proxy_cost_usd_total{{tool=\"{}\",model=\"{}\"}} {}",
+                escape_label(tool),
+                escape_label(model),
+                series.cost_usd
+            );
+        }
+
+        out.push_str("# HELP duckcoding_proxy_upstream_latency_seconds 上游响应耗时\n");
+        out.push_str("# TYPE duckcoding_proxy_upstream_latency_seconds histogram\n");
+        for ((tool, model), histogram) in &inner.latency {
+            let labels = format!(
+                "tool=\"{}\",model=\"{}\"",
+                escape_label(tool),
+                escape_label(model)
+            );
+            for (bound, count) in LATENCY_BUCKETS.iter().zip(histogram.buckets) {
+                let _ = writeln!(
+                    out,
+                    "duckcoding_proxy_upstream_latency_seconds_bucket{{{labels},le=\"{bound}\"}} {count}"
+                );
+            }
+            let _ = writeln!(
+                out,
+                "duckcoding_proxy_upstream_latency_seconds_bucket{{{labels},le=\"+Inf\"}} {}",
+                histogram.count
+            );
+            let _ = writeln!(
+                out,
+                "duckcoding_proxy_upstream_latency_seconds_sum{{{labels}}} {}",
+                histogram.sum
+            );
+            let _ = writeln!(
+                out,
+                "duckcoding_proxy_upstream_latency_seconds_count{{{labels}}} {}",
+                histogram.count
+            );
+        }
+
+        out.push_str("# HELP duckcoding_proxy_cache_lookups_total 响应缓存查找次数\n");
+        out.push_str("# TYPE duckcoding_proxy_cache_lookups_total counter\n");
+        for stats in ResponseCache::global().stats() {
+            let tool = escape_label(&stats.tool_id);
+            for (result, value) in [("hit", stats.hits), ("miss", stats.misses)] {
+                let _ = writeln!(
+                    out,
+                    "duckcoding_proxy_cache_lookups_total{{tool=\"{tool}\",result=\"{result}\"}} {value}"
+                );
+            }
+        }
+
+        out
+    }
+}
+
+/// 指标监听状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct MetricsStatus {
+    pub config: MetricsConfig,
+    pub running: bool,
+    /// 抓取地址（未运行时为 None）
+    pub endpoint: Option<String>,
+}
+
+/// 按配置启动、重启或停止指标监听（配置未变化时不做任何操作）
+pub async fn apply_config(config: &MetricsConfig) -> Result<()> {
+    SERVER
+        .apply(config, |config| {
+            if !config.enabled {
+                return Ok(None);
+            }
+            let addr = if config.allow_public {
+                SocketAddr::from(([0, 0, 0, 0], config.port))
+            } else {
+                SocketAddr::from(([127, 0, 0, 1], config.port))
+            };
+            Ok(Some((addr, |req| async move { respond(&req) })))
+        })
+        .await
+}
+
+/// 当前指标监听状态（地址为实际监听地址）
+pub async fn metrics_status(config: MetricsConfig) -> MetricsStatus {
+    let running = SERVER.running().await;
+    MetricsStatus {
+        endpoint: running
+            .as_ref()
+            .map(|(_, addr)| format!("http://{}/metrics", addr)),
+        running: running.is_some(),
+        config: running.map(|(config, _)| config).unwrap_or(config),
+    }
+}
+
+fn respond(req: &Request<Incoming>) -> HttpResponse {
+    if req.method() != Method::GET || req.uri().path() != "/metrics" {
+        return Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Full::new(Bytes::from_static(b"Not Found")))
+            .unwrap();
+    }
+    Response::builder()
+        .status(StatusCode::OK)
+        .header("content-type", "text/plain; version=0.0.4; charset=utf-8")
+        .body(Full::new(Bytes::from(ProxyMetrics::global().render())))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(model: &str, status: &str, latency_ms: i64, cost: f64) -> TokenLog {
+        TokenLog::test_builder()
+            .tool("codex")
+            .model(model)
+            .tokens(100, 40)
+            .cache_tokens(0, 10)
+            .status(status)
+            .response_type("sse")
+            .response_time(latency_ms)
+            .cost(cost)
+            .build()
+    }
+
+    #[tokio::test]
+    async fn test_status_reports_bound_address() {
+        let config = MetricsConfig {
+            enabled: true,
+            port: 0,
+            allow_public: true,
+        };
+        apply_config(&config).await.unwrap();
+        let endpoint = metrics_status(config.clone()).await.endpoint.unwrap();
+        assert!(endpoint.starts_with("http://0.0.0.0:"));
+        assert!(!endpoint.starts_with("http://0.0.0.0:0/"));
+
+        let disabled = MetricsConfig {
+            enabled: false,
+            ..config
+        };
+        apply_config(&disabled).await.unwrap();
+        assert!(metrics_status(disabled).await.endpoint.is_none());
+    }
+
+    #[test]
+    fn test_render_counters_and_histogram() {
+        let metrics = ProxyMetrics::default();
+        metrics.observe(&log("gpt-5", "success", 800, 0.5));
+        metrics.observe(&log("gpt-5", "failed", 3_000, 0.0));
+        metrics.observe(&log("gpt-\"x\"", "success", 200, 0.25));
+
+        let text = metrics.render();
+        assert!(text.contains(
+            "duckcoding_proxy_requests_total{tool=\"codex\",model=\"gpt-5\",status=\"failed\"} 1"
+        ));
+        assert!(text.contains(
+            "duckcoding_proxy_tokens_total{tool=\"codex\",model=\"gpt-5\",type=\"input\"} 200"
+        ));
+        assert!(
+            text.contains("duckcoding_proxy_cost_usd_total{tool=\"codex\",model=\"gpt-5\"} 0.5")
+        );
+        // 标签值中的引号被转义
+        assert!(text.contains("model=\"gpt-\\\"x\\\"\""));
+
+        let bucket = |le: &str| {
+            format!(
+                "duckcoding_proxy_upstream_latency_seconds_bucket{{tool=\"codex\",model=\"gpt-5\",le=\"{le}\"}}"
+            )
+        };
+        assert!(text.contains(&format!("{} 1", bucket("1"))));
+        assert!(text.contains(&format!("{} 2", bucket("5"))));
+        assert!(text.contains(&format!("{} 2", bucket("+Inf"))));
+        assert!(text.contains(
+            "duckcoding_proxy_upstream_latency_seconds_sum{tool=\"codex\",model=\"gpt-5\"} 3.8"
+        ));
+    }
+}
@@ -2,13 +2,32 @@
 //
 // 包含代理配置、透明代理等功能
 
+pub mod auth_bridge; // 企业认证代理（NTLM/Negotiate 本地认证桥）
+pub mod capture_store; // 请求/响应体捕获（强制过期）
+pub mod concurrency_limit; // 并发上游请求上限
 pub mod config; // 代理配置辅助模块
+pub mod cost_guard; // 单次请求输入成本预检
+pub mod egress; // 工具级出口代理（http / https / socks5）
+pub mod failover; // 多上游故障转移
 pub mod headers;
+pub mod key_pool; // 上游 API Key 池负载均衡
+pub mod listener; // 监听器（TCP 端口 / Unix 域套接字 / 命名管道）
 pub mod log_recorder; // 统一日志记录模块
+pub mod metrics; // Prometheus 指标导出
+pub mod model_router; // 按模型名路由上游
+pub mod protocol; // Anthropic ↔ OpenAI 协议转换
 pub mod proxy_instance;
 pub mod proxy_manager;
 pub mod proxy_service;
+pub mod rate_limit; // 本地限流（每分钟请求数 / Token 数）
+pub mod redaction; // 请求内容脱敏（掩码 / 拦截）
+pub mod replay; // 流量重放压测（按速率重放请求捕获）
+pub mod response_cache; // 幂等接口响应缓存（模型列表 / Embedding）
+pub mod trial; // 供应商试用模式（项目级临时代理）
+pub mod upstream_queue; // 上游限流排队（429 + Retry-After）
+pub mod upstream_timeout; // 上游超时（连接 / 总时长 / 流式响应空闲）
 pub mod utils;
+pub mod websocket; // WebSocket 升级透传
 
 pub use headers::{create_request_processor, ProcessedRequest, RequestProcessor};
 // 向后兼容的导出（已弃用）
@@ -0,0 +1,111 @@
+// 透明代理模型路由
+//
+// 按请求的模型名匹配 `ToolProxyConfig.routing_rules`：
+// - 规则按配置顺序匹配，第一条命中的启用规则生效
+// - 模型名匹配使用 glob（`*` / `?`），不区分大小写
+// - 未解析出上游地址的规则视为无效，直接跳过
+
+use crate::models::proxy_config::ModelRoutingRule;
+
+/// glob 匹配（`*` 匹配任意长度字符，`?` 匹配单个字符，不区分大小写）
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // 最近一个 `*` 的位置及其当时对应的文本位置，用于回溯
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// 查找第一条匹配模型名的有效规则
+pub fn match_rule<'a>(rules: &'a [ModelRoutingRule], model: &str) -> Option<&'a ModelRoutingRule> {
+    rules.iter().find(|rule| {
+        rule.enabled && !rule.base_url.is_empty() && glob_match(&rule.model_pattern, model)
+    })
+}
+
+/// 从 Gemini 风格路径提取模型名（`/v1beta/models/{model}:generateContent`）
+pub fn model_from_path(path: &str) -> Option<&str> {
+    let path = path.split('?').next().unwrap_or(path);
+    let rest = &path[path.find("/models/")? + "/models/".len()..];
+    let model = rest.split([':', '/']).next().unwrap_or(rest);
+    (!model.is_empty()).then_some(model)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(id: &str, pattern: &str, base_url: &str, enabled: bool) -> ModelRoutingRule {
+        ModelRoutingRule {
+            id: id.to_string(),
+            model_pattern: pattern.to_string(),
+            profile_name: format!("profile-{id}"),
+            base_url: base_url.to_string(),
+            api_key: "sk-test".to_string(),
+            pricing_template_id: None,
+            enabled,
+        }
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("claude-haiku-*", "claude-haiku-4-5-20251001"));
+        assert!(glob_match("CLAUDE-*-4-?", "claude-opus-4-1"));
+        assert!(glob_match("*sonnet*", "claude-sonnet-4-5"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("gpt-5", "GPT-5"));
+        assert!(!glob_match("gpt-5", "gpt-5-codex"));
+        assert!(!glob_match("claude-opus-*", "claude-haiku-4-5"));
+        assert!(!glob_match("a?c", "ac"));
+    }
+
+    #[test]
+    fn test_match_rule_first_enabled_wins() {
+        let rules = vec![
+            rule("disabled", "claude-haiku-*", "https://a.example.com", false),
+            rule("unresolved", "claude-haiku-*", "", true),
+            rule("cheap", "claude-haiku-*", "https://b.example.com", true),
+            rule("fallback", "claude-*", "https://c.example.com", true),
+        ];
+
+        assert_eq!(
+            match_rule(&rules, "claude-haiku-4-5").map(|r| r.id.as_str()),
+            Some("cheap")
+        );
+        assert_eq!(
+            match_rule(&rules, "claude-opus-4-1").map(|r| r.id.as_str()),
+            Some("fallback")
+        );
+        assert!(match_rule(&rules, "gpt-5").is_none());
+    }
+
+    #[test]
+    fn test_model_from_path() {
+        assert_eq!(
+            model_from_path("/v1beta/models/gemini-2.5-pro:streamGenerateContent?alt=sse"),
+            Some("gemini-2.5-pro")
+        );
+        assert_eq!(
+            model_from_path("/v1/models/gemini-2.5-flash"),
+            Some("gemini-2.5-flash")
+        );
+        assert_eq!(model_from_path("/v1/chat/completions"), None);
+    }
+}
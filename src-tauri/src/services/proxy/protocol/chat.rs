@@ -0,0 +1,718 @@
+//! Anthropic Messages ↔ OpenAI Chat Completions
+
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+use super::{
+    anthropic_blocks, anthropic_usage, as_i64, copy_fields, image_url_of, merge_usage, now_secs,
+    openai_content_to_blocks, openai_usage_parts, parse_arguments, push_blocks,
+    stringify_arguments, system_text, text_of, tool_choice_from_anthropic,
+    tool_choice_to_anthropic, DEFAULT_MAX_TOKENS,
+};
+
+/// Chat finish_reason → Anthropic stop_reason
+fn stop_reason_from_finish(reason: &str) -> &'static str {
+    match reason {
+        "length" => "max_tokens",
+        "tool_calls" | "function_call" => "tool_use",
+        _ => "end_turn",
+    }
+}
+
+/// Anthropic stop_reason → Chat finish_reason
+fn finish_from_stop_reason(reason: &str) -> &'static str {
+    match reason {
+        "max_tokens" => "length",
+        "tool_use" => "tool_calls",
+        _ => "stop",
+    }
+}
+
+/// Chat 用量 → Anthropic 用量
+fn usage_to_anthropic(usage: &Value) -> Value {
+    let cached = usage
+        .get("prompt_tokens_details")
+        .map(|details| as_i64(details, "cached_tokens"))
+        .unwrap_or(0);
+    anthropic_usage(
+        as_i64(usage, "prompt_tokens"),
+        as_i64(usage, "completion_tokens"),
+        cached,
+    )
+}
+
+/// Anthropic 用量 → Chat 用量
+fn usage_from_anthropic(usage: &Value) -> Value {
+    let (input, output, cached) = openai_usage_parts(usage);
+    json!({
+        "prompt_tokens": input,
+        "completion_tokens": output,
+        "total_tokens": input + output,
+        "prompt_tokens_details": { "cached_tokens": cached },
+    })
+}
+
+// ==================== 请求 ====================
+
+/// Chat Completions 请求 → Messages 请求
+pub fn request_to_anthropic(req: &Value) -> Value {
+    let mut system = Vec::new();
+    let mut messages = Vec::new();
+    for message in req["messages"].as_array().into_iter().flatten() {
+        match message["role"].as_str().unwrap_or("user") {
+            "system" | "developer" => system.push(text_of(&message["content"])),
+            "assistant" => {
+                let mut blocks = openai_content_to_blocks(&message["content"]);
+                for call in message["tool_calls"].as_array().into_iter().flatten() {
+                    blocks.push(json!({
+                        "type": "tool_use",
+                        "id": call["id"],
+                        "name": call["function"]["name"],
+                        "input": parse_arguments(&call["function"]["arguments"]),
+                    }));
+                }
+                push_blocks(&mut messages, "assistant", blocks);
+            }
+            "tool" => push_blocks(
+                &mut messages,
+                "user",
+                vec![json!({
+                    "type": "tool_result",
+                    "tool_use_id": message["tool_call_id"],
+                    "content": text_of(&message["content"]),
+                })],
+            ),
+            _ => push_blocks(
+                &mut messages,
+                "user",
+                openai_content_to_blocks(&message["content"]),
+            ),
+        }
+    }
+
+    let mut out = Map::new();
+    out.insert("model".into(), req["model"].clone());
+    out.insert(
+        "max_tokens".into(),
+        req.get("max_completion_tokens")
+            .or_else(|| req.get("max_tokens"))
+            .filter(|v| !v.is_null())
+            .cloned()
+            .unwrap_or_else(|| DEFAULT_MAX_TOKENS.into()),
+    );
+    let system: Vec<String> = system.into_iter().filter(|s| !s.is_empty()).collect();
+    if !system.is_empty() {
+        out.insert("system".into(), system.join("\n\n").into());
+    }
+    out.insert("messages".into(), messages.into());
+    copy_fields(
+        req,
+        &mut out,
+        &[
+            ("temperature", "temperature"),
+            ("top_p", "top_p"),
+            ("stream", "stream"),
+        ],
+    );
+    match &req["stop"] {
+        Value::String(stop) => {
+            out.insert("stop_sequences".into(), json!([stop]));
+        }
+        Value::Array(stops) => {
+            out.insert("stop_sequences".into(), stops.clone().into());
+        }
+        _ => {}
+    }
+
+    let tools: Vec<Value> = req["tools"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|tool| tool["type"] == "function")
+        .map(|tool| {
+            let function = &tool["function"];
+            let mut converted = json!({
+                "name": function["name"],
+                "input_schema": function
+                    .get("parameters")
+                    .filter(|v| !v.is_null())
+                    .cloned()
+                    .unwrap_or_else(|| json!({ "type": "object", "properties": {} })),
+            });
+            if let Some(description) = function.get("description").filter(|v| !v.is_null()) {
+                converted["description"] = description.clone();
+            }
+            converted
+        })
+        .collect();
+    if !tools.is_empty() {
+        out.insert("tools".into(), tools.into());
+        if let Some(choice) = req.get("tool_choice").and_then(tool_choice_to_anthropic) {
+            out.insert("tool_choice".into(), choice);
+        }
+    }
+    if let Some(user) = req.get("user").filter(|v| v.is_string()) {
+        out.insert("metadata".into(), json!({ "user_id": user }));
+    }
+    Value::Object(out)
+}
+
+/// Messages 请求 → Chat Completions 请求
+pub fn request_from_anthropic(req: &Value) -> Value {
+    let mut messages = Vec::new();
+    let system = system_text(&req["system"]);
+    if !system.is_empty() {
+        messages.push(json!({ "role": "system", "content": system }));
+    }
+
+    for message in req["messages"].as_array().into_iter().flatten() {
+        let blocks = anthropic_blocks(&message["content"]);
+        if message["role"] == "assistant" {
+            let text = text_of(&Value::Array(
+                blocks
+                    .iter()
+                    .filter(|b| b["type"] == "text")
+                    .cloned()
+                    .collect(),
+            ));
+            let tool_calls: Vec<Value> = blocks
+                .iter()
+                .filter(|b| b["type"] == "tool_use")
+                .map(|b| {
+                    json!({
+                        "id": b["id"],
+                        "type": "function",
+                        "function": {
+                            "name": b["name"],
+                            "arguments": stringify_arguments(&b["input"]),
+                        },
+                    })
+                })
+                .collect();
+            let mut converted = json!({ "role": "assistant", "content": text });
+            if !tool_calls.is_empty() {
+                if text.is_empty() {
+                    converted["content"] = Value::Null;
+                }
+                converted["tool_calls"] = tool_calls.into();
+            }
+            messages.push(converted);
+            continue;
+        }
+
+        // 工具结果需作为独立的 tool 消息，放在同一轮用户内容之前
+        let mut parts = Vec::new();
+        for block in &blocks {
+            match block["type"].as_str() {
+                Some("tool_result") => messages.push(json!({
+                    "role": "tool",
+                    "tool_call_id": block["tool_use_id"],
+                    "content": text_of(&block["content"]),
+                })),
+                Some("text") => parts.push(json!({ "type": "text", "text": block["text"] })),
+                Some("image") => {
+                    if let Some(url) = image_url_of(block) {
+                        parts.push(json!({ "type": "image_url", "image_url": { "url": url } }));
+                    }
+                }
+                _ => {}
+            }
+        }
+        if parts.is_empty() {
+            continue;
+        }
+        let content = if parts.iter().all(|p| p["type"] == "text") {
+            Value::String(text_of(&Value::Array(parts)))
+        } else {
+            Value::Array(parts)
+        };
+        messages.push(json!({ "role": "user", "content": content }));
+    }
+
+    let mut out = Map::new();
+    out.insert("model".into(), req["model"].clone());
+    out.insert("messages".into(), messages.into());
+    copy_fields(
+        req,
+        &mut out,
+        &[
+            ("max_tokens", "max_tokens"),
+            ("temperature", "temperature"),
+            ("top_p", "top_p"),
+            ("stop_sequences", "stop"),
+            ("stream", "stream"),
+        ],
+    );
+    if req["stream"] == true {
+        // 流末尾返回用量，供统计使用
+        out.insert("stream_options".into(), json!({ "include_usage": true }));
+    }
+
+    let tools: Vec<Value> = req["tools"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|tool| tool.get("input_schema").is_some())
+        .map(|tool| {
+            json!({
+                "type": "function",
+                "function": {
+                    "name": tool["name"],
+                    "description": tool.get("description").cloned().unwrap_or(Value::Null),
+                    "parameters": tool["input_schema"],
+                },
+            })
+        })
+        .collect();
+    if !tools.is_empty() {
+        out.insert("tools".into(), tools.into());
+        if let Some(choice) = req
+            .get("tool_choice")
+            .and_then(|choice| tool_choice_from_anthropic(choice, true))
+        {
+            out.insert("tool_choice".into(), choice);
+        }
+    }
+    if let Some(user) = req["metadata"].get("user_id").filter(|v| v.is_string()) {
+        out.insert("user".into(), user.clone());
+    }
+    Value::Object(out)
+}
+
+// ==================== 非流式响应 ====================
+
+/// Chat Completions 响应 → Messages 响应
+pub fn response_to_anthropic(resp: &Value) -> Value {
+    let choice = &resp["choices"][0];
+    let message = &choice["message"];
+
+    let mut content = Vec::new();
+    let text = text_of(&message["content"]);
+    if !text.is_empty() {
+        content.push(json!({ "type": "text", "text": text }));
+    }
+    for call in message["tool_calls"].as_array().into_iter().flatten() {
+        content.push(json!({
+            "type": "tool_use",
+            "id": call["id"],
+            "name": call["function"]["name"],
+            "input": parse_arguments(&call["function"]["arguments"]),
+        }));
+    }
+
+    json!({
+        "id": resp["id"],
+        "type": "message",
+        "role": "assistant",
+        "model": resp["model"],
+        "content": content,
+        "stop_reason": stop_reason_from_finish(choice["finish_reason"].as_str().unwrap_or("stop")),
+        "stop_sequence": null,
+        "usage": usage_to_anthropic(&resp["usage"]),
+    })
+}
+
+/// Messages 响应 → Chat Completions 响应
+pub fn response_from_anthropic(resp: &Value) -> Value {
+    let blocks = anthropic_blocks(&resp["content"]);
+    let text = text_of(&Value::Array(
+        blocks
+            .iter()
+            .filter(|b| b["type"] == "text")
+            .cloned()
+            .collect(),
+    ));
+    let tool_calls: Vec<Value> = blocks
+        .iter()
+        .filter(|b| b["type"] == "tool_use")
+        .map(|b| {
+            json!({
+                "id": b["id"],
+                "type": "function",
+                "function": { "name": b["name"], "arguments": stringify_arguments(&b["input"]) },
+            })
+        })
+        .collect();
+
+    let mut message = json!({ "role": "assistant", "content": text });
+    if !tool_calls.is_empty() {
+        if text.is_empty() {
+            message["content"] = Value::Null;
+        }
+        message["tool_calls"] = tool_calls.into();
+    }
+
+    json!({
+        "id": resp["id"],
+        "object": "chat.completion",
+        "created": now_secs(),
+        "model": resp["model"],
+        "choices": [{
+            "index": 0,
+            "message": message,
+            "finish_reason": finish_from_stop_reason(resp["stop_reason"].as_str().unwrap_or("end_turn")),
+        }],
+        "usage": usage_from_anthropic(&resp["usage"]),
+    })
+}
+
+// ==================== 流式响应 ====================
+
+/// 当前打开的内容块
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct OpenBlock {
+    /// Anthropic 内容块序号
+    index: usize,
+    /// 对应的 Chat tool_call 序号（文本块为 None）
+    tool_call: Option<u64>,
+}
+
+/// Chat 流式 chunk → Anthropic 流式事件
+#[derive(Debug, Default)]
+pub struct StreamDecoder {
+    started: bool,
+    finished: bool,
+    open: Option<OpenBlock>,
+    next_index: usize,
+    stop_reason: Option<&'static str>,
+    usage: Option<Value>,
+}
+
+impl StreamDecoder {
+    pub fn decode(&mut self, chunk: &Value) -> Vec<Value> {
+        let mut events = Vec::new();
+        if let Some(error) = chunk.get("error") {
+            let (kind, message) = super::error_parts(&json!({ "error": error }));
+            events.push(json!({ "type": "error", "error": { "type": kind, "message": message } }));
+            return events;
+        }
+        if !self.started {
+            self.started = true;
+            events.push(json!({
+                "type": "message_start",
+                "message": {
+                    "id": chunk["id"],
+                    "type": "message",
+                    "role": "assistant",
+                    "model": chunk["model"],
+                    "content": [],
+                    "stop_reason": null,
+                    "stop_sequence": null,
+                    "usage": { "input_tokens": 0, "output_tokens": 0 },
+                },
+            }));
+        }
+        if let Some(usage) = chunk.get("usage").filter(|u| u.is_object()) {
+            self.usage = Some(usage_to_anthropic(usage));
+        }
+        let Some(choice) = chunk["choices"].get(0) else {
+            return events;
+        };
+        let delta = &choice["delta"];
+
+        if let Some(text) = delta["content"].as_str().filter(|t| !t.is_empty()) {
+            let index = match self.open {
+                Some(block) if block.tool_call.is_none() => block.index,
+                _ => self.open_block(&mut events, None, json!({ "type": "text", "text": "" })),
+            };
+            events.push(json!({
+                "type": "content_block_delta",
+                "index": index,
+                "delta": { "type": "text_delta", "text": text },
+            }));
+        }
+
+        for call in delta["tool_calls"].as_array().into_iter().flatten() {
+            let call_index = call["index"].as_u64().unwrap_or(0);
+            let index = match self.open {
+                Some(block) if block.tool_call == Some(call_index) => block.index,
+                _ => self.open_block(
+                    &mut events,
+                    Some(call_index),
+                    json!({
+                        "type": "tool_use",
+                        "id": call["id"],
+                        "name": call["function"]["name"],
+                        "input": {},
+                    }),
+                ),
+            };
+            if let Some(arguments) = call["function"]["arguments"]
+                .as_str()
+                .filter(|a| !a.is_empty())
+            {
+                events.push(json!({
+                    "type": "content_block_delta",
+                    "index": index,
+                    "delta": { "type": "input_json_delta", "partial_json": arguments },
+                }));
+            }
+        }
+
+        if let Some(reason) = choice["finish_reason"].as_str() {
+            self.stop_reason = Some(stop_reason_from_finish(reason));
+        }
+        events
+    }
+
+    /// 流结束（`[DONE]` 或连接关闭）：关闭内容块并输出 message_delta / message_stop
+    pub fn finish(&mut self) -> Vec<Value> {
+        let mut events = Vec::new();
+        if !self.started || self.finished {
+            return events;
+        }
+        self.finished = true;
+        self.close_block(&mut events);
+        events.push(json!({
+            "type": "message_delta",
+            "delta": { "stop_reason": self.stop_reason.unwrap_or("end_turn"), "stop_sequence": null },
+            "usage": self.usage.clone().unwrap_or_else(|| json!({ "output_tokens": 0 })),
+        }));
+        events.push(json!({ "type": "message_stop" }));
+        events
+    }
+
+    fn open_block(
+        &mut self,
+        events: &mut Vec<Value>,
+        tool_call: Option<u64>,
+        content_block: Value,
+    ) -> usize {
+        self.close_block(events);
+        let index = self.next_index;
+        self.next_index += 1;
+        self.open = Some(OpenBlock { index, tool_call });
+        events.push(json!({
+            "type": "content_block_start",
+            "index": index,
+            "content_block": content_block,
+        }));
+        index
+    }
+
+    fn close_block(&mut self, events: &mut Vec<Value>) {
+        if let Some(block) = self.open.take() {
+            events.push(json!({ "type": "content_block_stop", "index": block.index }));
+        }
+    }
+}
+
+/// Anthropic 流式事件 → Chat 流式 chunk
+#[derive(Debug, Default)]
+pub struct StreamEncoder {
+    id: Value,
+    model: Value,
+    created: i64,
+    /// Anthropic 内容块序号 → Chat tool_call 序号
+    tool_calls: HashMap<u64, usize>,
+    usage: Value,
+}
+
+impl StreamEncoder {
+    pub fn encode(&mut self, event: &Value) -> String {
+        match event["type"].as_str().unwrap_or("") {
+            "message_start" => {
+                let message = &event["message"];
+                self.id = message["id"].clone();
+                self.model = message["model"].clone();
+                self.created = now_secs();
+                self.usage = message["usage"].clone();
+                self.chunk(json!({ "role": "assistant", "content": "" }), None)
+            }
+            "content_block_start" if event["content_block"]["type"] == "tool_use" => {
+                let block = &event["content_block"];
+                let call_index = self.tool_calls.len();
+                self.tool_calls
+                    .insert(event["index"].as_u64().unwrap_or(0), call_index);
+                self.chunk(
+                    json!({ "tool_calls": [{
+                        "index": call_index,
+                        "id": block["id"],
+                        "type": "function",
+                        "function": { "name": block["name"], "arguments": "" },
+                    }] }),
+                    None,
+                )
+            }
+            "content_block_delta" => {
+                let delta = &event["delta"];
+                match delta["type"].as_str() {
+                    Some("text_delta") => self.chunk(json!({ "content": delta["text"] }), None),
+                    Some("input_json_delta") => {
+                        let block = event["index"].as_u64().unwrap_or(0);
+                        match self.tool_calls.get(&block) {
+                            Some(call_index) => self.chunk(
+                                json!({ "tool_calls": [{
+                                    "index": call_index,
+                                    "function": { "arguments": delta["partial_json"] },
+                                }] }),
+                                None,
+                            ),
+                            None => String::new(),
+                        }
+                    }
+                    _ => String::new(),
+                }
+            }
+            "message_delta" => {
+                merge_usage(&mut self.usage, &event["usage"]);
+                let finish = finish_from_stop_reason(
+                    event["delta"]["stop_reason"].as_str().unwrap_or("end_turn"),
+                );
+                let usage = json!({
+                    "id": self.id,
+                    "object": "chat.completion.chunk",
+                    "created": self.created,
+                    "model": self.model,
+                    "choices": [],
+                    "usage": usage_from_anthropic(&self.usage),
+                });
+                format!("{}data: {usage}\n\n", self.chunk(json!({}), Some(finish)))
+            }
+            "message_stop" => "data: [DONE]\n\n".to_string(),
+            "error" => {
+                let error = &event["error"];
+                format!(
+                    "data: {}\n\n",
+                    json!({ "error": { "message": error["message"], "type": error["type"] } })
+                )
+            }
+            _ => String::new(),
+        }
+    }
+
+    fn chunk(&self, delta: Value, finish_reason: Option<&str>) -> String {
+        let chunk = json!({
+            "id": self.id,
+            "object": "chat.completion.chunk",
+            "created": self.created,
+            "model": self.model,
+            "choices": [{ "index": 0, "delta": delta, "finish_reason": finish_reason }],
+        });
+        format!("data: {chunk}\n\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_round_trip_with_tools() {
+        let anthropic = json!({
+            "model": "claude-sonnet-4-5",
+            "max_tokens": 1024,
+            "system": [{ "type": "text", "text": "You are helpful." }],
+            "stream": true,
+            "metadata": { "user_id": "user_abc" },
+            "tools": [{
+                "name": "read_file",
+                "description": "Read a file",
+                "input_schema": { "type": "object", "properties": { "path": { "type": "string" } } },
+            }],
+            "tool_choice": { "type": "auto" },
+            "messages": [
+                { "role": "user", "content": "open main.rs" },
+                { "role": "assistant", "content": [
+                    { "type": "text", "text": "Reading." },
+                    { "type": "tool_use", "id": "toolu_1", "name": "read_file", "input": { "path": "main.rs" } },
+                ] },
+                { "role": "user", "content": [
+                    { "type": "tool_result", "tool_use_id": "toolu_1", "content": "fn main() {}" },
+                    { "type": "text", "text": "explain" },
+                ] },
+            ],
+        });
+
+        let chat = request_from_anthropic(&anthropic);
+        assert_eq!(chat["messages"][0]["role"], "system");
+        assert_eq!(
+            chat["messages"][2]["tool_calls"][0]["function"]["arguments"],
+            r#"{"path":"main.rs"}"#
+        );
+        assert_eq!(chat["messages"][3]["role"], "tool");
+        assert_eq!(chat["messages"][3]["tool_call_id"], "toolu_1");
+        assert_eq!(chat["messages"][4]["content"], "explain");
+        assert_eq!(chat["stream_options"]["include_usage"], true);
+        assert_eq!(chat["tool_choice"], "auto");
+        assert_eq!(chat["user"], "user_abc");
+
+        let back = request_to_anthropic(&chat);
+        assert_eq!(back["system"], "You are helpful.");
+        assert_eq!(back["max_tokens"], 1024);
+        assert_eq!(back["messages"][0]["content"][0]["text"], "open main.rs");
+        assert_eq!(back["messages"][1], anthropic["messages"][1]);
+        assert_eq!(back["messages"][2], anthropic["messages"][2]);
+        assert_eq!(
+            back["tools"][0]["input_schema"],
+            anthropic["tools"][0]["input_schema"]
+        );
+        assert_eq!(back["metadata"]["user_id"], "user_abc");
+    }
+
+    #[test]
+    fn test_response_usage_and_stop_reason() {
+        let chat = json!({
+            "id": "chatcmpl-1",
+            "model": "gpt-4.1",
+            "choices": [{
+                "index": 0,
+                "message": { "role": "assistant", "content": null, "tool_calls": [{
+                    "id": "call_1", "type": "function",
+                    "function": { "name": "read_file", "arguments": "{\"path\":\"a\"}" },
+                }] },
+                "finish_reason": "tool_calls",
+            }],
+            "usage": { "prompt_tokens": 100, "completion_tokens": 20, "total_tokens": 120,
+                "prompt_tokens_details": { "cached_tokens": 60 } },
+        });
+        let anthropic = response_to_anthropic(&chat);
+        assert_eq!(anthropic["stop_reason"], "tool_use");
+        assert_eq!(anthropic["content"][0]["input"]["path"], "a");
+        assert_eq!(anthropic["usage"]["input_tokens"], 40);
+        assert_eq!(anthropic["usage"]["cache_read_input_tokens"], 60);
+
+        let back = response_from_anthropic(&anthropic);
+        assert_eq!(back["choices"][0]["finish_reason"], "tool_calls");
+        assert_eq!(back["usage"]["prompt_tokens"], 100);
+        assert_eq!(back["usage"]["prompt_tokens_details"]["cached_tokens"], 60);
+    }
+
+    #[test]
+    fn test_stream_decoder_builds_blocks() {
+        let mut decoder = StreamDecoder::default();
+        let mut events = Vec::new();
+        for chunk in [
+            json!({ "id": "c1", "model": "gpt-4.1", "choices": [{ "index": 0, "delta": { "role": "assistant", "content": "Hi" } }] }),
+            json!({ "id": "c1", "choices": [{ "index": 0, "delta": { "tool_calls": [{ "index": 0, "id": "call_1", "type": "function", "function": { "name": "ls", "arguments": "" } }] } }] }),
+            json!({ "id": "c1", "choices": [{ "index": 0, "delta": { "tool_calls": [{ "index": 0, "function": { "arguments": "{}" } }] } }] }),
+            json!({ "id": "c1", "choices": [{ "index": 0, "delta": {}, "finish_reason": "tool_calls" }] }),
+            json!({ "id": "c1", "choices": [], "usage": { "prompt_tokens": 10, "completion_tokens": 5 } }),
+        ] {
+            events.extend(decoder.decode(&chunk));
+        }
+        events.extend(decoder.finish());
+        assert!(decoder.finish().is_empty());
+
+        let types: Vec<&str> = events.iter().map(|e| e["type"].as_str().unwrap()).collect();
+        assert_eq!(
+            types,
+            vec![
+                "message_start",
+                "content_block_start",
+                "content_block_delta",
+                "content_block_stop",
+                "content_block_start",
+                "content_block_delta",
+                "content_block_stop",
+                "message_delta",
+                "message_stop",
+            ]
+        );
+        assert_eq!(events[4]["content_block"]["id"], "call_1");
+        assert_eq!(events[4]["index"], 1);
+        assert_eq!(events[7]["delta"]["stop_reason"], "tool_use");
+        assert_eq!(events[7]["usage"]["input_tokens"], 10);
+        assert_eq!(events[7]["usage"]["output_tokens"], 5);
+    }
+}
@@ -0,0 +1,535 @@
+// 协议转换层
+//
+// 让工具连接其他 API 协议的上游（如 Claude Code 指向 OpenAI 兼容端点、Codex 指向 Anthropic 端点）：
+// - 以 Anthropic Messages 为中间格式：客户端请求 → Messages → 上游请求，响应按相反方向转换
+// - 支持 OpenAI Chat Completions 与 Responses，非流式响应、SSE 流与错误响应均转换
+// - 只转换生成接口（`/v1/messages`、`/v1/chat/completions`、`/v1/responses`），其他请求原样转发
+//
+// 客户端协议按请求路径识别，上游协议由 `ToolProxyConfig.upstream_protocol` / `UpstreamTarget.protocol` 配置
+
+mod chat;
+mod responses;
+mod stream;
+
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use reqwest::header::HeaderValue;
+use serde_json::{json, Map, Value};
+
+use super::headers::ProcessedRequest;
+use crate::models::proxy_config::ApiProtocol;
+
+pub use stream::SseTranscoder;
+
+/// 上游未给出 max_tokens 时的默认值（Anthropic 要求必填）
+const DEFAULT_MAX_TOKENS: u64 = 8192;
+
+/// Anthropic API 版本（转换到 Anthropic 上游时补充）
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// 根据请求路径识别客户端协议（非生成接口返回 None）
+pub fn client_protocol(path: &str) -> Option<ApiProtocol> {
+    match path {
+        "/v1/messages" | "/messages" => Some(ApiProtocol::Anthropic),
+        "/v1/chat/completions" | "/chat/completions" => Some(ApiProtocol::OpenaiChat),
+        "/v1/responses" | "/responses" => Some(ApiProtocol::OpenaiResponses),
+        _ => None,
+    }
+}
+
+/// 将指定协议的请求体转换为 Anthropic Messages 格式
+pub fn request_as_anthropic(protocol: ApiProtocol, body: &Value) -> Value {
+    match protocol {
+        ApiProtocol::Anthropic => body.clone(),
+        ApiProtocol::OpenaiChat => chat::request_to_anthropic(body),
+        ApiProtocol::OpenaiResponses => responses::request_to_anthropic(body),
+    }
+}
+
+/// 将指定协议的非流式成功响应体转换为 Anthropic Messages 格式
+pub fn response_as_anthropic(protocol: ApiProtocol, body: &Value) -> Value {
+    match protocol {
+        ApiProtocol::Anthropic => body.clone(),
+        ApiProtocol::OpenaiChat => chat::response_to_anthropic(body),
+        ApiProtocol::OpenaiResponses => responses::response_to_anthropic(body),
+    }
+}
+
+/// 协议的生成接口路径
+fn endpoint_path(protocol: ApiProtocol) -> &'static str {
+    match protocol {
+        ApiProtocol::Anthropic => "/v1/messages",
+        ApiProtocol::OpenaiChat => "/v1/chat/completions",
+        ApiProtocol::OpenaiResponses => "/v1/responses",
+    }
+}
+
+/// 单次请求的协议适配器
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProtocolAdapter {
+    client: ApiProtocol,
+    upstream: ApiProtocol,
+}
+
+impl ProtocolAdapter {
+    /// 需要转换时返回适配器（未配置上游协议、不是生成接口或协议相同时返回 None）
+    pub fn resolve(path: &str, upstream: Option<ApiProtocol>) -> Option<Self> {
+        let client = client_protocol(path)?;
+        let upstream = upstream.filter(|upstream| *upstream != client)?;
+        Some(Self { client, upstream })
+    }
+
+    pub fn client(&self) -> ApiProtocol {
+        self.client
+    }
+
+    pub fn upstream(&self) -> ApiProtocol {
+        self.upstream
+    }
+
+    /// 改写出站请求：替换接口路径、认证头与请求体
+    ///
+    /// `client_path` 为客户端请求路径，用于从处理器生成的 URL 中取回上游地址
+    pub fn rewrite_request(
+        &self,
+        processed: &mut ProcessedRequest,
+        client_path: &str,
+    ) -> Result<()> {
+        let body: Value =
+            serde_json::from_slice(&processed.body).context("请求体不是合法的 JSON")?;
+        let pivot = request_as_anthropic(self.client, &body);
+        let translated = match self.upstream {
+            ApiProtocol::Anthropic => pivot,
+            ApiProtocol::OpenaiChat => chat::request_from_anthropic(&pivot),
+            ApiProtocol::OpenaiResponses => responses::request_from_anthropic(&pivot),
+        };
+        processed.body = Bytes::from(serde_json::to_vec(&translated)?);
+        processed.target_url = rebase_url(&processed.target_url, client_path, self.upstream);
+        self.rewrite_headers(&mut processed.headers)
+    }
+
+    fn rewrite_headers(&self, headers: &mut reqwest::header::HeaderMap) -> Result<()> {
+        // 请求体已改变；不透传客户端的压缩偏好，保证响应能被解析
+        headers.remove("content-length");
+        headers.remove("accept-encoding");
+
+        let api_key = headers
+            .get("authorization")
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.strip_prefix("Bearer ").unwrap_or(v).to_string())
+            .unwrap_or_default();
+        match self.upstream {
+            ApiProtocol::Anthropic => {
+                headers.remove("authorization");
+                headers.insert("x-api-key", HeaderValue::from_str(&api_key)?);
+                if !headers.contains_key("anthropic-version") {
+                    headers.insert(
+                        "anthropic-version",
+                        HeaderValue::from_static(ANTHROPIC_VERSION),
+                    );
+                }
+            }
+            ApiProtocol::OpenaiChat | ApiProtocol::OpenaiResponses => {
+                headers.remove("anthropic-version");
+                headers.remove("anthropic-beta");
+            }
+        }
+        Ok(())
+    }
+
+    /// 转换非流式响应体（错误响应转换为客户端协议的错误格式，无法解析时原样返回）
+    pub fn translate_response(&self, body: &Bytes, status: u16) -> Bytes {
+        let Ok(json) = serde_json::from_slice::<Value>(body) else {
+            return body.clone();
+        };
+        let translated = if (200..300).contains(&status) {
+            let pivot = response_as_anthropic(self.upstream, &json);
+            match self.client {
+                ApiProtocol::Anthropic => pivot,
+                ApiProtocol::OpenaiChat => chat::response_from_anthropic(&pivot),
+                ApiProtocol::OpenaiResponses => responses::response_from_anthropic(&pivot),
+            }
+        } else {
+            let (kind, message) = error_parts(&json);
+            error_body(self.client, &kind, &message)
+        };
+        serde_json::to_vec(&translated)
+            .map(Bytes::from)
+            .unwrap_or_else(|_| body.clone())
+    }
+
+    /// 创建 SSE 流转换器
+    pub fn transcoder(&self) -> SseTranscoder {
+        SseTranscoder::new(self.upstream, self.client)
+    }
+}
+
+/// 将处理器生成的目标 URL 改为上游协议的接口（丢弃客户端查询参数，如 `?beta=true`）
+fn rebase_url(target_url: &str, client_path: &str, upstream: ApiProtocol) -> String {
+    let without_query = target_url.split('?').next().unwrap_or(target_url);
+    // Codex 处理器会去掉 base_url 已包含的 `/v1`
+    let base = without_query
+        .strip_suffix(client_path)
+        .or_else(|| {
+            client_path
+                .strip_prefix("/v1")
+                .and_then(|rest| without_query.strip_suffix(rest))
+        })
+        .unwrap_or(without_query)
+        .trim_end_matches('/');
+
+    let endpoint = endpoint_path(upstream);
+    match base.strip_suffix("/v1") {
+        Some(_) => format!("{base}{}", &endpoint[3..]),
+        None => format!("{base}{endpoint}"),
+    }
+}
+
+/// 上游错误响应中的 (错误类型, 错误信息)
+fn error_parts(json: &Value) -> (String, String) {
+    let error = json.get("error").unwrap_or(json);
+    let message = match error {
+        Value::String(message) => message.clone(),
+        _ => error
+            .get("message")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| json.to_string()),
+    };
+    let kind = error
+        .get("type")
+        .or_else(|| error.get("code"))
+        .and_then(|v| v.as_str())
+        .unwrap_or("api_error")
+        .to_string();
+    (kind, message)
+}
+
+/// 按协议构造错误响应体
+fn error_body(protocol: ApiProtocol, kind: &str, message: &str) -> Value {
+    match protocol {
+        ApiProtocol::Anthropic => json!({
+            "type": "error",
+            "error": { "type": kind, "message": message }
+        }),
+        ApiProtocol::OpenaiChat | ApiProtocol::OpenaiResponses => json!({
+            "error": { "message": message, "type": kind, "param": null, "code": null }
+        }),
+    }
+}
+
+// ==================== 共用转换辅助 ====================
+
+fn now_secs() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+fn as_i64(value: &Value, key: &str) -> i64 {
+    value.get(key).and_then(|v| v.as_i64()).unwrap_or(0)
+}
+
+/// 拼接文本内容（字符串，或内容块数组中各块的 `text`）
+fn text_of(content: &Value) -> String {
+    match content {
+        Value::String(text) => text.clone(),
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(|part| part.get("text").and_then(|v| v.as_str()))
+            .collect::<Vec<_>>()
+            .join(""),
+        _ => String::new(),
+    }
+}
+
+/// Anthropic 内容（字符串或内容块数组）统一为内容块数组
+fn anthropic_blocks(content: &Value) -> Vec<Value> {
+    match content {
+        Value::String(text) => vec![json!({ "type": "text", "text": text })],
+        Value::Array(blocks) => blocks.clone(),
+        _ => Vec::new(),
+    }
+}
+
+/// OpenAI 内容（字符串或 text/input_text/image_url/input_image 片段）→ Anthropic 内容块
+fn openai_content_to_blocks(content: &Value) -> Vec<Value> {
+    let parts = match content {
+        Value::String(text) => {
+            return if text.is_empty() {
+                Vec::new()
+            } else {
+                vec![json!({ "type": "text", "text": text })]
+            };
+        }
+        Value::Array(parts) => parts,
+        _ => return Vec::new(),
+    };
+    parts
+        .iter()
+        .filter_map(|part| match part.get("type").and_then(|v| v.as_str()) {
+            Some("text" | "input_text" | "output_text") => part
+                .get("text")
+                .and_then(|v| v.as_str())
+                .filter(|text| !text.is_empty())
+                .map(|text| json!({ "type": "text", "text": text })),
+            Some("image_url" | "input_image") => {
+                let url = &part["image_url"];
+                url.get("url")
+                    .or(Some(url))
+                    .and_then(|v| v.as_str())
+                    .map(image_block_from_url)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// 图片 URL（支持 data URL）→ Anthropic 图片块
+fn image_block_from_url(url: &str) -> Value {
+    let inline = url
+        .strip_prefix("data:")
+        .and_then(|rest| rest.split_once(";base64,"));
+    match inline {
+        Some((media_type, data)) => json!({
+            "type": "image",
+            "source": { "type": "base64", "media_type": media_type, "data": data }
+        }),
+        None => json!({ "type": "image", "source": { "type": "url", "url": url } }),
+    }
+}
+
+/// Anthropic 图片块 → 图片 URL（base64 转为 data URL）
+fn image_url_of(block: &Value) -> Option<String> {
+    let source = block.get("source")?;
+    match source.get("type").and_then(|v| v.as_str())? {
+        "base64" => Some(format!(
+            "data:{};base64,{}",
+            source.get("media_type")?.as_str()?,
+            source.get("data")?.as_str()?
+        )),
+        "url" => source.get("url")?.as_str().map(|s| s.to_string()),
+        _ => None,
+    }
+}
+
+/// 追加消息内容块（与上一条消息角色相同时合并，Anthropic 要求角色交替）
+fn push_blocks(messages: &mut Vec<Value>, role: &str, blocks: Vec<Value>) {
+    if blocks.is_empty() {
+        return;
+    }
+    if let Some(last) = messages.last_mut() {
+        if last["role"] == role {
+            if let Some(content) = last["content"].as_array_mut() {
+                content.extend(blocks);
+                return;
+            }
+        }
+    }
+    messages.push(json!({ "role": role, "content": blocks }));
+}
+
+/// 函数调用参数（JSON 字符串）→ 对象，解析失败时保留原文
+fn parse_arguments(arguments: &Value) -> Value {
+    match arguments {
+        Value::String(text) if text.trim().is_empty() => json!({}),
+        Value::String(text) => {
+            serde_json::from_str(text).unwrap_or_else(|_| json!({ "arguments": text }))
+        }
+        Value::Null => json!({}),
+        other => other.clone(),
+    }
+}
+
+/// 工具参数对象 → JSON 字符串
+fn stringify_arguments(input: &Value) -> String {
+    match input {
+        Value::Null => "{}".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// OpenAI 用量（输入包含缓存命中）→ Anthropic 用量（输入不含缓存命中）
+fn anthropic_usage(input_total: i64, output: i64, cached: i64) -> Value {
+    json!({
+        "input_tokens": (input_total - cached).max(0),
+        "output_tokens": output,
+        "cache_read_input_tokens": cached,
+    })
+}
+
+/// Anthropic 用量 → (输入总数, 输出, 缓存命中)
+fn openai_usage_parts(usage: &Value) -> (i64, i64, i64) {
+    let cached = as_i64(usage, "cache_read_input_tokens");
+    let input =
+        as_i64(usage, "input_tokens") + cached + as_i64(usage, "cache_creation_input_tokens");
+    (input, as_i64(usage, "output_tokens"), cached)
+}
+
+/// 合并 Anthropic 流式用量（message_delta 中的字段覆盖 message_start 中的值）
+fn merge_usage(target: &mut Value, update: &Value) {
+    let (Some(target), Some(update)) = (target.as_object_mut(), update.as_object()) else {
+        if update.is_object() {
+            *target = update.clone();
+        }
+        return;
+    };
+    for (key, value) in update {
+        if !value.is_null() {
+            target.insert(key.clone(), value.clone());
+        }
+    }
+}
+
+/// 复制存在的字段
+fn copy_fields(from: &Value, to: &mut Map<String, Value>, keys: &[(&str, &str)]) {
+    for (source, target) in keys {
+        if let Some(value) = from.get(*source).filter(|v| !v.is_null()) {
+            to.insert(target.to_string(), value.clone());
+        }
+    }
+}
+
+/// OpenAI tool_choice → Anthropic tool_choice
+fn tool_choice_to_anthropic(choice: &Value) -> Option<Value> {
+    match choice {
+        Value::String(mode) => match mode.as_str() {
+            "auto" => Some(json!({ "type": "auto" })),
+            "required" => Some(json!({ "type": "any" })),
+            "none" => Some(json!({ "type": "none" })),
+            _ => None,
+        },
+        Value::Object(_) => {
+            let name = choice
+                .get("function")
+                .and_then(|f| f.get("name"))
+                .or_else(|| choice.get("name"))?;
+            Some(json!({ "type": "tool", "name": name }))
+        }
+        _ => None,
+    }
+}
+
+/// Anthropic tool_choice → OpenAI tool_choice（`function` 为 Chat 的嵌套写法）
+fn tool_choice_from_anthropic(choice: &Value, nested: bool) -> Option<Value> {
+    match choice.get("type").and_then(|v| v.as_str())? {
+        "auto" => Some(json!("auto")),
+        "any" => Some(json!("required")),
+        "none" => Some(json!("none")),
+        "tool" => {
+            let name = choice.get("name")?;
+            Some(if nested {
+                json!({ "type": "function", "function": { "name": name } })
+            } else {
+                json!({ "type": "function", "name": name })
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Anthropic system（字符串或文本块数组）→ 文本
+fn system_text(system: &Value) -> String {
+    match system {
+        Value::Array(blocks) => blocks
+            .iter()
+            .filter_map(|b| b.get("text").and_then(|v| v.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        other => text_of(other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderMap;
+
+    #[test]
+    fn test_resolve_only_for_generation_endpoints() {
+        assert!(ProtocolAdapter::resolve("/v1/messages", None).is_none());
+        assert!(ProtocolAdapter::resolve("/v1/messages", Some(ApiProtocol::Anthropic)).is_none());
+        assert!(ProtocolAdapter::resolve("/v1/models", Some(ApiProtocol::OpenaiChat)).is_none());
+
+        let adapter =
+            ProtocolAdapter::resolve("/v1/messages", Some(ApiProtocol::OpenaiChat)).unwrap();
+        assert_eq!(adapter.client(), ApiProtocol::Anthropic);
+        assert_eq!(adapter.upstream(), ApiProtocol::OpenaiChat);
+    }
+
+    #[test]
+    fn test_rebase_url() {
+        assert_eq!(
+            rebase_url(
+                "https://api.example.com/v1/messages?beta=true",
+                "/v1/messages",
+                ApiProtocol::OpenaiChat
+            ),
+            "https://api.example.com/v1/chat/completions"
+        );
+        // base_url 已包含 /v1
+        assert_eq!(
+            rebase_url(
+                "https://api.openai.com/v1/messages",
+                "/v1/messages",
+                ApiProtocol::OpenaiResponses
+            ),
+            "https://api.openai.com/v1/responses"
+        );
+        // Codex 处理器去掉了重复的 /v1
+        assert_eq!(
+            rebase_url(
+                "https://relay.example.com/v1/responses",
+                "/v1/responses",
+                ApiProtocol::Anthropic
+            ),
+            "https://relay.example.com/v1/messages"
+        );
+    }
+
+    #[test]
+    fn test_rewrite_request_to_anthropic_upstream() {
+        let adapter =
+            ProtocolAdapter::resolve("/v1/chat/completions", Some(ApiProtocol::Anthropic)).unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", HeaderValue::from_static("Bearer sk-real"));
+        headers.insert("content-length", HeaderValue::from_static("42"));
+        let mut processed = ProcessedRequest {
+            target_url: "https://api.anthropic.com/v1/chat/completions".to_string(),
+            headers,
+            body: Bytes::from_static(
+                br#"{"model":"claude-sonnet-4-5","messages":[{"role":"user","content":"hi"}]}"#,
+            ),
+        };
+        adapter
+            .rewrite_request(&mut processed, "/v1/chat/completions")
+            .unwrap();
+
+        assert_eq!(
+            processed.target_url,
+            "https://api.anthropic.com/v1/messages"
+        );
+        assert_eq!(processed.headers["x-api-key"], "sk-real");
+        assert_eq!(processed.headers["anthropic-version"], ANTHROPIC_VERSION);
+        assert!(processed.headers.get("content-length").is_none());
+        let body: Value = serde_json::from_slice(&processed.body).unwrap();
+        assert_eq!(body["max_tokens"], DEFAULT_MAX_TOKENS);
+        assert_eq!(body["messages"][0]["content"][0]["text"], "hi");
+    }
+
+    #[test]
+    fn test_translate_error_response() {
+        let adapter =
+            ProtocolAdapter::resolve("/v1/messages", Some(ApiProtocol::OpenaiChat)).unwrap();
+        let body = Bytes::from_static(
+            br#"{"error":{"message":"Invalid API key","type":"invalid_request_error","code":"invalid_api_key"}}"#,
+        );
+        let translated: Value =
+            serde_json::from_slice(&adapter.translate_response(&body, 401)).unwrap();
+        assert_eq!(translated["type"], "error");
+        assert_eq!(translated["error"]["type"], "invalid_request_error");
+        assert_eq!(translated["error"]["message"], "Invalid API key");
+
+        // 非 JSON 响应原样返回
+        let html = Bytes::from_static(b"<html>Bad Gateway</html>");
+        assert_eq!(adapter.translate_response(&html, 502), html);
+    }
+}
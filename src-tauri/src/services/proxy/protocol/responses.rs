@@ -0,0 +1,822 @@
+//! Anthropic Messages ↔ OpenAI Responses
+
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+use super::{
+    anthropic_blocks, anthropic_usage, as_i64, copy_fields, image_url_of, merge_usage, now_secs,
+    openai_content_to_blocks, openai_usage_parts, parse_arguments, push_blocks,
+    stringify_arguments, system_text, text_of, tool_choice_from_anthropic,
+    tool_choice_to_anthropic, DEFAULT_MAX_TOKENS,
+};
+
+/// Responses 用量 → Anthropic 用量
+fn usage_to_anthropic(usage: &Value) -> Value {
+    let cached = usage
+        .get("input_tokens_details")
+        .map(|details| as_i64(details, "cached_tokens"))
+        .unwrap_or(0);
+    anthropic_usage(
+        as_i64(usage, "input_tokens"),
+        as_i64(usage, "output_tokens"),
+        cached,
+    )
+}
+
+/// Anthropic 用量 → Responses 用量
+fn usage_from_anthropic(usage: &Value) -> Value {
+    let (input, output, cached) = openai_usage_parts(usage);
+    json!({
+        "input_tokens": input,
+        "input_tokens_details": { "cached_tokens": cached },
+        "output_tokens": output,
+        "output_tokens_details": { "reasoning_tokens": 0 },
+        "total_tokens": input + output,
+    })
+}
+
+/// Responses 结束状态 → Anthropic stop_reason
+fn stop_reason_of(response: &Value, has_tool_use: bool) -> &'static str {
+    if has_tool_use {
+        "tool_use"
+    } else if response["status"] == "incomplete"
+        && response["incomplete_details"]["reason"] == "max_output_tokens"
+    {
+        "max_tokens"
+    } else {
+        "end_turn"
+    }
+}
+
+// ==================== 请求 ====================
+
+/// Responses 请求 → Messages 请求
+pub fn request_to_anthropic(req: &Value) -> Value {
+    let mut system = Vec::new();
+    if let Some(instructions) = req["instructions"].as_str().filter(|s| !s.is_empty()) {
+        system.push(instructions.to_string());
+    }
+
+    let mut messages = Vec::new();
+    match &req["input"] {
+        Value::String(text) => push_blocks(
+            &mut messages,
+            "user",
+            openai_content_to_blocks(&Value::String(text.clone())),
+        ),
+        Value::Array(items) => {
+            for item in items {
+                match item["type"].as_str().unwrap_or("message") {
+                    "message" => match item["role"].as_str().unwrap_or("user") {
+                        "system" | "developer" => system.push(text_of(&item["content"])),
+                        "assistant" => push_blocks(
+                            &mut messages,
+                            "assistant",
+                            openai_content_to_blocks(&item["content"]),
+                        ),
+                        _ => push_blocks(
+                            &mut messages,
+                            "user",
+                            openai_content_to_blocks(&item["content"]),
+                        ),
+                    },
+                    "function_call" => push_blocks(
+                        &mut messages,
+                        "assistant",
+                        vec![json!({
+                            "type": "tool_use",
+                            "id": item["call_id"],
+                            "name": item["name"],
+                            "input": parse_arguments(&item["arguments"]),
+                        })],
+                    ),
+                    "function_call_output" => push_blocks(
+                        &mut messages,
+                        "user",
+                        vec![json!({
+                            "type": "tool_result",
+                            "tool_use_id": item["call_id"],
+                            "content": text_of(&item["output"]),
+                        })],
+                    ),
+                    // reasoning 等条目在 Messages 中没有对应内容
+                    _ => {}
+                }
+            }
+        }
+        _ => {}
+    }
+
+    let mut out = Map::new();
+    out.insert("model".into(), req["model"].clone());
+    out.insert(
+        "max_tokens".into(),
+        req.get("max_output_tokens")
+            .filter(|v| !v.is_null())
+            .cloned()
+            .unwrap_or_else(|| DEFAULT_MAX_TOKENS.into()),
+    );
+    let system: Vec<String> = system.into_iter().filter(|s| !s.is_empty()).collect();
+    if !system.is_empty() {
+        out.insert("system".into(), system.join("\n\n").into());
+    }
+    out.insert("messages".into(), messages.into());
+    copy_fields(
+        req,
+        &mut out,
+        &[
+            ("temperature", "temperature"),
+            ("top_p", "top_p"),
+            ("stream", "stream"),
+        ],
+    );
+
+    // 只转换函数工具，web_search 等内置工具无法在其他协议上执行
+    let tools: Vec<Value> = req["tools"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|tool| tool["type"] == "function")
+        .map(|tool| {
+            let mut converted = json!({
+                "name": tool["name"],
+                "input_schema": tool
+                    .get("parameters")
+                    .filter(|v| !v.is_null())
+                    .cloned()
+                    .unwrap_or_else(|| json!({ "type": "object", "properties": {} })),
+            });
+            if let Some(description) = tool.get("description").filter(|v| !v.is_null()) {
+                converted["description"] = description.clone();
+            }
+            converted
+        })
+        .collect();
+    if !tools.is_empty() {
+        out.insert("tools".into(), tools.into());
+        if let Some(choice) = req.get("tool_choice").and_then(tool_choice_to_anthropic) {
+            out.insert("tool_choice".into(), choice);
+        }
+    }
+    if let Some(user) = req.get("user").filter(|v| v.is_string()) {
+        out.insert("metadata".into(), json!({ "user_id": user }));
+    }
+    Value::Object(out)
+}
+
+/// Messages 请求 → Responses 请求
+pub fn request_from_anthropic(req: &Value) -> Value {
+    let mut input = Vec::new();
+    for message in req["messages"].as_array().into_iter().flatten() {
+        let role = if message["role"] == "assistant" {
+            "assistant"
+        } else {
+            "user"
+        };
+        let text_type = if role == "assistant" {
+            "output_text"
+        } else {
+            "input_text"
+        };
+
+        // 连续的文本/图片合并为一条 message 条目，工具调用与结果为独立条目
+        let mut parts = Vec::new();
+        let flush = |parts: &mut Vec<Value>, input: &mut Vec<Value>| {
+            if !parts.is_empty() {
+                input.push(json!({
+                    "type": "message",
+                    "role": role,
+                    "content": std::mem::take(parts),
+                }));
+            }
+        };
+        for block in anthropic_blocks(&message["content"]) {
+            match block["type"].as_str() {
+                Some("text") => parts.push(json!({ "type": text_type, "text": block["text"] })),
+                Some("image") => {
+                    if let Some(url) = image_url_of(&block) {
+                        parts.push(json!({ "type": "input_image", "image_url": url }));
+                    }
+                }
+                Some("tool_use") => {
+                    flush(&mut parts, &mut input);
+                    input.push(json!({
+                        "type": "function_call",
+                        "call_id": block["id"],
+                        "name": block["name"],
+                        "arguments": stringify_arguments(&block["input"]),
+                    }));
+                }
+                Some("tool_result") => {
+                    flush(&mut parts, &mut input);
+                    input.push(json!({
+                        "type": "function_call_output",
+                        "call_id": block["tool_use_id"],
+                        "output": text_of(&block["content"]),
+                    }));
+                }
+                _ => {}
+            }
+        }
+        flush(&mut parts, &mut input);
+    }
+
+    let mut out = Map::new();
+    out.insert("model".into(), req["model"].clone());
+    let instructions = system_text(&req["system"]);
+    if !instructions.is_empty() {
+        out.insert("instructions".into(), instructions.into());
+    }
+    out.insert("input".into(), input.into());
+    copy_fields(
+        req,
+        &mut out,
+        &[
+            ("max_tokens", "max_output_tokens"),
+            ("temperature", "temperature"),
+            ("top_p", "top_p"),
+            ("stream", "stream"),
+        ],
+    );
+
+    let tools: Vec<Value> = req["tools"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|tool| tool.get("input_schema").is_some())
+        .map(|tool| {
+            json!({
+                "type": "function",
+                "name": tool["name"],
+                "description": tool.get("description").cloned().unwrap_or(Value::Null),
+                "parameters": tool["input_schema"],
+            })
+        })
+        .collect();
+    if !tools.is_empty() {
+        out.insert("tools".into(), tools.into());
+        if let Some(choice) = req
+            .get("tool_choice")
+            .and_then(|choice| tool_choice_from_anthropic(choice, false))
+        {
+            out.insert("tool_choice".into(), choice);
+        }
+    }
+    if let Some(user) = req["metadata"].get("user_id").filter(|v| v.is_string()) {
+        out.insert("user".into(), user.clone());
+    }
+    Value::Object(out)
+}
+
+// ==================== 非流式响应 ====================
+
+/// Responses 响应 → Messages 响应
+pub fn response_to_anthropic(resp: &Value) -> Value {
+    let mut content = Vec::new();
+    for item in resp["output"].as_array().into_iter().flatten() {
+        match item["type"].as_str() {
+            Some("message") => {
+                for part in item["content"].as_array().into_iter().flatten() {
+                    let text = part
+                        .get("text")
+                        .or_else(|| part.get("refusal"))
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("");
+                    if !text.is_empty() {
+                        content.push(json!({ "type": "text", "text": text }));
+                    }
+                }
+            }
+            Some("function_call") => content.push(json!({
+                "type": "tool_use",
+                "id": item["call_id"],
+                "name": item["name"],
+                "input": parse_arguments(&item["arguments"]),
+            })),
+            _ => {}
+        }
+    }
+    let has_tool_use = content.iter().any(|b| b["type"] == "tool_use");
+
+    json!({
+        "id": resp["id"],
+        "type": "message",
+        "role": "assistant",
+        "model": resp["model"],
+        "content": content,
+        "stop_reason": stop_reason_of(resp, has_tool_use),
+        "stop_sequence": null,
+        "usage": usage_to_anthropic(&resp["usage"]),
+    })
+}
+
+/// Messages 响应 → Responses 响应
+pub fn response_from_anthropic(resp: &Value) -> Value {
+    let id = resp["id"].as_str().unwrap_or("resp");
+    let mut output = Vec::new();
+    let mut text_parts = Vec::new();
+    for block in anthropic_blocks(&resp["content"]) {
+        match block["type"].as_str() {
+            Some("text") => text_parts.push(json!({
+                "type": "output_text",
+                "text": block["text"],
+                "annotations": [],
+            })),
+            Some("tool_use") => output.push(function_call_item(
+                &block["id"],
+                &block["name"],
+                stringify_arguments(&block["input"]),
+            )),
+            _ => {}
+        }
+    }
+    if !text_parts.is_empty() {
+        output.insert(0, message_item(&format!("msg_{id}"), text_parts));
+    }
+
+    let truncated = resp["stop_reason"] == "max_tokens";
+    json!({
+        "id": id,
+        "object": "response",
+        "created_at": now_secs(),
+        "model": resp["model"],
+        "status": if truncated { "incomplete" } else { "completed" },
+        "incomplete_details": if truncated { json!({ "reason": "max_output_tokens" }) } else { Value::Null },
+        "output": output,
+        "usage": usage_from_anthropic(&resp["usage"]),
+    })
+}
+
+fn message_item(id: &str, content: Vec<Value>) -> Value {
+    json!({
+        "type": "message",
+        "id": id,
+        "status": "completed",
+        "role": "assistant",
+        "content": content,
+    })
+}
+
+fn function_call_item(call_id: &Value, name: &Value, arguments: String) -> Value {
+    json!({
+        "type": "function_call",
+        "id": format!("fc_{}", call_id.as_str().unwrap_or("")),
+        "call_id": call_id,
+        "name": name,
+        "arguments": arguments,
+        "status": "completed",
+    })
+}
+
+// ==================== 流式响应 ====================
+
+/// Responses 流式事件 → Anthropic 流式事件
+#[derive(Debug, Default)]
+pub struct StreamDecoder {
+    started: bool,
+    finished: bool,
+    /// Responses output_index → 已打开的 Anthropic 内容块序号
+    open: HashMap<u64, usize>,
+    next_index: usize,
+    has_tool_use: bool,
+}
+
+impl StreamDecoder {
+    pub fn decode(&mut self, event: &Value) -> Vec<Value> {
+        let mut events = Vec::new();
+        let output_index = event["output_index"].as_u64().unwrap_or(0);
+        match event["type"].as_str().unwrap_or("") {
+            "response.created" | "response.in_progress" => {
+                self.ensure_started(&mut events, &event["response"]);
+            }
+            "response.output_item.added" if event["item"]["type"] == "function_call" => {
+                self.ensure_started(&mut events, &Value::Null);
+                let item = &event["item"];
+                self.has_tool_use = true;
+                self.open_block(
+                    &mut events,
+                    output_index,
+                    json!({ "type": "tool_use", "id": item["call_id"], "name": item["name"], "input": {} }),
+                );
+            }
+            "response.output_text.delta" => {
+                self.ensure_started(&mut events, &Value::Null);
+                let index = match self.open.get(&output_index) {
+                    Some(index) => *index,
+                    None => self.open_block(
+                        &mut events,
+                        output_index,
+                        json!({ "type": "text", "text": "" }),
+                    ),
+                };
+                events.push(json!({
+                    "type": "content_block_delta",
+                    "index": index,
+                    "delta": { "type": "text_delta", "text": event["delta"] },
+                }));
+            }
+            "response.function_call_arguments.delta" => {
+                if let Some(index) = self.open.get(&output_index) {
+                    events.push(json!({
+                        "type": "content_block_delta",
+                        "index": index,
+                        "delta": { "type": "input_json_delta", "partial_json": event["delta"] },
+                    }));
+                }
+            }
+            "response.output_item.done" => {
+                if let Some(index) = self.open.remove(&output_index) {
+                    events.push(json!({ "type": "content_block_stop", "index": index }));
+                }
+            }
+            "response.completed" | "response.incomplete" => {
+                let response = &event["response"];
+                self.ensure_started(&mut events, response);
+                let stop_reason = stop_reason_of(response, self.has_tool_use);
+                self.finish_with(
+                    &mut events,
+                    stop_reason,
+                    usage_to_anthropic(&response["usage"]),
+                );
+            }
+            "response.failed" | "error" => {
+                let error = event["response"]
+                    .get("error")
+                    .filter(|e| e.is_object())
+                    .unwrap_or(event);
+                let (kind, message) = super::error_parts(&json!({ "error": error }));
+                events.push(
+                    json!({ "type": "error", "error": { "type": kind, "message": message } }),
+                );
+                self.finished = true;
+            }
+            _ => {}
+        }
+        events
+    }
+
+    /// 流结束：上游未发送 response.completed 时补齐结束事件
+    pub fn finish(&mut self) -> Vec<Value> {
+        let mut events = Vec::new();
+        if self.started && !self.finished {
+            let stop_reason = if self.has_tool_use {
+                "tool_use"
+            } else {
+                "end_turn"
+            };
+            self.finish_with(&mut events, stop_reason, json!({ "output_tokens": 0 }));
+        }
+        events
+    }
+
+    fn ensure_started(&mut self, events: &mut Vec<Value>, response: &Value) {
+        if self.started {
+            return;
+        }
+        self.started = true;
+        events.push(json!({
+            "type": "message_start",
+            "message": {
+                "id": response["id"],
+                "type": "message",
+                "role": "assistant",
+                "model": response["model"],
+                "content": [],
+                "stop_reason": null,
+                "stop_sequence": null,
+                "usage": { "input_tokens": 0, "output_tokens": 0 },
+            },
+        }));
+    }
+
+    fn open_block(&mut self, events: &mut Vec<Value>, output_index: u64, block: Value) -> usize {
+        let index = self.next_index;
+        self.next_index += 1;
+        self.open.insert(output_index, index);
+        events
+            .push(json!({ "type": "content_block_start", "index": index, "content_block": block }));
+        index
+    }
+
+    fn finish_with(&mut self, events: &mut Vec<Value>, stop_reason: &str, usage: Value) {
+        let mut open: Vec<usize> = self.open.drain().map(|(_, index)| index).collect();
+        open.sort_unstable();
+        for index in open {
+            events.push(json!({ "type": "content_block_stop", "index": index }));
+        }
+        events.push(json!({
+            "type": "message_delta",
+            "delta": { "stop_reason": stop_reason, "stop_sequence": null },
+            "usage": usage,
+        }));
+        events.push(json!({ "type": "message_stop" }));
+        self.finished = true;
+    }
+}
+
+/// Anthropic 流式事件 → Responses 流式事件
+#[derive(Debug, Default)]
+pub struct StreamEncoder {
+    id: String,
+    model: Value,
+    created_at: i64,
+    sequence: u64,
+    /// Anthropic 内容块序号 → output 条目序号（thinking 等块不输出）
+    items: HashMap<u64, usize>,
+    output: Vec<Value>,
+    usage: Value,
+    stop_reason: String,
+}
+
+impl StreamEncoder {
+    pub fn encode(&mut self, event: &Value) -> String {
+        let block_index = event["index"].as_u64().unwrap_or(0);
+        match event["type"].as_str().unwrap_or("") {
+            "message_start" => {
+                let message = &event["message"];
+                self.id = message["id"].as_str().unwrap_or("resp").to_string();
+                self.model = message["model"].clone();
+                self.created_at = now_secs();
+                self.usage = message["usage"].clone();
+                let response = self.snapshot("in_progress", Vec::new());
+                let mut out =
+                    self.event("response.created", json!({ "response": response.clone() }));
+                out.push_str(&self.event("response.in_progress", json!({ "response": response })));
+                out
+            }
+            "content_block_start" => {
+                let block = &event["content_block"];
+                let output_index = self.output.len();
+                match block["type"].as_str() {
+                    Some("text") => {
+                        let item_id = format!("msg_{}_{output_index}", self.id);
+                        let mut item = message_item(&item_id, Vec::new());
+                        item["status"] = "in_progress".into();
+                        self.items.insert(block_index, output_index);
+                        self.output.push(item.clone());
+                        let mut out = self.event(
+                            "response.output_item.added",
+                            json!({ "output_index": output_index, "item": item }),
+                        );
+                        out.push_str(&self.event(
+                            "response.content_part.added",
+                            json!({
+                                "item_id": item_id,
+                                "output_index": output_index,
+                                "content_index": 0,
+                                "part": { "type": "output_text", "text": "", "annotations": [] },
+                            }),
+                        ));
+                        out
+                    }
+                    Some("tool_use") => {
+                        let mut item =
+                            function_call_item(&block["id"], &block["name"], String::new());
+                        item["status"] = "in_progress".into();
+                        self.items.insert(block_index, output_index);
+                        self.output.push(item.clone());
+                        self.event(
+                            "response.output_item.added",
+                            json!({ "output_index": output_index, "item": item }),
+                        )
+                    }
+                    _ => String::new(),
+                }
+            }
+            "content_block_delta" => {
+                let Some(&output_index) = self.items.get(&block_index) else {
+                    return String::new();
+                };
+                let delta = &event["delta"];
+                let item_id = self.output[output_index]["id"].clone();
+                match delta["type"].as_str() {
+                    Some("text_delta") => {
+                        let text = delta["text"].as_str().unwrap_or("");
+                        let item = &mut self.output[output_index];
+                        let accumulated = item["text"].as_str().unwrap_or("").to_string() + text;
+                        item["text"] = accumulated.into();
+                        self.event(
+                            "response.output_text.delta",
+                            json!({
+                                "item_id": item_id,
+                                "output_index": output_index,
+                                "content_index": 0,
+                                "delta": text,
+                            }),
+                        )
+                    }
+                    Some("input_json_delta") => {
+                        let partial = delta["partial_json"].as_str().unwrap_or("");
+                        let item = &mut self.output[output_index];
+                        let arguments =
+                            item["arguments"].as_str().unwrap_or("").to_string() + partial;
+                        item["arguments"] = arguments.into();
+                        self.event(
+                            "response.function_call_arguments.delta",
+                            json!({ "item_id": item_id, "output_index": output_index, "delta": partial }),
+                        )
+                    }
+                    _ => String::new(),
+                }
+            }
+            "content_block_stop" => match self.items.get(&block_index) {
+                Some(&output_index) => self.complete_item(output_index),
+                None => String::new(),
+            },
+            "message_delta" => {
+                merge_usage(&mut self.usage, &event["usage"]);
+                if let Some(reason) = event["delta"]["stop_reason"].as_str() {
+                    self.stop_reason = reason.to_string();
+                }
+                String::new()
+            }
+            "message_stop" => {
+                let truncated = self.stop_reason == "max_tokens";
+                let mut response = self.snapshot(
+                    if truncated { "incomplete" } else { "completed" },
+                    self.output.clone(),
+                );
+                if truncated {
+                    response["incomplete_details"] = json!({ "reason": "max_output_tokens" });
+                }
+                response["usage"] = usage_from_anthropic(&self.usage);
+                let kind = if truncated {
+                    "response.incomplete"
+                } else {
+                    "response.completed"
+                };
+                self.event(kind, json!({ "response": response }))
+            }
+            "error" => {
+                let error = &event["error"];
+                self.event(
+                    "error",
+                    json!({ "code": error["type"], "message": error["message"], "param": null }),
+                )
+            }
+            _ => String::new(),
+        }
+    }
+
+    /// 结束 output 条目：文本写入 content，输出 done 事件
+    fn complete_item(&mut self, output_index: usize) -> String {
+        let mut item = self.output[output_index].clone();
+        item["status"] = "completed".into();
+        let item_id = item["id"].clone();
+        let mut out = String::new();
+        if item["type"] == "message" {
+            let text = item
+                .as_object_mut()
+                .and_then(|map| map.remove("text"))
+                .unwrap_or_else(|| "".into());
+            let part = json!({ "type": "output_text", "text": text, "annotations": [] });
+            item["content"] = json!([part.clone()]);
+            out.push_str(&self.event(
+                "response.output_text.done",
+                json!({ "item_id": item_id, "output_index": output_index, "content_index": 0, "text": text }),
+            ));
+            out.push_str(&self.event(
+                "response.content_part.done",
+                json!({ "item_id": item_id, "output_index": output_index, "content_index": 0, "part": part }),
+            ));
+        } else {
+            if item["arguments"].as_str().unwrap_or("").is_empty() {
+                item["arguments"] = "{}".into();
+            }
+            out.push_str(&self.event(
+                "response.function_call_arguments.done",
+                json!({ "item_id": item_id, "output_index": output_index, "arguments": item["arguments"] }),
+            ));
+        }
+        self.output[output_index] = item.clone();
+        out.push_str(&self.event(
+            "response.output_item.done",
+            json!({ "output_index": output_index, "item": item }),
+        ));
+        out
+    }
+
+    fn snapshot(&self, status: &str, output: Vec<Value>) -> Value {
+        json!({
+            "id": self.id,
+            "object": "response",
+            "created_at": self.created_at,
+            "model": self.model,
+            "status": status,
+            "output": output,
+        })
+    }
+
+    fn event(&mut self, kind: &str, mut data: Value) -> String {
+        data["type"] = kind.into();
+        data["sequence_number"] = self.sequence.into();
+        self.sequence += 1;
+        format!("event: {kind}\ndata: {data}\n\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_from_anthropic_splits_tool_items() {
+        let anthropic = json!({
+            "model": "gpt-5",
+            "max_tokens": 2048,
+            "system": "Be brief.",
+            "messages": [
+                { "role": "user", "content": "list files" },
+                { "role": "assistant", "content": [
+                    { "type": "text", "text": "Listing." },
+                    { "type": "tool_use", "id": "call_1", "name": "ls", "input": {} },
+                ] },
+                { "role": "user", "content": [
+                    { "type": "tool_result", "tool_use_id": "call_1", "content": [{ "type": "text", "text": "a.rs" }] },
+                ] },
+            ],
+        });
+        let req = request_from_anthropic(&anthropic);
+        assert_eq!(req["instructions"], "Be brief.");
+        assert_eq!(req["max_output_tokens"], 2048);
+        let types: Vec<&str> = req["input"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["type"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                "message",
+                "message",
+                "function_call",
+                "function_call_output"
+            ]
+        );
+        assert_eq!(req["input"][1]["content"][0]["type"], "output_text");
+        assert_eq!(req["input"][3]["output"], "a.rs");
+
+        let back = request_to_anthropic(&req);
+        assert_eq!(back["system"], "Be brief.");
+        assert_eq!(back["messages"][1]["content"][1]["id"], "call_1");
+        assert_eq!(back["messages"][2]["content"][0]["tool_use_id"], "call_1");
+    }
+
+    #[test]
+    fn test_response_round_trip() {
+        let anthropic = json!({
+            "id": "msg_1",
+            "model": "claude-sonnet-4-5",
+            "content": [
+                { "type": "text", "text": "Done." },
+                { "type": "tool_use", "id": "toolu_1", "name": "ls", "input": { "dir": "." } },
+            ],
+            "stop_reason": "tool_use",
+            "usage": { "input_tokens": 10, "output_tokens": 4, "cache_read_input_tokens": 90 },
+        });
+        let resp = response_from_anthropic(&anthropic);
+        assert_eq!(resp["status"], "completed");
+        assert_eq!(resp["output"][0]["content"][0]["text"], "Done.");
+        assert_eq!(resp["output"][1]["call_id"], "toolu_1");
+        assert_eq!(resp["usage"]["input_tokens"], 100);
+        assert_eq!(resp["usage"]["input_tokens_details"]["cached_tokens"], 90);
+
+        let back = response_to_anthropic(&resp);
+        assert_eq!(back["content"], anthropic["content"]);
+        assert_eq!(back["stop_reason"], "tool_use");
+        assert_eq!(back["usage"]["input_tokens"], 10);
+    }
+
+    #[test]
+    fn test_stream_encoder_emits_completed_response() {
+        let mut encoder = StreamEncoder::default();
+        let mut out = String::new();
+        for event in [
+            json!({ "type": "message_start", "message": { "id": "msg_1", "model": "claude-sonnet-4-5", "usage": { "input_tokens": 12, "output_tokens": 0 } } }),
+            json!({ "type": "content_block_start", "index": 0, "content_block": { "type": "thinking", "thinking": "" } }),
+            json!({ "type": "content_block_stop", "index": 0 }),
+            json!({ "type": "content_block_start", "index": 1, "content_block": { "type": "text", "text": "" } }),
+            json!({ "type": "content_block_delta", "index": 1, "delta": { "type": "text_delta", "text": "Hel" } }),
+            json!({ "type": "content_block_delta", "index": 1, "delta": { "type": "text_delta", "text": "lo" } }),
+            json!({ "type": "content_block_stop", "index": 1 }),
+            json!({ "type": "message_delta", "delta": { "stop_reason": "end_turn" }, "usage": { "output_tokens": 3 } }),
+            json!({ "type": "message_stop" }),
+        ] {
+            out.push_str(&encoder.encode(&event));
+        }
+
+        let completed: Value = out
+            .split("\n\n")
+            .find(|event| event.starts_with("event: response.completed"))
+            .and_then(|event| event.split_once("data: "))
+            .map(|(_, data)| serde_json::from_str(data).unwrap())
+            .unwrap();
+        let response = &completed["response"];
+        assert_eq!(response["output"].as_array().unwrap().len(), 1);
+        assert_eq!(response["output"][0]["content"][0]["text"], "Hello");
+        assert!(response["output"][0].get("text").is_none());
+        assert_eq!(response["usage"]["input_tokens"], 12);
+        assert_eq!(response["usage"]["output_tokens"], 3);
+    }
+}
@@ -0,0 +1,224 @@
+//! SSE 流转换：上游事件 → Anthropic 事件 → 客户端事件
+
+use serde_json::Value;
+
+use super::{chat, responses};
+use crate::models::proxy_config::ApiProtocol;
+use crate::services::proxy::utils::sse_quirks::{data_payloads, find_event_boundary};
+
+/// 上游事件解码器（输出 Anthropic 事件）
+#[derive(Debug)]
+enum Decoder {
+    Anthropic,
+    Chat(chat::StreamDecoder),
+    Responses(responses::StreamDecoder),
+}
+
+/// 客户端事件编码器（输入 Anthropic 事件）
+#[derive(Debug)]
+enum Encoder {
+    Anthropic,
+    Chat(chat::StreamEncoder),
+    Responses(responses::StreamEncoder),
+}
+
+/// SSE 流转换器
+///
+/// 跨 chunk 边界拼接上游事件，每个完整事件转换后立即输出
+#[derive(Debug)]
+pub struct SseTranscoder {
+    decoder: Decoder,
+    encoder: Encoder,
+    /// 尚未遇到事件分隔符的残留数据
+    pending: Vec<u8>,
+}
+
+impl SseTranscoder {
+    pub fn new(upstream: ApiProtocol, client: ApiProtocol) -> Self {
+        let decoder = match upstream {
+            ApiProtocol::Anthropic => Decoder::Anthropic,
+            ApiProtocol::OpenaiChat => Decoder::Chat(Default::default()),
+            ApiProtocol::OpenaiResponses => Decoder::Responses(Default::default()),
+        };
+        let encoder = match client {
+            ApiProtocol::Anthropic => Encoder::Anthropic,
+            ApiProtocol::OpenaiChat => Encoder::Chat(Default::default()),
+            ApiProtocol::OpenaiResponses => Encoder::Responses(Default::default()),
+        };
+        Self {
+            decoder,
+            encoder,
+            pending: Vec::new(),
+        }
+    }
+
+    /// 输入一个网络 chunk，返回转换后可立即转发的数据
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<u8> {
+        self.pending.extend_from_slice(chunk);
+        let pending = std::mem::take(&mut self.pending);
+
+        let mut out = String::new();
+        let mut consumed = 0;
+        while let Some((event_end, next_start)) = find_event_boundary(&pending[consumed..]) {
+            self.transcode_event(&pending[consumed..consumed + event_end], &mut out);
+            consumed += next_start;
+        }
+
+        self.pending = pending;
+        self.pending.drain(..consumed);
+        out.into_bytes()
+    }
+
+    /// 结束输入：处理残留事件，并补齐上游未发送的结束事件
+    pub fn finish(&mut self) -> Vec<u8> {
+        let pending = std::mem::take(&mut self.pending);
+        let mut out = String::new();
+        if !pending.is_empty() {
+            self.transcode_event(&pending, &mut out);
+        }
+        let events = match &mut self.decoder {
+            Decoder::Anthropic => Vec::new(),
+            Decoder::Chat(decoder) => decoder.finish(),
+            Decoder::Responses(decoder) => decoder.finish(),
+        };
+        for event in events {
+            out.push_str(&self.encode(&event));
+        }
+        out.into_bytes()
+    }
+
+    fn transcode_event(&mut self, raw: &[u8], out: &mut String) {
+        let text = String::from_utf8_lossy(raw);
+        let data = data_payloads(&text).collect::<Vec<_>>().join("\n");
+        if data.trim().is_empty() {
+            return;
+        }
+
+        let events = if data.trim() == "[DONE]" {
+            match &mut self.decoder {
+                Decoder::Chat(decoder) => decoder.finish(),
+                _ => Vec::new(),
+            }
+        } else {
+            let Ok(json) = serde_json::from_str::<Value>(&data) else {
+                tracing::debug!(data = %data, "协议转换跳过无法解析的 SSE 事件");
+                return;
+            };
+            match &mut self.decoder {
+                Decoder::Anthropic => vec![json],
+                Decoder::Chat(decoder) => decoder.decode(&json),
+                Decoder::Responses(decoder) => decoder.decode(&json),
+            }
+        };
+        for event in events {
+            out.push_str(&self.encode(&event));
+        }
+    }
+
+    fn encode(&mut self, event: &Value) -> String {
+        match &mut self.encoder {
+            Encoder::Anthropic => {
+                let kind = event["type"].as_str().unwrap_or("message");
+                format!("event: {kind}\ndata: {event}\n\n")
+            }
+            Encoder::Chat(encoder) => encoder.encode(event),
+            Encoder::Responses(encoder) => encoder.encode(event),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn events_of(output: &[u8]) -> Vec<Value> {
+        String::from_utf8_lossy(output)
+            .split("\n\n")
+            .filter_map(|event| {
+                data_payloads(event)
+                    .next()
+                    .and_then(|data| serde_json::from_str(data).ok())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_chat_upstream_to_anthropic_client_across_chunks() {
+        let mut transcoder = SseTranscoder::new(ApiProtocol::OpenaiChat, ApiProtocol::Anthropic);
+        let upstream = concat!(
+            "data: {\"id\":\"c1\",\"model\":\"gpt-4.1\",\"choices\":[{\"index\":0,\"delta\":{\"content\":\"Hel\"}}]}\n\n",
+            "data: {\"id\":\"c1\",\"choices\":[{\"index\":0,\"delta\":{\"content\":\"lo\"},\"finish_reason\":\"stop\"}]}\n\n",
+            "data: {\"id\":\"c1\",\"choices\":[],\"usage\":{\"prompt_tokens\":7,\"completion_tokens\":2}}\n\n",
+            "data: [DONE]\n\n",
+        );
+        // 在事件中间切分，模拟网络 chunk
+        let (first, second) = upstream.split_at(50);
+        let mut output = transcoder.feed(first.as_bytes());
+        output.extend(transcoder.feed(second.as_bytes()));
+        output.extend(transcoder.finish());
+
+        let text = String::from_utf8_lossy(&output);
+        assert!(text.starts_with("event: message_start\n"));
+        let events = events_of(&output);
+        let types: Vec<&str> = events.iter().map(|e| e["type"].as_str().unwrap()).collect();
+        assert_eq!(
+            types,
+            vec![
+                "message_start",
+                "content_block_start",
+                "content_block_delta",
+                "content_block_delta",
+                "content_block_stop",
+                "message_delta",
+                "message_stop",
+            ]
+        );
+        assert_eq!(events[5]["usage"]["input_tokens"], 7);
+        assert_eq!(events[5]["delta"]["stop_reason"], "end_turn");
+    }
+
+    #[test]
+    fn test_anthropic_upstream_to_chat_client() {
+        let mut transcoder = SseTranscoder::new(ApiProtocol::Anthropic, ApiProtocol::OpenaiChat);
+        let upstream = concat!(
+            "event: message_start\ndata: {\"type\":\"message_start\",\"message\":{\"id\":\"msg_1\",\"model\":\"claude-sonnet-4-5\",\"usage\":{\"input_tokens\":5,\"output_tokens\":0}}}\n\n",
+            "event: content_block_start\ndata: {\"type\":\"content_block_start\",\"index\":0,\"content_block\":{\"type\":\"text\",\"text\":\"\"}}\n\n",
+            "event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"Hi\"}}\n\n",
+            "event: content_block_stop\ndata: {\"type\":\"content_block_stop\",\"index\":0}\n\n",
+            "event: message_delta\ndata: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"end_turn\"},\"usage\":{\"output_tokens\":1}}\n\n",
+            "event: message_stop\ndata: {\"type\":\"message_stop\"}\n\n",
+        );
+        let mut output = transcoder.feed(upstream.as_bytes());
+        output.extend(transcoder.finish());
+
+        let text = String::from_utf8_lossy(&output);
+        assert!(text.ends_with("data: [DONE]\n\n"));
+        let chunks = events_of(&output);
+        assert_eq!(chunks[1]["choices"][0]["delta"]["content"], "Hi");
+        assert_eq!(chunks[2]["choices"][0]["finish_reason"], "stop");
+        assert_eq!(chunks[3]["usage"]["prompt_tokens"], 5);
+        assert_eq!(chunks[3]["usage"]["completion_tokens"], 1);
+    }
+
+    #[test]
+    fn test_responses_upstream_without_completed_event() {
+        let mut transcoder =
+            SseTranscoder::new(ApiProtocol::OpenaiResponses, ApiProtocol::Anthropic);
+        let upstream = concat!(
+            "event: response.created\ndata: {\"type\":\"response.created\",\"response\":{\"id\":\"resp_1\",\"model\":\"gpt-5\"}}\n\n",
+            "event: response.output_item.added\ndata: {\"type\":\"response.output_item.added\",\"output_index\":0,\"item\":{\"type\":\"function_call\",\"call_id\":\"call_1\",\"name\":\"ls\"}}\n\n",
+            "event: response.function_call_arguments.delta\ndata: {\"type\":\"response.function_call_arguments.delta\",\"output_index\":0,\"delta\":\"{}\"}\n\n",
+        );
+        let mut output = transcoder.feed(upstream.as_bytes());
+        output.extend(transcoder.finish());
+
+        let events = events_of(&output);
+        let last_two: Vec<&str> = events[events.len() - 2..]
+            .iter()
+            .map(|e| e["type"].as_str().unwrap())
+            .collect();
+        assert_eq!(last_two, vec!["message_delta", "message_stop"]);
+        assert_eq!(events[1]["content_block"]["id"], "call_1");
+        assert_eq!(events[events.len() - 2]["delta"]["stop_reason"], "tool_use");
+    }
+}
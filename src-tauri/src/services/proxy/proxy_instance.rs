@@ -11,19 +11,38 @@ use http_body_util::{BodyExt, Full};
 use hyper::body::{Frame, Incoming};
 use hyper::server::conn::http1;
 use hyper::service::service_fn;
-use hyper::{Method, Request, Response, StatusCode};
+use hyper::{HeaderMap, Method, Request, Response, StatusCode};
 use hyper_util::rt::TokioIo;
 use std::convert::Infallible;
-use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
-use tokio::net::TcpListener;
+use std::time::Duration;
 use tokio::sync::RwLock;
 use tokio_util::sync::CancellationToken;
 
+use super::capture_store;
+use super::concurrency_limit::{ConcurrencyLimiter, ConcurrencyRejection, ConcurrencyStatus};
+use super::cost_guard::{CostGuard, CostGuardDecision, COST_GUARD_HEADER};
+use super::failover::{self, FailoverState, FailoverStatus, ProfileFailoverTrigger};
 use super::headers::RequestProcessor;
+use super::key_pool::KeyBalancer;
+use super::listener::ProxyListener;
+use super::log_recorder::{
+    CostAnnotation, LiveUsageReporter, LogRecorder, ParsedResponse, RequestLogContext,
+    ResponseParser,
+};
+use super::protocol::ProtocolAdapter;
+use super::rate_limit::RateLimiter;
+use super::redaction;
+use super::response_cache::{self, CachedResponse, ResponseCache};
+use super::upstream_queue::{self, UpstreamQueue, UpstreamQueueStatus};
+use super::upstream_timeout::{self, StreamTimeout};
 use super::utils::body::{box_body, BoxBody};
 use super::utils::{error_responses, loop_detector};
-use crate::models::proxy_config::ToolProxyConfig;
+use super::websocket;
+use crate::models::proxy_config::{BodyCaptureConfig, ClientAuth, ToolProxyConfig};
+use crate::services::session::{transcript, SessionEvent, SESSION_MANAGER};
+use crate::services::token_stats::BudgetTracker;
 
 /// 单个代理实例
 pub struct ProxyInstance {
@@ -32,6 +51,33 @@ pub struct ProxyInstance {
     processor: Arc<dyn RequestProcessor>,
     server_handle: Arc<RwLock<Option<tokio::task::JoinHandle<()>>>>,
     cancel_token: CancellationToken,
+    /// 排空信号：停止接受新连接，已有连接处理完当前请求后关闭
+    drain_token: CancellationToken,
+    active_connections: Arc<AtomicUsize>,
+    /// 多上游故障转移状态（重启实例后重置）
+    failover: Arc<FailoverState>,
+    /// 主上游 Key 池选择器
+    key_balancer: Arc<KeyBalancer>,
+    /// 上游限流排队计数
+    queue: Arc<UpstreamQueue>,
+    /// 并发上游请求上限
+    concurrency: Arc<ConcurrencyLimiter>,
+}
+
+/// 连接计数守卫（连接任务结束时自动减一）
+struct ConnectionGuard(Arc<AtomicUsize>);
+
+impl ConnectionGuard {
+    fn new(counter: &Arc<AtomicUsize>) -> Self {
+        counter.fetch_add(1, Ordering::SeqCst);
+        Self(Arc::clone(counter))
+    }
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
 }
 
 impl ProxyInstance {
@@ -47,6 +93,12 @@ impl ProxyInstance {
             processor: Arc::from(processor),
             server_handle: Arc::new(RwLock::new(None)),
             cancel_token: CancellationToken::new(),
+            drain_token: CancellationToken::new(),
+            active_connections: Arc::new(AtomicUsize::new(0)),
+            failover: Arc::new(FailoverState::new()),
+            key_balancer: Arc::new(KeyBalancer::new()),
+            queue: Arc::new(UpstreamQueue::new()),
+            concurrency: Arc::new(ConcurrencyLimiter::new()),
         }
     }
 
@@ -70,21 +122,12 @@ impl ProxyInstance {
             );
         }
 
-        // 绑定地址
-        let addr = if config.allow_public {
-            SocketAddr::from(([0, 0, 0, 0], config.port))
-        } else {
-            SocketAddr::from(([127, 0, 0, 1], config.port))
-        };
-
-        let listener = TcpListener::bind(addr)
-            .await
-            .context(format!("绑定端口 {} 失败", config.port))?;
+        // 绑定地址（TCP 端口或本地套接字）
+        let mut listener = ProxyListener::bind(&config).await?;
 
         tracing::info!(
             tool_id = %self.tool_id,
-            addr = %addr,
-            bind_mode = if config.allow_public { "0.0.0.0" } else { "127.0.0.1" },
+            addr = %config.listen_address(),
             "透明代理启动成功"
         );
 
@@ -93,6 +136,12 @@ impl ProxyInstance {
         let port = config.port;
         let tool_id = self.tool_id.clone();
         let cancel_token = self.cancel_token.clone();
+        let drain_token = self.drain_token.clone();
+        let active_connections = Arc::clone(&self.active_connections);
+        let failover_clone = Arc::clone(&self.failover);
+        let key_balancer_clone = Arc::clone(&self.key_balancer);
+        let queue_clone = Arc::clone(&self.queue);
+        let concurrency_clone = Arc::clone(&self.concurrency);
 
         // 启动服务器
         let handle = tokio::spawn(async move {
@@ -102,43 +151,85 @@ impl ProxyInstance {
                         tracing::debug!(tool_id = %tool_id, "代理服务器收到取消信号");
                         break;
                     }
+                    _ = drain_token.cancelled() => {
+                        tracing::debug!(tool_id = %tool_id, "代理服务器停止接受新连接");
+                        break;
+                    }
                     result = listener.accept() => {
                         match result {
-                            Ok((stream, _addr)) => {
+                            Ok(stream) => {
                                 let config = Arc::clone(&config_clone);
                                 let processor = Arc::clone(&processor_clone);
+                                let failover = Arc::clone(&failover_clone);
+                                let key_balancer = Arc::clone(&key_balancer_clone);
+                                let queue = Arc::clone(&queue_clone);
+                                let concurrency = Arc::clone(&concurrency_clone);
                                 let tool_id_inner = tool_id.clone();
                                 let tool_id_for_error = tool_id.clone();
                                 let conn_cancel = cancel_token.clone();
+                                let tunnel_cancel = cancel_token.clone();
+                                let conn_drain = drain_token.clone();
+                                let guard = ConnectionGuard::new(&active_connections);
 
                                 tokio::spawn(async move {
+                                    let _guard = guard;
                                     let io = TokioIo::new(stream);
                                     let service = service_fn(move |req| {
                                         let config = Arc::clone(&config);
                                         let processor = Arc::clone(&processor);
+                                        let failover = Arc::clone(&failover);
+                                        let key_balancer = Arc::clone(&key_balancer);
+                                        let queue = Arc::clone(&queue);
+                                        let concurrency = Arc::clone(&concurrency);
                                         let tool_id = tool_id_inner.clone();
+                                        let tunnel_cancel = tunnel_cancel.clone();
                                         async move {
-                                            handle_request(req, config, processor, port, &tool_id).await
+                                            handle_request(
+                                                req,
+                                                config,
+                                                processor,
+                                                failover,
+                                                key_balancer,
+                                                queue,
+                                                concurrency,
+                                                port,
+                                                &tool_id,
+                                                tunnel_cancel,
+                                            )
+                                            .await
                                         }
                                     });
 
-                                    let conn = http1::Builder::new().serve_connection(io, service);
+                                    // 支持 WebSocket 升级（升级后的隧道由请求处理器接管）
+                                    let conn = http1::Builder::new()
+                                        .serve_connection(io, service)
+                                        .with_upgrades();
                                     tokio::pin!(conn);
 
-                                    // 使用 select 在连接完成或取消时退出
-                                    tokio::select! {
-                                        _ = conn_cancel.cancelled() => {
-                                            tracing::debug!(tool_id = %tool_id_for_error, "连接被取消");
-                                        }
-                                        result = &mut conn => {
-                                            if let Err(err) = result {
-                                                if !err.is_incomplete_message() {
-                                                    tracing::error!(
-                                                        tool_id = %tool_id_for_error,
-                                                        error = ?err,
-                                                        "处理连接失败"
-                                                    );
+                                    // 使用 select 在连接完成或取消时退出；
+                                    // 排空时当前请求（含流式响应）完成后关闭连接
+                                    let mut draining = false;
+                                    loop {
+                                        tokio::select! {
+                                            _ = conn_cancel.cancelled() => {
+                                                tracing::debug!(tool_id = %tool_id_for_error, "连接被取消");
+                                                break;
+                                            }
+                                            _ = conn_drain.cancelled(), if !draining => {
+                                                draining = true;
+                                                conn.as_mut().graceful_shutdown();
+                                            }
+                                            result = &mut conn => {
+                                                if let Err(err) = result {
+                                                    if !err.is_incomplete_message() {
+                                                        tracing::error!(
+                                                            tool_id = %tool_id_for_error,
+                                                            error = ?err,
+                                                            "处理连接失败"
+                                                        );
+                                                    }
                                                 }
+                                                break;
                                             }
                                         }
                                     }
@@ -211,6 +302,65 @@ impl ProxyInstance {
         Ok(())
     }
 
+    /// 排空后停止：不再接受新连接，等待进行中的请求完成（最长 `timeout`）
+    ///
+    /// 返回是否在超时前排空完毕；超时后剩余连接被强制关闭。
+    pub async fn drain_and_stop(&self, timeout: Duration) -> Result<bool> {
+        self.drain_token.cancel();
+
+        let deadline = tokio::time::Instant::now() + timeout;
+        let drained = loop {
+            if self.active_connections() == 0 {
+                break true;
+            }
+            if tokio::time::Instant::now() >= deadline {
+                break false;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        };
+        if !drained {
+            tracing::warn!(
+                tool_id = %self.tool_id,
+                remaining = self.active_connections(),
+                "透明代理排空超时，强制关闭剩余连接"
+            );
+        }
+
+        self.stop().await?;
+        Ok(drained)
+    }
+
+    /// 当前打开的连接数
+    pub fn active_connections(&self) -> usize {
+        self.active_connections.load(Ordering::SeqCst)
+    }
+
+    /// 获取当前配置
+    pub async fn config(&self) -> ToolProxyConfig {
+        self.config.read().await.clone()
+    }
+
+    /// 设置 Profile 自动切换触发回调（当前 Profile 主上游连续出错达到阈值时调用）
+    pub fn set_profile_failover_trigger(&self, trigger: ProfileFailoverTrigger) {
+        self.failover.set_profile_trigger(trigger);
+    }
+
+    /// 多上游故障转移状态
+    pub async fn failover_status(&self) -> FailoverStatus {
+        let config = self.config.read().await;
+        self.failover.snapshot(&config)
+    }
+
+    /// 上游限流排队状态
+    pub fn queue_status(&self) -> UpstreamQueueStatus {
+        self.queue.status()
+    }
+
+    /// 并发上游请求状态
+    pub fn concurrency_status(&self) -> ConcurrencyStatus {
+        self.concurrency.status()
+    }
+
     /// 检查服务是否在运行
     pub fn is_running(&self) -> bool {
         // 使用 blocking 方式读取，因为这是同步方法
@@ -227,6 +377,12 @@ impl ProxyInstance {
     /// 更新配置（无需重启）
     pub async fn update_config(&self, new_config: ToolProxyConfig) -> Result<()> {
         let mut config = self.config.write().await;
+        // 主上游变化后重新统计连续错误
+        if config.real_profile_name != new_config.real_profile_name
+            || config.real_base_url != new_config.real_base_url
+        {
+            self.failover.reset_profile_errors();
+        }
         *config = new_config;
         tracing::info!(tool_id = %self.tool_id, "透明代理配置已更新");
         Ok(())
@@ -234,14 +390,33 @@ impl ProxyInstance {
 }
 
 /// 处理单个请求
+#[allow(clippy::too_many_arguments)]
 async fn handle_request(
     req: Request<Incoming>,
     config: Arc<RwLock<ToolProxyConfig>>,
     processor: Arc<dyn RequestProcessor>,
+    failover: Arc<FailoverState>,
+    key_balancer: Arc<KeyBalancer>,
+    queue: Arc<UpstreamQueue>,
+    concurrency: Arc<ConcurrencyLimiter>,
     own_port: u16,
     tool_id: &str,
+    tunnel_cancel: CancellationToken,
 ) -> Result<Response<BoxBody>, Infallible> {
-    match handle_request_inner(req, config, processor, own_port, tool_id).await {
+    match handle_request_inner(
+        req,
+        config,
+        processor,
+        failover,
+        key_balancer,
+        queue,
+        concurrency,
+        own_port,
+        tool_id,
+        tunnel_cancel,
+    )
+    .await
+    {
         Ok(res) => Ok(res),
         Err(e) => {
             tracing::error!(
@@ -254,18 +429,24 @@ async fn handle_request(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn handle_request_inner(
     req: Request<Incoming>,
     config: Arc<RwLock<ToolProxyConfig>>,
     processor: Arc<dyn RequestProcessor>,
+    failover: Arc<FailoverState>,
+    key_balancer: Arc<KeyBalancer>,
+    queue: Arc<UpstreamQueue>,
+    concurrency: Arc<ConcurrencyLimiter>,
     own_port: u16,
     tool_id: &str,
+    tunnel_cancel: CancellationToken,
 ) -> Result<Response<BoxBody>> {
     // 记录请求开始时间（用于计算响应时间）
     let start_time = std::time::Instant::now();
 
     // 获取配置
-    let proxy_config = {
+    let mut proxy_config = {
         let cfg = config.read().await;
         if cfg.real_api_key.is_none() || cfg.real_base_url.is_none() {
             return Ok(error_responses::configuration_missing(tool_id));
@@ -291,10 +472,35 @@ async fn handle_request_inner(
         auth_header
     };
 
-    if let Some(local_key) = &proxy_config.local_api_key {
-        if provided_key != local_key {
-            return Ok(error_responses::unauthorized());
+    // 客户端 Key 命中时记录其标签，用于按客户端统计用量
+    let client_key_label = match proxy_config.authorize_client(provided_key) {
+        ClientAuth::Denied => return Ok(error_responses::unauthorized()),
+        ClientAuth::Primary => None,
+        ClientAuth::Client(label) => Some(label),
+    };
+
+    // WebSocket 升级：与当前首选上游建立隧道（不读取请求体，不做路由与故障转移）
+    if websocket::is_upgrade_request(req.headers()) {
+        let mut candidates = proxy_config.upstream_candidates();
+        if let (Some(key), Some(primary)) = (
+            key_balancer.pick(&proxy_config.key_pool, proxy_config.key_balance_mode),
+            candidates.first_mut(),
+        ) {
+            primary.api_key = key.api_key;
         }
+        let Some(upstream) = failover.order(candidates).into_iter().next() else {
+            return Ok(error_responses::configuration_missing(tool_id));
+        };
+        return websocket::proxy_upgrade(
+            req,
+            processor.as_ref(),
+            &upstream,
+            &proxy_config.egress_proxy,
+            own_port,
+            tool_id,
+            tunnel_cancel,
+        )
+        .await;
     }
 
     // 提取请求信息（先借用，避免与后续的 collect 冲突）
@@ -332,129 +538,435 @@ async fn handle_request_inner(
         .unwrap_or("unknown")
         .to_string();
 
-    // amp-code 在 processor 内部获取配置，这里传占位符
-    let base = proxy_config
-        .real_base_url
-        .as_deref()
-        .map(|s| s.trim_end_matches('/'))
-        .unwrap_or("");
-
     // 读取请求体（消费 req）
-    let body_bytes = if method != Method::GET && method != Method::HEAD {
+    let mut body_bytes = if method != Method::GET && method != Method::HEAD {
         req.collect().await?.to_bytes()
     } else {
         Bytes::new()
     };
 
-    // 使用 RequestProcessor 统一处理请求（URL + headers + body）
-    // amp-code 忽略传入的 base/api_key，在内部通过 amp_selection 获取
-    let processed = processor
-        .process_outgoing_request(
-            base,
-            proxy_config.real_api_key.as_deref().unwrap_or(""),
+    // 模型路由：命中规则时主上游切换为规则指定的 Profile（Key 池仅属于原 Profile，不再使用）
+    let routed = match processor.route_upstream(&proxy_config.routing_rules, &path, &body_bytes) {
+        Some(rule) => {
+            tracing::debug!(
+                tool_id = %tool_id,
+                rule_id = %rule.id,
+                pattern = %rule.model_pattern,
+                profile = %rule.profile_name,
+                "请求命中模型路由规则"
+            );
+            let rule = rule.clone();
+            proxy_config.apply_routing_rule(&rule);
+            true
+        }
+        None => false,
+    };
+
+    // 消费预算：拦截模式下超出预算直接拒绝（状态由 TokenStatsManager 后台每分钟刷新）
+    if BudgetTracker::global().is_blocked(tool_id, &proxy_config.budget) {
+        tracing::warn!(tool_id = %tool_id, "消费超出预算，请求被拦截");
+        return Ok(error_responses::budget_exceeded(tool_id));
+    }
+
+    // 本地限流：超出额度时直接返回 429，不转发到上游
+    if proxy_config.rate_limit.is_active() {
+        let session_id = if proxy_config.rate_limit.per_session {
+            serde_json::from_slice::<serde_json::Value>(&body_bytes)
+                .ok()
+                .and_then(|json| RequestLogContext::extract_session_id(tool_id, &json))
+        } else {
+            None
+        };
+        if let Err(rejection) = RateLimiter::global().try_acquire(
+            tool_id,
+            session_id.as_deref(),
+            &proxy_config.rate_limit,
+        ) {
+            tracing::warn!(
+                tool_id = %tool_id,
+                reason = %rejection.reason,
+                retry_after = rejection.retry_after_secs,
+                "请求被本地限流拦截"
+            );
+            let context = RequestLogContext::from_request(
+                tool_id,
+                proxy_config
+                    .real_profile_name
+                    .as_deref()
+                    .unwrap_or("default"),
+                &client_ip,
+                proxy_config.pricing_template_id.as_deref(),
+                &body_bytes,
+                None,
+            )
+            .with_client_key_label(client_key_label.as_deref());
+            LogRecorder::record_rate_limited(&context, &rejection.reason);
+            return Ok(error_responses::rate_limited(
+                tool_id,
+                &rejection.reason,
+                rejection.retry_after_secs,
+            ));
+        }
+    }
+
+    // 内容脱敏：转发前扫描请求体，按配置替换敏感内容或拦截请求
+    if proxy_config.redaction.enabled {
+        let outcome = redaction::scan(&proxy_config.redaction, &body_bytes);
+        if !outcome.hits.is_empty() {
+            let blocked = outcome.is_blocked(&proxy_config.redaction);
+            let rules = outcome.rule_names();
+            tracing::warn!(tool_id = %tool_id, rules = %rules, blocked, "请求命中脱敏规则");
+            redaction::record_hits(tool_id, &proxy_config.redaction, outcome.hits);
+            if blocked {
+                return Ok(error_responses::content_blocked(tool_id, &rules));
+            }
+            if let Some(body) = outcome.body {
+                body_bytes = body;
+            }
+        }
+    }
+
+    // 响应缓存：幂等接口命中缓存时直接返回，不转发到上游
+    let cache_key =
+        response_cache::cache_key(&proxy_config, &method, &path, query.as_deref(), &body_bytes);
+    if let Some(key) = &cache_key {
+        let ttl = Duration::from_secs(proxy_config.response_cache.ttl_secs);
+        if let Some(cached) = ResponseCache::global().get(tool_id, key, ttl) {
+            tracing::debug!(tool_id = %tool_id, path = %path, "响应缓存命中");
+            return Ok(cached.into_response());
+        }
+    }
+
+    // 成本预检：预估输入成本超出单次上限时按配置标记、等待用户确认或拒绝
+    let cost_guard_tag = match CostGuard::global()
+        .check(
+            tool_id,
+            proxy_config.pricing_template_id.as_deref(),
             &path,
-            query.as_deref(),
-            &headers,
             &body_bytes,
+            &proxy_config.cost_guard,
         )
         .await
-        .context("处理出站请求失败")?;
-
-    // 本地工具处理：dc-local:// 协议标记的请求直接返回 body
-    if processed.target_url.starts_with("dc-local://") {
-        tracing::debug!(
-            tool_id = %tool_id,
-            source = %processed.target_url,
-            "直接返回预构建响应"
-        );
-        let mut response = Response::builder()
-            .status(StatusCode::OK)
-            .header("content-type", "application/json");
+    {
+        CostGuardDecision::Allow => None,
+        CostGuardDecision::Tagged(estimate) => {
+            tracing::warn!(
+                tool_id = %tool_id,
+                model = %estimate.model,
+                input_tokens = estimate.input_tokens,
+                input_cost_usd = estimate.input_cost_usd,
+                "请求预估输入成本超出单次上限，继续转发"
+            );
+            Some(estimate.header_value())
+        }
+        CostGuardDecision::Rejected(estimate) => {
+            tracing::warn!(
+                tool_id = %tool_id,
+                model = %estimate.model,
+                input_tokens = estimate.input_tokens,
+                input_cost_usd = estimate.input_cost_usd,
+                "请求预估输入成本超出单次上限，已拦截"
+            );
+            return Ok(error_responses::cost_guard_rejected(
+                tool_id,
+                estimate.input_tokens,
+                estimate.input_cost_usd,
+                estimate.threshold_usd,
+            ));
+        }
+    };
 
-        for (name, value) in processed.headers.iter() {
-            response = response.header(name.as_str(), value.as_bytes());
+    // 并发上限：占用名额后再转发，名额在响应（含流式响应）传输结束时释放
+    let concurrency_permit = match concurrency.acquire(&proxy_config.concurrency_limit).await {
+        Ok(permit) => permit,
+        Err(rejection) => {
+            let limit = &proxy_config.concurrency_limit;
+            let reason = match rejection {
+                ConcurrencyRejection::Full => {
+                    format!("并发上游请求数已达上限 {}", limit.max_concurrent)
+                }
+                ConcurrencyRejection::Timeout => format!(
+                    "并发上游请求数已达上限 {}，排队超过 {} 秒",
+                    limit.max_concurrent, limit.max_wait_secs
+                ),
+            };
+            tracing::warn!(tool_id = %tool_id, reason = %reason, "请求被并发上限拦截");
+            return Ok(error_responses::concurrency_limited(tool_id, &reason));
         }
+    };
 
-        return Ok(response
-            .body(box_body(http_body_util::Full::new(processed.body)))
-            .unwrap());
+    // 配置了 Key 池时，主上游按负载均衡策略选出本次使用的 Key
+    let mut candidates = proxy_config.upstream_candidates();
+    let pooled_key = if routed {
+        None
+    } else {
+        key_balancer.pick(&proxy_config.key_pool, proxy_config.key_balance_mode)
+    };
+    if let (Some(key), Some(primary)) = (&pooled_key, candidates.first_mut()) {
+        primary.api_key = key.api_key.clone();
     }
 
-    // 回环检测
-    if loop_detector::is_proxy_loop(&processed.target_url, own_port) {
-        return Ok(error_responses::proxy_loop_detected(tool_id));
+    // 按尝试顺序排列上游：主上游优先，返回 5xx/429 或请求失败时依次切换到备用上游
+    let upstreams = failover.order(candidates);
+    if upstreams.is_empty() {
+        return Ok(error_responses::configuration_missing(tool_id));
     }
 
-    tracing::debug!(
-        tool_id = %tool_id,
-        method = %method,
-        path = %path,
-        target_url = %processed.target_url,
-        "代理请求"
-    );
+    // 全部上游返回 429 时按 Retry-After 排队，持有名额直到离开重试循环
+    let mut queue_slot = None;
+    let mut queued_at: Option<std::time::Instant> = None;
+
+    let mut attempt = 0;
+    let (request_body, upstream_res, upstream_key_alias, adapter, sent_at) = loop {
+        let upstream = &upstreams[attempt];
+        let has_next = attempt + 1 < upstreams.len();
+        // 当前 Profile 的主上游（未命中路由规则时），其连续错误用于 Profile 自动切换
+        let is_profile_primary =
+            !routed && proxy_config.real_base_url.as_deref() == Some(upstream.base_url.as_str());
+        let upstream_key_alias = pooled_key
+            .as_ref()
+            .filter(|key| {
+                key.api_key == upstream.api_key
+                    && proxy_config.real_base_url.as_deref() == Some(upstream.base_url.as_str())
+            })
+            .map(|key| key.alias.clone());
+
+        // 使用 RequestProcessor 统一处理请求（URL + headers + body）
+        // amp-code 忽略传入的 base/api_key，在内部通过 amp_selection 获取
+        let mut processed = processor
+            .process_outgoing_request(
+                upstream.base_url.trim_end_matches('/'),
+                &upstream.api_key,
+                &path,
+                query.as_deref(),
+                &headers,
+                &body_bytes,
+            )
+            .await
+            .context("处理出站请求失败")?;
+
+        // 协议转换：上游协议与客户端不同时改写请求（日志与统计仍使用客户端格式的请求体）
+        let request_body = processed.body.clone();
+        let adapter = ProtocolAdapter::resolve(&path, upstream.protocol)
+            .filter(|_| !processed.target_url.starts_with("dc-local://"));
+        if let Some(adapter) = &adapter {
+            adapter
+                .rewrite_request(&mut processed, &path)
+                .context("协议转换失败")?;
+        }
 
-    // 构建上游请求（使用处理后的信息）
-    let mut reqwest_builder = reqwest::Client::new().request(method.clone(), &processed.target_url);
+        // 本地工具处理：dc-local:// 协议标记的请求直接返回 body
+        if processed.target_url.starts_with("dc-local://") {
+            tracing::debug!(
+                tool_id = %tool_id,
+                source = %processed.target_url,
+                "直接返回预构建响应"
+            );
+            let mut response = Response::builder()
+                .status(StatusCode::OK)
+                .header("content-type", "application/json");
 
-    // 应用处理后的 headers
-    for (name, value) in processed.headers.iter() {
-        reqwest_builder = reqwest_builder.header(name, value);
-    }
+            for (name, value) in processed.headers.iter() {
+                response = response.header(name.as_str(), value.as_bytes());
+            }
 
-    // 添加请求体
-    if !processed.body.is_empty() {
-        reqwest_builder = reqwest_builder.body(processed.body.to_vec());
-    }
+            return Ok(response
+                .body(box_body(http_body_util::Full::new(processed.body)))
+                .unwrap());
+        }
 
-    // 发送请求
-    let upstream_res = match reqwest_builder.send().await {
-        Ok(res) => res,
-        Err(e) => {
-            // 上游请求失败，记录错误到数据库
-            let processor_clone = Arc::clone(&processor);
-            let client_ip_clone = client_ip.clone();
-            let config_name_clone = proxy_config
-                .real_profile_name
-                .clone()
-                .unwrap_or_else(|| "default".to_string());
-            let proxy_pricing_template_id_clone = proxy_config.pricing_template_id.clone();
-            let request_body_clone = processed.body.clone();
-            // 展开完整错误链（reqwest 的 source chain 包含底层原因如 DNS/TLS/超时等）
-            let error_msg = {
-                let mut msg = e.to_string();
-                let mut source = std::error::Error::source(&e);
-                while let Some(cause) = source {
-                    msg.push_str(&format!(" → {}", cause));
-                    source = std::error::Error::source(cause);
+        // 回环检测
+        if loop_detector::is_proxy_loop(&processed.target_url, own_port) {
+            return Ok(error_responses::proxy_loop_detected(tool_id));
+        }
+
+        tracing::debug!(
+            tool_id = %tool_id,
+            method = %method,
+            path = %path,
+            target_url = %processed.target_url,
+            "代理请求"
+        );
+
+        // 构建上游请求（使用处理后的信息，按工具的出口代理与连接超时配置连接）
+        let mut reqwest_builder = upstream_timeout::build_client(
+            &proxy_config.egress_proxy,
+            &proxy_config.upstream_timeout,
+        )?
+        .request(method.clone(), &processed.target_url);
+
+        // 应用处理后的 headers
+        for (name, value) in processed.headers.iter() {
+            reqwest_builder = reqwest_builder.header(name, value);
+        }
+
+        // 添加请求体
+        if !processed.body.is_empty() {
+            reqwest_builder = reqwest_builder.body(processed.body.to_vec());
+        }
+
+        // 发送请求（连接超时或总超时前未收到响应头时视为超时）
+        let sent_at = std::time::Instant::now();
+        let sent = upstream_timeout::send(reqwest_builder, &proxy_config.upstream_timeout).await;
+        let upstream_res = match sent {
+            Ok(res) => res,
+            Err(failure) => {
+                let error_msg = failure.detail().to_string();
+
+                failover.record_failure(upstream, error_msg.clone());
+                if is_profile_primary {
+                    failover.record_profile_error(
+                        error_msg.clone(),
+                        &proxy_config.profile_failover,
+                        proxy_config.real_profile_name.as_deref(),
+                    );
+                }
+                if has_next {
+                    tracing::warn!(
+                        tool_id = %tool_id,
+                        upstream = %upstream.base_url,
+                        error = %error_msg,
+                        "上游请求失败，切换到下一个上游"
+                    );
+                    attempt += 1;
+                    continue;
                 }
-                msg
-            };
 
-            // 从请求体中判断是否为流式请求
-            let is_sse = serde_json::from_slice::<serde_json::Value>(&processed.body)
-                .ok()
-                .and_then(|json| json.get("stream").and_then(|v| v.as_bool()))
-                .unwrap_or(false);
-
-            tokio::spawn(async move {
-                // 调用 record_request_log，传递 response_status=0 标记为上游失败
-                let _ = processor_clone
-                    .record_request_log(
-                        &client_ip_clone,
-                        &config_name_clone,
-                        proxy_pricing_template_id_clone.as_deref(),
-                        &request_body_clone,
-                        0,      // response_status=0 标记上游请求失败
-                        &[],    // 空响应体
-                        is_sse, // 从请求体提取
-                        Some(start_time.elapsed().as_millis() as i64),
+                if failure.is_timeout() {
+                    let context = timeout_log_context(
+                        tool_id,
+                        &proxy_config,
+                        &client_ip,
+                        &request_body,
+                        start_time,
                     )
-                    .await;
-            });
+                    .with_upstream_key_alias(upstream_key_alias.as_deref())
+                    .with_client_key_label(client_key_label.as_deref());
+                    LogRecorder::record_timeout(&context, &error_msg);
+                    return Ok(error_responses::upstream_timeout(tool_id, &error_msg));
+                }
 
-            return Err(anyhow::anyhow!("上游请求失败: {}", error_msg));
+                // 上游请求失败，记录错误到数据库
+                let processor_clone = Arc::clone(&processor);
+                let client_ip_clone = client_ip.clone();
+                let config_name_clone = proxy_config
+                    .real_profile_name
+                    .clone()
+                    .unwrap_or_else(|| "default".to_string());
+                let proxy_pricing_template_id_clone = proxy_config.pricing_template_id.clone();
+                let request_body_clone = request_body.clone();
+                let client_key_label_clone = client_key_label.clone();
+
+                // 从请求体中判断是否为流式请求
+                let is_sse = serde_json::from_slice::<serde_json::Value>(&request_body)
+                    .ok()
+                    .and_then(|json| json.get("stream").and_then(|v| v.as_bool()))
+                    .unwrap_or(false);
+
+                let capture = CaptureContext::new(
+                    tool_id,
+                    &proxy_config,
+                    &method,
+                    &path,
+                    query.as_deref(),
+                    &headers,
+                    &request_body,
+                );
+                tokio::spawn(async move {
+                    // 调用 record_request_log，传递 response_status=0 标记为上游失败
+                    let _ = processor_clone
+                        .record_request_log(
+                            &client_ip_clone,
+                            &config_name_clone,
+                            proxy_pricing_template_id_clone.as_deref(),
+                            &request_body_clone,
+                            0, // response_status=0 标记上游请求失败
+                            ParsedResponse::Empty,
+                            Some(start_time.elapsed().as_millis() as i64),
+                            None, // 无上游响应头
+                            upstream_key_alias.as_deref(),
+                            client_key_label_clone.as_deref(),
+                        )
+                        .await;
+                    capture.record(&request_body_clone, 0, &[], is_sse);
+                });
+
+                return Err(anyhow::anyhow!("上游请求失败: {}", error_msg));
+            }
+        };
+
+        let upstream_status = upstream_res.status().as_u16();
+        if failover::is_retryable_status(upstream_status) {
+            failover.record_failure(upstream, format!("HTTP {upstream_status}"));
+            if is_profile_primary {
+                failover.record_profile_error(
+                    format!("HTTP {upstream_status}"),
+                    &proxy_config.profile_failover,
+                    proxy_config.real_profile_name.as_deref(),
+                );
+            }
+            if has_next {
+                tracing::warn!(
+                    tool_id = %tool_id,
+                    upstream = %upstream.base_url,
+                    status = upstream_status,
+                    "上游返回可重试状态，切换到下一个上游"
+                );
+                attempt += 1;
+                continue;
+            }
+            if upstream_status == 429 {
+                let retry_after =
+                    upstream_queue::parse_retry_after(upstream_res.headers(), chrono::Utc::now());
+                let waited = queued_at.map(|t| t.elapsed()).unwrap_or_default();
+                if let Some(delay) =
+                    upstream_queue::queue_delay(&proxy_config.upstream_queue, retry_after, waited)
+                {
+                    if queue_slot.is_none() {
+                        queue_slot = queue.try_enter(proxy_config.upstream_queue.max_requests);
+                    }
+                    if queue_slot.is_some() {
+                        queued_at.get_or_insert_with(std::time::Instant::now);
+                        tracing::info!(
+                            tool_id = %tool_id,
+                            wait_ms = delay.as_millis() as u64,
+                            depth = queue.depth(),
+                            "上游限流，请求进入排队"
+                        );
+                        record_queued(tool_id, &body_bytes, delay);
+                        tokio::time::sleep(delay).await;
+                        attempt = 0;
+                        continue;
+                    }
+                    tracing::warn!(
+                        tool_id = %tool_id,
+                        max_requests = proxy_config.upstream_queue.max_requests,
+                        "排队请求数已达上限，直接返回上游 429"
+                    );
+                }
+            }
+        } else {
+            if is_profile_primary {
+                failover.reset_profile_errors();
+            }
+            if failover.record_success(upstream) {
+                tracing::info!(
+                    tool_id = %tool_id,
+                    upstream = %upstream.base_url,
+                    "活跃上游已切换"
+                );
+            }
         }
+
+        break (
+            request_body,
+            upstream_res,
+            upstream_key_alias,
+            adapter,
+            sent_at,
+        );
     };
+    drop(queue_slot);
 
     // 构建响应
     let status = StatusCode::from_u16(upstream_res.status().as_u16())
@@ -468,21 +980,35 @@ async fn handle_request_inner(
         .map(|v| v.contains("text/event-stream"))
         .unwrap_or(false);
 
+    // 捕获上游响应头（请求 ID 等），随日志一起记录
+    let upstream_headers = super::utils::header_capture::capture_headers(
+        upstream_res.headers(),
+        &proxy_config.captured_response_headers,
+    );
+
     let mut response = Response::builder().status(status);
 
-    // 复制响应 headers
+    // 复制响应 headers（协议转换会改变响应体长度）
     for (name, value) in upstream_res.headers().iter() {
+        if adapter.is_some() && name == hyper::header::CONTENT_LENGTH {
+            continue;
+        }
         response = response.header(name.as_str(), value.as_bytes());
     }
+    if let Some(tag) = &cost_guard_tag {
+        response = response.header(COST_GUARD_HEADER, tag.as_str());
+    }
 
     if is_sse {
         tracing::debug!(tool_id = %tool_id, "SSE 流式响应");
 
-        // SSE 流式响应：收集响应体并调用 processor.record_request_log
+        // SSE 流式响应：转发过程中逐个事件累加 Token，流结束后调用 processor.record_request_log
         use futures_util::StreamExt;
         use std::sync::{Arc, Mutex};
 
         use super::headers::strip_mcp_name_prefix_bytes;
+        use super::utils::sse_filter::{SseEventFilter, SseFlavor};
+        use super::utils::sse_quirks::SseStreamNormalizer;
 
         let config_name = proxy_config
             .real_profile_name
@@ -491,25 +1017,120 @@ async fn handle_request_inner(
 
         let proxy_pricing_template_id = proxy_config.pricing_template_id.clone();
 
-        // 使用 Arc<Mutex<Vec>> 在流处理过程中收集数据
-        let sse_chunks = Arc::new(Mutex::new(Vec::new()));
-        let sse_chunks_clone = Arc::clone(&sse_chunks);
+        // 统计旁路：按 API 风格过滤无关事件，只复制统计所需的数据
+        let flavor = if proxy_config.sse_tap_filter {
+            SseFlavor::for_tool(tool_id)
+        } else {
+            SseFlavor::Passthrough
+        };
+        let sse_tap = Arc::new(Mutex::new(Some(SseEventFilter::new(flavor))));
+        let sse_tap_clone = Arc::clone(&sse_tap);
+
+        // 非标准中转兼容：按当前 Profile 的选项改写转发给客户端的流（统计旁路仍使用原始数据）
+        let sse_compat = proxy_config.effective_sse_compat();
+        let normalizer = sse_compat
+            .is_enabled()
+            .then(|| Arc::new(Mutex::new(SseStreamNormalizer::new(sse_compat))));
+        let normalizer_flush = normalizer.clone();
+
+        // 成本注解：流结束时按统计旁路数据计算成本，追加一条 SSE 注释
+        let annotation_source = (proxy_config.cost_annotation && status.is_success()).then(|| {
+            (
+                Arc::clone(&sse_tap),
+                tool_id.to_string(),
+                config_name.clone(),
+                client_ip.clone(),
+                proxy_pricing_template_id.clone(),
+                request_body.clone(),
+            )
+        });
+
+        // 实时用量：前端监听时按节流间隔发送 token-usage-delta 事件
+        let live_usage = status
+            .is_success()
+            .then(|| {
+                LiveUsageReporter::start(|| {
+                    RequestLogContext::from_request(
+                        tool_id,
+                        &config_name,
+                        &client_ip,
+                        proxy_pricing_template_id.as_deref(),
+                        &request_body,
+                        None,
+                    )
+                })
+            })
+            .flatten()
+            .map(|reporter| Arc::new(Mutex::new(reporter)));
+        let live_usage_clone = live_usage.clone();
+
+        // 请求体捕获与会话对话记录：仅在开启时按大小上限保留响应
+        let capture = CaptureContext::new(
+            tool_id,
+            &proxy_config,
+            &method,
+            &path,
+            query.as_deref(),
+            &headers,
+            &request_body,
+        );
+        let capture_limit = capture.stream_buffer_limit();
+        let capture_buf = capture
+            .buffers_stream()
+            .then(|| Arc::new(Mutex::new(Vec::new())));
+        let capture_buf_clone = capture_buf.clone();
 
         // 创建一个通道,在流完全消费后触发统计
         let (stream_end_tx, stream_end_rx) = tokio::sync::oneshot::channel::<()>();
 
-        let stream = upstream_res.bytes_stream();
+        // 协议转换：上游事件先转换为客户端协议，统计旁路、捕获与兼容改写都基于转换后的数据
+        let transcoder = adapter.map(|a| Arc::new(Mutex::new(a.transcoder())));
+        let transcoder_flush = transcoder.clone();
+        // 流空闲超时：超过上限未收到数据时中断流，流结束后按超时记录日志
+        let stream_timeout = StreamTimeout::default();
+        let upstream_stream = upstream_timeout::guard_stream(
+            upstream_res.bytes_stream(),
+            proxy_config.upstream_timeout.idle_stream_timeout(),
+            Arc::clone(&stream_timeout),
+        );
+        let stream = upstream_stream
+            .map(move |result| match (result, transcoder.as_ref()) {
+                (Ok(chunk), Some(transcoder)) => Ok(match transcoder.lock() {
+                    Ok(mut transcoder) => Bytes::from(transcoder.feed(&chunk)),
+                    Err(_) => chunk,
+                }),
+                (result, _) => result,
+            })
+            .chain(futures_util::stream::iter(transcoder_flush).filter_map(
+                |transcoder| async move {
+                    let rest = transcoder.lock().ok()?.finish();
+                    (!rest.is_empty()).then(|| Ok(Bytes::from(rest)))
+                },
+            ));
 
         // amp-code 需要移除工具名前缀
         let is_amp_code = tool_id == "amp-code";
 
-        // 拦截流数据并收集
+        // 拦截流数据并输入统计旁路
         let mapped_stream = stream
             .map(move |result| {
                 match &result {
                     Ok(chunk) => {
-                        if let Ok(mut chunks) = sse_chunks_clone.lock() {
-                            chunks.push(chunk.clone());
+                        if let Ok(mut tap) = sse_tap_clone.lock() {
+                            if let Some(filter) = tap.as_mut() {
+                                filter.feed(chunk);
+                                if let Some(Ok(mut live)) =
+                                    live_usage_clone.as_ref().map(|l| l.lock())
+                                {
+                                    if live.is_due() {
+                                        live.observe(filter.snapshot());
+                                    }
+                                }
+                            }
+                        }
+                        if let Some(Ok(mut buf)) = capture_buf_clone.as_ref().map(|b| b.lock()) {
+                            let take = capture_limit.saturating_sub(buf.len()).min(chunk.len());
+                            buf.extend_from_slice(&chunk[..take]);
                         }
                     }
                     Err(_) => {
@@ -519,6 +1140,10 @@ async fn handle_request_inner(
 
                 result
                     .map(|bytes| {
+                        let bytes = match normalizer.as_ref().map(|n| n.lock()) {
+                            Some(Ok(mut n)) => Bytes::from(n.feed(&bytes)),
+                            _ => bytes,
+                        };
                         if is_amp_code {
                             Frame::data(strip_mcp_name_prefix_bytes(&bytes))
                         } else {
@@ -527,8 +1152,40 @@ async fn handle_request_inner(
                     })
                     .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)
             })
+            // 输出兼容改写器中尚未遇到事件分隔符的残留数据
+            .chain(futures_util::stream::iter(normalizer_flush).filter_map(
+                move |normalizer| async move {
+                    let rest = normalizer.lock().ok()?.finish();
+                    if rest.is_empty() {
+                        return None;
+                    }
+                    let rest = Bytes::from(rest);
+                    Some(Ok(if is_amp_code {
+                        Frame::data(strip_mcp_name_prefix_bytes(&rest))
+                    } else {
+                        Frame::data(rest)
+                    }))
+                },
+            ))
+            .chain(futures_util::stream::iter(annotation_source).filter_map(
+                |(tap, tool_id, config_name, client_ip, template_id, request_body)| async move {
+                    let parsed = tap.lock().ok()?.as_ref()?.snapshot();
+                    let context = RequestLogContext::from_request(
+                        &tool_id,
+                        &config_name,
+                        &client_ip,
+                        template_id.as_deref(),
+                        &request_body,
+                        None,
+                    );
+                    let annotation = CostAnnotation::estimate(&context, parsed)?;
+                    Some(Ok(Frame::data(Bytes::from(annotation.sse_comment()))))
+                },
+            ))
             // 在流的最后一个元素之后插入完成信号
             .chain(futures_util::stream::once(async move {
+                // 流结束（或客户端断开、流被丢弃）时释放并发名额
+                drop(concurrency_permit);
                 // 发送流完成信号
                 let _ = stream_end_tx.send(());
                 tracing::debug!("SSE 流已完全消费完毕,发送完成信号");
@@ -552,10 +1209,12 @@ async fn handle_request_inner(
         // 在流真正结束后异步记录日志
         let processor_clone = Arc::clone(&processor);
         let client_ip_clone = client_ip.clone();
-        let request_body_clone = processed.body.clone();
+        let request_body_clone = request_body.clone();
         let response_status = status.as_u16();
         let start_time_clone = start_time;
         let proxy_pricing_template_id_clone = proxy_pricing_template_id.clone();
+        let client_key_label_clone = client_key_label.clone();
+        let tool_id_clone = tool_id.to_string();
 
         tokio::spawn(async move {
             // 等待流完全消费的信号(无超时,真正等待流结束)
@@ -571,51 +1230,101 @@ async fn handle_request_inner(
             // 小延迟确保最后的 chunk 写入完成(异步锁竞争)
             tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
 
-            let chunks = match sse_chunks.lock() {
-                Ok(guard) => guard.clone(),
+            let parsed = match sse_tap.lock() {
+                Ok(mut guard) => guard
+                    .take()
+                    .map(SseEventFilter::finish)
+                    .unwrap_or(ParsedResponse::Empty),
                 Err(e) => {
-                    tracing::error!(error = ?e, "获取 SSE chunks 锁失败");
+                    tracing::error!(error = ?e, "获取 SSE 统计旁路锁失败");
                     return;
                 }
             };
 
-            tracing::debug!(
-                chunks_count = chunks.len(),
-                "开始处理 SSE chunks 进行 token 统计"
-            );
-
-            // 将所有 chunk 合并为完整响应体
-            let mut full_data = Vec::new();
-            for chunk in &chunks {
-                full_data.extend_from_slice(chunk);
+            if let Some(Ok(mut live)) = live_usage.as_ref().map(|l| l.lock()) {
+                live.finish(parsed.clone());
             }
 
             // 计算响应时间(从请求开始到流完全消费的总时间)
             let response_time_ms = start_time_clone.elapsed().as_millis() as i64;
 
-            // 调用工具特定的日志记录
-            if let Err(e) = processor_clone
+            // 流因空闲超时被中断时记录为超时，否则调用工具特定的日志记录
+            let timeout_detail = stream_timeout.lock().ok().and_then(|t| t.clone());
+            if let Some(detail) = timeout_detail {
+                let context = RequestLogContext::from_request(
+                    &tool_id_clone,
+                    &config_name,
+                    &client_ip_clone,
+                    proxy_pricing_template_id_clone.as_deref(),
+                    &request_body_clone,
+                    Some(response_time_ms),
+                )
+                .with_upstream_headers(upstream_headers.as_deref())
+                .with_upstream_key_alias(upstream_key_alias.as_deref())
+                .with_client_key_label(client_key_label_clone.as_deref());
+                LogRecorder::record_timeout(&context, &detail);
+            } else if let Err(e) = processor_clone
                 .record_request_log(
                     &client_ip_clone,
                     &config_name,
                     proxy_pricing_template_id_clone.as_deref(),
                     &request_body_clone,
                     response_status,
-                    &full_data,
-                    true, // is_sse
+                    parsed,
                     Some(response_time_ms),
+                    upstream_headers.as_deref(),
+                    upstream_key_alias.as_deref(),
+                    client_key_label_clone.as_deref(),
                 )
                 .await
             {
                 tracing::error!(error = ?e, "SSE 流日志记录失败");
             }
+            if let Some(buf) = capture_buf.and_then(|b| b.lock().ok().map(|b| b.clone())) {
+                capture.record(&request_body_clone, response_status, &buf, true);
+            }
         });
 
         let body = http_body_util::StreamBody::new(mapped_stream);
         Ok(response.body(box_body(body)).unwrap())
     } else {
         // 普通响应：读取响应体并调用 processor.record_request_log
-        let body_bytes = upstream_res.bytes().await.context("读取响应体失败")?;
+        let content_type = upstream_res
+            .headers()
+            .get("content-type")
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        // 总超时覆盖到读完响应体
+        let remaining = upstream_timeout::remaining(&proxy_config.upstream_timeout, sent_at);
+        let body_bytes = match remaining {
+            Some(remaining) => match tokio::time::timeout(remaining, upstream_res.bytes()).await {
+                Ok(result) => result,
+                Err(_) => {
+                    let detail = format!(
+                        "{} 秒内未读完上游响应",
+                        proxy_config.upstream_timeout.total_timeout_secs
+                    );
+                    let context = timeout_log_context(
+                        tool_id,
+                        &proxy_config,
+                        &client_ip,
+                        &request_body,
+                        start_time,
+                    )
+                    .with_upstream_headers(upstream_headers.as_deref())
+                    .with_upstream_key_alias(upstream_key_alias.as_deref())
+                    .with_client_key_label(client_key_label.as_deref());
+                    LogRecorder::record_timeout(&context, &detail);
+                    return Ok(error_responses::upstream_timeout(tool_id, &detail));
+                }
+            },
+            None => upstream_res.bytes().await,
+        }
+        .context("读取响应体失败")?;
+        let body_bytes = match &adapter {
+            Some(adapter) => adapter.translate_response(&body_bytes, status.as_u16()),
+            None => body_bytes,
+        };
 
         // amp-code 需要清理响应体中的工具名前缀
         let final_body = if tool_id == "amp-code" {
@@ -624,6 +1333,19 @@ async fn handle_request_inner(
             body_bytes.clone()
         };
 
+        if let Some(key) = cache_key.filter(|_| status.is_success()) {
+            ResponseCache::global().put(
+                tool_id,
+                key,
+                CachedResponse {
+                    status: status.as_u16(),
+                    content_type,
+                    body: final_body.clone(),
+                },
+                proxy_config.response_cache.max_entries,
+            );
+        }
+
         // 获取配置名称
         let config_name = proxy_config
             .real_profile_name
@@ -632,13 +1354,41 @@ async fn handle_request_inner(
 
         let proxy_pricing_template_id = proxy_config.pricing_template_id.clone();
 
+        // 成本注解：按与统计相同的方式计算成本，写入响应头
+        if proxy_config.cost_annotation && status.is_success() {
+            let context = RequestLogContext::from_request(
+                tool_id,
+                &config_name,
+                &client_ip,
+                proxy_pricing_template_id.as_deref(),
+                &request_body,
+                None,
+            );
+            let parsed = ResponseParser::parse(&body_bytes, status.as_u16(), false);
+            if let Some(annotation) = CostAnnotation::estimate(&context, parsed) {
+                for (name, value) in annotation.headers() {
+                    response = response.header(name, value);
+                }
+            }
+        }
+
         // 异步记录日志
         let processor_clone = Arc::clone(&processor);
         let client_ip_clone = client_ip.clone();
-        let request_body_clone = processed.body.clone();
+        let request_body_clone = request_body.clone();
         let response_body_clone = body_bytes.clone();
         let response_status = status.as_u16();
         let response_time_ms = start_time.elapsed().as_millis() as i64; // 计算响应时间
+        let client_key_label_clone = client_key_label.clone();
+        let capture = CaptureContext::new(
+            tool_id,
+            &proxy_config,
+            &method,
+            &path,
+            query.as_deref(),
+            &headers,
+            &request_body,
+        );
 
         tokio::spawn(async move {
             // 调用工具特定的日志记录
@@ -649,14 +1399,22 @@ async fn handle_request_inner(
                     proxy_pricing_template_id.as_deref(),
                     &request_body_clone,
                     response_status,
-                    &response_body_clone,
-                    false, // is_sse
+                    ResponseParser::parse(&response_body_clone, response_status, false),
                     Some(response_time_ms),
+                    upstream_headers.as_deref(),
+                    upstream_key_alias.as_deref(),
+                    client_key_label_clone.as_deref(),
                 )
                 .await
             {
                 tracing::error!(error = ?e, "日志记录失败");
             }
+            capture.record(
+                &request_body_clone,
+                response_status,
+                &response_body_clone,
+                false,
+            );
         });
 
         Ok(response
@@ -664,3 +1422,146 @@ async fn handle_request_inner(
             .unwrap())
     }
 }
+
+/// 上游超时日志的请求上下文
+fn timeout_log_context(
+    tool_id: &str,
+    proxy_config: &ToolProxyConfig,
+    client_ip: &str,
+    request_body: &[u8],
+    start_time: std::time::Instant,
+) -> RequestLogContext {
+    RequestLogContext::from_request(
+        tool_id,
+        proxy_config
+            .real_profile_name
+            .as_deref()
+            .unwrap_or("default"),
+        client_ip,
+        proxy_config.pricing_template_id.as_deref(),
+        request_body,
+        Some(start_time.elapsed().as_millis() as i64),
+    )
+}
+
+/// 记录排队事件到请求所属会话（无法识别会话时仅输出日志）
+fn record_queued(tool_id: &str, body: &[u8], delay: Duration) {
+    let Some(session_id) = serde_json::from_slice::<serde_json::Value>(body)
+        .ok()
+        .and_then(|json| RequestLogContext::extract_session_id(tool_id, &json))
+    else {
+        return;
+    };
+    let event = SessionEvent::RequestQueued {
+        session_id,
+        tool_id: tool_id.to_string(),
+        timestamp: chrono::Utc::now().timestamp(),
+        wait_ms: delay.as_millis() as i64,
+    };
+    if let Err(e) = SESSION_MANAGER.send_event(event) {
+        tracing::warn!(tool_id = %tool_id, error = ?e, "记录排队事件失败");
+    }
+}
+
+/// 请求体捕获上下文（在日志任务中写入捕获与会话对话记录）
+struct CaptureContext {
+    tool_id: String,
+    config: BodyCaptureConfig,
+    method: String,
+    path: String,
+    query: Option<String>,
+    /// 仅在开启捕获时保留客户端请求头
+    headers: HeaderMap,
+    /// 请求所属会话已开启对话记录时的会话 ID
+    transcript_session: Option<String>,
+}
+
+impl CaptureContext {
+    fn new(
+        tool_id: &str,
+        proxy_config: &ToolProxyConfig,
+        method: &Method,
+        path: &str,
+        query: Option<&str>,
+        headers: &HeaderMap,
+        request_body: &[u8],
+    ) -> Self {
+        let config = proxy_config.body_capture;
+        let transcript_session = if transcript::any_recording() {
+            serde_json::from_slice::<serde_json::Value>(request_body)
+                .ok()
+                .and_then(|json| RequestLogContext::extract_session_id(tool_id, &json))
+                .filter(|session_id| transcript::is_recording(session_id))
+        } else {
+            None
+        };
+        Self {
+            tool_id: tool_id.to_string(),
+            config,
+            method: method.to_string(),
+            path: path.to_string(),
+            query: query.map(|q| q.to_string()),
+            headers: if config.enabled {
+                headers.clone()
+            } else {
+                HeaderMap::new()
+            },
+            transcript_session,
+        }
+    }
+
+    /// 是否需要保留流式响应（开启捕获或会话对话记录）
+    fn buffers_stream(&self) -> bool {
+        self.config.enabled || self.transcript_session.is_some()
+    }
+
+    /// 流式响应的保留上限（多保留 1 字节用于判断截断）
+    fn stream_buffer_limit(&self) -> usize {
+        let capture_limit = if self.config.enabled {
+            self.config.effective_max_body_bytes() + 1
+        } else {
+            0
+        };
+        if self.transcript_session.is_some() {
+            capture_limit.max(transcript::RESPONSE_BUFFER_LIMIT)
+        } else {
+            capture_limit
+        }
+    }
+
+    fn record(&self, request_body: &[u8], status: u16, response_body: &[u8], is_sse: bool) {
+        let record = capture_store::CaptureRecord {
+            tool_id: &self.tool_id,
+            method: &self.method,
+            path: &self.path,
+            query: self.query.as_deref(),
+            headers: &self.headers,
+            request_body,
+            response_status: status,
+            response_body,
+            is_sse,
+        };
+        if let Err(e) = capture_store::record_capture(&self.config, &record) {
+            tracing::warn!(tool_id = %self.tool_id, error = ?e, "写入请求体捕获失败");
+        }
+
+        let Some(session_id) = self
+            .transcript_session
+            .as_deref()
+            .filter(|_| (200..300).contains(&status))
+        else {
+            return;
+        };
+        let exchange = transcript::ExchangeRecord {
+            session_id,
+            tool_id: &self.tool_id,
+            path: &self.path,
+            request_body,
+            response_body,
+            is_sse,
+        };
+        if let Err(e) = transcript::record_exchange(&exchange) {
+            tracing::warn!(session_id = %session_id, error = ?e, "写入会话对话记录失败");
+        }
+    }
+}
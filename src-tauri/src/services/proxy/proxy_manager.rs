@@ -4,29 +4,82 @@
 // - 启动和停止指定工具的代理
 // - 管理所有代理实例的状态
 // - 确保端口不冲突
+// - 当前 Profile 的上游持续出错时切换到备用 Profile（Profile 自动切换）
 
 use anyhow::{Context, Result};
 use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, RwLock};
 
+use super::concurrency_limit::ConcurrencyStatus;
+use super::failover::{FailoverStatus, ProxyFailoverEvent};
 use super::headers::create_request_processor;
 use super::proxy_instance::ProxyInstance;
+use super::upstream_queue::UpstreamQueueStatus;
 use crate::models::proxy_config::ToolProxyConfig;
+use crate::services::profile_manager::ProfileManager;
+use crate::services::proxy_config_manager::ProxyConfigManager;
+
+type ProxyInstances = Arc<RwLock<HashMap<String, ProxyInstance>>>;
+
+/// Profile 自动切换回调
+pub type ProxyFailoverNotifier = Box<dyn Fn(ProxyFailoverEvent) + Send + Sync>;
+
+/// Profile 切换请求（工具 ID, 连续错误次数, 最后一次错误）
+type FailoverRequest = (String, u32, Option<String>);
 
 /// 代理管理器
 pub struct ProxyManager {
-    instances: Arc<RwLock<HashMap<String, ProxyInstance>>>,
+    instances: ProxyInstances,
+    failover_tx: mpsc::UnboundedSender<FailoverRequest>,
+    /// 切换请求接收端（首次启动代理时交给后台任务）
+    failover_rx: Mutex<Option<mpsc::UnboundedReceiver<FailoverRequest>>>,
+    failover_notifier: Arc<std::sync::RwLock<Option<ProxyFailoverNotifier>>>,
 }
 
 impl ProxyManager {
     /// 创建新的代理管理器
     pub fn new() -> Self {
+        let (failover_tx, failover_rx) = mpsc::unbounded_channel();
         Self {
             instances: Arc::new(RwLock::new(HashMap::new())),
+            failover_tx,
+            failover_rx: Mutex::new(Some(failover_rx)),
+            failover_notifier: Arc::new(std::sync::RwLock::new(None)),
         }
     }
 
+    /// 设置 Profile 自动切换回调
+    pub fn set_failover_notifier(&self, notifier: ProxyFailoverNotifier) {
+        *self.failover_notifier.write().unwrap() = Some(notifier);
+    }
+
+    /// 启动处理 Profile 切换请求的后台任务（仅启动一次）
+    fn ensure_failover_worker(&self) {
+        let Some(mut rx) = self.failover_rx.lock().unwrap().take() else {
+            return;
+        };
+        let instances = Arc::clone(&self.instances);
+        let notifier = Arc::clone(&self.failover_notifier);
+        tokio::spawn(async move {
+            while let Some((tool_id, errors, last_error)) = rx.recv().await {
+                match switch_to_backup_profile(&instances, &tool_id, errors, last_error).await {
+                    Ok(Some(event)) => {
+                        if let Some(notify) = notifier.read().unwrap().as_ref() {
+                            notify(event);
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        tracing::error!(tool_id = %tool_id, error = ?e, "切换备用 Profile 失败");
+                        crate::core::report_task_error(&format!("failover:{tool_id}"), &e);
+                    }
+                }
+            }
+        });
+    }
+
     /// 启动指定工具的代理
     ///
     /// # 参数
@@ -63,6 +116,12 @@ impl ProxyManager {
 
         // 创建并启动代理实例
         let instance = ProxyInstance::new(tool_id.to_string(), config, processor);
+        self.ensure_failover_worker();
+        let failover_tx = self.failover_tx.clone();
+        let trigger_tool_id = tool_id.to_string();
+        instance.set_profile_failover_trigger(Box::new(move |errors, last_error| {
+            let _ = failover_tx.send((trigger_tool_id.clone(), errors, last_error));
+        }));
         instance
             .start()
             .await
@@ -151,6 +210,66 @@ impl ProxyManager {
         status_map
     }
 
+    /// 排空并重启指定工具的代理（配置沿用当前实例）
+    ///
+    /// 返回是否在超时前排空完毕。
+    pub async fn restart_proxy(&self, tool_id: &str, drain_timeout: Duration) -> Result<bool> {
+        // 先移出实例再排空，避免排空期间长时间持有写锁
+        let instance = self
+            .instances
+            .write()
+            .await
+            .remove(tool_id)
+            .ok_or_else(|| anyhow::anyhow!("{tool_id} 代理未运行"))?;
+        let config = instance.config().await;
+
+        let drained = instance
+            .drain_and_stop(drain_timeout)
+            .await
+            .context(format!("停止 {tool_id} 代理失败"))?;
+        self.start_proxy(tool_id, config).await?;
+
+        tracing::info!(tool_id = %tool_id, drained, "透明代理已重启");
+        Ok(drained)
+    }
+
+    /// 所有代理当前打开的连接总数
+    pub async fn active_connections(&self) -> usize {
+        let instances = self.instances.read().await;
+        instances.values().map(|i| i.active_connections()).sum()
+    }
+
+    /// 指定工具代理的多上游故障转移状态（未运行时为 None）
+    pub async fn failover_status(&self, tool_id: &str) -> Option<FailoverStatus> {
+        let instances = self.instances.read().await;
+        match instances.get(tool_id) {
+            Some(instance) if instance.is_running_async().await => {
+                Some(instance.failover_status().await)
+            }
+            _ => None,
+        }
+    }
+
+    /// 指定工具代理的上游限流排队状态（未运行时为 None）
+    pub async fn queue_status(&self, tool_id: &str) -> Option<UpstreamQueueStatus> {
+        let instances = self.instances.read().await;
+        match instances.get(tool_id) {
+            Some(instance) if instance.is_running_async().await => Some(instance.queue_status()),
+            _ => None,
+        }
+    }
+
+    /// 指定工具代理的并发上游请求状态（未运行时为 None）
+    pub async fn concurrency_status(&self, tool_id: &str) -> Option<ConcurrencyStatus> {
+        let instances = self.instances.read().await;
+        match instances.get(tool_id) {
+            Some(instance) if instance.is_running_async().await => {
+                Some(instance.concurrency_status())
+            }
+            _ => None,
+        }
+    }
+
     /// 更新指定工具的代理配置（无需重启）
     pub async fn update_config(&self, tool_id: &str, config: ToolProxyConfig) -> Result<()> {
         let instances = self.instances.read().await;
@@ -168,6 +287,68 @@ impl ProxyManager {
     }
 }
 
+/// 将运行中的代理切换到备用 Profile，并写回配置文件
+///
+/// 代理已停止、策略已关闭或已在使用备用 Profile 时返回 `None`。
+async fn switch_to_backup_profile(
+    instances: &ProxyInstances,
+    tool_id: &str,
+    consecutive_errors: u32,
+    last_error: Option<String>,
+) -> Result<Option<ProxyFailoverEvent>> {
+    let instances = instances.read().await;
+    let Some(instance) = instances.get(tool_id) else {
+        return Ok(None);
+    };
+    let mut config = instance.config().await;
+    let from_profile = config.real_profile_name.clone();
+    if !config.profile_failover.is_active(from_profile.as_deref()) {
+        return Ok(None);
+    }
+    let backup = config
+        .profile_failover
+        .backup_profile
+        .clone()
+        .unwrap_or_default();
+
+    let (api_key, base_url, pricing_template_id) = ProfileManager::new()?
+        .get_profile_upstream(tool_id, &backup)
+        .with_context(|| format!("读取备用 Profile 失败: {backup}"))?;
+    let apply = |config: &mut ToolProxyConfig| {
+        config.real_api_key = Some(api_key.clone());
+        config.real_base_url = Some(base_url.clone());
+        config.real_profile_name = Some(backup.clone());
+        config.pricing_template_id = pricing_template_id.clone();
+    };
+
+    // 写回配置文件（以文件中的配置为准，只改主上游字段）
+    let config_mgr = ProxyConfigManager::new()?;
+    if let Some(mut stored) = config_mgr.get_config(tool_id)? {
+        apply(&mut stored);
+        config_mgr.update_config(tool_id, stored)?;
+    }
+
+    apply(&mut config);
+    instance.update_config(config).await?;
+
+    tracing::warn!(
+        tool_id = %tool_id,
+        from = ?from_profile,
+        to = %backup,
+        consecutive_errors,
+        last_error = ?last_error,
+        "当前 Profile 上游持续出错，已切换到备用 Profile"
+    );
+    Ok(Some(ProxyFailoverEvent {
+        tool_id: tool_id.to_string(),
+        from_profile,
+        to_profile: backup,
+        consecutive_errors,
+        last_error,
+        switched_at: chrono::Utc::now().timestamp_millis(),
+    }))
+}
+
 impl Default for ProxyManager {
     fn default() -> Self {
         Self::new()
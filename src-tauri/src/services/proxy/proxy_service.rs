@@ -1,3 +1,4 @@
+use super::auth_bridge;
 use crate::GlobalConfig;
 use std::env;
 use url::Url;
@@ -153,9 +154,12 @@ impl ProxyService {
         }
 
         let proxy_type = config.proxy_type.as_deref().unwrap_or("http");
+        // NTLM/Negotiate 经本地认证桥，凭据由桥接程序处理
+        let (scheme, with_credentials) = auth_bridge::proxy_url_scheme(proxy_type);
 
         // 构建认证部分
-        let auth = if let (Some(username), Some(password)) = (
+        let auth = if let (true, Some(username), Some(password)) = (
+            with_credentials,
             config.proxy_username.as_ref(),
             config.proxy_password.as_ref(),
         ) {
@@ -168,13 +172,6 @@ impl ProxyService {
             String::new()
         };
 
-        // 对于 socks5，使用标准 scheme；其他情形使用 http/https
-        let scheme = match proxy_type {
-            "socks5" => "socks5",
-            "https" => "https",
-            _ => "http",
-        };
-
         // 构建完整的代理 URL
         Some(format!("{scheme}://{auth}{host}:{port}"))
     }
@@ -230,8 +227,19 @@ mod tests {
             external_poll_interval_ms: 5000,
             single_instance_enabled: true,
             startup_enabled: false,
+            startup_mode: crate::models::config::StartupMode::default(),
             config_watch: crate::models::config::ConfigWatchConfig::default(),
             token_stats_config: crate::models::config::TokenStatsConfig::default(),
+            tool_config_dirs: std::collections::HashMap::new(),
+            observer_mode: false,
+            maintenance: crate::models::config::MaintenanceConfig::default(),
+            metrics: crate::models::config::MetricsConfig::default(),
+            pricing_sync: crate::models::config::PricingSyncConfig::default(),
+            admin_api: crate::models::config::AdminApiConfig::default(),
+            mcp: crate::models::config::McpConfig::default(),
+            balance_monitor: crate::models::config::BalanceMonitorConfig::default(),
+            crash_report: crate::models::config::CrashReportConfig::default(),
+            extra_paths: Vec::new(),
         };
 
         let url = ProxyService::build_proxy_url(&config);
@@ -261,8 +269,19 @@ mod tests {
             external_poll_interval_ms: 5000,
             single_instance_enabled: true,
             startup_enabled: false,
+            startup_mode: crate::models::config::StartupMode::default(),
             config_watch: crate::models::config::ConfigWatchConfig::default(),
             token_stats_config: crate::models::config::TokenStatsConfig::default(),
+            tool_config_dirs: std::collections::HashMap::new(),
+            observer_mode: false,
+            maintenance: crate::models::config::MaintenanceConfig::default(),
+            metrics: crate::models::config::MetricsConfig::default(),
+            pricing_sync: crate::models::config::PricingSyncConfig::default(),
+            admin_api: crate::models::config::AdminApiConfig::default(),
+            mcp: crate::models::config::McpConfig::default(),
+            balance_monitor: crate::models::config::BalanceMonitorConfig::default(),
+            crash_report: crate::models::config::CrashReportConfig::default(),
+            extra_paths: Vec::new(),
         };
 
         let url = ProxyService::build_proxy_url(&config);
@@ -295,8 +314,19 @@ mod tests {
             external_poll_interval_ms: 5000,
             single_instance_enabled: true,
             startup_enabled: false,
+            startup_mode: crate::models::config::StartupMode::default(),
             config_watch: crate::models::config::ConfigWatchConfig::default(),
             token_stats_config: crate::models::config::TokenStatsConfig::default(),
+            tool_config_dirs: std::collections::HashMap::new(),
+            observer_mode: false,
+            maintenance: crate::models::config::MaintenanceConfig::default(),
+            metrics: crate::models::config::MetricsConfig::default(),
+            pricing_sync: crate::models::config::PricingSyncConfig::default(),
+            admin_api: crate::models::config::AdminApiConfig::default(),
+            mcp: crate::models::config::McpConfig::default(),
+            balance_monitor: crate::models::config::BalanceMonitorConfig::default(),
+            crash_report: crate::models::config::CrashReportConfig::default(),
+            extra_paths: Vec::new(),
         };
 
         let url = ProxyService::build_proxy_url(&config);
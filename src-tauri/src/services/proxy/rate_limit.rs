@@ -0,0 +1,248 @@
+// 透明代理本地限流
+//
+// 按工具（或工具 + 会话）维护滑动 1 分钟窗口：
+// - 请求数：转发前计数，超出上限直接返回 429
+// - Token 数：请求完成写入统计日志时累计（输入 + 输出），窗口内已用量达到上限后拒绝新请求
+//
+// 限流器为进程级单例，统计日志写入路径无需持有代理实例即可回填 Token 用量
+
+use once_cell::sync::Lazy;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::models::proxy_config::RateLimitConfig;
+
+/// 滑动窗口长度
+const WINDOW: Duration = Duration::from_secs(60);
+
+static RATE_LIMITER: Lazy<RateLimiter> = Lazy::new(RateLimiter::default);
+
+/// 被限流时的拒绝信息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimitRejection {
+    /// 拒绝原因（写入日志与响应）
+    pub reason: String,
+    /// 建议的重试等待秒数（Retry-After）
+    pub retry_after_secs: u64,
+}
+
+#[derive(Debug, Default)]
+struct Window {
+    requests: VecDeque<Instant>,
+    tokens: VecDeque<(Instant, u64)>,
+}
+
+impl Window {
+    fn prune(&mut self, now: Instant) {
+        while self
+            .requests
+            .front()
+            .is_some_and(|t| now.duration_since(*t) >= WINDOW)
+        {
+            self.requests.pop_front();
+        }
+        while self
+            .tokens
+            .front()
+            .is_some_and(|(t, _)| now.duration_since(*t) >= WINDOW)
+        {
+            self.tokens.pop_front();
+        }
+    }
+
+    fn token_sum(&self) -> u64 {
+        self.tokens.iter().map(|(_, n)| n).sum()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.requests.is_empty() && self.tokens.is_empty()
+    }
+}
+
+/// 距离 `since` 移出窗口还需等待的秒数（向上取整，至少 1 秒）
+fn retry_after(since: Instant, now: Instant) -> u64 {
+    let remaining = WINDOW.saturating_sub(now.duration_since(since));
+    remaining.as_secs_f64().ceil().max(1.0) as u64
+}
+
+/// 滑动窗口限流器
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    windows: Mutex<HashMap<String, Window>>,
+}
+
+impl RateLimiter {
+    /// 全局限流器
+    pub fn global() -> &'static RateLimiter {
+        &RATE_LIMITER
+    }
+
+    fn scope_key(tool_id: &str, session_id: Option<&str>) -> String {
+        match session_id {
+            Some(session_id) => format!("{tool_id}:{session_id}"),
+            None => tool_id.to_string(),
+        }
+    }
+
+    /// 尝试占用一次请求额度
+    ///
+    /// `session_id` 仅在开启按会话限流时使用；未通过时不计入请求数
+    pub fn try_acquire(
+        &self,
+        tool_id: &str,
+        session_id: Option<&str>,
+        config: &RateLimitConfig,
+    ) -> Result<(), RateLimitRejection> {
+        self.try_acquire_at(tool_id, session_id, config, Instant::now())
+    }
+
+    fn try_acquire_at(
+        &self,
+        tool_id: &str,
+        session_id: Option<&str>,
+        config: &RateLimitConfig,
+        now: Instant,
+    ) -> Result<(), RateLimitRejection> {
+        if !config.is_active() {
+            return Ok(());
+        }
+
+        let session_id = session_id.filter(|_| config.per_session);
+        let key = Self::scope_key(tool_id, session_id);
+        let mut windows = self.windows.lock().unwrap();
+        windows.retain(|_, w| {
+            w.prune(now);
+            !w.is_empty()
+        });
+        let window = windows.entry(key).or_default();
+
+        let rpm = config.requests_per_minute as usize;
+        if rpm > 0 && window.requests.len() >= rpm {
+            // 最早的 (len - rpm + 1) 条请求移出窗口后才有余量
+            let oldest = window.requests[window.requests.len() - rpm];
+            return Err(RateLimitRejection {
+                reason: format!("超出每分钟 {} 次请求限制", config.requests_per_minute),
+                retry_after_secs: retry_after(oldest, now),
+            });
+        }
+
+        let tpm = u64::from(config.tokens_per_minute);
+        let mut used = window.token_sum();
+        if tpm > 0 && used >= tpm {
+            let mut retry_secs = 1;
+            for (at, tokens) in &window.tokens {
+                used -= tokens;
+                if used < tpm {
+                    retry_secs = retry_after(*at, now);
+                    break;
+                }
+            }
+            return Err(RateLimitRejection {
+                reason: format!("超出每分钟 {} Token 限制", config.tokens_per_minute),
+                retry_after_secs: retry_secs,
+            });
+        }
+
+        window.requests.push_back(now);
+        Ok(())
+    }
+
+    /// 回填已完成请求的 Token 用量（同时计入工具与会话窗口）
+    pub fn record_tokens(&self, tool_id: &str, session_id: &str, tokens: u64) {
+        self.record_tokens_at(tool_id, session_id, tokens, Instant::now());
+    }
+
+    fn record_tokens_at(&self, tool_id: &str, session_id: &str, tokens: u64, now: Instant) {
+        if tokens == 0 {
+            return;
+        }
+        let mut windows = self.windows.lock().unwrap();
+        // 只回填已有窗口：未开启限流的工具不会产生窗口
+        for key in [
+            Self::scope_key(tool_id, None),
+            Self::scope_key(tool_id, Some(session_id)),
+        ] {
+            if let Some(window) = windows.get_mut(&key) {
+                window.tokens.push_back((now, tokens));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(rpm: u32, tpm: u32, per_session: bool) -> RateLimitConfig {
+        RateLimitConfig {
+            enabled: true,
+            requests_per_minute: rpm,
+            tokens_per_minute: tpm,
+            per_session,
+        }
+    }
+
+    #[test]
+    fn test_requests_per_minute_with_retry_after() {
+        let limiter = RateLimiter::default();
+        let cfg = config(2, 0, false);
+        let start = Instant::now();
+
+        assert!(limiter.try_acquire_at("codex", None, &cfg, start).is_ok());
+        let second = start + Duration::from_secs(10);
+        assert!(limiter.try_acquire_at("codex", None, &cfg, second).is_ok());
+
+        let rejected = limiter
+            .try_acquire_at("codex", None, &cfg, start + Duration::from_secs(20))
+            .unwrap_err();
+        assert_eq!(rejected.retry_after_secs, 40);
+
+        // 其他工具互不影响；第一条移出窗口后恢复
+        assert!(limiter
+            .try_acquire_at("claude-code", None, &cfg, second)
+            .is_ok());
+        assert!(limiter
+            .try_acquire_at("codex", None, &cfg, start + WINDOW)
+            .is_ok());
+
+        // 未开启时不限制
+        let disabled = RateLimitConfig::default();
+        assert!(limiter
+            .try_acquire_at("codex", None, &disabled, second)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_tokens_per_minute_per_session() {
+        let limiter = RateLimiter::default();
+        let cfg = config(0, 1000, true);
+        let start = Instant::now();
+
+        assert!(limiter
+            .try_acquire_at("claude-code", Some("s1"), &cfg, start)
+            .is_ok());
+        limiter.record_tokens_at("claude-code", "s1", 600, start);
+        let later = start + Duration::from_secs(5);
+        assert!(limiter
+            .try_acquire_at("claude-code", Some("s1"), &cfg, later)
+            .is_ok());
+        limiter.record_tokens_at("claude-code", "s1", 500, later);
+
+        let rejected = limiter
+            .try_acquire_at(
+                "claude-code",
+                Some("s1"),
+                &cfg,
+                start + Duration::from_secs(30),
+            )
+            .unwrap_err();
+        // 第一批 600 Token 移出窗口后用量降到 500
+        assert_eq!(rejected.retry_after_secs, 30);
+
+        // 其他会话使用独立额度
+        assert!(limiter
+            .try_acquire_at("claude-code", Some("s2"), &cfg, later)
+            .is_ok());
+    }
+}
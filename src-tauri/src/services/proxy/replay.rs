@@ -0,0 +1,668 @@
+//! 流量重放压测
+//!
+//! 把请求捕获（`request_logs`）中记录的真实请求按固定速率发送到指定上游，
+//! 统计延迟分布与错误分布，用于在整个团队切换供应商前评估新供应商：
+//!
+//! - 请求直接发往目标上游，不经过透明代理（不写入统计、会话与捕获）
+//! - 捕获中的鉴权头一律丢弃，按工具的鉴权方式以目标上游的 API Key 重新设置
+//! - 按计划速率匀速发出请求，同时进行的请求数受 `max_concurrent` 限制
+//! - 请求体被截断或不属于该工具的捕获无法重放，跳过并在报告中列出
+
+use super::capture_store::{self, CapturedExchange};
+use super::egress;
+use crate::services::proxy_config_manager::ProxyConfigManager;
+use crate::services::session::tags::CWD_HEADER;
+use anyhow::{bail, Context, Result};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tokio::time::MissedTickBehavior;
+
+/// 支持直接重放的工具（AMP Code 的请求需经代理转换，无法直接发往上游）
+const REPLAY_TOOLS: [&str; 3] = ["claude-code", "codex", "gemini-cli"];
+
+/// 请求速率上限（次/秒）
+const MAX_RATE_PER_SEC: f64 = 50.0;
+
+/// 单次计划的请求总数上限
+const MAX_TOTAL_REQUESTS: usize = 1000;
+
+/// 错误样本保留的字符数
+const ERROR_SAMPLE_CHARS: usize = 300;
+
+/// 重放时不透传的请求头（鉴权头按目标上游重新设置，其余由 HTTP 客户端重新生成）
+const SKIPPED_HEADERS: [&str; 10] = [
+    "host",
+    "content-length",
+    "connection",
+    "transfer-encoding",
+    "accept-encoding",
+    "authorization",
+    "proxy-authorization",
+    "x-api-key",
+    "x-goog-api-key",
+    "cookie",
+];
+
+/// 重放计划
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayPlan {
+    pub tool_id: String,
+    /// 要重放的捕获 ID（为空时取该工具最近的捕获）
+    #[serde(default)]
+    pub capture_ids: Vec<String>,
+    /// 未指定捕获 ID 时取最近捕获的数量
+    #[serde(default = "default_capture_limit")]
+    pub capture_limit: usize,
+    /// 目标上游 Base URL
+    pub base_url: String,
+    /// 目标上游 API Key
+    pub api_key: String,
+    /// 每秒发出的请求数
+    #[serde(default = "default_rate_per_sec")]
+    pub rate_per_sec: f64,
+    /// 每条捕获重放的次数
+    #[serde(default = "default_repeat")]
+    pub repeat: u32,
+    /// 同时进行的请求数上限
+    #[serde(default = "default_max_concurrent")]
+    pub max_concurrent: u32,
+    /// 单个请求的超时（秒，含读完响应体）
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_capture_limit() -> usize {
+    20
+}
+
+fn default_rate_per_sec() -> f64 {
+    1.0
+}
+
+fn default_repeat() -> u32 {
+    1
+}
+
+fn default_max_concurrent() -> u32 {
+    4
+}
+
+fn default_timeout_secs() -> u64 {
+    300
+}
+
+impl ReplayPlan {
+    fn validate(&self) -> Result<()> {
+        if !REPLAY_TOOLS.contains(&self.tool_id.as_str()) {
+            bail!("{} 不支持直接重放到上游", self.tool_id);
+        }
+        let base_url = self.base_url.trim();
+        if !base_url.starts_with("http://") && !base_url.starts_with("https://") {
+            bail!("Base URL 必须以 http:// 或 https:// 开头");
+        }
+        if self.api_key.trim().is_empty() {
+            bail!("API Key 不能为空");
+        }
+        if !(self.rate_per_sec > 0.0 && self.rate_per_sec <= MAX_RATE_PER_SEC) {
+            bail!("请求速率必须在 0 ~ {} 次/秒之间", MAX_RATE_PER_SEC);
+        }
+        if self.repeat == 0 || self.max_concurrent == 0 || self.timeout_secs == 0 {
+            bail!("重放次数、并发数与超时必须大于 0");
+        }
+        Ok(())
+    }
+}
+
+/// 重放请求的延迟分布（毫秒）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayLatency {
+    pub min_ms: u64,
+    pub mean_ms: f64,
+    pub p50_ms: u64,
+    pub p90_ms: u64,
+    pub p95_ms: u64,
+    pub p99_ms: u64,
+    pub max_ms: u64,
+}
+
+impl ReplayLatency {
+    /// 按最近秩法计算分位数（无样本时返回 None）
+    fn from_samples(mut samples: Vec<u64>) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        samples.sort_unstable();
+        let percentile = |p: f64| {
+            let rank = (p / 100.0 * samples.len() as f64).ceil() as usize;
+            samples[rank.clamp(1, samples.len()) - 1]
+        };
+        Some(Self {
+            min_ms: samples[0],
+            mean_ms: samples.iter().sum::<u64>() as f64 / samples.len() as f64,
+            p50_ms: percentile(50.0),
+            p90_ms: percentile(90.0),
+            p95_ms: percentile(95.0),
+            p99_ms: percentile(99.0),
+            max_ms: samples[samples.len() - 1],
+        })
+    }
+}
+
+/// 同类错误的统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayErrorBucket {
+    /// 错误类型：`http_<状态码>` / `timeout` / `connect` / `body` / `request`
+    pub kind: String,
+    pub count: usize,
+    /// 首次出现时的错误信息（HTTP 错误为响应体开头）
+    pub sample: String,
+}
+
+/// 未重放的捕获
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplaySkipped {
+    pub capture_id: String,
+    pub reason: String,
+}
+
+/// 重放报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayReport {
+    pub tool_id: String,
+    pub base_url: String,
+    /// 开始时间（Unix 时间戳，毫秒）
+    pub started_at: i64,
+    /// 从发出第一个请求到所有请求结束的时长
+    pub duration_ms: u64,
+    pub total_requests: usize,
+    /// 返回 2xx 且读完响应体的请求数
+    pub succeeded: usize,
+    pub failed: usize,
+    /// 失败比例（0 ~ 1）
+    pub error_rate: f64,
+    /// 实际达到的请求速率（次/秒）
+    pub achieved_rate: f64,
+    /// 收到响应的请求从发出到读完响应体的耗时
+    pub latency: Option<ReplayLatency>,
+    /// 收到响应的请求从发出到收到响应头的耗时（流式响应的首字延迟）
+    pub first_byte_latency: Option<ReplayLatency>,
+    /// 各响应状态码的次数
+    pub status_counts: BTreeMap<u16, usize>,
+    /// 错误分布（次数多的在前）
+    pub errors: Vec<ReplayErrorBucket>,
+    pub skipped: Vec<ReplaySkipped>,
+}
+
+/// 单个请求的结果
+#[derive(Debug)]
+struct ReplayOutcome {
+    status: Option<u16>,
+    first_byte_ms: Option<u64>,
+    total_ms: u64,
+    /// 失败时的 (错误类型, 错误信息)
+    error: Option<(String, String)>,
+}
+
+/// 按计划重放捕获的请求并汇总报告
+pub async fn run_replay(plan: ReplayPlan) -> Result<ReplayReport> {
+    plan.validate()?;
+
+    let ids = if plan.capture_ids.is_empty() {
+        capture_store::list_captures(Some(&plan.tool_id), plan.capture_limit)?
+            .into_iter()
+            .map(|summary| summary.id)
+            .collect()
+    } else {
+        plan.capture_ids.clone()
+    };
+
+    let mut captures = Vec::new();
+    let mut skipped = Vec::new();
+    for id in ids {
+        match capture_store::get_capture(&id) {
+            Ok(capture) if capture.tool_id != plan.tool_id => skipped.push(ReplaySkipped {
+                capture_id: id,
+                reason: format!("捕获属于 {}", capture.tool_id),
+            }),
+            Ok(capture) if capture.request_truncated => skipped.push(ReplaySkipped {
+                capture_id: id,
+                reason: "请求体超出捕获大小上限已被截断".to_string(),
+            }),
+            Ok(capture) => captures.push(Arc::new(capture)),
+            Err(e) => skipped.push(ReplaySkipped {
+                capture_id: id,
+                reason: e.to_string(),
+            }),
+        }
+    }
+    if captures.is_empty() {
+        bail!(
+            "没有可重放的请求捕获（请先为 {} 开启请求体捕获）",
+            plan.tool_id
+        );
+    }
+    let total = captures.len() * plan.repeat as usize;
+    if total > MAX_TOTAL_REQUESTS {
+        bail!(
+            "请求总数 {} 超过上限 {}，请减少捕获数量或重放次数",
+            total,
+            MAX_TOTAL_REQUESTS
+        );
+    }
+
+    // 与代理转发一致，使用工具的出口代理配置
+    let egress_proxy = ProxyConfigManager::new()?
+        .get_config(&plan.tool_id)?
+        .map(|config| config.egress_proxy)
+        .unwrap_or_default();
+    let client = egress::client_builder(&egress_proxy)?
+        .timeout(Duration::from_secs(plan.timeout_secs))
+        .build()
+        .context("创建重放 HTTP 客户端失败")?;
+
+    tracing::info!(
+        tool_id = %plan.tool_id,
+        base_url = %plan.base_url,
+        requests = total,
+        rate = plan.rate_per_sec,
+        "开始重放请求捕获"
+    );
+    let mut report = execute(&client, &plan, &captures).await;
+    report.skipped = skipped;
+    tracing::info!(
+        tool_id = %plan.tool_id,
+        succeeded = report.succeeded,
+        failed = report.failed,
+        duration_ms = report.duration_ms,
+        "请求捕获重放完成"
+    );
+    Ok(report)
+}
+
+/// 按速率与并发上限发出所有请求（每条捕获依次重放 `repeat` 轮）
+async fn execute(
+    client: &reqwest::Client,
+    plan: &ReplayPlan,
+    captures: &[Arc<CapturedExchange>],
+) -> ReplayReport {
+    let started_at = chrono::Utc::now().timestamp_millis();
+    let start = Instant::now();
+    let semaphore = Arc::new(Semaphore::new(plan.max_concurrent as usize));
+    let mut ticker = tokio::time::interval(Duration::from_secs_f64(1.0 / plan.rate_per_sec));
+    // 并发已满导致发送落后时不补发，保持请求间隔
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    let mut tasks = Vec::new();
+    for _ in 0..plan.repeat {
+        for capture in captures {
+            ticker.tick().await;
+            let permit = Arc::clone(&semaphore)
+                .acquire_owned()
+                .await
+                .expect("重放信号量不会被关闭");
+            let request = build_request(
+                client,
+                &plan.tool_id,
+                &plan.base_url,
+                &plan.api_key,
+                capture,
+            );
+            tasks.push(tokio::spawn(async move {
+                let _permit = permit;
+                match request {
+                    Ok(request) => send(request).await,
+                    Err(e) => ReplayOutcome {
+                        status: None,
+                        first_byte_ms: None,
+                        total_ms: 0,
+                        error: Some(("request".to_string(), e.to_string())),
+                    },
+                }
+            }));
+        }
+    }
+
+    let mut outcomes = Vec::with_capacity(tasks.len());
+    for task in tasks {
+        match task.await {
+            Ok(outcome) => outcomes.push(outcome),
+            Err(e) => tracing::warn!(error = ?e, "重放任务异常退出"),
+        }
+    }
+    summarize(plan, started_at, start.elapsed(), outcomes)
+}
+
+/// 发送单个请求并读完响应体
+async fn send(request: reqwest::RequestBuilder) -> ReplayOutcome {
+    let start = Instant::now();
+    let elapsed_ms = || start.elapsed().as_millis() as u64;
+
+    let response = match request.send().await {
+        Ok(response) => response,
+        Err(e) => {
+            return ReplayOutcome {
+                status: None,
+                first_byte_ms: None,
+                total_ms: elapsed_ms(),
+                error: Some((error_kind(&e).to_string(), error_chain(&e))),
+            }
+        }
+    };
+    let status = response.status();
+    let first_byte_ms = elapsed_ms();
+
+    // 流式响应逐块读取，只保留错误样本需要的开头部分
+    let mut head = Vec::new();
+    let mut body = response.bytes_stream();
+    let mut read_error = None;
+    while let Some(chunk) = body.next().await {
+        match chunk {
+            Ok(chunk) if head.len() < ERROR_SAMPLE_CHARS * 4 => head.extend_from_slice(&chunk),
+            Ok(_) => {}
+            Err(e) => {
+                let kind = if e.is_timeout() { "timeout" } else { "body" };
+                read_error = Some((kind.to_string(), error_chain(&e)));
+                break;
+            }
+        }
+    }
+
+    let error = if !status.is_success() {
+        let sample = String::from_utf8_lossy(&head)
+            .chars()
+            .take(ERROR_SAMPLE_CHARS)
+            .collect();
+        Some((format!("http_{}", status.as_u16()), sample))
+    } else {
+        read_error
+    };
+    ReplayOutcome {
+        status: Some(status.as_u16()),
+        first_byte_ms: Some(first_byte_ms),
+        total_ms: elapsed_ms(),
+        error,
+    }
+}
+
+/// 按捕获构建发往目标上游的请求
+fn build_request(
+    client: &reqwest::Client,
+    tool_id: &str,
+    base_url: &str,
+    api_key: &str,
+    capture: &CapturedExchange,
+) -> Result<reqwest::RequestBuilder> {
+    let method = reqwest::Method::from_bytes(capture.method.as_bytes())
+        .with_context(|| format!("无效的请求方法: {}", capture.method))?;
+    let url = target_url(tool_id, base_url, &capture.path, capture.query.as_deref());
+
+    let mut builder = client.request(method, url);
+    for (name, value) in &capture.request_headers {
+        if SKIPPED_HEADERS.contains(&name.as_str()) || name.eq_ignore_ascii_case(CWD_HEADER) {
+            continue;
+        }
+        builder = builder.header(name, value);
+    }
+    // 与各工具请求处理器的鉴权方式一致
+    builder = match tool_id {
+        "gemini-cli" => builder.header("x-goog-api-key", api_key.trim()),
+        _ => builder.header("authorization", format!("Bearer {}", api_key.trim())),
+    };
+    if !capture.request_body.is_empty() {
+        builder = builder.body(capture.request_body.clone());
+    }
+    Ok(builder)
+}
+
+/// 拼接目标 URL（Codex 的 Base URL 以 /v1 结尾时避免路径重复）
+fn target_url(tool_id: &str, base_url: &str, path: &str, query: Option<&str>) -> String {
+    let base = base_url.trim().trim_end_matches('/');
+    let path = match path.strip_prefix("/v1") {
+        Some(rest) if tool_id == "codex" && base.ends_with("/v1") => rest,
+        _ => path,
+    };
+    match query {
+        Some(query) => format!("{base}{path}?{query}"),
+        None => format!("{base}{path}"),
+    }
+}
+
+fn error_kind(error: &reqwest::Error) -> &'static str {
+    if error.is_timeout() {
+        "timeout"
+    } else if error.is_connect() {
+        "connect"
+    } else {
+        "request"
+    }
+}
+
+/// 展开完整错误链（reqwest 的 source chain 包含底层原因如 DNS/TLS 等）
+fn error_chain(error: &dyn std::error::Error) -> String {
+    let mut msg = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        msg.push_str(&format!(" → {}", cause));
+        source = cause.source();
+    }
+    msg
+}
+
+fn summarize(
+    plan: &ReplayPlan,
+    started_at: i64,
+    elapsed: Duration,
+    outcomes: Vec<ReplayOutcome>,
+) -> ReplayReport {
+    let mut status_counts = BTreeMap::new();
+    let mut buckets: BTreeMap<String, ReplayErrorBucket> = BTreeMap::new();
+    let mut latencies = Vec::new();
+    let mut first_bytes = Vec::new();
+
+    for outcome in &outcomes {
+        if let Some(status) = outcome.status {
+            *status_counts.entry(status).or_insert(0) += 1;
+            latencies.push(outcome.total_ms);
+        }
+        if let Some(first_byte_ms) = outcome.first_byte_ms {
+            first_bytes.push(first_byte_ms);
+        }
+        if let Some((kind, detail)) = &outcome.error {
+            buckets
+                .entry(kind.clone())
+                .or_insert_with(|| ReplayErrorBucket {
+                    kind: kind.clone(),
+                    count: 0,
+                    sample: detail.clone(),
+                })
+                .count += 1;
+        }
+    }
+
+    let total = outcomes.len();
+    let failed = outcomes.iter().filter(|o| o.error.is_some()).count();
+    let mut errors: Vec<ReplayErrorBucket> = buckets.into_values().collect();
+    errors.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.kind.cmp(&b.kind)));
+    let secs = elapsed.as_secs_f64();
+
+    ReplayReport {
+        tool_id: plan.tool_id.clone(),
+        base_url: plan.base_url.clone(),
+        started_at,
+        duration_ms: elapsed.as_millis() as u64,
+        total_requests: total,
+        succeeded: total - failed,
+        failed,
+        error_rate: if total > 0 {
+            failed as f64 / total as f64
+        } else {
+            0.0
+        },
+        achieved_rate: if secs > 0.0 { total as f64 / secs } else { 0.0 },
+        latency: ReplayLatency::from_samples(latencies),
+        first_byte_latency: ReplayLatency::from_samples(first_bytes),
+        status_counts,
+        errors,
+        skipped: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn plan(base_url: &str) -> ReplayPlan {
+        ReplayPlan {
+            tool_id: "claude-code".to_string(),
+            capture_ids: Vec::new(),
+            capture_limit: default_capture_limit(),
+            base_url: base_url.to_string(),
+            api_key: "sk-target".to_string(),
+            rate_per_sec: MAX_RATE_PER_SEC,
+            repeat: 2,
+            max_concurrent: 2,
+            timeout_secs: 5,
+        }
+    }
+
+    fn capture(path: &str) -> CapturedExchange {
+        CapturedExchange {
+            id: path.to_string(),
+            tool_id: "claude-code".to_string(),
+            captured_at: 0,
+            expires_at: i64::MAX,
+            method: "POST".to_string(),
+            path: path.to_string(),
+            query: Some("beta=true".to_string()),
+            request_headers: BTreeMap::from([
+                ("authorization".to_string(), "[REDACTED]".to_string()),
+                ("anthropic-version".to_string(), "2023-06-01".to_string()),
+                (CWD_HEADER.to_string(), "/home/dev/project".to_string()),
+            ]),
+            request_body: "{\"model\":\"claude-sonnet-4-5\"}".to_string(),
+            request_truncated: false,
+            response_status: 200,
+            response_body: String::new(),
+            response_truncated: false,
+            is_sse: false,
+        }
+    }
+
+    /// 读取完整请求（请求头 + Content-Length 指定的请求体）
+    async fn read_request(stream: &mut tokio::net::TcpStream) -> String {
+        let mut data = Vec::new();
+        let mut buf = [0u8; 1024];
+        loop {
+            let n = stream.read(&mut buf).await.unwrap();
+            data.extend_from_slice(&buf[..n]);
+            let text = String::from_utf8_lossy(&data).to_ascii_lowercase();
+            if let Some(end) = text.find("\r\n\r\n") {
+                let length = text
+                    .lines()
+                    .find_map(|line| line.strip_prefix("content-length: "))
+                    .and_then(|v| v.trim().parse::<usize>().ok())
+                    .unwrap_or(0);
+                if n == 0 || data.len() >= end + 4 + length {
+                    return text;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_latency_percentiles() {
+        let stats = ReplayLatency::from_samples((1..=100).rev().collect()).unwrap();
+        assert_eq!(stats.min_ms, 1);
+        assert_eq!(stats.p50_ms, 50);
+        assert_eq!(stats.p90_ms, 90);
+        assert_eq!(stats.p99_ms, 99);
+        assert_eq!(stats.max_ms, 100);
+        assert_eq!(stats.mean_ms, 50.5);
+
+        let single = ReplayLatency::from_samples(vec![7]).unwrap();
+        assert_eq!((single.p50_ms, single.p99_ms), (7, 7));
+        assert!(ReplayLatency::from_samples(Vec::new()).is_none());
+    }
+
+    #[test]
+    fn test_target_url_and_validation() {
+        assert_eq!(
+            target_url(
+                "codex",
+                "https://api.example.com/v1/",
+                "/v1/responses",
+                None
+            ),
+            "https://api.example.com/v1/responses"
+        );
+        assert_eq!(
+            target_url(
+                "claude-code",
+                "https://api.example.com/v1",
+                "/v1/messages",
+                Some("beta=true")
+            ),
+            "https://api.example.com/v1/v1/messages?beta=true"
+        );
+
+        assert!(plan("https://api.example.com").validate().is_ok());
+        assert!(plan("api.example.com").validate().is_err());
+        let too_fast = ReplayPlan {
+            rate_per_sec: MAX_RATE_PER_SEC + 1.0,
+            ..plan("https://api.example.com")
+        };
+        assert!(too_fast.validate().is_err());
+        let amp = ReplayPlan {
+            tool_id: "amp-code".to_string(),
+            ..plan("https://api.example.com")
+        };
+        assert!(amp.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_replay_reports_status_and_error_distribution() {
+        // 上游：/v1/messages 返回 200，其余路径返回 429；校验鉴权头已替换
+        let upstream = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = upstream.local_addr().unwrap().port();
+        let upstream_task = tokio::spawn(async move {
+            for _ in 0..4 {
+                let (mut stream, _) = upstream.accept().await.unwrap();
+                let request = read_request(&mut stream).await;
+                assert!(request.contains("authorization: bearer sk-target"));
+                assert!(request.contains("anthropic-version: 2023-06-01"));
+                assert!(!request.contains(CWD_HEADER));
+                let response: &[u8] = if request.starts_with("post /v1/messages?beta=true ") {
+                    b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok"
+                } else {
+                    b"HTTP/1.1 429 Too Many Requests\r\nContent-Length: 4\r\nConnection: close\r\n\r\nslow"
+                };
+                stream.write_all(response).await.unwrap();
+            }
+        });
+
+        let plan = plan(&format!("http://127.0.0.1:{port}"));
+        let captures = vec![
+            Arc::new(capture("/v1/messages")),
+            Arc::new(capture("/v1/messages/count_tokens")),
+        ];
+        let report = execute(&reqwest::Client::new(), &plan, &captures).await;
+        upstream_task.await.unwrap();
+
+        assert_eq!(report.total_requests, 4);
+        assert_eq!(report.succeeded, 2);
+        assert_eq!(report.failed, 2);
+        assert_eq!(report.error_rate, 0.5);
+        assert_eq!(report.status_counts, BTreeMap::from([(200, 2), (429, 2)]));
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].kind, "http_429");
+        assert_eq!(report.errors[0].count, 2);
+        assert_eq!(report.errors[0].sample, "slow");
+        assert!(report.latency.is_some());
+    }
+}
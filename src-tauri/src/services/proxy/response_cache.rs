@@ -0,0 +1,370 @@
+// 透明代理响应缓存
+//
+// 对幂等接口的成功响应做进程级缓存（按工具开启，默认关闭）：
+// - GET 模型列表 / 模型详情（`/v1/models`、Gemini `/v1beta/models` 等）
+// - 可选：请求体完全相同的 Embedding 请求（`/v1/embeddings`、Gemini `:embedContent`）
+// - 缓存键包含请求方法、路径、查询、请求体摘要以及上游地址与 API Key 摘要，不同账号互不共享
+// - 仅缓存 2xx 非流式响应；条目超过有效期后失效，超出条数上限时淘汰最早写入的条目
+//
+// 命中次数与未命中次数随 Prometheus 指标导出，代理重启不会清空缓存
+
+use bytes::Bytes;
+use hyper::{Method, Response, StatusCode};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::utils::body::{box_body, BoxBody};
+use crate::models::proxy_config::{ResponseCacheConfig, ToolProxyConfig};
+
+/// 命中缓存时附加的响应头
+pub const CACHE_HEADER: &str = "x-duckcoding-cache";
+
+static RESPONSE_CACHE: Lazy<ResponseCache> = Lazy::new(ResponseCache::default);
+
+/// 缓存的响应
+#[derive(Debug, Clone)]
+pub struct CachedResponse {
+    pub status: u16,
+    pub content_type: Option<String>,
+    pub body: Bytes,
+}
+
+impl CachedResponse {
+    /// 构建返回给客户端的响应
+    pub fn into_response(self) -> Response<BoxBody> {
+        let mut response = Response::builder()
+            .status(StatusCode::from_u16(self.status).unwrap_or(StatusCode::OK))
+            .header(CACHE_HEADER, "hit");
+        if let Some(content_type) = &self.content_type {
+            response = response.header("content-type", content_type);
+        }
+        response
+            .body(box_body(http_body_util::Full::new(self.body)))
+            .unwrap()
+    }
+}
+
+#[derive(Debug)]
+struct Entry {
+    response: CachedResponse,
+    stored_at: Instant,
+}
+
+#[derive(Debug, Default)]
+struct ToolCache {
+    entries: HashMap<String, Entry>,
+    /// 写入顺序（用于淘汰最早的条目）
+    order: VecDeque<String>,
+    hits: u64,
+    misses: u64,
+}
+
+/// 单个工具的缓存统计
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct ResponseCacheStats {
+    pub tool_id: String,
+    /// 当前缓存条目数（含已过期但尚未清理的条目）
+    pub entries: usize,
+    /// 缓存响应体总字节数
+    pub total_bytes: u64,
+    /// 启动以来的命中次数
+    pub hits: u64,
+    /// 启动以来的未命中次数
+    pub misses: u64,
+}
+
+/// 进程级响应缓存
+#[derive(Debug, Default)]
+pub struct ResponseCache {
+    tools: Mutex<BTreeMap<String, ToolCache>>,
+}
+
+/// 计算请求的缓存键（请求不可缓存时返回 None）
+pub fn cache_key(
+    config: &ToolProxyConfig,
+    method: &Method,
+    path: &str,
+    query: Option<&str>,
+    body: &[u8],
+) -> Option<String> {
+    if !is_cacheable(&config.response_cache, method, path) {
+        return None;
+    }
+    let digest = Sha256::new()
+        .chain_update(config.real_base_url.as_deref().unwrap_or_default())
+        .chain_update([0])
+        .chain_update(config.real_api_key.as_deref().unwrap_or_default())
+        .chain_update([0])
+        .chain_update(body)
+        .finalize();
+    let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+    Some(format!(
+        "{} {}?{} {}",
+        method,
+        path,
+        query.unwrap_or_default(),
+        hex
+    ))
+}
+
+/// 请求是否属于可缓存的幂等接口
+fn is_cacheable(config: &ResponseCacheConfig, method: &Method, path: &str) -> bool {
+    if !config.enabled || config.ttl_secs == 0 || config.max_entries == 0 {
+        return false;
+    }
+    let path = path.trim_end_matches('/');
+    if *method == Method::GET {
+        // 模型列表或单个模型详情（Gemini 的 `models/xxx:method` 为调用接口，不缓存）
+        let mut segments = path.rsplit('/');
+        let last = segments.next().unwrap_or_default();
+        let parent = segments.next().unwrap_or_default();
+        return !last.contains(':') && (last == "models" || parent == "models");
+    }
+    *method == Method::POST
+        && config.cache_embeddings
+        && (path.ends_with("/embeddings")
+            || path.ends_with(":embedContent")
+            || path.ends_with(":batchEmbedContents"))
+}
+
+impl ResponseCache {
+    /// 全局响应缓存
+    pub fn global() -> &'static ResponseCache {
+        &RESPONSE_CACHE
+    }
+
+    /// 查找未过期的缓存响应（同时累计命中 / 未命中次数）
+    pub fn get(&self, tool_id: &str, key: &str, ttl: Duration) -> Option<CachedResponse> {
+        self.get_at(tool_id, key, ttl, Instant::now())
+    }
+
+    fn get_at(
+        &self,
+        tool_id: &str,
+        key: &str,
+        ttl: Duration,
+        now: Instant,
+    ) -> Option<CachedResponse> {
+        let mut tools = self.tools.lock().unwrap();
+        let cache = tools.entry(tool_id.to_string()).or_default();
+        let fresh = cache
+            .entries
+            .get(key)
+            .filter(|entry| now.duration_since(entry.stored_at) < ttl)
+            .map(|entry| entry.response.clone());
+        match fresh {
+            Some(response) => {
+                cache.hits += 1;
+                Some(response)
+            }
+            None => {
+                cache.misses += 1;
+                if cache.entries.remove(key).is_some() {
+                    cache.order.retain(|k| k != key);
+                }
+                None
+            }
+        }
+    }
+
+    /// 写入缓存，超出条数上限时淘汰最早写入的条目
+    pub fn put(&self, tool_id: &str, key: String, response: CachedResponse, max_entries: u32) {
+        self.put_at(tool_id, key, response, max_entries, Instant::now());
+    }
+
+    fn put_at(
+        &self,
+        tool_id: &str,
+        key: String,
+        response: CachedResponse,
+        max_entries: u32,
+        now: Instant,
+    ) {
+        let mut tools = self.tools.lock().unwrap();
+        let cache = tools.entry(tool_id.to_string()).or_default();
+        if cache.entries.contains_key(&key) {
+            cache.order.retain(|k| *k != key);
+        }
+        cache.order.push_back(key.clone());
+        cache.entries.insert(
+            key,
+            Entry {
+                response,
+                stored_at: now,
+            },
+        );
+        while cache.entries.len() > max_entries as usize {
+            let Some(oldest) = cache.order.pop_front() else {
+                break;
+            };
+            cache.entries.remove(&oldest);
+        }
+    }
+
+    /// 清空缓存（tool_id 为空时清空全部工具），返回删除的条目数
+    pub fn clear(&self, tool_id: Option<&str>) -> usize {
+        let mut tools = self.tools.lock().unwrap();
+        let mut removed = 0;
+        for (id, cache) in tools.iter_mut() {
+            if tool_id.is_none_or(|t| t == id) {
+                removed += cache.entries.len();
+                cache.entries.clear();
+                cache.order.clear();
+            }
+        }
+        removed
+    }
+
+    /// 各工具的缓存统计（按工具 ID 排序）
+    pub fn stats(&self) -> Vec<ResponseCacheStats> {
+        let tools = self.tools.lock().unwrap();
+        tools
+            .iter()
+            .map(|(tool_id, cache)| ResponseCacheStats {
+                tool_id: tool_id.clone(),
+                entries: cache.entries.len(),
+                total_bytes: cache
+                    .entries
+                    .values()
+                    .map(|e| e.response.body.len() as u64)
+                    .sum(),
+                hits: cache.hits,
+                misses: cache.misses,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(cache_embeddings: bool) -> ToolProxyConfig {
+        let mut config = ToolProxyConfig::new(8787);
+        config.real_base_url = Some("https://api.example.com".to_string());
+        config.real_api_key = Some("sk-a".to_string());
+        config.response_cache = ResponseCacheConfig {
+            enabled: true,
+            cache_embeddings,
+            ..Default::default()
+        };
+        config
+    }
+
+    fn response(body: &'static str) -> CachedResponse {
+        CachedResponse {
+            status: 200,
+            content_type: Some("application/json".to_string()),
+            body: Bytes::from_static(body.as_bytes()),
+        }
+    }
+
+    #[test]
+    fn test_cacheable_requests() {
+        let cfg = config(false);
+        assert!(cache_key(&cfg, &Method::GET, "/v1/models", None, b"").is_some());
+        assert!(cache_key(&cfg, &Method::GET, "/v1/models/claude-x/", None, b"").is_some());
+        assert!(cache_key(
+            &cfg,
+            &Method::GET,
+            "/v1beta/models",
+            Some("pageSize=50"),
+            b""
+        )
+        .is_some());
+        assert!(cache_key(&cfg, &Method::POST, "/v1/messages", None, b"{}").is_none());
+        assert!(cache_key(&cfg, &Method::POST, "/v1/embeddings", None, b"{}").is_none());
+        assert!(cache_key(
+            &cfg,
+            &Method::GET,
+            "/v1beta/models/gemini:countTokens",
+            None,
+            b""
+        )
+        .is_none());
+        assert!(cache_key(
+            &ToolProxyConfig::new(8787),
+            &Method::GET,
+            "/v1/models",
+            None,
+            b""
+        )
+        .is_none());
+
+        let cfg = config(true);
+        let a = cache_key(
+            &cfg,
+            &Method::POST,
+            "/v1/embeddings",
+            None,
+            b"{\"input\":\"a\"}",
+        );
+        let b = cache_key(
+            &cfg,
+            &Method::POST,
+            "/v1/embeddings",
+            None,
+            b"{\"input\":\"b\"}",
+        );
+        assert!(a.is_some() && b.is_some() && a != b);
+        assert!(cache_key(
+            &cfg,
+            &Method::POST,
+            "/v1beta/models/text-embedding-004:embedContent",
+            None,
+            b"{}"
+        )
+        .is_some());
+
+        // 不同 API Key 不共享缓存
+        let mut other = config(true);
+        other.real_api_key = Some("sk-b".to_string());
+        assert_ne!(
+            cache_key(&cfg, &Method::GET, "/v1/models", None, b""),
+            cache_key(&other, &Method::GET, "/v1/models", None, b"")
+        );
+    }
+
+    #[test]
+    fn test_ttl_and_eviction() {
+        let cache = ResponseCache::default();
+        let ttl = Duration::from_secs(60);
+        let t0 = Instant::now();
+
+        assert!(cache.get_at("codex", "a", ttl, t0).is_none());
+        cache.put_at("codex", "a".to_string(), response("A"), 2, t0);
+        cache.put_at("codex", "b".to_string(), response("BB"), 2, t0);
+        assert_eq!(cache.get_at("codex", "a", ttl, t0).unwrap().body, "A");
+
+        // 超出上限时淘汰最早写入的条目
+        cache.put_at("codex", "c".to_string(), response("C"), 2, t0);
+        assert!(cache.get_at("codex", "a", ttl, t0).is_none());
+        assert!(cache.get_at("codex", "b", ttl, t0).is_some());
+
+        // 过期条目视为未命中并移除
+        assert!(cache
+            .get_at("codex", "c", ttl, t0 + Duration::from_secs(61))
+            .is_none());
+
+        let stats = cache.stats();
+        assert_eq!(
+            stats,
+            vec![ResponseCacheStats {
+                tool_id: "codex".to_string(),
+                entries: 1,
+                total_bytes: 2,
+                hits: 2,
+                misses: 3,
+            }]
+        );
+
+        assert_eq!(cache.clear(Some("claude-code")), 0);
+        assert_eq!(cache.clear(None), 1);
+        assert_eq!(cache.stats()[0].entries, 0);
+    }
+}
@@ -0,0 +1,418 @@
+//! 供应商试用模式
+//!
+//! 在不改动全局配置的前提下试用新的供应商：为试用创建临时代理实例（随机空闲端口），
+//! 只把指定项目的项目级配置指向该实例，到期后自动停止实例并还原项目配置。
+//!
+//! - Claude Code: `<项目>/.claude/settings.local.json` 的 `env`
+//! - Gemini CLI: `<项目>/.gemini/.env`
+//!
+//! Codex 没有项目级配置，不支持试用。试用记录持久化在 `~/.duckcoding/trials.json`，
+//! 应用异常退出后由启动流程还原遗留的项目配置。
+
+use super::headers::create_request_processor;
+use super::proxy_instance::ProxyInstance;
+use crate::data::DataManager;
+use crate::models::proxy_config::ToolProxyConfig;
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tokio::sync::RwLock;
+
+/// 试用时长上限（分钟）
+pub const MAX_TRIAL_MINUTES: u32 = 24 * 60;
+
+/// 正在运行的试用代理实例（试用 ID -> 实例）
+static TRIAL_INSTANCES: Lazy<RwLock<HashMap<String, ProxyInstance>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// 发起试用的参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrialRequest {
+    pub tool_id: String,
+    /// 通过试用供应商访问的项目目录
+    pub project_path: String,
+    pub base_url: String,
+    pub api_key: String,
+    /// 试用名称（用于统计中区分，默认取 Base URL 的主机名）
+    pub name: Option<String>,
+    pub duration_minutes: u32,
+    pub pricing_template_id: Option<String>,
+}
+
+/// 试用会话
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrialSession {
+    pub id: String,
+    pub name: String,
+    pub tool_id: String,
+    pub project_path: String,
+    /// 被改写的项目级配置文件
+    pub config_path: String,
+    pub base_url: String,
+    /// 临时代理端口
+    pub port: u16,
+    pub started_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    /// 项目配置文件的原始内容（试用前不存在时为 None）
+    #[serde(default)]
+    pub original_content: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TrialStore {
+    #[serde(default)]
+    trials: Vec<TrialSession>,
+}
+
+fn default_store_path() -> Result<PathBuf> {
+    let home_dir = dirs::home_dir().ok_or_else(|| anyhow!("无法获取用户主目录"))?;
+    Ok(home_dir.join(".duckcoding").join("trials.json"))
+}
+
+fn load_store(path: &Path) -> Result<TrialStore> {
+    if !path.exists() {
+        return Ok(TrialStore::default());
+    }
+    let value = DataManager::new()
+        .json_uncached()
+        .read(path)
+        .context("读取 trials.json 失败")?;
+    serde_json::from_value(value).context("解析 trials.json 失败")
+}
+
+fn save_store(path: &Path, store: &TrialStore) -> Result<()> {
+    let value = serde_json::to_value(store)?;
+    DataManager::new()
+        .json_uncached()
+        .write(path, &value)
+        .map_err(Into::into)
+}
+
+/// 工具对应的项目级配置文件
+fn project_config_path(tool_id: &str, project: &Path) -> Result<PathBuf> {
+    match tool_id {
+        "claude-code" => Ok(project.join(".claude").join("settings.local.json")),
+        "gemini-cli" => Ok(project.join(".gemini").join(".env")),
+        "codex" => bail!("Codex 不支持项目级配置，无法按项目试用"),
+        _ => bail!("不支持试用的工具: {}", tool_id),
+    }
+}
+
+/// 将项目级配置指向试用代理，返回原始内容
+fn apply_project_route(
+    tool_id: &str,
+    config_path: &Path,
+    proxy_url: &str,
+    local_key: &str,
+) -> Result<Option<String>> {
+    let original = if config_path.exists() {
+        Some(
+            std::fs::read_to_string(config_path)
+                .with_context(|| format!("读取 {} 失败", config_path.display()))?,
+        )
+    } else {
+        None
+    };
+
+    let manager = DataManager::new();
+    match tool_id {
+        "claude-code" => {
+            let mut settings = if original.is_some() {
+                manager.json_uncached().read(config_path)?
+            } else {
+                Value::Object(Map::new())
+            };
+            let root = settings
+                .as_object_mut()
+                .ok_or_else(|| anyhow!("{} 不是 JSON 对象", config_path.display()))?;
+            let env = root
+                .entry("env")
+                .or_insert_with(|| Value::Object(Map::new()));
+            let env = env
+                .as_object_mut()
+                .ok_or_else(|| anyhow!("{} 中的 env 不是对象", config_path.display()))?;
+            env.insert("ANTHROPIC_BASE_URL".into(), Value::String(proxy_url.into()));
+            env.insert(
+                "ANTHROPIC_AUTH_TOKEN".into(),
+                Value::String(local_key.into()),
+            );
+            manager.json_uncached().write(config_path, &settings)?;
+        }
+        _ => {
+            let env = manager.env();
+            env.set(config_path, "GOOGLE_GEMINI_BASE_URL", proxy_url)?;
+            env.set(config_path, "GEMINI_API_KEY", local_key)?;
+        }
+    }
+    Ok(original)
+}
+
+/// 还原项目级配置：写回原始内容，试用前不存在的文件直接删除
+fn restore_project_route(session: &TrialSession) -> Result<()> {
+    let path = Path::new(&session.config_path);
+    crate::data::guard::ensure_writable(path)?;
+    match &session.original_content {
+        Some(content) => std::fs::write(path, content)
+            .with_context(|| format!("还原 {} 失败", path.display()))?,
+        None if path.exists() => {
+            std::fs::remove_file(path).with_context(|| format!("删除 {} 失败", path.display()))?
+        }
+        None => {}
+    }
+    Ok(())
+}
+
+/// 申请一个本机空闲端口
+fn ephemeral_port() -> Result<u16> {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").context("申请临时端口失败")?;
+    Ok(listener.local_addr()?.port())
+}
+
+fn validate(request: &TrialRequest) -> Result<()> {
+    if request.base_url.trim().is_empty() || request.api_key.trim().is_empty() {
+        bail!("试用需要填写 Base URL 和 API Key");
+    }
+    url::Url::parse(request.base_url.trim()).context("Base URL 格式不正确")?;
+    if !(1..=MAX_TRIAL_MINUTES).contains(&request.duration_minutes) {
+        bail!("试用时长需在 1 到 {} 分钟之间", MAX_TRIAL_MINUTES);
+    }
+    if !Path::new(&request.project_path).is_dir() {
+        bail!("项目目录不存在: {}", request.project_path);
+    }
+    Ok(())
+}
+
+/// 试用名称：未指定时取 Base URL 的主机名
+fn trial_name(request: &TrialRequest) -> String {
+    request
+        .name
+        .as_deref()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(String::from)
+        .or_else(|| {
+            url::Url::parse(request.base_url.trim())
+                .ok()
+                .and_then(|url| url.host_str().map(String::from))
+        })
+        .unwrap_or_else(|| "trial".to_string())
+}
+
+/// 开始试用：启动临时代理、改写项目配置，并在到期后自动清理
+pub async fn start_trial(request: TrialRequest) -> Result<TrialSession> {
+    validate(&request)?;
+    let store_path = default_store_path()?;
+    let config_path = project_config_path(&request.tool_id, Path::new(&request.project_path))?;
+
+    let mut store = load_store(&store_path)?;
+    if let Some(existing) = store
+        .trials
+        .iter()
+        .find(|t| t.config_path == config_path.to_string_lossy())
+    {
+        bail!("该项目已有进行中的试用（{}），请先结束", existing.name);
+    }
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let name = trial_name(&request);
+    let port = ephemeral_port()?;
+    let local_key = format!("dc-trial-{}", uuid::Uuid::new_v4().simple());
+
+    let mut config = ToolProxyConfig::new(port);
+    config.enabled = true;
+    config.local_api_key = Some(local_key.clone());
+    config.real_api_key = Some(request.api_key.trim().to_string());
+    config.real_base_url = Some(request.base_url.trim().to_string());
+    config.real_profile_name = Some(format!("trial:{name}"));
+    config.pricing_template_id = request.pricing_template_id.clone();
+
+    let processor = create_request_processor(&request.tool_id).context("创建请求处理器失败")?;
+    let instance = ProxyInstance::new(request.tool_id.clone(), config, processor);
+    instance.start().await.context("启动试用代理失败")?;
+
+    let proxy_url = format!("http://127.0.0.1:{port}");
+    let original_content =
+        match apply_project_route(&request.tool_id, &config_path, &proxy_url, &local_key) {
+            Ok(original) => original,
+            Err(e) => {
+                let _ = instance.stop().await;
+                return Err(e.context("写入项目配置失败"));
+            }
+        };
+
+    let started_at = Utc::now();
+    let session = TrialSession {
+        id: id.clone(),
+        name,
+        tool_id: request.tool_id,
+        project_path: request.project_path,
+        config_path: config_path.to_string_lossy().to_string(),
+        base_url: request.base_url.trim().to_string(),
+        port,
+        started_at,
+        expires_at: started_at + Duration::minutes(request.duration_minutes as i64),
+        original_content,
+    };
+    store.trials.push(session.clone());
+    if let Err(e) = save_store(&store_path, &store) {
+        let _ = restore_project_route(&session);
+        let _ = instance.stop().await;
+        return Err(e.context("保存试用记录失败"));
+    }
+    TRIAL_INSTANCES.write().await.insert(id.clone(), instance);
+
+    tracing::info!(
+        trial_id = %id,
+        tool_id = %session.tool_id,
+        project = %session.project_path,
+        port = port,
+        expires_at = %session.expires_at,
+        "供应商试用已开始"
+    );
+
+    let wait = (session.expires_at - Utc::now())
+        .to_std()
+        .unwrap_or_default();
+    tokio::spawn(async move {
+        tokio::time::sleep(wait).await;
+        // 用户可能已提前结束试用，此时记录已不存在
+        if let Err(e) = stop_trial(&id).await {
+            tracing::debug!(trial_id = %id, error = ?e, "试用到期清理跳过");
+        }
+    });
+
+    Ok(session)
+}
+
+/// 列出进行中的试用
+pub fn list_trials() -> Result<Vec<TrialSession>> {
+    Ok(load_store(&default_store_path()?)?.trials)
+}
+
+/// 结束试用：停止临时代理并还原项目配置
+pub async fn stop_trial(id: &str) -> Result<()> {
+    let store_path = default_store_path()?;
+    let mut store = load_store(&store_path)?;
+    let index = store
+        .trials
+        .iter()
+        .position(|t| t.id == id)
+        .ok_or_else(|| anyhow!("未找到试用: {}", id))?;
+
+    if let Some(instance) = TRIAL_INSTANCES.write().await.remove(id) {
+        instance.stop().await.context("停止试用代理失败")?;
+    }
+    let session = store.trials.remove(index);
+    restore_project_route(&session)?;
+    save_store(&store_path, &store)?;
+
+    tracing::info!(trial_id = %id, project = %session.project_path, "供应商试用已结束");
+    Ok(())
+}
+
+/// 还原所有试用的项目配置并清空记录
+///
+/// 启动时调用以清理异常退出遗留的试用；应用退出时调用以避免项目配置指向已关闭的端口。
+pub fn cleanup_all_trials() {
+    let Ok(store_path) = default_store_path() else {
+        return;
+    };
+    if let Err(e) = cleanup_store(&store_path) {
+        tracing::warn!(error = ?e, "清理供应商试用失败");
+    }
+}
+
+fn cleanup_store(store_path: &Path) -> Result<()> {
+    let store = load_store(store_path)?;
+    if store.trials.is_empty() {
+        return Ok(());
+    }
+    let mut remaining = Vec::new();
+    for session in store.trials {
+        match restore_project_route(&session) {
+            Ok(()) => tracing::info!(
+                trial_id = %session.id,
+                project = %session.project_path,
+                "已还原试用项目配置"
+            ),
+            Err(e) => {
+                tracing::warn!(trial_id = %session.id, error = ?e, "还原试用项目配置失败");
+                remaining.push(session);
+            }
+        }
+    }
+    save_store(store_path, &TrialStore { trials: remaining })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(config_path: &Path, original_content: Option<String>) -> TrialSession {
+        TrialSession {
+            id: "t1".to_string(),
+            name: "example".to_string(),
+            tool_id: "claude-code".to_string(),
+            project_path: String::new(),
+            config_path: config_path.to_string_lossy().to_string(),
+            base_url: "https://api.example.com".to_string(),
+            port: 1,
+            started_at: Utc::now(),
+            expires_at: Utc::now(),
+            original_content,
+        }
+    }
+
+    #[test]
+    fn test_claude_route_apply_and_restore() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = project_config_path("claude-code", dir.path()).unwrap();
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let original = r#"{"permissions":{"allow":["Bash"]},"env":{"FOO":"1"}}"#;
+        std::fs::write(&path, original).unwrap();
+
+        let backup =
+            apply_project_route("claude-code", &path, "http://127.0.0.1:9000", "dc-trial-x")
+                .unwrap();
+        assert_eq!(backup.as_deref(), Some(original));
+        let value: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(value["env"]["ANTHROPIC_BASE_URL"], "http://127.0.0.1:9000");
+        assert_eq!(value["env"]["FOO"], "1");
+        assert_eq!(value["permissions"]["allow"][0], "Bash");
+
+        restore_project_route(&session(&path, backup)).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), original);
+    }
+
+    #[test]
+    fn test_gemini_route_and_cleanup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = project_config_path("gemini-cli", dir.path()).unwrap();
+        let backup =
+            apply_project_route("gemini-cli", &path, "http://127.0.0.1:9001", "dc-trial-y")
+                .unwrap();
+        assert!(backup.is_none());
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains("GOOGLE_GEMINI_BASE_URL=http://127.0.0.1:9001"));
+        assert!(content.contains("GEMINI_API_KEY=dc-trial-y"));
+
+        // 遗留记录清理后，试用前不存在的文件被删除
+        let store_path = dir.path().join("trials.json");
+        save_store(
+            &store_path,
+            &TrialStore {
+                trials: vec![session(&path, backup)],
+            },
+        )
+        .unwrap();
+        cleanup_store(&store_path).unwrap();
+        assert!(!path.exists());
+        assert!(load_store(&store_path).unwrap().trials.is_empty());
+
+        assert!(project_config_path("codex", dir.path()).is_err());
+    }
+}
@@ -0,0 +1,205 @@
+// 上游限流排队
+//
+// 上游返回 429 并给出 Retry-After 时，不立即把 429 返回给客户端，而是暂存请求，
+// 等待指定时长后重新转发：
+// - 同时排队的请求数不超过 `max_requests`，单个请求累计等待不超过 `max_wait_secs`
+// - 缺少 Retry-After、等待时长超出剩余额度或队列已满时照常返回 429
+// - 排队深度按代理实例统计，随代理状态展示
+
+use chrono::{DateTime, Utc};
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::Duration;
+
+use crate::models::proxy_config::UpstreamQueueConfig;
+
+/// 代理实例的排队状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct UpstreamQueueStatus {
+    /// 当前排队等待的请求数
+    pub depth: u32,
+    /// 启动以来进入排队的请求总数
+    pub total_queued: u64,
+}
+
+/// 排队计数（重启实例后重置）
+#[derive(Debug, Default)]
+pub struct UpstreamQueue {
+    depth: AtomicUsize,
+    total_queued: AtomicU64,
+}
+
+/// 排队名额守卫（请求离开队列时自动释放）
+#[derive(Debug)]
+pub struct QueueSlot<'a>(&'a UpstreamQueue);
+
+impl Drop for QueueSlot<'_> {
+    fn drop(&mut self) {
+        self.0.depth.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl UpstreamQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 占用一个排队名额（队列已满时返回 None）
+    pub fn try_enter(&self, max_requests: u32) -> Option<QueueSlot<'_>> {
+        self.depth
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |depth| {
+                (depth < max_requests as usize).then_some(depth + 1)
+            })
+            .ok()?;
+        self.total_queued.fetch_add(1, Ordering::Relaxed);
+        Some(QueueSlot(self))
+    }
+
+    /// 当前排队深度
+    pub fn depth(&self) -> usize {
+        self.depth.load(Ordering::SeqCst)
+    }
+
+    pub fn status(&self) -> UpstreamQueueStatus {
+        UpstreamQueueStatus {
+            depth: self.depth() as u32,
+            total_queued: self.total_queued.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// 解析上游建议的等待时长
+///
+/// 优先使用毫秒精度的 `retry-after-ms`，其次为 `Retry-After`（秒数或 HTTP 日期）。
+pub fn parse_retry_after(headers: &HeaderMap, now: DateTime<Utc>) -> Option<Duration> {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::trim)
+    };
+    if let Some(ms) = header("retry-after-ms").and_then(|v| v.parse::<f64>().ok()) {
+        return (ms.is_finite() && ms >= 0.0).then(|| Duration::from_millis(ms as u64));
+    }
+
+    let value = header("retry-after")?;
+    if let Ok(secs) = value.parse::<f64>() {
+        return (secs.is_finite() && secs >= 0.0).then(|| Duration::from_secs_f64(secs));
+    }
+    let at = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (at.with_timezone(&Utc) - now)
+            .to_std()
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+/// 本次应等待的时长（不满足排队条件时返回 None）
+///
+/// `waited` 为该请求此前已排队的累计时长。
+pub fn queue_delay(
+    config: &UpstreamQueueConfig,
+    retry_after: Option<Duration>,
+    waited: Duration,
+) -> Option<Duration> {
+    if !config.enabled || config.max_requests == 0 {
+        return None;
+    }
+    let delay = retry_after?;
+    let budget = Duration::from_secs(config.max_wait_secs).saturating_sub(waited);
+    (delay <= budget).then_some(delay)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use reqwest::header::HeaderValue;
+
+    fn config(max_requests: u32, max_wait_secs: u64) -> UpstreamQueueConfig {
+        UpstreamQueueConfig {
+            enabled: true,
+            max_requests,
+            max_wait_secs,
+        }
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 16, 8, 0, 0).unwrap();
+        let mut headers = HeaderMap::new();
+        assert_eq!(parse_retry_after(&headers, now), None);
+
+        headers.insert("retry-after", HeaderValue::from_static("7"));
+        assert_eq!(
+            parse_retry_after(&headers, now),
+            Some(Duration::from_secs(7))
+        );
+
+        headers.insert(
+            "retry-after",
+            HeaderValue::from_static("Fri, 16 Oct 2026 08:00:30 GMT"),
+        );
+        assert_eq!(
+            parse_retry_after(&headers, now),
+            Some(Duration::from_secs(30))
+        );
+
+        // 已过去的日期视为立即重试
+        headers.insert(
+            "retry-after",
+            HeaderValue::from_static("Fri, 16 Oct 2026 07:59:00 GMT"),
+        );
+        assert_eq!(parse_retry_after(&headers, now), Some(Duration::ZERO));
+
+        headers.insert("retry-after-ms", HeaderValue::from_static("1500"));
+        assert_eq!(
+            parse_retry_after(&headers, now),
+            Some(Duration::from_millis(1500))
+        );
+
+        headers.remove("retry-after-ms");
+        headers.insert("retry-after", HeaderValue::from_static("soon"));
+        assert_eq!(parse_retry_after(&headers, now), None);
+    }
+
+    #[test]
+    fn test_queue_delay() {
+        let five = Some(Duration::from_secs(5));
+        assert_eq!(
+            queue_delay(&config(4, 10), five, Duration::ZERO),
+            Some(Duration::from_secs(5))
+        );
+        // 累计等待超出上限
+        assert_eq!(
+            queue_delay(&config(4, 10), five, Duration::from_secs(6)),
+            None
+        );
+        // 缺少 Retry-After
+        assert_eq!(queue_delay(&config(4, 10), None, Duration::ZERO), None);
+        assert_eq!(
+            queue_delay(&UpstreamQueueConfig::default(), five, Duration::ZERO),
+            None
+        );
+    }
+
+    #[test]
+    fn test_queue_capacity() {
+        let queue = UpstreamQueue::new();
+        let first = queue.try_enter(2).unwrap();
+        let second = queue.try_enter(2).unwrap();
+        assert!(queue.try_enter(2).is_none());
+        assert_eq!(queue.depth(), 2);
+
+        drop(first);
+        assert_eq!(queue.depth(), 1);
+        let _third = queue.try_enter(2).unwrap();
+        drop(second);
+
+        let status = queue.status();
+        assert_eq!(status.depth, 1);
+        assert_eq!(status.total_queued, 3);
+    }
+}
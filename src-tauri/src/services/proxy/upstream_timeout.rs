@@ -0,0 +1,196 @@
+// 上游超时
+//
+// 按工具配置限制转发上游的等待时间，防止上游挂起时客户端无限等待：
+// - 连接超时：由 HTTP 客户端在建立连接时控制
+// - 总超时：非流式请求覆盖到读完响应体，流式请求覆盖到收到响应头
+// - 流空闲超时：流式响应相邻两次收到数据的最长间隔，超时后中断流
+// 返回响应头之前超时时返回 504；所有超时均以 `timeout` 状态写入统计日志
+
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::egress;
+use crate::models::proxy_config::{EgressProxyConfig, UpstreamTimeoutConfig};
+
+/// 流式响应的超时原因（流被中断时写入，供流结束后的日志记录读取）
+pub type StreamTimeout = Arc<Mutex<Option<String>>>;
+
+/// 上游请求发送失败
+#[derive(Debug)]
+pub enum UpstreamSendError {
+    /// 连接超时或总超时前未收到响应头
+    Timeout(String),
+    /// 其他请求错误（完整错误链）
+    Failed(String),
+}
+
+impl UpstreamSendError {
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, UpstreamSendError::Timeout(_))
+    }
+
+    pub fn detail(&self) -> &str {
+        match self {
+            UpstreamSendError::Timeout(detail) | UpstreamSendError::Failed(detail) => detail,
+        }
+    }
+}
+
+/// 按出口代理与连接超时配置创建转发上游用的 HTTP 客户端
+pub fn build_client(
+    egress_proxy: &EgressProxyConfig,
+    timeouts: &UpstreamTimeoutConfig,
+) -> Result<reqwest::Client> {
+    let mut builder = egress::client_builder(egress_proxy)?;
+    if let Some(connect) = timeouts.connect_timeout() {
+        builder = builder.connect_timeout(connect);
+    }
+    builder.build().context("创建上游 HTTP 客户端失败")
+}
+
+/// 发送上游请求，总超时前未收到响应头时返回超时
+pub async fn send(
+    request: reqwest::RequestBuilder,
+    timeouts: &UpstreamTimeoutConfig,
+) -> std::result::Result<reqwest::Response, UpstreamSendError> {
+    let result = match timeouts.total_timeout() {
+        Some(total) => tokio::time::timeout(total, request.send())
+            .await
+            .map_err(|_| {
+                UpstreamSendError::Timeout(format!("{} 秒内未收到上游响应", total.as_secs()))
+            })?,
+        None => request.send().await,
+    };
+
+    result.map_err(|e| {
+        // reqwest 客户端只设置了连接超时
+        if e.is_timeout() {
+            UpstreamSendError::Timeout(format!("连接上游超时: {}", error_chain(&e)))
+        } else {
+            UpstreamSendError::Failed(error_chain(&e))
+        }
+    })
+}
+
+/// 本次请求剩余的总超时时长（未设置总超时时返回 None）
+pub fn remaining(timeouts: &UpstreamTimeoutConfig, sent_at: Instant) -> Option<Duration> {
+    timeouts
+        .total_timeout()
+        .map(|total| total.saturating_sub(sent_at.elapsed()))
+}
+
+/// 为流式响应加上空闲超时：超过 `idle` 未收到数据时记录超时原因，输出一个超时错误后结束
+///
+/// 上游错误统一转换为 `std::io::Error`，便于与超时错误合并为同一种流
+pub fn guard_stream<S, E>(
+    stream: S,
+    idle: Option<Duration>,
+    timed_out: StreamTimeout,
+) -> impl Stream<Item = std::io::Result<Bytes>>
+where
+    S: Stream<Item = std::result::Result<Bytes, E>> + Send + 'static,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    futures_util::stream::unfold(Some(Box::pin(stream)), move |state| {
+        let timed_out = Arc::clone(&timed_out);
+        async move {
+            let mut stream = state?;
+            let next = match idle {
+                Some(idle) => match tokio::time::timeout(idle, stream.next()).await {
+                    Ok(next) => next,
+                    Err(_) => {
+                        let detail = format!("流式响应超过 {} 秒未收到数据", idle.as_secs());
+                        if let Ok(mut slot) = timed_out.lock() {
+                            *slot = Some(detail.clone());
+                        }
+                        let error = std::io::Error::new(std::io::ErrorKind::TimedOut, detail);
+                        return Some((Err(error), None));
+                    }
+                },
+                None => stream.next().await,
+            };
+            next.map(|item| (item.map_err(std::io::Error::other), Some(stream)))
+        }
+    })
+}
+
+/// 展开完整错误链（reqwest 的 source chain 包含底层原因如 DNS/TLS/超时等）
+fn error_chain(error: &dyn std::error::Error) -> String {
+    let mut msg = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        msg.push_str(&format!(" → {}", cause));
+        source = cause.source();
+    }
+    msg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunks(
+        items: Vec<(u64, &'static str)>,
+    ) -> impl Stream<Item = std::result::Result<Bytes, std::io::Error>> + Send + 'static {
+        futures_util::stream::iter(items).then(|(delay_ms, chunk)| async move {
+            tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+            Ok(Bytes::from(chunk))
+        })
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_guard_stream_passes_through_active_stream() {
+        let timed_out = StreamTimeout::default();
+        let items: Vec<_> = guard_stream(
+            chunks(vec![(0, "a"), (10, "b")]),
+            Some(Duration::from_secs(1)),
+            Arc::clone(&timed_out),
+        )
+        .collect()
+        .await;
+
+        assert_eq!(items.len(), 2);
+        assert!(items.iter().all(|item| item.is_ok()));
+        assert!(timed_out.lock().unwrap().is_none());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_guard_stream_ends_after_idle_timeout() {
+        let timed_out = StreamTimeout::default();
+        let items: Vec<_> = guard_stream(
+            chunks(vec![(0, "a"), (200, "b"), (0, "c")]),
+            Some(Duration::from_millis(50)),
+            Arc::clone(&timed_out),
+        )
+        .collect()
+        .await;
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].as_ref().unwrap(), "a");
+        assert_eq!(
+            items[1].as_ref().unwrap_err().kind(),
+            std::io::ErrorKind::TimedOut
+        );
+        assert!(timed_out.lock().unwrap().is_some());
+    }
+
+    #[test]
+    fn test_timeouts_zero_means_unlimited() {
+        let config = UpstreamTimeoutConfig {
+            connect_timeout_secs: 5,
+            total_timeout_secs: 0,
+            idle_stream_timeout_secs: 0,
+        };
+        assert_eq!(config.connect_timeout(), Some(Duration::from_secs(5)));
+        assert_eq!(config.total_timeout(), None);
+        assert_eq!(config.idle_stream_timeout(), None);
+        assert_eq!(remaining(&config, Instant::now()), None);
+
+        let default = UpstreamTimeoutConfig::default();
+        assert!(remaining(&default, Instant::now()).unwrap() <= Duration::from_secs(600));
+        assert!(build_client(&EgressProxyConfig::default(), &default).is_ok());
+    }
+}
@@ -37,6 +37,111 @@ pub fn proxy_loop_detected(tool_id: &str) -> Response<BoxBody> {
         .unwrap()
 }
 
+/// 本地限流错误（429 + Retry-After）
+pub fn rate_limited(tool_id: &str, reason: &str, retry_after_secs: u64) -> Response<BoxBody> {
+    let body = serde_json::json!({
+        "error": "RATE_LIMITED",
+        "message": format!("{tool_id} 透明代理已限流：{reason}"),
+        "details": format!("请在 {retry_after_secs} 秒后重试，或在代理设置中调整限流配置"),
+    });
+    Response::builder()
+        .status(StatusCode::TOO_MANY_REQUESTS)
+        .header("content-type", "application/json")
+        .header("retry-after", retry_after_secs.to_string())
+        .body(box_body(http_body_util::Full::new(Bytes::from(
+            body.to_string(),
+        ))))
+        .unwrap()
+}
+
+/// 并发上游请求数已达上限（拒绝模式或排队超时）
+pub fn concurrency_limited(tool_id: &str, reason: &str) -> Response<BoxBody> {
+    let body = serde_json::json!({
+        "error": "CONCURRENCY_LIMITED",
+        "message": format!("{tool_id} 透明代理{reason}"),
+        "details": "请稍后重试，或在代理设置中调整并发上限",
+    });
+    Response::builder()
+        .status(StatusCode::SERVICE_UNAVAILABLE)
+        .header("content-type", "application/json")
+        .header("retry-after", "1")
+        .body(box_body(http_body_util::Full::new(Bytes::from(
+            body.to_string(),
+        ))))
+        .unwrap()
+}
+
+/// 上游超时（连接超时或总超时前未完成响应）
+pub fn upstream_timeout(tool_id: &str, detail: &str) -> Response<BoxBody> {
+    let body = serde_json::json!({
+        "error": "UPSTREAM_TIMEOUT",
+        "message": format!("{tool_id} 透明代理等待上游超时：{detail}"),
+        "details": "请检查上游服务状态，或在代理设置中调整超时配置",
+    });
+    Response::builder()
+        .status(StatusCode::GATEWAY_TIMEOUT)
+        .header("content-type", "application/json")
+        .body(box_body(http_body_util::Full::new(Bytes::from(
+            body.to_string(),
+        ))))
+        .unwrap()
+}
+
+/// 消费超出预算错误（拦截模式）
+pub fn budget_exceeded(tool_id: &str) -> Response<BoxBody> {
+    let body = serde_json::json!({
+        "error": "BUDGET_EXCEEDED",
+        "message": format!("{tool_id} 已超出消费预算，请求已被拦截"),
+        "details": "请等待下一个统计周期，或在代理设置中调整预算",
+    });
+    Response::builder()
+        .status(StatusCode::PAYMENT_REQUIRED)
+        .header("content-type", "application/json")
+        .body(box_body(http_body_util::Full::new(Bytes::from(
+            body.to_string(),
+        ))))
+        .unwrap()
+}
+
+/// 请求预估输入成本超出单次上限被拒绝（拒绝模式、用户拒绝或确认超时）
+pub fn cost_guard_rejected(
+    tool_id: &str,
+    input_tokens: i64,
+    input_cost_usd: f64,
+    threshold_usd: f64,
+) -> Response<BoxBody> {
+    let body = serde_json::json!({
+        "error": "COST_GUARD_REJECTED",
+        "message": format!(
+            "{tool_id} 请求预估输入约 {input_tokens} Token（${input_cost_usd:.4}），超出单次上限 ${threshold_usd:.4}，已被拦截"
+        ),
+        "details": "请精简上下文后重试，或在代理设置中调整成本预检",
+    });
+    Response::builder()
+        .status(StatusCode::BAD_REQUEST)
+        .header("content-type", "application/json")
+        .body(box_body(http_body_util::Full::new(Bytes::from(
+            body.to_string(),
+        ))))
+        .unwrap()
+}
+
+/// 请求内容命中脱敏规则被拦截
+pub fn content_blocked(tool_id: &str, rules: &str) -> Response<BoxBody> {
+    let body = serde_json::json!({
+        "error": "CONTENT_BLOCKED",
+        "message": format!("{tool_id} 请求包含敏感内容，已被拦截（命中规则：{rules}）"),
+        "details": "请移除敏感内容后重试，或在代理设置中调整脱敏规则",
+    });
+    Response::builder()
+        .status(StatusCode::BAD_REQUEST)
+        .header("content-type", "application/json")
+        .body(box_body(http_body_util::Full::new(Bytes::from(
+            body.to_string(),
+        ))))
+        .unwrap()
+}
+
 /// 未授权错误
 pub fn unauthorized() -> Response<BoxBody> {
     Response::builder()
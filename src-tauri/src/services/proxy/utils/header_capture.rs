@@ -0,0 +1,90 @@
+//! 上游响应头捕获
+//!
+//! 把上游（官方 API 或中转站）返回的请求 ID、路由区域等响应头记录到日志行，
+//! 便于向中转站运营方提交工单时附上对方自己的请求 ID。
+
+use hyper::HeaderMap;
+use std::collections::BTreeMap;
+
+/// 单个响应头值的最大保留长度
+const MAX_HEADER_VALUE_LEN: usize = 256;
+
+/// 按捕获列表提取响应头，序列化为 JSON 对象（未命中任何响应头时返回 None）
+///
+/// 名称匹配不区分大小写；同名多值以 `, ` 连接
+pub fn capture_headers(headers: &HeaderMap, names: &[String]) -> Option<String> {
+    let mut captured = BTreeMap::new();
+
+    for name in names {
+        let name = name.trim().to_ascii_lowercase();
+        if name.is_empty() {
+            continue;
+        }
+
+        let values: Vec<&str> = headers
+            .get_all(name.as_str())
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .collect();
+        if values.is_empty() {
+            continue;
+        }
+
+        let mut value = values.join(", ");
+        if value.len() > MAX_HEADER_VALUE_LEN {
+            let mut end = MAX_HEADER_VALUE_LEN;
+            while !value.is_char_boundary(end) {
+                end -= 1;
+            }
+            value.truncate(end);
+        }
+        captured.insert(name, value);
+    }
+
+    if captured.is_empty() {
+        return None;
+    }
+    serde_json::to_string(&captured).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::header::HeaderValue;
+
+    #[test]
+    fn test_capture_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-request-id", HeaderValue::from_static("req_123"));
+        headers.append("cf-ray", HeaderValue::from_static("abc-HKG"));
+        headers.append("cf-ray", HeaderValue::from_static("def-SJC"));
+        headers.insert(
+            "content-type",
+            HeaderValue::from_static("text/event-stream"),
+        );
+
+        let names = vec![
+            "X-Request-Id".to_string(),
+            "cf-ray".to_string(),
+            "request-id".to_string(),
+            " ".to_string(),
+        ];
+        assert_eq!(
+            capture_headers(&headers, &names).as_deref(),
+            Some(r#"{"cf-ray":"abc-HKG, def-SJC","x-request-id":"req_123"}"#)
+        );
+
+        assert_eq!(capture_headers(&headers, &["request-id".to_string()]), None);
+    }
+
+    #[test]
+    fn test_capture_truncates_long_values() {
+        let mut headers = HeaderMap::new();
+        let long = "a".repeat(1000);
+        headers.insert("x-trace", HeaderValue::from_str(&long).unwrap());
+
+        let captured = capture_headers(&headers, &["x-trace".to_string()]).unwrap();
+        let parsed: BTreeMap<String, String> = serde_json::from_str(&captured).unwrap();
+        assert_eq!(parsed["x-trace"].len(), MAX_HEADER_VALUE_LEN);
+    }
+}
@@ -4,7 +4,10 @@
 
 pub mod body;
 pub mod error_responses;
+pub mod header_capture;
 pub mod loop_detector;
+pub mod sse_filter;
+pub mod sse_quirks;
 
 // 重新导出常用类型
 pub use body::{box_body, BoxBody};
@@ -0,0 +1,324 @@
+//! SSE 统计旁路过滤器
+//!
+//! 透明代理在转发 SSE 流的同时会把数据输入统计旁路用于 Token 统计。
+//! 大量 thinking/content delta 对统计毫无用处，此过滤器按 API 风格只把
+//! 提取所需的事件（如 message_start、message_delta、error）输入 Token 累加器，
+//! 事件本身不做缓存，长流的内存占用保持恒定，也省去无关事件的 JSON 解析。
+//!
+//! 上游实际风格与工具预期不符时（如 claude-code 指向 OpenAI 风格中转），
+//! 过滤器会根据首个事件自动切换风格，避免误丢统计事件。
+//!
+//! 事件切分与类型识别兼容非标准中转（BOM、`\r` 换行、`data:` 无空格），
+//! 保留事件中的这些差异会被记录下来，供日志层诊断。
+
+use super::sse_quirks::{
+    data_payloads, detect_quirks, find_event_boundary, json_type, split_lines, SseQuirk,
+};
+use crate::services::proxy::log_recorder::{ParsedResponse, ResponseParser};
+use crate::services::token_stats::StreamingTokenAccumulator;
+
+/// SSE 事件风格（决定保留哪些事件类型）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SseFlavor {
+    /// Anthropic Messages API（claude-code）
+    Anthropic,
+    /// OpenAI Responses API（codex）
+    OpenAiResponses,
+    /// 未知风格：保留全部事件
+    Passthrough,
+}
+
+impl SseFlavor {
+    /// 根据工具 ID 选择默认风格
+    pub fn for_tool(tool_id: &str) -> Self {
+        match tool_id {
+            "claude-code" => SseFlavor::Anthropic,
+            "codex" => SseFlavor::OpenAiResponses,
+            // gemini-cli 每个 chunk 都携带 usageMetadata，amp-code 按请求路由到不同上游
+            _ => SseFlavor::Passthrough,
+        }
+    }
+
+    /// 根据事件类型推断所属风格（无法判断时返回 None）
+    fn sniff(event_type: &str) -> Option<Self> {
+        if event_type.starts_with("response.") {
+            return Some(SseFlavor::OpenAiResponses);
+        }
+        if event_type == "ping"
+            || event_type.starts_with("message_")
+            || event_type.starts_with("content_block_")
+        {
+            return Some(SseFlavor::Anthropic);
+        }
+        None
+    }
+
+    /// 是否保留指定类型的事件
+    fn retains(&self, event_type: &str) -> bool {
+        match self {
+            SseFlavor::Anthropic => {
+                matches!(event_type, "message_start" | "message_delta" | "error")
+            }
+            SseFlavor::OpenAiResponses => matches!(
+                event_type,
+                "response.created"
+                    | "response.completed"
+                    | "response.incomplete"
+                    | "response.failed"
+                    | "error"
+            ),
+            SseFlavor::Passthrough => true,
+        }
+    }
+}
+
+/// 流式 SSE 事件过滤器
+///
+/// 跨 chunk 边界拼接事件，统计相关事件直接输入 Token 累加器，不保留事件原文
+#[derive(Debug, Clone)]
+pub struct SseEventFilter {
+    flavor: SseFlavor,
+    /// 是否已根据首个可识别事件校验过风格
+    sniffed: bool,
+    /// 尚未遇到事件分隔符的残留数据
+    pending: Vec<u8>,
+    /// 保留事件的 Token 累加状态
+    stream: StreamingTokenAccumulator,
+    /// 保留事件中观察到的非标准写法
+    quirks: Vec<SseQuirk>,
+    /// 被丢弃的字节数（诊断用）
+    dropped_bytes: usize,
+}
+
+impl SseEventFilter {
+    pub fn new(flavor: SseFlavor) -> Self {
+        Self {
+            flavor,
+            sniffed: false,
+            pending: Vec::new(),
+            stream: StreamingTokenAccumulator::new(),
+            quirks: Vec::new(),
+            dropped_bytes: 0,
+        }
+    }
+
+    /// 当前使用的风格
+    pub fn flavor(&self) -> SseFlavor {
+        self.flavor
+    }
+
+    /// 输入一个网络 chunk
+    pub fn feed(&mut self, chunk: &[u8]) {
+        let mut pending = std::mem::take(&mut self.pending);
+        pending.extend_from_slice(chunk);
+
+        let mut consumed = 0;
+        while let Some((_, next_start)) = find_event_boundary(&pending[consumed..]) {
+            // 连同分隔符一起检查，才能识别 `\r` 换行等写法
+            self.keep_or_drop(&pending[consumed..consumed + next_start]);
+            consumed += next_start;
+        }
+
+        if consumed > 0 {
+            pending.drain(..consumed);
+        }
+        self.pending = pending;
+    }
+
+    /// 当前的解析结果（含未遇到分隔符的残留数据，不结束输入）
+    pub fn snapshot(&self) -> ParsedResponse {
+        self.clone().finish()
+    }
+
+    /// 结束输入，返回累加后的解析结果
+    pub fn finish(mut self) -> ParsedResponse {
+        if !self.pending.is_empty() {
+            let pending = std::mem::take(&mut self.pending);
+            self.keep_or_drop(&pending);
+        }
+
+        tracing::debug!(
+            flavor = ?self.flavor,
+            data_lines = self.stream.data_line_count(),
+            dropped_bytes = self.dropped_bytes,
+            "SSE 统计旁路已完成累加"
+        );
+
+        self.quirks.sort();
+        self.quirks.dedup();
+        ResponseParser::from_stream(self.stream, self.quirks)
+    }
+
+    fn keep_or_drop(&mut self, event: &[u8]) {
+        if event.iter().all(|b| b.is_ascii_whitespace()) {
+            return;
+        }
+
+        // 无法识别类型的事件一律保留，宁可多算不可漏统计
+        let keep = match event_type(event) {
+            Some(event_type) => {
+                self.sniff_flavor(&event_type);
+                self.flavor.retains(&event_type)
+            }
+            None => true,
+        };
+
+        if keep {
+            self.quirks.extend(detect_quirks(event));
+            for data in data_payloads(&String::from_utf8_lossy(event)) {
+                self.stream.push_data(data);
+            }
+        } else {
+            self.dropped_bytes += event.len();
+        }
+    }
+
+    /// 用首个可识别事件校验风格，与预期不符时切换
+    fn sniff_flavor(&mut self, event_type: &str) {
+        if self.sniffed {
+            return;
+        }
+        let Some(actual) = SseFlavor::sniff(event_type) else {
+            return;
+        };
+        self.sniffed = true;
+
+        if actual != self.flavor {
+            tracing::warn!(
+                expected = ?self.flavor,
+                actual = ?actual,
+                "SSE 上游风格与工具预期不符，统计旁路已自动切换"
+            );
+            self.flavor = actual;
+        }
+    }
+}
+
+/// 提取事件类型：优先 `event:` 字段，回退到 data JSON 的首个 `"type"` 字段
+fn event_type(event: &[u8]) -> Option<String> {
+    let text = String::from_utf8_lossy(event);
+    let text = text.trim_start_matches('\u{feff}');
+
+    for line in split_lines(text) {
+        if let Some(name) = line.strip_prefix("event:") {
+            let name = name.trim();
+            if !name.is_empty() {
+                return Some(name.to_string());
+            }
+        }
+    }
+
+    let data = split_lines(text).find_map(|line| line.strip_prefix("data:"))?;
+    json_type(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ANTHROPIC_STREAM: &str = "event: message_start\n\
+data: {\"type\":\"message_start\",\"message\":{\"id\":\"msg_1\",\"usage\":{\"input_tokens\":10}}}\n\n\
+event: content_block_delta\n\
+data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"thinking_delta\",\"thinking\":\"long...\"}}\n\n\
+event: message_delta\n\
+data: {\"type\":\"message_delta\",\"usage\":{\"output_tokens\":5}}\n\n\
+event: message_stop\n\
+data: {\"type\":\"message_stop\"}\n\n";
+
+    /// 结束输入，返回保留的 data 块与非标准写法
+    fn finish(filter: SseEventFilter) -> (Vec<String>, Vec<SseQuirk>) {
+        match filter.finish() {
+            ParsedResponse::Sse { stream, quirks } => (stream.head_lines().to_vec(), quirks),
+            _ => (Vec::new(), Vec::new()),
+        }
+    }
+
+    #[test]
+    fn test_anthropic_keeps_only_usage_events() {
+        let mut filter = SseEventFilter::new(SseFlavor::Anthropic);
+        filter.feed(ANTHROPIC_STREAM.as_bytes());
+        let (lines, _) = finish(filter);
+
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("message_start"));
+        assert!(lines[1].contains("message_delta"));
+    }
+
+    #[test]
+    fn test_events_split_across_chunks() {
+        let mut filter = SseEventFilter::new(SseFlavor::Anthropic);
+        for chunk in ANTHROPIC_STREAM.as_bytes().chunks(7) {
+            filter.feed(chunk);
+        }
+
+        let request_body = br#"{"model":"claude-sonnet-4-5"}"#;
+        match filter.finish() {
+            ParsedResponse::Sse { stream, .. } => {
+                assert_eq!(stream.data_line_count(), 2);
+                let info = stream.token_info("claude-code", request_body).unwrap();
+                assert_eq!(info.input_tokens, 10);
+                assert_eq!(info.output_tokens, 5);
+            }
+            other => panic!("unexpected: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_type_from_data_without_event_line() {
+        let stream = "data: {\"type\":\"response.output_text.delta\",\"delta\":\"hi\"}\r\n\r\n\
+data: {\"type\":\"response.completed\",\"response\":{\"usage\":{}}}\r\n\r\n";
+        let mut filter = SseEventFilter::new(SseFlavor::OpenAiResponses);
+        filter.feed(stream.as_bytes());
+        let (lines, _) = finish(filter);
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].contains("response.completed"));
+    }
+
+    #[test]
+    fn test_unknown_events_and_passthrough_are_retained() {
+        let mut filter = SseEventFilter::new(SseFlavor::Anthropic);
+        filter.feed(b"data: [DONE]");
+        // 仅有结束标记时没有可统计的数据
+        assert!(matches!(filter.finish(), ParsedResponse::ParseError { .. }));
+
+        let mut filter = SseEventFilter::new(SseFlavor::for_tool("gemini-cli"));
+        filter.feed(b"data: {\"candidates\":[]}\n\n");
+        assert_eq!(finish(filter).0, vec![r#"{"candidates":[]}"#.to_string()]);
+    }
+
+    #[test]
+    fn test_quirky_relay_stream_keeps_usage_events() {
+        let stream = "\u{feff}: keep-alive\r\r\
+data:{\"type\":\"message_start\",\"message\":{}}\r\r\
+data:{\"type\":\"content_block_delta\",\"delta\":{}}\r\r\
+data:{\"type\":\"message_delta\",\"usage\":{}}\r\r";
+        let mut filter = SseEventFilter::new(SseFlavor::Anthropic);
+        for chunk in stream.as_bytes().chunks(5) {
+            filter.feed(chunk);
+        }
+        let (lines, quirks) = finish(filter);
+
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("message_start"));
+        assert!(lines[1].contains("message_delta"));
+        assert!(quirks.contains(&SseQuirk::Bom));
+        assert!(quirks.contains(&SseQuirk::CommentLines));
+        assert!(quirks.contains(&SseQuirk::DataWithoutSpace));
+        assert!(quirks.contains(&SseQuirk::BareCarriageReturn));
+    }
+
+    #[test]
+    fn test_switches_flavor_on_mismatched_stream() {
+        let stream = "data: {\"type\":\"response.created\",\"response\":{}}\n\n\
+data: {\"type\":\"response.output_text.delta\",\"delta\":\"hi\"}\n\n\
+data: {\"type\":\"response.completed\",\"response\":{\"usage\":{}}}\n\n";
+        let mut filter = SseEventFilter::new(SseFlavor::Anthropic);
+        filter.feed(stream.as_bytes());
+        assert_eq!(filter.flavor(), SseFlavor::OpenAiResponses);
+
+        let (lines, _) = finish(filter);
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("response.created"));
+        assert!(lines[1].contains("response.completed"));
+    }
+}
@@ -0,0 +1,374 @@
+//! 非标准 SSE 兼容层
+//!
+//! 部分中转站输出的 SSE 不够规范：流首带 UTF-8 BOM、插入 `:` 注释或 keep-alive 垃圾行、
+//! `data:` 后缺少空格、只用 `\r` 换行、缺少 `event:` 字段等。
+//!
+//! - [`detect_quirks`]：识别响应中出现的非标准写法（写入会话诊断）
+//! - [`SseStreamNormalizer`]：按代理配置改写转发给客户端的流（默认不改写）
+
+use crate::models::proxy_config::SseCompatConfig;
+use serde::{Deserialize, Serialize};
+
+/// UTF-8 BOM
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// 观察到的非标准 SSE 写法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SseQuirk {
+    /// 流首带 UTF-8 BOM
+    Bom,
+    /// 含 `:` 开头的注释 / keep-alive 行
+    CommentLines,
+    /// `data:` 后缺少空格
+    DataWithoutSpace,
+    /// 仅使用 `\r` 作为换行符
+    BareCarriageReturn,
+    /// 事件带 `"type"` 但缺少 `event:` 字段
+    MissingEventName,
+}
+
+impl SseQuirk {
+    /// 诊断标识（写入会话记录）
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SseQuirk::Bom => "bom",
+            SseQuirk::CommentLines => "comment-lines",
+            SseQuirk::DataWithoutSpace => "data-without-space",
+            SseQuirk::BareCarriageReturn => "bare-cr",
+            SseQuirk::MissingEventName => "missing-event-name",
+        }
+    }
+}
+
+/// 识别响应中出现的非标准 SSE 写法（结果已排序去重）
+pub fn detect_quirks(body: &[u8]) -> Vec<SseQuirk> {
+    let mut quirks = Vec::new();
+
+    if body.starts_with(BOM) {
+        quirks.push(SseQuirk::Bom);
+    }
+    if body
+        .iter()
+        .enumerate()
+        .any(|(i, &b)| b == b'\r' && body.get(i + 1) != Some(&b'\n'))
+    {
+        quirks.push(SseQuirk::BareCarriageReturn);
+    }
+
+    let text = String::from_utf8_lossy(body.strip_prefix(BOM).unwrap_or(body));
+    let mut has_event_line = false;
+    let mut has_typed_data = false;
+    for line in split_lines(&text).chain(std::iter::once("")) {
+        if line.is_empty() {
+            if has_typed_data && !has_event_line {
+                quirks.push(SseQuirk::MissingEventName);
+            }
+            has_event_line = false;
+            has_typed_data = false;
+            continue;
+        }
+
+        if line.starts_with(':') {
+            quirks.push(SseQuirk::CommentLines);
+        } else if line.starts_with("event:") {
+            has_event_line = true;
+        } else if let Some(data) = line.strip_prefix("data:") {
+            if !data.is_empty() && !data.starts_with(' ') {
+                quirks.push(SseQuirk::DataWithoutSpace);
+            }
+            if json_type(data).is_some() {
+                has_typed_data = true;
+            }
+        }
+    }
+
+    quirks.sort();
+    quirks.dedup();
+    quirks
+}
+
+/// 按 SSE 规范拆分行（`\r\n`、`\r`、`\n` 均视为换行）
+pub fn split_lines(text: &str) -> impl Iterator<Item = &str> {
+    let text = text
+        .strip_suffix("\r\n")
+        .or_else(|| text.strip_suffix('\n'))
+        .or_else(|| text.strip_suffix('\r'))
+        .unwrap_or(text);
+    let mut rest = Some(text);
+
+    std::iter::from_fn(move || {
+        let current = rest?;
+        match current.find(['\r', '\n']) {
+            Some(pos) => {
+                let skip = if current[pos..].starts_with("\r\n") {
+                    2
+                } else {
+                    1
+                };
+                rest = Some(&current[pos + skip..]);
+                Some(&current[..pos])
+            }
+            None => {
+                rest = None;
+                Some(current)
+            }
+        }
+    })
+}
+
+/// 提取 SSE 文本中所有 `data:` 行的内容（去掉 BOM 与 `data:` 后的可选空格）
+pub fn data_payloads(text: &str) -> impl Iterator<Item = &str> {
+    split_lines(text.trim_start_matches('\u{feff}'))
+        .filter_map(|line| line.strip_prefix("data:"))
+        .map(|data| data.strip_prefix(' ').unwrap_or(data))
+}
+
+/// 查找事件分隔符（连续两个换行，兼容 `\r\n`、`\r`、`\n` 混用）
+///
+/// 返回 (事件结束位置, 下一事件起始位置)。缓冲区末尾的 `\r` 可能是 `\r\n` 的前半部分，
+/// 此时返回 None 等待更多数据
+pub fn find_event_boundary(buf: &[u8]) -> Option<(usize, usize)> {
+    let mut i = 0;
+    while i < buf.len() {
+        let Some(first) = line_ending_len(buf, i) else {
+            i += 1;
+            continue;
+        };
+        if let Some(second) = line_ending_len(buf, i + first) {
+            return Some((i, i + first + second));
+        }
+        i += first;
+    }
+    None
+}
+
+/// 指定位置的换行符长度（非换行或无法确定时返回 None）
+fn line_ending_len(buf: &[u8], i: usize) -> Option<usize> {
+    match buf.get(i)? {
+        b'\n' => Some(1),
+        b'\r' => match buf.get(i + 1) {
+            Some(b'\n') => Some(2),
+            Some(_) => Some(1),
+            None => None,
+        },
+        _ => None,
+    }
+}
+
+/// 从 `data:` 内容中提取 JSON 的首个 `"type"` 字段
+///
+/// 不做完整 JSON 解析，避免在大体积 delta 上浪费 CPU
+pub fn json_type(data: &str) -> Option<String> {
+    let data = data.trim_start();
+    if !data.starts_with('{') {
+        return None;
+    }
+    let after_key = &data[data.find("\"type\"")? + "\"type\"".len()..];
+    let after_colon = after_key.trim_start().strip_prefix(':')?.trim_start();
+    let value = after_colon.strip_prefix('"')?;
+    value.find('"').map(|end| value[..end].to_string())
+}
+
+/// 客户端流改写器
+///
+/// 按事件缓冲上游数据，依据 [`SseCompatConfig`] 改写非标准写法后再转发。
+/// 无需改写的事件原样输出
+#[derive(Debug)]
+pub struct SseStreamNormalizer {
+    config: SseCompatConfig,
+    /// 是否已处理流首（BOM 检查）
+    started: bool,
+    /// 尚未遇到事件分隔符的残留数据
+    pending: Vec<u8>,
+}
+
+impl SseStreamNormalizer {
+    pub fn new(config: SseCompatConfig) -> Self {
+        Self {
+            config,
+            started: false,
+            pending: Vec::new(),
+        }
+    }
+
+    /// 输入一个网络 chunk，返回可立即转发的数据
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<u8> {
+        self.pending.extend_from_slice(chunk);
+
+        if !self.started {
+            if self.config.strip_bom {
+                // BOM 可能被拆在多个 chunk 中
+                if self.pending.len() < BOM.len() && BOM.starts_with(&self.pending) {
+                    return Vec::new();
+                }
+                if self.pending.starts_with(BOM) {
+                    self.pending.drain(..BOM.len());
+                }
+            }
+            self.started = true;
+        }
+
+        let pending = std::mem::take(&mut self.pending);
+        let mut out = Vec::with_capacity(pending.len());
+        let mut consumed = 0;
+        while let Some((event_end, next_start)) = find_event_boundary(&pending[consumed..]) {
+            self.write_event(
+                &pending[consumed..consumed + event_end],
+                &pending[consumed + event_end..consumed + next_start],
+                &mut out,
+            );
+            consumed += next_start;
+        }
+
+        self.pending = pending;
+        self.pending.drain(..consumed);
+        out
+    }
+
+    /// 结束输入，返回剩余数据
+    pub fn finish(&mut self) -> Vec<u8> {
+        let pending = std::mem::take(&mut self.pending);
+        let mut out = Vec::new();
+        if !pending.is_empty() {
+            self.write_event(&pending, b"", &mut out);
+        }
+        out
+    }
+
+    fn write_event(&self, event: &[u8], terminator: &[u8], out: &mut Vec<u8>) {
+        let text = String::from_utf8_lossy(event);
+        let lines: Vec<&str> = split_lines(&text).collect();
+
+        let fill_event_name = if self.config.fill_event_names
+            && !lines.iter().any(|line| line.starts_with("event:"))
+        {
+            lines
+                .iter()
+                .find_map(|line| line.strip_prefix("data:"))
+                .and_then(json_type)
+        } else {
+            None
+        };
+        let needs_rewrite = fill_event_name.is_some()
+            || (self.config.normalize_line_endings
+                && (event.contains(&b'\r') || terminator.contains(&b'\r')))
+            || (self.config.drop_comments && lines.iter().any(|line| line.starts_with(':')))
+            || (self.config.fix_data_prefix
+                && lines.iter().any(|line| is_data_without_space(line)));
+
+        if !needs_rewrite {
+            out.extend_from_slice(event);
+            out.extend_from_slice(terminator);
+            return;
+        }
+
+        let mut rewritten: Vec<String> = Vec::with_capacity(lines.len() + 1);
+        if let Some(name) = fill_event_name {
+            rewritten.push(format!("event: {}", name));
+        }
+        for line in lines {
+            if self.config.drop_comments && line.starts_with(':') {
+                continue;
+            }
+            if self.config.fix_data_prefix && is_data_without_space(line) {
+                rewritten.push(format!("data: {}", &line["data:".len()..]));
+            } else {
+                rewritten.push(line.to_string());
+            }
+        }
+
+        // 仅含注释的 keep-alive 事件整体丢弃
+        if rewritten.is_empty() {
+            return;
+        }
+        out.extend_from_slice(rewritten.join("\n").as_bytes());
+        if !terminator.is_empty() {
+            out.extend_from_slice(b"\n\n");
+        }
+    }
+}
+
+fn is_data_without_space(line: &str) -> bool {
+    line.len() > "data:".len() && line.starts_with("data:") && !line.starts_with("data: ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn all_enabled() -> SseCompatConfig {
+        SseCompatConfig {
+            strip_bom: true,
+            drop_comments: true,
+            fix_data_prefix: true,
+            normalize_line_endings: true,
+            fill_event_names: true,
+        }
+    }
+
+    #[test]
+    fn test_detect_quirks() {
+        let body = b"\xEF\xBB\xBF: keep-alive\r\rdata:{\"type\":\"message_start\"}\r\r";
+        assert_eq!(
+            detect_quirks(body),
+            vec![
+                SseQuirk::Bom,
+                SseQuirk::CommentLines,
+                SseQuirk::DataWithoutSpace,
+                SseQuirk::BareCarriageReturn,
+                SseQuirk::MissingEventName,
+            ]
+        );
+
+        let standard = b"event: message_start\ndata: {\"type\":\"message_start\"}\n\n\
+data: {\"object\":\"chat.completion.chunk\"}\n\ndata: [DONE]\n\n";
+        assert!(detect_quirks(standard).is_empty());
+    }
+
+    #[test]
+    fn test_find_event_boundary_mixed_line_endings() {
+        assert_eq!(find_event_boundary(b"data: a\n\ndata: b"), Some((7, 9)));
+        assert_eq!(
+            find_event_boundary(b"data: a\r\n\r\ndata: b"),
+            Some((7, 11))
+        );
+        assert_eq!(find_event_boundary(b"data: a\r\rdata: b"), Some((7, 9)));
+        // 末尾的 \r 可能属于 \r\n，等待更多数据
+        assert_eq!(find_event_boundary(b"data: a\r\n\r"), None);
+        assert_eq!(find_event_boundary(b"data: a\ndata: b"), None);
+    }
+
+    #[test]
+    fn test_normalizer_rewrites_quirky_stream() {
+        let mut normalizer = SseStreamNormalizer::new(all_enabled());
+        let stream = b"\xEF\xBB\xBF: ping\r\r\
+data:{\"type\":\"message_start\"}\r\r\
+event: message_delta\r\ndata: {\"type\":\"message_delta\"}\r\n\r\n";
+
+        let mut out = Vec::new();
+        for chunk in stream.chunks(2) {
+            out.extend(normalizer.feed(chunk));
+        }
+        out.extend(normalizer.finish());
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "event: message_start\ndata: {\"type\":\"message_start\"}\n\n\
+event: message_delta\ndata: {\"type\":\"message_delta\"}\n\n"
+        );
+    }
+
+    #[test]
+    fn test_normalizer_disabled_is_byte_identical() {
+        let stream: &[u8] = b"\xEF\xBB\xBF: ping\r\n\r\ndata:{\"type\":\"x\"}\r\n\r\ndata: tail";
+        let mut normalizer = SseStreamNormalizer::new(SseCompatConfig::default());
+
+        let mut out = Vec::new();
+        for chunk in stream.chunks(3) {
+            out.extend(normalizer.feed(chunk));
+        }
+        out.extend(normalizer.finish());
+        assert_eq!(out, stream);
+    }
+}
@@ -0,0 +1,248 @@
+// 透明代理 WebSocket 透传
+//
+// 客户端发起 `Upgrade: websocket` 请求时：
+// - 通过 RequestProcessor 生成上游地址与请求头，认证头与普通请求一样替换为真实 Key
+// - 以 HTTP/1.1 向上游发起升级请求，上游返回 101 后将握手响应转发给客户端
+// - 两侧连接升级后双向复制数据帧，直到任一方关闭或代理停止
+//
+// 上游拒绝升级时原样返回上游响应；WebSocket 会话不计入 Token 统计
+
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use http_body_util::Full;
+use hyper::body::Incoming;
+use hyper::{HeaderMap, Request, Response};
+use hyper_util::rt::TokioIo;
+use reqwest::header::HeaderValue;
+use tokio_util::sync::CancellationToken;
+
+use super::egress;
+use super::headers::RequestProcessor;
+use super::utils::body::{box_body, BoxBody};
+use super::utils::{error_responses, loop_detector};
+use crate::models::proxy_config::{EgressProxyConfig, UpstreamTarget};
+
+/// 是否为 WebSocket 升级请求
+pub fn is_upgrade_request(headers: &HeaderMap) -> bool {
+    let has_token = |name: &str, token: &str| {
+        headers
+            .get_all(name)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .any(|v| v.trim().eq_ignore_ascii_case(token))
+    };
+    has_token("connection", "upgrade") && has_token("upgrade", "websocket")
+}
+
+/// 建立客户端与上游之间的 WebSocket 隧道
+///
+/// `shutdown` 取消时关闭隧道（代理停止）
+pub async fn proxy_upgrade(
+    req: Request<Incoming>,
+    processor: &dyn RequestProcessor,
+    upstream: &UpstreamTarget,
+    egress_proxy: &EgressProxyConfig,
+    own_port: u16,
+    tool_id: &str,
+    shutdown: CancellationToken,
+) -> Result<Response<BoxBody>> {
+    let path = req.uri().path().to_string();
+    let query = req.uri().query().map(|s| s.to_string());
+
+    let mut processed = processor
+        .process_outgoing_request(
+            upstream.base_url.trim_end_matches('/'),
+            &upstream.api_key,
+            &path,
+            query.as_deref(),
+            req.headers(),
+            &[],
+        )
+        .await
+        .context("处理 WebSocket 升级请求失败")?;
+
+    if loop_detector::is_proxy_loop(&processed.target_url, own_port) {
+        return Ok(error_responses::proxy_loop_detected(tool_id));
+    }
+
+    // 握手必须走 HTTP/1.1，且显式携带升级头
+    processed
+        .headers
+        .insert("connection", HeaderValue::from_static("Upgrade"));
+    processed
+        .headers
+        .insert("upgrade", HeaderValue::from_static("websocket"));
+    processed.headers.remove("content-length");
+
+    tracing::debug!(
+        tool_id = %tool_id,
+        path = %path,
+        target_url = %processed.target_url,
+        "WebSocket 升级请求"
+    );
+
+    let client = egress::client_builder(egress_proxy)?
+        .http1_only()
+        .build()
+        .context("创建 WebSocket 上游客户端失败")?;
+    let upstream_res = client
+        .get(&processed.target_url)
+        .headers(processed.headers)
+        .send()
+        .await
+        .context("连接 WebSocket 上游失败")?;
+
+    let status = upstream_res.status();
+    let mut response = Response::builder().status(status.as_u16());
+    for (name, value) in upstream_res.headers().iter() {
+        response = response.header(name.as_str(), value.as_bytes());
+    }
+
+    if status != reqwest::StatusCode::SWITCHING_PROTOCOLS {
+        tracing::warn!(
+            tool_id = %tool_id,
+            status = status.as_u16(),
+            "上游拒绝 WebSocket 升级"
+        );
+        let body = upstream_res.bytes().await.unwrap_or_default();
+        return response
+            .body(box_body(Full::new(body)))
+            .context("构建 WebSocket 拒绝响应失败");
+    }
+
+    let mut upstream_io = upstream_res
+        .upgrade()
+        .await
+        .context("上游 WebSocket 连接升级失败")?;
+
+    let tool_id = tool_id.to_string();
+    tokio::spawn(async move {
+        let client_io = match hyper::upgrade::on(req).await {
+            Ok(upgraded) => upgraded,
+            Err(e) => {
+                tracing::warn!(tool_id = %tool_id, error = ?e, "客户端 WebSocket 连接升级失败");
+                return;
+            }
+        };
+        let mut client_io = TokioIo::new(client_io);
+
+        tokio::select! {
+            result = tokio::io::copy_bidirectional(&mut client_io, &mut upstream_io) => {
+                match result {
+                    Ok((sent, received)) => tracing::debug!(
+                        tool_id = %tool_id,
+                        sent,
+                        received,
+                        "WebSocket 隧道已关闭"
+                    ),
+                    Err(e) => tracing::debug!(
+                        tool_id = %tool_id,
+                        error = %e,
+                        "WebSocket 隧道异常断开"
+                    ),
+                }
+            }
+            _ = shutdown.cancelled() => {
+                tracing::debug!(tool_id = %tool_id, "代理停止，关闭 WebSocket 隧道");
+            }
+        }
+    });
+
+    response
+        .body(box_body(Full::new(Bytes::new())))
+        .context("构建 WebSocket 握手响应失败")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_upgrade_request() {
+        let mut headers = HeaderMap::new();
+        headers.insert("connection", "keep-alive, Upgrade".parse().unwrap());
+        headers.insert("upgrade", "WebSocket".parse().unwrap());
+        assert!(is_upgrade_request(&headers));
+
+        headers.insert("upgrade", "h2c".parse().unwrap());
+        assert!(!is_upgrade_request(&headers));
+
+        let mut plain = HeaderMap::new();
+        plain.insert("upgrade", "websocket".parse().unwrap());
+        assert!(!is_upgrade_request(&plain));
+    }
+
+    /// 读取到空行为止的 HTTP 头
+    async fn read_head(stream: &mut tokio::net::TcpStream) -> String {
+        use tokio::io::AsyncReadExt;
+        let mut head = Vec::new();
+        let mut byte = [0u8; 1];
+        while !head.ends_with(b"\r\n\r\n") {
+            stream.read_exact(&mut byte).await.unwrap();
+            head.push(byte[0]);
+        }
+        String::from_utf8(head).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_tunnel_injects_real_key_and_copies_frames() {
+        use crate::models::proxy_config::ToolProxyConfig;
+        use crate::services::proxy::{create_request_processor, ProxyInstance};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::{TcpListener, TcpStream};
+
+        // 上游：校验认证头后完成握手，之后回显收到的数据
+        let upstream = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let upstream_port = upstream.local_addr().unwrap().port();
+        let upstream_task = tokio::spawn(async move {
+            let (mut stream, _) = upstream.accept().await.unwrap();
+            let head = read_head(&mut stream).await.to_ascii_lowercase();
+            assert!(head.starts_with("get /v1/realtime?model=x "));
+            assert!(head.contains("authorization: bearer sk-real"));
+            assert!(head.contains("sec-websocket-key: dghlihnhbxbszsbub25jzq=="));
+            stream
+                .write_all(b"HTTP/1.1 101 Switching Protocols\r\nConnection: Upgrade\r\nUpgrade: websocket\r\nSec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n\r\n")
+                .await
+                .unwrap();
+            let mut buf = [0u8; 4];
+            stream.read_exact(&mut buf).await.unwrap();
+            stream.write_all(&buf).await.unwrap();
+        });
+
+        let proxy_port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let mut config = ToolProxyConfig::new(proxy_port);
+        config.local_api_key = Some("sk-local".to_string());
+        config.real_api_key = Some("sk-real".to_string());
+        config.real_base_url = Some(format!("http://127.0.0.1:{upstream_port}"));
+        let instance = ProxyInstance::new(
+            "claude-code".to_string(),
+            config,
+            create_request_processor("claude-code").unwrap(),
+        );
+        instance.start().await.unwrap();
+
+        let mut client = TcpStream::connect(("127.0.0.1", proxy_port)).await.unwrap();
+        client
+            .write_all(
+                b"GET /v1/realtime?model=x HTTP/1.1\r\nHost: localhost\r\nConnection: Upgrade\r\nUpgrade: websocket\r\nSec-WebSocket-Version: 13\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nx-api-key: sk-local\r\n\r\n",
+            )
+            .await
+            .unwrap();
+        let head = read_head(&mut client).await;
+        assert!(head.starts_with("HTTP/1.1 101"), "{head}");
+        assert!(head.contains("s3pPLMBiTxaQ9kYGzzhZRbK+xOo="));
+
+        client.write_all(b"ping").await.unwrap();
+        let mut echoed = [0u8; 4];
+        client.read_exact(&mut echoed).await.unwrap();
+        assert_eq!(&echoed, b"ping");
+
+        upstream_task.await.unwrap();
+        instance.stop().await.unwrap();
+    }
+}
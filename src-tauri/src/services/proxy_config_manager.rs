@@ -1,14 +1,66 @@
 //! 透明代理配置管理器
 
+use crate::core::secrets::SecretStore;
+use crate::data::compat;
 use crate::data::DataManager;
 use crate::models::proxy_config::ProxyStore;
 use crate::models::proxy_config::ToolProxyConfig;
+use crate::services::audit::{self, AuditAction};
 use anyhow::{Context, Result};
-use std::path::PathBuf;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+
+/// 含凭证的配置字段（审计时单独标记）
+const CREDENTIAL_FIELDS: [&str; 9] = [
+    "local_api_key",
+    "client_keys",
+    "real_api_key",
+    "key_pool",
+    "upstreams",
+    "routing_rules",
+    "tavily_api_key",
+    "original_amp_secrets",
+    "egress_proxy",
+];
+
+/// 记录代理配置变更审计（配置未变化时不记录）
+fn audit_config_change(
+    audit_dir: &Path,
+    tool_id: &str,
+    previous: Option<&ToolProxyConfig>,
+    config: &ToolProxyConfig,
+    reset: bool,
+) {
+    let before = previous
+        .and_then(|p| serde_json::to_value(p).ok())
+        .unwrap_or_default();
+    let after = serde_json::to_value(config).unwrap_or_default();
+    let changed = audit::changed_fields(&before, &after);
+    if changed.is_empty() {
+        return;
+    }
+    let credential_fields: Vec<&String> = changed
+        .iter()
+        .filter(|f| CREDENTIAL_FIELDS.contains(&f.as_str()))
+        .collect();
+    audit::record_in(
+        audit_dir,
+        AuditAction::ProxyConfigUpdated,
+        tool_id,
+        config.real_profile_name.as_deref(),
+        json!({
+            "reset": reset,
+            "changed_fields": changed,
+            "credential_fields": credential_fields,
+        }),
+    );
+}
 
 pub struct ProxyConfigManager {
     data_manager: DataManager,
     proxy_path: PathBuf,
+    /// 审计日志目录
+    audit_dir: PathBuf,
 }
 
 impl ProxyConfigManager {
@@ -20,6 +72,7 @@ impl ProxyConfigManager {
         Ok(Self {
             data_manager: DataManager::new(),
             proxy_path: duckcoding_dir.join("proxy.json"),
+            audit_dir: audit::audit_dir()?,
         })
     }
 
@@ -35,16 +88,27 @@ impl ProxyConfigManager {
             .read(&self.proxy_path)
             .context("读取 proxy.json 失败")?;
 
-        serde_json::from_value(value).context("反序列化 ProxyStore 失败")
+        let (mut store, _): (ProxyStore, _) =
+            compat::from_value_tolerant(value).context("反序列化 ProxyStore 失败")?;
+        SecretStore::global().reveal_fields(&mut store);
+        Ok(store)
     }
 
-    /// 保存 proxy.json
+    /// 保存 proxy.json（密钥存入密钥存储，文件中只保留引用）
     pub fn save_proxy_store(&self, store: &ProxyStore) -> Result<()> {
-        let value = serde_json::to_value(store)?;
-        self.data_manager
-            .json()
-            .write(&self.proxy_path, &value)
-            .map_err(Into::into)
+        let secrets = SecretStore::global();
+        let mut sealed = store.clone();
+        secrets.seal_fields(&mut sealed)?;
+        let mut value = serde_json::to_value(&sealed)?;
+        let existing = self.data_manager.json().read(&self.proxy_path).ok();
+        if let Some(existing) = &existing {
+            compat::preserve_fields::<ProxyStore>(existing, &mut value);
+        }
+        self.data_manager.json().write(&self.proxy_path, &value)?;
+        if let Some(existing) = &existing {
+            secrets.prune(existing, &value);
+        }
+        Ok(())
     }
 
     /// 获取指定工具的代理配置
@@ -56,16 +120,28 @@ impl ProxyConfigManager {
     /// 更新指定工具的代理配置
     pub fn update_config(&self, tool_id: &str, config: ToolProxyConfig) -> Result<()> {
         let mut store = self.load_proxy_store()?;
-        store.update_config(tool_id, config);
-        self.save_proxy_store(&store)
+        let previous = store.get_config(tool_id).cloned();
+        store.update_config(tool_id, config.clone());
+        self.save_proxy_store(&store)?;
+        audit_config_change(&self.audit_dir, tool_id, previous.as_ref(), &config, false);
+        Ok(())
     }
 
     /// 删除指定工具的代理配置（重置为默认）
     pub fn reset_config(&self, tool_id: &str) -> Result<()> {
         let mut store = self.load_proxy_store()?;
+        let previous = store.get_config(tool_id).cloned();
         let default_port = ToolProxyConfig::default_port(tool_id);
-        store.update_config(tool_id, ToolProxyConfig::new(default_port));
-        self.save_proxy_store(&store)
+        let config = ToolProxyConfig::new(default_port);
+        store.update_config(tool_id, config.clone());
+        self.save_proxy_store(&store)?;
+        audit_config_change(&self.audit_dir, tool_id, previous.as_ref(), &config, true);
+        Ok(())
+    }
+
+    /// proxy.json 路径
+    pub fn store_path(&self) -> &Path {
+        &self.proxy_path
     }
 
     /// 获取所有工具的配置
@@ -0,0 +1,536 @@
+//! 异常退出恢复
+//!
+//! 启动时持有 `instance.lock` 文件锁，并在其中写入运行标记（pid、启动时间），
+//! 正常退出时清空标记。下次启动若发现标记仍在，说明上次为异常退出（崩溃、强杀、断电），
+//! 此时执行恢复流程：
+//!
+//! 1. 回放并检查点 SQLite WAL（Token 统计、会话数据库）
+//! 2. 还原被代理改写但未来得及还原的工具配置
+//!
+//! 恢复结果保存为报告，供前端启动后展示。
+//!
+//! 此外运行期间定期检查「工具配置指向本机代理端口，但对应代理并未运行」的不一致状态
+//! （例如代理异常停止后 CLI 无法连接），按设置自动还原或仅上报给前端。
+
+use crate::data::DataManager;
+use crate::models::proxy_config::ToolProxyConfig;
+use crate::services::amp_native_config::{self, AmpConfigBackup};
+use crate::services::profile_manager::ProfileManager;
+use crate::services::proxy::ProxyManager;
+use crate::services::proxy_config_manager::ProxyConfigManager;
+use crate::utils::config::config_dir;
+use anyhow::{Context, Result};
+use fs2::FileExt;
+use once_cell::sync::{Lazy, OnceCell};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// 实例锁文件名
+const INSTANCE_LOCK_FILE: &str = "instance.lock";
+
+/// 启动时需要检查点的数据库
+const DATABASES: [&str; 2] = ["token_stats.db", "sessions.db"];
+
+/// 代理内置 Profile 名称前缀
+const PROXY_PROFILE_PREFIX: &str = "dc_proxy_";
+
+/// 配置一致性检查间隔
+const CONSISTENCY_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 进程存活期间持有的实例锁（进程退出时由系统释放）
+static INSTANCE_LOCK: OnceCell<File> = OnceCell::new();
+
+/// 最近一次恢复报告（用户确认后清空）
+static LAST_REPORT: Lazy<RwLock<Option<RecoveryReport>>> = Lazy::new(|| RwLock::new(None));
+
+/// 运行标记
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunMarker {
+    pub pid: u32,
+    /// 启动时间（Unix 时间戳，秒）
+    pub started_at: i64,
+}
+
+/// 单项恢复操作
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoveryAction {
+    /// 操作类型："db_checkpoint" | "tool_config_restore"
+    pub kind: String,
+    /// 操作对象（数据库文件名或工具 ID）
+    pub target: String,
+    pub success: bool,
+    pub message: String,
+}
+
+/// 恢复报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoveryReport {
+    /// 上次异常退出的运行标记
+    pub previous_run: RunMarker,
+    /// 恢复时间（Unix 时间戳，秒）
+    pub recovered_at: i64,
+    pub actions: Vec<RecoveryAction>,
+}
+
+/// 实例锁获取结果
+#[derive(Debug)]
+enum LockOutcome {
+    /// 获取成功，附带上次遗留的运行标记（存在即表示异常退出）
+    Acquired {
+        file: File,
+        previous: Option<RunMarker>,
+    },
+    /// 另一个实例正在运行
+    HeldByOther,
+}
+
+/// 获取实例锁并读取遗留标记
+fn acquire_instance_lock(path: &Path) -> Result<LockOutcome> {
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+        .with_context(|| format!("打开实例锁文件失败: {}", path.display()))?;
+
+    if file.try_lock_exclusive().is_err() {
+        return Ok(LockOutcome::HeldByOther);
+    }
+
+    let mut content = String::new();
+    file.read_to_string(&mut content)
+        .context("读取实例锁文件失败")?;
+    let previous = serde_json::from_str::<RunMarker>(content.trim()).ok();
+
+    Ok(LockOutcome::Acquired { file, previous })
+}
+
+/// 写入运行标记
+fn write_marker(file: &mut File, marker: &RunMarker) -> Result<()> {
+    file.set_len(0)?;
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(serde_json::to_string(marker)?.as_bytes())?;
+    file.sync_all()?;
+    Ok(())
+}
+
+/// 清空运行标记（表示正常退出）
+fn clear_marker(file: &File) -> Result<()> {
+    file.set_len(0)?;
+    file.sync_all()?;
+    Ok(())
+}
+
+/// 启动时执行异常退出检测与恢复
+///
+/// 必须在代理自启动之前调用；另一个实例正在运行时不做任何操作。
+/// 返回本次恢复报告（上次为正常退出时返回 None）
+pub fn run_startup_recovery() -> Option<RecoveryReport> {
+    let lock_path = match config_dir() {
+        Ok(dir) => dir.join(INSTANCE_LOCK_FILE),
+        Err(e) => {
+            tracing::warn!(error = ?e, "获取配置目录失败，跳过异常退出检测");
+            return None;
+        }
+    };
+
+    let (mut file, previous) = match acquire_instance_lock(&lock_path) {
+        Ok(LockOutcome::Acquired { file, previous }) => (file, previous),
+        Ok(LockOutcome::HeldByOther) => {
+            tracing::info!("检测到其他 DuckCoding 实例正在运行，跳过异常退出检测");
+            return None;
+        }
+        Err(e) => {
+            tracing::warn!(error = ?e, "获取实例锁失败，跳过异常退出检测");
+            return None;
+        }
+    };
+
+    let report = previous.map(|previous_run| {
+        tracing::warn!(
+            pid = previous_run.pid,
+            started_at = previous_run.started_at,
+            "检测到上次异常退出，开始恢复"
+        );
+        let report = recover(previous_run);
+        tracing::info!(
+            actions = report.actions.len(),
+            failed = report.actions.iter().filter(|a| !a.success).count(),
+            "异常退出恢复完成"
+        );
+        report
+    });
+
+    let marker = RunMarker {
+        pid: std::process::id(),
+        started_at: chrono::Utc::now().timestamp(),
+    };
+    if let Err(e) = write_marker(&mut file, &marker) {
+        tracing::warn!(error = ?e, "写入运行标记失败");
+    }
+    let _ = INSTANCE_LOCK.set(file);
+
+    if let Ok(mut last) = LAST_REPORT.write() {
+        *last = report.clone();
+    }
+    report
+}
+
+/// 标记正常退出（在应用关闭清理完成后调用）
+pub fn mark_clean_shutdown() {
+    if let Some(file) = INSTANCE_LOCK.get() {
+        if let Err(e) = clear_marker(file) {
+            tracing::warn!(error = ?e, "清除运行标记失败");
+        }
+    }
+}
+
+/// 获取最近一次恢复报告
+pub fn last_recovery_report() -> Option<RecoveryReport> {
+    LAST_REPORT.read().ok().and_then(|r| r.clone())
+}
+
+/// 用户确认后清空恢复报告
+pub fn dismiss_recovery_report() {
+    if let Ok(mut last) = LAST_REPORT.write() {
+        *last = None;
+    }
+}
+
+/// 执行恢复流程
+fn recover(previous_run: RunMarker) -> RecoveryReport {
+    let mut actions = Vec::new();
+
+    if let Ok(dir) = config_dir() {
+        for db_name in DATABASES {
+            let db_path = dir.join(db_name);
+            if !db_path.exists() {
+                continue;
+            }
+            let result = checkpoint_database(&db_path);
+            actions.push(RecoveryAction {
+                kind: "db_checkpoint".to_string(),
+                target: db_name.to_string(),
+                success: result.is_ok(),
+                message: match result {
+                    Ok(()) => "已回放 WAL 并完成检查点".to_string(),
+                    Err(e) => format!("检查点失败: {}", e),
+                },
+            });
+        }
+    }
+
+    // 会被代理改写原生配置的工具（含支持代理的自定义工具）
+    let tool_ids = ProxyConfigManager::new()
+        .and_then(|mgr| mgr.load_proxy_store())
+        .unwrap_or_default()
+        .tool_ids();
+    for tool_id in tool_ids {
+        let result = restore_tool_config(&tool_id, true);
+        let message = match result {
+            Ok(None) => continue,
+            Ok(Some(message)) => Ok(message),
+            Err(e) => Err(format!("还原失败: {}", e)),
+        };
+        actions.push(RecoveryAction {
+            kind: "tool_config_restore".to_string(),
+            target: tool_id,
+            success: message.is_ok(),
+            message: message.unwrap_or_else(|e| e),
+        });
+    }
+
+    RecoveryReport {
+        previous_run,
+        recovered_at: chrono::Utc::now().timestamp(),
+        actions,
+    }
+}
+
+/// 回放 WAL 并截断（打开连接时 SQLite 会自动回放未合并的 WAL）
+fn checkpoint_database(db_path: &Path) -> Result<()> {
+    let db = DataManager::global().sqlite(db_path)?;
+    db.execute_raw("PRAGMA wal_checkpoint(TRUNCATE)")?;
+    Ok(())
+}
+
+/// 还原被代理改写的工具原生配置（使用代理启动时保存在 proxy.json 中的备份）
+///
+/// `skip_auto_start` 为 true 时跳过配置了自启动的代理（其配置指向即将重新启动的代理，属于预期状态）。
+/// 返回还原说明；无需还原时返回 None
+pub fn restore_tool_config(tool_id: &str, skip_auto_start: bool) -> Result<Option<String>> {
+    let proxy_mgr = ProxyConfigManager::new()?;
+    let Some(mut config) = proxy_mgr.get_config(tool_id)? else {
+        return Ok(None);
+    };
+    if skip_auto_start && config.enabled && config.auto_start {
+        return Ok(None);
+    }
+
+    if tool_id == "amp-code" {
+        if config.original_amp_settings.is_none() && config.original_amp_secrets.is_none() {
+            return Ok(None);
+        }
+        let backup = AmpConfigBackup {
+            settings: config.original_amp_settings.take(),
+            secrets: config.original_amp_secrets.take(),
+        };
+        amp_native_config::restore_amp_config(&backup)?;
+        proxy_mgr.update_config(tool_id, config)?;
+        tracing::info!(tool_id = %tool_id, "已还原 AMP Code 原生配置");
+        return Ok(Some("已还原 AMP Code 原生配置".to_string()));
+    }
+
+    let Some(profile_name) = config.original_active_profile.take() else {
+        return Ok(None);
+    };
+    ProfileManager::new()?.activate_profile(tool_id, &profile_name)?;
+    proxy_mgr.update_config(tool_id, config)?;
+    tracing::info!(tool_id = %tool_id, profile = %profile_name, "已还原到原始 Profile");
+    Ok(Some(format!("已还原到 Profile: {}", profile_name)))
+}
+
+/// 指向未运行代理的工具配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaleProxyConfig {
+    pub tool_id: String,
+    /// 代理配置的监听端口
+    pub port: u16,
+    /// 原生配置中的 Base URL（读取失败或未配置时为 None）
+    pub base_url: Option<String>,
+    /// 当前激活的 Profile（AMP Code 无 Profile）
+    pub active_profile: Option<String>,
+    /// 是否存在可用于还原的备份
+    pub restorable: bool,
+}
+
+/// 判断 URL 是否指向本机指定端口
+pub fn points_to_local_port(url: &str, port: u16) -> bool {
+    let Ok(parsed) = url::Url::parse(url.trim()) else {
+        return false;
+    };
+    matches!(
+        parsed.host_str(),
+        Some("127.0.0.1" | "localhost" | "0.0.0.0" | "[::1]")
+    ) && parsed.port_or_known_default() == Some(port)
+}
+
+/// 检测配置指向本机代理但代理未运行的工具
+pub async fn find_stale_proxy_configs(manager: &ProxyManager) -> Result<Vec<StaleProxyConfig>> {
+    let store = ProxyConfigManager::new()?.load_proxy_store()?;
+    let profile_mgr = ProfileManager::new()?;
+
+    let mut stale = Vec::new();
+    for tool_id in store.tool_ids() {
+        if manager.is_running(&tool_id).await {
+            continue;
+        }
+        let Some(config) = store.get_config(&tool_id) else {
+            continue;
+        };
+        // 端口仍有进程监听（如另一个 DuckCoding 实例的代理）时不视为失效
+        if port_in_use(config.port).await {
+            continue;
+        }
+        stale.extend(inspect_tool_config(&tool_id, config, &profile_mgr));
+    }
+    Ok(stale)
+}
+
+/// 本机端口是否有进程在监听
+async fn port_in_use(port: u16) -> bool {
+    tokio::time::timeout(
+        Duration::from_millis(500),
+        tokio::net::TcpStream::connect(("127.0.0.1", port)),
+    )
+    .await
+    .is_ok_and(|conn| conn.is_ok())
+}
+
+/// 检查单个工具的原生配置是否仍指向代理
+fn inspect_tool_config(
+    tool_id: &str,
+    config: &ToolProxyConfig,
+    profile_mgr: &ProfileManager,
+) -> Option<StaleProxyConfig> {
+    let base_url = read_native_base_url(tool_id, profile_mgr)
+        .map_err(|e| tracing::debug!(tool_id = %tool_id, error = ?e, "读取原生配置 Base URL 失败"))
+        .ok()
+        .filter(|url| !url.is_empty());
+    let active_profile = if tool_id == "amp-code" {
+        None
+    } else {
+        profile_mgr.get_active_profile_name(tool_id).ok().flatten()
+    };
+
+    let points_to_proxy = base_url
+        .as_deref()
+        .is_some_and(|url| points_to_local_port(url, config.port));
+    let proxy_profile_active = active_profile
+        .as_deref()
+        .is_some_and(|name| name.starts_with(PROXY_PROFILE_PREFIX));
+    if !points_to_proxy && !proxy_profile_active {
+        return None;
+    }
+
+    let restorable = if tool_id == "amp-code" {
+        config.original_amp_settings.is_some() || config.original_amp_secrets.is_some()
+    } else {
+        config.original_active_profile.is_some()
+    };
+
+    Some(StaleProxyConfig {
+        tool_id: tool_id.to_string(),
+        port: config.port,
+        base_url,
+        active_profile,
+        restorable,
+    })
+}
+
+/// 读取工具原生配置中的 Base URL
+fn read_native_base_url(tool_id: &str, profile_mgr: &ProfileManager) -> Result<String> {
+    if tool_id == "amp-code" {
+        let backup = amp_native_config::backup_amp_config()?;
+        return Ok(backup
+            .settings
+            .as_ref()
+            .and_then(|s| s.get("amp.url"))
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string());
+    }
+    profile_mgr.read_native_base_url(tool_id)
+}
+
+/// 是否开启自动还原
+fn auto_restore_enabled() -> bool {
+    ProxyConfigManager::new()
+        .and_then(|mgr| mgr.load_proxy_store())
+        .map(|store| store.auto_restore_stale_config)
+        .unwrap_or(true)
+}
+
+/// 定期检查配置一致性（随应用运行，不会返回）
+///
+/// 同一工具需连续两次检测到不一致才处理，避免与正在启动或停止的代理竞争
+pub async fn run_consistency_monitor(manager: Arc<ProxyManager>) {
+    let mut interval = tokio::time::interval(CONSISTENCY_CHECK_INTERVAL);
+    // 首个 tick 立即触发，跳过以等待自启动代理就绪
+    interval.tick().await;
+
+    let mut suspected: HashSet<String> = HashSet::new();
+    loop {
+        interval.tick().await;
+
+        let stale = match find_stale_proxy_configs(&manager).await {
+            Ok(stale) => stale,
+            Err(e) => {
+                tracing::warn!(error = ?e, "配置一致性检查失败");
+                continue;
+            }
+        };
+
+        let confirmed: Vec<&StaleProxyConfig> = stale
+            .iter()
+            .filter(|item| suspected.contains(&item.tool_id))
+            .collect();
+        suspected = stale.iter().map(|item| item.tool_id.clone()).collect();
+        if confirmed.is_empty() {
+            continue;
+        }
+
+        let auto_restore = auto_restore_enabled();
+        for item in confirmed {
+            if !auto_restore || !item.restorable {
+                tracing::warn!(
+                    tool_id = %item.tool_id,
+                    port = item.port,
+                    base_url = ?item.base_url,
+                    restorable = item.restorable,
+                    "工具配置指向未运行的代理"
+                );
+                continue;
+            }
+            match restore_tool_config(&item.tool_id, false) {
+                Ok(Some(message)) => {
+                    tracing::warn!(tool_id = %item.tool_id, message = %message, "代理未运行，已自动还原工具配置")
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    tracing::error!(tool_id = %item.tool_id, error = ?e, "自动还原工具配置失败")
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn acquire(path: &Path) -> (File, Option<RunMarker>) {
+        match acquire_instance_lock(path).unwrap() {
+            LockOutcome::Acquired { file, previous } => (file, previous),
+            LockOutcome::HeldByOther => panic!("lock unexpectedly held"),
+        }
+    }
+
+    #[test]
+    fn test_detects_dirty_shutdown() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(INSTANCE_LOCK_FILE);
+        let marker = RunMarker {
+            pid: 42,
+            started_at: 1_700_000_000,
+        };
+
+        // 首次启动：无遗留标记
+        let (mut file, previous) = acquire(&path);
+        assert!(previous.is_none());
+        write_marker(&mut file, &marker).unwrap();
+
+        // 未清除标记即退出（模拟崩溃）
+        drop(file);
+        let (file, previous) = acquire(&path);
+        assert_eq!(previous, Some(marker));
+
+        // 正常退出后不再视为异常
+        clear_marker(&file).unwrap();
+        drop(file);
+        let (_file, previous) = acquire(&path);
+        assert!(previous.is_none());
+    }
+
+    #[test]
+    fn test_lock_held_by_other_instance() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(INSTANCE_LOCK_FILE);
+
+        let (_held, _) = acquire(&path);
+        assert!(matches!(
+            acquire_instance_lock(&path).unwrap(),
+            LockOutcome::HeldByOther
+        ));
+    }
+
+    #[test]
+    fn test_points_to_local_port() {
+        assert!(points_to_local_port("http://127.0.0.1:8787", 8787));
+        assert!(points_to_local_port("http://localhost:8788/v1", 8788));
+        assert!(points_to_local_port(" http://[::1]:8789 ", 8789));
+        assert!(!points_to_local_port("http://127.0.0.1:8787", 8788));
+        assert!(!points_to_local_port("https://api.anthropic.com", 8787));
+        assert!(!points_to_local_port(
+            "https://relay.example.com:8787",
+            8787
+        ));
+        assert!(!points_to_local_port("not a url", 8787));
+    }
+}
@@ -0,0 +1,440 @@
+//! 全局搜索
+//!
+//! 为命令面板提供统一的搜索入口，覆盖：
+//!
+//! - Profile：名称、Base URL、Provider、模型
+//! - 会话：会话 ID、备注、自定义 Profile 名称
+//! - 设置项：全局配置与透明代理配置的键路径（不索引取值，避免泄露密钥）
+//! - 请求日志：会话 ID、模型、配置名称、上游请求 ID（数据量大，直接查询数据库）
+//!
+//! Profile / 会话 / 设置项构建为内存索引并短时缓存，配置变更后可调用
+//! [`invalidate_search_index`] 使缓存失效。
+
+use crate::data::DataManager;
+use crate::services::profile_manager::ProfileManager;
+use crate::services::proxy_config_manager::ProxyConfigManager;
+use crate::services::session::SESSION_MANAGER;
+use crate::utils::config::{config_dir, read_global_config};
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+/// 内存索引缓存时长
+const INDEX_TTL: Duration = Duration::from_secs(30);
+
+/// 索引的最近会话数量上限
+const MAX_INDEXED_SESSIONS: usize = 500;
+
+/// 默认返回结果数
+const DEFAULT_LIMIT: usize = 20;
+
+/// 请求日志搜索的最短关键字长度（过短时 LIKE 查询命中过多且无意义）
+const MIN_LOG_QUERY_LEN: usize = 3;
+
+/// 标题字段命中的权重倍数
+const TITLE_BOOST: u32 = 2;
+
+/// 内存索引及其构建时间
+type CachedIndex = Option<(Instant, Arc<SearchIndex>)>;
+
+/// 缓存的内存索引
+static INDEX_CACHE: Lazy<RwLock<CachedIndex>> = Lazy::new(|| RwLock::new(None));
+
+/// 搜索结果类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchKind {
+    Profile,
+    Session,
+    Setting,
+    Log,
+}
+
+/// 单条搜索结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResult {
+    pub kind: SearchKind,
+    /// 结果标识（Profile 名称、会话 ID、设置键路径、日志 ID）
+    pub id: String,
+    pub title: String,
+    pub subtitle: Option<String>,
+    pub tool_id: Option<String>,
+    /// 相关度得分（越高越相关）
+    pub score: u32,
+}
+
+/// 搜索参数
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchQuery {
+    pub query: String,
+    /// 只搜索指定类型（为空时搜索全部）
+    #[serde(default)]
+    pub kinds: Vec<SearchKind>,
+    /// 最大返回数量（默认 20）
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// 索引文档
+#[derive(Debug, Clone)]
+struct SearchDocument {
+    kind: SearchKind,
+    id: String,
+    title: String,
+    subtitle: Option<String>,
+    tool_id: Option<String>,
+    /// 小写后的可检索字段，首个字段为标题
+    fields: Vec<String>,
+}
+
+impl SearchDocument {
+    fn new(kind: SearchKind, id: impl Into<String>, title: impl Into<String>) -> Self {
+        let title = title.into();
+        Self {
+            kind,
+            id: id.into(),
+            fields: vec![title.to_lowercase()],
+            title,
+            subtitle: None,
+            tool_id: None,
+        }
+    }
+
+    fn subtitle(mut self, subtitle: impl Into<String>) -> Self {
+        self.subtitle = Some(subtitle.into());
+        self
+    }
+
+    fn tool(mut self, tool_id: impl Into<String>) -> Self {
+        self.tool_id = Some(tool_id.into());
+        self
+    }
+
+    /// 追加检索字段（空值忽略）
+    fn field(mut self, value: Option<&str>) -> Self {
+        if let Some(value) = value.filter(|v| !v.is_empty()) {
+            self.fields.push(value.to_lowercase());
+        }
+        self
+    }
+
+    /// 计算文档得分：每个关键词都须命中某个字段，得分为各关键词最佳命中之和
+    fn score(&self, terms: &[String]) -> Option<u32> {
+        terms.iter().try_fold(0, |total, term| {
+            let best = self
+                .fields
+                .iter()
+                .enumerate()
+                .filter_map(|(i, field)| {
+                    let score = match_score(field, term)?;
+                    Some(if i == 0 { score * TITLE_BOOST } else { score })
+                })
+                .max()?;
+            Some(total + best)
+        })
+    }
+
+    fn to_result(&self, score: u32) -> SearchResult {
+        SearchResult {
+            kind: self.kind,
+            id: self.id.clone(),
+            title: self.title.clone(),
+            subtitle: self.subtitle.clone(),
+            tool_id: self.tool_id.clone(),
+            score,
+        }
+    }
+}
+
+/// 单个字段的匹配得分（均已小写）
+///
+/// 完全匹配 > 前缀 > 单词前缀 > 子串 > 按序包含所有字符（模糊）
+fn match_score(text: &str, term: &str) -> Option<u32> {
+    if text == term {
+        Some(100)
+    } else if text.starts_with(term) {
+        Some(80)
+    } else if text
+        .split(|c: char| !c.is_alphanumeric())
+        .any(|word| word.starts_with(term))
+    {
+        Some(60)
+    } else if text.contains(term) {
+        Some(40)
+    } else if is_subsequence(text, term) {
+        Some(10)
+    } else {
+        None
+    }
+}
+
+fn is_subsequence(text: &str, term: &str) -> bool {
+    let mut chars = text.chars();
+    term.chars().all(|c| chars.any(|t| t == c))
+}
+
+/// 拆分关键词
+fn split_terms(query: &str) -> Vec<String> {
+    query
+        .split_whitespace()
+        .map(|term| term.to_lowercase())
+        .collect()
+}
+
+/// 内存搜索索引
+#[derive(Debug, Default)]
+struct SearchIndex {
+    documents: Vec<SearchDocument>,
+}
+
+impl SearchIndex {
+    /// 从各数据源构建索引（单个数据源失败不影响其他数据源）
+    fn build() -> Self {
+        let mut documents = Vec::new();
+        for (source, result) in [
+            ("profiles", index_profiles()),
+            ("sessions", index_sessions()),
+            ("settings", index_settings()),
+        ] {
+            match result {
+                Ok(docs) => documents.extend(docs),
+                Err(e) => tracing::warn!(source = source, error = ?e, "构建搜索索引失败"),
+            }
+        }
+        Self { documents }
+    }
+
+    fn search(&self, terms: &[String], kinds: &[SearchKind]) -> Vec<SearchResult> {
+        self.documents
+            .iter()
+            .filter(|doc| kinds.is_empty() || kinds.contains(&doc.kind))
+            .filter_map(|doc| doc.score(terms).map(|score| doc.to_result(score)))
+            .collect()
+    }
+}
+
+fn index_profiles() -> Result<Vec<SearchDocument>> {
+    let descriptors = ProfileManager::new()?.list_all_descriptors()?;
+    Ok(descriptors
+        .into_iter()
+        .map(|p| {
+            SearchDocument::new(
+                SearchKind::Profile,
+                format!("{}/{}", p.tool_id, p.name),
+                p.name.clone(),
+            )
+            .subtitle(p.base_url.clone())
+            .field(Some(&p.base_url))
+            .field(p.provider.as_deref())
+            .field(p.model.as_deref())
+            .tool(p.tool_id)
+        })
+        .collect())
+}
+
+fn index_sessions() -> Result<Vec<SearchDocument>> {
+    let sessions = SESSION_MANAGER.list_recent_sessions(MAX_INDEXED_SESSIONS)?;
+    Ok(sessions
+        .into_iter()
+        .map(|s| {
+            let title = s.note.clone().unwrap_or_else(|| s.display_id.clone());
+            SearchDocument::new(SearchKind::Session, s.session_id.clone(), title)
+                .subtitle(s.display_id.clone())
+                .field(Some(&s.display_id))
+                .field(Some(&s.session_id))
+                .field(s.custom_profile_name.as_deref())
+                .tool(s.tool_id)
+        })
+        .collect())
+}
+
+fn index_settings() -> Result<Vec<SearchDocument>> {
+    let mut keys = Vec::new();
+
+    if let Some(config) = read_global_config().map_err(|e| anyhow!(e))? {
+        flatten_keys(&serde_json::to_value(config)?, "", &mut keys);
+    }
+    let mut proxy_store = serde_json::to_value(ProxyConfigManager::new()?.load_proxy_store()?)?;
+    if let Some(obj) = proxy_store.as_object_mut() {
+        obj.remove("metadata");
+    }
+    flatten_keys(&proxy_store, "proxy", &mut keys);
+
+    Ok(keys
+        .into_iter()
+        .map(|key| {
+            let leaf = key.rsplit('.').next().unwrap_or(&key).to_string();
+            SearchDocument::new(SearchKind::Setting, key.clone(), leaf)
+                .subtitle(key.clone())
+                .field(Some(&key))
+        })
+        .collect())
+}
+
+/// 展开 JSON 对象的键路径（只收集对象键，不展开数组，不记录取值）
+fn flatten_keys(value: &Value, prefix: &str, out: &mut Vec<String>) {
+    let Some(obj) = value.as_object() else {
+        return;
+    };
+    for (key, child) in obj {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        if child.is_object() {
+            flatten_keys(child, &path, out);
+        } else {
+            out.push(path);
+        }
+    }
+}
+
+/// 获取内存索引（过期时重建）
+fn cached_index() -> Arc<SearchIndex> {
+    if let Ok(cache) = INDEX_CACHE.read() {
+        if let Some((built_at, index)) = cache.as_ref() {
+            if built_at.elapsed() < INDEX_TTL {
+                return Arc::clone(index);
+            }
+        }
+    }
+
+    let index = Arc::new(SearchIndex::build());
+    if let Ok(mut cache) = INDEX_CACHE.write() {
+        *cache = Some((Instant::now(), Arc::clone(&index)));
+    }
+    index
+}
+
+/// 使内存索引失效（下次搜索时重建）
+pub fn invalidate_search_index() {
+    if let Ok(mut cache) = INDEX_CACHE.write() {
+        *cache = None;
+    }
+}
+
+/// 搜索请求日志
+fn search_logs(query: &str, limit: usize) -> Result<Vec<SearchResult>> {
+    let db_path = config_dir().map_err(|e| anyhow!(e))?.join("token_stats.db");
+    if !db_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let escaped = query
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    let pattern = format!("%{}%", escaped);
+    let limit = limit.to_string();
+    let rows = DataManager::global().sqlite(&db_path)?.query(
+        "SELECT id, tool_type, session_id, model, config_name, timestamp FROM token_logs \
+         WHERE session_id LIKE ?1 ESCAPE '\\' OR model LIKE ?1 ESCAPE '\\' \
+         OR config_name LIKE ?1 ESCAPE '\\' OR upstream_headers LIKE ?1 ESCAPE '\\' \
+         ORDER BY timestamp DESC LIMIT ?2",
+        &[&pattern, &limit],
+    )?;
+
+    let term = query.to_lowercase();
+    Ok(rows
+        .iter()
+        .filter_map(|row| {
+            let text = |i: usize| row.values.get(i).and_then(|v| v.as_str()).unwrap_or("");
+            let id = row.values.first()?.as_i64()?;
+            let (session_id, model, config_name) = (text(2), text(3), text(4));
+            let doc = SearchDocument::new(SearchKind::Log, id.to_string(), model)
+                .subtitle(format!("{} · {}", config_name, session_id))
+                .field(Some(session_id))
+                .field(Some(config_name))
+                .tool(text(1));
+            // 仅命中响应头时给予基础分
+            let score = doc.score(std::slice::from_ref(&term)).unwrap_or(20);
+            Some(doc.to_result(score))
+        })
+        .collect())
+}
+
+/// 执行全局搜索，按得分降序返回
+pub fn global_search(query: &SearchQuery) -> Result<Vec<SearchResult>> {
+    let terms = split_terms(&query.query);
+    if terms.is_empty() {
+        return Ok(Vec::new());
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+
+    let mut results = cached_index().search(&terms, &query.kinds);
+
+    let trimmed = query.query.trim();
+    if (query.kinds.is_empty() || query.kinds.contains(&SearchKind::Log))
+        && trimmed.chars().count() >= MIN_LOG_QUERY_LEN
+    {
+        match search_logs(trimmed, limit) {
+            Ok(logs) => results.extend(logs),
+            Err(e) => tracing::warn!(error = ?e, "搜索请求日志失败"),
+        }
+    }
+
+    results.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.title.cmp(&b.title)));
+    results.truncate(limit);
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_score_ranking() {
+        assert_eq!(match_score("anyrouter", "anyrouter"), Some(100));
+        assert_eq!(match_score("anyrouter", "any"), Some(80));
+        assert_eq!(
+            match_score("https://api.anyrouter.top", "anyrouter"),
+            Some(60)
+        );
+        assert_eq!(match_score("myrelay", "relay"), Some(40));
+        assert_eq!(match_score("token_stats_config", "tsc"), Some(10));
+        assert_eq!(match_score("codex", "claude"), None);
+    }
+
+    #[test]
+    fn test_index_search_requires_all_terms() {
+        let index = SearchIndex {
+            documents: vec![
+                SearchDocument::new(SearchKind::Profile, "claude-code/work", "work")
+                    .field(Some("https://relay.example.com")),
+                SearchDocument::new(SearchKind::Setting, "proxy.claude-code.port", "port")
+                    .field(Some("proxy.claude-code.port")),
+            ],
+        };
+
+        let results = index.search(&split_terms("work relay"), &[]);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "claude-code/work");
+        assert_eq!(results[0].score, 100 * TITLE_BOOST + 60);
+
+        let results = index.search(&split_terms("port"), &[SearchKind::Profile]);
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn test_flatten_keys() {
+        let value = serde_json::json!({
+            "log_config": { "level": "info", "file": { "enabled": true } },
+            "proxy_bypass_urls": ["localhost"],
+        });
+        let mut keys = Vec::new();
+        flatten_keys(&value, "", &mut keys);
+        keys.sort();
+        assert_eq!(
+            keys,
+            vec![
+                "log_config.file.enabled",
+                "log_config.level",
+                "proxy_bypass_urls"
+            ]
+        );
+    }
+}
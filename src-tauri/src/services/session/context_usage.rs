@@ -0,0 +1,255 @@
+//! 会话上下文窗口占用
+//!
+//! 每次请求写入统计日志时，记录会话最近一次请求的提示词大小（输入 + 缓存创建 + 缓存读取），
+//! 即当前上下文占用；上下文上限取自价格模板中模型的 `context_window`（未设置时按内置模型族默认值）。
+//! - 占用首次达到提醒 / 临界比例时通过通知回调发送 `session-context-warning` 事件
+//! - 每个会话每个级别只提醒一次，占用回落到提醒比例以下（如压缩上下文后）时重置
+
+use crate::models::token_stats::{TokenLog, TokenStatsQuery};
+use crate::services::pricing::PRICING_MANAGER;
+use crate::services::session::ProxySession;
+use crate::services::token_stats::TokenStatsManager;
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Mutex, RwLock};
+
+/// 达到上下文窗口的该百分比时提醒
+const WARNING_PERCENT: f64 = 80.0;
+
+/// 达到上下文窗口的该百分比时视为即将截断
+const CRITICAL_PERCENT: f64 = 95.0;
+
+/// 从统计日志恢复占用时向前查找的请求数
+const LOG_LOOKBACK: u32 = 20;
+
+static CONTEXT_TRACKER: Lazy<ContextUsageTracker> = Lazy::new(ContextUsageTracker::default);
+
+/// 上下文占用级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub enum ContextUsageLevel {
+    /// 达到提醒比例
+    Warning,
+    /// 接近上限，工具即将压缩或截断上下文
+    Critical,
+}
+
+/// 会话的上下文窗口占用（`session-context-warning` 事件载荷）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct SessionContextUsage {
+    /// 完整会话 ID（会话表主键）
+    pub session_id: String,
+    pub tool_id: String,
+    pub model: String,
+    /// 最近一次请求的提示词 Token 数（输入 + 缓存创建 + 缓存读取）
+    pub prompt_tokens: i64,
+    /// 模型上下文窗口（未知模型为 None）
+    pub context_window: Option<i64>,
+    /// 占用百分比
+    pub percent: Option<f64>,
+    pub level: Option<ContextUsageLevel>,
+    /// 最近一次请求时间（Unix 时间戳，毫秒）
+    pub updated_at: i64,
+}
+
+impl SessionContextUsage {
+    fn from_log(session_id: &str, log: &TokenLog) -> Option<Self> {
+        let prompt_tokens = log.input_tokens + log.cache_creation_tokens + log.cache_read_tokens;
+        if log.request_status != "success" || prompt_tokens <= 0 {
+            return None;
+        }
+        let context_window = PRICING_MANAGER.context_window(
+            log.pricing_template_id.as_deref(),
+            Some(&log.tool_type),
+            &log.model,
+        );
+        let percent = context_window.map(|window| prompt_tokens as f64 * 100.0 / window as f64);
+        Some(Self {
+            session_id: session_id.to_string(),
+            tool_id: log.tool_type.clone(),
+            model: log.model.clone(),
+            prompt_tokens,
+            context_window,
+            percent,
+            level: percent.and_then(level_for),
+            updated_at: log.timestamp,
+        })
+    }
+}
+
+fn level_for(percent: f64) -> Option<ContextUsageLevel> {
+    if percent >= CRITICAL_PERCENT {
+        Some(ContextUsageLevel::Critical)
+    } else if percent >= WARNING_PERCENT {
+        Some(ContextUsageLevel::Warning)
+    } else {
+        None
+    }
+}
+
+/// 上下文占用提醒回调
+pub type ContextUsageNotifier = Box<dyn Fn(SessionContextUsage) + Send + Sync + 'static>;
+
+struct TrackedSession {
+    usage: SessionContextUsage,
+    /// 已提醒的最高级别
+    notified: Option<ContextUsageLevel>,
+}
+
+/// 上下文占用跟踪器（进程级单例）
+#[derive(Default)]
+pub struct ContextUsageTracker {
+    sessions: Mutex<HashMap<String, TrackedSession>>,
+    notifier: RwLock<Option<ContextUsageNotifier>>,
+}
+
+impl ContextUsageTracker {
+    /// 全局跟踪器
+    pub fn global() -> &'static ContextUsageTracker {
+        &CONTEXT_TRACKER
+    }
+
+    /// 设置提醒回调（由应用启动时注册，转发为前端事件）
+    pub fn set_notifier(&self, notifier: ContextUsageNotifier) {
+        *self.notifier.write().unwrap() = Some(notifier);
+    }
+
+    /// 记录一次请求的提示词大小（失败请求与无会话 ID 的请求忽略）
+    pub fn observe(&self, session_id: &str, log: &TokenLog) {
+        if session_id.is_empty() || session_id == "unknown" {
+            return;
+        }
+        let Some(usage) = SessionContextUsage::from_log(session_id, log) else {
+            return;
+        };
+
+        let notify = {
+            let mut sessions = self.sessions.lock().unwrap();
+            let notified = sessions.get(session_id).and_then(|s| s.notified);
+            let (notified, notify) = match usage.level {
+                Some(level) if notified.is_none_or(|prev| level > prev) => (Some(level), true),
+                Some(_) => (notified, false),
+                None => (None, false),
+            };
+            sessions.insert(
+                session_id.to_string(),
+                TrackedSession {
+                    usage: usage.clone(),
+                    notified,
+                },
+            );
+            notify
+        };
+
+        if notify {
+            tracing::warn!(
+                session_id = %session_id,
+                model = %usage.model,
+                prompt_tokens = usage.prompt_tokens,
+                context_window = ?usage.context_window,
+                "会话上下文接近模型上限"
+            );
+            if let Some(notifier) = self.notifier.read().unwrap().as_ref() {
+                notifier(usage);
+            }
+        }
+    }
+
+    /// 会话的当前占用（本次运行未见过该会话时返回 None）
+    pub fn get(&self, session_id: &str) -> Option<SessionContextUsage> {
+        self.sessions
+            .lock()
+            .unwrap()
+            .get(session_id)
+            .map(|s| s.usage.clone())
+    }
+
+    /// 移除会话的占用记录
+    pub fn forget(&self, session_id: &str) {
+        self.sessions.lock().unwrap().remove(session_id);
+    }
+
+    /// 移除工具所有会话的占用记录
+    pub fn forget_tool(&self, tool_id: &str) {
+        self.sessions
+            .lock()
+            .unwrap()
+            .retain(|_, s| s.usage.tool_id != tool_id);
+    }
+}
+
+/// 查询会话的上下文占用（本次运行未见过的会话从统计日志中最近的成功请求恢复）
+pub fn session_context_usage(session_id: &str) -> Result<Option<SessionContextUsage>> {
+    if let Some(usage) = ContextUsageTracker::global().get(session_id) {
+        return Ok(Some(usage));
+    }
+
+    let page = TokenStatsManager::get().query_logs(TokenStatsQuery {
+        session_id: Some(ProxySession::extract_display_id(session_id)),
+        page_size: LOG_LOOKBACK,
+        ..Default::default()
+    })?;
+    Ok(page
+        .logs
+        .iter()
+        .find_map(|log| SessionContextUsage::from_log(session_id, log)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn log(input: i64, cache_read: i64) -> TokenLog {
+        TokenLog::test_builder()
+            .timestamp(chrono::Utc::now().timestamp_millis())
+            .session("abc")
+            .tokens(input, 100)
+            .cache_tokens(0, cache_read)
+            .response_type("sse")
+            .template("missing-template")
+            .build()
+    }
+
+    #[test]
+    fn test_level_thresholds() {
+        assert_eq!(level_for(50.0), None);
+        assert_eq!(level_for(80.0), Some(ContextUsageLevel::Warning));
+        assert_eq!(level_for(96.5), Some(ContextUsageLevel::Critical));
+    }
+
+    #[test]
+    fn test_notifies_once_per_level_and_resets_after_compaction() {
+        let tracker = ContextUsageTracker::default();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        tracker.set_notifier(Box::new(move |usage| {
+            sink.lock().unwrap().push(usage.level);
+        }));
+
+        // 默认 Claude 上下文窗口 200k
+        tracker.observe("s1", &log(1_000, 150_000));
+        tracker.observe("s1", &log(2_000, 160_000));
+        tracker.observe("s1", &log(1_000, 191_000));
+        tracker.observe("s1", &log(1_000, 195_000));
+        // 压缩上下文后回落，再次增长时重新提醒
+        tracker.observe("s1", &log(20_000, 0));
+        tracker.observe("s1", &log(1_000, 170_000));
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                Some(ContextUsageLevel::Warning),
+                Some(ContextUsageLevel::Critical),
+                Some(ContextUsageLevel::Warning),
+            ]
+        );
+        let usage = tracker.get("s1").unwrap();
+        assert_eq!(usage.prompt_tokens, 171_000);
+        assert_eq!(usage.context_window, Some(200_000));
+    }
+}
@@ -4,6 +4,7 @@
 
 use crate::data::managers::sqlite::QueryRow;
 use crate::services::session::models::ProxySession;
+use crate::services::session::tags::split_tags;
 use anyhow::{anyhow, Context, Result};
 
 /// 会话配置类型：(config_name, custom_profile_name, url, api_key, pricing_template_id)
@@ -11,7 +12,7 @@ pub type SessionConfig = (String, Option<String>, String, String, Option<String>
 
 /// 标准会话查询的 SQL 语句
 ///
-/// **字段顺序（共 14 个）：**
+/// **字段顺序（共 19 个）：**
 /// 1. session_id
 /// 2. display_id
 /// 3. tool_id
@@ -26,10 +27,17 @@ pub type SessionConfig = (String, Option<String>, String, String, Option<String>
 /// 12. created_at
 /// 13. updated_at
 /// 14. pricing_template_id
+/// 15. api_flavor
+/// 16. sse_quirks
+/// 17. tags
+/// 18. queued_count
+/// 19. queued_wait_ms
 pub const SELECT_SESSION_FIELDS: &str = "session_id, display_id, tool_id, config_name, \
                                           custom_profile_name, url, api_key, note, \
                                           first_seen_at, last_seen_at, request_count, \
-                                          created_at, updated_at, pricing_template_id";
+                                          created_at, updated_at, pricing_template_id, \
+                                          api_flavor, sse_quirks, tags, \
+                                          queued_count, queued_wait_ms";
 
 /// 创建表的 SQL 语句
 pub const CREATE_TABLE_SQL: &str = "
@@ -47,7 +55,12 @@ CREATE TABLE IF NOT EXISTS claude_proxy_sessions (
     request_count INTEGER NOT NULL DEFAULT 0,
     created_at INTEGER NOT NULL,
     updated_at INTEGER NOT NULL,
-    pricing_template_id TEXT
+    pricing_template_id TEXT,
+    api_flavor TEXT,
+    sse_quirks TEXT,
+    tags TEXT,
+    queued_count INTEGER NOT NULL DEFAULT 0,
+    queued_wait_ms INTEGER NOT NULL DEFAULT 0
 );
 
 CREATE INDEX IF NOT EXISTS idx_tool_id ON claude_proxy_sessions(tool_id);
@@ -60,6 +73,11 @@ pub const ALTER_TABLE_STATEMENTS: &[&str] = &[
     "ALTER TABLE claude_proxy_sessions ADD COLUMN custom_profile_name TEXT",
     "ALTER TABLE claude_proxy_sessions ADD COLUMN note TEXT",
     "ALTER TABLE claude_proxy_sessions ADD COLUMN pricing_template_id TEXT",
+    "ALTER TABLE claude_proxy_sessions ADD COLUMN api_flavor TEXT",
+    "ALTER TABLE claude_proxy_sessions ADD COLUMN sse_quirks TEXT",
+    "ALTER TABLE claude_proxy_sessions ADD COLUMN tags TEXT",
+    "ALTER TABLE claude_proxy_sessions ADD COLUMN queued_count INTEGER NOT NULL DEFAULT 0",
+    "ALTER TABLE claude_proxy_sessions ADD COLUMN queued_wait_ms INTEGER NOT NULL DEFAULT 0",
 ];
 
 /// 兼容旧数据库的字段添加语句（已废弃，保留用于向后兼容）
@@ -90,10 +108,13 @@ ALTER TABLE claude_proxy_sessions ADD COLUMN pricing_template_id TEXT;
 /// - values[0..7]: 字符串字段
 /// - values[7]: note (可为 NULL)
 /// - values[8..12]: 整数字段
+/// - values[13..15]: 可选字符串字段
+/// - values[16]: 标签（逗号分隔，NULL 视为无标签）
+/// - values[17..18]: 排队次数与累计排队时长
 pub fn parse_proxy_session(row: &QueryRow) -> Result<ProxySession> {
-    if row.values.len() != 14 {
+    if row.values.len() != 19 {
         return Err(anyhow!(
-            "Invalid row: expected 14 columns, got {}",
+            "Invalid row: expected 19 columns, got {}",
             row.values.len()
         ));
     }
@@ -137,6 +158,13 @@ pub fn parse_proxy_session(row: &QueryRow) -> Result<ProxySession> {
         created_at: get_i64(11).context("created_at")?,
         updated_at: get_i64(12).context("updated_at")?,
         pricing_template_id: get_optional_string(13),
+        api_flavor: get_optional_string(14),
+        sse_quirks: get_optional_string(15),
+        tags: get_optional_string(16)
+            .map(|raw| split_tags(&raw))
+            .unwrap_or_default(),
+        queued_count: get_i64(17).context("queued_count")?,
+        queued_wait_ms: get_i64(18).context("queued_wait_ms")?,
     })
 }
 
@@ -225,6 +253,11 @@ mod tests {
                 "created_at".to_string(),
                 "updated_at".to_string(),
                 "pricing_template_id".to_string(),
+                "api_flavor".to_string(),
+                "sse_quirks".to_string(),
+                "tags".to_string(),
+                "queued_count".to_string(),
+                "queued_wait_ms".to_string(),
             ],
             values: vec![
                 json!("test_session_1"),
@@ -241,6 +274,11 @@ mod tests {
                 json!(1000),
                 json!(2000),
                 json!("anthropic_official"),
+                json!("openai-responses"),
+                json!("bom,comment-lines"),
+                json!("web,billing"),
+                json!(2),
+                json!(4500),
             ],
         };
 
@@ -263,6 +301,11 @@ mod tests {
             session.pricing_template_id,
             Some("anthropic_official".to_string())
         );
+        assert_eq!(session.api_flavor, Some("openai-responses".to_string()));
+        assert_eq!(session.sse_quirks, Some("bom,comment-lines".to_string()));
+        assert_eq!(session.tags, vec!["web", "billing"]);
+        assert_eq!(session.queued_count, 2);
+        assert_eq!(session.queued_wait_ms, 4500);
     }
 
     #[test]
@@ -283,6 +326,11 @@ mod tests {
                 "created_at".to_string(),
                 "updated_at".to_string(),
                 "pricing_template_id".to_string(),
+                "api_flavor".to_string(),
+                "sse_quirks".to_string(),
+                "tags".to_string(),
+                "queued_count".to_string(),
+                "queued_wait_ms".to_string(),
             ],
             values: vec![
                 json!("test_session_2"),
@@ -299,6 +347,11 @@ mod tests {
                 json!(3000),
                 json!(4000),
                 json!(null), // pricing_template_id
+                json!(null), // api_flavor
+                json!(null), // sse_quirks
+                json!(null), // tags
+                json!(0),
+                json!(0),
             ],
         };
 
@@ -309,6 +362,9 @@ mod tests {
         assert_eq!(session.custom_profile_name, None);
         assert_eq!(session.note, None);
         assert_eq!(session.pricing_template_id, None);
+        assert_eq!(session.api_flavor, None);
+        assert_eq!(session.sse_quirks, None);
+        assert!(session.tags.is_empty());
         assert_eq!(session.request_count, 10);
     }
 
@@ -393,6 +449,6 @@ mod tests {
         assert!(result
             .unwrap_err()
             .to_string()
-            .contains("expected 14 columns"));
+            .contains("expected 19 columns"));
     }
 }
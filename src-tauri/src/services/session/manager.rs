@@ -6,6 +6,7 @@ use crate::services::session::db_utils::{
     CREATE_TABLE_SQL, SELECT_SESSION_FIELDS,
 };
 use crate::services::session::models::{ProxySession, SessionEvent, SessionListResponse};
+use crate::services::session::tags::{join_tags, normalize_tags, split_tags};
 use anyhow::Result;
 use lazy_static::lazy_static;
 use once_cell::sync::Lazy;
@@ -149,24 +150,59 @@ impl SessionManager {
                     session_id,
                     tool_id,
                     timestamp,
+                    project_tag,
                 } => {
                     // 提取 display_id
                     let display_id = ProxySession::extract_display_id(&session_id);
 
-                    // Upsert 会话
+                    // Upsert 会话（tags 为 NULL 表示从未设置，此时才采用自动项目标签）
                     if let Ok(db) = manager.sqlite(db_path) {
                         if db
                             .execute(
                                 "INSERT INTO claude_proxy_sessions (
                                 session_id, display_id, tool_id, config_name, url, api_key,
                                 first_seen_at, last_seen_at, request_count,
-                                created_at, updated_at
-                            ) VALUES (?1, ?2, ?3, 'global', '', '', ?4, ?4, 1, ?4, ?4)
+                                created_at, updated_at, tags
+                            ) VALUES (?1, ?2, ?3, 'global', '', '', ?4, ?4, 1, ?4, ?4, NULLIF(?5, ''))
                             ON CONFLICT(session_id) DO UPDATE SET
                                 last_seen_at = ?4,
                                 request_count = request_count + 1,
-                                updated_at = ?4",
-                                &[&session_id, &display_id, &tool_id, &timestamp.to_string()],
+                                updated_at = ?4,
+                                tags = COALESCE(tags, excluded.tags)",
+                                &[
+                                    &session_id,
+                                    &display_id,
+                                    &tool_id,
+                                    &timestamp.to_string(),
+                                    project_tag.as_deref().unwrap_or(""),
+                                ],
+                            )
+                            .is_ok()
+                        {
+                            has_writes = true;
+                        }
+                    }
+                }
+                SessionEvent::RequestQueued {
+                    session_id,
+                    tool_id,
+                    timestamp,
+                    wait_ms,
+                } => {
+                    if let Ok(db) = manager.sqlite(db_path) {
+                        if db
+                            .execute(
+                                "UPDATE claude_proxy_sessions SET
+                                queued_count = queued_count + 1,
+                                queued_wait_ms = queued_wait_ms + ?1,
+                                updated_at = ?2
+                            WHERE session_id = ?3 AND tool_id = ?4",
+                                &[
+                                    &wait_ms.to_string(),
+                                    &timestamp.to_string(),
+                                    &session_id,
+                                    &tool_id,
+                                ],
                             )
                             .is_ok()
                         {
@@ -284,6 +320,40 @@ impl SessionManager {
         })
     }
 
+    /// 获取所有工具最近活跃的会话（公共 API，用于全局搜索索引）
+    pub fn list_recent_sessions(&self, limit: usize) -> Result<Vec<ProxySession>> {
+        let db = self.manager.sqlite(&self.db_path)?;
+        let sql = format!(
+            "SELECT {} FROM claude_proxy_sessions ORDER BY last_seen_at DESC LIMIT ?",
+            SELECT_SESSION_FIELDS
+        );
+        let rows = db.query(&sql, &[&limit.to_string()])?;
+
+        rows.iter().map(parse_proxy_session).collect()
+    }
+
+    /// 清理三个工具的过期会话（公共 API，用于维护任务）
+    pub fn cleanup_expired_sessions(&self) -> Result<usize> {
+        let mut deleted = 0;
+        for tool_id in &["claude-code", "codex", "gemini-cli"] {
+            deleted += Self::cleanup_old_sessions_internal(
+                &self.manager,
+                &self.db_path,
+                tool_id,
+                1000,
+                30,
+            )?;
+        }
+        Ok(deleted)
+    }
+
+    /// 执行 TRUNCATE checkpoint，回写并截断 WAL 文件
+    pub fn force_checkpoint(&self) -> Result<()> {
+        let db = self.manager.sqlite(&self.db_path)?;
+        db.execute_raw("PRAGMA wal_checkpoint(TRUNCATE)")?;
+        Ok(())
+    }
+
     /// 删除单个会话（公共 API）
     pub fn delete_session(&self, session_id: &str) -> Result<()> {
         let db = self.manager.sqlite(&self.db_path)?;
@@ -401,6 +471,77 @@ impl SessionManager {
 
         Ok(())
     }
+
+    /// 设置会话标签（公共 API）
+    ///
+    /// 标签经规范化后保存；设置为空列表后不再自动打项目标签。
+    /// 返回规范化后的标签，会话不存在时返回 None
+    pub fn update_session_tags(
+        &self,
+        session_id: &str,
+        tags: &[String],
+    ) -> Result<Option<Vec<String>>> {
+        let db = self.manager.sqlite(&self.db_path)?;
+        let now = chrono::Utc::now().timestamp();
+        let tags = normalize_tags(tags);
+
+        let updated = db.execute(
+            "UPDATE claude_proxy_sessions SET tags = ?, updated_at = ? WHERE session_id = ?",
+            &[&join_tags(&tags), &now.to_string(), session_id],
+        )?;
+
+        if updated == 0 {
+            return Ok(None);
+        }
+        let _ = db.execute_raw("PRAGMA wal_checkpoint(PASSIVE)");
+        Ok(Some(tags))
+    }
+
+    /// 查询会话标签（公共 API，用于写入 Token 日志）
+    pub fn get_session_tags(&self, session_id: &str) -> Result<Vec<String>> {
+        let db = self.manager.sqlite(&self.db_path)?;
+        let rows = db.query(
+            "SELECT tags FROM claude_proxy_sessions WHERE session_id = ?",
+            &[session_id],
+        )?;
+
+        Ok(rows
+            .first()
+            .and_then(|row| row.values.first())
+            .and_then(|v| v.as_str())
+            .map(split_tags)
+            .unwrap_or_default())
+    }
+
+    /// 记录检测到的上游 API 风格（公共 API）
+    ///
+    /// 返回是否实际更新了会话（会话尚未落库时返回 false）
+    pub fn update_session_flavor(&self, session_id: &str, api_flavor: &str) -> Result<bool> {
+        let db = self.manager.sqlite(&self.db_path)?;
+        let now = chrono::Utc::now().timestamp();
+
+        let updated = db.execute(
+            "UPDATE claude_proxy_sessions SET api_flavor = ?, updated_at = ? WHERE session_id = ?",
+            &[api_flavor, &now.to_string(), session_id],
+        )?;
+
+        Ok(updated > 0)
+    }
+
+    /// 记录观察到的非标准 SSE 写法（公共 API）
+    ///
+    /// 返回是否实际更新了会话（会话尚未落库时返回 false）
+    pub fn update_session_quirks(&self, session_id: &str, sse_quirks: &str) -> Result<bool> {
+        let db = self.manager.sqlite(&self.db_path)?;
+        let now = chrono::Utc::now().timestamp();
+
+        let updated = db.execute(
+            "UPDATE claude_proxy_sessions SET sse_quirks = ?, updated_at = ? WHERE session_id = ?",
+            &[sse_quirks, &now.to_string(), session_id],
+        )?;
+
+        Ok(updated > 0)
+    }
 }
 
 /// 关闭 SessionManager 后台任务
@@ -455,6 +596,7 @@ mod tests {
             session_id: "test_user_session_abc-123".to_string(),
             tool_id: "claude-code".to_string(),
             timestamp,
+            project_tag: None,
         };
 
         // 发送事件
@@ -482,6 +624,7 @@ mod tests {
                 session_id: "test_session_cache_xyz".to_string(),
                 tool_id: "claude-code".to_string(),
                 timestamp,
+                project_tag: None,
             })
             .unwrap();
 
@@ -548,4 +691,124 @@ mod tests {
         assert_eq!(session.url, "https://api.test.com");
         assert_eq!(session.api_key, "sk-test");
     }
+
+    #[tokio::test]
+    async fn test_update_session_flavor() {
+        let temp = TempDir::new().expect("create temp dir");
+        let manager = create_test_manager(&temp);
+
+        // 会话尚未落库时不更新
+        assert!(!manager
+            .update_session_flavor("missing_session", "anthropic")
+            .unwrap());
+
+        let db = manager.manager.sqlite(&manager.db_path).unwrap();
+        let now = chrono::Utc::now().timestamp().to_string();
+        db.execute(
+            "INSERT INTO claude_proxy_sessions (
+                session_id, display_id, tool_id, config_name, url, api_key,
+                first_seen_at, last_seen_at, request_count,
+                created_at, updated_at
+            ) VALUES (?, ?, ?, 'global', '', '', ?, ?, 1, ?, ?)",
+            &[
+                "test_session_flavor",
+                "uuid-flavor",
+                "claude-code",
+                &now,
+                &now,
+                &now,
+                &now,
+            ],
+        )
+        .unwrap();
+
+        assert!(manager
+            .update_session_flavor("test_session_flavor", "openai-responses")
+            .unwrap());
+        let session = manager.get_session("test_session_flavor").unwrap().unwrap();
+        assert_eq!(session.api_flavor, Some("openai-responses".to_string()));
+
+        assert!(manager
+            .update_session_quirks("test_session_flavor", "bom,bare-cr")
+            .unwrap());
+        let session = manager.get_session("test_session_flavor").unwrap().unwrap();
+        assert_eq!(session.sse_quirks, Some("bom,bare-cr".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_request_queued_event() {
+        let temp = TempDir::new().expect("create temp dir");
+        let manager = create_test_manager(&temp);
+        let timestamp = chrono::Utc::now().timestamp();
+        let queued = |wait_ms| SessionEvent::RequestQueued {
+            session_id: "test_session_queued".to_string(),
+            tool_id: "claude-code".to_string(),
+            timestamp,
+            wait_ms,
+        };
+
+        manager
+            .send_event(SessionEvent::NewRequest {
+                session_id: "test_session_queued".to_string(),
+                tool_id: "claude-code".to_string(),
+                timestamp,
+                project_tag: None,
+            })
+            .unwrap();
+        manager.send_event(queued(2000)).unwrap();
+        manager.send_event(queued(1500)).unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let session = manager.get_session("test_session_queued").unwrap().unwrap();
+        assert_eq!(session.queued_count, 2);
+        assert_eq!(session.queued_wait_ms, 3500);
+    }
+
+    #[tokio::test]
+    async fn test_session_tags_auto_and_manual() {
+        let temp = TempDir::new().expect("create temp dir");
+        let manager = create_test_manager(&temp);
+        let event = |project: Option<&str>| SessionEvent::NewRequest {
+            session_id: "test_session_tags".to_string(),
+            tool_id: "claude-code".to_string(),
+            timestamp: chrono::Utc::now().timestamp(),
+            project_tag: project.map(|p| p.to_string()),
+        };
+
+        assert!(manager
+            .update_session_tags("test_session_tags", &["x".to_string()])
+            .unwrap()
+            .is_none());
+
+        // 新会话采用自动项目标签，后续请求不覆盖
+        manager.send_event(event(Some("duckcoding"))).unwrap();
+        manager.send_event(event(Some("other"))).unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(
+            manager.get_session_tags("test_session_tags").unwrap(),
+            vec!["duckcoding"]
+        );
+
+        let tags = manager
+            .update_session_tags(
+                "test_session_tags",
+                &["billing, web".to_string(), "web".to_string()],
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(tags, vec!["billing", "web"]);
+        let session = manager.get_session("test_session_tags").unwrap().unwrap();
+        assert_eq!(session.tags, vec!["billing", "web"]);
+
+        // 手动清空后不再自动打标签
+        manager
+            .update_session_tags("test_session_tags", &[])
+            .unwrap();
+        manager.send_event(event(Some("duckcoding"))).unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(manager
+            .get_session_tags("test_session_tags")
+            .unwrap()
+            .is_empty());
+    }
 }
@@ -1,8 +1,11 @@
 // 会话管理服务模块
 
+pub mod context_usage; // 会话上下文窗口占用
 mod db_utils;
 pub mod manager;
 pub mod models;
+pub mod tags; // 会话标签（项目归集）
+pub mod transcript; // 会话对话记录
 
 pub use manager::{shutdown_session_manager, SESSION_MANAGER};
 pub use models::{ProxySession, SessionEvent, SessionListResponse};
@@ -34,6 +34,21 @@ pub struct ProxySession {
     /// 价格模板 ID（用于成本计算）
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub pricing_template_id: Option<String>,
+    /// 从响应形态检测到的上游 API 风格（如 "anthropic"、"openai-responses"）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_flavor: Option<String>,
+    /// 观察到的非标准 SSE 写法（逗号分隔，如 "bom,comment-lines"）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sse_quirks: Option<String>,
+    /// 会话标签（用户设置或按客户端工作目录自动生成，用于按项目归集花费）
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// 因上游限流进入排队的次数
+    #[serde(default)]
+    pub queued_count: i64,
+    /// 累计排队等待时长（毫秒）
+    #[serde(default)]
+    pub queued_wait_ms: i64,
 }
 
 /// 会话事件（异步队列传递）
@@ -44,6 +59,16 @@ pub enum SessionEvent {
         session_id: String,
         tool_id: String,
         timestamp: i64,
+        /// 按客户端工作目录推断的项目标签（仅在会话未设置标签时生效）
+        project_tag: Option<String>,
+    },
+    /// 请求因上游限流（429）排队等待
+    RequestQueued {
+        session_id: String,
+        tool_id: String,
+        timestamp: i64,
+        /// 本次等待时长（毫秒）
+        wait_ms: i64,
     },
 }
 
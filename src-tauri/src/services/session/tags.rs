@@ -0,0 +1,85 @@
+// 会话标签
+//
+// 标签以逗号分隔的文本存储（会话表与 token_logs 相同），用于按项目归集花费：
+// - 用户通过 `tag_session` 手动设置
+// - 客户端携带工作目录请求头时，新会话自动打上目录名标签（用户手动设置过则不再覆盖）
+
+use hyper::HeaderMap;
+
+/// 客户端工作目录请求头（值可为百分号编码，便于传递非 ASCII 路径）
+pub const CWD_HEADER: &str = "x-duckcoding-cwd";
+
+/// 规范化标签：拆分逗号、去除首尾空白、去空、去重（保持顺序）
+pub fn normalize_tags<S: AsRef<str>>(tags: &[S]) -> Vec<String> {
+    let mut result: Vec<String> = Vec::new();
+    for tag in tags.iter().flat_map(|t| t.as_ref().split(',')) {
+        let tag = tag.trim();
+        if !tag.is_empty() && !result.iter().any(|t| t == tag) {
+            result.push(tag.to_string());
+        }
+    }
+    result
+}
+
+/// 标签列表 → 存储文本
+pub fn join_tags(tags: &[String]) -> String {
+    tags.join(",")
+}
+
+/// 存储文本 → 标签列表
+pub fn split_tags(raw: &str) -> Vec<String> {
+    normalize_tags(&[raw])
+}
+
+/// 从工作目录请求头推断项目标签（取目录名）
+pub fn project_tag(headers: &HeaderMap) -> Option<String> {
+    let raw = String::from_utf8_lossy(headers.get(CWD_HEADER)?.as_bytes()).to_string();
+    let path = urlencoding::decode(&raw)
+        .map(|decoded| decoded.into_owned())
+        .unwrap_or(raw);
+    let name = path
+        .trim()
+        .trim_end_matches(['/', '\\'])
+        .rsplit(['/', '\\'])
+        .next()?
+        .trim()
+        .replace(',', "_");
+    // 盘符根目录（如 `C:`）不是有意义的项目名
+    if name.is_empty() || name.ends_with(':') {
+        return None;
+    }
+    Some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_tags() {
+        assert_eq!(
+            normalize_tags(&[" web ", "api,web", "", " , billing"]),
+            vec!["web", "api", "billing"]
+        );
+        assert_eq!(split_tags(""), Vec::<String>::new());
+        assert_eq!(join_tags(&split_tags("a, b")), "a,b");
+    }
+
+    #[test]
+    fn test_project_tag_from_cwd_header() {
+        let tag = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(CWD_HEADER, value.parse().unwrap());
+            project_tag(&headers)
+        };
+        assert_eq!(tag("/home/dev/duckcoding/"), Some("duckcoding".to_string()));
+        assert_eq!(tag(r"C:\work\billing-api"), Some("billing-api".to_string()));
+        assert_eq!(
+            tag("/home/dev/%E9%A1%B9%E7%9B%AE"),
+            Some("项目".to_string())
+        );
+        assert_eq!(tag("/"), None);
+        assert_eq!(tag(r"C:\"), None);
+        assert_eq!(project_tag(&HeaderMap::new()), None);
+    }
+}
@@ -0,0 +1,816 @@
+// 会话对话记录
+//
+// 按会话开启后，代理把该会话每次生成请求的消息与模型回复整理为统一格式写入 `transcripts.db`，
+// 可导出为 Markdown 或 JSON 归档：
+// - OpenAI Chat / Responses 的请求与响应先经协议转换层转换为 Anthropic Messages 格式再解析
+// - 客户端每次请求都携带完整历史；与近期某条记录的消息前缀一致时只保存新增消息
+// - 图片只保留类型，不保存内容
+// - 记录不会自动过期，删除会话时一并删除
+
+use crate::data::DataManager;
+use crate::models::proxy_config::ApiProtocol;
+use crate::services::proxy::protocol::{self, SseTranscoder};
+use crate::services::proxy::utils::sse_quirks::data_payloads;
+use crate::utils::config::config_dir;
+use anyhow::{anyhow, Context, Result};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// 对话记录数据库文件名
+const TRANSCRIPT_DB: &str = "transcripts.db";
+
+/// 查找消息前缀时回溯的记录数（覆盖主对话之间穿插的标题生成等旁路请求）
+const PREFIX_LOOKBACK: usize = 20;
+
+/// 记录会话时流式响应的缓冲上限
+pub const RESPONSE_BUFFER_LIMIT: usize = 8 * 1024 * 1024;
+
+const CREATE_TABLE_SQL: &str = "
+CREATE TABLE IF NOT EXISTS transcript_recordings (
+    session_id TEXT PRIMARY KEY,
+    enabled_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS transcript_exchanges (
+    id TEXT PRIMARY KEY,
+    session_id TEXT NOT NULL,
+    tool_id TEXT NOT NULL,
+    recorded_at INTEGER NOT NULL,
+    model TEXT,
+    continues INTEGER NOT NULL DEFAULT 0,
+    messages TEXT NOT NULL,
+    total_messages INTEGER NOT NULL,
+    fingerprint TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_transcript_exchanges_session
+    ON transcript_exchanges(session_id, recorded_at);
+";
+
+/// 已开启记录的会话（首次访问时从数据库加载）
+static RECORDING: Lazy<RwLock<HashSet<String>>> = Lazy::new(|| {
+    let sessions = transcript_db_path()
+        .and_then(|path| load_recordings(&path))
+        .unwrap_or_else(|e| {
+            tracing::warn!(error = ?e, "加载会话记录开关失败");
+            Vec::new()
+        });
+    RwLock::new(sessions.into_iter().collect())
+});
+
+/// 消息角色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptRole {
+    System,
+    User,
+    Assistant,
+}
+
+/// 消息内容块
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TranscriptBlock {
+    Text {
+        text: String,
+    },
+    Thinking {
+        text: String,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        input: Value,
+    },
+    ToolResult {
+        tool_use_id: String,
+        content: String,
+        is_error: bool,
+    },
+    /// 图片（不保存内容）
+    Image {
+        media_type: String,
+    },
+}
+
+/// 统一格式的消息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptMessage {
+    pub role: TranscriptRole,
+    pub blocks: Vec<TranscriptBlock>,
+}
+
+/// 一次请求记录的消息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptExchange {
+    pub id: String,
+    /// 记录时间（Unix 时间戳，毫秒）
+    pub recorded_at: i64,
+    pub model: Option<String>,
+    /// 是否接续之前的记录（false 表示 `messages` 为该请求的完整上下文）
+    pub continues: bool,
+    /// 新增的消息（含模型回复）
+    pub messages: Vec<TranscriptMessage>,
+}
+
+/// 会话对话记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionTranscript {
+    pub session_id: String,
+    pub tool_id: Option<String>,
+    /// 导出时间（Unix 时间戳，毫秒）
+    pub exported_at: i64,
+    pub exchanges: Vec<TranscriptExchange>,
+}
+
+/// 导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriptFormat {
+    Markdown,
+    Json,
+}
+
+/// 待记录的一次请求
+pub struct ExchangeRecord<'a> {
+    pub session_id: &'a str,
+    pub tool_id: &'a str,
+    /// 客户端请求路径（用于识别协议）
+    pub path: &'a str,
+    pub request_body: &'a [u8],
+    pub response_body: &'a [u8],
+    pub is_sse: bool,
+}
+
+/// 对话记录数据库路径
+fn transcript_db_path() -> Result<PathBuf> {
+    Ok(config_dir().map_err(|e| anyhow!(e))?.join(TRANSCRIPT_DB))
+}
+
+/// 获取对话记录数据库（确保表已创建）
+fn open_db(db_path: &Path) -> Result<std::sync::Arc<crate::data::managers::sqlite::SqliteManager>> {
+    let manager = DataManager::global()
+        .sqlite(db_path)
+        .context("打开对话记录数据库失败")?;
+    if !manager.table_exists("transcript_exchanges")? {
+        manager.execute_raw(CREATE_TABLE_SQL)?;
+    }
+    Ok(manager)
+}
+
+fn load_recordings(db_path: &Path) -> Result<Vec<String>> {
+    let rows = open_db(db_path)?.query("SELECT session_id FROM transcript_recordings", &[])?;
+    Ok(rows
+        .iter()
+        .filter_map(|row| Some(row.values.first()?.as_str()?.to_string()))
+        .collect())
+}
+
+/// 是否有会话开启了记录（代理据此决定是否解析请求体）
+pub fn any_recording() -> bool {
+    RECORDING.read().map(|set| !set.is_empty()).unwrap_or(false)
+}
+
+/// 会话是否开启了记录
+pub fn is_recording(session_id: &str) -> bool {
+    RECORDING
+        .read()
+        .map(|set| set.contains(session_id))
+        .unwrap_or(false)
+}
+
+/// 开启记录的会话 ID 列表
+pub fn recording_sessions() -> Vec<String> {
+    let mut sessions: Vec<String> = RECORDING
+        .read()
+        .map(|set| set.iter().cloned().collect())
+        .unwrap_or_default();
+    sessions.sort();
+    sessions
+}
+
+/// 开启或关闭会话记录（关闭不删除已有记录）
+pub fn set_recording(session_id: &str, enabled: bool) -> Result<()> {
+    let manager = open_db(&transcript_db_path()?)?;
+    if enabled {
+        manager.execute(
+            "INSERT OR IGNORE INTO transcript_recordings (session_id, enabled_at) VALUES (?1, ?2)",
+            &[
+                session_id,
+                &chrono::Utc::now().timestamp_millis().to_string(),
+            ],
+        )?;
+    } else {
+        manager.execute(
+            "DELETE FROM transcript_recordings WHERE session_id = ?1",
+            &[session_id],
+        )?;
+    }
+
+    if let Ok(mut set) = RECORDING.write() {
+        if enabled {
+            set.insert(session_id.to_string());
+        } else {
+            set.remove(session_id);
+        }
+    }
+    tracing::info!(session_id = %session_id, enabled, "会话对话记录开关已更新");
+    Ok(())
+}
+
+/// 记录一次请求（无法识别协议或解析请求体时返回 false）
+pub fn record_exchange(record: &ExchangeRecord<'_>) -> Result<bool> {
+    insert_exchange(
+        &transcript_db_path()?,
+        record,
+        chrono::Utc::now().timestamp_millis(),
+    )
+}
+
+fn insert_exchange(db_path: &Path, record: &ExchangeRecord<'_>, recorded_at: i64) -> Result<bool> {
+    let Some(protocol) = protocol::client_protocol(record.path) else {
+        return Ok(false);
+    };
+    let Ok(request) = serde_json::from_slice::<Value>(record.request_body) else {
+        return Ok(false);
+    };
+    let request = protocol::request_as_anthropic(protocol, &request);
+    let model = request["model"].as_str().map(str::to_string);
+
+    let mut messages = request_messages(&request);
+    let context_len = messages.len();
+    let reply = if record.is_sse {
+        reply_from_sse(protocol, record.response_body)
+    } else {
+        serde_json::from_slice::<Value>(record.response_body)
+            .ok()
+            .and_then(|body| reply_from_message(&protocol::response_as_anthropic(protocol, &body)))
+    };
+    messages.extend(reply);
+
+    // 与近期记录的消息前缀一致时只保存新增部分
+    let manager = open_db(db_path)?;
+    let rows = manager.query(
+        "SELECT total_messages, fingerprint FROM transcript_exchanges
+         WHERE session_id = ?1 ORDER BY recorded_at DESC LIMIT ?2",
+        &[record.session_id, &PREFIX_LOOKBACK.to_string()],
+    )?;
+    let prefix_len = rows
+        .iter()
+        .filter_map(|row| {
+            let total = row.values.first()?.as_u64()? as usize;
+            let fingerprint = row.values.get(1)?.as_str()?;
+            (total <= context_len && fingerprint_of(&messages[..total]) == fingerprint)
+                .then_some(total)
+        })
+        .max()
+        .unwrap_or(0);
+
+    let params = [
+        uuid::Uuid::new_v4().to_string(),
+        record.session_id.to_string(),
+        record.tool_id.to_string(),
+        recorded_at.to_string(),
+        model.unwrap_or_default(),
+        i32::from(prefix_len > 0).to_string(),
+        serde_json::to_string(&messages[prefix_len..])?,
+        messages.len().to_string(),
+        fingerprint_of(&messages),
+    ];
+    let param_refs: Vec<&str> = params.iter().map(|s| s.as_str()).collect();
+    manager.execute(
+        "INSERT INTO transcript_exchanges (
+            id, session_id, tool_id, recorded_at, model, continues, messages,
+            total_messages, fingerprint
+        ) VALUES (?1, ?2, ?3, ?4, NULLIF(?5, ''), ?6, ?7, ?8, ?9)",
+        &param_refs,
+    )?;
+    Ok(true)
+}
+
+/// 消息序列指纹
+///
+/// 只取角色、文本与工具调用 ID：客户端回传历史时常省略思考内容或调整缓存标记，
+/// 这些差异不应视为新的上下文
+fn fingerprint_of(messages: &[TranscriptMessage]) -> String {
+    let mut hasher = Sha256::new();
+    for message in messages {
+        hasher.update(format!("{:?}", message.role));
+        for block in &message.blocks {
+            match block {
+                TranscriptBlock::Text { text } => hasher.update(text),
+                TranscriptBlock::ToolUse { id, .. } => hasher.update(format!("tool_use:{id}")),
+                TranscriptBlock::ToolResult { tool_use_id, .. } => {
+                    hasher.update(format!("tool_result:{tool_use_id}"))
+                }
+                TranscriptBlock::Thinking { .. } | TranscriptBlock::Image { .. } => continue,
+            }
+            hasher.update([1]);
+        }
+        hasher.update([0]);
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Anthropic Messages 请求中的系统提示与消息
+fn request_messages(request: &Value) -> Vec<TranscriptMessage> {
+    let mut messages = Vec::new();
+    let system = content_blocks(&request["system"]);
+    if !system.is_empty() {
+        messages.push(TranscriptMessage {
+            role: TranscriptRole::System,
+            blocks: system,
+        });
+    }
+    for message in request["messages"].as_array().into_iter().flatten() {
+        let blocks = content_blocks(&message["content"]);
+        if blocks.is_empty() {
+            continue;
+        }
+        messages.push(TranscriptMessage {
+            role: if message["role"] == "assistant" {
+                TranscriptRole::Assistant
+            } else {
+                TranscriptRole::User
+            },
+            blocks,
+        });
+    }
+    messages
+}
+
+/// Anthropic Messages 响应中的模型回复
+fn reply_from_message(message: &Value) -> Option<TranscriptMessage> {
+    let blocks = content_blocks(&message["content"]);
+    (!blocks.is_empty()).then_some(TranscriptMessage {
+        role: TranscriptRole::Assistant,
+        blocks,
+    })
+}
+
+/// 从 SSE 响应拼出模型回复（先转换为 Anthropic 事件）
+fn reply_from_sse(protocol: ApiProtocol, body: &[u8]) -> Option<TranscriptMessage> {
+    let mut transcoder = SseTranscoder::new(protocol, ApiProtocol::Anthropic);
+    let mut events = transcoder.feed(body);
+    events.extend(transcoder.finish());
+    let text = String::from_utf8_lossy(&events);
+
+    let mut blocks: BTreeMap<u64, Value> = BTreeMap::new();
+    let mut partial_json: BTreeMap<u64, String> = BTreeMap::new();
+    for data in text.split("\n\n").flat_map(data_payloads) {
+        let Ok(event) = serde_json::from_str::<Value>(data) else {
+            continue;
+        };
+        let index = event["index"].as_u64().unwrap_or(0);
+        match event["type"].as_str() {
+            Some("content_block_start") => {
+                blocks.insert(index, event["content_block"].clone());
+            }
+            Some("content_block_delta") => {
+                let Some(block) = blocks.get_mut(&index) else {
+                    continue;
+                };
+                let delta = &event["delta"];
+                match delta["type"].as_str() {
+                    Some("text_delta") => append_str(block, "text", &delta["text"]),
+                    Some("thinking_delta") => append_str(block, "thinking", &delta["thinking"]),
+                    Some("input_json_delta") => partial_json
+                        .entry(index)
+                        .or_default()
+                        .push_str(delta["partial_json"].as_str().unwrap_or_default()),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+    for (index, json) in partial_json {
+        if let (Some(block), Ok(input)) =
+            (blocks.get_mut(&index), serde_json::from_str::<Value>(&json))
+        {
+            block["input"] = input;
+        }
+    }
+
+    let content = Value::Array(blocks.into_values().collect());
+    reply_from_message(&serde_json::json!({ "content": content }))
+}
+
+fn append_str(block: &mut Value, field: &str, delta: &Value) {
+    let current = block[field].as_str().unwrap_or_default();
+    block[field] = Value::String(format!("{current}{}", delta.as_str().unwrap_or_default()));
+}
+
+/// Anthropic 消息内容（字符串或内容块数组）
+fn content_blocks(content: &Value) -> Vec<TranscriptBlock> {
+    match content {
+        Value::String(text) if !text.is_empty() => {
+            vec![TranscriptBlock::Text { text: text.clone() }]
+        }
+        Value::Array(items) => items.iter().filter_map(content_block).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn content_block(item: &Value) -> Option<TranscriptBlock> {
+    let text = |field: &str| item[field].as_str().unwrap_or_default().to_string();
+    Some(match item["type"].as_str()? {
+        "text" if !text("text").is_empty() => TranscriptBlock::Text { text: text("text") },
+        "thinking" if !text("thinking").is_empty() => TranscriptBlock::Thinking {
+            text: text("thinking"),
+        },
+        "tool_use" | "server_tool_use" => TranscriptBlock::ToolUse {
+            id: text("id"),
+            name: text("name"),
+            input: item["input"].clone(),
+        },
+        "tool_result" => TranscriptBlock::ToolResult {
+            tool_use_id: text("tool_use_id"),
+            content: plain_text(&item["content"]),
+            is_error: item["is_error"].as_bool().unwrap_or(false),
+        },
+        "image" => TranscriptBlock::Image {
+            media_type: item["source"]["media_type"]
+                .as_str()
+                .unwrap_or("image")
+                .to_string(),
+        },
+        _ => return None,
+    })
+}
+
+/// 工具结果等内容的纯文本（图片以占位符表示）
+fn plain_text(content: &Value) -> String {
+    match content {
+        Value::String(text) => text.clone(),
+        Value::Array(items) => items
+            .iter()
+            .filter_map(|item| match item["type"].as_str() {
+                Some("text") => item["text"].as_str().map(str::to_string),
+                Some("image") => Some("[图片]".to_string()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn load_transcript(
+    db_path: &Path,
+    session_id: &str,
+    exported_at: i64,
+) -> Result<SessionTranscript> {
+    let rows = open_db(db_path)?.query(
+        "SELECT id, tool_id, recorded_at, model, continues, messages
+         FROM transcript_exchanges WHERE session_id = ?1 ORDER BY recorded_at",
+        &[session_id],
+    )?;
+    let tool_id = rows
+        .first()
+        .and_then(|row| row.values.get(1)?.as_str())
+        .map(str::to_string);
+    let exchanges = rows
+        .iter()
+        .filter_map(|row| {
+            let v = &row.values;
+            Some(TranscriptExchange {
+                id: v.first()?.as_str()?.to_string(),
+                recorded_at: v.get(2)?.as_i64()?,
+                model: v.get(3).and_then(|v| v.as_str()).map(str::to_string),
+                continues: v.get(4)?.as_i64()? != 0,
+                messages: serde_json::from_str(v.get(5)?.as_str()?).ok()?,
+            })
+        })
+        .collect();
+
+    Ok(SessionTranscript {
+        session_id: session_id.to_string(),
+        tool_id,
+        exported_at,
+        exchanges,
+    })
+}
+
+/// 导出会话对话记录
+pub fn export_transcript(session_id: &str, format: TranscriptFormat) -> Result<String> {
+    let transcript = load_transcript(
+        &transcript_db_path()?,
+        session_id,
+        chrono::Utc::now().timestamp_millis(),
+    )?;
+    if transcript.exchanges.is_empty() {
+        anyhow::bail!("会话 {} 没有对话记录，请先开启记录", session_id);
+    }
+    Ok(match format {
+        TranscriptFormat::Markdown => render_markdown(&transcript),
+        TranscriptFormat::Json => serde_json::to_string_pretty(&transcript)?,
+    })
+}
+
+/// 删除会话的对话记录与记录开关，返回删除的请求数
+pub fn delete_transcript(session_id: &str) -> Result<usize> {
+    let manager = open_db(&transcript_db_path()?)?;
+    manager.execute(
+        "DELETE FROM transcript_recordings WHERE session_id = ?1",
+        &[session_id],
+    )?;
+    if let Ok(mut set) = RECORDING.write() {
+        set.remove(session_id);
+    }
+    Ok(manager.execute(
+        "DELETE FROM transcript_exchanges WHERE session_id = ?1",
+        &[session_id],
+    )?)
+}
+
+/// 删除指定工具全部会话的对话记录，返回删除的请求数
+pub fn delete_tool_transcripts(tool_id: &str) -> Result<usize> {
+    let manager = open_db(&transcript_db_path()?)?;
+    let rows = manager.query(
+        "SELECT DISTINCT session_id FROM transcript_exchanges WHERE tool_id = ?1",
+        &[tool_id],
+    )?;
+    for session_id in rows.iter().filter_map(|row| row.values.first()?.as_str()) {
+        manager.execute(
+            "DELETE FROM transcript_recordings WHERE session_id = ?1",
+            &[session_id],
+        )?;
+        if let Ok(mut set) = RECORDING.write() {
+            set.remove(session_id);
+        }
+    }
+    Ok(manager.execute(
+        "DELETE FROM transcript_exchanges WHERE tool_id = ?1",
+        &[tool_id],
+    )?)
+}
+
+/// 渲染为 Markdown
+fn render_markdown(transcript: &SessionTranscript) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# 会话记录\n");
+    let _ = writeln!(out, "- 会话: `{}`", transcript.session_id);
+    if let Some(tool_id) = &transcript.tool_id {
+        let _ = writeln!(out, "- 工具: {}", tool_id);
+    }
+    let _ = writeln!(out, "- 请求数: {}", transcript.exchanges.len());
+    let _ = writeln!(out, "- 导出时间: {}", format_time(transcript.exported_at));
+
+    for (i, exchange) in transcript.exchanges.iter().enumerate() {
+        let _ = write!(
+            out,
+            "\n## 请求 {} · {}",
+            i + 1,
+            format_time(exchange.recorded_at)
+        );
+        if let Some(model) = &exchange.model {
+            let _ = write!(out, " · {}", model);
+        }
+        out.push('\n');
+        if !exchange.continues && i > 0 {
+            out.push_str("\n> 以下为该请求的完整上下文\n");
+        }
+
+        for message in &exchange.messages {
+            let role = match message.role {
+                TranscriptRole::System => "System",
+                TranscriptRole::User => "User",
+                TranscriptRole::Assistant => "Assistant",
+            };
+            let _ = writeln!(out, "\n### {}\n", role);
+            for block in &message.blocks {
+                render_block(&mut out, block);
+            }
+        }
+    }
+    out
+}
+
+fn render_block(out: &mut String, block: &TranscriptBlock) {
+    match block {
+        TranscriptBlock::Text { text } => {
+            let _ = writeln!(out, "{}\n", text.trim_end());
+        }
+        TranscriptBlock::Thinking { text } => {
+            out.push_str("<details><summary>思考过程</summary>\n\n");
+            let _ = writeln!(out, "{}\n\n</details>\n", text.trim_end());
+        }
+        TranscriptBlock::ToolUse { id, name, input } => {
+            let input = serde_json::to_string_pretty(input).unwrap_or_default();
+            let _ = writeln!(out, "**调用工具 `{}`** (`{}`)\n", name, id);
+            let _ = writeln!(out, "{}\n", fenced(&input, "json"));
+        }
+        TranscriptBlock::ToolResult {
+            tool_use_id,
+            content,
+            is_error,
+        } => {
+            let label = if *is_error {
+                "工具错误"
+            } else {
+                "工具结果"
+            };
+            let _ = writeln!(out, "**{}** (`{}`)\n", label, tool_use_id);
+            let _ = writeln!(out, "{}\n", fenced(content, ""));
+        }
+        TranscriptBlock::Image { media_type } => {
+            let _ = writeln!(out, "*[图片: {}]*\n", media_type);
+        }
+    }
+}
+
+/// 代码块（围栏长度超过内容中最长的连续反引号）
+fn fenced(content: &str, lang: &str) -> String {
+    let longest = content.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest.max(2) + 1);
+    format!("{fence}{lang}\n{}\n{fence}", content.trim_end())
+}
+
+fn format_time(timestamp_ms: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(timestamp_ms)
+        .map(|t| {
+            t.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn anthropic_request(messages: Value) -> Vec<u8> {
+        serde_json::to_vec(&serde_json::json!({
+            "model": "claude-sonnet-4",
+            "system": [{ "type": "text", "text": "You are helpful." }],
+            "messages": messages,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_reply_from_sse() {
+        let body = concat!(
+            "event: message_start\ndata: {\"type\":\"message_start\",\"message\":{\"id\":\"m1\",\"content\":[]}}\n\n",
+            "event: content_block_start\ndata: {\"type\":\"content_block_start\",\"index\":0,\"content_block\":{\"type\":\"text\",\"text\":\"\"}}\n\n",
+            "event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"Let me \"}}\n\n",
+            "event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"check.\"}}\n\n",
+            "event: content_block_start\ndata: {\"type\":\"content_block_start\",\"index\":1,\"content_block\":{\"type\":\"tool_use\",\"id\":\"t1\",\"name\":\"Read\",\"input\":{}}}\n\n",
+            "event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"index\":1,\"delta\":{\"type\":\"input_json_delta\",\"partial_json\":\"{\\\"path\\\":\"}}\n\n",
+            "event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"index\":1,\"delta\":{\"type\":\"input_json_delta\",\"partial_json\":\"\\\"a.rs\\\"}\"}}\n\n",
+            "event: message_stop\ndata: {\"type\":\"message_stop\"}\n\n",
+        );
+
+        let reply = reply_from_sse(ApiProtocol::Anthropic, body.as_bytes()).unwrap();
+        assert_eq!(
+            reply.blocks,
+            vec![
+                TranscriptBlock::Text {
+                    text: "Let me check.".to_string()
+                },
+                TranscriptBlock::ToolUse {
+                    id: "t1".to_string(),
+                    name: "Read".to_string(),
+                    input: serde_json::json!({ "path": "a.rs" }),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_openai_chat_exchange_normalized() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = dir.path().join(TRANSCRIPT_DB);
+        let request = serde_json::json!({
+            "model": "gpt-5",
+            "messages": [
+                { "role": "system", "content": "Be brief." },
+                { "role": "user", "content": "hi" },
+            ],
+        });
+        let response = serde_json::json!({
+            "choices": [{ "message": { "role": "assistant", "content": "hello" }, "finish_reason": "stop" }],
+        });
+        let record = ExchangeRecord {
+            session_id: "s1",
+            tool_id: "codex",
+            path: "/v1/chat/completions",
+            request_body: &serde_json::to_vec(&request).unwrap(),
+            response_body: &serde_json::to_vec(&response).unwrap(),
+            is_sse: false,
+        };
+        assert!(insert_exchange(&db, &record, 1_000).unwrap());
+
+        let transcript = load_transcript(&db, "s1", 2_000).unwrap();
+        let messages = &transcript.exchanges[0].messages;
+        let roles: Vec<TranscriptRole> = messages.iter().map(|m| m.role).collect();
+        assert_eq!(
+            roles,
+            vec![
+                TranscriptRole::System,
+                TranscriptRole::User,
+                TranscriptRole::Assistant
+            ]
+        );
+        assert_eq!(
+            messages[2].blocks,
+            vec![TranscriptBlock::Text {
+                text: "hello".to_string()
+            }]
+        );
+        assert_eq!(transcript.exchanges[0].model.as_deref(), Some("gpt-5"));
+    }
+
+    fn anthropic_record<'a>(request: &'a [u8], response: &'a [u8]) -> ExchangeRecord<'a> {
+        ExchangeRecord {
+            session_id: "s1",
+            tool_id: "claude-code",
+            path: "/v1/messages",
+            request_body: request,
+            response_body: response,
+            is_sse: false,
+        }
+    }
+
+    fn anthropic_reply(text: &str) -> Vec<u8> {
+        serde_json::to_vec(&serde_json::json!({
+            "content": [
+                { "type": "thinking", "thinking": "hmm" },
+                { "type": "text", "text": text },
+            ],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_transcript_stores_only_new_messages() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = dir.path().join(TRANSCRIPT_DB);
+
+        let first = anthropic_request(serde_json::json!([
+            { "role": "user", "content": "What is 1+1?" },
+        ]));
+        insert_exchange(&db, &anthropic_record(&first, &anthropic_reply("2")), 1_000).unwrap();
+
+        // 标题生成等旁路请求：上下文不同，保存完整消息
+        let side = serde_json::to_vec(&serde_json::json!({
+            "model": "claude-haiku",
+            "messages": [{ "role": "user", "content": "Summarize: What is 1+1?" }],
+        }))
+        .unwrap();
+        insert_exchange(
+            &db,
+            &anthropic_record(&side, &anthropic_reply("Math")),
+            1_500,
+        )
+        .unwrap();
+
+        // 客户端回传历史时省略了思考内容，仍视为接续
+        let second = anthropic_request(serde_json::json!([
+            { "role": "user", "content": "What is 1+1?" },
+            { "role": "assistant", "content": [{ "type": "text", "text": "2" }] },
+            { "role": "user", "content": [{ "type": "text", "text": "And 2+2?" }] },
+        ]));
+        insert_exchange(
+            &db,
+            &anthropic_record(&second, &anthropic_reply("4")),
+            2_000,
+        )
+        .unwrap();
+
+        let transcript = load_transcript(&db, "s1", 3_000).unwrap();
+        assert_eq!(transcript.tool_id.as_deref(), Some("claude-code"));
+        let exchanges = &transcript.exchanges;
+        assert_eq!(exchanges.len(), 3);
+        assert!(!exchanges[0].continues);
+        assert_eq!(exchanges[0].messages.len(), 3);
+        assert!(!exchanges[1].continues);
+        assert!(exchanges[2].continues);
+        assert_eq!(exchanges[2].messages.len(), 2);
+        assert_eq!(exchanges[2].messages[0].role, TranscriptRole::User);
+
+        let markdown = render_markdown(&transcript);
+        assert!(markdown.contains("## 请求 3"));
+        assert!(markdown.contains("And 2+2?"));
+        assert!(markdown.contains("以下为该请求的完整上下文"));
+    }
+
+    #[test]
+    fn test_fenced_escapes_backticks() {
+        assert_eq!(fenced("let x = 1;", "rust"), "```rust\nlet x = 1;\n```");
+        assert_eq!(fenced("a ``` b", ""), "````\na ``` b\n````");
+    }
+}
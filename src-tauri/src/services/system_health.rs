@@ -0,0 +1,29 @@
+//! 系统健康报告
+//!
+//! 汇总后台组件（配置守护等）的运行状态，后台线程静默失效时可在此发现。
+
+use crate::services::config::watcher::{watcher_health, WatcherHealth};
+use serde::Serialize;
+
+/// 系统健康报告
+#[derive(Debug, Clone, Serialize)]
+pub struct SystemHealthReport {
+    /// 生成时间（Unix 时间戳，毫秒）
+    pub generated_at: i64,
+    /// 所有已启用的后台组件均正常
+    pub healthy: bool,
+    /// 配置守护（外部配置变更检测）
+    pub config_watcher: WatcherHealth,
+}
+
+/// 收集系统健康状态
+pub fn collect_system_health() -> SystemHealthReport {
+    let config_watcher = watcher_health();
+    let watcher_ok = !config_watcher.enabled || (config_watcher.running && !config_watcher.stalled);
+
+    SystemHealthReport {
+        generated_at: chrono::Utc::now().timestamp_millis(),
+        healthy: watcher_ok,
+        config_watcher,
+    }
+}
@@ -1,10 +1,12 @@
 //! Token 统计分析模块
 //!
-//! 提供趋势分析和成本汇总查询功能
+//! 提供趋势分析、成本汇总、工具横向对比、响应时间分位数与缓存效率查询功能
 
 use crate::data::DataManager;
-use anyhow::{Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 
 /// 时间粒度
@@ -24,6 +26,19 @@ pub enum TimeGranularity {
     Day,
 }
 
+impl TimeGranularity {
+    /// 时间桶长度（毫秒）
+    pub fn interval_ms(self) -> i64 {
+        match self {
+            TimeGranularity::FifteenMinutes => 15 * 60 * 1000,
+            TimeGranularity::ThirtyMinutes => 30 * 60 * 1000,
+            TimeGranularity::Hour => 60 * 60 * 1000,
+            TimeGranularity::TwelveHours => 12 * 60 * 60 * 1000,
+            TimeGranularity::Day => 24 * 60 * 60 * 1000,
+        }
+    }
+}
+
 /// 趋势查询参数
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct TrendQuery {
@@ -39,6 +54,9 @@ pub struct TrendQuery {
     pub config_name: Option<String>,
     /// 会话 ID 过滤
     pub session_id: Option<String>,
+    /// 标签过滤（如项目名）
+    #[serde(default)]
+    pub tag: Option<String>,
     /// 时间粒度
     pub granularity: TimeGranularity,
 }
@@ -85,6 +103,10 @@ pub enum CostGroupBy {
     Config,
     /// 按会话分组
     Session,
+    /// 按 Key 池中的 Key 别名分组（未使用 Key 池的请求归为空别名）
+    UpstreamKey,
+    /// 按鉴权所用的客户端 Key 标签分组（使用主保护密钥的请求归为空标签）
+    ClientKey,
 }
 
 /// 成本汇总查询参数
@@ -98,6 +120,9 @@ pub struct CostSummaryQuery {
     pub tool_type: Option<String>,
     /// 会话 ID 过滤
     pub session_id: Option<String>,
+    /// 标签过滤（如项目名）
+    #[serde(default)]
+    pub tag: Option<String>,
     /// 分组方式
     pub group_by: CostGroupBy,
 }
@@ -119,6 +144,427 @@ pub struct CostSummary {
     pub avg_response_time: Option<f64>,
 }
 
+/// 月度账单查询参数
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct MonthlyCostQuery {
+    /// 开始时间戳（毫秒）
+    pub start_time: Option<i64>,
+    /// 结束时间戳（毫秒）
+    pub end_time: Option<i64>,
+    /// 工具类型过滤
+    pub tool_type: Option<String>,
+}
+
+/// 单个价格模板的月度账单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonthlyTemplateCost {
+    /// 价格模板 ID（未记录模板时为空字符串）
+    pub template_id: String,
+    /// 用量成本（含按次附加费，USD）
+    pub usage_cost: f64,
+    pub request_count: i64,
+    /// 模板的月最低消费（USD）
+    pub min_monthly_fee: f64,
+    /// 为达到最低消费补足的金额（USD）
+    pub minimum_topup: f64,
+    /// 结算金额：max(用量成本, 最低消费)
+    pub billed_cost: f64,
+}
+
+/// 月度账单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonthlyCostReport {
+    /// 月份（本地时间，YYYY-MM）
+    pub month: String,
+    pub usage_cost: f64,
+    pub minimum_topup: f64,
+    pub billed_cost: f64,
+    pub request_count: i64,
+    pub templates: Vec<MonthlyTemplateCost>,
+}
+
+/// 标签过滤条件（tags 为逗号分隔文本，前后补逗号后按整词匹配）
+pub const TAG_FILTER_CLAUSE: &str = "instr(',' || COALESCE(tags, '') || ',', ?) > 0";
+
+/// 标签过滤参数
+pub fn tag_filter_param(tag: &str) -> String {
+    format!(",{},", tag.trim())
+}
+
+/// 解析起始时间：相对时长（`30m` / `24h` / `7d` / `2w`）或日期（本地时间零点），返回毫秒时间戳
+pub fn parse_since(value: &str, now: DateTime<Local>) -> Result<i64> {
+    let value = value.trim();
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        let midnight = date
+            .and_hms_opt(0, 0, 0)
+            .and_then(|dt| Local.from_local_datetime(&dt).earliest())
+            .ok_or_else(|| anyhow!("无效的日期: {}", value))?;
+        return Ok(midnight.timestamp_millis());
+    }
+
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+    let Ok(amount) = amount.parse::<i64>() else {
+        bail!(
+            "无效的时间范围: {}（示例：30m、24h、7d、2026-10-01）",
+            value
+        );
+    };
+    let duration = match unit {
+        "m" => Duration::minutes(amount),
+        "h" => Duration::hours(amount),
+        "d" => Duration::days(amount),
+        "w" => Duration::weeks(amount),
+        _ => bail!("无效的时间单位: {}（支持 m / h / d / w）", value),
+    };
+    Ok((now - duration).timestamp_millis())
+}
+
+/// 参与横向对比的工具（按展示顺序）
+const COMPARED_TOOLS: [&str; 4] = ["claude-code", "codex", "gemini-cli", "amp-code"];
+
+/// 工具横向对比查询参数
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ToolComparisonQuery {
+    /// 开始时间戳（毫秒）
+    pub start_time: Option<i64>,
+    /// 结束时间戳（毫秒）
+    pub end_time: Option<i64>,
+}
+
+/// 单个工具的对比指标
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolComparisonStat {
+    pub tool_type: String,
+    pub request_count: i64,
+    pub failed_requests: i64,
+    /// 总成本（USD）
+    pub total_cost: f64,
+    /// 每次请求平均成本（无请求时为 None）
+    pub cost_per_request: Option<f64>,
+    /// 平均响应时间（毫秒）
+    pub avg_response_time: Option<f64>,
+    /// 失败率（0~1）
+    pub error_rate: Option<f64>,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cache_creation_tokens: i64,
+    pub cache_read_tokens: i64,
+    /// 缓存命中率：缓存读取 / (输入 + 缓存写入 + 缓存读取)
+    pub cache_hit_rate: Option<f64>,
+}
+
+/// 各项指标表现最好的工具（仅在有请求的工具之间比较）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolComparisonLeaders {
+    pub lowest_cost_per_request: Option<String>,
+    pub lowest_latency: Option<String>,
+    pub lowest_error_rate: Option<String>,
+    pub highest_cache_hit_rate: Option<String>,
+}
+
+/// 工具横向对比结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolComparison {
+    pub start_time: Option<i64>,
+    pub end_time: Option<i64>,
+    /// 每个工具一项（无请求的工具指标为 0 / None）
+    pub tools: Vec<ToolComparisonStat>,
+    pub leaders: ToolComparisonLeaders,
+}
+
+impl ToolComparisonStat {
+    /// 填充派生指标
+    fn with_derived_metrics(mut self) -> Self {
+        if self.request_count > 0 {
+            let requests = self.request_count as f64;
+            self.cost_per_request = Some(self.total_cost / requests);
+            self.error_rate = Some(self.failed_requests as f64 / requests);
+        }
+        let cacheable = self.input_tokens + self.cache_creation_tokens + self.cache_read_tokens;
+        if cacheable > 0 {
+            self.cache_hit_rate = Some(self.cache_read_tokens as f64 / cacheable as f64);
+        }
+        self
+    }
+}
+
+impl ToolComparisonLeaders {
+    /// 从对比结果中选出各项指标最优的工具
+    fn from_stats(stats: &[ToolComparisonStat]) -> Self {
+        fn pick(
+            stats: &[ToolComparisonStat],
+            metric: impl Fn(&ToolComparisonStat) -> Option<f64>,
+            lower_is_better: bool,
+        ) -> Option<String> {
+            stats
+                .iter()
+                .filter(|s| s.request_count > 0)
+                .filter_map(|s| metric(s).map(|v| (s, v)))
+                .min_by(|(_, a), (_, b)| {
+                    let ord = a.total_cmp(b);
+                    if lower_is_better {
+                        ord
+                    } else {
+                        ord.reverse()
+                    }
+                })
+                .map(|(s, _)| s.tool_type.clone())
+        }
+
+        Self {
+            lowest_cost_per_request: pick(stats, |s| s.cost_per_request, true),
+            lowest_latency: pick(stats, |s| s.avg_response_time, true),
+            lowest_error_rate: pick(stats, |s| s.error_rate, true),
+            highest_cache_hit_rate: pick(stats, |s| s.cache_hit_rate, false),
+        }
+    }
+}
+
+/// 按模型用量明细查询参数（对比周期为紧邻其前、等长的时间段）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ModelBreakdownQuery {
+    /// 开始时间戳（毫秒）
+    pub start_time: i64,
+    /// 结束时间戳（毫秒）
+    pub end_time: i64,
+}
+
+/// 单个模型在一个周期内的用量
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModelUsageStat {
+    pub model: String,
+    pub request_count: i64,
+    pub failed_requests: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cache_creation_tokens: i64,
+    pub cache_read_tokens: i64,
+    /// 总 Token 数（输入 + 输出 + 缓存）
+    pub total_tokens: i64,
+    /// 总成本（USD）
+    pub total_cost: f64,
+    /// 平均响应时间（毫秒）
+    pub avg_response_time: Option<f64>,
+    /// 失败率（0~1）
+    pub error_rate: Option<f64>,
+}
+
+/// 模型排行中的一项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelBreakdownEntry {
+    pub current: ModelUsageStat,
+    /// 上一周期的用量（上一周期未使用该模型时为 None）
+    pub previous: Option<ModelUsageStat>,
+    /// 占本周期总成本的比例（0~1）
+    pub cost_share: Option<f64>,
+    /// 请求数环比变化（百分比，上一周期无请求时为 None）
+    pub request_change_percent: Option<f64>,
+    /// 成本环比变化（百分比，上一周期无成本时为 None）
+    pub cost_change_percent: Option<f64>,
+}
+
+/// 按模型用量明细（按成本降序，成本相同时按请求数）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelBreakdown {
+    pub start_time: i64,
+    pub end_time: i64,
+    pub previous_start_time: i64,
+    pub previous_end_time: i64,
+    pub total_requests: i64,
+    pub total_cost: f64,
+    pub previous_total_requests: i64,
+    pub previous_total_cost: f64,
+    pub models: Vec<ModelBreakdownEntry>,
+}
+
+impl ModelUsageStat {
+    /// 填充派生指标
+    fn with_derived_metrics(mut self) -> Self {
+        self.total_tokens = self.input_tokens
+            + self.output_tokens
+            + self.cache_creation_tokens
+            + self.cache_read_tokens;
+        if self.request_count > 0 {
+            self.error_rate = Some(self.failed_requests as f64 / self.request_count as f64);
+        }
+        self
+    }
+}
+
+/// 环比变化百分比（基数为 0 时无意义，返回 None）
+fn change_percent(current: f64, previous: f64) -> Option<f64> {
+    (previous > 0.0).then(|| (current - previous) / previous * 100.0)
+}
+
+/// 响应时间统计分组方式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LatencyGroupBy {
+    /// 按模型分组
+    #[default]
+    Model,
+    /// 按配置分组
+    Config,
+    /// 按时间桶分组（粒度由 granularity 指定）
+    Time,
+}
+
+/// 响应时间统计查询参数
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct LatencyStatsQuery {
+    /// 开始时间戳（毫秒）
+    pub start_time: Option<i64>,
+    /// 结束时间戳（毫秒）
+    pub end_time: Option<i64>,
+    /// 工具类型过滤
+    pub tool_type: Option<String>,
+    /// 模型过滤
+    pub model: Option<String>,
+    /// 配置名称过滤
+    pub config_name: Option<String>,
+    /// 标签过滤（如项目名）
+    #[serde(default)]
+    pub tag: Option<String>,
+    /// 分组方式
+    #[serde(default)]
+    pub group_by: LatencyGroupBy,
+    /// 时间粒度（仅按时间分组时使用）
+    #[serde(default)]
+    pub granularity: TimeGranularity,
+}
+
+/// 单个分组的响应时间分布（毫秒）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LatencyStats {
+    /// 分组名称（模型 / 配置名称；按时间分组时为时间桶起点的毫秒时间戳）
+    pub group_name: String,
+    /// 计入统计的请求数（仅成功且记录了响应时间的请求）
+    pub request_count: i64,
+    pub avg_ms: f64,
+    pub min_ms: i64,
+    pub max_ms: i64,
+    pub p50_ms: i64,
+    pub p90_ms: i64,
+    pub p99_ms: i64,
+}
+
+impl LatencyStats {
+    /// 由升序排列的响应时间计算分布（最近秩法）
+    fn from_sorted(group_name: String, sorted: &[i64]) -> Option<Self> {
+        let (&min_ms, &max_ms) = (sorted.first()?, sorted.last()?);
+        let percentile = |p: f64| {
+            let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+            sorted[rank.clamp(1, sorted.len()) - 1]
+        };
+        Some(Self {
+            group_name,
+            request_count: sorted.len() as i64,
+            avg_ms: sorted.iter().sum::<i64>() as f64 / sorted.len() as f64,
+            min_ms,
+            max_ms,
+            p50_ms: percentile(50.0),
+            p90_ms: percentile(90.0),
+            p99_ms: percentile(99.0),
+        })
+    }
+}
+
+/// 缓存效率查询参数
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CacheEfficiencyQuery {
+    /// 开始时间戳（毫秒）
+    pub start_time: Option<i64>,
+    /// 结束时间戳（毫秒）
+    pub end_time: Option<i64>,
+    /// 工具类型过滤
+    pub tool_type: Option<String>,
+    /// 时间粒度
+    #[serde(default)]
+    pub granularity: TimeGranularity,
+}
+
+/// 缓存效率指标
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CacheEfficiencyStat {
+    pub request_count: i64,
+    pub input_tokens: i64,
+    pub cache_creation_tokens: i64,
+    pub cache_read_tokens: i64,
+    /// 缓存读取占比：缓存读取 / (输入 + 缓存写入 + 缓存读取)
+    pub cache_read_ratio: Option<f64>,
+    /// 缓存写入与读取的实际成本（USD）
+    pub actual_cache_cost: f64,
+    /// 假设不使用缓存时，这部分 Token 按输入单价计价的成本（USD）
+    pub uncached_cost: f64,
+    /// 缓存节省（USD）：uncached_cost - actual_cache_cost，缓存写入多于复用时为负
+    pub saved_cost: f64,
+}
+
+/// 单个模型的缓存效率
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelCacheEfficiency {
+    pub model: String,
+    #[serde(flatten)]
+    pub stat: CacheEfficiencyStat,
+}
+
+/// 单个时间桶的缓存效率
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CacheEfficiencyPoint {
+    /// 时间桶起点（毫秒）
+    pub timestamp: i64,
+    #[serde(flatten)]
+    pub stat: CacheEfficiencyStat,
+}
+
+/// 缓存效率分析结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEfficiencyReport {
+    pub total: CacheEfficiencyStat,
+    /// 按节省金额降序
+    pub by_model: Vec<ModelCacheEfficiency>,
+    /// 按时间升序（指定起止时间时补齐空桶）
+    pub by_time: Vec<CacheEfficiencyPoint>,
+}
+
+impl CacheEfficiencyStat {
+    /// 累加一组用量（`input_price_per_token` 为 None 时不估算节省）
+    fn add(&mut self, row: &CacheEfficiencyRow, input_price_per_token: Option<f64>) {
+        let actual = row.cache_write_price + row.cache_read_price;
+        let uncached = input_price_per_token
+            .map(|price| (row.cache_creation_tokens + row.cache_read_tokens) as f64 * price)
+            .unwrap_or(actual);
+        self.request_count += row.request_count;
+        self.input_tokens += row.input_tokens;
+        self.cache_creation_tokens += row.cache_creation_tokens;
+        self.cache_read_tokens += row.cache_read_tokens;
+        self.actual_cache_cost += actual;
+        self.uncached_cost += uncached;
+        self.saved_cost = self.uncached_cost - self.actual_cache_cost;
+        let cacheable = self.input_tokens + self.cache_creation_tokens + self.cache_read_tokens;
+        self.cache_read_ratio =
+            (cacheable > 0).then(|| self.cache_read_tokens as f64 / cacheable as f64);
+    }
+}
+
+/// 按时间桶、工具、模型与价格模板聚合的缓存用量
+struct CacheEfficiencyRow {
+    timestamp: i64,
+    tool_type: String,
+    model: String,
+    template_id: String,
+    request_count: i64,
+    input_tokens: i64,
+    cache_creation_tokens: i64,
+    cache_read_tokens: i64,
+    input_price: f64,
+    cache_write_price: f64,
+    cache_read_price: f64,
+}
+
 /// Token 统计分析服务
 pub struct TokenStatsAnalytics {
     db_path: PathBuf,
@@ -194,6 +640,11 @@ impl TokenStatsAnalytics {
             params.push(Box::new(session_id.clone()));
         }
 
+        if let Some(ref tag) = query.tag {
+            where_clauses.push(TAG_FILTER_CLAUSE);
+            params.push(Box::new(tag_filter_param(tag)));
+        }
+
         let where_clause = if where_clauses.is_empty() {
             String::new()
         } else {
@@ -278,13 +729,7 @@ impl TokenStatsAnalytics {
         use std::collections::HashMap;
 
         // 计算时间间隔（毫秒）
-        let interval_ms = match granularity {
-            TimeGranularity::FifteenMinutes => 15 * 60 * 1000,
-            TimeGranularity::ThirtyMinutes => 30 * 60 * 1000,
-            TimeGranularity::Hour => 60 * 60 * 1000,
-            TimeGranularity::TwelveHours => 12 * 60 * 60 * 1000,
-            TimeGranularity::Day => 24 * 60 * 60 * 1000,
-        };
+        let interval_ms = granularity.interval_ms();
 
         // 将数据库结果转换为 HashMap 以便快速查找
         let mut data_map: HashMap<i64, TrendDataPoint> = HashMap::new();
@@ -336,6 +781,8 @@ impl TokenStatsAnalytics {
             CostGroupBy::Model => "model",
             CostGroupBy::Config => "config_name",
             CostGroupBy::Session => "session_id",
+            CostGroupBy::UpstreamKey => "COALESCE(upstream_key_alias, '')",
+            CostGroupBy::ClientKey => "COALESCE(client_key_label, '')",
         };
 
         // 构建 WHERE 子句
@@ -362,6 +809,11 @@ impl TokenStatsAnalytics {
             params.push(Box::new(session_id.clone()));
         }
 
+        if let Some(ref tag) = query.tag {
+            where_clauses.push(TAG_FILTER_CLAUSE);
+            params.push(Box::new(tag_filter_param(tag)));
+        }
+
         let where_clause = if where_clauses.is_empty() {
             String::new()
         } else {
@@ -407,6 +859,511 @@ impl TokenStatsAnalytics {
     }
 }
 
+impl TokenStatsAnalytics {
+    /// 同一时间段内各工具的横向对比（成本、延迟、失败率、缓存效率）
+    pub fn query_tool_comparison(&self, query: &ToolComparisonQuery) -> Result<ToolComparison> {
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+
+        let mut where_clauses = Vec::new();
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+
+        if let Some(start_time) = query.start_time {
+            where_clauses.push("timestamp >= ?");
+            params.push(Box::new(start_time));
+        }
+
+        if let Some(end_time) = query.end_time {
+            where_clauses.push("timestamp <= ?");
+            params.push(Box::new(end_time));
+        }
+
+        let where_clause = if where_clauses.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", where_clauses.join(" AND "))
+        };
+
+        let sql = format!(
+            "SELECT
+                tool_type,
+                COUNT(*) as request_count,
+                COALESCE(SUM(CASE WHEN request_status IN ('failed', 'timeout') THEN 1 ELSE 0 END), 0) as failed,
+                COALESCE(SUM(total_cost), 0.0) as total_cost,
+                AVG(response_time_ms) as avg_response_time,
+                COALESCE(SUM(input_tokens), 0) as input_tokens,
+                COALESCE(SUM(output_tokens), 0) as output_tokens,
+                COALESCE(SUM(cache_creation_tokens), 0) as cache_creation_tokens,
+                COALESCE(SUM(cache_read_tokens), 0) as cache_read_tokens
+            FROM token_logs
+            {}
+            GROUP BY tool_type",
+            where_clause
+        );
+
+        let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+
+        let rows = manager.transaction(|tx| {
+            let mut stmt = tx.prepare(&sql)?;
+            let rows = stmt
+                .query_map(param_refs.as_slice(), |row| {
+                    Ok(ToolComparisonStat {
+                        tool_type: row.get(0)?,
+                        request_count: row.get(1)?,
+                        failed_requests: row.get(2)?,
+                        total_cost: row.get(3)?,
+                        avg_response_time: row.get(4)?,
+                        input_tokens: row.get(5)?,
+                        output_tokens: row.get(6)?,
+                        cache_creation_tokens: row.get(7)?,
+                        cache_read_tokens: row.get(8)?,
+                        ..Default::default()
+                    })
+                })?
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(crate::data::DataError::Database)?;
+            Ok(rows)
+        })?;
+
+        // 固定展示全部工具，未使用的工具以空指标占位
+        let tools: Vec<ToolComparisonStat> = COMPARED_TOOLS
+            .iter()
+            .map(|tool| {
+                rows.iter()
+                    .find(|row| row.tool_type == *tool)
+                    .cloned()
+                    .unwrap_or_else(|| ToolComparisonStat {
+                        tool_type: tool.to_string(),
+                        ..Default::default()
+                    })
+                    .with_derived_metrics()
+            })
+            .collect();
+
+        Ok(ToolComparison {
+            start_time: query.start_time,
+            end_time: query.end_time,
+            leaders: ToolComparisonLeaders::from_stats(&tools),
+            tools,
+        })
+    }
+}
+
+impl TokenStatsAnalytics {
+    /// 按模型的用量排行（请求数、Token、成本、平均延迟、失败率），附带与上一等长周期的对比
+    pub fn query_model_breakdown(&self, query: &ModelBreakdownQuery) -> Result<ModelBreakdown> {
+        if query.start_time >= query.end_time {
+            bail!("开始时间必须早于结束时间");
+        }
+        let span = query.end_time - query.start_time;
+        let previous_start = query.start_time - span;
+
+        let current = self.model_usage(query.start_time, query.end_time)?;
+        let previous = self.model_usage(previous_start, query.start_time)?;
+        let total_cost: f64 = current.iter().map(|m| m.total_cost).sum();
+
+        let mut models: Vec<ModelBreakdownEntry> = current
+            .into_iter()
+            .map(|stat| {
+                let previous = previous.iter().find(|p| p.model == stat.model).cloned();
+                let (prev_requests, prev_cost) = previous
+                    .as_ref()
+                    .map_or((0.0, 0.0), |p| (p.request_count as f64, p.total_cost));
+                ModelBreakdownEntry {
+                    cost_share: (total_cost > 0.0).then(|| stat.total_cost / total_cost),
+                    request_change_percent: change_percent(
+                        stat.request_count as f64,
+                        prev_requests,
+                    ),
+                    cost_change_percent: change_percent(stat.total_cost, prev_cost),
+                    current: stat,
+                    previous,
+                }
+            })
+            .collect();
+        models.sort_by(|a, b| {
+            b.current
+                .total_cost
+                .total_cmp(&a.current.total_cost)
+                .then(b.current.request_count.cmp(&a.current.request_count))
+                .then(a.current.model.cmp(&b.current.model))
+        });
+
+        Ok(ModelBreakdown {
+            start_time: query.start_time,
+            end_time: query.end_time,
+            previous_start_time: previous_start,
+            previous_end_time: query.start_time,
+            total_requests: models.iter().map(|m| m.current.request_count).sum(),
+            total_cost,
+            previous_total_requests: previous.iter().map(|m| m.request_count).sum(),
+            previous_total_cost: previous.iter().map(|m| m.total_cost).sum(),
+            models,
+        })
+    }
+
+    /// 时间段 [start, end) 内按模型汇总的用量
+    fn model_usage(&self, start: i64, end: i64) -> Result<Vec<ModelUsageStat>> {
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+
+        let sql = "SELECT
+                model,
+                COUNT(*) as request_count,
+                COALESCE(SUM(CASE WHEN request_status IN ('failed', 'timeout') THEN 1 ELSE 0 END), 0) as failed,
+                COALESCE(SUM(input_tokens), 0) as input_tokens,
+                COALESCE(SUM(output_tokens), 0) as output_tokens,
+                COALESCE(SUM(cache_creation_tokens), 0) as cache_creation_tokens,
+                COALESCE(SUM(cache_read_tokens), 0) as cache_read_tokens,
+                COALESCE(SUM(total_cost), 0.0) as total_cost,
+                AVG(response_time_ms) as avg_response_time
+            FROM token_logs
+            WHERE timestamp >= ?1 AND timestamp < ?2
+            GROUP BY model";
+
+        let rows = manager.transaction(|tx| {
+            let mut stmt = tx.prepare(sql)?;
+            let rows = stmt
+                .query_map(rusqlite::params![start, end], |row| {
+                    Ok(ModelUsageStat {
+                        model: row.get(0)?,
+                        request_count: row.get(1)?,
+                        failed_requests: row.get(2)?,
+                        input_tokens: row.get(3)?,
+                        output_tokens: row.get(4)?,
+                        cache_creation_tokens: row.get(5)?,
+                        cache_read_tokens: row.get(6)?,
+                        total_cost: row.get(7)?,
+                        avg_response_time: row.get(8)?,
+                        ..Default::default()
+                    })
+                })?
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(crate::data::DataError::Database)?;
+            Ok(rows)
+        })?;
+
+        Ok(rows
+            .into_iter()
+            .map(ModelUsageStat::with_derived_metrics)
+            .collect())
+    }
+}
+
+impl TokenStatsAnalytics {
+    /// 响应时间分位数（p50 / p90 / p99），按模型、配置或时间桶分组
+    ///
+    /// 失败请求的耗时不代表上游的正常响应速度，不计入统计。
+    /// 按时间分组时按时间升序返回，其余按请求数降序返回
+    pub fn query_latency_stats(&self, query: &LatencyStatsQuery) -> Result<Vec<LatencyStats>> {
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+
+        // 未记录的响应时间以空字符串写入，按存储类型筛选
+        let mut where_clauses = vec![
+            "typeof(response_time_ms) = 'integer'",
+            "request_status NOT IN ('failed', 'timeout')",
+        ];
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+
+        if let Some(start_time) = query.start_time {
+            where_clauses.push("timestamp >= ?");
+            params.push(Box::new(start_time));
+        }
+
+        if let Some(end_time) = query.end_time {
+            where_clauses.push("timestamp <= ?");
+            params.push(Box::new(end_time));
+        }
+
+        if let Some(ref tool_type) = query.tool_type {
+            where_clauses.push("tool_type = ?");
+            params.push(Box::new(tool_type.clone()));
+        }
+
+        if let Some(ref model) = query.model {
+            where_clauses.push("model = ?");
+            params.push(Box::new(model.clone()));
+        }
+
+        if let Some(ref config_name) = query.config_name {
+            where_clauses.push("config_name = ?");
+            params.push(Box::new(config_name.clone()));
+        }
+
+        if let Some(ref tag) = query.tag {
+            where_clauses.push(TAG_FILTER_CLAUSE);
+            params.push(Box::new(tag_filter_param(tag)));
+        }
+
+        let interval_ms = query.granularity.interval_ms();
+        let group_expr = match query.group_by {
+            LatencyGroupBy::Model => "model".to_string(),
+            LatencyGroupBy::Config => "config_name".to_string(),
+            LatencyGroupBy::Time => {
+                format!("CAST((timestamp / {interval_ms}) * {interval_ms} AS TEXT)")
+            }
+        };
+
+        // 分位数在 Rust 中按分组计算：SQLite 没有内置的分位数聚合函数
+        let sql = format!(
+            "SELECT {group_expr} as group_name, response_time_ms
+            FROM token_logs
+            WHERE {}
+            ORDER BY group_name, response_time_ms",
+            where_clauses.join(" AND ")
+        );
+
+        let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+
+        let rows = manager.transaction(|tx| {
+            let mut stmt = tx.prepare(&sql)?;
+            let rows = stmt
+                .query_map(param_refs.as_slice(), |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+                })?
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(crate::data::DataError::Database)?;
+            Ok(rows)
+        })?;
+
+        let mut stats: Vec<LatencyStats> = rows
+            .chunk_by(|a, b| a.0 == b.0)
+            .filter_map(|group| {
+                let times: Vec<i64> = group.iter().map(|(_, ms)| *ms).collect();
+                LatencyStats::from_sorted(group[0].0.clone(), &times)
+            })
+            .collect();
+
+        match query.group_by {
+            LatencyGroupBy::Time => {
+                stats.sort_by_key(|s| s.group_name.parse::<i64>().unwrap_or_default())
+            }
+            _ => stats.sort_by_key(|s| std::cmp::Reverse(s.request_count)),
+        }
+        Ok(stats)
+    }
+}
+
+impl TokenStatsAnalytics {
+    /// 月度账单：按月份与价格模板汇总用量成本，并计入模板的月最低消费
+    ///
+    /// `min_monthly_fees` 为模板 ID -> 月最低消费。最低消费只作用于当月有请求记录的模板。
+    pub fn query_monthly_costs(
+        &self,
+        query: &MonthlyCostQuery,
+        min_monthly_fees: &HashMap<String, f64>,
+    ) -> Result<Vec<MonthlyCostReport>> {
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+
+        let mut where_clauses = Vec::new();
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+        if let Some(start_time) = query.start_time {
+            where_clauses.push("timestamp >= ?");
+            params.push(Box::new(start_time));
+        }
+        if let Some(end_time) = query.end_time {
+            where_clauses.push("timestamp <= ?");
+            params.push(Box::new(end_time));
+        }
+        if let Some(ref tool_type) = query.tool_type {
+            where_clauses.push("tool_type = ?");
+            params.push(Box::new(tool_type.clone()));
+        }
+        let where_clause = if where_clauses.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", where_clauses.join(" AND "))
+        };
+
+        let sql = format!(
+            "SELECT
+                strftime('%Y-%m', timestamp / 1000, 'unixepoch', 'localtime') as month,
+                COALESCE(pricing_template_id, '') as template_id,
+                COALESCE(SUM(total_cost), 0) as usage_cost,
+                COUNT(*) as request_count
+            FROM token_logs
+            {}
+            GROUP BY month, template_id
+            ORDER BY month ASC, template_id ASC",
+            where_clause
+        );
+        let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+
+        let rows: Vec<(String, String, f64, i64)> = manager.transaction(|tx| {
+            let mut stmt = tx.prepare(&sql)?;
+            let rows = stmt
+                .query_map(param_refs.as_slice(), |row| {
+                    Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+                })?
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(crate::data::DataError::Database)?;
+            Ok(rows)
+        })?;
+
+        let mut reports: Vec<MonthlyCostReport> = Vec::new();
+        for (month, template_id, usage_cost, request_count) in rows {
+            let min_monthly_fee = min_monthly_fees.get(&template_id).copied().unwrap_or(0.0);
+            let billed_cost = usage_cost.max(min_monthly_fee);
+            let template = MonthlyTemplateCost {
+                template_id,
+                usage_cost,
+                request_count,
+                min_monthly_fee,
+                minimum_topup: billed_cost - usage_cost,
+                billed_cost,
+            };
+
+            if reports.last().is_none_or(|r| r.month != month) {
+                reports.push(MonthlyCostReport {
+                    month,
+                    usage_cost: 0.0,
+                    minimum_topup: 0.0,
+                    billed_cost: 0.0,
+                    request_count: 0,
+                    templates: Vec::new(),
+                });
+            }
+            let report = reports.last_mut().expect("刚插入的月份");
+            report.usage_cost += template.usage_cost;
+            report.minimum_topup += template.minimum_topup;
+            report.billed_cost += template.billed_cost;
+            report.request_count += template.request_count;
+            report.templates.push(template);
+        }
+        Ok(reports)
+    }
+}
+
+impl TokenStatsAnalytics {
+    /// 缓存效率：缓存读取占比，以及与不使用缓存时相比节省的成本，按模型与时间桶分组
+    ///
+    /// `input_price_lookup(template_id, tool_type, model)` 返回价格模板中的输入单价
+    /// （USD / Token，模板 ID 为空表示工具默认模板）。查不到时按该组日志记录的
+    /// 输入成本反推单价；仍无法确定时不估算该组的节省。
+    pub fn query_cache_efficiency(
+        &self,
+        query: &CacheEfficiencyQuery,
+        mut input_price_lookup: impl FnMut(&str, &str, &str) -> Option<f64>,
+    ) -> Result<CacheEfficiencyReport> {
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+
+        let mut where_clauses = vec!["(cache_creation_tokens > 0 OR cache_read_tokens > 0)"];
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+        if let Some(start_time) = query.start_time {
+            where_clauses.push("timestamp >= ?");
+            params.push(Box::new(start_time));
+        }
+        if let Some(end_time) = query.end_time {
+            where_clauses.push("timestamp <= ?");
+            params.push(Box::new(end_time));
+        }
+        if let Some(ref tool_type) = query.tool_type {
+            where_clauses.push("tool_type = ?");
+            params.push(Box::new(tool_type.clone()));
+        }
+
+        let interval_ms = query.granularity.interval_ms();
+        let sql = format!(
+            "SELECT
+                CAST((timestamp / {interval_ms}) * {interval_ms} AS INTEGER) as bucket,
+                tool_type,
+                model,
+                COALESCE(pricing_template_id, '') as template_id,
+                COUNT(*),
+                COALESCE(SUM(input_tokens), 0),
+                COALESCE(SUM(cache_creation_tokens), 0),
+                COALESCE(SUM(cache_read_tokens), 0),
+                COALESCE(SUM(input_price), 0.0),
+                COALESCE(SUM(cache_write_price), 0.0),
+                COALESCE(SUM(cache_read_price), 0.0)
+            FROM token_logs
+            WHERE {}
+            GROUP BY bucket, tool_type, model, template_id
+            ORDER BY bucket",
+            where_clauses.join(" AND ")
+        );
+        let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+
+        let rows = manager.transaction(|tx| {
+            let mut stmt = tx.prepare(&sql)?;
+            let rows = stmt
+                .query_map(param_refs.as_slice(), |row| {
+                    Ok(CacheEfficiencyRow {
+                        timestamp: row.get(0)?,
+                        tool_type: row.get(1)?,
+                        model: row.get(2)?,
+                        template_id: row.get(3)?,
+                        request_count: row.get(4)?,
+                        input_tokens: row.get(5)?,
+                        cache_creation_tokens: row.get(6)?,
+                        cache_read_tokens: row.get(7)?,
+                        input_price: row.get(8)?,
+                        cache_write_price: row.get(9)?,
+                        cache_read_price: row.get(10)?,
+                    })
+                })?
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(crate::data::DataError::Database)?;
+            Ok(rows)
+        })?;
+
+        let mut prices: HashMap<(String, String, String), Option<f64>> = HashMap::new();
+        let mut total = CacheEfficiencyStat::default();
+        let mut by_model: HashMap<String, CacheEfficiencyStat> = HashMap::new();
+        let mut by_time: BTreeMap<i64, CacheEfficiencyStat> = BTreeMap::new();
+        for row in &rows {
+            let key = (
+                row.template_id.clone(),
+                row.tool_type.clone(),
+                row.model.clone(),
+            );
+            let price = *prices.entry(key).or_insert_with(|| {
+                input_price_lookup(&row.template_id, &row.tool_type, &row.model)
+            });
+            let price = price.or_else(|| {
+                (row.input_tokens > 0).then(|| row.input_price / row.input_tokens as f64)
+            });
+            total.add(row, price);
+            by_model
+                .entry(row.model.clone())
+                .or_default()
+                .add(row, price);
+            by_time.entry(row.timestamp).or_default().add(row, price);
+        }
+
+        if let (Some(start_time), Some(end_time)) = (query.start_time, query.end_time) {
+            let mut bucket = (start_time / interval_ms) * interval_ms;
+            while bucket <= end_time {
+                by_time.entry(bucket).or_default();
+                bucket += interval_ms;
+            }
+        }
+
+        let mut by_model: Vec<ModelCacheEfficiency> = by_model
+            .into_iter()
+            .map(|(model, stat)| ModelCacheEfficiency { model, stat })
+            .collect();
+        by_model.sort_by(|a, b| b.stat.saved_cost.total_cmp(&a.stat.saved_cost));
+
+        Ok(CacheEfficiencyReport {
+            total,
+            by_model,
+            by_time: by_time
+                .into_iter()
+                .map(|(timestamp, stat)| CacheEfficiencyPoint { timestamp, stat })
+                .collect(),
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -415,6 +1372,28 @@ mod tests {
     use chrono::TimeZone;
     use tempfile::tempdir;
 
+    #[test]
+    fn test_parse_since() {
+        let now = Local.with_ymd_and_hms(2026, 10, 16, 12, 0, 0).unwrap();
+        let ms = |d: Duration| (now - d).timestamp_millis();
+
+        assert_eq!(parse_since("30m", now).unwrap(), ms(Duration::minutes(30)));
+        assert_eq!(parse_since("24h", now).unwrap(), ms(Duration::hours(24)));
+        assert_eq!(parse_since(" 7d ", now).unwrap(), ms(Duration::days(7)));
+        assert_eq!(parse_since("2w", now).unwrap(), ms(Duration::weeks(2)));
+        assert_eq!(
+            parse_since("2026-10-01", now).unwrap(),
+            Local
+                .with_ymd_and_hms(2026, 10, 1, 0, 0, 0)
+                .unwrap()
+                .timestamp_millis()
+        );
+
+        assert!(parse_since("7", now).is_err());
+        assert!(parse_since("d", now).is_err());
+        assert!(parse_since("3y", now).is_err());
+    }
+
     #[test]
     fn test_query_trends() {
         // 创建临时数据库
@@ -430,33 +1409,22 @@ mod tests {
             .timestamp_millis();
 
         for i in 0..10 {
-            let log = TokenLog::new(
-                "claude_code".to_string(),
-                base_time - (i * 3600 * 1000), // 每小时一条
-                "127.0.0.1".to_string(),
-                "test_session".to_string(),
-                "default".to_string(),
-                "claude-sonnet-4-5-20250929".to_string(),
-                Some(format!("msg_{}", i)),
-                100,
-                50,
-                10,
-                0, // cache_creation_1h_tokens
-                20,
-                0, // reasoning_tokens
-                "success".to_string(),
-                "json".to_string(),
-                None,
-                None,
-                Some(100),
-                Some(0.001),
-                Some(0.002),
-                Some(0.0001),
-                Some(0.0002),
-                None, // reasoning_price
-                0.0033,
-                Some("test_template".to_string()),
-            );
+            let log = TokenLog::test_builder()
+                .tool("claude_code")
+                .timestamp(base_time - (i * 3600 * 1000))
+                .session("test_session")
+                .model("claude-sonnet-4-5-20250929")
+                .message_id(&format!("msg_{}", i))
+                .tokens(100, 50)
+                .cache_tokens(10, 20)
+                .response_time(100)
+                .input_price(0.001)
+                .output_price(0.002)
+                .cache_write_price(0.0001)
+                .cache_read_price(0.0002)
+                .cost(0.0033)
+                .template("test_template")
+                .build();
             db.insert_log(&log).unwrap();
         }
 
@@ -495,33 +1463,22 @@ mod tests {
 
         for session_idx in 0..3 {
             for i in 0..5 {
-                let log = TokenLog::new(
-                    "claude_code".to_string(),
-                    base_time - (i * 1000),
-                    "127.0.0.1".to_string(),
-                    format!("session_{}", session_idx),
-                    "default".to_string(),
-                    "claude-sonnet-4-5-20250929".to_string(),
-                    Some(format!("msg_{}_{}", session_idx, i)),
-                    100,
-                    50,
-                    10,
-                    0, // cache_creation_1h_tokens
-                    20,
-                    0, // reasoning_tokens
-                    "success".to_string(),
-                    "json".to_string(),
-                    None,
-                    None,
-                    Some(100),
-                    Some(0.001),
-                    Some(0.002),
-                    Some(0.0001),
-                    Some(0.0002),
-                    None, // reasoning_price
-                    0.0033,
-                    Some("test_template".to_string()),
-                );
+                let log = TokenLog::test_builder()
+                    .tool("claude_code")
+                    .timestamp(base_time - (i * 1000))
+                    .session(&format!("session_{}", session_idx))
+                    .model("claude-sonnet-4-5-20250929")
+                    .message_id(&format!("msg_{}_{}", session_idx, i))
+                    .tokens(100, 50)
+                    .cache_tokens(10, 20)
+                    .response_time(100)
+                    .input_price(0.001)
+                    .output_price(0.002)
+                    .cache_write_price(0.0001)
+                    .cache_read_price(0.0002)
+                    .cost(0.0033)
+                    .template("test_template")
+                    .build();
                 db.insert_log(&log).unwrap();
             }
         }
@@ -543,4 +1500,393 @@ mod tests {
             assert!((summary.total_cost - 0.0165).abs() < 0.001); // 0.0033 * 5
         }
     }
+
+    #[test]
+    fn test_query_latency_stats() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test_latency.db");
+        let db = TokenStatsDb::new(db_path.clone());
+        db.init_table().unwrap();
+
+        let hour = 3_600_000;
+        let insert = |timestamp: i64, model: &str, status: &str, response_time: Option<i64>| {
+            let log = TokenLog::test_builder()
+                .timestamp(timestamp)
+                .model(model)
+                .tokens(100, 50)
+                .status(status)
+                .response_time(response_time)
+                .build();
+            db.insert_log(&log).unwrap();
+        };
+        // sonnet：1..=100 秒，分布在两个小时内
+        for i in 1..=100 {
+            insert(hour * (i % 2), "sonnet", "success", Some(i * 1000));
+        }
+        insert(0, "haiku", "success", Some(300));
+        insert(0, "haiku", "success", Some(500));
+        // 失败请求与缺少耗时的请求不计入
+        insert(0, "haiku", "failed", Some(90_000));
+        insert(0, "haiku", "success", None);
+
+        let analytics = TokenStatsAnalytics::new(db_path);
+        let by_model = analytics
+            .query_latency_stats(&LatencyStatsQuery::default())
+            .unwrap();
+        assert_eq!(by_model.len(), 2);
+        let sonnet = &by_model[0];
+        assert_eq!(sonnet.group_name, "sonnet");
+        assert_eq!(sonnet.request_count, 100);
+        assert_eq!(
+            (sonnet.p50_ms, sonnet.p90_ms, sonnet.p99_ms),
+            (50_000, 90_000, 99_000)
+        );
+        assert_eq!((sonnet.min_ms, sonnet.max_ms), (1_000, 100_000));
+        assert!((sonnet.avg_ms - 50_500.0).abs() < f64::EPSILON);
+        let haiku = &by_model[1];
+        assert_eq!(haiku.request_count, 2);
+        assert_eq!((haiku.p50_ms, haiku.p99_ms), (300, 500));
+
+        let by_hour = analytics
+            .query_latency_stats(&LatencyStatsQuery {
+                model: Some("sonnet".to_string()),
+                group_by: LatencyGroupBy::Time,
+                granularity: TimeGranularity::Hour,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(
+            by_hour
+                .iter()
+                .map(|s| (s.group_name.as_str(), s.request_count))
+                .collect::<Vec<_>>(),
+            vec![("0", 50), ("3600000", 50)]
+        );
+    }
+
+    #[test]
+    fn test_tag_filters() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test_tags.db");
+        let db = TokenStatsDb::new(db_path.clone());
+        db.init_table().unwrap();
+
+        for (session, tags) in [("s1", "billing,web"), ("s2", "billing-api"), ("s3", "")] {
+            let mut log = TokenLog::test_builder()
+                .timestamp(1_000)
+                .session(session)
+                .tokens(100, 50)
+                .response_time(100)
+                .cost(1.0)
+                .build();
+            log.tags = crate::services::session::tags::split_tags(tags);
+            db.insert_log(&log).unwrap();
+        }
+
+        let analytics = TokenStatsAnalytics::new(db_path);
+        let summary = |tag: &str| {
+            analytics
+                .query_cost_summary(&CostSummaryQuery {
+                    tag: Some(tag.to_string()),
+                    group_by: CostGroupBy::Session,
+                    ..Default::default()
+                })
+                .unwrap()
+                .into_iter()
+                .map(|s| s.group_name)
+                .collect::<Vec<_>>()
+        };
+        // 整词匹配：billing 不命中 billing-api
+        assert_eq!(summary("billing"), vec!["s1"]);
+        assert_eq!(summary("web"), vec!["s1"]);
+        assert!(summary("missing").is_empty());
+
+        // 回填后 s3 也归入 billing
+        db.update_session_tags("claude-code", "s3", &["billing".to_string()])
+            .unwrap();
+        let trends = analytics
+            .query_trends(&TrendQuery {
+                tag: Some("billing".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(trends.iter().map(|t| t.request_count).sum::<i64>(), 2);
+    }
+
+    #[test]
+    fn test_query_tool_comparison() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test_comparison.db");
+        let db = TokenStatsDb::new(db_path.clone());
+        db.init_table().unwrap();
+
+        // (工具, 状态, 响应时间, 缓存读取, 成本)
+        let rows = [
+            ("claude-code", "success", 1000, 300, 0.02),
+            ("claude-code", "success", 3000, 300, 0.02),
+            ("codex", "success", 500, 0, 0.01),
+            ("codex", "failed", 700, 0, 0.0),
+        ];
+        for (i, (tool, status, latency, cache_read, cost)) in rows.into_iter().enumerate() {
+            let log = TokenLog::test_builder()
+                .tool(tool)
+                .timestamp(1_700_000_000_000 + i as i64)
+                .model("model")
+                .tokens(100, 50)
+                .cache_tokens(0, cache_read)
+                .status(status)
+                .response_time(latency)
+                .cost(cost)
+                .build();
+            db.insert_log(&log).unwrap();
+        }
+
+        let analytics = TokenStatsAnalytics::new(db_path);
+        let comparison = analytics
+            .query_tool_comparison(&ToolComparisonQuery::default())
+            .unwrap();
+
+        assert_eq!(comparison.tools.len(), COMPARED_TOOLS.len());
+        let claude = &comparison.tools[0];
+        assert_eq!(claude.request_count, 2);
+        assert_eq!(claude.avg_response_time, Some(2000.0));
+        assert_eq!(claude.error_rate, Some(0.0));
+        assert!((claude.cache_hit_rate.unwrap() - 0.75).abs() < 1e-9);
+
+        let codex = &comparison.tools[1];
+        assert_eq!(codex.error_rate, Some(0.5));
+        assert!((codex.cost_per_request.unwrap() - 0.005).abs() < 1e-9);
+
+        let gemini = &comparison.tools[2];
+        assert_eq!(gemini.request_count, 0);
+        assert_eq!(gemini.cost_per_request, None);
+
+        let leaders = &comparison.leaders;
+        assert_eq!(leaders.lowest_cost_per_request.as_deref(), Some("codex"));
+        assert_eq!(leaders.lowest_latency.as_deref(), Some("codex"));
+        assert_eq!(leaders.lowest_error_rate.as_deref(), Some("claude-code"));
+        assert_eq!(
+            leaders.highest_cache_hit_rate.as_deref(),
+            Some("claude-code")
+        );
+    }
+
+    #[test]
+    fn test_query_model_breakdown() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test_model_breakdown.db");
+        let db = TokenStatsDb::new(db_path.clone());
+        db.init_table().unwrap();
+
+        let start = 1_700_000_000_000;
+        let span = 1_000_000;
+        // (时间, 模型, 状态, 响应时间, 成本)
+        let rows = [
+            (start - 10, "sonnet", "success", 1000, 0.02),
+            (start + 10, "sonnet", "success", 1000, 0.03),
+            (start + 20, "sonnet", "failed", 3000, 0.0),
+            (start + 30, "opus", "success", 2000, 0.10),
+            (start + 40, "sonnet", "success", 1000, 0.0),
+            (start - span - 1, "opus", "success", 2000, 9.0),
+        ];
+        for (timestamp, model, status, latency, cost) in rows {
+            let log = TokenLog::test_builder()
+                .timestamp(timestamp)
+                .model(model)
+                .tokens(100, 50)
+                .cache_tokens(0, 10)
+                .status(status)
+                .response_time(latency)
+                .cost(cost)
+                .build();
+            db.insert_log(&log).unwrap();
+        }
+
+        let analytics = TokenStatsAnalytics::new(db_path);
+        let breakdown = analytics
+            .query_model_breakdown(&ModelBreakdownQuery {
+                start_time: start,
+                end_time: start + span,
+            })
+            .unwrap();
+
+        assert_eq!(breakdown.previous_start_time, start - span);
+        assert_eq!(breakdown.total_requests, 4);
+        assert_eq!(breakdown.previous_total_requests, 1);
+        assert_eq!(breakdown.models.len(), 2);
+
+        // 按成本降序
+        let opus = &breakdown.models[0];
+        assert_eq!(opus.current.model, "opus");
+        // 早于上一周期的日志不参与对比
+        assert!(opus.previous.is_none());
+        assert_eq!(opus.cost_change_percent, None);
+
+        let sonnet = &breakdown.models[1];
+        assert_eq!(sonnet.current.request_count, 3);
+        assert_eq!(sonnet.current.total_tokens, 480);
+        assert_eq!(sonnet.current.avg_response_time, Some(5000.0 / 3.0));
+        assert!((sonnet.current.error_rate.unwrap() - 1.0 / 3.0).abs() < 1e-9);
+        assert!((sonnet.cost_share.unwrap() - 0.03 / 0.13).abs() < 1e-9);
+        assert_eq!(sonnet.previous.as_ref().unwrap().request_count, 1);
+        assert_eq!(sonnet.request_change_percent, Some(200.0));
+        assert!((sonnet.cost_change_percent.unwrap() - 50.0).abs() < 1e-9);
+
+        assert!(analytics
+            .query_model_breakdown(&ModelBreakdownQuery {
+                start_time: start,
+                end_time: start,
+            })
+            .is_err());
+    }
+
+    #[test]
+    fn test_query_monthly_costs() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test_monthly.db");
+        let db = TokenStatsDb::new(db_path.clone());
+        db.init_table().unwrap();
+
+        let mid_month = |month: u32| {
+            chrono::Utc
+                .with_ymd_and_hms(2026, month, 15, 12, 0, 0)
+                .unwrap()
+                .timestamp_millis()
+        };
+        for (i, (timestamp, template, cost)) in [
+            (mid_month(1), "relay", 3.0),
+            (mid_month(1), "relay", 2.0),
+            (mid_month(1), "builtin_claude", 1.5),
+            (mid_month(2), "relay", 12.0),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let log = TokenLog::test_builder()
+                .timestamp(timestamp)
+                .message_id(&format!("msg_{}", i))
+                .tokens(100, 50)
+                .response_time(100)
+                .cost(cost)
+                .template(template)
+                .build();
+            db.insert_log(&log).unwrap();
+        }
+
+        let analytics = TokenStatsAnalytics::new(db_path);
+        let fees = HashMap::from([("relay".to_string(), 10.0)]);
+        let reports = analytics
+            .query_monthly_costs(&MonthlyCostQuery::default(), &fees)
+            .unwrap();
+
+        assert_eq!(reports.len(), 2);
+        let january = &reports[0];
+        assert_eq!(january.month, "2026-01");
+        assert_eq!(january.request_count, 3);
+        assert_eq!(january.usage_cost, 6.5);
+        // relay 用量 5.0，最低消费 10.0，补足 5.0
+        assert_eq!(january.minimum_topup, 5.0);
+        assert_eq!(january.billed_cost, 11.5);
+
+        // 用量超过最低消费时不补足
+        assert_eq!(reports[1].minimum_topup, 0.0);
+        assert_eq!(reports[1].billed_cost, 12.0);
+    }
+
+    #[test]
+    fn test_query_cache_efficiency() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test_cache_efficiency.db");
+        let db = TokenStatsDb::new(db_path.clone());
+        db.init_table().unwrap();
+
+        let base_time = chrono::Utc
+            .with_ymd_and_hms(2026, 1, 10, 12, 0, 0)
+            .unwrap()
+            .timestamp_millis();
+        let hour = 3600 * 1000;
+        // (时间, 模型, 模板, 输入, 缓存写入, 缓存读取, 输入成本, 缓存写入成本, 缓存读取成本)
+        for (
+            i,
+            (timestamp, model, template, input, write, read, input_price, write_price, read_price),
+        ) in [
+            (
+                base_time, "sonnet", "relay", 1000, 0, 9000, 0.003, 0.0, 0.0027,
+            ),
+            (
+                base_time + hour,
+                "sonnet",
+                "relay",
+                1000,
+                0,
+                9000,
+                0.003,
+                0.0,
+                0.0027,
+            ),
+            // 模板中查不到单价：按记录的输入成本反推（1e-6 / Token）
+            (
+                base_time + hour,
+                "haiku",
+                "unknown",
+                1000,
+                2000,
+                0,
+                0.001,
+                0.0025,
+                0.0,
+            ),
+            // 无缓存用量的请求不计入
+            (base_time, "sonnet", "relay", 5000, 0, 0, 0.015, 0.0, 0.0),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let log = TokenLog::test_builder()
+                .timestamp(timestamp)
+                .model(model)
+                .message_id(&format!("msg_{}", i))
+                .tokens(input, 10)
+                .cache_tokens(write, read)
+                .response_time(100)
+                .input_price(input_price)
+                .cache_write_price(write_price)
+                .cache_read_price(read_price)
+                .cost(input_price + write_price + read_price)
+                .template(template)
+                .build();
+            db.insert_log(&log).unwrap();
+        }
+
+        let analytics = TokenStatsAnalytics::new(db_path);
+        let query = CacheEfficiencyQuery {
+            start_time: Some(base_time),
+            end_time: Some(base_time + 2 * hour),
+            granularity: TimeGranularity::Hour,
+            ..Default::default()
+        };
+        let report = analytics
+            .query_cache_efficiency(&query, |template_id, _, _| {
+                (template_id == "relay").then_some(3e-6)
+            })
+            .unwrap();
+
+        assert_eq!(report.total.request_count, 3);
+        assert_eq!(report.total.cache_read_tokens, 18000);
+        // sonnet：18000 * 3e-6 - 0.0054；haiku：2000 * 1e-6 - 0.0025
+        assert!((report.total.saved_cost - (0.0486 - 0.0005)).abs() < 1e-9);
+        assert!((report.total.cache_read_ratio.unwrap() - 18000.0 / 23000.0).abs() < 1e-9);
+
+        assert_eq!(report.by_model[0].model, "sonnet");
+        assert!((report.by_model[0].stat.saved_cost - 0.0486).abs() < 1e-9);
+        assert!((report.by_model[0].stat.cache_read_ratio.unwrap() - 0.9).abs() < 1e-9);
+        assert_eq!(report.by_model[1].model, "haiku");
+        assert!((report.by_model[1].stat.saved_cost + 0.0005).abs() < 1e-9);
+
+        // 补齐空桶
+        assert_eq!(report.by_time.len(), 3);
+        assert_eq!(report.by_time[0].stat.request_count, 1);
+        assert_eq!(report.by_time[1].stat.request_count, 2);
+        assert_eq!(report.by_time[2].stat, CacheEfficiencyStat::default());
+    }
 }
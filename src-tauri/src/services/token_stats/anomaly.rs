@@ -0,0 +1,263 @@
+//! 用量异常检测
+//!
+//! 后台任务每分钟比较各工具当前小时（UTC 整点起）的消费与 Token 用量和滚动基线：
+//! - 基线为最近 `baseline_hours` 小时中有用量的小时的平均值（空闲小时不拉低基线）
+//! - 有用量的小时少于 [`MIN_BASELINE_HOURS`] 时基线不可靠，不做判断
+//! - 超过基线指定倍数且达到最低用量时发送 `usage-anomaly` 事件，同一小时每项指标只提醒一次
+
+use crate::models::config::UsageAnomalyConfig;
+use crate::services::token_stats::db::TokenStatsDb;
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::{Mutex, RwLock};
+
+/// 基线至少需要的有用量小时数
+pub const MIN_BASELINE_HOURS: usize = 3;
+
+const HOUR_MS: i64 = 3_600_000;
+
+static ANOMALY_DETECTOR: Lazy<AnomalyDetector> = Lazy::new(AnomalyDetector::default);
+
+/// 异常指标
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub enum AnomalyMetric {
+    /// 消费（USD）
+    Cost,
+    /// Token 总数（输入 + 输出 + 缓存）
+    Tokens,
+}
+
+/// 单个工具一个小时的用量
+#[derive(Debug, Clone, PartialEq)]
+pub struct HourlyUsage {
+    pub tool_type: String,
+    /// 小时开始时间（Unix 时间戳，毫秒）
+    pub hour_start: i64,
+    pub total_cost: f64,
+    pub total_tokens: i64,
+}
+
+/// `usage-anomaly` 事件载荷
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct UsageAnomalyEvent {
+    pub tool_id: String,
+    pub metric: AnomalyMetric,
+    /// 当前小时累计值（USD 或 Token 数）
+    pub current: f64,
+    /// 基线（有用量小时的平均值）
+    pub baseline: f64,
+    /// 当前值 / 基线
+    pub ratio: f64,
+    /// 配置的异常倍数
+    pub multiplier: f64,
+    /// 当前小时开始时间（Unix 时间戳，毫秒）
+    pub hour_start: i64,
+    /// 是否同时发送系统桌面通知
+    pub desktop_notification: bool,
+}
+
+/// 异常提醒回调
+pub type AnomalyNotifier = Box<dyn Fn(UsageAnomalyEvent) + Send + Sync + 'static>;
+
+/// 用量异常检测器
+#[derive(Default)]
+pub struct AnomalyDetector {
+    /// 当前小时已提醒的（工具, 指标）
+    notified: Mutex<HashSet<(String, AnomalyMetric, i64)>>,
+    notifier: RwLock<Option<AnomalyNotifier>>,
+}
+
+impl AnomalyDetector {
+    /// 全局异常检测器
+    pub fn global() -> &'static AnomalyDetector {
+        &ANOMALY_DETECTOR
+    }
+
+    /// 设置异常提醒回调
+    pub fn set_notifier(&self, notifier: AnomalyNotifier) {
+        *self.notifier.write().unwrap() = Some(notifier);
+    }
+
+    /// 检查当前小时的用量，返回本次新发现的异常
+    pub fn check(
+        &self,
+        db: &TokenStatsDb,
+        config: &UsageAnomalyConfig,
+    ) -> Result<Vec<UsageAnomalyEvent>> {
+        self.check_at(db, config, chrono::Utc::now().timestamp_millis())
+    }
+
+    fn check_at(
+        &self,
+        db: &TokenStatsDb,
+        config: &UsageAnomalyConfig,
+        now: i64,
+    ) -> Result<Vec<UsageAnomalyEvent>> {
+        if !config.enabled || config.multiplier <= 0.0 || config.baseline_hours == 0 {
+            return Ok(Vec::new());
+        }
+        let hour_start = now.div_euclid(HOUR_MS) * HOUR_MS;
+        let since = hour_start - i64::from(config.baseline_hours) * HOUR_MS;
+        let usage = db.hourly_usage_since(since)?;
+
+        let mut notified = self.notified.lock().unwrap();
+        notified.retain(|(_, _, hour)| *hour == hour_start);
+        let fresh: Vec<UsageAnomalyEvent> = detect(&usage, config, hour_start)
+            .into_iter()
+            .filter(|event| notified.insert((event.tool_id.clone(), event.metric, hour_start)))
+            .collect();
+        drop(notified);
+
+        for event in &fresh {
+            tracing::warn!(
+                tool_id = %event.tool_id,
+                metric = ?event.metric,
+                current = event.current,
+                baseline = event.baseline,
+                ratio = event.ratio,
+                "当前小时用量异常"
+            );
+            if let Some(notifier) = self.notifier.read().unwrap().as_ref() {
+                notifier(event.clone());
+            }
+        }
+        Ok(fresh)
+    }
+}
+
+/// 从按工具、小时汇总的用量中找出当前小时超过基线的指标
+fn detect(
+    usage: &[HourlyUsage],
+    config: &UsageAnomalyConfig,
+    hour_start: i64,
+) -> Vec<UsageAnomalyEvent> {
+    let mut events = Vec::new();
+    for current in usage.iter().filter(|u| u.hour_start == hour_start) {
+        let baseline: Vec<&HourlyUsage> = usage
+            .iter()
+            .filter(|u| u.tool_type == current.tool_type && u.hour_start < hour_start)
+            .filter(|u| u.total_cost > 0.0 || u.total_tokens > 0)
+            .collect();
+        if baseline.len() < MIN_BASELINE_HOURS {
+            continue;
+        }
+        let hours = baseline.len() as f64;
+        let metrics = [
+            (
+                AnomalyMetric::Cost,
+                current.total_cost,
+                baseline.iter().map(|u| u.total_cost).sum::<f64>() / hours,
+                config.min_cost_usd,
+            ),
+            (
+                AnomalyMetric::Tokens,
+                current.total_tokens as f64,
+                baseline.iter().map(|u| u.total_tokens as f64).sum::<f64>() / hours,
+                config.min_tokens as f64,
+            ),
+        ];
+        for (metric, value, average, floor) in metrics {
+            // 基线为 0（如未配置价格）时无法判断倍数
+            if value < floor || average <= 0.0 {
+                continue;
+            }
+            let ratio = value / average;
+            if ratio >= config.multiplier {
+                events.push(UsageAnomalyEvent {
+                    tool_id: current.tool_type.clone(),
+                    metric,
+                    current: value,
+                    baseline: average,
+                    ratio,
+                    multiplier: config.multiplier,
+                    hour_start,
+                    desktop_notification: config.desktop_notification,
+                });
+            }
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::token_stats::TokenLog;
+    use std::sync::Arc;
+    use tempfile::tempdir;
+
+    fn log_at(tool: &str, timestamp: i64, tokens: i64, cost: f64) -> TokenLog {
+        TokenLog::test_builder()
+            .tool(tool)
+            .timestamp(timestamp)
+            .tokens(tokens, 0)
+            .cost(cost)
+            .build()
+    }
+
+    #[test]
+    fn test_detects_spike_once_per_hour() {
+        let dir = tempdir().unwrap();
+        let db = TokenStatsDb::new(dir.path().join("anomaly.db"));
+        db.init_table().unwrap();
+
+        let hour = 500_000 * HOUR_MS;
+        // 基线：前 4 个小时各 $2 / 100k Token，另有一个空闲小时
+        for h in [1, 2, 4, 5] {
+            db.insert_log(&log_at("claude-code", hour - h * HOUR_MS, 100_000, 2.0))
+                .unwrap();
+        }
+        // 当前小时：$10 / 200k Token（消费 5 倍，Token 低于最低值）
+        db.insert_log(&log_at("claude-code", hour + 60_000, 200_000, 10.0))
+            .unwrap();
+        // 基线不足的工具不判断
+        db.insert_log(&log_at("codex", hour - HOUR_MS, 100, 0.1))
+            .unwrap();
+        db.insert_log(&log_at("codex", hour + 60_000, 1_000_000_000, 100.0))
+            .unwrap();
+
+        let detector = AnomalyDetector::default();
+        let sink = Arc::new(Mutex::new(Vec::new()));
+        let events = Arc::clone(&sink);
+        detector.set_notifier(Box::new(move |event| events.lock().unwrap().push(event)));
+
+        let config = UsageAnomalyConfig::default();
+        let found = detector.check_at(&db, &config, hour + 120_000).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].tool_id, "claude-code");
+        assert_eq!(found[0].metric, AnomalyMetric::Cost);
+        assert!((found[0].baseline - 2.0).abs() < 1e-9);
+        assert!((found[0].ratio - 5.0).abs() < 1e-9);
+        assert_eq!(found[0].hour_start, hour);
+        assert_eq!(sink.lock().unwrap().len(), 1);
+
+        // 同一小时不再重复提醒
+        assert!(detector
+            .check_at(&db, &config, hour + 180_000)
+            .unwrap()
+            .is_empty());
+
+        // 倍数调高后不视为异常；关闭后不检查
+        let strict = UsageAnomalyConfig {
+            multiplier: 6.0,
+            ..UsageAnomalyConfig::default()
+        };
+        assert!(AnomalyDetector::default()
+            .check_at(&db, &strict, hour + 120_000)
+            .unwrap()
+            .is_empty());
+        let disabled = UsageAnomalyConfig {
+            enabled: false,
+            ..UsageAnomalyConfig::default()
+        };
+        assert!(AnomalyDetector::default()
+            .check_at(&db, &disabled, hour + 120_000)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,373 @@
+//! 消费预算检查
+//!
+//! 按 `token_logs.total_cost`（写入时已由 PricingManager 计价）汇总各工具在本地时间
+//! 当前自然日 / 周 / 月的消费：
+//! - 检查结果缓存在全局 `BudgetTracker` 中，透明代理据此决定是否拦截（block 模式）
+//! - 消费首次跨过提醒阈值或上限时通过通知回调发送 `budget-threshold-reached` 事件
+
+use crate::models::proxy_config::{BudgetConfig, BudgetMode, ProxyStore};
+use crate::services::token_stats::db::TokenStatsDb;
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, Local, LocalResult, NaiveDate, TimeZone};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Mutex, RwLock};
+
+static BUDGET_TRACKER: Lazy<BudgetTracker> = Lazy::new(BudgetTracker::default);
+
+/// 预算统计周期
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub enum BudgetPeriod {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// 预算提醒级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub enum BudgetLevel {
+    /// 达到提醒阈值
+    Warning,
+    /// 达到上限
+    Exceeded,
+}
+
+/// 单个周期的消费情况
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct BudgetPeriodStatus {
+    pub period: BudgetPeriod,
+    pub limit_usd: f64,
+    pub spent_usd: f64,
+    /// 已用百分比
+    pub percent: f64,
+    /// 周期开始时间（Unix 时间戳，毫秒）
+    pub period_start: i64,
+    pub level: Option<BudgetLevel>,
+}
+
+/// 工具的预算状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct BudgetStatus {
+    pub tool_id: String,
+    pub enabled: bool,
+    pub mode: BudgetMode,
+    /// 已设置上限的周期
+    pub periods: Vec<BudgetPeriodStatus>,
+    /// 任一周期达到提醒阈值
+    pub warning: bool,
+    /// 任一周期达到上限
+    pub exceeded: bool,
+    /// 检查时间（Unix 时间戳，毫秒）
+    pub checked_at: i64,
+}
+
+/// `budget-threshold-reached` 事件载荷
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct BudgetThresholdEvent {
+    pub tool_id: String,
+    pub period: BudgetPeriod,
+    pub level: BudgetLevel,
+    pub mode: BudgetMode,
+    pub limit_usd: f64,
+    pub spent_usd: f64,
+}
+
+/// 预算提醒回调
+pub type BudgetNotifier = Box<dyn Fn(BudgetThresholdEvent) + Send + Sync + 'static>;
+
+/// 本地时间下周期的开始时间（毫秒）：自然日 0 点、本周一 0 点、本月 1 日 0 点
+pub fn period_start(period: BudgetPeriod, now: DateTime<Local>) -> i64 {
+    let today = now.date_naive();
+    let start = match period {
+        BudgetPeriod::Daily => today,
+        BudgetPeriod::Weekly => {
+            today - Duration::days(i64::from(today.weekday().num_days_from_monday()))
+        }
+        BudgetPeriod::Monthly => {
+            NaiveDate::from_ymd_opt(today.year(), today.month(), 1).unwrap_or(today)
+        }
+    };
+    let midnight = start.and_hms_opt(0, 0, 0).unwrap_or_default();
+    match Local.from_local_datetime(&midnight) {
+        LocalResult::Single(dt) | LocalResult::Ambiguous(dt, _) => dt.timestamp_millis(),
+        // 0 点恰好落在夏令时跳变中时退回 UTC 解释
+        LocalResult::None => midnight.and_utc().timestamp_millis(),
+    }
+}
+
+fn limits(config: &BudgetConfig) -> [(BudgetPeriod, Option<f64>); 3] {
+    [
+        (BudgetPeriod::Daily, config.daily_limit_usd),
+        (BudgetPeriod::Weekly, config.weekly_limit_usd),
+        (BudgetPeriod::Monthly, config.monthly_limit_usd),
+    ]
+}
+
+/// 预算状态缓存与阈值提醒
+#[derive(Default)]
+pub struct BudgetTracker {
+    statuses: RwLock<HashMap<String, BudgetStatus>>,
+    /// 各周期已提醒的最高级别（消费回落到阈值以下时清除，新周期可再次提醒）
+    notified: Mutex<HashMap<(String, BudgetPeriod), BudgetLevel>>,
+    notifier: RwLock<Option<BudgetNotifier>>,
+}
+
+impl BudgetTracker {
+    /// 全局预算跟踪器
+    pub fn global() -> &'static BudgetTracker {
+        &BUDGET_TRACKER
+    }
+
+    /// 设置阈值提醒回调
+    pub fn set_notifier(&self, notifier: BudgetNotifier) {
+        *self.notifier.write().unwrap() = Some(notifier);
+    }
+
+    /// 按最近一次检查结果判断是否应拦截请求
+    pub fn is_blocked(&self, tool_id: &str, config: &BudgetConfig) -> bool {
+        config.is_active()
+            && config.mode == BudgetMode::Block
+            && self
+                .statuses
+                .read()
+                .unwrap()
+                .get(tool_id)
+                .is_some_and(|status| status.exceeded)
+    }
+
+    /// 重新计算工具的预算状态并更新缓存
+    pub fn evaluate(
+        &self,
+        db: &TokenStatsDb,
+        tool_id: &str,
+        config: &BudgetConfig,
+    ) -> Result<BudgetStatus> {
+        self.evaluate_at(db, tool_id, config, Local::now())
+    }
+
+    fn evaluate_at(
+        &self,
+        db: &TokenStatsDb,
+        tool_id: &str,
+        config: &BudgetConfig,
+        now: DateTime<Local>,
+    ) -> Result<BudgetStatus> {
+        let mut periods = Vec::new();
+        if config.is_active() {
+            let warn_ratio = f64::from(config.warn_percent.min(100)) / 100.0;
+            for (period, limit) in limits(config) {
+                let Some(limit_usd) = limit.filter(|v| *v > 0.0) else {
+                    continue;
+                };
+                let start = period_start(period, now);
+                let spent_usd = db.sum_cost_since(tool_id, start)?;
+                let level = if spent_usd >= limit_usd {
+                    Some(BudgetLevel::Exceeded)
+                } else if spent_usd >= limit_usd * warn_ratio {
+                    Some(BudgetLevel::Warning)
+                } else {
+                    None
+                };
+                periods.push(BudgetPeriodStatus {
+                    period,
+                    limit_usd,
+                    spent_usd,
+                    percent: spent_usd / limit_usd * 100.0,
+                    period_start: start,
+                    level,
+                });
+            }
+        }
+
+        let status = BudgetStatus {
+            tool_id: tool_id.to_string(),
+            enabled: config.enabled,
+            mode: config.mode,
+            warning: periods.iter().any(|p| p.level.is_some()),
+            exceeded: periods
+                .iter()
+                .any(|p| p.level == Some(BudgetLevel::Exceeded)),
+            periods,
+            checked_at: now.timestamp_millis(),
+        };
+
+        self.notify_crossings(&status);
+        self.statuses
+            .write()
+            .unwrap()
+            .insert(tool_id.to_string(), status.clone());
+        Ok(status)
+    }
+
+    /// 检查 proxy.json 中所有工具（含自定义工具）的预算
+    pub fn check_all(&self, db: &TokenStatsDb, store: &ProxyStore) -> Result<Vec<BudgetStatus>> {
+        store
+            .tool_ids()
+            .iter()
+            .map(|tool_id| {
+                let config = store
+                    .get_config(tool_id)
+                    .map(|c| c.budget)
+                    .unwrap_or_default();
+                self.evaluate(db, tool_id, &config)
+            })
+            .collect()
+    }
+
+    /// 对新跨过的阈值发送提醒
+    fn notify_crossings(&self, status: &BudgetStatus) {
+        let mut notified = self.notified.lock().unwrap();
+        notified.retain(|(tool_id, period), _| {
+            tool_id != &status.tool_id || status.periods.iter().any(|p| p.period == *period)
+        });
+
+        for period in &status.periods {
+            let key = (status.tool_id.clone(), period.period);
+            let Some(level) = period.level else {
+                notified.remove(&key);
+                continue;
+            };
+            if notified.get(&key).is_some_and(|last| *last >= level) {
+                continue;
+            }
+            notified.insert(key, level);
+
+            tracing::warn!(
+                tool_id = %status.tool_id,
+                period = ?period.period,
+                spent_usd = period.spent_usd,
+                limit_usd = period.limit_usd,
+                "消费达到预算{}",
+                if level == BudgetLevel::Exceeded { "上限" } else { "提醒阈值" }
+            );
+            if let Some(notifier) = self.notifier.read().unwrap().as_ref() {
+                notifier(BudgetThresholdEvent {
+                    tool_id: status.tool_id.clone(),
+                    period: period.period,
+                    level,
+                    mode: status.mode,
+                    limit_usd: period.limit_usd,
+                    spent_usd: period.spent_usd,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::token_stats::TokenLog;
+    use std::sync::Arc;
+    use tempfile::tempdir;
+
+    fn log_with_cost(timestamp: i64, cost: f64) -> TokenLog {
+        TokenLog::test_builder()
+            .tool("codex")
+            .timestamp(timestamp)
+            .model("gpt-5")
+            .tokens(100, 50)
+            .cost(cost)
+            .build()
+    }
+
+    #[test]
+    fn test_period_start() {
+        // 2026-01-15 为周四
+        let now = Local.with_ymd_and_hms(2026, 1, 15, 18, 30, 0).unwrap();
+        let at = |y, m, d| Local.with_ymd_and_hms(y, m, d, 0, 0, 0).unwrap();
+        assert_eq!(
+            period_start(BudgetPeriod::Daily, now),
+            at(2026, 1, 15).timestamp_millis()
+        );
+        assert_eq!(
+            period_start(BudgetPeriod::Weekly, now),
+            at(2026, 1, 12).timestamp_millis()
+        );
+        assert_eq!(
+            period_start(BudgetPeriod::Monthly, now),
+            at(2026, 1, 1).timestamp_millis()
+        );
+    }
+
+    #[test]
+    fn test_evaluate_blocks_and_notifies_once() {
+        let dir = tempdir().unwrap();
+        let db = TokenStatsDb::new(dir.path().join("budget.db"));
+        db.init_table().unwrap();
+
+        let now = Local.with_ymd_and_hms(2026, 1, 15, 18, 0, 0).unwrap();
+        let today = now.timestamp_millis();
+        let last_week = today - 7 * 24 * 3600 * 1000;
+        db.insert_log(&log_with_cost(today, 0.9)).unwrap();
+        db.insert_log(&log_with_cost(last_week, 5.0)).unwrap();
+
+        let tracker = BudgetTracker::default();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        tracker.set_notifier(Box::new(move |event| sink.lock().unwrap().push(event)));
+
+        let config = BudgetConfig {
+            enabled: true,
+            daily_limit_usd: Some(1.0),
+            monthly_limit_usd: Some(10.0),
+            mode: BudgetMode::Block,
+            ..Default::default()
+        };
+
+        // 当日 0.9 达到 80% 提醒阈值，本月 5.9 未达到
+        let status = tracker.evaluate_at(&db, "codex", &config, now).unwrap();
+        assert!(status.warning);
+        assert!(!status.exceeded);
+        assert_eq!(status.periods[1].spent_usd, 5.9);
+        assert!(!tracker.is_blocked("codex", &config));
+
+        db.insert_log(&log_with_cost(today, 0.2)).unwrap();
+        let status = tracker.evaluate_at(&db, "codex", &config, now).unwrap();
+        assert!(status.exceeded);
+        assert!(tracker.is_blocked("codex", &config));
+        // 仅提醒模式不拦截
+        let warn_only = BudgetConfig {
+            mode: BudgetMode::Warn,
+            ..config
+        };
+        assert!(!tracker.is_blocked("codex", &warn_only));
+
+        // 重复检查不重复提醒
+        tracker.evaluate_at(&db, "codex", &config, now).unwrap();
+        let levels: Vec<_> = events.lock().unwrap().iter().map(|e| e.level).collect();
+        assert_eq!(levels, [BudgetLevel::Warning, BudgetLevel::Exceeded]);
+    }
+
+    #[test]
+    fn test_check_all_includes_custom_tools() {
+        let dir = tempdir().unwrap();
+        let db = TokenStatsDb::new(dir.path().join("budget.db"));
+        db.init_table().unwrap();
+
+        let mut store = ProxyStore::new();
+        let mut custom = store.codex.clone();
+        custom.budget = BudgetConfig {
+            enabled: true,
+            daily_limit_usd: Some(1.0),
+            ..Default::default()
+        };
+        store.update_config("my-agent", custom);
+
+        let statuses = BudgetTracker::default().check_all(&db, &store).unwrap();
+        let tool_ids: Vec<_> = statuses.iter().map(|s| s.tool_id.as_str()).collect();
+        assert_eq!(
+            tool_ids,
+            ["claude-code", "codex", "gemini-cli", "amp-code", "my-agent"]
+        );
+        assert!(statuses[4].enabled);
+    }
+}
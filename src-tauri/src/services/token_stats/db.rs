@@ -1,7 +1,49 @@
 use crate::data::DataManager;
 use crate::models::token_stats::{SessionStats, TokenLog, TokenLogsPage, TokenStatsQuery};
+use crate::services::session::tags::{join_tags, split_tags};
+use crate::services::token_stats::anomaly::HourlyUsage;
+use crate::services::token_stats::export::{self, LogExportFormat, LogExportSummary};
 use anyhow::{Context, Result};
-use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// 超出大小上限时删除旧日志的目标占用比例（留出余量，避免每次维护都要删除）
+const SIZE_LIMIT_TARGET_RATIO: f64 = 0.9;
+
+/// 空闲页占比超过该值时后台维护执行 VACUUM
+const FREELIST_VACUUM_RATIO: f64 = 0.25;
+
+/// 数据库页统计
+#[derive(Debug, Clone, Copy, Default)]
+struct PageStats {
+    page_count: i64,
+    page_size: i64,
+    freelist_count: i64,
+}
+
+impl PageStats {
+    /// 数据实际占用的字节数（不含空闲页）
+    fn used_bytes(&self) -> u64 {
+        ((self.page_count - self.freelist_count).max(0) * self.page_size) as u64
+    }
+}
+
+/// 数据库维护报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbMaintenanceReport {
+    /// 维护前主文件 / WAL 文件大小（字节）
+    pub db_bytes_before: u64,
+    pub wal_bytes_before: u64,
+    /// 维护后主文件 / WAL 文件大小（字节）
+    pub db_bytes_after: u64,
+    pub wal_bytes_after: u64,
+    /// 因超出大小上限删除的最旧日志条数
+    pub deleted_rows: usize,
+    /// 剩余日志条数
+    pub remaining_rows: i64,
+    /// 耗时（毫秒）
+    pub duration_ms: i64,
+}
 
 /// Token统计数据库操作层
 pub struct TokenStatsDb {
@@ -25,261 +67,51 @@ impl TokenStatsDb {
             .execute_raw("PRAGMA journal_mode=WAL")
             .context("Failed to enable WAL mode")?;
 
-        // 创建表（Schema v3 - 包含成本分析字段）
-        manager
-            .execute_raw(
-                "CREATE TABLE IF NOT EXISTS token_logs (
-                    id INTEGER PRIMARY KEY AUTOINCREMENT,
-                    tool_type TEXT NOT NULL,
-                    timestamp INTEGER NOT NULL,
-                    client_ip TEXT NOT NULL,
-                    session_id TEXT NOT NULL,
-                    config_name TEXT NOT NULL,
-                    model TEXT NOT NULL,
-                    message_id TEXT,
-
-                    -- Token 数量
-                    input_tokens INTEGER NOT NULL DEFAULT 0,
-                    output_tokens INTEGER NOT NULL DEFAULT 0,
-                    cache_creation_tokens INTEGER NOT NULL DEFAULT 0,
-                    cache_read_tokens INTEGER NOT NULL DEFAULT 0,
-                    reasoning_tokens INTEGER NOT NULL DEFAULT 0,
-
-                    -- 请求状态
-                    request_status TEXT NOT NULL DEFAULT 'success',
-                    response_type TEXT NOT NULL DEFAULT 'unknown',
-                    error_type TEXT,
-                    error_detail TEXT,
-
-                    -- 各部分的价格（USD）
-                    input_price REAL,
-                    output_price REAL,
-                    cache_write_price REAL,
-                    cache_read_price REAL,
-                    reasoning_price REAL,
-
-                    -- 总成本（USD）
-                    total_cost REAL NOT NULL DEFAULT 0.0,
-
-                    -- 响应时间
-                    response_time_ms INTEGER,
-
-                    -- 价格模板 ID
-                    pricing_template_id TEXT,
-
-                    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
-                )",
-            )
-            .context("Failed to create token_logs table")?;
-
-        // 创建索引
-        manager
-            .execute_raw(
-                "CREATE INDEX IF NOT EXISTS idx_session_timestamp
-                 ON token_logs(session_id, timestamp)",
-            )
-            .context("Failed to create session_timestamp index")?;
-
-        manager
-            .execute_raw(
-                "CREATE INDEX IF NOT EXISTS idx_timestamp
-                 ON token_logs(timestamp)",
-            )
-            .context("Failed to create timestamp index")?;
-
-        manager
-            .execute_raw(
-                "CREATE INDEX IF NOT EXISTS idx_tool_type
-                 ON token_logs(tool_type)",
-            )
-            .context("Failed to create tool_type index")?;
-
-        // 添加成本分析相关索引（Phase 1）
-        manager
-            .execute_raw(
-                "CREATE INDEX IF NOT EXISTS idx_model
-                 ON token_logs(model)",
-            )
-            .context("Failed to create model index")?;
-
+        // 建表与旧库升级（见 schema 模块的版本迁移步骤）
         manager
-            .execute_raw(
-                "CREATE INDEX IF NOT EXISTS idx_total_cost
-                 ON token_logs(total_cost)",
-            )
-            .context("Failed to create total_cost index")?;
-
-        manager
-            .execute_raw(
-                "CREATE INDEX IF NOT EXISTS idx_timestamp_cost
-                 ON token_logs(timestamp, total_cost)",
-            )
-            .context("Failed to create timestamp_cost index")?;
-
-        manager
-            .execute_raw(
-                "CREATE INDEX IF NOT EXISTS idx_tool_model
-                 ON token_logs(tool_type, model)",
-            )
-            .context("Failed to create tool_model index")?;
-
-        // 数据库迁移：添加 reasoning_tokens 和 reasoning_price 字段（如果不存在）
-        // 这是为了兼容旧版本数据库
-        self.migrate_add_reasoning_fields()?;
-
-        // 数据库迁移：添加 cache_creation_1h_tokens 字段（区分 5m/1h 缓存）
-        self.migrate_add_cache_1h_field()?;
-
-        Ok(())
-    }
-
-    /// 迁移：添加 reasoning_tokens 和 reasoning_price 字段
-    fn migrate_add_reasoning_fields(&self) -> Result<()> {
-        let manager = DataManager::global()
-            .sqlite(&self.db_path)
-            .context("Failed to get SQLite manager for migration")?;
-
-        // 检查 reasoning_tokens 字段是否存在
-        let check_query =
-            "SELECT COUNT(*) FROM pragma_table_info('token_logs') WHERE name='reasoning_tokens'";
-        let rows = manager
-            .query(check_query, &[])
-            .context("Failed to check reasoning_tokens column")?;
+            .migrate(super::schema::TOKEN_STATS_MIGRATIONS)
+            .context("Failed to migrate token_logs schema")?;
 
-        let exists = rows
-            .first()
-            .and_then(|row| row.values.first())
-            .and_then(|v| v.as_i64())
-            .unwrap_or(0)
-            > 0;
-
-        if !exists {
-            eprintln!("Migrating database: adding reasoning_tokens and reasoning_price columns");
-
-            // 添加 reasoning_tokens 字段
-            manager
-                .execute_raw(
-                    "ALTER TABLE token_logs ADD COLUMN reasoning_tokens INTEGER NOT NULL DEFAULT 0",
-                )
-                .context("Failed to add reasoning_tokens column")?;
-
-            // 添加 reasoning_price 字段
-            manager
-                .execute_raw("ALTER TABLE token_logs ADD COLUMN reasoning_price REAL")
-                .context("Failed to add reasoning_price column")?;
-
-            eprintln!("Database migration completed successfully");
-        }
+        // 统计周期表
+        super::epochs::StatsEpochManager::new(self.db_path.clone()).init_tables()?;
 
         Ok(())
     }
 
-    /// 迁移：添加 cache_creation_1h_tokens 字段（区分 5m/1h 缓存写入）
-    fn migrate_add_cache_1h_field(&self) -> Result<()> {
+    /// 回填会话的标签（会话标签变更后，已有日志随之归集）
+    ///
+    /// `session_id` 为日志中记录的显示 ID，返回更新的日志条数
+    pub fn update_session_tags(
+        &self,
+        tool_type: &str,
+        session_id: &str,
+        tags: &[String],
+    ) -> Result<usize> {
         let manager = DataManager::global()
             .sqlite(&self.db_path)
-            .context("Failed to get SQLite manager for cache_1h migration")?;
-
-        let check_query = "SELECT COUNT(*) FROM pragma_table_info('token_logs') WHERE name='cache_creation_1h_tokens'";
-        let rows = manager
-            .query(check_query, &[])
-            .context("Failed to check cache_creation_1h_tokens column")?;
-
-        let exists = rows
-            .first()
-            .and_then(|row| row.values.first())
-            .and_then(|v| v.as_i64())
-            .unwrap_or(0)
-            > 0;
-
-        if !exists {
-            eprintln!("Migrating database: adding cache_creation_1h_tokens column");
-
-            manager
-                .execute_raw(
-                    "ALTER TABLE token_logs ADD COLUMN cache_creation_1h_tokens INTEGER NOT NULL DEFAULT 0",
-                )
-                .context("Failed to add cache_creation_1h_tokens column")?;
+            .context("Failed to get SQLite manager")?;
 
-            eprintln!("Database cache_1h migration completed successfully");
-        }
+        let updated = manager
+            .execute(
+                "UPDATE token_logs SET tags = NULLIF(?1, '') WHERE tool_type = ?2 AND session_id = ?3",
+                &[&join_tags(tags), tool_type, session_id],
+            )
+            .context("Failed to update session tags")?;
 
-        Ok(())
+        Ok(updated)
     }
 
     /// 插入单条日志记录
     pub fn insert_log(&self, log: &TokenLog) -> Result<i64> {
-        let manager = DataManager::global()
-            .sqlite(&self.db_path)
-            .context("Failed to get SQLite manager")?;
-
-        let params = vec![
-            log.tool_type.clone(),
-            log.timestamp.to_string(),
-            log.client_ip.clone(),
-            log.session_id.clone(),
-            log.config_name.clone(),
-            log.model.clone(),
-            log.message_id.clone().unwrap_or_default(),
-            log.input_tokens.to_string(),
-            log.output_tokens.to_string(),
-            log.cache_creation_tokens.to_string(),
-            log.cache_creation_1h_tokens.to_string(),
-            log.cache_read_tokens.to_string(),
-            log.reasoning_tokens.to_string(),
-            log.request_status.clone(),
-            log.response_type.clone(),
-            log.error_type.clone().unwrap_or_default(),
-            log.error_detail.clone().unwrap_or_default(),
-            log.response_time_ms
-                .map(|v| v.to_string())
-                .unwrap_or_default(),
-            log.input_price.map(|v| v.to_string()).unwrap_or_default(),
-            log.output_price.map(|v| v.to_string()).unwrap_or_default(),
-            log.cache_write_price
-                .map(|v| v.to_string())
-                .unwrap_or_default(),
-            log.cache_read_price
-                .map(|v| v.to_string())
-                .unwrap_or_default(),
-            log.reasoning_price
-                .map(|v| v.to_string())
-                .unwrap_or_default(),
-            log.total_cost.to_string(),
-            log.pricing_template_id.clone().unwrap_or_default(),
-        ];
-
-        let params_refs: Vec<&str> = params.iter().map(|s| s.as_str()).collect();
-
-        manager
-            .execute(
-                "INSERT INTO token_logs (
-                    tool_type, timestamp, client_ip, session_id, config_name,
-                    model, message_id, input_tokens, output_tokens,
-                    cache_creation_tokens, cache_creation_1h_tokens, cache_read_tokens, reasoning_tokens,
-                    request_status, response_type, error_type, error_detail,
-                    response_time_ms, input_price, output_price, cache_write_price, cache_read_price, reasoning_price,
-                    total_cost, pricing_template_id
-                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25)",
-                &params_refs,
-            )
-            .context("Failed to insert token log")?;
+        let id = self.insert_log_without_checkpoint(log)?;
 
         // 执行 WAL checkpoint（TRUNCATE 模式，立即回写主文件）
         // 注意：这会稍微影响写入性能，但确保数据及时持久化
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
         let _ = manager.execute_raw("PRAGMA wal_checkpoint(TRUNCATE)");
 
-        // 获取最后插入的ID（通过查询max(id)）
-        let rows = manager
-            .query("SELECT max(id) as last_id FROM token_logs", &[])
-            .context("Failed to query last insert id")?;
-
-        let id = rows
-            .first()
-            .and_then(|row| row.values.first())
-            .and_then(|v| v.as_i64())
-            .unwrap_or(0);
-
         Ok(id)
     }
 
@@ -325,35 +157,30 @@ impl TokenStatsDb {
                 .unwrap_or_default(),
             log.total_cost.to_string(),
             log.pricing_template_id.clone().unwrap_or_default(),
+            log.upstream_headers.clone().unwrap_or_default(),
+            log.upstream_key_alias.clone().unwrap_or_default(),
+            join_tags(&log.tags),
+            log.client_key_label.clone().unwrap_or_default(),
         ];
 
         let params_refs: Vec<&str> = params.iter().map(|s| s.as_str()).collect();
 
-        manager
-            .execute(
+        // 插入语句固定，走连接上的预编译语句缓存；rowid 在同一次加锁内读取
+        let id = manager
+            .insert(
                 "INSERT INTO token_logs (
                     tool_type, timestamp, client_ip, session_id, config_name,
                     model, message_id, input_tokens, output_tokens,
                     cache_creation_tokens, cache_creation_1h_tokens, cache_read_tokens, reasoning_tokens,
                     request_status, response_type, error_type, error_detail,
                     response_time_ms, input_price, output_price, cache_write_price, cache_read_price, reasoning_price,
-                    total_cost, pricing_template_id
-                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25)",
+                    total_cost, pricing_template_id, upstream_headers, upstream_key_alias, tags,
+                    client_key_label
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27, NULLIF(?28, ''), NULLIF(?29, ''))",
                 &params_refs,
             )
             .context("Failed to insert token log")?;
 
-        // 获取最后插入的ID
-        let rows = manager
-            .query("SELECT max(id) as last_id FROM token_logs", &[])
-            .context("Failed to query last insert id")?;
-
-        let id = rows
-            .first()
-            .and_then(|row| row.values.first())
-            .and_then(|v| v.as_i64())
-            .unwrap_or(0);
-
         Ok(id)
     }
 
@@ -397,39 +224,7 @@ impl TokenStatsDb {
             .context("Failed to get SQLite manager")?;
 
         // 构建查询条件
-        let mut where_clauses = Vec::new();
-        let mut params = Vec::new();
-
-        if let Some(ref tool_type) = query.tool_type {
-            where_clauses.push("tool_type = ?");
-            params.push(tool_type.clone());
-        }
-
-        if let Some(ref session_id) = query.session_id {
-            where_clauses.push("session_id = ?");
-            params.push(session_id.clone());
-        }
-
-        if let Some(ref config_name) = query.config_name {
-            where_clauses.push("config_name = ?");
-            params.push(config_name.clone());
-        }
-
-        if let Some(start_time) = query.start_time {
-            where_clauses.push("timestamp >= ?");
-            params.push(start_time.to_string());
-        }
-
-        if let Some(end_time) = query.end_time {
-            where_clauses.push("timestamp <= ?");
-            params.push(end_time.to_string());
-        }
-
-        let where_clause = if where_clauses.is_empty() {
-            String::new()
-        } else {
-            format!("WHERE {}", where_clauses.join(" AND "))
-        };
+        let (where_clause, params) = Self::build_filters(query);
 
         // 查询总数
         let count_sql = format!("SELECT COUNT(*) FROM token_logs {}", where_clause);
@@ -453,7 +248,8 @@ impl TokenStatsDb {
                     cache_creation_tokens, cache_creation_1h_tokens, cache_read_tokens, reasoning_tokens,
                     request_status, response_type, error_type, error_detail,
                     response_time_ms, input_price, output_price, cache_write_price, cache_read_price, reasoning_price,
-                    total_cost, pricing_template_id
+                    total_cost, pricing_template_id, upstream_headers, upstream_key_alias, tags,
+                    client_key_label
              FROM token_logs {}
              ORDER BY timestamp DESC
              LIMIT ? OFFSET ?",
@@ -551,6 +347,30 @@ impl TokenStatsDb {
                         .get(25)
                         .and_then(|v| v.as_str())
                         .map(String::from),
+                    upstream_headers: row
+                        .values
+                        .get(26)
+                        .and_then(|v| v.as_str())
+                        .filter(|s| !s.is_empty())
+                        .map(String::from),
+                    upstream_key_alias: row
+                        .values
+                        .get(27)
+                        .and_then(|v| v.as_str())
+                        .filter(|s| !s.is_empty())
+                        .map(String::from),
+                    tags: row
+                        .values
+                        .get(28)
+                        .and_then(|v| v.as_str())
+                        .map(split_tags)
+                        .unwrap_or_default(),
+                    client_key_label: row
+                        .values
+                        .get(29)
+                        .and_then(|v| v.as_str())
+                        .filter(|s| !s.is_empty())
+                        .map(String::from),
                 })
             })
             .collect::<Result<Vec<TokenLog>>>()?;
@@ -563,6 +383,54 @@ impl TokenStatsDb {
         })
     }
 
+    /// 构建日志筛选条件（分页查询与导出共用），返回 WHERE 子句与参数
+    pub(super) fn build_filters(query: &TokenStatsQuery) -> (String, Vec<String>) {
+        let mut where_clauses = Vec::new();
+        let mut params = Vec::new();
+
+        if let Some(ref tool_type) = query.tool_type {
+            where_clauses.push("tool_type = ?");
+            params.push(tool_type.clone());
+        }
+
+        if let Some(ref session_id) = query.session_id {
+            where_clauses.push("session_id = ?");
+            params.push(session_id.clone());
+        }
+
+        if let Some(ref config_name) = query.config_name {
+            where_clauses.push("config_name = ?");
+            params.push(config_name.clone());
+        }
+
+        if let Some(start_time) = query.start_time {
+            where_clauses.push("timestamp >= ?");
+            params.push(start_time.to_string());
+        }
+
+        if let Some(end_time) = query.end_time {
+            where_clauses.push("timestamp <= ?");
+            params.push(end_time.to_string());
+        }
+
+        let where_clause = if where_clauses.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", where_clauses.join(" AND "))
+        };
+        (where_clause, params)
+    }
+
+    /// 按筛选条件将日志流式导出到文件（忽略分页参数）
+    pub fn export_logs(
+        &self,
+        query: &TokenStatsQuery,
+        format: LogExportFormat,
+        path: &Path,
+    ) -> Result<LogExportSummary> {
+        export::export_logs(&self.db_path, query, format, path)
+    }
+
     /// 清理旧数据
     pub fn cleanup_old_logs(
         &self,
@@ -640,6 +508,113 @@ impl TokenStatsDb {
         Ok((total, oldest, newest))
     }
 
+    /// 统计工具自指定时间（毫秒）以来的总成本（USD）
+    pub fn sum_cost_since(&self, tool_type: &str, since: i64) -> Result<f64> {
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+
+        let rows = manager
+            .query(
+                "SELECT COALESCE(SUM(total_cost), 0) FROM token_logs
+                WHERE tool_type = ?1 AND timestamp >= ?2",
+                &[tool_type, &since.to_string()],
+            )
+            .context("Failed to query cost sum")?;
+
+        Ok(rows
+            .first()
+            .and_then(|row| row.values.first())
+            .and_then(|v| v.as_f64())
+            .unwrap_or(0.0))
+    }
+
+    /// 统计所有工具自指定时间（毫秒）以来的请求数与总成本（USD）
+    pub fn usage_since(&self, since: i64) -> Result<(i64, f64)> {
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+
+        let rows = manager
+            .query(
+                "SELECT COUNT(*), COALESCE(SUM(total_cost), 0) FROM token_logs
+                WHERE timestamp >= ?1",
+                &[&since.to_string()],
+            )
+            .context("Failed to query usage")?;
+
+        let row = rows.first();
+        let value = |i: usize| row.and_then(|row| row.values.get(i));
+        Ok((
+            value(0).and_then(|v| v.as_i64()).unwrap_or(0),
+            value(1).and_then(|v| v.as_f64()).unwrap_or(0.0),
+        ))
+    }
+
+    /// 统计指定模型（含别名）自指定时间（毫秒）以来的累计 Token 数（输入 + 输出 + 缓存）
+    pub fn model_tokens_since(&self, models: &[String], since: i64) -> Result<i64> {
+        if models.is_empty() {
+            return Ok(0);
+        }
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+
+        let placeholders = vec!["?"; models.len()].join(", ");
+        let sql = format!(
+            "SELECT COALESCE(SUM(input_tokens + output_tokens + cache_creation_tokens + cache_read_tokens), 0)
+            FROM token_logs
+            WHERE timestamp >= ? AND model IN ({placeholders})"
+        );
+        let since = since.to_string();
+        let mut params: Vec<&str> = vec![&since];
+        params.extend(models.iter().map(String::as_str));
+
+        let rows = manager
+            .query(&sql, &params)
+            .context("Failed to query model token volume")?;
+
+        Ok(rows
+            .first()
+            .and_then(|row| row.values.first())
+            .and_then(|v| v.as_i64())
+            .unwrap_or(0))
+    }
+
+    /// 按工具与小时（UTC 整点）汇总自指定时间（毫秒）以来的消费与 Token
+    pub fn hourly_usage_since(&self, since: i64) -> Result<Vec<HourlyUsage>> {
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+
+        let rows = manager
+            .query(
+                "SELECT
+                    tool_type,
+                    (timestamp / 3600000) * 3600000 as hour_start,
+                    COALESCE(SUM(total_cost), 0.0) as total_cost,
+                    COALESCE(SUM(input_tokens + output_tokens + cache_creation_tokens + cache_read_tokens), 0) as total_tokens
+                FROM token_logs
+                WHERE timestamp >= ?1
+                GROUP BY tool_type, hour_start
+                ORDER BY tool_type, hour_start",
+                &[&since.to_string()],
+            )
+            .context("Failed to query hourly usage")?;
+
+        Ok(rows
+            .iter()
+            .filter_map(|row| {
+                Some(HourlyUsage {
+                    tool_type: row.values.first()?.as_str()?.to_string(),
+                    hour_start: row.values.get(1)?.as_i64()?,
+                    total_cost: row.values.get(2)?.as_f64().unwrap_or(0.0),
+                    total_tokens: row.values.get(3)?.as_i64().unwrap_or(0),
+                })
+            })
+            .collect())
+    }
+
     /// 强制执行 WAL checkpoint（手动触发）
     ///
     /// 将 WAL 文件中的所有数据回写到主数据库文件，
@@ -673,6 +648,129 @@ impl TokenStatsDb {
     }
 }
 
+impl TokenStatsDb {
+    /// 数据库维护：按大小上限删除最旧日志，然后 VACUUM、重建索引、ANALYZE 并截断 WAL
+    ///
+    /// `max_size_bytes` 为 None 时不限制大小
+    pub fn maintain(&self, max_size_bytes: Option<u64>) -> Result<DbMaintenanceReport> {
+        let started = std::time::Instant::now();
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+
+        let (db_bytes_before, wal_bytes_before) = self.file_sizes();
+        manager
+            .execute_raw("PRAGMA wal_checkpoint(TRUNCATE)")
+            .context("Failed to checkpoint WAL")?;
+
+        let deleted_rows = match max_size_bytes {
+            Some(max) => self.enforce_size_limit(max)?,
+            None => 0,
+        };
+
+        manager
+            .execute_raw("VACUUM")
+            .context("Failed to vacuum database")?;
+        manager
+            .execute_raw("REINDEX token_logs; ANALYZE; PRAGMA wal_checkpoint(TRUNCATE);")
+            .context("Failed to rebuild indexes")?;
+
+        let (db_bytes_after, wal_bytes_after) = self.file_sizes();
+        let (remaining_rows, _, _) = self.get_stats_summary()?;
+        let report = DbMaintenanceReport {
+            db_bytes_before,
+            wal_bytes_before,
+            db_bytes_after,
+            wal_bytes_after,
+            deleted_rows,
+            remaining_rows,
+            duration_ms: started.elapsed().as_millis() as i64,
+        };
+        tracing::info!(
+            db_bytes_before,
+            db_bytes_after,
+            wal_bytes_before,
+            deleted_rows,
+            "Token 统计数据库维护完成"
+        );
+        Ok(report)
+    }
+
+    /// 是否需要后台维护：超出大小上限，或空闲页占比过高
+    pub fn needs_maintenance(&self, max_size_bytes: Option<u64>) -> Result<bool> {
+        let stats = self.page_stats()?;
+        if max_size_bytes.is_some_and(|max| stats.used_bytes() > max) {
+            return Ok(true);
+        }
+        Ok(stats.page_count > 0
+            && stats.freelist_count as f64 / stats.page_count as f64 > FREELIST_VACUUM_RATIO)
+    }
+
+    /// 数据占用超过上限时按时间删除最旧的日志，返回删除条数
+    ///
+    /// 按平均每条日志的占用估算需要保留的条数，删除后占用约为上限的 90%
+    fn enforce_size_limit(&self, max_size_bytes: u64) -> Result<usize> {
+        let used = self.page_stats()?.used_bytes();
+        if used <= max_size_bytes {
+            return Ok(0);
+        }
+        let (total_rows, _, _) = self.get_stats_summary()?;
+        if total_rows == 0 {
+            return Ok(0);
+        }
+        let bytes_per_row = used as f64 / total_rows as f64;
+        let keep = (max_size_bytes as f64 * SIZE_LIMIT_TARGET_RATIO / bytes_per_row) as i64;
+        let delete = total_rows - keep.clamp(0, total_rows);
+        if delete == 0 {
+            return Ok(0);
+        }
+
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+        let deleted = manager
+            .execute(
+                "DELETE FROM token_logs WHERE id IN (
+                    SELECT id FROM token_logs ORDER BY timestamp ASC, id ASC LIMIT ?1
+                )",
+                &[&delete.to_string()],
+            )
+            .context("Failed to delete oldest logs")?;
+        tracing::warn!(
+            used_bytes = used,
+            max_size_bytes,
+            deleted,
+            "Token 统计数据库超出大小上限，已删除最旧的日志"
+        );
+        Ok(deleted)
+    }
+
+    /// 读取页统计（不经过查询缓存）
+    fn page_stats(&self) -> Result<PageStats> {
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+        Ok(manager.transaction(|tx| {
+            let pragma = |name: &str| -> rusqlite::Result<i64> {
+                tx.query_row(&format!("PRAGMA {name}"), [], |row| row.get(0))
+            };
+            Ok(PageStats {
+                page_count: pragma("page_count")?,
+                page_size: pragma("page_size")?,
+                freelist_count: pragma("freelist_count")?,
+            })
+        })?)
+    }
+
+    /// 主文件与 WAL 文件大小（字节，不存在时为 0）
+    fn file_sizes(&self) -> (u64, u64) {
+        let size = |path: &Path| std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        let mut wal = self.db_path.clone().into_os_string();
+        wal.push("-wal");
+        (size(&self.db_path), size(Path::new(&wal)))
+    }
+}
+
 impl Clone for TokenStatsDb {
     fn clone(&self) -> Self {
         Self::new(self.db_path.clone())
@@ -703,33 +801,15 @@ mod tests {
     fn test_insert_and_query() {
         let (db, _) = create_test_db();
 
-        let log = TokenLog::new(
-            "claude_code".to_string(),
-            chrono::Utc::now().timestamp_millis(),
-            "127.0.0.1".to_string(),
-            "session_123".to_string(),
-            "default".to_string(),
-            "claude-sonnet-4-5-20250929".to_string(),
-            Some("msg_123".to_string()),
-            1000,
-            500,
-            100,
-            0, // cache_creation_1h_tokens
-            200,
-            0, // reasoning_tokens
-            "success".to_string(),
-            "json".to_string(),
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            None, // reasoning_price
-            0.0,
-            None,
-        );
+        let log = TokenLog::test_builder()
+            .tool("claude_code")
+            .timestamp(chrono::Utc::now().timestamp_millis())
+            .session("session_123")
+            .model("claude-sonnet-4-5-20250929")
+            .message_id("msg_123")
+            .tokens(1000, 500)
+            .cache_tokens(100, 200)
+            .build();
 
         let id = db.insert_log(&log).unwrap();
         assert!(id > 0);
@@ -747,33 +827,16 @@ mod tests {
 
         // 插入多条记录
         for i in 0..25 {
-            let log = TokenLog::new(
-                "claude_code".to_string(),
-                chrono::Utc::now().timestamp_millis() + i,
-                "127.0.0.1".to_string(),
-                "session_123".to_string(),
-                "default".to_string(),
-                "claude-sonnet-4-5-20250929".to_string(),
-                Some(format!("msg_{}", i)),
-                100,
-                50,
-                10,
-                0, // cache_creation_1h_tokens
-                20,
-                0, // reasoning_tokens
-                "success".to_string(),
-                "sse".to_string(),
-                None,
-                None,
-                None,
-                None,
-                None,
-                None,
-                None,
-                None, // reasoning_price
-                0.0,
-                None,
-            );
+            let log = TokenLog::test_builder()
+                .tool("claude_code")
+                .timestamp(chrono::Utc::now().timestamp_millis() + i)
+                .session("session_123")
+                .model("claude-sonnet-4-5-20250929")
+                .message_id(&format!("msg_{}", i))
+                .tokens(100, 50)
+                .cache_tokens(10, 20)
+                .response_type("sse")
+                .build();
             db.insert_log(&log).unwrap();
         }
 
@@ -803,62 +866,22 @@ mod tests {
 
         // 插入旧数据和新数据
         let old_timestamp = chrono::Utc::now().timestamp_millis() - (40 * 86400 * 1000); // 40天前
-        let old_log = TokenLog::new(
-            "claude_code".to_string(),
-            old_timestamp,
-            "127.0.0.1".to_string(),
-            "session_old".to_string(),
-            "default".to_string(),
-            "claude-3".to_string(),
-            None,
-            100,
-            50,
-            0,
-            0, // cache_creation_1h_tokens
-            0,
-            0, // reasoning_tokens
-            "success".to_string(),
-            "json".to_string(),
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            None, // reasoning_price
-            0.0,
-            None,
-        );
+        let old_log = TokenLog::test_builder()
+            .tool("claude_code")
+            .timestamp(old_timestamp)
+            .session("session_old")
+            .model("claude-3")
+            .tokens(100, 50)
+            .build();
         db.insert_log(&old_log).unwrap();
 
-        let new_log = TokenLog::new(
-            "claude_code".to_string(),
-            chrono::Utc::now().timestamp_millis(),
-            "127.0.0.1".to_string(),
-            "session_new".to_string(),
-            "default".to_string(),
-            "claude-3".to_string(),
-            None,
-            200,
-            100,
-            0,
-            0, // cache_creation_1h_tokens
-            0,
-            0, // reasoning_tokens
-            "success".to_string(),
-            "json".to_string(),
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            None, // reasoning_price
-            0.0,
-            None,
-        );
+        let new_log = TokenLog::test_builder()
+            .tool("claude_code")
+            .timestamp(chrono::Utc::now().timestamp_millis())
+            .session("session_new")
+            .model("claude-3")
+            .tokens(200, 100)
+            .build();
         db.insert_log(&new_log).unwrap();
 
         // 清理30天前的数据
@@ -869,4 +892,52 @@ mod tests {
         let stats = db.get_session_stats("claude_code", "session_new").unwrap();
         assert_eq!(stats.request_count, 1);
     }
+
+    #[test]
+    fn test_maintain_enforces_size_limit() {
+        // 需要读取文件大小，临时目录须在测试期间保留
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("maintain.db");
+        let db = TokenStatsDb::new(db_path.clone());
+        db.init_table().unwrap();
+        let base = 1_700_000_000_000_i64;
+        for i in 0..400 {
+            let log = TokenLog::test_builder()
+                .timestamp(base + i * 1000)
+                .session(&format!("session_{i}"))
+                .tokens(100, 50)
+                .status("failed")
+                .error("upstream_error")
+                .error_detail(&"x".repeat(4000))
+                .response_time(100)
+                .build();
+            db.insert_log(&log).unwrap();
+        }
+
+        // 不限制大小：只整理，不删除
+        let report = db.maintain(None).unwrap();
+        assert_eq!(report.deleted_rows, 0);
+        assert_eq!(report.remaining_rows, 400);
+        assert_eq!(report.wal_bytes_after, 0);
+        assert!(
+            report.db_bytes_before + report.wal_bytes_before > 1_000_000,
+            "{report:?}"
+        );
+
+        // 上限为当前大小的一半：删除最旧的日志，保留最新的
+        let max = report.db_bytes_after / 2;
+        assert!(db.needs_maintenance(Some(max)).unwrap());
+        let report = db.maintain(Some(max)).unwrap();
+        assert!(report.deleted_rows >= 200, "{report:?}");
+        assert!(report.db_bytes_after <= max, "{report:?}");
+        assert_eq!(report.remaining_rows, 400 - report.deleted_rows as i64);
+        let (_, oldest, newest) = db.get_stats_summary().unwrap();
+        assert_eq!(newest, Some(base + 399 * 1000));
+        assert_eq!(oldest, Some(base + report.deleted_rows as i64 * 1000));
+        assert!(!db.needs_maintenance(Some(max)).unwrap());
+        assert_eq!(
+            std::fs::metadata(&db_path).unwrap().len(),
+            report.db_bytes_after
+        );
+    }
 }
@@ -0,0 +1,340 @@
+//! 统计周期（测量周期）
+//!
+//! 用户可以"开始新的统计周期"而不删除历史：关闭当前周期时，
+//! 将其按工具 + 模型聚合的用量归档到 `stats_epoch_summaries`，随后开启新周期。
+//! 仪表板默认只展示当前周期的数据，历史周期仍可按时间范围或归档汇总查询。
+//!
+//! 周期区间为左闭右开 `[started_at, ended_at)`，时间戳单位为毫秒。
+
+use crate::data::DataManager;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// 首个周期的默认名称
+const INITIAL_EPOCH_NAME: &str = "初始周期";
+
+/// 统计周期
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsEpoch {
+    pub id: i64,
+    pub name: String,
+    /// 开始时间戳（毫秒）
+    pub started_at: i64,
+    /// 结束时间戳（毫秒，当前周期为 None）
+    pub ended_at: Option<i64>,
+    /// 请求总数
+    pub request_count: i64,
+    /// 总成本（USD）
+    pub total_cost: f64,
+}
+
+/// 周期内按工具 + 模型聚合的用量
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EpochSummary {
+    pub tool_type: String,
+    pub model: String,
+    pub request_count: i64,
+    pub error_count: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cache_creation_tokens: i64,
+    pub cache_read_tokens: i64,
+    pub total_cost: f64,
+}
+
+/// 周期聚合字段（归档与实时查询共用）
+const SUMMARY_SELECT: &str = "tool_type, model,
+    COUNT(*),
+    COALESCE(SUM(CASE WHEN request_status IN ('failed', 'timeout') THEN 1 ELSE 0 END), 0),
+    COALESCE(SUM(input_tokens), 0),
+    COALESCE(SUM(output_tokens), 0),
+    COALESCE(SUM(cache_creation_tokens), 0),
+    COALESCE(SUM(cache_read_tokens), 0),
+    COALESCE(SUM(total_cost), 0.0)";
+
+/// 统计周期管理
+pub struct StatsEpochManager {
+    db_path: PathBuf,
+}
+
+impl StatsEpochManager {
+    pub fn new(db_path: PathBuf) -> Self {
+        Self { db_path }
+    }
+
+    /// 创建周期相关表（随 token_logs 一起初始化）
+    pub fn init_tables(&self) -> Result<()> {
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+        manager
+            .execute_raw(
+                "CREATE TABLE IF NOT EXISTS stats_epochs (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    name TEXT NOT NULL,
+                    started_at INTEGER NOT NULL,
+                    ended_at INTEGER
+                );
+                CREATE TABLE IF NOT EXISTS stats_epoch_summaries (
+                    epoch_id INTEGER NOT NULL,
+                    tool_type TEXT NOT NULL,
+                    model TEXT NOT NULL,
+                    request_count INTEGER NOT NULL DEFAULT 0,
+                    error_count INTEGER NOT NULL DEFAULT 0,
+                    input_tokens INTEGER NOT NULL DEFAULT 0,
+                    output_tokens INTEGER NOT NULL DEFAULT 0,
+                    cache_creation_tokens INTEGER NOT NULL DEFAULT 0,
+                    cache_read_tokens INTEGER NOT NULL DEFAULT 0,
+                    total_cost REAL NOT NULL DEFAULT 0.0,
+                    PRIMARY KEY (epoch_id, tool_type, model)
+                )",
+            )
+            .context("Failed to create stats epoch tables")?;
+        Ok(())
+    }
+
+    /// 获取当前周期（不存在时以最早一条日志为起点创建初始周期）
+    pub fn current(&self) -> Result<StatsEpoch> {
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+
+        let id = manager.transaction(|tx| {
+            let open: Option<i64> = tx
+                .query_row(
+                    "SELECT id FROM stats_epochs WHERE ended_at IS NULL ORDER BY id DESC LIMIT 1",
+                    [],
+                    |row| row.get(0),
+                )
+                .ok();
+            if let Some(id) = open {
+                return Ok(id);
+            }
+            let now = chrono::Utc::now().timestamp_millis();
+            let started_at: i64 = tx.query_row(
+                "SELECT COALESCE(MIN(timestamp), ?1) FROM token_logs",
+                [now],
+                |row| row.get(0),
+            )?;
+            tx.execute(
+                "INSERT INTO stats_epochs (name, started_at) VALUES (?1, ?2)",
+                rusqlite::params![INITIAL_EPOCH_NAME, started_at],
+            )?;
+            Ok(tx.last_insert_rowid())
+        })?;
+
+        self.get(id)
+    }
+
+    /// 按 ID 获取周期
+    pub fn get(&self, id: i64) -> Result<StatsEpoch> {
+        self.list()?
+            .into_iter()
+            .find(|epoch| epoch.id == id)
+            .with_context(|| format!("统计周期不存在: {}", id))
+    }
+
+    /// 列出所有周期（最新在前）
+    ///
+    /// 已关闭周期的总量取自归档，当前周期实时统计。
+    pub fn list(&self) -> Result<Vec<StatsEpoch>> {
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+
+        Ok(manager.transaction(|tx| {
+            let mut stmt = tx.prepare(
+                "SELECT e.id, e.name, e.started_at, e.ended_at,
+                    CASE WHEN e.ended_at IS NULL
+                        THEN (SELECT COUNT(*) FROM token_logs WHERE timestamp >= e.started_at)
+                        ELSE (SELECT COALESCE(SUM(request_count), 0) FROM stats_epoch_summaries
+                              WHERE epoch_id = e.id)
+                    END,
+                    CASE WHEN e.ended_at IS NULL
+                        THEN (SELECT COALESCE(SUM(total_cost), 0.0) FROM token_logs
+                              WHERE timestamp >= e.started_at)
+                        ELSE (SELECT COALESCE(SUM(total_cost), 0.0) FROM stats_epoch_summaries
+                              WHERE epoch_id = e.id)
+                    END
+                FROM stats_epochs e
+                ORDER BY e.started_at DESC, e.id DESC",
+            )?;
+            let epochs = stmt
+                .query_map([], |row| {
+                    Ok(StatsEpoch {
+                        id: row.get(0)?,
+                        name: row.get(1)?,
+                        started_at: row.get(2)?,
+                        ended_at: row.get(3)?,
+                        request_count: row.get(4)?,
+                        total_cost: row.get(5)?,
+                    })
+                })?
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(crate::data::DataError::Database)?;
+            Ok(epochs)
+        })?)
+    }
+
+    /// 查询周期的分组用量（已关闭周期读取归档，当前周期实时聚合）
+    pub fn summary(&self, id: i64) -> Result<Vec<EpochSummary>> {
+        let epoch = self.get(id)?;
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+
+        let (sql, params) = match epoch.ended_at {
+            Some(_) => (
+                "SELECT tool_type, model, request_count, error_count, input_tokens,
+                    output_tokens, cache_creation_tokens, cache_read_tokens, total_cost
+                FROM stats_epoch_summaries WHERE epoch_id = ?1
+                ORDER BY total_cost DESC"
+                    .to_string(),
+                vec![epoch.id],
+            ),
+            None => (
+                format!(
+                    "SELECT {} FROM token_logs WHERE timestamp >= ?1
+                    GROUP BY tool_type, model ORDER BY 9 DESC",
+                    SUMMARY_SELECT
+                ),
+                vec![epoch.started_at],
+            ),
+        };
+
+        Ok(manager.transaction(|tx| {
+            let mut stmt = tx.prepare(&sql)?;
+            let rows = stmt
+                .query_map(rusqlite::params_from_iter(params.iter()), |row| {
+                    Ok(EpochSummary {
+                        tool_type: row.get(0)?,
+                        model: row.get(1)?,
+                        request_count: row.get(2)?,
+                        error_count: row.get(3)?,
+                        input_tokens: row.get(4)?,
+                        output_tokens: row.get(5)?,
+                        cache_creation_tokens: row.get(6)?,
+                        cache_read_tokens: row.get(7)?,
+                        total_cost: row.get(8)?,
+                    })
+                })?
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(crate::data::DataError::Database)?;
+            Ok(rows)
+        })?)
+    }
+
+    /// 关闭当前周期并归档其聚合数据，随后开启新周期
+    ///
+    /// 返回新开启的周期。
+    pub fn start_new(&self, name: Option<String>) -> Result<StatsEpoch> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let name = match name.map(|n| n.trim().to_string()) {
+            Some(n) if n.is_empty() => bail!("周期名称不能为空"),
+            Some(n) => n,
+            None => chrono::Local::now().format("%Y-%m-%d %H:%M").to_string(),
+        };
+        self.start_new_at(&name, now)
+    }
+
+    fn start_new_at(&self, name: &str, now: i64) -> Result<StatsEpoch> {
+        let current = self.current()?;
+        if now <= current.started_at {
+            bail!("新周期的开始时间必须晚于当前周期");
+        }
+
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+        let id = manager.transaction(|tx| {
+            tx.execute(
+                &format!(
+                    "INSERT INTO stats_epoch_summaries (
+                        epoch_id, tool_type, model, request_count, error_count, input_tokens,
+                        output_tokens, cache_creation_tokens, cache_read_tokens, total_cost
+                    )
+                    SELECT ?1, {} FROM token_logs
+                    WHERE timestamp >= ?2 AND timestamp < ?3
+                    GROUP BY tool_type, model",
+                    SUMMARY_SELECT
+                ),
+                rusqlite::params![current.id, current.started_at, now],
+            )?;
+            tx.execute(
+                "UPDATE stats_epochs SET ended_at = ?1 WHERE id = ?2",
+                rusqlite::params![now, current.id],
+            )?;
+            tx.execute(
+                "INSERT INTO stats_epochs (name, started_at) VALUES (?1, ?2)",
+                rusqlite::params![name, now],
+            )?;
+            Ok(tx.last_insert_rowid())
+        })?;
+
+        tracing::info!(
+            closed = %current.name,
+            requests = current.request_count,
+            "统计周期已归档，开启新周期: {}",
+            name
+        );
+        self.get(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::token_stats::db::TokenStatsDb;
+
+    fn insert_log(db_path: &std::path::Path, timestamp: i64, model: &str, status: &str, cost: f64) {
+        DataManager::global()
+            .sqlite(db_path)
+            .unwrap()
+            .execute(
+                "INSERT INTO token_logs (tool_type, timestamp, client_ip, session_id, config_name,
+                    model, input_tokens, output_tokens, request_status, total_cost)
+                 VALUES ('claude_code', ?1, '127.0.0.1', 's', 'default', ?2, 100, 50, ?3, ?4)",
+                &[&timestamp.to_string(), model, status, &cost.to_string()],
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn test_start_new_epoch_archives_previous() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("token_stats.db");
+        TokenStatsDb::new(db_path.clone()).init_table().unwrap();
+        let epochs = StatsEpochManager::new(db_path.clone());
+
+        insert_log(&db_path, 1_000, "sonnet", "success", 1.0);
+        insert_log(&db_path, 2_000, "sonnet", "failed", 0.5);
+        insert_log(&db_path, 3_000, "opus", "success", 2.0);
+
+        // 初始周期从最早一条日志开始
+        let initial = epochs.current().unwrap();
+        assert_eq!(initial.started_at, 1_000);
+        assert_eq!(initial.request_count, 3);
+
+        let next = epochs.start_new_at("第二周期", 5_000).unwrap();
+        insert_log(&db_path, 6_000, "opus", "success", 4.0);
+
+        assert_eq!(epochs.current().unwrap().id, next.id);
+        let archived = epochs.summary(initial.id).unwrap();
+        assert_eq!(archived.len(), 2);
+        assert_eq!(archived[0].model, "opus");
+        assert_eq!(archived[1].request_count, 2);
+        assert_eq!(archived[1].error_count, 1);
+
+        // 归档后新增日志不影响已关闭周期
+        let list = epochs.list().unwrap();
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].name, "第二周期");
+        assert_eq!(list[0].request_count, 1);
+        assert_eq!(list[1].ended_at, Some(5_000));
+        assert!((list[1].total_cost - 3.5).abs() < 1e-9);
+
+        assert!(epochs.start_new_at("过早", 5_000).is_err());
+        assert!(epochs.start_new(Some("  ".to_string())).is_err());
+    }
+}
@@ -0,0 +1,247 @@
+//! Token 日志导出
+//!
+//! 按查询条件将 `token_logs` 导出为 CSV 或 JSON Lines：
+//! - 使用独立只读连接逐行读取并写入文件，不会一次性加载全部日志
+//! - 包含 Token 用量、单价与总成本列，可直接用表格软件分析
+
+use super::db::TokenStatsDb;
+use super::sql_console::{open_read_only, value_ref_to_json};
+use crate::models::token_stats::TokenStatsQuery;
+use anyhow::{bail, Context, Result};
+use rusqlite::params_from_iter;
+use rusqlite::types::ValueRef;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// 导出列（SQL 表达式, 列名）
+const EXPORT_COLUMNS: [(&str, &str); 30] = [
+    ("id", "id"),
+    ("timestamp", "timestamp"),
+    (
+        "strftime('%Y-%m-%d %H:%M:%S', timestamp / 1000, 'unixepoch', 'localtime')",
+        "time",
+    ),
+    ("tool_type", "tool_type"),
+    ("session_id", "session_id"),
+    ("config_name", "config_name"),
+    ("model", "model"),
+    ("message_id", "message_id"),
+    ("client_ip", "client_ip"),
+    ("client_key_label", "client_key_label"),
+    ("request_status", "request_status"),
+    ("response_type", "response_type"),
+    ("error_type", "error_type"),
+    ("error_detail", "error_detail"),
+    ("input_tokens", "input_tokens"),
+    ("output_tokens", "output_tokens"),
+    ("cache_creation_tokens", "cache_creation_tokens"),
+    ("cache_creation_1h_tokens", "cache_creation_1h_tokens"),
+    ("cache_read_tokens", "cache_read_tokens"),
+    ("reasoning_tokens", "reasoning_tokens"),
+    ("response_time_ms", "response_time_ms"),
+    ("input_price", "input_price"),
+    ("output_price", "output_price"),
+    ("cache_write_price", "cache_write_price"),
+    ("cache_read_price", "cache_read_price"),
+    ("reasoning_price", "reasoning_price"),
+    ("total_cost", "total_cost"),
+    ("pricing_template_id", "pricing_template_id"),
+    ("upstream_key_alias", "upstream_key_alias"),
+    ("tags", "tags"),
+];
+
+/// 导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub enum LogExportFormat {
+    /// 逗号分隔（含表头，UTF-8 BOM 便于 Excel 识别编码）
+    Csv,
+    /// 每行一个 JSON 对象
+    Jsonl,
+}
+
+/// 导出结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct LogExportSummary {
+    pub path: String,
+    pub format: LogExportFormat,
+    /// 导出的日志条数
+    pub row_count: u64,
+    /// 文件大小（字节）
+    pub bytes: u64,
+}
+
+/// CSV 字段转义（含分隔符、引号或换行时加引号）
+fn csv_escape(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn csv_field(value: ValueRef<'_>) -> String {
+    match value {
+        ValueRef::Null => String::new(),
+        ValueRef::Integer(i) => i.to_string(),
+        ValueRef::Real(f) => f.to_string(),
+        ValueRef::Text(s) => csv_escape(&String::from_utf8_lossy(s)),
+        ValueRef::Blob(b) => format!("<blob {} bytes>", b.len()),
+    }
+}
+
+/// 将筛选后的日志按时间升序写入 `path`
+pub(super) fn export_logs(
+    db_path: &Path,
+    query: &TokenStatsQuery,
+    format: LogExportFormat,
+    path: &Path,
+) -> Result<LogExportSummary> {
+    if !db_path.exists() {
+        bail!("统计数据库不存在: {}", db_path.display());
+    }
+    let conn = open_read_only(db_path)?;
+
+    let (where_clause, params) = TokenStatsDb::build_filters(query);
+    let select = EXPORT_COLUMNS
+        .iter()
+        .map(|(expr, _)| *expr)
+        .collect::<Vec<_>>()
+        .join(", ");
+    let sql =
+        format!("SELECT {select} FROM token_logs {where_clause} ORDER BY timestamp ASC, id ASC");
+
+    let file =
+        File::create(path).with_context(|| format!("创建导出文件失败: {}", path.display()))?;
+    let result = write_rows(&conn, &sql, &params, format, BufWriter::new(file));
+    let row_count = match result {
+        Ok(count) => count,
+        Err(e) => {
+            // 写入失败时不保留不完整的文件
+            let _ = std::fs::remove_file(path);
+            return Err(e);
+        }
+    };
+
+    Ok(LogExportSummary {
+        path: path.to_string_lossy().to_string(),
+        format,
+        row_count,
+        bytes: std::fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+    })
+}
+
+fn write_rows<W: Write>(
+    conn: &rusqlite::Connection,
+    sql: &str,
+    params: &[String],
+    format: LogExportFormat,
+    mut out: W,
+) -> Result<u64> {
+    if format == LogExportFormat::Csv {
+        out.write_all("\u{feff}".as_bytes())?;
+        let header: Vec<&str> = EXPORT_COLUMNS.iter().map(|(_, name)| *name).collect();
+        writeln!(out, "{}", header.join(","))?;
+    }
+
+    let mut stmt = conn.prepare(sql).context("准备导出查询失败")?;
+    let mut rows = stmt.query(params_from_iter(params.iter()))?;
+    let mut count = 0u64;
+    while let Some(row) = rows.next()? {
+        match format {
+            LogExportFormat::Csv => {
+                let fields = (0..EXPORT_COLUMNS.len())
+                    .map(|i| row.get_ref(i).map(csv_field))
+                    .collect::<rusqlite::Result<Vec<_>>>()?;
+                writeln!(out, "{}", fields.join(","))?;
+            }
+            LogExportFormat::Jsonl => {
+                let mut object = serde_json::Map::with_capacity(EXPORT_COLUMNS.len());
+                for (i, (_, name)) in EXPORT_COLUMNS.iter().enumerate() {
+                    // 可选字段写入时以空字符串存储，导出为 null
+                    let value = match row.get_ref(i)? {
+                        ValueRef::Text(b"") => serde_json::Value::Null,
+                        other => value_ref_to_json(other),
+                    };
+                    object.insert(name.to_string(), value);
+                }
+                serde_json::to_writer(&mut out, &object)?;
+                out.write_all(b"\n")?;
+            }
+        }
+        count += 1;
+    }
+
+    out.flush().context("写入导出文件失败")?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::token_stats::TokenLog;
+    use tempfile::tempdir;
+
+    fn sample_log(tool_type: &str, timestamp: i64, model: &str, cost: f64) -> TokenLog {
+        TokenLog::test_builder()
+            .tool(tool_type)
+            .timestamp(timestamp)
+            .model(model)
+            .tokens(100, 50)
+            .cache_tokens(0, 10)
+            .response_time(1200)
+            .input_price(0.0003)
+            .output_price(0.0015)
+            .cost(cost)
+            .build()
+    }
+
+    #[test]
+    fn test_export_csv_and_jsonl_with_filters() {
+        let dir = tempdir().unwrap();
+        let db = TokenStatsDb::new(dir.path().join("export.db"));
+        db.init_table().unwrap();
+        db.insert_log(&sample_log("codex", 2_000, "gpt-5", 0.25))
+            .unwrap();
+        db.insert_log(&sample_log("codex", 1_000, "model \"a\", b", 0.5))
+            .unwrap();
+        db.insert_log(&sample_log("claude-code", 3_000, "claude", 1.0))
+            .unwrap();
+
+        let query = TokenStatsQuery {
+            tool_type: Some("codex".to_string()),
+            ..Default::default()
+        };
+
+        let csv_path = dir.path().join("logs.csv");
+        let summary = db
+            .export_logs(&query, LogExportFormat::Csv, &csv_path)
+            .unwrap();
+        assert_eq!(summary.row_count, 2);
+        let csv = std::fs::read_to_string(&csv_path).unwrap();
+        let lines: Vec<&str> = csv.trim_start_matches('\u{feff}').lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("id,timestamp,time,tool_type"));
+        assert!(lines[0].ends_with("pricing_template_id,upstream_key_alias,tags"));
+        // 按时间升序，含特殊字符的字段被转义
+        assert!(lines[1].contains(",\"model \"\"a\"\", b\","));
+        assert!(lines[1].contains(",0.5,"));
+        assert!(lines[2].contains(",gpt-5,"));
+
+        let jsonl_path = dir.path().join("logs.jsonl");
+        let summary = db
+            .export_logs(&query, LogExportFormat::Jsonl, &jsonl_path)
+            .unwrap();
+        assert_eq!(summary.row_count, 2);
+        let jsonl = std::fs::read_to_string(&jsonl_path).unwrap();
+        let first: serde_json::Value = serde_json::from_str(jsonl.lines().next().unwrap()).unwrap();
+        assert_eq!(first["timestamp"], 1_000);
+        assert_eq!(first["total_cost"], 0.5);
+        assert_eq!(first["cache_read_tokens"], 10);
+        assert!(first["error_type"].is_null());
+    }
+}
@@ -0,0 +1,258 @@
+//! 请求流水导出（Flight Recorder）
+//!
+//! 启用后，每条完成的代理请求都会以一行紧凑的 NDJSON 追加到用户指定的文件，
+//! 便于 `tail -f` 等外部工具实时消费，无需开启完整的请求抓取。
+
+use crate::models::config::FlightRecorderConfig;
+use crate::models::token_stats::TokenLog;
+use crate::utils::expand_home;
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// 全局 FlightRecorder 单例
+static FLIGHT_RECORDER: Lazy<FlightRecorder> = Lazy::new(FlightRecorder::new);
+
+/// 单条流水记录（NDJSON 的一行）
+#[derive(Debug, Serialize)]
+pub struct FlightRecord<'a> {
+    /// 请求完成时间戳（毫秒）
+    pub ts: i64,
+    pub tool: &'a str,
+    pub session: &'a str,
+    pub model: &'a str,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cache_creation_tokens: i64,
+    pub cache_read_tokens: i64,
+    pub reasoning_tokens: i64,
+    /// 总成本（USD）
+    pub cost: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<i64>,
+    pub status: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_type: Option<&'a str>,
+}
+
+impl<'a> From<&'a TokenLog> for FlightRecord<'a> {
+    fn from(log: &'a TokenLog) -> Self {
+        Self {
+            ts: log.timestamp,
+            tool: &log.tool_type,
+            session: &log.session_id,
+            model: &log.model,
+            input_tokens: log.input_tokens,
+            output_tokens: log.output_tokens,
+            cache_creation_tokens: log.cache_creation_tokens,
+            cache_read_tokens: log.cache_read_tokens,
+            reasoning_tokens: log.reasoning_tokens,
+            cost: log.total_cost,
+            latency_ms: log.response_time_ms,
+            status: &log.request_status,
+            error_type: log.error_type.as_deref(),
+        }
+    }
+}
+
+/// 已打开的输出文件
+struct RecorderSink {
+    path: PathBuf,
+    file: File,
+}
+
+/// 请求流水记录器
+pub struct FlightRecorder {
+    sink: Mutex<Option<RecorderSink>>,
+}
+
+impl FlightRecorder {
+    /// 创建未启用的记录器
+    pub fn new() -> Self {
+        Self {
+            sink: Mutex::new(None),
+        }
+    }
+
+    /// 获取全局单例
+    pub fn global() -> &'static FlightRecorder {
+        &FLIGHT_RECORDER
+    }
+
+    /// 应用配置
+    ///
+    /// 启用时以追加模式打开目标文件（自动创建父目录），禁用时关闭文件
+    pub fn configure(&self, config: &FlightRecorderConfig) -> Result<()> {
+        let target = match (config.enabled, config.file_path.as_deref()) {
+            (true, Some(path)) if !path.trim().is_empty() => Some(expand_home(path.trim())),
+            (true, _) => anyhow::bail!("启用请求流水导出时必须指定输出文件"),
+            (false, _) => None,
+        };
+
+        let mut sink = self
+            .sink
+            .lock()
+            .map_err(|e| anyhow::anyhow!("获取 FlightRecorder 锁失败: {}", e))?;
+
+        let Some(path) = target else {
+            if sink.take().is_some() {
+                tracing::info!("请求流水导出已关闭");
+            }
+            return Ok(());
+        };
+
+        // 路径未变化时保留已打开的文件句柄
+        if sink.as_ref().is_some_and(|s| s.path == path) {
+            return Ok(());
+        }
+
+        let file = open_append(&path)?;
+        tracing::info!(path = %path.display(), "请求流水导出已启用");
+        *sink = Some(RecorderSink { path, file });
+        Ok(())
+    }
+
+    /// 是否已启用
+    pub fn is_enabled(&self) -> bool {
+        self.sink.lock().map(|s| s.is_some()).unwrap_or(false)
+    }
+
+    /// 当前输出文件路径
+    pub fn current_path(&self) -> Option<PathBuf> {
+        self.sink
+            .lock()
+            .ok()
+            .and_then(|s| s.as_ref().map(|sink| sink.path.clone()))
+    }
+
+    /// 追加一条请求记录（未启用时为空操作，写入失败仅记录警告）
+    pub fn record(&self, log: &TokenLog) {
+        let Ok(mut guard) = self.sink.lock() else {
+            return;
+        };
+        let Some(sink) = guard.as_mut() else {
+            return;
+        };
+
+        let mut line = match serde_json::to_vec(&FlightRecord::from(log)) {
+            Ok(line) => line,
+            Err(e) => {
+                tracing::warn!(error = ?e, "序列化请求流水失败");
+                return;
+            }
+        };
+        line.push(b'\n');
+
+        // 每行立即 flush，保证外部 tail 工具实时可见
+        if let Err(e) = sink.file.write_all(&line).and_then(|_| sink.file.flush()) {
+            tracing::warn!(
+                path = %sink.path.display(),
+                error = ?e,
+                "写入请求流水失败"
+            );
+        }
+    }
+}
+
+impl Default for FlightRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 以追加模式打开文件
+fn open_append(path: &Path) -> Result<File> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("创建目录失败: {}", parent.display()))?;
+    }
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("打开请求流水文件失败: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn sample_log(session: &str, status: &str) -> TokenLog {
+        TokenLog::test_builder()
+            .timestamp(1700000000000)
+            .session(session)
+            .model("claude-sonnet-4-5-20250929")
+            .tokens(1000, 500)
+            .cache_tokens(0, 200)
+            .status(status)
+            .response_type("sse")
+            .response_time(1234)
+            .cost(0.0123)
+            .build()
+    }
+
+    #[test]
+    fn test_record_appends_ndjson_lines() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("nested").join("feed.ndjson");
+        let recorder = FlightRecorder::new();
+        recorder
+            .configure(&FlightRecorderConfig {
+                enabled: true,
+                file_path: Some(path.to_string_lossy().to_string()),
+            })
+            .unwrap();
+        assert!(recorder.is_enabled());
+
+        recorder.record(&sample_log("s1", "success"));
+        recorder.record(&sample_log("s2", "failed"));
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<serde_json::Value> = content
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["session"], "s1");
+        assert_eq!(lines[0]["input_tokens"], 1000);
+        assert_eq!(lines[0]["latency_ms"], 1234);
+        assert_eq!(lines[1]["status"], "failed");
+        assert!(lines[1].get("error_type").is_none());
+    }
+
+    #[test]
+    fn test_disabled_recorder_writes_nothing() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("feed.ndjson");
+        let recorder = FlightRecorder::new();
+        recorder
+            .configure(&FlightRecorderConfig {
+                enabled: true,
+                file_path: Some(path.to_string_lossy().to_string()),
+            })
+            .unwrap();
+        recorder
+            .configure(&FlightRecorderConfig::default())
+            .unwrap();
+        assert!(!recorder.is_enabled());
+
+        recorder.record(&sample_log("s1", "success"));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "");
+    }
+
+    #[test]
+    fn test_enable_without_path_fails() {
+        let recorder = FlightRecorder::new();
+        let result = recorder.configure(&FlightRecorderConfig {
+            enabled: true,
+            file_path: None,
+        });
+        assert!(result.is_err());
+        assert!(!recorder.is_enabled());
+    }
+}
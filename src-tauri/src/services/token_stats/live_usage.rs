@@ -0,0 +1,58 @@
+//! 实时用量事件
+//!
+//! 透明代理转发 SSE 流时按节流间隔发送 `token-usage-delta` 事件，
+//! 前端据此在请求进行中实时展示 Token 与成本（请求结束后的统计仍以 token_logs 为准）：
+//! - 输入 / 缓存 Token 取自流开头的 usage，输出 Token 在最终 usage 到达前按已生成内容估算
+//! - 每个请求以 `done = true` 的事件结束（取消或中断的请求同样会发送）
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+use std::time::Duration;
+
+/// 同一请求两次实时用量事件的最小间隔
+pub const USAGE_DELTA_INTERVAL: Duration = Duration::from_millis(250);
+
+static USAGE_NOTIFIER: Lazy<RwLock<Option<UsageDeltaNotifier>>> = Lazy::new(|| RwLock::new(None));
+
+/// `token-usage-delta` 事件载荷（数值均为该请求截至目前的累计值）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct TokenUsageDelta {
+    pub tool_id: String,
+    /// 会话 display_id（与 token_logs.session_id 一致）
+    pub session_id: String,
+    /// 单次请求 ID（同一会话可能有多个并发请求）
+    pub request_id: String,
+    pub model: String,
+    pub input_tokens: i64,
+    /// 流未结束时为估算值
+    pub output_tokens: i64,
+    pub cache_creation_tokens: i64,
+    pub cache_read_tokens: i64,
+    /// 成本估算（USD）
+    pub total_cost: f64,
+    /// 请求已结束（此后不再发送该 request_id 的事件）
+    pub done: bool,
+    /// 事件时间（Unix 时间戳，毫秒）
+    pub timestamp: i64,
+}
+
+/// 实时用量事件回调
+pub type UsageDeltaNotifier = Box<dyn Fn(TokenUsageDelta) + Send + Sync + 'static>;
+
+/// 设置实时用量事件回调
+pub(super) fn set_notifier(notifier: UsageDeltaNotifier) {
+    *USAGE_NOTIFIER.write().unwrap() = Some(notifier);
+}
+
+/// 是否有接收方（没有时代理不计算实时用量）
+pub(super) fn has_notifier() -> bool {
+    USAGE_NOTIFIER.read().unwrap().is_some()
+}
+
+pub(super) fn emit(event: TokenUsageDelta) {
+    if let Some(notifier) = USAGE_NOTIFIER.read().unwrap().as_ref() {
+        notifier(event);
+    }
+}
@@ -4,6 +4,7 @@ use super::{LogStatus, ResponseType, TokenLogger};
 use crate::models::token_stats::TokenLog;
 use crate::services::pricing::PRICING_MANAGER;
 use crate::services::token_stats::processor::{create_processor, TokenInfo};
+use crate::services::token_stats::streaming::StreamingTokenAccumulator;
 use anyhow::Result;
 use chrono::Utc;
 
@@ -98,15 +99,14 @@ impl TokenLogger for CodexLogger {
     fn log_sse_response(
         &self,
         request_body: &[u8],
-        sse_chunks: Vec<String>,
+        stream: &StreamingTokenAccumulator,
         session_id: String,
         config_name: String,
         client_ip: String,
         response_time_ms: Option<i64>,
     ) -> Result<TokenLog> {
-        // 使用 processor 提取 TokenInfo
-        let processor = create_processor("codex")?;
-        let token_info = processor.process_sse_response(request_body, sse_chunks)?;
+        // 流式累加器已在转发过程中完成事件解析，这里只生成 TokenInfo
+        let token_info = stream.token_info(self.tool_id(), request_body)?;
 
         // 构建日志（成功状态）
         self.build_log(
@@ -203,15 +203,15 @@ mod tests {
     fn test_log_sse_response() {
         let logger = CodexLogger;
         let request_body = r#"{"model":"gpt-5.1","messages":[]}"#;
-        let sse_chunks = vec![
-            r#"{"type":"response.created","response":{"id":"resp_abc123"}}"#.to_string(),
-            r#"{"type":"response.completed","response":{"id":"resp_abc123","usage":{"input_tokens":10591,"input_tokens_details":{"cached_tokens":10240},"output_tokens":15,"output_tokens_details":{"reasoning_tokens":0},"total_tokens":10606}}}"#.to_string(),
-        ];
+        let stream = StreamingTokenAccumulator::from_data_lines([
+            r#"{"type":"response.created","response":{"id":"resp_abc123"}}"#,
+            r#"{"type":"response.completed","response":{"id":"resp_abc123","usage":{"input_tokens":10591,"input_tokens_details":{"cached_tokens":10240},"output_tokens":15,"output_tokens_details":{"reasoning_tokens":0},"total_tokens":10606}}}"#,
+        ]);
 
         let log = logger
             .log_sse_response(
                 request_body.as_bytes(),
-                sse_chunks,
+                &stream,
                 "session_123".to_string(),
                 "default".to_string(),
                 "127.0.0.1".to_string(),
@@ -11,6 +11,7 @@ pub use codex::CodexLogger;
 pub use types::{LogStatus, ResponseType};
 
 use crate::models::token_stats::TokenLog;
+use crate::services::token_stats::streaming::StreamingTokenAccumulator;
 use anyhow::{anyhow, Result};
 
 /// 工具日志记录器 - 负责将 Token 信息记录到日志
@@ -22,7 +23,7 @@ pub trait TokenLogger: Send + Sync {
     ///
     /// # 参数
     /// - `request_body`: 请求体（用于提取 model）
-    /// - `sse_chunks`: SSE 数据行（Vec<String>）
+    /// - `stream`: 转发过程中累加的 SSE Token 状态
     /// - `session_id`: 会话 ID
     /// - `config_name`: 配置名称
     /// - `client_ip`: 客户端 IP
@@ -33,7 +34,7 @@ pub trait TokenLogger: Send + Sync {
     fn log_sse_response(
         &self,
         request_body: &[u8],
-        sse_chunks: Vec<String>,
+        stream: &StreamingTokenAccumulator,
         session_id: String,
         config_name: String,
         client_ip: String,
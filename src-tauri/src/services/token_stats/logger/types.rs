@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 
 /// 日志状态
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
+#[serde(rename_all = "snake_case")]
 pub enum LogStatus {
     /// 成功
     Success,
@@ -14,6 +14,10 @@ pub enum LogStatus {
     Failed,
     /// 部分成功（已提取部分 Token 信息）
     Partial,
+    /// 被本地限流拦截（未转发到上游）
+    RateLimited,
+    /// 上游超时（连接、总时长或流式响应空闲超时）
+    Timeout,
 }
 
 impl LogStatus {
@@ -23,6 +27,8 @@ impl LogStatus {
             LogStatus::Success => "success",
             LogStatus::Failed => "failed",
             LogStatus::Partial => "partial",
+            LogStatus::RateLimited => "rate_limited",
+            LogStatus::Timeout => "timeout",
         }
     }
 
@@ -33,6 +39,8 @@ impl LogStatus {
             "success" => LogStatus::Success,
             "failed" => LogStatus::Failed,
             "partial" => LogStatus::Partial,
+            "rate_limited" => LogStatus::RateLimited,
+            "timeout" => LogStatus::Timeout,
             _ => LogStatus::Failed,
         }
     }
@@ -80,6 +88,8 @@ mod tests {
         assert_eq!(LogStatus::Success.as_str(), "success");
         assert_eq!(LogStatus::Failed.as_str(), "failed");
         assert_eq!(LogStatus::Partial.as_str(), "partial");
+        assert_eq!(LogStatus::RateLimited.as_str(), "rate_limited");
+        assert_eq!(LogStatus::Timeout.as_str(), "timeout");
     }
 
     #[test]
@@ -87,6 +97,8 @@ mod tests {
         assert_eq!(LogStatus::from_str("success"), LogStatus::Success);
         assert_eq!(LogStatus::from_str("failed"), LogStatus::Failed);
         assert_eq!(LogStatus::from_str("partial"), LogStatus::Partial);
+        assert_eq!(LogStatus::from_str("rate_limited"), LogStatus::RateLimited);
+        assert_eq!(LogStatus::from_str("timeout"), LogStatus::Timeout);
         assert_eq!(LogStatus::from_str("unknown"), LogStatus::Failed); // 回退
     }
 
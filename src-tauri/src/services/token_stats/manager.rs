@@ -1,9 +1,17 @@
 use crate::models::token_stats::{SessionStats, TokenLog, TokenLogsPage, TokenStatsQuery};
-use crate::services::token_stats::db::TokenStatsDb;
+use crate::services::proxy_config_manager::ProxyConfigManager;
+use crate::services::token_stats::anomaly::AnomalyDetector;
+use crate::services::token_stats::budget::{BudgetStatus, BudgetTracker};
+use crate::services::token_stats::db::{DbMaintenanceReport, TokenStatsDb};
+use crate::services::token_stats::export::{LogExportFormat, LogExportSummary};
+use crate::services::token_stats::flight_recorder::FlightRecorder;
+use crate::services::token_stats::live_usage::{self, TokenUsageDelta, UsageDeltaNotifier};
+use crate::services::token_stats::report::ReportService;
+use crate::utils::config::read_global_config;
 use crate::utils::config_dir;
 use anyhow::Result;
 use once_cell::sync::OnceCell;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tokio::sync::mpsc;
 use tokio::time::{interval, Duration};
 use tokio_util::sync::CancellationToken;
@@ -35,6 +43,15 @@ impl TokenStatsManager {
                 eprintln!("Failed to initialize token stats database: {}", e);
             }
 
+            // 按全局配置启用请求流水导出
+            if let Some(config) = read_global_config().ok().flatten() {
+                if let Err(e) =
+                    FlightRecorder::global().configure(&config.token_stats_config.flight_recorder)
+                {
+                    tracing::warn!("初始化请求流水导出失败: {}", e);
+                }
+            }
+
             // 创建事件队列
             let (event_sender, event_receiver) = mpsc::unbounded_channel();
 
@@ -114,6 +131,130 @@ impl TokenStatsManager {
                 }
             }
         });
+
+        // 预算检查任务（每分钟）
+        let db_budget = self.db.clone();
+        tokio::spawn(async move {
+            let mut budget_interval = interval(Duration::from_secs(60));
+
+            loop {
+                tokio::select! {
+                    _ = CANCELLATION_TOKEN.cancelled() => {
+                        tracing::info!("预算检查任务已停止");
+                        break;
+                    }
+                    _ = budget_interval.tick() => {
+                        if let Err(e) = Self::check_budgets_with(&db_budget) {
+                            tracing::error!("预算检查失败: {}", e);
+                        }
+                    }
+                }
+            }
+        });
+
+        // 数据库维护任务（每小时检查，超出大小上限或空闲页过多时执行）
+        let db_maintain = self.db.clone();
+        tokio::spawn(async move {
+            let period = Duration::from_secs(3600);
+            let mut maintain_interval =
+                tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+
+            loop {
+                tokio::select! {
+                    _ = CANCELLATION_TOKEN.cancelled() => {
+                        tracing::info!("数据库维护任务已停止");
+                        break;
+                    }
+                    _ = maintain_interval.tick() => {
+                        let max_size = Self::max_db_size_bytes();
+                        match db_maintain.needs_maintenance(max_size) {
+                            Ok(true) => {
+                                if let Err(e) = db_maintain.maintain(max_size) {
+                                    tracing::error!("数据库维护失败: {}", e);
+                                }
+                            }
+                            Ok(false) => {}
+                            Err(e) => tracing::error!("检查数据库维护条件失败: {}", e),
+                        }
+                    }
+                }
+            }
+        });
+
+        // 用量异常检测任务（每分钟）
+        let db_anomaly = self.db.clone();
+        tokio::spawn(async move {
+            let mut anomaly_interval = interval(Duration::from_secs(60));
+
+            loop {
+                tokio::select! {
+                    _ = CANCELLATION_TOKEN.cancelled() => {
+                        tracing::info!("用量异常检测任务已停止");
+                        break;
+                    }
+                    _ = anomaly_interval.tick() => {
+                        let config = read_global_config()
+                            .ok()
+                            .flatten()
+                            .map(|c| c.token_stats_config.anomaly_detection)
+                            .unwrap_or_default();
+                        if let Err(e) = AnomalyDetector::global().check(&db_anomaly, &config) {
+                            tracing::error!("用量异常检测失败: {}", e);
+                        }
+                    }
+                }
+            }
+        });
+
+        // 用量报告任务（每小时检查，本周期未生成时按配置生成）
+        tokio::spawn(async move {
+            let mut report_interval = interval(Duration::from_secs(3600));
+
+            loop {
+                tokio::select! {
+                    _ = CANCELLATION_TOKEN.cancelled() => {
+                        tracing::info!("用量报告任务已停止");
+                        break;
+                    }
+                    _ = report_interval.tick() => {
+                        let config = read_global_config()
+                            .ok()
+                            .flatten()
+                            .map(|c| c.token_stats_config.usage_report)
+                            .unwrap_or_default();
+                        if config.schedule.is_none() {
+                            continue;
+                        }
+                        let result = tokio::task::spawn_blocking(move || {
+                            ReportService::new(&config)?.run_scheduled(&config)
+                        })
+                        .await;
+                        match result {
+                            Ok(Err(e)) => tracing::error!("生成用量报告失败: {}", e),
+                            Err(e) => tracing::error!("用量报告任务失败: {}", e),
+                            Ok(Ok(_)) => {}
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    /// 全局配置中的数据库大小上限（字节）
+    fn max_db_size_bytes() -> Option<u64> {
+        read_global_config()
+            .ok()
+            .flatten()
+            .map(|c| c.token_stats_config)
+            .unwrap_or_default()
+            .max_db_size_mb
+            .map(|mb| u64::from(mb) * 1024 * 1024)
+    }
+
+    /// 按 proxy.json 中的预算配置检查各工具消费
+    fn check_budgets_with(db: &TokenStatsDb) -> Result<Vec<BudgetStatus>> {
+        let store = ProxyConfigManager::new()?.load_proxy_store()?;
+        BudgetTracker::global().check_all(db, &store)
     }
 
     /// 批量写入日志到数据库
@@ -127,6 +268,7 @@ impl TokenStatsManager {
             if let Err(e) = db.insert_log_without_checkpoint(&log) {
                 tracing::error!("插入 Token 日志失败: {}", e);
             }
+            FlightRecorder::global().record(&log);
         }
 
         // 批量写入后执行 checkpoint
@@ -154,6 +296,21 @@ impl TokenStatsManager {
         }
     }
 
+    /// 设置实时用量事件回调（`token-usage-delta`）
+    pub fn set_usage_notifier(notifier: UsageDeltaNotifier) {
+        live_usage::set_notifier(notifier);
+    }
+
+    /// 是否需要发送实时用量事件
+    pub fn has_usage_listener() -> bool {
+        live_usage::has_notifier()
+    }
+
+    /// 发送实时用量事件（不依赖数据库，无需初始化单例）
+    pub fn emit_usage_delta(event: TokenUsageDelta) {
+        live_usage::emit(event);
+    }
+
     /// 查询会话实时统计
     pub fn get_session_stats(&self, tool_type: &str, session_id: &str) -> Result<SessionStats> {
         self.db.get_session_stats(tool_type, session_id)
@@ -164,6 +321,26 @@ impl TokenStatsManager {
         self.db.query_logs(&query)
     }
 
+    /// 按筛选条件导出日志到文件（CSV / JSON Lines）
+    pub fn export_logs(
+        &self,
+        query: &TokenStatsQuery,
+        format: LogExportFormat,
+        path: &Path,
+    ) -> Result<LogExportSummary> {
+        self.db.export_logs(query, format, path)
+    }
+
+    /// 回填会话已有日志的标签，返回更新条数
+    pub fn update_session_tags(
+        &self,
+        tool_type: &str,
+        session_id: &str,
+        tags: &[String],
+    ) -> Result<usize> {
+        self.db.update_session_tags(tool_type, session_id, tags)
+    }
+
     /// 根据配置清理旧数据
     pub fn cleanup_by_config(
         &self,
@@ -173,11 +350,31 @@ impl TokenStatsManager {
         self.db.cleanup_old_logs(retention_days, max_count)
     }
 
+    /// 立即维护数据库（VACUUM / 重建索引 / ANALYZE，并按配置的大小上限删除最旧日志）
+    pub fn maintain_db(&self) -> Result<DbMaintenanceReport> {
+        self.db.maintain(Self::max_db_size_bytes())
+    }
+
+    /// 统计指定模型自某时间（毫秒）以来的累计 Token 数（阶梯计价使用）
+    pub fn model_tokens_since(&self, models: &[String], since: i64) -> Result<i64> {
+        self.db.model_tokens_since(models, since)
+    }
+
+    /// 统计所有工具自某时间（毫秒）以来的请求数与总成本
+    pub fn usage_since(&self, since: i64) -> Result<(i64, f64)> {
+        self.db.usage_since(since)
+    }
+
     /// 获取数据库统计摘要
     pub fn get_stats_summary(&self) -> Result<(i64, Option<i64>, Option<i64>)> {
         self.db.get_stats_summary()
     }
 
+    /// 立即检查所有工具的预算（同时刷新代理拦截使用的缓存状态）
+    pub fn check_budgets(&self) -> Result<Vec<BudgetStatus>> {
+        Self::check_budgets_with(&self.db)
+    }
+
     /// 强制执行 WAL checkpoint
     ///
     /// 将所有 WAL 数据回写到主数据库文件，
@@ -207,33 +404,15 @@ mod tests {
         let manager = TokenStatsManager::get();
 
         // 创建测试日志
-        let log = TokenLog::new(
-            "claude_code".to_string(),
-            chrono::Utc::now().timestamp_millis(),
-            "127.0.0.1".to_string(),
-            "test_write_session".to_string(),
-            "default".to_string(),
-            "claude-3".to_string(),
-            Some("msg_write_test".to_string()),
-            100,
-            50,
-            10,
-            0, // cache_creation_1h_tokens
-            20,
-            0, // reasoning_tokens
-            "success".to_string(),
-            "json".to_string(),
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            None, // reasoning_price
-            0.0,
-            None,
-        );
+        let log = TokenLog::test_builder()
+            .tool("claude_code")
+            .timestamp(chrono::Utc::now().timestamp_millis())
+            .session("test_write_session")
+            .model("claude-3")
+            .message_id("msg_write_test")
+            .tokens(100, 50)
+            .cache_tokens(10, 20)
+            .build();
 
         // 写入日志
         manager.write_log(log);
@@ -247,33 +426,15 @@ mod tests {
         let manager = TokenStatsManager::get();
 
         // 插入测试数据
-        let log = TokenLog::new(
-            "claude_code".to_string(),
-            chrono::Utc::now().timestamp_millis(),
-            "127.0.0.1".to_string(),
-            "test_query_session".to_string(),
-            "default".to_string(),
-            "claude-3".to_string(),
-            Some("msg_query_test".to_string()),
-            100,
-            50,
-            10,
-            0, // cache_creation_1h_tokens
-            20,
-            0, // reasoning_tokens
-            "success".to_string(),
-            "json".to_string(),
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            None, // reasoning_price
-            0.0,
-            None,
-        );
+        let log = TokenLog::test_builder()
+            .tool("claude_code")
+            .timestamp(chrono::Utc::now().timestamp_millis())
+            .session("test_query_session")
+            .model("claude-3")
+            .message_id("msg_query_test")
+            .tokens(100, 50)
+            .cache_tokens(10, 20)
+            .build();
         manager.db.insert_log(&log).unwrap();
 
         // 查询日志
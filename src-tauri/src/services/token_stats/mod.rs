@@ -3,17 +3,62 @@
 //! 提供透明代理的Token数据统计和请求记录功能。
 
 pub mod analytics;
+pub mod anomaly;
+pub mod budget;
 pub mod db;
+pub mod epochs;
+pub mod export;
+pub mod flight_recorder;
+pub mod live_usage;
 pub mod logger;
 pub mod manager;
 pub mod processor;
+pub mod productivity;
+pub mod redaction_events;
+pub mod report;
+pub mod saved_reports;
+pub mod schema;
+pub mod scrubber;
+pub mod sql_console;
+pub mod streaming;
+pub mod template_binding;
 
 #[cfg(test)]
 mod cost_calculation_test;
 
 pub use analytics::{
-    CostGroupBy, CostSummary, CostSummaryQuery, TimeGranularity, TokenStatsAnalytics,
-    TrendDataPoint, TrendQuery,
+    parse_since, CacheEfficiencyPoint, CacheEfficiencyQuery, CacheEfficiencyReport,
+    CacheEfficiencyStat, CostGroupBy, CostSummary, CostSummaryQuery, LatencyGroupBy, LatencyStats,
+    LatencyStatsQuery, ModelBreakdown, ModelBreakdownEntry, ModelBreakdownQuery,
+    ModelCacheEfficiency, ModelUsageStat, MonthlyCostQuery, MonthlyCostReport, MonthlyTemplateCost,
+    TimeGranularity, TokenStatsAnalytics, ToolComparison, ToolComparisonLeaders,
+    ToolComparisonQuery, ToolComparisonStat, TrendDataPoint, TrendQuery,
 };
-pub use db::TokenStatsDb;
+pub use anomaly::{AnomalyDetector, AnomalyMetric, UsageAnomalyEvent};
+pub use budget::{
+    BudgetLevel, BudgetPeriod, BudgetPeriodStatus, BudgetStatus, BudgetThresholdEvent,
+    BudgetTracker,
+};
+pub use db::{DbMaintenanceReport, TokenStatsDb};
+pub use epochs::{EpochSummary, StatsEpoch, StatsEpochManager};
+pub use export::{LogExportFormat, LogExportSummary};
+pub use flight_recorder::FlightRecorder;
+pub use live_usage::{TokenUsageDelta, UsageDeltaNotifier};
 pub use manager::{shutdown_token_stats_manager, TokenStatsManager};
+pub use productivity::{
+    GitChangeStats, ProductivityAnalytics, ProductivityQuery, ProductivityReport,
+};
+pub use redaction_events::{
+    RedactionEventLog, RedactionHit, RedactionRuleStat, RedactionStats, RedactionStatsQuery,
+};
+pub use report::{
+    CacheSavings, GeneratedUsageReport, ReportService, UsageReport, UsageReportEvent,
+    UsageReportRange, UsageReportTotals,
+};
+pub use saved_reports::{
+    ReportDefinition, ReportImportSummary, ReportOutput, ReportSchedule, SavedReport,
+    SavedReportManager,
+};
+pub use scrubber::{ScrubOptions, StatsScrubber};
+pub use sql_console::{SqlConsole, SqlConsoleQuery, SqlConsoleResult, SqlHistoryEntry};
+pub use streaming::StreamingTokenAccumulator;
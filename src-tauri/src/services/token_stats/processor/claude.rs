@@ -1,6 +1,6 @@
 //! Claude Code 工具的 Token 处理器
 
-use super::{TokenInfo, ToolProcessor};
+use super::{parse_sse_chunk, request_model, TokenInfo, ToolProcessor};
 use anyhow::{Context, Result};
 use serde_json::Value;
 
@@ -18,168 +18,18 @@ impl ToolProcessor for ClaudeProcessor {
         sse_chunks: Vec<String>,
     ) -> Result<TokenInfo> {
         // 1. 从请求体提取 model
-        let request_json: Value =
-            serde_json::from_slice(request_body).context("Failed to parse request body")?;
-        let model = request_json
-            .get("model")
-            .and_then(|v| v.as_str())
-            .context("Missing 'model' field in request body")?
-            .to_string();
-
-        // 2. 解析 SSE 事件，收集 message_start 和 message_delta
-        let mut message_id: Option<String> = None;
-        let mut input_tokens = 0i64;
-        let mut output_tokens = 0i64;
-        let mut cache_creation_tokens = 0i64;
-        let mut cache_creation_1h_tokens = 0i64;
-        let mut cache_read_tokens = 0i64;
+        let model = request_model(request_body)?;
 
+        // 2. 逐个事件输入状态机，收集 message_start 和 message_delta
+        let mut state = ClaudeSseState::default();
         for chunk in sse_chunks {
-            let data_line = chunk.trim();
-
-            // 跳过空行
-            if data_line.is_empty() {
-                continue;
-            }
-
-            // 去掉 "data: " 前缀
-            let json_str = if let Some(stripped) = data_line.strip_prefix("data: ") {
-                stripped
-            } else {
-                data_line
-            };
-
-            // 跳过 [DONE] 标记
-            if json_str.trim() == "[DONE]" {
-                continue;
-            }
-
-            let json: Value = match serde_json::from_str(json_str) {
-                Ok(j) => j,
-                Err(e) => {
-                    tracing::warn!("Failed to parse SSE chunk: {}", e);
-                    continue;
-                }
-            };
-
-            let event_type = json.get("type").and_then(|v| v.as_str()).unwrap_or("");
-
-            match event_type {
-                "message_start" => {
-                    if let Some(message) = json.get("message") {
-                        // 提取 message_id
-                        if let Some(id) = message.get("id").and_then(|v| v.as_str()) {
-                            message_id = Some(id.to_string());
-                        }
-
-                        // 提取 usage
-                        if let Some(usage) = message.get("usage") {
-                            input_tokens = usage
-                                .get("input_tokens")
-                                .and_then(|v| v.as_i64())
-                                .unwrap_or(0);
-
-                            output_tokens = usage
-                                .get("output_tokens")
-                                .and_then(|v| v.as_i64())
-                                .unwrap_or(0);
-
-                            // 提取缓存创建 token：优先读取扁平字段，回退到嵌套对象
-                            if let Some(flat_val) = usage
-                                .get("cache_creation_input_tokens")
-                                .and_then(|v| v.as_i64())
-                            {
-                                // 扁平字段：无法区分 5m/1h，全部视为 5m
-                                cache_creation_tokens = flat_val;
-                                cache_creation_1h_tokens = 0;
-                            } else if let Some(cache_obj) = usage.get("cache_creation") {
-                                let ephemeral_5m = cache_obj
-                                    .get("ephemeral_5m_input_tokens")
-                                    .and_then(|v| v.as_i64())
-                                    .unwrap_or(0);
-                                let ephemeral_1h = cache_obj
-                                    .get("ephemeral_1h_input_tokens")
-                                    .and_then(|v| v.as_i64())
-                                    .unwrap_or(0);
-                                cache_creation_tokens = ephemeral_5m + ephemeral_1h;
-                                cache_creation_1h_tokens = ephemeral_1h;
-                            }
-
-                            cache_read_tokens = usage
-                                .get("cache_read_input_tokens")
-                                .and_then(|v| v.as_i64())
-                                .unwrap_or(0);
-
-                            tracing::debug!(
-                                model = %model,
-                                message_id = ?message_id,
-                                input_tokens = input_tokens,
-                                cache_creation_1h_tokens = cache_creation_1h_tokens,
-                                "Claude message_start 提取成功"
-                            );
-                        }
-                    }
-                }
-                "message_delta" => {
-                    // message_delta 包含最终的 usage 统计（累加值）
-                    if let Some(usage) = json.get("usage") {
-                        // 更新 output_tokens 和缓存统计（这些是最终值）
-                        output_tokens = usage
-                            .get("output_tokens")
-                            .and_then(|v| v.as_i64())
-                            .unwrap_or(output_tokens);
-
-                        if let Some(flat_val) = usage
-                            .get("cache_creation_input_tokens")
-                            .and_then(|v| v.as_i64())
-                        {
-                            cache_creation_tokens = flat_val;
-                            // 扁平字段无法区分 5m/1h，保持之前的 1h 值
-                        } else if let Some(cache_obj) = usage.get("cache_creation") {
-                            let ephemeral_5m = cache_obj
-                                .get("ephemeral_5m_input_tokens")
-                                .and_then(|v| v.as_i64())
-                                .unwrap_or(0);
-                            let ephemeral_1h = cache_obj
-                                .get("ephemeral_1h_input_tokens")
-                                .and_then(|v| v.as_i64())
-                                .unwrap_or(0);
-                            cache_creation_tokens = ephemeral_5m + ephemeral_1h;
-                            cache_creation_1h_tokens = ephemeral_1h;
-                        }
-
-                        cache_read_tokens = usage
-                            .get("cache_read_input_tokens")
-                            .and_then(|v| v.as_i64())
-                            .unwrap_or(cache_read_tokens);
-
-                        tracing::debug!(
-                            output_tokens = output_tokens,
-                            cache_creation_tokens = cache_creation_tokens,
-                            cache_creation_1h_tokens = cache_creation_1h_tokens,
-                            cache_read_tokens = cache_read_tokens,
-                            "Claude message_delta 提取成功"
-                        );
-                    }
-                }
-                _ => {}
+            if let Some(json) = parse_sse_chunk(&chunk) {
+                state.apply(&json);
             }
         }
 
-        // 3. 验证必需字段
-        let message_id = message_id.context("Missing message_id in SSE stream")?;
-
-        // 4. 构建 TokenInfo
-        Ok(TokenInfo::new(
-            model,
-            message_id,
-            input_tokens,
-            output_tokens,
-            cache_creation_tokens,
-            cache_creation_1h_tokens,
-            cache_read_tokens,
-            0, // Claude 不使用 reasoning tokens
-        ))
+        // 3. 验证必需字段并构建 TokenInfo
+        state.finish(model)
     }
 
     fn process_json_response(&self, request_body: &[u8], json: &Value) -> Result<TokenInfo> {
@@ -230,15 +80,7 @@ impl ToolProcessor for ClaudeProcessor {
             // 扁平字段：无法区分 5m/1h，全部视为 5m
             (flat_val, 0)
         } else if let Some(cache_obj) = usage.get("cache_creation") {
-            let ephemeral_5m = cache_obj
-                .get("ephemeral_5m_input_tokens")
-                .and_then(|v| v.as_i64())
-                .unwrap_or(0);
-            let ephemeral_1h = cache_obj
-                .get("ephemeral_1h_input_tokens")
-                .and_then(|v| v.as_i64())
-                .unwrap_or(0);
-            (ephemeral_5m + ephemeral_1h, ephemeral_1h)
+            parse_nested_cache_creation(cache_obj)
         } else {
             (0, 0)
         };
@@ -262,6 +104,159 @@ impl ToolProcessor for ClaudeProcessor {
     }
 }
 
+/// Claude SSE 增量提取状态
+///
+/// 按事件顺序输入，只保留 message_start / message_delta 中的 usage
+#[derive(Debug, Clone, Default)]
+pub struct ClaudeSseState {
+    message_id: Option<String>,
+    input_tokens: i64,
+    output_tokens: i64,
+    cache_creation_tokens: i64,
+    cache_creation_1h_tokens: i64,
+    cache_read_tokens: i64,
+}
+
+impl ClaudeSseState {
+    /// 输入一个已解析的 SSE 事件
+    pub fn apply(&mut self, json: &Value) {
+        let event_type = json.get("type").and_then(|v| v.as_str()).unwrap_or("");
+
+        match event_type {
+            "message_start" => {
+                let Some(message) = json.get("message") else {
+                    return;
+                };
+                // 提取 message_id
+                if let Some(id) = message.get("id").and_then(|v| v.as_str()) {
+                    self.message_id = Some(id.to_string());
+                }
+
+                // 提取 usage
+                if let Some(usage) = message.get("usage") {
+                    self.input_tokens = usage
+                        .get("input_tokens")
+                        .and_then(|v| v.as_i64())
+                        .unwrap_or(0);
+
+                    self.output_tokens = usage
+                        .get("output_tokens")
+                        .and_then(|v| v.as_i64())
+                        .unwrap_or(0);
+
+                    // 提取缓存创建 token：优先读取扁平字段，回退到嵌套对象
+                    if let Some(flat_val) = usage
+                        .get("cache_creation_input_tokens")
+                        .and_then(|v| v.as_i64())
+                    {
+                        // 扁平字段：无法区分 5m/1h，全部视为 5m
+                        self.cache_creation_tokens = flat_val;
+                        self.cache_creation_1h_tokens = 0;
+                    } else if let Some(cache_obj) = usage.get("cache_creation") {
+                        let (total, ephemeral_1h) = parse_nested_cache_creation(cache_obj);
+                        self.cache_creation_tokens = total;
+                        self.cache_creation_1h_tokens = ephemeral_1h;
+                    }
+
+                    self.cache_read_tokens = usage
+                        .get("cache_read_input_tokens")
+                        .and_then(|v| v.as_i64())
+                        .unwrap_or(0);
+
+                    tracing::debug!(
+                        message_id = ?self.message_id,
+                        input_tokens = self.input_tokens,
+                        cache_creation_1h_tokens = self.cache_creation_1h_tokens,
+                        "Claude message_start 提取成功"
+                    );
+                }
+            }
+            "message_delta" => {
+                // message_delta 包含最终的 usage 统计（累加值）
+                let Some(usage) = json.get("usage") else {
+                    return;
+                };
+                // 部分上游（含协议转换后的流）在 message_delta 才给出 input_tokens
+                if let Some(input) = usage
+                    .get("input_tokens")
+                    .and_then(|v| v.as_i64())
+                    .filter(|v| *v > 0)
+                {
+                    self.input_tokens = input;
+                }
+
+                // 更新 output_tokens 和缓存统计（这些是最终值）
+                self.output_tokens = usage
+                    .get("output_tokens")
+                    .and_then(|v| v.as_i64())
+                    .unwrap_or(self.output_tokens);
+
+                if let Some(flat_val) = usage
+                    .get("cache_creation_input_tokens")
+                    .and_then(|v| v.as_i64())
+                {
+                    self.cache_creation_tokens = flat_val;
+                    // 扁平字段无法区分 5m/1h，保持之前的 1h 值
+                } else if let Some(cache_obj) = usage.get("cache_creation") {
+                    let (total, ephemeral_1h) = parse_nested_cache_creation(cache_obj);
+                    self.cache_creation_tokens = total;
+                    self.cache_creation_1h_tokens = ephemeral_1h;
+                }
+
+                self.cache_read_tokens = usage
+                    .get("cache_read_input_tokens")
+                    .and_then(|v| v.as_i64())
+                    .unwrap_or(self.cache_read_tokens);
+
+                tracing::debug!(
+                    output_tokens = self.output_tokens,
+                    cache_creation_tokens = self.cache_creation_tokens,
+                    cache_creation_1h_tokens = self.cache_creation_1h_tokens,
+                    cache_read_tokens = self.cache_read_tokens,
+                    "Claude message_delta 提取成功"
+                );
+            }
+            _ => {}
+        }
+    }
+
+    /// 用流式内容估算值抬高输出 token（流未结束时 usage 尚未给出最终值）
+    pub fn raise_output_tokens(&mut self, estimate: i64) {
+        self.output_tokens = self.output_tokens.max(estimate);
+    }
+
+    /// 结束输入，生成 TokenInfo（缺少 message_id 时报错）
+    pub fn finish(self, model: String) -> Result<TokenInfo> {
+        let message_id = self
+            .message_id
+            .context("Missing message_id in SSE stream")?;
+
+        Ok(TokenInfo::new(
+            model,
+            message_id,
+            self.input_tokens,
+            self.output_tokens,
+            self.cache_creation_tokens,
+            self.cache_creation_1h_tokens,
+            self.cache_read_tokens,
+            0, // Claude 不使用 reasoning tokens
+        ))
+    }
+}
+
+/// 嵌套缓存创建对象，返回 (5m + 1h 总量, 1h 部分)
+fn parse_nested_cache_creation(cache_obj: &Value) -> (i64, i64) {
+    let ephemeral_5m = cache_obj
+        .get("ephemeral_5m_input_tokens")
+        .and_then(|v| v.as_i64())
+        .unwrap_or(0);
+    let ephemeral_1h = cache_obj
+        .get("ephemeral_1h_input_tokens")
+        .and_then(|v| v.as_i64())
+        .unwrap_or(0);
+    (ephemeral_5m + ephemeral_1h, ephemeral_1h)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,6 +1,6 @@
 //! Codex 工具的 Token 处理器
 
-use super::{TokenInfo, ToolProcessor};
+use super::{parse_sse_chunk, request_model, TokenInfo, ToolProcessor};
 use anyhow::{Context, Result};
 use serde_json::Value;
 
@@ -18,138 +18,18 @@ impl ToolProcessor for CodexProcessor {
         sse_chunks: Vec<String>,
     ) -> Result<TokenInfo> {
         // 1. 从请求体提取 model
-        let request_json: Value =
-            serde_json::from_slice(request_body).context("Failed to parse request body")?;
-        let model = request_json
-            .get("model")
-            .and_then(|v| v.as_str())
-            .context("Missing 'model' field in request body")?
-            .to_string();
-
-        // 2. 解析 SSE 事件，收集 response.created 和 response.completed
-        let mut message_id: Option<String> = None;
-        let mut input_tokens = 0i64;
-        let mut output_tokens = 0i64;
-        let mut cache_read_tokens = 0i64;
-        let mut reasoning_tokens = 0i64;
+        let model = request_model(request_body)?;
 
+        // 2. 逐个事件输入状态机，收集 response.created 和 response.completed
+        let mut state = CodexSseState::default();
         for chunk in sse_chunks {
-            let data_line = chunk.trim();
-
-            // 跳过空行
-            if data_line.is_empty() {
-                continue;
-            }
-
-            // 去掉 "data: " 前缀
-            let json_str = if let Some(stripped) = data_line.strip_prefix("data: ") {
-                stripped
-            } else {
-                data_line
-            };
-
-            // 跳过 [DONE] 标记
-            if json_str.trim() == "[DONE]" {
-                continue;
-            }
-
-            let json: Value = match serde_json::from_str(json_str) {
-                Ok(j) => j,
-                Err(e) => {
-                    tracing::warn!("Failed to parse SSE chunk: {}", e);
-                    continue;
-                }
-            };
-
-            let event_type = json.get("type").and_then(|v| v.as_str()).unwrap_or("");
-
-            match event_type {
-                "response.created" => {
-                    // 提取 response_id
-                    if let Some(response) = json.get("response") {
-                        if let Some(id) = response.get("id").and_then(|v| v.as_str()) {
-                            message_id = Some(id.to_string());
-                            tracing::debug!(response_id = %id, "Codex response.created");
-                        }
-                    }
-                }
-                "response.completed" => {
-                    // 提取完整的 usage 统计
-                    if let Some(response) = json.get("response") {
-                        // 更新 response_id（以防 created 事件缺失）
-                        if message_id.is_none() {
-                            if let Some(id) = response.get("id").and_then(|v| v.as_str()) {
-                                message_id = Some(id.to_string());
-                            }
-                        }
-
-                        if let Some(usage) = response.get("usage") {
-                            // Codex 的 input_tokens 包括缓存的 token
-                            // 需要减去 cached_tokens 才是真正的新输入
-                            let total_input_tokens = usage
-                                .get("input_tokens")
-                                .and_then(|v| v.as_i64())
-                                .unwrap_or(0);
-
-                            output_tokens = usage
-                                .get("output_tokens")
-                                .and_then(|v| v.as_i64())
-                                .unwrap_or(0);
-
-                            // 提取 cached_tokens（缓存读取）
-                            cache_read_tokens = usage
-                                .get("input_tokens_details")
-                                .and_then(|d| d.get("cached_tokens"))
-                                .and_then(|v| v.as_i64())
-                                .unwrap_or(0);
-
-                            // 计算实际新输入 = 总输入 - 缓存读取
-                            // 这样才能避免重复计费
-                            input_tokens = total_input_tokens - cache_read_tokens;
-
-                            // 提取 reasoning_tokens
-                            reasoning_tokens = usage
-                                .get("output_tokens_details")
-                                .and_then(|d| d.get("reasoning_tokens"))
-                                .and_then(|v| v.as_i64())
-                                .unwrap_or(0);
-
-                            if reasoning_tokens > 0 {
-                                tracing::info!(
-                                    reasoning_tokens = reasoning_tokens,
-                                    "Codex 响应包含 reasoning tokens（暂不计费）"
-                                );
-                            }
-
-                            tracing::debug!(
-                                message_id = ?message_id,
-                                total_input = total_input_tokens,
-                                cached = cache_read_tokens,
-                                new_input = input_tokens,
-                                output_tokens = output_tokens,
-                                "Codex response.completed 提取成功（input = total - cached）"
-                            );
-                        }
-                    }
-                }
-                _ => {}
+            if let Some(json) = parse_sse_chunk(&chunk) {
+                state.apply(&json);
             }
         }
 
-        // 3. 验证必需字段
-        let message_id = message_id.context("Missing response_id in SSE stream")?;
-
-        // 4. 构建 TokenInfo
-        Ok(TokenInfo::new(
-            model,
-            message_id,
-            input_tokens,
-            output_tokens,
-            0, // Codex 不报告 cache_creation_tokens
-            0, // Codex 无 1h 缓存概念
-            cache_read_tokens,
-            reasoning_tokens,
-        ))
+        // 3. 验证必需字段并构建 TokenInfo
+        state.finish(model)
     }
 
     fn process_json_response(&self, request_body: &[u8], json: &Value) -> Result<TokenInfo> {
@@ -182,34 +62,8 @@ impl ToolProcessor for CodexProcessor {
             .get("usage")
             .context("Missing 'usage' field in response")?;
 
-        // Codex 的 input_tokens 包括缓存的 token
-        // 需要减去 cached_tokens 才是真正的新输入
-        let total_input_tokens = usage
-            .get("input_tokens")
-            .and_then(|v| v.as_i64())
-            .unwrap_or(0);
-
-        let output_tokens = usage
-            .get("output_tokens")
-            .and_then(|v| v.as_i64())
-            .unwrap_or(0);
-
-        // 提取 cached_tokens（缓存读取）
-        let cache_read_tokens = usage
-            .get("input_tokens_details")
-            .and_then(|d| d.get("cached_tokens"))
-            .and_then(|v| v.as_i64())
-            .unwrap_or(0);
-
-        // 计算实际新输入 = 总输入 - 缓存读取
-        let input_tokens = total_input_tokens - cache_read_tokens;
-
-        // 提取 reasoning_tokens
-        let reasoning_tokens = usage
-            .get("output_tokens_details")
-            .and_then(|d| d.get("reasoning_tokens"))
-            .and_then(|v| v.as_i64())
-            .unwrap_or(0);
+        let (_, input_tokens, output_tokens) = parse_io(usage);
+        let (cache_read_tokens, reasoning_tokens) = parse_details(usage);
 
         // 4. 构建 TokenInfo
         Ok(TokenInfo::new(
@@ -225,6 +79,138 @@ impl ToolProcessor for CodexProcessor {
     }
 }
 
+/// Codex SSE 增量提取状态
+///
+/// 按事件顺序输入，只保留 response.created / response.completed 中的 ID 与 usage
+#[derive(Debug, Clone, Default)]
+pub struct CodexSseState {
+    message_id: Option<String>,
+    input_tokens: i64,
+    output_tokens: i64,
+    cache_read_tokens: i64,
+    reasoning_tokens: i64,
+}
+
+impl CodexSseState {
+    /// 输入一个已解析的 SSE 事件
+    pub fn apply(&mut self, json: &Value) {
+        let event_type = json.get("type").and_then(|v| v.as_str()).unwrap_or("");
+
+        match event_type {
+            "response.created" => {
+                // 提取 response_id
+                if let Some(id) = json
+                    .get("response")
+                    .and_then(|r| r.get("id"))
+                    .and_then(|v| v.as_str())
+                {
+                    self.message_id = Some(id.to_string());
+                    tracing::debug!(response_id = %id, "Codex response.created");
+                }
+            }
+            "response.completed" => {
+                // 提取完整的 usage 统计
+                let Some(response) = json.get("response") else {
+                    return;
+                };
+                // 更新 response_id（以防 created 事件缺失）
+                if self.message_id.is_none() {
+                    if let Some(id) = response.get("id").and_then(|v| v.as_str()) {
+                        self.message_id = Some(id.to_string());
+                    }
+                }
+
+                if let Some(usage) = response.get("usage") {
+                    let (total_input_tokens, new_input, output) = parse_io(usage);
+                    self.input_tokens = new_input;
+                    self.output_tokens = output;
+                    (self.cache_read_tokens, self.reasoning_tokens) = parse_details(usage);
+
+                    if self.reasoning_tokens > 0 {
+                        tracing::info!(
+                            reasoning_tokens = self.reasoning_tokens,
+                            "Codex 响应包含 reasoning tokens（暂不计费）"
+                        );
+                    }
+
+                    tracing::debug!(
+                        message_id = ?self.message_id,
+                        total_input = total_input_tokens,
+                        cached = self.cache_read_tokens,
+                        new_input = self.input_tokens,
+                        output_tokens = self.output_tokens,
+                        "Codex response.completed 提取成功（input = total - cached）"
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// 用流式内容估算值抬高输出 token（response.completed 之前没有 usage）
+    pub fn raise_output_tokens(&mut self, estimate: i64) {
+        self.output_tokens = self.output_tokens.max(estimate);
+    }
+
+    /// 结束输入，生成 TokenInfo（缺少 response_id 时报错）
+    pub fn finish(self, model: String) -> Result<TokenInfo> {
+        let message_id = self
+            .message_id
+            .context("Missing response_id in SSE stream")?;
+
+        Ok(TokenInfo::new(
+            model,
+            message_id,
+            self.input_tokens,
+            self.output_tokens,
+            0, // Codex 不报告 cache_creation_tokens
+            0, // Codex 无 1h 缓存概念
+            self.cache_read_tokens,
+            self.reasoning_tokens,
+        ))
+    }
+}
+
+/// 提取缓存读取与推理 token
+///
+/// 缓存读取优先取 `input_tokens_details.cached_tokens`，
+/// 部分中转服务会把它平铺为 `usage.cached_tokens`。
+fn parse_details(usage: &Value) -> (i64, i64) {
+    let cache_read_tokens = usage
+        .get("input_tokens_details")
+        .and_then(|d| d.get("cached_tokens"))
+        .or_else(|| usage.get("cached_tokens"))
+        .and_then(|v| v.as_i64())
+        .unwrap_or(0);
+    let reasoning_tokens = usage
+        .get("output_tokens_details")
+        .and_then(|d| d.get("reasoning_tokens"))
+        .and_then(|v| v.as_i64())
+        .unwrap_or(0);
+    (cache_read_tokens, reasoning_tokens)
+}
+
+/// 提取输入/输出 token，返回 (总输入, 新输入, 输出)
+///
+/// Codex 的 input_tokens 包括缓存的 token，需要减去 cached_tokens 才是真正的新输入，
+/// 这样才能避免重复计费。
+fn parse_io(usage: &Value) -> (i64, i64, i64) {
+    let total_input_tokens = usage
+        .get("input_tokens")
+        .and_then(|v| v.as_i64())
+        .unwrap_or(0);
+    let output_tokens = usage
+        .get("output_tokens")
+        .and_then(|v| v.as_i64())
+        .unwrap_or(0);
+    let (cache_read_tokens, _) = parse_details(usage);
+    (
+        total_input_tokens,
+        (total_input_tokens - cache_read_tokens).max(0),
+        output_tokens,
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -326,4 +312,25 @@ mod tests {
         assert_eq!(result.cache_read_tokens, 0);
         assert_eq!(result.reasoning_tokens, 0);
     }
+
+    #[test]
+    fn test_process_sse_flat_cached_tokens() {
+        let processor = CodexProcessor;
+        let request_body = r#"{"model":"gpt-5-codex","input":[]}"#;
+        // 部分中转服务把 cached_tokens 平铺在 usage 下，且不发送 response.created
+        let sse_chunks = vec![
+            r#"data: {"type":"response.output_text.delta","delta":"hi"}"#.to_string(),
+            r#"data: {"type":"response.completed","response":{"id":"resp_flat","usage":{"input_tokens":800,"cached_tokens":300,"output_tokens":40}}}"#.to_string(),
+            "data: [DONE]".to_string(),
+        ];
+
+        let result = processor
+            .process_sse_response(request_body.as_bytes(), sse_chunks)
+            .unwrap();
+
+        assert_eq!(result.message_id, "resp_flat");
+        assert_eq!(result.input_tokens, 500);
+        assert_eq!(result.cache_read_tokens, 300);
+        assert_eq!(result.output_tokens, 40);
+    }
 }
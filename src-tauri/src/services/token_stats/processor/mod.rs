@@ -6,11 +6,11 @@ mod claude;
 mod codex;
 mod token_info;
 
-pub use claude::ClaudeProcessor;
-pub use codex::CodexProcessor;
+pub use claude::{ClaudeProcessor, ClaudeSseState};
+pub use codex::{CodexProcessor, CodexSseState};
 pub use token_info::TokenInfo;
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
 use serde_json::Value;
 
 /// 工具处理器 - 负责从原始响应中提取 Token 信息
@@ -57,3 +57,31 @@ pub fn create_processor(tool_id: &str) -> Result<Box<dyn ToolProcessor>> {
         _ => Err(anyhow!("Unsupported tool: {}", tool_id)),
     }
 }
+
+/// 从请求体提取 model（SSE 事件中不一定携带）
+pub(crate) fn request_model(request_body: &[u8]) -> Result<String> {
+    let request_json: Value =
+        serde_json::from_slice(request_body).context("Failed to parse request body")?;
+    Ok(request_json
+        .get("model")
+        .and_then(|v| v.as_str())
+        .context("Missing 'model' field in request body")?
+        .to_string())
+}
+
+/// 解析单个 SSE 数据行（兼容带 `data: ` 前缀的写法，跳过空行与 [DONE]）
+fn parse_sse_chunk(chunk: &str) -> Option<Value> {
+    let data_line = chunk.trim();
+    let json_str = data_line.strip_prefix("data: ").unwrap_or(data_line).trim();
+    if json_str.is_empty() || json_str == "[DONE]" {
+        return None;
+    }
+
+    match serde_json::from_str(json_str) {
+        Ok(json) => Some(json),
+        Err(e) => {
+            tracing::warn!("Failed to parse SSE chunk: {}", e);
+            None
+        }
+    }
+}
@@ -0,0 +1,239 @@
+//! 生产力关联统计
+//!
+//! 读取项目 git 提交的代码行变更，与同一时间窗口内 AI 会话的成本关联，
+//! 得出「每千行代码成本」「每次提交成本」等指标。
+//!
+//! 会话与提交的关联方式：时间窗口 + 可选的会话 ID / 工具过滤，
+//! 以及可选的提交信息匹配（如 `Co-Authored-By` trailer）来只统计 AI 辅助的提交。
+
+use crate::data::DataManager;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// 生产力查询参数
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProductivityQuery {
+    /// git 仓库路径
+    pub repo_path: String,
+    /// 开始时间戳（毫秒）
+    pub start_time: Option<i64>,
+    /// 结束时间戳（毫秒）
+    pub end_time: Option<i64>,
+    /// 工具类型过滤（成本侧）
+    #[serde(default)]
+    pub tool_type: Option<String>,
+    /// 会话 ID 过滤（成本侧，为空时统计时间窗口内全部会话）
+    #[serde(default)]
+    pub session_ids: Vec<String>,
+    /// 提交作者过滤（git --author）
+    #[serde(default)]
+    pub author: Option<String>,
+    /// 提交信息匹配（git --grep，忽略大小写），用于只统计 AI 辅助的提交
+    #[serde(default)]
+    pub commit_grep: Option<String>,
+}
+
+/// git 变更统计
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitChangeStats {
+    /// 非合并提交数
+    pub commits: i64,
+    pub lines_added: i64,
+    pub lines_deleted: i64,
+}
+
+impl GitChangeStats {
+    /// 变更总行数（新增 + 删除）
+    pub fn lines_changed(&self) -> i64 {
+        self.lines_added + self.lines_deleted
+    }
+}
+
+/// 生产力关联报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductivityReport {
+    pub git: GitChangeStats,
+    /// 时间窗口内的总成本（USD）
+    pub total_cost: f64,
+    pub request_count: i64,
+    /// 每千行变更成本（无变更时为 None）
+    pub cost_per_1k_lines: Option<f64>,
+    /// 每次提交成本（无提交时为 None）
+    pub cost_per_commit: Option<f64>,
+}
+
+/// 生产力统计
+pub struct ProductivityAnalytics {
+    db_path: PathBuf,
+}
+
+impl ProductivityAnalytics {
+    pub fn new(db_path: PathBuf) -> Self {
+        Self { db_path }
+    }
+
+    /// 计算生产力关联报告
+    pub fn query(&self, query: &ProductivityQuery) -> Result<ProductivityReport> {
+        let git = read_git_stats(Path::new(&query.repo_path), query)?;
+        let (total_cost, request_count) = self.query_cost(query)?;
+
+        let lines = git.lines_changed();
+        Ok(ProductivityReport {
+            cost_per_1k_lines: (lines > 0).then(|| total_cost / lines as f64 * 1000.0),
+            cost_per_commit: (git.commits > 0).then(|| total_cost / git.commits as f64),
+            git,
+            total_cost,
+            request_count,
+        })
+    }
+
+    /// 查询时间窗口内的成本与请求数
+    fn query_cost(&self, query: &ProductivityQuery) -> Result<(f64, i64)> {
+        let mut where_clauses: Vec<String> = Vec::new();
+        let mut params: Vec<String> = Vec::new();
+
+        if let Some(start) = query.start_time {
+            where_clauses.push("timestamp >= ?".to_string());
+            params.push(start.to_string());
+        }
+        if let Some(end) = query.end_time {
+            where_clauses.push("timestamp <= ?".to_string());
+            params.push(end.to_string());
+        }
+        if let Some(ref tool_type) = query.tool_type {
+            where_clauses.push("tool_type = ?".to_string());
+            params.push(tool_type.clone());
+        }
+        if !query.session_ids.is_empty() {
+            let placeholders = vec!["?"; query.session_ids.len()].join(", ");
+            where_clauses.push(format!("session_id IN ({})", placeholders));
+            params.extend(query.session_ids.iter().cloned());
+        }
+
+        let where_clause = if where_clauses.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", where_clauses.join(" AND "))
+        };
+        let sql = format!(
+            "SELECT COALESCE(SUM(total_cost), 0.0), COUNT(*) FROM token_logs {}",
+            where_clause
+        );
+
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+        let param_refs: Vec<&str> = params.iter().map(|s| s.as_str()).collect();
+        let rows = manager.query(&sql, &param_refs)?;
+
+        let row = rows.first().context("成本查询无结果")?;
+        let total_cost = row.values.first().and_then(|v| v.as_f64()).unwrap_or(0.0);
+        let request_count = row.values.get(1).and_then(|v| v.as_i64()).unwrap_or(0);
+        Ok((total_cost, request_count))
+    }
+}
+
+/// 读取仓库在时间窗口内的 git 变更统计（不含合并提交）
+pub fn read_git_stats(repo: &Path, query: &ProductivityQuery) -> Result<GitChangeStats> {
+    if !repo.join(".git").exists() {
+        bail!("不是 git 仓库: {}", repo.display());
+    }
+
+    let mut cmd = Command::new("git");
+    cmd.arg("-C")
+        .arg(repo)
+        .args(["log", "--no-merges", "--numstat", "--format=commit %H"]);
+    if let Some(start) = query.start_time {
+        cmd.arg(format!("--since=@{}", start / 1000));
+    }
+    if let Some(end) = query.end_time {
+        cmd.arg(format!("--until=@{}", end / 1000));
+    }
+    if let Some(ref author) = query.author {
+        cmd.arg(format!("--author={}", author));
+    }
+    if let Some(ref pattern) = query.commit_grep {
+        cmd.args(["-i", &format!("--grep={}", pattern)]);
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    }
+
+    let output = cmd.output().context("执行 git 失败，请确认已安装 git")?;
+    if !output.status.success() {
+        bail!(
+            "git log 执行失败: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(parse_numstat_log(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// 解析 `git log --numstat --format="commit %H"` 输出
+///
+/// 二进制文件（`-\t-\tpath`）不计入行数
+pub fn parse_numstat_log(output: &str) -> GitChangeStats {
+    let mut stats = GitChangeStats::default();
+
+    for line in output.lines() {
+        if line.starts_with("commit ") {
+            stats.commits += 1;
+            continue;
+        }
+
+        let mut parts = line.splitn(3, '\t');
+        if let (Some(added), Some(deleted), Some(_path)) =
+            (parts.next(), parts.next(), parts.next())
+        {
+            stats.lines_added += added.parse::<i64>().unwrap_or(0);
+            stats.lines_deleted += deleted.parse::<i64>().unwrap_or(0);
+        }
+    }
+
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_numstat_log() {
+        let output = "commit aaaaaaaa\n\
+\n\
+10\t2\tsrc/main.rs\n\
+-\t-\tassets/logo.png\n\
+commit bbbbbbbb\n\
+\n\
+5\t0\tREADME.md\n";
+
+        let stats = parse_numstat_log(output);
+        assert_eq!(
+            stats,
+            GitChangeStats {
+                commits: 2,
+                lines_added: 15,
+                lines_deleted: 2,
+            }
+        );
+        assert_eq!(stats.lines_changed(), 17);
+    }
+
+    #[test]
+    fn test_parse_empty_log() {
+        assert_eq!(parse_numstat_log(""), GitChangeStats::default());
+    }
+
+    #[test]
+    fn test_non_git_directory_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let result = read_git_stats(dir.path(), &ProductivityQuery::default());
+        assert!(result.is_err());
+    }
+}
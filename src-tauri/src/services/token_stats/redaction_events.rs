@@ -0,0 +1,271 @@
+//! 请求内容脱敏命中统计
+//!
+//! 透明代理每次命中脱敏规则时，按规则写入一行 `redaction_events`：
+//! - 同一请求的多条规则共享 `request_id`，便于按请求计数
+//! - 只记录规则名与命中次数，不保存命中的原文
+//!
+//! 时间戳单位为毫秒。
+
+use crate::data::DataManager;
+use crate::utils::config_dir;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// 单条规则的命中次数
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RedactionHit {
+    pub rule: String,
+    pub count: u32,
+}
+
+/// 命中统计查询条件
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct RedactionStatsQuery {
+    #[serde(default)]
+    pub tool_type: Option<String>,
+    /// 开始时间戳（毫秒，含）
+    #[serde(default)]
+    pub start_time: Option<i64>,
+    /// 结束时间戳（毫秒，不含）
+    #[serde(default)]
+    pub end_time: Option<i64>,
+}
+
+/// 单条规则的命中汇总
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct RedactionRuleStat {
+    pub rule_name: String,
+    /// 命中总次数
+    pub hits: i64,
+    /// 命中该规则的请求数
+    pub requests: i64,
+}
+
+/// 脱敏命中统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "bindings", derive(ts_rs::TS), ts(export))]
+pub struct RedactionStats {
+    /// 命中任一规则的请求数
+    pub total_requests: i64,
+    /// 脱敏后转发的请求数
+    pub masked_requests: i64,
+    /// 被拦截的请求数
+    pub blocked_requests: i64,
+    /// 命中总次数
+    pub total_hits: i64,
+    /// 按规则汇总（命中次数降序）
+    pub by_rule: Vec<RedactionRuleStat>,
+}
+
+/// 脱敏命中记录
+pub struct RedactionEventLog {
+    db_path: PathBuf,
+}
+
+impl RedactionEventLog {
+    pub fn new(db_path: PathBuf) -> Self {
+        Self { db_path }
+    }
+
+    /// 使用默认统计数据库（与 token_logs 同库）
+    pub fn open_default() -> Result<Self> {
+        let dir = config_dir().map_err(|e| anyhow!(e))?;
+        Ok(Self::new(dir.join("token_stats.db")))
+    }
+
+    /// 创建命中记录表
+    pub fn init_table(&self) -> Result<()> {
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+        manager
+            .execute_raw(
+                "CREATE TABLE IF NOT EXISTS redaction_events (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    timestamp INTEGER NOT NULL,
+                    tool_type TEXT NOT NULL,
+                    request_id TEXT NOT NULL,
+                    rule_name TEXT NOT NULL,
+                    hit_count INTEGER NOT NULL,
+                    action TEXT NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS idx_redaction_events_timestamp
+                    ON redaction_events(timestamp)",
+            )
+            .context("Failed to create redaction_events table")?;
+        Ok(())
+    }
+
+    /// 记录一次请求的命中情况（`action` 为 `mask` 或 `block`）
+    pub fn record(&self, tool_type: &str, action: &str, hits: &[RedactionHit]) -> Result<()> {
+        self.record_at(
+            tool_type,
+            action,
+            hits,
+            chrono::Utc::now().timestamp_millis(),
+        )
+    }
+
+    fn record_at(
+        &self,
+        tool_type: &str,
+        action: &str,
+        hits: &[RedactionHit],
+        timestamp: i64,
+    ) -> Result<()> {
+        if hits.is_empty() {
+            return Ok(());
+        }
+        self.init_table()?;
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+
+        let request_id = uuid::Uuid::new_v4().to_string();
+        let timestamp = timestamp.to_string();
+        let params: Vec<Vec<String>> = hits
+            .iter()
+            .map(|hit| {
+                vec![
+                    timestamp.clone(),
+                    tool_type.to_string(),
+                    request_id.clone(),
+                    hit.rule.clone(),
+                    hit.count.to_string(),
+                    action.to_string(),
+                ]
+            })
+            .collect();
+        manager
+            .execute_batch(
+                "INSERT INTO redaction_events
+                    (timestamp, tool_type, request_id, rule_name, hit_count, action)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                &params,
+            )
+            .context("Failed to insert redaction events")?;
+        Ok(())
+    }
+
+    /// 查询命中统计
+    pub fn stats(&self, query: &RedactionStatsQuery) -> Result<RedactionStats> {
+        self.init_table()?;
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+
+        let mut conditions = Vec::new();
+        let mut params: Vec<String> = Vec::new();
+        if let Some(tool_type) = &query.tool_type {
+            params.push(tool_type.clone());
+            conditions.push(format!("tool_type = ?{}", params.len()));
+        }
+        if let Some(start) = query.start_time {
+            params.push(start.to_string());
+            conditions.push(format!("timestamp >= ?{}", params.len()));
+        }
+        if let Some(end) = query.end_time {
+            params.push(end.to_string());
+            conditions.push(format!("timestamp < ?{}", params.len()));
+        }
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+
+        Ok(manager.transaction(|tx| {
+            let mut stats = tx.query_row(
+                &format!(
+                    "SELECT COUNT(DISTINCT request_id),
+                        COUNT(DISTINCT CASE WHEN action = 'mask' THEN request_id END),
+                        COUNT(DISTINCT CASE WHEN action = 'block' THEN request_id END),
+                        COALESCE(SUM(hit_count), 0)
+                    FROM redaction_events {where_clause}"
+                ),
+                rusqlite::params_from_iter(params.iter()),
+                |row| {
+                    Ok(RedactionStats {
+                        total_requests: row.get(0)?,
+                        masked_requests: row.get(1)?,
+                        blocked_requests: row.get(2)?,
+                        total_hits: row.get(3)?,
+                        by_rule: Vec::new(),
+                    })
+                },
+            )?;
+
+            let mut stmt = tx.prepare(&format!(
+                "SELECT rule_name, SUM(hit_count), COUNT(DISTINCT request_id)
+                FROM redaction_events {where_clause}
+                GROUP BY rule_name ORDER BY 2 DESC, rule_name ASC"
+            ))?;
+            stats.by_rule = stmt
+                .query_map(rusqlite::params_from_iter(params.iter()), |row| {
+                    Ok(RedactionRuleStat {
+                        rule_name: row.get(0)?,
+                        hits: row.get(1)?,
+                        requests: row.get(2)?,
+                    })
+                })?
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(crate::data::DataError::Database)?;
+            Ok(stats)
+        })?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn hit(rule: &str, count: u32) -> RedactionHit {
+        RedactionHit {
+            rule: rule.to_string(),
+            count,
+        }
+    }
+
+    #[test]
+    fn test_record_and_aggregate_hits() {
+        let dir = tempdir().unwrap();
+        let log = RedactionEventLog::new(dir.path().join("redaction.db"));
+
+        log.record_at(
+            "claude-code",
+            "mask",
+            &[hit("email", 2), hit("openai_api_key", 1)],
+            1_000,
+        )
+        .unwrap();
+        log.record_at("claude-code", "block", &[hit("email", 1)], 2_000)
+            .unwrap();
+        log.record_at("codex", "mask", &[hit("internal_host", 3)], 3_000)
+            .unwrap();
+        log.record_at("codex", "mask", &[], 4_000).unwrap();
+
+        let all = log.stats(&RedactionStatsQuery::default()).unwrap();
+        assert_eq!(all.total_requests, 3);
+        assert_eq!(all.masked_requests, 2);
+        assert_eq!(all.blocked_requests, 1);
+        assert_eq!(all.total_hits, 7);
+        assert_eq!(all.by_rule[0].rule_name, "email");
+        assert_eq!(all.by_rule[0].hits, 3);
+        assert_eq!(all.by_rule[0].requests, 2);
+
+        let filtered = log
+            .stats(&RedactionStatsQuery {
+                tool_type: Some("claude-code".to_string()),
+                start_time: Some(1_500),
+                end_time: None,
+            })
+            .unwrap();
+        assert_eq!(filtered.total_requests, 1);
+        assert_eq!(filtered.blocked_requests, 1);
+        assert_eq!(filtered.by_rule.len(), 1);
+    }
+}
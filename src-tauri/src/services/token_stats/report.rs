@@ -0,0 +1,695 @@
+//! 用量报告生成
+//!
+//! 汇总一段时间内的用量：总成本与 Token、成本最高的模型和会话、缓存命中节省、
+//! 星期 × 小时的请求热力图，渲染为 Markdown 或 HTML 写入报告目录。
+//!
+//! 按配置的周期自动生成时，每个周期（ISO 周 / 自然月）只生成一次，
+//! 文件名即周期标识（如 `usage-report-2026-W42.md`），生成后通过回调通知。
+
+use super::analytics::{CostGroupBy, CostSummary, CostSummaryQuery, TokenStatsAnalytics};
+use crate::data::DataManager;
+use crate::models::config::{UsageReportConfig, UsageReportFormat, UsageReportPeriod};
+use crate::utils::{config_dir, expand_home};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{Datelike, Local, TimeZone};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+const DAY_MS: i64 = 24 * 3600 * 1000;
+
+/// 报告中列出的模型 / 会话数
+const TOP_N: usize = 10;
+
+/// 热力图行标签（周一开始）
+const WEEKDAYS: [&str; 7] = ["周一", "周二", "周三", "周四", "周五", "周六", "周日"];
+
+static REPORT_NOTIFIER: Lazy<RwLock<Option<UsageReportNotifier>>> = Lazy::new(|| RwLock::new(None));
+
+/// 报告时间范围
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum UsageReportRange {
+    /// 最近 7 天
+    Week,
+    /// 最近 30 天
+    Month,
+    /// 自定义起止时间（毫秒）
+    Custom { start_time: i64, end_time: i64 },
+}
+
+impl From<UsageReportPeriod> for UsageReportRange {
+    fn from(period: UsageReportPeriod) -> Self {
+        match period {
+            UsageReportPeriod::Weekly => UsageReportRange::Week,
+            UsageReportPeriod::Monthly => UsageReportRange::Month,
+        }
+    }
+}
+
+impl UsageReportRange {
+    /// 解析为起止时间（毫秒）
+    pub fn resolve(self, now: i64) -> Result<(i64, i64)> {
+        match self {
+            UsageReportRange::Week => Ok((now - 7 * DAY_MS, now)),
+            UsageReportRange::Month => Ok((now - 30 * DAY_MS, now)),
+            UsageReportRange::Custom {
+                start_time,
+                end_time,
+            } => {
+                if start_time >= end_time {
+                    bail!("报告开始时间必须早于结束时间");
+                }
+                Ok((start_time, end_time))
+            }
+        }
+    }
+
+    /// 报告文件名中的范围标识：周报为 ISO 周，月报为年月，自定义范围为起止日期
+    fn file_key(self, start: i64, end: i64) -> String {
+        let local = |ms: i64| Local.timestamp_millis_opt(ms).single().unwrap_or_default();
+        match self {
+            UsageReportRange::Week => {
+                let week = local(end).iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+            UsageReportRange::Month => local(end).format("%Y-%m").to_string(),
+            UsageReportRange::Custom { .. } => format!(
+                "{}_{}",
+                local(start).format("%Y%m%d"),
+                local(end).format("%Y%m%d")
+            ),
+        }
+    }
+}
+
+/// 总体用量
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageReportTotals {
+    pub request_count: i64,
+    pub failed_requests: i64,
+    pub session_count: i64,
+    /// 总成本（USD）
+    pub total_cost: f64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cache_creation_tokens: i64,
+    pub cache_read_tokens: i64,
+}
+
+/// 缓存命中节省
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CacheSavings {
+    /// 缓存命中率：缓存读取 / (输入 + 缓存写入 + 缓存读取)
+    pub hit_rate: Option<f64>,
+    /// 估算节省（USD）：缓存读取 Token 按同请求输入单价计价与实际缓存读取成本之差
+    pub saved_cost: f64,
+}
+
+/// 用量报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageReport {
+    pub title: String,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub generated_at: i64,
+    pub totals: UsageReportTotals,
+    pub cache: CacheSavings,
+    pub top_models: Vec<CostSummary>,
+    pub top_sessions: Vec<CostSummary>,
+    /// 星期 × 小时（本地时间）请求数，7 行（周一开始）× 24 列
+    pub heatmap: Vec<Vec<i64>>,
+}
+
+/// 已写入磁盘的报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeneratedUsageReport {
+    pub path: String,
+    pub format: UsageReportFormat,
+    pub report: UsageReport,
+}
+
+/// `usage-report-generated` 事件载荷
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageReportEvent {
+    pub path: String,
+    pub title: String,
+    pub total_cost: f64,
+    /// 是否同时发送系统桌面通知
+    pub desktop_notification: bool,
+}
+
+/// 报告生成回调
+pub type UsageReportNotifier = Box<dyn Fn(UsageReportEvent) + Send + Sync + 'static>;
+
+/// 用量报告服务
+pub struct ReportService {
+    db_path: PathBuf,
+    output_dir: PathBuf,
+}
+
+impl ReportService {
+    /// 使用默认统计数据库，输出到配置的目录（未配置时为 `~/.duckcoding/reports`）
+    pub fn new(config: &UsageReportConfig) -> Result<Self> {
+        let dir = config_dir().map_err(|e| anyhow!("获取配置目录失败: {}", e))?;
+        let output_dir = match config.output_dir.as_deref().map(str::trim) {
+            Some(path) if !path.is_empty() => expand_home(path),
+            _ => dir.join("reports"),
+        };
+        Ok(Self::with_paths(dir.join("token_stats.db"), output_dir))
+    }
+
+    /// 使用指定路径创建服务
+    pub fn with_paths(db_path: PathBuf, output_dir: PathBuf) -> Self {
+        Self {
+            db_path,
+            output_dir,
+        }
+    }
+
+    /// 设置报告生成回调（仅自动生成时触发）
+    pub fn set_notifier(notifier: UsageReportNotifier) {
+        *REPORT_NOTIFIER.write().unwrap() = Some(notifier);
+    }
+
+    /// 生成报告并写入磁盘
+    pub fn generate(
+        &self,
+        range: UsageReportRange,
+        format: UsageReportFormat,
+    ) -> Result<GeneratedUsageReport> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let (start, end) = range.resolve(now)?;
+        let path = self.report_path(&range.file_key(start, end), format);
+        self.generate_to(&path, start, end, format)
+    }
+
+    /// 按配置的周期自动生成（本周期已生成时返回 None）
+    pub fn run_scheduled(
+        &self,
+        config: &UsageReportConfig,
+    ) -> Result<Option<GeneratedUsageReport>> {
+        let Some(period) = config.schedule else {
+            return Ok(None);
+        };
+        let range = UsageReportRange::from(period);
+        let now = chrono::Utc::now().timestamp_millis();
+        let (start, end) = range.resolve(now)?;
+        let path = self.report_path(&range.file_key(start, end), config.format);
+        if path.exists() {
+            return Ok(None);
+        }
+
+        let generated = self.generate_to(&path, start, end, config.format)?;
+        tracing::info!(path = %generated.path, "已自动生成用量报告");
+        if let Some(notifier) = REPORT_NOTIFIER.read().unwrap().as_ref() {
+            notifier(UsageReportEvent {
+                path: generated.path.clone(),
+                title: generated.report.title.clone(),
+                total_cost: generated.report.totals.total_cost,
+                desktop_notification: config.desktop_notification,
+            });
+        }
+        Ok(Some(generated))
+    }
+
+    fn report_path(&self, key: &str, format: UsageReportFormat) -> PathBuf {
+        let ext = match format {
+            UsageReportFormat::Markdown => "md",
+            UsageReportFormat::Html => "html",
+        };
+        self.output_dir.join(format!("usage-report-{key}.{ext}"))
+    }
+
+    fn generate_to(
+        &self,
+        path: &Path,
+        start: i64,
+        end: i64,
+        format: UsageReportFormat,
+    ) -> Result<GeneratedUsageReport> {
+        let report = self.build(start, end)?;
+        let content = match format {
+            UsageReportFormat::Markdown => render_markdown(&report),
+            UsageReportFormat::Html => render_html(&report),
+        };
+        std::fs::create_dir_all(&self.output_dir)
+            .with_context(|| format!("创建报告目录失败: {}", self.output_dir.display()))?;
+        std::fs::write(path, content)
+            .with_context(|| format!("写入报告失败: {}", path.display()))?;
+        Ok(GeneratedUsageReport {
+            path: path.to_string_lossy().to_string(),
+            format,
+            report,
+        })
+    }
+
+    /// 汇总时间范围内的用量
+    pub fn build(&self, start: i64, end: i64) -> Result<UsageReport> {
+        let analytics = TokenStatsAnalytics::new(self.db_path.clone());
+        let top = |group_by: CostGroupBy| -> Result<Vec<CostSummary>> {
+            let mut rows = analytics.query_cost_summary(&CostSummaryQuery {
+                start_time: Some(start),
+                end_time: Some(end),
+                group_by,
+                ..Default::default()
+            })?;
+            rows.truncate(TOP_N);
+            Ok(rows)
+        };
+        let top_models = top(CostGroupBy::Model)?;
+        let top_sessions = top(CostGroupBy::Session)?;
+
+        let manager = DataManager::global()
+            .sqlite(&self.db_path)
+            .context("Failed to get SQLite manager")?;
+        let (totals, saved_cost, cells) = manager.transaction(|tx| {
+            let (totals, saved_cost) = tx.query_row(
+                "SELECT
+                    COUNT(*),
+                    COALESCE(SUM(CASE WHEN request_status IN ('failed', 'timeout') THEN 1 ELSE 0 END), 0),
+                    COUNT(DISTINCT session_id),
+                    COALESCE(SUM(total_cost), 0.0),
+                    COALESCE(SUM(input_tokens), 0),
+                    COALESCE(SUM(output_tokens), 0),
+                    COALESCE(SUM(cache_creation_tokens), 0),
+                    COALESCE(SUM(cache_read_tokens), 0),
+                    COALESCE(SUM(CASE WHEN input_tokens > 0 AND cache_read_tokens > 0
+                        THEN cache_read_tokens * COALESCE(input_price, 0.0) / input_tokens
+                            - COALESCE(cache_read_price, 0.0)
+                        ELSE 0 END), 0.0)
+                FROM token_logs
+                WHERE timestamp >= ?1 AND timestamp <= ?2",
+                rusqlite::params![start, end],
+                |row| {
+                    Ok((
+                        UsageReportTotals {
+                            request_count: row.get(0)?,
+                            failed_requests: row.get(1)?,
+                            session_count: row.get(2)?,
+                            total_cost: row.get(3)?,
+                            input_tokens: row.get(4)?,
+                            output_tokens: row.get(5)?,
+                            cache_creation_tokens: row.get(6)?,
+                            cache_read_tokens: row.get(7)?,
+                        },
+                        row.get::<_, f64>(8)?,
+                    ))
+                },
+            )?;
+
+            let mut stmt = tx.prepare(
+                "SELECT
+                    CAST(strftime('%w', timestamp / 1000, 'unixepoch', 'localtime') AS INTEGER),
+                    CAST(strftime('%H', timestamp / 1000, 'unixepoch', 'localtime') AS INTEGER),
+                    COUNT(*)
+                FROM token_logs
+                WHERE timestamp >= ?1 AND timestamp <= ?2
+                GROUP BY 1, 2",
+            )?;
+            let cells = stmt
+                .query_map(rusqlite::params![start, end], |row| {
+                    Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?, row.get(2)?))
+                })?
+                .collect::<std::result::Result<Vec<(i64, i64, i64)>, _>>()
+                .map_err(crate::data::DataError::Database)?;
+            Ok((totals, saved_cost, cells))
+        })?;
+
+        let mut heatmap = vec![vec![0_i64; 24]; 7];
+        for (weekday, hour, count) in cells {
+            // SQLite 的 %w 以周日为 0，报告以周一开始
+            let row = ((weekday + 6) % 7) as usize;
+            if let Some(cell) = heatmap.get_mut(row).and_then(|r| r.get_mut(hour as usize)) {
+                *cell = count;
+            }
+        }
+
+        let cacheable =
+            totals.input_tokens + totals.cache_creation_tokens + totals.cache_read_tokens;
+        let local = |ms: i64| {
+            Local
+                .timestamp_millis_opt(ms)
+                .single()
+                .map(|t| t.format("%Y-%m-%d").to_string())
+                .unwrap_or_default()
+        };
+        Ok(UsageReport {
+            title: format!("用量报告 {} ~ {}", local(start), local(end)),
+            start_time: start,
+            end_time: end,
+            generated_at: chrono::Utc::now().timestamp_millis(),
+            cache: CacheSavings {
+                hit_rate: (cacheable > 0)
+                    .then(|| totals.cache_read_tokens as f64 / cacheable as f64),
+                saved_cost: saved_cost.max(0.0),
+            },
+            totals,
+            top_models,
+            top_sessions,
+            heatmap,
+        })
+    }
+}
+
+fn format_tokens(tokens: i64) -> String {
+    match tokens {
+        t if t >= 1_000_000 => format!("{:.2}M", t as f64 / 1_000_000.0),
+        t if t >= 1_000 => format!("{:.1}K", t as f64 / 1_000.0),
+        t => t.to_string(),
+    }
+}
+
+fn format_rate(rate: Option<f64>) -> String {
+    rate.map(|r| format!("{:.1}%", r * 100.0))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+/// 概览条目（标签, 值），Markdown 与 HTML 共用
+fn overview_rows(report: &UsageReport) -> Vec<(&'static str, String)> {
+    let t = &report.totals;
+    vec![
+        ("总成本", format!("${:.4}", t.total_cost)),
+        ("请求数", t.request_count.to_string()),
+        ("失败请求", t.failed_requests.to_string()),
+        ("会话数", t.session_count.to_string()),
+        ("输入 Token", format_tokens(t.input_tokens)),
+        ("输出 Token", format_tokens(t.output_tokens)),
+        ("缓存写入 Token", format_tokens(t.cache_creation_tokens)),
+        ("缓存读取 Token", format_tokens(t.cache_read_tokens)),
+        ("缓存命中率", format_rate(report.cache.hit_rate)),
+        (
+            "缓存节省（估算）",
+            format!("${:.4}", report.cache.saved_cost),
+        ),
+    ]
+}
+
+fn render_markdown(report: &UsageReport) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# {}\n", report.title);
+
+    let _ = writeln!(out, "## 概览\n\n| 指标 | 数值 |\n| --- | --- |");
+    for (label, value) in overview_rows(report) {
+        let _ = writeln!(out, "| {label} | {value} |");
+    }
+
+    for (heading, rows) in [
+        ("成本最高的模型", &report.top_models),
+        ("成本最高的会话", &report.top_sessions),
+    ] {
+        let _ = writeln!(out, "\n## {heading}\n");
+        if rows.is_empty() {
+            let _ = writeln!(out, "无数据");
+            continue;
+        }
+        let _ = writeln!(
+            out,
+            "| 名称 | 成本 | 请求数 | 输入 Token | 输出 Token |\n| --- | ---: | ---: | ---: | ---: |"
+        );
+        for row in rows.iter() {
+            let _ = writeln!(
+                out,
+                "| {} | ${:.4} | {} | {} | {} |",
+                row.group_name.replace('|', "\\|"),
+                row.total_cost,
+                row.request_count,
+                format_tokens(row.input_tokens),
+                format_tokens(row.output_tokens)
+            );
+        }
+    }
+
+    let _ = writeln!(out, "\n## 请求热力图（星期 × 小时）\n");
+    let hours: Vec<String> = (0..24).map(|h| h.to_string()).collect();
+    let _ = writeln!(out, "| | {} |", hours.join(" | "));
+    let _ = writeln!(out, "| --- |{}", " ---: |".repeat(24));
+    for (label, row) in WEEKDAYS.iter().zip(&report.heatmap) {
+        let cells: Vec<String> = row.iter().map(|c| c.to_string()).collect();
+        let _ = writeln!(out, "| {} | {} |", label, cells.join(" | "));
+    }
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn render_html(report: &UsageReport) -> String {
+    let mut out = String::new();
+    let title = escape_html(&report.title);
+    let _ = writeln!(
+        out,
+        "<!DOCTYPE html>\n<html lang=\"zh-CN\">\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+         <style>body{{font-family:sans-serif;margin:2em}}table{{border-collapse:collapse;margin-bottom:1.5em}}\
+         td,th{{border:1px solid #ddd;padding:4px 8px;text-align:right}}td:first-child,th:first-child{{text-align:left}}</style>\n\
+         </head>\n<body>\n<h1>{title}</h1>"
+    );
+
+    let _ = writeln!(out, "<h2>概览</h2>\n<table>");
+    for (label, value) in overview_rows(report) {
+        let _ = writeln!(
+            out,
+            "<tr><th>{label}</th><td>{}</td></tr>",
+            escape_html(&value)
+        );
+    }
+    let _ = writeln!(out, "</table>");
+
+    for (heading, rows) in [
+        ("成本最高的模型", &report.top_models),
+        ("成本最高的会话", &report.top_sessions),
+    ] {
+        let _ = writeln!(out, "<h2>{heading}</h2>");
+        if rows.is_empty() {
+            let _ = writeln!(out, "<p>无数据</p>");
+            continue;
+        }
+        let _ = writeln!(
+            out,
+            "<table>\n<tr><th>名称</th><th>成本</th><th>请求数</th><th>输入 Token</th><th>输出 Token</th></tr>"
+        );
+        for row in rows.iter() {
+            let _ = writeln!(
+                out,
+                "<tr><td>{}</td><td>${:.4}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape_html(&row.group_name),
+                row.total_cost,
+                row.request_count,
+                format_tokens(row.input_tokens),
+                format_tokens(row.output_tokens)
+            );
+        }
+        let _ = writeln!(out, "</table>");
+    }
+
+    let max = report
+        .heatmap
+        .iter()
+        .flatten()
+        .copied()
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let _ = writeln!(
+        out,
+        "<h2>请求热力图（星期 × 小时）</h2>\n<table>\n<tr><th></th>"
+    );
+    for hour in 0..24 {
+        let _ = write!(out, "<th>{hour}</th>");
+    }
+    let _ = writeln!(out, "</tr>");
+    for (label, row) in WEEKDAYS.iter().zip(&report.heatmap) {
+        let _ = write!(out, "<tr><th>{label}</th>");
+        for count in row {
+            let alpha = *count as f64 / max as f64;
+            let _ = write!(
+                out,
+                "<td style=\"background:rgba(255,140,0,{alpha:.2})\">{count}</td>"
+            );
+        }
+        let _ = writeln!(out, "</tr>");
+    }
+    let _ = writeln!(out, "</table>\n</body>\n</html>");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::token_stats::TokenLog;
+    use crate::services::token_stats::db::TokenStatsDb;
+    use chrono::Timelike;
+    use std::sync::{Arc, Mutex};
+    use tempfile::tempdir;
+
+    #[allow(clippy::too_many_arguments)]
+    fn log_at(
+        session: &str,
+        model: &str,
+        timestamp: i64,
+        input_tokens: i64,
+        cache_read_tokens: i64,
+        input_price: f64,
+        cache_read_price: f64,
+        cost: f64,
+    ) -> TokenLog {
+        TokenLog::test_builder()
+            .timestamp(timestamp)
+            .session(session)
+            .model(model)
+            .tokens(input_tokens, 100)
+            .cache_tokens(0, cache_read_tokens)
+            .input_price(input_price)
+            .cache_read_price(cache_read_price)
+            .cost(cost)
+            .build()
+    }
+
+    fn setup() -> (tempfile::TempDir, ReportService, i64) {
+        let dir = tempdir().unwrap();
+        let db = TokenStatsDb::new(dir.path().join("stats.db"));
+        db.init_table().unwrap();
+
+        let now = chrono::Utc::now().timestamp_millis();
+        // 1000 输入 Token 计 $0.003（$3/M），4000 缓存读取 Token 计 $0.0012（$0.3/M）
+        db.insert_log(&log_at(
+            "s1",
+            "claude-sonnet",
+            now - DAY_MS,
+            1000,
+            4000,
+            0.003,
+            0.0012,
+            0.5,
+        ))
+        .unwrap();
+        db.insert_log(&log_at(
+            "s1",
+            "claude-opus",
+            now - 2 * DAY_MS,
+            1000,
+            0,
+            0.015,
+            0.0,
+            2.0,
+        ))
+        .unwrap();
+        db.insert_log(&log_at(
+            "s2",
+            "claude-sonnet",
+            now - 3 * DAY_MS,
+            500,
+            0,
+            0.0015,
+            0.0,
+            0.25,
+        ))
+        .unwrap();
+        // 超出周报范围
+        db.insert_log(&log_at(
+            "s3",
+            "claude-opus",
+            now - 20 * DAY_MS,
+            1000,
+            0,
+            0.015,
+            0.0,
+            9.0,
+        ))
+        .unwrap();
+
+        let service =
+            ReportService::with_paths(dir.path().join("stats.db"), dir.path().join("reports"));
+        (dir, service, now)
+    }
+
+    #[test]
+    fn test_build_report() {
+        let (_dir, service, now) = setup();
+        let report = service.build(now - 7 * DAY_MS, now).unwrap();
+
+        assert_eq!(report.totals.request_count, 3);
+        assert_eq!(report.totals.session_count, 2);
+        assert!((report.totals.total_cost - 2.75).abs() < 1e-9);
+        assert_eq!(report.top_models[0].group_name, "claude-opus");
+        assert_eq!(report.top_sessions[0].group_name, "s1");
+        // 4000 × $3/M - $0.0012 = $0.0108
+        assert!((report.cache.saved_cost - 0.0108).abs() < 1e-9);
+        assert!((report.cache.hit_rate.unwrap() - 4000.0 / 6500.0).abs() < 1e-9);
+
+        let at = Local.timestamp_millis_opt(now - DAY_MS).unwrap();
+        let row = at.weekday().num_days_from_monday() as usize;
+        assert!(report.heatmap[row][at.hour() as usize] >= 1);
+        assert_eq!(report.heatmap.iter().flatten().sum::<i64>(), 3);
+    }
+
+    #[test]
+    fn test_generate_markdown_and_html() {
+        let (_dir, service, _) = setup();
+        let markdown = service
+            .generate(UsageReportRange::Month, UsageReportFormat::Markdown)
+            .unwrap();
+        assert!(markdown.path.ends_with(".md"));
+        assert_eq!(markdown.report.totals.request_count, 4);
+        let content = std::fs::read_to_string(&markdown.path).unwrap();
+        assert!(content.contains("## 成本最高的模型"));
+        assert!(content.contains("| 总成本 | $11.7500 |"));
+
+        let html = service
+            .generate(
+                UsageReportRange::Custom {
+                    start_time: 0,
+                    end_time: 1,
+                },
+                UsageReportFormat::Html,
+            )
+            .unwrap();
+        let content = std::fs::read_to_string(&html.path).unwrap();
+        assert!(content.starts_with("<!DOCTYPE html>"));
+        assert!(content.contains("<p>无数据</p>"));
+
+        assert!(service
+            .generate(
+                UsageReportRange::Custom {
+                    start_time: 2,
+                    end_time: 1,
+                },
+                UsageReportFormat::Markdown,
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_run_scheduled_once_per_period() {
+        let (_dir, service, _) = setup();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        ReportService::set_notifier(Box::new(move |event| sink.lock().unwrap().push(event)));
+
+        assert!(service
+            .run_scheduled(&UsageReportConfig::default())
+            .unwrap()
+            .is_none());
+
+        let config = UsageReportConfig {
+            schedule: Some(UsageReportPeriod::Weekly),
+            desktop_notification: true,
+            ..Default::default()
+        };
+        let generated = service.run_scheduled(&config).unwrap().unwrap();
+        assert!(generated.path.contains("-W"));
+        assert!(service.run_scheduled(&config).unwrap().is_none());
+
+        let events = events.lock().unwrap();
+        let ours: Vec<&UsageReportEvent> =
+            events.iter().filter(|e| e.path == generated.path).collect();
+        assert_eq!(ours.len(), 1);
+        assert!(ours[0].desktop_notification);
+    }
+}
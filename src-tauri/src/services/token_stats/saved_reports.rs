@@ -0,0 +1,423 @@
+//! 已保存的自定义报表
+//!
+//! 用户可以把常用的分析查询（过滤条件 + 分组 + 粒度，或只读 SQL）保存为命名报表，
+//! 随时重新运行，或标记执行周期交给报表生成器定期产出。
+//! 报表持久化在 `~/.duckcoding/reports.json`，支持导入/导出。
+
+use super::analytics::{
+    CostSummary, CostSummaryQuery, TokenStatsAnalytics, TrendDataPoint, TrendQuery,
+};
+use super::productivity::{ProductivityAnalytics, ProductivityQuery, ProductivityReport};
+use super::sql_console::{SqlConsole, SqlConsoleQuery, SqlConsoleResult};
+use crate::data::DataManager;
+use crate::utils::config::config_dir;
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// 报表存储格式版本
+const REPORTS_STORE_VERSION: u32 = 1;
+
+/// 报表查询定义
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ReportDefinition {
+    /// 趋势分析（过滤条件 + 时间粒度）
+    Trend { query: TrendQuery },
+    /// 成本汇总（过滤条件 + 分组方式）
+    CostSummary { query: CostSummaryQuery },
+    /// 只读 SQL
+    Sql { query: SqlConsoleQuery },
+    /// 生产力关联（git 变更 × 会话成本）
+    Productivity { query: ProductivityQuery },
+}
+
+/// 报表执行周期
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportSchedule {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// 已保存的报表
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedReport {
+    pub id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub definition: ReportDefinition,
+    /// 相对时间范围（最近 N 天）；设置后运行时覆盖查询中的起止时间
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range_days: Option<u32>,
+    /// 执行周期（由报表生成器定期运行）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<ReportSchedule>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// 报表运行结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "data", rename_all = "snake_case")]
+pub enum ReportOutput {
+    Trend(Vec<TrendDataPoint>),
+    CostSummary(Vec<CostSummary>),
+    Sql(SqlConsoleResult),
+    Productivity(ProductivityReport),
+}
+
+/// 导入结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReportImportSummary {
+    /// 新增或覆盖的报表数
+    pub imported: usize,
+    /// 因 ID 冲突跳过的报表数
+    pub skipped: usize,
+}
+
+/// 报表存储
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ReportsStore {
+    version: u32,
+    #[serde(default)]
+    reports: Vec<SavedReport>,
+}
+
+impl Default for ReportsStore {
+    fn default() -> Self {
+        Self {
+            version: REPORTS_STORE_VERSION,
+            reports: Vec::new(),
+        }
+    }
+}
+
+/// 已保存报表管理器
+pub struct SavedReportManager {
+    store_path: PathBuf,
+    db_path: PathBuf,
+}
+
+impl SavedReportManager {
+    /// 使用默认路径创建管理器
+    pub fn new() -> Result<Self> {
+        let dir = config_dir().map_err(|e| anyhow!("获取配置目录失败: {}", e))?;
+        Ok(Self::with_paths(
+            dir.join("reports.json"),
+            dir.join("token_stats.db"),
+        ))
+    }
+
+    /// 使用指定路径创建管理器
+    pub fn with_paths(store_path: PathBuf, db_path: PathBuf) -> Self {
+        Self {
+            store_path,
+            db_path,
+        }
+    }
+
+    /// 列出所有报表
+    pub fn list(&self) -> Result<Vec<SavedReport>> {
+        Ok(self.load_store()?.reports)
+    }
+
+    /// 获取单个报表
+    pub fn get(&self, id: &str) -> Result<SavedReport> {
+        self.load_store()?
+            .reports
+            .into_iter()
+            .find(|r| r.id == id)
+            .ok_or_else(|| anyhow!("报表不存在: {}", id))
+    }
+
+    /// 保存报表（id 为空时新建，否则按 id 覆盖）
+    pub fn save(&self, mut report: SavedReport) -> Result<SavedReport> {
+        report.name = report.name.trim().to_string();
+        if report.name.is_empty() {
+            bail!("报表名称不能为空");
+        }
+        if let ReportDefinition::Sql { ref query } = report.definition {
+            super::sql_console::validate_select(&query.sql)?;
+        }
+
+        let mut store = self.load_store()?;
+        let now = chrono::Utc::now().timestamp();
+        report.updated_at = now;
+
+        if report.id.is_empty() {
+            report.id = uuid::Uuid::new_v4().to_string();
+            report.created_at = now;
+            store.reports.push(report.clone());
+        } else if let Some(existing) = store.reports.iter_mut().find(|r| r.id == report.id) {
+            report.created_at = existing.created_at;
+            *existing = report.clone();
+        } else {
+            report.created_at = now;
+            store.reports.push(report.clone());
+        }
+
+        self.save_store(&store)?;
+        Ok(report)
+    }
+
+    /// 删除报表
+    pub fn delete(&self, id: &str) -> Result<()> {
+        let mut store = self.load_store()?;
+        let before = store.reports.len();
+        store.reports.retain(|r| r.id != id);
+        if store.reports.len() == before {
+            bail!("报表不存在: {}", id);
+        }
+        self.save_store(&store)
+    }
+
+    /// 运行报表
+    pub fn run(&self, id: &str) -> Result<ReportOutput> {
+        let report = self.get(id)?;
+        self.run_report(&report)
+    }
+
+    /// 运行报表定义（不要求已保存）
+    pub fn run_report(&self, report: &SavedReport) -> Result<ReportOutput> {
+        let range = report.range_days.map(|days| {
+            let end = chrono::Utc::now().timestamp_millis();
+            (end - i64::from(days) * 24 * 3600 * 1000, end)
+        });
+
+        match &report.definition {
+            ReportDefinition::Trend { query } => {
+                let mut query = query.clone();
+                if let Some((start, end)) = range {
+                    query.start_time = Some(start);
+                    query.end_time = Some(end);
+                }
+                let analytics = TokenStatsAnalytics::new(self.db_path.clone());
+                Ok(ReportOutput::Trend(analytics.query_trends(&query)?))
+            }
+            ReportDefinition::CostSummary { query } => {
+                let mut query = query.clone();
+                if let Some((start, end)) = range {
+                    query.start_time = Some(start);
+                    query.end_time = Some(end);
+                }
+                let analytics = TokenStatsAnalytics::new(self.db_path.clone());
+                Ok(ReportOutput::CostSummary(
+                    analytics.query_cost_summary(&query)?,
+                ))
+            }
+            // SQL 报表的时间范围由语句自身决定
+            ReportDefinition::Sql { query } => {
+                let history_path = self.store_path.with_file_name("sql_console_history.json");
+                let console = SqlConsole::with_paths(self.db_path.clone(), history_path);
+                Ok(ReportOutput::Sql(console.query(query)?))
+            }
+            ReportDefinition::Productivity { query } => {
+                let mut query = query.clone();
+                if let Some((start, end)) = range {
+                    query.start_time = Some(start);
+                    query.end_time = Some(end);
+                }
+                let analytics = ProductivityAnalytics::new(self.db_path.clone());
+                Ok(ReportOutput::Productivity(analytics.query(&query)?))
+            }
+        }
+    }
+
+    /// 获取指定周期的报表（供报表生成器使用）
+    pub fn scheduled(&self, schedule: ReportSchedule) -> Result<Vec<SavedReport>> {
+        Ok(self
+            .list()?
+            .into_iter()
+            .filter(|r| r.schedule == Some(schedule))
+            .collect())
+    }
+
+    /// 导出报表为 JSON（ids 为空时导出全部）
+    pub fn export(&self, ids: &[String]) -> Result<String> {
+        let mut store = self.load_store()?;
+        if !ids.is_empty() {
+            store.reports.retain(|r| ids.contains(&r.id));
+        }
+        serde_json::to_string_pretty(&store).context("序列化报表失败")
+    }
+
+    /// 从 JSON 导入报表
+    ///
+    /// `overwrite` 为 false 时跳过 ID 已存在的报表
+    pub fn import(&self, content: &str, overwrite: bool) -> Result<ReportImportSummary> {
+        let incoming: ReportsStore = serde_json::from_str(content).context("报表文件格式无效")?;
+        if incoming.version > REPORTS_STORE_VERSION {
+            bail!("报表文件版本 {} 高于当前支持的版本", incoming.version);
+        }
+
+        let mut store = self.load_store()?;
+        let mut summary = ReportImportSummary::default();
+
+        for mut report in incoming.reports {
+            if report.id.is_empty() {
+                report.id = uuid::Uuid::new_v4().to_string();
+            }
+            if let ReportDefinition::Sql { ref query } = report.definition {
+                if let Err(e) = super::sql_console::validate_select(&query.sql) {
+                    tracing::warn!(report = %report.name, error = %e, "跳过无效的 SQL 报表");
+                    summary.skipped += 1;
+                    continue;
+                }
+            }
+
+            match store.reports.iter_mut().find(|r| r.id == report.id) {
+                Some(existing) if overwrite => {
+                    *existing = report;
+                    summary.imported += 1;
+                }
+                Some(_) => summary.skipped += 1,
+                None => {
+                    store.reports.push(report);
+                    summary.imported += 1;
+                }
+            }
+        }
+
+        self.save_store(&store)?;
+        Ok(summary)
+    }
+
+    fn load_store(&self) -> Result<ReportsStore> {
+        if !self.store_path.exists() {
+            return Ok(ReportsStore::default());
+        }
+        let value = DataManager::new().json_uncached().read(&self.store_path)?;
+        serde_json::from_value(value).context("解析报表存储失败")
+    }
+
+    fn save_store(&self, store: &ReportsStore) -> Result<()> {
+        let value = serde_json::to_value(store)?;
+        DataManager::new()
+            .json_uncached()
+            .write(&self.store_path, &value)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::token_stats::CostGroupBy;
+    use tempfile::TempDir;
+
+    fn manager(dir: &TempDir) -> SavedReportManager {
+        SavedReportManager::with_paths(
+            dir.path().join("reports.json"),
+            dir.path().join("token_stats.db"),
+        )
+    }
+
+    fn cost_report(name: &str) -> SavedReport {
+        SavedReport {
+            id: String::new(),
+            name: name.to_string(),
+            description: None,
+            definition: ReportDefinition::CostSummary {
+                query: CostSummaryQuery {
+                    tool_type: Some("claude-code".to_string()),
+                    group_by: CostGroupBy::Model,
+                    ..Default::default()
+                },
+            },
+            range_days: Some(7),
+            schedule: Some(ReportSchedule::Weekly),
+            created_at: 0,
+            updated_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_save_update_delete() {
+        let dir = TempDir::new().unwrap();
+        let mgr = manager(&dir);
+
+        let saved = mgr.save(cost_report("每周模型成本")).unwrap();
+        assert!(!saved.id.is_empty());
+        assert!(saved.created_at > 0);
+
+        let mut renamed = saved.clone();
+        renamed.name = "模型成本".to_string();
+        mgr.save(renamed).unwrap();
+
+        let reports = mgr.list().unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].name, "模型成本");
+        assert_eq!(reports[0].created_at, saved.created_at);
+        assert_eq!(mgr.scheduled(ReportSchedule::Weekly).unwrap().len(), 1);
+        assert!(mgr.scheduled(ReportSchedule::Daily).unwrap().is_empty());
+
+        mgr.delete(&saved.id).unwrap();
+        assert!(mgr.list().unwrap().is_empty());
+        assert!(mgr.delete(&saved.id).is_err());
+    }
+
+    #[test]
+    fn test_invalid_sql_report_is_rejected() {
+        let dir = TempDir::new().unwrap();
+        let mgr = manager(&dir);
+
+        let mut report = cost_report("危险报表");
+        report.definition = ReportDefinition::Sql {
+            query: SqlConsoleQuery {
+                sql: "DELETE FROM token_logs".to_string(),
+                ..Default::default()
+            },
+        };
+        assert!(mgr.save(report).is_err());
+    }
+
+    #[test]
+    fn test_export_import_roundtrip() {
+        let source_dir = TempDir::new().unwrap();
+        let source = manager(&source_dir);
+        let a = source.save(cost_report("A")).unwrap();
+        source.save(cost_report("B")).unwrap();
+
+        let exported = source.export(std::slice::from_ref(&a.id)).unwrap();
+
+        let target_dir = TempDir::new().unwrap();
+        let target = manager(&target_dir);
+        let summary = target.import(&exported, false).unwrap();
+        assert_eq!(summary.imported, 1);
+        assert_eq!(target.get(&a.id).unwrap().name, "A");
+
+        // 重复导入：默认跳过，覆盖模式下替换
+        assert_eq!(target.import(&exported, false).unwrap().skipped, 1);
+        assert_eq!(target.import(&exported, true).unwrap().imported, 1);
+        assert_eq!(target.list().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_run_sql_report() {
+        let dir = TempDir::new().unwrap();
+        let mgr = manager(&dir);
+        rusqlite::Connection::open(dir.path().join("token_stats.db"))
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE token_logs (id INTEGER); INSERT INTO token_logs VALUES (1), (2);",
+            )
+            .unwrap();
+
+        let mut report = cost_report("SQL");
+        report.definition = ReportDefinition::Sql {
+            query: SqlConsoleQuery {
+                sql: "SELECT COUNT(*) AS n FROM token_logs".to_string(),
+                ..Default::default()
+            },
+        };
+        let saved = mgr.save(report).unwrap();
+
+        match mgr.run(&saved.id).unwrap() {
+            ReportOutput::Sql(result) => assert_eq!(result.rows[0][0], serde_json::json!(2)),
+            other => panic!("unexpected output: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,199 @@
+//! token_logs 表的 Schema 迁移步骤
+//!
+//! 版本管理引入前的旧库（版本 0）可能缺少任意后加的列，因此先补齐列再建索引；
+//! 以后修改表结构时在末尾追加新步骤，不要修改已发布的步骤。
+
+use crate::data::schema::{add_column_if_missing, SchemaMigration};
+use rusqlite::Transaction;
+
+/// token_logs 迁移步骤（按版本号递增）
+pub const TOKEN_STATS_MIGRATIONS: &[SchemaMigration] = &[
+    SchemaMigration {
+        version: 1,
+        name: "create_token_logs",
+        up: create_token_logs,
+    },
+    SchemaMigration {
+        version: 2,
+        name: "add_legacy_missing_columns",
+        up: add_legacy_missing_columns,
+    },
+    SchemaMigration {
+        version: 3,
+        name: "create_token_logs_indexes",
+        up: create_indexes,
+    },
+    SchemaMigration {
+        version: 4,
+        name: "add_client_key_label",
+        up: add_client_key_label,
+    },
+];
+
+/// 版本管理引入前陆续新增的列（旧库按需补齐）
+const LEGACY_COLUMNS: &[(&str, &str)] = &[
+    ("reasoning_tokens", "INTEGER NOT NULL DEFAULT 0"),
+    ("request_status", "TEXT NOT NULL DEFAULT 'success'"),
+    ("response_type", "TEXT NOT NULL DEFAULT 'unknown'"),
+    ("error_type", "TEXT"),
+    ("error_detail", "TEXT"),
+    ("input_price", "REAL"),
+    ("output_price", "REAL"),
+    ("cache_write_price", "REAL"),
+    ("cache_read_price", "REAL"),
+    ("reasoning_price", "REAL"),
+    ("total_cost", "REAL NOT NULL DEFAULT 0.0"),
+    ("response_time_ms", "INTEGER"),
+    ("pricing_template_id", "TEXT"),
+    ("upstream_headers", "TEXT"),
+    ("upstream_key_alias", "TEXT"),
+    ("tags", "TEXT"),
+    ("cache_creation_1h_tokens", "INTEGER NOT NULL DEFAULT 0"),
+];
+
+fn create_token_logs(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE IF NOT EXISTS token_logs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            tool_type TEXT NOT NULL,
+            timestamp INTEGER NOT NULL,
+            client_ip TEXT NOT NULL,
+            session_id TEXT NOT NULL,
+            config_name TEXT NOT NULL,
+            model TEXT NOT NULL,
+            message_id TEXT,
+
+            -- Token 数量
+            input_tokens INTEGER NOT NULL DEFAULT 0,
+            output_tokens INTEGER NOT NULL DEFAULT 0,
+            cache_creation_tokens INTEGER NOT NULL DEFAULT 0,
+            cache_read_tokens INTEGER NOT NULL DEFAULT 0,
+            reasoning_tokens INTEGER NOT NULL DEFAULT 0,
+
+            -- 请求状态
+            request_status TEXT NOT NULL DEFAULT 'success',
+            response_type TEXT NOT NULL DEFAULT 'unknown',
+            error_type TEXT,
+            error_detail TEXT,
+
+            -- 各部分的价格（USD）
+            input_price REAL,
+            output_price REAL,
+            cache_write_price REAL,
+            cache_read_price REAL,
+            reasoning_price REAL,
+
+            -- 总成本（USD）
+            total_cost REAL NOT NULL DEFAULT 0.0,
+
+            -- 响应时间
+            response_time_ms INTEGER,
+
+            -- 价格模板 ID
+            pricing_template_id TEXT,
+
+            -- 捕获的上游响应头（JSON）
+            upstream_headers TEXT,
+
+            -- Key 池中使用的 Key 别名
+            upstream_key_alias TEXT,
+
+            -- 会话标签（逗号分隔）
+            tags TEXT,
+
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+}
+
+fn add_legacy_missing_columns(tx: &Transaction) -> rusqlite::Result<()> {
+    for (column, definition) in LEGACY_COLUMNS {
+        if add_column_if_missing(tx, "token_logs", column, definition)? {
+            tracing::info!(column, "token_logs 补齐缺失列");
+        }
+    }
+    Ok(())
+}
+
+fn create_indexes(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_session_timestamp ON token_logs(session_id, timestamp);
+         CREATE INDEX IF NOT EXISTS idx_timestamp ON token_logs(timestamp);
+         CREATE INDEX IF NOT EXISTS idx_tool_type ON token_logs(tool_type);
+         -- 成本分析相关索引
+         CREATE INDEX IF NOT EXISTS idx_model ON token_logs(model);
+         CREATE INDEX IF NOT EXISTS idx_total_cost ON token_logs(total_cost);
+         CREATE INDEX IF NOT EXISTS idx_timestamp_cost ON token_logs(timestamp, total_cost);
+         CREATE INDEX IF NOT EXISTS idx_tool_model ON token_logs(tool_type, model);",
+    )
+}
+
+/// 记录鉴权所用的客户端 Key 标签（共用代理时按客户端统计用量）
+fn add_client_key_label(tx: &Transaction) -> rusqlite::Result<()> {
+    add_column_if_missing(tx, "token_logs", "client_key_label", "TEXT")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::token_stats::TokenStatsDb;
+    use rusqlite::Connection;
+
+    #[test]
+    fn test_upgrade_pre_versioned_db() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("token_stats.db");
+
+        // 早期版本的表结构：只有基础字段，没有成本分析列
+        let conn = Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE token_logs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                tool_type TEXT NOT NULL,
+                timestamp INTEGER NOT NULL,
+                client_ip TEXT NOT NULL,
+                session_id TEXT NOT NULL,
+                config_name TEXT NOT NULL,
+                model TEXT NOT NULL,
+                message_id TEXT,
+                input_tokens INTEGER NOT NULL DEFAULT 0,
+                output_tokens INTEGER NOT NULL DEFAULT 0,
+                cache_creation_tokens INTEGER NOT NULL DEFAULT 0,
+                cache_read_tokens INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            );
+            INSERT INTO token_logs (tool_type, timestamp, client_ip, session_id, config_name, model, input_tokens)
+            VALUES ('claude-code', 1700000000000, '127.0.0.1', 's1', 'default', 'claude-sonnet', 100);",
+        )
+        .unwrap();
+        drop(conn);
+
+        let db = TokenStatsDb::new(db_path.clone());
+        db.init_table().unwrap();
+
+        assert!(dir.path().join("token_stats.db.bak-v0").exists());
+        let conn = Connection::open(&db_path).unwrap();
+        let version: u32 = conn
+            .query_row("SELECT MAX(version) FROM schema_version", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(version, TOKEN_STATS_MIGRATIONS.last().unwrap().version);
+        let (cost, status): (f64, String) = conn
+            .query_row(
+                "SELECT total_cost, request_status FROM token_logs",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(cost, 0.0);
+        assert_eq!(status, "success");
+
+        // 旧数据可被正常查询
+        let stats = db.get_session_stats("claude-code", "s1").unwrap();
+        assert_eq!(stats.total_input, 100);
+        assert_eq!(db.sum_cost_since("claude-code", 0).unwrap(), 0.0);
+        assert_eq!(db.usage_since(0).unwrap(), (1, 0.0));
+    }
+}
@@ -0,0 +1,212 @@
+//! 统计数据隐私脱敏
+//!
+//! 截图 / 演示模式下，把统计接口返回的数据按字段名脱敏后再展示，
+//! 便于公开分享仪表板而不泄露供应商、项目或花费细节：
+//!
+//! - Profile / 配置名称 / Key 别名：替换为稳定的短哈希（同一次运行内同名得到相同结果，图表分组不变）
+//! - 会话 ID：替换为短哈希
+//! - 会话标签（多为项目名）：逐个替换为短哈希
+//! - Base URL、客户端 IP、API Key、备注、上游响应头：直接屏蔽
+//! - 成本：可选按数量级取整（保留 1 位有效数字）
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// 需要哈希的名称字段
+const NAME_FIELDS: [&str; 8] = [
+    "config_name",
+    "profile_name",
+    "custom_profile_name",
+    "real_profile_name",
+    "provider",
+    "upstream_key_alias",
+    "key_alias",
+    "client_key_label",
+];
+
+/// 需要缩短的会话 ID 字段
+const SESSION_FIELDS: [&str; 2] = ["session_id", "display_id"];
+
+/// 标签数组字段（逐个哈希）
+const TAG_FIELD: &str = "tags";
+
+/// 需要完全屏蔽的字段
+const MASKED_FIELDS: [&str; 9] = [
+    "url",
+    "base_url",
+    "real_base_url",
+    "client_ip",
+    "api_key",
+    "real_api_key",
+    "note",
+    "message_id",
+    "upstream_headers",
+];
+
+/// 屏蔽后的占位值
+const MASK: &str = "***";
+
+/// 哈希后保留的十六进制位数
+const HASH_LEN: usize = 6;
+
+/// 进程级随机盐：同一次运行内哈希稳定，跨运行不可关联
+static PROCESS_SALT: Lazy<String> = Lazy::new(|| uuid::Uuid::new_v4().to_string());
+
+/// 脱敏选项
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScrubOptions {
+    /// 成本按数量级取整
+    #[serde(default)]
+    pub bucket_costs: bool,
+    /// 自定义哈希盐（为空时使用进程级随机盐）；需要跨次截图保持名称一致时可指定
+    #[serde(default)]
+    pub salt: Option<String>,
+}
+
+/// 统计数据脱敏器
+pub struct StatsScrubber {
+    options: ScrubOptions,
+}
+
+impl StatsScrubber {
+    pub fn new(options: ScrubOptions) -> Self {
+        Self { options }
+    }
+
+    /// 脱敏任意统计数据（递归处理对象与数组）
+    pub fn scrub(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, field) in map.iter_mut() {
+                    self.scrub_field(key, field);
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    self.scrub(item);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// 脱敏可序列化的数据结构
+    pub fn scrub_serializable<T: Serialize>(&self, data: &T) -> serde_json::Result<Value> {
+        let mut value = serde_json::to_value(data)?;
+        self.scrub(&mut value);
+        Ok(value)
+    }
+
+    fn scrub_field(&self, key: &str, field: &mut Value) {
+        if MASKED_FIELDS.contains(&key) {
+            if !field.is_null() {
+                *field = Value::String(MASK.to_string());
+            }
+        } else if NAME_FIELDS.contains(&key) {
+            if let Some(name) = field.as_str() {
+                *field = Value::String(self.pseudonym("profile", name));
+            }
+        } else if key == TAG_FIELD && field.is_array() {
+            for tag in field.as_array_mut().into_iter().flatten() {
+                if let Some(name) = tag.as_str() {
+                    *tag = Value::String(self.pseudonym("tag", name));
+                }
+            }
+        } else if SESSION_FIELDS.contains(&key) {
+            if let Some(id) = field.as_str() {
+                *field = Value::String(self.pseudonym("s", id));
+            }
+        } else if self.options.bucket_costs && is_cost_field(key) && field.is_number() {
+            if let Some(cost) = field.as_f64() {
+                *field = serde_json::json!(bucket_cost(cost));
+            }
+        } else {
+            self.scrub(field);
+        }
+    }
+
+    /// 生成稳定的短哈希别名
+    fn pseudonym(&self, prefix: &str, raw: &str) -> String {
+        let salt = self.options.salt.as_deref().unwrap_or(&PROCESS_SALT);
+        let digest = Sha256::new()
+            .chain_update(salt.as_bytes())
+            .chain_update(raw.as_bytes())
+            .finalize();
+        let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+        format!("{}-{}", prefix, &hex[..HASH_LEN])
+    }
+}
+
+/// 是否为成本 / 价格字段
+fn is_cost_field(key: &str) -> bool {
+    key.contains("cost") || key.ends_with("_price")
+}
+
+/// 按数量级取整，保留 1 位有效数字（如 0.0347 → 0.03，123.4 → 100）
+pub fn bucket_cost(cost: f64) -> f64 {
+    if cost <= 0.0 || !cost.is_finite() {
+        return 0.0;
+    }
+    let magnitude = 10f64.powf(cost.log10().floor());
+    (cost / magnitude).floor() * magnitude
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn scrubber(bucket_costs: bool) -> StatsScrubber {
+        StatsScrubber::new(ScrubOptions {
+            bucket_costs,
+            salt: Some("test".to_string()),
+        })
+    }
+
+    #[test]
+    fn test_scrub_payload() {
+        let mut payload = json!({
+            "total_cost": 12.345,
+            "cost_by_config": [
+                { "config_name": "my-secret-relay", "total_cost": 0.0347 },
+                { "config_name": "my-secret-relay", "total_cost": 0.5 },
+            ],
+            "cost_by_model": [{ "model": "claude-sonnet-4-5", "total_cost": 123.4 }],
+            "logs": [{ "session_id": "user_abc_session_123", "client_ip": "10.0.0.8", "note": null, "tags": ["acme-billing"] }],
+        });
+        scrubber(true).scrub(&mut payload);
+
+        assert_eq!(payload["total_cost"], json!(10.0));
+        let configs = payload["cost_by_config"].as_array().unwrap();
+        let name = configs[0]["config_name"].as_str().unwrap();
+        assert!(name.starts_with("profile-") && name.len() == "profile-".len() + HASH_LEN);
+        assert_eq!(configs[1]["config_name"].as_str(), Some(name));
+        assert!((configs[0]["total_cost"].as_f64().unwrap() - 0.03).abs() < 1e-12);
+
+        assert_eq!(payload["cost_by_model"][0]["model"], "claude-sonnet-4-5");
+        assert_eq!(payload["cost_by_model"][0]["total_cost"], json!(100.0));
+
+        let log = &payload["logs"][0];
+        assert!(log["session_id"].as_str().unwrap().starts_with("s-"));
+        assert_eq!(log["client_ip"], MASK);
+        assert!(log["note"].is_null());
+        assert!(log["tags"][0].as_str().unwrap().starts_with("tag-"));
+    }
+
+    #[test]
+    fn test_costs_kept_without_bucketing() {
+        let mut payload = json!({ "total_cost": 12.345 });
+        scrubber(false).scrub(&mut payload);
+        assert_eq!(payload["total_cost"], json!(12.345));
+    }
+
+    #[test]
+    fn test_bucket_cost() {
+        assert_eq!(bucket_cost(0.0), 0.0);
+        assert_eq!(bucket_cost(7.0), 7.0);
+        assert_eq!(bucket_cost(98.6), 90.0);
+        assert_eq!(bucket_cost(1234.0), 1000.0);
+    }
+}
@@ -0,0 +1,473 @@
+//! 只读 SQL 控制台
+//!
+//! 允许高级用户直接对 token_stats.db 执行临时查询：
+//! - 仅允许单条 SELECT/WITH 语句，并以只读连接执行（双重保护）
+//! - 参数通过 `?` 占位符绑定，避免拼接 SQL
+//! - 限制返回行数与执行时间，超时自动中断
+//! - 查询历史持久化到 `~/.duckcoding/sql_console_history.json`
+
+use crate::data::DataManager;
+use crate::utils::config::config_dir;
+use anyhow::{anyhow, bail, Context, Result};
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params_from_iter, Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+/// 默认最大返回行数
+pub const DEFAULT_MAX_ROWS: usize = 500;
+/// 最大返回行数上限
+pub const MAX_ROWS_LIMIT: usize = 10_000;
+/// 默认执行超时（毫秒）
+pub const DEFAULT_TIMEOUT_MS: u64 = 5_000;
+/// 执行超时上限（毫秒）
+pub const MAX_TIMEOUT_MS: u64 = 30_000;
+/// 保留的历史记录条数
+const MAX_HISTORY_ENTRIES: usize = 100;
+
+/// SQL 控制台查询请求
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SqlConsoleQuery {
+    /// SQL 语句（仅允许单条 SELECT/WITH）
+    pub sql: String,
+    /// 按顺序绑定到 `?` 占位符的参数（字符串/数字/布尔/null）
+    #[serde(default)]
+    pub params: Vec<Value>,
+    /// 最大返回行数（默认 500，上限 10000）
+    #[serde(default)]
+    pub max_rows: Option<usize>,
+    /// 执行超时（毫秒，默认 5000，上限 30000）
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+/// SQL 控制台查询结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SqlConsoleResult {
+    /// 列名
+    pub columns: Vec<String>,
+    /// 行数据（与列名顺序一致）
+    pub rows: Vec<Vec<Value>>,
+    /// 结果是否因行数限制被截断
+    pub truncated: bool,
+    /// 执行耗时（毫秒）
+    pub elapsed_ms: u64,
+}
+
+/// 查询历史记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SqlHistoryEntry {
+    pub sql: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub params: Vec<Value>,
+    /// 执行时间（Unix 时间戳，毫秒）
+    pub executed_at: i64,
+    /// 返回行数（失败时为 0）
+    pub row_count: usize,
+    pub elapsed_ms: u64,
+    /// 失败原因（成功时为空）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 查询历史存储
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct SqlHistoryStore {
+    #[serde(default)]
+    entries: Vec<SqlHistoryEntry>,
+}
+
+/// 只读 SQL 控制台
+pub struct SqlConsole {
+    db_path: PathBuf,
+    history_path: PathBuf,
+}
+
+impl SqlConsole {
+    /// 使用默认路径（~/.duckcoding/token_stats.db）创建控制台
+    pub fn new() -> Result<Self> {
+        let dir = config_dir().map_err(|e| anyhow!("获取配置目录失败: {}", e))?;
+        Ok(Self::with_paths(
+            dir.join("token_stats.db"),
+            dir.join("sql_console_history.json"),
+        ))
+    }
+
+    /// 使用指定路径创建控制台
+    pub fn with_paths(db_path: PathBuf, history_path: PathBuf) -> Self {
+        Self {
+            db_path,
+            history_path,
+        }
+    }
+
+    /// 执行查询并记录历史（无论成功与否）
+    pub fn execute(&self, query: &SqlConsoleQuery) -> Result<SqlConsoleResult> {
+        let started = Instant::now();
+        let result = self.query(query);
+
+        let entry = SqlHistoryEntry {
+            sql: query.sql.trim().to_string(),
+            params: query.params.clone(),
+            executed_at: chrono::Utc::now().timestamp_millis(),
+            row_count: result.as_ref().map(|r| r.rows.len()).unwrap_or(0),
+            elapsed_ms: started.elapsed().as_millis() as u64,
+            error: result.as_ref().err().map(|e| e.to_string()),
+        };
+        if let Err(e) = self.append_history(entry) {
+            tracing::warn!(error = ?e, "保存 SQL 控制台历史失败");
+        }
+
+        result
+    }
+
+    /// 获取查询历史（最新在前）
+    pub fn history(&self) -> Result<Vec<SqlHistoryEntry>> {
+        let mut entries = self.load_history()?.entries;
+        entries.reverse();
+        Ok(entries)
+    }
+
+    /// 清空查询历史
+    pub fn clear_history(&self) -> Result<()> {
+        self.save_history(&SqlHistoryStore::default())
+    }
+
+    /// 执行查询（不记录历史，供已保存报表等内部调用）
+    pub fn query(&self, query: &SqlConsoleQuery) -> Result<SqlConsoleResult> {
+        let sql = validate_select(&query.sql)?;
+        let params = query
+            .params
+            .iter()
+            .map(json_to_sql)
+            .collect::<Result<Vec<_>>>()?;
+        let max_rows = query
+            .max_rows
+            .unwrap_or(DEFAULT_MAX_ROWS)
+            .clamp(1, MAX_ROWS_LIMIT);
+        let timeout = Duration::from_millis(
+            query
+                .timeout_ms
+                .unwrap_or(DEFAULT_TIMEOUT_MS)
+                .clamp(1, MAX_TIMEOUT_MS),
+        );
+
+        if !self.db_path.exists() {
+            bail!("统计数据库不存在: {}", self.db_path.display());
+        }
+        let conn = open_read_only(&self.db_path)?;
+
+        // 超时后中断查询
+        let interrupt = conn.get_interrupt_handle();
+        let (done_tx, done_rx) = mpsc::channel::<()>();
+        let watchdog = std::thread::spawn(move || {
+            if let Err(mpsc::RecvTimeoutError::Timeout) = done_rx.recv_timeout(timeout) {
+                interrupt.interrupt();
+            }
+        });
+
+        let started = Instant::now();
+        let result = collect_rows(&conn, sql, &params, max_rows);
+        let _ = done_tx.send(());
+        let _ = watchdog.join();
+
+        let (columns, rows, truncated) = result.map_err(|e| match e {
+            rusqlite::Error::SqliteFailure(err, _)
+                if err.code == rusqlite::ErrorCode::OperationInterrupted =>
+            {
+                anyhow!("查询超时（{} ms），已中断", timeout.as_millis())
+            }
+            other => anyhow!("查询失败: {}", other),
+        })?;
+
+        Ok(SqlConsoleResult {
+            columns,
+            rows,
+            truncated,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        })
+    }
+
+    fn load_history(&self) -> Result<SqlHistoryStore> {
+        if !self.history_path.exists() {
+            return Ok(SqlHistoryStore::default());
+        }
+        let value = DataManager::new()
+            .json_uncached()
+            .read(&self.history_path)?;
+        serde_json::from_value(value).context("解析 SQL 控制台历史失败")
+    }
+
+    fn save_history(&self, store: &SqlHistoryStore) -> Result<()> {
+        let value = serde_json::to_value(store)?;
+        DataManager::new()
+            .json_uncached()
+            .write(&self.history_path, &value)?;
+        Ok(())
+    }
+
+    fn append_history(&self, entry: SqlHistoryEntry) -> Result<()> {
+        let mut store = self.load_history().unwrap_or_default();
+        // 连续重复执行同一语句时只保留最新一条
+        if store
+            .entries
+            .last()
+            .is_some_and(|last| last.sql == entry.sql && last.params == entry.params)
+        {
+            store.entries.pop();
+        }
+        store.entries.push(entry);
+        if store.entries.len() > MAX_HISTORY_ENTRIES {
+            let overflow = store.entries.len() - MAX_HISTORY_ENTRIES;
+            store.entries.drain(..overflow);
+        }
+        self.save_history(&store)
+    }
+}
+
+/// 以只读模式打开数据库
+pub(super) fn open_read_only(path: &Path) -> Result<Connection> {
+    let conn = Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+    .with_context(|| format!("打开统计数据库失败: {}", path.display()))?;
+    conn.execute_batch("PRAGMA query_only = ON;")?;
+    Ok(conn)
+}
+
+/// 执行查询并收集结果（超出 max_rows 时截断）
+#[allow(clippy::type_complexity)]
+fn collect_rows(
+    conn: &Connection,
+    sql: &str,
+    params: &[SqlValue],
+    max_rows: usize,
+) -> rusqlite::Result<(Vec<String>, Vec<Vec<Value>>, bool)> {
+    let mut stmt = conn.prepare(sql)?;
+    if !stmt.readonly() {
+        return Err(rusqlite::Error::InvalidQuery);
+    }
+    let columns: Vec<String> = stmt.column_names().iter().map(|s| s.to_string()).collect();
+
+    let mut rows = Vec::new();
+    let mut truncated = false;
+    let mut cursor = stmt.query(params_from_iter(params.iter()))?;
+    while let Some(row) = cursor.next()? {
+        if rows.len() >= max_rows {
+            truncated = true;
+            break;
+        }
+        let values = (0..columns.len())
+            .map(|i| row.get_ref(i).map(value_ref_to_json))
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        rows.push(values);
+    }
+
+    Ok((columns, rows, truncated))
+}
+
+pub(super) fn value_ref_to_json(value: rusqlite::types::ValueRef<'_>) -> Value {
+    use rusqlite::types::ValueRef;
+    match value {
+        ValueRef::Null => Value::Null,
+        ValueRef::Integer(i) => Value::from(i),
+        ValueRef::Real(f) => serde_json::Number::from_f64(f)
+            .map(Value::Number)
+            .unwrap_or(Value::Null),
+        ValueRef::Text(s) => Value::String(String::from_utf8_lossy(s).to_string()),
+        ValueRef::Blob(b) => Value::String(format!("<blob {} bytes>", b.len())),
+    }
+}
+
+fn json_to_sql(value: &Value) -> Result<SqlValue> {
+    Ok(match value {
+        Value::Null => SqlValue::Null,
+        Value::Bool(b) => SqlValue::Integer(i64::from(*b)),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => SqlValue::Integer(i),
+            None => SqlValue::Real(n.as_f64().unwrap_or_default()),
+        },
+        Value::String(s) => SqlValue::Text(s.clone()),
+        other => bail!("不支持的参数类型: {}", other),
+    })
+}
+
+/// 校验 SQL：仅允许单条 SELECT/WITH 语句，返回去掉结尾分号后的语句
+pub fn validate_select(sql: &str) -> Result<&str> {
+    let trimmed = sql.trim().trim_end_matches(';').trim_end();
+    if trimmed.is_empty() {
+        bail!("SQL 不能为空");
+    }
+
+    let keyword: String = trimmed
+        .chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect::<String>()
+        .to_ascii_uppercase();
+    if keyword != "SELECT" && keyword != "WITH" {
+        bail!("仅允许 SELECT / WITH 查询");
+    }
+
+    if has_statement_separator(trimmed) {
+        bail!("仅允许执行单条语句");
+    }
+
+    Ok(trimmed)
+}
+
+/// 检查字符串字面量、标识符与注释之外是否存在分号
+fn has_statement_separator(sql: &str) -> bool {
+    let bytes = sql.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            quote @ (b'\'' | b'"' | b'`') => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote {
+                    i += 1;
+                }
+            }
+            b'[' => {
+                while i < bytes.len() && bytes[i] != b']' {
+                    i += 1;
+                }
+            }
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i += 2;
+                while i + 1 < bytes.len() && !(bytes[i] == b'*' && bytes[i + 1] == b'/') {
+                    i += 1;
+                }
+                i += 1;
+            }
+            b';' => return true,
+            _ => {}
+        }
+        i += 1;
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn setup() -> (TempDir, SqlConsole) {
+        let dir = TempDir::new().unwrap();
+        let db_path = dir.path().join("token_stats.db");
+        let conn = Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE token_logs (id INTEGER PRIMARY KEY, model TEXT, total_cost REAL);
+             INSERT INTO token_logs (model, total_cost) VALUES
+                ('claude-sonnet', 0.5), ('claude-sonnet', 0.25), ('gpt-5', 1.0);",
+        )
+        .unwrap();
+        let console = SqlConsole::with_paths(db_path, dir.path().join("history.json"));
+        (dir, console)
+    }
+
+    #[test]
+    fn test_validate_select() {
+        assert!(validate_select("select 1;").is_ok());
+        assert!(validate_select("WITH t AS (SELECT 1) SELECT * FROM t").is_ok());
+        assert!(validate_select("SELECT ';' AS semi -- ; comment").is_ok());
+        assert!(validate_select("DELETE FROM token_logs").is_err());
+        assert!(validate_select("SELECT 1; DROP TABLE token_logs").is_err());
+        assert!(validate_select("   ").is_err());
+    }
+
+    #[test]
+    fn test_execute_with_params_and_row_limit() {
+        let (_dir, console) = setup();
+
+        let result = console
+            .execute(&SqlConsoleQuery {
+                sql: "SELECT model, SUM(total_cost) AS cost FROM token_logs \
+                      WHERE model = ? GROUP BY model"
+                    .to_string(),
+                params: vec![json!("claude-sonnet")],
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(result.columns, vec!["model", "cost"]);
+        assert_eq!(result.rows, vec![vec![json!("claude-sonnet"), json!(0.75)]]);
+        assert!(!result.truncated);
+
+        let limited = console
+            .execute(&SqlConsoleQuery {
+                sql: "SELECT id FROM token_logs ORDER BY id".to_string(),
+                max_rows: Some(2),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(limited.rows.len(), 2);
+        assert!(limited.truncated);
+    }
+
+    #[test]
+    fn test_write_statements_are_rejected() {
+        let (_dir, console) = setup();
+
+        // 通过 CTE 伪装的写操作会被只读检查拦截
+        let result = console.execute(&SqlConsoleQuery {
+            sql: "WITH x AS (SELECT 1) DELETE FROM token_logs".to_string(),
+            ..Default::default()
+        });
+        assert!(result.is_err());
+
+        let count = console
+            .execute(&SqlConsoleQuery {
+                sql: "SELECT COUNT(*) FROM token_logs".to_string(),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(count.rows[0][0], json!(3));
+    }
+
+    #[test]
+    fn test_timeout_interrupts_long_query() {
+        let (_dir, console) = setup();
+        let result = console.execute(&SqlConsoleQuery {
+            sql: "WITH RECURSIVE c(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM c) \
+                  SELECT COUNT(*) FROM c"
+                .to_string(),
+            timeout_ms: Some(50),
+            ..Default::default()
+        });
+        assert!(result.unwrap_err().to_string().contains("超时"));
+    }
+
+    #[test]
+    fn test_history_is_persisted() {
+        let (_dir, console) = setup();
+        let query = SqlConsoleQuery {
+            sql: "SELECT 1".to_string(),
+            ..Default::default()
+        };
+        console.execute(&query).unwrap();
+        console.execute(&query).unwrap();
+        let _ = console.execute(&SqlConsoleQuery {
+            sql: "SELECT * FROM missing_table".to_string(),
+            ..Default::default()
+        });
+
+        let history = console.history().unwrap();
+        assert_eq!(history.len(), 2);
+        assert!(history[0].error.is_some());
+        assert_eq!(history[1].sql, "SELECT 1");
+        assert_eq!(history[1].row_count, 1);
+
+        console.clear_history().unwrap();
+        assert!(console.history().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,196 @@
+//! SSE 流式 Token 累加器
+//!
+//! 代理转发 SSE 流时逐个 data 块输入，只维护提取 Token 所需的状态：
+//! - 前若干个 data 块（供上游 API 风格检测）
+//! - Anthropic / OpenAI Responses 两种风格的 usage 状态机
+//! - 已生成内容的粗略 Token 估算（usage 在流末尾才给出最终值，供实时用量展示）
+//!
+//! 内存占用与流长度无关；流结束后按实际使用的提取器生成 [`TokenInfo`]
+
+use super::processor::{request_model, ClaudeSseState, CodexSseState, TokenInfo};
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+
+/// 保留用于风格检测的 data 块数量
+pub const HEAD_DATA_LINES: usize = 8;
+
+/// 每个 Token 约对应的 ASCII 字符数（非 ASCII 字符按 1 Token 计）
+const ASCII_CHARS_PER_TOKEN: usize = 4;
+
+/// 流式 Token 累加器
+#[derive(Debug, Clone, Default)]
+pub struct StreamingTokenAccumulator {
+    /// 前 [`HEAD_DATA_LINES`] 个 data 块原文
+    head: Vec<String>,
+    /// 已输入的有效 data 块数量
+    data_lines: usize,
+    claude: ClaudeSseState,
+    codex: CodexSseState,
+    /// 已生成内容的估算单位（ASCII 字符计 1，其他字符计 [`ASCII_CHARS_PER_TOKEN`]）
+    streamed_units: usize,
+}
+
+impl StreamingTokenAccumulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 由完整的 data 块列表构建（非流式场景，如一次性读取的 SSE 响应体）
+    pub fn from_data_lines<'a>(lines: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut accumulator = Self::new();
+        for line in lines {
+            accumulator.push_data(line);
+        }
+        accumulator
+    }
+
+    /// 输入一个 data 块内容（已去掉 `data:` 前缀；空块与 `[DONE]` 被忽略）
+    pub fn push_data(&mut self, data: &str) {
+        if data.is_empty() || data == "[DONE]" {
+            return;
+        }
+
+        self.data_lines += 1;
+        if self.head.len() < HEAD_DATA_LINES {
+            self.head.push(data.to_string());
+        }
+
+        match serde_json::from_str::<Value>(data) {
+            Ok(json) => {
+                self.claude.apply(&json);
+                self.codex.apply(&json);
+                if let Some(text) = delta_text(&json) {
+                    self.streamed_units += text
+                        .chars()
+                        .map(|c| {
+                            if c.is_ascii() {
+                                1
+                            } else {
+                                ASCII_CHARS_PER_TOKEN
+                            }
+                        })
+                        .sum::<usize>();
+                }
+            }
+            Err(e) => tracing::warn!("Failed to parse SSE chunk: {}", e),
+        }
+    }
+
+    /// 已输入的有效 data 块数量
+    pub fn data_line_count(&self) -> usize {
+        self.data_lines
+    }
+
+    /// 流开头的 data 块（最多 [`HEAD_DATA_LINES`] 个）
+    pub fn head_lines(&self) -> &[String] {
+        &self.head
+    }
+
+    /// 按已生成内容估算的输出 Token 数
+    pub fn estimated_output_tokens(&self) -> i64 {
+        self.streamed_units.div_ceil(ASCII_CHARS_PER_TOKEN) as i64
+    }
+
+    /// 输出 Token 至少取估算值的副本（流进行中的实时用量，不用于写入统计）
+    pub fn with_output_estimate(&self) -> Self {
+        let estimate = self.estimated_output_tokens();
+        let mut live = self.clone();
+        live.claude.raise_output_tokens(estimate);
+        live.codex.raise_output_tokens(estimate);
+        live
+    }
+
+    /// 按提取器（工具 ID）生成 Token 信息，model 取自请求体
+    pub fn token_info(&self, extractor_tool: &str, request_body: &[u8]) -> Result<TokenInfo> {
+        match extractor_tool {
+            "claude-code" => self.claude.clone().finish(request_model(request_body)?),
+            "codex" => self.codex.clone().finish(request_model(request_body)?),
+            _ => Err(anyhow!("Unsupported tool: {}", extractor_tool)),
+        }
+    }
+}
+
+/// 增量事件中模型生成的内容（文本、思考、工具参数）
+fn delta_text(json: &Value) -> Option<&str> {
+    let event_type = json.get("type")?.as_str()?;
+    let delta = json.get("delta")?;
+    if event_type == "content_block_delta" {
+        return ["text", "thinking", "partial_json"]
+            .iter()
+            .find_map(|key| delta.get(*key)?.as_str());
+    }
+    // Responses 风格：response.output_text.delta / response.function_call_arguments.delta 等
+    if event_type.starts_with("response.") && event_type.ends_with(".delta") {
+        return delta.as_str();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accumulates_usage_without_retaining_deltas() {
+        let request_body = br#"{"model":"claude-sonnet-4-5","messages":[]}"#;
+        let mut accumulator = StreamingTokenAccumulator::new();
+        accumulator.push_data(
+            r#"{"type":"message_start","message":{"id":"msg_1","usage":{"input_tokens":100,"cache_read_input_tokens":20,"output_tokens":1}}}"#,
+        );
+        for _ in 0..100 {
+            accumulator.push_data(
+                r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"hi"}}"#,
+            );
+        }
+        accumulator.push_data(r#"{"type":"message_delta","usage":{"output_tokens":42}}"#);
+        accumulator.push_data("[DONE]");
+
+        assert_eq!(accumulator.data_line_count(), 102);
+        assert_eq!(accumulator.head_lines().len(), HEAD_DATA_LINES);
+
+        let info = accumulator.token_info("claude-code", request_body).unwrap();
+        assert_eq!(info.message_id, "msg_1");
+        assert_eq!(info.input_tokens, 100);
+        assert_eq!(info.output_tokens, 42);
+        assert_eq!(info.cache_read_tokens, 20);
+
+        // 同一累加器也维护了 Responses 风格的状态，但缺少 response_id
+        assert!(accumulator.token_info("codex", request_body).is_err());
+        assert!(accumulator.token_info("gemini-cli", request_body).is_err());
+    }
+
+    #[test]
+    fn test_output_estimate_before_final_usage() {
+        let request_body = br#"{"model":"claude-sonnet-4-5","messages":[]}"#;
+        let mut accumulator = StreamingTokenAccumulator::new();
+        accumulator.push_data(
+            r#"{"type":"message_start","message":{"id":"msg_1","usage":{"input_tokens":100,"output_tokens":1}}}"#,
+        );
+        accumulator.push_data(
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"hello world!"}}"#,
+        );
+        accumulator.push_data(
+            r#"{"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"你好"}}"#,
+        );
+        // 12 个 ASCII 字符 ≈ 3 Token，2 个汉字 ≈ 2 Token
+        assert_eq!(accumulator.estimated_output_tokens(), 5);
+
+        let live = accumulator.with_output_estimate();
+        assert_eq!(
+            live.token_info("claude-code", request_body)
+                .unwrap()
+                .output_tokens,
+            5
+        );
+
+        // 最终 usage 到达后以实际值为准
+        accumulator.push_data(r#"{"type":"message_delta","usage":{"output_tokens":4}}"#);
+        let info = accumulator.token_info("claude-code", request_body).unwrap();
+        assert_eq!(info.output_tokens, 4);
+
+        let mut codex = StreamingTokenAccumulator::new();
+        codex.push_data(r#"{"type":"response.created","response":{"id":"resp_1"}}"#);
+        codex.push_data(r#"{"type":"response.output_text.delta","delta":"abcdefgh"}"#);
+        assert_eq!(codex.estimated_output_tokens(), 2);
+    }
+}
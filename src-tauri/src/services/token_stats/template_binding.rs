@@ -0,0 +1,121 @@
+//! Profile 级价格模板绑定
+//!
+//! 同一工具可能通过倍率不同的中转站使用，按工具的默认模板计价并不准确。
+//! 记录日志时按以下优先级选择价格模板：
+//! 1. 请求上下文中的模板（会话自定义配置 > 代理配置）
+//! 2. 日志所属 Profile 绑定的模板
+//! 3. 工具的默认模板（日志记录器已按此计算，无需重算）
+
+use crate::models::token_stats::TokenLog;
+use crate::services::pricing::PRICING_MANAGER;
+use crate::services::profile_manager::ProfileManager;
+
+/// 读取 Profile 绑定的价格模板 ID
+pub fn profile_template_id(tool_id: &str, profile_name: &str) -> Option<String> {
+    let manager = ProfileManager::new().ok()?;
+    match tool_id {
+        "claude-code" => {
+            manager
+                .get_claude_profile(profile_name)
+                .ok()?
+                .pricing_template_id
+        }
+        "codex" => {
+            manager
+                .get_codex_profile(profile_name)
+                .ok()?
+                .pricing_template_id
+        }
+        "gemini-cli" => {
+            manager
+                .get_gemini_profile(profile_name)
+                .ok()?
+                .pricing_template_id
+        }
+        _ => None,
+    }
+    .filter(|id| !id.is_empty())
+}
+
+/// 解析日志应使用的价格模板（None 表示使用工具默认模板）
+pub fn resolve_template_id(
+    tool_id: &str,
+    profile_name: &str,
+    context_template_id: Option<&str>,
+) -> Option<String> {
+    context_template_id
+        .filter(|id| !id.is_empty())
+        .map(|id| id.to_string())
+        .or_else(|| profile_template_id(tool_id, profile_name))
+}
+
+/// 按指定模板重新计算日志成本
+///
+/// 失败请求（无 Token）不计价；模板不存在或未包含该模型时保留原有计价结果。
+pub fn apply_template(log: &mut TokenLog, template_id: &str) {
+    let tokens = log.input_tokens
+        + log.output_tokens
+        + log.cache_creation_tokens
+        + log.cache_read_tokens
+        + log.reasoning_tokens;
+    if tokens == 0 || log.pricing_template_id.as_deref() == Some(template_id) {
+        return;
+    }
+
+    match PRICING_MANAGER.calculate_cost(
+        Some(template_id),
+        Some(&log.tool_type),
+        &log.model,
+        log.input_tokens,
+        log.output_tokens,
+        log.cache_creation_tokens,
+        log.cache_creation_1h_tokens,
+        log.cache_read_tokens,
+        log.reasoning_tokens,
+    ) {
+        Ok(breakdown) => {
+            log.input_price = Some(breakdown.input_price);
+            log.output_price = Some(breakdown.output_price);
+            log.cache_write_price = Some(breakdown.cache_write_price);
+            log.cache_read_price = Some(breakdown.cache_read_price);
+            log.reasoning_price = Some(breakdown.reasoning_price);
+            log.total_cost = breakdown.total_cost;
+            log.pricing_template_id = Some(breakdown.template_id);
+        }
+        Err(e) => {
+            tracing::warn!(
+                template_id = template_id,
+                model = %log.model,
+                error = %e,
+                "按绑定的价格模板计价失败，保留默认模板计价结果"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_template_prefers_context() {
+        assert_eq!(
+            resolve_template_id("claude-code", "relay", Some("relay_x2")),
+            Some("relay_x2".to_string())
+        );
+        assert_eq!(resolve_template_id("unknown-tool", "relay", Some("")), None);
+    }
+
+    #[test]
+    fn test_apply_template_skips_failed_requests() {
+        let mut log = TokenLog::test_builder()
+            .config("relay")
+            .status("failed")
+            .response_type("sse")
+            .error("upstream_error")
+            .build();
+        apply_template(&mut log, "missing_template");
+        assert_eq!(log.pricing_template_id, None);
+        assert_eq!(log.input_price, None);
+    }
+}
@@ -0,0 +1,315 @@
+//! 用户自定义工具
+//!
+//! 除内置的 Claude Code / CodeX / Gemini CLI 外，用户可在 `~/.duckcoding/tools.d/`
+//! 下为每个 CLI 放一个 JSON 定义（如 Aider、OpenCode），声明配置目录与文件、
+//! 环境变量名、版本检查命令和 npm 包名。定义在首次使用时加载，
+//! 之后由工具列表、Detector 注册表、配置监听与透明代理共同使用。
+//!
+//! ```json
+//! {
+//!   "id": "opencode",
+//!   "name": "OpenCode",
+//!   "npm_package": "opencode-ai",
+//!   "check_command": "opencode --version",
+//!   "config_dir": "~/.config/opencode",
+//!   "config_files": ["opencode.json"],
+//!   "env_vars": { "api_key": "ANTHROPIC_API_KEY", "base_url": "ANTHROPIC_BASE_URL" },
+//!   "proxy_protocol": "anthropic"
+//! }
+//! ```
+
+use crate::models::{EnvVars, Tool};
+use crate::utils::config::config_dir;
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// 定义文件所在目录名
+pub const CUSTOM_TOOLS_DIR: &str = "tools.d";
+
+/// 内置工具 ID（自定义工具不能使用）
+const BUILTIN_TOOL_IDS: [&str; 4] = ["claude-code", "codex", "gemini-cli", "amp-code"];
+
+/// 自定义工具可复用的代理协议（对应内置工具的请求处理器）
+const PROXY_PROTOCOLS: [&str; 3] = ["anthropic", "openai", "gemini"];
+
+/// 自定义工具定义（`tools.d/*.json`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomToolDefinition {
+    /// 工具 ID（小写字母、数字和 `-`）
+    pub id: String,
+    pub name: String,
+    /// npm 包名（为空时不支持 APP 内安装/更新）
+    #[serde(default)]
+    pub npm_package: Option<String>,
+    /// 版本检查命令（如 `aider --version`）
+    pub check_command: String,
+    /// 默认配置目录（支持 `~`，相对路径基于用户主目录）
+    pub config_dir: String,
+    /// 可覆盖配置目录的环境变量
+    #[serde(default)]
+    pub config_dir_env: Option<String>,
+    /// 配置文件列表（第一个为主配置文件）
+    pub config_files: Vec<String>,
+    pub env_vars: EnvVars,
+    #[serde(default = "default_use_proxy_for_version_check")]
+    pub use_proxy_for_version_check: bool,
+    /// 透明代理使用的协议：`anthropic` / `openai` / `gemini`（为空时不支持代理）
+    #[serde(default)]
+    pub proxy_protocol: Option<String>,
+    /// 透明代理默认端口
+    #[serde(default)]
+    pub proxy_port: Option<u16>,
+}
+
+fn default_use_proxy_for_version_check() -> bool {
+    true
+}
+
+/// 加载失败的定义文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomToolLoadError {
+    pub path: String,
+    pub error: String,
+}
+
+/// 自定义工具加载结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CustomToolsReport {
+    pub dir: String,
+    pub tools: Vec<CustomToolDefinition>,
+    pub errors: Vec<CustomToolLoadError>,
+}
+
+static CUSTOM_TOOLS: Lazy<RwLock<CustomToolsReport>> = Lazy::new(|| RwLock::new(load_from_disk()));
+
+impl CustomToolDefinition {
+    /// 主配置文件
+    pub fn config_file(&self) -> &str {
+        self.config_files
+            .first()
+            .map(String::as_str)
+            .unwrap_or_default()
+    }
+
+    /// 转换为通用工具定义
+    pub fn to_tool(&self) -> Tool {
+        Tool {
+            id: self.id.clone(),
+            name: self.name.clone(),
+            group_name: format!("{} 专用分组", self.name),
+            npm_package: self.npm_package.clone().unwrap_or_default(),
+            check_command: self.check_command.clone(),
+            config_dir: Tool::resolve_config_dir(&self.id).config_dir,
+            config_file: self.config_file().to_string(),
+            env_vars: self.env_vars.clone(),
+            use_proxy_for_version_check: self.use_proxy_for_version_check,
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        let valid_id = !self.id.is_empty()
+            && self.id.len() <= 32
+            && self
+                .id
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+            && !self.id.starts_with('-');
+        if !valid_id {
+            anyhow::bail!("工具 ID 只能包含小写字母、数字和 -，且不超过 32 个字符");
+        }
+        if BUILTIN_TOOL_IDS.contains(&self.id.as_str()) {
+            anyhow::bail!("工具 ID {} 与内置工具冲突", self.id);
+        }
+        if self.name.trim().is_empty() {
+            anyhow::bail!("name 不能为空");
+        }
+        if self.check_command.trim().is_empty() {
+            anyhow::bail!("check_command 不能为空");
+        }
+        if self.config_dir.trim().is_empty() {
+            anyhow::bail!("config_dir 不能为空");
+        }
+        if self.config_files.iter().all(|f| f.trim().is_empty()) {
+            anyhow::bail!("config_files 至少需要一个配置文件");
+        }
+        if let Some(protocol) = &self.proxy_protocol {
+            if !PROXY_PROTOCOLS.contains(&protocol.as_str()) {
+                anyhow::bail!(
+                    "不支持的 proxy_protocol: {}（可选 {}）",
+                    protocol,
+                    PROXY_PROTOCOLS.join(" / ")
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 自定义工具定义目录（`~/.duckcoding/tools.d`）
+pub fn custom_tools_dir() -> Result<PathBuf> {
+    let dir = config_dir().map_err(|e| anyhow::anyhow!(e))?;
+    Ok(dir.join(CUSTOM_TOOLS_DIR))
+}
+
+/// 当前已加载的自定义工具
+pub fn custom_tools() -> Vec<CustomToolDefinition> {
+    CUSTOM_TOOLS
+        .read()
+        .map(|report| report.tools.clone())
+        .unwrap_or_default()
+}
+
+/// 按 ID 查找自定义工具
+pub fn find_custom_tool(tool_id: &str) -> Option<CustomToolDefinition> {
+    CUSTOM_TOOLS
+        .read()
+        .ok()?
+        .tools
+        .iter()
+        .find(|t| t.id == tool_id)
+        .cloned()
+}
+
+/// 最近一次加载的结果（含失败的定义文件）
+pub fn custom_tools_report() -> CustomToolsReport {
+    CUSTOM_TOOLS
+        .read()
+        .map(|report| report.clone())
+        .unwrap_or_default()
+}
+
+/// 重新读取定义目录（新建的 Detector 注册表与工具列表随之更新）
+pub fn reload_custom_tools() -> CustomToolsReport {
+    let report = load_from_disk();
+    if let Ok(mut current) = CUSTOM_TOOLS.write() {
+        *current = report.clone();
+    }
+    report
+}
+
+fn load_from_disk() -> CustomToolsReport {
+    match custom_tools_dir() {
+        Ok(dir) => load_dir(&dir),
+        Err(e) => {
+            tracing::warn!(error = ?e, "无法确定自定义工具目录");
+            CustomToolsReport::default()
+        }
+    }
+}
+
+/// 读取目录下全部 `*.json` 定义（按文件名排序，重复 ID 以先出现的为准）
+fn load_dir(dir: &Path) -> CustomToolsReport {
+    let mut report = CustomToolsReport {
+        dir: dir.display().to_string(),
+        ..Default::default()
+    };
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return report;
+    };
+
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        match parse_definition(&path) {
+            Ok(def) if report.tools.iter().any(|t| t.id == def.id) => {
+                report.errors.push(CustomToolLoadError {
+                    path: path.display().to_string(),
+                    error: format!("工具 ID {} 重复", def.id),
+                });
+            }
+            Ok(def) => report.tools.push(def),
+            Err(e) => report.errors.push(CustomToolLoadError {
+                path: path.display().to_string(),
+                error: format!("{e:#}"),
+            }),
+        }
+    }
+
+    for error in &report.errors {
+        tracing::warn!(path = %error.path, error = %error.error, "自定义工具定义无效");
+    }
+    if !report.tools.is_empty() {
+        tracing::info!(count = report.tools.len(), "已加载自定义工具");
+    }
+    report
+}
+
+fn parse_definition(path: &Path) -> Result<CustomToolDefinition> {
+    let content = std::fs::read_to_string(path).context("读取定义文件失败")?;
+    let def: CustomToolDefinition = serde_json::from_str(&content).context("解析 JSON 失败")?;
+    def.validate()?;
+    Ok(def)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(dir: &Path, name: &str, content: &str) {
+        std::fs::write(dir.join(name), content).unwrap();
+    }
+
+    #[test]
+    fn test_load_dir_validates_definitions() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "a-aider.json",
+            r#"{
+                "id": "aider",
+                "name": "Aider",
+                "check_command": "aider --version",
+                "config_dir": "~",
+                "config_files": [".aider.conf.yml"],
+                "env_vars": { "api_key": "OPENAI_API_KEY", "base_url": "OPENAI_API_BASE" },
+                "proxy_protocol": "openai"
+            }"#,
+        );
+        write(
+            dir.path(),
+            "b-duplicate.json",
+            r#"{"id":"aider","name":"Aider 2","check_command":"aider --version",
+                "config_dir":"~","config_files":["x"],"env_vars":{"api_key":"K","base_url":"U"}}"#,
+        );
+        write(
+            dir.path(),
+            "c-builtin.json",
+            r#"{"id":"codex","name":"X","check_command":"x","config_dir":"~",
+                "config_files":["x"],"env_vars":{"api_key":"K","base_url":"U"}}"#,
+        );
+        write(
+            dir.path(),
+            "d-protocol.json",
+            r#"{"id":"x","name":"X","check_command":"x","config_dir":"~",
+                "config_files":["x"],"env_vars":{"api_key":"K","base_url":"U"},
+                "proxy_protocol":"grpc"}"#,
+        );
+        write(dir.path(), "e-broken.json", "{");
+        write(dir.path(), "readme.txt", "ignored");
+
+        let report = load_dir(dir.path());
+        assert_eq!(report.tools.len(), 1);
+        let aider = &report.tools[0];
+        assert_eq!(aider.config_file(), ".aider.conf.yml");
+        assert!(aider.npm_package.is_none());
+        assert!(aider.use_proxy_for_version_check);
+        assert_eq!(report.errors.len(), 4);
+        assert!(report.errors[0].error.contains("重复"));
+        assert!(report.errors[1].error.contains("内置工具"));
+        assert!(report.errors[2].error.contains("proxy_protocol"));
+    }
+
+    #[test]
+    fn test_missing_dir_is_empty() {
+        let report = load_dir(Path::new("/nonexistent/duckcoding/tools.d"));
+        assert!(report.tools.is_empty());
+        assert!(report.errors.is_empty());
+    }
+}
@@ -2,14 +2,51 @@
 //
 // 从 SQLite 迁移到 JSON 文件，支持版本控制和多端同步
 
+use crate::data::schema::{self, add_column_if_missing, SchemaMigration};
 use crate::data::DataManager;
 use crate::models::{ToolInstance, ToolType};
 use crate::services::tool::tools_config::{
-    LocalToolInstance, SSHToolInstance, ToolsConfig, WSLToolInstance,
+    InstalledVersionRecord, LocalToolInstance, SSHToolInstance, ToolsConfig, WSLToolInstance,
 };
 use anyhow::{Context, Result};
 use std::path::PathBuf;
 
+/// 旧版 SQLite 工具实例表（迁移读取时确保存在）
+const LEGACY_TABLE_SQL: &str = "CREATE TABLE IF NOT EXISTS tool_instances (
+    instance_id TEXT PRIMARY KEY,
+    base_id TEXT NOT NULL,
+    tool_name TEXT NOT NULL,
+    tool_type TEXT NOT NULL,
+    installed INTEGER NOT NULL DEFAULT 0,
+    version TEXT,
+    install_path TEXT
+)";
+
+/// 旧版 SQLite 工具实例表的 Schema 迁移步骤
+const LEGACY_MIGRATIONS: &[SchemaMigration] = &[SchemaMigration {
+    version: 1,
+    name: "add_legacy_missing_columns",
+    up: |tx| {
+        for (column, definition) in [
+            ("wsl_distro", "TEXT"),
+            ("ssh_display_name", "TEXT"),
+            ("ssh_host", "TEXT"),
+            ("ssh_port", "INTEGER"),
+            ("ssh_user", "TEXT"),
+            ("ssh_key_path", "TEXT"),
+            ("is_builtin", "INTEGER NOT NULL DEFAULT 0"),
+            ("created_at", "INTEGER NOT NULL DEFAULT 0"),
+            ("updated_at", "INTEGER NOT NULL DEFAULT 0"),
+        ] {
+            add_column_if_missing(tx, "tool_instances", column, definition)?;
+        }
+        Ok(())
+    },
+}];
+
+/// 每个实例最多保留的版本历史条数
+const MAX_VERSION_HISTORY: usize = 20;
+
 /// 工具实例数据库管理（JSON 存储）
 pub struct ToolInstanceDB {
     config_path: PathBuf,
@@ -110,6 +147,8 @@ impl ToolInstanceDB {
                     is_builtin: instance.is_builtin,
                     created_at: instance.created_at,
                     updated_at: instance.updated_at,
+                    pinned_version: None,
+                    version_history: Vec::new(),
                 });
             }
             ToolType::WSL => {
@@ -302,6 +341,87 @@ impl ToolInstanceDB {
             .collect())
     }
 
+    /// 记录本地实例的已安装版本（写入版本历史并更新当前版本）
+    ///
+    /// 与最近一条记录版本相同时只刷新时间，历史最多保留 `MAX_VERSION_HISTORY` 条
+    pub fn record_installed_version(
+        &self,
+        tool_id: &str,
+        version: &str,
+        source: &str,
+    ) -> Result<()> {
+        let mut config = self.load_config()?;
+        let local = Self::local_tool_mut(&mut config, tool_id)?;
+        let now = chrono::Utc::now().timestamp();
+
+        let record = InstalledVersionRecord {
+            version: version.to_string(),
+            installed_at: now,
+            source: source.to_string(),
+        };
+        if local
+            .version_history
+            .first()
+            .is_some_and(|latest| latest.version == version)
+        {
+            local.version_history[0] = record;
+        } else {
+            local.version_history.insert(0, record);
+            local.version_history.truncate(MAX_VERSION_HISTORY);
+        }
+        local.version = Some(version.to_string());
+        local.installed = true;
+        local.updated_at = now;
+
+        config.updated_at = chrono::Utc::now().to_rfc3339();
+        self.save_config(&config)
+    }
+
+    /// 获取本地实例的版本历史（最新在前）
+    pub fn get_version_history(&self, tool_id: &str) -> Result<Vec<InstalledVersionRecord>> {
+        let config = self.load_config()?;
+        Ok(Self::local_tool(&config, tool_id)
+            .map(|local| local.version_history.clone())
+            .unwrap_or_default())
+    }
+
+    /// 获取本地实例固定的版本
+    pub fn get_pinned_version(&self, tool_id: &str) -> Result<Option<String>> {
+        let config = self.load_config()?;
+        Ok(Self::local_tool(&config, tool_id).and_then(|local| local.pinned_version.clone()))
+    }
+
+    /// 设置或取消（None）本地实例固定的版本
+    pub fn set_pinned_version(&self, tool_id: &str, version: Option<String>) -> Result<()> {
+        let mut config = self.load_config()?;
+        let local = Self::local_tool_mut(&mut config, tool_id)?;
+        local.pinned_version = version;
+        local.updated_at = chrono::Utc::now().timestamp();
+
+        config.updated_at = chrono::Utc::now().to_rfc3339();
+        self.save_config(&config)
+    }
+
+    fn local_tool<'a>(config: &'a ToolsConfig, tool_id: &str) -> Option<&'a LocalToolInstance> {
+        config
+            .tools
+            .iter()
+            .find(|g| g.id == tool_id)
+            .and_then(|g| g.local_tools.first())
+    }
+
+    fn local_tool_mut<'a>(
+        config: &'a mut ToolsConfig,
+        tool_id: &str,
+    ) -> Result<&'a mut LocalToolInstance> {
+        config
+            .tools
+            .iter_mut()
+            .find(|g| g.id == tool_id)
+            .and_then(|g| g.local_tools.first_mut())
+            .ok_or_else(|| anyhow::anyhow!("未找到工具 {} 的本地实例", tool_id))
+    }
+
     /// 从 SQLite 迁移到 JSON（一次性迁移）
     pub fn migrate_from_sqlite(&self) -> Result<()> {
         use rusqlite::Connection;
@@ -316,7 +436,11 @@ impl ToolInstanceDB {
 
         tracing::info!("开始从 SQLite 迁移到 JSON");
 
-        let conn = Connection::open(&old_db_path)?;
+        let mut conn = Connection::open(&old_db_path)?;
+
+        // 早期版本的表缺少后加的列，先补齐再读取
+        conn.execute_batch(LEGACY_TABLE_SQL)?;
+        schema::migrate(&mut conn, &old_db_path, LEGACY_MIGRATIONS)?;
 
         // 读取所有实例数据
         let mut stmt = conn.prepare(
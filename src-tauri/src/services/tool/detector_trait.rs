@@ -143,84 +143,10 @@ pub trait ToolDetector: Send + Sync {
     /// 默认实现：移除所有代理环境变量
     async fn execute_without_proxy(
         &self,
-        _executor: &CommandExecutor,
+        executor: &CommandExecutor,
         command: &str,
     ) -> crate::utils::CommandResult {
-        use crate::utils::platform::PlatformInfo;
-        use std::process::Command;
-
-        #[cfg(target_os = "windows")]
-        use std::os::windows::process::CommandExt;
-
-        let command_str = command.to_string();
-        let platform = PlatformInfo::current();
-
-        tokio::task::spawn_blocking(move || {
-            let enhanced_path = platform.build_enhanced_path();
-
-            let output = if platform.is_windows {
-                #[cfg(target_os = "windows")]
-                {
-                    Command::new("cmd")
-                        .args(["/C", &command_str])
-                        .creation_flags(0x08000000) // CREATE_NO_WINDOW
-                        .env("PATH", &enhanced_path)
-                        .env_remove("HTTP_PROXY")
-                        .env_remove("HTTPS_PROXY")
-                        .env_remove("ALL_PROXY")
-                        .env_remove("http_proxy")
-                        .env_remove("https_proxy")
-                        .env_remove("all_proxy")
-                        .output()
-                }
-                #[cfg(not(target_os = "windows"))]
-                {
-                    Command::new("cmd")
-                        .args(["/C", &command_str])
-                        .env("PATH", &enhanced_path)
-                        .env_remove("HTTP_PROXY")
-                        .env_remove("HTTPS_PROXY")
-                        .env_remove("ALL_PROXY")
-                        .env_remove("http_proxy")
-                        .env_remove("https_proxy")
-                        .env_remove("all_proxy")
-                        .output()
-                }
-            } else {
-                Command::new("sh")
-                    .args(["-c", &command_str])
-                    .env("PATH", &enhanced_path)
-                    .env_remove("HTTP_PROXY")
-                    .env_remove("HTTPS_PROXY")
-                    .env_remove("ALL_PROXY")
-                    .env_remove("http_proxy")
-                    .env_remove("https_proxy")
-                    .env_remove("all_proxy")
-                    .output()
-            };
-
-            match output {
-                Ok(output) => crate::utils::CommandResult {
-                    success: output.status.success(),
-                    stdout: String::from_utf8_lossy(&output.stdout).to_string(),
-                    stderr: String::from_utf8_lossy(&output.stderr).to_string(),
-                    exit_code: output.status.code(),
-                },
-                Err(e) => crate::utils::CommandResult {
-                    success: false,
-                    stdout: String::new(),
-                    stderr: e.to_string(),
-                    exit_code: None,
-                },
-            }
-        })
-        .await
-        .unwrap_or_else(|_| crate::utils::CommandResult {
-            success: false,
-            stdout: String::new(),
-            stderr: "执行失败".to_string(),
-            exit_code: None,
-        })
+        executor.execute_without_proxy_async(command).await
     }
 
     /// 默认版本号提取逻辑
@@ -4,7 +4,9 @@
 
 use super::super::detector_trait::ToolDetector;
 use crate::data::DataManager;
-use crate::models::InstallMethod;
+use crate::models::{InstallMethod, Tool};
+use crate::services::environment::explain_npm_missing;
+use crate::services::tool::user_prefix::{npm_install_global, npm_update_global};
 use crate::services::version::{VersionInfo, VersionService};
 use crate::utils::CommandExecutor;
 use anyhow::Result;
@@ -22,9 +24,8 @@ pub struct ClaudeCodeDetector {
 
 impl ClaudeCodeDetector {
     pub fn new() -> Self {
-        let home_dir = dirs::home_dir().expect("无法获取用户主目录");
         Self {
-            config_dir: home_dir.join(".claude"),
+            config_dir: Tool::resolve_config_dir("claude-code").config_dir,
         }
     }
 
@@ -186,9 +187,9 @@ impl ClaudeCodeDetector {
                         "{ps_exe} -NoProfile -ExecutionPolicy Bypass -OutputEncoding UTF8 -Command \"[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; irm https://mirror.duckcoding.com/claude-code/install.ps1 | iex\""
                     )
                 } else {
-                    // PowerShell 5 不支持 -OutputEncoding
+                    // PowerShell 5 不支持 -OutputEncoding（执行器已切换 UTF-8 代码页）
                     format!(
-                        "cmd /C \"chcp 65001 >nul && {ps_exe} -NoProfile -ExecutionPolicy Bypass -Command \\\"[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; irm https://mirror.duckcoding.com/claude-code/install.ps1 | iex\\\"\""
+                        "{ps_exe} -NoProfile -ExecutionPolicy Bypass -Command \"[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; irm https://mirror.duckcoding.com/claude-code/install.ps1 | iex\""
                     )
                 }
             }
@@ -213,7 +214,7 @@ impl ClaudeCodeDetector {
     /// 使用 npm 安装
     async fn install_npm(&self, executor: &CommandExecutor, force: bool) -> Result<()> {
         if !executor.command_exists_async("npm").await {
-            anyhow::bail!("npm 未安装，请先安装 Node.js");
+            anyhow::bail!(explain_npm_missing().await);
         }
 
         // 获取推荐版本
@@ -235,28 +236,15 @@ impl ClaudeCodeDetector {
             _ => "@anthropic-ai/claude-code@latest".to_string(),
         };
 
-        let command =
-            format!("npm install -g {package_spec} --registry https://registry.npmmirror.com");
-        let result = executor.execute_async(&command).await;
-
-        if result.success {
-            Ok(())
-        } else {
-            anyhow::bail!("❌ npm 安装失败\n\n{}", result.stderr)
-        }
+        // 全局目录无权限时自动回退到用户级目录
+        npm_install_global(executor, &package_spec).await?;
+        Ok(())
     }
 
     /// 使用 npm 更新
     async fn update_npm(&self, executor: &CommandExecutor) -> Result<()> {
-        let command =
-            "npm update -g @anthropic-ai/claude-code --registry https://registry.npmmirror.com";
-        let result = executor.execute_async(command).await;
-
-        if result.success {
-            Ok(())
-        } else {
-            anyhow::bail!("❌ npm 更新失败\n\n{}", result.stderr)
-        }
+        npm_update_global(executor, "@anthropic-ai/claude-code").await?;
+        Ok(())
     }
 
     /// 转换为旧版 Tool 结构（用于兼容 VersionService）
@@ -4,7 +4,9 @@
 
 use super::super::detector_trait::ToolDetector;
 use crate::data::DataManager;
-use crate::models::InstallMethod;
+use crate::models::{InstallMethod, Tool};
+use crate::services::environment::explain_npm_missing;
+use crate::services::tool::user_prefix::{npm_install_global, npm_update_global};
 use crate::services::version::{VersionInfo, VersionService};
 use crate::utils::CommandExecutor;
 use anyhow::Result;
@@ -19,9 +21,8 @@ pub struct CodeXDetector {
 
 impl CodeXDetector {
     pub fn new() -> Self {
-        let home_dir = dirs::home_dir().expect("无法获取用户主目录");
         Self {
-            config_dir: home_dir.join(".codex"),
+            config_dir: Tool::resolve_config_dir("codex").config_dir,
         }
     }
 }
@@ -189,7 +190,7 @@ impl CodeXDetector {
     /// 使用 npm 安装
     async fn install_npm(&self, executor: &CommandExecutor, force: bool) -> Result<()> {
         if !executor.command_exists_async("npm").await {
-            anyhow::bail!("npm 未安装");
+            anyhow::bail!(explain_npm_missing().await);
         }
 
         let version_hint = if !force {
@@ -208,15 +209,9 @@ impl CodeXDetector {
             _ => "@openai/codex@latest".to_string(),
         };
 
-        let command =
-            format!("npm install -g {package_spec} --registry https://registry.npmmirror.com");
-        let result = executor.execute_async(&command).await;
-
-        if result.success {
-            Ok(())
-        } else {
-            anyhow::bail!("❌ npm 安装失败\n\n{}", result.stderr)
-        }
+        // 全局目录无权限时自动回退到用户级目录
+        npm_install_global(executor, &package_spec).await?;
+        Ok(())
     }
 
     /// 使用 Homebrew 安装
@@ -241,14 +236,8 @@ impl CodeXDetector {
 
     /// 使用 npm 更新
     async fn update_npm(&self, executor: &CommandExecutor) -> Result<()> {
-        let command = "npm update -g @openai/codex --registry https://registry.npmmirror.com";
-        let result = executor.execute_async(command).await;
-
-        if result.success {
-            Ok(())
-        } else {
-            anyhow::bail!("❌ npm 更新失败\n\n{}", result.stderr)
-        }
+        npm_update_global(executor, "@openai/codex").await?;
+        Ok(())
     }
 
     /// 使用 Homebrew 更新
@@ -0,0 +1,211 @@
+// Custom Tool Detector
+//
+// 用户自定义工具（tools.d/*.json）的通用检测、安装、配置管理实现
+
+use super::super::custom_tools::CustomToolDefinition;
+use super::super::detector_trait::ToolDetector;
+use crate::data::DataManager;
+use crate::models::{InstallMethod, Tool};
+use crate::services::environment::explain_npm_missing;
+use crate::services::tool::user_prefix::{npm_install_global, npm_update_global};
+use crate::utils::CommandExecutor;
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// 自定义工具检测器（仅支持 npm 安装/更新，配置按文件扩展名读写）
+pub struct CustomToolDetector {
+    definition: CustomToolDefinition,
+    config_dir: PathBuf,
+}
+
+impl CustomToolDetector {
+    pub fn new(definition: CustomToolDefinition) -> Self {
+        let config_dir = Tool::resolve_config_dir(&definition.id).config_dir;
+        Self {
+            definition,
+            config_dir,
+        }
+    }
+
+    fn npm_package_required(&self) -> Result<&str> {
+        self.definition
+            .npm_package
+            .as_deref()
+            .filter(|p| !p.is_empty())
+            .ok_or_else(|| {
+                anyhow::anyhow!("{} 未配置 npm_package，请手动安装", self.definition.name)
+            })
+    }
+}
+
+#[async_trait]
+impl ToolDetector for CustomToolDetector {
+    // ==================== 基础信息 ====================
+
+    fn tool_id(&self) -> &str {
+        &self.definition.id
+    }
+
+    fn tool_name(&self) -> &str {
+        &self.definition.name
+    }
+
+    fn config_dir(&self) -> PathBuf {
+        self.config_dir.clone()
+    }
+
+    fn config_file(&self) -> &str {
+        self.definition.config_file()
+    }
+
+    fn npm_package(&self) -> &str {
+        self.definition.npm_package.as_deref().unwrap_or_default()
+    }
+
+    fn check_command(&self) -> &str {
+        &self.definition.check_command
+    }
+
+    fn use_proxy_for_version_check(&self) -> bool {
+        self.definition.use_proxy_for_version_check
+    }
+
+    // ==================== 检测逻辑 ====================
+
+    async fn detect_install_method(&self, executor: &CommandExecutor) -> Option<InstallMethod> {
+        let package = self.npm_package();
+        if !package.is_empty() && executor.command_exists_async("npm").await {
+            let stderr_redirect = if cfg!(windows) {
+                "2>nul"
+            } else {
+                "2>/dev/null"
+            };
+            let cmd = format!("npm list -g {package} {stderr_redirect}");
+            if executor.execute_async(&cmd).await.success {
+                return Some(InstallMethod::Npm);
+            }
+        }
+        Some(InstallMethod::Other)
+    }
+
+    // ==================== 安装逻辑 ====================
+
+    async fn install(
+        &self,
+        executor: &CommandExecutor,
+        method: &InstallMethod,
+        _force: bool,
+    ) -> Result<()> {
+        if *method != InstallMethod::Npm {
+            anyhow::bail!("自定义工具仅支持通过 npm 安装");
+        }
+        let package = self.npm_package_required()?;
+        if !executor.command_exists_async("npm").await {
+            anyhow::bail!(explain_npm_missing().await);
+        }
+        npm_install_global(executor, &format!("{package}@latest")).await?;
+        Ok(())
+    }
+
+    async fn update(&self, executor: &CommandExecutor, _force: bool) -> Result<()> {
+        let package = self.npm_package_required()?;
+        npm_update_global(executor, package).await?;
+        Ok(())
+    }
+
+    // ==================== 配置管理 ====================
+
+    async fn read_config(&self, manager: &DataManager) -> Result<Value> {
+        let config_path = self.config_dir.join(self.config_file());
+
+        match config_format(self.config_file()) {
+            ConfigFormat::Json => Ok(manager.json_uncached().read(&config_path)?),
+            ConfigFormat::Toml => {
+                let toml_value = manager.toml().read(&config_path)?;
+                Ok(serde_json::to_value(toml_value)?)
+            }
+            ConfigFormat::Env => {
+                let pairs = manager.env().read(&config_path)?;
+                Ok(serde_json::to_value(pairs)?)
+            }
+        }
+    }
+
+    async fn save_config(&self, manager: &DataManager, config: Value) -> Result<()> {
+        let config_path = self.config_dir.join(self.config_file());
+
+        match config_format(self.config_file()) {
+            ConfigFormat::Json => manager.json_uncached().write(&config_path, &config)?,
+            ConfigFormat::Toml => {
+                // 仅更新顶层标量键，保留原有注释和格式
+                let mut doc = manager.toml().read_document(&config_path)?;
+                for (key, value) in config.as_object().into_iter().flatten() {
+                    match value {
+                        Value::String(s) => doc[key] = toml_edit::value(s.clone()),
+                        Value::Bool(b) => doc[key] = toml_edit::value(*b),
+                        Value::Number(n) => {
+                            if let Some(i) = n.as_i64() {
+                                doc[key] = toml_edit::value(i);
+                            } else if let Some(f) = n.as_f64() {
+                                doc[key] = toml_edit::value(f);
+                            }
+                        }
+                        _ => tracing::warn!(key = %key, "TOML 配置仅支持写入标量值，已跳过"),
+                    }
+                }
+                manager.toml().write(&config_path, &doc)?;
+            }
+            ConfigFormat::Env => {
+                let pairs: HashMap<String, String> = config
+                    .as_object()
+                    .into_iter()
+                    .flatten()
+                    .map(|(key, value)| {
+                        let value = match value {
+                            Value::String(s) => s.clone(),
+                            other => other.to_string(),
+                        };
+                        (key.clone(), value)
+                    })
+                    .collect();
+                manager.env().write(&config_path, &pairs)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 配置文件格式（按扩展名判断）
+#[derive(Debug, PartialEq, Eq)]
+enum ConfigFormat {
+    Json,
+    Toml,
+    Env,
+}
+
+fn config_format(file_name: &str) -> ConfigFormat {
+    let lower = file_name.to_ascii_lowercase();
+    if lower.ends_with(".toml") {
+        ConfigFormat::Toml
+    } else if lower == ".env" || lower.ends_with(".env") {
+        ConfigFormat::Env
+    } else {
+        ConfigFormat::Json
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_format() {
+        assert_eq!(config_format("opencode.json"), ConfigFormat::Json);
+        assert_eq!(config_format("config.TOML"), ConfigFormat::Toml);
+        assert_eq!(config_format(".env"), ConfigFormat::Env);
+        assert_eq!(config_format("aider.env"), ConfigFormat::Env);
+    }
+}
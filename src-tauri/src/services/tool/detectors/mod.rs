@@ -4,10 +4,12 @@
 
 mod claude_code;
 mod codex;
+mod custom;
 mod gemini_cli;
 
 pub use claude_code::ClaudeCodeDetector;
 pub use codex::CodeXDetector;
+pub use custom::CustomToolDetector;
 pub use gemini_cli::GeminiCLIDetector;
 
 use super::detector_trait::ToolDetector;
@@ -22,7 +24,7 @@ pub struct DetectorRegistry {
 }
 
 impl DetectorRegistry {
-    /// 创建新的注册表并注册所有内置工具
+    /// 创建新的注册表并注册所有内置工具与自定义工具
     pub fn new() -> Self {
         let mut registry = Self {
             detectors: HashMap::new(),
@@ -33,6 +35,11 @@ impl DetectorRegistry {
         registry.register(Arc::new(CodeXDetector::new()));
         registry.register(Arc::new(GeminiCLIDetector::new()));
 
+        // 注册 tools.d 中的自定义工具
+        for definition in super::custom_tools::custom_tools() {
+            registry.register(Arc::new(CustomToolDetector::new(definition)));
+        }
+
         tracing::debug!(
             "Detector 注册表初始化完成，已注册 {} 个工具",
             registry.detectors.len()
@@ -0,0 +1,282 @@
+//! 安装/更新历史
+//!
+//! 每次安装或更新都会记录完整的执行过程（执行的命令、输出、耗时、结果），
+//! 保存在 `~/.duckcoding/install_history.db`，便于用户查看失败原因、
+//! 原样重新执行同一操作，并在反馈问题时附带可复现的记录。
+
+use crate::data::managers::sqlite::QueryRow;
+use crate::data::DataManager;
+use crate::models::InstallMethod;
+use crate::utils::config::config_dir;
+use crate::utils::{CommandExecutor, CommandTranscriptStep};
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Instant;
+
+/// 最多保留的历史记录数
+const MAX_HISTORY_ENTRIES: usize = 200;
+
+const CREATE_TABLE_SQL: &str = "
+CREATE TABLE IF NOT EXISTS install_history (
+    id TEXT PRIMARY KEY,
+    tool_id TEXT NOT NULL,
+    operation TEXT NOT NULL,
+    started_at INTEGER NOT NULL,
+    duration_ms INTEGER NOT NULL,
+    success INTEGER NOT NULL,
+    error TEXT,
+    steps TEXT NOT NULL,
+    os TEXT NOT NULL,
+    arch TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_install_history_started_at ON install_history(started_at);
+";
+
+const SELECT_FIELDS: &str =
+    "id, tool_id, operation, started_at, duration_ms, success, error, steps, os, arch";
+
+/// 安装/更新操作（包含原样重新执行所需的全部参数）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum InstallOperation {
+    /// 安装工具
+    Install {
+        tool_id: String,
+        method: InstallMethod,
+        force: bool,
+    },
+    /// 更新工具实例
+    UpdateInstance {
+        tool_id: String,
+        instance_id: String,
+        force: bool,
+    },
+    /// 通过 npm 安装指定版本（固定版本/回滚）
+    InstallVersion { tool_id: String, version: String },
+}
+
+impl InstallOperation {
+    pub fn tool_id(&self) -> &str {
+        match self {
+            InstallOperation::Install { tool_id, .. }
+            | InstallOperation::UpdateInstance { tool_id, .. }
+            | InstallOperation::InstallVersion { tool_id, .. } => tool_id,
+        }
+    }
+}
+
+/// 单次安装/更新记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstallAttempt {
+    pub id: String,
+    pub operation: InstallOperation,
+    /// 开始时间（Unix 时间戳，毫秒）
+    pub started_at: i64,
+    pub duration_ms: u64,
+    pub success: bool,
+    pub error: Option<String>,
+    /// 按执行顺序记录的命令
+    pub steps: Vec<CommandTranscriptStep>,
+    pub os: String,
+    pub arch: String,
+}
+
+/// 安装/更新历史存储
+pub struct InstallHistory {
+    db_path: PathBuf,
+}
+
+impl InstallHistory {
+    /// 使用默认路径创建
+    pub fn new() -> Result<Self> {
+        let dir = config_dir().map_err(|e| anyhow!("获取配置目录失败: {}", e))?;
+        Ok(Self::with_path(dir.join("install_history.db")))
+    }
+
+    /// 使用指定数据库路径创建
+    pub fn with_path(db_path: PathBuf) -> Self {
+        Self { db_path }
+    }
+
+    fn init_table(&self) -> Result<()> {
+        DataManager::global()
+            .sqlite(&self.db_path)?
+            .execute_raw(CREATE_TABLE_SQL)
+            .context("创建安装历史表失败")
+    }
+
+    /// 保存一条记录（超出上限时删除最旧的记录）
+    pub fn record(&self, attempt: &InstallAttempt) -> Result<()> {
+        self.init_table()?;
+        let db = DataManager::global().sqlite(&self.db_path)?;
+        let operation = serde_json::to_string(&attempt.operation)?;
+        let steps = serde_json::to_string(&attempt.steps)?;
+        let started_at = attempt.started_at.to_string();
+        let duration_ms = attempt.duration_ms.to_string();
+        let success = if attempt.success { "1" } else { "0" };
+
+        db.execute(
+            "INSERT INTO install_history
+             (id, tool_id, operation, started_at, duration_ms, success, error, steps, os, arch)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            &[
+                &attempt.id,
+                attempt.operation.tool_id(),
+                &operation,
+                &started_at,
+                &duration_ms,
+                success,
+                attempt.error.as_deref().unwrap_or(""),
+                &steps,
+                &attempt.os,
+                &attempt.arch,
+            ],
+        )?;
+        db.execute(
+            "DELETE FROM install_history WHERE id NOT IN
+             (SELECT id FROM install_history ORDER BY started_at DESC LIMIT ?1)",
+            &[&MAX_HISTORY_ENTRIES.to_string()],
+        )?;
+        Ok(())
+    }
+
+    /// 按时间倒序列出记录（可按工具过滤）
+    pub fn list(&self, tool_id: Option<&str>, limit: usize) -> Result<Vec<InstallAttempt>> {
+        self.init_table()?;
+        let db = DataManager::global().sqlite(&self.db_path)?;
+        let limit = limit.to_string();
+        let rows = match tool_id {
+            Some(tool_id) => db.query(
+                &format!(
+                    "SELECT {SELECT_FIELDS} FROM install_history WHERE tool_id = ?1
+                     ORDER BY started_at DESC LIMIT ?2"
+                ),
+                &[tool_id, &limit],
+            )?,
+            None => db.query(
+                &format!(
+                    "SELECT {SELECT_FIELDS} FROM install_history ORDER BY started_at DESC LIMIT ?1"
+                ),
+                &[&limit],
+            )?,
+        };
+        rows.iter().map(parse_row).collect()
+    }
+
+    /// 获取单条记录
+    pub fn get(&self, id: &str) -> Result<InstallAttempt> {
+        self.init_table()?;
+        let rows = DataManager::global().sqlite(&self.db_path)?.query(
+            &format!("SELECT {SELECT_FIELDS} FROM install_history WHERE id = ?1"),
+            &[id],
+        )?;
+        rows.first()
+            .map(parse_row)
+            .transpose()?
+            .ok_or_else(|| anyhow!("安装记录不存在: {}", id))
+    }
+
+    /// 清空历史
+    pub fn clear(&self) -> Result<usize> {
+        self.init_table()?;
+        Ok(DataManager::global()
+            .sqlite(&self.db_path)?
+            .execute("DELETE FROM install_history", &[])?)
+    }
+}
+
+fn parse_row(row: &QueryRow) -> Result<InstallAttempt> {
+    let text = |i: usize| row.values.get(i).and_then(|v| v.as_str()).unwrap_or("");
+    let int = |i: usize| row.values.get(i).and_then(|v| v.as_i64()).unwrap_or(0);
+
+    Ok(InstallAttempt {
+        id: text(0).to_string(),
+        operation: serde_json::from_str(text(2)).context("解析安装操作失败")?,
+        started_at: int(3),
+        duration_ms: int(4).max(0) as u64,
+        success: int(5) != 0,
+        error: Some(text(6).to_string()).filter(|e| !e.is_empty()),
+        steps: serde_json::from_str(text(7)).unwrap_or_default(),
+        os: text(8).to_string(),
+        arch: text(9).to_string(),
+    })
+}
+
+/// 按执行器记录的过程保存一次安装/更新（保存失败只记日志，不影响操作结果）
+pub fn record_attempt<T>(
+    operation: InstallOperation,
+    executor: &CommandExecutor,
+    started: Instant,
+    result: &Result<T>,
+) {
+    let duration_ms = started.elapsed().as_millis() as u64;
+    let attempt = InstallAttempt {
+        id: uuid::Uuid::new_v4().to_string(),
+        operation,
+        started_at: chrono::Utc::now().timestamp_millis() - duration_ms as i64,
+        duration_ms,
+        success: result.is_ok(),
+        error: result.as_ref().err().map(|e| format!("{:#}", e)),
+        steps: executor.transcript(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+    };
+
+    if let Err(e) = InstallHistory::new().and_then(|history| history.record(&attempt)) {
+        tracing::warn!(error = ?e, "保存安装记录失败");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_list() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = InstallHistory::with_path(dir.path().join("install_history.db"));
+
+        for (i, success) in [true, false].into_iter().enumerate() {
+            history
+                .record(&InstallAttempt {
+                    id: format!("attempt-{i}"),
+                    operation: InstallOperation::Install {
+                        tool_id: "codex".to_string(),
+                        method: InstallMethod::Npm,
+                        force: false,
+                    },
+                    started_at: 1_000 + i as i64,
+                    duration_ms: 42,
+                    success,
+                    error: (!success).then(|| "npm 安装失败".to_string()),
+                    steps: vec![CommandTranscriptStep {
+                        command: "npm install -g @openai/codex".to_string(),
+                        success,
+                        exit_code: Some(if success { 0 } else { 1 }),
+                        stdout: String::new(),
+                        stderr: "EACCES".to_string(),
+                        duration_ms: 40,
+                    }],
+                    os: "linux".to_string(),
+                    arch: "x86_64".to_string(),
+                })
+                .unwrap();
+        }
+
+        let attempts = history.list(Some("codex"), 10).unwrap();
+        assert_eq!(attempts.len(), 2);
+        assert_eq!(attempts[0].id, "attempt-1");
+        assert!(!attempts[0].success);
+        assert_eq!(attempts[0].error.as_deref(), Some("npm 安装失败"));
+        assert_eq!(attempts[0].steps[0].stderr, "EACCES");
+        assert!(history.list(Some("claude-code"), 10).unwrap().is_empty());
+
+        let attempt = history.get("attempt-0").unwrap();
+        assert!(attempt.success && attempt.error.is_none());
+        assert_eq!(attempt.operation.tool_id(), "codex");
+
+        assert_eq!(history.clear().unwrap(), 2);
+    }
+}
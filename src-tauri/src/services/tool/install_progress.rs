@@ -0,0 +1,291 @@
+//! 安装进度
+//!
+//! 安装过程中逐行读取子进程输出，转换为进度事件（阶段、可解析时的百分比、日志行），
+//! 由命令层转发为 `install-progress` 前端事件；同时登记进行中的安装任务，
+//! 支持按 install_id 取消。
+
+use crate::utils::{OutputObserver, OutputStream};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio_util::sync::CancellationToken;
+
+/// 前端事件名
+pub const INSTALL_PROGRESS_EVENT: &str = "install-progress";
+
+/// 安装阶段（按先后顺序，阶段只前进不回退）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstallPhase {
+    Preparing,
+    Downloading,
+    Installing,
+    Verifying,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl InstallPhase {
+    fn is_terminal(self) -> bool {
+        matches!(self, Self::Completed | Self::Failed | Self::Cancelled)
+    }
+}
+
+/// 安装进度事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstallProgressEvent {
+    pub install_id: String,
+    pub tool_id: String,
+    pub phase: InstallPhase,
+    /// 进度百分比（输出中无法解析时为 None）
+    pub percent: Option<u8>,
+    /// 子进程输出的一行日志（阶段变化事件为 None）
+    pub line: Option<String>,
+    pub stream: Option<OutputStream>,
+}
+
+/// 进度事件回调
+pub type InstallProgressCallback = Arc<dyn Fn(InstallProgressEvent) + Send + Sync>;
+
+/// install_id → 取消令牌（进行中的安装任务）
+static ACTIVE_INSTALLS: Lazy<Mutex<HashMap<String, CancellationToken>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+static PERCENT_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(\d{1,3}(?:\.\d+)?)\s*%").expect("百分比正则无效"));
+
+/// 取消进行中的安装（任务不存在或已结束时返回 false）
+pub fn cancel_install(install_id: &str) -> bool {
+    let token = ACTIVE_INSTALLS
+        .lock()
+        .ok()
+        .and_then(|installs| installs.get(install_id).cloned());
+    match token {
+        Some(token) => {
+            tracing::info!(install_id = %install_id, "取消安装");
+            token.cancel();
+            true
+        }
+        None => false,
+    }
+}
+
+struct ProgressState {
+    phase: InstallPhase,
+    percent: Option<u8>,
+}
+
+/// 单次安装的进度上报器
+///
+/// 创建时登记到进行中的安装任务，Drop 时移除
+pub struct InstallProgress {
+    install_id: String,
+    tool_id: String,
+    callback: InstallProgressCallback,
+    cancel: CancellationToken,
+    state: Arc<Mutex<ProgressState>>,
+}
+
+impl InstallProgress {
+    pub fn new(install_id: &str, tool_id: &str, callback: InstallProgressCallback) -> Self {
+        let cancel = CancellationToken::new();
+        if let Ok(mut installs) = ACTIVE_INSTALLS.lock() {
+            installs.insert(install_id.to_string(), cancel.clone());
+        }
+        Self {
+            install_id: install_id.to_string(),
+            tool_id: tool_id.to_string(),
+            callback,
+            cancel,
+            state: Arc::new(Mutex::new(ProgressState {
+                phase: InstallPhase::Preparing,
+                percent: None,
+            })),
+        }
+    }
+
+    pub fn install_id(&self) -> &str {
+        &self.install_id
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    /// 切换阶段并上报（阶段变化时百分比清零）
+    pub fn set_phase(&self, phase: InstallPhase) {
+        let percent = {
+            let Ok(mut state) = self.state.lock() else {
+                return;
+            };
+            if state.phase != phase {
+                state.percent = None;
+            }
+            state.phase = phase;
+            if phase == InstallPhase::Completed {
+                state.percent = Some(100);
+            }
+            state.percent
+        };
+        self.emit(phase, percent, None, None);
+    }
+
+    /// 根据安装结果上报最终阶段
+    pub fn finish(&self, result: &anyhow::Result<()>) {
+        let phase = if self.is_cancelled() {
+            InstallPhase::Cancelled
+        } else if result.is_ok() {
+            InstallPhase::Completed
+        } else {
+            InstallPhase::Failed
+        };
+        if let Err(e) = result {
+            self.emit(phase, None, Some(e.to_string()), Some(OutputStream::Stderr));
+        }
+        self.set_phase(phase);
+    }
+
+    /// 生成命令输出观察者（每行输出转为一条进度事件）
+    pub fn observer(&self) -> OutputObserver {
+        let install_id = self.install_id.clone();
+        let tool_id = self.tool_id.clone();
+        let callback = self.callback.clone();
+        let state = self.state.clone();
+
+        OutputObserver::new(
+            Arc::new(move |stream, line| {
+                let (phase, percent) = {
+                    let Ok(mut state) = state.lock() else {
+                        return;
+                    };
+                    if let Some(phase) = infer_phase(line) {
+                        if phase > state.phase && !state.phase.is_terminal() {
+                            state.phase = phase;
+                            state.percent = None;
+                        }
+                    }
+                    if let Some(percent) = parse_percent(line) {
+                        state.percent = Some(percent);
+                    }
+                    (state.phase, state.percent)
+                };
+                callback(InstallProgressEvent {
+                    install_id: install_id.clone(),
+                    tool_id: tool_id.clone(),
+                    phase,
+                    percent,
+                    line: Some(line.to_string()),
+                    stream: Some(stream),
+                });
+            }),
+            self.cancel.clone(),
+        )
+    }
+
+    fn emit(
+        &self,
+        phase: InstallPhase,
+        percent: Option<u8>,
+        line: Option<String>,
+        stream: Option<OutputStream>,
+    ) {
+        (self.callback)(InstallProgressEvent {
+            install_id: self.install_id.clone(),
+            tool_id: self.tool_id.clone(),
+            phase,
+            percent,
+            line,
+            stream,
+        });
+    }
+}
+
+impl Drop for InstallProgress {
+    fn drop(&mut self) {
+        if let Ok(mut installs) = ACTIVE_INSTALLS.lock() {
+            installs.remove(&self.install_id);
+        }
+    }
+}
+
+/// 从输出行中解析百分比（取最后一个，如 curl 的 `###### 45.3%`）
+fn parse_percent(line: &str) -> Option<u8> {
+    let caps = PERCENT_RE.captures_iter(line).last()?;
+    let value: f64 = caps[1].parse().ok()?;
+    (0.0..=100.0)
+        .contains(&value)
+        .then_some(value.floor() as u8)
+}
+
+/// 根据输出行推断安装阶段
+fn infer_phase(line: &str) -> Option<InstallPhase> {
+    let lower = line.to_lowercase();
+    if lower.contains("verif") || lower.contains("checksum") || lower.contains("--version") {
+        Some(InstallPhase::Verifying)
+    } else if lower.contains("install")
+        || lower.contains("extract")
+        || lower.contains("added ")
+        || lower.contains("changed ")
+        || lower.contains("pouring")
+    {
+        Some(InstallPhase::Installing)
+    } else if lower.contains("download") || lower.contains("fetch") || lower.contains('%') {
+        Some(InstallPhase::Downloading)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_percent_and_phase() {
+        assert_eq!(parse_percent("######## 45.3%"), Some(45));
+        assert_eq!(parse_percent("  12% of 10MB ... 100%"), Some(100));
+        assert_eq!(parse_percent("added 12 packages in 3s"), None);
+        assert_eq!(parse_percent("250%"), None);
+
+        assert_eq!(
+            infer_phase("Downloading claude 2.0.1"),
+            Some(InstallPhase::Downloading)
+        );
+        assert_eq!(
+            infer_phase("added 3 packages in 2s"),
+            Some(InstallPhase::Installing)
+        );
+        assert_eq!(infer_phase("npm notice"), None);
+    }
+
+    #[test]
+    fn test_progress_events_and_cancel() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let collected = events.clone();
+        let progress = InstallProgress::new(
+            "test-install",
+            "codex",
+            Arc::new(move |event| collected.lock().unwrap().push(event)),
+        );
+
+        let observer = progress.observer();
+        let executor = crate::utils::CommandExecutor::new().with_observer(observer);
+        executor.execute("echo 'Downloading 50%' && echo 'added 1 package'");
+
+        assert!(cancel_install("test-install"));
+        assert!(progress.is_cancelled());
+        progress.finish(&Err(anyhow::anyhow!("已取消")));
+        drop(progress);
+        assert!(!cancel_install("test-install"));
+
+        let events = events.lock().unwrap();
+        assert_eq!(events[0].phase, InstallPhase::Downloading);
+        assert_eq!(events[0].percent, Some(50));
+        assert_eq!(events[1].phase, InstallPhase::Installing);
+        assert_eq!(events[1].percent, None);
+        assert_eq!(events.last().unwrap().phase, InstallPhase::Cancelled);
+    }
+}
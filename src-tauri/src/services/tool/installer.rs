@@ -1,7 +1,14 @@
 use crate::models::{InstallMethod, Tool, ToolInstance, UpdateResult};
+use crate::services::environment::explain_npm_missing;
+use crate::services::tool::install_history::{record_attempt, InstallOperation};
+use crate::services::tool::install_progress::{InstallPhase, InstallProgress};
+use crate::services::tool::user_prefix::{
+    install_to_user_prefix, is_permission_failure, npm_install_global, NpmInstallTarget,
+};
 use crate::services::tool::DetectorRegistry;
 use crate::utils::parse_version_string;
 use anyhow::Result;
+use std::time::Instant;
 use tokio::time::{timeout, Duration};
 
 /// 安装服务（新架构：委托给 Detector）
@@ -18,17 +25,117 @@ impl InstallerService {
         }
     }
 
-    /// 安装工具（委托给 Detector）
+    /// 使用指定执行器创建（用于记录执行过程）
+    pub fn with_executor(command_executor: crate::utils::CommandExecutor) -> Self {
+        InstallerService {
+            detector_registry: DetectorRegistry::new(),
+            command_executor,
+        }
+    }
+
+    /// 安装工具（委托给 Detector，执行过程写入安装历史）
     pub async fn install(&self, tool: &Tool, method: &InstallMethod, force: bool) -> Result<()> {
+        self.run_install(tool, method, force, None).await
+    }
+
+    /// 安装工具并实时上报进度
+    ///
+    /// 子进程输出逐行转为进度事件；通过 `cancel_install` 取消后终止正在执行的命令，
+    /// 返回“安装已取消”错误（同样写入安装历史）
+    pub async fn install_with_progress(
+        &self,
+        tool: &Tool,
+        method: &InstallMethod,
+        force: bool,
+        progress: &InstallProgress,
+    ) -> Result<()> {
+        let result = self.run_install(tool, method, force, Some(progress)).await;
+        progress.finish(&result);
+        result
+    }
+
+    async fn run_install(
+        &self,
+        tool: &Tool,
+        method: &InstallMethod,
+        force: bool,
+        progress: Option<&InstallProgress>,
+    ) -> Result<()> {
         let detector = self
             .detector_registry
             .get(&tool.id)
             .ok_or_else(|| anyhow::anyhow!("未知的工具 ID: {}", tool.id))?;
 
         tracing::info!("使用 Detector 安装工具: {}", tool.name);
-        detector
-            .install(&self.command_executor, method, force)
+        let started = Instant::now();
+        let executor = self.command_executor.with_transcript();
+        let executor = match progress {
+            Some(progress) => {
+                progress.set_phase(InstallPhase::Preparing);
+                executor.with_observer(progress.observer())
+            }
+            None => executor,
+        };
+        let mut result = detector.install(&executor, method, force).await;
+        if executor.is_cancelled() {
+            result = Err(anyhow::anyhow!("安装已取消"));
+        }
+
+        record_attempt(
+            InstallOperation::Install {
+                tool_id: tool.id.clone(),
+                method: method.clone(),
+                force,
+            },
+            &executor,
+            started,
+            &result,
+        );
+        result
+    }
+
+    /// 通过 npm 安装指定版本（固定版本/回滚使用，执行过程写入安装历史）
+    ///
+    /// 使用 `<npm 包名>@<版本>` 安装，全局目录无权限时回退到用户级目录；
+    /// 成功后返回实际检测到的版本
+    pub async fn install_specific_version(&self, tool: &Tool, version: &str) -> Result<String> {
+        let version = version.trim().trim_start_matches('v');
+        if !is_valid_version_spec(version) {
+            anyhow::bail!("无效的版本号: {}", version);
+        }
+        let detector = self
+            .detector_registry
+            .get(&tool.id)
+            .ok_or_else(|| anyhow::anyhow!("未知的工具 ID: {}", tool.id))?;
+
+        tracing::info!("安装 {} 指定版本: {}", tool.name, version);
+        let started = Instant::now();
+        let executor = self.command_executor.with_transcript();
+        let result = async {
+            if !executor.command_exists_async("npm").await {
+                anyhow::bail!(explain_npm_missing().await);
+            }
+            let package_spec = format!("{}@{}", detector.npm_package(), version);
+            npm_install_global(&executor, &package_spec).await?;
+            Ok(())
+        }
+        .await;
+
+        record_attempt(
+            InstallOperation::InstallVersion {
+                tool_id: tool.id.clone(),
+                version: version.to_string(),
+            },
+            &executor,
+            started,
+            &result,
+        );
+        result?;
+
+        Ok(detector
+            .get_version(&self.command_executor)
             .await
+            .unwrap_or_else(|| version.to_string()))
     }
 
     /// 更新工具（委托给 Detector）
@@ -159,6 +266,33 @@ impl InstallerService {
                     tool_id: Some(instance.base_id.clone()),
                 })
             }
+            Ok(result)
+                if *install_method == InstallMethod::Npm
+                    && is_permission_failure(&format!("{}\n{}", result.stdout, result.stderr)) =>
+            {
+                // 全局目录无权限：回退到用户级目录安装最新版本
+                tracing::warn!("npm 全局更新无权限，回退到用户级目录");
+                let spec = format!("{}@latest", tool_obj.npm_package);
+                let target = install_to_user_prefix(&self.command_executor, &spec).await?;
+                let location = match target {
+                    NpmInstallTarget::UserPrefix(prefix) => prefix.display().to_string(),
+                    NpmInstallTarget::Global => String::new(),
+                };
+
+                Ok(UpdateResult {
+                    success: true,
+                    message: format!(
+                        "✅ 全局目录无写入权限，已将最新版本安装到用户目录 {}，请在工具管理中添加该位置的实例",
+                        location
+                    ),
+                    has_update: false,
+                    current_version: None,
+                    latest_version: None,
+                    mirror_version: None,
+                    mirror_is_stale: None,
+                    tool_id: Some(instance.base_id.clone()),
+                })
+            }
             Ok(result) => {
                 // 命令执行失败
                 anyhow::bail!(
@@ -174,6 +308,16 @@ impl InstallerService {
     }
 }
 
+/// 校验版本号（仅允许 semver 字符，避免拼接到命令中产生注入）
+fn is_valid_version_spec(version: &str) -> bool {
+    !version.is_empty()
+        && version.len() <= 64
+        && version.starts_with(|c: char| c.is_ascii_digit())
+        && version
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+'))
+}
+
 impl Default for InstallerService {
     fn default() -> Self {
         Self::new()
@@ -184,6 +328,16 @@ impl Default for InstallerService {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_version_spec_validation() {
+        assert!(is_valid_version_spec("2.0.61"));
+        assert!(is_valid_version_spec("0.13.0-preview.2"));
+        assert!(!is_valid_version_spec(""));
+        assert!(!is_valid_version_spec("latest"));
+        assert!(!is_valid_version_spec("1.0.0 && rm -rf ~"));
+        assert!(!is_valid_version_spec("1.0.0;echo"));
+    }
+
     #[test]
     fn test_service_creation() {
         let service = InstallerService::new();
@@ -0,0 +1,172 @@
+//! 镜像站分平台同步状态
+//!
+//! 镜像站对不同产物的同步进度不同（npm tarball、Windows 安装包、macOS/Linux 脚本、Homebrew），
+//! 这里按 工具 + 安装方式 + 系统 + 架构 记录每个产物的版本与滞后状态，
+//! 版本检查与安装预检按当前平台取对应产物，而不是使用全局的滞后标记。
+
+use crate::models::InstallMethod;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+/// 缓存有效期（超过后安装预检重新请求镜像站）
+pub const MIRROR_STATE_TTL: Duration = Duration::from_secs(600);
+
+/// 镜像产物同步状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MirrorArtifactState {
+    /// 安装方式：`npm` / `brew` / `official`
+    pub kind: String,
+    /// 系统（`windows` / `macos` / `linux`），None 表示与平台无关（如 npm tarball）
+    #[serde(default)]
+    pub os: Option<String>,
+    /// 架构（`x86_64` / `aarch64`），None 表示不区分架构
+    #[serde(default)]
+    pub arch: Option<String>,
+    /// 镜像上该产物可安装的版本
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub is_stale: bool,
+    /// 镜像最近一次同步时间
+    #[serde(default)]
+    pub synced_at: Option<String>,
+}
+
+impl MirrorArtifactState {
+    /// 是否适用于指定平台
+    fn applies_to(&self, kind: &str, os: &str, arch: &str) -> bool {
+        self.kind == kind
+            && self.os.as_deref().is_none_or(|o| o == os)
+            && self.arch.as_deref().is_none_or(|a| a == arch)
+    }
+
+    /// 匹配精度（同时指定系统和架构的产物优先）
+    fn specificity(&self) -> u8 {
+        u8::from(self.os.is_some()) + u8::from(self.arch.is_some())
+    }
+}
+
+/// 安装方式对应的产物类型
+pub fn artifact_kind(method: &InstallMethod) -> Option<&'static str> {
+    match method {
+        InstallMethod::Npm => Some("npm"),
+        InstallMethod::Brew => Some("brew"),
+        InstallMethod::Official => Some("official"),
+        InstallMethod::Other => None,
+    }
+}
+
+/// 在产物列表中查找最匹配指定平台的产物
+pub fn select_artifact<'a>(
+    artifacts: &'a [MirrorArtifactState],
+    kind: &str,
+    os: &str,
+    arch: &str,
+) -> Option<&'a MirrorArtifactState> {
+    artifacts
+        .iter()
+        .filter(|a| a.applies_to(kind, os, arch))
+        .max_by_key(|a| a.specificity())
+}
+
+/// 在产物列表中查找当前平台的产物
+pub fn select_current_platform<'a>(
+    artifacts: &'a [MirrorArtifactState],
+    method: &InstallMethod,
+) -> Option<&'a MirrorArtifactState> {
+    let kind = artifact_kind(method)?;
+    select_artifact(
+        artifacts,
+        kind,
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+    )
+}
+
+struct CachedArtifacts {
+    fetched_at: Instant,
+    artifacts: Vec<MirrorArtifactState>,
+}
+
+/// 工具 ID → 最近一次从镜像站获取的产物状态
+static MIRROR_SYNC_STATE: Lazy<RwLock<HashMap<String, CachedArtifacts>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// 记录工具的产物状态
+pub fn record_artifacts(tool_id: &str, artifacts: Vec<MirrorArtifactState>) {
+    if let Ok(mut state) = MIRROR_SYNC_STATE.write() {
+        state.insert(
+            tool_id.to_string(),
+            CachedArtifacts {
+                fetched_at: Instant::now(),
+                artifacts,
+            },
+        );
+    }
+}
+
+/// 获取未过期的产物状态（无记录或已过期时返回 None）
+pub fn cached_artifacts(tool_id: &str) -> Option<Vec<MirrorArtifactState>> {
+    let state = MIRROR_SYNC_STATE.read().ok()?;
+    state
+        .get(tool_id)
+        .filter(|cached| cached.fetched_at.elapsed() < MIRROR_STATE_TTL)
+        .map(|cached| cached.artifacts.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn artifact(
+        kind: &str,
+        os: Option<&str>,
+        arch: Option<&str>,
+        stale: bool,
+    ) -> MirrorArtifactState {
+        MirrorArtifactState {
+            kind: kind.to_string(),
+            os: os.map(str::to_string),
+            arch: arch.map(str::to_string),
+            version: None,
+            is_stale: stale,
+            synced_at: None,
+        }
+    }
+
+    #[test]
+    fn test_select_artifact_prefers_specific_platform() {
+        let artifacts = vec![
+            artifact("npm", None, None, false),
+            artifact("official", Some("windows"), None, true),
+            artifact("official", Some("macos"), None, false),
+            artifact("official", Some("macos"), Some("aarch64"), true),
+        ];
+
+        assert!(
+            !select_artifact(&artifacts, "npm", "windows", "x86_64")
+                .unwrap()
+                .is_stale
+        );
+        assert!(
+            select_artifact(&artifacts, "official", "windows", "x86_64")
+                .unwrap()
+                .is_stale
+        );
+        assert!(
+            !select_artifact(&artifacts, "official", "macos", "x86_64")
+                .unwrap()
+                .is_stale
+        );
+        assert!(
+            select_artifact(&artifacts, "official", "macos", "aarch64")
+                .unwrap()
+                .is_stale
+        );
+        assert!(select_artifact(&artifacts, "official", "linux", "x86_64").is_none());
+        assert!(select_artifact(&artifacts, "brew", "macos", "aarch64").is_none());
+    }
+}
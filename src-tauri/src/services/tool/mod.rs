@@ -2,21 +2,38 @@
 //
 // 包含工具的安装、版本检查、下载等功能
 
+pub mod custom_tools;
 pub mod db;
 pub mod detector_trait;
 pub mod detectors;
 pub mod downloader;
+pub mod install_history;
+pub mod install_progress;
 pub mod installer;
+pub mod mirror_state;
 pub mod registry;
+pub mod ssh_tunnel;
+pub mod status_cache;
 pub mod tools_config;
+pub mod user_prefix;
 pub mod version;
 
+pub use custom_tools::{CustomToolDefinition, CustomToolsReport};
 pub use db::ToolInstanceDB;
 pub use detector_trait::ToolDetector;
-pub use detectors::{ClaudeCodeDetector, CodeXDetector, DetectorRegistry, GeminiCLIDetector};
+pub use detectors::{
+    ClaudeCodeDetector, CodeXDetector, CustomToolDetector, DetectorRegistry, GeminiCLIDetector,
+};
 pub use downloader::FileDownloader;
+pub use install_history::{InstallAttempt, InstallHistory, InstallOperation};
+pub use install_progress::{
+    cancel_install, InstallPhase, InstallProgress, InstallProgressEvent, INSTALL_PROGRESS_EVENT,
+};
 pub use installer::InstallerService;
+pub use mirror_state::MirrorArtifactState;
 pub use registry::ToolRegistry;
+pub use ssh_tunnel::SshTunnelInfo;
+pub use status_cache::ToolStatusCache;
 pub use tools_config::{
     LocalToolInstance, SSHToolInstance, ToolGroup, ToolsConfig, WSLToolInstance,
 };
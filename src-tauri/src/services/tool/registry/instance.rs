@@ -2,9 +2,10 @@
 //!
 //! 负责工具实例的添加、删除操作（Local/WSL/SSH）
 
+use super::ssh_ops::detect_remote_tool;
 use super::ToolRegistry;
 use crate::models::{InstallMethod, SSHConfig, Tool, ToolInstance, ToolType};
-use crate::utils::WSLExecutor;
+use crate::utils::{SSHExecutor, WSLExecutor};
 use anyhow::Result;
 
 impl ToolRegistry {
@@ -50,7 +51,10 @@ impl ToolRegistry {
         Ok(instance)
     }
 
-    /// 添加SSH工具实例（本期仅存储配置，不实现检测）
+    /// 添加SSH工具实例
+    ///
+    /// 连接远程主机检测工具；主机暂时不可达时仍保存配置（installed 为 false），
+    /// 之后可通过刷新或远程安装补全
     pub async fn add_ssh_instance(
         &self,
         base_id: &str,
@@ -60,15 +64,29 @@ impl ToolRegistry {
         let tool =
             Tool::by_id(base_id).ok_or_else(|| anyhow::anyhow!("未知的工具ID: {}", base_id))?;
 
-        // 创建SSH实例（本期不检测，installed设为false）
-        let instance = ToolInstance::create_ssh_instance(
+        // 检测远程工具
+        let executor = SSHExecutor::new(ssh_config.clone())?;
+        let (installed, version, install_path) = match detect_remote_tool(&executor, &tool).await {
+            Ok(Some((path, version))) => (true, version, Some(path)),
+            Ok(None) => (false, None, None),
+            Err(e) => {
+                tracing::warn!("远程检测 {} 失败，仅保存配置: {}", executor.target(), e);
+                (false, None, None)
+            }
+        };
+
+        // 创建SSH实例
+        let mut instance = ToolInstance::create_ssh_instance(
             base_id.to_string(),
             tool.name.clone(),
             ssh_config,
-            false, // 本期不检测
-            None,
-            None,
+            installed,
+            version,
+            install_path,
         );
+        if installed {
+            instance.install_method = Some(InstallMethod::Npm);
+        }
 
         // 检查是否已存在
         let db = self.db.write().await;
@@ -100,9 +118,10 @@ impl ToolRegistry {
             return Err(anyhow::anyhow!("不允许删除内置实例"));
         }
 
-        // 删除
+        // 删除（同时关闭该实例的代理隧道）
         db.delete_instance(instance_id)?;
         drop(db);
+        crate::services::tool::ssh_tunnel::stop_tunnel(instance_id);
 
         Ok(())
     }
@@ -134,7 +153,8 @@ impl ToolRegistry {
         match &install_method {
             InstallMethod::Npm | InstallMethod::Brew => {
                 if let Some(ref installer) = installer_path {
-                    let installer_buf = PathBuf::from(installer);
+                    let installer_buf =
+                        crate::utils::extended_length_path(&PathBuf::from(installer));
                     if !installer_buf.exists() {
                         anyhow::bail!("安装器路径不存在: {}", installer);
                     }
@@ -147,7 +167,8 @@ impl ToolRegistry {
             }
             InstallMethod::Official | InstallMethod::Other => {
                 if let Some(ref installer) = installer_path {
-                    let installer_buf = PathBuf::from(installer);
+                    let installer_buf =
+                        crate::utils::extended_length_path(&PathBuf::from(installer));
                     if !installer_buf.exists() {
                         anyhow::bail!("安装器路径不存在: {}", installer);
                     }
@@ -5,7 +5,11 @@
 mod detection;
 mod instance;
 mod query;
+mod ssh_ops;
 mod version_ops;
+mod wsl_ops;
+
+pub use version_ops::ToolVersionHistory;
 
 use crate::services::tool::{DetectorRegistry, ToolInstanceDB};
 use crate::utils::{CommandExecutor, WSLExecutor};
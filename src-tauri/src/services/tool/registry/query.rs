@@ -38,8 +38,8 @@ impl ToolRegistry {
         }
 
         // 确保所有工具都有条目（即使没有实例）
-        for tool_id in &["claude-code", "codex", "gemini-cli"] {
-            grouped.entry(tool_id.to_string()).or_default();
+        for tool_id in self.detector_registry.all_tool_ids() {
+            grouped.entry(tool_id).or_default();
         }
 
         tracing::debug!("完成获取所有工具实例，共 {} 个工具", grouped.len());
@@ -228,7 +228,8 @@ impl ToolRegistry {
     pub async fn validate_tool_path(&self, path: &str) -> Result<String> {
         use std::path::PathBuf;
 
-        let path_buf = PathBuf::from(path);
+        // 长路径需要扩展长度语法才能检查（Windows）
+        let path_buf = crate::utils::extended_length_path(&PathBuf::from(path));
 
         // 检查文件是否存在
         if !path_buf.exists() {
@@ -241,7 +242,12 @@ impl ToolRegistry {
         }
 
         // 执行 --version 命令
-        let version_cmd = format!("{} --version", path);
+        // 路径含空格（如带空格的用户名）时需要加引号
+        let version_cmd = if path.contains(char::is_whitespace) {
+            format!("\"{}\" --version", path)
+        } else {
+            format!("{} --version", path)
+        };
         let result = self.command_executor.execute_async(&version_cmd).await;
 
         if !result.success {
@@ -0,0 +1,333 @@
+//! SSH 实例操作模块
+//!
+//! 负责远程主机上工具的检测、安装、更新、Profile 应用与代理隧道。
+//! 命令经系统 ssh 在远程登录 shell 中执行；配置文件先下载到本机临时目录，
+//! 复用本地的 Profile 写入逻辑后再上传有变化的文件。
+
+use super::ToolRegistry;
+use crate::data::guard;
+use crate::models::{InstallMethod, Tool, ToolInstance, ToolType, UpdateResult};
+use crate::services::profile_manager::ProfileManager;
+use crate::services::proxy_config_manager::ProxyConfigManager;
+use crate::services::tool::ssh_tunnel::{self, SshTunnelInfo};
+use crate::utils::{parse_version_string, posix_quote, SSHExecutor};
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// npm 全局安装超时
+const SSH_INSTALL_TIMEOUT: Duration = Duration::from_secs(600);
+
+impl ToolRegistry {
+    /// 重新检测 SSH 实例（安装状态、路径、版本）并写回数据库
+    pub async fn refresh_ssh_instance(&self, instance_id: &str) -> Result<ToolInstance> {
+        let instance = self.find_ssh_instance(instance_id).await?;
+        let executor = ssh_executor(&instance)?;
+        let tool = Tool::by_id(&instance.base_id)
+            .ok_or_else(|| anyhow::anyhow!("未知工具: {}", instance.base_id))?;
+
+        let detected = detect_remote_tool(&executor, &tool).await?;
+        let mut updated = instance.clone();
+        updated.installed = detected.is_some();
+        updated.install_path = detected.as_ref().map(|(path, _)| path.clone());
+        updated.version = detected.and_then(|(_, version)| version);
+        if updated.installed && updated.install_method.is_none() {
+            updated.install_method = Some(InstallMethod::Npm);
+        }
+        updated.updated_at = chrono::Utc::now().timestamp();
+        self.db.write().await.update_instance(&updated)?;
+
+        Ok(updated)
+    }
+
+    /// 在远程主机上通过 npm 安装工具
+    pub async fn install_ssh_instance(&self, instance_id: &str) -> Result<ToolInstance> {
+        let instance = self.find_ssh_instance(instance_id).await?;
+        let tool = Tool::by_id(&instance.base_id)
+            .ok_or_else(|| anyhow::anyhow!("未知工具: {}", instance.base_id))?;
+
+        remote_npm_install(&ssh_executor(&instance)?, &tool).await?;
+        let instance = self.refresh_ssh_instance(instance_id).await?;
+        if !instance.installed {
+            anyhow::bail!(
+                "npm 安装完成，但远程主机上未找到 {} 命令，请检查 npm 全局目录是否在 PATH 中",
+                tool.name
+            );
+        }
+
+        tracing::info!(
+            instance_id = %instance_id,
+            version = ?instance.version,
+            "远程工具安装完成"
+        );
+        Ok(instance)
+    }
+
+    /// 更新 SSH 实例（远程重新执行 npm 全局安装 latest）
+    pub(super) async fn update_ssh_instance(
+        &self,
+        instance: &ToolInstance,
+    ) -> Result<UpdateResult> {
+        let tool = Tool::by_id(&instance.base_id)
+            .ok_or_else(|| anyhow::anyhow!("未知工具: {}", instance.base_id))?;
+
+        remote_npm_install(&ssh_executor(instance)?, &tool).await?;
+        let updated = self.refresh_ssh_instance(&instance.instance_id).await?;
+
+        Ok(UpdateResult {
+            success: true,
+            message: "✅ 更新成功！".to_string(),
+            has_update: false,
+            current_version: updated.version.clone(),
+            latest_version: updated.version,
+            mirror_version: None,
+            mirror_is_stale: None,
+            tool_id: Some(instance.base_id.clone()),
+        })
+    }
+
+    /// 在远程主机上执行 `--version` 获取当前版本（命令执行失败时返回 None）
+    pub(super) async fn ssh_instance_version(
+        &self,
+        instance: &ToolInstance,
+    ) -> Result<Option<String>> {
+        let executor = ssh_executor(instance)?;
+        let version_cmd = match &instance.install_path {
+            Some(path) => format!("{} --version", posix_quote(path)),
+            None => Tool::by_id(&instance.base_id)
+                .map(|tool| tool.check_command)
+                .ok_or_else(|| anyhow::anyhow!("未知工具: {}", instance.base_id))?,
+        };
+
+        let result = executor.execute(&version_cmd).await?;
+        Ok(result
+            .success
+            .then(|| parse_version_string(result.stdout.trim())))
+    }
+
+    /// 将 Profile 应用到远程主机的配置文件，返回远程配置目录
+    ///
+    /// 配合 [`Self::start_ssh_proxy_tunnel`]，应用透明代理 Profile
+    /// 即可让远程工具的请求经本机透明代理转发
+    pub async fn apply_profile_to_ssh_instance(
+        &self,
+        instance_id: &str,
+        profile_name: &str,
+    ) -> Result<String> {
+        let instance = self.find_ssh_instance(instance_id).await?;
+        let executor = ssh_executor(&instance)?;
+        ensure_remote_writable(guard::is_observer_mode(), &executor.target())?;
+        let tool = Tool::by_id(&instance.base_id)
+            .ok_or_else(|| anyhow::anyhow!("未知工具: {}", instance.base_id))?;
+
+        let result = executor
+            .execute(&Tool::posix_config_dir_script(&tool.id))
+            .await?;
+        let remote_dir = result.stdout.trim().to_string();
+        if !result.success || !remote_dir.starts_with('/') {
+            anyhow::bail!("无法获取远程配置目录: {}", result.stderr.trim());
+        }
+
+        // 下载现有配置到临时目录，本地写入 Profile 后上传有变化的文件
+        let staging = StagingDir::new()?;
+        let mut originals = Vec::new();
+        for file in tool.config_files() {
+            let remote_path = format!("{}/{}", remote_dir, file);
+            let content = executor.read_file(&remote_path).await?;
+            if let Some(content) = &content {
+                staging.write(&file, content)?;
+            }
+            originals.push((file, remote_path, content));
+        }
+
+        ProfileManager::new()?.apply_profile_to_config_dir(
+            &tool.id,
+            profile_name,
+            staging.path(),
+        )?;
+
+        for (file, remote_path, original) in originals {
+            let Some(content) = staging.read(&file)? else {
+                continue;
+            };
+            if original.as_deref() != Some(content.as_str()) {
+                executor.write_file(&remote_path, &content).await?;
+            }
+        }
+
+        tracing::info!(
+            instance_id = %instance_id,
+            profile = %profile_name,
+            remote_dir = %remote_dir,
+            "已应用 Profile 到远程主机"
+        );
+        Ok(remote_dir)
+    }
+
+    /// 建立到本机透明代理的反向隧道
+    ///
+    /// 远程端口默认与本机代理端口相同，这样透明代理 Profile 中的
+    /// `http://127.0.0.1:<port>` 在远程主机上同样有效
+    pub async fn start_ssh_proxy_tunnel(
+        &self,
+        instance_id: &str,
+        remote_port: Option<u16>,
+    ) -> Result<SshTunnelInfo> {
+        let instance = self.find_ssh_instance(instance_id).await?;
+        let proxy_config = ProxyConfigManager::new()?
+            .get_config(&instance.base_id)?
+            .ok_or_else(|| anyhow::anyhow!("{} 未配置透明代理", instance.tool_name))?;
+        if !proxy_config.enabled {
+            anyhow::bail!("{} 的透明代理未启用", instance.tool_name);
+        }
+
+        let local_port = proxy_config.port;
+        ssh_tunnel::start_tunnel(
+            instance_id,
+            &ssh_executor(&instance)?,
+            local_port,
+            remote_port.unwrap_or(local_port),
+        )
+        .await
+    }
+
+    async fn find_ssh_instance(&self, instance_id: &str) -> Result<ToolInstance> {
+        self.db
+            .read()
+            .await
+            .get_instance(instance_id)?
+            .filter(|inst| inst.tool_type == ToolType::SSH)
+            .ok_or_else(|| anyhow::anyhow!("未找到 SSH 实例: {}", instance_id))
+    }
+}
+
+/// 实例对应的 SSH 执行器
+fn ssh_executor(instance: &ToolInstance) -> Result<SSHExecutor> {
+    let config = instance
+        .ssh_config
+        .clone()
+        .ok_or_else(|| anyhow::anyhow!("实例 {} 缺少 SSH 配置", instance.instance_id))?;
+    SSHExecutor::new(config)
+}
+
+/// 写入远程配置前检查：远程路径不经过本地写入守卫，观察模式下直接拒绝
+fn ensure_remote_writable(observer_mode: bool, target: &str) -> Result<()> {
+    if observer_mode {
+        tracing::warn!(target = %target, "只读观察模式下拒绝写入远程工具配置");
+        anyhow::bail!("只读观察模式下禁止写入工具配置: {}", target);
+    }
+    Ok(())
+}
+
+/// 在远程主机上检测工具，返回（安装路径，版本）
+pub(super) async fn detect_remote_tool(
+    executor: &SSHExecutor,
+    tool: &Tool,
+) -> Result<Option<(String, Option<String>)>> {
+    let cmd_name = tool
+        .check_command
+        .split_whitespace()
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("无效的检查命令"))?;
+
+    let which = executor
+        .execute(&format!("command -v {}", posix_quote(cmd_name)))
+        .await?;
+    let path = which.stdout.trim();
+    if !which.success || path.is_empty() {
+        return Ok(None);
+    }
+
+    let version = executor
+        .execute(&format!("{} --version", posix_quote(path)))
+        .await?;
+    let version = version
+        .success
+        .then(|| parse_version_string(version.stdout.trim()));
+    Ok(Some((path.to_string(), version)))
+}
+
+async fn remote_npm_install(executor: &SSHExecutor, tool: &Tool) -> Result<()> {
+    if tool.npm_package.is_empty() {
+        anyhow::bail!("{} 未提供 npm 包，无法远程安装", tool.name);
+    }
+
+    let npm = executor.execute("command -v npm").await?;
+    if !npm.success || npm.stdout.trim().is_empty() {
+        anyhow::bail!(
+            "{} 上未找到 npm，请先在远程主机安装 Node.js",
+            executor.target()
+        );
+    }
+
+    let install_cmd = format!(
+        "npm install -g {}",
+        posix_quote(&format!("{}@latest", tool.npm_package))
+    );
+    tracing::info!(target = %executor.target(), command = %install_cmd, "远程安装工具");
+    let result = executor
+        .execute_with_timeout(&install_cmd, SSH_INSTALL_TIMEOUT)
+        .await?;
+    if !result.success {
+        let stderr = result.stderr.trim();
+        let hint = if stderr.contains("EACCES") {
+            "（npm 全局目录无写权限，可在远程主机改用 nvm 或设置用户级 npm prefix）"
+        } else {
+            ""
+        };
+        anyhow::bail!("远程 npm 安装失败{}: {}", hint, stderr);
+    }
+    Ok(())
+}
+
+/// 应用 Profile 使用的本机临时目录（Drop 时删除，避免凭证残留）
+struct StagingDir(PathBuf);
+
+impl StagingDir {
+    fn new() -> Result<Self> {
+        let dir = std::env::temp_dir().join(format!("duckcoding-ssh-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).context("创建临时目录失败")?;
+        Ok(Self(dir))
+    }
+
+    fn path(&self) -> &Path {
+        &self.0
+    }
+
+    fn write(&self, file: &str, content: &str) -> Result<()> {
+        let path = self.0.join(file);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, content).with_context(|| format!("写入临时文件 {} 失败", file))
+    }
+
+    fn read(&self, file: &str) -> Result<Option<String>> {
+        let path = self.0.join(file);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(
+            std::fs::read_to_string(&path)
+                .with_context(|| format!("读取临时文件 {} 失败", file))?,
+        ))
+    }
+}
+
+impl Drop for StagingDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_observer_mode_blocks_remote_config_writes() {
+        let err = ensure_remote_writable(true, "dev@example.com:22").unwrap_err();
+        assert!(err.to_string().contains("只读观察模式"));
+        assert!(ensure_remote_writable(false, "dev@example.com:22").is_ok());
+    }
+}
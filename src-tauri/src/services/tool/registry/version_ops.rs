@@ -4,10 +4,21 @@
 
 use super::ToolRegistry;
 use crate::models::{InstallMethod, Tool, ToolType, UpdateResult};
+use crate::services::tool::install_history::{record_attempt, InstallOperation};
+use crate::services::tool::tools_config::InstalledVersionRecord;
 use crate::services::{tool::InstallerService, VersionService};
-use crate::utils::parse_version_string;
+use crate::utils::{parse_version_string, CommandExecutor};
 use anyhow::Result;
 use std::collections::HashMap;
+use std::time::Instant;
+
+/// 工具版本历史（含固定版本）
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ToolVersionHistory {
+    pub tool_id: String,
+    pub pinned_version: Option<String>,
+    pub history: Vec<InstalledVersionRecord>,
+}
 
 impl ToolRegistry {
     /// 更新工具实例（智能选择更新方式）
@@ -31,16 +42,165 @@ impl ToolRegistry {
 
         let instance = all_instances
             .iter()
-            .find(|inst| inst.instance_id == instance_id && inst.tool_type == ToolType::Local)
+            .find(|inst| inst.instance_id == instance_id)
             .ok_or_else(|| anyhow::anyhow!("未找到实例: {}", instance_id))?;
 
-        // 2. 根据安装方法选择更新方式
+        // WSL/SSH 实例在对应环境内通过 npm 更新（版本固定仅作用于本地实例）
+        match instance.tool_type {
+            ToolType::WSL => return self.update_wsl_instance(instance).await,
+            ToolType::SSH => return self.update_ssh_instance(instance).await,
+            ToolType::Local => {}
+        }
+
+        // 固定版本的工具仅允许强制更新
+        if !force {
+            if let Some(pinned) = self.db.read().await.get_pinned_version(&instance.base_id)? {
+                anyhow::bail!(
+                    "{} 已固定在版本 {}，请先取消固定或强制更新",
+                    instance.tool_name,
+                    pinned
+                );
+            }
+        }
+
+        // 2. 执行更新并写入安装历史
+        let started = Instant::now();
+        let executor = self.command_executor.with_transcript();
+        let result = self.run_instance_update(instance, &executor, force).await;
+        record_attempt(
+            InstallOperation::UpdateInstance {
+                tool_id: instance.base_id.clone(),
+                instance_id: instance_id.to_string(),
+                force,
+            },
+            &executor,
+            started,
+            &result,
+        );
+        let result = result?;
+
+        // 3. 如果更新成功，更新数据库中的版本号，并使版本状态缓存失效
+        if result.success {
+            crate::services::tool::ToolStatusCache::global().invalidate(&instance.base_id);
+            if let Some(ref new_version) = result.current_version {
+                let db = self.db.write().await;
+                let mut updated_instance = instance.clone();
+                updated_instance.version = Some(new_version.clone());
+                updated_instance.updated_at = chrono::Utc::now().timestamp();
+
+                if let Err(e) = db.update_instance(&updated_instance) {
+                    tracing::warn!("更新数据库版本失败: {}", e);
+                }
+                if let Err(e) =
+                    db.record_installed_version(&instance.base_id, new_version, "update")
+                {
+                    tracing::warn!("记录版本历史失败: {}", e);
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// 通过 npm 安装工具的指定版本
+    ///
+    /// # 参数
+    /// - tool_id: 工具ID
+    /// - version: 目标版本（如 2.0.61）
+    /// - pin: 安装后是否固定该版本（固定后普通更新会被拒绝）
+    ///
+    /// # 返回
+    /// - Ok(String): 安装后检测到的版本
+    /// - Err: 本地实例不是 npm 安装，或安装失败
+    pub async fn install_specific_version(
+        &self,
+        tool_id: &str,
+        version: &str,
+        pin: bool,
+    ) -> Result<String> {
+        self.install_version_with_source(tool_id, version, pin, "specific")
+            .await
+    }
+
+    /// 回滚到指定版本（安装该版本并固定，避免被再次自动更新）
+    pub async fn rollback_tool(&self, tool_id: &str, version: &str) -> Result<String> {
+        self.install_version_with_source(tool_id, version, true, "rollback")
+            .await
+    }
+
+    async fn install_version_with_source(
+        &self,
+        tool_id: &str,
+        version: &str,
+        pin: bool,
+        source: &str,
+    ) -> Result<String> {
+        let tool = Tool::by_id(tool_id).ok_or_else(|| anyhow::anyhow!("未知工具: {}", tool_id))?;
+
+        // 已存在的本地实例必须是 npm 安装，否则会在 npm 目录另装一份
+        let local = self
+            .db
+            .read()
+            .await
+            .get_local_instances()?
+            .into_iter()
+            .find(|i| i.base_id == tool_id);
+        if let Some(method) = local.as_ref().and_then(|i| i.install_method.as_ref()) {
+            if *method != InstallMethod::Npm {
+                anyhow::bail!(
+                    "{} 当前通过 {:?} 安装，仅支持对 npm 安装的实例切换版本",
+                    tool.name,
+                    method
+                );
+            }
+        }
+
+        let installer = InstallerService::with_executor(self.command_executor.clone());
+        let installed = installer.install_specific_version(&tool, version).await?;
+
+        if local.is_none() {
+            self.detect_and_persist_single_tool(tool_id).await?;
+        }
+        let db = self.db.write().await;
+        db.record_installed_version(tool_id, &installed, source)?;
+        if pin {
+            db.set_pinned_version(tool_id, Some(installed.clone()))?;
+        }
+        drop(db);
+        crate::services::tool::ToolStatusCache::global().invalidate(tool_id);
+
+        tracing::info!(tool_id = %tool_id, version = %installed, source = %source, pin, "已切换工具版本");
+        Ok(installed)
+    }
+
+    /// 获取工具的版本历史与固定版本
+    pub async fn get_version_history(&self, tool_id: &str) -> Result<ToolVersionHistory> {
+        let db = self.db.read().await;
+        Ok(ToolVersionHistory {
+            tool_id: tool_id.to_string(),
+            pinned_version: db.get_pinned_version(tool_id)?,
+            history: db.get_version_history(tool_id)?,
+        })
+    }
+
+    /// 固定或取消固定（None）工具版本
+    pub async fn set_version_pin(&self, tool_id: &str, version: Option<String>) -> Result<()> {
+        self.db.write().await.set_pinned_version(tool_id, version)
+    }
+
+    /// 根据安装方法选择更新方式并执行
+    async fn run_instance_update(
+        &self,
+        instance: &crate::models::ToolInstance,
+        executor: &CommandExecutor,
+        force: bool,
+    ) -> Result<UpdateResult> {
         let install_method = instance.install_method.clone();
 
         let result = match install_method {
             Some(InstallMethod::Npm) | Some(InstallMethod::Brew) => {
                 // Npm/Brew: 使用 InstallerService 执行更新
-                let installer = InstallerService::new();
+                let installer = InstallerService::with_executor(executor.clone());
                 installer
                     .update_instance_by_installer(instance, force)
                     .await?
@@ -59,19 +219,19 @@ impl ToolRegistry {
                 );
 
                 // 执行 Detector 的 update 方法
-                detector.update(&self.command_executor, force).await?;
+                detector.update(executor, force).await?;
 
                 // 更新成功，获取新版本
                 let new_version = if let Some(path) = &instance.install_path {
                     let version_cmd = format!("{} --version", path);
-                    let version_result = self.command_executor.execute_async(&version_cmd).await;
+                    let version_result = executor.execute_async(&version_cmd).await;
                     if version_result.success {
                         Some(parse_version_string(version_result.stdout.trim()))
                     } else {
                         None
                     }
                 } else {
-                    detector.get_version(&self.command_executor).await
+                    detector.get_version(executor).await
                 };
 
                 UpdateResult {
@@ -87,20 +247,6 @@ impl ToolRegistry {
             }
         };
 
-        // 3. 如果更新成功，更新数据库中的版本号
-        if result.success {
-            if let Some(ref new_version) = result.current_version {
-                let db = self.db.write().await;
-                let mut updated_instance = instance.clone();
-                updated_instance.version = Some(new_version.clone());
-                updated_instance.updated_at = chrono::Utc::now().timestamp();
-
-                if let Err(e) = db.update_instance(&updated_instance) {
-                    tracing::warn!("更新数据库版本失败: {}", e);
-                }
-            }
-        }
-
         Ok(result)
     }
 
@@ -120,11 +266,19 @@ impl ToolRegistry {
 
         let instance = all_instances
             .iter()
-            .find(|inst| inst.instance_id == instance_id && inst.tool_type == ToolType::Local)
+            .find(|inst| inst.instance_id == instance_id)
             .ok_or_else(|| anyhow::anyhow!("未找到实例: {}", instance_id))?;
 
-        // 2. 使用 install_path 执行 --version 获取当前版本
-        let current_version = if let Some(path) = &instance.install_path {
+        // 2. 使用 install_path 执行 --version 获取当前版本（WSL/SSH 实例在对应环境内执行）
+        let current_version = if instance.tool_type != ToolType::Local {
+            match self.remote_instance_version(instance).await? {
+                Some(version) => Some(version),
+                None => anyhow::bail!(
+                    "版本号获取错误：无法在 {} 中执行 --version",
+                    instance.tool_type.as_str()
+                ),
+            }
+        } else if let Some(path) = &instance.install_path {
             let version_cmd = format!("{} --version", path);
             tracing::info!("实例 {} 版本检查命令: {:?}", instance_id, version_cmd);
 
@@ -207,12 +361,21 @@ impl ToolRegistry {
 
         let mut statuses = Vec::new();
 
-        for instance in all_instances
-            .iter()
-            .filter(|i| i.tool_type == ToolType::Local)
-        {
-            // 使用 install_path 检测版本
-            let new_version = if let Some(path) = &instance.install_path {
+        for instance in &all_instances {
+            // 使用 install_path 检测版本（WSL/SSH 实例在对应环境内执行）
+            let new_version = if instance.tool_type != ToolType::Local {
+                match self.remote_instance_version(instance).await {
+                    Ok(Some(version)) => Some(version),
+                    _ => {
+                        tracing::warn!(
+                            "工具 {} ({}) 版本检测失败，保持原版本",
+                            instance.tool_name,
+                            instance.tool_type.as_str()
+                        );
+                        instance.version.clone()
+                    }
+                }
+            } else if let Some(path) = &instance.install_path {
                 let version_cmd = format!("{} --version", path);
                 tracing::info!("工具 {} 版本检查: {:?}", instance.tool_name, version_cmd);
 
@@ -252,18 +415,32 @@ impl ToolRegistry {
                 }
             }
 
-            // 添加到返回列表
-            statuses.push(crate::models::ToolStatus {
-                id: instance.base_id.clone(),
-                name: instance.tool_name.clone(),
-                installed: instance.installed,
-                version: new_version,
-            });
+            // 添加到返回列表（ToolStatus 按工具ID标识，仅返回本地实例，WSL/SSH 版本只写回数据库）
+            if instance.tool_type == ToolType::Local {
+                statuses.push(crate::models::ToolStatus {
+                    id: instance.base_id.clone(),
+                    name: instance.tool_name.clone(),
+                    installed: instance.installed,
+                    version: new_version,
+                });
+            }
         }
 
         Ok(statuses)
     }
 
+    /// 获取 WSL/SSH 实例的当前版本
+    async fn remote_instance_version(
+        &self,
+        instance: &crate::models::ToolInstance,
+    ) -> Result<Option<String>> {
+        match instance.tool_type {
+            ToolType::WSL => self.wsl_instance_version(instance).await,
+            ToolType::SSH => self.ssh_instance_version(instance).await,
+            ToolType::Local => Ok(instance.version.clone()),
+        }
+    }
+
     /// 检测工具的安装方式（用于更新时选择正确的方法）
     pub async fn detect_install_methods(&self) -> Result<HashMap<String, InstallMethod>> {
         let mut methods = HashMap::new();
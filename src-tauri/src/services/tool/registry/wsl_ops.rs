@@ -0,0 +1,224 @@
+//! WSL 实例操作模块
+//!
+//! 负责 WSL 发行版内工具的安装、更新、版本检测与 Profile 应用。
+//! 命令在发行版的登录 shell 中执行，配置文件经由 `\\wsl$\<发行版>\...` 读写。
+
+use super::ToolRegistry;
+use crate::models::{InstallMethod, Tool, ToolInstance, ToolType, UpdateResult};
+use crate::services::profile_manager::ProfileManager;
+use crate::utils::{parse_version_string, posix_quote, WSLExecutor};
+use anyhow::Result;
+use std::path::PathBuf;
+
+impl ToolRegistry {
+    /// 在 WSL 发行版中通过 npm 安装工具，并登记（或刷新）对应实例
+    pub async fn install_wsl_instance(
+        &self,
+        base_id: &str,
+        distro_name: &str,
+    ) -> Result<ToolInstance> {
+        if !WSLExecutor::is_available() {
+            return Err(anyhow::anyhow!("WSL 不可用，请确保已安装 WSL"));
+        }
+
+        let tool =
+            Tool::by_id(base_id).ok_or_else(|| anyhow::anyhow!("未知的工具ID: {}", base_id))?;
+
+        self.wsl_npm_install(&tool, distro_name).await?;
+
+        let instance = self.detect_wsl_tool(&tool, distro_name).await?;
+        if !instance.installed {
+            anyhow::bail!(
+                "npm 安装完成，但在 {} 中未找到 {} 命令，请检查 npm 全局目录是否在 PATH 中",
+                distro_name,
+                tool.name
+            );
+        }
+        self.db.write().await.upsert_instance(&instance)?;
+
+        tracing::info!(
+            tool_id = %base_id,
+            distro = %distro_name,
+            version = ?instance.version,
+            "WSL 工具安装完成"
+        );
+        Ok(instance)
+    }
+
+    /// 更新 WSL 实例（发行版内重新执行 npm 全局安装 latest）
+    pub(super) async fn update_wsl_instance(
+        &self,
+        instance: &ToolInstance,
+    ) -> Result<UpdateResult> {
+        let distro = wsl_distro(instance)?;
+        let tool = Tool::by_id(&instance.base_id)
+            .ok_or_else(|| anyhow::anyhow!("未知工具: {}", instance.base_id))?;
+
+        self.wsl_npm_install(&tool, distro).await?;
+        let new_version = self.wsl_instance_version(instance).await?;
+
+        let mut updated_instance = instance.clone();
+        updated_instance.installed = true;
+        updated_instance.version = new_version.clone();
+        updated_instance.install_method = Some(InstallMethod::Npm);
+        updated_instance.updated_at = chrono::Utc::now().timestamp();
+        if let Err(e) = self.db.write().await.update_instance(&updated_instance) {
+            tracing::warn!("更新数据库版本失败: {}", e);
+        }
+
+        Ok(UpdateResult {
+            success: true,
+            message: "✅ 更新成功！".to_string(),
+            has_update: false,
+            current_version: new_version.clone(),
+            latest_version: new_version,
+            mirror_version: None,
+            mirror_is_stale: None,
+            tool_id: Some(instance.base_id.clone()),
+        })
+    }
+
+    /// 在发行版内执行 `--version` 获取当前版本（命令执行失败时返回 None）
+    pub(super) async fn wsl_instance_version(
+        &self,
+        instance: &ToolInstance,
+    ) -> Result<Option<String>> {
+        let distro = wsl_distro(instance)?;
+        let version_cmd = match &instance.install_path {
+            Some(path) => format!("{} --version", posix_quote(path)),
+            None => Tool::by_id(&instance.base_id)
+                .map(|tool| tool.check_command)
+                .ok_or_else(|| anyhow::anyhow!("未知工具: {}", instance.base_id))?,
+        };
+
+        let result = self
+            .wsl_executor
+            .execute_login_in_distro(Some(distro), &version_cmd)
+            .await?;
+        Ok(result
+            .success
+            .then(|| parse_version_string(result.stdout.trim())))
+    }
+
+    /// 获取 WSL 实例的配置目录（Windows 侧访问路径）
+    pub async fn wsl_config_dir(&self, instance_id: &str) -> Result<PathBuf> {
+        let instance = self.find_wsl_instance(instance_id).await?;
+        let distro = wsl_distro(&instance)?;
+
+        let result = self
+            .wsl_executor
+            .execute_login_in_distro(
+                Some(distro),
+                &Tool::posix_config_dir_script(&instance.base_id),
+            )
+            .await?;
+        let linux_dir = result.stdout.trim();
+        if !result.success || !linux_dir.starts_with('/') {
+            anyhow::bail!("无法获取 {} 中的配置目录: {}", distro, result.stderr.trim());
+        }
+
+        Ok(WSLExecutor::unc_path(distro, linux_dir))
+    }
+
+    /// 将 Profile 应用到 WSL 实例的配置文件
+    ///
+    /// 透明代理 Profile 的地址为 127.0.0.1，WSL2 需启用 mirrored 网络模式
+    /// 才能从发行版内访问本机代理
+    pub async fn apply_profile_to_wsl_instance(
+        &self,
+        instance_id: &str,
+        profile_name: &str,
+    ) -> Result<PathBuf> {
+        let instance = self.find_wsl_instance(instance_id).await?;
+        let config_dir = self.wsl_config_dir(instance_id).await?;
+
+        let manager = ProfileManager::new()?;
+        manager.apply_profile_to_config_dir(&instance.base_id, profile_name, &config_dir)?;
+        Ok(config_dir)
+    }
+
+    async fn find_wsl_instance(&self, instance_id: &str) -> Result<ToolInstance> {
+        self.db
+            .read()
+            .await
+            .get_instance(instance_id)?
+            .filter(|inst| inst.tool_type == ToolType::WSL)
+            .ok_or_else(|| anyhow::anyhow!("未找到 WSL 实例: {}", instance_id))
+    }
+
+    /// 在发行版内检测工具（登录 shell，包含 nvm 等安装的 Node.js）
+    async fn detect_wsl_tool(&self, tool: &Tool, distro_name: &str) -> Result<ToolInstance> {
+        let cmd_name = tool
+            .check_command
+            .split_whitespace()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("无效的检查命令"))?;
+
+        let which = self
+            .wsl_executor
+            .execute_login_in_distro(Some(distro_name), &format!("command -v {}", cmd_name))
+            .await?;
+        let install_path = which
+            .success
+            .then(|| which.stdout.trim().to_string())
+            .filter(|path| !path.is_empty());
+
+        let mut instance = ToolInstance::create_wsl_instance(
+            tool.id.clone(),
+            tool.name.clone(),
+            distro_name.to_string(),
+            install_path.is_some(),
+            None,
+            install_path,
+        );
+        if instance.installed {
+            instance.version = self.wsl_instance_version(&instance).await?;
+            instance.install_method = Some(InstallMethod::Npm);
+        }
+        Ok(instance)
+    }
+
+    async fn wsl_npm_install(&self, tool: &Tool, distro_name: &str) -> Result<()> {
+        if tool.npm_package.is_empty() {
+            anyhow::bail!("{} 未提供 npm 包，无法在 WSL 中安装", tool.name);
+        }
+
+        let npm = self
+            .wsl_executor
+            .execute_login_in_distro(Some(distro_name), "command -v npm")
+            .await?;
+        if !npm.success || npm.stdout.trim().is_empty() {
+            anyhow::bail!(
+                "发行版 {} 中未找到 npm，请先在 WSL 中安装 Node.js",
+                distro_name
+            );
+        }
+
+        let install_cmd = format!(
+            "npm install -g {}",
+            posix_quote(&format!("{}@latest", tool.npm_package))
+        );
+        tracing::info!(distro = %distro_name, command = %install_cmd, "在 WSL 中安装工具");
+        let result = self
+            .wsl_executor
+            .execute_login_in_distro(Some(distro_name), &install_cmd)
+            .await?;
+        if !result.success {
+            let stderr = result.stderr.trim();
+            let hint = if stderr.contains("EACCES") {
+                "（npm 全局目录无写权限，可在 WSL 中改用 nvm 或设置用户级 npm prefix）"
+            } else {
+                ""
+            };
+            anyhow::bail!("WSL 中 npm 安装失败{}: {}", hint, stderr);
+        }
+        Ok(())
+    }
+}
+
+fn wsl_distro(instance: &ToolInstance) -> Result<&str> {
+    instance
+        .wsl_distro
+        .as_deref()
+        .ok_or_else(|| anyhow::anyhow!("实例 {} 缺少 WSL 发行版信息", instance.instance_id))
+}
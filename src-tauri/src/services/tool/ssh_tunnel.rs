@@ -0,0 +1,120 @@
+//! SSH 反向隧道
+//!
+//! 远程主机上的工具通过反向隧道访问本机透明代理：远程 `127.0.0.1:<remote_port>`
+//! 转发到本机代理端口。远程配置写入透明代理 Profile 后，请求即可复用本机的
+//! 代理、Token 统计与会话管理。隧道进程由这里登记，按实例 ID 启停。
+
+use crate::utils::SSHExecutor;
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+use tokio::process::Child;
+
+/// 启动后等待端口转发建立的时间（失败时 ssh 会在此期间退出）
+const TUNNEL_STARTUP_GRACE: Duration = Duration::from_secs(3);
+
+/// 隧道信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SshTunnelInfo {
+    pub instance_id: String,
+    /// 远程主机（user@host:port）
+    pub target: String,
+    pub local_port: u16,
+    pub remote_port: u16,
+    pub started_at: i64,
+}
+
+struct ActiveTunnel {
+    info: SshTunnelInfo,
+    child: Child,
+}
+
+/// instance_id → 运行中的隧道
+static TUNNELS: Lazy<Mutex<HashMap<String, ActiveTunnel>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 为实例启动反向隧道（已有隧道会先关闭）
+pub async fn start_tunnel(
+    instance_id: &str,
+    executor: &SSHExecutor,
+    local_port: u16,
+    remote_port: u16,
+) -> Result<SshTunnelInfo> {
+    stop_tunnel(instance_id);
+
+    let mut child = executor.spawn_reverse_tunnel(local_port, remote_port)?;
+    if let Ok(status) = tokio::time::timeout(TUNNEL_STARTUP_GRACE, child.wait()).await {
+        let mut stderr = String::new();
+        if let Some(mut pipe) = child.stderr.take() {
+            let _ = pipe.read_to_string(&mut stderr).await;
+        }
+        anyhow::bail!(
+            "SSH 隧道启动失败（{}）: {}",
+            status.map(|s| s.to_string()).unwrap_or_default(),
+            stderr.trim()
+        );
+    }
+
+    let info = SshTunnelInfo {
+        instance_id: instance_id.to_string(),
+        target: executor.target(),
+        local_port,
+        remote_port,
+        started_at: chrono::Utc::now().timestamp(),
+    };
+    if let Ok(mut tunnels) = TUNNELS.lock() {
+        tunnels.insert(
+            instance_id.to_string(),
+            ActiveTunnel {
+                info: info.clone(),
+                child,
+            },
+        );
+    }
+
+    tracing::info!(
+        instance_id = %instance_id,
+        target = %info.target,
+        local_port,
+        remote_port,
+        "SSH 反向隧道已建立"
+    );
+    Ok(info)
+}
+
+/// 关闭实例的隧道（不存在时返回 false）
+pub fn stop_tunnel(instance_id: &str) -> bool {
+    let tunnel = TUNNELS
+        .lock()
+        .ok()
+        .and_then(|mut tunnels| tunnels.remove(instance_id));
+    match tunnel {
+        Some(mut tunnel) => {
+            let _ = tunnel.child.start_kill();
+            tracing::info!(instance_id = %instance_id, "SSH 反向隧道已关闭");
+            true
+        }
+        None => false,
+    }
+}
+
+/// 列出运行中的隧道（同时清理已退出的进程）
+pub fn list_tunnels() -> Vec<SshTunnelInfo> {
+    let Ok(mut tunnels) = TUNNELS.lock() else {
+        return Vec::new();
+    };
+    tunnels.retain(|instance_id, tunnel| match tunnel.child.try_wait() {
+        Ok(None) => true,
+        _ => {
+            tracing::warn!(instance_id = %instance_id, "SSH 反向隧道已断开");
+            false
+        }
+    });
+    let mut list: Vec<SshTunnelInfo> = tunnels.values().map(|t| t.info.clone()).collect();
+    list.sort_by(|a, b| a.instance_id.cmp(&b.instance_id));
+    list
+}
@@ -0,0 +1,118 @@
+//! 工具版本状态缓存
+//!
+//! 启动阶段前端会同时请求多个工具的版本状态，逐个执行 `--version` 并请求镜像站较慢。
+//! 这里缓存最近一次批量检查的结果，在有效期内直接复用，过期或强制刷新时重新检查。
+
+use crate::services::tool::version::VersionInfo;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+/// 缓存有效期
+pub const TOOL_STATUS_TTL: Duration = Duration::from_secs(300);
+
+struct CachedStatus {
+    checked_at: Instant,
+    info: VersionInfo,
+}
+
+/// 工具 ID → 最近一次版本检查结果
+pub struct ToolStatusCache {
+    ttl: Duration,
+    entries: RwLock<HashMap<String, CachedStatus>>,
+}
+
+static GLOBAL_CACHE: Lazy<ToolStatusCache> = Lazy::new(|| ToolStatusCache::new(TOOL_STATUS_TTL));
+
+impl ToolStatusCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// 全局缓存实例
+    pub fn global() -> &'static ToolStatusCache {
+        &GLOBAL_CACHE
+    }
+
+    /// 获取未过期的检查结果（无记录或已过期时返回 None）
+    pub fn get(&self, tool_id: &str) -> Option<VersionInfo> {
+        let entries = self.entries.read().ok()?;
+        entries
+            .get(tool_id)
+            .filter(|cached| cached.checked_at.elapsed() < self.ttl)
+            .map(|cached| cached.info.clone())
+    }
+
+    /// 记录检查结果
+    pub fn store(&self, info: VersionInfo) {
+        if let Ok(mut entries) = self.entries.write() {
+            entries.insert(
+                info.tool_id.clone(),
+                CachedStatus {
+                    checked_at: Instant::now(),
+                    info,
+                },
+            );
+        }
+    }
+
+    /// 使单个工具的缓存失效（安装、更新、卸载后调用）
+    pub fn invalidate(&self, tool_id: &str) {
+        if let Ok(mut entries) = self.entries.write() {
+            entries.remove(tool_id);
+        }
+    }
+
+    /// 清空全部缓存
+    pub fn clear(&self) {
+        if let Ok(mut entries) = self.entries.write() {
+            entries.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::tool::version::VersionSource;
+
+    fn info(tool_id: &str) -> VersionInfo {
+        VersionInfo {
+            tool_id: tool_id.to_string(),
+            installed_version: Some("1.0.0".to_string()),
+            latest_version: Some("1.1.0".to_string()),
+            mirror_version: None,
+            mirror_is_stale: false,
+            has_update: true,
+            source: VersionSource::Mirror,
+        }
+    }
+
+    #[test]
+    fn test_cache_hit_and_invalidate() {
+        let cache = ToolStatusCache::new(Duration::from_secs(60));
+        cache.store(info("claude-code"));
+        cache.store(info("codex"));
+
+        assert!(cache.get("claude-code").unwrap().has_update);
+        assert!(cache.get("gemini-cli").is_none());
+
+        cache.invalidate("claude-code");
+        assert!(cache.get("claude-code").is_none());
+        assert!(cache.get("codex").is_some());
+
+        cache.clear();
+        assert!(cache.get("codex").is_none());
+    }
+
+    #[test]
+    fn test_cache_expires_after_ttl() {
+        let cache = ToolStatusCache::new(Duration::ZERO);
+        cache.store(info("codex"));
+        assert!(cache.get("codex").is_none());
+    }
+}
@@ -47,6 +47,22 @@ pub struct LocalToolInstance {
     pub is_builtin: bool,
     pub created_at: i64,
     pub updated_at: i64,
+    /// 固定的版本（设置后不再自动更新，需取消固定或强制更新）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pinned_version: Option<String>,
+    /// 已安装版本历史（最新在前，用于回滚）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub version_history: Vec<InstalledVersionRecord>,
+}
+
+/// 已安装版本记录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstalledVersionRecord {
+    pub version: String,
+    /// 安装时间（Unix 时间戳，秒）
+    pub installed_at: i64,
+    /// 来源：`install` / `update` / `specific` / `rollback`
+    pub source: String,
 }
 
 /// WSL 工具实例
@@ -223,6 +239,8 @@ impl ToolsConfig {
                             is_builtin: instance.is_builtin,
                             created_at: instance.created_at,
                             updated_at: instance.updated_at,
+                            pinned_version: None,
+                            version_history: Vec::new(),
                         });
                     }
                     ToolType::WSL => {
@@ -295,6 +313,8 @@ mod tests {
             is_builtin: true,
             created_at: 1733299200,
             updated_at: 1733299200,
+            pinned_version: None,
+            version_history: Vec::new(),
         });
 
         // 转换为 ToolInstance 列表
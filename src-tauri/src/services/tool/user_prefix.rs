@@ -0,0 +1,336 @@
+//! 非管理员 npm 安装回退
+//!
+//! 部分环境（公司电脑、未以管理员运行的 Windows、系统自带 Node 的 Linux）禁止写入
+//! npm 全局目录，`npm install -g` 会因权限失败。此时自动改为安装到用户级前缀目录
+//! （见 [`PlatformInfo::user_npm_prefix`]），并确保该目录在 PATH 中，
+//! 让工具检测和用户终端都能找到安装的命令。
+
+use crate::utils::{CommandExecutor, PlatformInfo};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// npm 镜像源
+const NPM_REGISTRY: &str = "https://registry.npmmirror.com";
+
+/// 权限失败的输出特征（npm、cmd、PowerShell 及中文系统提示）
+const PERMISSION_MARKERS: [&str; 7] = [
+    "eacces",
+    "eperm",
+    "permission denied",
+    "access is denied",
+    "operation not permitted",
+    "requires elevation",
+    "拒绝访问",
+];
+
+/// npm 安装位置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "prefix", rename_all = "snake_case")]
+pub enum NpmInstallTarget {
+    /// npm 默认全局目录
+    Global,
+    /// 用户级前缀目录
+    UserPrefix(PathBuf),
+}
+
+/// 用户级安装目录的 PATH 状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserPrefixPathStatus {
+    pub prefix: Option<String>,
+    pub bin_dir: Option<String>,
+    /// 用户级目录中已安装的 npm 包
+    pub installed_packages: Vec<String>,
+    /// 是否已在用户 PATH 中（Windows 读取用户环境变量，其他平台读取当前进程 PATH）
+    pub in_user_path: bool,
+    /// 需要手动处理时的提示
+    pub fix_hint: Option<String>,
+}
+
+/// 输出是否为权限失败
+pub fn is_permission_failure(output: &str) -> bool {
+    let output = output.to_lowercase();
+    PERMISSION_MARKERS.iter().any(|m| output.contains(m))
+}
+
+/// 从包规格中提取包名（`@scope/name@1.0.0` → `@scope/name`）
+pub fn package_name(package_spec: &str) -> &str {
+    match package_spec.rfind('@') {
+        Some(index) if index > 0 => &package_spec[..index],
+        _ => package_spec,
+    }
+}
+
+/// 包在用户级前缀中的安装目录
+fn package_dir(platform: &PlatformInfo, prefix: &Path, package: &str) -> PathBuf {
+    let modules = if platform.is_windows {
+        prefix.join("node_modules")
+    } else {
+        prefix.join("lib").join("node_modules")
+    };
+    package.split('/').fold(modules, |dir, part| dir.join(part))
+}
+
+/// 构建 npm 安装命令（指定前缀时安装到用户级目录）
+fn npm_install_command(package_spec: &str, prefix: Option<&Path>) -> String {
+    match prefix {
+        Some(prefix) => format!(
+            "npm install -g --prefix \"{}\" {package_spec} --registry {NPM_REGISTRY}",
+            prefix.display()
+        ),
+        None => format!("npm install -g {package_spec} --registry {NPM_REGISTRY}"),
+    }
+}
+
+/// 安装到用户级前缀目录并确保 PATH
+pub async fn install_to_user_prefix(
+    executor: &CommandExecutor,
+    package_spec: &str,
+) -> Result<NpmInstallTarget> {
+    let prefix = PlatformInfo::current()
+        .user_npm_prefix()
+        .ok_or_else(|| anyhow!("无法确定用户级安装目录"))?;
+    std::fs::create_dir_all(&prefix)?;
+
+    let result = executor
+        .execute_async(&npm_install_command(package_spec, Some(&prefix)))
+        .await;
+    if !result.success {
+        anyhow::bail!(
+            "❌ npm 安装失败（已尝试安装到用户目录 {}）\n\n{}",
+            prefix.display(),
+            result.stderr
+        );
+    }
+
+    if let Err(e) = ensure_user_path() {
+        tracing::warn!(error = ?e, "添加用户级安装目录到 PATH 失败");
+    }
+    tracing::info!(package = %package_spec, prefix = %prefix.display(), "已安装到用户级目录");
+    Ok(NpmInstallTarget::UserPrefix(prefix))
+}
+
+/// 全局安装 npm 包；因权限失败时自动回退到用户级目录
+pub async fn npm_install_global(
+    executor: &CommandExecutor,
+    package_spec: &str,
+) -> Result<NpmInstallTarget> {
+    let result = executor
+        .execute_async(&npm_install_command(package_spec, None))
+        .await;
+    if result.success {
+        return Ok(NpmInstallTarget::Global);
+    }
+
+    let output = format!("{}\n{}", result.stdout, result.stderr);
+    if !is_permission_failure(&output) {
+        anyhow::bail!("❌ npm 安装失败\n\n{}", result.stderr);
+    }
+
+    tracing::warn!(package = %package_spec, "全局安装无权限，回退到用户级目录");
+    install_to_user_prefix(executor, package_spec).await
+}
+
+/// 更新 npm 包：已安装在用户级目录的包在原位置更新，否则全局更新（无权限时回退）
+pub async fn npm_update_global(
+    executor: &CommandExecutor,
+    package: &str,
+) -> Result<NpmInstallTarget> {
+    let platform = PlatformInfo::current();
+    let in_user_prefix = platform
+        .user_npm_prefix()
+        .is_some_and(|prefix| package_dir(&platform, &prefix, package).exists());
+    if in_user_prefix {
+        return install_to_user_prefix(executor, &format!("{package}@latest")).await;
+    }
+
+    let result = executor
+        .execute_async(&format!(
+            "npm update -g {package} --registry {NPM_REGISTRY}"
+        ))
+        .await;
+    if result.success {
+        return Ok(NpmInstallTarget::Global);
+    }
+
+    let output = format!("{}\n{}", result.stdout, result.stderr);
+    if !is_permission_failure(&output) {
+        anyhow::bail!("❌ npm 更新失败\n\n{}", result.stderr);
+    }
+
+    tracing::warn!(package = %package, "全局更新无权限，回退到用户级目录");
+    install_to_user_prefix(executor, &format!("{package}@latest")).await
+}
+
+/// PATH 中是否包含指定目录（Windows 不区分大小写）
+pub(crate) fn path_contains(path_var: &str, dir: &Path, is_windows: bool) -> bool {
+    let separator = if is_windows { ';' } else { ':' };
+    let normalize = |p: &str| {
+        let p = p.trim().trim_end_matches(['/', '\\']);
+        if is_windows {
+            p.to_lowercase()
+        } else {
+            p.to_string()
+        }
+    };
+    let target = normalize(&dir.to_string_lossy());
+    path_var
+        .split(separator)
+        .any(|entry| normalize(entry) == target)
+}
+
+#[cfg(target_os = "windows")]
+fn read_user_path() -> Result<String> {
+    use winreg::enums::*;
+    use winreg::RegKey;
+
+    let env = RegKey::predef(HKEY_CURRENT_USER).open_subkey_with_flags("Environment", KEY_READ)?;
+    Ok(env.get_value("Path").unwrap_or_default())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn read_user_path() -> Result<String> {
+    Ok(std::env::var("PATH").unwrap_or_default())
+}
+
+/// 确保用户级安装目录在用户 PATH 中，返回是否做了修改
+///
+/// Windows 写入当前用户环境变量（新打开的终端生效）；
+/// 其他平台不修改 shell 配置，由 [`user_prefix_path_status`] 给出提示
+pub fn ensure_user_path() -> Result<bool> {
+    let platform = PlatformInfo::current();
+    let bin_dir = platform
+        .user_npm_bin_dir()
+        .ok_or_else(|| anyhow!("无法确定用户级安装目录"))?;
+    let user_path = read_user_path()?;
+    if path_contains(&user_path, &bin_dir, platform.is_windows) {
+        return Ok(false);
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use winreg::enums::*;
+        use winreg::RegKey;
+
+        let env = RegKey::predef(HKEY_CURRENT_USER)
+            .open_subkey_with_flags("Environment", KEY_READ | KEY_WRITE)?;
+        let new_path = if user_path.trim().is_empty() {
+            bin_dir.to_string_lossy().to_string()
+        } else {
+            format!("{};{}", user_path.trim_end_matches(';'), bin_dir.display())
+        };
+        // 以 REG_EXPAND_SZ 写入，保留原有 %VAR% 引用的展开
+        let bytes = new_path
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        env.set_raw_value(
+            "Path",
+            &winreg::RegValue {
+                bytes,
+                vtype: REG_EXPAND_SZ,
+            },
+        )?;
+        tracing::info!(dir = %bin_dir.display(), "已将用户级安装目录加入用户 PATH");
+        Ok(true)
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        Ok(false)
+    }
+}
+
+/// 检查用户级安装目录的 PATH 状态
+pub fn user_prefix_path_status() -> UserPrefixPathStatus {
+    let platform = PlatformInfo::current();
+    let prefix = platform.user_npm_prefix();
+    let bin_dir = platform.user_npm_bin_dir();
+
+    let installed_packages = prefix
+        .as_ref()
+        .map(|prefix| {
+            [
+                "@anthropic-ai/claude-code",
+                "@openai/codex",
+                "@google/gemini-cli",
+            ]
+            .into_iter()
+            .filter(|pkg| package_dir(&platform, prefix, pkg).exists())
+            .map(str::to_string)
+            .collect()
+        })
+        .unwrap_or_default();
+
+    let in_user_path = match (&bin_dir, read_user_path()) {
+        (Some(dir), Ok(path)) => path_contains(&path, dir, platform.is_windows),
+        _ => false,
+    };
+
+    let fix_hint = match &bin_dir {
+        Some(dir) if !in_user_path && !platform.is_windows => Some(format!(
+            "请在 shell 配置文件（如 ~/.zshrc 或 ~/.bashrc）中添加: export PATH=\"{}:$PATH\"",
+            dir.display()
+        )),
+        Some(_) if !in_user_path => {
+            Some("点击修复将目录加入用户 PATH，新打开的终端生效".to_string())
+        }
+        _ => None,
+    };
+
+    UserPrefixPathStatus {
+        prefix: prefix.map(|p| p.to_string_lossy().to_string()),
+        bin_dir: bin_dir.map(|p| p.to_string_lossy().to_string()),
+        installed_packages,
+        in_user_path,
+        fix_hint,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permission_failure_detection() {
+        assert!(is_permission_failure(
+            "npm ERR! Error: EACCES: permission denied, mkdir '/usr/lib/node_modules/@openai'"
+        ));
+        assert!(is_permission_failure("npm error code EPERM"));
+        assert!(is_permission_failure("Error: 拒绝访问。"));
+        assert!(!is_permission_failure("npm ERR! code ETIMEDOUT"));
+    }
+
+    #[test]
+    fn test_package_name_and_command() {
+        assert_eq!(package_name("@openai/codex@0.55.0"), "@openai/codex");
+        assert_eq!(package_name("@google/gemini-cli"), "@google/gemini-cli");
+        assert_eq!(package_name("typescript@5"), "typescript");
+
+        let prefix = Path::new("/home/张三/.duckcoding/npm-global");
+        assert_eq!(
+            npm_install_command("@openai/codex@latest", Some(prefix)),
+            format!(
+                "npm install -g --prefix \"{}\" @openai/codex@latest --registry {NPM_REGISTRY}",
+                prefix.display()
+            )
+        );
+    }
+
+    #[test]
+    fn test_path_contains() {
+        let dir = Path::new(r"C:\Users\张三\AppData\Local\DuckCoding\npm-global");
+        assert!(path_contains(
+            r"C:\Windows;c:\users\张三\appdata\local\duckcoding\npm-global\",
+            dir,
+            true
+        ));
+        assert!(!path_contains(r"C:\Windows", dir, true));
+        assert!(path_contains(
+            "/usr/bin:/home/a/.duckcoding/npm-global/bin/",
+            Path::new("/home/a/.duckcoding/npm-global/bin"),
+            false
+        ));
+    }
+}
@@ -1,9 +1,19 @@
-use crate::models::Tool;
-use crate::services::tool::DetectorRegistry;
+use crate::models::{InstallMethod, Tool};
+use crate::services::tool::mirror_state::{self, MirrorArtifactState};
+use crate::services::tool::status_cache::ToolStatusCache;
+use crate::services::tool::{DetectorRegistry, ToolDetector};
 use crate::utils::CommandExecutor;
 use anyhow::Result;
+use futures_util::stream::{self, StreamExt};
 use semver::Version;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// 批量检查时本地版本检测的最大并行数
+const CHECK_ALL_PARALLELISM: usize = 4;
+
+/// 批量检查的整体超时（镜像站请求与本地检测共享）
+const CHECK_ALL_TIMEOUT: Duration = Duration::from_secs(20);
 
 /// 版本信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,14 +47,16 @@ struct MirrorApiResponse {
     check_duration_ms: Option<u64>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 struct ToolVersionFromMirror {
     id: String,
     #[allow(dead_code)]
     name: Option<String>,
     latest_version: String,         // 官方最新版本（通常来自 npm）
     mirror_version: Option<String>, // 镜像实际可安装的版本
-    is_stale: Option<bool>,         // 镜像是否滞后
+    is_stale: Option<bool>,         // 镜像是否滞后（全局）
+    #[serde(default)]
+    artifacts: Vec<MirrorArtifactState>, // 分平台产物同步状态
     #[allow(dead_code)]
     release_date: Option<String>,
     #[allow(dead_code)]
@@ -118,16 +130,26 @@ impl VersionService {
 
         // 1. 尝试从镜像站获取最新版本
         match self.get_latest_from_mirror(tool_id).await {
-            Ok((latest_version, mirror_version, mirror_is_stale)) => {
+            Ok(mirror_tool) => {
+                let (mirror_version, mirror_is_stale) = self
+                    .resolve_platform_mirror(
+                        detector.as_ref(),
+                        installed_version.is_some(),
+                        &mirror_tool,
+                    )
+                    .await;
+
                 // 使用镜像版本判断是否有更新（因为这是实际能安装的版本）
-                let version_to_compare = mirror_version.as_ref().unwrap_or(&latest_version);
+                let version_to_compare = mirror_version
+                    .as_ref()
+                    .unwrap_or(&mirror_tool.latest_version);
                 let has_update =
                     Self::compare_versions(installed_version.as_deref(), version_to_compare);
 
                 return Ok(VersionInfo {
                     tool_id: tool_id.to_string(),
                     installed_version,
-                    latest_version: Some(latest_version),
+                    latest_version: Some(mirror_tool.latest_version),
                     mirror_version,
                     mirror_is_stale, // 传递镜像滞后状态
                     has_update,
@@ -152,34 +174,58 @@ impl VersionService {
     }
 
     /// 从镜像站 API 获取最新版本
-    async fn get_latest_from_mirror(
-        &self,
-        tool_id: &str,
-    ) -> Result<(String, Option<String>, bool)> {
-        // 统一通过带代理的 Client 进行请求
-        let client = crate::http_client::build_client().map_err(|e| anyhow::anyhow!(e))?;
-        let response = client
-            .get(&self.mirror_api_url)
-            .send()
+    async fn get_latest_from_mirror(&self, tool_id: &str) -> Result<ToolVersionFromMirror> {
+        self.get_all_from_mirror()
             .await?
-            .json::<MirrorApiResponse>()
-            .await?;
-
-        response
             .tools
-            .iter()
+            .into_iter()
             .find(|t| t.id == tool_id)
-            .map(|t| {
-                let mirror_is_stale = t.is_stale.unwrap_or(false);
-                (
-                    t.latest_version.clone(),
-                    t.mirror_version.clone(),
-                    mirror_is_stale,
-                )
-            })
             .ok_or_else(|| anyhow::anyhow!("工具 {tool_id} 不在镜像站 API 中"))
     }
 
+    /// 按当前平台与安装方式确定镜像版本和滞后状态
+    ///
+    /// 镜像站提供分平台产物状态时，以已安装实例的安装方式对应的产物为准；
+    /// 未安装、无法识别安装方式或镜像站未提供产物状态时，回退到全局状态
+    async fn resolve_platform_mirror(
+        &self,
+        detector: &dyn ToolDetector,
+        installed: bool,
+        mirror_tool: &ToolVersionFromMirror,
+    ) -> (Option<String>, bool) {
+        let global = (
+            mirror_tool.mirror_version.clone(),
+            mirror_tool.is_stale.unwrap_or(false),
+        );
+        if !installed || mirror_tool.artifacts.is_empty() {
+            return global;
+        }
+
+        let Some(method) = detector.detect_install_method(&self.command_executor).await else {
+            return global;
+        };
+        match mirror_state::select_current_platform(&mirror_tool.artifacts, &method) {
+            Some(artifact) => (artifact.version.clone().or(global.0), artifact.is_stale),
+            None => global,
+        }
+    }
+
+    /// 获取当前平台指定安装方式的镜像产物状态（安装预检使用）
+    ///
+    /// 优先使用最近一次版本检查的缓存，过期后重新请求镜像站；
+    /// 镜像站未提供该产物的分平台状态时返回 None
+    pub async fn platform_artifact_state(
+        &self,
+        tool_id: &str,
+        method: &InstallMethod,
+    ) -> Result<Option<MirrorArtifactState>> {
+        let artifacts = match mirror_state::cached_artifacts(tool_id) {
+            Some(artifacts) => artifacts,
+            None => self.get_latest_from_mirror(tool_id).await?.artifacts,
+        };
+        Ok(mirror_state::select_current_platform(&artifacts, method).cloned())
+    }
+
     /// 比较版本号
     fn compare_versions(installed: Option<&str>, latest: &str) -> bool {
         let latest_semver = Self::parse_version(latest);
@@ -216,6 +262,11 @@ impl VersionService {
 
         let json_response = response.json::<MirrorApiResponse>().await?;
 
+        // 记录分平台产物状态，供安装预检复用
+        for tool in &json_response.tools {
+            mirror_state::record_artifacts(&tool.id, tool.artifacts.clone());
+        }
+
         #[cfg(debug_assertions)]
         tracing::debug!(tool_count = json_response.tools.len(), "成功解析 JSON");
 
@@ -223,88 +274,95 @@ impl VersionService {
     }
 
     /// 批量检查所有工具（优化：单次 API 请求）
+    ///
+    /// 用户主动检查更新时调用，忽略缓存重新检查全部工具
     pub async fn check_all_tools(&self) -> Vec<VersionInfo> {
-        let detectors = self.detector_registry.all_detectors();
-        let mut results = Vec::new();
+        let tools: Vec<Tool> = self
+            .detector_registry
+            .all_tool_ids()
+            .iter()
+            .filter_map(|id| Tool::by_id(id))
+            .collect();
 
-        #[cfg(debug_assertions)]
-        tracing::debug!(tool_count = detectors.len(), "开始批量检查工具");
-
-        // 1. 尝试一次性从镜像站获取所有工具版本
-        match self.get_all_from_mirror().await {
-            Ok(mirror_data) => {
-                #[cfg(debug_assertions)]
-                tracing::debug!("镜像站数据获取成功");
-
-                // 成功获取镜像站数据，为每个工具构建 VersionInfo
-                for detector in &detectors {
-                    let tool_id = detector.tool_id();
-                    let installed_version = detector.get_version(&self.command_executor).await;
-
-                    // 从镜像站数据中查找该工具
-                    if let Some(mirror_tool) = mirror_data.tools.iter().find(|t| t.id == tool_id) {
-                        // 使用镜像版本判断是否有更新（这是实际能安装的版本）
-                        let version_to_compare = mirror_tool
-                            .mirror_version
-                            .as_ref()
-                            .unwrap_or(&mirror_tool.latest_version);
-
-                        let has_update = Self::compare_versions(
-                            installed_version.as_deref(),
-                            version_to_compare,
-                        );
-
-                        let mirror_is_stale = mirror_tool.is_stale.unwrap_or(false);
-
-                        #[cfg(debug_assertions)]
-                        tracing::debug!(
-                            tool_id = %tool_id,
-                            installed_version = ?installed_version,
-                            latest_version = %mirror_tool.latest_version,
-                            mirror_version = ?mirror_tool.mirror_version,
-                            mirror_is_stale = mirror_is_stale,
-                            has_update = has_update,
-                            "工具版本检查"
-                        );
-
-                        results.push(VersionInfo {
-                            tool_id: tool_id.to_string(),
-                            installed_version,
-                            latest_version: Some(mirror_tool.latest_version.clone()),
-                            mirror_version: mirror_tool.mirror_version.clone(),
-                            mirror_is_stale, // 传递镜像滞后状态
-                            has_update,
-                            source: VersionSource::Mirror,
-                        });
-                    } else {
-                        // 镜像站没有该工具数据，返回本地版本
-                        results.push(VersionInfo {
-                            tool_id: tool_id.to_string(),
-                            installed_version: installed_version.clone(),
-                            latest_version: installed_version,
-                            mirror_version: None,
-                            mirror_is_stale: false,
-                            has_update: false,
-                            source: VersionSource::MirrorFallback,
-                        });
-                    }
-                }
+        let cache = ToolStatusCache::global();
+        for tool in &tools {
+            cache.invalidate(&tool.id);
+        }
+        self.check_all(&tools).await
+    }
+
+    /// 并发检查多个工具的版本
+    ///
+    /// - 缓存有效期内的结果直接复用，仅检查缺失或已过期的工具
+    /// - 镜像站数据只请求一次，本地版本检测最多 `CHECK_ALL_PARALLELISM` 个并行
+    /// - 整批检查共享 `CHECK_ALL_TIMEOUT` 超时，超时未完成的工具不返回结果（也不写入缓存）
+    pub async fn check_all(&self, tools: &[Tool]) -> Vec<VersionInfo> {
+        let cache = ToolStatusCache::global();
+        let mut results = Vec::with_capacity(tools.len());
+        let mut pending = Vec::new();
+
+        for tool in tools {
+            match cache.get(&tool.id) {
+                Some(info) => results.push(info),
+                None => match self.detector_registry.get(&tool.id) {
+                    Some(detector) => pending.push(detector),
+                    None => tracing::warn!(tool_id = %tool.id, "未知的工具 ID，跳过版本检查"),
+                },
             }
-            Err(e) => {
-                // 镜像站不可用，回退到仅本地版本（无法判断是否有更新）
+        }
+
+        if pending.is_empty() {
+            return results;
+        }
+
+        #[cfg(debug_assertions)]
+        tracing::debug!(
+            cached = results.len(),
+            pending = pending.len(),
+            "开始并发检查工具版本"
+        );
+
+        let deadline = tokio::time::Instant::now() + CHECK_ALL_TIMEOUT;
+
+        // 1. 一次性从镜像站获取所有工具版本（失败或超时则回退到仅本地版本）
+        let mirror_data = match tokio::time::timeout_at(deadline, self.get_all_from_mirror()).await
+        {
+            Ok(Ok(data)) => Some(data),
+            Ok(Err(e)) => {
                 tracing::warn!(error = ?e, "镜像站 API 不可用，回退到本地检查");
-                for detector in &detectors {
-                    let tool_id = detector.tool_id();
-                    let installed_version = detector.get_version(&self.command_executor).await;
-                    results.push(VersionInfo {
-                        tool_id: tool_id.to_string(),
-                        installed_version: installed_version.clone(),
-                        latest_version: installed_version,
-                        mirror_version: None,
-                        mirror_is_stale: false,
-                        has_update: false,
-                        source: VersionSource::MirrorFallback,
-                    });
+                None
+            }
+            Err(_) => {
+                tracing::warn!("镜像站 API 请求超时，回退到本地检查");
+                None
+            }
+        };
+
+        // 2. 并发检测本地版本并构建 VersionInfo
+        let mut checks = stream::iter(pending.iter().map(|detector| {
+            let mirror_tool = mirror_data
+                .as_ref()
+                .and_then(|data| data.tools.iter().find(|t| t.id == detector.tool_id()));
+            self.build_version_info(detector.as_ref(), mirror_tool)
+        }))
+        .buffer_unordered(CHECK_ALL_PARALLELISM);
+
+        let mut checked = 0;
+        loop {
+            match tokio::time::timeout_at(deadline, checks.next()).await {
+                Ok(Some(info)) => {
+                    cache.store(info.clone());
+                    results.push(info);
+                    checked += 1;
+                }
+                Ok(None) => break,
+                Err(_) => {
+                    tracing::warn!(
+                        timed_out = pending.len() - checked,
+                        timeout_secs = CHECK_ALL_TIMEOUT.as_secs(),
+                        "部分工具版本检查超时"
+                    );
+                    break;
                 }
             }
         }
@@ -314,6 +372,60 @@ impl VersionService {
 
         results
     }
+
+    /// 检测单个工具的本地版本，并结合镜像站数据构建 VersionInfo
+    async fn build_version_info(
+        &self,
+        detector: &dyn ToolDetector,
+        mirror_tool: Option<&ToolVersionFromMirror>,
+    ) -> VersionInfo {
+        let tool_id = detector.tool_id();
+        let installed_version = detector.get_version(&self.command_executor).await;
+
+        let Some(mirror_tool) = mirror_tool else {
+            // 镜像站不可用或没有该工具数据，仅返回本地版本
+            return VersionInfo {
+                tool_id: tool_id.to_string(),
+                installed_version: installed_version.clone(),
+                latest_version: installed_version,
+                mirror_version: None,
+                mirror_is_stale: false,
+                has_update: false,
+                source: VersionSource::MirrorFallback,
+            };
+        };
+
+        let (mirror_version, mirror_is_stale) = self
+            .resolve_platform_mirror(detector, installed_version.is_some(), mirror_tool)
+            .await;
+
+        // 使用镜像版本判断是否有更新（这是实际能安装的版本）
+        let version_to_compare = mirror_version
+            .as_ref()
+            .unwrap_or(&mirror_tool.latest_version);
+        let has_update = Self::compare_versions(installed_version.as_deref(), version_to_compare);
+
+        #[cfg(debug_assertions)]
+        tracing::debug!(
+            tool_id = %tool_id,
+            installed_version = ?installed_version,
+            latest_version = %mirror_tool.latest_version,
+            mirror_version = ?mirror_version,
+            mirror_is_stale = mirror_is_stale,
+            has_update = has_update,
+            "工具版本检查"
+        );
+
+        VersionInfo {
+            tool_id: tool_id.to_string(),
+            installed_version,
+            latest_version: Some(mirror_tool.latest_version.clone()),
+            mirror_version,
+            mirror_is_stale, // 传递镜像滞后状态
+            has_update,
+            source: VersionSource::Mirror,
+        }
+    }
 }
 
 impl Default for VersionService {
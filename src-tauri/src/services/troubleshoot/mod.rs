@@ -0,0 +1,370 @@
+//! 引导式故障排查
+//!
+//! 将常见问题的排查思路编码为可执行的诊断流程：按顺序执行真实检查，
+//! 在第一个失败的步骤处停止（后续步骤标记为跳过），报告中标出失败步骤和修复建议。
+//!
+//! - proxy_auth: 透明代理已启动，但 CLI 请求返回 401
+//! - zero_tokens: 统计页面的 Token 始终为 0
+//! - version_check: 安装成功，但版本检测失败
+
+mod proxy_auth;
+mod version_check;
+mod zero_tokens;
+
+use crate::models::proxy_config::ToolProxyConfig;
+use crate::models::Tool;
+use crate::services::profile_manager::ProfileManager;
+use crate::services::proxy::ProxyManager;
+use crate::services::proxy_config_manager::ProxyConfigManager;
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::time::Instant;
+
+/// 诊断流程
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TroubleshootFlow {
+    /// 代理已启动但 CLI 返回 401
+    ProxyUnauthorized,
+    /// 统计显示 Token 为 0
+    ZeroTokenStats,
+    /// 安装成功但版本检测失败
+    VersionCheckFailed,
+}
+
+impl TroubleshootFlow {
+    pub fn all() -> [Self; 3] {
+        [
+            Self::ProxyUnauthorized,
+            Self::ZeroTokenStats,
+            Self::VersionCheckFailed,
+        ]
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Self::ProxyUnauthorized => "代理已启动，但 CLI 返回 401",
+            Self::ZeroTokenStats => "统计页面 Token 始终为 0",
+            Self::VersionCheckFailed => "安装成功，但版本检测失败",
+        }
+    }
+
+    /// 流程是否依赖透明代理（仅支持有代理配置的工具）
+    fn requires_proxy_tool(self) -> bool {
+        matches!(self, Self::ProxyUnauthorized | Self::ZeroTokenStats)
+    }
+}
+
+/// 流程说明（前端列表展示）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TroubleshootFlowInfo {
+    pub flow: TroubleshootFlow,
+    pub title: String,
+}
+
+/// 步骤状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    Passed,
+    /// 不阻断流程，但可能影响结果
+    Warning,
+    Failed,
+    /// 前序步骤失败，未执行
+    Skipped,
+}
+
+/// 单个检查的结果
+#[derive(Debug, Clone)]
+pub struct StepOutcome {
+    pub status: StepStatus,
+    pub detail: String,
+    pub fix: Option<String>,
+}
+
+impl StepOutcome {
+    pub fn passed(detail: impl Into<String>) -> Self {
+        Self {
+            status: StepStatus::Passed,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    pub fn warning(detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            status: StepStatus::Warning,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    pub fn failed(detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            status: StepStatus::Failed,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+/// 报告中的步骤
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagnosticStep {
+    pub id: String,
+    pub title: String,
+    pub status: StepStatus,
+    pub detail: String,
+    pub fix: Option<String>,
+    pub duration_ms: u64,
+}
+
+/// 诊断报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TroubleshootReport {
+    pub flow: TroubleshootFlow,
+    pub tool_id: String,
+    pub steps: Vec<DiagnosticStep>,
+    /// 第一个失败步骤的 ID（全部通过时为 None）
+    pub failed_step: Option<String>,
+    /// 失败与警告步骤的修复建议（按步骤顺序）
+    pub suggestions: Vec<String>,
+    /// 完成时间（Unix 时间戳，毫秒）
+    pub finished_at: i64,
+}
+
+/// 流程执行器：逐步执行检查，遇到失败后不再执行后续检查
+pub(crate) struct FlowRunner {
+    steps: Vec<DiagnosticStep>,
+    failed_step: Option<String>,
+}
+
+impl FlowRunner {
+    pub(crate) fn new() -> Self {
+        Self {
+            steps: Vec::new(),
+            failed_step: None,
+        }
+    }
+
+    /// 是否已有步骤失败
+    pub(crate) fn has_failed(&self) -> bool {
+        self.failed_step.is_some()
+    }
+
+    /// 执行一个步骤；已有步骤失败时不执行，直接标记为跳过
+    pub(crate) async fn step<F>(&mut self, id: &str, title: &str, check: F)
+    where
+        F: Future<Output = StepOutcome>,
+    {
+        if self.has_failed() {
+            self.steps.push(DiagnosticStep {
+                id: id.to_string(),
+                title: title.to_string(),
+                status: StepStatus::Skipped,
+                detail: "前序步骤失败，未执行".to_string(),
+                fix: None,
+                duration_ms: 0,
+            });
+            return;
+        }
+
+        let started = Instant::now();
+        let outcome = check.await;
+        if outcome.status == StepStatus::Failed {
+            self.failed_step = Some(id.to_string());
+        }
+        self.steps.push(DiagnosticStep {
+            id: id.to_string(),
+            title: title.to_string(),
+            status: outcome.status,
+            detail: outcome.detail,
+            fix: outcome.fix,
+            duration_ms: started.elapsed().as_millis() as u64,
+        });
+    }
+
+    pub(crate) fn finish(self, flow: TroubleshootFlow, tool_id: &str) -> TroubleshootReport {
+        let suggestions = self
+            .steps
+            .iter()
+            .filter(|s| matches!(s.status, StepStatus::Failed | StepStatus::Warning))
+            .filter_map(|s| s.fix.clone())
+            .collect();
+        TroubleshootReport {
+            flow,
+            tool_id: tool_id.to_string(),
+            steps: self.steps,
+            failed_step: self.failed_step,
+            suggestions,
+            finished_at: chrono::Utc::now().timestamp_millis(),
+        }
+    }
+}
+
+/// 诊断所需的运行时状态
+pub struct FlowContext<'a> {
+    /// 透明代理管理器（用于判断代理是否在运行）
+    pub proxy_manager: &'a ProxyManager,
+}
+
+/// 列出所有诊断流程
+pub fn list_flows() -> Vec<TroubleshootFlowInfo> {
+    TroubleshootFlow::all()
+        .into_iter()
+        .map(|flow| TroubleshootFlowInfo {
+            flow,
+            title: flow.title().to_string(),
+        })
+        .collect()
+}
+
+/// 执行诊断流程
+pub async fn run_flow(
+    flow: TroubleshootFlow,
+    tool_id: &str,
+    ctx: &FlowContext<'_>,
+) -> Result<TroubleshootReport> {
+    let tool = Tool::by_id(tool_id).ok_or_else(|| anyhow!("未找到工具: {}", tool_id))?;
+    if flow.requires_proxy_tool() && !matches!(tool_id, "claude-code" | "codex" | "gemini-cli") {
+        bail!("{} 不支持该诊断流程", tool.name);
+    }
+
+    let mut runner = FlowRunner::new();
+    match flow {
+        TroubleshootFlow::ProxyUnauthorized => proxy_auth::run(&mut runner, &tool, ctx).await,
+        TroubleshootFlow::ZeroTokenStats => zero_tokens::run(&mut runner, &tool, ctx).await,
+        TroubleshootFlow::VersionCheckFailed => version_check::run(&mut runner, &tool).await,
+    }
+
+    let report = runner.finish(flow, tool_id);
+    tracing::info!(
+        flow = ?flow,
+        tool_id = %tool_id,
+        failed_step = ?report.failed_step,
+        "故障排查完成"
+    );
+    Ok(report)
+}
+
+// ==================== 代理相关的公共检查 ====================
+
+/// 读取工具的透明代理配置
+fn load_proxy_config(tool_id: &str) -> Result<Option<ToolProxyConfig>> {
+    ProxyConfigManager::new()?.get_config(tool_id)
+}
+
+/// 检查透明代理是否已启用
+fn check_proxy_enabled(config: &Result<Option<ToolProxyConfig>>) -> StepOutcome {
+    match config {
+        Ok(Some(config)) if config.enabled => {
+            StepOutcome::passed(format!("透明代理已启用（端口 {}）", config.port))
+        }
+        Ok(Some(_)) => {
+            StepOutcome::failed("透明代理未启用", "在透明代理页面启用该工具的代理并保存配置")
+        }
+        Ok(None) => StepOutcome::failed(
+            "未找到该工具的透明代理配置",
+            "在透明代理页面为该工具创建代理配置",
+        ),
+        Err(e) => StepOutcome::failed(
+            format!("读取透明代理配置失败: {e}"),
+            "检查 ~/.duckcoding/proxy.json 是否损坏，必要时在透明代理页面重新保存配置",
+        ),
+    }
+}
+
+/// 检查透明代理是否在运行
+async fn check_proxy_running(ctx: &FlowContext<'_>, tool_id: &str) -> StepOutcome {
+    if ctx.proxy_manager.is_running(tool_id).await {
+        StepOutcome::passed("透明代理正在运行")
+    } else {
+        StepOutcome::failed("透明代理未运行", "在透明代理页面启动代理")
+    }
+}
+
+/// Base URL 是否指向本机指定端口的透明代理
+pub(crate) fn points_to_proxy(base_url: &str, port: u16) -> bool {
+    url::Url::parse(base_url.trim()).is_ok_and(|url| {
+        matches!(url.host_str(), Some("127.0.0.1" | "localhost" | "[::1]"))
+            && url.port_or_known_default() == Some(port)
+    })
+}
+
+/// 检查 CLI 原生配置是否指向透明代理
+fn check_native_endpoint(tool_id: &str, config: Option<&ToolProxyConfig>) -> StepOutcome {
+    let Some(config) = config else {
+        return StepOutcome::failed("未找到透明代理配置", "在透明代理页面保存代理配置");
+    };
+    let base_url = match ProfileManager::new().and_then(|m| m.read_native_base_url(tool_id)) {
+        Ok(base_url) => base_url,
+        Err(e) => {
+            return StepOutcome::failed(
+                format!("读取 CLI 配置失败: {e}"),
+                "确认工具配置文件存在且格式正确，或重新启动透明代理以写入配置",
+            )
+        }
+    };
+
+    if points_to_proxy(&base_url, config.port) {
+        StepOutcome::passed(format!("CLI 已指向透明代理：{base_url}"))
+    } else {
+        StepOutcome::failed(
+            format!(
+                "CLI 当前使用的地址为 {}，请求不会经过透明代理",
+                if base_url.is_empty() {
+                    "（未配置）"
+                } else {
+                    &base_url
+                }
+            ),
+            "重新启动透明代理以切换到代理 Profile；若手动修改过配置文件，请恢复代理地址",
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_runner_skips_after_failure() {
+        let mut runner = FlowRunner::new();
+        runner
+            .step("a", "A", async { StepOutcome::passed("ok") })
+            .await;
+        runner
+            .step("b", "B", async { StepOutcome::warning("slow", "升级") })
+            .await;
+        runner
+            .step("c", "C", async { StepOutcome::failed("bad", "修复 C") })
+            .await;
+        runner
+            .step("d", "D", async { panic!("失败后不应执行") })
+            .await;
+
+        let report = runner.finish(TroubleshootFlow::ZeroTokenStats, "codex");
+        let statuses: Vec<StepStatus> = report.steps.iter().map(|s| s.status).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                StepStatus::Passed,
+                StepStatus::Warning,
+                StepStatus::Failed,
+                StepStatus::Skipped
+            ]
+        );
+        assert_eq!(report.failed_step.as_deref(), Some("c"));
+        assert_eq!(report.suggestions, vec!["升级", "修复 C"]);
+    }
+
+    #[test]
+    fn test_points_to_proxy() {
+        assert!(points_to_proxy("http://127.0.0.1:8787", 8787));
+        assert!(points_to_proxy("http://localhost:8788/v1", 8788));
+        assert!(!points_to_proxy("http://127.0.0.1:8787", 8788));
+        assert!(!points_to_proxy("https://api.anthropic.com", 443));
+        assert!(!points_to_proxy("", 8787));
+    }
+}
@@ -0,0 +1,151 @@
+//! 代理已启动但 CLI 返回 401
+//!
+//! 401 可能来自两处：本地透明代理（CLI 携带的密钥与保护密钥不一致），
+//! 或上游（真实 API Key 无效）。先确认 CLI 确实连到代理且密钥一致，再验证上游密钥。
+
+use super::{
+    check_native_endpoint, check_proxy_enabled, check_proxy_running, load_proxy_config,
+    FlowContext, FlowRunner, StepOutcome,
+};
+use crate::models::proxy_config::{ClientAuth, ToolProxyConfig};
+use crate::models::Tool;
+use crate::services::profile_manager::health::probe_request;
+use crate::services::profile_manager::ProfileManager;
+use std::time::Duration;
+
+/// 上游探测超时时间
+const PROBE_TIMEOUT: Duration = Duration::from_secs(15);
+
+pub(super) async fn run(runner: &mut FlowRunner, tool: &Tool, ctx: &FlowContext<'_>) {
+    let loaded = load_proxy_config(&tool.id);
+    let config = loaded.as_ref().ok().cloned().flatten();
+
+    runner
+        .step("proxy_enabled", "透明代理配置", async {
+            check_proxy_enabled(&loaded)
+        })
+        .await;
+    runner
+        .step(
+            "proxy_running",
+            "透明代理运行状态",
+            check_proxy_running(ctx, &tool.id),
+        )
+        .await;
+    runner
+        .step("native_endpoint", "CLI 请求地址", async {
+            check_native_endpoint(&tool.id, config.as_ref())
+        })
+        .await;
+    runner
+        .step("native_key", "CLI 使用的密钥", async {
+            check_native_key(&tool.id, config.as_ref())
+        })
+        .await;
+    runner
+        .step("upstream_config", "上游配置", async {
+            check_upstream_config(config.as_ref())
+        })
+        .await;
+    runner
+        .step(
+            "upstream_auth",
+            "上游密钥验证",
+            check_upstream_auth(&tool.id, config.as_ref()),
+        )
+        .await;
+}
+
+/// CLI 携带的密钥必须与代理保护密钥一致，否则 401 由本地代理直接返回
+fn check_native_key(tool_id: &str, config: Option<&ToolProxyConfig>) -> StepOutcome {
+    let Some(local_key) = config.and_then(|c| c.local_api_key.as_deref()) else {
+        return StepOutcome::failed(
+            "透明代理保护密钥未设置",
+            "在透明代理页面生成保护密钥并保存，然后重启代理",
+        );
+    };
+    match ProfileManager::new().and_then(|m| m.read_native_credentials(tool_id)) {
+        Ok((api_key, _)) if api_key == local_key => {
+            StepOutcome::passed("CLI 使用的密钥与代理保护密钥一致")
+        }
+        Ok((api_key, _))
+            if config
+                .is_some_and(|c| matches!(c.authorize_client(&api_key), ClientAuth::Client(_))) =>
+        {
+            StepOutcome::passed("CLI 使用的是代理的客户端 Key")
+        }
+        Ok((api_key, _)) if api_key.is_empty() => StepOutcome::failed(
+            "CLI 配置中没有 API Key，代理会以 401 拒绝请求",
+            "重新启动透明代理，将保护密钥写入 CLI 配置",
+        ),
+        Ok(_) => StepOutcome::failed(
+            "CLI 使用的密钥与代理保护密钥不一致，401 由本地代理返回",
+            "重新启动透明代理以同步密钥；若终端设置了 API Key 环境变量，请移除后重开终端",
+        ),
+        Err(e) => StepOutcome::failed(
+            format!("读取 CLI 配置失败: {e}"),
+            "确认工具配置文件存在且格式正确",
+        ),
+    }
+}
+
+fn check_upstream_config(config: Option<&ToolProxyConfig>) -> StepOutcome {
+    let configured = |v: Option<&String>| v.is_some_and(|v| !v.trim().is_empty());
+    match config {
+        Some(c) if configured(c.real_api_key.as_ref()) && configured(c.real_base_url.as_ref()) => {
+            StepOutcome::passed(format!(
+                "上游地址：{}",
+                c.real_base_url.as_deref().unwrap_or_default()
+            ))
+        }
+        _ => StepOutcome::failed(
+            "未配置真实 API Key 或 Base URL",
+            "在透明代理页面选择上游 Profile 并保存",
+        ),
+    }
+}
+
+async fn check_upstream_auth(tool_id: &str, config: Option<&ToolProxyConfig>) -> StepOutcome {
+    let (Some(base_url), Some(api_key)) = (
+        config.and_then(|c| c.real_base_url.as_deref()),
+        config.and_then(|c| c.real_api_key.as_deref()),
+    ) else {
+        return StepOutcome::failed("未配置上游", "在透明代理页面选择上游 Profile");
+    };
+
+    let client = match crate::http_client::build_client() {
+        Ok(client) => client,
+        Err(e) => {
+            return StepOutcome::warning(
+                format!("创建 HTTP 客户端失败: {e}"),
+                "检查全局网络代理设置",
+            )
+        }
+    };
+    let (url, headers) = probe_request(tool_id, base_url, api_key);
+    let mut request = client.get(&url).timeout(PROBE_TIMEOUT);
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+
+    match request.send().await {
+        Ok(resp) if resp.status().is_success() => StepOutcome::passed("上游接受了真实 API Key"),
+        Ok(resp) if matches!(resp.status().as_u16(), 401 | 403) => StepOutcome::failed(
+            format!(
+                "上游拒绝了真实 API Key（HTTP {}），401 由上游返回",
+                resp.status().as_u16()
+            ),
+            "检查上游 Profile 的 API Key 是否正确、过期或余额不足，更新后重启代理",
+        ),
+        Ok(resp) => StepOutcome::warning(
+            format!(
+                "上游模型列表接口返回 HTTP {}，无法确认密钥状态",
+                resp.status().as_u16()
+            ),
+            "该上游可能不支持模型列表接口，请查看代理日志中 401 响应的来源",
+        ),
+        Err(e) => {
+            StepOutcome::warning(format!("请求上游失败: {e}"), "检查网络与全局代理设置后重试")
+        }
+    }
+}
@@ -0,0 +1,191 @@
+//! 安装成功但版本检测失败
+//!
+//! 依次确认最近一次安装的结果、命令能否找到、安装目录是否在 PATH 中、
+//! 运行时（Node.js）是否可用，最后实际执行版本命令并解析输出。
+
+use super::{FlowRunner, StepOutcome};
+use crate::models::Tool;
+use crate::services::tool::install_history::InstallHistory;
+use crate::services::tool::user_prefix::user_prefix_path_status;
+use crate::utils::version::parse_version;
+use crate::utils::CommandExecutor;
+
+/// 工具要求的最低 Node.js 主版本
+const MIN_NODE_MAJOR: u64 = 18;
+
+/// 错误输出最多展示的字符数
+const MAX_OUTPUT_CHARS: usize = 300;
+
+pub(super) async fn run(runner: &mut FlowRunner, tool: &Tool) {
+    let executor = CommandExecutor::new();
+    let command_name = tool
+        .check_command
+        .split_whitespace()
+        .next()
+        .unwrap_or(&tool.check_command)
+        .to_string();
+
+    runner
+        .step("last_install", "最近一次安装", async {
+            check_last_install(&tool.id)
+        })
+        .await;
+    runner
+        .step("command_found", "命令查找", async {
+            if executor.command_exists_async(&command_name).await {
+                StepOutcome::passed(format!("已找到 {command_name} 命令"))
+            } else {
+                StepOutcome::failed(
+                    format!("在 PATH 与常见安装目录中都找不到 {command_name} 命令"),
+                    "重新安装工具；若使用自定义目录安装，请在工具管理中手动添加实例",
+                )
+            }
+        })
+        .await;
+    runner
+        .step("user_path", "用户 PATH", async {
+            check_user_path(&tool.npm_package)
+        })
+        .await;
+    runner
+        .step("node_runtime", "Node.js 运行时", async {
+            check_node(&executor).await
+        })
+        .await;
+    runner
+        .step("version_output", "版本命令输出", async {
+            let result = executor.execute_async(&tool.check_command).await;
+            check_version_output(
+                &tool.check_command,
+                result.success,
+                &result.stdout,
+                &result.stderr,
+            )
+        })
+        .await;
+}
+
+fn check_last_install(tool_id: &str) -> StepOutcome {
+    let last = InstallHistory::new().and_then(|history| history.list(Some(tool_id), 1));
+    match last.as_deref() {
+        Ok([attempt, ..]) if !attempt.success => StepOutcome::failed(
+            format!(
+                "最近一次安装/更新失败：{}",
+                attempt.error.as_deref().unwrap_or("未知错误")
+            ),
+            "在安装记录中查看完整输出，解决问题后重新执行",
+        ),
+        Ok([_, ..]) => StepOutcome::passed("最近一次安装/更新成功"),
+        Ok([]) => StepOutcome::passed("没有安装记录（可能为外部安装）"),
+        Err(e) => StepOutcome::warning(
+            format!("读取安装记录失败: {e}"),
+            "检查 ~/.duckcoding/install_history.db 是否可读写",
+        ),
+    }
+}
+
+/// 安装在用户级目录但该目录不在 PATH 中时，终端里找不到命令
+fn check_user_path(npm_package: &str) -> StepOutcome {
+    let status = user_prefix_path_status();
+    if !status.installed_packages.iter().any(|p| p == npm_package) {
+        return StepOutcome::passed("未使用用户级安装目录");
+    }
+    if status.in_user_path {
+        return StepOutcome::passed("用户级安装目录已在 PATH 中");
+    }
+    StepOutcome::failed(
+        format!(
+            "工具安装在用户级目录 {}，但该目录不在 PATH 中",
+            status.bin_dir.as_deref().unwrap_or_default()
+        ),
+        status
+            .fix_hint
+            .unwrap_or_else(|| "将用户级安装目录加入 PATH 后重新打开终端".to_string()),
+    )
+}
+
+async fn check_node(executor: &CommandExecutor) -> StepOutcome {
+    let result = executor.execute_async("node --version").await;
+    if !result.success {
+        return StepOutcome::warning(
+            "未找到 Node.js（npm 安装的工具依赖 Node.js 运行）",
+            format!("安装 Node.js {MIN_NODE_MAJOR} 或更高版本"),
+        );
+    }
+    match parse_version(&result.stdout) {
+        Some(version) if version.major < MIN_NODE_MAJOR => StepOutcome::failed(
+            format!("Node.js 版本过低：{version}"),
+            format!("升级 Node.js 到 {MIN_NODE_MAJOR} 或更高版本"),
+        ),
+        Some(version) => StepOutcome::passed(format!("Node.js {version}")),
+        None => StepOutcome::warning(
+            format!("无法识别 Node.js 版本：{}", result.stdout),
+            "确认 node 命令指向正常的 Node.js 安装",
+        ),
+    }
+}
+
+/// 截断过长的命令输出
+fn excerpt(output: &str) -> String {
+    let output = output.trim();
+    if output.chars().count() <= MAX_OUTPUT_CHARS {
+        return output.to_string();
+    }
+    let head: String = output.chars().take(MAX_OUTPUT_CHARS).collect();
+    format!("{head}…")
+}
+
+pub(super) fn check_version_output(
+    command: &str,
+    success: bool,
+    stdout: &str,
+    stderr: &str,
+) -> StepOutcome {
+    if !success {
+        let output = if stderr.trim().is_empty() {
+            stdout
+        } else {
+            stderr
+        };
+        return StepOutcome::failed(
+            format!("执行 `{command}` 失败：{}", excerpt(output)),
+            "命令存在但无法运行：检查 Node.js 版本或重新安装（npm 安装可尝试强制重装）",
+        );
+    }
+    match parse_version(stdout) {
+        Some(version) => StepOutcome::passed(format!("当前版本 {version}")),
+        None => StepOutcome::failed(
+            format!("无法从输出中解析版本号：{}", excerpt(stdout)),
+            "PATH 中可能有同名的其他程序，检查命令实际路径或调整 PATH 顺序",
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::StepStatus;
+    use super::*;
+
+    #[test]
+    fn test_check_version_output() {
+        let ok = check_version_output("claude --version", true, "2.0.61 (Claude Code)", "");
+        assert_eq!(ok.status, StepStatus::Passed);
+        assert!(ok.detail.contains("2.0.61"));
+
+        let crashed = check_version_output(
+            "codex --version",
+            false,
+            "",
+            "SyntaxError: Unexpected token '?'",
+        );
+        assert_eq!(crashed.status, StepStatus::Failed);
+        assert!(crashed.detail.contains("SyntaxError"));
+
+        let garbage = check_version_output("gemini --version", true, "usage: gemini", "");
+        assert_eq!(garbage.status, StepStatus::Failed);
+        assert_eq!(
+            excerpt(&"a".repeat(400)).chars().count(),
+            MAX_OUTPUT_CHARS + 1
+        );
+    }
+}
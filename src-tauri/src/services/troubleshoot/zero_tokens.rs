@@ -0,0 +1,181 @@
+//! 统计页面 Token 始终为 0
+//!
+//! Token 统计只记录经过透明代理的请求：依次确认代理在运行、CLI 指向代理、
+//! 代理收到了请求、响应中解析到了 usage，最后检查成本是否匹配到价格模板。
+
+use super::{
+    check_native_endpoint, check_proxy_enabled, check_proxy_running, load_proxy_config,
+    FlowContext, FlowRunner, StepOutcome,
+};
+use crate::models::token_stats::{TokenLog, TokenStatsQuery};
+use crate::models::Tool;
+use crate::services::token_stats::TokenStatsManager;
+
+/// 参与分析的最近请求数
+const RECENT_LOG_LIMIT: u32 = 50;
+
+pub(super) async fn run(runner: &mut FlowRunner, tool: &Tool, ctx: &FlowContext<'_>) {
+    let loaded = load_proxy_config(&tool.id);
+    let config = loaded.as_ref().ok().cloned().flatten();
+
+    runner
+        .step("proxy_enabled", "透明代理配置", async {
+            check_proxy_enabled(&loaded)
+        })
+        .await;
+    runner
+        .step(
+            "proxy_running",
+            "透明代理运行状态",
+            check_proxy_running(ctx, &tool.id),
+        )
+        .await;
+    runner
+        .step("native_endpoint", "CLI 请求地址", async {
+            check_native_endpoint(&tool.id, config.as_ref())
+        })
+        .await;
+
+    let logs = if runner.has_failed() {
+        Ok(Vec::new())
+    } else {
+        TokenStatsManager::get()
+            .query_logs(TokenStatsQuery {
+                tool_type: Some(tool.id.clone()),
+                page_size: RECENT_LOG_LIMIT,
+                ..Default::default()
+            })
+            .map(|page| page.logs)
+    };
+
+    runner
+        .step("requests_recorded", "代理请求记录", async {
+            match &logs {
+                Ok(logs) if !logs.is_empty() => {
+                    StepOutcome::passed(format!("最近记录到 {} 条请求", logs.len()))
+                }
+                Ok(_) => StepOutcome::failed(
+                    "代理没有记录到该工具的请求",
+                    "在代理启动后新开终端运行 CLI（已打开的会话仍使用旧配置），然后重试",
+                ),
+                Err(e) => StepOutcome::failed(
+                    format!("读取统计数据库失败: {e}"),
+                    "检查 ~/.duckcoding/token_stats.db 是否可读写",
+                ),
+            }
+        })
+        .await;
+
+    let logs = logs.unwrap_or_default();
+    runner
+        .step("tokens_extracted", "Token 解析", async {
+            check_tokens_extracted(&logs)
+        })
+        .await;
+    runner
+        .step("cost_priced", "成本计算", async {
+            check_cost_priced(&logs)
+        })
+        .await;
+}
+
+fn total_tokens(log: &TokenLog) -> i64 {
+    log.input_tokens
+        + log.output_tokens
+        + log.cache_creation_tokens
+        + log.cache_read_tokens
+        + log.reasoning_tokens
+}
+
+/// 分析最近请求：区分全部失败、成功但未解析到 usage 两种情况
+pub(super) fn check_tokens_extracted(logs: &[TokenLog]) -> StepOutcome {
+    let succeeded: Vec<&TokenLog> = logs
+        .iter()
+        .filter(|l| l.request_status == "success")
+        .collect();
+    if succeeded.is_empty() {
+        let error = logs
+            .iter()
+            .find_map(|l| l.error_type.clone())
+            .unwrap_or_else(|| "未知错误".to_string());
+        return StepOutcome::failed(
+            format!(
+                "最近 {} 条请求均失败（{error}），失败请求不计 Token",
+                logs.len()
+            ),
+            "在统计页面查看失败请求的错误详情，先解决上游错误",
+        );
+    }
+
+    let tokens: i64 = succeeded.iter().map(|l| total_tokens(l)).sum();
+    if tokens == 0 {
+        return StepOutcome::failed(
+            format!("最近 {} 条成功请求均未解析到 usage", succeeded.len()),
+            "上游响应可能缺少 usage 字段或 SSE 格式不标准：可在透明代理设置中开启 SSE 兼容选项，\
+             使用第三方中转时请确认其返回 usage",
+        );
+    }
+
+    StepOutcome::passed(format!(
+        "最近 {} 条成功请求共 {tokens} tokens",
+        succeeded.len()
+    ))
+}
+
+/// Token 已记录但成本为 0 时提示未匹配价格模板
+pub(super) fn check_cost_priced(logs: &[TokenLog]) -> StepOutcome {
+    let unpriced = logs
+        .iter()
+        .filter(|l| total_tokens(l) > 0 && l.total_cost == 0.0)
+        .count();
+    if unpriced == 0 {
+        return StepOutcome::passed("成本计算正常");
+    }
+    let model = logs
+        .iter()
+        .find(|l| total_tokens(l) > 0 && l.total_cost == 0.0)
+        .map(|l| l.model.clone())
+        .unwrap_or_default();
+    StepOutcome::warning(
+        format!("{unpriced} 条请求有 Token 但成本为 0（模型 {model} 未匹配价格）"),
+        "为 Profile 或透明代理配置选择价格模板，或在价格模板中添加该模型",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::StepStatus;
+    use super::*;
+
+    fn log(status: &str, input: i64, output: i64, cost: f64) -> TokenLog {
+        let builder = TokenLog::test_builder()
+            .tokens(input, output)
+            .status(status)
+            .response_type("sse")
+            .cost(cost);
+        if status == "success" {
+            builder.build()
+        } else {
+            builder.error("upstream_error").build()
+        }
+    }
+
+    #[test]
+    fn test_check_tokens_extracted() {
+        let failed = check_tokens_extracted(&[log("failed", 0, 0, 0.0)]);
+        assert_eq!(failed.status, StepStatus::Failed);
+        assert!(failed.detail.contains("upstream_error"));
+
+        let no_usage = check_tokens_extracted(&[log("success", 0, 0, 0.0)]);
+        assert_eq!(no_usage.status, StepStatus::Failed);
+        assert!(no_usage.fix.unwrap().contains("SSE"));
+
+        let logs = [log("failed", 0, 0, 0.0), log("success", 100, 20, 0.0)];
+        assert_eq!(check_tokens_extracted(&logs).status, StepStatus::Passed);
+        assert_eq!(check_cost_priced(&logs).status, StepStatus::Warning);
+        assert_eq!(
+            check_cost_priced(&[log("success", 100, 20, 0.01)]).status,
+            StepStatus::Passed
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use duckcoding::core::init_logger;
+use duckcoding::core::{init_logger, install_panic_hook};
 use duckcoding::services::profile_manager::ProfileManager;
 use duckcoding::services::proxy_config_manager::ProxyConfigManager;
 use duckcoding::utils::config::read_global_config;
@@ -30,6 +30,7 @@ fn init_logging() -> Result<(), Box<dyn std::error::Error>> {
         eprintln!("WARNING: Failed to initialize logging system: {}", e);
         // 继续运行，但日志功能将不可用
     }
+    install_panic_hook(&log_config);
 
     tracing::info!("DuckCoding 应用启动");
     Ok(())
@@ -141,21 +142,20 @@ fn mark_expired_change_logs() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-/// 自动启动配置的代理
-async fn auto_start_proxies(
-    proxy_manager: &Arc<ProxyManager>,
-    _profile_manager: &Arc<tokio::sync::RwLock<ProfileManager>>,
-) {
-    duckcoding::auto_start_proxies(proxy_manager).await;
-}
-
 /// 执行所有启动初始化任务
 ///
-/// 按顺序执行：日志 → Profile → 迁移 → 标记过期日志 → 工具注册表 → 代理管理器
+/// 按顺序执行：日志 → 观察模式 → Profile → 迁移 → 标记过期日志 → 异常退出恢复 → 工具注册表 → 代理管理器 → 配置一致性检查 → 后台调度（价格同步、捕获清理、夜间维护、指标监听、管理 API）
 pub async fn initialize_app() -> Result<InitializationContext, Box<dyn std::error::Error>> {
     // 1. 初始化日志
     init_logging()?;
 
+    // 1.1 只读观察模式须在任何写入之前生效
+    let observer_mode = read_global_config()
+        .ok()
+        .flatten()
+        .is_some_and(|config| config.observer_mode);
+    duckcoding::data::guard::set_observer_mode(observer_mode);
+
     // 2. 初始化内置 Profile
     if let Err(e) = initialize_proxy_profiles() {
         tracing::warn!(error = ?e, "初始化内置 Profile 失败");
@@ -169,6 +169,12 @@ pub async fn initialize_app() -> Result<InitializationContext, Box<dyn std::erro
         tracing::warn!(error = ?e, "标记过期日志失败");
     }
 
+    // 4.1 检测上次异常退出并恢复（须在代理自启动之前，避免还原刚启动的代理配置）
+    duckcoding::services::recovery::run_startup_recovery();
+
+    // 4.2 还原上次运行遗留的供应商试用项目配置（试用代理不会随应用重启恢复）
+    duckcoding::services::proxy::trial::cleanup_all_trials();
+
     // 5. 创建工具注册表
     let tool_registry = ToolRegistry::new().await.expect("无法创建工具注册表");
 
@@ -177,23 +183,47 @@ pub async fn initialize_app() -> Result<InitializationContext, Box<dyn std::erro
         ProfileManager::new().expect("初始化 ProfileManager 失败"),
     ));
 
-    // 7. 创建代理管理器并异步启动自启动代理
+    // 7. 创建代理管理器（自启动代理在应用 setup 阶段进行，以便更新托盘）
     let proxy_manager = Arc::new(ProxyManager::new());
-    let proxy_manager_for_auto_start = proxy_manager.clone();
-    let profile_manager_for_auto_start = profile_manager.clone();
-    tauri::async_runtime::spawn(async move {
-        auto_start_proxies(
-            &proxy_manager_for_auto_start,
-            &profile_manager_for_auto_start,
-        )
-        .await;
-    });
+
+    // 7.1 定期检查工具配置是否指向未运行的代理
+    tauri::async_runtime::spawn(duckcoding::services::recovery::run_consistency_monitor(
+        proxy_manager.clone(),
+    ));
 
     // 8. 启动远程价格同步调度器
     tauri::async_runtime::spawn(async {
         duckcoding::services::pricing::remote_sync::start_sync_scheduler().await;
     });
 
+    // 9. 启动请求体捕获过期清理
+    tauri::async_runtime::spawn(duckcoding::services::proxy::capture_store::run_purge_scheduler());
+
+    // 10. 启动夜间维护调度器（代理重启、WAL 回写、日志与数据清理）
+    tauri::async_runtime::spawn(duckcoding::services::maintenance::run_scheduler(
+        proxy_manager.clone(),
+    ));
+
+    // 11. 启动 Prometheus 指标监听（如果启用）
+    if let Some(config) = read_global_config().ok().flatten() {
+        if let Err(e) = duckcoding::services::proxy::metrics::apply_config(&config.metrics).await {
+            tracing::error!(error = ?e, "启动 Prometheus 指标监听失败");
+        }
+
+        // 12. 启动本机管理 API（如果启用）
+        if let Err(e) =
+            duckcoding::services::admin_api::apply_config(&config.admin_api, proxy_manager.clone())
+                .await
+        {
+            tracing::error!(error = ?e, "启动管理 API 失败");
+        }
+
+        // 13. 启动 MCP HTTP 接入（如果启用）
+        if let Err(e) = duckcoding::services::mcp::apply_config(&config.mcp).await {
+            tracing::error!(error = ?e, "启动 MCP HTTP 接入失败");
+        }
+    }
+
     Ok(InitializationContext {
         proxy_manager,
         tool_registry: Arc::new(TokioMutex::new(tool_registry)),
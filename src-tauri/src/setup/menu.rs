@@ -20,6 +20,7 @@ use crate::commands::update_commands::{trigger_check_update_internal, UpdateServ
 use duckcoding::models::proxy_config::ToolProxyConfig;
 use duckcoding::services::config::watcher::suppress_external_detection_for_tool;
 use duckcoding::services::profile_manager::ProfileManager;
+use duckcoding::services::provider::ProviderHealthMonitor;
 use duckcoding::services::proxy_config_manager::ProxyConfigManager;
 
 /// Profile 菜单项 ID 前缀
@@ -149,11 +150,14 @@ fn build_tool_profile_submenu<R: Runtime>(
         for profile_name in profiles.iter().take(MAX_MENU_PROFILE_COUNT) {
             let is_active = active_profile == Some(profile_name.as_str());
             let menu_id = format!("{}{}:{}", PROFILE_MENU_PREFIX, tool_id, profile_name);
-            let display_text = if profile_name.len() > 30 {
+            let mut display_text = if profile_name.len() > 30 {
                 format!("{}...", &profile_name[..27])
             } else {
                 profile_name.to_string()
             };
+            if ProviderHealthMonitor::global().is_profile_unreachable(tool_id, profile_name) {
+                display_text.push_str("（不可达）");
+            }
             let item = CheckMenuItem::with_id(
                 app,
                 &menu_id,
@@ -686,6 +690,11 @@ pub fn refresh_app_menu(app: &AppHandle) -> Result<(), String> {
     refresh_app_menu_internal(app).map_err(|e| e.to_string())
 }
 
+/// 刷新应用菜单栏（异步上下文使用）
+pub async fn refresh_app_menu_async(app: &AppHandle) -> tauri::Result<()> {
+    refresh_app_menu_internal_async(app).await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -27,16 +27,42 @@ pub fn create_tray_menu<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<Menu<R>
     Ok(menu)
 }
 
-/// 聚焦主窗口
+/// 聚焦主窗口（后台运行时窗口尚未创建，此时创建窗口）
 pub fn focus_main_window<R: Runtime>(app: &AppHandle<R>) {
     if let Some(window) = app.get_webview_window("main") {
         tracing::info!("聚焦主窗口");
         restore_window_state(&window);
-    } else {
-        tracing::warn!("尝试聚焦时未找到主窗口");
+        return;
+    }
+    match create_main_window(app, true) {
+        Ok(window) => {
+            tracing::info!("后台运行中首次打开主窗口");
+            restore_window_state(&window);
+        }
+        Err(e) => tracing::error!(error = ?e, "创建主窗口失败"),
     }
 }
 
+/// 按 tauri.conf.json 中的 `main` 窗口配置创建主窗口（配置为 `create: false`，由启动方式决定何时创建）
+pub fn create_main_window<R: Runtime>(
+    app: &AppHandle<R>,
+    visible: bool,
+) -> tauri::Result<WebviewWindow<R>> {
+    let config = app
+        .config()
+        .app
+        .windows
+        .iter()
+        .find(|w| w.label == "main")
+        .cloned()
+        .ok_or(tauri::Error::WindowNotFound)?;
+    let window = tauri::WebviewWindowBuilder::from_config(app, &config)?
+        .visible(visible)
+        .build()?;
+    setup_window_close_handler(&window);
+    Ok(window)
+}
+
 /// 恢复窗口状态（跨平台支持）
 pub fn restore_window_state<R: Runtime>(window: &WebviewWindow<R>) {
     tracing::debug!(
@@ -93,8 +119,9 @@ pub fn setup_system_tray<R: Runtime>(app: &tauri::App<R>) -> tauri::Result<()> {
     let tray_menu = create_tray_menu(app.handle())?;
     let app_handle2 = app.handle().clone();
 
-    let _tray = TrayIconBuilder::new()
+    let _tray = TrayIconBuilder::with_id("main")
         .icon(app.default_window_icon().unwrap().clone())
+        .tooltip("DuckCoding")
         .menu(&tray_menu)
         .show_menu_on_left_click(false)
         .on_menu_event(move |app, event| {
@@ -139,28 +166,40 @@ pub fn setup_system_tray<R: Runtime>(app: &tauri::App<R>) -> tauri::Result<()> {
     Ok(())
 }
 
+/// 按运行中的代理更新托盘提示文字
+#[cfg(not(target_os = "macos"))]
+pub fn update_tray_tooltip<R: Runtime>(app: &AppHandle<R>, running_tools: &[String]) {
+    let Some(tray) = app.tray_by_id("main") else {
+        return;
+    };
+    let tooltip = if running_tools.is_empty() {
+        "DuckCoding".to_string()
+    } else {
+        format!("DuckCoding - 代理运行中: {}", running_tools.join(", "))
+    };
+    if let Err(e) = tray.set_tooltip(Some(tooltip)) {
+        tracing::error!(error = ?e, "更新托盘提示失败");
+    }
+}
+
 const CLOSE_CONFIRM_EVENT: &str = "duckcoding://request-close-action";
 
 /// 设置窗口关闭处理（最小化到托盘而不是退出，跨平台）
-pub fn setup_window_close_handler<R: Runtime>(app: &tauri::App<R>) -> tauri::Result<()> {
-    if let Some(window) = app.get_webview_window("main") {
-        let window_clone = window.clone();
-
-        window.on_window_event(move |event| {
-            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
-                tracing::info!("窗口关闭请求 - 提示用户选择操作");
-                // 阻止默认关闭行为
-                api.prevent_close();
-                if let Err(err) = window_clone.emit(CLOSE_CONFIRM_EVENT, ()) {
-                    tracing::error!(
-                        error = ?err,
-                        "发送关闭确认事件失败，降级为隐藏窗口"
-                    );
-                    ::duckcoding::ui::hide_window_to_tray(&window_clone);
-                }
+fn setup_window_close_handler<R: Runtime>(window: &WebviewWindow<R>) {
+    let window_clone = window.clone();
+
+    window.on_window_event(move |event| {
+        if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+            tracing::info!("窗口关闭请求 - 提示用户选择操作");
+            // 阻止默认关闭行为
+            api.prevent_close();
+            if let Err(err) = window_clone.emit(CLOSE_CONFIRM_EVENT, ()) {
+                tracing::error!(
+                    error = ?err,
+                    "发送关闭确认事件失败，降级为隐藏窗口"
+                );
+                ::duckcoding::ui::hide_window_to_tray(&window_clone);
             }
-        });
-    }
-
-    Ok(())
+        }
+    });
 }
@@ -6,11 +6,46 @@
 //! - Windows: 通过注册表 HKCU\Software\Microsoft\Windows\CurrentVersion\Run
 //! - macOS: 通过 LaunchAgents plist 文件
 //! - Linux: 通过 XDG autostart desktop 文件
+//!
+//! 自启动项会附加 [`AUTOSTART_ARG`] 参数，启动时据此按 `GlobalConfig.startup_mode`
+//! 决定显示窗口、隐藏到托盘或后台运行（见 [`resolve_startup_mode`]）。
 
 use crate::core::error::AppError;
+use crate::models::config::StartupMode;
 use std::env;
+use std::ffi::OsString;
 use std::path::PathBuf;
 
+/// 由自启动项启动时附加的参数
+pub const AUTOSTART_ARG: &str = "--autostart";
+
+/// 手动以后台方式启动（不创建窗口）
+pub const BACKGROUND_ARG: &str = "--background";
+
+/// 手动以隐藏到托盘方式启动
+pub const MINIMIZED_ARG: &str = "--minimized";
+
+/// 按启动参数确定本次的启动方式
+///
+/// 显式的 `--background` / `--minimized` 优先；由自启动项启动时使用配置的方式；
+/// 其余情况（用户手动打开）始终显示窗口。
+pub fn resolve_startup_mode<I, T>(args: I, configured: StartupMode) -> StartupMode
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString>,
+{
+    let args: Vec<OsString> = args.into_iter().skip(1).map(Into::into).collect();
+    if args.iter().any(|a| a == BACKGROUND_ARG) {
+        StartupMode::Background
+    } else if args.iter().any(|a| a == MINIMIZED_ARG) {
+        StartupMode::Minimized
+    } else if args.iter().any(|a| a == AUTOSTART_ARG) {
+        configured
+    } else {
+        StartupMode::Window
+    }
+}
+
 /// 启用开机自启动
 pub fn enable_auto_startup() -> Result<(), AppError> {
     #[cfg(target_os = "windows")]
@@ -113,8 +148,9 @@ fn enable_windows_startup() -> Result<(), AppError> {
             message: format!("无法打开注册表启动项: {}", e),
         })?;
 
+    let command = format!("\"{}\" {}", exe_path_str, AUTOSTART_ARG);
     run_key
-        .set_value("DuckCoding", &exe_path_str)
+        .set_value("DuckCoding", &command)
         .map_err(|e| AppError::Internal {
             message: format!("无法写入注册表启动项: {}", e),
         })?;
@@ -202,12 +238,13 @@ fn enable_macos_startup() -> Result<(), AppError> {
     <key>ProgramArguments</key>
     <array>
         <string>{}</string>
+        <string>{}</string>
     </array>
     <key>RunAtLoad</key>
     <true/>
 </dict>
 </plist>"#,
-        exe_path_str
+        exe_path_str, AUTOSTART_ARG
     );
 
     fs::write(&plist_path, plist_content).map_err(|e| AppError::Internal {
@@ -273,13 +310,13 @@ fn enable_linux_startup() -> Result<(), AppError> {
         r#"[Desktop Entry]
 Type=Application
 Name=DuckCoding
-Exec={}
+Exec="{}" {}
 Hidden=false
 NoDisplay=false
 X-GNOME-Autostart-enabled=true
 Comment=DuckCoding AI Tools Configuration Manager
 "#,
-        exe_path_str
+        exe_path_str, AUTOSTART_ARG
     );
 
     fs::write(&desktop_path, desktop_content).map_err(|e| AppError::Internal {
@@ -315,6 +352,32 @@ fn is_linux_startup_enabled() -> Result<bool, AppError> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_resolve_startup_mode() {
+        let configured = StartupMode::Background;
+        // 用户手动打开始终显示窗口
+        assert_eq!(
+            resolve_startup_mode(["duckcoding"], configured),
+            StartupMode::Window
+        );
+        assert_eq!(
+            resolve_startup_mode(["duckcoding", "--autostart"], configured),
+            StartupMode::Background
+        );
+        assert_eq!(
+            resolve_startup_mode(["duckcoding", "--autostart"], StartupMode::Minimized),
+            StartupMode::Minimized
+        );
+        assert_eq!(
+            resolve_startup_mode(["duckcoding", "--minimized"], StartupMode::Window),
+            StartupMode::Minimized
+        );
+        assert_eq!(
+            resolve_startup_mode(["duckcoding", "--background"], StartupMode::Window),
+            StartupMode::Background
+        );
+    }
+
     #[test]
     fn test_get_executable_path() {
         let result = get_executable_path();
@@ -1,6 +1,10 @@
+use super::console_encoding::{console_code_page, decode_console_output};
 use super::platform::PlatformInfo;
-use std::io;
-use std::process::{Command, Output};
+use std::io::{self, Read};
+use std::process::{Command, Output, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
 
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
@@ -15,15 +19,36 @@ pub struct CommandResult {
 }
 
 impl CommandResult {
+    /// 从进程输出构建结果（按控制台代码页解码，兼容 UTF-16 与非 UTF-8 输出）
     pub fn from_output(output: Output) -> Self {
+        let code_page = console_code_page();
         CommandResult {
             success: output.status.success(),
-            stdout: String::from_utf8_lossy(&output.stdout).trim().to_string(),
-            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            stdout: decode_console_output(&output.stdout, code_page)
+                .trim()
+                .to_string(),
+            stderr: decode_console_output(&output.stderr, code_page)
+                .trim()
+                .to_string(),
             exit_code: output.status.code(),
         }
     }
 
+    /// 被取消的命令（保留已产生的输出）
+    pub fn cancelled(stdout: String, stderr: String) -> Self {
+        let stderr = if stderr.trim().is_empty() {
+            COMMAND_CANCELLED.to_string()
+        } else {
+            format!("{}\n{}", stderr.trim(), COMMAND_CANCELLED)
+        };
+        CommandResult {
+            success: false,
+            stdout: stdout.trim().to_string(),
+            stderr,
+            exit_code: None,
+        }
+    }
+
     pub fn from_error(error: io::Error) -> Self {
         CommandResult {
             success: false,
@@ -34,19 +59,130 @@ impl CommandResult {
     }
 }
 
+/// 代理相关环境变量（版本检查等场景需要绕过代理）
+const PROXY_ENV_VARS: [&str; 6] = [
+    "HTTP_PROXY",
+    "HTTPS_PROXY",
+    "ALL_PROXY",
+    "http_proxy",
+    "https_proxy",
+    "all_proxy",
+];
+
+/// 构建 Windows `cmd` 命令行参数
+///
+/// - `/D` 跳过 AutoRun，避免用户 cmd 配置干扰
+/// - `/S /C "..."` 只剥离最外层引号，命令中的引号原样传递
+/// - 先切换到 UTF-8 代码页（65001），使子进程输出 UTF-8
+pub fn windows_command_line(command_str: &str) -> String {
+    format!("/D /S /C \"chcp 65001 >nul & {}\"", command_str)
+}
+
+/// 为 POSIX shell 转义单个参数（WSL / 远程主机上执行的命令使用）
+pub fn posix_quote(arg: &str) -> String {
+    if !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./@:=+%".contains(c))
+    {
+        return arg.to_string();
+    }
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+/// 命令执行记录（安装/更新历史使用）
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CommandTranscriptStep {
+    pub command: String,
+    pub success: bool,
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    pub duration_ms: u64,
+}
+
+/// 输出流类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// 输出行回调
+pub type OutputLineCallback = Arc<dyn Fn(OutputStream, &str) + Send + Sync>;
+
+/// 命令输出观察者（安装进度使用）
+///
+/// 设置后命令以管道方式执行，每读到一行输出就回调一次；
+/// 取消令牌触发后终止正在执行的子进程，后续命令直接返回失败
+#[derive(Clone)]
+pub struct OutputObserver {
+    on_line: OutputLineCallback,
+    cancel: CancellationToken,
+}
+
+impl OutputObserver {
+    pub fn new(on_line: OutputLineCallback, cancel: CancellationToken) -> Self {
+        Self { on_line, cancel }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+}
+
+/// 子进程状态轮询间隔（用于响应取消）
+const CHILD_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 命令被取消时的错误信息
+pub const COMMAND_CANCELLED: &str = "命令已取消";
+
 /// 命令执行器
 #[derive(Clone)]
 pub struct CommandExecutor {
     platform: PlatformInfo,
+    /// 命令执行记录（仅 `with_transcript` 创建的执行器记录）
+    transcript: Option<Arc<Mutex<Vec<CommandTranscriptStep>>>>,
+    /// 输出观察者（仅 `with_observer` 创建的执行器设置）
+    observer: Option<OutputObserver>,
 }
 
 impl CommandExecutor {
     pub fn new() -> Self {
         CommandExecutor {
             platform: PlatformInfo::current(),
+            transcript: None,
+            observer: None,
         }
     }
 
+    /// 创建记录执行过程的执行器（克隆出的执行器共享同一份记录）
+    pub fn with_transcript(&self) -> Self {
+        CommandExecutor {
+            platform: self.platform.clone(),
+            transcript: Some(Arc::new(Mutex::new(Vec::new()))),
+            observer: self.observer.clone(),
+        }
+    }
+
+    /// 创建实时输出并可取消的执行器（保留当前的执行记录）
+    pub fn with_observer(&self, observer: OutputObserver) -> Self {
+        CommandExecutor {
+            platform: self.platform.clone(),
+            transcript: self.transcript.clone(),
+            observer: Some(observer),
+        }
+    }
+
+    /// 获取已记录的命令执行过程
+    pub fn transcript(&self) -> Vec<CommandTranscriptStep> {
+        self.transcript
+            .as_ref()
+            .and_then(|t| t.lock().ok().map(|steps| steps.clone()))
+            .unwrap_or_default()
+    }
+
     /// 执行命令（使用增强的 PATH）
     ///
     /// 智能重试策略：
@@ -54,13 +190,20 @@ impl CommandExecutor {
     /// 2. 如果失败且 exit code = 127（命令未找到），尝试扫描安装器
     /// 3. 将安装器目录加入 PATH 后重试
     pub fn execute(&self, command_str: &str) -> CommandResult {
+        let started = Instant::now();
+        let result = self.execute_with_retry(command_str);
+        self.record_step(command_str, &result, started);
+        result
+    }
+
+    fn execute_with_retry(&self, command_str: &str) -> CommandResult {
         let enhanced_path = self.platform.build_enhanced_path();
 
         // 第一次尝试
         let result = self.execute_with_path(command_str, &enhanced_path);
 
         // 如果是 127 错误（命令未找到），尝试扫描安装器并重试
-        if !result.success && result.exit_code == Some(127) {
+        if !result.success && result.exit_code == Some(127) && !self.is_cancelled() {
             tracing::warn!(
                 "命令执行失败 (exit 127): {}，尝试扫描安装器后重试",
                 command_str
@@ -77,34 +220,92 @@ impl CommandExecutor {
         result
     }
 
-    /// 使用指定的 PATH 执行命令
-    fn execute_with_path(&self, command_str: &str, path_env: &str) -> CommandResult {
-        let output = if self.platform.is_windows {
+    /// 构建 shell 命令（所有命令执行的统一入口）
+    ///
+    /// Windows 下使用原样传递的命令行并切换 UTF-8 代码页，
+    /// 避免参数转义破坏引号以及中文用户名/路径输出乱码
+    fn build_command(&self, command_str: &str, path_env: &str) -> Command {
+        let mut command = if self.platform.is_windows {
+            let mut command = Command::new("cmd");
             #[cfg(target_os = "windows")]
             {
-                Command::new("cmd")
-                    .args(["/C", command_str])
-                    .creation_flags(0x08000000) // CREATE_NO_WINDOW
-                    .env("PATH", path_env)
-                    .output()
+                command
+                    .raw_arg(windows_command_line(command_str))
+                    .creation_flags(0x08000000); // CREATE_NO_WINDOW
             }
             #[cfg(not(target_os = "windows"))]
             {
-                Command::new("cmd")
-                    .args(["/C", command_str])
-                    .env("PATH", path_env)
-                    .output()
+                command.args(["/C", command_str]);
             }
+            command
         } else {
-            Command::new("sh")
-                .args(["-c", command_str])
-                .env("PATH", path_env)
-                .output()
+            let mut command = Command::new("sh");
+            command.args(["-c", command_str]);
+            command
         };
+        command.env("PATH", path_env);
+        command
+    }
 
-        match output {
-            Ok(output) => CommandResult::from_output(output),
-            Err(e) => CommandResult::from_error(e),
+    /// 使用指定的 PATH 执行命令
+    fn execute_with_path(&self, command_str: &str, path_env: &str) -> CommandResult {
+        self.run(self.build_command(command_str, path_env))
+    }
+
+    /// 执行已构建的命令（设置了观察者时实时输出）
+    fn run(&self, mut command: Command) -> CommandResult {
+        match &self.observer {
+            Some(observer) => run_observed(command, observer),
+            None => match command.output() {
+                Ok(output) => CommandResult::from_output(output),
+                Err(e) => CommandResult::from_error(e),
+            },
+        }
+    }
+
+    /// 是否已被取消（未设置观察者时始终为 false）
+    pub fn is_cancelled(&self) -> bool {
+        self.observer.as_ref().is_some_and(|o| o.is_cancelled())
+    }
+
+    /// 执行命令但不使用代理（移除所有代理环境变量，不做安装器扫描重试）
+    pub fn execute_without_proxy(&self, command_str: &str) -> CommandResult {
+        let started = Instant::now();
+        let mut command = self.build_command(command_str, &self.platform.build_enhanced_path());
+        for var in PROXY_ENV_VARS {
+            command.env_remove(var);
+        }
+        let result = self.run(command);
+        self.record_step(command_str, &result, started);
+        result
+    }
+
+    /// 执行命令但不使用代理（异步）
+    pub async fn execute_without_proxy_async(&self, command_str: &str) -> CommandResult {
+        let command_str = command_str.to_string();
+        let executor = self.clone();
+
+        tokio::task::spawn_blocking(move || executor.execute_without_proxy(&command_str))
+            .await
+            .unwrap_or_else(|e| CommandResult {
+                success: false,
+                stdout: String::new(),
+                stderr: format!("任务执行失败: {e}"),
+                exit_code: None,
+            })
+    }
+
+    /// 写入执行记录（仅记录模式下生效）
+    fn record_step(&self, command_str: &str, result: &CommandResult, started: Instant) {
+        if let Some(mut steps) = self.transcript.as_ref().and_then(|t| t.lock().ok()) {
+            steps.push(CommandTranscriptStep {
+                command: command_str.to_string(),
+                success: result.success,
+                exit_code: result.exit_code,
+                stdout: result.stdout.clone(),
+                stderr: result.stderr.clone(),
+                duration_ms: started.elapsed().as_millis() as u64,
+            });
         }
     }
 
@@ -175,19 +376,16 @@ impl CommandExecutor {
     /// 执行命令（异步）
     pub async fn execute_async(&self, command_str: &str) -> CommandResult {
         let command_str = command_str.to_string();
-        let platform = self.platform.clone();
-
-        tokio::task::spawn_blocking(move || {
-            let executor = CommandExecutor { platform };
-            executor.execute(&command_str)
-        })
-        .await
-        .unwrap_or_else(|e| CommandResult {
-            success: false,
-            stdout: String::new(),
-            stderr: format!("任务执行失败: {e}"),
-            exit_code: None,
-        })
+        let executor = self.clone();
+
+        tokio::task::spawn_blocking(move || executor.execute(&command_str))
+            .await
+            .unwrap_or_else(|e| CommandResult {
+                success: false,
+                stdout: String::new(),
+                stderr: format!("任务执行失败: {e}"),
+                exit_code: None,
+            })
     }
 
     /// 检查命令是否存在
@@ -239,6 +437,111 @@ impl CommandExecutor {
     }
 }
 
+/// 以管道方式执行命令，逐行回调输出，并在取消时终止子进程
+///
+/// 注意：终止的是 shell 进程本身，由其派生的子进程可能需要自行退出
+fn run_observed(mut command: Command, observer: &OutputObserver) -> CommandResult {
+    if observer.is_cancelled() {
+        return CommandResult::cancelled(String::new(), String::new());
+    }
+
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => return CommandResult::from_error(e),
+    };
+
+    let stdout_reader = child
+        .stdout
+        .take()
+        .map(|out| spawn_line_reader(out, OutputStream::Stdout, observer.clone()));
+    let stderr_reader = child
+        .stderr
+        .take()
+        .map(|err| spawn_line_reader(err, OutputStream::Stderr, observer.clone()));
+
+    let status = loop {
+        if observer.is_cancelled() {
+            let _ = child.kill();
+            let _ = child.wait();
+            break None;
+        }
+        match child.try_wait() {
+            Ok(Some(status)) => break Some(Ok(status)),
+            Ok(None) => std::thread::sleep(CHILD_POLL_INTERVAL),
+            Err(e) => break Some(Err(e)),
+        }
+    };
+
+    let join = |reader: Option<std::thread::JoinHandle<Vec<u8>>>| {
+        reader
+            .and_then(|handle| handle.join().ok())
+            .unwrap_or_default()
+    };
+    let stdout = join(stdout_reader);
+    let stderr = join(stderr_reader);
+
+    match status {
+        Some(Ok(status)) => CommandResult::from_output(Output {
+            status,
+            stdout,
+            stderr,
+        }),
+        Some(Err(e)) => CommandResult::from_error(e),
+        None => {
+            let code_page = console_code_page();
+            CommandResult::cancelled(
+                decode_console_output(&stdout, code_page),
+                decode_console_output(&stderr, code_page),
+            )
+        }
+    }
+}
+
+/// 在后台线程读取输出，按 `\n` / `\r` 切分行并回调，返回完整输出
+///
+/// 下载类命令常用 `\r` 刷新同一行的进度，这里同样视为一行
+fn spawn_line_reader<R: Read + Send + 'static>(
+    mut reader: R,
+    stream: OutputStream,
+    observer: OutputObserver,
+) -> std::thread::JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let code_page = console_code_page();
+        let mut all = Vec::new();
+        let mut line = Vec::new();
+        let mut buf = [0u8; 4096];
+        let emit = |line: &[u8]| {
+            let text = decode_console_output(line, code_page);
+            let text = text.trim();
+            if !text.is_empty() {
+                (observer.on_line)(stream, text);
+            }
+        };
+
+        loop {
+            let n = match reader.read(&mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => n,
+            };
+            all.extend_from_slice(&buf[..n]);
+            for &byte in &buf[..n] {
+                if byte == b'\n' || byte == b'\r' {
+                    emit(&line);
+                    line.clear();
+                } else {
+                    line.push(byte);
+                }
+            }
+        }
+        emit(&line);
+        all
+    })
+}
+
 impl Default for CommandExecutor {
     fn default() -> Self {
         Self::new()
@@ -249,6 +552,14 @@ impl Default for CommandExecutor {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_posix_quote() {
+        assert_eq!(posix_quote("/usr/bin/claude"), "/usr/bin/claude");
+        assert_eq!(posix_quote("my dir"), "'my dir'");
+        assert_eq!(posix_quote("it's"), r"'it'\''s'");
+        assert_eq!(posix_quote(""), "''");
+    }
+
     #[test]
     fn test_command_executor() {
         let executor = CommandExecutor::new();
@@ -279,4 +590,67 @@ mod tests {
 
         assert!(result.success);
     }
+
+    #[test]
+    fn test_windows_command_line_keeps_quotes() {
+        assert_eq!(
+            windows_command_line(r#""C:\Users\张三\npm\claude.cmd" --version"#),
+            r#"/D /S /C "chcp 65001 >nul & "C:\Users\张三\npm\claude.cmd" --version""#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_non_ascii_output_and_transcript() {
+        let dir = tempfile::tempdir().unwrap();
+        let user_dir = dir.path().join("用户 张三");
+        std::fs::create_dir(&user_dir).unwrap();
+        std::fs::write(user_dir.join("配置.txt"), "内容").unwrap();
+
+        let executor = CommandExecutor::new().with_transcript();
+        let command = if cfg!(windows) {
+            format!("dir /b \"{}\"", user_dir.display())
+        } else {
+            format!("ls '{}'", user_dir.display())
+        };
+        let result = executor.execute_without_proxy_async(&command).await;
+
+        assert!(result.success, "stderr: {}", result.stderr);
+        assert_eq!(result.stdout, "配置.txt");
+        let transcript = executor.transcript();
+        assert_eq!(transcript.len(), 1);
+        assert_eq!(transcript[0].command, command);
+        assert_eq!(transcript[0].stdout, "配置.txt");
+    }
+
+    #[tokio::test]
+    async fn test_observer_streams_lines_and_cancels() {
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let collected = lines.clone();
+        let cancel = CancellationToken::new();
+        let observer = OutputObserver::new(
+            Arc::new(move |stream, line| {
+                collected.lock().unwrap().push((stream, line.to_string()));
+            }),
+            cancel.clone(),
+        );
+        let executor = CommandExecutor::new()
+            .with_transcript()
+            .with_observer(observer);
+
+        let result = executor.execute_async("echo first && echo second").await;
+        assert!(result.success, "stderr: {}", result.stderr);
+        assert_eq!(
+            lines.lock().unwrap().as_slice(),
+            &[
+                (OutputStream::Stdout, "first".to_string()),
+                (OutputStream::Stdout, "second".to_string())
+            ]
+        );
+        assert_eq!(executor.transcript()[0].stdout, "first\nsecond");
+
+        cancel.cancel();
+        let result = executor.execute_async("echo never").await;
+        assert!(!result.success);
+        assert!(result.stderr.contains(COMMAND_CANCELLED));
+    }
 }
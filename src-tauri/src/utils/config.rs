@@ -1,3 +1,5 @@
+use crate::core::secrets::SecretStore;
+use crate::data::compat;
 use crate::data::DataManager;
 use crate::GlobalConfig;
 use std::fs;
@@ -42,8 +44,10 @@ pub fn read_global_config() -> Result<Option<GlobalConfig>, String> {
         .read(&config_path)
         .map_err(|e| format!("Failed to read config: {e}"))?;
 
-    let config: GlobalConfig =
-        serde_json::from_value(config_value).map_err(|e| format!("Failed to parse config: {e}"))?;
+    // 容错解析：降级后无法识别的取值回退为默认值（写回时保留原值）
+    let (mut config, _): (GlobalConfig, _) = compat::from_value_tolerant(config_value)
+        .map_err(|e| format!("Failed to parse config: {e}"))?;
+    SecretStore::global().reveal_fields(&mut config);
 
     // 注意：迁移逻辑已移到 MigrationManager，在应用启动时统一执行
 
@@ -59,13 +63,28 @@ pub fn write_global_config(config: &GlobalConfig) -> Result<(), String> {
 
     // 使用 DataManager 写入配置（无缓存模式）
     let manager = DataManager::new();
-    let config_value =
-        serde_json::to_value(config).map_err(|e| format!("Failed to serialize config: {e}"))?;
+    // 密钥存入密钥存储，文件中只保留引用
+    let secrets = SecretStore::global();
+    let mut sealed = config.clone();
+    secrets
+        .seal_fields(&mut sealed)
+        .map_err(|e| format!("Failed to store secrets: {e}"))?;
+    let mut config_value =
+        serde_json::to_value(&sealed).map_err(|e| format!("Failed to serialize config: {e}"))?;
+
+    // 保留其他版本写入、当前版本不认识的字段
+    let existing = manager.json_uncached().read(&config_path).ok();
+    if let Some(existing) = &existing {
+        compat::preserve_fields::<GlobalConfig>(existing, &mut config_value);
+    }
 
     manager
         .json_uncached()
         .write(&config_path, &config_value)
         .map_err(|e| format!("Failed to write config: {e}"))?;
+    if let Some(existing) = &existing {
+        secrets.prune(existing, &config_value);
+    }
 
     Ok(())
 }
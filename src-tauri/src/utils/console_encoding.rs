@@ -0,0 +1,171 @@
+//! 控制台输出编码与 Windows 长路径处理
+//!
+//! Windows 下子进程输出的编码取决于控制台代码页（中文系统默认 936/GBK），
+//! 部分程序（wsl.exe、PowerShell 重定向）还会输出 UTF-16。直接按 UTF-8 解码会出现乱码，
+//! 尤其是用户名或安装路径包含中文时。这里统一按以下顺序解码：
+//!
+//! 1. UTF-16 LE（带 BOM 或明显的 UTF-16 字节特征）
+//! 2. UTF-8（执行器已切换到 65001 代码页，大部分输出为 UTF-8）
+//! 3. 系统 OEM 代码页（未遵循控制台代码页的旧程序）
+
+use encoding_rs::Encoding;
+use std::path::{Path, PathBuf};
+
+/// UTF-8 代码页
+pub const CP_UTF8: u32 = 65001;
+
+/// Windows 传统路径长度上限
+const MAX_PATH: usize = 260;
+
+/// 当前系统控制台使用的 OEM 代码页（非 Windows 平台恒为 UTF-8）
+#[cfg(target_os = "windows")]
+pub fn console_code_page() -> u32 {
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetOEMCP() -> u32;
+    }
+    // SAFETY: GetOEMCP 无参数、无副作用，只读取系统设置
+    unsafe { GetOEMCP() }
+}
+
+/// 当前系统控制台使用的 OEM 代码页（非 Windows 平台恒为 UTF-8）
+#[cfg(not(target_os = "windows"))]
+pub fn console_code_page() -> u32 {
+    CP_UTF8
+}
+
+/// 代码页对应的编码（不支持的代码页返回 None）
+pub fn encoding_for_code_page(code_page: u32) -> Option<&'static Encoding> {
+    let label = match code_page {
+        CP_UTF8 => "utf-8",
+        936 => "gbk",
+        950 => "big5",
+        932 => "shift_jis",
+        949 => "euc-kr",
+        866 => "ibm866",
+        874 => "windows-874",
+        1250..=1258 => return Encoding::for_label(format!("windows-{}", code_page).as_bytes()),
+        _ => return None,
+    };
+    Encoding::for_label(label.as_bytes())
+}
+
+/// 是否像 UTF-16 LE 文本（ASCII 字符的高字节为 0）
+fn looks_like_utf16le(bytes: &[u8]) -> bool {
+    if bytes.len() < 4 || !bytes.len().is_multiple_of(2) {
+        return false;
+    }
+    let pairs = bytes.len() / 2;
+    let zero_high = bytes
+        .chunks_exact(2)
+        .filter(|c| c[1] == 0 && c[0] != 0)
+        .count();
+    zero_high * 2 > pairs
+}
+
+/// 解码子进程输出
+pub fn decode_console_output(bytes: &[u8], code_page: u32) -> String {
+    if let Some(rest) = bytes.strip_prefix(&[0xFF, 0xFE]) {
+        return decode_utf16le(rest);
+    }
+    if looks_like_utf16le(bytes) {
+        return decode_utf16le(bytes);
+    }
+
+    let bytes = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]).unwrap_or(bytes);
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return text.to_string();
+    }
+
+    match encoding_for_code_page(code_page) {
+        Some(encoding) => encoding.decode_without_bom_handling(bytes).0.into_owned(),
+        None => String::from_utf8_lossy(bytes).into_owned(),
+    }
+}
+
+fn decode_utf16le(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+/// 为超过 MAX_PATH 的 Windows 绝对路径添加 `\\?\` 前缀（其他路径原样返回）
+///
+/// 与平台无关的字符串处理，便于在任意平台测试
+pub fn extended_length_path_str(path: &str) -> String {
+    if path.encode_utf16().count() < MAX_PATH || path.starts_with(r"\\?\") {
+        return path.to_string();
+    }
+    let path = path.replace('/', "\\");
+    if let Some(unc) = path.strip_prefix(r"\\") {
+        return format!(r"\\?\UNC\{}", unc);
+    }
+    let bytes = path.as_bytes();
+    if bytes.len() > 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && bytes[2] == b'\\' {
+        return format!(r"\\?\{}", path);
+    }
+    path
+}
+
+/// 为长路径启用 Windows 扩展长度语法，避免深层 node_modules 或长用户名目录下文件检查失败
+pub fn extended_length_path(path: &Path) -> PathBuf {
+    if cfg!(target_os = "windows") {
+        PathBuf::from(extended_length_path_str(&path.to_string_lossy()))
+    } else {
+        path.to_path_buf()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USER_PATH: &str = r"C:\Users\张三\AppData\Roaming\npm\claude.cmd";
+
+    #[test]
+    fn test_decode_gbk_output_with_non_ascii_path() {
+        let (gbk, _, _) = encoding_rs::GBK.encode(USER_PATH);
+        assert!(std::str::from_utf8(&gbk).is_err());
+        assert_eq!(decode_console_output(&gbk, 936), USER_PATH);
+        // UTF-8 输出不受 OEM 代码页影响
+        assert_eq!(decode_console_output(USER_PATH.as_bytes(), 936), USER_PATH);
+    }
+
+    #[test]
+    fn test_decode_utf16_and_bom() {
+        let mut utf16: Vec<u8> = USER_PATH
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        assert_eq!(decode_console_output(&utf16, 936), USER_PATH);
+        utf16.splice(0..0, [0xFF, 0xFE]);
+        assert_eq!(decode_console_output(&utf16, 936), USER_PATH);
+
+        let with_bom = [&[0xEF, 0xBB, 0xBF][..], "Ünïcødé".as_bytes()].concat();
+        assert_eq!(decode_console_output(&with_bom, CP_UTF8), "Ünïcødé");
+        assert_eq!(decode_console_output(&[0xDC, 0x62], 1252), "Üb");
+    }
+
+    #[test]
+    fn test_extended_length_path() {
+        assert_eq!(extended_length_path_str(USER_PATH), USER_PATH);
+
+        let long = format!(r"C:\Users\张三\{}\cli.js", "node_modules\\pkg".repeat(20));
+        assert_eq!(extended_length_path_str(&long), format!(r"\\?\{}", long));
+        assert_eq!(
+            extended_length_path_str(&format!(r"\\?\{}", long)),
+            format!(r"\\?\{}", long)
+        );
+
+        let unc = format!(r"\\server\share\{}", "a".repeat(300));
+        assert_eq!(
+            extended_length_path_str(&unc),
+            format!(r"\\?\UNC\server\share\{}", "a".repeat(300))
+        );
+        // 相对路径无法使用扩展长度语法
+        let relative = "b".repeat(300);
+        assert_eq!(extended_length_path_str(&relative), relative);
+    }
+}
@@ -1,10 +1,10 @@
 //! 文件操作辅助函数
 //!
-//! 提供常用的文件操作工具函数，如文件校验和计算等。
+//! 提供常用的文件操作工具函数，如文件校验和计算、`~` 路径展开等。
 
 use anyhow::{Context, Result};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// 计算文件的 SHA256 哈希值
 ///
@@ -38,6 +38,25 @@ pub fn file_checksum(path: &Path) -> Result<String> {
     Ok(format!("{digest:x}"))
 }
 
+/// 将路径开头的 `~`（`~`、`~/...`、`~\...`）展开为当前用户主目录
+///
+/// 无法获取主目录或路径不以 `~` 开头时原样返回。
+pub fn expand_home(path: &str) -> PathBuf {
+    match dirs::home_dir() {
+        Some(home_dir) => expand_home_with(path, &home_dir),
+        None => PathBuf::from(path),
+    }
+}
+
+/// 将路径开头的 `~` 展开为指定的主目录
+pub fn expand_home_with(path: &str, home_dir: &Path) -> PathBuf {
+    match path.strip_prefix('~') {
+        Some("") => home_dir.to_path_buf(),
+        Some(rest) if rest.starts_with('/') || rest.starts_with('\\') => home_dir.join(&rest[1..]),
+        _ => PathBuf::from(path),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -69,6 +88,22 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_expand_home_with() {
+        let home = Path::new("/home/duck");
+        assert_eq!(expand_home_with("~", home), home);
+        assert_eq!(
+            expand_home_with("~/logs/a.jsonl", home),
+            home.join("logs/a.jsonl")
+        );
+        assert_eq!(expand_home_with("~\\logs", home), home.join("logs"));
+        assert_eq!(
+            expand_home_with("~other/x", home),
+            PathBuf::from("~other/x")
+        );
+        assert_eq!(expand_home_with("/tmp/x", home), PathBuf::from("/tmp/x"));
+    }
+
     #[test]
     fn test_file_checksum_deterministic() -> Result<()> {
         let mut temp_file1 = NamedTempFile::new()?;
@@ -1,18 +1,22 @@
 pub mod auto_startup;
 pub mod command;
 pub mod config;
+pub mod console_encoding;
 pub mod file_helpers;
 pub mod installer_scanner;
 pub mod platform;
 pub mod precision;
+pub mod ssh_executor;
 pub mod version;
 pub mod wsl_executor;
 
 pub use auto_startup::*;
 pub use command::*;
 pub use config::*;
+pub use console_encoding::*;
 pub use file_helpers::*;
 pub use installer_scanner::*;
 pub use platform::*;
+pub use ssh_executor::*;
 pub use version::*;
 pub use wsl_executor::*;
@@ -1,4 +1,32 @@
+use once_cell::sync::Lazy;
 use std::env;
+use std::sync::RwLock;
+
+/// 全局配置中的额外 PATH 目录（首次使用时读取，配置变更后由 [`set_extra_paths`] 刷新）
+static EXTRA_PATHS: Lazy<RwLock<Vec<String>>> = Lazy::new(|| {
+    RwLock::new(
+        crate::utils::config::read_global_config()
+            .ok()
+            .flatten()
+            .map(|config| config.extra_paths)
+            .unwrap_or_default(),
+    )
+});
+
+/// 当前生效的额外 PATH 目录
+pub fn extra_paths() -> Vec<String> {
+    EXTRA_PATHS
+        .read()
+        .map(|paths| paths.clone())
+        .unwrap_or_default()
+}
+
+/// 更新额外 PATH 目录（保存全局配置后调用，之后执行的命令立即生效）
+pub fn set_extra_paths(paths: Vec<String>) {
+    if let Ok(mut current) = EXTRA_PATHS.write() {
+        *current = paths;
+    }
+}
 
 /// 平台信息
 #[derive(Debug, Clone)]
@@ -47,9 +75,36 @@ impl PlatformInfo {
         }
     }
 
+    /// 用户级 npm 安装目录（全局安装无权限时的回退位置）
+    ///
+    /// - Windows: `%LOCALAPPDATA%\DuckCoding\npm-global`
+    /// - macOS/Linux: `~/.duckcoding/npm-global`
+    pub fn user_npm_prefix(&self) -> Option<std::path::PathBuf> {
+        if self.is_windows {
+            env::var("LOCALAPPDATA").ok().map(|dir| {
+                std::path::Path::new(&dir)
+                    .join("DuckCoding")
+                    .join("npm-global")
+            })
+        } else {
+            dirs::home_dir().map(|home| home.join(".duckcoding").join("npm-global"))
+        }
+    }
+
+    /// 用户级 npm 安装目录中可执行文件所在目录（Windows 的 .cmd 位于前缀根目录）
+    pub fn user_npm_bin_dir(&self) -> Option<std::path::PathBuf> {
+        let prefix = self.user_npm_prefix()?;
+        Some(if self.is_windows {
+            prefix
+        } else {
+            prefix.join("bin")
+        })
+    }
+
     /// 构建增强的 PATH 环境变量（合并模式：增强路径 + 当前 PATH）
     ///
     /// 策略：在当前 PATH 前追加工具常见路径，保留所有现有环境
+    /// - 用户额外目录：全局配置 `extra_paths`（优先级最高）
     /// - 增强路径包含：Homebrew、npm global、nvm、用户 bin 等
     /// - 当前 PATH：继承系统/shell 的完整 PATH
     ///
@@ -63,21 +118,27 @@ impl PlatformInfo {
         // 实时获取当前 PATH（而非缓存），确保获得最新环境
         let current_path = env::var("PATH").unwrap_or_default();
 
-        let system_paths = if self.is_windows {
-            self.windows_system_paths()
-        } else {
-            self.unix_system_paths()
-        };
+        let mut enhanced_paths = extra_paths();
+        enhanced_paths.extend(self.system_paths());
 
         // 合并策略：增强路径在前（高优先级），当前 PATH 在后（保留完整环境）
         format!(
             "{}{}{}",
-            system_paths.join(separator),
+            enhanced_paths.join(separator),
             separator,
             current_path
         )
     }
 
+    /// DuckCoding 追加的工具常见路径（不含额外目录与当前 PATH）
+    pub fn system_paths(&self) -> Vec<String> {
+        if self.is_windows {
+            self.windows_system_paths()
+        } else {
+            self.unix_system_paths()
+        }
+    }
+
     /// Windows 系统路径
     fn windows_system_paths(&self) -> Vec<String> {
         let mut paths = vec![
@@ -95,6 +156,11 @@ impl PlatformInfo {
             paths.push(format!("{user_profile}\\.local\\bin"));
         }
 
+        // 用户级 npm 安装目录（非管理员回退安装）
+        if let Some(bin_dir) = self.user_npm_bin_dir() {
+            paths.push(bin_dir.to_string_lossy().to_string());
+        }
+
         paths
     }
 
@@ -181,6 +247,11 @@ impl PlatformInfo {
                 paths.push(format!("{home_str}/.npm-global/bin"));
             }
 
+            // 用户级 npm 安装目录（全局安装无权限时的回退位置）
+            if let Some(bin_dir) = self.user_npm_bin_dir() {
+                paths.push(bin_dir.to_string_lossy().to_string());
+            }
+
             // asdf 支持（2025-12-11 新增）
             // asdf 是另一个流行的版本管理器
             let asdf_dir =
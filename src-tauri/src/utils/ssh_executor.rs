@@ -0,0 +1,241 @@
+use crate::models::SSHConfig;
+use crate::utils::{posix_quote, CommandResult};
+use anyhow::{Context, Result};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::{Child, Command};
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+/// 连接超时（秒）
+const CONNECT_TIMEOUT_SECS: u64 = 10;
+
+/// 默认命令超时
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// SSH 远程命令执行器
+///
+/// 调用系统 `ssh` 客户端（复用用户的 `~/.ssh/config`、known_hosts 与 ssh-agent），
+/// 以 BatchMode 运行，不支持交互式输入密码，需事先配置密钥登录。
+pub struct SSHExecutor {
+    config: SSHConfig,
+}
+
+impl SSHExecutor {
+    /// 创建指定主机的执行器（校验主机与用户名）
+    pub fn new(config: SSHConfig) -> Result<Self> {
+        validate_config(&config)?;
+        Ok(Self { config })
+    }
+
+    /// 检测系统是否安装了 ssh 客户端
+    pub fn is_available() -> bool {
+        let mut cmd = std::process::Command::new("ssh");
+        cmd.arg("-V").stdout(Stdio::null()).stderr(Stdio::null());
+
+        #[cfg(target_os = "windows")]
+        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+
+        cmd.status().is_ok()
+    }
+
+    /// 远程主机标识（user@host:port）
+    pub fn target(&self) -> String {
+        format!(
+            "{}@{}:{}",
+            self.config.user, self.config.host, self.config.port
+        )
+    }
+
+    /// 连接参数（不含远程命令）
+    fn connection_args(&self) -> Vec<String> {
+        let mut args = vec![
+            "-o".to_string(),
+            "BatchMode=yes".to_string(),
+            "-o".to_string(),
+            format!("ConnectTimeout={}", CONNECT_TIMEOUT_SECS),
+            "-p".to_string(),
+            self.config.port.to_string(),
+        ];
+        if let Some(key_path) = self.config.key_path.as_deref().filter(|p| !p.is_empty()) {
+            args.push("-i".to_string());
+            args.push(key_path.to_string());
+        }
+        // `--` 之后的参数不会被 ssh 当作选项解析
+        args.push("--".to_string());
+        args.push(format!("{}@{}", self.config.user, self.config.host));
+        args
+    }
+
+    fn command(&self, args: &[String]) -> Command {
+        let mut cmd = Command::new("ssh");
+        cmd.args(args).kill_on_drop(true);
+
+        #[cfg(target_os = "windows")]
+        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+
+        cmd
+    }
+
+    /// 在远程登录 shell 中执行命令（默认 30 秒超时）
+    pub async fn execute(&self, command: &str) -> Result<CommandResult> {
+        self.execute_with_timeout(command, DEFAULT_TIMEOUT).await
+    }
+
+    /// 在远程登录 shell 中执行命令（指定超时）
+    ///
+    /// 使用 `bash -lc`，使 nvm 等在 profile 中配置的 Node.js 可用
+    pub async fn execute_with_timeout(
+        &self,
+        command: &str,
+        timeout: Duration,
+    ) -> Result<CommandResult> {
+        self.run(command, None, timeout).await
+    }
+
+    /// 读取远程文件（文件不存在时返回 None）
+    pub async fn read_file(&self, path: &str) -> Result<Option<String>> {
+        let quoted = posix_quote(path);
+        let command = format!("if [ -f {quoted} ]; then cat {quoted}; else exit 3; fi");
+        let result = self.run(&command, None, DEFAULT_TIMEOUT).await?;
+        match result.exit_code {
+            Some(0) => Ok(Some(result.stdout)),
+            Some(3) => Ok(None),
+            _ => anyhow::bail!("读取远程文件 {} 失败: {}", path, result.stderr.trim()),
+        }
+    }
+
+    /// 写入远程文件（自动创建父目录，权限 600）
+    pub async fn write_file(&self, path: &str, content: &str) -> Result<()> {
+        let quoted = posix_quote(path);
+        let command = format!("mkdir -p \"$(dirname {quoted})\" && umask 077 && cat > {quoted}");
+        let result = self
+            .run(&command, Some(content.as_bytes()), DEFAULT_TIMEOUT)
+            .await?;
+        if !result.success {
+            anyhow::bail!("写入远程文件 {} 失败: {}", path, result.stderr.trim());
+        }
+        Ok(())
+    }
+
+    /// 建立反向隧道：远程 `127.0.0.1:remote_port` → 本机 `127.0.0.1:local_port`
+    ///
+    /// 返回的子进程在 Drop 时终止，隧道随之关闭
+    pub fn spawn_reverse_tunnel(&self, local_port: u16, remote_port: u16) -> Result<Child> {
+        let mut args = vec![
+            "-N".to_string(),
+            "-o".to_string(),
+            "ExitOnForwardFailure=yes".to_string(),
+            "-o".to_string(),
+            "ServerAliveInterval=30".to_string(),
+            "-R".to_string(),
+            format!("127.0.0.1:{}:127.0.0.1:{}", remote_port, local_port),
+        ];
+        args.extend(self.connection_args());
+
+        self.command(&args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("启动 ssh 隧道失败")
+    }
+
+    async fn run(
+        &self,
+        command: &str,
+        stdin: Option<&[u8]>,
+        timeout: Duration,
+    ) -> Result<CommandResult> {
+        let mut args = self.connection_args();
+        args.push(format!("bash -lc {}", posix_quote(command)));
+
+        let mut child = self
+            .command(&args)
+            .stdin(if stdin.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("执行 ssh 失败，请确认已安装 OpenSSH 客户端")?;
+
+        if let (Some(data), Some(mut pipe)) = (stdin, child.stdin.take()) {
+            pipe.write_all(data)
+                .await
+                .context("写入 ssh 标准输入失败")?;
+            drop(pipe);
+        }
+
+        let output = tokio::time::timeout(timeout, child.wait_with_output())
+            .await
+            .map_err(|_| anyhow::anyhow!("SSH 命令执行超时（{}）", self.target()))?
+            .context("等待 ssh 结果失败")?;
+
+        let exit_code = output.status.code();
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        // 255 为 ssh 自身的错误（连接/认证失败），与远程命令失败区分
+        if exit_code == Some(255) {
+            anyhow::bail!("无法连接 {}: {}", self.target(), stderr.trim());
+        }
+
+        Ok(CommandResult {
+            success: output.status.success(),
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr,
+            exit_code,
+        })
+    }
+}
+
+/// 校验 SSH 配置：主机与用户名不能为空，也不能以 `-` 开头（否则会被 ssh 当作选项）
+fn validate_config(config: &SSHConfig) -> Result<()> {
+    for (field, value) in [("主机地址", &config.host), ("用户名", &config.user)] {
+        if value.trim().is_empty() {
+            anyhow::bail!("SSH {}不能为空", field);
+        }
+        if value.starts_with('-') {
+            anyhow::bail!("SSH {}不能以 - 开头: {}", field, value);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(host: &str, user: &str) -> SSHConfig {
+        SSHConfig {
+            display_name: "dev".to_string(),
+            host: host.to_string(),
+            port: 2222,
+            user: user.to_string(),
+            key_path: Some("~/.ssh/id_ed25519".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_connection_args() {
+        let executor = SSHExecutor::new(config("dev.example.com", "alice")).unwrap();
+
+        let args = executor.connection_args();
+        assert_eq!(args.last().unwrap(), "alice@dev.example.com");
+        assert_eq!(args[args.len() - 2], "--");
+        assert!(args.windows(2).any(|w| w[0] == "-p" && w[1] == "2222"));
+        assert!(args
+            .windows(2)
+            .any(|w| w[0] == "-i" && w[1] == "~/.ssh/id_ed25519"));
+        assert!(args.contains(&"BatchMode=yes".to_string()));
+        assert_eq!(executor.target(), "alice@dev.example.com:2222");
+
+        // 以 - 开头的主机或用户名会被 ssh 当作选项（如 -oProxyCommand=...）
+        assert!(SSHExecutor::new(config("-oProxyCommand=touch /tmp/pwned", "alice")).is_err());
+        assert!(SSHExecutor::new(config("dev.example.com", "-oProxyCommand=id")).is_err());
+        assert!(SSHExecutor::new(config(" ", "alice")).is_err());
+    }
+}
@@ -1,5 +1,6 @@
 use crate::utils::CommandResult;
 use anyhow::Result;
+use std::path::PathBuf;
 use std::time::Duration;
 
 #[cfg(target_os = "windows")]
@@ -55,19 +56,11 @@ impl WSLExecutor {
                 return Err(anyhow::anyhow!("WSL --list 命令执行失败"));
             }
 
-            // 解析输出（可能是 UTF-16 编码）
-            let distros = if output.stdout.starts_with(&[0xFF, 0xFE]) {
-                // UTF-16 LE BOM
-                String::from_utf16_lossy(
-                    &output
-                        .stdout
-                        .chunks_exact(2)
-                        .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
-                        .collect::<Vec<u16>>(),
-                )
-            } else {
-                String::from_utf8_lossy(&output.stdout).to_string()
-            };
+            // 解析输出（wsl.exe 通常输出 UTF-16）
+            let distros = crate::utils::decode_console_output(
+                &output.stdout,
+                crate::utils::console_code_page(),
+            );
 
             // 解析每一行，过滤空行和特殊字符
             let distributions: Vec<String> = distros
@@ -103,7 +96,7 @@ impl WSLExecutor {
     ) -> Result<CommandResult> {
         #[cfg(target_os = "windows")]
         {
-            self.execute_windows(distro_name, command).await
+            self.execute_windows(distro_name, command, false).await
         }
 
         #[cfg(not(target_os = "windows"))]
@@ -113,12 +106,40 @@ impl WSLExecutor {
         }
     }
 
+    /// 在指定发行版的登录 shell 中执行命令
+    ///
+    /// 通过 nvm 等方式安装的 Node.js 只在登录 shell 读取 profile 后才在 PATH 中，
+    /// 安装、更新和版本检查都应走这里
+    pub async fn execute_login_in_distro(
+        &self,
+        distro_name: Option<&str>,
+        command: &str,
+    ) -> Result<CommandResult> {
+        #[cfg(target_os = "windows")]
+        {
+            self.execute_windows(distro_name, command, true).await
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            let _ = (distro_name, command);
+            Err(anyhow::anyhow!("WSL 仅在 Windows 平台可用"))
+        }
+    }
+
+    /// 发行版内 Linux 路径对应的 Windows 访问路径（`\\wsl$\<发行版>\...`）
+    pub fn unc_path(distro_name: &str, linux_path: &str) -> PathBuf {
+        let relative = linux_path.trim_start_matches('/').replace('/', "\\");
+        PathBuf::from(format!("\\\\wsl$\\{}\\{}", distro_name, relative))
+    }
+
     /// Windows 平台下执行 WSL 命令
     #[cfg(target_os = "windows")]
     async fn execute_windows(
         &self,
         distro_name: Option<&str>,
         command: &str,
+        login: bool,
     ) -> Result<CommandResult> {
         let command = command.to_string();
         let distro_name = distro_name.map(|s| s.to_string());
@@ -134,7 +155,7 @@ impl WSLExecutor {
             let output = cmd
                 .arg("--exec")
                 .arg("bash")
-                .arg("-c")
+                .arg(if login { "-lc" } else { "-c" })
                 .arg(&command)
                 .creation_flags(0x08000000) // CREATE_NO_WINDOW
                 .output()
@@ -303,6 +324,12 @@ mod tests {
         println!("WSL available: {}", available);
     }
 
+    #[test]
+    fn test_unc_path() {
+        let path = WSLExecutor::unc_path("Ubuntu", "/home/dev/.claude");
+        assert_eq!(path, PathBuf::from(r"\\wsl$\Ubuntu\home\dev\.claude"));
+    }
+
     #[tokio::test]
     async fn test_execute_simple_command() {
         if !WSLExecutor::is_available() {
@@ -2,11 +2,28 @@ import { useEffect } from 'react';
 import { listen } from '@tauri-apps/api/event';
 import { useAppContext } from '@/hooks/useAppContext';
 import { useToast } from '@/hooks/use-toast';
+import { ToastAction } from '@/components/ui/toast';
 import { useAppEvents } from '@/hooks/useAppEvents';
 import { useCloseAction } from '@/hooks/useCloseAction';
 import { CloseActionDialog } from '@/components/dialogs/CloseActionDialog';
-import type { UpdateInfo, CloseAction } from '@/lib/tauri-commands';
-import type { ToolType } from '@/types/token-stats';
+import {
+  checkApiHandshake,
+  confirmCostGuardRequest,
+  type UpdateInfo,
+  type CloseAction,
+  type AutoStartReport,
+  type PendingCostConfirmation,
+  type ProxyFailoverEvent,
+  type SessionContextUsage,
+} from '@/lib/tauri-commands';
+import {
+  BUDGET_PERIOD_NAMES,
+  TOOL_TYPE_NAMES,
+  type BudgetThresholdEvent,
+  type ToolType,
+  type UsageAnomalyEvent,
+} from '@/types/token-stats';
+import type { WatcherRecovery } from '@/types/config-watch';
 import type { TabType } from '@/contexts/AppContext.types';
 
 export function AppEventsHandler() {
@@ -66,6 +83,21 @@ export function AppEventsHandler() {
     executeCloseAction,
   });
 
+  // 启动时校验前后端接口版本
+  useEffect(() => {
+    checkApiHandshake()
+      .then((mismatch) => {
+        if (mismatch) {
+          toast({
+            variant: 'destructive',
+            title: '前后端版本不匹配',
+            description: `${mismatch}，部分功能可能异常，请重新安装或更新应用`,
+          });
+        }
+      })
+      .catch((error) => console.error('接口版本握手失败:', error));
+  }, [toast]);
+
   // Additional Event Listeners
   useEffect(() => {
     const unlistenUpdateAvailable = listen<UpdateInfo>('update-available', (event) => {
@@ -85,6 +117,125 @@ export function AppEventsHandler() {
       });
     });
 
+    const unlistenWatcherRecovered = listen<WatcherRecovery>(
+      'config-watcher-recovered',
+      (event) => {
+        const seconds = Math.max(1, Math.round(event.payload.downtime_ms / 1000));
+        toast({
+          title: '配置守护已自动恢复',
+          description: `外部配置变更检测中断了约 ${seconds} 秒（${event.payload.reason}），期间的修改未被检测，请确认工具配置是否正确`,
+        });
+      },
+    );
+
+    const unlistenBudget = listen<BudgetThresholdEvent>('budget-threshold-reached', (event) => {
+      const { tool_id, period, level, mode, limit_usd, spent_usd } = event.payload;
+      const toolName = TOOL_TYPE_NAMES[tool_id as ToolType] ?? tool_id;
+      const usage = `${BUDGET_PERIOD_NAMES[period]}已消费 $${spent_usd.toFixed(2)} / $${limit_usd.toFixed(2)}`;
+      toast({
+        variant: level === 'exceeded' ? 'destructive' : 'default',
+        title:
+          level === 'exceeded' ? `${toolName} 已超出消费预算` : `${toolName} 即将达到消费预算`,
+        description:
+          level === 'exceeded' && mode === 'block' ? `${usage}，新请求将被拦截` : usage,
+      });
+    });
+
+    const unlistenCostGuard = listen<PendingCostConfirmation>(
+      'cost-guard-confirmation',
+      (event) => {
+        const { id, estimate, expires_at } = event.payload;
+        const toolName = TOOL_TYPE_NAMES[estimate.tool_id as ToolType] ?? estimate.tool_id;
+        const remainingMs = Math.max(0, expires_at - Date.now());
+        const approve = () =>
+          confirmCostGuardRequest(id, true).catch((error) => {
+            toast({
+              variant: 'destructive',
+              title: '确认失败',
+              description: String(error),
+            });
+          });
+        toast({
+          variant: 'destructive',
+          title: `${toolName} 请求等待确认`,
+          description: `${estimate.model} 请求预估输入约 ${estimate.input_tokens.toLocaleString()} Token（$${estimate.input_cost_usd.toFixed(2)}），超出单次上限 $${estimate.threshold_usd.toFixed(2)}。${Math.round(remainingMs / 1000)} 秒内未确认将被拒绝`,
+          duration: remainingMs,
+          action: (
+            <ToastAction altText="继续发送" onClick={approve}>
+              继续发送
+            </ToastAction>
+          ),
+        });
+      },
+    );
+
+    const unlistenContextUsage = listen<SessionContextUsage>(
+      'session-context-warning',
+      (event) => {
+        const { tool_id, model, prompt_tokens, context_window, percent, level } = event.payload;
+        const toolName = TOOL_TYPE_NAMES[tool_id as ToolType] ?? tool_id;
+        const usage = `${model} 上下文已使用 ${prompt_tokens.toLocaleString()} / ${(context_window ?? 0).toLocaleString()} Token（${Math.round(percent ?? 0)}%）`;
+        toast({
+          variant: level === 'critical' ? 'destructive' : 'default',
+          title:
+            level === 'critical'
+              ? `${toolName} 会话上下文即将耗尽`
+              : `${toolName} 会话上下文接近上限`,
+          description:
+            level === 'critical' ? `${usage}，工具即将压缩或截断早期对话` : usage,
+        });
+      },
+    );
+
+    const unlistenAnomaly = listen<UsageAnomalyEvent>('usage-anomaly', (event) => {
+      const { tool_id, metric, current, baseline, ratio, desktop_notification } = event.payload;
+      const toolName = TOOL_TYPE_NAMES[tool_id as ToolType] ?? tool_id;
+      const title = `${toolName} 用量异常`;
+      const description =
+        metric === 'cost'
+          ? `本小时已消费 $${current.toFixed(2)}，是近期平均值 $${baseline.toFixed(2)} 的 ${ratio.toFixed(1)} 倍，请检查是否有失控的 Agent 循环`
+          : `本小时已使用 ${Math.round(current).toLocaleString()} Token，是近期平均值的 ${ratio.toFixed(1)} 倍，请检查是否有失控的 Agent 循环`;
+      toast({ variant: 'destructive', title, description });
+      if (desktop_notification && 'Notification' in window) {
+        const notify = () => new Notification(title, { body: description });
+        if (Notification.permission === 'granted') {
+          notify();
+        } else if (Notification.permission !== 'denied') {
+          Notification.requestPermission().then((permission) => {
+            if (permission === 'granted') notify();
+          });
+        }
+      }
+    });
+
+    const unlistenProxyFailover = listen<ProxyFailoverEvent>('proxy-failover', (event) => {
+      const { tool_id, from_profile, to_profile, consecutive_errors } = event.payload;
+      const toolName = TOOL_TYPE_NAMES[tool_id as ToolType] ?? tool_id;
+      toast({
+        variant: 'destructive',
+        title: `${toolName} 代理已切换到备用配置`,
+        description: `${from_profile ?? '当前配置'} 连续 ${consecutive_errors} 次请求出错，已切换到 "${to_profile}"`,
+      });
+    });
+
+    const unlistenProxyAutoStart = listen<AutoStartReport>(
+      'proxy-auto-start-completed',
+      (event) => {
+        const issues = [...event.payload.failed, ...event.payload.skipped];
+        if (issues.length === 0) return;
+        toast({
+          variant: 'destructive',
+          title: '部分代理未能自动启动',
+          description: issues
+            .map(({ tool_id, reason }) => {
+              const toolName = TOOL_TYPE_NAMES[tool_id as ToolType] ?? tool_id;
+              return `${toolName}: ${reason}`;
+            })
+            .join('\n'),
+        });
+      },
+    );
+
     const unlistenOpenSettings = listen<{ tab?: string; restrictToTab?: boolean }>(
       'open-settings',
       (event) => {
@@ -149,6 +300,13 @@ export function AppEventsHandler() {
       unlistenUpdateAvailable.then((fn) => fn());
       unlistenRequestCheck.then((fn) => fn());
       unlistenNotFound.then((fn) => fn());
+      unlistenWatcherRecovered.then((fn) => fn());
+      unlistenBudget.then((fn) => fn());
+      unlistenCostGuard.then((fn) => fn());
+      unlistenContextUsage.then((fn) => fn());
+      unlistenAnomaly.then((fn) => fn());
+      unlistenProxyFailover.then((fn) => fn());
+      unlistenProxyAutoStart.then((fn) => fn());
       unlistenOpenSettings.then((fn) => fn());
       unlistenAppNavigate.then((fn) => fn());
       unlistenProfileActivated.then((fn) => fn());
@@ -3,7 +3,7 @@
  * 统一管理预设时间范围和自定义时间范围的状态和逻辑
  */
 
-import { useState, useMemo, useCallback } from 'react';
+import { useState, useMemo, useCallback, useEffect } from 'react';
 import type { TimeRange, TimeGranularity } from '@/types/analytics';
 import {
   PRESET_ALLOWED_GRANULARITIES,
@@ -11,6 +11,7 @@ import {
   selectDefaultGranularity,
   calculatePresetStartTime,
 } from '@/utils/time-range';
+import { getCurrentStatsEpoch } from '@/lib/tauri-commands/analytics';
 
 export interface UseTimeRangeControlReturn {
   // 模式控制
@@ -36,6 +37,10 @@ export interface UseTimeRangeControlReturn {
   startTimeMs: number;
   endTimeMs: number;
 
+  // 当前统计周期开始时间（预设范围不早于该时间）
+  epochStartMs: number | null;
+  refreshEpoch: () => void;
+
   // 自定义时间对话框控制
   showCustomDialog: boolean;
   openCustomDialog: () => void;
@@ -70,6 +75,19 @@ export function useTimeRangeControl(): UseTimeRangeControlReturn {
   // 自定义时间对话框状态
   const [showCustomDialog, setShowCustomDialog] = useState(false);
 
+  // 当前统计周期开始时间
+  const [epochStartMs, setEpochStartMs] = useState<number | null>(null);
+
+  const refreshEpoch = useCallback(() => {
+    getCurrentStatsEpoch()
+      .then((epoch) => setEpochStartMs(epoch.started_at))
+      .catch((error) => console.error('获取统计周期失败:', error));
+  }, []);
+
+  useEffect(() => {
+    refreshEpoch();
+  }, [refreshEpoch]);
+
   // 计算允许的粒度选项
   const allowedGranularities = useMemo<TimeGranularity[]>(() => {
     if (mode === 'preset') {
@@ -91,8 +109,10 @@ export function useTimeRangeControl(): UseTimeRangeControlReturn {
     const now = Date.now();
 
     if (mode === 'preset') {
+      // 预设范围仅展示当前统计周期，历史周期通过自定义范围查询
+      const presetStart = calculatePresetStartTime(presetRange, now);
       return {
-        startTimeMs: calculatePresetStartTime(presetRange, now),
+        startTimeMs: epochStartMs ? Math.max(presetStart, epochStartMs) : presetStart,
         endTimeMs: now,
       };
     } else {
@@ -109,7 +129,7 @@ export function useTimeRangeControl(): UseTimeRangeControlReturn {
         endTimeMs: now,
       };
     }
-  }, [mode, presetRange, confirmedCustomStart, confirmedCustomEnd]);
+  }, [mode, presetRange, confirmedCustomStart, confirmedCustomEnd, epochStartMs]);
 
   // 验证自定义时间是否有效
   const isCustomTimeValid = useMemo(() => {
@@ -197,6 +217,8 @@ export function useTimeRangeControl(): UseTimeRangeControlReturn {
     allowedGranularities,
     startTimeMs,
     endTimeMs,
+    epochStartMs,
+    refreshEpoch,
     showCustomDialog,
     openCustomDialog,
     closeCustomDialog,
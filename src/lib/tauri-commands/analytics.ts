@@ -2,7 +2,34 @@
  * Token 统计分析相关 Tauri 命令
  */
 import { invoke } from '@tauri-apps/api/core';
-import type { TrendQuery, TrendDataPoint, CostSummary } from '@/types/analytics';
+import type {
+  TrendQuery,
+  TrendDataPoint,
+  CostSummary,
+  SqlConsoleQuery,
+  SqlConsoleResult,
+  SqlHistoryEntry,
+  SavedReport,
+  ReportOutput,
+  ReportImportSummary,
+  ProductivityQuery,
+  ProductivityReport,
+  ToolComparisonQuery,
+  ToolComparison,
+  ModelBreakdown,
+  LatencyStatsQuery,
+  LatencyStats,
+  MonthlyCostQuery,
+  MonthlyCostReport,
+  ScrubOptions,
+  StatsEpoch,
+  EpochSummary,
+  UsageReportRange,
+  UsageReportFormat,
+  GeneratedUsageReport,
+  CacheEfficiencyQuery,
+  CacheEfficiencyReport,
+} from '@/types/analytics';
 
 /**
  * 查询 Token 使用趋势数据
@@ -19,6 +46,7 @@ export async function queryTokenTrends(query: TrendQuery): Promise<TrendDataPoin
  * @param endTime 结束时间戳（毫秒）
  * @param toolType 工具类型过滤（可选）
  * @param sessionId 会话 ID 过滤（可选）
+ * @param tag 标签过滤（可选，如项目名）
  * @returns 成本汇总数据
  */
 export async function queryCostSummary(
@@ -26,11 +54,210 @@ export async function queryCostSummary(
   endTime: number,
   toolType?: string,
   sessionId?: string,
+  tag?: string,
 ): Promise<CostSummary> {
   return await invoke<CostSummary>('query_cost_summary', {
     startTime,
     endTime,
     toolType,
     sessionId,
+    tag,
+  });
+}
+
+/**
+ * 查询生产力关联指标（git 变更行数 × 会话成本）
+ * @param query 查询参数
+ * @returns 每千行成本、每次提交成本等指标
+ */
+export async function queryProductivityMetrics(
+  query: ProductivityQuery,
+): Promise<ProductivityReport> {
+  return await invoke<ProductivityReport>('query_productivity_metrics', { query });
+}
+
+/**
+ * 查询脱敏后的成本汇总（截图 / 演示模式）
+ * @returns 与 queryCostSummary 结构相同，配置名称哈希、会话 ID 缩短、成本可选取整
+ */
+export async function queryAnonymizedCostSummary(
+  startTime: number,
+  endTime: number,
+  options: ScrubOptions,
+  toolType?: string,
+  sessionId?: string,
+): Promise<CostSummary> {
+  return await invoke<CostSummary>('query_anonymized_cost_summary', {
+    startTime,
+    endTime,
+    toolType,
+    sessionId,
+    options,
+  });
+}
+
+/**
+ * 脱敏任意统计数据（趋势、日志、对比等已获取的数据）
+ */
+export async function anonymizeStatsPayload<T>(payload: T, options: ScrubOptions): Promise<T> {
+  return await invoke<T>('anonymize_stats_payload', { payload, options });
+}
+
+/**
+ * 查询同一时间段内各工具的横向对比
+ * @param query 查询参数
+ * @returns 每个工具的成本、延迟、失败率、缓存效率及各项最优工具
+ */
+export async function queryToolComparison(query: ToolComparisonQuery): Promise<ToolComparison> {
+  return await invoke<ToolComparison>('query_tool_comparison', { query });
+}
+
+/**
+ * 查询按模型的用量排行（与上一等长周期对比）
+ * @param range 时间范围（近 7 天 / 近 30 天 / 自定义）
+ * @returns 按成本降序的模型用量及环比变化
+ */
+export async function getModelBreakdown(range: UsageReportRange): Promise<ModelBreakdown> {
+  return await invoke<ModelBreakdown>('get_model_breakdown', { range });
+}
+
+/**
+ * 查询响应时间分位数（p50 / p90 / p99）
+ * @param query 查询参数（按模型、配置或时间桶分组）
+ * @returns 每个分组的响应时间分布
+ */
+export async function queryLatencyStats(query: LatencyStatsQuery): Promise<LatencyStats[]> {
+  return await invoke<LatencyStats[]>('query_latency_stats', { query });
+}
+
+/**
+ * 查询月度账单
+ * @param query 查询参数
+ * @returns 按月汇总的用量成本、最低消费补足金额与结算金额
+ */
+export async function queryMonthlyCostReport(
+  query: MonthlyCostQuery,
+): Promise<MonthlyCostReport[]> {
+  return await invoke<MonthlyCostReport[]>('query_monthly_cost_report', { query });
+}
+
+/**
+ * 查询缓存效率（缓存读取占比与缓存节省的成本）
+ */
+export async function queryCacheEfficiency(
+  query: CacheEfficiencyQuery,
+): Promise<CacheEfficiencyReport> {
+  return await invoke<CacheEfficiencyReport>('query_cache_efficiency', { query });
+}
+
+/**
+ * 执行只读 SQL 查询（SQL 控制台）
+ * @param query 查询参数
+ * @returns 查询结果
+ */
+export async function runSqlConsoleQuery(query: SqlConsoleQuery): Promise<SqlConsoleResult> {
+  return await invoke<SqlConsoleResult>('run_sql_console_query', { query });
+}
+
+/**
+ * 获取 SQL 控制台查询历史（最新在前）
+ */
+export async function getSqlConsoleHistory(): Promise<SqlHistoryEntry[]> {
+  return await invoke<SqlHistoryEntry[]>('get_sql_console_history');
+}
+
+/**
+ * 清空 SQL 控制台查询历史
+ */
+export async function clearSqlConsoleHistory(): Promise<void> {
+  return await invoke<void>('clear_sql_console_history');
+}
+
+/**
+ * 列出已保存的报表
+ */
+export async function listSavedReports(): Promise<SavedReport[]> {
+  return await invoke<SavedReport[]>('list_saved_reports');
+}
+
+/**
+ * 保存报表（id 为空时新建）
+ */
+export async function saveReport(report: SavedReport): Promise<SavedReport> {
+  return await invoke<SavedReport>('save_report', { report });
+}
+
+/**
+ * 删除报表
+ */
+export async function deleteSavedReport(id: string): Promise<void> {
+  return await invoke<void>('delete_saved_report', { id });
+}
+
+/**
+ * 运行已保存的报表
+ */
+export async function runSavedReport(id: string): Promise<ReportOutput> {
+  return await invoke<ReportOutput>('run_saved_report', { id });
+}
+
+/**
+ * 导出报表为 JSON 字符串（ids 为空时导出全部）
+ */
+export async function exportSavedReports(ids: string[] = []): Promise<string> {
+  return await invoke<string>('export_saved_reports', { ids });
+}
+
+/**
+ * 从 JSON 字符串导入报表
+ * @param overwrite 是否覆盖 ID 相同的已有报表
+ */
+export async function importSavedReports(
+  content: string,
+  overwrite = false,
+): Promise<ReportImportSummary> {
+  return await invoke<ReportImportSummary>('import_saved_reports', { content, overwrite });
+}
+
+/**
+ * 获取当前统计周期
+ */
+export async function getCurrentStatsEpoch(): Promise<StatsEpoch> {
+  return await invoke<StatsEpoch>('get_current_stats_epoch');
+}
+
+/**
+ * 列出所有统计周期（最新在前）
+ */
+export async function listStatsEpochs(): Promise<StatsEpoch[]> {
+  return await invoke<StatsEpoch[]>('list_stats_epochs');
+}
+
+/**
+ * 查询统计周期的分组用量
+ */
+export async function getStatsEpochSummary(epochId: number): Promise<EpochSummary[]> {
+  return await invoke<EpochSummary[]>('get_stats_epoch_summary', { epochId });
+}
+
+/**
+ * 关闭当前统计周期并归档，开启新周期
+ * @param name 新周期名称（省略时使用当前时间）
+ */
+export async function startNewStatsEpoch(name?: string): Promise<StatsEpoch> {
+  return await invoke<StatsEpoch>('start_new_stats_epoch', { name: name ?? null });
+}
+
+/**
+ * 生成用量报告并写入磁盘
+ * @param format 输出格式（省略时使用配置中的格式）
+ */
+export async function generateUsageReport(
+  range: UsageReportRange,
+  format?: UsageReportFormat,
+): Promise<GeneratedUsageReport> {
+  return await invoke<GeneratedUsageReport>('generate_usage_report', {
+    range,
+    format: format ?? null,
   });
 }
@@ -3,6 +3,31 @@
 
 import { invoke } from '@tauri-apps/api/core';
 import type { GenerateApiKeyResult, UsageStatsResult, UserQuotaResult } from './types';
+import type { ApiHandshake } from '@/types/bindings/ApiHandshake';
+
+/**
+ * 前端期望的命令接口版本（与后端 models::api::API_VERSION 保持一致）
+ */
+export const EXPECTED_API_VERSION = 1;
+
+/**
+ * 获取后端接口版本握手信息
+ */
+export async function getApiHandshake(): Promise<ApiHandshake> {
+  return await invoke<ApiHandshake>('get_api_handshake');
+}
+
+/**
+ * 校验前后端接口版本
+ * @returns 不匹配时返回说明，匹配时返回 null
+ */
+export async function checkApiHandshake(): Promise<string | null> {
+  const handshake = await getApiHandshake();
+  if (handshake.api_version === EXPECTED_API_VERSION) {
+    return null;
+  }
+  return `前端期望接口版本 ${EXPECTED_API_VERSION}，后端（v${handshake.app_version}）为 ${handshake.api_version}`;
+}
 
 /**
  * 为指定工具生成 API Key
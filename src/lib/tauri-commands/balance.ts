@@ -2,7 +2,7 @@
 // 负责余额配置的 CRUD 和数据迁移
 
 import { invoke } from '@tauri-apps/api/core';
-import type { BalanceStore, BalanceConfigBackend } from './types';
+import type { BalanceStore, BalanceConfigBackend, BalanceSummary } from './types';
 import type { BalanceConfig } from '@/pages/BalancePage/types';
 
 /**
@@ -86,3 +86,11 @@ export async function migrateBalanceFromLocalstorage(configs: BalanceConfig[]):
     configs: configs.map(toBackendConfig),
   });
 }
+
+/**
+ * 获取所有供应商与 Profile 的余额及历史
+ * @param refresh 为 true 时先立即查询一轮
+ */
+export async function getAllBalances(refresh = false): Promise<BalanceSummary[]> {
+  return invoke<BalanceSummary[]>('get_all_balances', { refresh });
+}
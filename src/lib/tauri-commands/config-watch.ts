@@ -2,7 +2,28 @@
  * 配置监听相关 Tauri 命令
  */
 import { invoke } from '@tauri-apps/api/core';
-import type { ConfigWatchConfig, ConfigChangeRecord } from '@/types/config-watch';
+import type {
+  AdminApiConfig,
+  AdminApiStatus,
+  AuditEntry,
+  AuditVerification,
+  ConfigWatchConfig,
+  ConfigChangeRecord,
+  DiagnosticsReport,
+  EnvironmentFix,
+  EnvironmentReport,
+  MaintenanceConfig,
+  MaintenanceReport,
+  MaintenanceStatus,
+  McpConfig,
+  McpStatus,
+  MetricsConfig,
+  MetricsStatus,
+  SimulatedChange,
+  SimulationResult,
+  SystemHealthReport,
+  ToolConfigDir,
+} from '@/types/config-watch';
 
 /**
  * 阻止外部变更（恢复快照）
@@ -32,6 +53,32 @@ export async function updateWatchConfig(config: ConfigWatchConfig): Promise<void
   await invoke('update_watch_config', { config });
 }
 
+/**
+ * 获取各工具实际使用的配置目录
+ */
+export async function getToolConfigDirs(): Promise<ToolConfigDir[]> {
+  return await invoke('get_tool_config_dirs');
+}
+
+/**
+ * 设置工具配置目录覆盖（传 null 清除覆盖）
+ */
+export async function setToolConfigDir(
+  toolId: string,
+  configDir: string | null,
+): Promise<ToolConfigDir> {
+  return await invoke('set_tool_config_dir', { toolId, configDir });
+}
+
+/**
+ * 模拟外部配置变更事件（仅开发模式可用）
+ */
+export async function simulateExternalConfigChange(
+  change: SimulatedChange,
+): Promise<SimulationResult> {
+  return await invoke('simulate_external_config_change', { change });
+}
+
 // ==================== 配置守护管理 ====================
 
 /**
@@ -101,3 +148,108 @@ export async function updateChangeLogAction(
 ): Promise<void> {
   await invoke('update_change_log_action', { toolId, timestamp, action });
 }
+
+/**
+ * 获取系统健康报告（含配置守护运行状态）
+ */
+export async function getSystemHealth(): Promise<SystemHealthReport> {
+  return await invoke('get_system_health');
+}
+
+/**
+ * 执行自诊断（配置、端口、数据库、磁盘、上游、时钟与工具命令）
+ */
+export async function runDiagnostics(): Promise<DiagnosticsReport> {
+  return await invoke('run_diagnostics');
+}
+
+/**
+ * 环境诊断（PATH、Node.js/npm、代理环境变量）
+ */
+export async function inspectEnvironment(): Promise<EnvironmentReport> {
+  return await invoke('inspect_environment');
+}
+
+/**
+ * 应用环境诊断给出的修复，返回是否修改了配置
+ */
+export async function applyEnvironmentFix(fix: EnvironmentFix): Promise<boolean> {
+  return await invoke('apply_environment_fix', { fix });
+}
+
+/**
+ * 获取夜间维护调度状态
+ */
+export async function getMaintenanceStatus(): Promise<MaintenanceStatus> {
+  return await invoke('get_maintenance_status');
+}
+
+/**
+ * 更新夜间维护配置
+ */
+export async function updateMaintenanceConfig(config: MaintenanceConfig): Promise<void> {
+  await invoke('update_maintenance_config', { config });
+}
+
+/**
+ * 立即执行一次维护（force 为 true 时忽略活跃会话检查）
+ */
+export async function runMaintenanceNow(force: boolean): Promise<MaintenanceReport> {
+  return await invoke('run_maintenance_now', { force });
+}
+
+/**
+ * 获取 Prometheus 指标监听状态
+ */
+export async function getMetricsStatus(): Promise<MetricsStatus> {
+  return await invoke('get_metrics_status');
+}
+
+/**
+ * 更新 Prometheus 指标配置（立即启动、重启或停止监听）
+ */
+export async function updateMetricsConfig(config: MetricsConfig): Promise<MetricsStatus> {
+  return await invoke('update_metrics_config', { config });
+}
+
+/**
+ * 获取本机管理 API 状态
+ */
+export async function getAdminApiStatus(): Promise<AdminApiStatus> {
+  return await invoke('get_admin_api_status');
+}
+
+/**
+ * 更新本机管理 API 配置（启用时令牌为空则自动生成）
+ */
+export async function updateAdminApiConfig(config: AdminApiConfig): Promise<AdminApiStatus> {
+  return await invoke('update_admin_api_config', { config });
+}
+
+/**
+ * 获取 MCP 服务状态
+ */
+export async function getMcpStatus(): Promise<McpStatus> {
+  return await invoke('get_mcp_status');
+}
+
+/**
+ * 更新 MCP 服务配置（启用 HTTP 接入时令牌为空则自动生成）
+ */
+export async function updateMcpConfig(config: McpConfig): Promise<McpStatus> {
+  return await invoke('update_mcp_config', { config });
+}
+
+/**
+ * 查询凭证操作审计日志（最新在前）
+ */
+export async function getAuditLog(toolId?: string, limit?: number): Promise<AuditEntry[]> {
+  return await invoke('get_audit_log', { toolId: toolId ?? null, limit: limit ?? null });
+}
+
+/**
+ * 校验审计日志哈希链
+ */
+export async function verifyAuditLog(): Promise<AuditVerification> {
+  return await invoke('verify_audit_log');
+}
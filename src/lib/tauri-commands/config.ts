@@ -3,6 +3,8 @@
 
 import { invoke } from '@tauri-apps/api/core';
 import type {
+  AuthBridgeStatus,
+  CompatFileReport,
   GlobalConfig,
   ClaudeSettingsPayload,
   CodexSettingsPayload,
@@ -13,6 +15,7 @@ import type {
   JsonValue,
   TestProxyResult,
   ProxyTestConfig,
+  StartupMode,
 } from './types';
 
 // ==================== 全局配置 ====================
@@ -31,6 +34,13 @@ export async function getGlobalConfig(): Promise<GlobalConfig | null> {
   return await invoke<GlobalConfig | null>('get_global_config');
 }
 
+/**
+ * 获取配置兼容性报告（降级后当前版本不理解的字段）
+ */
+export async function getConfigCompatReport(): Promise<CompatFileReport[]> {
+  return await invoke<CompatFileReport[]>('get_config_compat_report');
+}
+
 /**
  * 获取当前代理配置字符串
  */
@@ -55,6 +65,17 @@ export async function testProxyRequest(
   return await invoke<TestProxyResult>('test_proxy_request', { testUrl, proxyConfig });
 }
 
+/**
+ * 检测本地认证桥（NTLM/Negotiate 企业代理）
+ */
+export async function probeProxyAuthBridge(
+  host: string,
+  port: number,
+  testUrl: string,
+): Promise<AuthBridgeStatus> {
+  return await invoke<AuthBridgeStatus>('probe_proxy_auth_bridge', { host, port, testUrl });
+}
+
 // ==================== Claude Code 配置 ====================
 
 /**
@@ -186,6 +207,20 @@ export async function updateSingleInstanceConfig(enabled: boolean): Promise<void
   return await invoke<void>('update_single_instance_config', { enabled });
 }
 
+/**
+ * 获取只读观察模式状态
+ */
+export async function getObserverMode(): Promise<boolean> {
+  return await invoke<boolean>('get_observer_mode');
+}
+
+/**
+ * 切换只读观察模式（开启后不会写入任何工具配置文件）
+ */
+export async function setObserverMode(enabled: boolean): Promise<void> {
+  return await invoke<void>('set_observer_mode', { enabled });
+}
+
 // ==================== 开机自启动配置 ====================
 
 /**
@@ -203,3 +238,12 @@ export async function getStartupConfig(): Promise<boolean> {
 export async function updateStartupConfig(enabled: boolean): Promise<void> {
   return await invoke<void>('update_startup_config', { enabled });
 }
+
+/**
+ * 设置开机自启动及自启动时的启动方式
+ * @param enabled - 是否启用开机自启动
+ * @param startupMode - 自启动时的启动方式（省略时保持不变）
+ */
+export async function setAutostart(enabled: boolean, startupMode?: StartupMode): Promise<void> {
+  return await invoke<void>('set_autostart', { enabled, startupMode });
+}
@@ -0,0 +1,41 @@
+// 崩溃报告命令模块
+// 负责查看、删除本地崩溃报告，以及用户确认后发送报告
+
+import { invoke } from '@tauri-apps/api/core';
+import type { CrashReport, CrashReportConfig, CrashReportSummary } from '@/types/crash';
+
+/**
+ * 列出本地崩溃报告（从新到旧）
+ */
+export async function listCrashReports(): Promise<CrashReportSummary[]> {
+  return await invoke<CrashReportSummary[]>('list_crash_reports');
+}
+
+/**
+ * 获取崩溃报告详情（发送前供用户查看完整内容）
+ */
+export async function getCrashReport(id: string): Promise<CrashReport> {
+  return await invoke<CrashReport>('get_crash_report', { id });
+}
+
+/**
+ * 删除崩溃报告
+ */
+export async function deleteCrashReport(id: string): Promise<void> {
+  await invoke('delete_crash_report', { id });
+}
+
+/**
+ * 发送崩溃报告（需先开启上传并配置接收地址）
+ * @returns 已记录发送时间的报告
+ */
+export async function sendCrashReport(id: string): Promise<CrashReport> {
+  return await invoke<CrashReport>('send_crash_report', { id });
+}
+
+/**
+ * 更新崩溃报告上传配置
+ */
+export async function updateCrashReportConfig(config: CrashReportConfig): Promise<void> {
+  await invoke('update_crash_report_config', { config });
+}
@@ -1,7 +1,8 @@
 // Dashboard 管理命令模块
-// 负责仪表板状态管理：工具实例选择、选中供应商 Tab
+// 负责仪表板状态管理：工具实例选择、选中供应商 Tab、首屏汇总
 
 import { invoke } from '@tauri-apps/api/core';
+import type { DashboardSummary } from './types';
 
 /**
  * 获取工具实例选择
@@ -36,3 +37,11 @@ export async function getSelectedProviderId(): Promise<string | null> {
 export async function setSelectedProviderId(providerId: string | null): Promise<void> {
   return invoke<void>('set_selected_provider_id', { providerId });
 }
+
+/**
+ * 获取仪表板汇总（今日用量、运行中的代理、激活的 Profile、供应商健康、最近的配置变更、可用更新）
+ * @param force 为 true 时忽略后端缓存重新收集
+ */
+export async function getDashboardSummary(force?: boolean): Promise<DashboardSummary> {
+  return invoke<DashboardSummary>('get_dashboard_summary', { force: force ?? null });
+}
@@ -48,3 +48,16 @@ export * from './api';
 
 // AMP 用户认证
 export * from './amp';
+
+// 异常退出恢复
+export * from './recovery';
+
+// 崩溃报告
+export * from './crash';
+export * from './search';
+
+// 项目注册
+export * from './project';
+
+// 故障排查
+export * from './troubleshoot';
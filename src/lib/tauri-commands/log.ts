@@ -1,8 +1,8 @@
 // 日志管理命令模块
-// 负责日志配置的查询和更新
+// 负责日志配置的查询和更新，以及日志文件管理
 
 import { invoke } from '@tauri-apps/api/core';
-import type { LogConfig } from './types';
+import type { LogConfig, LogFileInfo } from './types';
 
 /**
  * 检测当前是否为 Release 构建
@@ -26,3 +26,19 @@ export async function getLogConfig(): Promise<LogConfig> {
 export async function updateLogConfig(newConfig: LogConfig): Promise<string> {
   return await invoke<string>('update_log_config', { newConfig });
 }
+
+/**
+ * 列出日志文件（当前日志在前，其余历史日志从新到旧）
+ */
+export async function getLogFiles(): Promise<LogFileInfo[]> {
+  return await invoke<LogFileInfo[]>('get_log_files');
+}
+
+/**
+ * 清理历史日志文件（不影响当前日志）
+ * @param olderThanDays - 仅删除早于该天数的日志，省略时删除全部历史日志
+ * @returns 删除的文件数
+ */
+export async function purgeLogs(olderThanDays?: number): Promise<number> {
+  return await invoke<number>('purge_logs', { olderThanDays: olderThanDays ?? null });
+}
@@ -5,7 +5,14 @@
  */
 
 import { invoke } from '@tauri-apps/api/core';
-import type { PricingTemplate, PricingToolId } from '@/types/pricing';
+import type {
+  CatalogSyncReport,
+  CostScenario,
+  CostSimulation,
+  PricingTemplate,
+  PricingTemplateValidation,
+  PricingToolId,
+} from '@/types/pricing';
 
 /**
  * 列出所有价格模板
@@ -73,3 +80,51 @@ export async function setDefaultTemplate(toolId: PricingToolId, templateId: stri
 export async function getDefaultTemplate(toolId: PricingToolId): Promise<PricingTemplate> {
   return invoke('get_default_template', { toolId });
 }
+
+/**
+ * 校验价格模板（别名冲突检测）
+ *
+ * @param templateId - 模板 ID（省略时校验所有模板）
+ * @returns 每个模板的校验结果，`ambiguous > 0` 表示存在无法计价的别名
+ */
+export async function validatePricingTemplate(
+  templateId?: string,
+): Promise<PricingTemplateValidation[]> {
+  return invoke('validate_pricing_template', { templateId: templateId ?? null });
+}
+
+/**
+ * 校验尚未保存的价格模板
+ *
+ * @param template - 待校验的模板数据
+ * @returns 校验结果，`valid` 为 false 时不建议保存或设为默认
+ */
+export async function validatePricingTemplateDraft(
+  template: PricingTemplate,
+): Promise<PricingTemplateValidation> {
+  return invoke('validate_pricing_template_draft', { template });
+}
+
+/**
+ * 按假设的 Token 用量试算成本（不写入统计数据）
+ *
+ * @param templateId - 模板 ID
+ * @param scenario - 试算场景
+ * @returns 实际使用的价格与成本分解
+ */
+export async function simulateCost(
+  templateId: string,
+  scenario: CostScenario,
+): Promise<CostSimulation> {
+  return invoke('simulate_cost', { templateId, scenario });
+}
+
+/**
+ * 立即从远程价格目录同步模型定价
+ *
+ * @param url - 价格目录地址（省略时使用配置的地址）
+ * @returns 各内置模板新增 / 更新的模型
+ */
+export async function syncPricingCatalog(url?: string): Promise<CatalogSyncReport> {
+  return invoke('sync_pricing_catalog', { url: url ?? null });
+}
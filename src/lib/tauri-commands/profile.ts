@@ -2,7 +2,16 @@
 // 负责 Profile 的 CRUD、激活、导入导出、原生配置同步
 
 import { invoke } from '@tauri-apps/api/core';
-import type { ProfileData, ProfileDescriptor, ProfilePayload, ToolId } from './types';
+import type {
+  ConfigDriftReport,
+  NativeConfigSnippet,
+  ProfileData,
+  ProfileDescriptor,
+  ProfileHealth,
+  ProfileHealthMatrix,
+  ProfilePayload,
+  ToolId,
+} from './types';
 
 // ==================== 旧版 Profile 管理 ====================
 
@@ -83,6 +92,48 @@ export async function pmCaptureFromNative(toolId: ToolId, name: string): Promise
   return invoke<void>('pm_capture_from_native', { toolId, name });
 }
 
+/**
+ * 导出 Profile 为工具原生格式的配置片段（redactKeys 为 true 时 API Key 替换为占位符）
+ */
+export async function pmExportNativeSnippet(
+  toolId: ToolId,
+  name: string,
+  redactKeys: boolean,
+): Promise<NativeConfigSnippet> {
+  return invoke<NativeConfigSnippet>('pm_export_native_snippet', { toolId, name, redactKeys });
+}
+
+/**
+ * 检测原生配置是否偏离当前激活的 Profile（Base URL、API Key、模型相关字段）
+ */
+export async function pmCheckConfigDrift(toolId: ToolId): Promise<ConfigDriftReport> {
+  return invoke<ConfigDriftReport>('pm_check_config_drift', { toolId });
+}
+
+/**
+ * 重新应用当前激活的 Profile 修复配置漂移
+ */
+export async function pmRepairDrift(toolId: ToolId): Promise<ConfigDriftReport> {
+  return invoke<ConfigDriftReport>('pm_repair_drift', { toolId });
+}
+
+/**
+ * 检查单个 Profile 的上游可达性、延迟与 API Key
+ */
+export async function pmCheckProfileHealth(
+  toolId: ToolId,
+  profileName: string,
+): Promise<ProfileHealth> {
+  return invoke<ProfileHealth>('pm_check_profile_health', { toolId, profileName });
+}
+
+/**
+ * 并发检查所有 Profile（force 为 false 时复用 5 分钟内的缓存结果）
+ */
+export async function pmCheckAllProfiles(force: boolean): Promise<ProfileHealthMatrix> {
+  return invoke<ProfileHealthMatrix>('pm_check_all_profiles', { force });
+}
+
 /**
  * 从 Profile 更新代理配置（不激活 Profile）
  */
@@ -0,0 +1,54 @@
+// 项目注册命令模块
+// 注册项目时识别技术栈，并按映射规则生成 Profile / 模型建议
+
+import { invoke } from '@tauri-apps/api/core';
+import type { ProjectRecord, ProjectStack, StackRule } from '@/types/project';
+
+/**
+ * 列出已注册项目
+ */
+export async function listProjects(): Promise<ProjectRecord[]> {
+  return await invoke<ProjectRecord[]>('list_projects');
+}
+
+/**
+ * 注册项目（同一路径重复注册时刷新识别结果）
+ */
+export async function registerProject(path: string, name?: string): Promise<ProjectRecord> {
+  return await invoke<ProjectRecord>('register_project', { path, name: name ?? null });
+}
+
+/**
+ * 重新识别项目技术栈并刷新建议
+ */
+export async function redetectProject(id: string): Promise<ProjectRecord> {
+  return await invoke<ProjectRecord>('redetect_project', { id });
+}
+
+/**
+ * 删除项目
+ */
+export async function removeProject(id: string): Promise<void> {
+  await invoke('remove_project', { id });
+}
+
+/**
+ * 预览目录的技术栈识别结果（不注册）
+ */
+export async function detectProjectStack(path: string): Promise<ProjectStack> {
+  return await invoke<ProjectStack>('detect_project_stack', { path });
+}
+
+/**
+ * 获取技术栈映射规则
+ */
+export async function getProjectStackRules(): Promise<StackRule[]> {
+  return await invoke<StackRule[]>('get_project_stack_rules');
+}
+
+/**
+ * 保存技术栈映射规则（不会刷新已注册项目的建议）
+ */
+export async function saveProjectStackRules(rules: StackRule[]): Promise<void> {
+  await invoke('save_project_stack_rules', { rules });
+}
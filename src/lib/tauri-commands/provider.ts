@@ -1,8 +1,14 @@
 // 供应商管理命令模块
-// 负责供应商的 CRUD、验证
+// 负责供应商的 CRUD、验证、上游健康状况
 
 import { invoke } from '@tauri-apps/api/core';
-import type { Provider, _ProviderFormData, ProviderValidationResult, ApiInfo } from './types';
+import type {
+  Provider,
+  _ProviderFormData,
+  ProviderValidationResult,
+  ApiInfo,
+  ProviderHealth,
+} from './types';
 
 /**
  * 列出所有供应商
@@ -61,3 +67,10 @@ export async function fetchProviderApiAddresses(websiteUrl: string): Promise<Api
     return [];
   }
 }
+
+/**
+ * 获取工具各上游的健康状况（后台定时探测，可达性变化时触发 provider-health-changed 事件）
+ */
+export async function getProviderHealth(toolId: string): Promise<ProviderHealth[]> {
+  return invoke<ProviderHealth[]>('get_provider_health', { toolId });
+}
@@ -2,7 +2,24 @@
 // 负责透明代理的启动、停止、状态查询和配置管理
 
 import { invoke } from '@tauri-apps/api/core';
-import type { AllProxyStatus, ToolProxyConfig, ToolId } from './types';
+import type {
+  AllProxyStatus,
+  CapturedExchange,
+  CaptureStatus,
+  CaptureSummary,
+  ClientApiKey,
+  ModelRoutingRule,
+  PendingCostConfirmation,
+  ReplayPlan,
+  ReplayReport,
+  ReplayResult,
+  ResponseCacheStats,
+  RoutingRuleInput,
+  ToolProxyConfig,
+  ToolId,
+  TrialRequest,
+  TrialSession,
+} from './types';
 
 // ==================== 多工具透明代理 API（新架构）====================
 
@@ -50,3 +67,152 @@ export async function updateProxyConfig(toolId: ToolId, config: ToolProxyConfig)
 export async function getAllProxyConfigs(): Promise<Record<string, ToolProxyConfig>> {
   return await invoke<Record<string, ToolProxyConfig>>('get_all_proxy_configs');
 }
+
+/**
+ * 获取指定工具的模型路由规则
+ */
+export async function listRoutingRules(toolId: ToolId): Promise<ModelRoutingRule[]> {
+  return await invoke<ModelRoutingRule[]>('list_routing_rules', { toolId });
+}
+
+/**
+ * 新建或更新模型路由规则（上游地址与 Key 从目标 Profile 解析，代理运行中时立即生效）
+ */
+export async function saveRoutingRule(
+  toolId: ToolId,
+  rule: RoutingRuleInput,
+): Promise<ModelRoutingRule> {
+  return await invoke<ModelRoutingRule>('save_routing_rule', { toolId, rule });
+}
+
+/**
+ * 删除模型路由规则
+ */
+export async function deleteRoutingRule(toolId: ToolId, ruleId: string): Promise<void> {
+  return await invoke<void>('delete_routing_rule', { toolId, ruleId });
+}
+
+/**
+ * 获取指定工具代理的客户端 Key
+ */
+export async function listProxyClientKeys(toolId: ToolId): Promise<ClientApiKey[]> {
+  return await invoke<ClientApiKey[]>('list_proxy_client_keys', { toolId });
+}
+
+/**
+ * 生成客户端 Key（多台机器 / 多位成员共用代理时按标签分别统计用量）
+ */
+export async function createProxyClientKey(toolId: ToolId, label: string): Promise<ClientApiKey> {
+  return await invoke<ClientApiKey>('create_proxy_client_key', { toolId, label });
+}
+
+/**
+ * 吊销客户端 Key（代理运行中时立即生效）
+ */
+export async function revokeProxyClientKey(toolId: ToolId, keyId: string): Promise<void> {
+  return await invoke<void>('revoke_proxy_client_key', { toolId, keyId });
+}
+
+/**
+ * 获取请求体捕获状态（当前捕获数量与过期时间）
+ */
+export async function getBodyCaptureStatus(): Promise<CaptureStatus> {
+  return await invoke<CaptureStatus>('get_body_capture_status');
+}
+
+/**
+ * 立即删除请求体捕获
+ * @param toolId - 工具 ID，为空时删除全部工具的捕获
+ * @returns 删除数量
+ */
+export async function clearBodyCaptures(toolId?: ToolId): Promise<number> {
+  return await invoke<number>('clear_body_captures', { toolId: toolId ?? null });
+}
+
+/**
+ * 获取各工具的响应缓存统计（条目数、命中 / 未命中次数）
+ */
+export async function getProxyCacheStats(): Promise<ResponseCacheStats[]> {
+  return await invoke<ResponseCacheStats[]>('get_proxy_cache_stats');
+}
+
+/**
+ * 清空响应缓存
+ * @param toolId - 工具 ID，为空时清空全部工具的缓存
+ * @returns 删除的条目数
+ */
+export async function clearProxyCache(toolId?: ToolId): Promise<number> {
+  return await invoke<number>('clear_proxy_cache', { toolId: toolId ?? null });
+}
+
+/**
+ * 列出未过期的请求捕获（最新在前）
+ * @param toolId - 工具 ID，为空时返回全部工具
+ * @param limit - 最大条数（默认 100）
+ */
+export async function listRequestLogs(toolId?: ToolId, limit?: number): Promise<CaptureSummary[]> {
+  return await invoke<CaptureSummary[]>('list_request_logs', {
+    toolId: toolId ?? null,
+    limit: limit ?? null,
+  });
+}
+
+/**
+ * 获取单条请求捕获（含请求头、请求体与响应体）
+ */
+export async function getRequestLog(id: string): Promise<CapturedExchange> {
+  return await invoke<CapturedExchange>('get_request_log', { id });
+}
+
+/**
+ * 经本地透明代理重放一条请求捕获（需代理正在运行）
+ */
+export async function replayRequest(id: string): Promise<ReplayResult> {
+  return await invoke<ReplayResult>('replay_request', { id });
+}
+
+/**
+ * 按计划把请求捕获重放到指定上游，返回延迟与错误分布（用于切换供应商前压测）
+ */
+export async function runReplay(plan: ReplayPlan): Promise<ReplayReport> {
+  return await invoke<ReplayReport>('run_replay', { plan });
+}
+
+// ==================== 供应商试用 ====================
+
+/**
+ * 开始供应商试用：启动临时代理，只让指定项目经该供应商访问，到期自动还原
+ */
+export async function startProviderTrial(request: TrialRequest): Promise<TrialSession> {
+  return await invoke<TrialSession>('start_provider_trial', { request });
+}
+
+/**
+ * 列出进行中的供应商试用
+ */
+export async function listProviderTrials(): Promise<TrialSession[]> {
+  return await invoke<TrialSession[]>('list_provider_trials');
+}
+
+/**
+ * 提前结束供应商试用并还原项目配置
+ */
+export async function stopProviderTrial(id: string): Promise<void> {
+  return await invoke<void>('stop_provider_trial', { id });
+}
+
+// ==================== 成本预检 ====================
+
+/**
+ * 列出等待确认的高成本请求
+ */
+export async function listPendingCostConfirmations(): Promise<PendingCostConfirmation[]> {
+  return await invoke<PendingCostConfirmation[]>('list_pending_cost_confirmations');
+}
+
+/**
+ * 确认（继续转发）或拒绝等待中的高成本请求
+ */
+export async function confirmCostGuardRequest(id: string, approve: boolean): Promise<void> {
+  return await invoke<void>('confirm_cost_guard_request', { id, approve });
+}
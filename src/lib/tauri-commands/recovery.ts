@@ -0,0 +1,42 @@
+// 异常退出恢复命令模块
+// 负责查询启动时的恢复报告，以及检查/还原指向未运行代理的工具配置
+
+import { invoke } from '@tauri-apps/api/core';
+import type { RecoveryReport, StaleProxyConfig } from '@/types/recovery';
+
+/**
+ * 获取启动时的异常退出恢复报告
+ * @returns 恢复报告（上次为正常退出时返回 null）
+ */
+export async function getStartupRecoveryReport(): Promise<RecoveryReport | null> {
+  return await invoke<RecoveryReport | null>('get_startup_recovery_report');
+}
+
+/**
+ * 用户确认后清除恢复报告
+ */
+export async function dismissStartupRecoveryReport(): Promise<void> {
+  await invoke('dismiss_startup_recovery_report');
+}
+
+/**
+ * 检查工具配置是否指向未运行的代理
+ */
+export async function checkProxyConfigConsistency(): Promise<StaleProxyConfig[]> {
+  return await invoke<StaleProxyConfig[]>('check_proxy_config_consistency');
+}
+
+/**
+ * 还原指向未运行代理的工具配置
+ * @returns 还原说明（无备份可还原时返回 null）
+ */
+export async function restoreStaleProxyConfig(toolId: string): Promise<string | null> {
+  return await invoke<string | null>('restore_stale_proxy_config', { toolId });
+}
+
+/**
+ * 设置是否自动还原指向未运行代理的工具配置
+ */
+export async function setAutoRestoreStaleConfig(enabled: boolean): Promise<void> {
+  await invoke('set_auto_restore_stale_config', { enabled });
+}
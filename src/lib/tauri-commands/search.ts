@@ -0,0 +1,20 @@
+// 全局搜索命令模块
+// 为命令面板提供 Profile、会话、设置项、请求日志的统一搜索
+
+import { invoke } from '@tauri-apps/api/core';
+import type { SearchQuery, SearchResult } from '@/types/search';
+
+/**
+ * 全局搜索
+ * @returns 按相关度降序排列的结果
+ */
+export async function globalSearch(query: SearchQuery): Promise<SearchResult[]> {
+  return await invoke<SearchResult[]>('global_search', { query });
+}
+
+/**
+ * 使搜索索引失效（数据变更后调用，下次搜索时重建）
+ */
+export async function invalidateSearchIndex(): Promise<void> {
+  await invoke('invalidate_search_index');
+}
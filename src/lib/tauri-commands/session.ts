@@ -2,7 +2,7 @@
 // 负责透明代理会话的 CRUD 和配置管理
 
 import { invoke } from '@tauri-apps/api/core';
-import type { SessionListResponse } from './types';
+import type { SessionContextUsage, SessionListResponse } from './types';
 
 /**
  * 获取会话列表
@@ -76,3 +76,56 @@ export async function updateSessionNote(sessionId: string, note: string | null):
     note,
   });
 }
+
+/**
+ * 设置会话标签（同时回填该会话已有日志的标签）
+ * @param sessionId - 会话 ID
+ * @param tags - 标签列表（空数组表示清空，且不再自动打项目标签）
+ * @returns 规范化后的标签
+ */
+export async function tagSession(sessionId: string, tags: string[]): Promise<string[]> {
+  return await invoke<string[]>('tag_session', {
+    sessionId,
+    tags,
+  });
+}
+
+/**
+ * 开启或关闭会话对话记录（关闭后保留已有记录）
+ * @param sessionId - 完整的会话 ID
+ * @param enabled - 是否记录
+ */
+export async function setSessionRecording(sessionId: string, enabled: boolean): Promise<void> {
+  return await invoke<void>('set_session_recording', { sessionId, enabled });
+}
+
+/**
+ * 获取已开启对话记录的会话 ID
+ */
+export async function getRecordingSessions(): Promise<string[]> {
+  return await invoke<string[]>('get_recording_sessions');
+}
+
+/**
+ * 导出会话对话记录
+ * @param sessionId - 完整的会话 ID
+ * @param format - 导出格式
+ * @returns Markdown 或 JSON 文本
+ */
+export async function exportSessionTranscript(
+  sessionId: string,
+  format: 'markdown' | 'json',
+): Promise<string> {
+  return await invoke<string>('export_session_transcript', { sessionId, format });
+}
+
+/**
+ * 获取会话的上下文窗口占用
+ * @param sessionId - 完整的会话 ID
+ * @returns 会话尚无成功请求时返回 null
+ */
+export async function getSessionContextUsage(
+  sessionId: string,
+): Promise<SessionContextUsage | null> {
+  return await invoke<SessionContextUsage | null>('get_session_context_usage', { sessionId });
+}
@@ -8,6 +8,12 @@ import type {
   TokenLogsPage,
   TokenStatsConfig,
   DatabaseSummary,
+  DbMaintenanceReport,
+  BudgetStatus,
+  LogExportFormat,
+  LogExportSummary,
+  RedactionStats,
+  RedactionStatsQuery,
 } from '@/types/token-stats';
 
 /**
@@ -34,6 +40,21 @@ export async function queryTokenLogs(queryParams: TokenStatsQuery): Promise<Toke
   });
 }
 
+/**
+ * 按筛选条件导出 Token 日志（后端流式写入，忽略分页参数）
+ * @param query - 查询参数（工具类型、会话ID、配置名称、时间范围）
+ * @param format - 导出格式（csv / jsonl）
+ * @param path - 导出文件路径
+ * @returns 导出结果（文件路径、条数、大小）
+ */
+export async function exportTokenLogs(
+  query: TokenStatsQuery,
+  format: LogExportFormat,
+  path: string,
+): Promise<LogExportSummary> {
+  return await invoke<LogExportSummary>('export_token_logs', { query, format, path });
+}
+
 /**
  * 手动清理旧日志
  * @param retentionDays - 保留天数（可选，未提供则使用配置）
@@ -70,6 +91,33 @@ export async function forceTokenStatsCheckpoint(): Promise<void> {
   return await invoke<void>('force_token_stats_checkpoint');
 }
 
+/**
+ * 维护 Token 统计数据库
+ *
+ * 按配置的大小上限删除最旧日志，然后执行 VACUUM、重建索引与 ANALYZE
+ */
+export async function maintainTokenStatsDb(): Promise<DbMaintenanceReport> {
+  return await invoke<DbMaintenanceReport>('maintain_token_stats_db');
+}
+
+/**
+ * 获取消费预算状态（后端立即重新检查）
+ * @param toolId - 工具 ID（可选，未提供则返回所有工具）
+ */
+export async function getBudgetStatus(toolId?: string): Promise<BudgetStatus[]> {
+  return await invoke<BudgetStatus[]>('get_budget_status', {
+    toolId: toolId ?? null,
+  });
+}
+
+/**
+ * 获取请求内容脱敏命中统计
+ * @param query - 工具与时间范围过滤（均可选）
+ */
+export async function getRedactionStats(query: RedactionStatsQuery = {}): Promise<RedactionStats> {
+  return await invoke<RedactionStats>('get_redaction_stats', { query });
+}
+
 /**
  * 获取 Token 统计配置
  * @returns Token 统计配置（保留天数、最大条数、自动清理开关）
@@ -5,11 +5,18 @@ import { invoke } from '@tauri-apps/api/core';
 import type {
   ToolStatus,
   InstallResult,
+  InstallPrecheck,
+  InstallAttempt,
   UpdateResult,
+  ToolStatusSnapshot,
+  ToolVersionHistory,
+  CustomToolsReport,
   NodeEnvironment,
+  UserPrefixPathStatus,
   ToolCandidate,
   InstallerCandidate,
   SSHConfig,
+  SshTunnelInfo,
 } from './types';
 import type { ToolInstance } from '@/types/tool-management';
 
@@ -37,17 +44,88 @@ export async function checkNodeEnvironment(): Promise<NodeEnvironment> {
 }
 
 /**
- * 安装工具
+ * 检查用户级安装目录（全局安装无权限时的回退位置）是否在 PATH 中
+ */
+export async function checkUserInstallPath(): Promise<UserPrefixPathStatus> {
+  return await invoke<UserPrefixPathStatus>('check_user_install_path');
+}
+
+/**
+ * 将用户级安装目录加入用户 PATH（仅 Windows 自动修改，其他平台返回提示）
+ */
+export async function fixUserInstallPath(): Promise<UserPrefixPathStatus> {
+  return await invoke<UserPrefixPathStatus>('fix_user_install_path');
+}
+
+/**
+ * 安装工具（进度通过 `install-progress` 事件推送）
  * @param tool - 工具 ID
  * @param method - 安装方法（npm/brew/official）
  * @param force - 是否强制安装
+ * @param installId - 安装任务 ID（用于取消，未传入时由后端生成）
  */
 export async function installTool(
   tool: string,
   method: string,
   force?: boolean,
+  installId?: string,
 ): Promise<InstallResult> {
-  return await invoke<InstallResult>('install_tool', { tool, method, force });
+  return await invoke<InstallResult>('install_tool', { tool, method, force, installId });
+}
+
+/**
+ * 取消进行中的安装
+ * @param installId - 安装任务 ID
+ * @returns 是否找到该安装任务
+ */
+export async function cancelToolInstall(installId: string): Promise<boolean> {
+  return await invoke<boolean>('cancel_tool_install', { installId });
+}
+
+/**
+ * 安装前预检：查询当前平台安装包在镜像站的同步状态
+ * @param tool - 工具 ID
+ * @param method - 安装方法（npm/brew/official）
+ */
+export async function precheckToolInstall(tool: string, method: string): Promise<InstallPrecheck> {
+  return await invoke<InstallPrecheck>('precheck_tool_install', { tool, method });
+}
+
+/**
+ * 列出安装/更新历史（按时间倒序）
+ * @param toolId - 仅列出指定工具（可选）
+ * @param limit - 最大返回数量（默认 50）
+ */
+export async function listInstallHistory(
+  toolId?: string,
+  limit?: number,
+): Promise<InstallAttempt[]> {
+  return await invoke<InstallAttempt[]>('list_install_history', {
+    toolId: toolId ?? null,
+    limit: limit ?? null,
+  });
+}
+
+/**
+ * 获取单条安装/更新记录（包含完整执行过程）
+ */
+export async function getInstallAttempt(id: string): Promise<InstallAttempt> {
+  return await invoke<InstallAttempt>('get_install_attempt', { id });
+}
+
+/**
+ * 原样重新执行历史记录中的操作，返回新生成的记录
+ */
+export async function rerunInstallAttempt(id: string): Promise<InstallAttempt> {
+  return await invoke<InstallAttempt>('rerun_install_attempt', { id });
+}
+
+/**
+ * 清空安装/更新历史
+ * @returns 删除的记录数
+ */
+export async function clearInstallHistory(): Promise<number> {
+  return await invoke<number>('clear_install_history');
 }
 
 /**
@@ -82,6 +160,57 @@ export async function refreshAllToolVersions(): Promise<ToolStatus[]> {
   return await invoke<ToolStatus[]>('refresh_all_tool_versions');
 }
 
+/**
+ * 一次性获取所有工具的安装状态与版本检查结果（版本结果带缓存）
+ * @param force - 是否忽略缓存重新检查
+ * @returns 工具状态快照
+ */
+export async function refreshAllToolStatus(force?: boolean): Promise<ToolStatusSnapshot> {
+  return await invoke<ToolStatusSnapshot>('refresh_all_tool_status', { force });
+}
+
+/**
+ * 通过 npm 安装工具的指定版本
+ * @param tool - 工具 ID
+ * @param version - 目标版本
+ * @param pin - 是否同时固定该版本
+ * @returns 安装后检测到的版本
+ */
+export async function installSpecificVersion(
+  tool: string,
+  version: string,
+  pin?: boolean,
+): Promise<string> {
+  return await invoke<string>('install_specific_version', { tool, version, pin });
+}
+
+/**
+ * 回滚工具到指定版本（安装并固定该版本）
+ * @param tool - 工具 ID
+ * @param version - 目标版本
+ * @returns 回滚后检测到的版本
+ */
+export async function rollbackTool(tool: string, version: string): Promise<string> {
+  return await invoke<string>('rollback_tool', { tool, version });
+}
+
+/**
+ * 获取工具的已安装版本历史与固定版本
+ * @param tool - 工具 ID
+ */
+export async function getToolVersionHistory(tool: string): Promise<ToolVersionHistory> {
+  return await invoke<ToolVersionHistory>('get_tool_version_history', { tool });
+}
+
+/**
+ * 固定工具版本
+ * @param tool - 工具 ID
+ * @param version - 固定的版本，传 null 取消固定
+ */
+export async function setToolVersionPin(tool: string, version: string | null): Promise<void> {
+  return await invoke<void>('set_tool_version_pin', { tool, version });
+}
+
 /**
  * 更新工具实例（使用配置的安装器路径）
  * @param instanceId - 工具实例ID
@@ -132,6 +261,32 @@ export async function addWslToolInstance(
   return await invoke<ToolInstance>('add_wsl_tool_instance', { baseId, distroName });
 }
 
+/**
+ * 在WSL发行版中通过 npm 安装工具，并登记为WSL实例
+ * @param baseId - 工具ID
+ * @param distroName - WSL发行版名称
+ * @returns 安装后的实例
+ */
+export async function installWslToolInstance(
+  baseId: string,
+  distroName: string,
+): Promise<ToolInstance> {
+  return await invoke<ToolInstance>('install_wsl_tool_instance', { baseId, distroName });
+}
+
+/**
+ * 将 Profile 应用到WSL实例的配置文件
+ * @param instanceId - WSL实例ID
+ * @param profileName - Profile 名称
+ * @returns 写入的配置目录（\\wsl$\... 路径）
+ */
+export async function applyProfileToWslInstance(
+  instanceId: string,
+  profileName: string,
+): Promise<string> {
+  return await invoke<string>('apply_profile_to_wsl_instance', { instanceId, profileName });
+}
+
 /**
  * 添加SSH工具实例
  * @param baseId - 工具ID
@@ -148,6 +303,67 @@ export async function addSshToolInstance(
   });
 }
 
+/**
+ * 重新检测SSH实例（安装状态、路径、版本）
+ * @param instanceId - SSH实例ID
+ * @returns 更新后的实例
+ */
+export async function refreshSshToolInstance(instanceId: string): Promise<ToolInstance> {
+  return await invoke<ToolInstance>('refresh_ssh_tool_instance', { instanceId });
+}
+
+/**
+ * 在远程主机上通过 npm 安装工具
+ * @param instanceId - SSH实例ID
+ * @returns 安装后的实例
+ */
+export async function installSshToolInstance(instanceId: string): Promise<ToolInstance> {
+  return await invoke<ToolInstance>('install_ssh_tool_instance', { instanceId });
+}
+
+/**
+ * 将 Profile 应用到SSH实例的远程配置文件
+ * @param instanceId - SSH实例ID
+ * @param profileName - Profile 名称
+ * @returns 远程配置目录
+ */
+export async function applyProfileToSshInstance(
+  instanceId: string,
+  profileName: string,
+): Promise<string> {
+  return await invoke<string>('apply_profile_to_ssh_instance', { instanceId, profileName });
+}
+
+/**
+ * 建立SSH反向隧道，让远程工具经本机透明代理转发
+ * @param instanceId - SSH实例ID
+ * @param remotePort - 远程监听端口（默认与本机代理端口相同）
+ */
+export async function startSshProxyTunnel(
+  instanceId: string,
+  remotePort?: number,
+): Promise<SshTunnelInfo> {
+  return await invoke<SshTunnelInfo>('start_ssh_proxy_tunnel', {
+    instanceId,
+    remotePort: remotePort ?? null,
+  });
+}
+
+/**
+ * 关闭SSH反向隧道
+ * @returns 隧道不存在时返回 false
+ */
+export async function stopSshProxyTunnel(instanceId: string): Promise<boolean> {
+  return await invoke<boolean>('stop_ssh_proxy_tunnel', { instanceId });
+}
+
+/**
+ * 列出运行中的SSH反向隧道
+ */
+export async function listSshProxyTunnels(): Promise<SshTunnelInfo[]> {
+  return await invoke<SshTunnelInfo[]>('list_ssh_proxy_tunnels');
+}
+
 /**
  * 删除工具实例（仅SSH类型）
  * @param instanceId - 实例ID
@@ -227,3 +443,17 @@ export async function detectSingleTool(
 ): Promise<ToolStatus> {
   return await invoke<ToolStatus>('detect_single_tool', { toolId, forceRedetect });
 }
+
+/**
+ * 获取已加载的自定义工具（含加载失败的定义文件）
+ */
+export async function listCustomTools(): Promise<CustomToolsReport> {
+  return await invoke<CustomToolsReport>('list_custom_tools');
+}
+
+/**
+ * 重新读取 ~/.duckcoding/tools.d 下的自定义工具定义
+ */
+export async function reloadCustomToolDefinitions(): Promise<CustomToolsReport> {
+  return await invoke<CustomToolsReport>('reload_custom_tool_definitions');
+}
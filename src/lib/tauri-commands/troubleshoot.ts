@@ -0,0 +1,26 @@
+// 引导式故障排查命令模块
+// 按步骤执行真实检查，返回标出失败步骤和修复建议的报告
+
+import { invoke } from '@tauri-apps/api/core';
+import type {
+  TroubleshootFlow,
+  TroubleshootFlowInfo,
+  TroubleshootReport,
+} from '@/types/troubleshoot';
+
+/**
+ * 列出可用的诊断流程
+ */
+export async function listTroubleshootFlows(): Promise<TroubleshootFlowInfo[]> {
+  return await invoke<TroubleshootFlowInfo[]>('list_troubleshoot_flows');
+}
+
+/**
+ * 对指定工具执行诊断流程
+ */
+export async function runTroubleshootFlow(
+  flow: TroubleshootFlow,
+  toolId: string,
+): Promise<TroubleshootReport> {
+  return await invoke<TroubleshootReport>('run_troubleshoot_flow', { flow, toolId });
+}
@@ -2,23 +2,54 @@
 // 集中管理所有 Tauri 命令相关的类型定义，避免循环依赖
 
 import type { SSHConfig } from '@/types/tool-management';
-import type { ProfileData, ProfileDescriptor, ProfilePayload, ToolId } from '@/types/profile';
+import type { AdminApiConfig, McpConfig, MetricsConfig } from '@/types/config-watch';
+import type { CrashReportConfig } from '@/types/crash';
+import type { PricingSyncConfig } from '@/types/pricing';
+import type {
+  ConfigDriftReport,
+  NativeConfigSnippet,
+  ProfileData,
+  ProfileDescriptor,
+  ProfileHealth,
+  ProfileHealthMatrix,
+  ProfilePayload,
+  ToolId,
+} from '@/types/profile';
 import type {
   Provider,
   ProviderStore,
   _ProviderFormData,
   ProviderValidationResult,
   ApiInfo,
+  ProviderHealth,
+  ProviderHealthChangedEvent,
 } from '@/types/provider';
 
 // 重新导出 Profile 相关类型供其他模块使用
-export type { ProfileData, ProfileDescriptor, ProfilePayload, ToolId };
+export type {
+  ConfigDriftReport,
+  NativeConfigSnippet,
+  ProfileData,
+  ProfileDescriptor,
+  ProfileHealth,
+  ProfileHealthMatrix,
+  ProfilePayload,
+  ToolId,
+};
 
 // 重新导出工具管理类型
 export type { SSHConfig };
 
 // 重新导出供应商管理类型
-export type { Provider, ProviderStore, _ProviderFormData, ProviderValidationResult, ApiInfo };
+export type {
+  Provider,
+  ProviderStore,
+  _ProviderFormData,
+  ProviderValidationResult,
+  ApiInfo,
+  ProviderHealth,
+  ProviderHealthChangedEvent,
+};
 
 export interface ToolStatus {
   mirrorIsStale: boolean;
@@ -31,12 +62,127 @@ export interface ToolStatus {
   version: string | null;
 }
 
+/**
+ * 用户级安装目录（非管理员回退安装位置）的 PATH 状态
+ */
+export interface UserPrefixPathStatus {
+  prefix: string | null;
+  bin_dir: string | null;
+  installed_packages: string[]; // 用户级目录中已安装的 npm 包
+  in_user_path: boolean;
+  fix_hint: string | null; // 需要手动处理时的提示
+}
+
 export interface InstallResult {
   success: boolean;
   message: string;
   output: string;
 }
 
+/**
+ * 镜像产物同步状态（按安装方式 + 系统 + 架构区分）
+ */
+export interface MirrorArtifactState {
+  kind: 'npm' | 'brew' | 'official' | string;
+  os: string | null; // null 表示与平台无关（如 npm tarball）
+  arch: string | null;
+  version: string | null;
+  is_stale: boolean;
+  synced_at: string | null;
+}
+
+/**
+ * 单条命令执行记录
+ */
+export interface CommandTranscriptStep {
+  command: string;
+  success: boolean;
+  exit_code: number | null;
+  stdout: string;
+  stderr: string;
+  duration_ms: number;
+}
+
+/**
+ * 安装/更新操作（包含原样重新执行所需的参数）
+ */
+export type InstallOperation =
+  | {
+      kind: 'install';
+      tool_id: string;
+      method: 'Npm' | 'Brew' | 'Official' | 'Other';
+      force: boolean;
+    }
+  | { kind: 'update_instance'; tool_id: string; instance_id: string; force: boolean }
+  | { kind: 'install_version'; tool_id: string; version: string };
+
+/**
+ * 已安装版本记录
+ */
+export interface InstalledVersionRecord {
+  version: string;
+  installed_at: number; // 秒级时间戳
+  source: 'install' | 'update' | 'specific' | 'rollback';
+}
+
+/**
+ * 工具版本历史（含固定版本）
+ */
+export interface ToolVersionHistory {
+  tool_id: string;
+  pinned_version: string | null;
+  history: InstalledVersionRecord[];
+}
+
+export type InstallPhase =
+  | 'preparing'
+  | 'downloading'
+  | 'installing'
+  | 'verifying'
+  | 'completed'
+  | 'failed'
+  | 'cancelled';
+
+/**
+ * 安装进度事件（`install-progress`）
+ */
+export interface InstallProgressEvent {
+  install_id: string;
+  tool_id: string;
+  phase: InstallPhase;
+  percent: number | null; // 输出中无法解析时为 null
+  line: string | null; // 子进程输出的一行日志，阶段变化事件为 null
+  stream: 'stdout' | 'stderr' | null;
+}
+
+/**
+ * 安装/更新历史记录
+ */
+export interface InstallAttempt {
+  id: string;
+  operation: InstallOperation;
+  started_at: number; // 毫秒时间戳
+  duration_ms: number;
+  success: boolean;
+  error: string | null;
+  steps: CommandTranscriptStep[];
+  os: string;
+  arch: string;
+}
+
+/**
+ * 安装预检结果
+ */
+export interface InstallPrecheck {
+  tool_id: string;
+  method: string;
+  os: string;
+  arch: string;
+  artifact: MirrorArtifactState | null; // 镜像站未提供分平台状态时为 null
+  mirror_is_stale: boolean;
+  warning: string | null;
+}
+
 export interface UpdateResult {
   success: boolean;
   message: string;
@@ -48,17 +194,41 @@ export interface UpdateResult {
   tool_id?: string;
 }
 
+export interface ToolStatusSnapshot {
+  tools: ToolStatus[];
+  updates: UpdateResult[];
+  from_cache: boolean; // 本次结果是否全部来自缓存
+  checked_at: number; // Unix 秒
+}
+
 export interface ActiveConfig {
   api_key: string;
   base_url: string;
   profile_name?: string;
 }
 
+// 配置兼容问题：unknown 为当前版本不认识的字段，incompatible 为无法解析的取值（已回退默认值）
+export interface CompatIssue {
+  path: string; // 字段路径，如 proxy_configs.codex.new_option
+  kind: 'unknown' | 'incompatible';
+  value_preview: string;
+}
+
+// 单个配置文件的兼容性报告
+export interface CompatFileReport {
+  name: string;
+  path: string;
+  exists: boolean;
+  file_version: string | null;
+  issues: CompatIssue[];
+  error: string | null; // 容错解析仍失败时的错误
+}
+
 export interface GlobalConfig {
   user_id?: string; // 已废弃，由供应商系统管理
   system_token?: string; // 已废弃，由供应商系统管理
   proxy_enabled?: boolean;
-  proxy_type?: 'http' | 'https' | 'socks5';
+  proxy_type?: ProxyType;
   proxy_host?: string;
   proxy_port?: string;
   proxy_username?: string;
@@ -79,8 +249,32 @@ export interface GlobalConfig {
   external_poll_interval_ms?: number;
   // 单实例模式开关（默认 true，仅生产环境生效）
   single_instance_enabled?: boolean;
+  startup_enabled?: boolean;
+  // 开机自启动时的启动方式
+  startup_mode?: StartupMode;
+  // 工具配置目录覆盖（tool_id -> 目录）
+  tool_config_dirs?: Record<string, string>;
+  // 只读观察模式（不写入任何工具配置文件）
+  observer_mode?: boolean;
+  // Prometheus 指标导出（独立端口提供 /metrics）
+  metrics?: MetricsConfig;
+  // 价格目录远程同步
+  pricing_sync?: PricingSyncConfig;
+  // 本机管理 API（供外部工具集成）
+  admin_api?: AdminApiConfig;
+  // MCP 服务（AI 编程工具查询用量、切换 Profile）
+  mcp?: McpConfig;
+  // 余额聚合监控（定时查询额度并提醒低余额）
+  balance_monitor?: BalanceMonitorConfig;
+  // 崩溃报告上传（默认关闭，需用户逐个确认发送）
+  crash_report?: CrashReportConfig;
+  // 额外加入命令搜索路径的目录（环境诊断修复时追加）
+  extra_paths?: string[];
 }
 
+// 开机自启动时的启动方式：显示窗口 / 隐藏到托盘 / 后台运行（不创建窗口）
+export type StartupMode = 'window' | 'minimized' | 'background';
+
 export type LogLevel = 'trace' | 'debug' | 'info' | 'warn' | 'error';
 export type LogFormat = 'json' | 'text';
 export type LogOutput = 'console' | 'file' | 'both';
@@ -90,6 +284,25 @@ export interface LogConfig {
   format: LogFormat;
   output: LogOutput;
   file_path: string | null;
+  rotation?: LogRotationConfig;
+}
+
+// 日志文件滚动与保留策略
+export interface LogRotationConfig {
+  max_file_size_mb: number;
+  max_files: number;
+  // 0 表示不按天数清理
+  max_age_days: number;
+  compress: boolean;
+}
+
+export interface LogFileInfo {
+  name: string;
+  path: string;
+  size_bytes: number;
+  modified_at: number;
+  compressed: boolean;
+  active: boolean;
 }
 
 export interface GenerateApiKeyResult {
@@ -202,6 +415,18 @@ export interface TestProxyResult {
   error?: string | null;
 }
 
+// 代理类型（ntlm/negotiate 经本地认证桥 px/cntlm）
+export type ProxyType = 'http' | 'https' | 'socks5' | 'ntlm' | 'negotiate';
+
+// 本地认证桥检测结果
+export interface AuthBridgeStatus {
+  listening: boolean;
+  authenticated: boolean;
+  status?: number | null;
+  upstream_schemes: Array<'basic' | 'digest' | 'ntlm' | 'negotiate'>;
+  hint?: string | null;
+}
+
 export interface ProxyTestConfig {
   enabled: boolean;
   proxy_type: string;
@@ -212,6 +437,22 @@ export interface ProxyTestConfig {
 }
 
 // 单个工具的代理配置
+// 非标准 SSE 兼容选项（改写转发给客户端的流）
+export interface SseCompatConfig {
+  strip_bom?: boolean; // 去除流首 UTF-8 BOM
+  drop_comments?: boolean; // 丢弃注释 / keep-alive 行
+  fix_data_prefix?: boolean; // 为 data: 补齐空格
+  normalize_line_endings?: boolean; // 统一换行符为 \n
+  fill_event_names?: boolean; // 补齐缺失的 event: 字段
+}
+
+// 请求/响应体捕获配置（过期删除不可关闭）
+export interface BodyCaptureConfig {
+  enabled: boolean;
+  ttl_minutes: number; // 保留时长（分钟，1 ~ 10080）
+  max_body_kb?: number; // 单个请求/响应体的保存上限（KB，默认 1024）
+}
+
 export interface ToolProxyConfig {
   enabled: boolean;
   port: number;
@@ -224,11 +465,404 @@ export interface ToolProxyConfig {
   session_endpoint_config_enabled: boolean; // 工具级：是否允许会话自定义端点
   auto_start: boolean; // 应用启动时自动运行代理（默认关闭）
   tavily_api_key?: string | null; // Tavily API Key（用于本地搜索，可选）
+  sse_tap_filter?: boolean; // SSE 统计旁路仅保留统计相关事件（默认开启）
+  sse_compat?: SseCompatConfig; // 非标准 SSE 兼容选项（默认全部关闭）
+  captured_response_headers?: string[]; // 随日志记录的上游响应头（如 x-request-id）
+  sse_compat_profiles?: Record<string, SseCompatConfig>; // 按 Profile 单独配置的 SSE 兼容选项
+  body_capture?: BodyCaptureConfig; // 请求/响应体捕获（默认关闭）
+  cost_annotation?: boolean; // 在响应头 / 流末尾 SSE 注释中附加单次调用成本（默认关闭）
+  upstreams?: UpstreamTarget[]; // 备用上游（主上游返回 5xx/429 或请求失败时依次重试）
+  key_pool?: PooledApiKey[]; // 主上游的 API Key 池（配置后代替 real_api_key 轮换使用）
+  key_balance_mode?: KeyBalanceMode; // Key 池负载均衡策略（默认轮询）
+  rate_limit?: RateLimitConfig; // 本地限流（默认关闭）
+  upstream_queue?: UpstreamQueueConfig; // 上游限流排队（默认关闭）
+  concurrency_limit?: ConcurrencyLimitConfig; // 并发上游请求上限（默认关闭）
+  upstream_timeout?: UpstreamTimeoutConfig; // 上游超时（连接 / 总时长 / 流式响应空闲）
+  response_cache?: ResponseCacheConfig; // 幂等接口响应缓存（默认关闭）
+  budget?: BudgetConfig; // 消费预算（默认关闭）
+  cost_guard?: CostGuardConfig; // 单次请求输入成本预检（默认关闭）
+  routing_rules?: ModelRoutingRule[]; // 模型路由规则（按顺序匹配，第一条命中的生效）
+  redaction?: RedactionConfig; // 请求内容脱敏（默认关闭）
+  profile_failover?: ProfileFailoverConfig; // Profile 自动切换（默认关闭）
+  upstream_protocol?: ApiProtocol | null; // 主上游 API 协议（未设置时与工具协议相同）
+  egress_proxy?: EgressProxyConfig; // 出口代理（默认跟随全局代理）
+  listen_socket?: string | null; // 本地套接字监听（Unix 域套接字路径 / Windows 命名管道名），设置后代替端口
+  client_keys?: ClientApiKey[]; // 按客户端分发的本地 API Key（与 local_api_key 同时有效）
+}
+
+// 工具级出口代理：global 跟随全局代理，direct 直连，custom 使用下方配置
+export type EgressMode = 'global' | 'direct' | 'custom';
+
+export type EgressProxyType = 'http' | 'https' | 'socks5';
+
+export interface EgressProxyConfig {
+  mode: EgressMode;
+  proxy_type: EgressProxyType;
+  host: string;
+  port: number;
+  username?: string | null;
+  password?: string | null;
+}
+
+// Profile 自动切换：当前 Profile 的主上游连续出错达到阈值后切换到备用 Profile（不会自动切回）
+export interface ProfileFailoverConfig {
+  enabled: boolean;
+  backup_profile?: string | null; // 备用 Profile 名称
+  error_threshold: number; // 连续错误次数（请求失败或 5xx/429，默认 3）
+}
+
+// 请求内容脱敏：转发前扫描请求体，命中时替换为 [REDACTED:规则名] 或拦截请求
+export interface RedactionConfig {
+  enabled: boolean;
+  action: RedactionAction;
+  builtin_rules: boolean; // 内置规则（常见 API Key、私钥、邮箱）
+  rules?: RedactionRule[]; // 自定义规则（如内部主机名）
+}
+
+// 命中脱敏规则时：替换后转发 / 拦截请求
+export type RedactionAction = 'mask' | 'block';
+
+// 自定义脱敏规则
+export interface RedactionRule {
+  name: string; // 规则名（记录到命中统计）
+  pattern: string; // 正则表达式
+  enabled: boolean;
+}
+
+// 模型路由规则：请求模型名匹配 glob 时改用指定 Profile 的上游
+export interface ModelRoutingRule {
+  id: string;
+  model_pattern: string; // 模型名 glob（* / ?，不区分大小写），如 claude-haiku-*
+  profile_name: string; // 目标 Profile
+  base_url: string; // 保存时从 Profile 解析
+  api_key: string; // 保存时从 Profile 解析
+  pricing_template_id?: string | null; // Profile 的价格模板
+  enabled: boolean;
+}
+
+// 保存模型路由规则的输入（id 为空时新建）
+export interface RoutingRuleInput {
+  id?: string | null;
+  model_pattern: string;
+  profile_name: string;
+  enabled?: boolean;
+}
+
+// 消费预算配置（USD，按本地时间自然日 / 周 / 月统计，未设置的周期不限制）
+export interface BudgetConfig {
+  enabled: boolean;
+  daily_limit_usd: number | null;
+  weekly_limit_usd: number | null; // 周一起算
+  monthly_limit_usd: number | null;
+  mode: BudgetMode;
+  warn_percent: number; // 达到上限的该百分比时提醒（默认 80）
+}
+
+// 超出预算时的处理：仅提醒 / 拦截新请求
+export type BudgetMode = 'warn' | 'block';
+
+// 单次请求输入成本预检：转发前估算输入 Token 与成本，超出上限时按 mode 处理
+export interface CostGuardConfig {
+  enabled: boolean;
+  max_input_cost_usd: number; // 单次请求预估输入成本上限（USD，默认 1）
+  mode: CostGuardMode;
+  confirm_timeout_secs: number; // 确认模式下等待确认的时长（秒，默认 120），超时视为拒绝
+}
+
+// 超出上限时：仅标记（响应头 x-duckcoding-cost-guard）/ 等待确认 / 拒绝
+export type CostGuardMode = 'tag' | 'confirm' | 'reject';
+
+// 请求的输入成本估算
+export interface CostEstimate {
+  tool_id: string;
+  model: string;
+  input_tokens: number; // 估算的输入 Token 数
+  input_cost_usd: number;
+  threshold_usd: number;
+}
+
+// 等待确认的高成本请求（cost-guard-confirmation 事件载荷）
+export interface PendingCostConfirmation {
+  id: string;
+  estimate: CostEstimate;
+  created_at: number;
+  expires_at: number; // 超时后请求被拒绝
+}
+
+// 本地限流配置（上限为 0 表示不限制该项）
+export interface RateLimitConfig {
+  enabled: boolean;
+  requests_per_minute: number; // 每分钟请求数上限
+  tokens_per_minute: number; // 每分钟 Token 数上限（输入 + 输出）
+  per_session: boolean; // 按会话单独计算额度（默认按工具整体计算）
+}
+
+// 上游限流排队：上游返回 429 且带 Retry-After 时暂存请求，等待后重新转发
+export interface UpstreamQueueConfig {
+  enabled: boolean;
+  max_requests: number; // 同时排队的请求数上限
+  max_wait_secs: number; // 单个请求累计排队时长上限（秒）
+}
+
+// 并发请求数达到上限时新请求的处理方式：排队等待 / 直接返回 503
+export type ConcurrencyOverflowMode = 'queue' | 'reject';
+
+// 并发上游请求上限：限制同时转发到上游的请求数（含流式响应的整个传输过程）
+export interface ConcurrencyLimitConfig {
+  enabled: boolean;
+  max_concurrent: number; // 同时转发到上游的请求数上限
+  overflow: ConcurrencyOverflowMode;
+  max_wait_secs: number; // 排队模式下单个请求等待名额的时长上限（秒）
+}
+
+// 上游超时（各项为 0 表示不限制），超时后返回 504 并记录为 timeout 状态
+export interface UpstreamTimeoutConfig {
+  connect_timeout_secs: number; // 建立连接超时（秒）
+  total_timeout_secs: number; // 请求总超时（秒）：非流式为完整响应时间，流式为收到响应头之前的时间
+  idle_stream_timeout_secs: number; // 流式响应相邻两次收到数据的最长间隔（秒）
+}
+
+// 响应缓存：缓存模型列表等幂等接口的成功响应，命中时不转发到上游
+export interface ResponseCacheConfig {
+  enabled: boolean;
+  ttl_secs: number; // 缓存有效期（秒）
+  max_entries: number; // 缓存条目数上限（超出时淘汰最早写入的条目）
+  cache_embeddings: boolean; // 同时缓存请求体完全相同的 Embedding 请求
+}
+
+// 单个工具的响应缓存统计
+export interface ResponseCacheStats {
+  tool_id: string;
+  entries: number;
+  total_bytes: number;
+  hits: number; // 启动以来的命中次数
+  misses: number; // 启动以来的未命中次数
+}
+
+// Key 池负载均衡策略：轮询 / 按权重平滑轮询
+export type KeyBalanceMode = 'round_robin' | 'weighted';
+
+// Key 池中的单个 API Key
+export interface PooledApiKey {
+  alias: string; // 别名（记录到 Token 日志，用于按 Key 统计用量）
+  api_key: string;
+  weight: number; // 权重（0 表示停用）
+}
+
+// 按客户端分发的本地 API Key（请求按标签分别统计用量）
+export interface ClientApiKey {
+  id: string;
+  label: string; // 标签（记录到 Token 日志的 client_key_label）
+  api_key: string;
+  created_at: number; // 创建时间（毫秒）
+}
+
+// 备用上游
+export interface UpstreamTarget {
+  base_url: string;
+  api_key: string;
+  priority: number; // 越小越优先，同优先级按配置顺序
+  protocol?: ApiProtocol | null; // 上游 API 协议（未设置时与工具协议相同）
+}
+
+// 上游 API 协议（与客户端协议不同时由透明代理转换请求与响应）
+export type ApiProtocol = 'anthropic' | 'openai_chat' | 'openai_responses';
+
+// 单个工具的请求体捕获状态
+export interface ToolCaptureStatus {
+  tool_id: string;
+  enabled: boolean;
+  ttl_minutes: number;
+  count: number; // 当前未过期的捕获数量
+  total_bytes: number;
+  next_expires_at: number | null; // 最早过期时间（毫秒）
+  last_expires_at: number | null; // 最晚过期时间（毫秒）
+}
+
+// 请求体捕获存储状态
+export interface CaptureStatus {
+  total_count: number;
+  total_bytes: number;
+  tools: ToolCaptureStatus[];
+}
+
+// 请求捕获列表项（不含请求/响应体）
+export interface CaptureSummary {
+  id: string;
+  tool_id: string;
+  captured_at: number; // 毫秒
+  expires_at: number; // 毫秒
+  method: string;
+  path: string;
+  response_status: number; // 0 表示上游请求失败
+  is_sse: boolean;
+  size: number; // 请求体与响应体的总字节数
+}
+
+// 单条请求捕获
+export interface CapturedExchange {
+  id: string;
+  tool_id: string;
+  captured_at: number;
+  expires_at: number;
+  method: string;
+  path: string;
+  query: string | null;
+  request_headers: Record<string, string>; // 鉴权相关请求头已脱敏
+  request_body: string;
+  request_truncated: boolean; // 截断的请求不可重放
+  response_status: number;
+  response_body: string;
+  response_truncated: boolean;
+  is_sse: boolean;
+}
+
+// 请求重放结果
+export interface ReplayResult {
+  capture_id: string;
+  status: number;
+  duration_ms: number;
+  response_body: string; // 超过 1 MiB 时截断
+  response_truncated: boolean;
+}
+
+// 流量重放计划：把请求捕获按速率直接发往指定上游（不经过透明代理）
+export interface ReplayPlan {
+  tool_id: string; // 仅支持 claude-code / codex / gemini-cli
+  capture_ids?: string[]; // 为空时取该工具最近的捕获
+  capture_limit?: number; // 未指定捕获 ID 时取最近捕获的数量（默认 20）
+  base_url: string;
+  api_key: string;
+  rate_per_sec?: number; // 每秒发出的请求数（默认 1，上限 50）
+  repeat?: number; // 每条捕获重放的次数（默认 1）
+  max_concurrent?: number; // 同时进行的请求数上限（默认 4）
+  timeout_secs?: number; // 单个请求超时（默认 300 秒）
+}
+
+// 流量重放的延迟分布（毫秒）
+export interface ReplayLatency {
+  min_ms: number;
+  mean_ms: number;
+  p50_ms: number;
+  p90_ms: number;
+  p95_ms: number;
+  p99_ms: number;
+  max_ms: number;
+}
+
+// 同类错误的统计
+export interface ReplayErrorBucket {
+  kind: string; // http_<状态码> / timeout / connect / body / request
+  count: number;
+  sample: string; // 首次出现时的错误信息
+}
+
+// 流量重放报告
+export interface ReplayReport {
+  tool_id: string;
+  base_url: string;
+  started_at: number; // 毫秒
+  duration_ms: number;
+  total_requests: number;
+  succeeded: number;
+  failed: number;
+  error_rate: number; // 0 ~ 1
+  achieved_rate: number; // 实际请求速率（次/秒）
+  latency: ReplayLatency | null; // 发出到读完响应体
+  first_byte_latency: ReplayLatency | null; // 发出到收到响应头
+  status_counts: Record<string, number>; // 状态码 -> 次数
+  errors: ReplayErrorBucket[]; // 次数多的在前
+  skipped: { capture_id: string; reason: string }[];
+}
+
+// 供应商试用参数
+export interface TrialRequest {
+  tool_id: string; // 仅支持 claude-code / gemini-cli（需项目级配置）
+  project_path: string;
+  base_url: string;
+  api_key: string;
+  name?: string | null; // 默认取 Base URL 主机名
+  duration_minutes: number; // 1 ~ 1440
+  pricing_template_id?: string | null;
+}
+
+// 进行中的供应商试用
+export interface TrialSession {
+  id: string;
+  name: string;
+  tool_id: string;
+  project_path: string;
+  config_path: string; // 被改写的项目级配置文件
+  base_url: string;
+  port: number; // 临时代理端口
+  started_at: string;
+  expires_at: string;
+  original_content: string | null;
 }
 
 export interface TransparentProxyStatus {
   running: boolean;
   port: number;
+  failover: FailoverStatus | null; // 多上游故障转移状态（仅运行中返回）
+  queue: UpstreamQueueStatus | null; // 上游限流排队状态（仅运行中返回）
+  concurrency: ConcurrencyStatus | null; // 并发上游请求状态（仅运行中返回）
+}
+
+// 代理实例的上游限流排队状态
+export interface UpstreamQueueStatus {
+  depth: number; // 当前排队等待的请求数
+  total_queued: number; // 启动以来进入排队的请求总数
+}
+
+// 代理实例的并发上游请求状态
+export interface ConcurrencyStatus {
+  in_flight: number; // 当前正在转发的请求数
+  waiting: number; // 当前等待名额的请求数
+  limit: number | null; // 并发上限（未启用时为 null）
+  total_rejected: number; // 启动以来因并发已满被拒绝的请求总数
+}
+
+// 单个上游的故障转移状态
+export interface UpstreamStatus {
+  base_url: string;
+  priority: number;
+  primary: boolean; // 当前 Profile 的主上游
+  active: boolean; // 最近一次成功响应的上游
+  cooling_down: boolean;
+  consecutive_failures: number;
+  cooldown_until: number | null; // 冷却结束时间（毫秒）
+  last_error: string | null;
+}
+
+// 代理实例的故障转移状态
+export interface FailoverStatus {
+  active_base_url: string | null;
+  last_failover_at: number | null; // 最近一次切换活跃上游的时间（毫秒）
+  upstreams: UpstreamStatus[];
+  profile_consecutive_errors: number; // 当前 Profile 主上游的连续错误次数
+}
+
+// proxy-failover 事件载荷：已切换到备用 Profile
+export interface ProxyFailoverEvent {
+  tool_id: string;
+  from_profile: string | null;
+  to_profile: string;
+  consecutive_errors: number;
+  last_error: string | null;
+  switched_at: number; // 毫秒时间戳
+}
+
+// 未能自启动的工具
+export interface AutoStartIssue {
+  tool_id: string;
+  reason: string;
+}
+
+// proxy-auto-start-completed 事件载荷：应用启动时代理自启动结果
+export interface AutoStartReport {
+  started: string[];
+  failed: AutoStartIssue[]; // 重试后端口仍被占用等
+  skipped: AutoStartIssue[]; // 缺少保护密钥或上游配置
 }
 
 // 多工具代理状态映射
@@ -251,6 +885,16 @@ export interface SessionRecord {
   request_count: number;
   created_at: number;
   updated_at: number;
+  /** 从响应形态检测到的上游 API 风格 */
+  api_flavor?: string;
+  /** 观察到的非标准 SSE 写法（逗号分隔，如 "bom,comment-lines"） */
+  sse_quirks?: string;
+  /** 会话标签（用户设置或按客户端工作目录自动生成） */
+  tags: string[];
+  /** 因上游限流进入排队的次数 */
+  queued_count: number;
+  /** 累计排队等待时长（毫秒） */
+  queued_wait_ms: number;
 }
 
 // 会话列表响应
@@ -261,6 +905,21 @@ export interface SessionListResponse {
   page_size: number;
 }
 
+// 会话上下文占用级别：达到 80% 提醒 / 达到 95% 即将截断
+export type ContextUsageLevel = 'warning' | 'critical';
+
+// 会话的上下文窗口占用（session-context-warning 事件载荷）
+export interface SessionContextUsage {
+  session_id: string; // 完整会话 ID
+  tool_id: string;
+  model: string;
+  prompt_tokens: number; // 最近一次请求的提示词 Token 数（输入 + 缓存创建 + 缓存读取）
+  context_window: number | null; // 模型上下文窗口（未知模型为 null）
+  percent: number | null;
+  level: ContextUsageLevel | null;
+  updated_at: number;
+}
+
 // 工具候选结果
 export interface ToolCandidate {
   tool_path: string;
@@ -298,6 +957,49 @@ export interface BalanceConfigBackend {
   updated_at: number;
 }
 
+// 余额聚合监控配置
+export interface BalanceMonitorConfig {
+  enabled: boolean; // 是否定期查询（默认开启）
+  interval_minutes: number; // 查询间隔（分钟）
+  low_balance_threshold_usd: number; // 低余额提醒阈值（USD），0 表示不提醒
+  desktop_notification: boolean;
+}
+
+// 余额来源：NEW API 供应商 / Profile（OpenAI 兼容账单接口）
+export type BalanceSourceKind = 'provider' | 'profile';
+
+// 单次余额查询结果（USD）
+export interface BalanceSnapshot {
+  timestamp: number; // 毫秒时间戳
+  remaining: number | null;
+  used: number | null;
+  total: number | null;
+  error: string | null;
+}
+
+// 单个来源的余额与历史
+export interface BalanceSummary {
+  source_id: string; // provider:<id> 或 profile:<tool_id>/<name>
+  kind: BalanceSourceKind;
+  name: string;
+  tool_id: string | null;
+  base_url: string;
+  profile_names: string[]; // 共用该上游与 API Key 的 Profile
+  latest: BalanceSnapshot | null;
+  history: BalanceSnapshot[]; // 新的在前
+}
+
+// balance-low 事件载荷
+export interface LowBalanceEvent {
+  source_id: string;
+  kind: BalanceSourceKind;
+  name: string;
+  remaining: number;
+  threshold: number;
+  desktop_notification: boolean;
+  checked_at: number;
+}
+
 // 前端 BalanceConfig 格式（camelCase）- 从 BalancePage 导入
 export type { BalanceConfig } from '@/pages/BalancePage/types';
 
@@ -308,3 +1010,100 @@ export interface AmpUserInfo {
   name: string | null;
   username: string | null;
 }
+
+/**
+ * 用户自定义工具定义（~/.duckcoding/tools.d/*.json）
+ */
+export interface CustomToolDefinition {
+  id: string;
+  name: string;
+  npm_package: string | null;
+  check_command: string;
+  config_dir: string;
+  config_dir_env: string | null;
+  config_files: string[];
+  env_vars: { api_key: string; base_url: string };
+  use_proxy_for_version_check: boolean;
+  proxy_protocol: 'anthropic' | 'openai' | 'gemini' | null;
+  proxy_port: number | null;
+}
+
+export interface CustomToolsReport {
+  dir: string;
+  tools: CustomToolDefinition[];
+  errors: { path: string; error: string }[];
+}
+
+export interface SshTunnelInfo {
+  instance_id: string;
+  target: string;
+  local_port: number;
+  remote_port: number;
+  started_at: number;
+}
+
+// 仪表板汇总：正在运行的透明代理
+export interface DashboardProxy {
+  tool_id: string;
+  port: number;
+  in_flight: number | null; // 当前正在转发的请求数
+}
+
+// 仪表板汇总：工具当前激活的 Profile
+export interface DashboardActiveProfile {
+  tool_id: string;
+  profile: string | null; // 未激活时为 null
+  switched_at: number | null; // 切换时间（毫秒）
+}
+
+// 仪表板汇总：上游健康状况（不含历史探测记录）
+export interface DashboardProviderHealth {
+  tool_id: string;
+  base_url: string;
+  profile_names: string[];
+  reachable: boolean | null; // 尚未探测时为 null
+  latency_ms: number | null;
+  uptime_24h: number | null; // 最近 24 小时可用率（0-100）
+  checked_at: number | null; // 最近一次探测时间（毫秒）
+}
+
+// 仪表板汇总：最近一次外部配置变更
+export interface DashboardConfigEvent {
+  tool_id: string;
+  timestamp: number;
+  changed_fields: string[];
+  is_sensitive: boolean;
+  action: string | null; // allow/block/superseded/expired，待处理时为 null
+}
+
+// 仪表板汇总：单个可用更新（应用 ID 为 duckcoding，工具为工具 ID）
+export interface AvailableUpdate {
+  id: string;
+  current_version: string | null;
+  latest_version: string | null;
+  required: boolean; // 是否为强制更新（仅应用更新）
+}
+
+export interface DashboardUpdates {
+  app: AvailableUpdate | null;
+  tools: AvailableUpdate[];
+}
+
+// 仪表板汇总中获取失败的部分（section 与 DashboardSummary 字段名一致）
+export interface DashboardSectionError {
+  section: string;
+  message: string;
+}
+
+// 仪表板汇总（后端缓存 10 秒）
+export interface DashboardSummary {
+  generated_at: number;
+  today_requests: number; // 今日（本地时间 0 点起）请求数
+  today_cost: number; // 今日总成本（USD）
+  active_proxies: DashboardProxy[];
+  active_profiles: DashboardActiveProfile[];
+  provider_health: DashboardProviderHealth[];
+  last_config_event: DashboardConfigEvent | null;
+  updates: DashboardUpdates;
+  errors: DashboardSectionError[];
+}
@@ -3,18 +3,29 @@ import { Switch } from '@/components/ui/switch';
 import { Label } from '@/components/ui/label';
 import { Button } from '@/components/ui/button';
 import { Card, CardContent, CardDescription, CardHeader, CardTitle } from '@/components/ui/card';
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from '@/components/ui/select';
 import { RefreshCw, Power, MonitorPlay } from 'lucide-react';
 import { useToast } from '@/hooks/use-toast';
 import {
   getSingleInstanceConfig,
   updateSingleInstanceConfig,
+  getGlobalConfig,
   getStartupConfig,
+  setAutostart,
   updateStartupConfig,
+  type StartupMode,
 } from '@/lib/tauri-commands';
 
 export function BasicSettingsTab() {
   const [singleInstanceEnabled, setSingleInstanceEnabled] = useState(true);
   const [startupEnabled, setStartupEnabled] = useState(false);
+  const [startupMode, setStartupMode] = useState<StartupMode>('window');
   const [loading, setLoading] = useState(true);
   const [saving, setSaving] = useState(false);
   const { toast } = useToast();
@@ -24,12 +35,14 @@ export function BasicSettingsTab() {
     const loadConfig = async () => {
       setLoading(true);
       try {
-        const [singleInstance, startup] = await Promise.all([
+        const [singleInstance, startup, globalConfig] = await Promise.all([
           getSingleInstanceConfig(),
           getStartupConfig(),
+          getGlobalConfig(),
         ]);
         setSingleInstanceEnabled(singleInstance);
         setStartupEnabled(startup);
+        setStartupMode(globalConfig?.startup_mode ?? 'window');
       } catch (error) {
         console.error('加载配置失败:', error);
         toast({
@@ -102,6 +115,28 @@ export function BasicSettingsTab() {
     }
   };
 
+  // 保存自启动时的启动方式
+  const handleStartupModeChange = async (mode: StartupMode) => {
+    setSaving(true);
+    try {
+      await setAutostart(startupEnabled, mode);
+      setStartupMode(mode);
+      toast({
+        title: '设置已保存',
+        description: '下次开机自启动时生效',
+      });
+    } catch (error) {
+      console.error('保存启动方式失败:', error);
+      toast({
+        title: '保存失败',
+        description: String(error),
+        variant: 'destructive',
+      });
+    } finally {
+      setSaving(false);
+    }
+  };
+
   return (
     <div className="grid gap-6">
       {/* 启动设置 */}
@@ -130,6 +165,32 @@ export function BasicSettingsTab() {
               disabled={loading || saving}
             />
           </div>
+          {startupEnabled && (
+            <div className="flex items-center justify-between p-4 border rounded-lg bg-muted/20">
+              <div className="space-y-0.5">
+                <Label htmlFor="startup-mode" className="text-base">
+                  自启动方式
+                </Label>
+                <p className="text-sm text-muted-foreground">
+                  后台运行时不打开窗口，代理、配置监听与用量统计照常工作，可从托盘打开窗口。
+                </p>
+              </div>
+              <Select
+                value={startupMode}
+                onValueChange={(value) => handleStartupModeChange(value as StartupMode)}
+                disabled={loading || saving}
+              >
+                <SelectTrigger id="startup-mode" className="w-40 shadow-sm">
+                  <SelectValue />
+                </SelectTrigger>
+                <SelectContent>
+                  <SelectItem value="window">显示窗口</SelectItem>
+                  <SelectItem value="minimized">隐藏到托盘</SelectItem>
+                  <SelectItem value="background">后台运行</SelectItem>
+                </SelectContent>
+              </Select>
+            </div>
+          )}
         </CardContent>
       </Card>
 
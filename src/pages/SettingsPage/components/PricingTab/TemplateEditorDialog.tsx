@@ -46,6 +46,8 @@ export function TemplateEditorDialog({
   const [description, setDescription] = useState('');
   const [inheritedModels, setInheritedModels] = useState<InheritedModel[]>([]);
   const [customModels, setCustomModels] = useState<Record<string, ModelPrice>>({});
+  const [perRequestFee, setPerRequestFee] = useState('');
+  const [minMonthlyFee, setMinMonthlyFee] = useState('');
   const [availableTemplates, setAvailableTemplates] = useState<PricingTemplate[]>([]);
   const [loadingTemplates, setLoadingTemplates] = useState(false);
 
@@ -75,12 +77,16 @@ export function TemplateEditorDialog({
       setDescription(template.description);
       setInheritedModels(template.inherited_models);
       setCustomModels(template.custom_models);
+      setPerRequestFee(template.per_request_fee ? String(template.per_request_fee) : '');
+      setMinMonthlyFee(template.min_monthly_fee ? String(template.min_monthly_fee) : '');
     } else {
       // 新建模式
       setName('');
       setDescription('');
       setInheritedModels([]);
       setCustomModels({});
+      setPerRequestFee('');
+      setMinMonthlyFee('');
     }
   }, [open, template]);
 
@@ -106,6 +112,26 @@ export function TemplateEditorDialog({
       return;
     }
 
+    // 验证附加费用
+    const requestFee = perRequestFee.trim() ? Number(perRequestFee) : 0;
+    const monthlyFee = minMonthlyFee.trim() ? Number(minMonthlyFee) : 0;
+    if (!Number.isFinite(requestFee) || requestFee < 0) {
+      toast({
+        title: '验证失败',
+        description: '按次附加费必须为非负数',
+        variant: 'destructive',
+      });
+      return;
+    }
+    if (!Number.isFinite(monthlyFee) || monthlyFee < 0) {
+      toast({
+        title: '验证失败',
+        description: '月最低消费必须为非负数',
+        variant: 'destructive',
+      });
+      return;
+    }
+
     // 验证继承配置的完整性
     for (const inherited of inheritedModels) {
       if (!inherited.model_name.trim()) {
@@ -149,6 +175,8 @@ export function TemplateEditorDialog({
         custom_models: customModels,
         tags: template?.tags || [],
         is_default_preset: template?.is_default_preset || false,
+        per_request_fee: requestFee,
+        min_monthly_fee: monthlyFee,
       };
 
       await savePricingTemplate(newTemplate);
@@ -238,6 +266,38 @@ export function TemplateEditorDialog({
                 disabled={isReadOnly}
               />
             </div>
+            <div className="grid grid-cols-2 gap-4">
+              <div className="space-y-2">
+                <Label htmlFor="per-request-fee">按次附加费（USD）</Label>
+                <Input
+                  id="per-request-fee"
+                  type="number"
+                  min={0}
+                  step="0.0001"
+                  placeholder="0"
+                  value={perRequestFee}
+                  onChange={(e) => setPerRequestFee(e.target.value)}
+                  disabled={isReadOnly}
+                />
+                <p className="text-xs text-muted-foreground">每个产生用量的请求额外计费</p>
+              </div>
+              <div className="space-y-2">
+                <Label htmlFor="min-monthly-fee">月最低消费（USD）</Label>
+                <Input
+                  id="min-monthly-fee"
+                  type="number"
+                  min={0}
+                  step="0.01"
+                  placeholder="0"
+                  value={minMonthlyFee}
+                  onChange={(e) => setMinMonthlyFee(e.target.value)}
+                  disabled={isReadOnly}
+                />
+                <p className="text-xs text-muted-foreground">
+                  当月用量成本不足时，月度账单按此金额结算
+                </p>
+              </div>
+            </div>
             {!isReadOnly && (
               <Alert>
                 <AlertCircle className="h-4 w-4" />
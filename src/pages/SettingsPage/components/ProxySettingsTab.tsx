@@ -10,13 +10,14 @@ import {
   SelectTrigger,
   SelectValue,
 } from '@/components/ui/select';
+import type { ProxyType } from '@/lib/tauri-commands';
 import { Info, ShieldCheck, Globe, ListFilter, Plus, X, PlayCircle, Loader2 } from 'lucide-react';
 
 interface ProxySettingsTabProps {
   proxyEnabled: boolean;
   setProxyEnabled: (value: boolean) => void;
-  proxyType: 'http' | 'https' | 'socks5';
-  setProxyType: (value: 'http' | 'https' | 'socks5') => void;
+  proxyType: ProxyType;
+  setProxyType: (value: ProxyType) => void;
   proxyHost: string;
   setProxyHost: (value: string) => void;
   proxyPort: string;
@@ -108,6 +109,8 @@ export function ProxySettingsTab({
                     <SelectItem value="http">HTTP</SelectItem>
                     <SelectItem value="https">HTTPS</SelectItem>
                     <SelectItem value="socks5">SOCKS5</SelectItem>
+                    <SelectItem value="ntlm">NTLM（本地认证桥）</SelectItem>
+                    <SelectItem value="negotiate">Negotiate / Kerberos（本地认证桥）</SelectItem>
                   </SelectContent>
                 </Select>
               </div>
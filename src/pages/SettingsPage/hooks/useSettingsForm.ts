@@ -5,6 +5,7 @@ import {
   type GlobalConfig,
   type TestProxyResult,
   type ProxyTestConfig,
+  type ProxyType,
 } from '@/lib/tauri-commands';
 
 interface UseSettingsFormProps {
@@ -15,7 +16,7 @@ interface UseSettingsFormProps {
 export function useSettingsForm({ initialConfig, onConfigChange }: UseSettingsFormProps) {
   // 代理设置状态
   const [proxyEnabled, setProxyEnabled] = useState(false);
-  const [proxyType, setProxyType] = useState<'http' | 'https' | 'socks5'>('http');
+  const [proxyType, setProxyType] = useState<ProxyType>('http');
   const [proxyHost, setProxyHost] = useState('');
   const [proxyPort, setProxyPort] = useState('');
   const [proxyUsername, setProxyUsername] = useState('');
@@ -21,15 +21,28 @@ import {
   DropdownMenuItem,
   DropdownMenuTrigger,
 } from '@/components/ui/dropdown-menu';
-import { Loader2, ChevronLeft, ChevronRight, ChevronDown, ChevronUp, Search } from 'lucide-react';
+import {
+  Loader2,
+  ChevronLeft,
+  ChevronRight,
+  ChevronDown,
+  ChevronUp,
+  Search,
+  Download,
+} from 'lucide-react';
+import { save } from '@tauri-apps/plugin-dialog';
 import { CustomTimeRangeDialog } from '@/components/dialogs/CustomTimeRangeDialog';
-import { queryTokenLogs } from '@/lib/tauri-commands';
-import type { TokenLog, TokenLogsPage } from '@/types/token-stats';
+import { useToast } from '@/hooks/use-toast';
+import { exportTokenLogs, queryTokenLogs } from '@/lib/tauri-commands';
+import type { LogExportFormat, TokenLog, TokenLogsPage } from '@/types/token-stats';
 import {
   TOOL_TYPE_NAMES,
   TIME_RANGE_OPTIONS,
   DEFAULT_PAGE_SIZE,
   RESPONSE_TYPE_NAMES,
+  REQUEST_STATUS_NAMES,
+  REQUEST_STATUS_COLORS,
+  ERROR_TYPE_NAMES,
   type ToolType,
 } from '@/types/token-stats';
 
@@ -108,35 +121,52 @@ export function LogsTable({
   const [isLoading, setIsLoading] = useState(true);
   const [error, setError] = useState<string | null>(null);
 
+  const [isExporting, setIsExporting] = useState(false);
+  const { toast } = useToast();
+
+  // 构建过滤条件 - 支持预设和自定义时间范围（列表查询与导出共用）
+  const buildFilters = useCallback(() => {
+    let start_time: number | undefined;
+    let end_time: number | undefined;
+
+    if (timeRangeMode === 'preset') {
+      const timeRange = TIME_RANGE_OPTIONS.find((opt) => opt.value === timeRangeFilter);
+      const range = timeRange?.getRange() ?? {};
+      start_time = range.start_time;
+      end_time = range.end_time;
+    } else {
+      // 自定义时间范围
+      if (customStartTime && customEndTime) {
+        start_time = Math.floor(customStartTime.getTime() / 1000);
+        end_time = Math.floor(customEndTime.getTime() / 1000);
+      }
+    }
+
+    return {
+      tool_type: toolTypeFilter,
+      session_id: sessionIdFilter || undefined,
+      config_name: configNameFilter || undefined,
+      start_time,
+      end_time,
+    };
+  }, [
+    toolTypeFilter,
+    sessionIdFilter,
+    configNameFilter,
+    timeRangeFilter,
+    timeRangeMode,
+    customStartTime,
+    customEndTime,
+  ]);
+
   // 获取日志数据
   const fetchLogs = useCallback(async () => {
     setIsLoading(true);
     setError(null);
 
     try {
-      // 构建查询参数 - 支持预设和自定义时间范围
-      let start_time: number | undefined;
-      let end_time: number | undefined;
-
-      if (timeRangeMode === 'preset') {
-        const timeRange = TIME_RANGE_OPTIONS.find((opt) => opt.value === timeRangeFilter);
-        const range = timeRange?.getRange() ?? {};
-        start_time = range.start_time;
-        end_time = range.end_time;
-      } else {
-        // 自定义时间范围
-        if (customStartTime && customEndTime) {
-          start_time = Math.floor(customStartTime.getTime() / 1000);
-          end_time = Math.floor(customEndTime.getTime() / 1000);
-        }
-      }
-
       const result = await queryTokenLogs({
-        tool_type: toolTypeFilter,
-        session_id: sessionIdFilter || undefined,
-        config_name: configNameFilter || undefined,
-        start_time,
-        end_time,
+        ...buildFilters(),
         page,
         page_size: pageSize,
       });
@@ -148,17 +178,41 @@ export function LogsTable({
     } finally {
       setIsLoading(false);
     }
-  }, [
-    page,
-    pageSize,
-    toolTypeFilter,
-    sessionIdFilter,
-    configNameFilter,
-    timeRangeFilter,
-    timeRangeMode,
-    customStartTime,
-    customEndTime,
-  ]);
+  }, [page, pageSize, buildFilters]);
+
+  // 按当前过滤条件导出日志
+  const handleExport = async (format: LogExportFormat) => {
+    const path = await save({
+      defaultPath: `token-logs-${new Date().toISOString().slice(0, 10)}.${format}`,
+      filters: [
+        format === 'csv'
+          ? { name: 'CSV', extensions: ['csv'] }
+          : { name: 'JSON Lines', extensions: ['jsonl'] },
+      ],
+    });
+    if (!path) return;
+
+    setIsExporting(true);
+    try {
+      const summary = await exportTokenLogs(
+        { ...buildFilters(), page: 0, page_size: 0 },
+        format,
+        path,
+      );
+      toast({
+        title: '导出完成',
+        description: `已导出 ${summary.row_count} 条记录到 ${summary.path}`,
+      });
+    } catch (err) {
+      toast({
+        variant: 'destructive',
+        title: '导出失败',
+        description: String(err),
+      });
+    } finally {
+      setIsExporting(false);
+    }
+  };
 
   // 初始加载和过滤器变更时重新加载
   useEffect(() => {
@@ -292,6 +346,24 @@ export function LogsTable({
               重置
             </Button>
 
+            {/* 导出按钮 */}
+            <DropdownMenu>
+              <DropdownMenuTrigger asChild>
+                <Button variant="outline" size="sm" disabled={isExporting}>
+                  {isExporting ? (
+                    <Loader2 className="mr-1 h-4 w-4 animate-spin" />
+                  ) : (
+                    <Download className="mr-1 h-4 w-4" />
+                  )}
+                  导出
+                </Button>
+              </DropdownMenuTrigger>
+              <DropdownMenuContent align="start">
+                <DropdownMenuItem onClick={() => handleExport('csv')}>CSV</DropdownMenuItem>
+                <DropdownMenuItem onClick={() => handleExport('jsonl')}>JSON Lines</DropdownMenuItem>
+              </DropdownMenuContent>
+            </DropdownMenu>
+
             {/* 统计信息 */}
             {data && (
               <div className="ml-auto text-sm text-muted-foreground">共 {data.total} 条记录</div>
@@ -372,12 +444,11 @@ export function LogsTable({
                               <Badge
                                 variant="outline"
                                 className={`text-xs ${
-                                  log.request_status === 'success'
-                                    ? 'text-green-700 bg-green-50 border-green-200'
-                                    : 'text-red-700 bg-red-50 border-red-200'
+                                  REQUEST_STATUS_COLORS[log.request_status] ??
+                                  REQUEST_STATUS_COLORS.failed
                                 }`}
                               >
-                                {log.request_status === 'success' ? '成功' : '失败'}
+                                {REQUEST_STATUS_NAMES[log.request_status] ?? '失败'}
                               </Badge>
                             </TableCell>
                             <TableCell>
@@ -390,7 +461,15 @@ export function LogsTable({
                             <TableCell className="text-xs font-mono">
                               {log.session_id.substring(0, 8)}
                             </TableCell>
-                            <TableCell className="text-xs">{log.config_name}</TableCell>
+                            <TableCell className="text-xs">
+                              {log.config_name}
+                              {log.upstream_key_alias && (
+                                <span className="text-muted-foreground">
+                                  {' '}
+                                  · {log.upstream_key_alias}
+                                </span>
+                              )}
+                            </TableCell>
                             <TableCell className="text-xs max-w-[150px] truncate" title={log.model}>
                               {log.model}
                             </TableCell>
@@ -491,17 +570,11 @@ export function LogsTable({
                                       <span className="font-mono text-xs">{log.message_id}</span>
                                     </div>
                                   )}
-                                  {log.request_status === 'failed' && log.error_type && (
+                                  {log.request_status !== 'success' && log.error_type && (
                                     <div className="pt-2 border-t">
                                       <div className="flex items-start gap-2">
                                         <Badge variant="destructive" className="text-xs">
-                                          {log.error_type === 'parse_error'
-                                            ? '解析失败'
-                                            : log.error_type === 'request_interrupted'
-                                              ? '请求中断'
-                                              : log.error_type === 'upstream_error'
-                                                ? '上游错误'
-                                                : log.error_type}
+                                          {ERROR_TYPE_NAMES[log.error_type] ?? log.error_type}
                                         </Badge>
                                         {log.error_detail && (
                                           <span className="text-xs text-muted-foreground flex-1">
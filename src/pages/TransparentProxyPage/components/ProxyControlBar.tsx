@@ -15,7 +15,12 @@ import {
   Settings2,
 } from 'lucide-react';
 import type { ToolMetadata, ToolId } from '../types/proxy-history';
-import type { ToolProxyConfig } from '@/lib/tauri-commands';
+import type {
+  ConcurrencyStatus,
+  FailoverStatus,
+  ToolProxyConfig,
+  UpstreamQueueStatus,
+} from '@/lib/tauri-commands';
 import { ProxyConfigDialog } from './ProxyConfigDialog';
 import { ProxySettingsDialog } from './ProxySettingsDialog';
 
@@ -26,6 +31,14 @@ interface ProxyControlBarProps {
   isRunning: boolean;
   /** 代理端口 */
   port: number | null;
+  /** 多上游故障转移状态 */
+  failover?: FailoverStatus | null;
+  /** 上游限流排队状态 */
+  queue?: UpstreamQueueStatus | null;
+  /** 并发上游请求状态 */
+  concurrency?: ConcurrencyStatus | null;
+  /** 刷新代理状态回调（展开详情时调用） */
+  onRefreshStatus?: () => void;
   /** 是否加载中（启动中或停止中） */
   isLoading: boolean;
   /** 是否已配置（有 API Key） */
@@ -51,10 +64,61 @@ function maskApiKey(apiKey: string | null | undefined): string {
   return `${apiKey.slice(0, 4)}****${apiKey.slice(-4)}`;
 }
 
+/**
+ * 备用上游状态列表（配置了备用上游时显示）
+ */
+function FailoverDetails({ failover }: { failover: FailoverStatus }) {
+  return (
+    <div className="mt-3 space-y-1">
+      <span className="text-xs text-muted-foreground">上游故障转移</span>
+      <div className="space-y-1">
+        {failover.upstreams.map((upstream) => (
+          <div
+            key={`${upstream.base_url}-${upstream.priority}`}
+            className="flex items-center gap-2 text-xs"
+            title={upstream.last_error ?? undefined}
+          >
+            <code className="flex-1 px-2 py-1 bg-muted rounded font-mono truncate">
+              {upstream.base_url}
+            </code>
+            {upstream.primary ? (
+              <Badge variant="outline" className="text-xs">
+                主上游
+              </Badge>
+            ) : (
+              <Badge variant="outline" className="text-xs">
+                优先级 {upstream.priority}
+              </Badge>
+            )}
+            {upstream.active && <Badge className="text-xs">活跃</Badge>}
+            {upstream.cooling_down && (
+              <Badge variant="destructive" className="text-xs">
+                冷却中（失败 {upstream.consecutive_failures} 次）
+              </Badge>
+            )}
+          </div>
+        ))}
+      </div>
+    </div>
+  );
+}
+
 /**
  * 代理详情组件（可折叠）
  */
-function ProxyDetails({ config, port }: { config: ToolProxyConfig | null; port: number | null }) {
+function ProxyDetails({
+  config,
+  port,
+  failover,
+  queue,
+  concurrency,
+}: {
+  config: ToolProxyConfig | null;
+  port: number | null;
+  failover?: FailoverStatus | null;
+  queue?: UpstreamQueueStatus | null;
+  concurrency?: ConcurrencyStatus | null;
+}) {
   const [copiedField, setCopiedField] = useState<string | null>(null);
 
   const handleCopy = async (value: string, field: string) => {
@@ -180,6 +244,21 @@ function ProxyDetails({ config, port }: { config: ToolProxyConfig | null; port:
           </div>
         </div>
       </div>
+      {failover && failover.upstreams.length > 1 && <FailoverDetails failover={failover} />}
+      {config?.upstream_queue?.enabled && queue && (
+        <div className="mt-3 text-xs text-muted-foreground">
+          上游限流排队：当前 {queue.depth} / {config.upstream_queue.max_requests} 个请求，累计排队{' '}
+          {queue.total_queued} 次
+        </div>
+      )}
+      {concurrency && (
+        <div className="mt-3 text-xs text-muted-foreground">
+          并发上游请求：当前 {concurrency.in_flight}
+          {concurrency.limit !== null && ` / ${concurrency.limit}`} 个
+          {concurrency.waiting > 0 && `，排队 ${concurrency.waiting} 个`}
+          {concurrency.total_rejected > 0 && `，累计拒绝 ${concurrency.total_rejected} 次`}
+        </div>
+      )}
     </div>
   );
 }
@@ -196,6 +275,10 @@ export function ProxyControlBar({
   tool,
   isRunning,
   port,
+  failover,
+  queue,
+  concurrency,
+  onRefreshStatus,
   isLoading,
   isConfigured,
   config,
@@ -223,6 +306,13 @@ export function ProxyControlBar({
     };
   }, [tool.id]);
 
+  // 展开详情时刷新故障转移状态
+  useEffect(() => {
+    if (detailsExpanded && isRunning) {
+      onRefreshStatus?.();
+    }
+  }, [detailsExpanded, isRunning, onRefreshStatus]);
+
   // 检查上游配置是否缺失（amp-code 除外，它使用 amp_selection）
   const isUpstreamConfigMissing =
     isRunning && tool.id !== 'amp-code' && (!config?.real_base_url || !config?.real_api_key);
@@ -429,7 +519,15 @@ export function ProxyControlBar({
       )}
 
       {/* 代理详情（可折叠） */}
-      {isRunning && detailsExpanded && <ProxyDetails config={config} port={port} />}
+      {isRunning && detailsExpanded && (
+        <ProxyDetails
+          config={config}
+          port={port}
+          failover={failover}
+          queue={queue}
+          concurrency={concurrency}
+        />
+      )}
 
       {/* 配置切换弹窗 */}
       <ProxyConfigDialog
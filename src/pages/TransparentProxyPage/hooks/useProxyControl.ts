@@ -7,6 +7,9 @@ import {
   stopToolProxy,
   getAllProxyStatus,
   type AllProxyStatus,
+  type ConcurrencyStatus,
+  type FailoverStatus,
+  type UpstreamQueueStatus,
 } from '@/lib/tauri-commands';
 import type { ToolId } from '../types/proxy-history';
 
@@ -113,6 +116,36 @@ export function useProxyControl() {
     [proxyStatus],
   );
 
+  /**
+   * 获取指定工具的多上游故障转移状态
+   */
+  const getFailover = useCallback(
+    (toolId: ToolId): FailoverStatus | null => {
+      return proxyStatus[toolId]?.failover ?? null;
+    },
+    [proxyStatus],
+  );
+
+  /**
+   * 获取指定工具的上游限流排队状态
+   */
+  const getQueue = useCallback(
+    (toolId: ToolId): UpstreamQueueStatus | null => {
+      return proxyStatus[toolId]?.queue ?? null;
+    },
+    [proxyStatus],
+  );
+
+  /**
+   * 获取指定工具的并发上游请求状态
+   */
+  const getConcurrency = useCallback(
+    (toolId: ToolId): ConcurrencyStatus | null => {
+      return proxyStatus[toolId]?.concurrency ?? null;
+    },
+    [proxyStatus],
+  );
+
   // 初始加载代理状态
   useEffect(() => {
     refreshProxyStatus();
@@ -126,5 +159,8 @@ export function useProxyControl() {
     isLoading,
     isRunning,
     getPort,
+    getFailover,
+    getQueue,
+    getConcurrency,
   };
 }
@@ -2,7 +2,13 @@
 // 统一管理三个工具的配置和状态数据
 
 import { useState, useEffect, useCallback } from 'react';
-import { getProxyConfig, updateProxyConfig, type ToolProxyConfig } from '@/lib/tauri-commands';
+import { listen } from '@tauri-apps/api/event';
+import {
+  getProxyConfig,
+  updateProxyConfig,
+  type ProxyFailoverEvent,
+  type ToolProxyConfig,
+} from '@/lib/tauri-commands';
 import type { ToolId } from '../types/proxy-history';
 import { useProxyControl } from './useProxyControl';
 
@@ -140,6 +146,26 @@ export function useToolProxyData() {
     loadAllConfigs();
   }, [loadAllConfigs]);
 
+  // 后端自动切换到备用 Profile 后重新加载该工具配置
+  useEffect(() => {
+    const unlisten = listen<ProxyFailoverEvent>('proxy-failover', (event) => {
+      loadToolConfig(event.payload.tool_id as ToolId);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [loadToolConfig]);
+
+  // 应用启动时代理自启动完成后刷新运行状态
+  useEffect(() => {
+    const unlisten = listen('proxy-auto-start-completed', () => {
+      refreshProxyStatus();
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [refreshProxyStatus]);
+
   return {
     configLoading,
     proxyStatus,
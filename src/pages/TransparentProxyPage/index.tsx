@@ -67,7 +67,17 @@ export function TransparentProxyPage({ selectedToolId: initialToolId }: Transpar
   const { getToolData, configLoading, refreshData, saveToolConfig } = useToolProxyData();
 
   // 使用代理控制 Hook
-  const { startProxy, stopProxy, isLoading, isRunning, getPort } = useProxyControl();
+  const {
+    startProxy,
+    stopProxy,
+    isLoading,
+    isRunning,
+    getPort,
+    getFailover,
+    getQueue,
+    getConcurrency,
+    refreshProxyStatus,
+  } = useProxyControl();
 
   /**
    * 导航到会话详情页
@@ -164,6 +174,10 @@ export function TransparentProxyPage({ selectedToolId: initialToolId }: Transpar
                   tool={tool}
                   isRunning={toolIsRunning}
                   port={toolPort}
+                  failover={getFailover(tool.id)}
+                  queue={getQueue(tool.id)}
+                  concurrency={getConcurrency(tool.id)}
+                  onRefreshStatus={refreshProxyStatus}
                   isLoading={toolIsLoading}
                   isConfigured={toolIsConfigured}
                   config={toolData.config}
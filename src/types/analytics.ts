@@ -41,6 +41,8 @@ export interface TrendQuery {
   model?: string;
   /** 配置名称过滤（可选） */
   config_name?: string;
+  /** 标签过滤（可选，如项目名） */
+  tag?: string;
   /** 时间粒度（必需） */
   granularity: TimeGranularity;
 }
@@ -101,6 +103,38 @@ export interface ConfigCostStat {
   request_count: number;
 }
 
+/**
+ * 按 Key 池 Key 别名分组的用量统计
+ */
+export interface KeyCostStat {
+  /** Key 别名 */
+  key_alias: string;
+  /** 总成本（USD） */
+  total_cost: number;
+  /** 请求数 */
+  request_count: number;
+  /** 输入 Token 总数 */
+  input_tokens: number;
+  /** 输出 Token 总数 */
+  output_tokens: number;
+}
+
+/**
+ * 按客户端 Key 标签分组的用量统计
+ */
+export interface ClientCostStat {
+  /** 客户端 Key 标签 */
+  client_label: string;
+  /** 总成本（USD） */
+  total_cost: number;
+  /** 请求数 */
+  request_count: number;
+  /** 输入 Token 总数 */
+  input_tokens: number;
+  /** 输出 Token 总数 */
+  output_tokens: number;
+}
+
 /**
  * 成本汇总数据
  */
@@ -119,6 +153,10 @@ export interface CostSummary {
   cost_by_model: ModelCostStat[];
   /** 按配置分组的成本 */
   cost_by_config: ConfigCostStat[];
+  /** 按 Key 池 Key 别名分组的用量（未使用 Key 池时为空） */
+  cost_by_key: KeyCostStat[];
+  /** 按客户端 Key 标签分组的用量（未使用客户端 Key 时为空） */
+  cost_by_client: ClientCostStat[];
   /** 按天的成本趋势 */
   daily_costs: Array<{
     /** 日期（时间戳毫秒） */
@@ -127,3 +165,465 @@ export interface CostSummary {
     cost: number;
   }>;
 }
+
+/**
+ * SQL 控制台查询请求（只读）
+ */
+export interface SqlConsoleQuery {
+  /** SQL 语句（仅允许单条 SELECT/WITH） */
+  sql: string;
+  /** 按顺序绑定到 ? 占位符的参数 */
+  params?: Array<string | number | boolean | null>;
+  /** 最大返回行数（默认 500，上限 10000） */
+  max_rows?: number;
+  /** 执行超时（毫秒，默认 5000，上限 30000） */
+  timeout_ms?: number;
+}
+
+/**
+ * SQL 控制台查询结果
+ */
+export interface SqlConsoleResult {
+  columns: string[];
+  rows: unknown[][];
+  /** 结果是否因行数限制被截断 */
+  truncated: boolean;
+  elapsed_ms: number;
+}
+
+/**
+ * SQL 控制台查询历史
+ */
+export interface SqlHistoryEntry {
+  sql: string;
+  params?: Array<string | number | boolean | null>;
+  /** 执行时间（毫秒时间戳） */
+  executed_at: number;
+  row_count: number;
+  elapsed_ms: number;
+  /** 失败原因 */
+  error?: string;
+}
+
+/**
+ * 生产力关联查询参数
+ */
+export interface ProductivityQuery {
+  /** git 仓库路径 */
+  repo_path: string;
+  start_time?: number;
+  end_time?: number;
+  tool_type?: string;
+  /** 会话 ID 过滤（为空时统计时间窗口内全部会话） */
+  session_ids?: string[];
+  /** 提交作者过滤 */
+  author?: string;
+  /** 提交信息匹配（忽略大小写），用于只统计 AI 辅助的提交 */
+  commit_grep?: string;
+}
+
+/**
+ * 生产力关联报告
+ */
+export interface ProductivityReport {
+  git: {
+    commits: number;
+    lines_added: number;
+    lines_deleted: number;
+  };
+  total_cost: number;
+  request_count: number;
+  /** 每千行变更成本（USD） */
+  cost_per_1k_lines: number | null;
+  /** 每次提交成本（USD） */
+  cost_per_commit: number | null;
+}
+
+/**
+ * 统计数据脱敏选项（截图 / 演示模式）
+ */
+export interface ScrubOptions {
+  /** 成本按数量级取整（保留 1 位有效数字） */
+  bucket_costs?: boolean;
+  /** 自定义哈希盐；为空时使用进程级随机盐，需要跨次截图保持名称一致时指定 */
+  salt?: string | null;
+}
+
+/**
+ * 工具横向对比查询参数
+ */
+export interface ToolComparisonQuery {
+  start_time?: number;
+  end_time?: number;
+}
+
+/**
+ * 单个工具的对比指标
+ */
+export interface ToolComparisonStat {
+  tool_type: string;
+  request_count: number;
+  failed_requests: number;
+  total_cost: number;
+  /** 每次请求平均成本（USD） */
+  cost_per_request: number | null;
+  /** 平均响应时间（毫秒） */
+  avg_response_time: number | null;
+  /** 失败率（0~1） */
+  error_rate: number | null;
+  input_tokens: number;
+  output_tokens: number;
+  cache_creation_tokens: number;
+  cache_read_tokens: number;
+  /** 缓存命中率（0~1） */
+  cache_hit_rate: number | null;
+}
+
+/**
+ * 响应时间统计分组方式
+ */
+export type LatencyGroupBy = 'model' | 'config' | 'time';
+
+/**
+ * 响应时间统计查询参数
+ */
+export interface LatencyStatsQuery {
+  start_time?: number;
+  end_time?: number;
+  tool_type?: string;
+  model?: string;
+  config_name?: string;
+  /** 标签过滤（如项目名） */
+  tag?: string;
+  group_by?: LatencyGroupBy;
+  /** 时间粒度（仅按时间分组时使用） */
+  granularity?: TimeGranularity;
+}
+
+/**
+ * 单个分组的响应时间分布（毫秒）
+ */
+export interface LatencyStats {
+  /** 分组名称（模型 / 配置名称；按时间分组时为时间桶起点的毫秒时间戳） */
+  group_name: string;
+  /** 计入统计的请求数（仅成功且记录了响应时间的请求） */
+  request_count: number;
+  avg_ms: number;
+  min_ms: number;
+  max_ms: number;
+  p50_ms: number;
+  p90_ms: number;
+  p99_ms: number;
+}
+
+/**
+ * 月度账单查询参数
+ */
+export interface MonthlyCostQuery {
+  start_time?: number;
+  end_time?: number;
+  tool_type?: string;
+}
+
+/**
+ * 单个价格模板的月度账单
+ */
+export interface MonthlyTemplateCost {
+  template_id: string;
+  /** 用量成本（含按次附加费，USD） */
+  usage_cost: number;
+  request_count: number;
+  /** 模板的月最低消费（USD） */
+  min_monthly_fee: number;
+  /** 为达到最低消费补足的金额（USD） */
+  minimum_topup: number;
+  /** 结算金额：max(用量成本, 最低消费) */
+  billed_cost: number;
+}
+
+/**
+ * 月度账单
+ */
+export interface MonthlyCostReport {
+  /** 月份（本地时间，YYYY-MM） */
+  month: string;
+  usage_cost: number;
+  minimum_topup: number;
+  billed_cost: number;
+  request_count: number;
+  templates: MonthlyTemplateCost[];
+}
+
+/**
+ * 工具横向对比结果
+ */
+export interface ToolComparison {
+  start_time: number | null;
+  end_time: number | null;
+  tools: ToolComparisonStat[];
+  /** 各项指标表现最好的工具 ID */
+  leaders: {
+    lowest_cost_per_request: string | null;
+    lowest_latency: string | null;
+    lowest_error_rate: string | null;
+    highest_cache_hit_rate: string | null;
+  };
+}
+
+/**
+ * 单个模型在一个周期内的用量
+ */
+export interface ModelUsageStat {
+  model: string;
+  request_count: number;
+  failed_requests: number;
+  input_tokens: number;
+  output_tokens: number;
+  cache_creation_tokens: number;
+  cache_read_tokens: number;
+  total_tokens: number;
+  total_cost: number;
+  /** 平均响应时间（毫秒） */
+  avg_response_time: number | null;
+  /** 失败率（0~1） */
+  error_rate: number | null;
+}
+
+/**
+ * 模型排行中的一项
+ */
+export interface ModelBreakdownEntry {
+  current: ModelUsageStat;
+  /** 上一周期的用量（上一周期未使用该模型时为 null） */
+  previous: ModelUsageStat | null;
+  /** 占本周期总成本的比例（0~1） */
+  cost_share: number | null;
+  /** 请求数环比变化（百分比） */
+  request_change_percent: number | null;
+  /** 成本环比变化（百分比） */
+  cost_change_percent: number | null;
+}
+
+/**
+ * 按模型用量明细（按成本降序）
+ */
+export interface ModelBreakdown {
+  start_time: number;
+  end_time: number;
+  previous_start_time: number;
+  previous_end_time: number;
+  total_requests: number;
+  total_cost: number;
+  previous_total_requests: number;
+  previous_total_cost: number;
+  models: ModelBreakdownEntry[];
+}
+
+/**
+ * 报表查询定义
+ */
+export type ReportDefinition =
+  | { kind: 'trend'; query: TrendQuery }
+  | {
+      kind: 'cost_summary';
+      query: {
+        start_time?: number;
+        end_time?: number;
+        tool_type?: string;
+        session_id?: string;
+        tag?: string;
+        group_by: 'model' | 'config' | 'session' | 'upstream_key' | 'client_key';
+      };
+    }
+  | { kind: 'sql'; query: SqlConsoleQuery }
+  | { kind: 'productivity'; query: ProductivityQuery };
+
+/**
+ * 报表执行周期
+ */
+export type ReportSchedule = 'daily' | 'weekly' | 'monthly';
+
+/**
+ * 已保存的报表
+ */
+export interface SavedReport {
+  /** 新建时传空字符串 */
+  id: string;
+  name: string;
+  description?: string;
+  definition: ReportDefinition;
+  /** 相对时间范围（最近 N 天），运行时覆盖起止时间 */
+  range_days?: number;
+  schedule?: ReportSchedule;
+  created_at: number;
+  updated_at: number;
+}
+
+/**
+ * 报表运行结果
+ */
+export type ReportOutput =
+  | { kind: 'trend'; data: TrendDataPoint[] }
+  | {
+      kind: 'cost_summary';
+      data: Array<{
+        group_name: string;
+        total_cost: number;
+        request_count: number;
+        input_tokens: number;
+        output_tokens: number;
+        avg_response_time: number | null;
+      }>;
+    }
+  | { kind: 'sql'; data: SqlConsoleResult }
+  | { kind: 'productivity'; data: ProductivityReport };
+
+/**
+ * 报表导入结果
+ */
+export interface ReportImportSummary {
+  imported: number;
+  skipped: number;
+}
+
+/**
+ * 统计周期（区间为 [started_at, ended_at)，毫秒）
+ */
+export interface StatsEpoch {
+  id: number;
+  name: string;
+  started_at: number;
+  /** 当前周期为 null */
+  ended_at: number | null;
+  request_count: number;
+  total_cost: number;
+}
+
+/**
+ * 统计周期内按工具 + 模型聚合的用量
+ */
+export interface EpochSummary {
+  tool_type: string;
+  model: string;
+  request_count: number;
+  error_count: number;
+  input_tokens: number;
+  output_tokens: number;
+  cache_creation_tokens: number;
+  cache_read_tokens: number;
+  total_cost: number;
+}
+
+/**
+ * 用量报告时间范围
+ */
+export type UsageReportRange =
+  | { kind: 'week' }
+  | { kind: 'month' }
+  | { kind: 'custom'; start_time: number; end_time: number };
+
+/**
+ * 用量报告输出格式
+ */
+export type UsageReportFormat = 'markdown' | 'html';
+
+/**
+ * 用量报告中按模型 / 会话分组的条目
+ */
+export interface UsageReportGroup {
+  /** 模型名或会话 ID */
+  group_name: string;
+  total_cost: number;
+  request_count: number;
+  input_tokens: number;
+  output_tokens: number;
+  avg_response_time: number | null;
+}
+
+/**
+ * 用量报告
+ */
+export interface UsageReport {
+  title: string;
+  start_time: number;
+  end_time: number;
+  generated_at: number;
+  totals: {
+    request_count: number;
+    failed_requests: number;
+    session_count: number;
+    total_cost: number;
+    input_tokens: number;
+    output_tokens: number;
+    cache_creation_tokens: number;
+    cache_read_tokens: number;
+  };
+  cache: {
+    /** 缓存命中率（无输入时为 null） */
+    hit_rate: number | null;
+    /** 估算节省（USD） */
+    saved_cost: number;
+  };
+  top_models: UsageReportGroup[];
+  top_sessions: UsageReportGroup[];
+  /** 星期 × 小时（本地时间）请求数，7 行（周一开始）× 24 列 */
+  heatmap: number[][];
+}
+
+/**
+ * 已写入磁盘的用量报告
+ */
+export interface GeneratedUsageReport {
+  path: string;
+  format: UsageReportFormat;
+  report: UsageReport;
+}
+
+/**
+ * usage-report-generated 事件载荷
+ */
+export interface UsageReportEvent {
+  path: string;
+  title: string;
+  total_cost: number;
+  desktop_notification: boolean;
+}
+
+/**
+ * 缓存效率查询参数
+ */
+export interface CacheEfficiencyQuery {
+  start_time?: number;
+  end_time?: number;
+  tool_type?: string;
+  granularity?: TimeGranularity;
+}
+
+/**
+ * 缓存效率指标
+ */
+export interface CacheEfficiencyStat {
+  request_count: number;
+  input_tokens: number;
+  cache_creation_tokens: number;
+  cache_read_tokens: number;
+  /** 缓存读取占比：缓存读取 / (输入 + 缓存写入 + 缓存读取) */
+  cache_read_ratio: number | null;
+  /** 缓存写入与读取的实际成本（USD） */
+  actual_cache_cost: number;
+  /** 假设不使用缓存时按输入单价计价的成本（USD） */
+  uncached_cost: number;
+  /** 缓存节省（USD），可能为负 */
+  saved_cost: number;
+}
+
+/**
+ * 缓存效率分析结果
+ */
+export interface CacheEfficiencyReport {
+  total: CacheEfficiencyStat;
+  /** 按节省金额降序 */
+  by_model: (CacheEfficiencyStat & { model: string })[];
+  /** 按时间升序 */
+  by_time: (CacheEfficiencyStat & { timestamp: number })[];
+}
@@ -0,0 +1,21 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 本机管理 API 配置
+ *
+ * 启用后在本机端口提供 REST 接口（代理状态、Profile 切换、Token 统计查询），
+ * 供 Shell 提示符、编辑器插件等外部工具集成；请求须携带 `Authorization: Bearer <token>`。
+ */
+export type AdminApiConfig = { 
+/**
+ * 是否启用（默认关闭）
+ */
+enabled: boolean, 
+/**
+ * 监听端口（仅监听 127.0.0.1）
+ */
+port: number, 
+/**
+ * 访问令牌（启用时为空则自动生成）
+ */
+token: string | null, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AdminApiConfig } from "./AdminApiConfig";
+
+/**
+ * 管理 API 监听状态
+ */
+export type AdminApiStatus = { config: AdminApiConfig, running: boolean, 
+/**
+ * 接口地址（未运行时为 None）
+ */
+endpoint: string | null, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 握手信息
+ */
+export type ApiHandshake = { 
+/**
+ * 命令接口版本
+ */
+api_version: number, 
+/**
+ * 应用版本
+ */
+app_version: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 上游 API 协议（与客户端协议不同时由透明代理转换请求与响应）
+ */
+export type ApiProtocol = "anthropic" | "openai_chat" | "openai_responses";
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 审计操作类型
+ */
+export type AuditAction = "profile_activated" | "profile_saved" | "profile_deleted" | "proxy_config_updated" | "external_change_allowed" | "external_change_blocked";
@@ -0,0 +1,28 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AuditAction } from "./AuditAction";
+import type { JsonValue } from "./serde_json/JsonValue";
+
+/**
+ * 单条审计记录
+ */
+export type AuditEntry = { 
+/**
+ * 序号（从 1 开始连续递增）
+ */
+seq: bigint, timestamp: string, action: AuditAction, tool_id: string, 
+/**
+ * 操作对象（如 Profile 名称）
+ */
+target: string | null, 
+/**
+ * 操作详情（变化的字段等，不含敏感内容）
+ */
+details: JsonValue, 
+/**
+ * 上一条记录的哈希
+ */
+prev_hash: string, 
+/**
+ * 本条记录的哈希
+ */
+hash: string, };
@@ -0,0 +1,26 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 审计日志校验结果
+ */
+export type AuditVerification = { 
+/**
+ * 哈希链完整
+ */
+valid: boolean, 
+/**
+ * 已校验的记录数
+ */
+entries: number, 
+/**
+ * 首个校验失败的行号（从 1 开始）
+ */
+broken_at_line: number | null, 
+/**
+ * 失败原因
+ */
+error: string | null, 
+/**
+ * 最后一条记录的哈希（可另行保存，用于发现整段截断）
+ */
+head_hash: string | null, };
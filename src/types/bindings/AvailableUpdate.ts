@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 单个可用更新
+ */
+export type AvailableUpdate = { 
+/**
+ * 应用为 `duckcoding`，工具为工具 ID
+ */
+id: string, current_version: string | null, latest_version: string | null, 
+/**
+ * 是否为强制更新（仅应用更新）
+ */
+required: boolean, };
@@ -0,0 +1,54 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 余额监控配置项
+ */
+export type BalanceConfig = { 
+/**
+ * 配置 ID
+ */
+id: string, 
+/**
+ * 配置名称
+ */
+name: string, 
+/**
+ * API 端点 URL
+ */
+endpoint: string, 
+/**
+ * HTTP 方法（GET | POST）
+ */
+method: string, 
+/**
+ * 静态请求头（持久化）
+ */
+static_headers: { [key in string]?: string } | null, 
+/**
+ * 提取器 JavaScript 代码
+ */
+extractor_script: string, 
+/**
+ * 自动刷新间隔（秒），0 或 None 表示不自动刷新
+ */
+interval_sec: number | null, 
+/**
+ * 请求超时（毫秒）
+ */
+timeout_ms: bigint | null, 
+/**
+ * 是否保存 API Key 到文件
+ */
+save_api_key: boolean, 
+/**
+ * API Key（可选，明文存储）
+ */
+api_key: string | null, 
+/**
+ * 创建时间（Unix 时间戳，毫秒）
+ */
+created_at: bigint, 
+/**
+ * 更新时间（Unix 时间戳，毫秒）
+ */
+updated_at: bigint, };
@@ -0,0 +1,25 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 余额监控配置
+ *
+ * 定期查询各供应商与 Profile 的剩余额度，历史写入 SQLite；
+ * 剩余额度低于阈值时发送 `balance-low` 事件（回升到阈值以上后重新提醒）。
+ */
+export type BalanceMonitorConfig = { 
+/**
+ * 是否定期查询（默认开启）
+ */
+enabled: boolean, 
+/**
+ * 查询间隔（分钟）
+ */
+interval_minutes: number, 
+/**
+ * 低余额提醒阈值（USD），0 表示不提醒
+ */
+low_balance_threshold_usd: number, 
+/**
+ * 是否同时发送系统桌面通知
+ */
+desktop_notification: boolean, };
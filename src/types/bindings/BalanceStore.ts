@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BalanceConfig } from "./BalanceConfig";
+
+/**
+ * 余额监控存储结构
+ */
+export type BalanceStore = { 
+/**
+ * 存储格式版本
+ */
+version: number, 
+/**
+ * 所有配置列表
+ */
+configs: Array<BalanceConfig>, };
@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 请求/响应体捕获配置
+ *
+ * 捕获内容包含提示词与密钥等敏感数据，因此过期删除不可关闭，只能调整时长
+ */
+export type BodyCaptureConfig = { enabled: boolean, 
+/**
+ * 捕获保留时长（分钟），超出范围时按上下限处理
+ */
+ttl_minutes: number, 
+/**
+ * 请求体/响应体各自的大小上限（KB），超出部分截断
+ */
+max_body_kb: number, };
@@ -0,0 +1,23 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BudgetMode } from "./BudgetMode";
+
+/**
+ * 按工具的消费预算（USD，按本地时间的自然日 / 周 / 月统计）
+ */
+export type BudgetConfig = { enabled: boolean, 
+/**
+ * 每日上限（None 表示不限制）
+ */
+daily_limit_usd: number | null, 
+/**
+ * 每周上限（周一起算）
+ */
+weekly_limit_usd: number | null, 
+/**
+ * 每月上限
+ */
+monthly_limit_usd: number | null, mode: BudgetMode, 
+/**
+ * 达到上限的该百分比时发送提醒
+ */
+warn_percent: number, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 预算提醒级别
+ */
+export type BudgetLevel = "warning" | "exceeded";
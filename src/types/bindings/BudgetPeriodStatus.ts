@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BudgetLevel } from "./BudgetLevel";
+import type { BudgetPeriod } from "./BudgetPeriod";
+
+/**
+ * 单个周期的消费情况
+ */
+export type BudgetPeriodStatus = { period: BudgetPeriod, limit_usd: number, spent_usd: number, 
+/**
+ * 已用百分比
+ */
+percent: number, 
+/**
+ * 周期开始时间（Unix 时间戳，毫秒）
+ */
+period_start: bigint, level: BudgetLevel | null, };
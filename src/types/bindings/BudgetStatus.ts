@@ -0,0 +1,24 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BudgetMode } from "./BudgetMode";
+import type { BudgetPeriodStatus } from "./BudgetPeriodStatus";
+
+/**
+ * 工具的预算状态
+ */
+export type BudgetStatus = { tool_id: string, enabled: boolean, mode: BudgetMode, 
+/**
+ * 已设置上限的周期
+ */
+periods: Array<BudgetPeriodStatus>, 
+/**
+ * 任一周期达到提醒阈值
+ */
+warning: boolean, 
+/**
+ * 任一周期达到上限
+ */
+exceeded: boolean, 
+/**
+ * 检查时间（Unix 时间戳，毫秒）
+ */
+checked_at: bigint, };
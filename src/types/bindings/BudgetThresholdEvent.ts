@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BudgetLevel } from "./BudgetLevel";
+import type { BudgetMode } from "./BudgetMode";
+import type { BudgetPeriod } from "./BudgetPeriod";
+
+/**
+ * `budget-threshold-reached` 事件载荷
+ */
+export type BudgetThresholdEvent = { tool_id: string, period: BudgetPeriod, level: BudgetLevel, mode: BudgetMode, limit_usd: number, spent_usd: number, };
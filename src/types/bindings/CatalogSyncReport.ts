@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TemplateSyncSummary } from "./TemplateSyncSummary";
+
+/**
+ * 价格目录同步结果
+ */
+export type CatalogSyncReport = { 
+/**
+ * 价格目录地址
+ */
+url: string, 
+/**
+ * 目录未变化（HTTP 304），未修改任何模板
+ */
+not_modified: boolean, templates: Array<TemplateSyncSummary>, 
+/**
+ * 同步时间（Unix 时间戳，毫秒）
+ */
+synced_at: bigint, };
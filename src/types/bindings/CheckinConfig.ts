@@ -0,0 +1,47 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 签到配置
+ */
+export type CheckinConfig = { 
+/**
+ * 是否启用自动签到
+ */
+enabled: boolean, 
+/**
+ * 签到 API 端点
+ */
+endpoint: string, 
+/**
+ * 签到时间范围 - 开始小时 (0-23，默认 0)
+ */
+checkin_hour_start: number, 
+/**
+ * 签到时间范围 - 结束小时 (0-23，默认 0)
+ * start == end 或 start > end 时视为全天 (0-23)
+ */
+checkin_hour_end: number, 
+/**
+ * 下次计划签到时间 (Unix timestamp)，由调度器生成随机时间
+ */
+next_checkin_at: bigint | null, 
+/**
+ * 最后签到时间
+ */
+last_checkin_at: bigint | null, 
+/**
+ * 最后签到状态
+ */
+last_checkin_status: string | null, 
+/**
+ * 最后签到消息
+ */
+last_checkin_message: string | null, 
+/**
+ * 累计签到次数
+ */
+total_checkins: number, 
+/**
+ * 累计获得额度
+ */
+total_quota: bigint, };
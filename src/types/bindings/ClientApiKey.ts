@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 按客户端分发的本地 API Key
+ *
+ * 多台机器或多位成员共用同一代理时各用一个 Key，请求按 Key 的标签分别统计用量
+ */
+export type ClientApiKey = { id: string, 
+/**
+ * 标签（记录到 Token 日志的 `client_key_label`）
+ */
+label: string, api_key: string, 
+/**
+ * 创建时间（Unix 时间戳，毫秒）
+ */
+created_at: number, };
@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ConcurrencyOverflowMode } from "./ConcurrencyOverflowMode";
+
+/**
+ * 并发上游请求上限：限制同时转发到上游的请求数（含流式响应的整个传输过程），
+ * 防止大量并行 Agent 耗尽本地连接或触发上游并发限制
+ */
+export type ConcurrencyLimitConfig = { enabled: boolean, 
+/**
+ * 同时转发到上游的请求数上限
+ */
+max_concurrent: number, overflow: ConcurrencyOverflowMode, 
+/**
+ * 排队模式下单个请求等待名额的时长上限（秒）
+ */
+max_wait_secs: bigint, };
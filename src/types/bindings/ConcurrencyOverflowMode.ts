@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 并发请求数达到上限时新请求的处理方式
+ */
+export type ConcurrencyOverflowMode = "queue" | "reject";
@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 代理实例的并发状态
+ */
+export type ConcurrencyStatus = { 
+/**
+ * 当前正在转发的请求数
+ */
+in_flight: number, 
+/**
+ * 当前等待名额的请求数
+ */
+waiting: number, 
+/**
+ * 并发上限（未启用时为 None）
+ */
+limit: number | null, 
+/**
+ * 启动以来因并发已满被拒绝的请求总数
+ */
+total_rejected: bigint, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 配置目录来源
+ */
+export type ConfigDirSource = "override" | "env" | "default";
@@ -0,0 +1,21 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JsonValue } from "./serde_json/JsonValue";
+
+/**
+ * 配置文件快照
+ */
+export type ConfigSnapshot = { 
+/**
+ * 工具 ID
+ */
+tool_id: string, 
+/**
+ * 配置文件快照（文件名 -> 内容）
+ * 对于 JSON 文件，直接存储 JSON 值
+ * 对于 TOML/ENV 文件，转换为 JSON 对象存储
+ */
+files: { [key in string]?: JsonValue }, 
+/**
+ * 最后更新时间
+ */
+last_updated: string, };
@@ -0,0 +1,34 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { WatchMode } from "./WatchMode";
+import type { WatchedPath } from "./WatchedPath";
+
+/**
+ * 配置监听配置
+ */
+export type ConfigWatchConfig = { 
+/**
+ * 是否启用配置守护
+ */
+enabled: boolean, 
+/**
+ * 监听模式
+ */
+mode: WatchMode, 
+/**
+ * 扫描间隔（秒）
+ */
+scan_interval: bigint, 
+/**
+ * 黑名单字段（按工具分组）
+ * 格式：{ "claude-code": ["env.model", "theme"], ... }
+ */
+blacklist: { [key in string]?: Array<string> }, 
+/**
+ * 敏感字段（按工具分组）
+ * 格式：{ "claude-code": ["env.ANTHROPIC_AUTH_TOKEN", ...], ... }
+ */
+sensitive_fields: { [key in string]?: Array<string> }, 
+/**
+ * 额外监听的路径（如项目内的 `.claude/settings.json` 或 `.gemini/` 目录）
+ */
+watched_paths: Array<WatchedPath>, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 上下文占用级别
+ */
+export type ContextUsageLevel = "warning" | "critical";
@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 请求的输入成本估算
+ */
+export type CostEstimate = { tool_id: string, model: string, 
+/**
+ * 估算的输入 Token 数
+ */
+input_tokens: bigint, 
+/**
+ * 估算的输入成本（USD）
+ */
+input_cost_usd: number, 
+/**
+ * 配置的单次请求上限（USD）
+ */
+threshold_usd: number, };
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CostGuardMode } from "./CostGuardMode";
+
+/**
+ * 单次请求输入成本预检：转发前按请求体估算输入 Token 与成本，防止误发超长上下文
+ */
+export type CostGuardConfig = { enabled: boolean, 
+/**
+ * 单次请求预估输入成本上限（USD）
+ */
+max_input_cost_usd: number, mode: CostGuardMode, 
+/**
+ * 确认模式下等待用户确认的时长（秒），超时视为拒绝
+ */
+confirm_timeout_secs: bigint, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 单次请求预估成本超出阈值时的处理方式
+ */
+export type CostGuardMode = "tag" | "confirm" | "reject";
@@ -0,0 +1,17 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 崩溃报告配置
+ *
+ * 崩溃报告始终只保存在本地 `~/.duckcoding/crashes/`；
+ * 仅在开启上传后，由用户对单个报告手动确认发送。
+ */
+export type CrashReportConfig = { 
+/**
+ * 是否允许发送崩溃报告（默认关闭）
+ */
+send_enabled: boolean, 
+/**
+ * 报告接收地址（POST JSON）
+ */
+endpoint: string | null, };
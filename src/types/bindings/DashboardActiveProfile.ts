@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 工具当前激活的 Profile
+ */
+export type DashboardActiveProfile = { tool_id: string, 
+/**
+ * 激活的 Profile 名称（未激活时为 None）
+ */
+profile: string | null, 
+/**
+ * 切换时间（Unix 时间戳，毫秒）
+ */
+switched_at: bigint | null, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 外部配置变更（不含变更前后的值）
+ */
+export type DashboardConfigEvent = { tool_id: string, 
+/**
+ * 检测时间（Unix 时间戳，毫秒）
+ */
+timestamp: bigint, changed_fields: Array<string>, is_sensitive: boolean, 
+/**
+ * 用户操作（allow/block/superseded/expired，待处理时为 None）
+ */
+action: string | null, };
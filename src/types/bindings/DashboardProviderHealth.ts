@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 上游健康状况（不含历史探测记录）
+ */
+export type DashboardProviderHealth = { tool_id: string, base_url: string, profile_names: Array<string>, 
+/**
+ * 最近一次探测是否可达（尚未探测时为 None）
+ */
+reachable: boolean | null, latency_ms: bigint | null, 
+/**
+ * 最近 24 小时可用率（0-100）
+ */
+uptime_24h: number | null, 
+/**
+ * 最近一次探测时间（Unix 时间戳，毫秒）
+ */
+checked_at: bigint | null, };
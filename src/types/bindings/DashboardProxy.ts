@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 正在运行的透明代理
+ */
+export type DashboardProxy = { tool_id: string, port: number, 
+/**
+ * 当前正在转发的请求数
+ */
+in_flight: number | null, };
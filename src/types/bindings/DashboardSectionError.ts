@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 汇总中获取失败的部分
+ */
+export type DashboardSectionError = { 
+/**
+ * 部分名称（与 [`DashboardSummary`] 字段名一致）
+ */
+section: string, message: string, };
@@ -0,0 +1,23 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 仪表板配置存储
+ */
+export type DashboardStore = { 
+/**
+ * 数据版本
+ */
+version: number, 
+/**
+ * 工具实例选择记录（key: tool_id, value: instance_id）
+ * 例如：{"claude-code": "claude-code-local", "codex": "codex-wsl-Ubuntu"}
+ */
+tool_instance_selections: { [key in string]?: string }, 
+/**
+ * 最后选中的供应商 ID
+ */
+selected_provider_id: string | null, 
+/**
+ * 最后更新时间（Unix 时间戳）
+ */
+updated_at: bigint, };
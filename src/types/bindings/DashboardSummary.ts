@@ -0,0 +1,48 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DashboardActiveProfile } from "./DashboardActiveProfile";
+import type { DashboardConfigEvent } from "./DashboardConfigEvent";
+import type { DashboardProviderHealth } from "./DashboardProviderHealth";
+import type { DashboardProxy } from "./DashboardProxy";
+import type { DashboardSectionError } from "./DashboardSectionError";
+import type { DashboardUpdates } from "./DashboardUpdates";
+
+/**
+ * 仪表板汇总（一次调用返回仪表板首屏所需的全部数据）
+ */
+export type DashboardSummary = { 
+/**
+ * 生成时间（Unix 时间戳，毫秒）
+ */
+generated_at: bigint, 
+/**
+ * 今日（本地时间 0 点起）所有工具的请求数
+ */
+today_requests: bigint, 
+/**
+ * 今日所有工具的总成本（USD）
+ */
+today_cost: number, 
+/**
+ * 正在运行的透明代理
+ */
+active_proxies: Array<DashboardProxy>, 
+/**
+ * 各工具当前激活的 Profile
+ */
+active_profiles: Array<DashboardActiveProfile>, 
+/**
+ * 各工具 Profile 上游的最近探测结果
+ */
+provider_health: Array<DashboardProviderHealth>, 
+/**
+ * 最近一次检测到的外部配置变更
+ */
+last_config_event: DashboardConfigEvent | null, 
+/**
+ * 可用更新
+ */
+updates: DashboardUpdates, 
+/**
+ * 获取失败的部分（其余部分照常返回）
+ */
+errors: Array<DashboardSectionError>, };
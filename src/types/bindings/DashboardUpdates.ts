@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AvailableUpdate } from "./AvailableUpdate";
+
+/**
+ * 可用更新
+ */
+export type DashboardUpdates = { 
+/**
+ * 应用新版本（无更新或检查失败时为 None）
+ */
+app: AvailableUpdate | null, 
+/**
+ * 有新版本的工具
+ */
+tools: Array<AvailableUpdate>, };
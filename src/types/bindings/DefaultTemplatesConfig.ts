@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 工具默认模板配置（存储在 default_templates.json）
+ */
+export type DefaultTemplatesConfig = { 
+/**
+ * 配置版本号（用于自动迁移）
+ */
+version: number, } & ({ [key in string]?: string });
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 下载进度
+ */
+export type DownloadProgress = { downloaded_bytes: bigint, total_bytes: bigint, percentage: number, speed: bigint | null, eta: number | null, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { EgressMode } from "./EgressMode";
+import type { EgressProxyType } from "./EgressProxyType";
+
+/**
+ * 工具级出口代理：透明代理转发上游请求时使用
+ *
+ * 例如 Claude 流量走 VPN 隧道，Gemini 保持直连
+ */
+export type EgressProxyConfig = { mode: EgressMode, proxy_type: EgressProxyType, host: string, port: number, username?: string | null, password?: string | null, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 出口代理协议
+ */
+export type EgressProxyType = "http" | "https" | "socks5";
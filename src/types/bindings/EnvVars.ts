@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 环境变量配置
+ */
+export type EnvVars = { api_key: string, base_url: string, };
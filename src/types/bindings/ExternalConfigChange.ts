@@ -0,0 +1,23 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FieldChange } from "./FieldChange";
+
+/**
+ * 外部配置变更事件
+ */
+export type ExternalConfigChange = { 
+/**
+ * 工具 ID
+ */
+tool_id: string, 
+/**
+ * 配置文件路径
+ */
+path: string, 
+/**
+ * 变更字段列表
+ */
+changed_fields: Array<FieldChange>, 
+/**
+ * 是否包含敏感字段
+ */
+is_sensitive: boolean, };
@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { UpstreamStatus } from "./UpstreamStatus";
+
+/**
+ * 代理实例的故障转移状态
+ */
+export type FailoverStatus = { 
+/**
+ * 当前活跃上游地址（尚无成功请求时为 None）
+ */
+active_base_url: string | null, 
+/**
+ * 最近一次切换活跃上游的时间（Unix 时间戳，毫秒）
+ */
+last_failover_at: bigint | null, upstreams: Array<UpstreamStatus>, 
+/**
+ * 当前 Profile 主上游的连续错误次数（Profile 自动切换计数）
+ */
+profile_consecutive_errors: number, };
@@ -0,0 +1,24 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ChangeType } from "./ChangeType";
+import type { JsonValue } from "./serde_json/JsonValue";
+
+/**
+ * 单个字段的变更
+ */
+export type FieldChange = { 
+/**
+ * 字段路径
+ */
+path: string, 
+/**
+ * 旧值（删除时为 Some，新增时为 None）
+ */
+old_value: JsonValue | null, 
+/**
+ * 新值（新增时为 Some，删除时为 None）
+ */
+new_value: JsonValue | null, 
+/**
+ * 变更类型
+ */
+change_type: ChangeType, };
@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 请求流水导出（Flight Recorder）配置
+ *
+ * 启用后每条完成的代理请求以一行 NDJSON 实时追加到指定文件
+ */
+export type FlightRecorderConfig = { 
+/**
+ * 是否启用
+ */
+enabled: boolean, 
+/**
+ * 输出文件路径（支持 `~/` 前缀）
+ */
+file_path: string | null, };
@@ -0,0 +1,97 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AdminApiConfig } from "./AdminApiConfig";
+import type { BalanceMonitorConfig } from "./BalanceMonitorConfig";
+import type { ConfigWatchConfig } from "./ConfigWatchConfig";
+import type { CrashReportConfig } from "./CrashReportConfig";
+import type { LegacyToolProxyConfig } from "./LegacyToolProxyConfig";
+import type { LogConfig } from "./LogConfig";
+import type { MaintenanceConfig } from "./MaintenanceConfig";
+import type { McpConfig } from "./McpConfig";
+import type { MetricsConfig } from "./MetricsConfig";
+import type { OnboardingStatus } from "./OnboardingStatus";
+import type { PricingSyncConfig } from "./PricingSyncConfig";
+import type { StartupMode } from "./StartupMode";
+import type { TokenStatsConfig } from "./TokenStatsConfig";
+
+export type GlobalConfig = { 
+/**
+ * 配置文件版本（用于迁移管理）
+ * 默认值为 "0.0.0"，迁移后更新为对应的应用版本号
+ */
+version: string | null, 
+/**
+ * 已废弃，由供应商系统管理
+ */
+user_id: string | null, 
+/**
+ * 已废弃，由供应商系统管理
+ */
+system_token: string | null, proxy_enabled: boolean, proxy_type: string | null, proxy_host: string | null, proxy_port: string | null, proxy_username: string | null, proxy_password: string | null, proxy_bypass_urls: Array<string>, proxy_configs: { [key in string]?: LegacyToolProxyConfig }, session_endpoint_config_enabled: boolean, hide_transparent_proxy_tip: boolean, hide_session_config_hint: boolean, log_config: LogConfig, onboarding_status: OnboardingStatus | null, 
+/**
+ * 外部改动监听是否开启（notify + 轮询）
+ */
+external_watch_enabled: boolean, 
+/**
+ * 外部改动轮询间隔（毫秒），用于前端补偿刷新
+ */
+external_poll_interval_ms: bigint, 
+/**
+ * 单实例模式开关（默认启用，仅生产环境生效）
+ */
+single_instance_enabled: boolean, 
+/**
+ * 开机自启动开关（默认关闭）
+ */
+startup_enabled: boolean, 
+/**
+ * 开机自启动时的启动方式
+ */
+startup_mode: StartupMode, 
+/**
+ * 配置监听配置
+ */
+config_watch: ConfigWatchConfig, 
+/**
+ * Token统计配置
+ */
+token_stats_config: TokenStatsConfig, 
+/**
+ * 工具配置目录覆盖（tool_id -> 目录），用于配置目录不在默认位置的情况
+ */
+tool_config_dirs: { [key in string]?: string }, 
+/**
+ * 只读观察模式：统计与变更检测照常，但绝不写入工具配置文件
+ */
+observer_mode: boolean, 
+/**
+ * 夜间维护窗口
+ */
+maintenance: MaintenanceConfig, 
+/**
+ * Prometheus 指标导出
+ */
+metrics: MetricsConfig, 
+/**
+ * 价格目录远程同步
+ */
+pricing_sync: PricingSyncConfig, 
+/**
+ * 本机管理 API
+ */
+admin_api: AdminApiConfig, 
+/**
+ * MCP 服务
+ */
+mcp: McpConfig, 
+/**
+ * 余额监控
+ */
+balance_monitor: BalanceMonitorConfig, 
+/**
+ * 崩溃报告
+ */
+crash_report: CrashReportConfig, 
+/**
+ * 额外加入命令搜索路径的目录（环境诊断修复时追加）
+ */
+extra_paths: Array<string>, };
@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 单个模型的继承配置
+ */
+export type InheritedModel = { 
+/**
+ * 模型名称（如："claude-sonnet-4.5"）
+ */
+model_name: string, 
+/**
+ * 从哪个模板继承（如："claude_official_2025_01"）
+ */
+source_template_id: string, 
+/**
+ * 倍率（应用到继承的价格上）
+ */
+multiplier: number, 
+/**
+ * 别名匹配优先级（多条继承配置匹配同一模型时数值大者优先，默认 0）
+ */
+priority?: number, };
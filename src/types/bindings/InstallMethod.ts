@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 安装方法
+ */
+export type InstallMethod = "Official" | "Npm" | "Brew" | "Other";
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Key 池负载均衡策略
+ */
+export type KeyBalanceMode = "round_robin" | "weighted";
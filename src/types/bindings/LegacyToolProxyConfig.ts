@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 单个工具的透明代理配置（旧版，保存在全局配置中；新版见 `proxy_config::ToolProxyConfig`）
+ */
+export type LegacyToolProxyConfig = { enabled: boolean, port: number, local_api_key: string | null, real_api_key: string | null, real_base_url: string | null, real_model_provider: string | null, real_profile_name: string | null, allow_public: boolean, session_endpoint_config_enabled: boolean, auto_start: boolean, 
+/**
+ * 启动代理前激活的 Profile 名称（用于关闭时还原）
+ */
+original_active_profile?: string | null, 
+/**
+ * 价格模板 ID（用于成本计算）
+ */
+pricing_template_id?: string | null, };
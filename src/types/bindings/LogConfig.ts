@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LogFormat } from "./LogFormat";
+import type { LogLevel } from "./LogLevel";
+import type { LogOutput } from "./LogOutput";
+import type { LogRotationConfig } from "./LogRotationConfig";
+
+/**
+ * 日志系统配置
+ */
+export type LogConfig = { level: LogLevel, format: LogFormat, output: LogOutput, file_path: string | null, 
+/**
+ * 日志文件滚动与保留策略
+ */
+rotation: LogRotationConfig, };
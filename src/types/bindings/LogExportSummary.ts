@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LogExportFormat } from "./LogExportFormat";
+
+/**
+ * 导出结果
+ */
+export type LogExportSummary = { path: string, format: LogExportFormat, 
+/**
+ * 导出的日志条数
+ */
+row_count: bigint, 
+/**
+ * 文件大小（字节）
+ */
+bytes: bigint, };
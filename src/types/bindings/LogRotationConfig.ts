@@ -0,0 +1,25 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 日志文件滚动与保留策略
+ *
+ * 当前日志写入 `duckcoding.log`，超过大小上限或跨天时滚动为
+ * `duckcoding.YYYYMMDD-HHMMSS.log`（可选 gzip 压缩）
+ */
+export type LogRotationConfig = { 
+/**
+ * 单个日志文件大小上限（MB）
+ */
+max_file_size_mb: number, 
+/**
+ * 保留的历史日志文件数（不含当前文件）
+ */
+max_files: number, 
+/**
+ * 历史日志保留天数（0 表示不按天数清理）
+ */
+max_age_days: number, 
+/**
+ * 是否 gzip 压缩滚动出的历史日志
+ */
+compress: boolean, };
@@ -0,0 +1,37 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 夜间维护窗口配置
+ *
+ * 在窗口内排空并重启透明代理、回写数据库 WAL、清理过期日志与统计数据。
+ * 存在活跃会话时推迟到窗口内的下一次检查。
+ */
+export type MaintenanceConfig = { 
+/**
+ * 是否启用（默认关闭）
+ */
+enabled: boolean, 
+/**
+ * 窗口开始时间（本地时间，HH:MM）
+ */
+window_start: string, 
+/**
+ * 窗口时长（分钟）
+ */
+window_minutes: number, 
+/**
+ * 是否重启运行中的透明代理
+ */
+restart_proxies: boolean, 
+/**
+ * 最近多少分钟内有请求视为会话活跃
+ */
+idle_minutes: number, 
+/**
+ * 排空代理连接的最长等待时间（秒）
+ */
+drain_timeout_secs: bigint, 
+/**
+ * 滚动日志保留天数
+ */
+log_retention_days: number, };
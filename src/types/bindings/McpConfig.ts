@@ -0,0 +1,25 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * MCP 服务配置
+ *
+ * 启用后 AI 编程工具可通过 MCP（Model Context Protocol）查询用量、预算并切换 Profile：
+ * 标准输入输出方式由工具启动 `duckcoding mcp` 子进程；HTTP 方式由界面在本机端口监听。
+ */
+export type McpConfig = { 
+/**
+ * 是否启用（默认关闭，关闭时 `duckcoding mcp` 拒绝启动）
+ */
+enabled: boolean, 
+/**
+ * 是否同时在本机端口提供 HTTP 接入（仅监听 127.0.0.1）
+ */
+http_enabled: boolean, 
+/**
+ * HTTP 监听端口
+ */
+port: number, 
+/**
+ * HTTP 访问令牌（启用 HTTP 时为空则自动生成）
+ */
+token: string | null, };
@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { McpConfig } from "./McpConfig";
+
+/**
+ * MCP 服务状态
+ */
+export type McpStatus = { config: McpConfig, 
+/**
+ * HTTP 接入正在监听
+ */
+http_running: boolean, 
+/**
+ * HTTP 接入地址（未运行时为 None）
+ */
+endpoint: string | null, 
+/**
+ * 标准输入输出方式的启动命令（供 AI 编程工具配置）
+ */
+stdio_command: string | null, };
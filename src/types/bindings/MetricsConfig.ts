@@ -0,0 +1,20 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Prometheus 指标导出配置
+ *
+ * 启用后在独立端口提供 `/metrics`，供监控系统抓取透明代理的请求、Token、成本与耗时指标。
+ */
+export type MetricsConfig = { 
+/**
+ * 是否启用（默认关闭）
+ */
+enabled: boolean, 
+/**
+ * 监听端口
+ */
+port: number, 
+/**
+ * 是否监听所有网卡（默认仅本机）
+ */
+allow_public: boolean, };
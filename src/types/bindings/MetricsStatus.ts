@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MetricsConfig } from "./MetricsConfig";
+
+/**
+ * 指标监听状态
+ */
+export type MetricsStatus = { config: MetricsConfig, running: boolean, 
+/**
+ * 抓取地址（未运行时为 None）
+ */
+endpoint: string | null, };
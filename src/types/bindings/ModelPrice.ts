@@ -0,0 +1,55 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PriceTier } from "./PriceTier";
+
+/**
+ * 单个模型的价格定义
+ */
+export type ModelPrice = { 
+/**
+ * 提供商（如：anthropic、openai）
+ */
+provider: string, 
+/**
+ * 输入价格（USD/百万 Token）
+ */
+input_price_per_1m: number, 
+/**
+ * 输出价格（USD/百万 Token）
+ */
+output_price_per_1m: number, 
+/**
+ * 缓存写入价格 - 5分钟TTL（USD/百万 Token，可选）
+ */
+cache_write_price_per_1m: number | null, 
+/**
+ * 缓存写入价格 - 1小时TTL（USD/百万 Token，可选）
+ */
+cache_write_1h_price_per_1m?: number | null, 
+/**
+ * 缓存读取价格（USD/百万 Token，可选，5m和1h读取价格相同）
+ */
+cache_read_price_per_1m: number | null, 
+/**
+ * 推理输出价格（USD/百万 Token，可选，如 OpenAI o1 系列）
+ */
+reasoning_output_price_per_1m: number | null, 
+/**
+ * 货币类型（默认：USD）
+ */
+currency: string, 
+/**
+ * 模型别名列表（支持多种 ID 格式）
+ */
+aliases: Array<string>, 
+/**
+ * 别名匹配优先级（多个模型共用同一别名时数值大者优先，默认 0）
+ */
+priority?: number, 
+/**
+ * 阶梯价格（按当月累计用量切换，未达到任何阈值时使用上面的基础价格）
+ */
+tiers?: Array<PriceTier>, 
+/**
+ * 上下文窗口（最大输入 Token 数，未设置时按内置模型族默认值）
+ */
+context_window?: bigint | null, };
@@ -0,0 +1,26 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 模型路由规则
+ */
+export type ModelRoutingRule = { id: string, 
+/**
+ * 模型名 glob（`*` 匹配任意字符，`?` 匹配单个字符，不区分大小写），如 `claude-haiku-*`
+ */
+model_pattern: string, 
+/**
+ * 目标 Profile 名称
+ */
+profile_name: string, 
+/**
+ * 保存规则时从 Profile 解析的上游地址
+ */
+base_url: string, 
+/**
+ * 保存规则时从 Profile 解析的 API Key
+ */
+api_key: string, 
+/**
+ * Profile 的价格模板（命中规则的请求按此计费）
+ */
+pricing_template_id?: string | null, enabled: boolean, };
@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 新用户引导状态
+ */
+export type OnboardingStatus = { 
+/**
+ * 已完成的引导版本（例如："v1", "v2"）
+ */
+completed_version: string, 
+/**
+ * 跳过的步骤 ID 列表
+ */
+skipped_steps: Array<string>, 
+/**
+ * 完成时间戳（ISO 8601 格式）
+ */
+completed_at: string | null, };
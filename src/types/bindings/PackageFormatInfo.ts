@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 包格式信息
+ */
+export type PackageFormatInfo = { platform: string, preferred_formats: Array<string>, fallback_format: string, };
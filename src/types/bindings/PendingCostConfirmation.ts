@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CostEstimate } from "./CostEstimate";
+
+/**
+ * 等待用户确认的请求（`cost-guard-confirmation` 事件载荷）
+ */
+export type PendingCostConfirmation = { id: string, estimate: CostEstimate, 
+/**
+ * 创建时间（Unix 时间戳，毫秒）
+ */
+created_at: bigint, 
+/**
+ * 超时时间（Unix 时间戳，毫秒），超时后请求被拒绝
+ */
+expires_at: bigint, };
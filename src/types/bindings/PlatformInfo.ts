@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 平台信息
+ */
+export type PlatformInfo = { os: string, arch: string, is_windows: boolean, is_macos: boolean, is_linux: boolean, };
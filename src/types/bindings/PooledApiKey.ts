@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Key 池中的单个 API Key
+ */
+export type PooledApiKey = { 
+/**
+ * 别名（记录到 Token 日志的 `upstream_key_alias`，用于按 Key 统计用量）
+ */
+alias: string, api_key: string, 
+/**
+ * 权重（加权模式按比例分配请求；0 表示停用该 Key）
+ */
+weight: number, };
@@ -0,0 +1,34 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 阶梯价格档位
+ */
+export type PriceTier = { 
+/**
+ * 当月该模型累计 Token 数（输入 + 输出 + 缓存）达到该值后适用本档价格
+ */
+threshold_tokens: bigint, 
+/**
+ * 输入价格（USD/百万 Token）
+ */
+input_price_per_1m: number, 
+/**
+ * 输出价格（USD/百万 Token）
+ */
+output_price_per_1m: number, 
+/**
+ * 缓存写入价格 - 5分钟TTL（未设置时沿用基础价格，下同）
+ */
+cache_write_price_per_1m?: number | null, 
+/**
+ * 缓存写入价格 - 1小时TTL
+ */
+cache_write_1h_price_per_1m?: number | null, 
+/**
+ * 缓存读取价格
+ */
+cache_read_price_per_1m?: number | null, 
+/**
+ * 推理输出价格
+ */
+reasoning_output_price_per_1m?: number | null, };
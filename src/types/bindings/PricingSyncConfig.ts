@@ -0,0 +1,20 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 价格目录远程同步配置
+ *
+ * 定期下载公开的模型价格目录（LiteLLM 格式），将新模型与价格变动合并到内置价格模板。
+ */
+export type PricingSyncConfig = { 
+/**
+ * 是否定期同步（默认开启）
+ */
+enabled: boolean, 
+/**
+ * 价格目录地址（为空时使用内置镜像地址）
+ */
+catalog_url: string | null, 
+/**
+ * 同步间隔（小时）
+ */
+interval_hours: number, };
@@ -0,0 +1,56 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { InheritedModel } from "./InheritedModel";
+import type { ModelPrice } from "./ModelPrice";
+
+/**
+ * 价格模板（统一结构，支持三种模式）
+ */
+export type PricingTemplate = { 
+/**
+ * 模板ID（唯一标识）
+ */
+id: string, 
+/**
+ * 模板名称
+ */
+name: string, 
+/**
+ * 模板描述
+ */
+description: string, 
+/**
+ * 模板版本
+ */
+version: string, 
+/**
+ * 创建时间（Unix 时间戳，毫秒）
+ */
+created_at: bigint, 
+/**
+ * 更新时间（Unix 时间戳，毫秒）
+ */
+updated_at: bigint, 
+/**
+ * 继承配置（每个模型独立配置，可从不同模板继承）
+ */
+inherited_models: Array<InheritedModel>, 
+/**
+ * 自定义模型（直接定义价格）
+ */
+custom_models: { [key in string]?: ModelPrice }, 
+/**
+ * 标签列表（用于分类和搜索）
+ */
+tags: Array<string>, 
+/**
+ * 是否为内置预设模板
+ */
+is_default_preset: boolean, 
+/**
+ * 按次附加费（USD，每个产生用量的请求额外计费）
+ */
+per_request_fee: number, 
+/**
+ * 月最低消费（USD，当月用量成本不足时按此金额结算）
+ */
+min_monthly_fee: number, };
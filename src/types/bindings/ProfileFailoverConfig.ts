@@ -0,0 +1,17 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Profile 自动切换配置
+ *
+ * 与 `upstreams` 的单请求重试不同，这里在当前 Profile 的主上游连续出错达到阈值后，
+ * 将代理配置整体切换到备用 Profile（写回配置文件，切换后不会自动切回）。
+ */
+export type ProfileFailoverConfig = { enabled: boolean, 
+/**
+ * 备用 Profile 名称
+ */
+backup_profile?: string | null, 
+/**
+ * 触发切换的连续错误次数（请求失败或返回 5xx/429）
+ */
+error_threshold: number, };
@@ -0,0 +1,51 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CheckinConfig } from "./CheckinConfig";
+
+/**
+ * 供应商配置
+ */
+export type Provider = { 
+/**
+ * 唯一标识
+ */
+id: string, 
+/**
+ * 供应商名称（如 DuckCoding）
+ */
+name: string, 
+/**
+ * 官网地址
+ */
+website_url: string, 
+/**
+ * API 地址（可选，优先于 website_url 用于 API 调用）
+ */
+api_address: string | null, 
+/**
+ * 用户ID
+ */
+user_id: string, 
+/**
+ * 系统访问令牌
+ */
+access_token: string, 
+/**
+ * 用户名（可选，用于确认）
+ */
+username: string | null, 
+/**
+ * 是否为默认供应商
+ */
+is_default: boolean, 
+/**
+ * 创建时间
+ */
+created_at: bigint, 
+/**
+ * 更新时间
+ */
+updated_at: bigint, 
+/**
+ * 签到配置
+ */
+checkin_config: CheckinConfig | null, };
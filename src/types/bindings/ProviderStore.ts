@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Provider } from "./Provider";
+
+/**
+ * 供应商存储结构
+ */
+export type ProviderStore = { 
+/**
+ * 数据版本
+ */
+version: number, 
+/**
+ * 供应商列表
+ */
+providers: Array<Provider>, 
+/**
+ * 最后更新时间
+ */
+updated_at: bigint, };
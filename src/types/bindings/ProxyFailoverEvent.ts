@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * `proxy-failover` 事件载荷：Profile 自动切换完成
+ */
+export type ProxyFailoverEvent = { tool_id: string, 
+/**
+ * 切换前的 Profile
+ */
+from_profile: string | null, 
+/**
+ * 切换后的备用 Profile
+ */
+to_profile: string, 
+/**
+ * 触发切换时的连续错误次数
+ */
+consecutive_errors: number, last_error: string | null, 
+/**
+ * 切换时间（Unix 时间戳，毫秒）
+ */
+switched_at: bigint, };
@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ProxyMetadata } from "./ProxyMetadata";
+import type { ToolProxyConfig } from "./ToolProxyConfig";
+
+/**
+ * proxy.json 顶层结构
+ */
+export type ProxyStore = { version: string, "claude-code": ToolProxyConfig, codex: ToolProxyConfig, "gemini-cli": ToolProxyConfig, "amp-code": ToolProxyConfig, 
+/**
+ * 检测到配置指向未运行的代理时自动还原原始配置（默认开启）
+ */
+auto_restore_stale_config: boolean, 
+/**
+ * 自定义工具（tools.d）的代理配置，按工具 ID 存储
+ */
+custom_tools?: { [key in string]?: ToolProxyConfig }, metadata: ProxyMetadata, };
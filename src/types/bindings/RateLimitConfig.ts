@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 本地限流配置（滑动 1 分钟窗口，超出时直接返回 429，不转发到上游）
+ */
+export type RateLimitConfig = { enabled: boolean, 
+/**
+ * 每分钟请求数上限（0 表示不限制）
+ */
+requests_per_minute: number, 
+/**
+ * 每分钟 Token 数上限（输入 + 输出，0 表示不限制）
+ */
+tokens_per_minute: number, 
+/**
+ * 按会话分别计算额度（关闭时整个工具共享额度）
+ */
+per_session: boolean, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 请求内容命中脱敏规则时的处理方式
+ */
+export type RedactionAction = "mask" | "block";
@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RedactionAction } from "./RedactionAction";
+import type { RedactionRule } from "./RedactionRule";
+
+/**
+ * 请求内容脱敏（转发前扫描请求体中的密钥、内部主机名、邮箱等）
+ */
+export type RedactionConfig = { enabled: boolean, action: RedactionAction, 
+/**
+ * 启用内置规则（常见 API Key 格式、私钥、邮箱）
+ */
+builtin_rules: boolean, 
+/**
+ * 自定义规则（如内部主机名）
+ */
+rules?: Array<RedactionRule>, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 自定义脱敏规则
+ */
+export type RedactionRule = { 
+/**
+ * 规则名称（记录到命中统计）
+ */
+name: string, 
+/**
+ * 正则表达式
+ */
+pattern: string, enabled: boolean, };
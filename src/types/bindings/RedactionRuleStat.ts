@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 单条规则的命中汇总
+ */
+export type RedactionRuleStat = { rule_name: string, 
+/**
+ * 命中总次数
+ */
+hits: bigint, 
+/**
+ * 命中该规则的请求数
+ */
+requests: bigint, };
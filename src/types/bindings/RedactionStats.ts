@@ -0,0 +1,27 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RedactionRuleStat } from "./RedactionRuleStat";
+
+/**
+ * 脱敏命中统计
+ */
+export type RedactionStats = { 
+/**
+ * 命中任一规则的请求数
+ */
+total_requests: bigint, 
+/**
+ * 脱敏后转发的请求数
+ */
+masked_requests: bigint, 
+/**
+ * 被拦截的请求数
+ */
+blocked_requests: bigint, 
+/**
+ * 命中总次数
+ */
+total_hits: bigint, 
+/**
+ * 按规则汇总（命中次数降序）
+ */
+by_rule: Array<RedactionRuleStat>, };
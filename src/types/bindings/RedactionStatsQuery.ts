@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 命中统计查询条件
+ */
+export type RedactionStatsQuery = { tool_type: string | null, 
+/**
+ * 开始时间戳（毫秒，含）
+ */
+start_time: bigint | null, 
+/**
+ * 结束时间戳（毫秒，不含）
+ */
+end_time: bigint | null, };
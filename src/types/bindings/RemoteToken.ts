@@ -0,0 +1,70 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 远程令牌（从 NEW API 拉取，不本地持久化）
+ */
+export type RemoteToken = { 
+/**
+ * 令牌 ID
+ */
+id: bigint, 
+/**
+ * 用户 ID
+ */
+user_id: bigint, 
+/**
+ * 令牌名称
+ */
+name: string, 
+/**
+ * 令牌密钥
+ */
+key: string, 
+/**
+ * 所属分组
+ */
+group: string, 
+/**
+ * 剩余额度
+ */
+remain_quota: bigint, 
+/**
+ * 已使用额度
+ */
+used_quota: bigint, 
+/**
+ * 过期时间（Unix 时间戳，-1 表示永不过期）
+ */
+expired_time: bigint, 
+/**
+ * 状态（1=启用，2=禁用）
+ */
+status: number, 
+/**
+ * 是否无限额度
+ */
+unlimited_quota: boolean, 
+/**
+ * 是否启用模型限制
+ */
+model_limits_enabled: boolean, 
+/**
+ * 模型限制（逗号分隔的模型列表）
+ */
+model_limits: string, 
+/**
+ * 允许的 IP 地址（逗号分隔）
+ */
+allow_ips: string, 
+/**
+ * 是否支持跨分组重试
+ */
+cross_group_retry: boolean, 
+/**
+ * 创建时间（Unix 时间戳）
+ */
+created_time: bigint, 
+/**
+ * 最后访问时间（Unix 时间戳）
+ */
+accessed_time: bigint, };
@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 远程令牌分组（前端展示用）
+ */
+export type RemoteTokenGroup = { 
+/**
+ * 分组 ID（即分组名称）
+ */
+id: string, 
+/**
+ * 分组描述
+ */
+desc: string, 
+/**
+ * 倍率
+ */
+ratio: number, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 远程令牌分组（API 返回的分组信息）
+ */
+export type RemoteTokenGroupInfo = { 
+/**
+ * 分组描述
+ */
+desc: string, 
+/**
+ * 倍率
+ */
+ratio: number, };
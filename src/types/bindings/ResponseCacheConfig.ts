@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 响应缓存配置：缓存模型列表等幂等接口的成功响应，命中时不转发到上游
+ */
+export type ResponseCacheConfig = { enabled: boolean, 
+/**
+ * 缓存有效期（秒）
+ */
+ttl_secs: bigint, 
+/**
+ * 缓存条目数上限（超出时淘汰最早写入的条目）
+ */
+max_entries: number, 
+/**
+ * 同时缓存请求体完全相同的 Embedding 请求
+ */
+cache_embeddings: boolean, };
@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 单个工具的缓存统计
+ */
+export type ResponseCacheStats = { tool_id: string, 
+/**
+ * 当前缓存条目数（含已过期但尚未清理的条目）
+ */
+entries: number, 
+/**
+ * 缓存响应体总字节数
+ */
+total_bytes: bigint, 
+/**
+ * 启动以来的命中次数
+ */
+hits: bigint, 
+/**
+ * 启动以来的未命中次数
+ */
+misses: bigint, };
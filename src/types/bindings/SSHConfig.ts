@@ -0,0 +1,26 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * SSH 连接配置
+ */
+export type SSHConfig = { 
+/**
+ * 显示名称（如"开发服务器"、"生产环境"）
+ */
+display_name: string, 
+/**
+ * 主机地址
+ */
+host: string, 
+/**
+ * 端口
+ */
+port: number, 
+/**
+ * 用户名
+ */
+user: string, 
+/**
+ * SSH 密钥路径（可选）
+ */
+key_path: string | null, };
@@ -0,0 +1,27 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ContextUsageLevel } from "./ContextUsageLevel";
+
+/**
+ * 会话的上下文窗口占用（`session-context-warning` 事件载荷）
+ */
+export type SessionContextUsage = { 
+/**
+ * 完整会话 ID（会话表主键）
+ */
+session_id: string, tool_id: string, model: string, 
+/**
+ * 最近一次请求的提示词 Token 数（输入 + 缓存创建 + 缓存读取）
+ */
+prompt_tokens: bigint, 
+/**
+ * 模型上下文窗口（未知模型为 None）
+ */
+context_window: bigint | null, 
+/**
+ * 占用百分比
+ */
+percent: number | null, level: ContextUsageLevel | null, 
+/**
+ * 最近一次请求时间（Unix 时间戳，毫秒）
+ */
+updated_at: bigint, };
@@ -0,0 +1,30 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 会话统计数据
+ */
+export type SessionStats = { 
+/**
+ * 总输入Token数量
+ */
+total_input: bigint, 
+/**
+ * 总输出Token数量
+ */
+total_output: bigint, 
+/**
+ * 总缓存创建Token数量
+ */
+total_cache_creation: bigint, 
+/**
+ * 总缓存读取Token数量
+ */
+total_cache_read: bigint, 
+/**
+ * 总推理Token数量
+ */
+total_reasoning: bigint, 
+/**
+ * 请求总数
+ */
+request_count: bigint, };
@@ -0,0 +1,28 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 非标准 SSE 兼容选项（改写转发给客户端的流，默认全部关闭）
+ *
+ * 会话诊断中记录的 SSE 差异可作为开启依据
+ */
+export type SseCompatConfig = { 
+/**
+ * 去除流首 UTF-8 BOM
+ */
+strip_bom: boolean, 
+/**
+ * 丢弃 `:` 开头的注释 / keep-alive 行
+ */
+drop_comments: boolean, 
+/**
+ * 为 `data:` 补齐空格
+ */
+fix_data_prefix: boolean, 
+/**
+ * 将 `\r\n`、`\r` 统一为 `\n`
+ */
+normalize_line_endings: boolean, 
+/**
+ * 根据 data 中的 `"type"` 补齐缺失的 `event:` 字段
+ */
+fill_event_names: boolean, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 开机自启动时的启动方式
+ */
+export type StartupMode = "window" | "minimized" | "background";
@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 单个价格模板的同步结果
+ */
+export type TemplateSyncSummary = { template_id: string, 
+/**
+ * 新增的模型
+ */
+added: Array<string>, 
+/**
+ * 价格有变动的模型
+ */
+updated: Array<string>, 
+/**
+ * 价格未变化的模型数
+ */
+unchanged: number, };
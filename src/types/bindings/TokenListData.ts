@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RemoteToken } from "./RemoteToken";
+
+/**
+ * NEW API 令牌列表响应的 data 部分
+ */
+export type TokenListData = { page: number, page_size: number, total: number, items: Array<RemoteToken>, };
@@ -0,0 +1,126 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Token日志记录
+ */
+export type TokenLog = { 
+/**
+ * 主键ID（自增）
+ */
+id: bigint | null, 
+/**
+ * 工具类型：claude_code, codex, gemini_cli
+ */
+tool_type: string, 
+/**
+ * 请求时间戳（毫秒）
+ */
+timestamp: bigint, 
+/**
+ * 客户端IP地址
+ */
+client_ip: string, 
+/**
+ * 会话ID
+ */
+session_id: string, 
+/**
+ * 使用的配置名称
+ */
+config_name: string, 
+/**
+ * 模型名称
+ */
+model: string, 
+/**
+ * API返回的消息ID
+ */
+message_id: string | null, 
+/**
+ * 输入Token数量
+ */
+input_tokens: bigint, 
+/**
+ * 输出Token数量
+ */
+output_tokens: bigint, 
+/**
+ * 缓存创建Token数量（5m + 1h 总量）
+ */
+cache_creation_tokens: bigint, 
+/**
+ * 1小时缓存创建Token数量
+ */
+cache_creation_1h_tokens: bigint, 
+/**
+ * 缓存读取Token数量
+ */
+cache_read_tokens: bigint, 
+/**
+ * 推理Token数量（如 OpenAI o1 系列）
+ */
+reasoning_tokens: bigint, 
+/**
+ * 请求状态：success, failed
+ */
+request_status: string, 
+/**
+ * 响应类型：sse, json, unknown
+ */
+response_type: string, 
+/**
+ * 错误类型：parse_error, request_interrupted, upstream_error（成功时为None）
+ */
+error_type: string | null, 
+/**
+ * 错误详情（成功时为None）
+ */
+error_detail: string | null, 
+/**
+ * 响应时间（毫秒）
+ */
+response_time_ms: bigint | null, 
+/**
+ * 输入部分价格（USD）
+ */
+input_price: number | null, 
+/**
+ * 输出部分价格（USD）
+ */
+output_price: number | null, 
+/**
+ * 缓存写入部分价格（USD）
+ */
+cache_write_price: number | null, 
+/**
+ * 缓存读取部分价格（USD）
+ */
+cache_read_price: number | null, 
+/**
+ * 推理Token部分价格（USD）
+ */
+reasoning_price: number | null, 
+/**
+ * 总成本（USD）
+ */
+total_cost: number, 
+/**
+ * 使用的价格模板ID
+ */
+pricing_template_id: string | null, 
+/**
+ * 捕获的上游响应头（JSON 对象，如请求 ID、路由区域）
+ */
+upstream_headers?: string | null, 
+/**
+ * Key 池中本次使用的 Key 别名（用于按 Key 统计用量）
+ */
+upstream_key_alias?: string | null, 
+/**
+ * 鉴权所用的客户端 Key 标签（多个客户端共用代理时按客户端统计用量）
+ */
+client_key_label?: string | null, 
+/**
+ * 会话标签（写入时从会话继承，用于按项目归集花费）
+ */
+tags?: Array<string>, };
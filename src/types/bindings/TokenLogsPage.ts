@@ -0,0 +1,23 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TokenLog } from "./TokenLog";
+
+/**
+ * 分页查询结果
+ */
+export type TokenLogsPage = { 
+/**
+ * 日志列表
+ */
+logs: Array<TokenLog>, 
+/**
+ * 总记录数
+ */
+total: bigint, 
+/**
+ * 当前页码
+ */
+page: number, 
+/**
+ * 每页大小
+ */
+page_size: number, };
@@ -0,0 +1,37 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FlightRecorderConfig } from "./FlightRecorderConfig";
+import type { UsageAnomalyConfig } from "./UsageAnomalyConfig";
+import type { UsageReportConfig } from "./UsageReportConfig";
+
+/**
+ * Token统计配置
+ */
+export type TokenStatsConfig = { 
+/**
+ * 数据保留天数（None表示不限制）
+ */
+retention_days: number | null, 
+/**
+ * 最大日志条数（None表示不限制）
+ */
+max_log_count: number | null, 
+/**
+ * 是否启用自动清理
+ */
+auto_cleanup_enabled: boolean, 
+/**
+ * 数据库大小上限（MB，None 表示不限制）；超出时后台维护删除最旧的日志
+ */
+max_db_size_mb: number | null, 
+/**
+ * 请求流水导出配置
+ */
+flight_recorder: FlightRecorderConfig, 
+/**
+ * 用量异常检测配置
+ */
+anomaly_detection: UsageAnomalyConfig, 
+/**
+ * 用量报告配置
+ */
+usage_report: UsageReportConfig, };
@@ -0,0 +1,34 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Token日志查询参数
+ */
+export type TokenStatsQuery = { 
+/**
+ * 工具类型筛选
+ */
+tool_type: string | null, 
+/**
+ * 会话ID筛选
+ */
+session_id: string | null, 
+/**
+ * 配置名称筛选
+ */
+config_name: string | null, 
+/**
+ * 开始时间戳（毫秒）
+ */
+start_time: bigint | null, 
+/**
+ * 结束时间戳（毫秒）
+ */
+end_time: bigint | null, 
+/**
+ * 分页：页码（从0开始）
+ */
+page: number, 
+/**
+ * 分页：每页大小
+ */
+page_size: number, };
@@ -0,0 +1,30 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * `token-usage-delta` 事件载荷（数值均为该请求截至目前的累计值）
+ */
+export type TokenUsageDelta = { tool_id: string, 
+/**
+ * 会话 display_id（与 token_logs.session_id 一致）
+ */
+session_id: string, 
+/**
+ * 单次请求 ID（同一会话可能有多个并发请求）
+ */
+request_id: string, model: string, input_tokens: bigint, 
+/**
+ * 流未结束时为估算值
+ */
+output_tokens: bigint, cache_creation_tokens: bigint, cache_read_tokens: bigint, 
+/**
+ * 成本估算（USD）
+ */
+total_cost: number, 
+/**
+ * 请求已结束（此后不再发送该 request_id 的事件）
+ */
+done: boolean, 
+/**
+ * 事件时间（Unix 时间戳，毫秒）
+ */
+timestamp: bigint, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { EnvVars } from "./EnvVars";
+
+/**
+ * 工具定义
+ */
+export type Tool = { id: string, name: string, group_name: string, npm_package: string, check_command: string, config_dir: string, config_file: string, env_vars: EnvVars, 
+/**
+ * 版本检查是否使用代理（某些工具如Claude Code在代理环境下会出错）
+ */
+use_proxy_for_version_check: boolean, };
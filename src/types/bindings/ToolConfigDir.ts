@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ConfigDirSource } from "./ConfigDirSource";
+
+/**
+ * 工具配置目录解析结果
+ */
+export type ToolConfigDir = { tool_id: string, config_dir: string, source: ConfigDirSource, 
+/**
+ * 工具支持的配置目录环境变量
+ */
+env_var: string | null, 
+/**
+ * 全局配置中设置的覆盖值
+ */
+override_dir: string | null, exists: boolean, };
@@ -0,0 +1,65 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { InstallMethod } from "./InstallMethod";
+import type { SSHConfig } from "./SSHConfig";
+import type { ToolType } from "./ToolType";
+
+/**
+ * 工具实例（具体环境中的安装）
+ */
+export type ToolInstance = { 
+/**
+ * 实例唯一标识（如"claude-code-local", "codex-wsl-Ubuntu", "gemini-ssh-dev"）
+ */
+instance_id: string, 
+/**
+ * 基础工具ID（claude-code, codex, gemini-cli）
+ */
+base_id: string, 
+/**
+ * 工具名称（用于显示）
+ */
+tool_name: string, 
+/**
+ * 环境类型
+ */
+tool_type: ToolType, 
+/**
+ * 安装方式（npm, brew, official）- 用于自动选择更新方法
+ */
+install_method: InstallMethod | null, 
+/**
+ * 是否已安装
+ */
+installed: boolean, 
+/**
+ * 版本号
+ */
+version: string | null, 
+/**
+ * 实际安装路径
+ */
+install_path: string | null, 
+/**
+ * 安装器路径（如 npm/brew 的可执行文件路径，用于更新）
+ */
+installer_path: string | null, 
+/**
+ * WSL发行版名称（仅WSL类型使用）
+ */
+wsl_distro: string | null, 
+/**
+ * SSH配置（仅SSH类型使用）
+ */
+ssh_config: SSHConfig | null, 
+/**
+ * 是否为内置实例（内置的本地工具实例）
+ */
+is_builtin: boolean, 
+/**
+ * 创建时间（Unix timestamp）
+ */
+created_at: bigint, 
+/**
+ * 更新时间（Unix timestamp）
+ */
+updated_at: bigint, };
@@ -0,0 +1,137 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ApiProtocol } from "./ApiProtocol";
+import type { BodyCaptureConfig } from "./BodyCaptureConfig";
+import type { BudgetConfig } from "./BudgetConfig";
+import type { ClientApiKey } from "./ClientApiKey";
+import type { ConcurrencyLimitConfig } from "./ConcurrencyLimitConfig";
+import type { CostGuardConfig } from "./CostGuardConfig";
+import type { EgressProxyConfig } from "./EgressProxyConfig";
+import type { KeyBalanceMode } from "./KeyBalanceMode";
+import type { ModelRoutingRule } from "./ModelRoutingRule";
+import type { PooledApiKey } from "./PooledApiKey";
+import type { ProfileFailoverConfig } from "./ProfileFailoverConfig";
+import type { RateLimitConfig } from "./RateLimitConfig";
+import type { RedactionConfig } from "./RedactionConfig";
+import type { ResponseCacheConfig } from "./ResponseCacheConfig";
+import type { SseCompatConfig } from "./SseCompatConfig";
+import type { UpstreamQueueConfig } from "./UpstreamQueueConfig";
+import type { UpstreamTimeoutConfig } from "./UpstreamTimeoutConfig";
+import type { UpstreamTarget } from "./UpstreamTarget";
+import type { JsonValue } from "./serde_json/JsonValue";
+
+/**
+ * 单个工具的透明代理配置
+ */
+export type ToolProxyConfig = { enabled: boolean, port: number, local_api_key: string | null, real_api_key: string | null, real_base_url: string | null, real_profile_name: string | null, allow_public: boolean, session_endpoint_config_enabled: boolean, auto_start: boolean, 
+/**
+ * 启动代理前激活的 Profile 名称（用于关闭时还原）
+ */
+original_active_profile?: string | null, 
+/**
+ * 价格模板 ID（用于成本计算）
+ */
+pricing_template_id?: string | null, 
+/**
+ * AMP Code 原始 settings.json 完整内容（用于关闭时还原，语义备份）
+ */
+original_amp_settings?: JsonValue | null, 
+/**
+ * AMP Code 原始 secrets.json 完整内容（用于关闭时还原，语义备份）
+ */
+original_amp_secrets?: JsonValue | null, 
+/**
+ * Tavily API Key（用于本地搜索，可选，无则降级 DuckDuckGo）
+ */
+tavily_api_key?: string | null, 
+/**
+ * SSE 统计旁路是否只保留统计相关事件（默认开启）
+ */
+sse_tap_filter: boolean, 
+/**
+ * 非标准 SSE 兼容选项（未按 Profile 单独配置时使用）
+ */
+sse_compat: SseCompatConfig, 
+/**
+ * 需要捕获并随日志记录的上游响应头（不区分大小写）
+ */
+captured_response_headers: Array<string>, 
+/**
+ * 按 Profile 名称单独配置的 SSE 兼容选项（优先于 `sse_compat`）
+ */
+sse_compat_profiles?: { [key in string]?: SseCompatConfig }, 
+/**
+ * 请求/响应体捕获（默认关闭，开启后按 TTL 强制过期删除）
+ */
+body_capture: BodyCaptureConfig, 
+/**
+ * 在响应中附加本次调用的成本与 Token 数（非流式为响应头，流式为末尾 SSE 注释）
+ */
+cost_annotation: boolean, 
+/**
+ * 备用上游：主上游返回 5xx/429 或请求失败时按优先级依次重试
+ */
+upstreams?: Array<UpstreamTarget>, 
+/**
+ * 主上游的 API Key 池（配置后按负载均衡策略轮换，代替 `real_api_key`）
+ */
+key_pool?: Array<PooledApiKey>, 
+/**
+ * Key 池负载均衡策略
+ */
+key_balance_mode: KeyBalanceMode, 
+/**
+ * 本地限流（默认关闭）
+ */
+rate_limit: RateLimitConfig, 
+/**
+ * 上游限流排队（默认关闭）
+ */
+upstream_queue: UpstreamQueueConfig, 
+/**
+ * 并发上游请求上限（默认关闭）
+ */
+concurrency_limit: ConcurrencyLimitConfig, 
+/**
+ * 上游超时（连接 / 总时长 / 流式响应空闲）
+ */
+upstream_timeout: UpstreamTimeoutConfig, 
+/**
+ * 幂等接口响应缓存（默认关闭）
+ */
+response_cache: ResponseCacheConfig, 
+/**
+ * 消费预算（默认关闭）
+ */
+budget: BudgetConfig, 
+/**
+ * 单次请求输入成本预检（默认关闭）
+ */
+cost_guard: CostGuardConfig, 
+/**
+ * 模型路由规则：按顺序匹配请求模型名，命中时改用规则指定 Profile 的上游
+ */
+routing_rules?: Array<ModelRoutingRule>, 
+/**
+ * 请求内容脱敏（默认关闭）
+ */
+redaction: RedactionConfig, 
+/**
+ * Profile 自动切换：当前 Profile 的上游持续出错时切换到备用 Profile（默认关闭）
+ */
+profile_failover: ProfileFailoverConfig, 
+/**
+ * 主上游的 API 协议（未设置时视为与工具协议相同，不做转换）
+ */
+upstream_protocol?: ApiProtocol | null, 
+/**
+ * 转发上游时使用的出口代理（默认跟随全局代理）
+ */
+egress_proxy: EgressProxyConfig, 
+/**
+ * 本地套接字监听（Unix 域套接字路径 / Windows 命名管道名），设置后代替 TCP 端口
+ */
+listen_socket?: string | null, 
+/**
+ * 按客户端分发的本地 API Key（与 `local_api_key` 同时有效）
+ */
+client_keys?: Array<ClientApiKey>, };
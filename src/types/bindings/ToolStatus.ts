@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 工具状态
+ */
+export type ToolStatus = { id: string, name: string, installed: boolean, version: string | null, };
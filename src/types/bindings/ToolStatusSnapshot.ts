@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ToolStatus } from "./ToolStatus";
+import type { UpdateResult } from "./UpdateResult";
+
+/**
+ * 工具状态快照（安装状态 + 版本检查结果，一次返回）
+ */
+export type ToolStatusSnapshot = { tools: Array<ToolStatus>, updates: Array<UpdateResult>, 
+/**
+ * 本次结果是否全部来自缓存
+ */
+from_cache: boolean, 
+/**
+ * 生成时间（Unix 秒）
+ */
+checked_at: bigint, };
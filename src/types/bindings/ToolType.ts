@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 工具环境类型
+ */
+export type ToolType = "Local" | "WSL" | "SSH";
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 更新配置
+ */
+export type UpdateConfig = { auto_check: boolean, check_interval_hours: number, download_in_background: boolean, auto_install: boolean, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { UpdateUrls } from "./UpdateUrls";
+
+/**
+ * 更新信息
+ */
+export type UpdateInfo = { current_version: string, latest_version: string, has_update: boolean, update_url: string | null, update: UpdateUrls | null, release_notes: string | null, file_size: bigint | null, required: boolean, };
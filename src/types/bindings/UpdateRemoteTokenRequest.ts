@@ -0,0 +1,38 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 更新远程令牌请求（支持完整字段更新）
+ */
+export type UpdateRemoteTokenRequest = { 
+/**
+ * 令牌名称
+ */
+name: string, 
+/**
+ * 分组名称
+ */
+group: string, 
+/**
+ * 剩余额度（token，500000 = 基准值）
+ */
+remain_quota: bigint, 
+/**
+ * 是否无限额度
+ */
+unlimited_quota: boolean, 
+/**
+ * 过期时间（Unix 时间戳，-1 表示永不过期）
+ */
+expired_time: bigint, 
+/**
+ * 是否启用模型限制
+ */
+model_limits_enabled: boolean, 
+/**
+ * 模型限制（逗号分隔的模型列表）
+ */
+model_limits: string, 
+/**
+ * 允许的 IP 地址（换行符分隔，支持 CIDR 表达式）
+ */
+allow_ips: string, };
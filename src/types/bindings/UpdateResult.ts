@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 工具更新结果
+ */
+export type UpdateResult = { success: boolean, message: string, has_update: boolean, current_version: string | null, latest_version: string | null, mirror_version: string | null, mirror_is_stale: boolean | null, tool_id: string | null, };
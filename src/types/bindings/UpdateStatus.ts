@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 更新状态
+ */
+export type UpdateStatus = "Idle" | "Checking" | "Available" | "Downloading" | "Downloaded" | "Installing" | "Installed" | { "Failed": string } | "Rollback" | "RolledBack";
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type UpdateUrls = { windows: string | null, windows_exe: string | null, windows_msi: string | null, macos: string | null, macos_dmg: string | null, linux: string | null, linux_deb: string | null, linux_rpm: string | null, linux_appimage: string | null, universal: string | null, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 上游限流排队配置：上游返回 429 且带 Retry-After 时暂存请求，等待后重新转发
+ */
+export type UpstreamQueueConfig = { enabled: boolean, 
+/**
+ * 同时排队的请求数上限（超出时直接返回 429）
+ */
+max_requests: number, 
+/**
+ * 单个请求累计排队时长上限（秒）
+ */
+max_wait_secs: bigint, };
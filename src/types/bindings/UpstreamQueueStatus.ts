@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 代理实例的排队状态
+ */
+export type UpstreamQueueStatus = { 
+/**
+ * 当前排队等待的请求数
+ */
+depth: number, 
+/**
+ * 启动以来进入排队的请求总数
+ */
+total_queued: bigint, };
@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 单个上游的故障转移状态
+ */
+export type UpstreamStatus = { base_url: string, priority: number, 
+/**
+ * 是否为当前 Profile 的主上游
+ */
+primary: boolean, 
+/**
+ * 是否为最近一次成功响应的上游
+ */
+active: boolean, 
+/**
+ * 是否处于冷却中
+ */
+cooling_down: boolean, consecutive_failures: number, 
+/**
+ * 冷却结束时间（Unix 时间戳，毫秒）
+ */
+cooldown_until: bigint | null, last_error: string | null, };
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ApiProtocol } from "./ApiProtocol";
+
+/**
+ * 上游目标
+ */
+export type UpstreamTarget = { base_url: string, api_key: string, 
+/**
+ * 优先级（越小越优先，同优先级按配置顺序）
+ */
+priority: number, 
+/**
+ * 上游 API 协议（未设置时视为与工具协议相同，不做转换）
+ */
+protocol?: ApiProtocol | null, };
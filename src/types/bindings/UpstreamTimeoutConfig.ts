@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 上游超时配置（各项为 0 表示不限制），超时后返回 504 并记录为 `timeout` 状态
+ */
+export type UpstreamTimeoutConfig = { 
+/**
+ * 建立连接超时（秒）
+ */
+connect_timeout_secs: bigint, 
+/**
+ * 请求总超时（秒）：非流式请求为完整响应时间，流式请求为收到响应头之前的时间
+ */
+total_timeout_secs: bigint, 
+/**
+ * 流式响应相邻两次收到数据的最长间隔（秒）
+ */
+idle_stream_timeout_secs: bigint, };
@@ -0,0 +1,33 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 用量异常检测配置
+ *
+ * 每分钟比较各工具当前小时的消费与 Token 用量和滚动基线
+ * （最近若干小时中有用量的小时的平均值），超过倍数时发送 `usage-anomaly` 事件
+ */
+export type UsageAnomalyConfig = { 
+/**
+ * 是否启用（默认开启）
+ */
+enabled: boolean, 
+/**
+ * 超过基线多少倍视为异常
+ */
+multiplier: number, 
+/**
+ * 基线统计的小时数（不含当前小时）
+ */
+baseline_hours: number, 
+/**
+ * 当前小时消费低于该值（USD）时不提醒，避免小额波动误报
+ */
+min_cost_usd: number, 
+/**
+ * 当前小时 Token 低于该值时不提醒
+ */
+min_tokens: bigint, 
+/**
+ * 是否同时发送系统桌面通知
+ */
+desktop_notification: boolean, };
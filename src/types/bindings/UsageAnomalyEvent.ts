@@ -0,0 +1,31 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AnomalyMetric } from "./AnomalyMetric";
+
+/**
+ * `usage-anomaly` 事件载荷
+ */
+export type UsageAnomalyEvent = { tool_id: string, metric: AnomalyMetric, 
+/**
+ * 当前小时累计值（USD 或 Token 数）
+ */
+current: number, 
+/**
+ * 基线（有用量小时的平均值）
+ */
+baseline: number, 
+/**
+ * 当前值 / 基线
+ */
+ratio: number, 
+/**
+ * 配置的异常倍数
+ */
+multiplier: number, 
+/**
+ * 当前小时开始时间（Unix 时间戳，毫秒）
+ */
+hour_start: bigint, 
+/**
+ * 是否同时发送系统桌面通知
+ */
+desktop_notification: boolean, };
@@ -0,0 +1,27 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { UsageReportFormat } from "./UsageReportFormat";
+import type { UsageReportPeriod } from "./UsageReportPeriod";
+
+/**
+ * 用量报告配置
+ *
+ * 按周期生成用量摘要（总成本、常用模型与会话、缓存节省、星期 × 小时热力图）写入磁盘，
+ * 生成后发送 `usage-report-generated` 事件
+ */
+export type UsageReportConfig = { 
+/**
+ * 自动生成周期（None 表示不自动生成）
+ */
+schedule: UsageReportPeriod | null, 
+/**
+ * 输出格式
+ */
+format: UsageReportFormat, 
+/**
+ * 输出目录（支持 `~/` 前缀，为空时使用 `~/.duckcoding/reports`）
+ */
+output_dir: string | null, 
+/**
+ * 生成后是否发送系统桌面通知
+ */
+desktop_notification: boolean, };
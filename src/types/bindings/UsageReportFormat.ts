@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 用量报告输出格式
+ */
+export type UsageReportFormat = "markdown" | "html";
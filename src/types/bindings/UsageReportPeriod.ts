@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 用量报告自动生成周期
+ */
+export type UsageReportPeriod = "weekly" | "monthly";
@@ -0,0 +1,21 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 额外监听的配置文件或目录
+ *
+ * 目录按非递归方式监听其中的 JSON / TOML / ENV 文件，字段路径带文件前缀
+ * （如 `settings.json:env.ANTHROPIC_AUTH_TOKEN`）；单个文件的字段路径不带前缀。
+ */
+export type WatchedPath = { 
+/**
+ * 文件或目录路径（支持 `~/` 开头）
+ */
+path: string, 
+/**
+ * 黑名单字段
+ */
+blacklist: Array<string>, 
+/**
+ * 敏感字段（默认模式下仅检测这些字段）
+ */
+sensitive_fields: Array<string>, };
@@ -0,0 +1,42 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 配置守护健康状态（供系统健康报告使用）
+ */
+export type WatcherHealth = { 
+/**
+ * 配置守护是否启用
+ */
+enabled: boolean, 
+/**
+ * 处理线程是否在运行
+ */
+running: boolean, 
+/**
+ * 心跳超时（线程存活但可能卡住）
+ */
+stalled: boolean, 
+/**
+ * 自动重启次数
+ */
+restarts: number, 
+/**
+ * 最近一次心跳（Unix 时间戳，毫秒）
+ */
+last_heartbeat: bigint | null, 
+/**
+ * 最近一次故障原因
+ */
+last_failure: string | null, 
+/**
+ * 最近一次故障时间（Unix 时间戳，毫秒）
+ */
+last_failure_at: bigint | null, 
+/**
+ * 当前中断开始时间（正常运行时为 None）
+ */
+down_since: bigint | null, 
+/**
+ * 累计中断时长（毫秒）
+ */
+total_downtime_ms: bigint, };
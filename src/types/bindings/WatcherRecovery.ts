@@ -0,0 +1,26 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 配置检测中断后恢复的通知
+ */
+export type WatcherRecovery = { 
+/**
+ * 中断开始时间（Unix 时间戳，毫秒）
+ */
+down_since: bigint, 
+/**
+ * 恢复时间（Unix 时间戳，毫秒）
+ */
+recovered_at: bigint, 
+/**
+ * 本次中断时长（毫秒），期间发生的外部修改未被检测
+ */
+downtime_ms: bigint, 
+/**
+ * 中断原因
+ */
+reason: string, 
+/**
+ * 累计重启次数
+ */
+restarts: number, };
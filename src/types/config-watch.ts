@@ -21,6 +21,20 @@ export interface ConfigWatchConfig {
   blacklist: Record<string, string[]>;
   /** 敏感字段（按工具分组） */
   sensitive_fields: Record<string, string[]>;
+  /** 额外监听的路径（如项目内的 `.claude/settings.json` 或 `.gemini/` 目录） */
+  watched_paths: WatchedPath[];
+}
+
+/**
+ * 额外监听的配置文件或目录（事件中的 tool_id 为 `path:<路径>`）
+ */
+export interface WatchedPath {
+  /** 文件或目录路径（支持 `~/` 开头） */
+  path: string;
+  /** 黑名单字段（目录时带文件前缀，如 `settings.json:theme`） */
+  blacklist: string[];
+  /** 敏感字段（默认模式下仅检测这些字段） */
+  sensitive_fields: string[];
 }
 
 /**
@@ -61,6 +75,37 @@ export interface ExternalConfigChange {
   is_sensitive: boolean;
 }
 
+/**
+ * 模拟字段变更（开发调试用，按新旧值推断变更类型）
+ */
+export interface SimulatedField {
+  path: string;
+  old_value?: any;
+  new_value?: any;
+}
+
+/**
+ * 模拟外部配置变更请求
+ */
+export interface SimulatedChange {
+  tool_id: string;
+  fields: SimulatedField[];
+  /** 强制指定敏感标记 */
+  is_sensitive?: boolean | null;
+  /** 跳过黑名单与监听模式过滤 */
+  skip_filters?: boolean;
+}
+
+/**
+ * 模拟变更结果
+ */
+export interface SimulationResult {
+  /** 实际发布的事件（被过滤时为 null） */
+  change: ExternalConfigChange | null;
+  /** 是否已通知前端 */
+  delivered: boolean;
+}
+
 /**
  * 配置变更记录
  */
@@ -98,6 +143,317 @@ export const WATCH_MODE_LABELS: Record<WatchMode, string> = {
   full: '全量模式',
 };
 
+/**
+ * 配置守护健康状态
+ */
+export interface WatcherHealth {
+  enabled: boolean;
+  running: boolean;
+  /** 心跳超时（线程存活但可能卡住） */
+  stalled: boolean;
+  /** 自动重启次数 */
+  restarts: number;
+  last_heartbeat: number | null;
+  last_failure: string | null;
+  last_failure_at: number | null;
+  /** 当前中断开始时间（正常运行时为 null） */
+  down_since: number | null;
+  /** 累计中断时长（毫秒） */
+  total_downtime_ms: number;
+}
+
+/**
+ * 配置检测中断后恢复的通知（config-watcher-recovered 事件）
+ */
+export interface WatcherRecovery {
+  down_since: number;
+  recovered_at: number;
+  /** 本次中断时长（毫秒），期间发生的外部修改未被检测 */
+  downtime_ms: number;
+  reason: string;
+  restarts: number;
+}
+
+/**
+ * 系统健康报告
+ */
+export interface SystemHealthReport {
+  generated_at: number;
+  healthy: boolean;
+  config_watcher: WatcherHealth;
+}
+
+export type DiagnosticSeverity = 'ok' | 'info' | 'warning' | 'error';
+
+export type DiagnosticCategory =
+  | 'config'
+  | 'port'
+  | 'database'
+  | 'disk'
+  | 'provider'
+  | 'clock'
+  | 'tool';
+
+/**
+ * 自诊断单项检查结果
+ */
+export interface DiagnosticCheck {
+  /** 检查 ID（如 db:token_stats.db） */
+  id: string;
+  category: DiagnosticCategory;
+  title: string;
+  severity: DiagnosticSeverity;
+  detail: string;
+  fix: string | null;
+}
+
+/**
+ * 自诊断报告
+ */
+export interface DiagnosticsReport {
+  checks: DiagnosticCheck[];
+  /** 最严重的检查级别 */
+  overall: DiagnosticSeverity;
+  error_count: number;
+  warning_count: number;
+  duration_ms: number;
+  generated_at: number;
+}
+
+export interface RuntimeStatus {
+  name: string;
+  available: boolean;
+  version: string | null;
+  /** 实际解析到的可执行文件路径 */
+  path: string | null;
+}
+
+export type PathSource = 'extra' | 'enhanced' | 'process';
+
+/**
+ * PATH 条目（按实际查找顺序）
+ */
+export interface PathEntry {
+  dir: string;
+  source: PathSource;
+  exists: boolean;
+}
+
+export interface ShellRcFile {
+  path: string;
+  path_lines: string[];
+}
+
+/**
+ * 代理环境变量（值中的账号密码已隐藏）
+ */
+export interface ProxyEnvVar {
+  name: string;
+  value: string;
+}
+
+/**
+ * 环境问题的修复方式（append_path 可自动应用）
+ */
+export type EnvironmentFix =
+  | { kind: 'append_path'; dir: string }
+  | { kind: 'manual'; instructions: string };
+
+export interface EnvironmentIssue {
+  /** 问题 ID（如 npm:prefix_not_on_path） */
+  id: string;
+  severity: DiagnosticSeverity;
+  title: string;
+  detail: string;
+  fix: EnvironmentFix | null;
+}
+
+/**
+ * 环境诊断报告
+ */
+export interface EnvironmentReport {
+  platform: string;
+  path_entries: PathEntry[];
+  shell_rc_files: ShellRcFile[];
+  node: RuntimeStatus;
+  npm: RuntimeStatus;
+  brew: RuntimeStatus | null;
+  npm_prefix: string | null;
+  npm_bin_dir: string | null;
+  proxy_env: ProxyEnvVar[];
+  issues: EnvironmentIssue[];
+  generated_at: number;
+}
+
+/**
+ * 夜间维护窗口配置
+ */
+export interface MaintenanceConfig {
+  enabled: boolean;
+  /** 窗口开始时间（本地时间，HH:MM） */
+  window_start: string;
+  window_minutes: number;
+  restart_proxies: boolean;
+  /** 最近多少分钟内有请求视为会话活跃 */
+  idle_minutes: number;
+  drain_timeout_secs: number;
+  log_retention_days: number;
+}
+
+/**
+ * 单个维护步骤的结果
+ */
+export interface MaintenanceStep {
+  name: string;
+  success: boolean;
+  detail: string;
+}
+
+/**
+ * 一次维护的执行报告
+ */
+export interface MaintenanceReport {
+  started_at: number;
+  finished_at: number;
+  forced: boolean;
+  success: boolean;
+  steps: MaintenanceStep[];
+}
+
+/**
+ * 维护调度状态
+ */
+export interface MaintenanceStatus {
+  config: MaintenanceConfig;
+  running: boolean;
+  next_window_start: number | null;
+  last_report: MaintenanceReport | null;
+  last_skip_reason: string | null;
+}
+
+/**
+ * Prometheus 指标导出配置
+ */
+export interface MetricsConfig {
+  enabled: boolean;
+  port: number;
+  /** 是否监听所有网卡（默认仅本机） */
+  allow_public: boolean;
+}
+
+/**
+ * Prometheus 指标监听状态
+ */
+export interface MetricsStatus {
+  config: MetricsConfig;
+  running: boolean;
+  /** 抓取地址（未运行时为 null） */
+  endpoint: string | null;
+}
+
+/**
+ * 本机管理 API 配置（请求须携带 `Authorization: Bearer <token>`）
+ */
+export interface AdminApiConfig {
+  enabled: boolean;
+  /** 监听端口（仅监听 127.0.0.1） */
+  port: number;
+  /** 访问令牌（启用时为空则自动生成） */
+  token: string | null;
+}
+
+/**
+ * 本机管理 API 状态
+ */
+export interface AdminApiStatus {
+  config: AdminApiConfig;
+  running: boolean;
+  /** 接口地址（未运行时为 null） */
+  endpoint: string | null;
+}
+
+/**
+ * MCP 服务配置（标准输入输出方式运行 `duckcoding mcp`，HTTP 方式须携带令牌）
+ */
+export interface McpConfig {
+  enabled: boolean;
+  /** 是否同时在本机端口提供 HTTP 接入 */
+  http_enabled: boolean;
+  /** HTTP 监听端口（仅监听 127.0.0.1） */
+  port: number;
+  /** HTTP 访问令牌（启用 HTTP 时为空则自动生成） */
+  token: string | null;
+}
+
+/**
+ * MCP 服务状态
+ */
+export interface McpStatus {
+  config: McpConfig;
+  http_running: boolean;
+  /** HTTP 接入地址（未运行时为 null） */
+  endpoint: string | null;
+  /** 标准输入输出方式的启动命令 */
+  stdio_command: string | null;
+}
+
+/**
+ * 审计操作类型
+ */
+export type AuditAction =
+  | 'profile_activated'
+  | 'profile_saved'
+  | 'profile_deleted'
+  | 'proxy_config_updated'
+  | 'external_change_allowed'
+  | 'external_change_blocked';
+
+/**
+ * 凭证操作审计记录（哈希链）
+ */
+export interface AuditEntry {
+  seq: number;
+  timestamp: string;
+  action: AuditAction;
+  tool_id: string;
+  /** 操作对象（如 Profile 名称） */
+  target: string | null;
+  /** 操作详情（变化的字段等，不含敏感内容） */
+  details: Record<string, unknown>;
+  prev_hash: string;
+  hash: string;
+}
+
+/**
+ * 审计日志校验结果
+ */
+export interface AuditVerification {
+  valid: boolean;
+  entries: number;
+  /** 首个校验失败的行号（从 1 开始） */
+  broken_at_line: number | null;
+  error: string | null;
+  /** 最后一条记录的哈希 */
+  head_hash: string | null;
+}
+
+/**
+ * 配置目录来源
+ */
+export type ConfigDirSource = 'override' | 'env' | 'default';
+
+/**
+ * 工具实际使用的配置目录
+ */
+export interface ToolConfigDir {
+  tool_id: string;
+  config_dir: string;
+  source: ConfigDirSource;
+  env_var: string | null;
+  override_dir: string | null;
+  exists: boolean;
+}
+
 /**
  * 监听模式描述
  */
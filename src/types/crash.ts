@@ -0,0 +1,45 @@
+/**
+ * 崩溃报告相关类型定义
+ */
+
+export type CrashKind = 'panic' | 'task_error';
+
+/**
+ * 崩溃报告列表项（不含调用栈与日志）
+ */
+export interface CrashReportSummary {
+  id: string;
+  kind: CrashKind;
+  /** panic 所在线程名 / 出错的后台任务名 */
+  source: string;
+  message: string;
+  app_version: string;
+  /** 创建时间（Unix 时间戳，毫秒） */
+  created_at: number;
+  /** 发送时间（未发送时为 null） */
+  sent_at: number | null;
+}
+
+/**
+ * 崩溃报告详情（已脱敏）
+ */
+export interface CrashReport extends CrashReportSummary {
+  /** panic 位置（文件:行:列） */
+  location: string | null;
+  /** 调用栈（后台任务错误为错误链） */
+  backtrace: string;
+  os: string;
+  arch: string;
+  /** 最近的日志 */
+  log_tail: string[];
+}
+
+/**
+ * 崩溃报告上传配置
+ */
+export interface CrashReportConfig {
+  /** 是否允许发送崩溃报告（默认关闭） */
+  send_enabled: boolean;
+  /** 报告接收地址（POST JSON） */
+  endpoint: string | null;
+}
@@ -26,6 +26,26 @@ export interface ModelPrice {
   currency: string;
   /** 模型别名列表（支持多种 ID 格式） */
   aliases: string[];
+  /** 别名匹配优先级（多个模型共用同一别名时数值大者优先，默认 0） */
+  priority?: number;
+  /** 阶梯价格（按当月累计用量切换，未达到任何阈值时使用基础价格） */
+  tiers?: PriceTier[];
+  /** 上下文窗口（最大输入 Token 数，未设置时按内置模型族默认值） */
+  context_window?: number | null;
+}
+
+/**
+ * 阶梯价格档位（未设置的可选价格沿用基础价格）
+ */
+export interface PriceTier {
+  /** 当月该模型累计 Token 数（输入 + 输出 + 缓存）达到该值后适用本档价格 */
+  threshold_tokens: number;
+  input_price_per_1m: number;
+  output_price_per_1m: number;
+  cache_write_price_per_1m?: number;
+  cache_write_1h_price_per_1m?: number;
+  cache_read_price_per_1m?: number;
+  reasoning_output_price_per_1m?: number;
 }
 
 /**
@@ -38,6 +58,8 @@ export interface InheritedModel {
   source_template_id: string;
   /** 倍率（应用到继承的价格上） */
   multiplier: number;
+  /** 别名匹配优先级（多条继承配置匹配同一模型时数值大者优先，默认 0） */
+  priority?: number;
 }
 
 /**
@@ -71,6 +93,168 @@ export interface PricingTemplate {
   tags: string[];
   /** 是否为内置预设模板 */
   is_default_preset: boolean;
+
+  // 附加费用
+  /** 按次附加费（USD，每个产生用量的请求额外计费） */
+  per_request_fee?: number;
+  /** 月最低消费（USD，当月用量成本不足时按此金额结算） */
+  min_monthly_fee?: number;
+}
+
+// ==================== 别名冲突检测 ====================
+
+/**
+ * 别名匹配候选来源
+ */
+export type AliasMatchSource = 'custom' | 'inherited';
+
+/**
+ * 别名匹配候选
+ */
+export interface AliasCandidate {
+  model: string;
+  source: AliasMatchSource;
+  source_template_id: string | null;
+  priority: number;
+  price: ModelPrice;
+}
+
+/**
+ * 单个别名的冲突
+ */
+export interface AliasConflict {
+  template_id: string;
+  alias: string;
+  source: AliasMatchSource;
+  candidates: AliasCandidate[];
+  /** 按优先级选出的模型（歧义时为 null） */
+  resolved_model: string | null;
+  ambiguous: boolean;
+}
+
+/**
+ * 模板校验结果
+ */
+export interface PricingTemplateValidation {
+  template_id: string;
+  issues: TemplateIssue[];
+  alias_conflicts: AliasConflict[];
+  /** 无法确定匹配结果的别名数（这些模型计价会失败） */
+  ambiguous: number;
+  /** 没有错误级问题且没有歧义别名 */
+  valid: boolean;
+}
+
+/**
+ * 问题严重程度（error：计价会失败或结果错误；warning：可能导致少计成本）
+ */
+export type IssueSeverity = 'error' | 'warning';
+
+/**
+ * 模板问题类型
+ */
+export type TemplateIssueKind =
+  | 'invalid_price'
+  | 'missing_cache_price'
+  | 'unknown_source_template'
+  | 'unknown_inherited_model'
+  | 'circular_inheritance';
+
+/**
+ * 模板问题
+ */
+export interface TemplateIssue {
+  kind: TemplateIssueKind;
+  severity: IssueSeverity;
+  /** 相关模型（模板级问题为 null） */
+  model: string | null;
+  message: string;
+}
+
+/**
+ * 成本分解（USD）
+ */
+export interface CostBreakdown {
+  input_price: number;
+  output_price: number;
+  cache_write_price: number;
+  cache_read_price: number;
+  reasoning_price: number;
+  /** 按次附加费 */
+  request_fee: number;
+  total_cost: number;
+  template_id: string;
+}
+
+/**
+ * 试算场景（假设的单次请求用量，省略的 Token 数按 0 计）
+ */
+export interface CostScenario {
+  model: string;
+  input_tokens?: number;
+  output_tokens?: number;
+  /** 缓存创建 Token 总量（5m + 1h） */
+  cache_creation_tokens?: number;
+  cache_creation_1h_tokens?: number;
+  cache_read_tokens?: number;
+  reasoning_tokens?: number;
+  /** 请求次数（默认 1） */
+  requests?: number;
+  /** 假设的当月已用 Token 数（用于选择阶梯价格，默认 0） */
+  monthly_tokens?: number;
+}
+
+/**
+ * 试算结果
+ */
+export interface CostSimulation {
+  template_id: string;
+  model: string;
+  /** 实际使用的模型价格（已应用阶梯价格） */
+  price: ModelPrice;
+  /** 命中的阶梯阈值（未命中时为 null） */
+  tier_threshold: number | null;
+  per_request: CostBreakdown;
+  requests: number;
+  /** 全部请求的总成本（USD） */
+  total_cost: number;
+}
+
+/**
+ * 价格目录远程同步配置
+ */
+export interface PricingSyncConfig {
+  /** 是否定期同步（默认开启） */
+  enabled: boolean;
+  /** 价格目录地址（为空时使用内置镜像地址） */
+  catalog_url: string | null;
+  /** 同步间隔（小时） */
+  interval_hours: number;
+}
+
+/**
+ * 单个价格模板的同步结果
+ */
+export interface TemplateSyncSummary {
+  template_id: string;
+  /** 新增的模型 */
+  added: string[];
+  /** 价格有变动的模型 */
+  updated: string[];
+  /** 价格未变化的模型数 */
+  unchanged: number;
+}
+
+/**
+ * 价格目录同步结果
+ */
+export interface CatalogSyncReport {
+  url: string;
+  /** 目录未变化（HTTP 304），未修改任何模板 */
+  not_modified: boolean;
+  templates: TemplateSyncSummary[];
+  /** 同步时间（Unix 时间戳，毫秒） */
+  synced_at: number;
 }
 
 // ==================== 工具 ID 类型 ====================
@@ -101,6 +101,74 @@ export interface ProfileDescriptor {
   pricing_template_id?: string;
 }
 
+/**
+ * 工具原生格式的配置片段（分享给未安装 DuckCoding 的成员）
+ */
+export interface NativeConfigSnippet {
+  tool_id: string;
+  profile_name: string;
+  file_name: string; // settings.json / config.toml / .env
+  format: 'json' | 'toml' | 'dotenv';
+  content: string;
+  redacted: boolean; // API Key 是否已替换为占位符
+}
+
+/**
+ * Profile 认证状态（valid: 上游接受 Key；invalid: 401/403；unknown: 可达但无法判断；unchecked: 不可达）
+ */
+export type ProfileAuthStatus = 'valid' | 'invalid' | 'unknown' | 'unchecked';
+
+/**
+ * 单个 Profile 的健康检查结果
+ */
+export interface ProfileHealth {
+  tool_id: string;
+  profile_name: string;
+  base_url: string;
+  reachable: boolean;
+  latency_ms: number | null;
+  auth: ProfileAuthStatus;
+  status_code: number | null;
+  error: string | null;
+  checked_at: number; // 毫秒时间戳
+}
+
+/**
+ * 批量健康检查矩阵（Profile × 可达性/延迟/认证）
+ */
+export interface ProfileHealthMatrix {
+  results: ProfileHealth[];
+  total: number;
+  healthy: number;
+  auth_failed: number;
+  unreachable: number;
+  cached: number; // 直接复用缓存的结果数
+  checked_at: number;
+}
+
+/**
+ * 单个字段的配置漂移（敏感字段的值已脱敏）
+ */
+export interface DriftField {
+  file: string; // 原生配置文件名
+  field: string; // 字段路径，如 env.ANTHROPIC_BASE_URL
+  expected: string; // Profile 应写入的值
+  actual: string | null; // 当前实际值（字段或文件不存在时为 null）
+  sensitive: boolean;
+}
+
+/**
+ * 原生配置与当前激活 Profile 的漂移检测结果
+ */
+export interface ConfigDriftReport {
+  tool_id: string;
+  profile_name: string | null; // 未激活 Profile 时为 null（不做检测）
+  drifted: boolean;
+  checked_fields: number;
+  fields: DriftField[];
+  checked_at: number; // 毫秒时间戳
+}
+
 /**
  * 可创建 Profile 的工具 ID（不含 AMP）
  */
@@ -0,0 +1,59 @@
+/**
+ * 项目注册相关类型定义
+ */
+
+/**
+ * 识别出的项目技术栈
+ */
+export interface ProjectStack {
+  /** 语言（如 typescript、rust、python） */
+  languages: string[];
+  /** 框架（如 react、tauri、django） */
+  frameworks: string[];
+  is_monorepo: boolean;
+  /** 命中的清单文件 */
+  markers: string[];
+}
+
+/**
+ * 规则匹配条件（所有已设置的条件都满足时命中）
+ */
+export interface RuleCondition {
+  language?: string;
+  framework?: string;
+  monorepo?: boolean;
+}
+
+/**
+ * 技术栈 → 默认配置映射规则
+ */
+export interface StackRule {
+  when: RuleCondition;
+  tool_id: string;
+  profile?: string;
+  model?: string;
+  reason?: string;
+}
+
+/**
+ * 单个工具的配置建议
+ */
+export interface ProfileSuggestion {
+  tool_id: string;
+  profile: string | null;
+  model: string | null;
+  reasons: string[];
+}
+
+/**
+ * 项目记录
+ */
+export interface ProjectRecord {
+  id: string;
+  name: string;
+  path: string;
+  stack: ProjectStack;
+  suggestions: ProfileSuggestion[];
+  created_at: number;
+  updated_at: number;
+}
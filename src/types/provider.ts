@@ -129,3 +129,49 @@ export interface ProviderValidationResult {
   /** 错误消息（验证失败时） */
   error?: string;
 }
+
+/**
+ * 单次上游健康探测结果
+ */
+export interface ProviderHealthSample {
+  /** 探测时间（毫秒时间戳） */
+  timestamp: number;
+  reachable: boolean;
+  latency_ms: number | null;
+  status_code: number | null;
+  error: string | null;
+}
+
+/**
+ * 上游健康状况（按 Base URL 聚合）
+ */
+export interface ProviderHealth {
+  tool_id: string;
+  base_url: string;
+  /** 使用该上游的 Profile */
+  profile_names: string[];
+  /** 最近一次探测结果（尚未探测时为 null） */
+  latest: ProviderHealthSample | null;
+  /** 最近 24 小时可用率（0-100） */
+  uptime_24h: number | null;
+  /** 最近 24 小时平均延迟 */
+  avg_latency_ms_24h: number | null;
+  /** 最近的探测记录（新的在前） */
+  recent: ProviderHealthSample[];
+}
+
+/**
+ * 上游可达性变化事件（provider-health-changed）
+ */
+export interface ProviderHealthChangedEvent {
+  tool_id: string;
+  base_url: string;
+  profile_names: string[];
+  reachable: boolean;
+  /** 变化前的可达性（首次探测为 null） */
+  previous: boolean | null;
+  latency_ms: number | null;
+  error: string | null;
+  /** 探测时间（毫秒时间戳） */
+  checked_at: number;
+}
@@ -0,0 +1,49 @@
+/**
+ * 异常退出恢复相关类型定义
+ */
+
+/**
+ * 上次运行标记
+ */
+export interface RunMarker {
+  pid: number;
+  /** 启动时间（Unix 时间戳，秒） */
+  started_at: number;
+}
+
+/**
+ * 单项恢复操作
+ */
+export interface RecoveryAction {
+  /** 操作类型 */
+  kind: 'db_checkpoint' | 'tool_config_restore';
+  /** 数据库文件名或工具 ID */
+  target: string;
+  success: boolean;
+  message: string;
+}
+
+/**
+ * 异常退出恢复报告
+ */
+export interface RecoveryReport {
+  previous_run: RunMarker;
+  /** 恢复时间（Unix 时间戳，秒） */
+  recovered_at: number;
+  actions: RecoveryAction[];
+}
+
+/**
+ * 指向未运行代理的工具配置
+ */
+export interface StaleProxyConfig {
+  tool_id: string;
+  /** 代理配置的监听端口 */
+  port: number;
+  /** 原生配置中的 Base URL */
+  base_url: string | null;
+  /** 当前激活的 Profile（AMP Code 无 Profile） */
+  active_profile: string | null;
+  /** 是否存在可用于还原的备份 */
+  restorable: boolean;
+}
@@ -0,0 +1,33 @@
+/**
+ * 全局搜索相关类型定义
+ */
+
+/**
+ * 搜索结果类型
+ */
+export type SearchKind = 'profile' | 'session' | 'setting' | 'log';
+
+/**
+ * 搜索参数
+ */
+export interface SearchQuery {
+  query: string;
+  /** 只搜索指定类型（为空时搜索全部） */
+  kinds?: SearchKind[];
+  /** 最大返回数量（默认 20） */
+  limit?: number | null;
+}
+
+/**
+ * 单条搜索结果
+ */
+export interface SearchResult {
+  kind: SearchKind;
+  /** Profile 为 `{tool_id}/{name}`，会话为会话 ID，设置项为键路径，日志为日志 ID */
+  id: string;
+  title: string;
+  subtitle: string | null;
+  tool_id: string | null;
+  /** 相关度得分（越高越相关） */
+  score: number;
+}
@@ -21,9 +21,14 @@ export interface TokenLog {
   cache_creation_tokens: number;
   cache_creation_1h_tokens?: number;
   cache_read_tokens: number;
-  request_status: 'success' | 'failed'; // 请求状态
+  request_status: 'success' | 'failed' | 'rate_limited' | 'timeout'; // 请求状态
   response_type: 'sse' | 'json' | 'unknown'; // 响应类型
-  error_type?: 'parse_error' | 'request_interrupted' | 'upstream_error'; // 错误类型
+  error_type?:
+    | 'parse_error'
+    | 'request_interrupted'
+    | 'upstream_error'
+    | 'rate_limited'
+    | 'timeout'; // 错误类型
   error_detail?: string; // 错误详情
   // 成本相关字段（Phase 6）
   total_cost: number; // 总成本
@@ -31,6 +36,10 @@ export interface TokenLog {
   output_price?: number; // 输出价格
   cache_write_price?: number; // 缓存写入价格
   cache_read_price?: number; // 缓存读取价格
+  upstream_headers?: string; // 捕获的上游响应头（JSON 对象字符串，如 {"x-request-id": "..."}）
+  upstream_key_alias?: string; // Key 池中本次使用的 Key 别名
+  client_key_label?: string; // 鉴权所用的客户端 Key 标签
+  tags?: string[]; // 会话标签（用于按项目归集花费）
 }
 
 /**
@@ -74,6 +83,53 @@ export interface TokenStatsConfig {
   retention_days?: number; // 保留天数（可选）
   max_log_count?: number; // 最大日志条数（可选）
   auto_cleanup_enabled: boolean; // 是否启用自动清理
+  max_db_size_mb?: number | null; // 数据库大小上限（MB，null 表示不限制）
+  flight_recorder?: FlightRecorderConfig; // 请求流水导出
+  anomaly_detection?: UsageAnomalyConfig; // 用量异常检测
+  usage_report?: UsageReportConfig; // 用量报告
+}
+
+/**
+ * 数据库维护报告
+ */
+export interface DbMaintenanceReport {
+  db_bytes_before: number; // 维护前主文件大小（字节）
+  wal_bytes_before: number; // 维护前 WAL 文件大小（字节）
+  db_bytes_after: number;
+  wal_bytes_after: number;
+  deleted_rows: number; // 因超出大小上限删除的最旧日志条数
+  remaining_rows: number;
+  duration_ms: number;
+}
+
+/**
+ * 用量异常检测配置（当前小时用量与最近若干小时的平均值比较）
+ */
+export interface UsageAnomalyConfig {
+  enabled: boolean;
+  multiplier: number; // 超过基线多少倍视为异常
+  baseline_hours: number; // 基线统计的小时数（不含当前小时）
+  min_cost_usd: number; // 当前小时消费低于该值时不提醒
+  min_tokens: number; // 当前小时 Token 低于该值时不提醒
+  desktop_notification: boolean; // 是否同时发送系统桌面通知
+}
+
+/**
+ * 用量报告配置（按周 / 按月自动生成摘要）
+ */
+export interface UsageReportConfig {
+  schedule?: 'weekly' | 'monthly' | null; // 自动生成周期（null 表示不自动生成）
+  format: 'markdown' | 'html';
+  output_dir?: string | null; // 输出目录（为空时使用 ~/.duckcoding/reports）
+  desktop_notification: boolean; // 生成后是否发送系统桌面通知
+}
+
+/**
+ * 请求流水导出配置（NDJSON 实时追加）
+ */
+export interface FlightRecorderConfig {
+  enabled: boolean;
+  file_path?: string;
 }
 
 // ==================== 前端辅助类型 ====================
@@ -106,17 +162,21 @@ export const TOOL_TYPE_COLORS: Record<ToolType, string> = {
 /**
  * 请求状态显示名称映射
  */
-export const REQUEST_STATUS_NAMES: Record<'success' | 'failed', string> = {
+export const REQUEST_STATUS_NAMES: Record<TokenLog['request_status'], string> = {
   success: '成功',
   failed: '失败',
+  rate_limited: '限流',
+  timeout: '超时',
 };
 
 /**
  * 请求状态颜色映射
  */
-export const REQUEST_STATUS_COLORS: Record<'success' | 'failed', string> = {
+export const REQUEST_STATUS_COLORS: Record<TokenLog['request_status'], string> = {
   success: 'text-green-700 bg-green-50 border-green-200',
   failed: 'text-red-700 bg-red-50 border-red-200',
+  rate_limited: 'text-amber-700 bg-amber-50 border-amber-200',
+  timeout: 'text-orange-700 bg-orange-50 border-orange-200',
 };
 
 /**
@@ -131,13 +191,12 @@ export const RESPONSE_TYPE_NAMES: Record<'sse' | 'json' | 'unknown', string> = {
 /**
  * 错误类型显示名称映射
  */
-export const ERROR_TYPE_NAMES: Record<
-  'parse_error' | 'request_interrupted' | 'upstream_error',
-  string
-> = {
+export const ERROR_TYPE_NAMES: Record<NonNullable<TokenLog['error_type']>, string> = {
   parse_error: '解析失败',
   request_interrupted: '请求中断',
   upstream_error: '上游错误',
+  rate_limited: '本地限流',
+  timeout: '上游超时',
 };
 
 /**
@@ -168,6 +227,137 @@ export interface DatabaseSummary {
   newest_timestamp?: number;
 }
 
+/**
+ * 日志导出格式
+ */
+export type LogExportFormat = 'csv' | 'jsonl';
+
+/**
+ * 日志导出结果
+ */
+export interface LogExportSummary {
+  path: string;
+  format: LogExportFormat;
+  row_count: number; // 导出的日志条数
+  bytes: number; // 文件大小（字节）
+}
+
+/**
+ * 预算统计周期（本地时间自然日 / 周 / 月）
+ */
+export type BudgetPeriod = 'daily' | 'weekly' | 'monthly';
+
+/**
+ * 预算提醒级别
+ */
+export type BudgetLevel = 'warning' | 'exceeded';
+
+/**
+ * 单个周期的消费情况
+ */
+export interface BudgetPeriodStatus {
+  period: BudgetPeriod;
+  limit_usd: number;
+  spent_usd: number;
+  percent: number; // 已用百分比
+  period_start: number; // 周期开始时间（毫秒）
+  level: BudgetLevel | null;
+}
+
+/**
+ * 工具的预算状态
+ */
+export interface BudgetStatus {
+  tool_id: string;
+  enabled: boolean;
+  mode: 'warn' | 'block';
+  periods: BudgetPeriodStatus[]; // 已设置上限的周期
+  warning: boolean; // 任一周期达到提醒阈值
+  exceeded: boolean; // 任一周期达到上限
+  checked_at: number;
+}
+
+/**
+ * budget-threshold-reached 事件载荷
+ */
+export interface BudgetThresholdEvent {
+  tool_id: string;
+  period: BudgetPeriod;
+  level: BudgetLevel;
+  mode: 'warn' | 'block';
+  limit_usd: number;
+  spent_usd: number;
+}
+
+/**
+ * usage-anomaly 事件载荷
+ */
+export interface UsageAnomalyEvent {
+  tool_id: string;
+  metric: 'cost' | 'tokens';
+  current: number; // 当前小时累计值（USD 或 Token 数）
+  baseline: number; // 基线（有用量小时的平均值）
+  ratio: number; // 当前值 / 基线
+  multiplier: number;
+  hour_start: number;
+  desktop_notification: boolean; // 是否同时发送系统桌面通知
+}
+
+/**
+ * token-usage-delta 事件载荷（流式请求进行中的实时用量，数值为该请求的累计值）
+ */
+export interface TokenUsageDelta {
+  tool_id: string;
+  session_id: string; // 会话 display_id
+  request_id: string; // 单次请求 ID（同一会话可能有多个并发请求）
+  model: string;
+  input_tokens: number;
+  output_tokens: number; // 流未结束时为估算值
+  cache_creation_tokens: number;
+  cache_read_tokens: number;
+  total_cost: number; // 成本估算（USD）
+  done: boolean; // 请求已结束
+  timestamp: number;
+}
+
+/**
+ * 脱敏命中统计查询条件
+ */
+export interface RedactionStatsQuery {
+  tool_type?: string | null;
+  start_time?: number | null; // 开始时间戳（毫秒，含）
+  end_time?: number | null; // 结束时间戳（毫秒，不含）
+}
+
+/**
+ * 单条脱敏规则的命中汇总
+ */
+export interface RedactionRuleStat {
+  rule_name: string;
+  hits: number; // 命中总次数
+  requests: number; // 命中该规则的请求数
+}
+
+/**
+ * 请求内容脱敏命中统计
+ */
+export interface RedactionStats {
+  total_requests: number; // 命中任一规则的请求数
+  masked_requests: number; // 脱敏后转发的请求数
+  blocked_requests: number; // 被拦截的请求数
+  total_hits: number;
+  by_rule: RedactionRuleStat[]; // 按命中次数降序
+}
+
+/**
+ * 预算周期显示名称映射
+ */
+export const BUDGET_PERIOD_NAMES: Record<BudgetPeriod, string> = {
+  daily: '今日',
+  weekly: '本周',
+  monthly: '本月',
+};
+
 // ==================== 查询过滤器默认值 ====================
 
 /**
@@ -0,0 +1,28 @@
+// 引导式故障排查类型定义
+
+export type TroubleshootFlow = 'proxy_unauthorized' | 'zero_token_stats' | 'version_check_failed';
+
+export interface TroubleshootFlowInfo {
+  flow: TroubleshootFlow;
+  title: string;
+}
+
+export type StepStatus = 'passed' | 'warning' | 'failed' | 'skipped';
+
+export interface DiagnosticStep {
+  id: string;
+  title: string;
+  status: StepStatus;
+  detail: string;
+  fix?: string | null;
+  duration_ms: number;
+}
+
+export interface TroubleshootReport {
+  flow: TroubleshootFlow;
+  tool_id: string;
+  steps: DiagnosticStep[];
+  failed_step?: string | null; // 第一个失败步骤的 ID
+  suggestions: string[];
+  finished_at: number; // 毫秒时间戳
+}